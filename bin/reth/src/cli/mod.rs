@@ -7,7 +7,8 @@ use crate::{
     },
     cli::ext::RethCliExt,
     commands::{
-        config_cmd, db, debug_cmd, import, init_cmd, node, p2p, recover, stage, test_vectors,
+        config_cmd, db, debug_cmd, export, import, init_cmd, init_state, node, p2p, profile_cmd,
+        recover, snapshot, stage, test_vectors, trie,
     },
     runner::CliRunner,
     version::{LONG_VERSION, SHORT_VERSION},
@@ -70,25 +71,40 @@ pub struct Cli<Ext: RethCliExt = ()> {
 impl<Ext: RethCliExt> Cli<Ext> {
     /// Execute the configured cli command.
     pub fn run(mut self) -> eyre::Result<()> {
-        // add network name to logs dir
-        self.logs.log_file_directory =
-            self.logs.log_file_directory.join(self.chain.chain.to_string());
+        // add profile or network name to logs dir
+        let logs_dir_name = match &self.command {
+            Commands::Node(command) => command.profile.clone(),
+            _ => None,
+        }
+        .unwrap_or_else(|| self.chain.chain.to_string());
+        self.logs.log_file_directory = self.logs.log_file_directory.join(logs_dir_name);
+        reth_node_core::crash_bundle::set_log_dir(self.logs.log_file_directory.clone().into());
 
         let _guard = self.init_tracing()?;
 
         let runner = CliRunner;
-        match self.command {
+        let result = match self.command {
             Commands::Node(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
             Commands::Init(command) => runner.run_blocking_until_ctrl_c(command.execute()),
+            Commands::InitState(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::Import(command) => runner.run_blocking_until_ctrl_c(command.execute()),
+            Commands::Export(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::Db(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::Stage(command) => runner.run_blocking_until_ctrl_c(command.execute()),
             Commands::P2P(command) => runner.run_until_ctrl_c(command.execute()),
             Commands::TestVectors(command) => runner.run_until_ctrl_c(command.execute()),
             Commands::Config(command) => runner.run_until_ctrl_c(command.execute()),
+            Commands::Profile(command) => runner.run_until_ctrl_c(command.execute()),
             Commands::Debug(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
             Commands::Recover(command) => runner.run_command_until_exit(|ctx| command.execute(ctx)),
-        }
+            Commands::Trie(command) => runner.run_blocking_until_ctrl_c(command.execute()),
+            Commands::Snapshot(command) => runner.run_blocking_until_ctrl_c(command.execute()),
+        };
+
+        // flush the spans that were not exported yet
+        reth_tracing::otlp::shutdown();
+
+        result
     }
 
     /// Initializes tracing with the configured options.
@@ -125,9 +141,15 @@ pub enum Commands<Ext: RethCliExt = ()> {
     /// Initialize the database from a genesis file.
     #[command(name = "init")]
     Init(init_cmd::InitCommand),
+    /// Initialize the database from a state snapshot.
+    #[command(name = "init-state")]
+    InitState(init_state::InitStateCommand),
     /// This syncs RLP encoded blocks from a file.
     #[command(name = "import")]
     Import(import::ImportCommand),
+    /// Export a range of blocks to RLP or era1 files.
+    #[command(name = "export")]
+    Export(export::ExportCommand),
     /// Database debugging utilities
     #[command(name = "db")]
     Db(db::Command),
@@ -143,12 +165,21 @@ pub enum Commands<Ext: RethCliExt = ()> {
     /// Write config to stdout
     #[command(name = "config")]
     Config(config_cmd::Command),
+    /// Manage named node profiles
+    #[command(name = "profile")]
+    Profile(profile_cmd::Command),
     /// Various debug routines
     #[command(name = "debug")]
     Debug(debug_cmd::Command),
     /// Scripts for node recovery
     #[command(name = "recover")]
     Recover(recover::Command),
+    /// State trie utilities
+    #[command(name = "trie")]
+    Trie(trie::Command),
+    /// Create and verify snapshots of the datadir of a running node
+    #[command(name = "snapshot")]
+    Snapshot(snapshot::Command),
 }
 
 impl<Ext: RethCliExt> Commands<Ext> {
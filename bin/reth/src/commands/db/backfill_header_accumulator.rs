@@ -0,0 +1,37 @@
+use clap::Parser;
+use reth_db::database::Database;
+use reth_primitives::{stage::StageId, ChainSpec};
+use reth_provider::{
+    HeaderAccumulatorReader, HeaderAccumulatorWriter, ProviderFactory, StageCheckpointReader,
+};
+use std::sync::Arc;
+use tracing::info;
+
+/// The arguments for the `reth db backfill-header-accumulator` command
+#[derive(Parser, Debug)]
+pub struct Command;
+
+impl Command {
+    /// Execute `db backfill-header-accumulator` command
+    ///
+    /// Commits all epochs that were completed before the node maintained the header accumulator,
+    /// e.g. on a datadir that was synced with an earlier version. The node only commits the
+    /// epochs it completes itself, and only once all earlier epochs are committed.
+    pub fn execute<DB: Database>(self, db: DB, chain: Arc<ChainSpec>) -> eyre::Result<()> {
+        let factory = ProviderFactory::new(db, chain);
+        let provider = factory.provider_rw()?;
+
+        let tip = provider
+            .get_stage_checkpoint(StageId::TotalDifficulty)?
+            .unwrap_or_default()
+            .block_number;
+        info!(target: "reth::cli", tip, "Backfilling header accumulator");
+
+        let epochs = provider.backfill_header_accumulator(tip)?;
+        let root = provider.header_accumulator_root()?;
+        provider.commit()?;
+        println!("Committed {epochs} epochs to the header accumulator, root {root}.");
+
+        Ok(())
+    }
+}
@@ -17,10 +17,11 @@ use crate::{
 use reth_db::{
     cursor::DbCursorRO, database::Database, mdbx::DatabaseArguments, open_db_read_only,
     table::Table, transaction::DbTx, AccountChangeSet, AccountHistory, AccountsTrie,
-    BlockBodyIndices, BlockOmmers, BlockWithdrawals, Bytecodes, CanonicalHeaders, DatabaseEnv,
-    HashedAccount, HashedStorage, HeaderNumbers, HeaderTD, Headers, PlainAccountState,
-    PlainStorageState, PruneCheckpoints, Receipts, StorageChangeSet, StorageHistory, StoragesTrie,
-    SyncStage, SyncStageProgress, Tables, TransactionBlock, Transactions, TxHashNumber, TxSenders,
+    BlockBodyIndices, BlockOmmers, BlockWithdrawals, Bytecodes, CanonicalHeaders, ChainState,
+    DatabaseEnv, EpochAccumulatorRoots, EpochAccumulators, HashedAccount, HashedStorage,
+    HeaderNumbers, HeaderTD, Headers, HotHashedStorages, PlainAccountState, PlainStorageState,
+    PruneCheckpoints, Receipts, StorageChangeSet, StorageHistory, StoragesTrie, SyncStage,
+    SyncStageProgress, Tables, TransactionBlock, Transactions, TxHashNumber, TxSenders,
 };
 use tracing::info;
 
@@ -135,6 +136,9 @@ impl Command {
                 Tables::StoragesTrie => {
                     find_diffs::<StoragesTrie>(primary_tx, secondary_tx, output_dir)?
                 }
+                Tables::HotHashedStorages => {
+                    find_diffs::<HotHashedStorages>(primary_tx, secondary_tx, output_dir)?
+                }
                 Tables::TxSenders => find_diffs::<TxSenders>(primary_tx, secondary_tx, output_dir)?,
                 Tables::SyncStage => find_diffs::<SyncStage>(primary_tx, secondary_tx, output_dir)?,
                 Tables::SyncStageProgress => {
@@ -143,6 +147,15 @@ impl Command {
                 Tables::PruneCheckpoints => {
                     find_diffs::<PruneCheckpoints>(primary_tx, secondary_tx, output_dir)?
                 }
+                Tables::ChainState => {
+                    find_diffs::<ChainState>(primary_tx, secondary_tx, output_dir)?
+                }
+                Tables::EpochAccumulators => {
+                    find_diffs::<EpochAccumulators>(primary_tx, secondary_tx, output_dir)?
+                }
+                Tables::EpochAccumulatorRoots => {
+                    find_diffs::<EpochAccumulatorRoots>(primary_tx, secondary_tx, output_dir)?
+                }
             };
         }
 
@@ -0,0 +1,158 @@
+//! Command that prunes the database in place to a prune configuration.
+
+use crate::{
+    args::PruningArgs,
+    dirs::{ChainPath, DataDirPath},
+};
+use clap::Parser;
+use eyre::WrapErr;
+use reth_config::Config;
+use reth_db::{mdbx::DatabaseArguments, open_db, DatabaseEnv};
+use reth_interfaces::db::LogLevel;
+use reth_primitives::{stage::StageId, ChainSpec, PruneProgress};
+use reth_provider::{ProviderFactory, StageCheckpointReader};
+use reth_prune::{segments::SegmentSet, Pruner};
+use reth_snapshot::Snapshotter;
+use std::{fs, path::PathBuf, sync::Arc, time::Instant};
+use tokio::sync::watch;
+use tracing::info;
+
+/// The name of the file the database is compacted into, before it replaces the database.
+const COMPACT_FILE: &str = "mdbx.dat.compact";
+
+/// The arguments for the `reth db prune` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The path to the configuration file to use.
+    ///
+    /// The prune configuration is read from and saved to this file.
+    #[arg(long, value_name = "FILE", verbatim_doc_comment)]
+    config: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pruning: PruningArgs,
+
+    /// The maximum number of entries to delete per database transaction.
+    #[arg(long, value_name = "ENTRIES", default_value_t = 1_000_000)]
+    batch_size: usize,
+
+    /// Do not compact the database after pruning.
+    ///
+    /// Pruning frees pages inside the database file, but doesn't shrink the file itself.
+    /// Compacting writes a copy of the database without the free pages, which needs free disk
+    /// space for the pruned database.
+    #[arg(long)]
+    no_compact: bool,
+}
+
+impl Command {
+    /// Execute `db prune` command
+    ///
+    /// Prunes the database to the prune configuration of `--full` or of the configuration file,
+    /// e.g. to convert an archive node to a full node without syncing it again. The prune
+    /// configuration is saved to the configuration file first, so the node keeps pruning with it.
+    ///
+    /// Every batch is committed with its prune checkpoints, so an interrupted run continues where
+    /// it stopped when it's started again. The node must be stopped while this is running.
+    pub fn execute(
+        self,
+        data_dir: ChainPath<DataDirPath>,
+        log_level: Option<LogLevel>,
+        chain: Arc<ChainSpec>,
+    ) -> eyre::Result<()> {
+        let config_path = self.config.clone().unwrap_or_else(|| data_dir.config_path());
+        let mut config: Config = confy::load_path(&config_path)
+            .wrap_err_with(|| format!("Could not load config file {config_path:?}"))?;
+        let prune_config = self
+            .pruning
+            .prune_config(chain.clone(), config.prune.clone())?
+            .filter(|prune_config| prune_config.segments != Default::default())
+            .ok_or_else(|| {
+                eyre::eyre!("No prune configuration, pass --full or configure [prune] in reth.toml")
+            })?;
+        if config.prune.as_ref() != Some(&prune_config) {
+            config.prune = Some(prune_config.clone());
+            confy::store_path(&config_path, &config)?;
+            info!(target: "reth::cli", ?config_path, "Saved prune configuration");
+        }
+
+        let db_path = data_dir.db_path();
+        let compact_path = db_path.join(COMPACT_FILE);
+        if compact_path.exists() {
+            // left over from an interrupted compaction
+            fs::remove_file(&compact_path)?;
+        }
+
+        let db = Arc::new(open_db(&db_path, DatabaseArguments::default().log_level(log_level))?);
+        let snapshot_block_interval = chain.snapshot_block_interval;
+        let factory = ProviderFactory::new(db, chain);
+        // transaction lookup entries are only pruned up to the highest transaction snapshot
+        let snapshotter =
+            Snapshotter::new(factory.clone(), data_dir.snapshots_path(), snapshot_block_interval)?;
+        let tip = factory
+            .provider()?
+            .get_stage_checkpoint(StageId::Finish)?
+            .map(|checkpoint| checkpoint.block_number)
+            .unwrap_or_default();
+
+        let segments =
+            SegmentSet::<Arc<DatabaseEnv>>::from_prune_modes(prune_config.segments.clone())
+                .into_vec();
+        // the pruner deletes up to the batch size on every run while it's aggressive
+        let (_aggressive_tx, aggressive_rx) = watch::channel(true);
+        let mut pruner = Pruner::new(
+            factory.clone(),
+            segments,
+            0,
+            self.batch_size,
+            1,
+            snapshotter.highest_snapshot_receiver(),
+        )
+        .with_aggressive_signal(aggressive_rx);
+        if self.pruning.transaction_lookup_distance.is_some() {
+            pruner = pruner.with_snapshotted_transaction_lookup();
+        }
+
+        info!(target: "reth::cli", tip, ?prune_config, "Pruning database");
+        let started_at = Instant::now();
+        // a run stops at the delete limit, which can leave the segments after the last pruned
+        // one untouched although the run reports that it finished
+        let mut unfinished = pruner.unfinished_segments(tip)?;
+        while !unfinished.is_empty() {
+            let progress = pruner.run(tip)?;
+
+            let previous = std::mem::replace(&mut unfinished, pruner.unfinished_segments(tip)?);
+            for (segment, pruned, target) in &unfinished {
+                info!(
+                    target: "reth::cli",
+                    ?segment,
+                    ?pruned,
+                    target,
+                    elapsed = ?started_at.elapsed(),
+                    "Pruning progress"
+                );
+            }
+
+            // the checkpoint of a segment stays at the last block with data to prune, e.g. if the
+            // last blocks before the target have no transactions
+            if progress == PruneProgress::Finished && unfinished == previous {
+                break
+            }
+        }
+        info!(target: "reth::cli", elapsed = ?started_at.elapsed(), "Pruned database");
+
+        if self.no_compact {
+            return Ok(())
+        }
+
+        info!(target: "reth::cli", "Compacting database");
+        factory.db_ref().copy(&compact_path, true)?;
+        drop(pruner);
+        drop(factory);
+        // the compacted copy is only complete once it's renamed
+        fs::rename(&compact_path, db_path.join("mdbx.dat"))?;
+        info!(target: "reth::cli", elapsed = ?started_at.elapsed(), "Compacted database");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use clap::Parser;
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{keccak256, stage::StageId, ChainSpec, StorageEntry};
+use reth_provider::{HeaderProvider, ProviderFactory, StageCheckpointReader};
+use reth_trie::{trie_cursor::noop::NoopTrieCursorFactory, StateRoot};
+use std::sync::Arc;
+use tracing::info;
+
+/// The number of entries after which progress is logged.
+const LOG_INTERVAL: usize = 1_000_000;
+
+/// The arguments for the `reth db repair-hashed-state` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Rebuild and verify the hashed state, but do not commit it.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Command {
+    /// Execute `db repair-hashed-state` command
+    ///
+    /// Rebuilds the `HashedAccount` and `HashedStorage` tables from the plain state and verifies
+    /// the state root of the rebuilt hashed state, and of the stored trie, against the header of
+    /// the last executed block. The rebuilt tables are only committed if the root matches.
+    pub fn execute<DB: Database>(self, db: DB, chain: Arc<ChainSpec>) -> eyre::Result<()> {
+        let factory = ProviderFactory::new(db, chain);
+        let provider = factory.provider_rw()?;
+
+        let block_number =
+            provider.get_stage_checkpoint(StageId::Execution)?.unwrap_or_default().block_number;
+        let header = provider
+            .sealed_header(block_number)?
+            .ok_or_else(|| eyre::eyre!("Header of block {block_number} not found"))?;
+        info!(target: "reth::cli", block_number, state_root = %header.state_root, "Repairing hashed state");
+
+        let tx = provider.tx_ref();
+
+        tx.clear::<tables::HashedAccount>()?;
+        let mut accounts = 0;
+        for entry in tx.cursor_read::<tables::PlainAccountState>()?.walk(None)? {
+            let (address, account) = entry?;
+            tx.put::<tables::HashedAccount>(keccak256(address), account)?;
+            accounts += 1;
+            if accounts % LOG_INTERVAL == 0 {
+                info!(target: "reth::cli", accounts, "Hashing accounts");
+            }
+        }
+        info!(target: "reth::cli", accounts, "Rebuilt hashed accounts");
+
+        tx.clear::<tables::HashedStorage>()?;
+        let mut slots = 0;
+        for entry in tx.cursor_read::<tables::PlainStorageState>()?.walk(None)? {
+            let (address, StorageEntry { key, value }) = entry?;
+            tx.put::<tables::HashedStorage>(
+                keccak256(address),
+                StorageEntry { key: keccak256(key), value },
+            )?;
+            slots += 1;
+            if slots % LOG_INTERVAL == 0 {
+                info!(target: "reth::cli", slots, "Hashing storage slots");
+            }
+        }
+        info!(target: "reth::cli", slots, "Rebuilt hashed storages");
+
+        // Compute the root from the hashed state only, ignoring all stored trie nodes.
+        info!(target: "reth::cli", "Computing state root of rebuilt hashed state");
+        let root = StateRoot::from_tx(tx).with_trie_cursor_factory(NoopTrieCursorFactory).root()?;
+        if root != header.state_root {
+            eyre::bail!(
+                "State root mismatch after rebuilding hashed state at block {block_number}: expected {}, got {root}. The plain state is likely corrupted as well, nothing was written.",
+                header.state_root
+            )
+        }
+        info!(target: "reth::cli", %root, "Rebuilt hashed state matches state root");
+
+        // The stored trie is only consistent with the plain state if the merkle stage is done.
+        let merkle_checkpoint =
+            provider.get_stage_checkpoint(StageId::MerkleExecute)?.unwrap_or_default();
+        if merkle_checkpoint.block_number == block_number {
+            let trie_root = StateRoot::from_tx(tx).root()?;
+            if trie_root == header.state_root {
+                info!(target: "reth::cli", "Stored trie matches state root");
+            } else {
+                println!(
+                    "Stored trie root {trie_root} does not match the state root, the trie tables need to be rebuilt with `reth stage drop merkle` and `reth stage run merkle`."
+                );
+            }
+        }
+
+        if self.dry_run {
+            println!("Dry run, hashed state is valid but was not written.");
+            return Ok(())
+        }
+
+        provider.commit()?;
+        println!("Repaired hashed state: {accounts} accounts, {slots} storage slots.");
+
+        Ok(())
+    }
+}
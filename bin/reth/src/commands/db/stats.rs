@@ -0,0 +1,377 @@
+use crate::{
+    dirs::{ChainPath, DataDirPath},
+    utils::DbTool,
+};
+use clap::Parser;
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::WrapErr;
+use human_bytes::human_bytes;
+use reth_db::{database::Database, mdbx, DatabaseEnv, Tables};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The arguments for the `reth db stats` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Append a timestamped snapshot of the current stats to the stats history of the data
+    /// directory.
+    #[arg(long)]
+    record: bool,
+
+    /// Compare the current stats against a previously recorded snapshot.
+    ///
+    /// Takes the position of the snapshot counted from the most recent one, defaults to the most
+    /// recent snapshot.
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "1")]
+    diff: Option<usize>,
+}
+
+impl Command {
+    /// Execute `db stats` command
+    pub fn execute(
+        self,
+        data_dir: ChainPath<DataDirPath>,
+        tool: &DbTool<'_, DatabaseEnv>,
+    ) -> eyre::Result<()> {
+        let mut snapshot = StatsSnapshot::collect(tool)?;
+        snapshot.snapshots_size = dir_size(&data_dir.snapshots_path())?;
+        println!("{}", snapshot.table());
+
+        let history_path = data_dir.db_stats_path();
+        if let Some(n) = self.diff {
+            let history = StatsSnapshot::load_history(&history_path)?;
+            let Some(previous) = history.len().checked_sub(n).and_then(|idx| history.get(idx))
+            else {
+                eyre::bail!(
+                    "No recorded snapshot #{n}, {} snapshot(s) recorded at {}",
+                    history.len(),
+                    history_path.display()
+                )
+            };
+            let elapsed = snapshot.timestamp.saturating_sub(previous.timestamp);
+            println!();
+            println!(
+                "Growth since snapshot recorded {} ago:",
+                humantime::format_duration(Duration::from_secs(elapsed))
+            );
+            println!("{}", snapshot.diff_table(previous));
+        }
+
+        if self.record {
+            snapshot.record(&history_path)?;
+            println!("Recorded stats snapshot to {}", history_path.display());
+        }
+
+        Ok(())
+    }
+}
+
+/// Stats of a single table.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+struct TableStats {
+    entries: usize,
+    branch_pages: usize,
+    leaf_pages: usize,
+    overflow_pages: usize,
+    size: usize,
+}
+
+/// A timestamped snapshot of the database stats.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StatsSnapshot {
+    /// UNIX timestamp in seconds.
+    timestamp: u64,
+    tables: BTreeMap<String, TableStats>,
+    freelist_pages: usize,
+    freelist_size: usize,
+    #[serde(default)]
+    snapshots_size: u64,
+}
+
+impl StatsSnapshot {
+    /// Collects the stats of all tables.
+    fn collect(tool: &DbTool<'_, DatabaseEnv>) -> eyre::Result<Self> {
+        tool.db.view(|tx| {
+            let mut tables = BTreeMap::new();
+            for table in Tables::ALL.iter().map(|table| table.name()) {
+                let table_db = tx.inner.open_db(Some(table)).wrap_err("Could not open db.")?;
+
+                let stats = tx
+                    .inner
+                    .db_stat(&table_db)
+                    .wrap_err(format!("Could not find table: {table}"))?;
+
+                // Defaults to 16KB right now but we should
+                // re-evaluate depending on the DB we end up using
+                // (e.g. REDB does not have these options as configurable intentionally)
+                let page_size = stats.page_size() as usize;
+                let leaf_pages = stats.leaf_pages();
+                let branch_pages = stats.branch_pages();
+                let overflow_pages = stats.overflow_pages();
+                let num_pages = leaf_pages + branch_pages + overflow_pages;
+
+                tables.insert(
+                    table.to_string(),
+                    TableStats {
+                        entries: stats.entries(),
+                        branch_pages,
+                        leaf_pages,
+                        overflow_pages,
+                        size: page_size * num_pages,
+                    },
+                );
+            }
+
+            let freelist_pages = tx.inner.env().freelist()?;
+            let freelist_size = freelist_pages *
+                tx.inner.db_stat(&mdbx::Database::freelist_db())?.page_size() as usize;
+
+            Ok::<_, eyre::Report>(Self {
+                timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+                tables,
+                freelist_pages,
+                freelist_size,
+                snapshots_size: 0,
+            })
+        })?
+    }
+
+    /// Loads all recorded snapshots, oldest first.
+    fn load_history(path: &Path) -> eyre::Result<Vec<Self>> {
+        if !path.exists() {
+            return Ok(Vec::new())
+        }
+
+        BufReader::new(fs::File::open(path)?)
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+
+    /// Appends the snapshot to the history at the given path.
+    fn record(&self, path: &Path) -> eyre::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        serde_json::to_writer(&mut file, self)?;
+        writeln!(file)?;
+        Ok(())
+    }
+
+    fn total_size(&self) -> usize {
+        self.tables.values().map(|table| table.size).sum()
+    }
+
+    /// Renders the stats as a table.
+    fn table(&self) -> ComfyTable {
+        let mut stats_table = ComfyTable::new();
+        stats_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        stats_table.set_header([
+            "Table Name",
+            "# Entries",
+            "Branch Pages",
+            "Leaf Pages",
+            "Overflow Pages",
+            "Total Size",
+        ]);
+
+        for (table, stats) in &self.tables {
+            let mut row = Row::new();
+            row.add_cell(Cell::new(table))
+                .add_cell(Cell::new(stats.entries))
+                .add_cell(Cell::new(stats.branch_pages))
+                .add_cell(Cell::new(stats.leaf_pages))
+                .add_cell(Cell::new(stats.overflow_pages))
+                .add_cell(Cell::new(human_bytes(stats.size as f64)));
+            stats_table.add_row(row);
+        }
+
+        add_separator(&mut stats_table);
+
+        let mut row = Row::new();
+        row.add_cell(Cell::new("Total DB size"))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(human_bytes(self.total_size() as f64)));
+        stats_table.add_row(row);
+
+        let mut row = Row::new();
+        row.add_cell(Cell::new("Freelist size"))
+            .add_cell(Cell::new(self.freelist_pages))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(human_bytes(self.freelist_size as f64)));
+        stats_table.add_row(row);
+
+        let mut row = Row::new();
+        row.add_cell(Cell::new("Snapshots size"))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(""))
+            .add_cell(Cell::new(human_bytes(self.snapshots_size as f64)));
+        stats_table.add_row(row);
+
+        stats_table
+    }
+
+    /// Renders the growth since the `previous` snapshot as a table.
+    fn diff_table(&self, previous: &Self) -> ComfyTable {
+        let elapsed = self.timestamp.saturating_sub(previous.timestamp);
+        let days = elapsed as f64 / 86_400.0;
+
+        let mut diff_table = ComfyTable::new();
+        diff_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        diff_table.set_header([
+            "Table Name",
+            "Δ Entries",
+            "Previous Size",
+            "Current Size",
+            "Δ Size",
+            "Growth / Day",
+        ]);
+
+        let mut add_row = |name: &str, entries: Option<(usize, usize)>, before: u64, after: u64| {
+            let delta = after as i128 - before as i128;
+            let mut row = Row::new();
+            row.add_cell(Cell::new(name))
+                .add_cell(Cell::new(
+                    entries
+                        .map(|(before, after)| signed(after as i128 - before as i128))
+                        .unwrap_or_default(),
+                ))
+                .add_cell(Cell::new(human_bytes(before as f64)))
+                .add_cell(Cell::new(human_bytes(after as f64)))
+                .add_cell(Cell::new(signed_bytes(delta)))
+                .add_cell(Cell::new(if days > 0.0 {
+                    signed_bytes((delta as f64 / days) as i128)
+                } else {
+                    String::new()
+                }));
+            diff_table.add_row(row);
+        };
+
+        for (table, stats) in &self.tables {
+            let before = previous.tables.get(table).copied().unwrap_or_default();
+            add_row(
+                table,
+                Some((before.entries, stats.entries)),
+                before.size as u64,
+                stats.size as u64,
+            );
+        }
+        add_row("Total DB size", None, previous.total_size() as u64, self.total_size() as u64);
+        add_row(
+            "Freelist size",
+            Some((previous.freelist_pages, self.freelist_pages)),
+            previous.freelist_size as u64,
+            self.freelist_size as u64,
+        );
+        add_row("Snapshots size", None, previous.snapshots_size, self.snapshots_size);
+
+        diff_table
+    }
+}
+
+fn add_separator(table: &mut ComfyTable) {
+    let max_widths = table.column_max_content_widths();
+
+    let mut seperator = Row::new();
+    for width in max_widths {
+        seperator.add_cell(Cell::new("-".repeat(width as usize)));
+    }
+    table.add_row(seperator);
+}
+
+fn signed(value: i128) -> String {
+    if value > 0 {
+        format!("+{value}")
+    } else {
+        value.to_string()
+    }
+}
+
+fn signed_bytes(value: i128) -> String {
+    let sign = if value > 0 {
+        "+"
+    } else if value < 0 {
+        "-"
+    } else {
+        ""
+    };
+    format!("{sign}{}", human_bytes(value.unsigned_abs() as f64))
+}
+
+/// Returns the total size of all files in the directory, or zero if it does not exist.
+fn dir_size(path: &Path) -> eyre::Result<u64> {
+    if !path.exists() {
+        return Ok(0)
+    }
+
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() { dir_size(&entry.path())? } else { metadata.len() };
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(
+        timestamp: u64,
+        entries: usize,
+        size: usize,
+        freelist_pages: usize,
+    ) -> StatsSnapshot {
+        let stats = TableStats { entries, leaf_pages: size / 4096, size, ..Default::default() };
+        StatsSnapshot {
+            timestamp,
+            tables: BTreeMap::from([("PlainAccountState".to_string(), stats)]),
+            freelist_pages,
+            freelist_size: freelist_pages * 4096,
+            snapshots_size: 0,
+        }
+    }
+
+    #[test]
+    fn record_and_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db-stats.jsonl");
+        assert!(StatsSnapshot::load_history(&path).unwrap().is_empty());
+
+        snapshot(0, 100, 4096, 2).record(&path).unwrap();
+        snapshot(2 * 86_400, 150, 3 * 4096, 1).record(&path).unwrap();
+
+        let history = StatsSnapshot::load_history(&path).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].tables["PlainAccountState"].entries, 100);
+        assert_eq!(history[1].tables["PlainAccountState"].entries, 150);
+
+        let diff = history[1].diff_table(&history[0]);
+        let rows = diff
+            .row_iter()
+            .map(|row| row.cell_iter().map(|cell| cell.content()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                ["PlainAccountState", "+50", "4 KiB", "12 KiB", "+8 KiB", "+4 KiB"],
+                ["Total DB size", "", "4 KiB", "12 KiB", "+8 KiB", "+4 KiB"],
+                ["Freelist size", "-1", "8 KiB", "4 KiB", "-4 KiB", "-2 KiB"],
+                ["Snapshots size", "", "0 B", "0 B", "0 B", "0 B"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Command for bundling diagnostics to attach to bug reports.
+
+use crate::{
+    args::utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+    dirs::{DataDirPath, MaybePlatformPath},
+};
+use clap::Parser;
+use reth_config::Config;
+use reth_db::{mdbx::DatabaseArguments, open_db_read_only};
+use reth_node_core::crash_bundle::{bundle_file_name, CrashBundle, DEFAULT_MAX_LOG_BYTES};
+use reth_primitives::ChainSpec;
+use reth_provider::ProviderFactory;
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use tracing::{info, warn};
+
+/// `reth debug dump-bundle` command
+///
+/// Collects the recent logs, the configuration, the stage checkpoints, recent state root latency
+/// dumps and information about the environment into a single `.tar.gz` archive, to be attached to
+/// bug reports. Secrets, i.e. RPC API keys, the JWT secret, the P2P secret key and credentials in
+/// URLs, are redacted.
+///
+/// The node writes the same bundle when it panics, unless `--debug.no-crash-bundle` is set.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The path to the configuration file to use.
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    /// The metrics endpoint of the running node, e.g. `127.0.0.1:9001`, to include its current
+    /// metrics and trie debug snapshot.
+    #[arg(long, value_name = "SOCKET")]
+    metrics: Option<SocketAddr>,
+
+    /// The maximum number of bytes of logs to include, the most recent logs are kept.
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_LOG_BYTES)]
+    max_log_bytes: u64,
+
+    /// The file to write the bundle to.
+    ///
+    /// Defaults to `reth-bundle-<TIMESTAMP>.tar.gz` in the current directory.
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl Command {
+    /// Execute `debug dump-bundle` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let mut bundle = CrashBundle::new()
+            .with_data_dir(data_dir.clone())
+            .with_max_log_bytes(self.max_log_bytes);
+
+        // the config is not created if it doesn't exist
+        let config_path = self.config.clone().unwrap_or_else(|| data_dir.config_path());
+        if config_path.exists() {
+            match confy::load_path::<Config>(&config_path) {
+                Ok(config) => bundle = bundle.with_config(config),
+                Err(err) => {
+                    warn!(target: "reth::cli", path = ?config_path, %err, "Failed to load config")
+                }
+            }
+        }
+
+        // the database can be opened read-only while the node is running
+        match open_db_read_only(&data_dir.db_path(), DatabaseArguments::default()) {
+            Ok(db) => {
+                let factory = ProviderFactory::new(Arc::new(db), self.chain.clone());
+                bundle = bundle.with_checkpoints(Box::new(factory));
+            }
+            Err(err) => warn!(target: "reth::cli", %err, "Failed to open database"),
+        }
+
+        if let Some(addr) = self.metrics {
+            for (name, path) in [("metrics.txt", "/"), ("trie.json", "/debug/trie")] {
+                match fetch(addr, path).await {
+                    Ok(stats) => bundle = bundle.with_stats_source(name, move || stats.clone()),
+                    Err(err) => {
+                        warn!(target: "reth::cli", %addr, path, %err, "Failed to fetch stats")
+                    }
+                }
+            }
+        }
+
+        let output = self.output.unwrap_or_else(|| bundle_file_name().into());
+        bundle.write(&output, None)?;
+        info!(target: "reth::cli", path = %output.display(), "Wrote bundle");
+        Ok(())
+    }
+}
+
+/// Fetches the body of the given path of the metrics endpoint.
+async fn fetch(addr: SocketAddr, path: &str) -> eyre::Result<String> {
+    let uri: hyper::Uri = format!("http://{addr}{path}").parse()?;
+    let response = hyper::Client::new().get(uri).await?;
+    eyre::ensure!(response.status().is_success(), "status {}", response.status());
+    let body = hyper::body::to_bytes(response.into_body()).await?;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
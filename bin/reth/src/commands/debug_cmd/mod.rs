@@ -5,10 +5,14 @@ use clap::{Parser, Subcommand};
 use crate::runner::CliContext;
 
 mod build_block;
+mod dump_bundle;
 mod execution;
 mod in_memory_merkle;
 mod merkle;
 mod replay_engine;
+mod replay_engine_journal;
+mod root_fixture;
+mod shadow_fork;
 
 /// `reth debug` command
 #[derive(Debug, Parser)]
@@ -30,6 +34,15 @@ pub enum Subcommands {
     BuildBlock(build_block::Command),
     /// Debug engine API by replaying stored messages.
     ReplayEngine(replay_engine::Command),
+    /// Debug the engine by replaying a journal of recorded engine API exchanges.
+    ReplayEngineJournal(replay_engine_journal::Command),
+    /// Follow a remote node and compare locally executed blocks against it.
+    ShadowFork(shadow_fork::Command),
+    /// Capture a fixture for replaying the state root computation of a block offline.
+    RootFixture(root_fixture::Command),
+    /// Bundle recent logs, config, stage checkpoints and stats into a redacted archive for bug
+    /// reports.
+    DumpBundle(dump_bundle::Command),
 }
 
 impl Command {
@@ -41,6 +54,10 @@ impl Command {
             Subcommands::InMemoryMerkle(command) => command.execute(ctx).await,
             Subcommands::BuildBlock(command) => command.execute(ctx).await,
             Subcommands::ReplayEngine(command) => command.execute(ctx).await,
+            Subcommands::ReplayEngineJournal(command) => command.execute(ctx).await,
+            Subcommands::ShadowFork(command) => command.execute(ctx).await,
+            Subcommands::RootFixture(command) => command.execute().await,
+            Subcommands::DumpBundle(command) => command.execute().await,
         }
     }
 }
@@ -8,10 +8,13 @@ use crate::{
     dirs::{DataDirPath, MaybePlatformPath},
     runner::CliContext,
 };
-use clap::Parser;
+use clap::{Args, Parser};
 use eyre::Context;
 use reth_basic_payload_builder::{BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig};
-use reth_beacon_consensus::{hooks::EngineHooks, BeaconConsensus, BeaconConsensusEngine};
+use reth_beacon_consensus::{
+    hooks::EngineHooks, BeaconConsensus, BeaconConsensusEngine, BeaconConsensusEngineError,
+    BeaconConsensusEngineHandle,
+};
 use reth_blockchain_tree::{
     BlockchainTree, BlockchainTreeConfig, ShareableBlockchainTree, TreeExternals,
 };
@@ -45,6 +48,47 @@ use tracing::*;
 /// It does not require
 #[derive(Debug, Parser)]
 pub struct Command {
+    #[clap(flatten)]
+    engine: ReplayEngineArgs,
+
+    /// The path to read engine API messages from.
+    #[arg(long = "engine-api-store", value_name = "PATH")]
+    engine_api_store: PathBuf,
+
+    /// The number of milliseconds between Engine API messages.
+    #[arg(long = "interval", default_value_t = 1_000)]
+    interval: u64,
+}
+
+/// The engine types of the replayed messages.
+#[cfg(not(feature = "optimism"))]
+pub(crate) type ReplayEngineTypes = EthEngineTypes;
+
+/// The engine types of the replayed messages.
+#[cfg(feature = "optimism")]
+pub(crate) type ReplayEngineTypes = OptimismEngineTypes;
+
+/// The provider of the replaying node.
+pub(crate) type ReplayProvider = BlockchainProvider<
+    Arc<DatabaseEnv>,
+    ShareableBlockchainTree<Arc<DatabaseEnv>, EvmProcessorFactory>,
+>;
+
+/// A running consensus engine that recorded engine API messages are replayed against.
+pub(crate) struct ReplayEngine {
+    /// The provider of the node.
+    pub(crate) provider: ReplayProvider,
+    /// The handle to send engine API messages to the consensus engine.
+    pub(crate) engine_handle: BeaconConsensusEngineHandle<ReplayEngineTypes>,
+    /// The handle to the payload builder service.
+    pub(crate) payload_builder: PayloadBuilderHandle<ReplayEngineTypes>,
+    /// Receives the result of the consensus engine once it exits.
+    pub(crate) engine_result: oneshot::Receiver<Result<(), BeaconConsensusEngineError>>,
+}
+
+/// Arguments for starting a consensus engine to replay engine API messages against.
+#[derive(Debug, Args)]
+pub(crate) struct ReplayEngineArgs {
     /// The path to the data dir for all reth files and subdirectories.
     ///
     /// Defaults to the OS-specific data directory:
@@ -72,17 +116,14 @@ pub struct Command {
 
     #[clap(flatten)]
     network: NetworkArgs,
-
-    /// The path to read engine API messages from.
-    #[arg(long = "engine-api-store", value_name = "PATH")]
-    engine_api_store: PathBuf,
-
-    /// The number of milliseconds between Engine API messages.
-    #[arg(long = "interval", default_value_t = 1_000)]
-    interval: u64,
 }
 
-impl Command {
+impl ReplayEngineArgs {
+    /// Returns the chain spec of the node.
+    pub(crate) fn chain(&self) -> Arc<ChainSpec> {
+        self.chain.clone()
+    }
+
     async fn build_network(
         &self,
         config: &Config,
@@ -109,8 +150,9 @@ impl Command {
         Ok(network)
     }
 
-    /// Execute `debug replay-engine` command
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+    /// Opens the database, starts the network and payload builder, and spawns the consensus
+    /// engine.
+    pub(crate) async fn start(&self, ctx: &CliContext) -> eyre::Result<ReplayEngine> {
         let config = Config::default();
 
         // Add network name to data dir
@@ -191,7 +233,7 @@ impl Command {
             Box::new(network),
             None,
             false,
-            payload_builder,
+            payload_builder.clone(),
             None,
             u64::MAX,
             consensus_engine_tx,
@@ -208,6 +250,21 @@ impl Command {
             let _ = tx.send(res);
         });
 
+        Ok(ReplayEngine {
+            provider: blockchain_db,
+            engine_handle: beacon_engine_handle,
+            payload_builder,
+            engine_result: rx,
+        })
+    }
+}
+
+impl Command {
+    /// Execute `debug replay-engine` command
+    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+        let ReplayEngine { engine_handle: beacon_engine_handle, engine_result: rx, .. } =
+            self.engine.start(&ctx).await?;
+
         let engine_api_store = EngineApiStore::new(self.engine_api_store.clone());
         for filepath in engine_api_store.engine_messages_iter()? {
             let contents =
@@ -0,0 +1,156 @@
+use super::replay_engine::{ReplayEngine, ReplayEngineArgs, ReplayEngineTypes, ReplayProvider};
+use crate::runner::CliContext;
+use clap::Parser;
+use eyre::Context;
+use reth_rpc_engine_api::{EngineApi, EngineApiJournal, EngineApiResult, JournalEntry};
+use serde::Serialize;
+use std::{path::PathBuf, time::Duration};
+use tracing::*;
+
+/// `reth debug replay-engine-journal` command
+///
+/// Replays the exchanges recorded with `--debug.engine-api-journal` against a local node, in
+/// order and with the recorded pauses between them, and compares the responses with the recorded
+/// ones.
+///
+/// Payloads returned by `engine_getPayload` are built from the transactions of the recording
+/// node, so only whether the call succeeded is compared for them.
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[clap(flatten)]
+    engine: ReplayEngineArgs,
+
+    /// The path of the journal to replay.
+    #[arg(long = "journal", value_name = "PATH")]
+    journal: PathBuf,
+
+    /// Send the requests back to back instead of keeping the recorded pauses between them.
+    #[arg(long = "no-delay")]
+    no_delay: bool,
+
+    /// Stop at the first response that differs from the recorded one.
+    #[arg(long = "stop-on-mismatch")]
+    stop_on_mismatch: bool,
+}
+
+impl Command {
+    /// Execute `debug replay-engine-journal` command
+    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+        let entries = EngineApiJournal::read(&self.journal)
+            .wrap_err(format!("failed to read journal: {}", self.journal.display()))?;
+        info!(target: "reth::cli", entries = entries.len(), "Read engine API journal");
+
+        let ReplayEngine { provider, engine_handle, payload_builder, .. } =
+            self.engine.start(&ctx).await?;
+        let api = EngineApi::new(
+            provider,
+            self.engine.chain(),
+            engine_handle,
+            payload_builder.into(),
+            Box::new(ctx.task_executor.clone()),
+        );
+
+        let mut mismatches = 0;
+        let mut previous_timestamp = None;
+        for (index, entry) in entries.iter().enumerate() {
+            if let Some(previous_timestamp) = previous_timestamp.filter(|_| !self.no_delay) {
+                let pause = entry.timestamp.saturating_sub(previous_timestamp);
+                tokio::time::sleep(Duration::from_millis(pause)).await;
+            }
+            previous_timestamp = Some(entry.timestamp);
+
+            let response = replay(&api, entry)
+                .await
+                .wrap_err(format!("failed to replay entry {index} ({})", entry.method))?;
+            if matches_recording(entry, &response) {
+                debug!(target: "reth::cli", index, method = %entry.method, ?response, "Replayed engine API exchange");
+                continue
+            }
+
+            mismatches += 1;
+            warn!(
+                target: "reth::cli",
+                index,
+                method = %entry.method,
+                params = %entry.params,
+                expected = ?entry.result.as_ref().ok_or(&entry.error),
+                actual = ?response,
+                "Response differs from the recording"
+            );
+            if self.stop_on_mismatch {
+                eyre::bail!(
+                    "response to entry {index} ({}) differs from the recording",
+                    entry.method
+                )
+            }
+        }
+
+        info!(target: "reth::cli", entries = entries.len(), mismatches, "Finished replaying engine API journal");
+        Ok(())
+    }
+}
+
+/// Sends the request of the entry and returns the JSON encoded response or the error message.
+async fn replay(
+    api: &EngineApi<ReplayProvider, ReplayEngineTypes>,
+    entry: &JournalEntry,
+) -> eyre::Result<Result<serde_json::Value, String>> {
+    let params = entry.params.clone();
+    let response = match entry.method.as_str() {
+        "engine_newPayloadV1" => {
+            let (payload,) = serde_json::from_value(params)?;
+            encode(api.new_payload_v1(payload).await)
+        }
+        "engine_newPayloadV2" => {
+            let (payload,) = serde_json::from_value(params)?;
+            encode(api.new_payload_v2(payload).await)
+        }
+        "engine_newPayloadV3" => {
+            let (payload, versioned_hashes, parent_beacon_block_root) =
+                serde_json::from_value(params)?;
+            encode(api.new_payload_v3(payload, versioned_hashes, parent_beacon_block_root).await)
+        }
+        "engine_forkchoiceUpdatedV1" => {
+            let (state, payload_attrs) = serde_json::from_value(params)?;
+            encode(api.fork_choice_updated_v1(state, payload_attrs).await)
+        }
+        "engine_forkchoiceUpdatedV2" => {
+            let (state, payload_attrs) = serde_json::from_value(params)?;
+            encode(api.fork_choice_updated_v2(state, payload_attrs).await)
+        }
+        "engine_forkchoiceUpdatedV3" => {
+            let (state, payload_attrs) = serde_json::from_value(params)?;
+            encode(api.fork_choice_updated_v3(state, payload_attrs).await)
+        }
+        "engine_getPayloadV1" => {
+            let (payload_id,) = serde_json::from_value(params)?;
+            encode(api.get_payload_v1(payload_id).await)
+        }
+        "engine_getPayloadV2" => {
+            let (payload_id,) = serde_json::from_value(params)?;
+            encode(api.get_payload_v2(payload_id).await)
+        }
+        "engine_getPayloadV3" => {
+            let (payload_id,) = serde_json::from_value(params)?;
+            encode(api.get_payload_v3(payload_id).await)
+        }
+        method => eyre::bail!("unsupported method: {method}"),
+    };
+    Ok(response)
+}
+
+fn encode<T: Serialize>(res: EngineApiResult<T>) -> Result<serde_json::Value, String> {
+    res.map_err(|err| err.to_string())
+        .and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string()))
+}
+
+/// Returns `true` if the response is the recorded one.
+fn matches_recording(entry: &JournalEntry, response: &Result<serde_json::Value, String>) -> bool {
+    match (response, &entry.result, &entry.error) {
+        // built payloads depend on the transaction pool of the recording node
+        (Ok(_), Some(_), _) if entry.method.starts_with("engine_getPayload") => true,
+        (Ok(actual), Some(expected), _) => actual == expected,
+        (Err(actual), _, Some(expected)) => actual == expected,
+        _ => false,
+    }
+}
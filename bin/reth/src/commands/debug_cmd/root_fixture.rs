@@ -0,0 +1,124 @@
+//! Command for capturing state root replay fixtures of local blocks.
+
+use crate::{
+    args::{
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+        DatabaseArgs,
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+};
+use clap::Parser;
+use reth_db::{init_db, mdbx::DatabaseArguments};
+use reth_primitives::{stage::StageId, BlockNumHash, BlockNumber, ChainSpec};
+use reth_provider::{BlockExecutionWriter, ProviderFactory, StageCheckpointReader};
+use reth_trie::fixture::RootFixture;
+use std::{path::PathBuf, sync::Arc};
+use tracing::info;
+
+/// `reth debug root-fixture` command
+///
+/// Captures everything needed to replay the state root computation of a block offline: the prefix
+/// sets, the hashed post state and the entries the trie and hashed cursors return.
+///
+/// The state of the database is unwound to the parent of the block in a transaction that is never
+/// committed, so unwinding a long range may take a while and use a lot of memory.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The block to capture the state root computation of.
+    #[arg(long)]
+    block: BlockNumber,
+
+    /// The file to write the fixture to.
+    ///
+    /// Defaults to `root-fixture-<BLOCK>.json` in the current directory.
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl Command {
+    /// Execute `debug root-fixture` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db = Arc::new(init_db(
+            data_dir.db_path(),
+            DatabaseArguments::default().log_level(self.db.log_level),
+        )?);
+        let factory = ProviderFactory::new(&db, self.chain.clone());
+
+        // the trie tables are at the merkle checkpoint
+        let tip = factory
+            .provider()?
+            .get_stage_checkpoint(StageId::MerkleExecute)?
+            .map(|checkpoint| checkpoint.block_number)
+            .unwrap_or_default();
+        eyre::ensure!(
+            (1..=tip).contains(&self.block),
+            "Block {} is not in the range of blocks with state root (1..={tip})",
+            self.block
+        );
+
+        info!(target: "reth::cli", block = self.block, tip, "Unwinding state to the parent block");
+        let provider = factory.provider_rw()?;
+        let chain = provider.take_block_and_execution_range(&self.chain, self.block..=tip)?;
+        let block = &chain.blocks()[&self.block];
+        let state = chain.state_at_block(self.block).expect("block is in chain");
+
+        info!(target: "reth::cli", block = self.block, "Capturing state root computation");
+        let hashed_state = state.hash_state_slow().sorted();
+        let fixture = RootFixture::capture(
+            provider.tx_ref(),
+            BlockNumHash::new(block.number, block.hash()),
+            &hashed_state,
+        )?;
+        // never commit the unwind
+        drop(provider);
+
+        eyre::ensure!(
+            fixture.state_root == block.state_root,
+            "Captured state root {} does not match the state root {} of block {}",
+            fixture.state_root,
+            block.state_root,
+            self.block
+        );
+
+        let output = self
+            .output
+            .unwrap_or_else(|| PathBuf::from(format!("root-fixture-{}.json", self.block)));
+        fixture.write(&output)?;
+        info!(
+            target: "reth::cli",
+            path = %output.display(),
+            account_nodes = fixture.trace.account_trie.0.len(),
+            storage_tries = fixture.trace.storage_tries.len(),
+            hashed_accounts = fixture.trace.hashed_accounts.len(),
+            "Wrote state root fixture"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+//! Command for following a remote node and re-executing its blocks locally.
+
+use crate::{
+    args::{
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+        DatabaseArgs,
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+    runner::CliContext,
+};
+use alloy_rlp::Decodable;
+use clap::Parser;
+use humantime::parse_duration;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use reth_db::{init_db, mdbx::DatabaseArguments};
+use reth_interfaces::executor::BlockValidationError;
+use reth_primitives::{
+    fs, logs_bloom, proofs, stage::StageId, Block, BlockNumber, Bytes, ChainSpec, ReceiptWithBloom,
+};
+use reth_provider::{
+    BlockHashReader, BlockNumReader, BlockWriter, ExecutorFactory, HeaderProvider,
+    LatestStateProviderRef, ProviderFactory, StageCheckpointReader,
+};
+use reth_rpc_api::{DebugApiClient, EthApiClient};
+use std::{fmt, sync::Arc, time::Duration};
+use tracing::*;
+
+/// `reth debug shadow-fork` command
+///
+/// Follows the canonical chain of a remote node over JSON-RPC, executes every block locally on top
+/// of the local database and compares the results against the block of the remote node.
+///
+/// Blocks and receipts are fetched via `debug_getRawBlock` and `debug_getRawReceipts`, so the
+/// remote node must expose the `debug` namespace. Matching blocks are committed to the local
+/// database together with the checkpoints of all stages, the command stops at the first
+/// divergence or if the remote chain no longer extends the local tip.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The HTTP RPC endpoint of the node to follow.
+    #[arg(long, value_name = "URL")]
+    rpc_url: String,
+
+    /// The block number to stop at.
+    ///
+    /// If not set, the remote chain is followed indefinitely.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// How long to wait before polling the remote node again once the local chain caught up.
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    #[arg(long, default_value = "2s", value_parser = parse_duration, verbatim_doc_comment)]
+    poll_interval: Duration,
+}
+
+impl Command {
+    /// Execute `debug shadow-fork` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        // add network name to data dir
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = data_dir.db_path();
+        fs::create_dir_all(&db_path)?;
+
+        // initialize the database
+        let db =
+            Arc::new(init_db(db_path, DatabaseArguments::default().log_level(self.db.log_level))?);
+        let factory = ProviderFactory::new(&db, self.chain.clone());
+
+        let client = HttpClientBuilder::default().build(&self.rpc_url)?;
+        let provider = factory.provider()?;
+        let local_tip = provider.last_block_number()?;
+        // blocks are committed with the output of every stage, so the stages must be at the tip
+        for stage in StageId::ALL {
+            let checkpoint = provider.get_stage_checkpoint(stage)?.unwrap_or_default().block_number;
+            if checkpoint != local_tip {
+                eyre::bail!(
+                    "{stage} stage checkpoint is at block {checkpoint}, but the local tip is at block {local_tip}, sync the stages to the tip first"
+                )
+            }
+        }
+        drop(provider);
+        let mut next_block = local_tip + 1;
+        info!(target: "reth::cli", rpc_url = %self.rpc_url, next_block, "Following remote chain");
+
+        loop {
+            if self.to.is_some_and(|to| next_block > to) {
+                info!(target: "reth::cli", to = ?self.to, "Reached target block");
+                return Ok(())
+            }
+
+            let remote_tip: BlockNumber = EthApiClient::block_number(&client).await?.to();
+            if remote_tip < next_block {
+                trace!(target: "reth::cli", remote_tip, next_block, "Waiting for new remote block");
+                tokio::time::sleep(self.poll_interval).await;
+                continue
+            }
+
+            let (block, remote_receipts) = fetch_block(&client, next_block).await?;
+            let divergences = self.execute_and_commit(&factory, block, remote_receipts)?;
+            if !divergences.is_empty() {
+                for divergence in &divergences {
+                    error!(target: "reth::cli", block = next_block, %divergence, "Divergence from remote chain");
+                }
+                eyre::bail!(
+                    "Block {next_block} diverged from remote chain in {} field(s)",
+                    divergences.len()
+                )
+            }
+
+            info!(target: "reth::cli", block = next_block, "Block matches remote chain");
+            next_block += 1;
+        }
+    }
+
+    /// Executes the block on top of the latest local state and compares the outcome to the remote
+    /// block. The block is only committed if no divergence was found.
+    ///
+    /// Fails if the block is not a child of the local tip, e.g. after a reorg of the remote chain.
+    fn execute_and_commit<DB: reth_db::database::Database>(
+        &self,
+        factory: &ProviderFactory<DB>,
+        block: Block,
+        remote_receipts: Vec<Bytes>,
+    ) -> eyre::Result<Vec<Divergence>> {
+        let block = block.seal_slow();
+        let provider = factory.provider()?;
+        let parent_number = block.number - 1;
+        let parent_hash = provider
+            .block_hash(parent_number)?
+            .ok_or_else(|| eyre::eyre!("local block {parent_number} not found"))?;
+        if block.parent_hash != parent_hash {
+            eyre::bail!(
+                "remote block {} has parent {}, but the local block {parent_number} is {parent_hash}",
+                block.number,
+                block.parent_hash
+            )
+        }
+        let parent_td = provider.header_td_by_number(parent_number)?.ok_or_else(|| {
+            eyre::eyre!("total difficulty of local block {parent_number} not found")
+        })?;
+
+        let executor_factory = reth_revm::EvmProcessorFactory::new(self.chain.clone());
+        let mut executor =
+            executor_factory.with_state(LatestStateProviderRef::new(provider.tx_ref()));
+        let block_with_senders = block
+            .clone()
+            .try_seal_with_senders()
+            .map_err(|_| BlockValidationError::SenderRecoveryError)?;
+        executor.execute(&block_with_senders.clone().unseal(), parent_td + block.difficulty)?;
+        let block_state = executor.take_output_state();
+
+        let receipts = block_state
+            .receipts_by_block(block.number)
+            .iter()
+            .map(|receipt| receipt.clone().expect("receipts are not pruned").with_bloom())
+            .collect::<Vec<_>>();
+
+        let mut divergences = Vec::new();
+        let gas_used = receipts.last().map(|r| r.receipt.cumulative_gas_used).unwrap_or_default();
+        Divergence::check("gas used", gas_used, block.gas_used, &mut divergences);
+        Divergence::check(
+            "logs bloom",
+            logs_bloom(receipts.iter().flat_map(|r| &r.receipt.logs)),
+            block.logs_bloom,
+            &mut divergences,
+        );
+        Divergence::check(
+            "receipts root",
+            proofs::calculate_receipt_root(
+                &receipts,
+                #[cfg(feature = "optimism")]
+                self.chain.as_ref(),
+                #[cfg(feature = "optimism")]
+                block.timestamp,
+            ),
+            block.receipts_root,
+            &mut divergences,
+        );
+        Divergence::check(
+            "receipts count",
+            receipts.len(),
+            remote_receipts.len(),
+            &mut divergences,
+        );
+        if let Some((index, (local, remote))) = receipts
+            .iter()
+            .zip(&remote_receipts)
+            .enumerate()
+            .find(|(_, (local, remote))| !receipt_matches(local, remote))
+        {
+            divergences.push(Divergence {
+                field: "first mismatching receipt",
+                local: format!("index {index}: {local:?}"),
+                remote: format!("index {index}: {remote:?}"),
+            });
+        }
+
+        let hashed_state = block_state.hash_state_slow();
+        let (state_root, trie_updates) = hashed_state.state_root_with_updates(provider.tx_ref())?;
+        Divergence::check("state root", state_root, block.state_root, &mut divergences);
+        drop(executor);
+        drop(provider);
+
+        if divergences.is_empty() {
+            // the checkpoints of all stages are moved to the block in the same transaction, so a
+            // later pipeline run continues after it
+            let provider_rw = factory.provider_rw()?;
+            provider_rw.append_blocks_with_state(
+                vec![block_with_senders],
+                block_state,
+                hashed_state,
+                trie_updates,
+                None,
+            )?;
+            provider_rw.commit()?;
+        }
+
+        Ok(divergences)
+    }
+}
+
+/// Fetches the block with the given number and its receipts from the remote node.
+async fn fetch_block(
+    client: &HttpClient,
+    number: BlockNumber,
+) -> eyre::Result<(Block, Vec<Bytes>)> {
+    let raw_block = DebugApiClient::raw_block(client, number.into()).await?;
+    let block = Block::decode(&mut raw_block.as_ref())?;
+    let receipts = DebugApiClient::raw_receipts(client, number.into()).await?;
+    Ok((block, receipts))
+}
+
+/// Returns true if the remote raw receipt matches the local receipt.
+///
+/// Nodes disagree on whether typed receipts are returned as plain EIP-2718 envelopes or wrapped in
+/// an RLP string, so both encodings are accepted.
+fn receipt_matches(local: &ReceiptWithBloom, remote: &Bytes) -> bool {
+    let mut envelope = Vec::new();
+    local.encode_inner(&mut envelope, false);
+    let mut wrapped = Vec::new();
+    local.encode_inner(&mut wrapped, true);
+    remote.as_ref() == envelope.as_slice() || remote.as_ref() == wrapped.as_slice()
+}
+
+/// A mismatch between the locally executed block and the block of the remote node.
+#[derive(Debug)]
+struct Divergence {
+    /// The name of the mismatching field.
+    field: &'static str,
+    /// The locally computed value.
+    local: String,
+    /// The value reported by the remote node.
+    remote: String,
+}
+
+impl Divergence {
+    /// Records a divergence if the local and remote values differ.
+    fn check<T: PartialEq + fmt::Debug>(
+        field: &'static str,
+        local: T,
+        remote: T,
+        divergences: &mut Vec<Self>,
+    ) {
+        if local != remote {
+            divergences.push(Self {
+                field,
+                local: format!("{local:?}"),
+                remote: format!("{remote:?}"),
+            })
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: local {}, remote {}", self.field, self.local, self.remote)
+    }
+}
@@ -0,0 +1,229 @@
+//! Writer of era1 files, the archive format of pre-merge history.
+//!
+//! An era1 file is an e2store file of up to [BLOCKS_PER_ERA] blocks, with their receipts and total
+//! difficulties, followed by the accumulator root of the era and an index of the blocks. See
+//! <https://github.com/eth-clients/e2store-format-specs/blob/main/formats/era1.md>.
+
+use alloy_rlp::Encodable;
+use reth_primitives::{hex, Block, BlockBody, BlockHash, ReceiptWithBloom, B256, U256};
+use sha2::{Digest, Sha256};
+use std::io::{self, Write};
+
+/// The maximum number of blocks of an era1 file.
+pub(crate) const BLOCKS_PER_ERA: u64 = 8192;
+
+/// The type of the version entry, `e2`.
+const VERSION: u16 = 0x3265;
+const COMPRESSED_HEADER: u16 = 0x03;
+const COMPRESSED_BODY: u16 = 0x04;
+const COMPRESSED_RECEIPTS: u16 = 0x05;
+const TOTAL_DIFFICULTY: u16 = 0x06;
+const ACCUMULATOR: u16 = 0x07;
+const BLOCK_INDEX: u16 = 0x3266;
+
+/// The length of the header of an e2store entry: the type, the length of the data and two
+/// reserved bytes.
+const ENTRY_HEADER_LEN: u64 = 8;
+
+/// Returns the name of the era1 file of the given era, e.g. `mainnet-00000-5ec1ffb8.era1`.
+pub(crate) fn file_name(network: &str, era: u64, accumulator_root: B256) -> String {
+    format!("{network}-{era:05}-{}.era1", hex::encode(&accumulator_root[..4]))
+}
+
+/// Writes consecutive blocks of one era to an era1 file.
+#[derive(Debug)]
+pub(crate) struct Era1Writer<W> {
+    writer: W,
+    /// The number of bytes written so far.
+    position: u64,
+    /// The number of the first block of the file.
+    start: Option<u64>,
+    /// The positions of the header entries of the written blocks.
+    offsets: Vec<u64>,
+    /// The hash tree roots of the header records of the written blocks.
+    records: Vec<B256>,
+}
+
+impl<W: Write> Era1Writer<W> {
+    /// Starts an era1 file by writing its version entry.
+    pub(crate) fn new(writer: W) -> io::Result<Self> {
+        let mut this =
+            Self { writer, position: 0, start: None, offsets: Vec::new(), records: Vec::new() };
+        this.write_entry(VERSION, &[])?;
+        Ok(this)
+    }
+
+    /// Appends the block with its receipts and total difficulty, returning the number of written
+    /// bytes.
+    ///
+    /// Blocks must be appended in order, and at most [BLOCKS_PER_ERA] blocks can be appended.
+    pub(crate) fn append(
+        &mut self,
+        block: Block,
+        hash: BlockHash,
+        receipts: &[ReceiptWithBloom],
+        total_difficulty: U256,
+    ) -> io::Result<u64> {
+        let start = *self.start.get_or_insert(block.number);
+        if block.number != start + self.offsets.len() as u64 ||
+            self.offsets.len() as u64 >= BLOCKS_PER_ERA
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("block {} does not continue the era starting at {start}", block.number),
+            ))
+        }
+
+        let position = self.position;
+        self.offsets.push(position);
+
+        let Block { header, body, ommers, withdrawals } = block;
+        self.write_entry(COMPRESSED_HEADER, &compress(&alloy_rlp::encode(&header))?)?;
+        let body = BlockBody { transactions: body, ommers, withdrawals };
+        self.write_entry(COMPRESSED_BODY, &compress(&alloy_rlp::encode(&body))?)?;
+
+        let mut encoded_receipts = Vec::new();
+        let payload_length = receipts.iter().map(Encodable::length).sum();
+        alloy_rlp::Header { list: true, payload_length }.encode(&mut encoded_receipts);
+        for receipt in receipts {
+            receipt.encode(&mut encoded_receipts);
+        }
+        self.write_entry(COMPRESSED_RECEIPTS, &compress(&encoded_receipts)?)?;
+
+        let total_difficulty = total_difficulty.to_le_bytes::<32>();
+        self.write_entry(TOTAL_DIFFICULTY, &total_difficulty)?;
+        self.records.push(sha256_pair(hash, B256::from(total_difficulty)));
+
+        Ok(self.position - position)
+    }
+
+    /// Writes the accumulator and the block index, returning the writer and the accumulator root.
+    pub(crate) fn finish(mut self) -> io::Result<(W, B256)> {
+        let Some(start) = self.start else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "era has no blocks"))
+        };
+
+        let root = accumulator_root(&self.records);
+        self.write_entry(ACCUMULATOR, root.as_slice())?;
+
+        // the offsets are relative to the start of the block index entry
+        let index_position = self.position as i64;
+        let mut index = Vec::with_capacity(16 + self.offsets.len() * 8);
+        index.extend_from_slice(&start.to_le_bytes());
+        for offset in &self.offsets {
+            index.extend_from_slice(&(*offset as i64 - index_position).to_le_bytes());
+        }
+        index.extend_from_slice(&(self.offsets.len() as u64).to_le_bytes());
+        self.write_entry(BLOCK_INDEX, &index)?;
+
+        self.writer.flush()?;
+        Ok((self.writer, root))
+    }
+
+    fn write_entry(&mut self, ty: u16, data: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "e2store entry too large"))?;
+        self.writer.write_all(&ty.to_le_bytes())?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(&[0; 2])?;
+        self.writer.write_all(data)?;
+        self.position += ENTRY_HEADER_LEN + data.len() as u64;
+        Ok(())
+    }
+}
+
+/// Compresses the data with the snappy framing format.
+fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = snap::write::FrameEncoder::new(Vec::new());
+    encoder.write_all(data)?;
+    encoder.into_inner().map_err(|err| io::Error::new(err.error().kind(), err.error().to_string()))
+}
+
+/// Returns the SSZ hash tree root of the list of header records, whose limit is
+/// [BLOCKS_PER_ERA].
+fn accumulator_root(records: &[B256]) -> B256 {
+    let mut layer = records.to_vec();
+    let mut zero = B256::ZERO;
+    for _ in 0..BLOCKS_PER_ERA.trailing_zeros() {
+        if layer.len() % 2 == 1 {
+            layer.push(zero);
+        }
+        layer = layer.chunks(2).map(|pair| sha256_pair(pair[0], pair[1])).collect();
+        zero = sha256_pair(zero, zero);
+    }
+    let root = layer.first().copied().unwrap_or(zero);
+
+    // mix in the length of the list
+    let mut length = B256::ZERO;
+    length[..8].copy_from_slice(&(records.len() as u64).to_le_bytes());
+    sha256_pair(root, length)
+}
+
+fn sha256_pair(left: B256, right: B256) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from_slice(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::Header;
+
+    /// Reads the type and the data of the e2store entry at the given position.
+    fn read_entry(file: &[u8], position: usize) -> (u16, &[u8]) {
+        let ty = u16::from_le_bytes(file[position..position + 2].try_into().unwrap());
+        let len = u32::from_le_bytes(file[position + 2..position + 6].try_into().unwrap());
+        let start = position + ENTRY_HEADER_LEN as usize;
+        (ty, &file[start..start + len as usize])
+    }
+
+    #[test]
+    fn writes_indexed_era_file() {
+        let mut writer = Era1Writer::new(Vec::new()).unwrap();
+        for number in 8192..8195 {
+            let block =
+                Block { header: Header { number, ..Default::default() }, ..Default::default() };
+            let hash = block.header.hash_slow();
+            writer.append(block, hash, &[], U256::from(number)).unwrap();
+        }
+
+        // blocks must be consecutive
+        let gap =
+            Block { header: Header { number: 8196, ..Default::default() }, ..Default::default() };
+        assert!(writer.append(gap, B256::ZERO, &[], U256::ZERO).is_err());
+
+        let (file, root) = writer.finish().unwrap();
+        assert_eq!(read_entry(&file, 0), (VERSION, &[][..]));
+
+        // the block index is the last entry
+        let index_len = 16 + 3 * 8;
+        let index_position = file.len() - ENTRY_HEADER_LEN as usize - index_len;
+        let (ty, index) = read_entry(&file, index_position);
+        assert_eq!(ty, BLOCK_INDEX);
+        assert_eq!(u64::from_le_bytes(index[..8].try_into().unwrap()), 8192);
+        assert_eq!(u64::from_le_bytes(index[index_len - 8..].try_into().unwrap()), 3);
+
+        // the offsets point to the headers of the blocks
+        for (i, offset) in index[8..index_len - 8].chunks(8).enumerate() {
+            let offset = i64::from_le_bytes(offset.try_into().unwrap());
+            let (ty, data) = read_entry(&file, (index_position as i64 + offset) as usize);
+            assert_eq!(ty, COMPRESSED_HEADER);
+
+            let mut decoder = snap::read::FrameDecoder::new(data);
+            let mut header = Vec::new();
+            io::Read::read_to_end(&mut decoder, &mut header).unwrap();
+            let header = <Header as alloy_rlp::Decodable>::decode(&mut &header[..]).unwrap();
+            assert_eq!(header.number, 8192 + i as u64);
+        }
+
+        // the accumulator precedes the block index
+        let (ty, accumulator) = read_entry(&file, index_position - ENTRY_HEADER_LEN as usize - 32);
+        assert_eq!((ty, accumulator), (ACCUMULATOR, root.as_slice()));
+        assert_eq!(
+            file_name("mainnet", 1, root),
+            format!("mainnet-00001-{}.era1", hex::encode(&root[..4]))
+        );
+    }
+}
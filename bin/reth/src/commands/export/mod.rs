@@ -0,0 +1,353 @@
+//! Command that exports a range of blocks to files.
+
+use crate::{
+    args::{
+        types::ZeroAsNoneU64,
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+        DatabaseArgs,
+    },
+    commands::snapshot::Throttle,
+    dirs::{DataDirPath, MaybePlatformPath},
+};
+use alloy_rlp::Encodable;
+use clap::{Parser, ValueEnum};
+use era::{Era1Writer, BLOCKS_PER_ERA};
+use reth_db::{mdbx::DatabaseArguments, open_db_read_only};
+use reth_primitives::{stage::StageId, Block, BlockNumber, ChainSpec, ReceiptWithBloom, U256};
+use reth_provider::{
+    BlockReader, HeaderProvider, ProviderError, ProviderFactory, ReceiptProvider,
+    StageCheckpointReader,
+};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+use tracing::info;
+
+mod era;
+
+/// Exports a range of blocks, optionally with their receipts, to RLP or era1 files.
+///
+/// RLP files can be imported with `reth import`, e.g. to seed other nodes or to build test
+/// fixtures.
+///
+/// The database is opened read-only and read in short read transactions, so blocks can be
+/// exported from the datadir of a running node.
+#[derive(Debug, Parser)]
+pub struct ExportCommand {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The first block to export.
+    #[arg(long, value_name = "BLOCK_NUMBER", default_value_t = 0)]
+    from: BlockNumber,
+
+    /// The last block to export.
+    ///
+    /// Defaults to the highest block that was fully synced.
+    #[arg(long, value_name = "BLOCK_NUMBER")]
+    to: Option<BlockNumber>,
+
+    /// The format to export the blocks in.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Rlp)]
+    format: ExportFormat,
+
+    /// The file to write the receipts of the exported blocks to, as one RLP list of receipts per
+    /// block, in the order of the blocks.
+    ///
+    /// Only supported by the `rlp` format, era1 files always contain the receipts.
+    #[arg(long, value_name = "FILE")]
+    receipts: Option<PathBuf>,
+
+    /// The number of blocks to read in one database read transaction.
+    ///
+    /// The node can't reuse the pages of the database that were freed while a read transaction is
+    /// open, so short read transactions keep the database of a running node from growing.
+    #[arg(long, value_name = "BLOCKS", default_value_t = 1000)]
+    batch_size: u64,
+
+    /// The maximum rate to write the exported files at, in MB per second, to limit the impact on
+    /// the disk of the running node.
+    ///
+    /// Set to 0 to disable the limit.
+    #[arg(long, value_name = "MB_PER_SEC", default_value_t = ZeroAsNoneU64::new(64))]
+    max_rate: ZeroAsNoneU64,
+
+    /// The file to write the blocks to with the `rlp` format, or the directory to write the era1
+    /// files to with the `era1` format.
+    #[arg(value_name = "OUTPUT")]
+    output: PathBuf,
+}
+
+/// The format of exported blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// RLP encoded blocks, one after the other, as read by `reth import`.
+    Rlp,
+    /// Era1 files of 8192 blocks each, with the receipts and total difficulties of the blocks.
+    ///
+    /// Era1 is the archive format of pre-merge history. Files whose blocks don't cover a whole
+    /// era, because the exported range starts or ends within it, have a different accumulator
+    /// root than the canonical file of the era.
+    Era1,
+}
+
+impl ExportCommand {
+    /// Execute `export` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        eyre::ensure!(
+            self.receipts.is_none() || self.format == ExportFormat::Rlp,
+            "Receipts can only be exported to a separate file with the rlp format"
+        );
+
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db = open_db_read_only(
+            &data_dir.db_path(),
+            DatabaseArguments::default().log_level(self.db.log_level),
+        )?;
+        let factory = ProviderFactory::new(db, self.chain.clone());
+
+        let to = match self.to {
+            Some(to) => to,
+            None => factory
+                .provider()?
+                .get_stage_checkpoint(StageId::Finish)?
+                .map(|checkpoint| checkpoint.block_number)
+                .unwrap_or_default(),
+        };
+        eyre::ensure!(self.from <= to, "Invalid block range {}..={to}", self.from);
+
+        let mut output = match self.format {
+            ExportFormat::Rlp => ExportOutput::Rlp {
+                blocks: BufWriter::new(create_file(&self.output)?),
+                receipts: self
+                    .receipts
+                    .as_ref()
+                    .map(|path| create_file(path))
+                    .transpose()?
+                    .map(BufWriter::new),
+            },
+            ExportFormat::Era1 => {
+                fs::create_dir_all(&self.output)?;
+                ExportOutput::Era1 {
+                    dir: self.output.clone(),
+                    network: self.chain.chain.to_string(),
+                    era: None,
+                }
+            }
+        };
+        let with_receipts = output.with_receipts();
+        let with_total_difficulty = self.format == ExportFormat::Era1;
+
+        let mut throttle = Throttle::new(self.max_rate.0.map(|rate| rate * 1024 * 1024));
+        let started_at = Instant::now();
+        info!(target: "reth::cli", from = self.from, to, format = ?self.format, "Exporting blocks");
+
+        let mut next = self.from;
+        while next <= to {
+            let last = to.min(next.saturating_add(self.batch_size.max(1) - 1));
+
+            // a new read transaction for every batch
+            let provider = factory.provider()?;
+            for number in next..=last {
+                let block = provider
+                    .block(number.into())?
+                    .ok_or(ProviderError::HeaderNotFound(number.into()))?;
+                let receipts = if with_receipts {
+                    let receipts = provider
+                        .receipts_by_block(number.into())?
+                        .filter(|receipts| receipts.len() == block.body.len())
+                        .ok_or_else(|| {
+                            eyre::eyre!(
+                                "Receipts of block {number} are missing, they may be pruned"
+                            )
+                        })?;
+                    receipts.into_iter().map(|receipt| receipt.with_bloom()).collect()
+                } else {
+                    Vec::new()
+                };
+                let total_difficulty = if with_total_difficulty {
+                    provider
+                        .header_td_by_number(number)?
+                        .ok_or(ProviderError::TotalDifficultyNotFound(number))?
+                } else {
+                    U256::ZERO
+                };
+
+                let written = output.write(block, &receipts, total_difficulty)?;
+                throttle.consume(written);
+            }
+            drop(provider);
+
+            info!(target: "reth::cli", block = last, to, written = throttle.copied, "Exported blocks");
+            next = last + 1;
+        }
+
+        output.finish()?;
+        info!(
+            target: "reth::cli",
+            written = throttle.copied,
+            elapsed = ?started_at.elapsed(),
+            "Exported blocks to {:?}",
+            self.output
+        );
+        Ok(())
+    }
+}
+
+/// The files the exported blocks are written to.
+#[derive(Debug)]
+enum ExportOutput {
+    Rlp {
+        blocks: BufWriter<File>,
+        receipts: Option<BufWriter<File>>,
+    },
+    Era1 {
+        dir: PathBuf,
+        network: String,
+        /// The era that is currently written, with the path of its temporary file.
+        era: Option<(u64, PathBuf, Era1Writer<BufWriter<File>>)>,
+    },
+}
+
+impl ExportOutput {
+    fn with_receipts(&self) -> bool {
+        match self {
+            Self::Rlp { receipts, .. } => receipts.is_some(),
+            Self::Era1 { .. } => true,
+        }
+    }
+
+    /// Writes the block, returning the number of written bytes.
+    ///
+    /// The total difficulty is only written to era1 files.
+    fn write(
+        &mut self,
+        block: Block,
+        receipts: &[ReceiptWithBloom],
+        total_difficulty: U256,
+    ) -> eyre::Result<u64> {
+        match self {
+            Self::Rlp { blocks, receipts: receipts_file } => {
+                let mut buf = Vec::new();
+                block.encode(&mut buf);
+                let block_len = buf.len();
+                blocks.write_all(&buf)?;
+
+                if let Some(receipts_file) = receipts_file {
+                    buf.clear();
+                    let payload_length = receipts.iter().map(Encodable::length).sum();
+                    alloy_rlp::Header { list: true, payload_length }.encode(&mut buf);
+                    for receipt in receipts {
+                        receipt.encode(&mut buf);
+                    }
+                    receipts_file.write_all(&buf)?;
+                    return Ok((block_len + buf.len()) as u64)
+                }
+                Ok(block_len as u64)
+            }
+            Self::Era1 { dir, network, era } => {
+                let number = block.number;
+                if era.as_ref().is_some_and(|(current, ..)| *current != number / BLOCKS_PER_ERA) {
+                    Self::finish_era(dir, network, era.take())?;
+                }
+                if era.is_none() {
+                    let index = number / BLOCKS_PER_ERA;
+                    let path = dir.join(format!("{network}-{index:05}.era1.tmp"));
+                    let writer = Era1Writer::new(BufWriter::new(create_file(&path)?))?;
+                    *era = Some((index, path, writer));
+                }
+                let (_, _, writer) = era.as_mut().expect("era was started");
+                let hash = block.header.hash_slow();
+                Ok(writer.append(block, hash, receipts, total_difficulty)?)
+            }
+        }
+    }
+
+    /// Flushes the files, completing the last era1 file.
+    fn finish(self) -> eyre::Result<()> {
+        match self {
+            Self::Rlp { blocks, receipts } => {
+                for writer in std::iter::once(blocks).chain(receipts) {
+                    writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+                }
+            }
+            Self::Era1 { dir, network, era } => Self::finish_era(&dir, &network, era)?,
+        }
+        Ok(())
+    }
+
+    /// Completes the era1 file and renames it after its accumulator root.
+    fn finish_era(
+        dir: &Path,
+        network: &str,
+        era: Option<(u64, PathBuf, Era1Writer<BufWriter<File>>)>,
+    ) -> eyre::Result<()> {
+        let Some((index, path, writer)) = era else { return Ok(()) };
+        let (writer, root) = writer.finish()?;
+        writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+
+        let dest = dir.join(era::file_name(network, index, root));
+        fs::rename(&path, &dest)?;
+        info!(target: "reth::cli", era = index, path = ?dest, "Wrote era1 file");
+        Ok(())
+    }
+}
+
+/// Creates the file, which must not exist.
+fn create_file(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_export_command() {
+        let args = ExportCommand::parse_from([
+            "reth",
+            "--from",
+            "100",
+            "--to",
+            "200",
+            "--format",
+            "era1",
+            "--max-rate",
+            "0",
+            "out",
+        ]);
+        assert_eq!((args.from, args.to), (100, Some(200)));
+        assert_eq!(args.format, ExportFormat::Era1);
+        assert_eq!(args.max_rate, ZeroAsNoneU64(None));
+        assert_eq!(args.output, PathBuf::from("out"));
+
+        let args = ExportCommand::parse_from(["reth", "blocks.rlp"]);
+        assert_eq!((args.from, args.to, args.format), (0, None, ExportFormat::Rlp));
+    }
+}
@@ -8,21 +8,25 @@ use crate::{
 use clap::Parser;
 use eyre::Context;
 use futures::{Stream, StreamExt};
+use rayon::prelude::*;
 use reth_beacon_consensus::BeaconConsensus;
 use reth_config::Config;
 use reth_db::{database::Database, init_db, mdbx::DatabaseArguments};
 use reth_downloaders::{
-    bodies::bodies::BodiesDownloaderBuilder, file_client::FileClient,
+    bodies::bodies::BodiesDownloaderBuilder,
+    file_client::{ChunkedFileReader, FileClient, DEFAULT_CHUNK_BYTE_LEN},
     headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
 use reth_interfaces::consensus::Consensus;
-use reth_primitives::{stage::StageId, ChainSpec, B256};
-use reth_provider::{HeaderSyncMode, ProviderFactory, StageCheckpointReader};
+use reth_primitives::{
+    stage::StageId, BlockNumber, ChainSpec, Header, SealedBlock, SealedHeader, B256,
+};
+use reth_provider::{HeaderProvider, HeaderSyncMode, ProviderFactory, StageCheckpointReader};
 use reth_stages::{
     prelude::*,
     stages::{ExecutionStage, ExecutionStageThresholds, SenderRecoveryStage, TotalDifficultyStage},
 };
-use std::{path::PathBuf, sync::Arc};
+use std::{ops::RangeInclusive, path::PathBuf, sync::Arc};
 use tokio::sync::watch;
 use tracing::{debug, info};
 
@@ -35,6 +39,10 @@ use crate::{
 };
 
 /// Syncs RLP encoded blocks from a file.
+///
+/// The file is imported in chunks: the blocks of a chunk are validated in parallel and then
+/// committed by the pipeline before the next chunk is read. An interrupted import resumes after
+/// the last block that was committed by all stages.
 #[derive(Debug, Parser)]
 pub struct ImportCommand {
     /// The path to the configuration file to use.
@@ -66,6 +74,22 @@ pub struct ImportCommand {
     #[clap(flatten)]
     db: DatabaseArgs,
 
+    /// The number of bytes of the file to read and import at once.
+    ///
+    /// A chunk always contains complete blocks, so it may exceed this length by up to one block.
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_CHUNK_BYTE_LEN)]
+    chunk_len: u64,
+
+    /// The block up to which the blocks of the file were verified before, e.g. by a previous
+    /// import of the same file.
+    ///
+    /// The headers, blocks and transaction senders of chunks that end at or before this block are
+    /// not validated before they are committed, and their state root is not computed. The state
+    /// root is computed and verified once, when the first chunk after this block or the last
+    /// chunk of the file is imported.
+    #[arg(long, value_name = "BLOCK_NUMBER")]
+    verified_until: Option<BlockNumber>,
+
     /// The path to a block file for import.
     ///
     /// The online stages (headers and bodies) are replaced by a file import, after which the
@@ -101,45 +125,83 @@ impl ImportCommand {
         let consensus = Arc::new(BeaconConsensus::new(self.chain.clone()));
         info!(target: "reth::cli", "Consensus engine initialized");
 
-        // create a new FileClient
-        info!(target: "reth::cli", "Importing chain file");
-        let file_client = Arc::new(FileClient::new(&self.path).await?);
+        info!(target: "reth::cli", path = ?self.path, "Importing chain file");
+        let mut reader = ChunkedFileReader::new(&self.path, self.chunk_len).await?;
+
+        while let Some(file_client) = reader.next_chunk().await? {
+            let (Some(min_block), Some(max_block)) =
+                (file_client.min_block(), file_client.max_block())
+            else {
+                continue
+            };
+
+            let provider = provider_factory.provider()?;
+            let latest_block_number =
+                provider.get_stage_checkpoint(StageId::Finish)?.map(|ch| ch.block_number);
+
+            // the blocks of the chunk were committed before the import was interrupted
+            if latest_block_number.is_some_and(|latest| latest >= max_block) {
+                debug!(target: "reth::cli", min_block, max_block, "Skipping imported chunk");
+                continue
+            }
+
+            let verified = self.verified_until.is_some_and(|verified| max_block <= verified);
+            if !verified {
+                // the genesis block and the committed blocks are not validated again
+                let first =
+                    min_block.max(latest_block_number.map_or(0, |latest| latest + 1)).max(1);
+                let parent = provider
+                    .sealed_header(first - 1)?
+                    .ok_or_else(|| eyre::eyre!("parent of block {first} is not imported"))?;
+                info!(target: "reth::cli", from = first, to = max_block, "Validating chunk");
+                validate_chunk(&file_client, parent, first..=max_block, consensus.as_ref())?;
+            }
+            drop(provider);
+
+            let tip = file_client.tip().expect("file client has no tip");
+
+            let (mut pipeline, events) = self
+                .build_import_pipeline(
+                    config.clone(),
+                    provider_factory.clone(),
+                    &consensus,
+                    Arc::new(file_client),
+                    verified && !reader.is_done(),
+                )
+                .await?;
+
+            // override the tip
+            pipeline.set_tip(tip);
+            debug!(target: "reth::cli", ?tip, "Tip manually set");
+
+            tokio::spawn(handle_events(None, latest_block_number, events, db.clone()));
+
+            // Run pipeline
+            info!(target: "reth::cli", min_block, max_block, "Starting sync pipeline");
+            tokio::select! {
+                res = pipeline.run() => res?,
+                _ = tokio::signal::ctrl_c() => {
+                    info!(target: "reth::cli", "Import interrupted, run it again to resume");
+                    return Ok(())
+                },
+            };
+        }
 
-        // override the tip
-        let tip = file_client.tip().expect("file client has no tip");
         info!(target: "reth::cli", "Chain file imported");
-
-        let (mut pipeline, events) = self
-            .build_import_pipeline(config, provider_factory.clone(), &consensus, file_client)
-            .await?;
-
-        // override the tip
-        pipeline.set_tip(tip);
-        debug!(target: "reth::cli", ?tip, "Tip manually set");
-
-        let provider = provider_factory.provider()?;
-
-        let latest_block_number =
-            provider.get_stage_checkpoint(StageId::Finish)?.map(|ch| ch.block_number);
-        tokio::spawn(handle_events(None, latest_block_number, events, db.clone()));
-
-        // Run pipeline
-        info!(target: "reth::cli", "Starting sync pipeline");
-        tokio::select! {
-            res = pipeline.run() => res?,
-            _ = tokio::signal::ctrl_c() => {},
-        };
-
-        info!(target: "reth::cli", "Finishing up");
         Ok(())
     }
 
+    /// Builds the pipeline that imports the blocks of the given file client.
+    ///
+    /// If `skip_state_root` is set, the hashing and merkle stages are disabled, so they catch up
+    /// with the state of the chunk when the pipeline of a later chunk runs.
     async fn build_import_pipeline<DB, C>(
         &self,
         config: Config,
         provider_factory: ProviderFactory<DB>,
         consensus: &Arc<C>,
         file_client: Arc<FileClient>,
+        skip_state_root: bool,
     ) -> eyre::Result<(Pipeline<DB>, impl Stream<Item = NodeEvent>)>
     where
         DB: Database + Clone + Unpin + 'static,
@@ -196,7 +258,11 @@ impl ImportCommand {
                         .max(config.stages.account_hashing.clean_threshold)
                         .max(config.stages.storage_hashing.clean_threshold),
                     config.prune.map(|prune| prune.segments).unwrap_or_default(),
-                )),
+                ))
+                .disable_if(StageId::MerkleUnwind, || skip_state_root)
+                .disable_if(StageId::AccountHashing, || skip_state_root)
+                .disable_if(StageId::StorageHashing, || skip_state_root)
+                .disable_if(StageId::MerkleExecute, || skip_state_root),
             )
             .build(provider_factory);
 
@@ -212,6 +278,50 @@ impl ImportCommand {
     }
 }
 
+/// Validates the headers, the blocks and the transaction senders of the given range of blocks of
+/// the file client in parallel, so an invalid block is found before the chunk is committed.
+fn validate_chunk<C: Consensus>(
+    file_client: &FileClient,
+    parent: SealedHeader,
+    range: RangeInclusive<BlockNumber>,
+    consensus: &C,
+) -> eyre::Result<()> {
+    let headers = range
+        .into_par_iter()
+        .map(|number| {
+            file_client
+                .header(number)
+                .cloned()
+                .map(Header::seal_slow)
+                .ok_or_else(|| eyre::eyre!("the chain file is missing block {number}"))
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    (0..headers.len()).into_par_iter().try_for_each(|index| {
+        let header = &headers[index];
+        let parent = if index == 0 { &parent } else { &headers[index - 1] };
+        let number = header.number;
+
+        consensus
+            .validate_header(header)
+            .and_then(|_| consensus.validate_header_against_parent(header, parent))
+            .map_err(|err| eyre::eyre!("invalid header of block {number}: {err}"))?;
+
+        let body = file_client
+            .body(&header.hash)
+            .ok_or_else(|| eyre::eyre!("the chain file is missing the body of block {number}"))?;
+        let block = SealedBlock::new(header.clone(), body.clone());
+        consensus
+            .validate_block(&block)
+            .map_err(|err| eyre::eyre!("invalid block {number}: {err}"))?;
+
+        if let Some(tx) = block.body.iter().find(|tx| tx.recover_signer().is_none()) {
+            eyre::bail!("invalid sender of transaction {} in block {number}", tx.hash)
+        }
+        Ok(())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,388 @@
+//! Command that initializes the node state from a state snapshot.
+
+use crate::{
+    args::{
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+        DatabaseArgs,
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+    init::init_genesis,
+};
+use alloy_rlp::Decodable;
+use clap::Parser;
+use eyre::Context;
+use reth_db::{
+    database::Database,
+    init_db,
+    mdbx::DatabaseArguments,
+    table::{Decode, Decompress, Table},
+    tables::{self, RawKey, RawTable, RawValue},
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{
+    fs, keccak256,
+    stage::{StageCheckpoint, StageId},
+    Address, Bytes, ChainSpec, Header, Signature, TxNumber, B256, U256,
+};
+use reth_provider::{ProviderFactory, StageCheckpointWriter};
+use reth_trie::{trie_cursor::noop::NoopTrieCursorFactory, StateRoot};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use tracing::info;
+
+/// The name of the manifest file at the root of a snapshot.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// The stages whose output is contained in a snapshot, their checkpoints are moved to the snapshot
+/// block. The other stages sync the blocks up to the snapshot block before these continue.
+const STATE_STAGES: [StageId; 7] = [
+    StageId::Execution,
+    StageId::MerkleUnwind,
+    StageId::AccountHashing,
+    StageId::StorageHashing,
+    StageId::MerkleExecute,
+    StageId::IndexStorageHistory,
+    StageId::IndexAccountHistory,
+];
+
+/// Initializes the database from a state snapshot.
+///
+/// A snapshot is a directory, either local or served over HTTP(S), containing a `manifest.json` and
+/// a set of chunk files. Each chunk holds raw database entries of a single table, encoded as a
+/// sequence of `[key length: u32 BE][key][value length: u32 BE][value]` records.
+///
+/// The manifest is signed by the snapshot producer, and every chunk is verified against the hash
+/// listed in the manifest before it is imported. Once imported, the state root is recomputed from
+/// the hashed state alone, without the imported trie tables, and compared against the snapshot
+/// header.
+///
+/// Only the snapshot header is inserted, the headers and bodies below it are still missing. The
+/// checkpoints of the stages that derive from the state are moved to the snapshot block, while
+/// the header and body stages start from genesis: the headers stage fills the gap below the
+/// snapshot header, which also proves that the snapshot block is part of the chain, and execution
+/// continues after the snapshot block.
+#[derive(Debug, Parser)]
+pub struct InitStateCommand {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The location of the snapshot, either a `http://` or `https://` URL or a local directory.
+    #[arg(long, value_name = "URL")]
+    from_snapshot: String,
+
+    /// The address that is expected to have signed the snapshot manifest.
+    #[arg(long, value_name = "ADDRESS")]
+    snapshot_signer: Address,
+}
+
+impl InitStateCommand {
+    /// Execute the `init-state` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        info!(target: "reth::cli", "reth init-state starting");
+
+        // add network name to data dir
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = data_dir.db_path();
+        info!(target: "reth::cli", path = ?db_path, "Opening database");
+        let db =
+            Arc::new(init_db(&db_path, DatabaseArguments::default().log_level(self.db.log_level))?);
+        info!(target: "reth::cli", "Database opened");
+
+        init_genesis(db.clone(), self.chain.clone())?;
+        {
+            let tx = db.tx()?;
+            if tx.entries::<tables::CanonicalHeaders>()? > 1 {
+                eyre::bail!("init-state can only be run against a database that contains no blocks")
+            }
+        }
+
+        let source = SnapshotSource::new(&self.from_snapshot);
+        let manifest: SnapshotManifest =
+            serde_json::from_slice(&source.fetch(MANIFEST_FILE).await?)
+                .wrap_err("failed to parse snapshot manifest")?;
+        let signer = manifest.recover_signer()?;
+        if signer != self.snapshot_signer {
+            eyre::bail!("snapshot manifest signed by {signer}, expected {}", self.snapshot_signer)
+        }
+
+        let header = Header::decode(&mut manifest.header.as_ref())?.seal_slow();
+        info!(target: "reth::cli", number = header.number, hash = ?header.hash, chunks = manifest.chunks.len(), "Importing snapshot");
+
+        let tx = db.tx_mut()?;
+        for table in SnapshotTable::ALL {
+            table.clear(&tx)?;
+        }
+        // history of the genesis state is meaningless on top of the snapshot state
+        tx.clear::<tables::AccountHistory>()?;
+        tx.clear::<tables::StorageHistory>()?;
+        tx.clear::<tables::AccountChangeSet>()?;
+        tx.clear::<tables::StorageChangeSet>()?;
+
+        for (idx, chunk) in manifest.chunks.iter().enumerate() {
+            let data = source.fetch(&chunk.file).await?;
+            let hash = keccak256(&data);
+            if hash != chunk.hash {
+                eyre::bail!(
+                    "chunk {} hash mismatch: expected {}, got {hash}",
+                    chunk.file,
+                    chunk.hash
+                )
+            }
+            let entries = chunk.table.import(&tx, &data)?;
+            info!(target: "reth::cli", chunk = idx + 1, total = manifest.chunks.len(), table = ?chunk.table, entries, "Imported chunk");
+        }
+
+        // The imported trie tables are not used, otherwise the root of the imported account trie
+        // would be returned as is and a corrupted snapshot would pass.
+        info!(target: "reth::cli", "Verifying state root");
+        let state_root =
+            StateRoot::from_tx(&tx).with_trie_cursor_factory(NoopTrieCursorFactory).root()?;
+        if state_root != header.state_root {
+            eyre::bail!(
+                "state root mismatch: snapshot header has {}, computed {state_root}",
+                header.state_root
+            )
+        }
+
+        // Insert the snapshot block header, which the headers stage treats as the end of a gap
+        // and downloads the headers below it towards genesis. The total difficulty and body
+        // indices are appended by their stages, so they are not inserted for the snapshot block.
+        tx.put::<tables::CanonicalHeaders>(header.number, header.hash)?;
+        tx.put::<tables::HeaderNumbers>(header.hash, header.number)?;
+        tx.put::<tables::Headers>(header.number, header.header.clone())?;
+        tx.commit()?;
+
+        let factory = ProviderFactory::new(&db, self.chain.clone());
+        let provider_rw = factory.provider_rw()?;
+        for stage in STATE_STAGES {
+            provider_rw.save_stage_checkpoint(stage, StageCheckpoint::new(header.number))?;
+        }
+        provider_rw.commit()?;
+
+        info!(target: "reth::cli", number = header.number, ?state_root, "Snapshot imported");
+        Ok(())
+    }
+}
+
+/// Where snapshot files are read from.
+#[derive(Debug)]
+enum SnapshotSource {
+    /// Files are fetched relative to the given `http://` or `https://` base URL.
+    Http(String),
+    /// Files are read relative to the given directory.
+    Local(PathBuf),
+}
+
+impl SnapshotSource {
+    fn new(location: &str) -> Self {
+        if location.starts_with("http://") || location.starts_with("https://") {
+            Self::Http(location.trim_end_matches('/').to_string())
+        } else {
+            Self::Local(PathBuf::from(location))
+        }
+    }
+
+    /// Returns the content of the file with the given name.
+    async fn fetch(&self, name: &str) -> eyre::Result<Vec<u8>> {
+        match self {
+            Self::Http(base) => {
+                let uri: hyper::Uri = format!("{base}/{name}").parse()?;
+                let connector = hyper_rustls::HttpsConnectorBuilder::new()
+                    .with_native_roots()
+                    .https_or_http()
+                    .enable_http1()
+                    .build();
+                let response = hyper::Client::builder()
+                    .build::<_, hyper::Body>(connector)
+                    .get(uri.clone())
+                    .await?;
+                if !response.status().is_success() {
+                    eyre::bail!("failed to fetch {uri}: {}", response.status())
+                }
+                Ok(hyper::body::to_bytes(response.into_body()).await?.to_vec())
+            }
+            Self::Local(dir) => Ok(fs::read(dir.join(name))?),
+        }
+    }
+}
+
+/// The manifest describing a state snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotManifest {
+    /// The RLP encoded header of the block the snapshot was taken at.
+    header: Bytes,
+    /// The total difficulty at the snapshot block.
+    ///
+    /// Informational, the total difficulty stage computes it from the synced headers.
+    total_difficulty: U256,
+    /// The transaction number following the last transaction of the snapshot block.
+    ///
+    /// Informational, the bodies stage assigns transaction numbers from the synced bodies.
+    next_tx_num: TxNumber,
+    /// The chunks of the snapshot.
+    chunks: Vec<SnapshotChunk>,
+    /// The 65 byte `r || s || v` signature over the keccak256 hash of the manifest JSON, encoded
+    /// without this field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<Bytes>,
+}
+
+impl SnapshotManifest {
+    /// Recovers the signer of the manifest.
+    fn recover_signer(&self) -> eyre::Result<Address> {
+        let Some(signature) = self.signature.as_ref().filter(|sig| sig.len() == 65) else {
+            eyre::bail!("snapshot manifest is not signed")
+        };
+        let signature = Signature {
+            r: U256::from_be_slice(&signature[..32]),
+            s: U256::from_be_slice(&signature[32..64]),
+            odd_y_parity: signature[64] % 27 == 1,
+        };
+        let unsigned = Self { signature: None, ..self.clone() };
+        signature
+            .recover_signer(manifest_hash(&unsigned)?)
+            .ok_or_else(|| eyre::eyre!("invalid snapshot manifest signature"))
+    }
+}
+
+/// Returns the hash that is signed by the snapshot producer.
+fn manifest_hash(unsigned: &SnapshotManifest) -> eyre::Result<B256> {
+    Ok(keccak256(serde_json::to_vec(unsigned)?))
+}
+
+/// A single chunk of a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotChunk {
+    /// The table the entries of the chunk belong to.
+    table: SnapshotTable,
+    /// The file name of the chunk, relative to the snapshot location.
+    file: String,
+    /// The keccak256 hash of the chunk file.
+    hash: B256,
+}
+
+/// The tables that can be imported from a snapshot.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum SnapshotTable {
+    PlainAccountState,
+    PlainStorageState,
+    Bytecodes,
+    HashedAccount,
+    HashedStorage,
+    AccountsTrie,
+    StoragesTrie,
+}
+
+impl SnapshotTable {
+    const ALL: [SnapshotTable; 7] = [
+        SnapshotTable::PlainAccountState,
+        SnapshotTable::PlainStorageState,
+        SnapshotTable::Bytecodes,
+        SnapshotTable::HashedAccount,
+        SnapshotTable::HashedStorage,
+        SnapshotTable::AccountsTrie,
+        SnapshotTable::StoragesTrie,
+    ];
+
+    /// Removes all entries from the table.
+    fn clear<TX: DbTxMut>(&self, tx: &TX) -> eyre::Result<()> {
+        match self {
+            Self::PlainAccountState => tx.clear::<tables::PlainAccountState>()?,
+            Self::PlainStorageState => tx.clear::<tables::PlainStorageState>()?,
+            Self::Bytecodes => tx.clear::<tables::Bytecodes>()?,
+            Self::HashedAccount => tx.clear::<tables::HashedAccount>()?,
+            Self::HashedStorage => tx.clear::<tables::HashedStorage>()?,
+            Self::AccountsTrie => tx.clear::<tables::AccountsTrie>()?,
+            Self::StoragesTrie => tx.clear::<tables::StoragesTrie>()?,
+        }
+        Ok(())
+    }
+
+    /// Imports the entries of a chunk and returns the number of imported entries.
+    fn import<TX: DbTxMut>(&self, tx: &TX, data: &[u8]) -> eyre::Result<usize> {
+        match self {
+            Self::PlainAccountState => import_chunk::<tables::PlainAccountState, _>(tx, data),
+            Self::PlainStorageState => import_chunk::<tables::PlainStorageState, _>(tx, data),
+            Self::Bytecodes => import_chunk::<tables::Bytecodes, _>(tx, data),
+            Self::HashedAccount => import_chunk::<tables::HashedAccount, _>(tx, data),
+            Self::HashedStorage => import_chunk::<tables::HashedStorage, _>(tx, data),
+            Self::AccountsTrie => import_chunk::<tables::AccountsTrie, _>(tx, data),
+            Self::StoragesTrie => import_chunk::<tables::StoragesTrie, _>(tx, data),
+        }
+    }
+}
+
+/// Writes all raw entries of the chunk into table `T`.
+fn import_chunk<T: Table, TX: DbTxMut>(tx: &TX, mut data: &[u8]) -> eyre::Result<usize> {
+    let mut entries = 0;
+    while !data.is_empty() {
+        let key = RawKey::<T::Key>::decode(next_record(&mut data)?)?;
+        let value = RawValue::<T::Value>::decompress(next_record(&mut data)?)?;
+        tx.put::<RawTable<T>>(key, value)?;
+        entries += 1;
+    }
+    Ok(entries)
+}
+
+/// Splits off the next length-prefixed record.
+fn next_record<'a>(data: &mut &'a [u8]) -> eyre::Result<&'a [u8]> {
+    if data.len() < 4 {
+        eyre::bail!("truncated chunk record length")
+    }
+    let (len, rest) = data.split_at(4);
+    let len = u32::from_be_bytes(len.try_into().expect("4 bytes")) as usize;
+    if rest.len() < len {
+        eyre::bail!("truncated chunk record")
+    }
+    let (record, rest) = rest.split_at(len);
+    *data = rest;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_records() {
+        let mut data = Vec::new();
+        for record in [&b"key"[..], &b""[..], &b"value"[..]] {
+            data.extend_from_slice(&(record.len() as u32).to_be_bytes());
+            data.extend_from_slice(record);
+        }
+
+        let mut buf = data.as_slice();
+        assert_eq!(next_record(&mut buf).unwrap(), b"key");
+        assert_eq!(next_record(&mut buf).unwrap(), b"");
+        assert_eq!(next_record(&mut buf).unwrap(), b"value");
+        assert!(buf.is_empty());
+
+        let mut truncated = &data[..data.len() - 1];
+        next_record(&mut truncated).unwrap();
+        next_record(&mut truncated).unwrap();
+        assert!(next_record(&mut truncated).is_err());
+    }
+}
@@ -3,10 +3,15 @@
 pub mod config_cmd;
 pub mod db;
 pub mod debug_cmd;
+pub mod export;
 pub mod import;
 pub mod init_cmd;
+pub mod init_state;
 pub mod node;
 pub mod p2p;
+pub mod profile_cmd;
 pub mod recover;
+pub mod snapshot;
 pub mod stage;
 pub mod test_vectors;
+pub mod trie;
@@ -8,6 +8,7 @@ use reth_beacon_consensus::BeaconConsensus;
 use reth_interfaces::consensus::Consensus;
 use reth_primitives::ChainSpec;
 use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use tracing::info;
 
 /// Re-export from `reth_node_core` for backwards compatibility.
 pub mod events {
@@ -22,11 +23,13 @@ pub mod cl_events {
 use crate::{
     args::{
         utils::{chain_help, genesis_value_parser, parse_socket_address, SUPPORTED_CHAINS},
-        DatabaseArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs, PruningArgs,
-        RpcServerArgs, TxPoolArgs,
+        DatabaseArgs, DebugArgs, DevArgs, DiskWatchdogArgs, HealthArgs, NetworkArgs,
+        OutcomeStreamArgs, PayloadBuilderArgs, PinnedTrieArgs, PruningArgs, RootSloArgs,
+        RpcServerArgs, TaskGroupArgs, TxPoolArgs,
     },
     builder::NodeConfig,
     cli::{db_type::DatabaseBuilder, ext::RethCliExt},
+    core::profiles::{default_profiles_path, NodeProfiles},
     dirs::{DataDirPath, MaybePlatformPath},
     runner::CliContext,
 };
@@ -48,6 +51,13 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[arg(long, value_name = "FILE", verbatim_doc_comment)]
     pub config: Option<PathBuf>,
 
+    /// Watch the configuration file and apply changes to runtime-tunable settings.
+    ///
+    /// Currently only the peer connection limits can be changed at runtime, all other changes
+    /// take effect after a restart.
+    #[arg(long = "config.watch", verbatim_doc_comment)]
+    pub config_watch: bool,
+
     /// The chain this node is running.
     ///
     /// Possible values are either a built-in chain or the path to a chain specification file.
@@ -65,9 +75,17 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     /// Enable Prometheus metrics.
     ///
     /// The metrics will be served at the given interface and port.
+    /// A JSON snapshot of the state root computations and trie caches is served at `/debug/trie`.
     #[arg(long, value_name = "SOCKET", value_parser = parse_socket_address, help_heading = "Metrics")]
     pub metrics: Option<SocketAddr>,
 
+    /// Serve CPU and heap profiling endpoints on the metrics server.
+    ///
+    /// CPU profiles are served at `/debug/pprof/profile?seconds=N`, heap profiles at
+    /// `/debug/pprof/heap`. Requires the `profiling` and `jemalloc-prof` features respectively.
+    #[arg(long = "metrics.profiling", requires = "metrics", help_heading = "Metrics")]
+    pub metrics_profiling: bool,
+
     /// Add a new instance of a node.
     ///
     /// Configures the ports of the node to avoid conflicts with the defaults.
@@ -84,6 +102,20 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[arg(long, value_name = "INSTANCE", global = true, default_value_t = 1, value_parser = value_parser!(u16).range(..=200))]
     pub instance: u16,
 
+    /// Start the node with the settings of a named profile.
+    ///
+    /// The profile sets the chain, data dir, config file and instance of the node, see `reth
+    /// profile`. Metrics are labeled with the profile name and logs are written to a directory
+    /// named after the profile.
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["datadir", "config", "chain"])]
+    pub profile: Option<String>,
+
+    /// The path to the profiles file.
+    ///
+    /// Defaults to `profiles.toml` in the OS-specific configuration directory.
+    #[arg(long, value_name = "FILE", requires = "profile")]
+    pub profiles_file: Option<PathBuf>,
+
     /// Overrides the KZG trusted setup by reading from the supplied file.
     #[arg(long, value_name = "PATH")]
     pub trusted_setup_file: Option<PathBuf>,
@@ -120,6 +152,30 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[clap(flatten)]
     pub pruning: PruningArgs,
 
+    /// All execution outcome stream related arguments with --outcome-stream prefix
+    #[clap(flatten)]
+    pub outcome_stream: OutcomeStreamArgs,
+
+    /// All task group related arguments with --task-group prefix
+    #[clap(flatten)]
+    pub task_groups: TaskGroupArgs,
+
+    /// All disk watchdog related arguments with --disk prefix
+    #[clap(flatten)]
+    pub disk_watchdog: DiskWatchdogArgs,
+
+    /// All state root latency related arguments with --root-slo prefix
+    #[clap(flatten)]
+    pub root_slo: RootSloArgs,
+
+    /// All pinned trie related arguments with --trie prefix
+    #[clap(flatten)]
+    pub pinned_trie: PinnedTrieArgs,
+
+    /// All readiness check related arguments with --health prefix
+    #[clap(flatten)]
+    pub health: HealthArgs,
+
     /// Rollup related arguments
     #[cfg(feature = "optimism")]
     #[clap(flatten)]
@@ -137,8 +193,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let Self {
             datadir,
             config,
+            config_watch,
             chain,
             metrics,
+            metrics_profiling,
+            profile,
+            profiles_file,
             trusted_setup_file,
             instance,
             network,
@@ -149,6 +209,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            outcome_stream,
+            task_groups,
+            disk_watchdog,
+            root_slo,
+            pinned_trie,
+            health,
             #[cfg(feature = "optimism")]
             rollup,
             ..
@@ -156,8 +222,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         NodeCommand {
             datadir,
             config,
+            config_watch,
             chain,
             metrics,
+            metrics_profiling,
+            profile,
+            profiles_file,
             instance,
             trusted_setup_file,
             network,
@@ -168,6 +238,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            outcome_stream,
+            task_groups,
+            disk_watchdog,
+            root_slo,
+            pinned_trie,
+            health,
             #[cfg(feature = "optimism")]
             rollup,
             ext,
@@ -175,12 +251,18 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
     }
 
     /// Execute `node` command
-    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+    pub async fn execute(mut self, ctx: CliContext) -> eyre::Result<()> {
+        self.apply_profile()?;
+
         let Self {
             datadir,
             config,
+            config_watch,
             chain,
             metrics,
+            metrics_profiling,
+            profile,
+            profiles_file: _,
             trusted_setup_file,
             instance,
             network,
@@ -191,6 +273,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            outcome_stream,
+            task_groups,
+            disk_watchdog,
+            root_slo,
+            pinned_trie,
+            health,
             #[cfg(feature = "optimism")]
             rollup,
             ext,
@@ -203,8 +291,11 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let node_config = NodeConfig {
             database,
             config,
+            config_watch,
             chain,
             metrics,
+            metrics_profiling,
+            profile,
             instance,
             trusted_setup_file,
             network,
@@ -215,6 +306,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            outcome_stream,
+            task_groups,
+            disk_watchdog,
+            root_slo,
+            pinned_trie,
+            health,
             #[cfg(feature = "optimism")]
             rollup,
         };
@@ -226,6 +323,31 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         handle.wait_for_node_exit().await
     }
 
+    /// Applies the settings of the profile selected with `--profile`, if any.
+    pub fn apply_profile(&mut self) -> eyre::Result<()> {
+        let Some(name) = &self.profile else { return Ok(()) };
+
+        let path = self.profiles_file.clone().or_else(default_profiles_path).ok_or_else(|| {
+            eyre::eyre!("Could not resolve the profiles file, set one with --profiles-file")
+        })?;
+        let profiles = NodeProfiles::load(&path)?;
+        let profile = profiles.get(name)?;
+
+        if let Some(chain) = &profile.chain {
+            self.chain = genesis_value_parser(chain)?;
+        }
+        if let Some(datadir) = profile.datadir(name) {
+            self.datadir = datadir.into();
+        }
+        self.config = profile.config.clone();
+        if let Some(instance) = profile.instance {
+            self.instance = instance;
+        }
+
+        info!(target: "reth::cli", profile = %name, path = %path.display(), "Applied node profile");
+        Ok(())
+    }
+
     /// Returns the [Consensus] instance to use.
     ///
     /// By default this will be a [BeaconConsensus] instance, but if the `--dev` flag is set, it
@@ -396,4 +518,51 @@ mod tests {
         // check network listening port number
         assert_eq!(cmd.network.port, 30305);
     }
+
+    #[test]
+    #[cfg(not(feature = "optimism"))]
+    fn parse_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let profiles_file = dir.path().join("profiles.toml");
+        let mut profiles = NodeProfiles::default();
+        profiles
+            .insert(
+                "builder-holesky".to_string(),
+                crate::core::profiles::NodeProfile {
+                    chain: Some("holesky".to_string()),
+                    datadir: Some(dir.path().join("builder")),
+                    config: None,
+                    instance: Some(3),
+                },
+            )
+            .unwrap();
+        profiles.store(&profiles_file).unwrap();
+
+        let mut cmd = NodeCommand::<()>::try_parse_from([
+            "reth",
+            "--profile",
+            "builder-holesky",
+            "--profiles-file",
+            profiles_file.to_str().unwrap(),
+        ])
+        .unwrap();
+        cmd.apply_profile().unwrap();
+        assert_eq!(cmd.chain.chain, reth_primitives::HOLESKY.chain);
+        assert_eq!(cmd.instance, 3);
+        let data_dir = cmd.datadir.unwrap_or_chain_default(cmd.chain.chain);
+        assert_eq!(data_dir.db_path(), dir.path().join("builder").join("db"));
+
+        cmd.profile = Some("unknown".to_string());
+        assert!(cmd.apply_profile().is_err());
+
+        // the profile sets the data dir
+        assert!(NodeCommand::<()>::try_parse_from([
+            "reth",
+            "--profile",
+            "builder-holesky",
+            "--datadir",
+            "my/custom/path"
+        ])
+        .is_err());
+    }
 }
@@ -12,12 +12,22 @@ use crate::{
 use backon::{ConstantBuilder, Retryable};
 use clap::{Parser, Subcommand};
 use reth_config::Config;
-use reth_db::{mdbx::DatabaseArguments, open_db};
-use reth_discv4::NatResolver;
+use reth_db::{database::Database, mdbx::DatabaseArguments, open_db, open_db_read_only};
+use reth_discv4::{NatResolver, DEFAULT_DISCOVERY_ADDR, DEFAULT_DISCOVERY_PORT};
 use reth_interfaces::p2p::bodies::client::BodiesClient;
-use reth_primitives::{BlockHashOrNumber, ChainSpec, NodeRecord};
-use reth_provider::ProviderFactory;
-use std::{path::PathBuf, sync::Arc};
+use reth_network::{NetworkConfigBuilder, NetworkManager};
+use reth_network_api::NetworkInfo;
+use reth_primitives::{stage::StageId, BlockHashOrNumber, ChainSpec, Head, NodeRecord};
+use reth_provider::{
+    BlockHashReader, HeaderProvider, ProviderError, ProviderFactory, StageCheckpointReader,
+};
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{info, warn};
 
 /// `reth p2p` command
 #[derive(Debug, Parser)]
@@ -95,16 +105,32 @@ pub enum Subcommands {
         #[arg(value_parser = hash_or_num_value_parser)]
         id: BlockHashOrNumber,
     },
+    /// Serve the blocks of the datadir to peers, without syncing or executing blocks.
+    ///
+    /// Runs discovery, RLPx sessions and the `eth` request handler on top of the database of the
+    /// datadir, which is opened read-only, so it can be shared with a running node. There is no
+    /// engine, pipeline or transaction pool, so transactions are not exchanged with peers, and the
+    /// head announced to peers is refreshed from the database periodically.
+    ///
+    /// The `snap` protocol is not supported.
+    Serve {
+        /// Network listening address
+        #[arg(long = "addr", value_name = "ADDR", default_value_t = DEFAULT_DISCOVERY_ADDR)]
+        addr: Ipv4Addr,
+
+        /// Network listening port
+        #[arg(long = "port", value_name = "PORT", default_value_t = DEFAULT_DISCOVERY_PORT)]
+        port: u16,
+
+        /// The interval to refresh the head announced to peers at, in seconds.
+        #[arg(long, value_name = "SECONDS", default_value_t = 12)]
+        head_refresh_interval: u64,
+    },
 }
+
 impl Command {
     /// Execute `p2p` command
     pub async fn execute(&self) -> eyre::Result<()> {
-        let tempdir = tempfile::TempDir::new()?;
-        let noop_db = Arc::new(open_db(
-            &tempdir.into_path(),
-            DatabaseArguments::default().log_level(self.db.log_level),
-        )?);
-
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let config_path = self.config.clone().unwrap_or(data_dir.config_path());
@@ -130,6 +156,26 @@ impl Command {
 
         network_config_builder = self.discovery.apply_to_builder(network_config_builder);
 
+        if let Subcommands::Serve { addr, port, head_refresh_interval } = self.command {
+            let db = open_db_read_only(
+                &data_dir.db_path(),
+                DatabaseArguments::default().log_level(self.db.log_level),
+            )?;
+            let factory = ProviderFactory::new(Arc::new(db), self.chain.clone());
+            return serve(
+                network_config_builder.listener_addr(SocketAddr::V4(SocketAddrV4::new(addr, port))),
+                factory,
+                Duration::from_secs(head_refresh_interval.max(1)),
+            )
+            .await
+        }
+
+        let tempdir = tempfile::TempDir::new()?;
+        let noop_db = Arc::new(open_db(
+            &tempdir.into_path(),
+            DatabaseArguments::default().log_level(self.db.log_level),
+        )?);
+
         let network = network_config_builder
             .build(Arc::new(ProviderFactory::new(noop_db, self.chain.clone())))
             .start_network()
@@ -179,8 +225,79 @@ impl Command {
                 let body = result.into_iter().next().unwrap();
                 println!("Successfully downloaded body: {body:?}")
             }
+            Subcommands::Serve { .. } => unreachable!("served before the network is started"),
         }
 
         Ok(())
     }
 }
+
+/// Runs the network with the `eth` request handler backed by the given provider factory, until
+/// the network is shut down.
+async fn serve<DB>(
+    network_config_builder: NetworkConfigBuilder,
+    factory: ProviderFactory<DB>,
+    head_refresh_interval: Duration,
+) -> eyre::Result<()>
+where
+    DB: Database + Unpin + Clone + 'static,
+{
+    let head = lookup_head(&factory)?;
+    let network_config = network_config_builder.set_head(head).build(factory.clone());
+    let (handle, network, _, eth) = NetworkManager::builder(network_config)
+        .await?
+        .request_handler(factory.clone())
+        .split_with_handle();
+    tokio::spawn(eth);
+
+    info!(
+        target: "reth::cli",
+        peer_id = %handle.peer_id(),
+        addr = %handle.local_addr(),
+        head = head.number,
+        "Serving blocks over P2P"
+    );
+
+    // the database may be written by a running node, so peers are told about its new blocks
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(head_refresh_interval);
+        let mut current = head.number;
+        loop {
+            interval.tick().await;
+            match lookup_head(&factory) {
+                Ok(head) if head.number != current => {
+                    current = head.number;
+                    handle.update_status(head);
+                }
+                Ok(_) => {}
+                Err(err) => warn!(target: "reth::cli", %err, "Failed to refresh head"),
+            }
+        }
+    });
+
+    network.await;
+    Ok(())
+}
+
+/// Returns the highest block that was fully synced in the database.
+fn lookup_head<DB: Database>(factory: &ProviderFactory<DB>) -> eyre::Result<Head> {
+    let provider = factory.provider()?;
+    let number = provider
+        .get_stage_checkpoint(StageId::Finish)?
+        .map(|checkpoint| checkpoint.block_number)
+        .unwrap_or_default();
+    let header =
+        provider.header_by_number(number)?.ok_or(ProviderError::HeaderNotFound(number.into()))?;
+    let total_difficulty = provider
+        .header_td_by_number(number)?
+        .ok_or(ProviderError::TotalDifficultyNotFound(number))?;
+    let hash = provider.block_hash(number)?.ok_or(ProviderError::HeaderNotFound(number.into()))?;
+
+    Ok(Head {
+        number,
+        hash,
+        difficulty: header.difficulty,
+        total_difficulty,
+        timestamp: header.timestamp,
+    })
+}
@@ -0,0 +1,111 @@
+//! CLI command to manage named node profiles.
+
+use crate::{
+    args::utils::{chain_help, genesis_value_parser},
+    core::profiles::{default_profiles_path, NodeProfile, NodeProfiles},
+};
+use clap::{value_parser, Parser, Subcommand};
+use eyre::bail;
+use std::path::PathBuf;
+
+/// `reth profile` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the profiles file.
+    ///
+    /// Defaults to `profiles.toml` in the OS-specific configuration directory.
+    #[arg(long, value_name = "FILE", global = true)]
+    profiles_file: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth profile` subcommands
+#[derive(Debug, Subcommand)]
+pub enum Subcommands {
+    /// List all profiles
+    List,
+    /// Show the settings of a profile
+    Show {
+        /// The name of the profile.
+        name: String,
+    },
+    /// Add a profile, or replace the profile with the same name
+    Add {
+        /// The name of the profile.
+        name: String,
+
+        /// The chain of the profile.
+        ///
+        /// Possible values are either a built-in chain or the path to a chain specification file.
+        #[arg(long, value_name = "CHAIN_OR_PATH", long_help = chain_help())]
+        chain: Option<String>,
+
+        /// The data dir of the profile.
+        ///
+        /// Defaults to a directory named after the profile in `profiles/` of the default data dir.
+        #[arg(long, value_name = "DATA_DIR")]
+        datadir: Option<PathBuf>,
+
+        /// The config file of the profile.
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+
+        /// The instance the ports of the profile are derived from.
+        ///
+        /// Defaults to the lowest instance not used by another profile.
+        #[arg(long, value_name = "INSTANCE", value_parser = value_parser!(u16).range(1..=200))]
+        instance: Option<u16>,
+    },
+    /// Remove a profile
+    Remove {
+        /// The name of the profile.
+        name: String,
+    },
+}
+
+impl Command {
+    /// Execute `profile` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let Some(path) = self.profiles_file.or_else(default_profiles_path) else {
+            bail!("Could not resolve the profiles file, set one with --profiles-file")
+        };
+        let mut profiles = NodeProfiles::load(&path)?;
+
+        match self.command {
+            Subcommands::List => {
+                for (name, profile) in &profiles.profiles {
+                    println!(
+                        "{name}\tchain={}\tdatadir={}\tinstance={}",
+                        profile.chain.as_deref().unwrap_or("mainnet"),
+                        profile.datadir(name).unwrap_or_default().display(),
+                        profile.instance.unwrap_or(1),
+                    );
+                }
+            }
+            Subcommands::Show { name } => {
+                println!("{}", toml::to_string_pretty(profiles.get(&name)?)?);
+            }
+            Subcommands::Add { name, chain, datadir, config, instance } => {
+                if let Some(chain) = &chain {
+                    genesis_value_parser(chain)?;
+                }
+                let profile = profiles
+                    .insert(name.clone(), NodeProfile { chain, datadir, config, instance })?;
+                println!("{}", toml::to_string_pretty(profile)?);
+                profiles.store(&path)?;
+                println!("Stored profile {name} in {}", path.display());
+            }
+            Subcommands::Remove { name } => {
+                if profiles.remove(&name).is_none() {
+                    bail!("Unknown profile: {name}")
+                }
+                profiles.store(&path)?;
+                println!("Removed profile {name} from {}", path.display());
+            }
+        }
+
+        Ok(())
+    }
+}
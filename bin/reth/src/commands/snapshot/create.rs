@@ -0,0 +1,374 @@
+//! Command for creating a snapshot of the datadir of a running node.
+
+use super::{sha256_file, SnapshotFile, SnapshotManifest, MANIFEST_FILE};
+use crate::{
+    args::{
+        types::ZeroAsNoneU64,
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+        DatabaseArgs,
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+};
+use clap::Parser;
+use reth_db::{
+    mdbx::{DatabaseArguments, Environment},
+    open_db_read_only,
+    version::DB_VERSION_FILE_NAME,
+};
+use reth_primitives::{stage::StageId, ChainSpec};
+use reth_provider::{BlockHashReader, ProviderError, ProviderFactory, StageCheckpointReader};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{info, warn};
+
+/// The name of the database file in the database directory.
+const MDBX_DAT_FILE: &str = "mdbx.dat";
+
+/// The size of the chunks files are copied in.
+const COPY_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// `reth snapshot create` command
+///
+/// Copies the database, the static files and the config of the node into a new datadir that a
+/// node can be started from.
+///
+/// The database is copied from a read transaction, so the copy is consistent even while the node
+/// is writing to it. The read transaction is held until the copy is done, which keeps the node
+/// from reusing freed pages in the meantime, so the database of the node may grow while copying
+/// large databases at a low copy rate.
+///
+/// The static files are cloned with reflinks if the filesystem supports them, and copied at the
+/// limited copy rate otherwise. The database is always copied, since a reflink of a file that is
+/// being written to is not guaranteed to be consistent.
+///
+/// The node keys (JWT secret and P2P secret key) are not copied.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The directory to create the snapshot in. It must not exist or be empty.
+    ///
+    /// The snapshot is a datadir that a node can be started from with `--datadir`.
+    #[arg(value_name = "DEST")]
+    dest: PathBuf,
+
+    /// The maximum rate to copy files at, in MB per second, to limit the impact on the disk of
+    /// the running node.
+    ///
+    /// Reflinks are not limited. Set to 0 to disable the limit.
+    #[arg(long, value_name = "MB_PER_SEC", default_value_t = ZeroAsNoneU64::new(256))]
+    max_copy_rate: ZeroAsNoneU64,
+
+    /// Always copy the static files, instead of cloning them with reflinks if the filesystem
+    /// supports them.
+    #[arg(long)]
+    no_reflink: bool,
+}
+
+impl Command {
+    /// Execute `snapshot create` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db_path = data_dir.db_path();
+        eyre::ensure!(db_path.join(MDBX_DAT_FILE).exists(), "No database at {db_path:?}");
+
+        if self.dest.exists() {
+            eyre::ensure!(
+                fs::read_dir(&self.dest)?.next().is_none(),
+                "Snapshot directory {:?} is not empty",
+                self.dest
+            );
+        }
+        let dest_db_path = self.dest.join("db");
+        let dest_snapshots_path = self.dest.join("snapshots");
+        fs::create_dir_all(&dest_db_path)?;
+
+        let mut copier =
+            FileCopier::new(self.max_copy_rate.0.map(|rate| rate * 1024 * 1024), !self.no_reflink);
+        let started_at = Instant::now();
+
+        let db =
+            open_db_read_only(&db_path, DatabaseArguments::default().log_level(self.db.log_level))?;
+        info!(target: "reth::cli", "Copying database");
+        copier.copy_database(&db, &dest_db_path.join(MDBX_DAT_FILE))?;
+        drop(db);
+        copier.copy_file(
+            &db_path.join(DB_VERSION_FILE_NAME),
+            &dest_db_path.join(DB_VERSION_FILE_NAME),
+        )?;
+        info!(target: "reth::cli", elapsed = ?started_at.elapsed(), "Copied database");
+
+        // static files are immutable and only ever added, so copying them after the database
+        // ensures that the snapshot has every static file the copied database relies on
+        let snapshots_path = data_dir.snapshots_path();
+        if snapshots_path.exists() {
+            fs::create_dir_all(&dest_snapshots_path)?;
+            for entry in fs::read_dir(&snapshots_path)? {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue
+                }
+                copier.copy_file(&entry.path(), &dest_snapshots_path.join(entry.file_name()))?;
+            }
+            info!(target: "reth::cli", elapsed = ?started_at.elapsed(), "Copied static files");
+        }
+
+        let config_path = data_dir.config_path();
+        if config_path.exists() {
+            copier.copy_file(&config_path, &self.dest.join("reth.toml"))?;
+        }
+
+        // the tip is read from the copy, since the node may have advanced while copying
+        let db = open_db_read_only(
+            &dest_db_path,
+            DatabaseArguments::default().log_level(self.db.log_level),
+        )?;
+        let provider = ProviderFactory::new(db, self.chain.clone()).provider()?;
+        let block_number = provider
+            .get_stage_checkpoint(StageId::Finish)?
+            .map(|checkpoint| checkpoint.block_number)
+            .unwrap_or_default();
+        let block_hash = provider
+            .block_hash(block_number)?
+            .ok_or(ProviderError::HeaderNotFound(block_number.into()))?;
+        drop(provider);
+
+        let manifest = SnapshotManifest {
+            chain_id: self.chain.chain.id(),
+            block_number,
+            block_hash,
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            files: snapshot_files(&self.dest)?,
+        };
+        fs::write(self.dest.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?)?;
+
+        info!(
+            target: "reth::cli",
+            block_number,
+            %block_hash,
+            copied = copier.throttle.copied,
+            elapsed = ?started_at.elapsed(),
+            "Created snapshot at {:?}",
+            self.dest
+        );
+        Ok(())
+    }
+}
+
+/// Returns the files of the snapshot for the manifest.
+fn snapshot_files(dest: &Path) -> eyre::Result<Vec<SnapshotFile>> {
+    let mut paths = vec![PathBuf::from("db").join(MDBX_DAT_FILE)];
+    paths.push(PathBuf::from("db").join(DB_VERSION_FILE_NAME));
+    if dest.join("snapshots").exists() {
+        let mut snapshots = fs::read_dir(dest.join("snapshots"))?
+            .map(|entry| Ok(PathBuf::from("snapshots").join(entry?.file_name())))
+            .collect::<io::Result<Vec<_>>>()?;
+        snapshots.sort_unstable();
+        paths.extend(snapshots);
+    }
+    if dest.join("reth.toml").exists() {
+        paths.push(PathBuf::from("reth.toml"));
+    }
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let full_path = dest.join(&path);
+            let size = fs::metadata(&full_path)?.len();
+            let sha256 = (!path.ends_with(MDBX_DAT_FILE)).then(|| sha256_file(&full_path));
+            Ok(SnapshotFile {
+                path: path.to_string_lossy().replace('\\', "/"),
+                size,
+                sha256: sha256.transpose()?,
+            })
+        })
+        .collect()
+}
+
+/// Copies files at a limited rate, or clones them with reflinks.
+#[derive(Debug)]
+struct FileCopier {
+    throttle: Throttle,
+    /// Whether to clone files with reflinks. Disabled after the first failed reflink.
+    reflink: bool,
+}
+
+impl FileCopier {
+    fn new(bytes_per_sec: Option<u64>, reflink: bool) -> Self {
+        Self { throttle: Throttle::new(bytes_per_sec), reflink }
+    }
+
+    /// Copies the file to the destination, which must not exist.
+    fn copy_file(&mut self, src: &Path, dest: &Path) -> io::Result<()> {
+        let src = File::open(src)?;
+        let mut dest = OpenOptions::new().write(true).create_new(true).open(dest)?;
+
+        if self.reflink {
+            match reflink(&src, &dest) {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    warn!(target: "reth::cli", %err, "Failed to clone file, falling back to copying");
+                    self.reflink = false;
+                }
+            }
+        }
+
+        self.throttle.copy(src, &mut dest)?;
+        dest.sync_all()
+    }
+
+    /// Copies the database from a read transaction to the destination, which must not exist.
+    #[cfg(unix)]
+    fn copy_database(&mut self, env: &Environment, dest: &Path) -> eyre::Result<()> {
+        use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+        let mut dest = OpenOptions::new().write(true).create_new(true).open(dest)?;
+
+        // the database is written to a pipe, and copied from the pipe at the limited rate
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for the two file descriptors of the pipe.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error().into())
+        }
+        // SAFETY: the file descriptors were just created and are owned by nothing else.
+        let (reader, writer) = unsafe { (File::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+
+        let throttle = &mut self.throttle;
+        std::thread::scope(|scope| {
+            // the reader is dropped if copying fails, so writing to the pipe fails instead of
+            // blocking forever
+            let copy = scope
+                .spawn(move || throttle.copy(reader, &mut dest).and_then(|()| dest.sync_all()));
+
+            let written = env.copy_to_fd(writer.as_raw_fd(), false);
+            // closing the writer ends the copy
+            drop(writer);
+            let copied = copy.join().expect("copy thread panicked");
+
+            written?;
+            copied?;
+            Ok(())
+        })
+    }
+
+    /// Copies the database from a read transaction to the destination, which must not exist.
+    #[cfg(not(unix))]
+    fn copy_database(&mut self, env: &Environment, dest: &Path) -> eyre::Result<()> {
+        env.copy(dest, false)?;
+        Ok(())
+    }
+}
+
+/// Limits the rate of copied bytes.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    bytes_per_sec: Option<u64>,
+    started_at: Instant,
+    /// The total number of copied bytes.
+    pub(crate) copied: u64,
+}
+
+impl Throttle {
+    pub(crate) fn new(bytes_per_sec: Option<u64>) -> Self {
+        Self { bytes_per_sec, started_at: Instant::now(), copied: 0 }
+    }
+
+    /// Copies everything from the reader to the writer, waiting as long as needed to keep the
+    /// average rate within the limit.
+    fn copy(&mut self, mut reader: impl Read, writer: &mut impl Write) -> io::Result<()> {
+        let mut buf = vec![0; COPY_CHUNK_SIZE];
+        loop {
+            let read = match reader.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            writer.write_all(&buf[..read])?;
+            self.consume(read as u64);
+        }
+    }
+
+    /// Records the given number of copied bytes, waiting as long as needed to keep the average
+    /// rate within the limit.
+    pub(crate) fn consume(&mut self, bytes: u64) {
+        self.copied += bytes;
+        let Some(bytes_per_sec) = self.bytes_per_sec else { return };
+        let expected = Duration::from_secs_f64(self.copied as f64 / bytes_per_sec as f64);
+        if let Some(wait) = expected.checked_sub(self.started_at.elapsed()) {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// Clones the contents of the source file into the destination file, sharing their blocks on
+/// disk.
+#[cfg(target_os = "linux")]
+fn reflink(src: &File, dest: &File) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    /// The `FICLONE` ioctl request, see `ioctl_ficlone(2)`.
+    const FICLONE: u64 = 0x4004_9409;
+
+    // SAFETY: both file descriptors are valid for the duration of the call.
+    if unsafe { libc::ioctl(dest.as_raw_fd(), FICLONE as _, src.as_raw_fd()) } == -1 {
+        return Err(io::Error::last_os_error())
+    }
+    Ok(())
+}
+
+/// Clones the contents of the source file into the destination file, sharing their blocks on
+/// disk.
+#[cfg(not(target_os = "linux"))]
+fn reflink(_src: &File, _dest: &File) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "reflinks are only supported on Linux"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Throttle;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn throttle_limits_rate() {
+        let mut throttle = Throttle::new(Some(1024 * 1024));
+        let mut copied = Vec::new();
+        let started_at = Instant::now();
+        throttle.copy(&vec![1u8; 512 * 1024][..], &mut copied).unwrap();
+        assert_eq!(copied.len(), 512 * 1024);
+        assert_eq!(throttle.copied, 512 * 1024);
+        assert!(started_at.elapsed() >= Duration::from_millis(450));
+
+        let mut unlimited = Throttle::new(None);
+        unlimited.copy(&vec![1u8; 1024][..], &mut Vec::new()).unwrap();
+        assert_eq!(unlimited.copied, 1024);
+    }
+}
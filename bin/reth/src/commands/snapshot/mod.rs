@@ -0,0 +1,95 @@
+//! `reth snapshot` command.
+//!
+//! Creates copies of the datadir of a node while it's running, which new nodes can be started
+//! from, e.g. to spin up new replicas of a block builder without syncing them.
+
+use clap::{Parser, Subcommand};
+use reth_primitives::{BlockNumber, B256};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+mod create;
+mod verify;
+
+pub(crate) use create::Throttle;
+
+/// The name of the manifest file in the snapshot directory.
+const MANIFEST_FILE: &str = "reth-snapshot.json";
+
+/// `reth snapshot` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth snapshot` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Create a snapshot of the database and static files of a node, while the node is running.
+    Create(create::Command),
+    /// Verify a snapshot against its manifest.
+    Verify(verify::Command),
+}
+
+impl Command {
+    /// Execute `snapshot` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Create(command) => command.execute().await,
+            Subcommands::Verify(command) => command.execute().await,
+        }
+    }
+}
+
+/// The manifest of a snapshot, written to [MANIFEST_FILE] in the snapshot directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotManifest {
+    /// The id of the chain of the snapshot.
+    chain_id: u64,
+    /// The number of the highest block that was fully synced in the snapshot.
+    block_number: BlockNumber,
+    /// The hash of the highest block that was fully synced in the snapshot.
+    block_hash: B256,
+    /// The time the snapshot was created at, in seconds since the unix epoch.
+    created_at: u64,
+    /// The files of the snapshot.
+    files: Vec<SnapshotFile>,
+}
+
+/// A file of a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotFile {
+    /// The path of the file, relative to the snapshot directory.
+    path: String,
+    /// The size of the file in bytes.
+    size: u64,
+    /// The SHA-256 checksum of the file.
+    ///
+    /// Not set for the database, which is verified by opening it instead, since checksumming it
+    /// would take as long as copying it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<B256>,
+}
+
+/// Returns the SHA-256 checksum of the file.
+fn sha256_file(path: &Path) -> io::Result<B256> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(B256::from_slice(&hasher.finalize()))
+}
@@ -0,0 +1,125 @@
+//! Command for verifying a snapshot against its manifest.
+
+use super::{sha256_file, SnapshotManifest, MANIFEST_FILE};
+use crate::args::{
+    utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+    DatabaseArgs,
+};
+use clap::Parser;
+use reth_db::{mdbx::DatabaseArguments, open_db_read_only, version::check_db_version_file};
+use reth_primitives::{stage::StageId, ChainSpec};
+use reth_provider::{BlockHashReader, ProviderFactory, StageCheckpointReader};
+use std::{fs, path::PathBuf, sync::Arc};
+use tracing::info;
+
+/// `reth snapshot verify` command
+///
+/// Checks the sizes and checksums of the files of a snapshot against its manifest, and checks
+/// that the database of the snapshot opens, belongs to the chain and is at the block of the
+/// manifest.
+///
+/// Verify a snapshot before starting a node from it, since the node modifies the files.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The chain of the snapshot.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The directory of the snapshot.
+    #[arg(value_name = "DIR")]
+    dir: PathBuf,
+}
+
+impl Command {
+    /// Execute `snapshot verify` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let manifest: SnapshotManifest =
+            serde_json::from_slice(&fs::read(self.dir.join(MANIFEST_FILE))?)?;
+        eyre::ensure!(
+            manifest.chain_id == self.chain.chain.id(),
+            "Snapshot is of chain {}, expected chain {}",
+            manifest.chain_id,
+            self.chain.chain.id()
+        );
+
+        let mut mismatches = Vec::new();
+        for file in &manifest.files {
+            let path = self.dir.join(&file.path);
+            let size = match fs::metadata(&path) {
+                Ok(metadata) => metadata.len(),
+                Err(err) => {
+                    mismatches.push(format!("{}: {err}", file.path));
+                    continue
+                }
+            };
+            if size != file.size {
+                mismatches.push(format!("{}: size is {size}, expected {}", file.path, file.size));
+                continue
+            }
+            if let Some(expected) = file.sha256 {
+                let checksum = sha256_file(&path)?;
+                if checksum != expected {
+                    mismatches.push(format!(
+                        "{}: checksum is {checksum}, expected {expected}",
+                        file.path
+                    ));
+                }
+            }
+        }
+        eyre::ensure!(
+            mismatches.is_empty(),
+            "Snapshot files don't match the manifest:\n{}",
+            mismatches.join("\n")
+        );
+        info!(target: "reth::cli", files = manifest.files.len(), "Verified snapshot files");
+
+        let db_path = self.dir.join("db");
+        check_db_version_file(&db_path)?;
+        let db =
+            open_db_read_only(&db_path, DatabaseArguments::default().log_level(self.db.log_level))?;
+        let provider = ProviderFactory::new(db, self.chain.clone()).provider()?;
+
+        let genesis_hash = provider.block_hash(0)?;
+        eyre::ensure!(
+            genesis_hash == Some(self.chain.genesis_hash()),
+            "Genesis hash of the snapshot {genesis_hash:?} doesn't match the chain {}",
+            self.chain.genesis_hash()
+        );
+
+        let block_number = provider
+            .get_stage_checkpoint(StageId::Finish)?
+            .map(|checkpoint| checkpoint.block_number)
+            .unwrap_or_default();
+        eyre::ensure!(
+            block_number == manifest.block_number,
+            "Snapshot is at block {block_number}, expected block {}",
+            manifest.block_number
+        );
+        let block_hash = provider.block_hash(block_number)?;
+        eyre::ensure!(
+            block_hash == Some(manifest.block_hash),
+            "Hash of block {block_number} is {block_hash:?}, expected {}",
+            manifest.block_hash
+        );
+
+        info!(
+            target: "reth::cli",
+            block_number,
+            block_hash = %manifest.block_hash,
+            "Verified snapshot at {:?}",
+            self.dir
+        );
+        Ok(())
+    }
+}
@@ -17,6 +17,9 @@ use reth_beacon_consensus::BeaconConsensus;
 use reth_config::Config;
 use reth_db::{init_db, mdbx::DatabaseArguments};
 use reth_downloaders::bodies::bodies::BodiesDownloaderBuilder;
+use reth_node_core::metrics::{
+    cardinality::SubsystemFilter, health::HealthSources, trie_debug::TrieDebugSources,
+};
 
 use reth_primitives::ChainSpec;
 use reth_provider::{ProviderFactory, StageCheckpointReader};
@@ -132,11 +135,17 @@ impl Command {
 
         if let Some(listen_addr) = self.metrics {
             info!(target: "reth::cli", "Starting metrics endpoint at {}", listen_addr);
+            let health = HealthSources::default();
+            health.set_database(Arc::clone(&db) as _, data_dir.db_path());
             prometheus_exporter::serve(
                 listen_addr,
                 prometheus_exporter::install_recorder()?,
                 Arc::clone(&db),
                 metrics_process::Collector::default(),
+                false,
+                TrieDebugSources::default(),
+                health,
+                SubsystemFilter::new(&config.metrics.disabled_subsystems),
             )
             .await?;
         }
@@ -0,0 +1,28 @@
+//! `reth trie` command.
+
+use clap::{Parser, Subcommand};
+
+mod recompute;
+
+/// `reth trie` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+/// `reth trie` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Recompute and verify the state roots of a range of historical blocks.
+    Recompute(recompute::Command),
+}
+
+impl Command {
+    /// Execute `trie` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Recompute(command) => command.execute().await,
+        }
+    }
+}
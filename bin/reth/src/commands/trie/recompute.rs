@@ -0,0 +1,206 @@
+//! Command for recomputing and verifying the state roots of historical blocks.
+
+use crate::{
+    args::{
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+        DatabaseArgs,
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+};
+use clap::Parser;
+use rayon::prelude::*;
+use reth_db::{database::Database, mdbx::DatabaseArguments, open_db_read_only};
+use reth_primitives::{stage::StageId, BlockNumber, ChainSpec, B256};
+use reth_provider::{HeaderProvider, ProviderError, ProviderFactory, StageCheckpointReader};
+use reth_trie::HistoricalTrieView;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// `reth trie recompute` command
+///
+/// Recomputes the state root of every block in the range from the hashed state and the trie
+/// tables at the merkle checkpoint, overlaid with the reverts of all blocks above it, and compares
+/// it with the state root of the header. Blocks are recomputed in parallel, each in its own read
+/// transaction, and the timings of every block are written to a CSV file.
+///
+/// Changesets of the range up to the merkle checkpoint must not be pruned.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The first block to recompute the state root of.
+    #[arg(long)]
+    from: BlockNumber,
+
+    /// The last block to recompute the state root of, at most the merkle checkpoint.
+    #[arg(long)]
+    to: BlockNumber,
+
+    /// The number of blocks to recompute in parallel.
+    ///
+    /// Defaults to the available parallelism of the system.
+    #[arg(long)]
+    parallelism: Option<NonZeroUsize>,
+
+    /// The CSV file to write the per block results to.
+    ///
+    /// Defaults to `trie-recompute-<FROM>-<TO>.csv` in the current directory.
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// The result of recomputing the state root of a single block.
+#[derive(Debug)]
+struct RecomputedRoot {
+    block: BlockNumber,
+    expected: B256,
+    computed: B256,
+    /// The time it took to collect the reverts down to the block.
+    reverts: Duration,
+    /// The time it took to compute the state root.
+    root: Duration,
+}
+
+impl Command {
+    /// Execute `trie recompute` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let db = open_db_read_only(
+            &data_dir.db_path(),
+            DatabaseArguments::default().log_level(self.db.log_level),
+        )?;
+        let factory = ProviderFactory::new(db, self.chain.clone());
+
+        // the trie tables are at the merkle checkpoint
+        let tip = factory
+            .provider()?
+            .get_stage_checkpoint(StageId::MerkleExecute)?
+            .map(|checkpoint| checkpoint.block_number)
+            .unwrap_or_default();
+        let to = self.to;
+        eyre::ensure!(
+            1 <= self.from && self.from <= to && to <= tip,
+            "Block range {}..={to} is not in the range of blocks with state root (1..={tip})",
+            self.from
+        );
+
+        let parallelism = self
+            .parallelism
+            .or_else(|| std::thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(parallelism)
+            .thread_name(|i| format!("trie-recompute-{i}"))
+            .build()?;
+
+        info!(target: "reth::cli", from = self.from, to, tip, parallelism, "Recomputing state roots");
+        let started_at = Instant::now();
+        let mut results = pool.install(|| {
+            (self.from..=to)
+                .into_par_iter()
+                .map(|block| recompute_root(&factory, block, tip))
+                .collect::<eyre::Result<Vec<_>>>()
+        })?;
+        let elapsed = started_at.elapsed();
+        results.sort_unstable_by_key(|result| result.block);
+
+        let output = self
+            .output
+            .unwrap_or_else(|| PathBuf::from(format!("trie-recompute-{}-{to}.csv", self.from)));
+        let mut writer = BufWriter::new(File::create(&output)?);
+        writeln!(writer, "block,expected_root,computed_root,matches,reverts_ms,root_ms")?;
+        for result in &results {
+            writeln!(
+                writer,
+                "{},{},{},{},{:.3},{:.3}",
+                result.block,
+                result.expected,
+                result.computed,
+                result.expected == result.computed,
+                result.reverts.as_secs_f64() * 1000.0,
+                result.root.as_secs_f64() * 1000.0,
+            )?;
+        }
+        writer.flush()?;
+
+        let mismatches = results
+            .iter()
+            .filter(|result| result.expected != result.computed)
+            .inspect(|result| {
+                warn!(
+                    target: "reth::cli",
+                    block = result.block,
+                    expected = ?result.expected,
+                    computed = ?result.computed,
+                    "State root mismatch"
+                )
+            })
+            .count();
+        let total_root = results.iter().map(|result| result.root).sum::<Duration>();
+        info!(
+            target: "reth::cli",
+            blocks = results.len(),
+            mismatches,
+            ?elapsed,
+            avg_root = ?total_root / results.len().max(1) as u32,
+            path = %output.display(),
+            "Recomputed state roots"
+        );
+
+        eyre::ensure!(mismatches == 0, "{mismatches} recomputed state roots do not match");
+        Ok(())
+    }
+}
+
+/// Recomputes the state root of the block with a dedicated read transaction.
+fn recompute_root<DB: Database>(
+    factory: &ProviderFactory<DB>,
+    block: BlockNumber,
+    tip: BlockNumber,
+) -> eyre::Result<RecomputedRoot> {
+    let provider = factory.provider()?.disable_long_read_transaction_safety();
+    let expected = provider
+        .sealed_header(block)?
+        .ok_or(ProviderError::HeaderNotFound(block.into()))?
+        .state_root;
+
+    let started_at = Instant::now();
+    let view = HistoricalTrieView::from_tx(provider.tx_ref(), block, tip)?;
+    let reverts = started_at.elapsed();
+
+    let started_at = Instant::now();
+    let computed = view.state_root(provider.tx_ref())?;
+    let root = started_at.elapsed();
+
+    Ok(RecomputedRoot { block, expected, computed, reverts, root })
+}
@@ -0,0 +1,254 @@
+//! Benchmark harness for state root computation strategies.
+//!
+//! Replays a range of historical blocks from an existing reth database and computes the state root
+//! of every block with each of the selected strategies. The state at a historical block is
+//! reconstructed as an in-memory overlay of the changesets between that block and the database
+//! tip, so the database is only ever opened read-only.
+//!
+//! Every computed root is checked against the root in the block header and the latency
+//! distribution is reported per strategy, along with the hit rates of the cursor cache if the
+//! `cached` strategy is selected.
+//!
+//! Available strategies:
+//!
+//! - `incremental`: recomputes only the parts of the trie touched by the overlay and reuses the
+//!   stored intermediate nodes for everything else. This is the strategy used by the node.
+//! - `cached`: like `incremental`, but reads the database through a cursor cache that is kept
+//!   across all replayed blocks. The database state doesn't change while replaying, so the cache
+//!   stays valid and the overlay of every block is layered over it.
+//! - `full`: ignores all stored intermediate nodes and recomputes the whole trie from the hashed
+//!   state tables. Only feasible on small databases.
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+
+use clap::{Parser, ValueEnum};
+use reth_db::{
+    cursor::DbCursorRO, database::Database, mdbx::DatabaseArguments, open_db_read_only, tables,
+    transaction::DbTx,
+};
+use reth_primitives::{BlockNumber, B256};
+use reth_trie::{
+    cached_cursors::{CacheStats, CursorCache, CursorCacheConfig, EvictionPolicy},
+    hashed_cursor::HashedPostStateCursorFactory,
+    trie_cursor::noop::NoopTrieCursorFactory,
+    HashedPostState, StateRoot, StateRootError,
+};
+use std::{
+    fmt,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Replays historical blocks against state root strategies.
+#[derive(Debug, Parser)]
+#[command(name = "trie-bench")]
+struct Args {
+    /// The path to the database directory, e.g. `~/.local/share/reth/mainnet/db`.
+    #[arg(long, value_name = "PATH")]
+    db: PathBuf,
+
+    /// The first block to replay.
+    #[arg(long)]
+    from: BlockNumber,
+
+    /// The last block to replay, defaults to the database tip.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// The strategies to benchmark.
+    #[arg(long, value_delimiter = ',', default_value = "incremental")]
+    strategies: Vec<Strategy>,
+
+    /// How many times every root is computed per strategy.
+    #[arg(long, default_value_t = 1)]
+    iterations: usize,
+
+    /// The capacity of each cache of the `cached` strategy in entries, unbounded if not set.
+    #[arg(long, value_name = "ENTRIES")]
+    cache_capacity: Option<usize>,
+}
+
+/// A state root computation strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Strategy {
+    /// Recompute the parts of the trie touched by the overlay.
+    Incremental,
+    /// Recompute the parts of the trie touched by the overlay, reading through a cursor cache.
+    Cached,
+    /// Recompute the whole trie without stored intermediate nodes.
+    Full,
+}
+
+impl Strategy {
+    /// Computes the state root of the database with the given overlay applied.
+    ///
+    /// The cache is only used by the `cached` strategy.
+    fn state_root<TX: DbTx>(
+        &self,
+        tx: &TX,
+        overlay: &HashedPostState,
+        cache: &CursorCache,
+    ) -> Result<B256, StateRootError> {
+        match self {
+            Self::Incremental => overlay.state_root(tx),
+            Self::Cached => overlay
+                .state_root_calculator(tx)
+                .with_hashed_cursor_factory(cache.overlay_hashed_cursor_factory(tx, overlay))
+                .with_trie_cursor_factory(cache.trie_cursor_factory(tx))
+                .root(),
+            Self::Full => StateRoot::from_tx(tx)
+                .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, overlay))
+                .with_trie_cursor_factory(NoopTrieCursorFactory)
+                .root(),
+        }
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let db = open_db_read_only(&args.db, DatabaseArguments::default())?;
+    let tx = db.tx()?;
+
+    let tip = tx
+        .cursor_read::<tables::CanonicalHeaders>()?
+        .last()?
+        .map(|(number, _)| number)
+        .ok_or_else(|| eyre::eyre!("database is empty"))?;
+    let to = args.to.unwrap_or(tip);
+    eyre::ensure!(to <= tip, "block {to} is above the database tip {tip}");
+    eyre::ensure!(args.from <= to, "empty block range {}..={to}", args.from);
+
+    let policy = args
+        .cache_capacity
+        .map_or(EvictionPolicy::Unbounded, |capacity| EvictionPolicy::Lru { capacity });
+    let cache =
+        CursorCache::new(CursorCacheConfig { accounts: policy, storages: policy, tries: policy });
+
+    let mut samples = vec![Vec::new(); args.strategies.len()];
+    for number in args.from..=to {
+        let header = tx
+            .get::<tables::Headers>(number)?
+            .ok_or_else(|| eyre::eyre!("missing header for block {number}"))?;
+        let overlay = HashedPostState::from_revert_range(&tx, number + 1..=tip)?;
+
+        for (strategy, samples) in args.strategies.iter().zip(&mut samples) {
+            for _ in 0..args.iterations {
+                let started_at = Instant::now();
+                let root = strategy.state_root(&tx, &overlay, &cache)?;
+                samples.push(started_at.elapsed());
+                eyre::ensure!(
+                    root == header.state_root,
+                    "{strategy:?} state root mismatch at block {number}: got {root}, expected {}",
+                    header.state_root
+                );
+            }
+        }
+    }
+
+    println!("replayed blocks {}..={to} ({} iterations each)", args.from, args.iterations);
+    for (strategy, samples) in args.strategies.iter().zip(samples) {
+        println!("{strategy:?}: {}", LatencyDistribution::new(samples));
+    }
+    if args.strategies.contains(&Strategy::Cached) {
+        let stats = cache.stats();
+        println!(
+            "Cached hit rates: accounts={} storages={} account_trie={} storage_tries={} total={} \
+             entries={}",
+            HitRate(stats.accounts),
+            HitRate(stats.storages),
+            HitRate(stats.account_trie),
+            HitRate(stats.storage_tries),
+            HitRate(stats.total()),
+            cache.size(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Summary of a set of latency samples.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct LatencyDistribution {
+    samples: usize,
+    min: Duration,
+    p50: Duration,
+    p90: Duration,
+    p99: Duration,
+    max: Duration,
+    mean: Duration,
+}
+
+impl LatencyDistribution {
+    /// Computes the distribution of the given samples.
+    fn new(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default()
+        }
+        samples.sort_unstable();
+
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+        Self {
+            samples: samples.len(),
+            min: samples[0],
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: samples[samples.len() - 1],
+            mean: samples.iter().sum::<Duration>() / samples.len() as u32,
+        }
+    }
+}
+
+impl fmt::Display for LatencyDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "samples={} min={:?} p50={:?} p90={:?} p99={:?} max={:?} mean={:?}",
+            self.samples, self.min, self.p50, self.p90, self.p99, self.max, self.mean
+        )
+    }
+}
+
+/// Displays the hit rate of a cache with its number of lookups.
+struct HitRate(CacheStats);
+
+impl fmt::Display for HitRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lookups = self.0.hits + self.0.misses;
+        match self.0.hit_rate() {
+            Some(rate) => write!(f, "{:.1}% ({lookups})", rate * 100.0),
+            None => write!(f, "n/a"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_distribution() {
+        let samples = (1..=100).rev().map(Duration::from_millis).collect();
+        let distribution = LatencyDistribution::new(samples);
+        assert_eq!(distribution.samples, 100);
+        assert_eq!(distribution.min, Duration::from_millis(1));
+        assert_eq!(distribution.p50, Duration::from_millis(50));
+        assert_eq!(distribution.p90, Duration::from_millis(90));
+        assert_eq!(distribution.p99, Duration::from_millis(99));
+        assert_eq!(distribution.max, Duration::from_millis(100));
+        assert_eq!(distribution.mean, Duration::from_micros(50_500));
+
+        assert_eq!(LatencyDistribution::new(Vec::new()), LatencyDistribution::default());
+    }
+
+    #[test]
+    fn hit_rate() {
+        let stats = CacheStats { hits: 3, misses: 1, inserts: 1 };
+        assert_eq!(HitRate(stats).to_string(), "75.0% (4)");
+        assert_eq!(HitRate(CacheStats::default()).to_string(), "n/a");
+    }
+}
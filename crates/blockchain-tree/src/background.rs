@@ -0,0 +1,158 @@
+//! Writing files off the tree thread.
+
+use parking_lot::{Condvar, Mutex};
+use std::{
+    fs, io,
+    io::Write,
+    path::Path,
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+};
+
+/// Hands values to a background thread that writes them, so the tree lock is not held while files
+/// are written.
+///
+/// Only the latest submitted value is written: a value that is replaced before the thread picks
+/// it up is skipped. Dropping the writer waits for the pending value to be written.
+pub(crate) struct LatestWriter<T> {
+    shared: Arc<Shared<T>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> LatestWriter<T> {
+    /// Spawns the thread that writes the submitted values with the given function.
+    pub(crate) fn spawn(name: &str, mut write: impl FnMut(T) + Send + 'static) -> io::Result<Self> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State { pending: None, busy: false, closed: false }),
+            changed: Condvar::new(),
+        });
+        let thread = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new().name(name.to_string()).spawn(move || loop {
+                let value = {
+                    let mut state = shared.state.lock();
+                    while state.pending.is_none() && !state.closed {
+                        shared.changed.wait(&mut state);
+                    }
+                    let Some(value) = state.pending.take() else { return };
+                    state.busy = true;
+                    value
+                };
+                write(value);
+                shared.state.lock().busy = false;
+                shared.changed.notify_all();
+            })?
+        };
+        Ok(Self { shared, thread: Some(thread) })
+    }
+
+    /// Submits a value to be written, replacing the pending value if it wasn't picked up yet.
+    pub(crate) fn submit(&self, value: T) {
+        self.shared.state.lock().pending = Some(value);
+        self.shared.changed.notify_all();
+    }
+
+    /// Blocks until all submitted values are written.
+    pub(crate) fn flush(&self) {
+        let mut state = self.shared.state.lock();
+        while state.pending.is_some() || state.busy {
+            self.shared.changed.wait(&mut state);
+        }
+    }
+}
+
+impl<T> Drop for LatestWriter<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().closed = true;
+        self.shared.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for LatestWriter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.shared.state.lock();
+        f.debug_struct("LatestWriter")
+            .field("pending", &state.pending.is_some())
+            .field("busy", &state.busy)
+            .finish()
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    changed: Condvar,
+}
+
+struct State<T> {
+    /// The value that is written next.
+    pending: Option<T>,
+    /// Whether the thread is writing a value.
+    busy: bool,
+    /// Set once the writer is dropped, the thread exits after writing the pending value.
+    closed: bool,
+}
+
+/// Hands values to a background thread that writes them in order.
+///
+/// Unlike [LatestWriter], every submitted value is written. Dropping the writer waits for all
+/// submitted values to be written.
+pub(crate) struct QueueWriter<T> {
+    sender: Option<mpsc::Sender<T>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> QueueWriter<T> {
+    /// Spawns the thread that writes the submitted values with the given function.
+    pub(crate) fn spawn(name: &str, mut write: impl FnMut(T) + Send + 'static) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new().name(name.to_string()).spawn(move || {
+            for value in receiver {
+                write(value);
+            }
+        })?;
+        Ok(Self { sender: Some(sender), thread: Some(thread) })
+    }
+
+    /// Submits a value to be written after all previously submitted values.
+    pub(crate) fn submit(&self, value: T) {
+        if let Some(sender) = &self.sender {
+            // the thread only exits once the sender is dropped
+            let _ = sender.send(value);
+        }
+    }
+}
+
+impl<T> Drop for QueueWriter<T> {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for QueueWriter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueueWriter").finish_non_exhaustive()
+    }
+}
+
+/// Replaces the file with the given content.
+///
+/// The content is written and synced to a temporary file first, which is then renamed, so a
+/// crash leaves either the previous or the new file behind, never a truncated one.
+pub(crate) fn replace_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(tmp, path)
+}
@@ -4,8 +4,10 @@ use crate::{
     canonical_chain::CanonicalChain,
     chain::BlockKind,
     metrics::{MakeCanonicalAction, MakeCanonicalDurationsRecorder, TreeMetrics},
+    perf::PersistencePerfRecord,
     state::{BlockChainId, TreeState},
-    AppendableChain, BlockIndices, BlockchainTreeConfig, BundleStateData, TreeExternals,
+    AppendableChain, BlockIndices, BlockchainTreeConfig, BundleStateData, ChainJournal,
+    ChainJournalState, ReorgLog, TreeExternals,
 };
 use reth_db::{database::Database, DatabaseError};
 use reth_interfaces::{
@@ -27,11 +29,14 @@ use reth_provider::{
     BlockExecutionWriter, BlockNumReader, BlockWriter, BundleStateWithReceipts,
     CanonStateNotification, CanonStateNotificationSender, CanonStateNotifications, Chain,
     ChainSpecProvider, DisplayBlocksChain, ExecutorFactory, HeaderProvider, ProviderError,
+    ReorgEvent,
 };
 use reth_stages::{MetricEvent, MetricEventsSender};
+use reth_trie::hashed_cursor::storages_of;
 use std::{
     collections::{BTreeMap, HashSet},
     sync::Arc,
+    time::Instant,
 };
 use tracing::{debug, error, info, instrument, trace, warn};
 
@@ -75,6 +80,10 @@ pub struct BlockchainTree<DB: Database, EF: ExecutorFactory> {
     /// Metrics for sync stages.
     sync_metrics_tx: Option<MetricEventsSender>,
     prune_modes: Option<PruneModes>,
+    /// History of the most recent reorgs.
+    reorg_log: ReorgLog,
+    /// Journal the chains of the tree are persisted to, if any.
+    chain_journal: Option<ChainJournal>,
 }
 
 impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
@@ -118,6 +127,8 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
             metrics: Default::default(),
             sync_metrics_tx: None,
             prune_modes,
+            reorg_log: Default::default(),
+            chain_journal: None,
         })
     }
 
@@ -127,6 +138,101 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
         self
     }
 
+    /// Set the log that reorgs are recorded to.
+    pub fn with_reorg_log(mut self, reorg_log: ReorgLog) -> Self {
+        self.reorg_log = reorg_log;
+        self
+    }
+
+    /// Set the journal the chains of the tree are persisted to, restoring the chains that were
+    /// recorded before the restart.
+    pub fn with_chain_journal(mut self, mut chain_journal: ChainJournal) -> Self {
+        if let Some(state) = chain_journal.take_loaded() {
+            let restored = self.restore_chains(state);
+            chain_journal.on_restored(restored);
+        }
+        self.chain_journal = Some(chain_journal);
+        self
+    }
+
+    /// Inserts the chains that were recorded in the chain journal and returns the number of
+    /// restored chains.
+    ///
+    /// Nothing is restored if a recorded canonical block is no longer canonical. A chain is only
+    /// restored if it forks off a canonical block that is not finalized or off another restored
+    /// chain, and none of its blocks is known yet.
+    fn restore_chains(&mut self, state: ChainJournalState) -> usize {
+        let tip = self.block_indices().canonical_tip();
+        let diverged = state.canonical.iter().any(|block| {
+            block.number > tip.number ||
+                self.block_indices()
+                    .canonical_hash(&block.number)
+                    .is_some_and(|hash| hash != block.hash)
+        });
+        if diverged {
+            warn!(target: "blockchain_tree", ?tip, "Discarding chain journal that does not match the canonical chain");
+            return 0
+        }
+
+        let last_finalized_block = self.block_indices().last_finalized_block();
+        let mut pending = state.chains;
+        let mut restored = 0;
+        loop {
+            // chains are restored once the chain they fork off is known
+            let (ready, rest): (Vec<_>, Vec<_>) = pending.into_iter().partition(|chain| {
+                let fork = chain.fork_block();
+                fork.number >= last_finalized_block &&
+                    (self.block_indices().canonical_hash(&fork.number) == Some(fork.hash) ||
+                        self.block_indices().get_blocks_chain_id(&fork.hash).is_some())
+            });
+            if ready.is_empty() {
+                break
+            }
+            pending = rest;
+
+            for chain in ready {
+                let known = chain.blocks_iter().any(|block| {
+                    self.block_indices().canonical_hash(&block.number) == Some(block.hash()) ||
+                        self.block_indices().get_blocks_chain_id(&block.hash()).is_some()
+                });
+                if known {
+                    continue
+                }
+                debug!(target: "blockchain_tree", fork = ?chain.fork_block(), tip = ?chain.tip().num_hash(), "Restoring chain from the chain journal");
+                self.insert_chain(AppendableChain::new(chain));
+                restored += 1;
+            }
+        }
+
+        if restored > 0 || !pending.is_empty() {
+            info!(target: "blockchain_tree", restored, discarded = pending.len(), "Restored chains from the chain journal");
+        }
+        restored
+    }
+
+    /// Records the current chains of the tree to the chain journal, if any.
+    ///
+    /// The canonical blocks above the last finalized block are recorded as well, since these are
+    /// the blocks the chains can fork off.
+    pub(crate) fn record_chain_journal(&mut self) {
+        let Some(chain_journal) = self.chain_journal.as_mut() else { return };
+        let last_finalized_block = self.state.block_indices.last_finalized_block();
+        let canonical = self
+            .state
+            .block_indices
+            .canonical_chain()
+            .inner()
+            .range(last_finalized_block..)
+            .map(|(number, hash)| BlockNumHash::new(*number, *hash))
+            .collect::<Vec<_>>();
+        chain_journal.record(&canonical, self.state.chains.values().map(|chain| &**chain));
+    }
+
+    /// Returns the most recent reorgs of the canonical chain, oldest first.
+    pub fn reorg_history(&self) -> Vec<ReorgEvent> {
+        self.reorg_log.events().cloned().collect()
+    }
+
     /// Check if the block is known to blockchain tree or database and return its status.
     ///
     /// Function will check:
@@ -679,8 +785,12 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
         &mut self,
         block: SealedBlock,
     ) -> Result<InsertPayloadOk, InsertBlockError> {
+        let started_at = Instant::now();
         match block.try_seal_with_senders() {
-            Ok(block) => self.insert_block(block, BlockValidationKind::Exhaustive),
+            Ok(block) => {
+                self.externals.perf.record_senders_recovery(block.hash, started_at.elapsed());
+                self.insert_block(block, BlockValidationKind::Exhaustive)
+            }
             Err(block) => Err(InsertBlockError::sender_recovery_error(block)),
         }
     }
@@ -1113,6 +1223,11 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
                     new: Arc::new(new_canon_chain.clone()),
                 };
                 let reorg_depth = old_canon_chain.len();
+                self.reorg_log.record(
+                    &old_canon_chain,
+                    &new_canon_chain,
+                    durations_recorder.elapsed(),
+                );
 
                 // insert old canon chain
                 self.insert_unwound_chain(AppendableChain::new(old_canon_chain));
@@ -1200,6 +1315,19 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
         };
         recorder.record_relative(MakeCanonicalAction::RetrieveStateTrieUpdates);
 
+        let (first, last) = (blocks.first().number, blocks.tip().number);
+        let (parent, tip) = (blocks.first().parent_num_hash(), blocks.tip().num_hash());
+        let pinned_updates = self
+            .externals
+            .pinned_account_nodes
+            .as_ref()
+            .map(|pinned| (pinned, pinned.pinned_updates(&trie_updates)));
+        let filter_storages = self
+            .externals
+            .storage_filter
+            .as_ref()
+            .map(|filter| (filter, storages_of(&hashed_state)));
+        let started_at = Instant::now();
         let provider_rw = self.externals.provider_factory.provider_rw()?;
         provider_rw
             .append_blocks_with_state(
@@ -1212,7 +1340,18 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
             .map_err(|e| BlockExecutionError::CanonicalCommit { inner: e.to_string() })?;
 
         provider_rw.commit()?;
+        if let Some((pinned, updates)) = pinned_updates {
+            pinned.apply_updates(parent, tip, &updates);
+        }
+        if let Some((filter, storages)) = filter_storages {
+            filter.extend(parent, tip, storages);
+        }
         recorder.record_relative(MakeCanonicalAction::CommitCanonicalChainToDatabase);
+        self.externals.perf.record_persistence(&PersistencePerfRecord {
+            first,
+            last,
+            duration: started_at.elapsed(),
+        });
 
         Ok(())
     }
@@ -4,8 +4,8 @@
 //! blocks, as well as a list of the blocks the chain is composed of.
 
 use super::externals::TreeExternals;
-use crate::BundleStateDataRef;
-use reth_db::database::Database;
+use crate::{perf::BlockPerfRecord, BundleStateDataRef};
+use reth_db::{database::Database, transaction::DbTx, DatabaseError};
 use reth_interfaces::{
     blockchain_tree::{
         error::{BlockchainTreeError, InsertBlockError},
@@ -15,17 +15,31 @@ use reth_interfaces::{
     RethResult,
 };
 use reth_primitives::{
-    BlockHash, BlockNumber, ForkBlock, GotExpected, SealedBlockWithSenders, SealedHeader, U256,
+    BlockHash, BlockNumHash, BlockNumber, ForkBlock, GotExpected, SealedBlockWithSenders,
+    SealedHeader, B256, U256,
 };
 use reth_provider::{
     providers::BundleStateProvider, BundleStateDataProvider, BundleStateWithReceipts, Chain,
-    ExecutorFactory, StateRootProvider,
+    ExecutorFactory,
+};
+use reth_trie::{
+    hashed_cursor::{FilteredHashedCursorFactory, HashedCursorFactory, OverlayHashedCursorFactory},
+    keccak::KeyHasher,
+    node_provider::{
+        DatabaseTrieNodeProvider, FallbackTrieNodeProvider, TrieNodeProviderCursorFactory,
+    },
+    slo::RootComputation,
+    trie_cursor::pinned::PinnedTrieCursorFactory,
+    updates::TrieUpdates,
+    StateRoot,
 };
-use reth_trie::updates::TrieUpdates;
 use std::{
     collections::BTreeMap,
     ops::{Deref, DerefMut},
+    sync::Arc,
+    time::Instant,
 };
+use tracing::warn;
 
 /// A chain if the blockchain tree, that has functionality to execute blocks and append them to the
 /// it self.
@@ -219,16 +233,88 @@ impl AppendableChain {
 
         let provider = BundleStateProvider::new(state_provider, bundle_state_data_provider);
 
+        // the state root can only be validated if the block extends the canonical chain
+        let validate_state_root =
+            block_kind.extends_canonical_head() && block_validation_kind.is_exhaustive();
+
         let mut executor = externals.executor_factory.with_state(&provider);
+        // hash the changed keys for the state root while the block is executed
+        let key_hasher = if validate_state_root {
+            KeyHasher::spawn()
+                .map_err(|err| warn!(target: "blockchain_tree", %err, "Failed to spawn key hasher"))
+                .ok()
+        } else {
+            None
+        };
+        if let Some(key_hasher) = &key_hasher {
+            executor.set_key_sender(key_hasher.sender());
+        }
+        let block_hash = block.hash;
         let block = block.unseal();
+        let started_at = Instant::now();
         executor.execute_and_verify_receipt(&block, U256::MAX)?;
         let bundle_state = executor.take_output_state();
+        let mut perf = BlockPerfRecord {
+            number: block.number,
+            hash: block_hash,
+            transactions: block.body.len(),
+            gas_used: block.gas_used,
+            senders_recovery: externals.perf.take_senders_recovery(&block_hash),
+            execution: started_at.elapsed(),
+            state_root: None,
+            hashed_state: None,
+            key_hashing: None,
+        };
 
         // check state root if the block extends the canonical chain __and__ if state root
         // validation was requested.
-        if block_kind.extends_canonical_head() && block_validation_kind.is_exhaustive() {
+        let result = if validate_state_root {
             // check state root
-            let (state_root, trie_updates) = provider.state_root_with_updates(&bundle_state)?;
+            let started_at = Instant::now();
+            let root_timer = externals
+                .root_slo
+                .as_ref()
+                .map(|root_slo| root_slo.start(RootComputation::Validation, block.number));
+            let prehashed = key_hasher.map(KeyHasher::finish).unwrap_or_default();
+            let hashed_state = bundle_state.hash_state_prehashed(&prehashed);
+            perf.hashed_state = Some(started_at.elapsed());
+            perf.key_hashing = Some(prehashed.hashing_duration());
+
+            // the block extends the canonical head, so the database holds the parent state
+            let db_provider = externals.provider_factory.provider()?;
+            let tx = db_provider.tx_ref();
+            let calculator = hashed_state.state_root_calculator(tx);
+            let storage_filter = externals
+                .storage_filter
+                .as_ref()
+                .and_then(|filter| filter.get_or_rebuild(parent_block.num_hash()));
+            let (state_root, trie_updates) = match storage_filter {
+                Some(filter) => root_with_trie_sources(
+                    externals,
+                    tx,
+                    parent_block,
+                    calculator.with_hashed_cursor_factory(OverlayHashedCursorFactory::new(
+                        FilteredHashedCursorFactory::new(tx, filter),
+                        &hashed_state,
+                    )),
+                )?,
+                None => root_with_trie_sources(externals, tx, parent_block, calculator)?,
+            };
+            let state_root_duration = started_at.elapsed();
+            perf.state_root = Some(state_root_duration);
+            if let Some(root_timer) = root_timer {
+                root_timer.finish();
+            }
+            if let Some(root_cross_check) =
+                externals.root_cross_check.as_ref().filter(|check| check.should_check(block.number))
+            {
+                root_cross_check.check(
+                    tx,
+                    BlockNumHash::new(block.number, block_hash),
+                    &hashed_state,
+                    state_root,
+                );
+            }
             if block.state_root != state_root {
                 return Err(ConsensusError::BodyStateRootDiff(
                     GotExpected { got: state_root, expected: block.state_root }.into(),
@@ -236,10 +322,13 @@ impl AppendableChain {
                 .into())
             }
 
-            Ok((bundle_state, Some(trie_updates)))
+            (bundle_state, Some(trie_updates))
         } else {
-            Ok((bundle_state, None))
-        }
+            (bundle_state, None)
+        };
+        externals.perf.record_block(&perf);
+
+        Ok(result)
     }
 
     /// Validate and execute the given sidechain block, skipping state root validation.
@@ -317,6 +406,40 @@ impl AppendableChain {
     }
 }
 
+/// Computes the state root of a block extending the canonical head, reading the trie nodes from
+/// the sources configured in the externals.
+fn root_with_trie_sources<DB, EF, TX, H>(
+    externals: &TreeExternals<DB, EF>,
+    tx: &TX,
+    parent_block: &SealedHeader,
+    calculator: StateRoot<&TX, H>,
+) -> Result<(B256, TrieUpdates), DatabaseError>
+where
+    TX: DbTx,
+    H: HashedCursorFactory + Clone,
+{
+    match (&externals.pinned_account_nodes, &externals.remote_trie_nodes) {
+        (Some(pinned), _) => {
+            pinned.ensure_tip(tx, parent_block.num_hash())?;
+            calculator
+                .with_trie_cursor_factory(PinnedTrieCursorFactory::new(tx, Arc::clone(pinned)))
+                .root_with_updates()
+        }
+        (None, Some(remote)) => {
+            // the nodes of the remote must be of the same state as the local ones
+            remote.ensure_state_root(parent_block.state_root)?;
+            let nodes = FallbackTrieNodeProvider::new()
+                .with_tier("database", DatabaseTrieNodeProvider::new(tx))
+                .with_tier("remote", Arc::clone(remote));
+            calculator
+                .with_trie_cursor_factory(TrieNodeProviderCursorFactory::new(&nodes))
+                .root_with_updates()
+        }
+        (None, None) => calculator.root_with_updates(),
+    }
+    .map_err(Into::into)
+}
+
 /// Represents what kind of block is being executed and validated.
 ///
 /// This is required because the state root check can only be performed if the targeted block can be
@@ -0,0 +1,564 @@
+//! Persistent journal of the chains that are kept in memory by the tree.
+//!
+//! Canonical blocks are written to the database when they are made canonical, so on restart only
+//! the executed chains that are not canonical are lost: pending blocks that were not made
+//! canonical yet, competing side chains and chains that were reverted by a reorg. The journal
+//! keeps their blocks, execution outcomes and trie updates, so the tree can restore them without
+//! re-executing the blocks.
+
+use crate::background::{replace_file, LatestWriter};
+use alloy_rlp::{Decodable, Encodable};
+use reth_db::table::{Compress, Decompress};
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_primitives::{
+    revm::compat::into_reth_acc, Account, Address, BlockNumHash, Bytecode, Receipt, Receipts,
+    SealedBlock, SealedBlockWithSenders, StorageEntry, B256, U256,
+};
+use reth_provider::{
+    bundle_state::{BundleStateInit, RevertsInit},
+    BundleStateWithReceipts, Chain,
+};
+use reth_trie::journal::{decode_trie_updates, encode_trie_updates};
+use revm::db::states::{reverts::AccountInfoRevert, RevertToSlot};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+/// The version of the persisted journal format.
+const CHAIN_JOURNAL_VERSION: u8 = 1;
+
+/// The chains of the tree at the time the journal was persisted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainJournalState {
+    /// The canonical blocks the chains can fork from, in ascending order.
+    ///
+    /// Used to detect that the canonical chain of the database no longer matches the journal.
+    pub canonical: Vec<BlockNumHash>,
+    /// The executed chains that are not part of the canonical chain.
+    pub chains: Vec<Chain>,
+}
+
+impl ChainJournalState {
+    /// Encodes the state of the journal.
+    pub fn encode(&self) -> Vec<u8> {
+        encode_state(&self.canonical, self.chains.iter())
+    }
+
+    /// Decodes a state that was encoded with [ChainJournalState::encode].
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let mut reader = Reader(buf);
+        if reader.u8()? != CHAIN_JOURNAL_VERSION {
+            return None
+        }
+
+        let mut canonical = Vec::new();
+        for _ in 0..reader.u32()? {
+            canonical.push(BlockNumHash { number: reader.u64()?, hash: reader.b256()? });
+        }
+        let mut chains = Vec::new();
+        for _ in 0..reader.u32()? {
+            chains.push(decode_chain(&mut reader)?);
+        }
+        reader.0.is_empty().then_some(Self { canonical, chains })
+    }
+}
+
+/// Journal of the chains of the tree that is rewritten whenever the chains change.
+///
+/// Persisting is skipped if neither the canonical chain nor the tips of the chains changed since
+/// the journal was last written. The chains are encoded and written on a background thread, so
+/// recording only clones them while the tree is locked.
+#[derive(Debug)]
+pub struct ChainJournal {
+    /// The file the journal is persisted to.
+    path: PathBuf,
+    /// The state read from the journal file that was not restored yet.
+    loaded: Option<ChainJournalState>,
+    /// The canonical tip and the chain tips at the time the journal was last recorded.
+    recorded: Option<Vec<BlockNumHash>>,
+    /// Set by the writer if persisting failed, so the next record is written again.
+    persist_failed: Arc<AtomicBool>,
+    /// Persisting is skipped while this is set.
+    persist_paused: Option<watch::Receiver<bool>>,
+    /// Encodes and writes the recorded chains.
+    writer: LatestWriter<ChainJournalState>,
+    metrics: ChainJournalMetrics,
+}
+
+impl ChainJournal {
+    /// Opens the journal that is persisted to the given file, loading the chains that were
+    /// recorded before the restart.
+    ///
+    /// An unreadable journal file is discarded.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let loaded = match fs::read(&path) {
+            Ok(buf) => {
+                let state = ChainJournalState::decode(&buf);
+                if state.is_none() {
+                    warn!(target: "blockchain_tree::chain_journal", path = %path.display(), "Discarding unreadable chain journal")
+                }
+                state
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+
+        let metrics = ChainJournalMetrics::default();
+        let persist_failed = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let (path, metrics, persist_failed) =
+                (path.clone(), metrics.clone(), Arc::clone(&persist_failed));
+            LatestWriter::spawn("chain-journal", move |state: ChainJournalState| {
+                let buf = state.encode();
+                match replace_file(&path, &buf) {
+                    Ok(()) => {
+                        metrics.persisted.increment(1);
+                        metrics.size.set(buf.len() as f64);
+                        metrics.chains.set(state.chains.len() as f64);
+                    }
+                    Err(err) => {
+                        warn!(target: "blockchain_tree::chain_journal", %err, "Failed to persist chain journal");
+                        metrics.persist_errors.increment(1);
+                        persist_failed.store(true, Ordering::Relaxed);
+                    }
+                }
+            })?
+        };
+
+        Ok(Self {
+            path,
+            loaded,
+            recorded: None,
+            persist_failed,
+            persist_paused: None,
+            writer,
+            metrics,
+        })
+    }
+
+    /// Skips persisting the journal while the given signal is set, e.g. while disk space is low.
+    pub fn with_persist_paused(mut self, paused: watch::Receiver<bool>) -> Self {
+        self.persist_paused = Some(paused);
+        self
+    }
+
+    /// Returns the path of the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Takes the state that was loaded from the journal file, if any.
+    pub fn take_loaded(&mut self) -> Option<ChainJournalState> {
+        self.loaded.take()
+    }
+
+    /// Records the current chains of the tree, rewriting the journal file in the background if
+    /// they changed.
+    ///
+    /// `canonical` are the canonical blocks the chains can fork from, in ascending order.
+    pub fn record<'a>(
+        &mut self,
+        canonical: &[BlockNumHash],
+        chains: impl IntoIterator<Item = &'a Chain>,
+    ) {
+        let mut chains = chains.into_iter().collect::<Vec<_>>();
+        chains.sort_unstable_by_key(|chain| chain.first().num_hash());
+
+        let mut fingerprint = canonical.last().copied().into_iter().collect::<Vec<_>>();
+        fingerprint.extend(chains.iter().map(|chain| chain.tip().num_hash()));
+        if self.recorded.as_ref() == Some(&fingerprint) &&
+            !self.persist_failed.load(Ordering::Relaxed)
+        {
+            return
+        }
+
+        if self.persist_paused.as_ref().is_some_and(|paused| *paused.borrow()) {
+            debug!(target: "blockchain_tree::chain_journal", "Persisting chain journal is paused");
+            return
+        }
+
+        self.recorded = Some(fingerprint);
+        self.persist_failed.store(false, Ordering::Relaxed);
+        self.writer.submit(ChainJournalState {
+            canonical: canonical.to_vec(),
+            chains: chains.into_iter().cloned().collect(),
+        });
+    }
+
+    /// Blocks until the last recorded chains are written to the journal file.
+    pub fn flush(&self) {
+        self.writer.flush()
+    }
+
+    /// Records the number of chains that were restored from the journal.
+    pub(crate) fn on_restored(&self, restored: usize) {
+        self.metrics.restored_chains.increment(restored as u64);
+    }
+}
+
+/// Metrics of the chain journal.
+#[derive(Clone, Metrics)]
+#[metrics(scope = "blockchain_tree.chain_journal")]
+struct ChainJournalMetrics {
+    /// The number of times the journal was persisted
+    persisted: Counter,
+    /// The number of times the journal failed to be persisted
+    persist_errors: Counter,
+    /// The size of the last persisted journal in bytes
+    size: Gauge,
+    /// The number of chains in the last persisted journal
+    chains: Gauge,
+    /// The number of chains that were restored from the journal
+    restored_chains: Counter,
+}
+
+fn encode_state<'a>(
+    canonical: &[BlockNumHash],
+    chains: impl ExactSizeIterator<Item = &'a Chain>,
+) -> Vec<u8> {
+    let mut buf = vec![CHAIN_JOURNAL_VERSION];
+    put_u32(&mut buf, canonical.len());
+    for block in canonical {
+        buf.extend_from_slice(&block.number.to_le_bytes());
+        buf.extend_from_slice(block.hash.as_slice());
+    }
+    put_u32(&mut buf, chains.len());
+    for chain in chains {
+        encode_chain(chain, &mut buf);
+    }
+    buf
+}
+
+/// Encodes the blocks, the execution outcome and the trie updates of the chain.
+///
+/// Maps are encoded in sorted order, so the encoding of a chain is deterministic.
+fn encode_chain(chain: &Chain, buf: &mut Vec<u8>) {
+    put_u32(buf, chain.len());
+    for block in chain.blocks_iter() {
+        let mut rlp = Vec::new();
+        block.block.encode(&mut rlp);
+        put_bytes(buf, &rlp);
+        put_u32(buf, block.senders.len());
+        for sender in &block.senders {
+            buf.extend_from_slice(sender.as_slice());
+        }
+    }
+
+    let state = chain.state();
+    buf.extend_from_slice(&state.first_block().to_le_bytes());
+
+    let bundle = state.state();
+    let mut accounts = bundle.state.iter().collect::<Vec<_>>();
+    accounts.sort_unstable_by_key(|(address, _)| **address);
+    put_u32(buf, accounts.len());
+    for (address, account) in accounts {
+        buf.extend_from_slice(address.as_slice());
+        put_account(buf, account.original_info.clone().map(into_reth_acc));
+        put_account(buf, account.info.clone().map(into_reth_acc));
+        let mut storage = account.storage.iter().collect::<Vec<_>>();
+        storage.sort_unstable_by_key(|(key, _)| **key);
+        put_u32(buf, storage.len());
+        for (key, slot) in storage {
+            put_u256(buf, *key);
+            put_u256(buf, slot.previous_or_original_value);
+            put_u256(buf, slot.present_value);
+        }
+    }
+
+    put_u32(buf, bundle.reverts.len());
+    for block_reverts in bundle.reverts.iter() {
+        let mut block_reverts = block_reverts.iter().collect::<Vec<_>>();
+        block_reverts.sort_unstable_by_key(|(address, _)| *address);
+        put_u32(buf, block_reverts.len());
+        for (address, revert) in block_reverts {
+            buf.extend_from_slice(address.as_slice());
+            match &revert.account {
+                AccountInfoRevert::DoNothing => buf.push(0),
+                AccountInfoRevert::DeleteIt => buf.push(1),
+                AccountInfoRevert::RevertTo(info) => {
+                    buf.push(2);
+                    put_account(buf, Some(into_reth_acc(info.clone())));
+                }
+            }
+            let mut storage = revert.storage.iter().collect::<Vec<_>>();
+            storage.sort_unstable_by_key(|(key, _)| **key);
+            put_u32(buf, storage.len());
+            for (key, slot) in storage {
+                put_u256(buf, *key);
+                // destroyed slots revert to zero, like the storage reverts read from the database
+                let value = match slot {
+                    RevertToSlot::Some(value) => *value,
+                    RevertToSlot::Destroyed => U256::ZERO,
+                };
+                put_u256(buf, value);
+            }
+        }
+    }
+
+    let mut contracts = bundle.contracts.iter().collect::<Vec<_>>();
+    contracts.sort_unstable_by_key(|(hash, _)| **hash);
+    put_u32(buf, contracts.len());
+    for (hash, bytecode) in contracts {
+        buf.extend_from_slice(hash.as_slice());
+        put_bytes(buf, Bytecode(bytecode.clone()).compress().as_ref());
+    }
+
+    put_u32(buf, state.receipts().len());
+    for block_receipts in state.receipts().iter() {
+        put_u32(buf, block_receipts.len());
+        for receipt in block_receipts {
+            match receipt {
+                Some(receipt) => {
+                    buf.push(1);
+                    put_bytes(buf, receipt.clone().compress().as_ref());
+                }
+                None => buf.push(0),
+            }
+        }
+    }
+
+    match chain.trie_updates() {
+        Some(updates) => {
+            buf.push(1);
+            encode_trie_updates(updates, buf);
+        }
+        None => buf.push(0),
+    }
+}
+
+fn decode_chain(reader: &mut Reader<'_>) -> Option<Chain> {
+    let mut blocks = Vec::new();
+    for _ in 0..reader.u32()? {
+        let block = SealedBlock::decode(&mut reader.bytes_with_len()?).ok()?;
+        let mut senders = Vec::new();
+        for _ in 0..reader.u32()? {
+            senders.push(Address::from_slice(reader.bytes(20)?));
+        }
+        blocks.push(SealedBlockWithSenders::new(block, senders)?);
+    }
+
+    let first_block = reader.u64()?;
+
+    let mut state: BundleStateInit = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let address = Address::from_slice(reader.bytes(20)?);
+        let original = reader.account()?;
+        let present = reader.account()?;
+        let mut storage = HashMap::new();
+        for _ in 0..reader.u32()? {
+            storage.insert(reader.b256()?, (reader.u256()?, reader.u256()?));
+        }
+        state.insert(address, (original, present, storage));
+    }
+
+    // every block has an entry, so blocks without changes keep their place in the reverts
+    let mut reverts: RevertsInit = HashMap::new();
+    for number in first_block..first_block + reader.u32()? as u64 {
+        let block_reverts = reverts.entry(number).or_default();
+        for _ in 0..reader.u32()? {
+            let address = Address::from_slice(reader.bytes(20)?);
+            let account = match reader.u8()? {
+                0 => None,
+                1 => Some(None),
+                2 => Some(Some(reader.account()??)),
+                _ => return None,
+            };
+            let mut storage = Vec::new();
+            for _ in 0..reader.u32()? {
+                storage.push(StorageEntry { key: reader.b256()?, value: reader.u256()? });
+            }
+            block_reverts.insert(address, (account, storage));
+        }
+    }
+
+    let mut contracts = Vec::new();
+    for _ in 0..reader.u32()? {
+        let hash = reader.b256()?;
+        contracts.push((hash, Bytecode::decompress(reader.bytes_with_len()?).ok()?));
+    }
+
+    let mut receipts = Vec::new();
+    for _ in 0..reader.u32()? {
+        let mut block_receipts = Vec::new();
+        for _ in 0..reader.u32()? {
+            let receipt = match reader.u8()? {
+                0 => None,
+                1 => Some(Receipt::decompress(reader.bytes_with_len()?).ok()?),
+                _ => return None,
+            };
+            block_receipts.push(receipt);
+        }
+        receipts.push(block_receipts);
+    }
+
+    let trie_updates = match reader.u8()? {
+        0 => None,
+        1 => Some(decode_trie_updates(&mut reader.0)?),
+        _ => return None,
+    };
+
+    let state = BundleStateWithReceipts::new_init(
+        state,
+        reverts,
+        contracts,
+        Receipts::from_vec(receipts),
+        first_block,
+    );
+    (!blocks.is_empty()).then(|| Chain::new(blocks, state, trie_updates))
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: usize) {
+    buf.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    put_u32(buf, bytes.len());
+    buf.extend_from_slice(bytes);
+}
+
+fn put_u256(buf: &mut Vec<u8>, value: U256) {
+    buf.extend_from_slice(&value.to_be_bytes::<32>());
+}
+
+fn put_account(buf: &mut Vec<u8>, account: Option<Account>) {
+    match account {
+        Some(account) => {
+            buf.push(1);
+            put_bytes(buf, account.compress().as_ref());
+        }
+        None => buf.push(0),
+    }
+}
+
+/// A cursor over an encoded journal.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn bytes_with_len(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    fn b256(&mut self) -> Option<B256> {
+        Some(B256::from_slice(self.bytes(32)?))
+    }
+
+    fn u256(&mut self) -> Option<U256> {
+        Some(U256::from_be_slice(self.bytes(32)?))
+    }
+
+    fn account(&mut self) -> Option<Option<Account>> {
+        match self.u8()? {
+            0 => Some(None),
+            1 => Some(Some(Account::decompress(self.bytes_with_len()?).ok()?)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::test_utils::tempdir_path;
+    use reth_provider::test_utils::blocks::BlockChainTestData;
+
+    fn test_chain() -> Chain {
+        let data = BlockChainTestData::default();
+        let mut blocks = data.blocks.into_iter();
+        let (block1, state1) = blocks.next().unwrap();
+        let mut chain = Chain::from_block(block1, state1, None);
+        for (block, state) in blocks {
+            chain.append_block(block, state, None);
+        }
+        chain
+    }
+
+    #[test]
+    fn encode_decode_chains() {
+        let chain = test_chain();
+        let state =
+            ChainJournalState { canonical: vec![chain.fork_block()], chains: vec![chain.clone()] };
+
+        let encoded = state.encode();
+        let decoded = ChainJournalState::decode(&encoded).unwrap();
+        assert_eq!(decoded.canonical, state.canonical);
+        assert_eq!(decoded.chains.len(), 1);
+
+        let restored = &decoded.chains[0];
+        assert_eq!(restored.blocks(), chain.blocks());
+        assert_eq!(restored.state().receipts(), chain.state().receipts());
+        assert_eq!(restored.state().first_block(), chain.state().first_block());
+        for (address, _) in chain.state().accounts_iter() {
+            assert_eq!(restored.state().account(&address), chain.state().account(&address));
+        }
+        assert_eq!(restored.state().state().reverts.len(), chain.state().state().reverts.len());
+        // the encoding does not depend on the iteration order of the maps
+        assert_eq!(decoded.encode(), encoded);
+
+        assert!(ChainJournalState::decode(&encoded[..encoded.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn persists_when_chains_change() {
+        let chain = test_chain();
+        let path = tempdir_path().join("chains.journal");
+        let mut journal = ChainJournal::open(&path).unwrap();
+        assert!(journal.take_loaded().is_none());
+
+        let canonical = vec![chain.fork_block()];
+        journal.record(&canonical, [&chain]);
+        journal.flush();
+        let written = fs::read(&path).unwrap();
+
+        // unchanged chains are not rewritten
+        fs::remove_file(&path).unwrap();
+        journal.record(&canonical, [&chain]);
+        journal.flush();
+        assert!(!path.exists());
+
+        journal.record(&canonical, Vec::<&Chain>::new());
+        journal.flush();
+        assert!(!path.with_extension("tmp").exists());
+        let mut reopened = ChainJournal::open(&path).unwrap();
+        assert_eq!(reopened.take_loaded().map(|state| state.chains.len()), Some(0));
+
+        fs::write(&path, written).unwrap();
+        let mut reopened = ChainJournal::open(&path).unwrap();
+        let loaded = reopened.take_loaded().unwrap();
+        assert_eq!(loaded.canonical, canonical);
+        assert_eq!(loaded.chains[0].blocks(), chain.blocks());
+    }
+}
@@ -1,9 +1,14 @@
 //! Blockchain tree externals.
 
+use crate::{perf::BlockPerfRecorder, root_check::StateRootCrossCheck};
 use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
 use reth_interfaces::{consensus::Consensus, RethResult};
 use reth_primitives::{BlockHash, BlockNumber};
 use reth_provider::ProviderFactory;
+use reth_trie::{
+    hashed_cursor::SharedStorageFilter, node_provider::StateRootTrieNodeProvider,
+    slo::RootSloTracker, trie_cursor::pinned::PinnedAccountNodes,
+};
 use std::{collections::BTreeMap, sync::Arc};
 
 /// A container for external components.
@@ -15,6 +20,12 @@ use std::{collections::BTreeMap, sync::Arc};
 /// - A handle to the consensus engine
 /// - The executor factory to execute blocks with
 /// - The chain spec
+/// - The recorder for per-block performance telemetry
+/// - The optional state root cross-check
+/// - The optional tracker of state root latency objectives
+/// - The optional pinned account trie nodes
+/// - The optional remote provider of trie nodes that are missing locally
+/// - The optional filter over the hashed addresses with storage
 #[derive(Debug)]
 pub struct TreeExternals<DB, EF> {
     /// The provider factory, used to commit the canonical chain, or unwind it.
@@ -23,6 +34,19 @@ pub struct TreeExternals<DB, EF> {
     pub(crate) consensus: Arc<dyn Consensus>,
     /// The executor factory to execute blocks with.
     pub(crate) executor_factory: EF,
+    /// The recorder for per-block performance telemetry.
+    pub(crate) perf: BlockPerfRecorder,
+    /// The cross-check of incrementally computed state roots, if enabled.
+    pub(crate) root_cross_check: Option<StateRootCrossCheck>,
+    /// The tracker of state root latency objectives, if enabled.
+    pub(crate) root_slo: Option<Arc<RootSloTracker>>,
+    /// The account trie nodes pinned in memory for state root validation, if any.
+    pub(crate) pinned_account_nodes: Option<Arc<PinnedAccountNodes>>,
+    /// The provider of trie nodes that are missing locally, asked during state root validation.
+    pub(crate) remote_trie_nodes: Option<Arc<dyn StateRootTrieNodeProvider>>,
+    /// The filter skipping the storage reads of accounts without storage during state root
+    /// validation, if enabled.
+    pub(crate) storage_filter: Option<SharedStorageFilter>,
 }
 
 impl<DB, EF> TreeExternals<DB, EF> {
@@ -32,7 +56,55 @@ impl<DB, EF> TreeExternals<DB, EF> {
         consensus: Arc<dyn Consensus>,
         executor_factory: EF,
     ) -> Self {
-        Self { provider_factory, consensus, executor_factory }
+        Self {
+            provider_factory,
+            consensus,
+            executor_factory,
+            perf: BlockPerfRecorder::default(),
+            root_cross_check: None,
+            root_slo: None,
+            pinned_account_nodes: None,
+            remote_trie_nodes: None,
+            storage_filter: None,
+        }
+    }
+
+    /// Sets the recorder for per-block performance telemetry.
+    pub fn with_perf_recorder(mut self, perf: BlockPerfRecorder) -> Self {
+        self.perf = perf;
+        self
+    }
+
+    /// Enables the cross-check of incrementally computed state roots.
+    pub fn with_state_root_cross_check(mut self, root_cross_check: StateRootCrossCheck) -> Self {
+        self.root_cross_check = Some(root_cross_check);
+        self
+    }
+
+    /// Sets the tracker the latency of state root computations is recorded to.
+    pub fn with_root_slo(mut self, root_slo: Arc<RootSloTracker>) -> Self {
+        self.root_slo = Some(root_slo);
+        self
+    }
+
+    /// Sets the account trie nodes that are kept in memory for state root validation.
+    pub fn with_pinned_account_nodes(mut self, pinned: Arc<PinnedAccountNodes>) -> Self {
+        self.pinned_account_nodes = Some(pinned);
+        self
+    }
+
+    /// Sets the provider that is asked for the trie nodes missing from the database during state
+    /// root validation.
+    pub fn with_remote_trie_nodes(mut self, nodes: Arc<dyn StateRootTrieNodeProvider>) -> Self {
+        self.remote_trie_nodes = Some(nodes);
+        self
+    }
+
+    /// Sets the filter over the hashed addresses with storage, which is consulted during state
+    /// root validation and extended with the blocks committed to the database.
+    pub fn with_storage_filter(mut self, storage_filter: SharedStorageFilter) -> Self {
+        self.storage_filter = Some(storage_filter);
+        self
     }
 }
 
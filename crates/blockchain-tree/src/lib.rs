@@ -45,9 +45,22 @@ mod canonical_chain;
 /// Common blockchain tree metrics.
 pub mod metrics;
 
+pub mod perf;
+
+pub mod chain_journal;
+pub use chain_journal::{ChainJournal, ChainJournalState};
+
+pub mod reorg_log;
+pub use reorg_log::ReorgLog;
+
+pub mod root_check;
+
 pub use block_buffer::BlockBuffer;
 
 /// Implementation of Tree traits that does nothing.
 pub mod noop;
 
+/// Background writers for files that are rewritten while the tree is locked.
+mod background;
+
 mod state;
@@ -60,6 +60,11 @@ impl MakeCanonicalDurationsRecorder {
 
         self.latest = Some(elapsed);
     }
+
+    /// Returns the time elapsed since the recorder was created.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
 }
 
 /// Represents actions for making a canonical chain.
@@ -12,7 +12,7 @@ use reth_primitives::{
 };
 use reth_provider::{
     BlockchainTreePendingStateProvider, BundleStateDataProvider, CanonStateNotificationSender,
-    CanonStateNotifications, CanonStateSubscriptions,
+    CanonStateNotifications, CanonStateSubscriptions, ReorgEvent, ReorgHistoryProvider,
 };
 use std::collections::{BTreeMap, HashSet};
 
@@ -137,3 +137,9 @@ impl CanonStateSubscriptions for NoopBlockchainTree {
         CanonStateNotificationSender::new(1).subscribe()
     }
 }
+
+impl ReorgHistoryProvider for NoopBlockchainTree {
+    fn reorg_history(&self) -> Vec<ReorgEvent> {
+        Vec::new()
+    }
+}
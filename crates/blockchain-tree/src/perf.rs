@@ -0,0 +1,275 @@
+//! Per-block performance telemetry.
+
+use crate::background::QueueWriter;
+use lru::LruCache;
+use parking_lot::Mutex;
+use reth_metrics::{
+    metrics::{Gauge, Histogram},
+    Metrics,
+};
+use reth_primitives::{BlockHash, BlockNumber};
+use serde::{Serialize, Serializer};
+use std::{
+    fs::OpenOptions,
+    io::{self, LineWriter, Write},
+    num::NonZeroUsize,
+    path::Path,
+    time::Duration,
+};
+use tracing::warn;
+
+/// Performance record of a single executed block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockPerfRecord {
+    /// The block number.
+    pub number: BlockNumber,
+    /// The block hash.
+    pub hash: BlockHash,
+    /// The number of transactions in the block.
+    pub transactions: usize,
+    /// The gas used by the block.
+    pub gas_used: u64,
+    /// The time it took to recover the senders of the block, if they were recovered by the tree.
+    #[serde(rename = "senders_recovery_us", serialize_with = "serialize_opt_micros")]
+    pub senders_recovery: Option<Duration>,
+    /// The time it took to execute the block.
+    #[serde(rename = "execution_us", serialize_with = "serialize_micros")]
+    pub execution: Duration,
+    /// The time it took to compute the state root, if it was computed.
+    #[serde(rename = "state_root_us", serialize_with = "serialize_opt_micros")]
+    pub state_root: Option<Duration>,
+    /// The time it took to build the hashed post state for the state root, included in
+    /// `state_root`.
+    #[serde(rename = "hashed_state_us", serialize_with = "serialize_opt_micros")]
+    pub hashed_state: Option<Duration>,
+    /// The time spent hashing changed keys on a separate thread while the block was executed,
+    /// which building the hashed post state saved.
+    #[serde(rename = "key_hashing_us", serialize_with = "serialize_opt_micros")]
+    pub key_hashing: Option<Duration>,
+}
+
+impl BlockPerfRecord {
+    /// Returns the execution throughput in gas per second.
+    pub fn gas_per_second(&self) -> f64 {
+        let secs = self.execution.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0
+        }
+        self.gas_used as f64 / secs
+    }
+}
+
+/// Performance record of writing a chain of blocks to the database.
+///
+/// Blocks are persisted in chains once they are canonical, long after they were executed, so the
+/// persistence timings are logged as separate records that cover the range of blocks.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PersistencePerfRecord {
+    /// The first persisted block.
+    pub first: BlockNumber,
+    /// The last persisted block.
+    pub last: BlockNumber,
+    /// The time it took to write and commit the blocks.
+    #[serde(rename = "duration_us", serialize_with = "serialize_micros")]
+    pub duration: Duration,
+}
+
+/// A single line of the performance log.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PerfLogEntry<'a> {
+    Block(&'a BlockPerfRecord),
+    Persistence(&'a PersistencePerfRecord),
+}
+
+/// The number of blocks whose senders recovery timings are kept until the block is executed.
+const SENDERS_RECOVERY_CAPACITY: usize = 256;
+
+/// Publishes performance records as metrics and, optionally, as JSONL to a file.
+///
+/// The file is appended to on a background thread, so writing it never blocks the tree.
+#[derive(Debug)]
+pub struct BlockPerfRecorder {
+    metrics: BlockPerfMetrics,
+    /// Senders recovery timings of blocks that were not executed yet.
+    senders_recovery: Mutex<LruCache<BlockHash, Duration>>,
+    log: Option<QueueWriter<Vec<u8>>>,
+}
+
+impl Default for BlockPerfRecorder {
+    fn default() -> Self {
+        Self {
+            metrics: Default::default(),
+            senders_recovery: Mutex::new(LruCache::new(
+                NonZeroUsize::new(SENDERS_RECOVERY_CAPACITY).expect("not zero"),
+            )),
+            log: None,
+        }
+    }
+}
+
+impl BlockPerfRecorder {
+    /// Creates a new recorder that additionally appends every record to the JSONL file at the
+    /// given path.
+    pub fn with_log_file(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut file = LineWriter::new(file);
+        let log = QueueWriter::spawn("block-perf-log", move |line: Vec<u8>| {
+            if let Err(err) = file.write_all(&line) {
+                warn!(target: "blockchain_tree", %err, "Failed to write block performance record");
+            }
+        })?;
+        Ok(Self { log: Some(log), ..Default::default() })
+    }
+
+    /// Records the time it took to recover the senders of a block.
+    ///
+    /// The timing is added to the record of the block once it is executed.
+    pub fn record_senders_recovery(&self, hash: BlockHash, duration: Duration) {
+        self.metrics.senders_recovery_duration.record(duration);
+        self.senders_recovery.lock().put(hash, duration);
+    }
+
+    /// Takes the senders recovery timing of the block with the given hash, if it was recorded.
+    pub fn take_senders_recovery(&self, hash: &BlockHash) -> Option<Duration> {
+        self.senders_recovery.lock().pop(hash)
+    }
+
+    /// Records the performance of an executed block.
+    pub fn record_block(&self, record: &BlockPerfRecord) {
+        self.metrics.execution_duration.record(record.execution);
+        if let Some(state_root) = record.state_root {
+            self.metrics.state_root_duration.record(state_root);
+        }
+        if let Some(hashed_state) = record.hashed_state {
+            self.metrics.hashed_state_duration.record(hashed_state);
+        }
+        if let Some(key_hashing) = record.key_hashing {
+            self.metrics.key_hashing_duration.record(key_hashing);
+        }
+        self.metrics.gas_per_second.set(record.gas_per_second());
+        self.write(PerfLogEntry::Block(record));
+    }
+
+    /// Records the performance of a database commit.
+    pub fn record_persistence(&self, record: &PersistencePerfRecord) {
+        self.metrics.persistence_duration.record(record.duration);
+        self.write(PerfLogEntry::Persistence(record));
+    }
+
+    fn write(&self, entry: PerfLogEntry<'_>) {
+        let Some(log) = &self.log else { return };
+        match serde_json::to_vec(&entry) {
+            Ok(mut line) => {
+                line.push(b'\n');
+                log.submit(line);
+            }
+            Err(err) => {
+                warn!(target: "blockchain_tree", %err, "Failed to encode block performance record")
+            }
+        }
+    }
+}
+
+/// Per-block performance metrics.
+#[derive(Metrics)]
+#[metrics(scope = "blockchain_tree.block_perf")]
+struct BlockPerfMetrics {
+    /// The time it took to recover the senders of a block
+    senders_recovery_duration: Histogram,
+    /// The time it took to execute a block
+    execution_duration: Histogram,
+    /// The time it took to compute the state root of a block
+    state_root_duration: Histogram,
+    /// The time it took to build the hashed post state of a block
+    hashed_state_duration: Histogram,
+    /// The time spent hashing changed keys while a block was executed
+    key_hashing_duration: Histogram,
+    /// The time it took to write a chain of blocks to the database
+    persistence_duration: Histogram,
+    /// The execution throughput of the last executed block in gas per second
+    gas_per_second: Gauge,
+}
+
+fn serialize_micros<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_micros() as u64)
+}
+
+fn serialize_opt_micros<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serialize_micros(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_record_json() {
+        let record = BlockPerfRecord {
+            number: 1,
+            hash: BlockHash::ZERO,
+            transactions: 2,
+            gas_used: 30_000_000,
+            senders_recovery: Some(Duration::from_micros(500)),
+            execution: Duration::from_millis(100),
+            state_root: None,
+            hashed_state: None,
+            key_hashing: Some(Duration::from_micros(250)),
+        };
+        assert_eq!(record.gas_per_second(), 300_000_000.0);
+
+        let json = serde_json::to_value(PerfLogEntry::Block(&record)).unwrap();
+        assert_eq!(json["kind"], "block");
+        assert_eq!(json["senders_recovery_us"], 500);
+        assert_eq!(json["execution_us"], 100_000);
+        assert!(json["state_root_us"].is_null());
+        assert_eq!(json["key_hashing_us"], 250);
+    }
+
+    #[test]
+    fn log_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("perf.jsonl");
+        let recorder = BlockPerfRecorder::with_log_file(&path).unwrap();
+
+        let hash = BlockHash::with_last_byte(1);
+        recorder.record_senders_recovery(hash, Duration::from_micros(300));
+        let record = BlockPerfRecord {
+            number: 1,
+            hash,
+            transactions: 1,
+            gas_used: 21_000,
+            senders_recovery: recorder.take_senders_recovery(&hash),
+            execution: Duration::from_micros(100),
+            state_root: None,
+            hashed_state: None,
+            key_hashing: None,
+        };
+        assert_eq!(recorder.take_senders_recovery(&hash), None);
+        recorder.record_block(&record);
+        recorder.record_persistence(&PersistencePerfRecord {
+            first: 1,
+            last: 1,
+            duration: Duration::from_micros(700),
+        });
+        // waits for the records to be written
+        drop(recorder);
+
+        let lines = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["kind"], "block");
+        assert_eq!(lines[0]["senders_recovery_us"], 300);
+        assert_eq!(lines[1]["kind"], "persistence");
+        assert_eq!(lines[1]["duration_us"], 700);
+    }
+}
@@ -0,0 +1,259 @@
+//! Persistent history of canonical chain reorgs.
+
+use crate::background::{replace_file, LatestWriter};
+use reth_metrics::{
+    metrics::{Counter, Histogram},
+    Metrics,
+};
+use reth_primitives::{BlockHash, BlockNumHash, BlockNumber, TxHash};
+use reth_provider::{Chain, ReorgEvent};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::File,
+    io::{self, BufReader},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+/// The default number of reorgs that are kept in the [ReorgLog].
+pub const DEFAULT_REORG_LOG_CAPACITY: usize = 256;
+
+/// Ring buffer of the most recent reorgs of the canonical chain.
+///
+/// If the log is backed by a file, the file is rewritten on a background thread after every
+/// recorded reorg, so the history survives restarts without blocking the tree.
+#[derive(Debug)]
+pub struct ReorgLog {
+    /// Recorded reorgs, oldest first.
+    events: VecDeque<ReorgEvent>,
+    /// The maximum number of recorded reorgs.
+    capacity: usize,
+    /// Writes the recorded reorgs to the log file, if the log is persisted.
+    writer: Option<LatestWriter<Vec<StoredReorgEvent>>>,
+    /// Persisting is skipped while this is set.
+    persist_paused: Option<watch::Receiver<bool>>,
+    metrics: ReorgLogMetrics,
+}
+
+impl ReorgLog {
+    /// Creates a new in-memory log with the given capacity.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            writer: None,
+            persist_paused: None,
+            metrics: Default::default(),
+        }
+    }
+
+    /// Opens the log that is persisted to the given file, loading the reorgs that were already
+    /// recorded.
+    ///
+    /// An unreadable log file is discarded.
+    pub fn open(path: impl Into<PathBuf>, capacity: usize) -> io::Result<Self> {
+        let path = path.into();
+        let mut log = Self::new(capacity);
+        match File::open(&path) {
+            Ok(file) => {
+                match serde_json::from_reader::<_, Vec<StoredReorgEvent>>(BufReader::new(file)) {
+                    Ok(events) => {
+                        let skip = events.len().saturating_sub(log.capacity);
+                        log.events.extend(events.into_iter().skip(skip).map(Into::into));
+                    }
+                    Err(err) => {
+                        warn!(target: "blockchain_tree::reorg_log", path = %path.display(), %err, "Discarding unreadable reorg log")
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        let metrics = log.metrics.clone();
+        log.writer = Some(LatestWriter::spawn(
+            "reorg-log",
+            move |events: Vec<StoredReorgEvent>| {
+                let result = serde_json::to_vec(&events)
+                    .map_err(io::Error::from)
+                    .and_then(|buf| replace_file(&path, &buf));
+                if let Err(err) = result {
+                    warn!(target: "blockchain_tree::reorg_log", %err, "Failed to persist reorg log");
+                    metrics.persist_errors.increment(1);
+                }
+            },
+        )?);
+        Ok(log)
+    }
+
+    /// Skips persisting the log while the given signal is set, e.g. while disk space is low.
+    ///
+    /// Reorgs are still recorded in memory, the whole log is persisted with the first reorg after
+    /// the signal is cleared.
+    pub fn with_persist_paused(mut self, paused: watch::Receiver<bool>) -> Self {
+        self.persist_paused = Some(paused);
+        self
+    }
+
+    /// Returns the recorded reorgs, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = &ReorgEvent> + '_ {
+        self.events.iter()
+    }
+
+    /// Records a reorg from the old to the new canonical chain, evicting the oldest reorg if the
+    /// log is full.
+    pub fn record(&mut self, old: &Chain, new: &Chain, duration: Duration) {
+        let included = new.blocks_iter().flat_map(|block| block.body.iter().map(|tx| tx.hash()));
+        let included = included.collect::<HashSet<_>>();
+        let dropped_transactions = old
+            .blocks_iter()
+            .flat_map(|block| block.body.iter().map(|tx| tx.hash()))
+            .filter(|hash| !included.contains(hash))
+            .collect::<Vec<_>>();
+
+        let event = ReorgEvent {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|now| now.as_secs())
+                .unwrap_or_default(),
+            old_tip: old.tip().header.num_hash(),
+            new_tip: new.tip().header.num_hash(),
+            depth: old.len() as u64,
+            dropped_transactions,
+            duration,
+        };
+        self.metrics.reorgs.increment(1);
+        self.metrics.depth.record(event.depth as f64);
+        self.metrics.dropped_transactions.record(event.dropped_transactions.len() as f64);
+        self.metrics.duration.record(duration);
+
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+
+        let Some(writer) = &self.writer else { return };
+        if self.persist_paused.as_ref().is_some_and(|paused| *paused.borrow()) {
+            debug!(target: "blockchain_tree::reorg_log", "Persisting reorg log is paused");
+            return
+        }
+        writer.submit(self.events.iter().map(StoredReorgEvent::from).collect());
+    }
+
+    /// Blocks until the recorded reorgs are written to the log file.
+    pub fn flush(&self) {
+        if let Some(writer) = &self.writer {
+            writer.flush()
+        }
+    }
+}
+
+impl Default for ReorgLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_REORG_LOG_CAPACITY)
+    }
+}
+
+/// The representation of a [ReorgEvent] in the log file.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredReorgEvent {
+    timestamp: u64,
+    old_tip: (BlockNumber, BlockHash),
+    new_tip: (BlockNumber, BlockHash),
+    depth: u64,
+    dropped_transactions: Vec<TxHash>,
+    duration: Duration,
+}
+
+impl From<&ReorgEvent> for StoredReorgEvent {
+    fn from(event: &ReorgEvent) -> Self {
+        Self {
+            timestamp: event.timestamp,
+            old_tip: (event.old_tip.number, event.old_tip.hash),
+            new_tip: (event.new_tip.number, event.new_tip.hash),
+            depth: event.depth,
+            dropped_transactions: event.dropped_transactions.clone(),
+            duration: event.duration,
+        }
+    }
+}
+
+impl From<StoredReorgEvent> for ReorgEvent {
+    fn from(event: StoredReorgEvent) -> Self {
+        Self {
+            timestamp: event.timestamp,
+            old_tip: BlockNumHash::new(event.old_tip.0, event.old_tip.1),
+            new_tip: BlockNumHash::new(event.new_tip.0, event.new_tip.1),
+            depth: event.depth,
+            dropped_transactions: event.dropped_transactions,
+            duration: event.duration,
+        }
+    }
+}
+
+/// Metrics of the recorded reorgs.
+#[derive(Clone, Metrics)]
+#[metrics(scope = "blockchain_tree.reorg_log")]
+struct ReorgLogMetrics {
+    /// The number of recorded reorgs
+    reorgs: Counter,
+    /// The number of reverted blocks per reorg
+    depth: Histogram,
+    /// The number of dropped transactions per reorg
+    dropped_transactions: Histogram,
+    /// The time it took to make the new chain canonical
+    duration: Histogram,
+    /// The number of times the log failed to be persisted
+    persist_errors: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::test_utils::tempdir_path;
+    use reth_interfaces::test_utils::generators::{self, random_block};
+    use reth_primitives::SealedBlock;
+    use reth_provider::BundleStateWithReceipts;
+
+    #[test]
+    fn records_and_persists_reorgs() {
+        let mut rng = generators::rng();
+        let parent = random_block(&mut rng, 0, None, None, None);
+        let old_block = random_block(&mut rng, 1, Some(parent.hash()), Some(2), None);
+        let mut new_block = random_block(&mut rng, 1, Some(parent.hash()), Some(1), None);
+        // one of the reverted transactions is included in the new chain
+        new_block.body.push(old_block.body[0].clone());
+        let chain = |block: &SealedBlock| {
+            let block = block.clone().seal_with_senders().unwrap();
+            Chain::from_block(block, BundleStateWithReceipts::default(), None)
+        };
+        let old = chain(&old_block);
+        let new = chain(&new_block);
+
+        let path = tempdir_path().join("reorgs.json");
+        let mut log = ReorgLog::open(&path, 2).unwrap();
+        assert_eq!(log.events().count(), 0);
+        for _ in 0..3 {
+            log.record(&old, &new, Duration::from_millis(10));
+        }
+        log.flush();
+
+        let expected = ReorgEvent {
+            timestamp: log.events.back().unwrap().timestamp,
+            old_tip: old_block.num_hash(),
+            new_tip: new_block.num_hash(),
+            depth: 1,
+            dropped_transactions: vec![old_block.body[1].hash()],
+            duration: Duration::from_millis(10),
+        };
+        assert_eq!(log.events().count(), 2);
+        assert_eq!(log.events().last(), Some(&expected));
+
+        let reopened = ReorgLog::open(&path, 1).unwrap();
+        assert_eq!(reopened.events().collect::<Vec<_>>(), vec![&expected]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Cross-validation of incrementally computed state roots.
+
+use reth_db::transaction::DbTx;
+use reth_metrics::{
+    metrics::{Counter, Histogram},
+    Metrics,
+};
+use reth_primitives::{trie::Nibbles, Account, BlockNumHash, BlockNumber, B256, U256};
+use reth_trie::HashedPostState;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+use tracing::{debug, error, warn};
+
+/// Recomputes the state root of every Nth block from scratch and compares it with the
+/// incrementally computed root.
+///
+/// The incremental computation reuses the intermediate trie nodes stored in the database and only
+/// walks the changed prefixes, the reference ignores all stored trie nodes and hashes the entire
+/// state. This is expensive on large states and should be used with a large interval.
+#[derive(Debug)]
+pub struct StateRootCrossCheck {
+    /// Check every block whose number is a multiple of the interval.
+    interval: u64,
+    /// The directory to write repro bundles of divergent blocks to.
+    repro_dir: Option<PathBuf>,
+    metrics: StateRootCrossCheckMetrics,
+}
+
+impl StateRootCrossCheck {
+    /// Creates a new cross-check of every block whose number is a multiple of the interval.
+    pub fn new(interval: u64) -> Self {
+        Self { interval: interval.max(1), repro_dir: None, metrics: Default::default() }
+    }
+
+    /// Sets the directory to write repro bundles of divergent blocks to.
+    pub fn with_repro_dir(mut self, dir: PathBuf) -> Self {
+        self.repro_dir = Some(dir);
+        self
+    }
+
+    /// Returns `true` if the block should be cross-checked.
+    pub fn should_check(&self, number: BlockNumber) -> bool {
+        number % self.interval == 0
+    }
+
+    /// Compares the incremental state root of the block with the reference root computed on top
+    /// of the database state.
+    ///
+    /// Returns `false` if the roots diverge, in which case the divergence is logged and a repro
+    /// bundle with the post state and prefix sets is written if a directory was configured.
+    pub fn check<TX: DbTx>(
+        &self,
+        tx: &TX,
+        block: BlockNumHash,
+        hashed_state: &HashedPostState,
+        root: B256,
+    ) -> bool {
+        let started_at = Instant::now();
+        let reference = match hashed_state.state_root_from_scratch(tx) {
+            Ok(reference) => reference,
+            Err(err) => {
+                warn!(target: "blockchain_tree::root_check", ?block, %err, "Failed to compute reference state root");
+                self.metrics.errors.increment(1);
+                return true
+            }
+        };
+        self.metrics.reference_duration.record(started_at.elapsed());
+        self.metrics.checks.increment(1);
+
+        if reference == root {
+            debug!(target: "blockchain_tree::root_check", ?block, %root, "State root matches reference");
+            return true
+        }
+
+        self.metrics.divergences.increment(1);
+        error!(target: "blockchain_tree::root_check", ?block, %root, %reference, "State root diverges from reference");
+        if let Some(dir) = &self.repro_dir {
+            let bundle = ReproBundle::new(block, root, reference, hashed_state);
+            match bundle.write(dir) {
+                Ok(path) => {
+                    error!(target: "blockchain_tree::root_check", path = %path.display(), "Wrote state root repro bundle")
+                }
+                Err(err) => {
+                    warn!(target: "blockchain_tree::root_check", %err, "Failed to write state root repro bundle")
+                }
+            }
+        }
+        false
+    }
+}
+
+/// The inputs of a divergent state root computation.
+#[derive(Debug, Serialize)]
+struct ReproBundle {
+    number: BlockNumber,
+    hash: B256,
+    incremental_root: B256,
+    reference_root: B256,
+    /// Changed accounts, `None` if the account was destroyed.
+    accounts: Vec<(B256, Option<Account>)>,
+    storages: BTreeMap<B256, ReproStorage>,
+    /// Changed account prefixes, as hex nibbles.
+    account_prefix_set: Vec<String>,
+    /// Changed storage prefixes by hashed address, as hex nibbles.
+    storage_prefix_sets: BTreeMap<B256, Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReproStorage {
+    wiped: bool,
+    slots: Vec<(B256, U256)>,
+}
+
+impl ReproBundle {
+    fn new(
+        block: BlockNumHash,
+        incremental_root: B256,
+        reference_root: B256,
+        hashed_state: &HashedPostState,
+    ) -> Self {
+        let (account_prefix_set, storage_prefix_sets) = hashed_state.construct_prefix_sets();
+        Self {
+            number: block.number,
+            hash: block.hash,
+            incremental_root,
+            reference_root,
+            accounts: hashed_state.accounts().collect(),
+            storages: hashed_state
+                .storages()
+                .map(|(hashed_address, storage)| {
+                    let storage = ReproStorage {
+                        wiped: storage.wiped(),
+                        slots: storage.storage_slots().collect(),
+                    };
+                    (*hashed_address, storage)
+                })
+                .collect(),
+            account_prefix_set: account_prefix_set.keys().iter().map(nibbles_hex).collect(),
+            storage_prefix_sets: storage_prefix_sets
+                .into_iter()
+                .map(|(hashed_address, prefix_set)| {
+                    (hashed_address, prefix_set.keys().iter().map(nibbles_hex).collect())
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the bundle as JSON into the directory and returns the path of the file.
+    fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("state-root-{}-{}.json", self.number, self.hash));
+        serde_json::to_writer_pretty(BufWriter::new(File::create(&path)?), self)?;
+        Ok(path)
+    }
+}
+
+fn nibbles_hex(nibbles: &Nibbles) -> String {
+    nibbles.iter().map(|nibble| format!("{nibble:x}")).collect()
+}
+
+/// Metrics of the state root cross-check.
+#[derive(Metrics)]
+#[metrics(scope = "blockchain_tree.state_root_cross_check")]
+struct StateRootCrossCheckMetrics {
+    /// The number of blocks whose state root was cross-checked
+    checks: Counter,
+    /// The number of blocks whose state root diverged from the reference
+    divergences: Counter,
+    /// The number of cross-checks that failed to compute the reference root
+    errors: Counter,
+    /// The time it took to compute the reference state root
+    reference_duration: Histogram,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database,
+        test_utils::{create_test_rw_db, tempdir_path},
+    };
+    use reth_trie::HashedStorage;
+
+    #[test]
+    fn divergence_writes_repro_bundle() {
+        let db = create_test_rw_db();
+        let dir = tempdir_path();
+        let check = StateRootCrossCheck::new(2).with_repro_dir(dir.clone());
+        assert!(check.should_check(4));
+        assert!(!check.should_check(5));
+
+        let mut hashed_state = HashedPostState::default();
+        hashed_state.insert_account(
+            B256::with_last_byte(1),
+            Some(Account { nonce: 1, ..Default::default() }),
+        );
+        let mut storage = HashedStorage::new(false);
+        storage.insert_slot(B256::with_last_byte(2), U256::from(3));
+        hashed_state.insert_hashed_storage(B256::with_last_byte(1), storage);
+        let hashed_state = hashed_state.sorted();
+
+        let tx = db.tx().unwrap();
+        let root = hashed_state.state_root(&tx).unwrap();
+        let block = BlockNumHash::new(4, B256::with_last_byte(4));
+        assert!(check.check(&tx, block, &hashed_state, root));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        assert!(!check.check(&tx, block, &hashed_state, B256::ZERO));
+        let path = dir.join(format!("state-root-4-{}.json", block.hash));
+        let bundle: serde_json::Value = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        assert_eq!(bundle["reference_root"], serde_json::json!(root));
+        assert_eq!(bundle["account_prefix_set"][0].as_str().unwrap().len(), 64);
+    }
+}
@@ -16,7 +16,7 @@ use reth_primitives::{
 };
 use reth_provider::{
     BlockchainTreePendingStateProvider, BundleStateDataProvider, CanonStateSubscriptions,
-    ExecutorFactory,
+    ExecutorFactory, ReorgEvent, ReorgHistoryProvider,
 };
 use std::{
     collections::{BTreeMap, HashSet},
@@ -55,6 +55,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.insert_block(block, validation_kind);
         tree.update_chains_metrics();
+        tree.record_chain_journal();
         res
     }
 
@@ -63,6 +64,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         tree.finalize_block(finalized_block);
         tree.update_chains_metrics();
+        tree.record_chain_journal();
     }
 
     fn connect_buffered_blocks_to_canonical_hashes_and_finalize(
@@ -74,6 +76,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let res =
             tree.connect_buffered_blocks_to_canonical_hashes_and_finalize(last_finalized_block);
         tree.update_chains_metrics();
+        tree.record_chain_journal();
         res
     }
 
@@ -82,6 +85,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.connect_buffered_blocks_to_canonical_hashes();
         tree.update_chains_metrics();
+        tree.record_chain_journal();
         res
     }
 
@@ -90,6 +94,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.make_canonical(block_hash);
         tree.update_chains_metrics();
+        tree.record_chain_journal();
         res
     }
 
@@ -98,6 +103,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.unwind(unwind_to);
         tree.update_chains_metrics();
+        tree.record_chain_journal();
         res
     }
 }
@@ -216,3 +222,10 @@ impl<DB: Database, EF: ExecutorFactory> CanonStateSubscriptions
         self.tree.read().subscribe_canon_state()
     }
 }
+
+impl<DB: Database, EF: ExecutorFactory> ReorgHistoryProvider for ShareableBlockchainTree<DB, EF> {
+    fn reorg_history(&self) -> Vec<ReorgEvent> {
+        trace!(target: "blockchain_tree", "Returning reorg history");
+        self.tree.read().reorg_history()
+    }
+}
@@ -3,9 +3,10 @@
 use reth_discv4::Discv4Config;
 use reth_network::{NetworkConfigBuilder, PeersConfig, SessionsConfig};
 use reth_primitives::PruneModes;
+use reth_rpc_types::ApiKey;
 use secp256k1::SecretKey;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, time::Duration};
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
 
 /// Configuration for the reth node.
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Serialize)]
@@ -21,6 +22,12 @@ pub struct Config {
     pub peers: PeersConfig,
     /// Configuration for peer sessions.
     pub sessions: SessionsConfig,
+    /// Configuration for the RPC servers.
+    pub rpc: RpcConfig,
+    /// Configuration for the exported metrics.
+    pub metrics: MetricsConfig,
+    /// Configuration for the payload builder.
+    pub builder: BuilderConfig,
 }
 
 impl Config {
@@ -271,14 +278,89 @@ pub struct PruneConfig {
     /// Pruning configuration for every part of the data that can be pruned.
     #[serde(alias = "parts")]
     pub segments: PruneModes,
+    /// The maximum number of entries to delete per pruned block, overrides the limit of the
+    /// chain. Can be changed at runtime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete_limit: Option<usize>,
 }
 
 impl Default for PruneConfig {
     fn default() -> Self {
-        Self { block_interval: 5, segments: PruneModes::none() }
+        Self { block_interval: 5, segments: PruneModes::none(), delete_limit: None }
     }
 }
 
+/// Configuration for the RPC servers.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    /// API keys of the HTTP and WS servers.
+    ///
+    /// If set, every request must carry one of the keys, and is restricted to the namespaces and
+    /// limits of its key. Keys can be added and removed at runtime via the `admin_` API on IPC.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_keys: Option<Vec<ApiKey>>,
+    /// Capacities of the RPC state cache, overriding the defaults of the `--rpc-cache.*` flags.
+    ///
+    /// Flags that are set on the command line take precedence. Can be changed at runtime.
+    #[serde(skip_serializing_if = "RpcCacheConfig::is_empty")]
+    pub cache: RpcCacheConfig,
+}
+
+/// Capacities of the RPC state cache.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct RpcCacheConfig {
+    /// Max number of blocks in cache.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_blocks: Option<u32>,
+    /// Max number of receipts in cache.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_receipts: Option<u32>,
+    /// Max number of cached env data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_envs: Option<u32>,
+}
+
+impl RpcCacheConfig {
+    /// Returns true if no capacity is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Configuration for the payload builder.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct BuilderConfig {
+    /// The deadline for when the payload builder job should resolve, overriding the default of
+    /// `--builder.deadline`.
+    ///
+    /// The flag takes precedence if it is set on the command line. Can be changed at runtime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<Duration>,
+}
+
+/// Configuration for the exported metrics.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Subsystems whose metrics are not exported, e.g. `network` or `rpc_server`.
+    ///
+    /// A subsystem is a prefix of the exported metric names after `reth_`, e.g. `network`
+    /// disables all `reth_network_*` metrics. Can be changed at runtime.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disabled_subsystems: Vec<String>,
+    /// The maximum number of distinct values of a label per metric, by label name, e.g.
+    /// `method = 20`.
+    ///
+    /// Series with further values of the label are aggregated into one series with the value
+    /// `other`. A limit of 0 drops the label, which aggregates all its series. Takes effect after
+    /// a restart.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub label_limits: BTreeMap<String, usize>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::Config;
@@ -430,4 +512,48 @@ storage_history = { distance = 16384 }
 #";
         let _conf: Config = toml::from_str(alpha_0_0_11).unwrap();
     }
+
+    #[test]
+    fn test_conf_rpc_api_keys() {
+        let s = r"#
+[[rpc.api_keys]]
+key = 'internal'
+
+[[rpc.api_keys]]
+key = 'public'
+namespaces = ['eth', 'net', 'web3']
+requests_per_second = 50
+max_call_gas = 50000000
+#";
+        let conf: Config = toml::from_str(s).unwrap();
+        let keys = conf.rpc.api_keys.unwrap();
+        assert!(keys[0].is_unrestricted());
+        assert!(keys[1].allows_namespace("eth"));
+        assert!(!keys[1].allows_namespace("debug"));
+        assert_eq!(keys[1].requests_per_second, Some(50));
+        assert_eq!(keys[1].max_call_gas, Some(50_000_000));
+    }
+
+    #[test]
+    fn test_conf_runtime_tunables() {
+        let s = r"#
+[prune]
+block_interval = 5
+delete_limit = 1000
+
+[rpc.cache]
+max_blocks = 100
+
+[builder]
+deadline = { secs = 4, nanos = 0 }
+#";
+        let conf: Config = toml::from_str(s).unwrap();
+        assert_eq!(conf.prune.unwrap().delete_limit, Some(1000));
+        assert_eq!(conf.rpc.cache.max_blocks, Some(100));
+        assert_eq!(conf.rpc.cache.max_receipts, None);
+        assert_eq!(conf.builder.deadline, Some(std::time::Duration::from_secs(4)));
+
+        // unset capacities are not written
+        assert!(!toml::to_string(&Config::default()).unwrap().contains("cache"));
+    }
 }
@@ -0,0 +1,119 @@
+//! Manual controls for the auto seal miner.
+
+use crate::Storage;
+use std::time::Duration;
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+
+/// The maximum number of blocks a single [MiningControl::mine] call can mine.
+pub const MAX_MINE_BLOCKS: u64 = 1024;
+
+/// Commands sent from a [MiningControl] to the [MiningTask](crate::MiningTask).
+#[derive(Debug)]
+pub(crate) enum MiningCommand {
+    /// Mine the given number of blocks with the currently best transactions.
+    ///
+    /// The sender is notified once the last block was made canonical, or with the error if a
+    /// block could not be mined.
+    Mine { blocks: u64, tx: MineListener },
+    /// Replace the active mining mode with interval mining, or disable automatic mining
+    /// entirely if `None`.
+    SetInterval(Option<Duration>),
+}
+
+/// Notified once a [MiningCommand::Mine] request was handled.
+pub(crate) type MineListener = oneshot::Sender<Result<(), MiningControlError>>;
+
+/// Errors returned by [MiningControl].
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum MiningControlError {
+    /// The mining task is no longer running.
+    #[error("mining task is not running")]
+    TaskClosed,
+    /// More blocks were requested than can be mined at once.
+    #[error("cannot mine {blocks} blocks at once, the maximum is {max}")]
+    TooManyBlocks {
+        /// The requested number of blocks
+        blocks: u64,
+        /// The maximum number of blocks per call
+        max: u64,
+    },
+    /// The requested timestamp is not after the timestamp of the current best block.
+    #[error("timestamp {timestamp} is lower than or equal to previous block's timestamp {best}")]
+    TimestampTooLow {
+        /// The requested timestamp
+        timestamp: u64,
+        /// The timestamp of the current best block
+        best: u64,
+    },
+    /// A block could not be built, executed or made canonical.
+    ///
+    /// The remaining blocks of the request are not mined.
+    #[error("failed to mine block: {0}")]
+    Execution(String),
+}
+
+/// A handle to manually drive the [MiningTask](crate::MiningTask).
+///
+/// Blocks mined via this handle go through the same execution and engine path as automatically
+/// mined blocks.
+#[derive(Debug, Clone)]
+pub struct MiningControl {
+    /// Sender half of the command channel of the mining task.
+    to_task: UnboundedSender<MiningCommand>,
+    /// Shared storage, used for timestamp overrides.
+    storage: Storage,
+}
+
+// === impl MiningControl ===
+
+impl MiningControl {
+    /// Creates a new handle
+    pub(crate) fn new(to_task: UnboundedSender<MiningCommand>, storage: Storage) -> Self {
+        Self { to_task, storage }
+    }
+
+    /// Mines `blocks` new blocks and waits until the last one has been made canonical.
+    ///
+    /// Returns [MiningControlError::Execution] if one of the blocks could not be mined.
+    ///
+    /// At most [MAX_MINE_BLOCKS] blocks can be mined per call.
+    pub async fn mine(&self, blocks: u64) -> Result<(), MiningControlError> {
+        if blocks > MAX_MINE_BLOCKS {
+            return Err(MiningControlError::TooManyBlocks { blocks, max: MAX_MINE_BLOCKS })
+        }
+        let (tx, rx) = oneshot::channel();
+        self.to_task
+            .send(MiningCommand::Mine { blocks, tx })
+            .map_err(|_| MiningControlError::TaskClosed)?;
+        rx.await.map_err(|_| MiningControlError::TaskClosed)?
+    }
+
+    /// Sets the timestamp of the next block.
+    pub async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<(), MiningControlError> {
+        let mut storage = self.storage.write().await;
+        let best = storage.best_timestamp();
+        if timestamp <= best {
+            return Err(MiningControlError::TimestampTooLow { timestamp, best })
+        }
+        storage.next_timestamp = Some(timestamp);
+        Ok(())
+    }
+
+    /// Shifts the clock of all future blocks by `seconds` and returns the new total offset.
+    pub async fn increase_time(&self, seconds: u64) -> u64 {
+        let mut storage = self.storage.write().await;
+        storage.time_offset = storage.time_offset.saturating_add(seconds);
+        storage.time_offset
+    }
+
+    /// Switches to interval mining with the given block time, or disables automatic mining if
+    /// `None`.
+    pub fn set_interval_mining(
+        &self,
+        interval: Option<Duration>,
+    ) -> Result<(), MiningControlError> {
+        self.to_task
+            .send(MiningCommand::SetInterval(interval))
+            .map_err(|_| MiningControlError::TaskClosed)
+    }
+}
@@ -14,17 +14,19 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+use crate::payload::AutoSealPayloadBuilder;
 use reth_beacon_consensus::BeaconEngineMessage;
 use reth_interfaces::{
     consensus::{Consensus, ConsensusError},
     executor::{BlockExecutionError, BlockValidationError},
 };
 use reth_node_api::EngineTypes;
+use reth_payload_builder::PayloadBuilderHandle;
 use reth_primitives::{
     constants::{EMPTY_RECEIPTS, EMPTY_TRANSACTIONS, ETHEREUM_BLOCK_GAS_LIMIT},
     proofs, Block, BlockBody, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Bloom,
-    ChainSpec, Header, ReceiptWithBloom, SealedBlock, SealedHeader, TransactionSigned, B256,
-    EMPTY_OMMER_ROOT_HASH, U256,
+    ChainSpec, Header, ReceiptWithBloom, SealedBlock, SealedBlockWithSenders, SealedHeader,
+    TransactionSigned, B256, EMPTY_OMMER_ROOT_HASH, U256,
 };
 use reth_provider::{
     BlockExecutor, BlockReaderIdExt, BundleStateWithReceipts, CanonStateNotificationSender,
@@ -44,11 +46,16 @@ use tokio::sync::{mpsc::UnboundedSender, RwLock, RwLockReadGuard, RwLockWriteGua
 use tracing::trace;
 
 mod client;
+mod control;
 mod mode;
+mod payload;
+mod rpc;
 mod task;
 
 pub use crate::client::AutoSealClient;
+pub use control::{MiningControl, MiningControlError, MAX_MINE_BLOCKS};
 pub use mode::{FixedBlockTimeMiner, MiningMode, ReadyTransactionMiner};
+pub use rpc::EvmApi;
 pub use task::MiningTask;
 
 /// A consensus implementation intended for local development and testing purposes.
@@ -102,6 +109,7 @@ pub struct AutoSealBuilder<Client, Pool, Engine: EngineTypes> {
     storage: Storage,
     to_engine: UnboundedSender<BeaconEngineMessage<Engine>>,
     canon_state_notification: CanonStateNotificationSender,
+    payload_builder: Option<AutoSealPayloadBuilder<Engine>>,
 }
 
 // === impl AutoSealBuilder ===
@@ -135,6 +143,7 @@ where
             mode,
             to_engine,
             canon_state_notification,
+            payload_builder: None,
         }
     }
 
@@ -144,11 +153,34 @@ where
         self
     }
 
+    /// Builds the mined blocks with the given payload builder instead of executing the mined
+    /// transactions directly.
+    ///
+    /// Every block is built by a payload job started with the attributes `attributes` returns
+    /// for the parent hash and the timestamp of the block. The job selects the transactions from
+    /// the pool, so the transactions picked by the [MiningMode] only trigger mining.
+    pub fn with_payload_builder(
+        mut self,
+        payload_builder: PayloadBuilderHandle<Engine>,
+        attributes: impl Fn(B256, u64) -> Engine::PayloadBuilderAttributes + Send + Sync + 'static,
+    ) -> Self {
+        self.payload_builder = Some(AutoSealPayloadBuilder::new(payload_builder, attributes));
+        self
+    }
+
     /// Consumes the type and returns all components
     #[track_caller]
     pub fn build(self) -> (AutoSealConsensus, AutoSealClient, MiningTask<Client, Pool, Engine>) {
-        let Self { client, consensus, pool, mode, storage, to_engine, canon_state_notification } =
-            self;
+        let Self {
+            client,
+            consensus,
+            pool,
+            mode,
+            storage,
+            to_engine,
+            canon_state_notification,
+            payload_builder,
+        } = self;
         let auto_client = AutoSealClient::new(storage.clone());
         let task = MiningTask::new(
             Arc::clone(&consensus.chain_spec),
@@ -158,6 +190,7 @@ where
             storage,
             client,
             pool,
+            payload_builder,
         );
         (consensus, auto_client, task)
     }
@@ -211,6 +244,10 @@ pub(crate) struct StorageInner {
     pub(crate) best_hash: B256,
     /// The total difficulty of the chain until this block
     pub(crate) total_difficulty: U256,
+    /// Timestamp override for the next block, see `evm_setNextBlockTimestamp`
+    pub(crate) next_timestamp: Option<u64>,
+    /// Offset in seconds added to the wall clock for new blocks, see `evm_increaseTime`
+    pub(crate) time_offset: u64,
 }
 
 // === impl StorageInner ===
@@ -246,6 +283,26 @@ impl StorageInner {
         self.headers.insert(header.number, header);
         self.bodies.insert(self.best_hash, body);
         self.hash_to_number.insert(self.best_hash, self.best_block);
+
+        // a timestamp override only applies to a single block
+        self.next_timestamp = None;
+    }
+
+    /// Returns the timestamp of the current best block.
+    pub(crate) fn best_timestamp(&self) -> u64 {
+        self.headers.get(&self.best_block).map(|header| header.timestamp).unwrap_or_default()
+    }
+
+    /// Returns the timestamp to use for the next block.
+    ///
+    /// This is either the configured override or the wall clock shifted by the configured time
+    /// offset, but always greater than the timestamp of the current best block.
+    pub(crate) fn next_block_timestamp(&self) -> u64 {
+        let timestamp = self.next_timestamp.unwrap_or_else(|| {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            now.saturating_add(self.time_offset)
+        });
+        timestamp.max(self.best_timestamp().saturating_add(1))
     }
 
     /// Fills in pre-execution header fields based on the current best block and given
@@ -255,7 +312,7 @@ impl StorageInner {
         transactions: &[TransactionSigned],
         chain_spec: Arc<ChainSpec>,
     ) -> Header {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let timestamp = self.next_block_timestamp();
 
         // check previous block for base fee
         let base_fee_per_gas = self
@@ -418,4 +475,40 @@ impl StorageInner {
 
         Ok((new_header, bundle_state))
     }
+
+    /// Executes a block built by the payload builder on top of the current best block and
+    /// inserts it into storage.
+    ///
+    /// The payload builder already completed the header, the block is executed again to obtain
+    /// the state changes for the canonical state notification.
+    pub(crate) fn insert_built_block(
+        &mut self,
+        block: SealedBlock,
+        client: &impl StateProviderFactory,
+        chain_spec: Arc<ChainSpec>,
+    ) -> Result<(SealedBlockWithSenders, BundleStateWithReceipts), BlockExecutionError> {
+        let block = block
+            .seal_with_senders()
+            .ok_or(BlockExecutionError::Validation(BlockValidationError::SenderRecoveryError))?;
+
+        let db = State::builder()
+            .with_database_boxed(Box::new(StateProviderDatabase::new(
+                client.latest().map_err(|_| BlockExecutionError::ProviderError)?,
+            )))
+            .with_bundle_update()
+            .build();
+        let mut executor = EVMProcessor::new_with_state(chain_spec, db);
+        executor.set_first_block(block.number);
+        executor.execute_and_verify_receipt(&block.clone().unseal(), U256::ZERO)?;
+        let bundle_state = executor.take_output_state();
+
+        let SealedBlock { header, body, ommers, withdrawals } = block.block.clone();
+        self.insert_new_block(
+            header.unseal(),
+            BlockBody { transactions: body, ommers, withdrawals },
+        );
+        debug_assert_eq!(self.best_hash, block.hash(), "built block extends the best block");
+
+        Ok((block, bundle_state))
+    }
 }
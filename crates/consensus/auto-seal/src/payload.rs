@@ -0,0 +1,81 @@
+//! Building auto sealed blocks with the payload builder of the node.
+
+use reth_node_api::{BuiltPayload, EngineTypes};
+use reth_payload_builder::{PayloadBuilderHandle, PayloadStore};
+use reth_primitives::{SealedBlock, B256};
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+/// How long to wait for the payload job to build its first payload before resolving it.
+const FIRST_PAYLOAD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often the payload job is checked for a built payload.
+const FIRST_PAYLOAD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Derives the attributes of a payload job from the parent hash and the timestamp of the block.
+type AttributesFn<Engine> =
+    Arc<dyn Fn(B256, u64) -> <Engine as EngineTypes>::PayloadBuilderAttributes + Send + Sync>;
+
+/// Builds the blocks of the [MiningTask](crate::MiningTask) with the payload builder service of
+/// the node, so mined blocks are assembled exactly like the payloads handed out to a consensus
+/// layer.
+///
+/// The payload job picks the transactions from the pool itself.
+pub(crate) struct AutoSealPayloadBuilder<Engine: EngineTypes> {
+    handle: PayloadBuilderHandle<Engine>,
+    attributes: AttributesFn<Engine>,
+}
+
+// === impl AutoSealPayloadBuilder ===
+
+impl<Engine: EngineTypes> AutoSealPayloadBuilder<Engine> {
+    /// Creates a new instance that starts payload jobs with the attributes returned by
+    /// `attributes`.
+    pub(crate) fn new(
+        handle: PayloadBuilderHandle<Engine>,
+        attributes: impl Fn(B256, u64) -> Engine::PayloadBuilderAttributes + Send + Sync + 'static,
+    ) -> Self {
+        Self { handle, attributes: Arc::new(attributes) }
+    }
+
+    /// Builds a block on top of `parent` with the given timestamp.
+    ///
+    /// A job that is resolved before it built anything yields an empty block, so this waits for
+    /// the first payload of the job, up to [FIRST_PAYLOAD_TIMEOUT].
+    pub(crate) async fn build(&self, parent: B256, timestamp: u64) -> Result<SealedBlock, String> {
+        let id = self
+            .handle
+            .new_payload((self.attributes)(parent, timestamp))
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let deadline = Instant::now() + FIRST_PAYLOAD_TIMEOUT;
+        while Instant::now() < deadline {
+            if self.handle.built_payloads().await.iter().any(|(built, _)| *built == id) {
+                break
+            }
+            tokio::time::sleep(FIRST_PAYLOAD_POLL_INTERVAL).await;
+        }
+
+        let payload = PayloadStore::from(self.handle.clone())
+            .resolve(id)
+            .await
+            .ok_or_else(|| format!("payload job {id} not found"))?
+            .map_err(|err| err.to_string())?;
+        Ok(payload.block().clone())
+    }
+}
+
+impl<Engine: EngineTypes> Clone for AutoSealPayloadBuilder<Engine> {
+    fn clone(&self) -> Self {
+        Self { handle: self.handle.clone(), attributes: Arc::clone(&self.attributes) }
+    }
+}
+
+impl<Engine: EngineTypes> fmt::Debug for AutoSealPayloadBuilder<Engine> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoSealPayloadBuilder")
+            .field("handle", &self.handle)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,60 @@
+//! `evm_` namespace implementation for dev mode.
+
+use crate::{MiningControl, MiningControlError};
+use async_trait::async_trait;
+use jsonrpsee::{
+    core::RpcResult,
+    types::error::{ErrorObject, INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+};
+use reth_primitives::U64;
+use reth_rpc_api::EvmApiServer;
+use std::time::Duration;
+
+/// `evm` API implementation backed by the [MiningControl] of the auto seal miner.
+#[derive(Debug, Clone)]
+pub struct EvmApi {
+    control: MiningControl,
+}
+
+impl EvmApi {
+    /// Creates a new instance of the [EvmApi]
+    pub fn new(control: MiningControl) -> Self {
+        Self { control }
+    }
+}
+
+#[async_trait]
+impl EvmApiServer for EvmApi {
+    /// Handler for `evm_mine`
+    async fn evm_mine(&self, blocks: Option<U64>) -> RpcResult<String> {
+        let blocks = blocks.map(|b| b.to::<u64>()).unwrap_or(1);
+        self.control.mine(blocks).await.map_err(to_rpc_err)?;
+        Ok("0x0".to_string())
+    }
+
+    /// Handler for `evm_setNextBlockTimestamp`
+    async fn evm_set_next_block_timestamp(&self, timestamp: U64) -> RpcResult<()> {
+        self.control.set_next_block_timestamp(timestamp.to()).await.map_err(to_rpc_err)
+    }
+
+    /// Handler for `evm_increaseTime`
+    async fn evm_increase_time(&self, seconds: U64) -> RpcResult<U64> {
+        Ok(U64::from(self.control.increase_time(seconds.to()).await))
+    }
+
+    /// Handler for `evm_setIntervalMining`
+    async fn evm_set_interval_mining(&self, interval_ms: u64) -> RpcResult<()> {
+        let interval = (interval_ms > 0).then(|| Duration::from_millis(interval_ms));
+        self.control.set_interval_mining(interval).map_err(to_rpc_err)
+    }
+}
+
+fn to_rpc_err(err: MiningControlError) -> ErrorObject<'static> {
+    let code = match &err {
+        MiningControlError::TaskClosed | MiningControlError::Execution(_) => INTERNAL_ERROR_CODE,
+        MiningControlError::TooManyBlocks { .. } | MiningControlError::TimestampTooLow { .. } => {
+            INVALID_PARAMS_CODE
+        }
+    };
+    ErrorObject::owned(code, err.to_string(), None::<()>)
+}
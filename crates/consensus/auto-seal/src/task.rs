@@ -1,12 +1,17 @@
-use crate::{mode::MiningMode, Storage};
-use futures_util::{future::BoxFuture, FutureExt};
+use crate::{
+    control::{MineListener, MiningCommand, MiningControl, MiningControlError},
+    mode::MiningMode,
+    payload::AutoSealPayloadBuilder,
+    Storage,
+};
+use futures_util::{future::BoxFuture, FutureExt, StreamExt};
 use reth_beacon_consensus::{BeaconEngineMessage, ForkchoiceStatus};
 use reth_interfaces::consensus::ForkchoiceState;
 use reth_node_api::EngineTypes;
 use reth_primitives::{Block, ChainSpec, IntoRecoveredTransaction, SealedBlockWithSenders};
 use reth_provider::{CanonChainTracker, CanonStateNotificationSender, Chain, StateProviderFactory};
 use reth_stages::PipelineEvent;
-use reth_transaction_pool::{TransactionPool, ValidPoolTransaction};
+use reth_transaction_pool::{PoolTransaction, TransactionPool, ValidPoolTransaction};
 use std::{
     collections::VecDeque,
     future::Future,
@@ -14,10 +19,27 @@ use std::{
     sync::Arc,
     task::{Context, Poll},
 };
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedSender},
+    oneshot,
+};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, error, warn};
 
+/// Blocks waiting to be mined.
+struct QueuedBlocks<T: PoolTransaction> {
+    /// The transactions of the first block, any further blocks are empty.
+    transactions: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// The number of blocks to mine.
+    blocks: u64,
+    /// Notified once the last block was made canonical, or with the error if a block could not
+    /// be mined.
+    listener: Option<MineListener>,
+}
+
+/// The outcome of an insert task, the pipeline events are handed back to the [MiningTask].
+type InsertOutcome = (Option<UnboundedReceiverStream<PipelineEvent>>, Result<(), String>);
+
 /// A Future that listens for new ready transactions and puts new blocks into storage
 pub struct MiningTask<Client, Pool: TransactionPool, Engine: EngineTypes> {
     /// The configured chain spec
@@ -27,13 +49,21 @@ pub struct MiningTask<Client, Pool: TransactionPool, Engine: EngineTypes> {
     /// The active miner
     miner: MiningMode,
     /// Single active future that inserts a new block into `storage`
-    insert_task: Option<BoxFuture<'static, Option<UnboundedReceiverStream<PipelineEvent>>>>,
+    insert_task: Option<BoxFuture<'static, InsertOutcome>>,
+    /// The blocks the active insert task is mining, without the transactions it took
+    mining: Option<QueuedBlocks<<Pool as TransactionPool>::Transaction>>,
     /// Shared storage to insert new blocks
     storage: Storage,
     /// Pool where transactions are stored
     pool: Pool,
-    /// backlog of sets of transactions ready to be mined
-    queued: VecDeque<Vec<Arc<ValidPoolTransaction<<Pool as TransactionPool>::Transaction>>>>,
+    /// backlog of blocks ready to be mined
+    queued: VecDeque<QueuedBlocks<<Pool as TransactionPool>::Transaction>>,
+    /// Builds the blocks with the payload builder of the node, if set
+    payload_builder: Option<AutoSealPayloadBuilder<Engine>>,
+    /// Handle that can be used to manually control mining
+    control: MiningControl,
+    /// Incoming commands from [MiningControl] handles
+    commands: UnboundedReceiverStream<MiningCommand>,
     // TODO: ideally this would just be a sender of hashes
     to_engine: UnboundedSender<BeaconEngineMessage<Engine>>,
     /// Used to notify consumers of new blocks
@@ -46,6 +76,7 @@ pub struct MiningTask<Client, Pool: TransactionPool, Engine: EngineTypes> {
 
 impl<Client, Pool: TransactionPool, Engine: EngineTypes> MiningTask<Client, Pool, Engine> {
     /// Creates a new instance of the task
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         chain_spec: Arc<ChainSpec>,
         miner: MiningMode,
@@ -54,14 +85,20 @@ impl<Client, Pool: TransactionPool, Engine: EngineTypes> MiningTask<Client, Pool
         storage: Storage,
         client: Client,
         pool: Pool,
+        payload_builder: Option<AutoSealPayloadBuilder<Engine>>,
     ) -> Self {
+        let (to_task, commands) = unbounded_channel();
         Self {
+            control: MiningControl::new(to_task, storage.clone()),
+            commands: UnboundedReceiverStream::new(commands),
             chain_spec,
             client,
             miner,
             insert_task: None,
+            mining: None,
             storage,
             pool,
+            payload_builder,
             to_engine,
             canon_state_notification,
             queued: Default::default(),
@@ -69,10 +106,42 @@ impl<Client, Pool: TransactionPool, Engine: EngineTypes> MiningTask<Client, Pool
         }
     }
 
+    /// Returns a [MiningControl] handle that can be used to manually mine blocks and manipulate
+    /// block timestamps.
+    pub fn control(&self) -> MiningControl {
+        self.control.clone()
+    }
+
     /// Sets the pipeline events to listen on.
     pub fn set_pipeline_events(&mut self, events: UnboundedReceiverStream<PipelineEvent>) {
         self.pipe_line_events = Some(events);
     }
+
+    /// Handles the outcome of the finished insert task.
+    ///
+    /// The remaining blocks of a request are queued again in front, if mining a block failed
+    /// they are dropped and the listener is notified with the error.
+    fn on_insert_outcome(&mut self, result: Result<(), String>) {
+        let Some(QueuedBlocks { blocks, listener, .. }) = self.mining.take() else { return };
+        match result {
+            Ok(()) if blocks > 1 => self.queued.push_front(QueuedBlocks {
+                transactions: Vec::new(),
+                blocks: blocks - 1,
+                listener,
+            }),
+            Ok(()) => {
+                if let Some(listener) = listener {
+                    let _ = listener.send(Ok(()));
+                }
+            }
+            Err(err) => {
+                warn!(target: "consensus::auto", %err, remaining = blocks - 1, "failed to mine block");
+                if let Some(listener) = listener {
+                    let _ = listener.send(Err(MiningControlError::Execution(err)));
+                }
+            }
+        }
+    }
 }
 
 impl<Client, Pool, Engine> Future for MiningTask<Client, Pool, Engine>
@@ -89,25 +158,52 @@ where
 
         // this drives block production and
         loop {
+            // handle manual mining commands
+            while let Poll::Ready(Some(cmd)) = this.commands.poll_next_unpin(cx) {
+                match cmd {
+                    MiningCommand::Mine { blocks, tx } => {
+                        if blocks == 0 {
+                            let _ = tx.send(Ok(()));
+                            continue
+                        }
+                        // all ready transactions go into the first block, the rest are empty
+                        this.queued.push_back(QueuedBlocks {
+                            transactions: this.pool.best_transactions().collect(),
+                            blocks,
+                            listener: Some(tx),
+                        });
+                    }
+                    MiningCommand::SetInterval(interval) => {
+                        debug!(target: "consensus::auto", ?interval, "Updating interval mining");
+                        this.miner = match interval {
+                            Some(interval) => MiningMode::interval(interval),
+                            None => MiningMode::None,
+                        };
+                    }
+                }
+            }
+
             if let Poll::Ready(transactions) = this.miner.poll(&this.pool, cx) {
                 // miner returned a set of transaction that we feed to the producer
-                this.queued.push_back(transactions);
+                this.queued.push_back(QueuedBlocks { transactions, blocks: 1, listener: None });
             }
 
             if this.insert_task.is_none() {
-                if this.queued.is_empty() {
+                let Some(mut queued) = this.queued.pop_front() else {
                     // nothing to insert
                     break
-                }
+                };
 
                 // ready to queue in new insert task
-                let storage = this.storage.clone();
-                let transactions = this.queued.pop_front().expect("not empty");
+                let transactions = std::mem::take(&mut queued.transactions);
+                this.mining = Some(queued);
 
+                let storage = this.storage.clone();
                 let to_engine = this.to_engine.clone();
                 let client = this.client.clone();
                 let chain_spec = Arc::clone(&this.chain_spec);
                 let pool = this.pool.clone();
+                let payload_builder = this.payload_builder.clone();
                 let events = this.pipe_line_events.take();
                 let canon_state_notification = this.canon_state_notification.clone();
 
@@ -116,107 +212,125 @@ where
                 this.insert_task = Some(Box::pin(async move {
                     let mut storage = storage.write().await;
 
-                    let (transactions, senders): (Vec<_>, Vec<_>) = transactions
-                        .into_iter()
-                        .map(|tx| {
-                            let recovered = tx.to_recovered_transaction();
-                            let signer = recovered.signer();
-                            (recovered.into_signed(), signer)
-                        })
-                        .unzip();
-
-                    match storage.build_and_execute(transactions.clone(), &client, chain_spec) {
-                        Ok((new_header, bundle_state)) => {
-                            // clear all transactions from pool
-                            pool.remove_transactions(
-                                transactions.iter().map(|tx| tx.hash()).collect(),
-                            );
-
-                            let state = ForkchoiceState {
-                                head_block_hash: new_header.hash,
-                                finalized_block_hash: new_header.hash,
-                                safe_block_hash: new_header.hash,
-                            };
-                            drop(storage);
-
-                            // TODO: make this a future
-                            // await the fcu call rx for SYNCING, then wait for a VALID response
-                            loop {
-                                // send the new update to the engine, this will trigger the engine
-                                // to download and execute the block we just inserted
-                                let (tx, rx) = oneshot::channel();
-                                let _ = to_engine.send(BeaconEngineMessage::ForkchoiceUpdated {
-                                    state,
-                                    payload_attrs: None,
-                                    tx,
-                                });
-                                debug!(target: "consensus::auto", ?state, "Sent fork choice update");
-
-                                match rx.await.unwrap() {
-                                    Ok(fcu_response) => {
-                                        match fcu_response.forkchoice_status() {
-                                            ForkchoiceStatus::Valid => break,
-                                            ForkchoiceStatus::Invalid => {
-                                                error!(target: "consensus::auto", ?fcu_response, "Forkchoice update returned invalid response");
-                                                return None
-                                            }
-                                            ForkchoiceStatus::Syncing => {
-                                                debug!(target: "consensus::auto", ?fcu_response, "Forkchoice update returned SYNCING, waiting for VALID");
-                                                // wait for the next fork choice update
-                                                continue
-                                            }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        error!(target: "consensus::auto", ?err, "Autoseal fork choice update failed");
-                                        return None
-                                    }
-                                }
+                    let built = match payload_builder {
+                        Some(payload_builder) => {
+                            let timestamp = storage.next_block_timestamp();
+                            match payload_builder.build(storage.best_hash, timestamp).await {
+                                Ok(block) => storage
+                                    .insert_built_block(block, &client, chain_spec)
+                                    .map_err(|err| err.to_string()),
+                                Err(err) => Err(err),
                             }
+                        }
+                        None => {
+                            let (transactions, senders): (Vec<_>, Vec<_>) = transactions
+                                .into_iter()
+                                .map(|tx| {
+                                    let recovered = tx.to_recovered_transaction();
+                                    let signer = recovered.signer();
+                                    (recovered.into_signed(), signer)
+                                })
+                                .unzip();
 
-                            // seal the block
-                            let block = Block {
-                                header: new_header.clone().unseal(),
-                                body: transactions,
-                                ommers: vec![],
-                                withdrawals: None,
-                            };
-                            let sealed_block = block.seal_slow();
-
-                            let sealed_block_with_senders =
-                                SealedBlockWithSenders::new(sealed_block, senders)
-                                    .expect("senders are valid");
-
-                            // update canon chain for rpc
-                            client.set_canonical_head(new_header.clone());
-                            client.set_safe(new_header.clone());
-                            client.set_finalized(new_header.clone());
-
-                            debug!(target: "consensus::auto", header=?sealed_block_with_senders.hash(), "sending block notification");
-
-                            let chain = Arc::new(Chain::new(
-                                vec![sealed_block_with_senders],
-                                bundle_state,
-                                None,
-                            ));
-
-                            // send block notification
-                            let _ = canon_state_notification
-                                .send(reth_provider::CanonStateNotification::Commit { new: chain });
+                            storage
+                                .build_and_execute(transactions.clone(), &client, chain_spec)
+                                .map(|(new_header, bundle_state)| {
+                                    // seal the block
+                                    let block = Block {
+                                        header: new_header.unseal(),
+                                        body: transactions,
+                                        ommers: vec![],
+                                        withdrawals: None,
+                                    };
+                                    let sealed_block_with_senders =
+                                        SealedBlockWithSenders::new(block.seal_slow(), senders)
+                                            .expect("senders are valid");
+                                    (sealed_block_with_senders, bundle_state)
+                                })
+                                .map_err(|err| err.to_string())
                         }
-                        Err(err) => {
-                            warn!(target: "consensus::auto", ?err, "failed to execute block")
+                    };
+                    drop(storage);
+
+                    let (sealed_block_with_senders, bundle_state) = match built {
+                        Ok(built) => built,
+                        Err(err) => return (events, Err(err)),
+                    };
+
+                    // clear all transactions from pool
+                    pool.remove_transactions(
+                        sealed_block_with_senders.body.iter().map(|tx| tx.hash()).collect(),
+                    );
+
+                    let new_header = sealed_block_with_senders.header.clone();
+                    let state = ForkchoiceState {
+                        head_block_hash: new_header.hash,
+                        finalized_block_hash: new_header.hash,
+                        safe_block_hash: new_header.hash,
+                    };
+
+                    // TODO: make this a future
+                    // await the fcu call rx for SYNCING, then wait for a VALID response
+                    loop {
+                        // send the new update to the engine, this will trigger the engine
+                        // to download and execute the block we just inserted
+                        let (tx, rx) = oneshot::channel();
+                        let _ = to_engine.send(BeaconEngineMessage::ForkchoiceUpdated {
+                            state,
+                            payload_attrs: None,
+                            tx,
+                        });
+                        debug!(target: "consensus::auto", ?state, "Sent fork choice update");
+
+                        let Ok(response) = rx.await else {
+                            return (events, Err("consensus engine is not running".to_string()))
+                        };
+                        match response {
+                            Ok(fcu_response) => match fcu_response.forkchoice_status() {
+                                ForkchoiceStatus::Valid => break,
+                                ForkchoiceStatus::Invalid => {
+                                    error!(target: "consensus::auto", ?fcu_response, "Forkchoice update returned invalid response");
+                                    return (
+                                        events,
+                                        Err("forkchoice update returned invalid status".to_string()),
+                                    )
+                                }
+                                ForkchoiceStatus::Syncing => {
+                                    debug!(target: "consensus::auto", ?fcu_response, "Forkchoice update returned SYNCING, waiting for VALID");
+                                    // wait for the next fork choice update
+                                    continue
+                                }
+                            },
+                            Err(err) => {
+                                error!(target: "consensus::auto", ?err, "Autoseal fork choice update failed");
+                                return (events, Err(format!("forkchoice update failed: {err}")))
+                            }
                         }
                     }
 
-                    events
+                    // update canon chain for rpc
+                    client.set_canonical_head(new_header.clone());
+                    client.set_safe(new_header.clone());
+                    client.set_finalized(new_header);
+
+                    debug!(target: "consensus::auto", header=?sealed_block_with_senders.hash(), "sending block notification");
+
+                    let chain =
+                        Arc::new(Chain::new(vec![sealed_block_with_senders], bundle_state, None));
+
+                    // send block notification
+                    let _ = canon_state_notification
+                        .send(reth_provider::CanonStateNotification::Commit { new: chain });
+
+                    (events, Ok(()))
                 }));
             }
 
             if let Some(mut fut) = this.insert_task.take() {
                 match fut.poll_unpin(cx) {
-                    Poll::Ready(events) => {
+                    Poll::Ready((events, result)) => {
                         this.pipe_line_events = events;
+                        this.on_insert_outcome(result);
                     }
                     Poll::Pending => {
                         this.insert_task = Some(fut);
@@ -85,13 +85,13 @@ pub(crate) fn test_auto_mine() {
     assert!(node_command.is_ok())
 }
 
-fn custom_chain() -> Arc<ChainSpec> {
+pub(crate) fn custom_chain() -> Arc<ChainSpec> {
     let custom_genesis = r#"
 {
     "nonce": "0x42",
     "timestamp": "0x0",
     "extraData": "0x5343",
-    "gasLimit": "0x1388",
+    "gasLimit": "0x1c9c380",
     "difficulty": "0x400000000",
     "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
     "coinbase": "0x0000000000000000000000000000000000000000",
@@ -0,0 +1,142 @@
+//! `evm_` namespace integration tests
+
+use crate::auto_mine::custom_chain;
+use clap::Parser;
+use jsonrpsee::{core::client::ClientT, http_client::HttpClientBuilder, rpc_params};
+use reth::{
+    cli::{
+        components::RethNodeComponents,
+        ext::{NoArgs, NoArgsCliExt, RethNodeCommandConfig},
+    },
+    commands::node::NodeCommand,
+    runner::CliRunner,
+    tasks::TaskSpawner,
+};
+use reth_provider::CanonStateSubscriptions;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// The http port of a node started with `--instance 2`.
+const EVM_MINE_HTTP_PORT: u16 = 8544;
+
+/// The http port of a node started with `--instance 3`.
+const INTERVAL_MINING_HTTP_PORT: u16 = 8543;
+
+#[derive(Debug)]
+struct EvmMineConfig;
+
+impl RethNodeCommandConfig for EvmMineConfig {
+    fn on_node_started<Reth: RethNodeComponents>(&mut self, components: &Reth) -> eyre::Result<()> {
+        let mut canon_events = components.events().subscribe_to_canonical_state();
+
+        components.task_executor().spawn_critical_blocking(
+            "rpc request",
+            Box::pin(async move {
+                let client = HttpClientBuilder::default()
+                    .build(format!("http://127.0.0.1:{EVM_MINE_HTTP_PORT}"))
+                    .expect("http client should bind to instance rpc port");
+
+                // requests above the limit are rejected without mining anything
+                let err = client
+                    .request::<String, _>("evm_mine", rpc_params!["0x401"])
+                    .await
+                    .expect_err("too many blocks");
+                assert!(err.to_string().contains("cannot mine 1025 blocks"), "{err}");
+
+                // returns once both blocks are canonical
+                let response: String = client
+                    .request("evm_mine", rpc_params!["0x2"])
+                    .await
+                    .expect("evm_mine should succeed");
+                assert_eq!(response, "0x0");
+
+                for number in 1..=2 {
+                    let update = timeout(Duration::from_secs(15), canon_events.recv())
+                        .await
+                        .expect("canon state should change before timeout")
+                        .expect("canon events stream is still open");
+                    assert_eq!(update.tip().number, number);
+                }
+            }),
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct IntervalMiningConfig;
+
+impl RethNodeCommandConfig for IntervalMiningConfig {
+    fn on_node_started<Reth: RethNodeComponents>(&mut self, components: &Reth) -> eyre::Result<()> {
+        let mut canon_events = components.events().subscribe_to_canonical_state();
+
+        components.task_executor().spawn_critical_blocking(
+            "rpc request",
+            Box::pin(async move {
+                let client = HttpClientBuilder::default()
+                    .build(format!("http://127.0.0.1:{INTERVAL_MINING_HTTP_PORT}"))
+                    .expect("http client should bind to instance rpc port");
+
+                // no transactions are submitted, so no block is mined until interval mining is on
+                assert!(timeout(Duration::from_secs(2), canon_events.recv()).await.is_err());
+
+                let _: () = client
+                    .request("evm_setIntervalMining", rpc_params![500u64])
+                    .await
+                    .expect("evm_setIntervalMining should succeed");
+
+                // empty blocks are mined on every tick
+                for number in 1..=2 {
+                    let update = timeout(Duration::from_secs(15), canon_events.recv())
+                        .await
+                        .expect("canon state should change before timeout")
+                        .expect("canon events stream is still open");
+                    let tip = update.tip();
+                    assert_eq!(tip.number, number);
+                    assert!(tip.body.is_empty());
+                }
+            }),
+        );
+        Ok(())
+    }
+}
+
+/// Runs a dev node with the given instance number until it reached block 2.
+fn run_dev_node<Conf>(conf: Conf, instance: &str)
+where
+    Conf: RethNodeCommandConfig + Send + Sync + 'static,
+{
+    let temp_path = tempfile::TempDir::new().expect("tempdir is okay").into_path();
+    let datadir = temp_path.to_str().expect("temp path is okay");
+
+    let mut command = NodeCommand::<NoArgsCliExt<Conf>>::parse_from([
+        "reth",
+        "--dev",
+        "--http",
+        "--instance",
+        instance,
+        "--datadir",
+        datadir,
+        "--debug.max-block",
+        "2",
+        "--debug.terminate",
+    ])
+    .with_ext::<NoArgsCliExt<Conf>>(NoArgs::with(conf));
+    command.chain = custom_chain();
+
+    let runner = CliRunner::default();
+    let node_command = runner.run_command_until_exit(|ctx| command.execute(ctx));
+    assert!(node_command.is_ok())
+}
+
+#[test]
+#[cfg_attr(feature = "optimism", ignore)]
+fn test_evm_mine() {
+    run_dev_node(EvmMineConfig, "2");
+}
+
+#[test]
+#[cfg_attr(feature = "optimism", ignore)]
+fn test_evm_set_interval_mining() {
+    run_dev_node(IntervalMiningConfig, "3");
+}
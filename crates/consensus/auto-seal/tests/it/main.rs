@@ -1,4 +1,5 @@
 //! auto-mine consensus tests
 mod auto_mine;
+mod evm;
 
 async fn main() {}
@@ -2,7 +2,7 @@
 
 use crate::{
     engine::message::OnForkChoiceUpdated, BeaconConsensusEngineEvent, BeaconEngineMessage,
-    BeaconForkChoiceUpdateError, BeaconOnNewPayloadError,
+    BeaconForkChoiceUpdateError, BeaconOnNewPayloadError, PayloadPrevalidator,
 };
 use futures::TryFutureExt;
 use reth_interfaces::RethResult;
@@ -23,6 +23,8 @@ where
     Engine: EngineTypes,
 {
     pub(crate) to_engine: UnboundedSender<BeaconEngineMessage<Engine>>,
+    /// Runs the stateless checks of new payloads before they reach the engine, if configured.
+    pub(crate) prevalidator: Option<PayloadPrevalidator>,
 }
 
 impl<Engine> Clone for BeaconConsensusEngineHandle<Engine>
@@ -30,7 +32,7 @@ where
     Engine: EngineTypes,
 {
     fn clone(&self) -> Self {
-        Self { to_engine: self.to_engine.clone() }
+        Self { to_engine: self.to_engine.clone(), prevalidator: self.prevalidator.clone() }
     }
 }
 
@@ -42,7 +44,14 @@ where
 {
     /// Creates a new beacon consensus engine handle.
     pub fn new(to_engine: UnboundedSender<BeaconEngineMessage<Engine>>) -> Self {
-        Self { to_engine }
+        Self { to_engine, prevalidator: None }
+    }
+
+    /// Sets the [PayloadPrevalidator] that starts the stateless checks of new payloads before they
+    /// are sent to the engine.
+    pub fn with_prevalidator(mut self, prevalidator: PayloadPrevalidator) -> Self {
+        self.prevalidator = Some(prevalidator);
+        self
     }
 
     /// Sends a new payload message to the beacon consensus engine and waits for a response.
@@ -54,7 +63,18 @@ where
         cancun_fields: Option<CancunPayloadFields>,
     ) -> Result<PayloadStatus, BeaconOnNewPayloadError> {
         let (tx, rx) = oneshot::channel();
-        let _ = self.to_engine.send(BeaconEngineMessage::NewPayload { payload, cancun_fields, tx });
+        // start the stateless checks right away, so they overlap with the engine processing
+        // earlier messages
+        let prevalidation = self
+            .prevalidator
+            .as_ref()
+            .map(|prevalidator| prevalidator.spawn(payload.clone(), cancun_fields.clone()));
+        let _ = self.to_engine.send(BeaconEngineMessage::NewPayload {
+            payload,
+            cancun_fields,
+            prevalidation,
+            tx,
+        });
         rx.await.map_err(|_| BeaconOnNewPayloadError::EngineUnavailable)?
     }
 
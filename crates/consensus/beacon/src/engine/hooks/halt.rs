@@ -0,0 +1,105 @@
+//! Halt hook for the engine implementation.
+
+use crate::{
+    engine::hooks::{EngineContext, EngineHook, EngineHookEvent},
+    hooks::EngineHookDBAccessLevel,
+};
+use futures::StreamExt;
+use reth_interfaces::RethResult;
+use std::task::{Context, Poll};
+use tokio::sync::watch;
+use tokio_stream::wrappers::WatchStream;
+
+/// Halts the engine while a signal is set, e.g. while disk space is critically low.
+///
+/// The hook needs read-write access to the database, so it only starts once neither the pipeline
+/// nor another hook with read-write access is running. While it is running, the engine doesn't
+/// commit anything to the database and responds with `SYNCING` to forkchoice updates.
+#[derive(Debug)]
+pub struct HaltHook {
+    /// Changes of the halt signal.
+    signal: WatchStream<bool>,
+    /// The latest value of the halt signal.
+    halt_requested: bool,
+    /// Whether the hook is running, i.e. the engine is halted.
+    halted: bool,
+}
+
+impl HaltHook {
+    /// Create a new instance
+    pub fn new(signal: watch::Receiver<bool>) -> Self {
+        Self { signal: WatchStream::new(signal), halt_requested: false, halted: false }
+    }
+}
+
+impl EngineHook for HaltHook {
+    fn name(&self) -> &'static str {
+        "Halt"
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+        _ctx: EngineContext,
+    ) -> Poll<RethResult<EngineHookEvent>> {
+        loop {
+            match self.signal.poll_next_unpin(cx) {
+                Poll::Ready(Some(halt_requested)) => self.halt_requested = halt_requested,
+                // the signal can't be set anymore
+                Poll::Ready(None) => {
+                    self.halt_requested = false;
+                    break
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        match (self.halted, self.halt_requested) {
+            (false, true) => {
+                self.halted = true;
+                Poll::Ready(Ok(EngineHookEvent::Started))
+            }
+            (true, false) => {
+                self.halted = false;
+                Poll::Ready(Ok(EngineHookEvent::Finished(Ok(()))))
+            }
+            _ => Poll::Pending,
+        }
+    }
+
+    fn db_access_level(&self) -> EngineHookDBAccessLevel {
+        EngineHookDBAccessLevel::ReadWrite
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::poll;
+    use std::future::poll_fn;
+
+    #[tokio::test]
+    async fn halts_while_signal_is_set() {
+        let (tx, rx) = watch::channel(false);
+        let mut hook = HaltHook::new(rx);
+        let context = EngineContext { tip_block_number: 2, finalized_block_number: Some(1) };
+
+        assert!(poll!(poll_fn(|cx| hook.poll(cx, context))).is_pending());
+
+        tx.send_replace(true);
+        let event = poll!(poll_fn(|cx| hook.poll(cx, context)));
+        assert!(matches!(event, Poll::Ready(Ok(EngineHookEvent::Started))));
+        assert!(poll!(poll_fn(|cx| hook.poll(cx, context))).is_pending());
+
+        tx.send_replace(false);
+        let event = poll!(poll_fn(|cx| hook.poll(cx, context)));
+        assert!(matches!(event, Poll::Ready(Ok(EngineHookEvent::Finished(Ok(()))))));
+
+        tx.send_replace(true);
+        assert!(poll!(poll_fn(|cx| hook.poll(cx, context))).is_ready());
+        // the engine resumes if the signal can't be cleared anymore
+        drop(tx);
+        let event = poll!(poll_fn(|cx| hook.poll(cx, context)));
+        assert!(matches!(event, Poll::Ready(Ok(EngineHookEvent::Finished(Ok(()))))));
+    }
+}
@@ -8,6 +8,9 @@ use std::{
 mod controller;
 pub(crate) use controller::{EngineHooksController, PolledHook};
 
+mod halt;
+pub use halt::HaltHook;
+
 mod prune;
 pub use prune::PruneHook;
 
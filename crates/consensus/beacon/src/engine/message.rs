@@ -1,5 +1,8 @@
 use crate::{
-    engine::{error::BeaconOnNewPayloadError, forkchoice::ForkchoiceStatus},
+    engine::{
+        error::BeaconOnNewPayloadError, forkchoice::ForkchoiceStatus,
+        prevalidation::PendingPrevalidation,
+    },
     BeaconConsensusEngineEvent,
 };
 use futures::{future::Either, FutureExt};
@@ -148,6 +151,10 @@ pub enum BeaconEngineMessage<Engine: EngineTypes> {
         payload: ExecutionPayload,
         /// The cancun-related newPayload fields, if any.
         cancun_fields: Option<CancunPayloadFields>,
+        /// The stateless checks of the payload that were started when it was received, if any.
+        ///
+        /// If absent, the engine runs the checks itself.
+        prevalidation: Option<PendingPrevalidation>,
         /// The sender for returning payload status result.
         tx: oneshot::Sender<Result<PayloadStatus, BeaconOnNewPayloadError>>,
     },
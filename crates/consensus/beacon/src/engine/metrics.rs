@@ -21,6 +21,8 @@ pub(crate) struct EngineMetrics {
     pub(crate) make_canonical_error_latency: Histogram,
     /// Latency for all making canonical results
     pub(crate) make_canonical_latency: Histogram,
+    /// Time the engine waited for the pre-validation of a new payload to finish
+    pub(crate) prevalidation_wait_duration: Histogram,
 }
 
 /// Metrics for the `EngineSyncController`.
@@ -30,3 +32,17 @@ pub(crate) struct EngineSyncMetrics {
     /// How many blocks are currently being downloaded.
     pub(crate) active_block_downloads: Gauge,
 }
+
+/// Metrics for the stateless pre-validation of new payloads.
+#[derive(Metrics)]
+#[metrics(scope = "consensus.engine.beacon.prevalidation")]
+pub(crate) struct PayloadPrevalidationMetrics {
+    /// Time a payload waited for a free worker of the pre-validation pool
+    pub(crate) queue_duration: Histogram,
+    /// Time spent converting the payload into a block and validating its hash
+    pub(crate) well_formed_duration: Histogram,
+    /// Time spent on the stateless body checks
+    pub(crate) body_checks_duration: Histogram,
+    /// Time spent recovering the transaction senders
+    pub(crate) sender_recovery_duration: Histogram,
+}
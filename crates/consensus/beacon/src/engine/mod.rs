@@ -24,14 +24,18 @@ use reth_node_api::{EngineTypes, PayloadAttributes, PayloadBuilderAttributes};
 use reth_payload_builder::PayloadBuilderHandle;
 use reth_primitives::{
     constants::EPOCH_SLOTS, stage::StageId, BlockNumHash, BlockNumber, Head, Header, SealedBlock,
-    SealedHeader, B256,
+    SealedBlockWithSenders, SealedHeader, B256,
 };
 use reth_provider::{
     BlockIdReader, BlockReader, BlockSource, CanonChainTracker, ChainSpecProvider, ProviderError,
     StageCheckpointReader,
 };
-use reth_rpc_types::engine::{
-    CancunPayloadFields, ExecutionPayload, PayloadStatus, PayloadStatusEnum, PayloadValidationError,
+use reth_rpc_types::{
+    engine::{
+        CancunPayloadFields, ExecutionPayload, PayloadStatus, PayloadStatusEnum,
+        PayloadValidationError,
+    },
+    PayloadError,
 };
 
 use reth_stages::{ControlFlow, Pipeline, PipelineError};
@@ -69,6 +73,10 @@ pub use event::BeaconConsensusEngineEvent;
 mod handle;
 pub use handle::BeaconConsensusEngineHandle;
 
+mod prevalidation;
+use prevalidation::PrevalidationError;
+pub use prevalidation::{PayloadPrevalidator, PendingPrevalidation};
+
 mod forkchoice;
 use crate::hooks::{EngineHookEvent, EngineHooks, PolledHook};
 pub use forkchoice::ForkchoiceStatus;
@@ -287,7 +295,13 @@ where
         rx: UnboundedReceiver<BeaconEngineMessage<EngineT>>,
         hooks: EngineHooks,
     ) -> RethResult<(Self, BeaconConsensusEngineHandle<EngineT>)> {
-        let handle = BeaconConsensusEngineHandle { to_engine };
+        let payload_validator = ExecutionPayloadValidator::new(blockchain.chain_spec());
+        let prevalidator = PayloadPrevalidator::with_default_pool(payload_validator.clone())
+            .map_err(|err| {
+                warn!(target: "consensus::engine", %err, "Failed to build payload pre-validation pool, validating payloads on the engine task");
+            })
+            .ok();
+        let handle = BeaconConsensusEngineHandle { to_engine, prevalidator };
         let sync = EngineSyncController::new(
             pipeline,
             client,
@@ -298,7 +312,7 @@ where
         );
         let mut this = Self {
             sync,
-            payload_validator: ExecutionPayloadValidator::new(blockchain.chain_spec()),
+            payload_validator,
             blockchain,
             sync_state_updater,
             engine_message_rx: UnboundedReceiverStream::new(rx),
@@ -1069,10 +1083,34 @@ where
         &mut self,
         payload: ExecutionPayload,
         cancun_fields: Option<CancunPayloadFields>,
+        prevalidation: Option<PendingPrevalidation>,
     ) -> Result<PayloadStatus, BeaconOnNewPayloadError> {
-        let block = match self.ensure_well_formed_payload(payload, cancun_fields) {
-            Ok(block) => block,
-            Err(status) => return Ok(status),
+        let parent_hash = payload.parent_hash();
+        let prevalidated = prevalidation.and_then(|pending| {
+            let start = Instant::now();
+            let res = pending.wait();
+            self.metrics.prevalidation_wait_duration.record(start.elapsed());
+            res
+        });
+        let block = match prevalidated {
+            Some(Ok(block)) => block,
+            Some(Err(PrevalidationError::Payload(error))) => {
+                return Ok(self.invalid_payload_status(parent_hash, error))
+            }
+            Some(Err(PrevalidationError::Block(error))) => return self.map_insert_error(error),
+            None => {
+                // the checks were not started or the pool dropped them, so run them here
+                let block = match self.ensure_well_formed_payload(payload, cancun_fields) {
+                    Ok(block) => block,
+                    Err(status) => return Ok(status),
+                };
+                match block.try_seal_with_senders() {
+                    Ok(block) => block,
+                    Err(block) => {
+                        return self.map_insert_error(InsertBlockError::sender_recovery_error(block))
+                    }
+                }
+            }
         };
         let block_hash = block.hash();
         let block_num_hash = block.num_hash();
@@ -1154,26 +1192,28 @@ where
     ) -> Result<SealedBlock, PayloadStatus> {
         let parent_hash = payload.parent_hash();
 
-        match self.payload_validator.ensure_well_formed_payload(payload, cancun_fields.into()) {
-            Ok(block) => Ok(block),
-            Err(error) => {
-                error!(target: "consensus::engine", ?error, "Invalid payload");
-                // we need to convert the error to a payload status (response to the CL)
-
-                let latest_valid_hash =
-                    if error.is_block_hash_mismatch() || error.is_invalid_versioned_hashes() {
-                        // Engine-API rules:
-                        // > `latestValidHash: null` if the blockHash validation has failed (<https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/shanghai.md?plain=1#L113>)
-                        // > `latestValidHash: null` if the expected and the actual arrays don't match (<https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/cancun.md?plain=1#L103>)
-                        None
-                    } else {
-                        self.latest_valid_hash_for_invalid_payload(parent_hash, None)
-                    };
+        self.payload_validator
+            .ensure_well_formed_payload(payload, cancun_fields.into())
+            .map_err(|error| self.invalid_payload_status(parent_hash, error))
+    }
 
-                let status = PayloadStatusEnum::from(error);
-                Err(PayloadStatus::new(status, latest_valid_hash))
-            }
-        }
+    /// Converts the error of a payload that is not well formed to a payload status (response to
+    /// the CL).
+    fn invalid_payload_status(&self, parent_hash: B256, error: PayloadError) -> PayloadStatus {
+        error!(target: "consensus::engine", ?error, "Invalid payload");
+
+        let latest_valid_hash =
+            if error.is_block_hash_mismatch() || error.is_invalid_versioned_hashes() {
+                // Engine-API rules:
+                // > `latestValidHash: null` if the blockHash validation has failed (<https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/shanghai.md?plain=1#L113>)
+                // > `latestValidHash: null` if the expected and the actual arrays don't match (<https://github.com/ethereum/execution-apis/blob/fe8e13c288c592ec154ce25c534e26cb7ce0530d/src/engine/cancun.md?plain=1#L103>)
+                None
+            } else {
+                self.latest_valid_hash_for_invalid_payload(parent_hash, None)
+            };
+
+        let status = PayloadStatusEnum::from(error);
+        PayloadStatus::new(status, latest_valid_hash)
     }
 
     /// Validates the payload attributes with respect to the header and fork choice state.
@@ -1240,9 +1280,9 @@ where
     #[instrument(level = "trace", skip_all, target = "consensus::engine", ret)]
     fn try_buffer_payload(
         &mut self,
-        block: SealedBlock,
+        block: SealedBlockWithSenders,
     ) -> Result<PayloadStatus, InsertBlockError> {
-        self.blockchain.buffer_block_without_senders(block)?;
+        self.blockchain.buffer_block(block)?;
         Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
     }
 
@@ -1252,16 +1292,15 @@ where
     #[instrument(level = "trace", skip_all, target = "consensus::engine", ret)]
     fn try_insert_new_payload(
         &mut self,
-        block: SealedBlock,
+        block: SealedBlockWithSenders,
     ) -> Result<PayloadStatus, InsertBlockError> {
         debug_assert!(self.sync.is_pipeline_idle(), "pipeline must be idle");
 
         let block_hash = block.hash;
-        let status = self
-            .blockchain
-            .insert_block_without_senders(block.clone(), BlockValidationKind::Exhaustive)?;
+        let sealed_block = block.block.clone();
+        let status = self.blockchain.insert_block(block, BlockValidationKind::Exhaustive)?;
         let mut latest_valid_hash = None;
-        let block = Arc::new(block);
+        let block = Arc::new(sealed_block);
         let status = match status {
             InsertPayloadOk::Inserted(BlockStatus::Valid) => {
                 latest_valid_hash = Some(block_hash);
@@ -1795,9 +1834,14 @@ where
                                 }
                             }
                         }
-                        BeaconEngineMessage::NewPayload { payload, cancun_fields, tx } => {
+                        BeaconEngineMessage::NewPayload {
+                            payload,
+                            cancun_fields,
+                            prevalidation,
+                            tx,
+                        } => {
                             this.metrics.new_payload_messages.increment(1);
-                            let res = this.on_new_payload(payload, cancun_fields);
+                            let res = this.on_new_payload(payload, cancun_fields, prevalidation);
                             let _ = tx.send(res);
                         }
                         BeaconEngineMessage::TransitionConfigurationExchanged => {
@@ -2345,12 +2389,20 @@ mod tests {
 
     mod new_payload {
         use super::*;
+        use reth_blockchain_tree::config::BlockchainTreeConfig;
         use reth_interfaces::test_utils::{generators, generators::random_block};
         use reth_primitives::{
             genesis::{Genesis, GenesisAllocator},
             Hardfork, U256,
         };
-        use reth_provider::test_utils::blocks::BlockChainTestData;
+        use reth_provider::test_utils::{
+            blocks::BlockChainTestData,
+            reorg::{
+                assert_canonical_branch, assert_cursor_cache_consistent, cursor_cache_root,
+                executor_results, ChainBranchBuilder, TestState,
+            },
+        };
+        use reth_trie::cached_cursors::CursorCache;
 
         #[tokio::test]
         async fn new_payload_before_forkchoice() {
@@ -2618,5 +2670,80 @@ mod tests {
 
             assert_matches!(engine_rx.try_recv(), Err(TryRecvError::Empty));
         }
+
+        #[tokio::test]
+        async fn reorg_to_competing_branch() {
+            let chain_spec = Arc::new(
+                ChainSpecBuilder::default()
+                    .chain(MAINNET.chain)
+                    .genesis(Genesis::default())
+                    .paris_activated()
+                    .build(),
+            );
+            let genesis =
+                SealedBlock { header: chain_spec.sealed_genesis_header(), ..Default::default() };
+
+            let first = ChainBranchBuilder::new(genesis.header.clone(), TestState::default())
+                .with_seed(1)
+                .with_depth(3)
+                .build();
+            let second = ChainBranchBuilder::new(genesis.header.clone(), TestState::default())
+                .with_seed(2)
+                .with_depth(4)
+                .with_branch_accounts(2)
+                .with_slots_per_account(4)
+                .build();
+
+            let (consensus_engine, env) = TestConsensusEngineBuilder::new(chain_spec.clone())
+                .with_pipeline_exec_outputs(VecDeque::from([Ok(ExecOutput {
+                    checkpoint: StageCheckpoint::new(0),
+                    done: true,
+                })]))
+                .with_executor_results(executor_results([&first, &second]))
+                .with_tree_config(BlockchainTreeConfig::new(4, 8, 4, 2))
+                .build();
+
+            insert_blocks(env.db.as_ref(), chain_spec.clone(), [&genesis].into_iter());
+            let factory = ProviderFactory::new(env.db.clone(), chain_spec);
+
+            let mut engine_rx = spawn_consensus_engine(consensus_engine);
+
+            let res = env
+                .send_forkchoice_updated(ForkchoiceState {
+                    head_block_hash: genesis.hash,
+                    finalized_block_hash: genesis.hash,
+                    ..Default::default()
+                })
+                .await;
+            let expected_result = PayloadStatus::from_status(PayloadStatusEnum::Valid)
+                .with_latest_valid_hash(genesis.hash);
+            assert_matches!(res, Ok(ForkchoiceUpdated { payload_status, .. }) => assert_eq!(payload_status, expected_result));
+
+            env.import_branch(&first).await;
+            assert_canonical_branch(&factory, &first, &[]);
+            let cache = CursorCache::default();
+            assert_cursor_cache_consistent(&factory, &cache, &first);
+
+            env.import_branch(&second).await;
+            assert_canonical_branch(&factory, &second, &[&first]);
+            // the cache populated before the reorg serves the reorged state
+            assert_ne!(cursor_cache_root(&factory, &cache), second.tip().state_root);
+            assert_cursor_cache_consistent(&factory, &CursorCache::default(), &second);
+
+            // the reorged blocks are kept in the tree, so switching back doesn't execute them
+            let head_block_hash = first.tip().hash();
+            let res = env
+                .send_forkchoice_retry_on_syncing(ForkchoiceState {
+                    head_block_hash,
+                    ..Default::default()
+                })
+                .await;
+            let expected_result = PayloadStatus::from_status(PayloadStatusEnum::Valid)
+                .with_latest_valid_hash(head_block_hash);
+            assert_matches!(res, Ok(ForkchoiceUpdated { payload_status, .. }) => assert_eq!(payload_status, expected_result));
+            assert_canonical_branch(&factory, &first, &[&second]);
+
+            assert_matches!(engine_rx.try_recv(), Err(TryRecvError::Empty));
+        }
     }
 }
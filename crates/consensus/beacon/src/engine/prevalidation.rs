@@ -0,0 +1,161 @@
+//! Stateless pre-validation of new payloads on a dedicated worker pool.
+
+use crate::engine::metrics::PayloadPrevalidationMetrics;
+use reth_consensus_common::validation::validate_block_standalone;
+use reth_interfaces::blockchain_tree::error::InsertBlockError;
+use reth_payload_validator::ExecutionPayloadValidator;
+use reth_primitives::SealedBlockWithSenders;
+use reth_rpc_types::{
+    engine::{CancunPayloadFields, ExecutionPayload},
+    PayloadError,
+};
+use std::{
+    fmt,
+    sync::{mpsc, Arc},
+    time::Instant,
+};
+use tracing::trace;
+
+/// The default maximum number of threads of the pre-validation pool.
+const MAX_PREVALIDATION_THREADS: usize = 4;
+
+/// The outcome of the stateless checks of a payload.
+pub(crate) type PrevalidationResult = Result<SealedBlockWithSenders, PrevalidationError>;
+
+/// Errors of the stateless checks of a payload.
+#[derive(Debug)]
+pub(crate) enum PrevalidationError {
+    /// The payload could not be converted into a well formed block.
+    Payload(PayloadError),
+    /// The block failed sender recovery or the stateless body checks.
+    Block(InsertBlockError),
+}
+
+/// Runs the stateless checks of new payloads on a dedicated worker pool.
+///
+/// The checks of a payload start as soon as the payload is received by the engine API, so they can
+/// overlap with the engine processing earlier messages, e.g. a forkchoice update. The engine only
+/// waits for the outcome once it reaches the payload.
+///
+/// The checks are:
+///    - conversion of the payload into a block and validation of the block hash, see
+///      [`ExecutionPayloadValidator::ensure_well_formed_payload`]
+///    - recovery of the transaction senders
+///    - the stateless body checks: ommers, transactions and withdrawals roots and blob gas
+///
+/// Blob KZG proofs are not part of execution payloads, the engine only validates the versioned
+/// hashes of the blob transactions.
+#[derive(Clone)]
+pub struct PayloadPrevalidator {
+    inner: Arc<PayloadPrevalidatorInner>,
+}
+
+impl PayloadPrevalidator {
+    /// Creates a new pre-validator with a pool of `num_threads` workers.
+    pub fn new(
+        validator: ExecutionPayloadValidator,
+        num_threads: usize,
+    ) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("payload-prevalidation-{i:02}"))
+            .build()?;
+        let inner = PayloadPrevalidatorInner {
+            pool,
+            validator,
+            metrics: PayloadPrevalidationMetrics::default(),
+        };
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// Creates a new pre-validator with a pool sized by the available parallelism, at most
+    /// [`MAX_PREVALIDATION_THREADS`] workers.
+    pub fn with_default_pool(
+        validator: ExecutionPayloadValidator,
+    ) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let num_threads = std::thread::available_parallelism()
+            .map_or(1, |num| num.get())
+            .min(MAX_PREVALIDATION_THREADS);
+        Self::new(validator, num_threads)
+    }
+
+    /// Spawns the stateless checks of the given payload onto the pool.
+    pub fn spawn(
+        &self,
+        payload: ExecutionPayload,
+        cancun_fields: Option<CancunPayloadFields>,
+    ) -> PendingPrevalidation {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let inner = Arc::clone(&self.inner);
+        let queued_at = Instant::now();
+        self.inner.pool.spawn(move || {
+            inner.metrics.queue_duration.record(queued_at.elapsed());
+            let _ = tx.send(inner.prevalidate(payload, cancun_fields));
+        });
+        PendingPrevalidation { rx }
+    }
+}
+
+impl fmt::Debug for PayloadPrevalidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PayloadPrevalidator")
+            .field("num_threads", &self.inner.pool.current_num_threads())
+            .finish_non_exhaustive()
+    }
+}
+
+struct PayloadPrevalidatorInner {
+    /// The pool the checks are run on.
+    pool: rayon::ThreadPool,
+    /// Validator for execution payloads.
+    validator: ExecutionPayloadValidator,
+    /// Timing of the individual checks.
+    metrics: PayloadPrevalidationMetrics,
+}
+
+impl PayloadPrevalidatorInner {
+    /// Runs all stateless checks of the payload.
+    fn prevalidate(
+        &self,
+        payload: ExecutionPayload,
+        cancun_fields: Option<CancunPayloadFields>,
+    ) -> PrevalidationResult {
+        let start = Instant::now();
+        let block = self
+            .validator
+            .ensure_well_formed_payload(payload, cancun_fields.into())
+            .map_err(PrevalidationError::Payload)?;
+        self.metrics.well_formed_duration.record(start.elapsed());
+
+        let start = Instant::now();
+        if let Err(error) = validate_block_standalone(&block, self.validator.chain_spec()) {
+            return Err(PrevalidationError::Block(InsertBlockError::consensus_error(error, block)))
+        }
+        self.metrics.body_checks_duration.record(start.elapsed());
+
+        // senders are recovered in parallel on the pool for large blocks
+        let start = Instant::now();
+        let block = block.try_seal_with_senders().map_err(|block| {
+            PrevalidationError::Block(InsertBlockError::sender_recovery_error(block))
+        })?;
+        self.metrics.sender_recovery_duration.record(start.elapsed());
+
+        trace!(target: "consensus::engine", hash = ?block.hash, number = block.number, "Pre-validated payload");
+        Ok(block)
+    }
+}
+
+/// The pending outcome of the stateless checks of a payload, see [`PayloadPrevalidator::spawn`].
+#[derive(Debug)]
+pub struct PendingPrevalidation {
+    rx: mpsc::Receiver<PrevalidationResult>,
+}
+
+impl PendingPrevalidation {
+    /// Blocks until the checks are done.
+    ///
+    /// Returns `None` if the pool dropped the job.
+    pub(crate) fn wait(self) -> Option<PrevalidationResult> {
+        self.rx.recv().ok()
+    }
+}
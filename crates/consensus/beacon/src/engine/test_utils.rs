@@ -26,15 +26,18 @@ use reth_node_builder::EthEngineTypes;
 use reth_payload_builder::test_utils::spawn_test_payload_service;
 use reth_primitives::{BlockNumber, ChainSpec, PruneModes, Receipt, B256, U256};
 use reth_provider::{
-    providers::BlockchainProvider, test_utils::TestExecutorFactory, BlockExecutor,
-    BundleStateWithReceipts, ExecutorFactory, HeaderSyncMode, ProviderFactory,
+    providers::BlockchainProvider,
+    test_utils::{reorg::ChainBranch, TestExecutorFactory},
+    BlockExecutor, BundleStateWithReceipts, ExecutorFactory, HeaderSyncMode, ProviderFactory,
     PrunableBlockExecutor,
 };
 use reth_prune::Pruner;
 use reth_revm::EvmProcessorFactory;
 use reth_rpc_types::engine::{
     CancunPayloadFields, ExecutionPayload, ForkchoiceState, ForkchoiceUpdated, PayloadStatus,
+    PayloadStatusEnum,
 };
+use reth_rpc_types_compat::engine::payload::try_block_to_payload_v1;
 use reth_stages::{sets::DefaultStages, test_utils::TestStages, ExecOutput, Pipeline, StageError};
 use reth_tasks::TokioTaskExecutor;
 use std::{collections::VecDeque, sync::Arc};
@@ -115,6 +118,43 @@ impl<DB> TestEnv<DB> {
             }
         }
     }
+
+    /// Sends the blocks of the branch as new payloads, then makes the tip of the branch canonical
+    /// like a consensus client that switches to a competing branch.
+    ///
+    /// Panics if the engine doesn't accept a block of the branch or its tip as the new head.
+    pub async fn import_branch(&self, branch: &ChainBranch) -> ForkchoiceUpdated {
+        for (block, _) in &branch.blocks {
+            let status = self
+                .send_new_payload_retry_on_syncing(
+                    try_block_to_payload_v1(block.block.clone()),
+                    None,
+                )
+                .await
+                .expect("failed to send new payload");
+            assert!(
+                matches!(status.status, PayloadStatusEnum::Valid | PayloadStatusEnum::Accepted),
+                "block {} was not accepted: {status:?}",
+                block.number
+            );
+        }
+
+        let head_block_hash = branch.tip().hash();
+        let updated = self
+            .send_forkchoice_retry_on_syncing(ForkchoiceState {
+                head_block_hash,
+                ..Default::default()
+            })
+            .await
+            .expect("failed to send forkchoice updated");
+        assert_eq!(
+            updated.payload_status,
+            PayloadStatus::from_status(PayloadStatusEnum::Valid)
+                .with_latest_valid_hash(head_block_hash),
+            "tip {head_block_hash} was not made canonical"
+        );
+        updated
+    }
 }
 
 // TODO: add with_consensus in case we want to use the TestConsensus purposeful failure - this
@@ -290,6 +330,7 @@ pub struct TestConsensusEngineBuilder {
     pipeline_run_threshold: Option<u64>,
     max_block: Option<BlockNumber>,
     consensus: TestConsensusConfig,
+    tree_config: BlockchainTreeConfig,
 }
 
 impl TestConsensusEngineBuilder {
@@ -302,6 +343,7 @@ impl TestConsensusEngineBuilder {
             pipeline_run_threshold: None,
             max_block: None,
             consensus: Default::default(),
+            tree_config: BlockchainTreeConfig::new(1, 2, 3, 2),
         }
     }
 
@@ -344,6 +386,12 @@ impl TestConsensusEngineBuilder {
         self
     }
 
+    /// Sets the configuration of the blockchain tree, e.g. to allow deeper reorgs.
+    pub fn with_tree_config(mut self, tree_config: BlockchainTreeConfig) -> Self {
+        self.tree_config = tree_config;
+        self
+    }
+
     /// Disables blockchain tree driven sync. This is the same as setting the pipeline run
     /// threshold to 0.
     pub fn disable_blockchain_tree_sync(mut self) -> Self {
@@ -511,7 +559,7 @@ where
 
         // Setup blockchain tree
         let externals = TreeExternals::new(provider_factory.clone(), consensus, executor_factory);
-        let config = BlockchainTreeConfig::new(1, 2, 3, 2);
+        let config = self.base_config.tree_config;
         let tree = ShareableBlockchainTree::new(
             BlockchainTree::new(externals, config, None).expect("failed to create tree"),
         );
@@ -36,6 +36,9 @@ pub enum DatabaseError {
     /// Failed to use the specified log level, as it's not available.
     #[error("log level {0:?} is not available")]
     LogLevelUnavailable(LogLevel),
+    /// Other database-related error.
+    #[error("{0}")]
+    Other(String),
 }
 
 impl From<DatabaseWriteError> for DatabaseError {
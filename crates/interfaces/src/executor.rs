@@ -29,6 +29,12 @@ pub enum BlockValidationError {
     /// Error when header bloom filter doesn't match expected value
     #[error("header bloom filter mismatch: {0}")]
     BloomLogDiff(GotExpectedBoxed<Bloom>),
+    /// Error when state root doesn't match expected value
+    #[error("state root mismatch: {0}")]
+    StateRootDiff(GotExpectedBoxed<B256>),
+    /// Error when the execution witness of a block is invalid or incomplete
+    #[error("invalid execution witness: {0}")]
+    InvalidWitness(ProviderError),
     /// Error when transaction gas limit exceeds available block gas
     #[error("transaction gas limit {transaction_gas_limit} is more than blocks available gas {block_available_gas}")]
     TransactionGasLimitMoreThanAvailableBlockGas {
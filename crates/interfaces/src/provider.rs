@@ -1,5 +1,5 @@
 use reth_primitives::{
-    Address, BlockHash, BlockHashOrNumber, BlockNumber, GotExpected, SnapshotSegment,
+    Address, BlockHash, BlockHashOrNumber, BlockNumber, GotExpected, PruneSegment, SnapshotSegment,
     TxHashOrNumber, TxNumber, B256, U256,
 };
 use std::path::PathBuf;
@@ -107,9 +107,24 @@ pub enum ProviderError {
     /// Root mismatch during unwind
     #[error("unwind merkle trie {0}")]
     UnwindStateRootMismatch(Box<RootMismatch>),
-    /// State is not available for the given block number because it is pruned.
-    #[error("state at block #{0} is pruned")]
-    StateAtBlockPruned(BlockNumber),
+    /// The data of the segment is not available for the given block because it is pruned.
+    #[error("{segment} of block #{block_number} is pruned, the earliest available block is #{earliest_available_block}")]
+    Pruned {
+        /// The pruned segment.
+        segment: PruneSegment,
+        /// The requested block.
+        block_number: BlockNumber,
+        /// The first block whose data of the segment is available.
+        earliest_available_block: BlockNumber,
+    },
+    /// The body and receipts of the given block were dropped by history expiry.
+    #[error("history of block #{block_number} has expired, the first available block is #{first_available_block}")]
+    HistoryExpired {
+        /// The requested block.
+        block_number: BlockNumber,
+        /// The first block with an available body and receipts.
+        first_available_block: BlockNumber,
+    },
     /// Provider does not support this particular request.
     #[error("this provider does not support this request")]
     UnsupportedProvider,
@@ -125,6 +140,12 @@ pub enum ProviderError {
     /// Error encountered when the block number conversion from U256 to u64 causes an overflow.
     #[error("failed to convert block number U256 to u64: {0}")]
     BlockNumberOverflow(U256),
+    /// The execution witness does not contain the requested state or is invalid.
+    #[error("execution witness error: {0}")]
+    ExecutionWitness(String),
+    /// The remote node of a forked provider failed to serve a request.
+    #[error("remote provider error: {0}")]
+    Remote(String),
 }
 
 impl From<reth_nippy_jar::NippyJarError> for ProviderError {
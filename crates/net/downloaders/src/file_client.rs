@@ -1,4 +1,4 @@
-use super::file_codec::BlockFileCodec;
+use alloy_rlp::Decodable;
 use itertools::Either;
 use reth_interfaces::p2p::{
     bodies::client::{BodiesClient, BodiesFut},
@@ -8,15 +8,17 @@ use reth_interfaces::p2p::{
     priority::Priority,
 };
 use reth_primitives::{
-    BlockBody, BlockHash, BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId, B256,
+    Block, BlockBody, BlockHash, BlockHashOrNumber, BlockNumber, Header, HeadersDirection, PeerId,
+    B256,
 };
 use std::{self, collections::HashMap, path::Path};
 use thiserror::Error;
 use tokio::{fs::File, io::AsyncReadExt};
-use tokio_stream::StreamExt;
-use tokio_util::codec::FramedRead;
 use tracing::{trace, warn};
 
+/// The default number of bytes of a chain file that [ChunkedFileReader] reads at once.
+pub const DEFAULT_CHUNK_BYTE_LEN: u64 = 1_000_000_000;
+
 /// Front-end API for fetching chain data from a file.
 ///
 /// Blocks are assumed to be written one after another in a file, as rlp bytes.
@@ -27,7 +29,8 @@ use tracing::{trace, warn};
 /// Blocks are assumed to have populated transactions, so reading headers will also buffer
 /// transactions in memory for use in the bodies stage.
 ///
-/// This reads the entire file into memory, so it is not suitable for large files.
+/// This reads the entire file into memory, so it is not suitable for large files. Large files can
+/// be read in chunks with the [ChunkedFileReader].
 #[derive(Debug)]
 pub struct FileClient {
     /// The buffered headers retrieved when fetching new bodies.
@@ -61,23 +64,29 @@ impl FileClient {
 
     /// Initialize the [`FileClient`] with a file directly.
     pub(crate) async fn from_file(mut file: File) -> Result<Self, FileClientError> {
-        // get file len from metadata before reading
-        let metadata = file.metadata().await?;
-        let file_len = metadata.len();
-
         // read the entire file into memory
-        let mut reader = vec![];
-        file.read_to_end(&mut reader).await.unwrap();
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes).await?;
+
+        let (client, incomplete) = Self::from_bytes(&bytes)?;
+        if incomplete > 0 {
+            return Err(alloy_rlp::Error::InputTooShort.into())
+        }
+        Ok(client)
+    }
 
+    /// Decodes the complete blocks at the start of the given bytes.
+    ///
+    /// Returns the client and the number of bytes at the end that belong to an incomplete block.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), FileClientError> {
         let mut headers = HashMap::new();
         let mut hash_to_number = HashMap::new();
         let mut bodies = HashMap::new();
 
-        // use with_capacity to make sure the internal buffer contains the entire file
-        let mut stream = FramedRead::with_capacity(&reader[..], BlockFileCodec, file_len as usize);
-
-        while let Some(block_res) = stream.next().await {
-            let block = block_res?;
+        let mut remaining = bytes;
+        while let Some(block_len) = complete_block_len(remaining)? {
+            let block = Block::decode(&mut &remaining[..block_len])?;
+            remaining = &remaining[block_len..];
             let block_hash = block.header.hash_slow();
 
             // add to the internal maps
@@ -95,12 +104,17 @@ impl FileClient {
 
         trace!(blocks = headers.len(), "Initialized file client");
 
-        Ok(Self { headers, hash_to_number, bodies })
+        Ok((Self { headers, hash_to_number, bodies }, remaining.len()))
     }
 
     /// Get the tip hash of the chain.
     pub fn tip(&self) -> Option<B256> {
-        self.headers.get(&(self.headers.len() as u64)).map(|h| h.hash_slow())
+        self.max_block().and_then(|number| self.headers.get(&number)).map(|h| h.hash_slow())
+    }
+
+    /// Returns the lowest block number of this client has or `None` if empty
+    pub fn min_block(&self) -> Option<u64> {
+        self.headers.keys().min().copied()
     }
 
     /// Returns the highest block number of this client has or `None` if empty
@@ -108,6 +122,16 @@ impl FileClient {
         self.headers.keys().max().copied()
     }
 
+    /// Returns the header of the block with the given number, if the client has it.
+    pub fn header(&self, number: BlockNumber) -> Option<&Header> {
+        self.headers.get(&number)
+    }
+
+    /// Returns the body of the block with the given hash, if the client has it.
+    pub fn body(&self, hash: &BlockHash) -> Option<&BlockBody> {
+        self.bodies.get(hash)
+    }
+
     /// Returns true if all blocks are canonical (no gaps)
     pub fn has_canonical_blocks(&self) -> bool {
         if self.headers.is_empty() {
@@ -142,6 +166,88 @@ impl FileClient {
     }
 }
 
+/// Returns the length of the block at the start of the given bytes, or `None` if the bytes end
+/// before the block does.
+fn complete_block_len(bytes: &[u8]) -> Result<Option<usize>, FileClientError> {
+    if bytes.is_empty() {
+        return Ok(None)
+    }
+    let mut payload = bytes;
+    let header = match alloy_rlp::Header::decode(&mut payload) {
+        Ok(header) => header,
+        Err(alloy_rlp::Error::InputTooShort) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let len = bytes.len() - payload.len() + header.payload_length;
+    Ok((len <= bytes.len()).then_some(len))
+}
+
+/// Reads a chain file in chunks of complete blocks, so files that don't fit into memory can be
+/// imported.
+#[derive(Debug)]
+pub struct ChunkedFileReader {
+    /// The file that is read.
+    file: File,
+    /// The number of bytes of the file that were not read yet.
+    unread: u64,
+    /// The bytes of the incomplete block at the end of the previous chunk.
+    buf: Vec<u8>,
+    /// The number of bytes to read at once.
+    chunk_byte_len: u64,
+}
+
+impl ChunkedFileReader {
+    /// Opens the file at the given path, to be read in chunks of the given number of bytes.
+    pub async fn new<P: AsRef<Path>>(
+        path: P,
+        chunk_byte_len: u64,
+    ) -> Result<Self, FileClientError> {
+        Self::from_file(File::open(path).await?, chunk_byte_len).await
+    }
+
+    /// Reads the given file in chunks of the given number of bytes.
+    pub async fn from_file(file: File, chunk_byte_len: u64) -> Result<Self, FileClientError> {
+        let unread = file.metadata().await?.len();
+        Ok(Self { file, unread, buf: Vec::new(), chunk_byte_len: chunk_byte_len.max(1) })
+    }
+
+    /// Returns true if all blocks of the file were read.
+    pub fn is_done(&self) -> bool {
+        self.unread == 0 && self.buf.is_empty()
+    }
+
+    /// Returns a client with the blocks of the next chunk of the file, or `None` if all blocks
+    /// were read.
+    ///
+    /// A chunk only contains complete blocks, the bytes of a block at its end are kept for the
+    /// next chunk. Blocks that are larger than the chunk length are read whole.
+    pub async fn next_chunk(&mut self) -> Result<Option<FileClient>, FileClientError> {
+        if self.is_done() {
+            return Ok(None)
+        }
+
+        loop {
+            let len = self.chunk_byte_len.min(self.unread) as usize;
+            let start = self.buf.len();
+            self.buf.resize(start + len, 0);
+            self.file.read_exact(&mut self.buf[start..]).await?;
+            self.unread -= len as u64;
+
+            let (client, incomplete) = FileClient::from_bytes(&self.buf)?;
+            if client.headers.is_empty() && self.unread > 0 {
+                // the chunk ends before the first block does
+                continue
+            }
+            if incomplete > 0 && self.unread == 0 {
+                return Err(alloy_rlp::Error::InputTooShort.into())
+            }
+
+            self.buf.drain(..self.buf.len() - incomplete);
+            return Ok(Some(client))
+        }
+    }
+}
+
 impl HeadersClient for FileClient {
     type Output = HeadersFut;
 
@@ -359,4 +465,31 @@ mod tests {
             Some(Ok(res)) => assert_eq!(res, zip_blocks(headers.iter(), &mut bodies))
         );
     }
+
+    #[tokio::test]
+    async fn reads_file_in_chunks() {
+        // Generate some random blocks
+        let (file, headers, bodies) = generate_bodies_file(0..=19).await;
+
+        // chunks smaller than a block are extended until they contain a complete block
+        let mut reader = ChunkedFileReader::from_file(file, 200).await.unwrap();
+
+        let mut next = 0;
+        let mut chunks = 0;
+        while let Some(client) = reader.next_chunk().await.unwrap() {
+            assert_eq!(client.min_block(), Some(next));
+            let max = client.max_block().unwrap();
+            for header in &headers[next as usize..=max as usize] {
+                assert_eq!(client.header(header.number), Some(&header.header));
+                assert_eq!(client.body(&header.hash()), bodies.get(&header.hash()));
+            }
+            assert_eq!(client.tip(), Some(headers[max as usize].hash()));
+            next = max + 1;
+            chunks += 1;
+        }
+
+        assert!(reader.is_done());
+        assert_eq!(next, 20);
+        assert!(chunks > 1);
+    }
 }
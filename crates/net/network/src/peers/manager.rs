@@ -73,6 +73,14 @@ impl PeersHandle {
 
         rx.await.unwrap_or_default()
     }
+
+    /// Updates the maximum number of inbound and outbound connections.
+    ///
+    /// Lowering a limit does not disconnect already established sessions, it only prevents new
+    /// ones until the number of connections drops below the new limit.
+    pub fn set_connection_limits(&self, max_inbound: usize, max_outbound: usize) {
+        self.send(PeerCommand::SetConnectionLimits { max_inbound, max_outbound });
+    }
 }
 
 /// Maintains the state of _all_ the peers known to the network.
@@ -745,6 +753,12 @@ impl PeersManager {
                     PeerCommand::GetPeers(tx) => {
                         let _ = tx.send(self.iter_peers().collect());
                     }
+                    PeerCommand::SetConnectionLimits { max_inbound, max_outbound } => {
+                        info!(target: "net::peers", max_inbound, max_outbound, "Updating connection limits");
+                        self.connection_info.max_inbound = max_inbound;
+                        self.connection_info.max_outbound = max_outbound;
+                        self.fill_outbound_slots();
+                    }
                 }
             }
 
@@ -813,6 +827,16 @@ pub struct ConnectionInfo {
 // === impl ConnectionInfo ===
 
 impl ConnectionInfo {
+    /// Returns the maximum allowed outbound connections.
+    pub fn max_outbound(&self) -> usize {
+        self.max_outbound
+    }
+
+    /// Returns the maximum allowed inbound connections.
+    pub fn max_inbound(&self) -> usize {
+        self.max_inbound
+    }
+
     ///  Returns `true` if there's still capacity for a new outgoing connection.
     fn has_out_capacity(&self) -> bool {
         self.num_outbound < self.max_outbound
@@ -1040,6 +1064,8 @@ pub(crate) enum PeerCommand {
     GetPeer(PeerId, oneshot::Sender<Option<Peer>>),
     /// Get node information on all peers
     GetPeers(oneshot::Sender<Vec<NodeRecord>>),
+    /// Update the maximum number of inbound and outbound connections
+    SetConnectionLimits { max_inbound: usize, max_outbound: usize },
 }
 
 /// Actions the peer manager can trigger.
@@ -1915,6 +1941,34 @@ mod tests {
         assert_eq!(peers.get_reputation(&peer), Some(0));
     }
 
+    #[tokio::test]
+    async fn test_set_connection_limits() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let mut peers = PeersManager::new(PeersConfig::default().with_max_outbound(0));
+        peers.add_peer(peer, socket_addr, None);
+
+        match event!(peers) {
+            PeerAction::PeerAdded(peer_id) => {
+                assert_eq!(peer_id, peer);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(peers.num_outbound_connections(), 0);
+
+        peers.handle().set_connection_limits(10, 1);
+
+        match event!(peers) {
+            PeerAction::Connect { peer_id, remote_addr } => {
+                assert_eq!(peer_id, peer);
+                assert_eq!(remote_addr, socket_addr);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(peers.connection_info.max_inbound(), 10);
+        assert_eq!(peers.connection_info.max_outbound(), 1);
+    }
+
     #[tokio::test]
     async fn test_remove_discovered_active() {
         let peer = PeerId::random();
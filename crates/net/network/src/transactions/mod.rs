@@ -50,8 +50,10 @@ use reth_primitives::{
     B256,
 };
 use reth_transaction_pool::{
-    error::PoolResult, GetPooledTransactionLimit, PoolTransaction, PropagateKind,
-    PropagatedTransactions, TransactionPool, ValidPoolTransaction,
+    error::PoolResult,
+    first_seen::{self, SeenOrigin},
+    GetPooledTransactionLimit, PoolTransaction, PropagateKind, PropagatedTransactions,
+    TransactionPool, ValidPoolTransaction,
 };
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
@@ -568,6 +570,12 @@ where
         });
         let mut hashes = msg.into_hashes();
 
+        if let Some(first_seen) = first_seen::tracker() {
+            for hash in hashes.iter().copied() {
+                first_seen.record(hash, SeenOrigin::Announcement(peer_id));
+            }
+        }
+
         // keep track of the transactions the peer knows
         let mut num_already_seen = 0;
         for tx in hashes.iter().copied() {
@@ -785,6 +793,12 @@ where
                     .filter_map(Result::ok)
                     .collect::<Vec<_>>();
 
+                if let Some(first_seen) = first_seen::tracker() {
+                    for tx in &non_blob_txs {
+                        first_seen.record(*tx.hash(), SeenOrigin::Broadcast(peer_id));
+                    }
+                }
+
                 // mark the transactions as received
                 self.transaction_fetcher.on_received_full_transactions_broadcast(
                     non_blob_txs.iter().map(|tx| *tx.hash()),
@@ -64,6 +64,65 @@ pub struct DebugArgs {
     /// will be written to specified location.
     #[arg(long = "debug.engine-api-store", help_heading = "Debug", value_name = "PATH")]
     pub engine_api_store: Option<PathBuf>,
+
+    /// The path to append engine API exchanges to.
+    /// If specified, all `engine_newPayload`, `engine_forkchoiceUpdated` and `engine_getPayload`
+    /// requests and their responses will be recorded to the file as JSON lines, to be replayed
+    /// with `reth debug replay-engine-journal`.
+    #[arg(long = "debug.engine-api-journal", help_heading = "Debug", value_name = "PATH")]
+    pub engine_api_journal: Option<PathBuf>,
+
+    /// Serve `debug_insertBlock` on the authenticated engine API server.
+    /// Imported blocks go through the same validation as `engine_newPayload`, which lets test
+    /// frameworks drive the node without a consensus layer.
+    #[arg(long = "debug.insert-block-api", help_heading = "Debug")]
+    pub insert_block_api: bool,
+
+    /// The path to append per-block performance records to.
+    /// If specified, execution, state root and persistence timings
+    /// of every block will be written to the file as JSON lines.
+    #[arg(long = "debug.block-perf-log", help_heading = "Debug", value_name = "PATH")]
+    pub block_perf_log: Option<PathBuf>,
+
+    /// Recompute the state root of every Nth block from scratch and compare it with the
+    /// incrementally computed root.
+    /// This is expensive on large states and is meant for testing networks.
+    #[arg(long = "debug.state-root-cross-check", help_heading = "Debug", value_name = "N")]
+    pub state_root_cross_check: Option<u64>,
+
+    /// The directory to write repro bundles of blocks whose state root diverges from the
+    /// reference to.
+    #[arg(
+        long = "debug.state-root-cross-check-dir",
+        help_heading = "Debug",
+        value_name = "PATH",
+        requires = "state_root_cross_check"
+    )]
+    pub state_root_cross_check_dir: Option<PathBuf>,
+
+    /// Do not write a crash bundle when the node panics.
+    /// By default, the recent logs, the configuration, the stage checkpoints and stats are
+    /// bundled into a redacted archive in the `crash-bundles` directory of the data dir, to be
+    /// attached to bug reports.
+    #[arg(long = "debug.no-crash-bundle", help_heading = "Debug")]
+    pub no_crash_bundle: bool,
+
+    /// The directory to record the state accessed by every executed block to.
+    /// If specified, the accounts and storage slots read and written by each transaction are
+    /// written to a gzip compressed JSON file per block, for research on parallel execution and
+    /// caching.
+    #[arg(long = "debug.state-access-dir", help_heading = "Debug", value_name = "PATH")]
+    pub state_access_dir: Option<PathBuf>,
+
+    /// The number of most recent blocks to keep the recorded state accesses of.
+    /// Defaults to 10000 blocks.
+    #[arg(
+        long = "debug.state-access-retention",
+        help_heading = "Debug",
+        value_name = "BLOCKS",
+        requires = "state_access_dir"
+    )]
+    pub state_access_retention: Option<u64>,
 }
 
 #[cfg(test)]
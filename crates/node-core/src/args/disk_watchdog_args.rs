@@ -0,0 +1,115 @@
+//! clap [Args](clap::Args) for the disk space watchdog
+
+use crate::{
+    args::utils::parse_duration_from_secs,
+    disk_watchdog::{DiskThresholds, DEFAULT_DISK_CHECK_INTERVAL},
+};
+use clap::Args;
+use std::time::Duration;
+
+/// The default free space in GB below which the node is degraded.
+pub const DEFAULT_DEGRADED_THRESHOLD_GB: u64 = 50;
+
+/// The default free space in GB below which sync is halted.
+pub const DEFAULT_CRITICAL_THRESHOLD_GB: u64 = 10;
+
+const GB: u64 = 1_000_000_000;
+
+/// Parameters for the disk space watchdog
+#[derive(Debug, Clone, Args, PartialEq, Eq)]
+#[clap(next_help_heading = "Disk watchdog")]
+pub struct DiskWatchdogArgs {
+    /// Disable the disk space watchdog.
+    #[arg(long = "disk.disable-watchdog", default_value_t = false)]
+    pub disable: bool,
+
+    /// Free space in GB below which optional writes are paused and pruning runs on every block.
+    #[arg(
+        long = "disk.degraded-threshold",
+        value_name = "GB",
+        default_value_t = DEFAULT_DEGRADED_THRESHOLD_GB
+    )]
+    pub degraded_threshold: u64,
+
+    /// Free space in GB below which sync is halted until space is freed.
+    #[arg(
+        long = "disk.critical-threshold",
+        value_name = "GB",
+        default_value_t = DEFAULT_CRITICAL_THRESHOLD_GB
+    )]
+    pub critical_threshold: u64,
+
+    /// Interval in seconds at which the free space is checked.
+    #[arg(
+        long = "disk.check-interval",
+        value_name = "SECONDS",
+        value_parser = parse_duration_from_secs,
+        default_value = "10"
+    )]
+    pub check_interval: Duration,
+}
+
+impl DiskWatchdogArgs {
+    /// Returns the configured thresholds in bytes.
+    pub fn thresholds(&self) -> eyre::Result<DiskThresholds> {
+        eyre::ensure!(
+            self.critical_threshold <= self.degraded_threshold,
+            "the critical disk threshold must not exceed the degraded threshold"
+        );
+        Ok(DiskThresholds {
+            degraded: self.degraded_threshold.saturating_mul(GB),
+            critical: self.critical_threshold.saturating_mul(GB),
+        })
+    }
+}
+
+impl Default for DiskWatchdogArgs {
+    fn default() -> Self {
+        Self {
+            disable: false,
+            degraded_threshold: DEFAULT_DEGRADED_THRESHOLD_GB,
+            critical_threshold: DEFAULT_CRITICAL_THRESHOLD_GB,
+            check_interval: DEFAULT_DISK_CHECK_INTERVAL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_disk_watchdog_args() {
+        let args = CommandParser::<DiskWatchdogArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, DiskWatchdogArgs::default());
+
+        let args = CommandParser::<DiskWatchdogArgs>::parse_from([
+            "reth",
+            "--disk.degraded-threshold",
+            "20",
+            "--disk.critical-threshold",
+            "5",
+        ])
+        .args;
+        assert_eq!(
+            args.thresholds().unwrap(),
+            DiskThresholds { degraded: 20 * GB, critical: 5 * GB }
+        );
+
+        let args = CommandParser::<DiskWatchdogArgs>::parse_from([
+            "reth",
+            "--disk.critical-threshold",
+            "100",
+        ])
+        .args;
+        assert!(args.thresholds().is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! clap [Args](clap::Args) for the health endpoints
+
+use crate::{args::utils::parse_duration_from_secs, metrics::health::ReadinessThresholds};
+use clap::Args;
+use std::time::Duration;
+
+/// Parameters for the readiness checks of the `/ready` endpoint of the metrics server
+#[derive(Debug, Clone, Copy, Args, PartialEq, Eq)]
+#[clap(next_help_heading = "Health")]
+pub struct HealthArgs {
+    /// Maximum time in seconds since the last forkchoice update of the consensus layer for the
+    /// node to be ready.
+    #[arg(
+        long = "health.max-forkchoice-age",
+        value_name = "SECONDS",
+        value_parser = parse_duration_from_secs,
+        default_value = "120"
+    )]
+    pub max_forkchoice_age: Duration,
+
+    /// Minimum number of connected peers for the node to be ready.
+    #[arg(long = "health.min-peers", value_name = "PEERS", default_value_t = 1)]
+    pub min_peers: usize,
+
+    /// Maximum age in seconds of the head block, by its timestamp, for the node to be ready.
+    #[arg(
+        long = "health.max-head-age",
+        value_name = "SECONDS",
+        value_parser = parse_duration_from_secs,
+        default_value = "60"
+    )]
+    pub max_head_age: Duration,
+}
+
+impl HealthArgs {
+    /// Returns the configured readiness thresholds.
+    pub fn thresholds(&self) -> ReadinessThresholds {
+        ReadinessThresholds {
+            max_forkchoice_age: self.max_forkchoice_age,
+            min_peers: self.min_peers,
+            max_head_age: self.max_head_age,
+        }
+    }
+}
+
+impl Default for HealthArgs {
+    fn default() -> Self {
+        let ReadinessThresholds { max_forkchoice_age, min_peers, max_head_age } =
+            ReadinessThresholds::default();
+        Self { max_forkchoice_age, min_peers, max_head_age }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_health_args() {
+        let args = CommandParser::<HealthArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, HealthArgs::default());
+
+        let args = CommandParser::<HealthArgs>::parse_from([
+            "reth",
+            "--health.min-peers",
+            "0",
+            "--health.max-head-age",
+            "24",
+        ])
+        .args;
+        assert_eq!(
+            args.thresholds(),
+            ReadinessThresholds {
+                min_peers: 0,
+                max_head_age: Duration::from_secs(24),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -62,6 +62,24 @@ pub struct LogArgs {
     )]
     pub journald_filter: String,
 
+    /// Export spans to the OpenTelemetry collector at the given OTLP/gRPC endpoint, e.g.
+    /// `http://localhost:4317`.
+    ///
+    /// The trace context of RPC requests is taken from their `traceparent` header, so the spans
+    /// of a request, the validation of its transactions and their inclusion in payloads are part
+    /// of the trace of the caller.
+    #[arg(long = "tracing.otlp", value_name = "URL", global = true)]
+    pub otlp: Option<String>,
+
+    /// The filter to use for the exported spans.
+    #[arg(
+        long = "tracing.otlp.filter",
+        value_name = "FILTER",
+        global = true,
+        default_value = "debug"
+    )]
+    pub otlp_filter: String,
+
     /// Sets whether or not the formatter emits ANSI terminal escape codes for colors and other
     /// text formatting.
     #[arg(
@@ -113,6 +131,10 @@ impl LogArgs {
             tracer = tracer.with_file(file, info);
         }
 
+        if let Some(endpoint) = &self.otlp {
+            tracer = tracer.with_otlp(endpoint.clone(), self.otlp_filter.clone());
+        }
+
         let guard = tracer.init()?;
         Ok(guard)
     }
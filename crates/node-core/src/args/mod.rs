@@ -51,6 +51,30 @@ pub use dev_args::DevArgs;
 mod pruning_args;
 pub use pruning_args::PruningArgs;
 
+/// OutcomeStreamArgs for streaming execution outcomes to message queues
+mod outcome_stream_args;
+pub use outcome_stream_args::OutcomeStreamArgs;
+
+/// TaskGroupArgs for running subsystems on dedicated runtimes
+mod task_group_args;
+pub use task_group_args::TaskGroupArgs;
+
+/// DiskWatchdogArgs for degrading the node under disk pressure
+mod disk_watchdog_args;
+pub use disk_watchdog_args::DiskWatchdogArgs;
+
+/// HealthArgs for the readiness checks of the health endpoints
+mod health_args;
+pub use health_args::HealthArgs;
+
+/// RootSloArgs for tracking the latency of state root computations
+mod root_slo_args;
+pub use root_slo_args::RootSloArgs;
+
+/// PinnedTrieArgs for keeping account trie subtrees in memory
+mod pinned_trie_args;
+pub use pinned_trie_args::PinnedTrieArgs;
+
 /// RollupArgs for configuring the op-reth rollup
 #[cfg(feature = "optimism")]
 mod rollup_args;
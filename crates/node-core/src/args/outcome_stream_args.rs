@@ -0,0 +1,82 @@
+//! clap [Args](clap::Args) for streaming execution outcomes to message queues
+
+use clap::Args;
+use std::path::PathBuf;
+
+/// The default subject execution outcomes are published to.
+pub const DEFAULT_OUTCOME_STREAM_SUBJECT: &str = "reth.outcomes";
+
+/// Parameters for streaming execution outcomes to message queues
+#[derive(Debug, Args, PartialEq)]
+#[clap(next_help_heading = "Outcome stream")]
+pub struct OutcomeStreamArgs {
+    /// Publish the execution outcome of every canonical block to the NATS JetStream server at
+    /// this URL, e.g. `nats://localhost:4222`.
+    ///
+    /// Use `tls://` to always connect with TLS, `nats://` upgrades only if the server requires
+    /// it. Credentials are passed as `user:password@` or `token@` in the URL, and are only sent
+    /// over TLS.
+    ///
+    /// A JetStream stream that captures the subject must exist. Publishing resumes after the
+    /// last acknowledged block on restart.
+    #[arg(long = "outcome-stream.nats", value_name = "URL")]
+    pub nats: Option<String>,
+
+    /// The subject to publish execution outcomes to.
+    #[arg(
+        long = "outcome-stream.subject",
+        value_name = "SUBJECT",
+        default_value = DEFAULT_OUTCOME_STREAM_SUBJECT
+    )]
+    pub subject: String,
+
+    /// PEM file with CA certificates to trust for TLS connections to the NATS server, in addition
+    /// to the webpki roots.
+    #[arg(long = "outcome-stream.ca-file", value_name = "PATH")]
+    pub ca_file: Option<PathBuf>,
+}
+
+impl Default for OutcomeStreamArgs {
+    fn default() -> Self {
+        Self { nats: None, subject: DEFAULT_OUTCOME_STREAM_SUBJECT.to_string(), ca_file: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_outcome_stream_args() {
+        let args = CommandParser::<OutcomeStreamArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, OutcomeStreamArgs::default());
+
+        let args = CommandParser::<OutcomeStreamArgs>::parse_from([
+            "reth",
+            "--outcome-stream.nats",
+            "nats://localhost:4222",
+        ])
+        .args;
+        assert_eq!(args.nats.as_deref(), Some("nats://localhost:4222"));
+        assert_eq!(args.subject, DEFAULT_OUTCOME_STREAM_SUBJECT);
+        assert_eq!(args.ca_file, None);
+
+        let args = CommandParser::<OutcomeStreamArgs>::parse_from([
+            "reth",
+            "--outcome-stream.nats",
+            "tls://nats.internal:4222",
+            "--outcome-stream.ca-file",
+            "/etc/reth/nats-ca.pem",
+        ])
+        .args;
+        assert_eq!(args.ca_file, Some(PathBuf::from("/etc/reth/nats-ca.pem")));
+    }
+}
@@ -6,10 +6,21 @@ use clap::{
     builder::{RangedU64ValueParser, TypedValueParser},
     Arg, Args, Command,
 };
-use reth_primitives::constants::{
-    ETHEREUM_BLOCK_GAS_LIMIT, MAXIMUM_EXTRA_DATA_SIZE, SLOT_DURATION,
+use reth_basic_payload_builder::OrderingAlgorithm;
+use reth_payload_builder::{
+    TopOfBlockAuction, DEFAULT_MAX_PAYLOAD_JOBS, DEFAULT_MAX_TOP_OF_BLOCK_BIDS,
+    DEFAULT_TOP_OF_BLOCK_CUTOFF,
+};
+use reth_primitives::{
+    constants::{ETHEREUM_BLOCK_GAS_LIMIT, MAXIMUM_EXTRA_DATA_SIZE, SLOT_DURATION},
+    Address,
+};
+use std::{
+    borrow::Cow,
+    ffi::OsStr,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
 };
-use std::{borrow::Cow, ffi::OsStr, time::Duration};
 
 /// Parameters for configuring the Payload Builder
 #[derive(Debug, Args, PartialEq)]
@@ -35,6 +46,69 @@ pub struct PayloadBuilderArgs {
     #[arg(long = "builder.max-tasks", default_value = "3", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
     pub max_payload_tasks: usize,
 
+    /// Maximum number of concurrent payload jobs.
+    ///
+    /// If a new job would exceed the limit, resolved jobs and then the oldest unresolved jobs are
+    /// evicted.
+    #[arg(long = "builder.max-jobs", default_value_t = DEFAULT_MAX_PAYLOAD_JOBS, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_payload_jobs: usize,
+
+    /// How long resolved payloads are kept for repeated requests of the consensus layer (in
+    /// seconds).
+    #[arg(long = "builder.resolved-retention", value_parser = parse_duration_from_secs, default_value = "12", value_name = "SECONDS")]
+    pub resolved_retention: Duration,
+
+    /// Only compute the state root of the payload that is handed out to the consensus layer.
+    ///
+    /// Candidate payloads are built and compared with a placeholder state root, the root of the
+    /// best candidate is computed when the payload is requested with `engine_getPayload`.
+    #[arg(long = "builder.deferred-state-root")]
+    pub deferred_state_root: bool,
+
+    /// Order the transactions of the pool by merging groups of non-conflicting transactions, the
+    /// most profitable per gas first, instead of in the order of the pool.
+    ///
+    /// The best transactions of the pool are simulated on the parent state to record the state
+    /// they access, for up to the ordering budget per build.
+    #[arg(long = "builder.conflict-aware-ordering")]
+    pub conflict_aware_ordering: bool,
+
+    /// The maximum time the conflict-aware ordering simulates transactions per build (in
+    /// milliseconds).
+    #[arg(long = "builder.ordering-budget", default_value = "200", value_name = "MILLIS")]
+    pub ordering_budget: u64,
+
+    /// Serve `builder_submitTopOfBlockBid` on a dedicated endpoint and place the highest valid bid
+    /// at the top of built blocks.
+    ///
+    /// Bids have to be signed by one of the top of block bidders. Bids are validated at the start
+    /// of the block, a bid is valid if all of its transactions succeed and they pay the fee
+    /// recipient at least the value of the bid.
+    #[arg(long = "builder.top-of-block-auction", requires = "top_of_block_bidders")]
+    pub top_of_block_auction: bool,
+
+    /// The addresses whose signed bids the top of block auction accepts.
+    #[arg(long = "builder.top-of-block-bidders", value_name = "ADDRESS", value_delimiter = ',')]
+    pub top_of_block_bidders: Vec<Address>,
+
+    /// The address the top of block bids are served on.
+    #[arg(long = "builder.top-of-block-addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub top_of_block_addr: IpAddr,
+
+    /// The port the top of block bids are served on.
+    #[arg(long = "builder.top-of-block-port", default_value_t = 8552)]
+    pub top_of_block_port: u16,
+
+    /// The time before the timestamp of a block after which no more top of block bids are
+    /// accepted (in seconds).
+    #[arg(long = "builder.top-of-block-cutoff", value_parser = parse_duration_from_secs, default_value = "2", value_name = "SECONDS")]
+    pub top_of_block_cutoff: Duration,
+
+    /// The maximum number of top of block bids kept per block, lower bids are dropped for higher
+    /// ones.
+    #[arg(long = "builder.top-of-block-max-bids", default_value_t = DEFAULT_MAX_TOP_OF_BLOCK_BIDS, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub top_of_block_max_bids: usize,
+
     /// By default the pending block equals the latest block
     /// to save resources and not leak txs from the tx-pool,
     /// this flag enables computing of the pending block
@@ -56,12 +130,52 @@ impl Default for PayloadBuilderArgs {
             interval: Duration::from_secs(1),
             deadline: SLOT_DURATION,
             max_payload_tasks: 3,
+            max_payload_jobs: DEFAULT_MAX_PAYLOAD_JOBS,
+            resolved_retention: SLOT_DURATION,
+            deferred_state_root: false,
+            conflict_aware_ordering: false,
+            ordering_budget: 200,
+            top_of_block_auction: false,
+            top_of_block_bidders: Vec::new(),
+            top_of_block_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            top_of_block_port: 8552,
+            top_of_block_cutoff: DEFAULT_TOP_OF_BLOCK_CUTOFF,
+            top_of_block_max_bids: DEFAULT_MAX_TOP_OF_BLOCK_BIDS,
             #[cfg(feature = "optimism")]
             compute_pending_block: false,
         }
     }
 }
 
+impl PayloadBuilderArgs {
+    /// Returns how the transactions of the pool are ordered in built blocks.
+    pub fn ordering(&self) -> OrderingAlgorithm {
+        if self.conflict_aware_ordering {
+            OrderingAlgorithm::ConflictAware {
+                time_budget: Duration::from_millis(self.ordering_budget),
+            }
+        } else {
+            OrderingAlgorithm::Pool
+        }
+    }
+
+    /// Returns the auction of the top of built blocks, if enabled.
+    pub fn top_of_block_auction(&self) -> Option<TopOfBlockAuction> {
+        self.top_of_block_auction.then(|| {
+            TopOfBlockAuction::new(
+                self.top_of_block_cutoff,
+                self.top_of_block_max_bids,
+                self.top_of_block_bidders.iter().copied(),
+            )
+        })
+    }
+
+    /// Returns the socket address the top of block bids are served on.
+    pub fn top_of_block_socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.top_of_block_addr, self.top_of_block_port)
+    }
+}
+
 impl PayloadBuilderConfig for PayloadBuilderArgs {
     fn extradata(&self) -> Cow<'_, str> {
         self.extradata.as_str().into()
@@ -83,6 +197,14 @@ impl PayloadBuilderConfig for PayloadBuilderArgs {
         self.max_payload_tasks
     }
 
+    fn max_payload_jobs(&self) -> usize {
+        self.max_payload_jobs
+    }
+
+    fn resolved_payload_retention(&self) -> Duration {
+        self.resolved_retention
+    }
+
     #[cfg(feature = "optimism")]
     fn compute_pending_block(&self) -> bool {
         self.compute_pending_block
@@ -146,6 +268,45 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn test_args_with_ordering() {
+        let args = CommandParser::<PayloadBuilderArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.ordering(), OrderingAlgorithm::Pool);
+
+        let args = CommandParser::<PayloadBuilderArgs>::parse_from([
+            "reth",
+            "--builder.conflict-aware-ordering",
+            "--builder.ordering-budget",
+            "50",
+        ])
+        .args;
+        assert_eq!(
+            args.ordering(),
+            OrderingAlgorithm::ConflictAware { time_budget: Duration::from_millis(50) }
+        );
+    }
+
+    #[test]
+    fn test_args_with_payload_job_limits() {
+        let args = CommandParser::<PayloadBuilderArgs>::parse_from([
+            "reth",
+            "--builder.max-jobs",
+            "4",
+            "--builder.resolved-retention",
+            "30",
+        ])
+        .args;
+        assert_eq!(args.max_payload_jobs, 4);
+        assert_eq!(args.resolved_retention, Duration::from_secs(30));
+
+        assert!(CommandParser::<PayloadBuilderArgs>::try_parse_from([
+            "reth",
+            "--builder.max-jobs",
+            "0"
+        ])
+        .is_err());
+    }
+
     #[test]
     fn test_default_extradata() {
         let extradata = default_extradata();
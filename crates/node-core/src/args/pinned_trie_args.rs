@@ -0,0 +1,120 @@
+//! clap [Args](clap::Args) for the sources of trie nodes of state root validation
+
+use clap::Args;
+use reth_primitives::trie::Nibbles;
+use reth_trie::trie_cursor::pinned::PinnedAccountNodes;
+use std::sync::Arc;
+
+/// Parameters for keeping account trie subtrees in memory and fetching missing trie nodes
+#[derive(Debug, Clone, Default, Args, PartialEq, Eq)]
+#[clap(next_help_heading = "Pinned trie")]
+pub struct PinnedTrieArgs {
+    /// Hashed account key prefixes whose account trie nodes are kept in memory for state root
+    /// validation, as comma separated hex nibbles, e.g. `a7,0f3`.
+    ///
+    /// The subtrees are loaded once and refreshed with the trie updates of every canonical block,
+    /// so computing the state root never reads their nodes from the database.
+    #[arg(
+        long = "trie.pinned-prefixes",
+        value_name = "NIBBLES",
+        value_delimiter = ',',
+        value_parser = parse_nibbles
+    )]
+    pub pinned_prefixes: Vec<Nibbles>,
+
+    /// IPC path of the trie node server of another node (`--trie-server.ipcpath`), asked for
+    /// the trie nodes that are missing from the local database during state root validation.
+    ///
+    /// Allows running with a partial trie. The other node must be synced to the same chain, state
+    /// roots fail to validate while it is at another block.
+    #[arg(long = "trie.remote-nodes", value_name = "PATH", conflicts_with = "pinned_prefixes")]
+    pub remote_nodes: Option<String>,
+
+    /// Keep an in-memory filter over the accounts with storage, which skips the storage reads
+    /// of accounts without storage during state root validation and proof generation.
+    ///
+    /// The filter is built from the database in the background and rebuilt whenever the state
+    /// was changed by anything else than the blocks committed by the node, e.g. the pipeline.
+    #[arg(long = "trie.storage-filter")]
+    pub storage_filter: bool,
+}
+
+impl PinnedTrieArgs {
+    /// Returns the pinned account trie nodes, if any prefixes are configured.
+    pub fn pinned_account_nodes(&self) -> Option<Arc<PinnedAccountNodes>> {
+        (!self.pinned_prefixes.is_empty())
+            .then(|| Arc::new(PinnedAccountNodes::new(self.pinned_prefixes.iter().cloned())))
+    }
+}
+
+/// Parses a string of hex nibbles, e.g. `a7f`.
+fn parse_nibbles(value: &str) -> eyre::Result<Nibbles> {
+    let nibbles = value
+        .chars()
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| eyre::eyre!("invalid hex nibbles: {value}"))?;
+    Ok(Nibbles::from_nibbles_unchecked(nibbles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_pinned_trie_args() {
+        let args = CommandParser::<PinnedTrieArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, PinnedTrieArgs::default());
+        assert!(args.pinned_account_nodes().is_none());
+
+        let args = CommandParser::<PinnedTrieArgs>::parse_from([
+            "reth",
+            "--trie.pinned-prefixes",
+            "a7,0F3",
+        ])
+        .args;
+        assert_eq!(
+            args.pinned_prefixes,
+            vec![
+                Nibbles::from_nibbles_unchecked([0xa, 0x7]),
+                Nibbles::from_nibbles_unchecked([0x0, 0xf, 0x3])
+            ]
+        );
+
+        assert!(CommandParser::<PinnedTrieArgs>::try_parse_from([
+            "reth",
+            "--trie.pinned-prefixes",
+            "0x12"
+        ])
+        .is_err());
+
+        let args = CommandParser::<PinnedTrieArgs>::parse_from([
+            "reth",
+            "--trie.remote-nodes",
+            "/tmp/trie.ipc",
+        ])
+        .args;
+        assert_eq!(args.remote_nodes.as_deref(), Some("/tmp/trie.ipc"));
+        assert!(!args.storage_filter);
+        assert!(CommandParser::<PinnedTrieArgs>::try_parse_from([
+            "reth",
+            "--trie.remote-nodes",
+            "/tmp/trie.ipc",
+            "--trie.pinned-prefixes",
+            "a7",
+        ])
+        .is_err());
+
+        let args =
+            CommandParser::<PinnedTrieArgs>::parse_from(["reth", "--trie.storage-filter"]).args;
+        assert!(args.storage_filter);
+    }
+}
@@ -15,12 +15,37 @@ pub struct PruningArgs {
     /// This flag takes priority over pruning configuration in reth.toml.
     #[arg(long, default_value_t = false)]
     pub full: bool,
+
+    /// Expire the pre-merge history: drop the bodies and receipts of all blocks before the merge
+    /// once their headers are verified against the header accumulator.
+    ///
+    /// RPC requests for the expired blocks fail with a "history expired" error.
+    #[arg(long = "prune.history-expiry", default_value_t = false)]
+    pub history_expiry: bool,
+
+    /// Keep the transaction hash lookup entries of only the most recent blocks.
+    ///
+    /// Transactions of older blocks are still found by hash in the snapshots, using their bloom
+    /// filters. Entries are only pruned up to the highest transaction snapshot.
+    #[arg(
+        long = "prune.transaction-lookup.distance",
+        value_name = "BLOCKS",
+        value_parser = clap::value_parser!(u64).range(MINIMUM_PRUNING_DISTANCE..)
+    )]
+    pub transaction_lookup_distance: Option<u64>,
 }
 
 impl PruningArgs {
     /// Returns pruning configuration.
-    pub fn prune_config(&self, chain_spec: Arc<ChainSpec>) -> eyre::Result<Option<PruneConfig>> {
-        Ok(if self.full {
+    ///
+    /// The full node configuration takes priority over the given configuration from reth.toml.
+    /// History expiry and the transaction lookup distance are added on top of either of them.
+    pub fn prune_config(
+        &self,
+        chain_spec: Arc<ChainSpec>,
+        config: Option<PruneConfig>,
+    ) -> eyre::Result<Option<PruneConfig>> {
+        let mut prune_config = if self.full {
             Some(PruneConfig {
                 block_interval: 5,
                 segments: PruneModes {
@@ -32,6 +57,7 @@ impl PruningArgs {
                         .map(|contract| PruneMode::Before(contract.block)),
                     account_history: Some(PruneMode::Distance(MINIMUM_PRUNING_DISTANCE)),
                     storage_history: Some(PruneMode::Distance(MINIMUM_PRUNING_DISTANCE)),
+                    history_expiry: None,
                     receipts_log_filter: ReceiptsLogPruneConfig(
                         chain_spec
                             .deposit_contract
@@ -41,10 +67,27 @@ impl PruningArgs {
                             .collect(),
                     ),
                 },
+                delete_limit: None,
             })
         } else {
-            None
-        })
+            config
+        };
+
+        if self.history_expiry {
+            let (paris_block, _) =
+                chain_spec.paris_block_and_final_difficulty.ok_or_else(|| {
+                    eyre::eyre!("history expiry requires a chain with a known merge block")
+                })?;
+            prune_config.get_or_insert_with(PruneConfig::default).segments.history_expiry =
+                Some(PruneMode::Before(paris_block));
+        }
+
+        if let Some(distance) = self.transaction_lookup_distance {
+            prune_config.get_or_insert_with(PruneConfig::default).segments.transaction_lookup =
+                Some(PruneMode::Distance(distance));
+        }
+
+        Ok(prune_config)
     }
 }
 
@@ -66,4 +109,48 @@ mod tests {
         let args = CommandParser::<PruningArgs>::parse_from(["reth"]).args;
         assert_eq!(args, default_args);
     }
+
+    #[test]
+    fn history_expiry_before_merge() {
+        let args =
+            CommandParser::<PruningArgs>::parse_from(["reth", "--prune.history-expiry"]).args;
+        let chain_spec = reth_primitives::MAINNET.clone();
+        let (paris_block, _) = chain_spec.paris_block_and_final_difficulty.unwrap();
+
+        let config = args.prune_config(chain_spec.clone(), None).unwrap().unwrap();
+        assert_eq!(config.segments.history_expiry, Some(PruneMode::Before(paris_block)));
+
+        // the configuration from reth.toml is kept
+        let toml_config = PruneConfig {
+            block_interval: 10,
+            segments: PruneModes { sender_recovery: Some(PruneMode::Full), ..PruneModes::none() },
+            delete_limit: None,
+        };
+        let config = args.prune_config(chain_spec, Some(toml_config)).unwrap().unwrap();
+        assert_eq!(config.block_interval, 10);
+        assert_eq!(config.segments.sender_recovery, Some(PruneMode::Full));
+        assert_eq!(config.segments.history_expiry, Some(PruneMode::Before(paris_block)));
+    }
+
+    #[test]
+    fn transaction_lookup_distance() {
+        let args = CommandParser::<PruningArgs>::parse_from([
+            "reth",
+            "--full",
+            "--prune.transaction-lookup.distance",
+            "100000",
+        ])
+        .args;
+        let config = args.prune_config(reth_primitives::MAINNET.clone(), None).unwrap().unwrap();
+        assert_eq!(config.segments.transaction_lookup, Some(PruneMode::Distance(100_000)));
+        assert_eq!(config.segments.sender_recovery, Some(PruneMode::Full));
+
+        // the distance must keep the blocks that can still be reorged
+        assert!(CommandParser::<PruningArgs>::try_parse_from([
+            "reth",
+            "--prune.transaction-lookup.distance",
+            "1",
+        ])
+        .is_err());
+    }
 }
@@ -0,0 +1,148 @@
+//! clap [Args](clap::Args) for state root latency objectives
+
+use clap::Args;
+use humantime::parse_duration;
+use reth_trie::slo::{
+    RootComputation, RootSloThresholds, RootSloTracker, DEFAULT_ROOT_SLO_MIN_SAMPLES,
+    DEFAULT_ROOT_SLO_WINDOW,
+};
+use std::{path::PathBuf, time::Duration};
+
+/// Parameters for tracking the latency of state root computations
+#[derive(Debug, Clone, Args, PartialEq, Eq)]
+#[clap(next_help_heading = "State root latency")]
+pub struct RootSloArgs {
+    /// The length of the sliding window the latency percentiles are computed over.
+    #[arg(
+        long = "root-slo.window",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        default_value = "10m"
+    )]
+    pub window: Duration,
+
+    /// The number of state roots in the window below which thresholds are not evaluated.
+    #[arg(
+        long = "root-slo.min-samples",
+        value_name = "COUNT",
+        default_value_t = DEFAULT_ROOT_SLO_MIN_SAMPLES
+    )]
+    pub min_samples: usize,
+
+    /// Alert if the median latency of validating blocks exceeds this duration, e.g. `200ms`.
+    #[arg(long = "root-slo.validation-p50", value_name = "DURATION", value_parser = parse_duration)]
+    pub validation_p50: Option<Duration>,
+
+    /// Alert if the p95 latency of validating blocks exceeds this duration.
+    #[arg(long = "root-slo.validation-p95", value_name = "DURATION", value_parser = parse_duration)]
+    pub validation_p95: Option<Duration>,
+
+    /// Alert if the p99 latency of validating blocks exceeds this duration.
+    #[arg(long = "root-slo.validation-p99", value_name = "DURATION", value_parser = parse_duration)]
+    pub validation_p99: Option<Duration>,
+
+    /// Alert if the median latency of building payloads exceeds this duration.
+    #[arg(long = "root-slo.building-p50", value_name = "DURATION", value_parser = parse_duration)]
+    pub building_p50: Option<Duration>,
+
+    /// Alert if the p95 latency of building payloads exceeds this duration.
+    #[arg(long = "root-slo.building-p95", value_name = "DURATION", value_parser = parse_duration)]
+    pub building_p95: Option<Duration>,
+
+    /// Alert if the p99 latency of building payloads exceeds this duration.
+    #[arg(long = "root-slo.building-p99", value_name = "DURATION", value_parser = parse_duration)]
+    pub building_p99: Option<Duration>,
+
+    /// The directory to write a dump of the latencies and all metrics to when a threshold is
+    /// exceeded for the first time.
+    ///
+    /// Defaults to `root-slo` in the data dir of the chain.
+    #[arg(long = "root-slo.dump-dir", value_name = "PATH")]
+    pub dump_dir: Option<PathBuf>,
+}
+
+impl RootSloArgs {
+    /// Returns the configured thresholds of the given computation.
+    pub fn thresholds(&self, kind: RootComputation) -> RootSloThresholds {
+        match kind {
+            RootComputation::Validation => RootSloThresholds {
+                p50: self.validation_p50,
+                p95: self.validation_p95,
+                p99: self.validation_p99,
+            },
+            RootComputation::Building => RootSloThresholds {
+                p50: self.building_p50,
+                p95: self.building_p95,
+                p99: self.building_p99,
+            },
+        }
+    }
+
+    /// Returns a tracker with the configured window and thresholds that writes its dump to the
+    /// configured directory, or the given default.
+    pub fn tracker(&self, default_dump_dir: PathBuf) -> RootSloTracker {
+        RootSloTracker::new(self.window)
+            .with_min_samples(self.min_samples)
+            .with_thresholds(
+                RootComputation::Validation,
+                self.thresholds(RootComputation::Validation),
+            )
+            .with_thresholds(RootComputation::Building, self.thresholds(RootComputation::Building))
+            .with_dump_dir(self.dump_dir.clone().unwrap_or(default_dump_dir))
+    }
+}
+
+impl Default for RootSloArgs {
+    fn default() -> Self {
+        Self {
+            window: DEFAULT_ROOT_SLO_WINDOW,
+            min_samples: DEFAULT_ROOT_SLO_MIN_SAMPLES,
+            validation_p50: None,
+            validation_p95: None,
+            validation_p99: None,
+            building_p50: None,
+            building_p95: None,
+            building_p99: None,
+            dump_dir: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_root_slo_args() {
+        let args = CommandParser::<RootSloArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, RootSloArgs::default());
+
+        let args = CommandParser::<RootSloArgs>::parse_from([
+            "reth",
+            "--root-slo.window",
+            "5m",
+            "--root-slo.validation-p99",
+            "500ms",
+            "--root-slo.building-p95",
+            "1s",
+        ])
+        .args;
+        assert_eq!(args.window, Duration::from_secs(300));
+        assert_eq!(
+            args.thresholds(RootComputation::Validation),
+            RootSloThresholds { p99: Some(Duration::from_millis(500)), ..Default::default() }
+        );
+        assert_eq!(
+            args.thresholds(RootComputation::Building),
+            RootSloThresholds { p95: Some(Duration::from_secs(1)), ..Default::default() }
+        );
+    }
+}
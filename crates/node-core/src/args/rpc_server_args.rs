@@ -3,6 +3,7 @@
 use crate::{
     args::{
         types::{MaxU32, ZeroAsNoneU64},
+        utils::parse_duration_from_secs,
         GasPriceOracleArgs, RpcStateCacheArgs,
     },
     cli::{
@@ -17,17 +18,24 @@ use clap::{
     Arg, Args, Command,
 };
 use futures::TryFutureExt;
+use jsonrpsee::Methods;
 use rand::Rng;
 use reth_network_api::{NetworkInfo, Peers};
 use reth_node_api::EngineTypes;
 use reth_provider::{
     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    EvmEnvProvider, HeaderProvider, StateProviderFactory,
+    EvmEnvProvider, HeaderAccumulatorReader, HeaderProvider, PruneCheckpointReader,
+    ReorgHistoryProvider, StateProviderFactory,
 };
 use reth_rpc::{
-    eth::{cache::EthStateCacheConfig, gas_oracle::GasPriceOracleConfig, RPC_DEFAULT_GAS_CAP},
-    JwtError, JwtSecret,
+    eth::{
+        cache::EthStateCacheConfig, gas_oracle::GasPriceOracleConfig, EthPubSubConfig,
+        SlowConsumerPolicy, RPC_DEFAULT_GAS_CAP,
+    },
+    AdminApiKeysApi, ApiKeyLayer, ApiKeys, BatchLayer, JwtError, JwtSecret, StreamingLayer,
+    TracingLimits,
 };
+use reth_rpc_api::{AdminApiKeysApiServer, RethStreamApiServer};
 use reth_rpc_builder::{
     auth::{AuthServerConfig, AuthServerHandle},
     constants,
@@ -42,6 +50,8 @@ use std::{
     ffi::OsStr,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
+    sync::Arc,
+    time::Duration,
 };
 use tracing::{debug, info};
 
@@ -108,6 +118,17 @@ pub struct RpcServerArgs {
     #[arg(long, default_value_t = constants::DEFAULT_IPC_ENDPOINT.to_string())]
     pub ipcpath: String,
 
+    /// Serve the `trie_` namespace for distributed proof workers on a dedicated IPC endpoint at
+    /// the given path.
+    #[arg(long = "trie-server.ipcpath", value_name = "PATH")]
+    pub trie_server_ipcpath: Option<String>,
+
+    /// Serve the gRPC data and admin services on the given address, e.g. `127.0.0.1:50051`.
+    ///
+    /// Admin calls are authenticated with a JWT signed with the secret of the auth server.
+    #[arg(long = "grpc.addr", value_name = "SOCKET")]
+    pub grpc_addr: Option<SocketAddr>,
+
     /// Auth server address to listen on
     #[arg(long = "authrpc.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
     pub auth_addr: IpAddr,
@@ -153,6 +174,38 @@ pub struct RpcServerArgs {
     #[arg(long, value_name = "COUNT", default_value_t = constants::DEFAULT_MAX_TRACING_REQUESTS)]
     pub rpc_max_tracing_requests: u32,
 
+    /// Timeout of `debug_trace*` calls that don't set one (in seconds).
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = parse_duration_from_secs,
+        default_value = "60"
+    )]
+    pub rpc_trace_timeout: Duration,
+
+    /// Maximum timeout a `debug_trace*` call can set (in seconds).
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = parse_duration_from_secs,
+        default_value = "300"
+    )]
+    pub rpc_max_trace_timeout: Duration,
+
+    /// Maximum number of bytes of memory captured per step by `debug_trace*` calls that don't set
+    /// a `memoryLimit`.
+    #[arg(long, value_name = "BYTES", default_value_t = TracingLimits::default().default_memory_limit)]
+    pub rpc_trace_memory_limit: usize,
+
+    /// Maximum `memoryLimit` a `debug_trace*` call can set.
+    #[arg(long, value_name = "BYTES", default_value_t = TracingLimits::default().max_memory_limit)]
+    pub rpc_max_trace_memory_limit: usize,
+
+    /// Maximum call depth logged by `debug_trace*` calls, and the maximum `maxDepth` a call can
+    /// set.
+    #[arg(long, value_name = "DEPTH", default_value_t = TracingLimits::default().max_depth)]
+    pub rpc_max_trace_depth: u64,
+
     /// Maximum number of blocks that could be scanned per filter request. (0 = entire chain)
     #[arg(long, value_name = "COUNT", default_value_t = ZeroAsNoneU64::new(constants::DEFAULT_MAX_BLOCKS_PER_FILTER))]
     pub rpc_max_blocks_per_filter: ZeroAsNoneU64,
@@ -171,6 +224,61 @@ pub struct RpcServerArgs {
     )]
     pub rpc_gas_cap: u64,
 
+    /// Maximum number of calls of a JSON-RPC batch that are executed concurrently over HTTP.
+    ///
+    /// With 1, batches are executed by the server as usual, unless a batch cost limit is set.
+    #[arg(
+        long,
+        value_name = "COUNT",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        default_value_t = constants::DEFAULT_BATCH_PARALLELISM
+    )]
+    pub rpc_batch_parallelism: usize,
+
+    /// Maximum total cost of the calls of a JSON-RPC batch over HTTP. (0 = no limit)
+    ///
+    /// Plain lookups cost 1, `eth_call`, `eth_estimateGas` and `eth_getLogs` cost 10, and tracing
+    /// calls cost 50.
+    #[arg(long, value_name = "COST", default_value_t = ZeroAsNoneU64(None))]
+    pub rpc_max_batch_cost: ZeroAsNoneU64,
+
+    /// Maximum number of calls of a JSON-RPC batch over HTTP, if batches are executed
+    /// concurrently or have a cost limit.
+    #[arg(
+        long,
+        value_name = "COUNT",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        default_value_t = constants::DEFAULT_MAX_BATCH_CALLS
+    )]
+    pub rpc_max_batch_calls: usize,
+
+    /// Maximum number of notifications that are buffered per subscription while the subscriber
+    /// is busy.
+    #[arg(
+        long,
+        value_name = "COUNT",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        default_value_t = EthPubSubConfig::default().buffer_capacity
+    )]
+    pub rpc_subscription_buffer: usize,
+
+    /// What happens to a subscription if its buffer is full: drop-oldest, disconnect or
+    /// coalesce-new-heads.
+    ///
+    /// coalesce-new-heads drops all buffered headers of `newHeads` subscriptions, so the
+    /// subscriber continues with the latest header, and behaves like drop-oldest for other
+    /// subscriptions.
+    #[arg(long, value_name = "POLICY", default_value_t = SlowConsumerPolicy::default())]
+    pub rpc_slow_consumer_policy: SlowConsumerPolicy,
+
+    /// Stream the results of `eth_getLogs`, `trace_filter` and `debug_traceBlockBy*` over HTTP as
+    /// they become available, and enable the `reth_subscribeResultStream` subscription over WS and
+    /// IPC.
+    ///
+    /// Streamed `eth_getLogs` results are not limited by the maximum number of logs per response.
+    #[arg(long)]
+    pub rpc_stream_responses: bool,
+
     /// State cache configuration.
     #[clap(flatten)]
     pub rpc_state_cache: RpcStateCacheArgs,
@@ -242,6 +350,19 @@ impl RpcServerArgs {
         self
     }
 
+    /// Returns the layer that executes batches concurrently, if batches are executed concurrently
+    /// or have a cost limit.
+    fn rpc_batch_layer(&self) -> Option<BatchLayer> {
+        let max_cost = self.rpc_max_batch_cost.0;
+        (self.rpc_batch_parallelism > 1 || max_cost.is_some()).then(|| {
+            BatchLayer::new(self.rpc_batch_parallelism)
+                .max_calls(self.rpc_max_batch_calls)
+                .max_cost(max_cost)
+                .max_request_size(self.rpc_max_request_size_bytes())
+                .max_response_size(self.rpc_max_response_size_bytes())
+        })
+    }
+
     /// Append a random string to the ipc path, to prevent possible collisions when multiple nodes
     /// are being run on the same machine.
     pub fn with_ipc_random_path(mut self) -> Self {
@@ -276,6 +397,42 @@ impl RpcServerArgs {
         jwt_secret: JwtSecret,
         conf: &mut Conf,
     ) -> eyre::Result<RethRpcServerHandles>
+    where
+        Reth: RethNodeComponents,
+        Engine: EngineApiServer<EngineT>,
+        Conf: RethNodeCommandConfig,
+    {
+        self.start_servers_with(
+            components,
+            engine_api,
+            jwt_secret,
+            conf,
+            Methods::new(),
+            Methods::new(),
+            None,
+        )
+        .await
+    }
+
+    /// Same as [Self::start_servers], but additionally merges the given methods into all
+    /// configured transports and the given auth methods into the auth server before the
+    /// [RethNodeCommandConfig::extend_rpc_modules] hook is invoked.
+    ///
+    /// This is used to install node-internal namespaces, like the `evm_` namespace in dev mode or
+    /// `debug_insertBlock` on the auth server.
+    ///
+    /// If API keys are given, the http and ws servers only accept requests with one of the keys,
+    /// and the keys can be managed with the `admin_` API key methods on the ipc transport.
+    pub async fn start_servers_with<Reth, Engine, Conf, EngineT: EngineTypes>(
+        &self,
+        components: &Reth,
+        engine_api: Engine,
+        jwt_secret: JwtSecret,
+        conf: &mut Conf,
+        additional_methods: impl Into<Methods>,
+        additional_auth_methods: impl Into<Methods>,
+        api_keys: Option<ApiKeys>,
+    ) -> eyre::Result<RethRpcServerHandles>
     where
         Reth: RethNodeComponents,
         Engine: EngineApiServer<EngineT>,
@@ -292,8 +449,27 @@ impl RpcServerArgs {
             .with_network(components.network())
             .with_events(components.events())
             .with_executor(components.task_executor())
+            .with_evm_config(components.evm_config())
             .build_with_auth_server(module_config, engine_api);
 
+        modules.merge_configured(additional_methods)?;
+        auth_module.merge_auth_methods(additional_auth_methods)?;
+        if let Some(keys) = &api_keys {
+            // only local consumers may manage the keys of the public servers
+            modules.merge_ipc(AdminApiKeysApi::new(keys.clone()).into_rpc())?;
+        }
+        let streaming_layer = if self.rpc_stream_responses {
+            let streaming_api = registry.streaming_api();
+            modules.merge_ws(streaming_api.clone().into_rpc())?;
+            modules.merge_ipc(streaming_api.clone().into_rpc())?;
+            Some(
+                StreamingLayer::new(Arc::new(streaming_api))
+                    .max_request_size(self.rpc_max_request_size_bytes()),
+            )
+        } else {
+            None
+        };
+
         let rpc_components = RethRpcComponents {
             registry: &mut registry,
             modules: &mut modules,
@@ -302,7 +478,12 @@ impl RpcServerArgs {
         // apply configured customization
         conf.extend_rpc_modules(self, components, rpc_components)?;
 
-        let server_config = self.rpc_server_config();
+        let server_config = self
+            .rpc_server_config()
+            .with_api_key_layer(api_keys.map(|keys| {
+                ApiKeyLayer::new(keys).max_request_size(self.rpc_max_request_size_bytes())
+            }))
+            .with_streaming_layer(streaming_layer);
         let launch_rpc = modules.clone().start_server(server_config).map_ok(|handle| {
             if let Some(url) = handle.ipc_endpoint() {
                 info!(target: "reth::cli", url=%url, "RPC IPC server started");
@@ -324,7 +505,7 @@ impl RpcServerArgs {
 
         // launch servers concurrently
         let (rpc, auth) = futures::future::try_join(launch_rpc, launch_auth).await?;
-        let handles = RethRpcServerHandles { rpc, auth };
+        let handles = RethRpcServerHandles { rpc, auth, eth_cache: registry.eth_cache() };
 
         // call hook
         let rpc_components = RethRpcComponents {
@@ -354,6 +535,9 @@ impl RpcServerArgs {
             + EvmEnvProvider
             + ChainSpecProvider
             + ChangeSetReader
+            + HeaderAccumulatorReader
+            + PruneCheckpointReader
+            + ReorgHistoryProvider
             + Clone
             + Unpin
             + 'static,
@@ -429,6 +613,19 @@ impl RethRpcConfig for RpcServerArgs {
             .max_blocks_per_filter(self.rpc_max_blocks_per_filter.unwrap_or_max())
             .max_logs_per_response(self.rpc_max_logs_per_response.unwrap_or_max() as usize)
             .rpc_gas_cap(self.rpc_gas_cap)
+            .tracing_limits(
+                TracingLimits::default()
+                    .default_timeout(self.rpc_trace_timeout)
+                    .max_timeout(self.rpc_max_trace_timeout)
+                    .default_memory_limit(self.rpc_trace_memory_limit)
+                    .max_memory_limit(self.rpc_max_trace_memory_limit)
+                    .max_depth(self.rpc_max_trace_depth),
+            )
+            .pubsub_config(
+                EthPubSubConfig::default()
+                    .buffer_capacity(self.rpc_subscription_buffer)
+                    .slow_consumer_policy(self.rpc_slow_consumer_policy),
+            )
             .state_cache(self.state_cache_config())
             .gpo_config(self.gas_price_oracle_config())
     }
@@ -498,7 +695,9 @@ impl RethRpcConfig for RpcServerArgs {
     }
 
     fn rpc_server_config(&self) -> RpcServerConfig {
-        let mut config = RpcServerConfig::default().with_jwt_secret(self.rpc_secret_key());
+        let mut config = RpcServerConfig::default()
+            .with_jwt_secret(self.rpc_secret_key())
+            .with_batch_layer(self.rpc_batch_layer());
 
         if self.http {
             let socket_address = SocketAddr::new(self.http_addr, self.http_port);
@@ -558,6 +757,8 @@ impl Default for RpcServerArgs {
             ws_api: None,
             ipcdisable: false,
             ipcpath: constants::DEFAULT_IPC_ENDPOINT.to_string(),
+            trie_server_ipcpath: None,
+            grpc_addr: None,
             auth_addr: Ipv4Addr::LOCALHOST.into(),
             auth_port: constants::DEFAULT_AUTH_PORT,
             auth_jwtsecret: None,
@@ -567,9 +768,20 @@ impl Default for RpcServerArgs {
             rpc_max_subscriptions_per_connection: RPC_DEFAULT_MAX_SUBS_PER_CONN.into(),
             rpc_max_connections: RPC_DEFAULT_MAX_CONNECTIONS.into(),
             rpc_max_tracing_requests: constants::DEFAULT_MAX_TRACING_REQUESTS,
+            rpc_trace_timeout: TracingLimits::default().default_timeout,
+            rpc_max_trace_timeout: TracingLimits::default().max_timeout,
+            rpc_trace_memory_limit: TracingLimits::default().default_memory_limit,
+            rpc_max_trace_memory_limit: TracingLimits::default().max_memory_limit,
+            rpc_max_trace_depth: TracingLimits::default().max_depth,
             rpc_max_blocks_per_filter: constants::DEFAULT_MAX_BLOCKS_PER_FILTER.into(),
             rpc_max_logs_per_response: (constants::DEFAULT_MAX_LOGS_PER_RESPONSE as u64).into(),
             rpc_gas_cap: RPC_DEFAULT_GAS_CAP.into(),
+            rpc_batch_parallelism: constants::DEFAULT_BATCH_PARALLELISM,
+            rpc_max_batch_cost: ZeroAsNoneU64(None),
+            rpc_max_batch_calls: constants::DEFAULT_MAX_BATCH_CALLS,
+            rpc_subscription_buffer: EthPubSubConfig::default().buffer_capacity,
+            rpc_slow_consumer_policy: SlowConsumerPolicy::default(),
+            rpc_stream_responses: false,
             gas_price_oracle: GasPriceOracleArgs::default(),
             rpc_state_cache: RpcStateCacheArgs::default(),
         }
@@ -622,6 +834,82 @@ mod tests {
         args: T,
     }
 
+    #[test]
+    fn test_rpc_batch_layer() {
+        // batches are executed by the server unless the layer is enabled
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        assert!(args.rpc_batch_layer().is_none());
+
+        let args =
+            CommandParser::<RpcServerArgs>::parse_from(["reth", "--rpc-batch-parallelism", "8"])
+                .args;
+        assert!(args.rpc_batch_layer().is_some());
+
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--rpc-batch-parallelism",
+            "1",
+            "--rpc-max-batch-cost",
+            "100",
+        ])
+        .args;
+        assert!(args.rpc_batch_layer().is_some());
+
+        let args = CommandParser::<RpcServerArgs>::try_parse_from([
+            "reth",
+            "--rpc-batch-parallelism",
+            "0",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn test_rpc_slow_consumer_policy() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        let config = args.eth_config().pubsub;
+        assert_eq!(config, EthPubSubConfig::default());
+
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--rpc-subscription-buffer",
+            "16",
+            "--rpc-slow-consumer-policy",
+            "coalesce-new-heads",
+        ])
+        .args;
+        let config = args.eth_config().pubsub;
+        assert_eq!(config.buffer_capacity, 16);
+        assert_eq!(config.slow_consumer_policy, SlowConsumerPolicy::CoalesceNewHeads);
+
+        let args = CommandParser::<RpcServerArgs>::try_parse_from([
+            "reth",
+            "--rpc-slow-consumer-policy",
+            "block",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn test_rpc_tracing_limits() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.eth_config().tracing_limits, TracingLimits::default());
+
+        let args = CommandParser::<RpcServerArgs>::parse_from([
+            "reth",
+            "--rpc-trace-timeout",
+            "10",
+            "--rpc-max-trace-timeout",
+            "20",
+            "--rpc-max-trace-depth",
+            "64",
+        ])
+        .args;
+        let limits = args.eth_config().tracing_limits;
+        assert_eq!(limits.default_timeout, Duration::from_secs(10));
+        assert_eq!(limits.max_timeout, Duration::from_secs(20));
+        assert_eq!(limits.max_depth, 64);
+    }
+
     #[test]
     fn test_rpc_gas_cap() {
         let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
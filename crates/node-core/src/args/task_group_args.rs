@@ -0,0 +1,72 @@
+//! clap [Args](clap::Args) for task groups
+
+use clap::Args;
+use reth_tasks::group::TaskGroup;
+
+/// Parameters for running subsystems on dedicated runtimes
+#[derive(Debug, Clone, Default, Args, PartialEq, Eq)]
+#[clap(next_help_heading = "Task groups")]
+pub struct TaskGroupArgs {
+    /// Run the tasks of a subsystem on a dedicated runtime with the given number of worker
+    /// threads, e.g. `engine=2`. Can be repeated for multiple subsystems.
+    ///
+    /// Subsystems: network, engine, payload-builder, pruning, rpc.
+    #[arg(
+        long = "task-group.dedicated-runtime",
+        value_name = "GROUP=THREADS",
+        value_parser = parse_dedicated_runtime
+    )]
+    pub dedicated_runtimes: Vec<(TaskGroup, usize)>,
+}
+
+/// Parses a `GROUP=THREADS` pair.
+fn parse_dedicated_runtime(value: &str) -> Result<(TaskGroup, usize), String> {
+    let (group, threads) =
+        value.split_once('=').ok_or_else(|| format!("expected GROUP=THREADS, got {value}"))?;
+    let threads = threads.parse::<usize>().map_err(|err| format!("invalid thread count: {err}"))?;
+    if threads == 0 {
+        return Err("a dedicated runtime needs at least one thread".to_string())
+    }
+    Ok((group.parse()?, threads))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_task_group_args() {
+        let args = CommandParser::<TaskGroupArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, TaskGroupArgs::default());
+
+        let args = CommandParser::<TaskGroupArgs>::parse_from([
+            "reth",
+            "--task-group.dedicated-runtime",
+            "engine=2",
+            "--task-group.dedicated-runtime",
+            "payload-builder=4",
+        ])
+        .args;
+        assert_eq!(
+            args.dedicated_runtimes,
+            vec![(TaskGroup::Engine, 2), (TaskGroup::PayloadBuilder, 4)]
+        );
+
+        for invalid in ["engine", "engine=0", "consensus=1"] {
+            assert!(CommandParser::<TaskGroupArgs>::try_parse_from([
+                "reth",
+                "--task-group.dedicated-runtime",
+                invalid
+            ])
+            .is_err());
+        }
+    }
+}
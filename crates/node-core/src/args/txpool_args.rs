@@ -4,9 +4,10 @@ use crate::cli::config::RethTransactionPoolConfig;
 use clap::Args;
 use reth_primitives::Address;
 use reth_transaction_pool::{
-    LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
-    REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
-    TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
+    first_seen::DEFAULT_MAX_TRACKED_TRANSACTIONS, LocalTransactionConfig, PoolConfig,
+    PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP, REPLACE_BLOB_PRICE_BUMP,
+    TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER, TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT,
+    TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
 };
 /// Parameters for debugging purposes
 #[derive(Debug, Args, PartialEq)]
@@ -50,6 +51,16 @@ pub struct TxPoolArgs {
     /// Flag to allow certain addresses as local
     #[arg(long = "txpool.locals")]
     pub locals: Vec<Address>,
+
+    /// Record when transactions are first seen from peers or over RPC, and how long before their
+    /// inclusion in a block.
+    ///
+    /// The first sightings can be queried with `admin_transactionFirstSeen`.
+    #[arg(long = "txpool.first_seen")]
+    pub first_seen: bool,
+    /// Max number of transactions whose first sighting is kept.
+    #[arg(long = "txpool.first_seen_max", default_value_t = DEFAULT_MAX_TRACKED_TRANSACTIONS)]
+    pub first_seen_max: u32,
 }
 
 impl Default for TxPoolArgs {
@@ -66,6 +77,8 @@ impl Default for TxPoolArgs {
             blob_transaction_price_bump: REPLACE_BLOB_PRICE_BUMP,
             no_locals: false,
             locals: Default::default(),
+            first_seen: false,
+            first_seen_max: DEFAULT_MAX_TRACKED_TRANSACTIONS,
         }
     }
 }
@@ -6,8 +6,11 @@ use reth_network_api::{NetworkInfo, Peers};
 use reth_primitives::ChainSpec;
 use reth_provider::{
     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    DatabaseProviderFactory, EvmEnvProvider, StateProviderFactory,
+    DatabaseProviderFactory, EvmEnvProvider, HeaderAccumulatorReader, PruneCheckpointReader,
+    ReorgHistoryProvider, StateProviderFactory,
 };
+use reth_revm::EvmConfig;
+use reth_rpc::eth::cache::EthStateCache;
 use reth_rpc_builder::{
     auth::{AuthRpcModule, AuthServerHandle},
     RethModuleRegistry, RpcServerHandle, TransportRpcModules,
@@ -25,6 +28,9 @@ pub trait FullProvider<DB: Database>:
     + EvmEnvProvider
     + ChainSpecProvider
     + ChangeSetReader
+    + HeaderAccumulatorReader
+    + PruneCheckpointReader
+    + ReorgHistoryProvider
     + Clone
     + Unpin
     + 'static
@@ -39,6 +45,9 @@ impl<T, DB: Database> FullProvider<DB> for T where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + HeaderAccumulatorReader
+        + PruneCheckpointReader
+        + ReorgHistoryProvider
         + Clone
         + Unpin
         + 'static
@@ -79,6 +88,11 @@ pub trait RethNodeComponents: Clone + Send + Sync + 'static {
     fn chain_spec(&self) -> Arc<ChainSpec> {
         self.provider().chain_spec()
     }
+
+    /// Returns the customizations of the EVM used by all executions of the node.
+    fn evm_config(&self) -> EvmConfig {
+        EvmConfig::default()
+    }
 }
 
 /// Helper container to encapsulate [RethModuleRegistry],[TransportRpcModules] and [AuthRpcModule].
@@ -127,6 +141,8 @@ pub struct RethNodeComponentsImpl<DB, Provider, Pool, Network, Events, Tasks> {
     pub task_executor: Tasks,
     /// Represents the events subscription handler instance.
     pub events: Events,
+    /// Represents the customizations of the EVM.
+    pub evm_config: EvmConfig,
 }
 
 impl<DB, Provider, Pool, Network, Events, Tasks>
@@ -140,7 +156,21 @@ impl<DB, Provider, Pool, Network, Events, Tasks>
         task_executor: Tasks,
         events: Events,
     ) -> Self {
-        Self { provider, pool, network, task_executor, events, __phantom: std::marker::PhantomData }
+        Self {
+            provider,
+            pool,
+            network,
+            task_executor,
+            events,
+            evm_config: EvmConfig::default(),
+            __phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets the customizations of the EVM.
+    pub fn with_evm_config(mut self, evm_config: EvmConfig) -> Self {
+        self.evm_config = evm_config;
+        self
     }
 }
 
@@ -180,6 +210,10 @@ where
     fn events(&self) -> Self::Events {
         self.events.clone()
     }
+
+    fn evm_config(&self) -> EvmConfig {
+        self.evm_config.clone()
+    }
 }
 
 /// Contains the handles to the spawned RPC servers.
@@ -201,4 +235,6 @@ pub struct RethRpcServerHandles {
     pub rpc: RpcServerHandle,
     /// The handle to the auth server (engine API)
     pub auth: AuthServerHandle,
+    /// The cache of the `eth` handlers.
+    pub eth_cache: EthStateCache,
 }
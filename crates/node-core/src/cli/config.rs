@@ -13,6 +13,7 @@ use reth_rpc_builder::{
 };
 use reth_transaction_pool::PoolConfig;
 use std::{borrow::Cow, path::PathBuf, time::Duration};
+use tokio::sync::watch;
 
 /// A trait that provides a configured RPC server.
 ///
@@ -100,17 +101,82 @@ pub trait PayloadBuilderConfig {
     /// The deadline for when the payload builder job should resolve.
     fn deadline(&self) -> Duration;
 
+    /// Returns the receiver of deadline changes at runtime, if the deadline can change.
+    fn deadline_updates(&self) -> Option<watch::Receiver<Duration>> {
+        None
+    }
+
     /// Target gas ceiling for built blocks.
     fn max_gas_limit(&self) -> u64;
 
     /// Maximum number of tasks to spawn for building a payload.
     fn max_payload_tasks(&self) -> usize;
 
+    /// Maximum number of concurrent payload jobs.
+    fn max_payload_jobs(&self) -> usize;
+
+    /// How long resolved payloads are kept for repeated requests.
+    fn resolved_payload_retention(&self) -> Duration;
+
     /// Returns whether or not to construct the pending block.
     #[cfg(feature = "optimism")]
     fn compute_pending_block(&self) -> bool;
 }
 
+/// A [PayloadBuilderConfig] whose deadline can be changed at runtime, see
+/// [ConfigWatcher](crate::config_watcher::ConfigWatcher).
+#[derive(Debug)]
+pub struct PayloadBuilderConfigWithDeadline<'a, C> {
+    inner: &'a C,
+    deadline: watch::Receiver<Duration>,
+}
+
+impl<'a, C> PayloadBuilderConfigWithDeadline<'a, C> {
+    /// Wraps the config, with the deadline taken from the given receiver.
+    pub fn new(inner: &'a C, deadline: watch::Receiver<Duration>) -> Self {
+        Self { inner, deadline }
+    }
+}
+
+impl<C: PayloadBuilderConfig> PayloadBuilderConfig for PayloadBuilderConfigWithDeadline<'_, C> {
+    fn extradata(&self) -> Cow<'_, str> {
+        self.inner.extradata()
+    }
+
+    fn interval(&self) -> Duration {
+        self.inner.interval()
+    }
+
+    fn deadline(&self) -> Duration {
+        *self.deadline.borrow()
+    }
+
+    fn deadline_updates(&self) -> Option<watch::Receiver<Duration>> {
+        Some(self.deadline.clone())
+    }
+
+    fn max_gas_limit(&self) -> u64 {
+        self.inner.max_gas_limit()
+    }
+
+    fn max_payload_tasks(&self) -> usize {
+        self.inner.max_payload_tasks()
+    }
+
+    fn max_payload_jobs(&self) -> usize {
+        self.inner.max_payload_jobs()
+    }
+
+    fn resolved_payload_retention(&self) -> Duration {
+        self.inner.resolved_payload_retention()
+    }
+
+    #[cfg(feature = "optimism")]
+    fn compute_pending_block(&self) -> bool {
+        self.inner.compute_pending_block()
+    }
+}
+
 /// A trait that represents the configured network and can be used to apply additional configuration
 /// to the network.
 pub trait RethNetworkConfig {
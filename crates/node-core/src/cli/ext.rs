@@ -9,8 +9,9 @@ use reth_basic_payload_builder::{
     BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig, PayloadBuilder,
 };
 use reth_node_api::EngineTypes;
-use reth_payload_builder::{PayloadBuilderHandle, PayloadBuilderService};
+use reth_payload_builder::{PayloadBuilderHandle, PayloadBuilderService, PayloadJobsConfig};
 use reth_provider::CanonStateSubscriptions;
+use reth_revm::EvmConfig;
 use reth_tasks::TaskSpawner;
 use std::{fmt, marker::PhantomData};
 
@@ -36,13 +37,24 @@ impl RethCliExt for () {
 ///
 /// The functions are invoked during the initialization of the node command in the following order:
 ///
-/// 1. [configure_network](RethNodeCommandConfig::configure_network)
-/// 2. [on_components_initialized](RethNodeCommandConfig::on_components_initialized)
-/// 3. [spawn_payload_builder_service](RethNodeCommandConfig::spawn_payload_builder_service)
-/// 4. [extend_rpc_modules](RethNodeCommandConfig::extend_rpc_modules)
-/// 5. [on_rpc_server_started](RethNodeCommandConfig::on_rpc_server_started)
-/// 6. [on_node_started](RethNodeCommandConfig::on_node_started)
+/// 1. [configure_evm](RethNodeCommandConfig::configure_evm)
+/// 2. [configure_network](RethNodeCommandConfig::configure_network)
+/// 3. [on_components_initialized](RethNodeCommandConfig::on_components_initialized)
+/// 4. [spawn_payload_builder_service](RethNodeCommandConfig::spawn_payload_builder_service)
+/// 5. [extend_rpc_modules](RethNodeCommandConfig::extend_rpc_modules)
+/// 6. [on_rpc_server_started](RethNodeCommandConfig::on_rpc_server_started)
+/// 7. [on_node_started](RethNodeCommandConfig::on_node_started)
 pub trait RethNodeCommandConfig: fmt::Debug {
+    /// Invoked with the [EvmConfig] before any component that executes transactions is built.
+    ///
+    /// This allows registering additional precompiles and overriding the EVM configuration. The
+    /// customizations apply alike to block execution, RPC simulations and the Ethereum payload
+    /// builder.
+    fn configure_evm(&mut self, config: &mut EvmConfig) -> eyre::Result<()> {
+        let _ = config;
+        Ok(())
+    }
+
     /// Invoked with the network configuration before the network is configured.
     ///
     /// This allows additional configuration of the network before it is launched.
@@ -156,7 +168,7 @@ pub trait RethNodeCommandConfig: fmt::Debug {
         #[cfg(feature = "optimism")]
         let payload_job_config = payload_job_config.extradata(Default::default());
 
-        let payload_generator = BasicPayloadJobGenerator::with_builder(
+        let mut payload_generator = BasicPayloadJobGenerator::with_builder(
             components.provider(),
             components.pool(),
             components.task_executor(),
@@ -164,9 +176,16 @@ pub trait RethNodeCommandConfig: fmt::Debug {
             components.chain_spec(),
             payload_builder,
         );
-        let (payload_service, payload_builder) = PayloadBuilderService::new(
+        if let Some(deadline) = conf.deadline_updates() {
+            payload_generator = payload_generator.with_deadline_updates(deadline);
+        }
+        let payload_jobs_config = PayloadJobsConfig::default()
+            .max_jobs(conf.max_payload_jobs())
+            .resolved_retention(conf.resolved_payload_retention());
+        let (payload_service, payload_builder) = PayloadBuilderService::with_config(
             payload_generator,
             components.events().canonical_state_stream(),
+            payload_jobs_config,
         );
 
         components
@@ -246,6 +265,14 @@ impl<T> NoArgs<T> {
 }
 
 impl<T: RethNodeCommandConfig> RethNodeCommandConfig for NoArgs<T> {
+    fn configure_evm(&mut self, config: &mut EvmConfig) -> eyre::Result<()> {
+        if let Some(conf) = self.inner_mut() {
+            conf.configure_evm(config)
+        } else {
+            Ok(())
+        }
+    }
+
     fn configure_network<Conf, Reth>(
         &mut self,
         config: &mut Conf,
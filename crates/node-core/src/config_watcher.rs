@@ -0,0 +1,555 @@
+//! Hot reloading of the node configuration file.
+//!
+//! The [ConfigWatcher] polls the configuration file for changes and applies changes to settings
+//! that can be tuned at runtime. Changes to all other settings are reported, but only take effect
+//! after a restart.
+//!
+//! Runtime-tunable settings:
+//!
+//! - `peers.connection_info.max_inbound`
+//! - `peers.connection_info.max_outbound`
+//! - `metrics.disabled_subsystems`
+//! - `rpc.api_keys`, including the rate limits of the keys
+//! - `rpc.cache`
+//! - `prune.delete_limit`
+//! - `builder.deadline`
+//!
+//! Only settings that changed in the file since it was last loaded are applied, so changes made
+//! at runtime by other means, e.g. API keys added via the `admin_` API, are kept until the setting
+//! is edited in the file. Settings that are set on the command line keep their command line
+//! value, see [CliOverrides].
+
+use crate::{
+    args::{NetworkArgs, PayloadBuilderArgs, RpcStateCacheArgs},
+    metrics::cardinality::SubsystemFilter,
+};
+use jsonrpsee::{
+    core::RpcResult,
+    types::error::{ErrorObject, INTERNAL_ERROR_CODE},
+};
+use reth_config::{config::RpcCacheConfig, Config};
+use reth_network::{peers::PeersHandle, PeersConfig};
+use reth_primitives::constants::SLOT_DURATION;
+use reth_rpc::{
+    eth::cache::{
+        EthStateCache, DEFAULT_BLOCK_CACHE_MAX_LEN, DEFAULT_ENV_CACHE_MAX_LEN,
+        DEFAULT_RECEIPT_CACHE_MAX_LEN,
+    },
+    ApiKeys,
+};
+use reth_rpc_api::AdminConfigApiServer;
+use reth_rpc_types::ApiKey;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+
+/// The default interval at which the configuration file is checked for changes.
+pub const DEFAULT_CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Runtime-tunable settings that are set on the command line.
+///
+/// Command line flags take precedence over the configuration file, so reloads leave these
+/// settings alone. Flags with a default value count as set if they differ from their default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CliOverrides {
+    /// `--max-inbound-peers`
+    pub max_inbound_peers: Option<usize>,
+    /// `--max-outbound-peers`
+    pub max_outbound_peers: Option<usize>,
+    /// `--rpc-cache.max-blocks`
+    pub rpc_cache_max_blocks: Option<u32>,
+    /// `--rpc-cache.max-receipts`
+    pub rpc_cache_max_receipts: Option<u32>,
+    /// `--rpc-cache.max-envs`
+    pub rpc_cache_max_envs: Option<u32>,
+    /// `--builder.deadline`
+    pub payload_deadline: Option<Duration>,
+}
+
+impl CliOverrides {
+    /// Collects the runtime-tunable settings that are set by the given command line arguments.
+    pub fn from_args(
+        network: &NetworkArgs,
+        rpc_cache: &RpcStateCacheArgs,
+        builder: &PayloadBuilderArgs,
+    ) -> Self {
+        Self {
+            max_inbound_peers: network.max_inbound_peers,
+            max_outbound_peers: network.max_outbound_peers,
+            rpc_cache_max_blocks: non_default(rpc_cache.max_blocks, DEFAULT_BLOCK_CACHE_MAX_LEN),
+            rpc_cache_max_receipts: non_default(
+                rpc_cache.max_receipts,
+                DEFAULT_RECEIPT_CACHE_MAX_LEN,
+            ),
+            rpc_cache_max_envs: non_default(rpc_cache.max_envs, DEFAULT_ENV_CACHE_MAX_LEN),
+            payload_deadline: non_default(builder.deadline, SLOT_DURATION),
+        }
+    }
+
+    /// Returns the `(max_inbound, max_outbound)` connection limits of the configuration, with
+    /// the command line taking precedence.
+    fn connection_limits(&self, config: &Config) -> (usize, usize) {
+        let (max_inbound, max_outbound) = connection_limits(config);
+        (
+            self.max_inbound_peers.unwrap_or(max_inbound),
+            self.max_outbound_peers.unwrap_or(max_outbound),
+        )
+    }
+
+    /// Returns the RPC cache capacities of the configuration, with the command line taking
+    /// precedence and unset capacities filled in with their defaults.
+    fn rpc_cache(&self, config: &Config) -> RpcCacheConfig {
+        let cache = &config.rpc.cache;
+        RpcCacheConfig {
+            max_blocks: Some(
+                self.rpc_cache_max_blocks
+                    .or(cache.max_blocks)
+                    .unwrap_or(DEFAULT_BLOCK_CACHE_MAX_LEN),
+            ),
+            max_receipts: Some(
+                self.rpc_cache_max_receipts
+                    .or(cache.max_receipts)
+                    .unwrap_or(DEFAULT_RECEIPT_CACHE_MAX_LEN),
+            ),
+            max_envs: Some(
+                self.rpc_cache_max_envs.or(cache.max_envs).unwrap_or(DEFAULT_ENV_CACHE_MAX_LEN),
+            ),
+        }
+    }
+
+    /// Returns the payload deadline of the configuration, with the command line taking
+    /// precedence.
+    fn payload_deadline(&self, config: &Config) -> Duration {
+        self.payload_deadline.or(config.builder.deadline).unwrap_or(SLOT_DURATION)
+    }
+}
+
+/// Watches the configuration file and applies changes to runtime-tunable settings.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    /// The path to the configuration file.
+    path: PathBuf,
+    /// How often the file is checked for changes.
+    poll_interval: Duration,
+    /// The modification time of the file when it was last loaded.
+    last_modified: Option<SystemTime>,
+    /// The configuration as it was last loaded from the file.
+    loaded: Config,
+    /// The settings that are set on the command line.
+    overrides: CliOverrides,
+    /// Handle to the peers manager, used to apply connection limits.
+    peers: PeersHandle,
+    /// Filter of the served metrics, used to apply the disabled metrics subsystems.
+    metrics_subsystems: SubsystemFilter,
+    /// The API keys of the RPC servers, if the servers require keys.
+    api_keys: Option<ApiKeys>,
+    /// The RPC state cache, used to apply cache capacities.
+    eth_cache: Option<EthStateCache>,
+    /// Sender of the prune delete limit, and the limit of the chain that applies if the file
+    /// sets none.
+    prune_delete_limit: Option<(watch::Sender<usize>, usize)>,
+    /// Sender of the payload deadline.
+    payload_deadline: Option<watch::Sender<Duration>>,
+    /// The effective configuration.
+    effective: watch::Sender<Config>,
+}
+
+impl ConfigWatcher {
+    /// Creates a new watcher for the configuration file at `path`, that was loaded as `config`.
+    pub fn new(
+        path: PathBuf,
+        mut config: Config,
+        overrides: CliOverrides,
+        peers: PeersHandle,
+        metrics_subsystems: SubsystemFilter,
+    ) -> Self {
+        let last_modified = modified(&path);
+        // The given config may contain overrides from the command line, so keep track of the file
+        // contents separately to detect which settings were changed in the file.
+        let loaded = confy::load_path::<Config>(&path).unwrap_or_else(|_| config.clone());
+        config.peers = config
+            .peers
+            .with_max_inbound_opt(overrides.max_inbound_peers)
+            .with_max_outbound_opt(overrides.max_outbound_peers);
+        let (effective, _) = watch::channel(config);
+        Self {
+            path,
+            poll_interval: DEFAULT_CONFIG_POLL_INTERVAL,
+            last_modified,
+            loaded,
+            overrides,
+            peers,
+            metrics_subsystems,
+            api_keys: None,
+            eth_cache: None,
+            prune_delete_limit: None,
+            payload_deadline: None,
+            effective,
+        }
+    }
+
+    /// Sets the interval at which the configuration file is checked for changes.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Applies changes of the API keys in the file to the given registry of the RPC servers.
+    pub fn with_api_keys(mut self, api_keys: ApiKeys) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+
+    /// Applies the RPC cache capacities of the file to the given cache, now and on every reload.
+    pub fn with_eth_cache(mut self, eth_cache: EthStateCache) -> Self {
+        let cache = self.overrides.rpc_cache(&self.loaded);
+        set_cache_capacities(&eth_cache, cache);
+        self.effective.send_modify(|effective| effective.rpc.cache = cache);
+        self.eth_cache = Some(eth_cache);
+        self
+    }
+
+    /// Returns a receiver of the prune delete limit, which is the limit of the file or `default`
+    /// if the file sets none.
+    pub fn prune_delete_limit(&mut self, default: usize) -> watch::Receiver<usize> {
+        let limit = file_delete_limit(&self.loaded).unwrap_or(default);
+        self.effective.send_modify(|effective| set_delete_limit(effective, limit));
+        let (tx, rx) = watch::channel(limit);
+        self.prune_delete_limit = Some((tx, default));
+        rx
+    }
+
+    /// Returns a receiver of the payload deadline, which is the deadline of the command line or
+    /// the file.
+    pub fn payload_deadline(&mut self) -> watch::Receiver<Duration> {
+        let deadline = self.overrides.payload_deadline(&self.loaded);
+        self.effective.send_modify(|effective| effective.builder.deadline = Some(deadline));
+        let (tx, rx) = watch::channel(deadline);
+        self.payload_deadline = Some(tx);
+        rx
+    }
+
+    /// Returns a receiver for the effective configuration.
+    pub fn subscribe(&self) -> watch::Receiver<Config> {
+        self.effective.subscribe()
+    }
+
+    /// Polls the configuration file forever and applies any changes.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+
+            let last_modified = modified(&self.path);
+            if last_modified == self.last_modified {
+                continue
+            }
+            self.last_modified = last_modified;
+
+            match confy::load_path::<Config>(&self.path) {
+                Ok(config) => self.apply(config),
+                Err(err) => {
+                    warn!(target: "reth::cli", path = ?self.path, %err, "Failed to reload configuration, keeping current configuration")
+                }
+            }
+        }
+    }
+
+    /// Validates the new configuration and applies the runtime-tunable settings that changed
+    /// since the file was last loaded.
+    fn apply(&mut self, new: Config) {
+        if let Err(err) = validate(&new) {
+            warn!(target: "reth::cli", path = ?self.path, %err, "Invalid configuration, keeping current configuration");
+            return
+        }
+
+        let mut effective = self.effective.borrow().clone();
+
+        if connection_limits(&self.loaded) != connection_limits(&new) {
+            let (max_inbound, max_outbound) = self.overrides.connection_limits(&new);
+            if (max_inbound, max_outbound) != connection_limits(&effective) {
+                self.peers.set_connection_limits(max_inbound, max_outbound);
+                effective.peers =
+                    effective.peers.with_max_inbound(max_inbound).with_max_outbound(max_outbound);
+                info!(target: "reth::cli", max_inbound, max_outbound, "Applied new peer connection limits");
+            } else {
+                debug!(target: "reth::cli", "Peer connection limits are set on the command line, ignoring changed limits");
+            }
+        }
+
+        if self.loaded.metrics.disabled_subsystems != new.metrics.disabled_subsystems {
+            self.metrics_subsystems.set_disabled(&new.metrics.disabled_subsystems);
+            effective.metrics.disabled_subsystems = new.metrics.disabled_subsystems.clone();
+            info!(target: "reth::cli", disabled_subsystems = ?new.metrics.disabled_subsystems, "Applied new disabled metrics subsystems");
+        }
+
+        if self.loaded.rpc.api_keys != new.rpc.api_keys {
+            match (&self.api_keys, &new.rpc.api_keys) {
+                (Some(registry), Some(keys)) => {
+                    let (upserted, removed) = diff_api_keys(
+                        self.loaded.rpc.api_keys.as_deref().unwrap_or_default(),
+                        keys,
+                    );
+                    for key in &removed {
+                        registry.remove(key);
+                    }
+                    let upserted_count = upserted.len();
+                    for key in upserted {
+                        registry.insert(key);
+                    }
+                    effective.rpc.api_keys = new.rpc.api_keys.clone();
+                    info!(target: "reth::cli", upserted = upserted_count, removed = removed.len(), "Applied new RPC API keys");
+                }
+                _ => {
+                    warn!(target: "reth::cli", path = ?self.path, "Enabling or disabling RPC API keys only takes effect after a restart")
+                }
+            }
+        }
+
+        if self.loaded.rpc.cache != new.rpc.cache {
+            if let Some(eth_cache) = &self.eth_cache {
+                let cache = self.overrides.rpc_cache(&new);
+                if cache != effective.rpc.cache {
+                    set_cache_capacities(eth_cache, cache);
+                    effective.rpc.cache = cache;
+                    info!(target: "reth::cli", ?cache, "Applied new RPC cache capacities");
+                }
+            }
+        }
+
+        if file_delete_limit(&self.loaded) != file_delete_limit(&new) {
+            if let Some((delete_limit, default)) = &self.prune_delete_limit {
+                let limit = file_delete_limit(&new).unwrap_or(*default);
+                delete_limit.send_replace(limit);
+                set_delete_limit(&mut effective, limit);
+                info!(target: "reth::cli", delete_limit = limit, "Applied new prune delete limit");
+            }
+        }
+
+        if self.loaded.builder.deadline != new.builder.deadline {
+            if let Some(payload_deadline) = &self.payload_deadline {
+                let deadline = self.overrides.payload_deadline(&new);
+                if deadline != *payload_deadline.borrow() {
+                    payload_deadline.send_replace(deadline);
+                    effective.builder.deadline = Some(deadline);
+                    info!(target: "reth::cli", ?deadline, "Applied new payload deadline");
+                }
+            }
+        }
+
+        // Everything else requires a restart.
+        if without_tunables(&new) != without_tunables(&self.loaded) {
+            warn!(target: "reth::cli", path = ?self.path, "Configuration changes to settings that are not runtime-tunable only take effect after a restart");
+        }
+
+        self.loaded = new;
+        self.effective.send_replace(effective);
+    }
+}
+
+/// Returns the value if it differs from the default.
+fn non_default<T: PartialEq>(value: T, default: T) -> Option<T> {
+    (value != default).then_some(value)
+}
+
+/// Returns the `(max_inbound, max_outbound)` connection limits of the configuration.
+fn connection_limits(config: &Config) -> (usize, usize) {
+    let info = &config.peers.connection_info;
+    (info.max_inbound(), info.max_outbound())
+}
+
+/// Returns the prune delete limit set in the configuration.
+fn file_delete_limit(config: &Config) -> Option<usize> {
+    config.prune.as_ref().and_then(|prune| prune.delete_limit)
+}
+
+/// Sets the prune delete limit of the configuration, if it configures pruning.
+fn set_delete_limit(config: &mut Config, limit: usize) {
+    if let Some(prune) = config.prune.as_mut() {
+        prune.delete_limit = Some(limit);
+    }
+}
+
+fn set_cache_capacities(eth_cache: &EthStateCache, cache: RpcCacheConfig) {
+    eth_cache.set_max_lengths(
+        cache.max_blocks.unwrap_or(DEFAULT_BLOCK_CACHE_MAX_LEN),
+        cache.max_receipts.unwrap_or(DEFAULT_RECEIPT_CACHE_MAX_LEN),
+        cache.max_envs.unwrap_or(DEFAULT_ENV_CACHE_MAX_LEN),
+    );
+}
+
+/// Returns the keys of `new` that are added or changed compared to `old`, and the keys of `old`
+/// that are removed.
+fn diff_api_keys(old: &[ApiKey], new: &[ApiKey]) -> (Vec<ApiKey>, Vec<String>) {
+    let upserted = new.iter().filter(|key| !old.contains(key)).cloned().collect();
+    let removed = old
+        .iter()
+        .filter(|key| !new.iter().any(|new| new.key == key.key))
+        .map(|key| key.key.clone())
+        .collect();
+    (upserted, removed)
+}
+
+/// Returns the configuration with all runtime-tunable settings reset.
+fn without_tunables(config: &Config) -> Config {
+    let defaults = PeersConfig::default();
+    let (max_inbound, max_outbound) =
+        (defaults.connection_info.max_inbound(), defaults.connection_info.max_outbound());
+    let mut config = config.clone();
+    config.peers = config.peers.with_max_inbound(max_inbound).with_max_outbound(max_outbound);
+    config.metrics.disabled_subsystems = Vec::new();
+    config.rpc.api_keys = None;
+    config.rpc.cache = RpcCacheConfig::default();
+    if let Some(prune) = config.prune.as_mut() {
+        prune.delete_limit = None;
+    }
+    config.builder.deadline = None;
+    config
+}
+
+/// Validates the runtime-tunable settings of the configuration.
+fn validate(config: &Config) -> eyre::Result<()> {
+    let (max_inbound, max_outbound) = connection_limits(config);
+    eyre::ensure!(
+        max_inbound > 0 || max_outbound > 0,
+        "at least one of max_inbound and max_outbound must be non-zero"
+    );
+    eyre::ensure!(file_delete_limit(config) != Some(0), "prune delete_limit must be non-zero");
+    eyre::ensure!(
+        config.builder.deadline != Some(Duration::ZERO),
+        "builder deadline must be non-zero"
+    );
+    Ok(())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// `admin_config` implementation that reports the effective configuration.
+#[derive(Debug, Clone)]
+pub struct AdminConfigApi {
+    effective: watch::Receiver<Config>,
+}
+
+impl AdminConfigApi {
+    /// Creates a new instance of the [AdminConfigApi].
+    pub fn new(effective: watch::Receiver<Config>) -> Self {
+        Self { effective }
+    }
+}
+
+impl AdminConfigApiServer for AdminConfigApi {
+    /// Handler for `admin_config`
+    fn config(&self) -> RpcResult<serde_json::Value> {
+        serde_json::to_value(&*self.effective.borrow())
+            .map_err(|err| ErrorObject::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_config::PruneConfig;
+
+    #[test]
+    fn validate_connection_limits() {
+        let mut config = Config::default();
+        assert!(validate(&config).is_ok());
+
+        config.peers = PeersConfig::default().with_max_inbound(0).with_max_outbound(0);
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn validate_budgets() {
+        let mut config = Config::default();
+        config.prune = Some(PruneConfig { delete_limit: Some(0), ..Default::default() });
+        assert!(validate(&config).is_err());
+
+        let mut config = Config::default();
+        config.builder.deadline = Some(Duration::ZERO);
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn command_line_takes_precedence() {
+        let overrides = CliOverrides {
+            max_inbound_peers: Some(5),
+            rpc_cache_max_blocks: Some(10),
+            payload_deadline: Some(Duration::from_secs(2)),
+            ..Default::default()
+        };
+
+        let mut config = Config::default();
+        config.peers = PeersConfig::default().with_max_inbound(50).with_max_outbound(60);
+        config.rpc.cache.max_blocks = Some(100);
+        config.rpc.cache.max_receipts = Some(200);
+        config.builder.deadline = Some(Duration::from_secs(4));
+
+        assert_eq!(overrides.connection_limits(&config), (5, 60));
+        assert_eq!(
+            overrides.rpc_cache(&config),
+            RpcCacheConfig {
+                max_blocks: Some(10),
+                max_receipts: Some(200),
+                max_envs: Some(DEFAULT_ENV_CACHE_MAX_LEN),
+            }
+        );
+        assert_eq!(overrides.payload_deadline(&config), Duration::from_secs(2));
+        assert_eq!(CliOverrides::default().payload_deadline(&config), Duration::from_secs(4));
+        assert_eq!(CliOverrides::default().payload_deadline(&Config::default()), SLOT_DURATION);
+    }
+
+    #[test]
+    fn overrides_from_default_args_are_unset() {
+        let overrides = CliOverrides::from_args(
+            &NetworkArgs::default(),
+            &RpcStateCacheArgs::default(),
+            &PayloadBuilderArgs::default(),
+        );
+        assert_eq!(overrides, CliOverrides::default());
+
+        let rpc_cache = RpcStateCacheArgs { max_envs: 1, ..Default::default() };
+        let overrides = CliOverrides::from_args(
+            &NetworkArgs::default(),
+            &rpc_cache,
+            &PayloadBuilderArgs::default(),
+        );
+        assert_eq!(overrides.rpc_cache_max_envs, Some(1));
+    }
+
+    #[test]
+    fn api_key_changes() {
+        let key = |key: &str, requests_per_second| ApiKey {
+            key: key.to_string(),
+            namespaces: Vec::new(),
+            requests_per_second,
+            max_call_gas: None,
+        };
+        let old = [key("a", None), key("b", Some(10)), key("c", None)];
+        let new = [key("a", None), key("b", Some(20)), key("d", None)];
+
+        let (upserted, removed) = diff_api_keys(&old, &new);
+        assert_eq!(upserted, vec![key("b", Some(20)), key("d", None)]);
+        assert_eq!(removed, vec!["c".to_string()]);
+    }
+
+    #[test]
+    fn tunables_are_ignored_for_restart_check() {
+        let loaded = Config::default();
+        let mut new = loaded.clone();
+        new.peers = PeersConfig::default().with_max_inbound(1);
+        new.rpc.cache.max_blocks = Some(1);
+        new.builder.deadline = Some(Duration::from_secs(1));
+        new.metrics.disabled_subsystems = vec!["network".to_string()];
+        assert_eq!(without_tunables(&new), without_tunables(&loaded));
+
+        new.sessions.session_command_buffer += 1;
+        assert_ne!(without_tunables(&new), without_tunables(&loaded));
+    }
+}
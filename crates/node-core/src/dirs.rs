@@ -287,6 +287,13 @@ impl<D> ChainPath<D> {
         self.0.join("snapshots").into()
     }
 
+    /// Returns the path to the history of recorded database stats for this chain.
+    ///
+    /// `<DIR>/<CHAIN_ID>/db-stats.jsonl`
+    pub fn db_stats_path(&self) -> PathBuf {
+        self.0.join("db-stats.jsonl").into()
+    }
+
     /// Returns the path to the reth p2p secret key for this chain.
     ///
     /// `<DIR>/<CHAIN_ID>/discovery-secret`
@@ -316,6 +323,42 @@ impl<D> ChainPath<D> {
         self.0.join("txpool-transactions-backup.rlp").into()
     }
 
+    /// Returns the path to the reorg history file for this chain.
+    ///
+    /// `<DIR>/<CHAIN_ID>/reorgs.json`
+    pub fn reorg_log_path(&self) -> PathBuf {
+        self.0.join("reorgs.json").into()
+    }
+
+    /// Returns the path to the journal of the in-memory chains of the blockchain tree for this
+    /// chain.
+    ///
+    /// `<DIR>/<CHAIN_ID>/chains.journal`
+    pub fn chain_journal_path(&self) -> PathBuf {
+        self.0.join("chains.journal").into()
+    }
+
+    /// Returns the path to the directory state root latency dumps are written to for this chain.
+    ///
+    /// `<DIR>/<CHAIN_ID>/root-slo`
+    pub fn root_slo_dump_path(&self) -> PathBuf {
+        self.0.join("root-slo").into()
+    }
+
+    /// Returns the path to the directory crash bundles are written to for this chain.
+    ///
+    /// `<DIR>/<CHAIN_ID>/crash-bundles`
+    pub fn crash_bundles_path(&self) -> PathBuf {
+        self.0.join("crash-bundles").into()
+    }
+
+    /// Returns the path to the resume token of the execution outcome stream for this chain.
+    ///
+    /// `<DIR>/<CHAIN_ID>/outcome-stream.json`
+    pub fn outcome_stream_token_path(&self) -> PathBuf {
+        self.0.join("outcome-stream.json").into()
+    }
+
     /// Returns the path to the config file for this chain.
     ///
     /// `<DIR>/<CHAIN_ID>/reth.toml`
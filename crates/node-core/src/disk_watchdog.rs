@@ -0,0 +1,274 @@
+//! Graceful degradation under disk pressure.
+//!
+//! The [DiskWatchdog] periodically checks the free space of the filesystem the database is on and
+//! restricts the node step by step as it runs out of space, instead of letting a commit fail
+//! half-way:
+//!
+//! - [DiskPressure::Degraded]: optional writes, like the reorg log and the engine API store, are
+//!   paused and the pruner runs on every block with its maximum delete limit.
+//! - [DiskPressure::Critical]: sync is halted, so nothing is written to the database until free
+//!   space is available again.
+//!
+//! The current state is reported by `admin_diskStatus`.
+
+use jsonrpsee::core::RpcResult;
+use reth_metrics::{metrics::Gauge, Metrics};
+use reth_rpc_api::AdminDiskApiServer;
+use reth_rpc_types::{DiskPressure, DiskStatus};
+use std::{io, path::PathBuf, time::Duration};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// The default interval at which free disk space is checked.
+pub const DEFAULT_DISK_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A pressure level is only left once free space exceeds its threshold by `1 /
+/// RECOVERY_MARGIN_DIVISOR` of the threshold, so the node doesn't flap around a threshold.
+const RECOVERY_MARGIN_DIVISOR: u64 = 10;
+
+/// Free space thresholds in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskThresholds {
+    /// Available bytes below which the node is degraded.
+    pub degraded: u64,
+    /// Available bytes below which sync is halted.
+    pub critical: u64,
+}
+
+impl DiskThresholds {
+    /// Returns the pressure for the available bytes, given the current pressure.
+    fn pressure(&self, current: DiskPressure, available: u64) -> DiskPressure {
+        let below = |threshold: u64, level: DiskPressure| {
+            let threshold = if current >= level {
+                threshold.saturating_add(threshold / RECOVERY_MARGIN_DIVISOR)
+            } else {
+                threshold
+            };
+            available < threshold
+        };
+
+        if below(self.critical, DiskPressure::Critical) {
+            DiskPressure::Critical
+        } else if below(self.degraded, DiskPressure::Degraded) {
+            DiskPressure::Degraded
+        } else {
+            DiskPressure::Normal
+        }
+    }
+}
+
+/// Watches the free disk space and signals when the node needs to be restricted.
+#[derive(Debug)]
+pub struct DiskWatchdog {
+    /// The monitored directory.
+    path: PathBuf,
+    thresholds: DiskThresholds,
+    /// How often the free space is checked.
+    check_interval: Duration,
+    /// The latest status.
+    status: watch::Sender<DiskStatus>,
+    /// Set while the pressure is at least [DiskPressure::Degraded].
+    degraded: watch::Sender<bool>,
+    /// Set while the pressure is [DiskPressure::Critical].
+    critical: watch::Sender<bool>,
+    metrics: DiskWatchdogMetrics,
+}
+
+impl DiskWatchdog {
+    /// Creates a new watchdog for the filesystem of the given directory and checks the free space
+    /// right away.
+    pub fn new(path: PathBuf, thresholds: DiskThresholds) -> Self {
+        let status = DiskStatus {
+            path: path.display().to_string(),
+            degraded_threshold: thresholds.degraded,
+            critical_threshold: thresholds.critical,
+            ..Default::default()
+        };
+        let mut watchdog = Self {
+            path,
+            thresholds,
+            check_interval: DEFAULT_DISK_CHECK_INTERVAL,
+            status: watch::channel(status).0,
+            degraded: watch::channel(false).0,
+            critical: watch::channel(false).0,
+            metrics: Default::default(),
+        };
+        watchdog.check();
+        watchdog
+    }
+
+    /// Sets the interval at which the free space is checked.
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Returns a receiver for the latest status.
+    pub fn subscribe(&self) -> watch::Receiver<DiskStatus> {
+        self.status.subscribe()
+    }
+
+    /// Returns a receiver that is `true` while optional writes should be paused.
+    pub fn degraded_signal(&self) -> watch::Receiver<bool> {
+        self.degraded.subscribe()
+    }
+
+    /// Returns a receiver that is `true` while sync should be halted.
+    pub fn critical_signal(&self) -> watch::Receiver<bool> {
+        self.critical.subscribe()
+    }
+
+    /// Checks the free space forever.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.check_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.check();
+        }
+    }
+
+    /// Checks the free space and updates the status and signals.
+    fn check(&mut self) {
+        let previous = self.status.borrow().pressure;
+        let mut status = self.status.borrow().clone();
+
+        match disk_space(&self.path) {
+            Ok((available, total)) => {
+                status.available_bytes = Some(available);
+                status.total_bytes = Some(total);
+                status.pressure = self.thresholds.pressure(previous, available);
+                self.metrics.available_bytes.set(available as f64);
+            }
+            Err(err) => {
+                // keep the current pressure, the free space is unknown
+                warn!(target: "reth::cli", path = ?self.path, %err, "Failed to check free disk space");
+                status.available_bytes = None;
+                status.total_bytes = None;
+            }
+        }
+
+        let pressure = status.pressure;
+        if pressure != previous {
+            let available = status.available_bytes;
+            match pressure {
+                DiskPressure::Critical => {
+                    error!(target: "reth::cli", path = ?self.path, ?available, "Free disk space is critically low, halting sync")
+                }
+                DiskPressure::Degraded if previous < pressure => {
+                    warn!(target: "reth::cli", path = ?self.path, ?available, "Free disk space is low, pausing optional writes and pruning aggressively")
+                }
+                _ => {
+                    info!(target: "reth::cli", path = ?self.path, ?available, ?pressure, "Free disk space recovered")
+                }
+            }
+        }
+
+        self.metrics.pressure.set(pressure as u8 as f64);
+        self.status.send_replace(status);
+        self.degraded.send_if_modified(|degraded| {
+            let modified = *degraded != (pressure >= DiskPressure::Degraded);
+            *degraded = pressure >= DiskPressure::Degraded;
+            modified
+        });
+        self.critical.send_if_modified(|critical| {
+            let modified = *critical != (pressure == DiskPressure::Critical);
+            *critical = pressure == DiskPressure::Critical;
+            modified
+        });
+    }
+}
+
+/// Returns the `(available, total)` bytes of the filesystem of the given path.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn disk_space(path: &std::path::Path) -> io::Result<(u64, u64)> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid C string and `stat` is only read if the call succeeded
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error())
+    }
+    let stat = unsafe { stat.assume_init() };
+    let fragment_size = stat.f_frsize as u64;
+    Ok((stat.f_bavail as u64 * fragment_size, stat.f_blocks as u64 * fragment_size))
+}
+
+/// Returns the `(available, total)` bytes of the filesystem of the given path.
+#[cfg(not(unix))]
+fn disk_space(_path: &std::path::Path) -> io::Result<(u64, u64)> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "free disk space can only be checked on unix"))
+}
+
+/// Metrics of the [DiskWatchdog].
+#[derive(Metrics)]
+#[metrics(scope = "disk_watchdog")]
+struct DiskWatchdogMetrics {
+    /// Bytes available on the filesystem of the database
+    available_bytes: Gauge,
+    /// The disk pressure: 0 for normal, 1 for degraded, 2 for critical
+    pressure: Gauge,
+}
+
+/// `admin_diskStatus` implementation that reports the latest [DiskStatus].
+#[derive(Debug, Clone)]
+pub struct AdminDiskApi {
+    status: watch::Receiver<DiskStatus>,
+}
+
+impl AdminDiskApi {
+    /// Creates a new instance of the [AdminDiskApi].
+    pub fn new(status: watch::Receiver<DiskStatus>) -> Self {
+        Self { status }
+    }
+}
+
+impl AdminDiskApiServer for AdminDiskApi {
+    /// Handler for `admin_diskStatus`
+    fn disk_status(&self) -> RpcResult<DiskStatus> {
+        Ok(self.status.borrow().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressure_with_hysteresis() {
+        let thresholds = DiskThresholds { degraded: 1000, critical: 100 };
+
+        assert_eq!(thresholds.pressure(DiskPressure::Normal, 1000), DiskPressure::Normal);
+        assert_eq!(thresholds.pressure(DiskPressure::Normal, 999), DiskPressure::Degraded);
+        assert_eq!(thresholds.pressure(DiskPressure::Normal, 99), DiskPressure::Critical);
+
+        // leaving a level requires a margin above its threshold
+        assert_eq!(thresholds.pressure(DiskPressure::Critical, 100), DiskPressure::Critical);
+        assert_eq!(thresholds.pressure(DiskPressure::Critical, 110), DiskPressure::Degraded);
+        assert_eq!(thresholds.pressure(DiskPressure::Degraded, 1050), DiskPressure::Degraded);
+        assert_eq!(thresholds.pressure(DiskPressure::Degraded, 1100), DiskPressure::Normal);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn signals_follow_pressure() {
+        let dir = tempfile::tempdir().unwrap();
+        let watchdog = DiskWatchdog::new(
+            dir.path().to_path_buf(),
+            DiskThresholds { degraded: u64::MAX, critical: u64::MAX },
+        );
+        assert_eq!(watchdog.subscribe().borrow().pressure, DiskPressure::Critical);
+        assert!(*watchdog.degraded_signal().borrow());
+        assert!(*watchdog.critical_signal().borrow());
+
+        let watchdog = DiskWatchdog::new(
+            dir.path().to_path_buf(),
+            DiskThresholds { degraded: 0, critical: 0 },
+        );
+        assert_eq!(watchdog.subscribe().borrow().pressure, DiskPressure::Normal);
+        assert!(!*watchdog.degraded_signal().borrow());
+    }
+}
@@ -9,7 +9,10 @@ use reth_rpc_types::{
 };
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, path::PathBuf, time::SystemTime};
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    watch,
+};
 use tracing::*;
 
 /// A message from the engine API that has been stored to disk.
@@ -37,6 +40,8 @@ pub enum StoredEngineApiMessage<Attributes> {
 pub struct EngineApiStore {
     /// The path to the directory that stores the engine API messages.
     path: PathBuf,
+    /// Messages are not stored while this is set.
+    paused: Option<watch::Receiver<bool>>,
 }
 
 impl EngineApiStore {
@@ -44,7 +49,14 @@ impl EngineApiStore {
     ///
     /// The path is expected to be a directory, where individual message JSON files will be stored.
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self { path, paused: None }
+    }
+
+    /// Skips storing intercepted messages while the given signal is set, e.g. while disk space is
+    /// low.
+    pub fn with_pause_signal(mut self, paused: watch::Receiver<bool>) -> Self {
+        self.paused = Some(paused);
+        self
     }
 
     /// Stores the received [BeaconEngineMessage] to disk, appending the `received_at` time to the
@@ -70,7 +82,7 @@ impl EngineApiStore {
                     })?,
                 )?;
             }
-            BeaconEngineMessage::NewPayload { payload, cancun_fields, tx: _tx } => {
+            BeaconEngineMessage::NewPayload { payload, cancun_fields, .. } => {
                 let filename = format!("{}-new_payload-{}.json", timestamp, payload.block_hash());
                 fs::write(
                     self.path.join(filename),
@@ -121,7 +133,9 @@ impl EngineApiStore {
     {
         loop {
             let Some(msg) = rx.recv().await else { break };
-            if let Err(error) = self.on_message(&msg, SystemTime::now()) {
+            if self.paused.as_ref().is_some_and(|paused| *paused.borrow()) {
+                trace!(target: "engine::intercept", "Storing Engine API messages is paused");
+            } else if let Err(error) = self.on_message(&msg, SystemTime::now()) {
                 error!(target: "engine::intercept", ?msg, %error, "Error handling Engine API message");
             }
             let _ = to_engine.send(msg);
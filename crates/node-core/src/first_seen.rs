@@ -0,0 +1,36 @@
+//! Querying when transactions were first seen over RPC.
+
+use jsonrpsee::core::RpcResult;
+use reth_primitives::TxHash;
+use reth_rpc_api::AdminFirstSeenApiServer;
+use reth_rpc_types::TransactionFirstSeen;
+use reth_transaction_pool::first_seen::FirstSeenTracker;
+
+/// `admin_` first seen implementation that reads the installed tracker.
+#[derive(Debug)]
+pub struct AdminFirstSeenApi {
+    tracker: &'static FirstSeenTracker,
+}
+
+impl AdminFirstSeenApi {
+    /// Creates a new instance of the [AdminFirstSeenApi].
+    pub fn new(tracker: &'static FirstSeenTracker) -> Self {
+        Self { tracker }
+    }
+}
+
+impl AdminFirstSeenApiServer for AdminFirstSeenApi {
+    /// Handler for `admin_transactionFirstSeen`
+    fn transaction_first_seen(&self, hash: TxHash) -> RpcResult<Option<TransactionFirstSeen>> {
+        Ok(self.tracker.get(&hash).map(|first_seen| TransactionFirstSeen {
+            seen_at: first_seen.seen_at,
+            origin: first_seen.origin.as_str().to_string(),
+            peer_id: first_seen.origin.peer_id(),
+            block_number: first_seen.inclusion.map(|inclusion| inclusion.block_number),
+            block_hash: first_seen.inclusion.map(|inclusion| inclusion.block_hash),
+            block_timestamp: first_seen.inclusion.map(|inclusion| inclusion.block_timestamp),
+            included_at: first_seen.inclusion.map(|inclusion| inclusion.observed_at),
+            lead_time_ms: first_seen.lead_time_ms(),
+        }))
+    }
+}
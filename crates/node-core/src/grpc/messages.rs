@@ -0,0 +1,220 @@
+//! Requests and responses of the gRPC services.
+
+use crate::{
+    outcome_stream::proto::encode_receipt,
+    protobuf::{
+        encode_bool, encode_bytes, encode_length_delimited, encode_message, encode_uint,
+        DecodeError, FieldReader, FieldValue,
+    },
+};
+use reth_network_api::PeerInfo;
+use reth_primitives::{
+    trie::AccountProof, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber,
+    Receipt, TransactionSigned, B256, U256,
+};
+
+/// `reth.v1.BlockRequest`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct BlockRequest {
+    /// The requested block, `None` for the latest block.
+    pub(crate) id: Option<BlockHashOrNumber>,
+}
+
+impl BlockRequest {
+    pub(crate) fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let mut request = Self::default();
+        for field in FieldReader::new(buf) {
+            match field? {
+                (1, FieldValue::Varint(number)) => request.id = Some(number.into()),
+                (2, FieldValue::LengthDelimited(hash)) => {
+                    request.id = Some(decode_b256(hash)?.into())
+                }
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+}
+
+/// `reth.v1.StateRequest`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct StateRequest {
+    pub(crate) address: Address,
+    pub(crate) storage_keys: Vec<B256>,
+    pub(crate) block: BlockRequest,
+}
+
+impl StateRequest {
+    pub(crate) fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let mut request = Self::default();
+        for field in FieldReader::new(buf) {
+            match field? {
+                (1, FieldValue::LengthDelimited(address)) => {
+                    request.address = Address::try_from(address).map_err(|_| DecodeError)?;
+                }
+                (2, FieldValue::LengthDelimited(key)) => {
+                    request.storage_keys.push(decode_b256(key)?)
+                }
+                (3, FieldValue::LengthDelimited(block)) => {
+                    request.block = BlockRequest::decode(block)?
+                }
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+}
+
+/// `reth.v1.SubscribeRequest`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct SubscribeRequest {
+    /// The first block to publish, `None` to only publish new blocks.
+    pub(crate) from_block: Option<BlockNumber>,
+}
+
+impl SubscribeRequest {
+    pub(crate) fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let mut request = Self::default();
+        for field in FieldReader::new(buf) {
+            if let (1, FieldValue::Varint(number)) = field? {
+                request.from_block = Some(number);
+            }
+        }
+        Ok(request)
+    }
+}
+
+/// `reth.v1.PeerRequest`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct PeerRequest {
+    /// The enode URL of the peer.
+    pub(crate) enode: String,
+}
+
+impl PeerRequest {
+    pub(crate) fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let mut request = Self::default();
+        for field in FieldReader::new(buf) {
+            if let (1, FieldValue::LengthDelimited(enode)) = field? {
+                request.enode = String::from_utf8(enode.to_vec()).map_err(|_| DecodeError)?;
+            }
+        }
+        Ok(request)
+    }
+}
+
+fn decode_b256(buf: &[u8]) -> Result<B256, DecodeError> {
+    B256::try_from(buf).map_err(|_| DecodeError)
+}
+
+/// Encodes a `reth.v1.Block`.
+pub(crate) fn encode_block(number: BlockNumber, hash: BlockHash, block: &Block) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encode_uint(&mut buf, 1, number);
+    encode_bytes(&mut buf, 2, hash.as_slice());
+    encode_bytes(&mut buf, 3, &alloy_rlp::encode(block));
+    buf
+}
+
+/// Encodes a `reth.v1.Receipts`.
+pub(crate) fn encode_receipts(transactions: &[TransactionSigned], receipts: &[Receipt]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for (transaction, receipt) in transactions.iter().zip(receipts) {
+        encode_message(&mut buf, 1, |buf| encode_receipt(buf, &transaction.hash(), receipt));
+    }
+    buf
+}
+
+/// Encodes a `reth.v1.Account`.
+pub(crate) fn encode_account(account: Option<Account>, storage: &[(B256, U256)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encode_account_fields(&mut buf, account);
+    for (key, value) in storage {
+        encode_message(&mut buf, 5, |buf| {
+            encode_bytes(buf, 1, key.as_slice());
+            encode_bytes(buf, 2, &value.to_be_bytes::<32>());
+        });
+    }
+    buf
+}
+
+fn encode_account_fields(buf: &mut Vec<u8>, account: Option<Account>) {
+    if let Some(account) = account {
+        encode_bool(buf, 1, true);
+        encode_uint(buf, 2, account.nonce);
+        encode_bytes(buf, 3, &account.balance.to_be_bytes::<32>());
+        if let Some(code_hash) = account.bytecode_hash {
+            encode_bytes(buf, 4, code_hash.as_slice());
+        }
+    }
+}
+
+/// Encodes a `reth.v1.Proof`.
+pub(crate) fn encode_proof(proof: &AccountProof) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encode_message(&mut buf, 1, |buf| encode_account_fields(buf, proof.info));
+    for node in &proof.proof {
+        encode_length_delimited(&mut buf, 2, node);
+    }
+    encode_bytes(&mut buf, 3, proof.storage_root.as_slice());
+    for storage_proof in &proof.storage_proofs {
+        encode_message(&mut buf, 4, |buf| {
+            encode_bytes(buf, 1, storage_proof.key.as_slice());
+            encode_bytes(buf, 2, &storage_proof.value.to_be_bytes::<32>());
+            for node in &storage_proof.proof {
+                encode_length_delimited(buf, 3, node);
+            }
+        });
+    }
+    buf
+}
+
+/// Encodes a `reth.v1.Peers`.
+pub(crate) fn encode_peers(peers: &[PeerInfo]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for peer in peers {
+        encode_message(&mut buf, 1, |buf| {
+            encode_bytes(buf, 1, peer.remote_id.as_slice());
+            encode_bytes(buf, 2, peer.remote_addr.to_string().as_bytes());
+            encode_bytes(buf, 3, peer.client_version.as_bytes());
+            encode_bool(buf, 4, peer.direction.is_incoming());
+        });
+    }
+    buf
+}
+
+/// Encodes a `reth.v1.ClearedCaches` message.
+pub(crate) fn encode_cleared_caches(entries: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encode_uint(&mut buf, 1, entries);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_state_request() {
+        let mut block = Vec::new();
+        encode_bytes(&mut block, 2, B256::repeat_byte(2).as_slice());
+        let mut buf = Vec::new();
+        encode_bytes(&mut buf, 1, Address::repeat_byte(1).as_slice());
+        encode_length_delimited(&mut buf, 2, B256::ZERO.as_slice());
+        encode_length_delimited(&mut buf, 2, B256::with_last_byte(1).as_slice());
+        encode_length_delimited(&mut buf, 3, &block);
+        // unknown fields are skipped
+        encode_uint(&mut buf, 10, 1);
+
+        assert_eq!(
+            StateRequest::decode(&buf).unwrap(),
+            StateRequest {
+                address: Address::repeat_byte(1),
+                storage_keys: vec![B256::ZERO, B256::with_last_byte(1)],
+                block: BlockRequest { id: Some(B256::repeat_byte(2).into()) },
+            }
+        );
+        assert_eq!(StateRequest::decode(&buf[..10]), Err(DecodeError));
+        assert_eq!(BlockRequest::decode(&[]).unwrap(), BlockRequest::default());
+    }
+}
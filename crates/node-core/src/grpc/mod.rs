@@ -0,0 +1,618 @@
+//! gRPC server for data and admin access to the node.
+//!
+//! The server speaks plain gRPC over HTTP/2 without TLS or compression. It serves the following
+//! services, which are shipped as `proto/reth/v1/node.proto` of this crate. Messages that are not
+//! defined here are part of the [execution outcome schema](crate::outcome_stream::proto).
+//!
+//! Calls of the `Admin` service have to carry a JWT signed with the secret of the engine API in
+//! their `authorization: Bearer <token>` metadata, like the requests of the auth server.
+#![doc = concat!("```proto\n", include_str!("../../proto/reth/v1/node.proto"), "```")]
+
+use crate::{
+    outcome_stream::{notification_outcomes, proto::BlockOutcome},
+    protobuf::DecodeError,
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    stream::{self, BoxStream},
+    SinkExt, StreamExt,
+};
+use hyper::{
+    body::{Bytes, HttpBody},
+    header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE},
+    http::HeaderMap,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server,
+};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_network_api::{PeerKind, Peers};
+use reth_primitives::{BlockHashOrNumber, NodeRecord};
+use reth_provider::{
+    AccountReader, BlockReader, CanonStateNotification, CanonStateReplay, CanonStateSubscriptions,
+    StateProvider, StateProviderFactory, DEFAULT_REPLAY_CHUNK_SIZE,
+};
+use reth_rpc::{eth::cache::EthStateCache, JwtSecret};
+use reth_tasks::TaskSpawner;
+use std::{
+    convert::Infallible,
+    fmt,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tracing::{debug, error};
+
+mod messages;
+use messages::{
+    encode_account, encode_block, encode_cleared_caches, encode_peers, encode_proof,
+    encode_receipts, BlockRequest, PeerRequest, StateRequest, SubscribeRequest,
+};
+
+/// The maximum size of a request message, the gRPC default.
+const MAX_REQUEST_SIZE: usize = 4 * 1024 * 1024;
+
+/// The maximum number of storage keys of a `GetAccount` or `GetProof` request.
+const MAX_STORAGE_KEYS: usize = 1024;
+
+/// The number of encoded block outcomes buffered per subscription.
+const SUBSCRIPTION_BUFFER: usize = 64;
+
+/// Serves the `reth.v1.Data` and `reth.v1.Admin` gRPC services.
+pub struct GrpcServer<Provider, Network> {
+    inner: Arc<GrpcServerInner<Provider, Network>>,
+}
+
+struct GrpcServerInner<Provider, Network> {
+    provider: Provider,
+    network: Network,
+    /// The cache of the RPC servers, cleared by the `Admin` service.
+    eth_cache: EthStateCache,
+    /// The secret the tokens of `Admin` calls are validated with.
+    jwt_secret: JwtSecret,
+    /// Provider reads are spawned as blocking tasks, subscriptions are driven by regular tasks.
+    executor: Box<dyn TaskSpawner>,
+}
+
+impl<Provider, Network> GrpcServer<Provider, Network>
+where
+    Provider: BlockReader + StateProviderFactory + CanonStateReplay + Clone + 'static,
+    Network: Peers + 'static,
+{
+    /// Creates a new server, `Admin` calls are authenticated with the given secret.
+    pub fn new(
+        provider: Provider,
+        network: Network,
+        eth_cache: EthStateCache,
+        jwt_secret: JwtSecret,
+        executor: Box<dyn TaskSpawner>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(GrpcServerInner { provider, network, eth_cache, jwt_secret, executor }),
+        }
+    }
+
+    /// Binds to the given address and spawns the server, returning the local address.
+    pub fn start(self, addr: SocketAddr) -> Result<SocketAddr, hyper::Error> {
+        let inner = self.inner.clone();
+        let make_svc = make_service_fn(move |_| {
+            let inner = inner.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let inner = inner.clone();
+                    async move { Ok::<_, Infallible>(inner.handle(req).await) }
+                }))
+            }
+        });
+        let server = Server::try_bind(&addr)?.http2_only(true).serve(make_svc);
+        let local_addr = server.local_addr();
+        self.inner.executor.spawn(Box::pin(async move {
+            if let Err(err) = server.await {
+                error!(target: "reth::grpc", %err, "gRPC server failed");
+            }
+        }));
+        Ok(local_addr)
+    }
+}
+
+impl<Provider, Network> fmt::Debug for GrpcServer<Provider, Network> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrpcServer").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, Network> GrpcServerInner<Provider, Network>
+where
+    Provider: BlockReader + StateProviderFactory + CanonStateReplay + Clone + 'static,
+    Network: Peers + 'static,
+{
+    async fn handle(self: Arc<Self>, req: Request<Body>) -> Response<GrpcBody> {
+        if req.method() != Method::POST {
+            return GrpcBody::status(Status::unimplemented("gRPC requires POST requests"))
+        }
+        let method = req.uri().path().to_string();
+        if method.starts_with("/reth.v1.Admin/") {
+            if let Err(status) = self.authenticate(req.headers()) {
+                return GrpcBody::status(status)
+            }
+        }
+        let message = match read_message(req.into_body()).await {
+            Ok(message) => message,
+            Err(status) => return GrpcBody::status(status),
+        };
+        debug!(target: "reth::grpc", %method, "Serving gRPC request");
+
+        let response = match method.as_str() {
+            "/reth.v1.Data/GetBlock" => self.get_block(message).await,
+            "/reth.v1.Data/GetReceipts" => self.get_receipts(message).await,
+            "/reth.v1.Data/GetAccount" => self.get_account(message).await,
+            "/reth.v1.Data/GetProof" => self.get_proof(message).await,
+            "/reth.v1.Data/SubscribeBlocks" => return self.subscribe_blocks(message),
+            "/reth.v1.Admin/ListPeers" => self.list_peers().await,
+            "/reth.v1.Admin/AddPeer" => self.add_peer(message),
+            "/reth.v1.Admin/RemovePeer" => self.remove_peer(message),
+            "/reth.v1.Admin/ClearCaches" => self.clear_caches().await,
+            _ => Err(Status::unimplemented(format!("unknown method {method}"))),
+        };
+        match response {
+            Ok(message) => GrpcBody::unary(message),
+            Err(status) => GrpcBody::status(status),
+        }
+    }
+
+    /// Validates the bearer token of the request against the JWT secret.
+    fn authenticate(&self, headers: &HeaderMap) -> Result<(), Status> {
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+        self.jwt_secret
+            .validate(token.to_string())
+            .map_err(|err| Status::unauthenticated(err.to_string()))
+    }
+
+    /// Runs the closure on a blocking task.
+    async fn on_blocking_task<F, R>(self: &Arc<Self>, f: F) -> Result<R, Status>
+    where
+        F: FnOnce(&Provider) -> Result<R, Status> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let this = self.clone();
+        self.executor.spawn_blocking(Box::pin(async move {
+            let _ = tx.send(f(&this.provider));
+        }));
+        rx.await.map_err(|_| Status::internal("blocking task dropped"))?
+    }
+
+    async fn get_block(self: &Arc<Self>, message: Bytes) -> Result<Vec<u8>, Status> {
+        let request = BlockRequest::decode(&message)?;
+        self.on_blocking_task(move |provider| {
+            let number = block_number(provider, request.id)?;
+            let block = provider.block(number.into())?.ok_or_else(|| block_not_found(number))?;
+            Ok(encode_block(number, block.header.hash_slow(), &block))
+        })
+        .await
+    }
+
+    async fn get_receipts(self: &Arc<Self>, message: Bytes) -> Result<Vec<u8>, Status> {
+        let request = BlockRequest::decode(&message)?;
+        self.on_blocking_task(move |provider| {
+            let number = block_number(provider, request.id)?;
+            let block = provider.block(number.into())?.ok_or_else(|| block_not_found(number))?;
+            let receipts = provider
+                .receipts_by_block(number.into())?
+                .ok_or_else(|| block_not_found(number))?;
+            Ok(encode_receipts(&block.body, &receipts))
+        })
+        .await
+    }
+
+    async fn get_account(self: &Arc<Self>, message: Bytes) -> Result<Vec<u8>, Status> {
+        let request = StateRequest::decode(&message)?;
+        ensure_storage_keys(&request)?;
+        self.on_blocking_task(move |provider| {
+            let number = block_number(provider, request.block.id)?;
+            let state = provider.history_by_block_number(number)?;
+            let account = state.basic_account(request.address)?;
+            let storage = request
+                .storage_keys
+                .iter()
+                .map(|key| Ok((*key, state.storage(request.address, *key)?.unwrap_or_default())))
+                .collect::<ProviderResult<Vec<_>>>()?;
+            Ok(encode_account(account, &storage))
+        })
+        .await
+    }
+
+    async fn get_proof(self: &Arc<Self>, message: Bytes) -> Result<Vec<u8>, Status> {
+        let request = StateRequest::decode(&message)?;
+        ensure_storage_keys(&request)?;
+        self.on_blocking_task(move |provider| {
+            let number = block_number(provider, request.block.id)?;
+            let state = provider.history_by_block_number(number)?;
+            Ok(encode_proof(&state.proof(request.address, &request.storage_keys)?))
+        })
+        .await
+    }
+
+    /// Streams block outcomes from a task that drives the canonical state stream.
+    fn subscribe_blocks(self: Arc<Self>, message: Bytes) -> Response<GrpcBody> {
+        let request = match SubscribeRequest::decode(&message) {
+            Ok(request) => request,
+            Err(err) => return GrpcBody::status(err.into()),
+        };
+
+        let (mut tx, rx) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let this = self.clone();
+        self.executor.spawn(Box::pin(async move {
+            if let Err(status) = this.stream_blocks(request, &mut tx).await {
+                let _ = tx.send(Err(status)).await;
+            }
+        }));
+
+        GrpcBody::streaming(rx.boxed())
+    }
+
+    /// Sends the outcomes of the persisted blocks from the requested block on, then the outcomes
+    /// of all canonical state notifications.
+    ///
+    /// Only the replayed blocks are read on blocking tasks. Returns once the client went away or
+    /// the notifications ended.
+    async fn stream_blocks(
+        self: &Arc<Self>,
+        request: SubscribeRequest,
+        tx: &mut mpsc::Sender<Result<Vec<u8>, Status>>,
+    ) -> Result<(), Status> {
+        // subscribe before the replay range is determined, so no block is missed
+        let mut notifications = self.provider.canonical_state_stream();
+        let (mut next_block, replay_tip) = self
+            .on_blocking_task(move |provider| {
+                let next_block = match request.from_block {
+                    Some(from_block) => from_block,
+                    None => provider.best_block_number()? + 1,
+                };
+                Ok((next_block, provider.last_block_number()?))
+            })
+            .await?;
+
+        while next_block <= replay_tip {
+            let end = (next_block + DEFAULT_REPLAY_CHUNK_SIZE - 1).min(replay_tip);
+            let (outcomes, _) = self
+                .on_blocking_task(move |provider| {
+                    let replayed = provider.replay_canonical_state(next_block..=end)?;
+                    Ok(notification_outcomes(provider, next_block, &replayed)?)
+                })
+                .await?;
+            if !send_outcomes(tx, &outcomes).await {
+                return Ok(())
+            }
+            next_block = end + 1;
+        }
+
+        while let Some(notification) = notifications.next().await {
+            if let CanonStateNotification::Commit { new } = &notification {
+                if new.tip().number <= replay_tip {
+                    continue
+                }
+            }
+            // blocks between the last sent block and the notification have to be replayed
+            let missed = notification
+                .committed()
+                .filter(|new| !new.is_empty())
+                .is_some_and(|new| new.first().number > next_block);
+            let (outcomes, tip) = if missed {
+                self.on_blocking_task(move |provider| {
+                    Ok(notification_outcomes(provider, next_block, &notification)?)
+                })
+                .await?
+            } else {
+                notification_outcomes(&self.provider, next_block, &notification)?
+            };
+            if !send_outcomes(tx, &outcomes).await {
+                return Ok(())
+            }
+            if let Some(tip) = tip {
+                next_block = tip.number + 1;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_peers(&self) -> Result<Vec<u8>, Status> {
+        let peers =
+            self.network.get_all_peers().await.map_err(|err| Status::internal(err.to_string()))?;
+        Ok(encode_peers(&peers))
+    }
+
+    fn add_peer(&self, message: Bytes) -> Result<Vec<u8>, Status> {
+        let record = parse_enode(&message)?;
+        self.network.add_peer(record.id, record.tcp_addr());
+        Ok(Vec::new())
+    }
+
+    fn remove_peer(&self, message: Bytes) -> Result<Vec<u8>, Status> {
+        let record = parse_enode(&message)?;
+        self.network.remove_peer(record.id, PeerKind::Basic);
+        Ok(Vec::new())
+    }
+
+    async fn clear_caches(&self) -> Result<Vec<u8>, Status> {
+        let entries = self.eth_cache.clear().await?;
+        Ok(encode_cleared_caches(entries as u64))
+    }
+}
+
+/// Returns the number of the requested canonical block.
+fn block_number<Provider: BlockReader>(
+    provider: &Provider,
+    id: Option<BlockHashOrNumber>,
+) -> Result<u64, Status> {
+    match id {
+        None => Ok(provider.best_block_number()?),
+        Some(BlockHashOrNumber::Number(number)) => Ok(number),
+        Some(BlockHashOrNumber::Hash(hash)) => provider
+            .block_number(hash)?
+            .ok_or_else(|| Status::not_found(format!("block {hash} not found"))),
+    }
+}
+
+/// Sends the encoded outcomes, returns `false` if the client went away.
+async fn send_outcomes(
+    tx: &mut mpsc::Sender<Result<Vec<u8>, Status>>,
+    outcomes: &[BlockOutcome],
+) -> bool {
+    for outcome in outcomes {
+        if tx.send(Ok(outcome.encode())).await.is_err() {
+            return false
+        }
+    }
+    true
+}
+
+/// Rejects requests for more storage slots than a single call may read.
+fn ensure_storage_keys(request: &StateRequest) -> Result<(), Status> {
+    if request.storage_keys.len() > MAX_STORAGE_KEYS {
+        return Err(Status::invalid_argument(format!(
+            "too many storage keys, at most {MAX_STORAGE_KEYS} are allowed"
+        )))
+    }
+    Ok(())
+}
+
+fn block_not_found(number: u64) -> Status {
+    Status::not_found(format!("block {number} not found"))
+}
+
+fn parse_enode(message: &[u8]) -> Result<NodeRecord, Status> {
+    let request = PeerRequest::decode(message)?;
+    request.enode.parse().map_err(|err| Status::invalid_argument(format!("invalid enode: {err}")))
+}
+
+/// Reads the single length-prefixed message of a unary or server streaming request.
+///
+/// The body is read chunk by chunk and rejected as soon as it exceeds the maximum message size.
+async fn read_message(mut body: Body) -> Result<Bytes, Status> {
+    let mut buf = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk =
+            chunk.map_err(|err| Status::internal(format!("failed to read request: {err}")))?;
+        if buf.len() + chunk.len() > MAX_REQUEST_SIZE + 5 {
+            return Err(Status::resource_exhausted("message too large"))
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    let body = Bytes::from(buf);
+    if body.len() < 5 {
+        return Err(Status::invalid_argument("missing message"))
+    }
+    if body[0] != 0 {
+        return Err(Status::unimplemented("compressed messages are not supported"))
+    }
+    let len = u32::from_be_bytes(body[1..5].try_into().expect("is 4 bytes")) as usize;
+    if len > MAX_REQUEST_SIZE {
+        return Err(Status::resource_exhausted("message too large"))
+    }
+    if body.len() != len + 5 {
+        return Err(Status::invalid_argument("expected a single message"))
+    }
+    Ok(body.slice(5..))
+}
+
+/// Prefixes the message with the gRPC message header.
+fn frame(message: &[u8]) -> Bytes {
+    let mut frame = Vec::with_capacity(message.len() + 5);
+    frame.push(0);
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    frame.into()
+}
+
+/// A gRPC status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Status {
+    code: u32,
+    message: String,
+}
+
+impl Status {
+    const OK: u32 = 0;
+    const INVALID_ARGUMENT: u32 = 3;
+    const NOT_FOUND: u32 = 5;
+    const RESOURCE_EXHAUSTED: u32 = 8;
+    const UNIMPLEMENTED: u32 = 12;
+    const INTERNAL: u32 = 13;
+    const UNAUTHENTICATED: u32 = 16;
+
+    fn new(code: u32, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    fn ok() -> Self {
+        Self::new(Self::OK, "")
+    }
+
+    fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_ARGUMENT, message)
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(Self::NOT_FOUND, message)
+    }
+
+    fn resource_exhausted(message: impl Into<String>) -> Self {
+        Self::new(Self::RESOURCE_EXHAUSTED, message)
+    }
+
+    fn unimplemented(message: impl Into<String>) -> Self {
+        Self::new(Self::UNIMPLEMENTED, message)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new(Self::INTERNAL, message)
+    }
+
+    fn unauthenticated(message: impl Into<String>) -> Self {
+        Self::new(Self::UNAUTHENTICATED, message)
+    }
+
+    fn trailers(&self) -> HeaderMap {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", self.code.into());
+        if !self.message.is_empty() {
+            // the message is percent-encoded, only the printable ASCII characters of the spec are
+            // kept as they are
+            let mut message = String::with_capacity(self.message.len());
+            for byte in self.message.bytes() {
+                if (0x20..0x7f).contains(&byte) && byte != b'%' {
+                    message.push(byte as char);
+                } else {
+                    message.push_str(&format!("%{byte:02X}"));
+                }
+            }
+            trailers.insert(
+                "grpc-message",
+                HeaderValue::from_str(&message).expect("only printable ASCII"),
+            );
+        }
+        trailers
+    }
+}
+
+impl From<DecodeError> for Status {
+    fn from(err: DecodeError) -> Self {
+        Self::invalid_argument(err.to_string())
+    }
+}
+
+impl From<ProviderError> for Status {
+    fn from(err: ProviderError) -> Self {
+        match err {
+            ProviderError::HeaderNotFound(_) | ProviderError::BlockHashNotFound(_) => {
+                Self::not_found(err.to_string())
+            }
+            err => Self::internal(err.to_string()),
+        }
+    }
+}
+
+/// The body of a gRPC response: length-prefixed messages followed by the status trailers.
+struct GrpcBody {
+    /// The remaining messages, `None` once all messages were sent.
+    messages: Option<BoxStream<'static, Result<Vec<u8>, Status>>>,
+    /// The final status, replaced by the first error of the message stream.
+    status: Status,
+}
+
+impl GrpcBody {
+    fn new(
+        messages: Option<BoxStream<'static, Result<Vec<u8>, Status>>>,
+        status: Status,
+    ) -> Response<Self> {
+        let mut response = Response::new(Self { messages, status });
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+        response
+    }
+
+    /// A response with a single message.
+    fn unary(message: Vec<u8>) -> Response<Self> {
+        Self::new(Some(stream::once(async move { Ok(message) }).boxed()), Status::ok())
+    }
+
+    /// A response with a stream of messages.
+    fn streaming(messages: BoxStream<'static, Result<Vec<u8>, Status>>) -> Response<Self> {
+        Self::new(Some(messages), Status::ok())
+    }
+
+    /// A response without messages.
+    fn status(status: Status) -> Response<Self> {
+        Self::new(None, status)
+    }
+}
+
+impl HttpBody for GrpcBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let Some(messages) = &mut self.messages else { return Poll::Ready(None) };
+        match ready!(messages.poll_next_unpin(cx)) {
+            Some(Ok(message)) => Poll::Ready(Some(Ok(frame(&message)))),
+            Some(Err(status)) => {
+                self.status = status;
+                self.messages = None;
+                Poll::Ready(None)
+            }
+            None => {
+                self.messages = None;
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(Some(self.status.trailers())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::B256;
+
+    #[tokio::test]
+    async fn read_framed_message() {
+        let message = read_message(Body::from(frame(b"reth"))).await.unwrap();
+        assert_eq!(&message[..], b"reth");
+
+        let status = read_message(Body::from(&b"\x01\x00\x00\x00\x00"[..])).await.unwrap_err();
+        assert_eq!(status.code, Status::UNIMPLEMENTED);
+        let status = read_message(Body::from(frame(b"reth").slice(..6))).await.unwrap_err();
+        assert_eq!(status.code, Status::INVALID_ARGUMENT);
+        let status = read_message(Body::from(vec![0; MAX_REQUEST_SIZE + 6])).await.unwrap_err();
+        assert_eq!(status.code, Status::RESOURCE_EXHAUSTED);
+    }
+
+    #[test]
+    fn storage_keys_limit() {
+        let mut request =
+            StateRequest { storage_keys: vec![B256::ZERO; MAX_STORAGE_KEYS], ..Default::default() };
+        assert_eq!(ensure_storage_keys(&request), Ok(()));
+        request.storage_keys.push(B256::ZERO);
+        assert_eq!(ensure_storage_keys(&request).unwrap_err().code, Status::INVALID_ARGUMENT);
+    }
+
+    #[test]
+    fn status_trailers() {
+        let trailers = Status::not_found("block 1 not found: 100%").trailers();
+        assert_eq!(trailers["grpc-status"], "5");
+        assert_eq!(trailers["grpc-message"], "block 1 not found: 100%25");
+    }
+}
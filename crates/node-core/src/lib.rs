@@ -10,12 +10,22 @@
 pub mod args;
 pub mod cl_events;
 pub mod cli;
+pub mod config_watcher;
+pub mod crash_bundle;
 pub mod dirs;
+pub mod disk_watchdog;
 pub mod engine_api_store;
 pub mod events;
+pub mod first_seen;
+pub mod grpc;
 pub mod init;
+pub mod log_levels;
 pub mod metrics;
 pub mod node_config;
+pub mod outcome_stream;
+pub mod profiles;
+mod protobuf;
+pub mod trie_server;
 pub mod utils;
 pub mod version;
 
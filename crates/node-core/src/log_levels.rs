@@ -0,0 +1,96 @@
+//! Runtime changes of the log levels of the node over RPC.
+
+use jsonrpsee::{
+    core::RpcResult,
+    types::error::{ErrorObject, INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+};
+use reth_rpc_api::AdminLogApiServer;
+use reth_rpc_types::TargetLogLevel;
+use reth_tasks::TaskSpawner;
+use reth_tracing::{
+    log_filters::{LogFilters, TargetLevel},
+    tracing::level_filters::LevelFilter,
+};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// `admin_` log level implementation that changes the filters of the installed tracer.
+pub struct AdminLogApi {
+    filters: &'static LogFilters,
+    task_spawner: Box<dyn TaskSpawner>,
+}
+
+impl AdminLogApi {
+    /// Creates a new instance of the [AdminLogApi].
+    ///
+    /// Levels that are set for a limited time are restored on tasks spawned with the given
+    /// spawner.
+    pub fn new(filters: &'static LogFilters, task_spawner: Box<dyn TaskSpawner>) -> Self {
+        Self { filters, task_spawner }
+    }
+}
+
+impl std::fmt::Debug for AdminLogApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminLogApi").field("filters", &self.filters).finish_non_exhaustive()
+    }
+}
+
+impl AdminLogApiServer for AdminLogApi {
+    /// Handler for `admin_setLogLevel`
+    fn set_log_level(
+        &self,
+        target: String,
+        level: String,
+        duration_secs: Option<u64>,
+    ) -> RpcResult<TargetLogLevel> {
+        let level_filter = level.parse::<LevelFilter>().map_err(|err| {
+            ErrorObject::owned(INVALID_PARAMS_CODE, format!("{level}: {err}"), None::<()>)
+        })?;
+        let duration = duration_secs.map(Duration::from_secs);
+        let level = self.filters.set_level(&target, level_filter, duration).map_err(|err| {
+            ErrorObject::owned(INVALID_PARAMS_CODE, format!("{target}: {err}"), None::<()>)
+        })?;
+        info!(target: "reth::cli", %target, level = %level_filter, ?duration, "Changed log level");
+
+        if let Some(duration) = duration {
+            let filters = self.filters;
+            self.task_spawner.spawn(Box::pin(async move {
+                tokio::time::sleep(duration).await;
+                if let Err(err) = filters.remove_expired() {
+                    warn!(target: "reth::cli", %err, "Failed to restore log levels");
+                }
+            }));
+        }
+
+        Ok(target_log_level(level, Instant::now()))
+    }
+
+    /// Handler for `admin_resetLogLevel`
+    fn reset_log_level(&self, target: String) -> RpcResult<bool> {
+        let removed = self
+            .filters
+            .remove_level(&target)
+            .map_err(|err| ErrorObject::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>))?;
+        if removed {
+            info!(target: "reth::cli", %target, "Restored log level");
+        }
+        Ok(removed)
+    }
+
+    /// Handler for `admin_logLevels`
+    fn log_levels(&self) -> RpcResult<Vec<TargetLogLevel>> {
+        let now = Instant::now();
+        Ok(self.filters.levels().into_iter().map(|level| target_log_level(level, now)).collect())
+    }
+}
+
+fn target_log_level(level: TargetLevel, now: Instant) -> TargetLogLevel {
+    TargetLogLevel {
+        target: level.target,
+        level: level.level.to_string().to_lowercase(),
+        expires_in_secs: level
+            .expires_at
+            .map(|expires_at| expires_at.saturating_duration_since(now).as_secs()),
+    }
+}
@@ -0,0 +1,242 @@
+//! Cardinality control of the exported metrics.
+//!
+//! The [LabelLimitLayer] caps the number of series of high-cardinality label families, e.g.
+//! per-method or per-table metrics, when they are registered. The [SubsystemFilter] removes whole
+//! subsystems from the rendered metrics, and can be changed at runtime.
+
+use metrics::{
+    Counter, Gauge, Histogram, Key, KeyName, Label, Metadata, Recorder, SharedString, Unit,
+};
+use metrics_util::layers::Layer;
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+
+/// The label value of the series that aggregates all values beyond the limit of the label.
+pub const OTHER_LABEL_VALUE: &str = "other";
+
+/// Filters the subsystems whose metrics are rendered.
+///
+/// A subsystem is a prefix of the exported metric names after `reth_`, e.g. `network` for all
+/// `reth_network_*` metrics. Clones share the same set of disabled subsystems.
+#[derive(Debug, Clone, Default)]
+pub struct SubsystemFilter {
+    /// The name prefixes of the disabled subsystems, e.g. `reth_network_`.
+    disabled: Arc<RwLock<Vec<String>>>,
+}
+
+impl SubsystemFilter {
+    /// Creates a new filter with the given disabled subsystems.
+    pub fn new(disabled: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let filter = Self::default();
+        filter.set_disabled(disabled);
+        filter
+    }
+
+    /// Replaces the disabled subsystems.
+    pub fn set_disabled(&self, disabled: impl IntoIterator<Item = impl AsRef<str>>) {
+        *self.disabled.write() = disabled
+            .into_iter()
+            .map(|subsystem| format!("reth_{}_", subsystem.as_ref().trim_end_matches('_')))
+            .collect();
+    }
+
+    /// Removes the samples, descriptions and types of the disabled subsystems from metrics in the
+    /// Prometheus text format.
+    pub fn apply(&self, rendered: String) -> String {
+        let disabled = self.disabled.read();
+        if disabled.is_empty() {
+            return rendered
+        }
+
+        let mut filtered = String::with_capacity(rendered.len());
+        for line in rendered.lines() {
+            let name = line
+                .strip_prefix("# HELP ")
+                .or_else(|| line.strip_prefix("# TYPE "))
+                .unwrap_or(line);
+            if !disabled.iter().any(|prefix| name.starts_with(prefix.as_str())) {
+                filtered.push_str(line);
+                filtered.push('\n');
+            }
+        }
+        filtered
+    }
+}
+
+/// [Layer] that limits the number of distinct values of labels per metric.
+///
+/// Series with values beyond the limit are registered with the value [OTHER_LABEL_VALUE] instead,
+/// so they are aggregated into one series. Labels with a limit of 0 are dropped.
+///
+/// Gauges of aggregated series are overwritten by each other, so limits should only be set for
+/// labels of counters and histograms, or of gauges where any of the values is representative.
+#[derive(Debug, Clone, Default)]
+pub struct LabelLimitLayer {
+    limits: BTreeMap<String, usize>,
+}
+
+impl LabelLimitLayer {
+    /// Creates a new layer with the given limits, by label name.
+    pub fn new(limits: BTreeMap<String, usize>) -> Self {
+        Self { limits }
+    }
+}
+
+impl<R> Layer<R> for LabelLimitLayer {
+    type Output = LabelLimit<R>;
+
+    fn layer(&self, inner: R) -> Self::Output {
+        LabelLimit { inner, limits: self.limits.clone(), seen: Default::default() }
+    }
+}
+
+/// Recorder that limits the number of distinct values of labels per metric, see
+/// [LabelLimitLayer].
+#[derive(Debug)]
+pub struct LabelLimit<R> {
+    inner: R,
+    limits: BTreeMap<String, usize>,
+    /// The values of limited labels that have their own series, by metric and label name.
+    seen: Mutex<HashMap<(String, String), HashSet<String>>>,
+}
+
+impl<R> LabelLimit<R> {
+    /// Returns the key the metric is registered with.
+    fn limit(&self, key: &Key) -> Key {
+        if self.limits.is_empty() ||
+            !key.labels().any(|label| self.limits.contains_key(label.key()))
+        {
+            return key.clone()
+        }
+
+        let mut seen = self.seen.lock();
+        let labels = key
+            .labels()
+            .filter_map(|label| {
+                let Some(&limit) = self.limits.get(label.key()) else { return Some(label.clone()) };
+                if limit == 0 {
+                    return None
+                }
+
+                let values =
+                    seen.entry((key.name().to_string(), label.key().to_string())).or_default();
+                if values.contains(label.value()) {
+                    return Some(label.clone())
+                }
+                if values.len() < limit {
+                    values.insert(label.value().to_string());
+                    return Some(label.clone())
+                }
+                Some(Label::new(label.key().to_string(), OTHER_LABEL_VALUE))
+            })
+            .collect::<Vec<_>>();
+        Key::from_parts(key.name().to_string(), labels)
+    }
+}
+
+impl<R: Recorder> Recorder for LabelLimit<R> {
+    fn describe_counter(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner.describe_counter(key, unit, description)
+    }
+
+    fn describe_gauge(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner.describe_gauge(key, unit, description)
+    }
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner.describe_histogram(key, unit, description)
+    }
+
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        self.inner.register_counter(&self.limit(key), metadata)
+    }
+
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        self.inner.register_gauge(&self.limit(key), metadata)
+    }
+
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        self.inner.register_histogram(&self.limit(key), metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::DebuggingRecorder;
+
+    #[test]
+    fn label_limits() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let limits = BTreeMap::from([("method".to_string(), 2), ("peer".to_string(), 0)]);
+        let recorder = LabelLimitLayer::new(limits).layer(recorder);
+
+        let metadata = Metadata::new(module_path!(), metrics::Level::INFO, None);
+        for method in ["eth_call", "eth_getLogs", "eth_call", "eth_chainId", "eth_blockNumber"] {
+            let key = Key::from_parts("rpc_server.calls", vec![Label::new("method", method)]);
+            recorder.register_counter(&key, &metadata).increment(1);
+        }
+        for peer in ["a", "b"] {
+            let key = Key::from_parts(
+                "network.messages",
+                vec![Label::new("peer", peer), Label::new("kind", "tx")],
+            );
+            recorder.register_counter(&key, &metadata).increment(1);
+        }
+
+        let mut series = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, _)| {
+                let key = key.key();
+                let labels = key
+                    .labels()
+                    .map(|label| format!("{}={}", label.key(), label.value()))
+                    .collect::<Vec<_>>();
+                format!("{}{{{}}}", key.name(), labels.join(","))
+            })
+            .collect::<Vec<_>>();
+        series.sort();
+        assert_eq!(
+            series,
+            vec![
+                "network.messages{kind=tx}",
+                "rpc_server.calls{method=eth_call}",
+                "rpc_server.calls{method=eth_getLogs}",
+                "rpc_server.calls{method=other}",
+            ]
+        );
+    }
+
+    #[test]
+    fn subsystem_filter() {
+        let rendered = "\
+# TYPE reth_network_peers gauge
+reth_network_peers 5
+# HELP reth_rpc_server_calls The number of calls
+# TYPE reth_rpc_server_calls counter
+reth_rpc_server_calls{method=\"eth_call\"} 2
+reth_sync_checkpoint 10
+"
+        .to_string();
+
+        let filter = SubsystemFilter::new(["rpc_server"]);
+        assert_eq!(
+            filter.apply(rendered.clone()),
+            "# TYPE reth_network_peers gauge\nreth_network_peers 5\nreth_sync_checkpoint 10\n"
+        );
+
+        // clones share the disabled subsystems
+        filter.clone().set_disabled(["network", "sync"]);
+        assert!(!filter.apply(rendered.clone()).contains("reth_network"));
+        assert!(!filter.apply(rendered.clone()).contains("reth_sync"));
+
+        filter.set_disabled(Vec::<String>::new());
+        assert_eq!(filter.apply(rendered.clone()), rendered);
+    }
+}
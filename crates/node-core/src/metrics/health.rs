@@ -0,0 +1,358 @@
+//! Health and readiness endpoints, served alongside the Prometheus metrics.
+//!
+//! - `/health` checks that the node is able to make progress: the database is writable and the
+//!   snapshots are intact. Meant for liveness probes.
+//! - `/ready` additionally checks that the node is connected and synced: the consensus layer sent a
+//!   recent forkchoice update, enough peers are connected and the head block is recent. Meant for
+//!   readiness probes and load balancers.
+//!
+//! Both return the result of every check as JSON, with status `200` if all checks pass and `503`
+//! otherwise. Checks of components that are not started yet fail.
+
+use hyper::{header::CONTENT_TYPE, Body, Response, StatusCode, Uri};
+use parking_lot::RwLock;
+use reth_db::database::Database;
+use reth_network_api::PeersInfo;
+use reth_primitives::SealedHeader;
+use reth_provider::{providers::SnapshotProvider, BlockReaderIdExt, CanonChainTracker};
+use reth_rpc_types::{DiskPressure, DiskStatus};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::watch;
+
+/// The path of the liveness endpoint.
+const HEALTH_PATH: &str = "/health";
+
+/// The path of the readiness endpoint.
+const READY_PATH: &str = "/ready";
+
+/// The name of the data file of the database.
+const DATABASE_FILE: &str = "mdbx.dat";
+
+/// The thresholds of the readiness checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadinessThresholds {
+    /// The maximum time since the last forkchoice update.
+    pub max_forkchoice_age: Duration,
+    /// The minimum number of connected peers.
+    pub min_peers: usize,
+    /// The maximum age of the head block, by its timestamp.
+    pub max_head_age: Duration,
+}
+
+impl Default for ReadinessThresholds {
+    fn default() -> Self {
+        Self {
+            max_forkchoice_age: Duration::from_secs(120),
+            min_peers: 1,
+            max_head_age: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The database, as checked by the health endpoints.
+pub trait HealthDatabase: Send + Sync {
+    /// Checks that a read transaction can be opened.
+    ///
+    /// Writability isn't checked with a write transaction, since it would wait for the one of
+    /// the sync, which can be open for minutes.
+    fn check_readable(&self) -> Result<(), String>;
+}
+
+impl<DB: Database> HealthDatabase for DB {
+    fn check_readable(&self) -> Result<(), String> {
+        self.tx().map(drop).map_err(|err| err.to_string())
+    }
+}
+
+/// The canonical chain, as checked by the readiness endpoint.
+pub trait HealthChain: Send + Sync {
+    /// Returns the last time a forkchoice update was received from the consensus layer.
+    fn last_forkchoice_update(&self) -> Option<Instant>;
+
+    /// Returns the header of the canonical head.
+    fn head(&self) -> Result<Option<SealedHeader>, String>;
+}
+
+impl<T: CanonChainTracker + BlockReaderIdExt> HealthChain for T {
+    fn last_forkchoice_update(&self) -> Option<Instant> {
+        self.last_received_update_timestamp()
+    }
+
+    fn head(&self) -> Result<Option<SealedHeader>, String> {
+        self.latest_header().map_err(|err| err.to_string())
+    }
+}
+
+/// The components checked by the health endpoints.
+///
+/// Components are added as they are started, clones share the same components.
+#[derive(Clone, Default)]
+pub struct HealthSources {
+    inner: Arc<RwLock<HealthSourcesInner>>,
+    thresholds: ReadinessThresholds,
+}
+
+#[derive(Default)]
+struct HealthSourcesInner {
+    database: Option<(Arc<dyn HealthDatabase>, PathBuf)>,
+    disk: Option<watch::Receiver<DiskStatus>>,
+    snapshots: Option<Arc<SnapshotProvider>>,
+    chain: Option<Box<dyn HealthChain>>,
+    peers: Option<Box<dyn PeersInfo>>,
+}
+
+impl std::fmt::Debug for HealthSources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthSources")
+            .field("thresholds", &self.thresholds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HealthSources {
+    /// Creates new sources with the given readiness thresholds.
+    pub fn new(thresholds: ReadinessThresholds) -> Self {
+        Self { inner: Default::default(), thresholds }
+    }
+
+    /// Checks the given database, with its files in the given directory.
+    pub fn set_database(&self, db: Arc<dyn HealthDatabase>, path: PathBuf) {
+        self.inner.write().database = Some((db, path));
+    }
+
+    /// Checks the disk pressure reported by the disk watchdog.
+    pub fn set_disk_status(&self, status: watch::Receiver<DiskStatus>) {
+        self.inner.write().disk = Some(status);
+    }
+
+    /// Checks the integrity of the given snapshots.
+    pub fn set_snapshots(&self, snapshots: Arc<SnapshotProvider>) {
+        self.inner.write().snapshots = Some(snapshots);
+    }
+
+    /// Checks the forkchoice updates and the head of the given chain.
+    pub fn set_chain(&self, chain: Box<dyn HealthChain>) {
+        self.inner.write().chain = Some(chain);
+    }
+
+    /// Checks the number of peers connected to the given network.
+    pub fn set_peers(&self, peers: Box<dyn PeersInfo>) {
+        self.inner.write().peers = Some(peers);
+    }
+
+    /// Runs the liveness checks.
+    fn health(&self) -> HealthReport {
+        let inner = self.inner.read();
+        let mut report = HealthReport::default();
+        report.add("database", inner.check_database());
+        report.add("snapshots", inner.check_snapshots());
+        report
+    }
+
+    /// Runs the liveness and readiness checks.
+    fn readiness(&self) -> HealthReport {
+        let mut report = self.health();
+        let inner = self.inner.read();
+        report.add("forkchoice", inner.check_forkchoice(&self.thresholds));
+        report.add("peers", inner.check_peers(&self.thresholds));
+        report.add("sync", inner.check_sync(&self.thresholds));
+        report
+    }
+}
+
+impl HealthSourcesInner {
+    fn check_database(&self) -> Check {
+        let Some((db, path)) = &self.database else { return Check::not_started() };
+        if let Err(err) = db.check_readable() {
+            return Check::failed(format!("failed to open a read transaction: {err}"))
+        }
+        // opening the data file for writing fails on read-only filesystems and with missing
+        // permissions, without writing anything
+        if let Err(err) = OpenOptions::new().write(true).open(path.join(DATABASE_FILE)) {
+            return Check::failed(format!("database file is not writable: {err}"))
+        }
+        if let Some(disk) = &self.disk {
+            if disk.borrow().pressure == DiskPressure::Critical {
+                return Check::failed("disk space is critically low, writes are halted")
+            }
+        }
+        Check::ok()
+    }
+
+    fn check_snapshots(&self) -> Check {
+        let Some(snapshots) = &self.snapshots else { return Check::not_started() };
+        match snapshots.check_integrity() {
+            Ok(problems) if problems.is_empty() => Check::ok(),
+            Ok(problems) => Check::failed(problems.join("; ")),
+            Err(err) => Check::failed(format!("failed to read snapshots: {err}")),
+        }
+    }
+
+    fn check_forkchoice(&self, thresholds: &ReadinessThresholds) -> Check {
+        let Some(chain) = &self.chain else { return Check::not_started() };
+        let Some(last_update) = chain.last_forkchoice_update() else {
+            return Check::failed("no forkchoice update received from the consensus layer")
+        };
+        let age = last_update.elapsed();
+        let check = if age <= thresholds.max_forkchoice_age {
+            Check::ok()
+        } else {
+            Check::failed("no recent forkchoice update received from the consensus layer")
+        };
+        check.with_value("seconds_since_update", age.as_secs())
+    }
+
+    fn check_peers(&self, thresholds: &ReadinessThresholds) -> Check {
+        let Some(peers) = &self.peers else { return Check::not_started() };
+        let connected = peers.num_connected_peers();
+        let check = if connected >= thresholds.min_peers {
+            Check::ok()
+        } else {
+            Check::failed(format!("fewer than {} peers connected", thresholds.min_peers))
+        };
+        check.with_value("connected", connected as u64)
+    }
+
+    fn check_sync(&self, thresholds: &ReadinessThresholds) -> Check {
+        let Some(chain) = &self.chain else { return Check::not_started() };
+        let head = match chain.head() {
+            Ok(Some(head)) => head,
+            Ok(None) => return Check::failed("head block is missing"),
+            Err(err) => return Check::failed(format!("failed to read the head block: {err}")),
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let head_age = now.saturating_sub(head.timestamp);
+        let check = if head_age <= thresholds.max_head_age.as_secs() {
+            Check::ok()
+        } else {
+            Check::failed("head block is behind the current time")
+        };
+        check.with_value("head_number", head.number).with_value("head_age_seconds", head_age)
+    }
+}
+
+/// The result of the checks of an endpoint.
+#[derive(Debug, Default, Serialize)]
+struct HealthReport {
+    healthy: bool,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+impl HealthReport {
+    fn add(&mut self, name: &'static str, check: Check) {
+        self.checks.insert(name, check);
+        self.healthy = self.checks.values().all(|check| check.healthy);
+    }
+}
+
+/// The result of a single check.
+#[derive(Debug, Serialize)]
+struct Check {
+    healthy: bool,
+    /// Why the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// The values the check is based on.
+    #[serde(flatten)]
+    values: BTreeMap<&'static str, u64>,
+}
+
+impl Check {
+    fn ok() -> Self {
+        Self { healthy: true, error: None, values: BTreeMap::new() }
+    }
+
+    fn failed(error: impl Into<String>) -> Self {
+        Self { healthy: false, error: Some(error.into()), values: BTreeMap::new() }
+    }
+
+    fn not_started() -> Self {
+        Self::failed("not started")
+    }
+
+    fn with_value(mut self, name: &'static str, value: u64) -> Self {
+        self.values.insert(name, value);
+        self
+    }
+}
+
+/// Handles a request to the health or readiness endpoint.
+///
+/// Returns `None` if the request is not targeted at either endpoint.
+pub(crate) fn handle(uri: &Uri, sources: &HealthSources) -> Option<Response<Body>> {
+    let report = match uri.path() {
+        HEALTH_PATH => sources.health(),
+        READY_PATH => sources.readiness(),
+        _ => return None,
+    };
+
+    let status = if report.healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = serde_json::to_vec(&report).expect("serializable");
+    Some(
+        Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .expect("valid response"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::test_utils::create_test_rw_db;
+    use reth_network_api::noop::NoopNetwork;
+
+    async fn request(
+        sources: &HealthSources,
+        path: &'static str,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = handle(&Uri::from_static(path), sources).unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn serves_health_checks() {
+        let sources = HealthSources::new(ReadinessThresholds::default());
+        assert!(handle(&Uri::from_static("/metrics"), &sources).is_none());
+
+        // nothing is started yet
+        let (status, report) = request(&sources, "/health").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["checks"]["database"]["error"], "not started");
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(DATABASE_FILE), []).unwrap();
+        sources.set_database(create_test_rw_db(), dir.path().to_path_buf());
+        sources.set_snapshots(Arc::new(SnapshotProvider::new(dir.path()).unwrap()));
+        let (status, report) = request(&sources, "/health").await;
+        assert_eq!(status, StatusCode::OK, "{report}");
+        assert_eq!(report["healthy"], true);
+
+        // the node has no peers and the consensus layer never sent a forkchoice update
+        sources.set_peers(Box::new(NoopNetwork::default()));
+        let (status, report) = request(&sources, "/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["checks"]["database"]["healthy"], true);
+        assert_eq!(report["checks"]["peers"]["healthy"], false);
+        assert_eq!(report["checks"]["peers"]["connected"], 0);
+        assert_eq!(report["checks"]["forkchoice"]["error"], "not started");
+
+        // the readiness checks pass without required peers
+        let sources = HealthSources {
+            thresholds: ReadinessThresholds { min_peers: 0, ..Default::default() },
+            ..sources
+        };
+        let (_, report) = request(&sources, "/ready").await;
+        assert_eq!(report["checks"]["peers"]["healthy"], true);
+    }
+}
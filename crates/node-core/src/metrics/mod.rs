@@ -1,4 +1,8 @@
 //! Metrics utilities for the node.
 
+pub mod cardinality;
+pub mod health;
+pub mod profiling;
 pub mod prometheus_exporter;
+pub mod trie_debug;
 pub mod version_metrics;
@@ -0,0 +1,139 @@
+//! CPU and heap profiling endpoints, served alongside the Prometheus metrics.
+//!
+//! - `/debug/pprof/profile?seconds=N` samples the CPU for `N` seconds (default 10) and returns a
+//!   flamegraph SVG. Requires the `profiling` feature.
+//! - `/debug/pprof/heap` dumps a jemalloc heap profile that can be analyzed with `jeprof`. Requires
+//!   the `jemalloc-prof` feature and the node to be started with `_RJEM_MALLOC_CONF=prof:true`.
+
+use hyper::{header::CONTENT_TYPE, Body, Response, StatusCode, Uri};
+use std::time::Duration;
+
+/// The path of the CPU profiling endpoint.
+const CPU_PROFILE_PATH: &str = "/debug/pprof/profile";
+
+/// The path of the heap profiling endpoint.
+const HEAP_PROFILE_PATH: &str = "/debug/pprof/heap";
+
+/// The default duration of a CPU profile.
+const DEFAULT_CPU_PROFILE_DURATION: Duration = Duration::from_secs(10);
+
+/// The maximum duration of a CPU profile.
+const MAX_CPU_PROFILE_DURATION: Duration = Duration::from_secs(300);
+
+/// Handles a request to one of the profiling endpoints.
+///
+/// Returns `None` if the request is not targeted at a profiling endpoint.
+pub(crate) async fn handle(uri: &Uri) -> Option<Response<Body>> {
+    let result = match uri.path() {
+        CPU_PROFILE_PATH => {
+            let duration = match cpu_profile_duration(uri.query()) {
+                Ok(duration) => duration,
+                Err(err) => return Some(error_response(StatusCode::BAD_REQUEST, err)),
+            };
+            tokio::task::spawn_blocking(move || cpu_flamegraph(duration))
+                .await
+                .map_err(Into::into)
+                .and_then(|res| res)
+                .map(|svg| ("image/svg+xml", svg))
+        }
+        HEAP_PROFILE_PATH => tokio::task::spawn_blocking(heap_profile)
+            .await
+            .map_err(Into::into)
+            .and_then(|res| res)
+            .map(|profile| ("application/octet-stream", profile)),
+        _ => return None,
+    };
+
+    Some(match result {
+        Ok((content_type, body)) => Response::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .expect("valid response"),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    })
+}
+
+/// Parses the `seconds` query parameter of a CPU profile request.
+fn cpu_profile_duration(query: Option<&str>) -> eyre::Result<Duration> {
+    let Some(seconds) = query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|param| param.strip_prefix("seconds="))
+    else {
+        return Ok(DEFAULT_CPU_PROFILE_DURATION)
+    };
+
+    let duration = Duration::from_secs(seconds.parse()?);
+    eyre::ensure!(
+        !duration.is_zero() && duration <= MAX_CPU_PROFILE_DURATION,
+        "profile duration must be between 1 and {} seconds",
+        MAX_CPU_PROFILE_DURATION.as_secs()
+    );
+    Ok(duration)
+}
+
+/// Returns a response with the given status and the error as body.
+pub(crate) fn error_response(status: StatusCode, err: eyre::Report) -> Response<Body> {
+    Response::builder().status(status).body(Body::from(err.to_string())).expect("valid response")
+}
+
+#[cfg(all(feature = "profiling", unix))]
+fn cpu_flamegraph(duration: Duration) -> eyre::Result<Vec<u8>> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(1000)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+    std::thread::sleep(duration);
+
+    let mut svg = Vec::new();
+    guard.report().build()?.flamegraph(&mut svg)?;
+    Ok(svg)
+}
+
+#[cfg(not(all(feature = "profiling", unix)))]
+fn cpu_flamegraph(_duration: Duration) -> eyre::Result<Vec<u8>> {
+    eyre::bail!("CPU profiling is not supported, build with the `profiling` feature")
+}
+
+#[cfg(all(feature = "jemalloc-prof", unix))]
+fn heap_profile() -> eyre::Result<Vec<u8>> {
+    use std::ffi::CString;
+
+    // SAFETY: `opt.prof` is a boolean option.
+    let enabled: bool = unsafe { jemalloc_ctl::raw::read(b"opt.prof\0") }?;
+    eyre::ensure!(
+        enabled,
+        "heap profiling is disabled, start the node with `_RJEM_MALLOC_CONF=prof:true`"
+    );
+
+    let path = std::env::temp_dir().join(format!("reth-heap-{}.prof", std::process::id()));
+    let c_path = CString::new(path.to_string_lossy().as_bytes())?;
+    // SAFETY: `prof.dump` expects a pointer to a nul-terminated file name.
+    unsafe { jemalloc_ctl::raw::write(b"prof.dump\0", c_path.as_ptr()) }?;
+
+    let profile = std::fs::read(&path)?;
+    let _ = std::fs::remove_file(&path);
+    Ok(profile)
+}
+
+#[cfg(not(all(feature = "jemalloc-prof", unix)))]
+fn heap_profile() -> eyre::Result<Vec<u8>> {
+    eyre::bail!("heap profiling is not supported, build with the `jemalloc-prof` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cpu_profile_duration() {
+        assert_eq!(cpu_profile_duration(None).unwrap(), DEFAULT_CPU_PROFILE_DURATION);
+        assert_eq!(
+            cpu_profile_duration(Some("foo=bar&seconds=30")).unwrap(),
+            Duration::from_secs(30)
+        );
+        assert!(cpu_profile_duration(Some("seconds=0")).is_err());
+        assert!(cpu_profile_duration(Some("seconds=301")).is_err());
+        assert!(cpu_profile_duration(Some("seconds=abc")).is_err());
+    }
+}
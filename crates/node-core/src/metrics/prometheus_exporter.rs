@@ -1,6 +1,12 @@
 //! Prometheus exporter
 
-use crate::metrics::version_metrics::register_version_metrics;
+use crate::metrics::{
+    cardinality::{LabelLimitLayer, SubsystemFilter},
+    health::{self, HealthSources},
+    profiling,
+    trie_debug::{self, TrieDebugSources},
+    version_metrics::register_version_metrics,
+};
 use eyre::WrapErr;
 use hyper::{
     service::{make_service_fn, service_fn},
@@ -11,19 +17,36 @@ use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use metrics_util::layers::{PrefixLayer, Stack};
 use reth_db::database_metrics::DatabaseMetrics;
 use reth_metrics::metrics::Unit;
-use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use std::{collections::BTreeMap, convert::Infallible, net::SocketAddr, sync::Arc};
 
 pub(crate) trait Hook: Fn() + Send + Sync {}
 impl<T: Fn() + Send + Sync> Hook for T {}
 
 /// Installs Prometheus as the metrics recorder.
 pub fn install_recorder() -> eyre::Result<PrometheusHandle> {
-    let recorder = PrometheusBuilder::new().build_recorder();
+    install_recorder_with_labels(std::iter::empty::<(String, String)>(), BTreeMap::new())
+}
+
+/// Installs Prometheus as the metrics recorder, adding the given labels to all metrics.
+///
+/// The number of distinct values of labels is limited by the given limits, see
+/// [LabelLimitLayer].
+pub fn install_recorder_with_labels<K: Into<String>, V: Into<String>>(
+    labels: impl IntoIterator<Item = (K, V)>,
+    label_limits: BTreeMap<String, usize>,
+) -> eyre::Result<PrometheusHandle> {
+    let recorder = labels
+        .into_iter()
+        .fold(PrometheusBuilder::new(), |builder, (key, value)| {
+            builder.add_global_label(key, value)
+        })
+        .build_recorder();
     let handle = recorder.handle();
 
     // Build metrics stack
     Stack::new(recorder)
         .push(PrefixLayer::new("reth"))
+        .push(LabelLimitLayer::new(label_limits))
         .install()
         .wrap_err("Couldn't set metrics recorder.")?;
 
@@ -34,17 +57,35 @@ pub fn install_recorder() -> eyre::Result<PrometheusHandle> {
 ///
 /// The hooks are called every time the metrics are requested at the given endpoint, and can be used
 /// to record values for pull-style metrics, i.e. metrics that are not automatically updated.
+///
+/// If `profiling` is enabled, the [profiling](crate::metrics::profiling) endpoints are served as
+/// well. The [trie debug](crate::metrics::trie_debug) and [health](crate::metrics::health)
+/// endpoints are always served.
+///
+/// The metrics of the subsystems disabled by the given filter are not served.
 pub(crate) async fn serve_with_hooks<F: Hook + 'static>(
     listen_addr: SocketAddr,
     handle: PrometheusHandle,
     hooks: impl IntoIterator<Item = F>,
+    profiling: bool,
+    trie_debug: TrieDebugSources,
+    health: HealthSources,
+    subsystems: SubsystemFilter,
 ) -> eyre::Result<()> {
     let hooks: Vec<_> = hooks.into_iter().collect();
 
     // Start endpoint
-    start_endpoint(listen_addr, handle, Arc::new(move || hooks.iter().for_each(|hook| hook())))
-        .await
-        .wrap_err("Could not start Prometheus endpoint")?;
+    start_endpoint(
+        listen_addr,
+        handle,
+        Arc::new(move || hooks.iter().for_each(|hook| hook())),
+        profiling,
+        Arc::new(trie_debug),
+        health,
+        subsystems,
+    )
+    .await
+    .wrap_err("Could not start Prometheus endpoint")?;
 
     Ok(())
 }
@@ -54,15 +95,41 @@ async fn start_endpoint<F: Hook + 'static>(
     listen_addr: SocketAddr,
     handle: PrometheusHandle,
     hook: Arc<F>,
+    profiling: bool,
+    trie_debug: Arc<TrieDebugSources>,
+    health: HealthSources,
+    subsystems: SubsystemFilter,
 ) -> eyre::Result<()> {
     let make_svc = make_service_fn(move |_| {
         let handle = handle.clone();
         let hook = Arc::clone(&hook);
+        let trie_debug = Arc::clone(&trie_debug);
+        let health = health.clone();
+        let subsystems = subsystems.clone();
         async move {
-            Ok::<_, Infallible>(service_fn(move |_: Request<Body>| {
-                (hook)();
-                let metrics = handle.render();
-                async move { Ok::<_, Infallible>(Response::new(Body::from(metrics))) }
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let handle = handle.clone();
+                let hook = Arc::clone(&hook);
+                let trie_debug = Arc::clone(&trie_debug);
+                let health = health.clone();
+                let subsystems = subsystems.clone();
+                async move {
+                    if let Some(response) = health::handle(req.uri(), &health) {
+                        return Ok::<_, Infallible>(response)
+                    }
+                    if let Some(response) = trie_debug::handle(req.uri(), &trie_debug) {
+                        return Ok::<_, Infallible>(response)
+                    }
+                    if profiling {
+                        if let Some(response) = profiling::handle(req.uri()).await {
+                            return Ok::<_, Infallible>(response)
+                        }
+                    }
+
+                    (hook)();
+                    let metrics = subsystems.apply(handle.render());
+                    Ok(Response::new(Body::from(metrics)))
+                }
             }))
         }
     });
@@ -75,11 +142,23 @@ async fn start_endpoint<F: Hook + 'static>(
 }
 
 /// Serves Prometheus metrics over HTTP with database and process metrics.
+///
+/// If `profiling` is enabled, CPU and heap profiles can be requested from the same endpoint, see
+/// [profiling](crate::metrics::profiling).
+///
+/// The given trie state is served at the [trie debug](crate::metrics::trie_debug) endpoint, and
+/// the given components are checked by the [health](crate::metrics::health) endpoints.
+///
+/// The metrics of the subsystems disabled by the given filter are not served.
 pub async fn serve<Metrics>(
     listen_addr: SocketAddr,
     handle: PrometheusHandle,
     db: Metrics,
     process: metrics_process::Collector,
+    profiling: bool,
+    trie_debug: TrieDebugSources,
+    health: HealthSources,
+    subsystems: SubsystemFilter,
 ) -> eyre::Result<()>
 where
     Metrics: DatabaseMetrics + 'static + Send + Sync,
@@ -94,7 +173,7 @@ where
         Box::new(collect_memory_stats),
         Box::new(collect_io_stats),
     ];
-    serve_with_hooks(listen_addr, handle, hooks).await?;
+    serve_with_hooks(listen_addr, handle, hooks, profiling, trie_debug, health, subsystems).await?;
 
     // We describe the metrics after the recorder is installed, otherwise this information is not
     // registered
@@ -0,0 +1,162 @@
+//! Trie debug endpoint, served alongside the Prometheus metrics.
+//!
+//! - `/debug/trie` returns a JSON snapshot of the state root computations, i.e. their latency
+//!   percentiles, the most recent ones and the ones in progress, the pinned account trie nodes and
+//!   the sizes and hit rates of the registered cursor caches.
+
+use crate::metrics::profiling::error_response;
+use hyper::{header::CONTENT_TYPE, Body, Response, StatusCode, Uri};
+use reth_primitives::{BlockNumber, B256};
+use reth_trie::{
+    cached_cursors::{CursorCache, CursorCacheStats},
+    slo::{RootSloSnapshot, RootSloTracker},
+    trie_cursor::pinned::PinnedAccountNodes,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// The path of the trie debug endpoint.
+const TRIE_DEBUG_PATH: &str = "/debug/trie";
+
+/// The state served at the trie debug endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct TrieDebugSources {
+    root_slo: Option<Arc<RootSloTracker>>,
+    pinned_account_nodes: Option<Arc<PinnedAccountNodes>>,
+    cursor_caches: Vec<(&'static str, CursorCache)>,
+}
+
+impl TrieDebugSources {
+    /// Serves the state root computations tracked by the given tracker.
+    pub fn with_root_slo(mut self, root_slo: Arc<RootSloTracker>) -> Self {
+        self.root_slo = Some(root_slo);
+        self
+    }
+
+    /// Serves the given pinned account trie nodes.
+    pub fn with_pinned_account_nodes(mut self, pinned: Arc<PinnedAccountNodes>) -> Self {
+        self.pinned_account_nodes = Some(pinned);
+        self
+    }
+
+    /// Serves the size and statistics of the given cursor cache under the given name.
+    pub fn with_cursor_cache(mut self, name: &'static str, cache: CursorCache) -> Self {
+        self.cursor_caches.push((name, cache));
+        self
+    }
+
+    /// Renders a snapshot of all sources as JSON, as served at the trie debug endpoint.
+    pub fn render(&self) -> String {
+        serde_json::to_string_pretty(&self.snapshot()).unwrap_or_else(|err| err.to_string())
+    }
+
+    /// Takes a snapshot of all sources.
+    fn snapshot(&self) -> TrieDebugSnapshot {
+        TrieDebugSnapshot {
+            root_computations: self.root_slo.as_ref().map(|root_slo| root_slo.snapshot()),
+            pinned_account_nodes: self.pinned_account_nodes.as_ref().map(|pinned| {
+                let tip = pinned.tip();
+                PinnedNodesSnapshot {
+                    tip_number: tip.map(|tip| tip.number),
+                    tip_hash: tip.map(|tip| tip.hash),
+                    nodes: pinned.len(),
+                    prefixes: pinned
+                        .prefixes()
+                        .iter()
+                        .map(|prefix| prefix.iter().map(|nibble| format!("{nibble:x}")).collect())
+                        .collect(),
+                }
+            }),
+            cursor_caches: self
+                .cursor_caches
+                .iter()
+                .map(|(name, cache)| {
+                    let stats = cache.stats();
+                    let snapshot = CursorCacheSnapshot {
+                        entries: cache.size(),
+                        hit_rate: stats.total().hit_rate(),
+                        stats,
+                    };
+                    (*name, snapshot)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TrieDebugSnapshot {
+    root_computations: Option<RootSloSnapshot>,
+    pinned_account_nodes: Option<PinnedNodesSnapshot>,
+    cursor_caches: BTreeMap<&'static str, CursorCacheSnapshot>,
+}
+
+#[derive(Debug, Serialize)]
+struct PinnedNodesSnapshot {
+    /// The block the pinned nodes are at, if loaded.
+    tip_number: Option<BlockNumber>,
+    tip_hash: Option<B256>,
+    nodes: usize,
+    /// The pinned prefixes as hex nibbles.
+    prefixes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CursorCacheSnapshot {
+    entries: usize,
+    /// The share of all operations served from the cache.
+    hit_rate: Option<f64>,
+    stats: CursorCacheStats,
+}
+
+/// Handles a request to the trie debug endpoint.
+///
+/// Returns `None` if the request is not targeted at the trie debug endpoint.
+pub(crate) fn handle(uri: &Uri, sources: &TrieDebugSources) -> Option<Response<Body>> {
+    if uri.path() != TRIE_DEBUG_PATH {
+        return None
+    }
+
+    Some(match serde_json::to_vec(&sources.snapshot()) {
+        Ok(body) => Response::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .expect("valid response"),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err.into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::trie::Nibbles;
+    use reth_trie::slo::RootComputation;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn serves_trie_snapshot() {
+        let root_slo = Arc::new(RootSloTracker::new(Duration::from_secs(60)));
+        root_slo.start(RootComputation::Validation, 7).finish();
+        let _in_flight = root_slo.start(RootComputation::Building, 8);
+        let pinned = Arc::new(PinnedAccountNodes::new([Nibbles::from_nibbles_unchecked([0xa, 7])]));
+        let sources = TrieDebugSources::default()
+            .with_root_slo(root_slo)
+            .with_pinned_account_nodes(pinned)
+            .with_cursor_cache("payload", CursorCache::default());
+
+        assert!(handle(&Uri::from_static("/metrics"), &sources).is_none());
+
+        let response = handle(&Uri::from_static("/debug/trie"), &sources).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let snapshot: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(snapshot["root_computations"]["recent"][0]["block_number"], 7);
+        assert_eq!(snapshot["root_computations"]["in_flight"][0]["kind"], "building");
+        // the pinned nodes are loaded once the first block is validated
+        assert!(snapshot["pinned_account_nodes"]["tip_number"].is_null());
+        assert_eq!(snapshot["pinned_account_nodes"]["prefixes"], serde_json::json!(["a7"]));
+        assert_eq!(snapshot["cursor_caches"]["payload"]["entries"], 0);
+        assert!(snapshot["cursor_caches"]["payload"]["hit_rate"].is_null());
+        assert_eq!(snapshot["cursor_caches"]["payload"]["stats"]["accounts"]["misses"], 0);
+    }
+}
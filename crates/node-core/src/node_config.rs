@@ -2,21 +2,33 @@
 
 use crate::{
     args::{
-        get_secret_key, DatabaseArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs,
-        PruningArgs, RpcServerArgs, TxPoolArgs,
+        get_secret_key, DatabaseArgs, DebugArgs, DevArgs, DiskWatchdogArgs, HealthArgs,
+        NetworkArgs, OutcomeStreamArgs, PayloadBuilderArgs, PinnedTrieArgs, PruningArgs,
+        RootSloArgs, RpcServerArgs, TaskGroupArgs, TxPoolArgs,
     },
     cl_events::ConsensusLayerHealthEvents,
     cli::{
         components::{RethNodeComponentsImpl, RethRpcServerHandles},
-        config::{RethRpcConfig, RethTransactionPoolConfig},
+        config::{PayloadBuilderConfigWithDeadline, RethRpcConfig, RethTransactionPoolConfig},
         db_type::{DatabaseBuilder, DatabaseInstance},
         ext::{DefaultRethNodeCommandConfig, RethCliExt, RethNodeCommandConfig},
     },
+    config_watcher::{AdminConfigApi, CliOverrides, ConfigWatcher},
+    crash_bundle::CrashBundle,
     dirs::{ChainPath, DataDirPath, MaybePlatformPath},
+    disk_watchdog::{AdminDiskApi, DiskWatchdog},
     engine_api_store::EngineApiStore,
     events,
+    first_seen::AdminFirstSeenApi,
+    grpc::GrpcServer,
     init::init_genesis,
-    metrics::prometheus_exporter,
+    log_levels::AdminLogApi,
+    metrics::{
+        cardinality::SubsystemFilter, health::HealthSources, prometheus_exporter,
+        trie_debug::TrieDebugSources,
+    },
+    outcome_stream::{NatsSink, OutcomePublisher},
+    trie_server::TrieNodeServer,
     utils::{get_single_header, write_peers_to_file},
     version::SHORT_VERSION,
 };
@@ -24,19 +36,25 @@ use core::future::Future;
 use eyre::WrapErr;
 use fdlimit::raise_fd_limit;
 use futures::{future::Either, stream, stream_select, FutureExt, StreamExt};
+use jsonrpsee::Methods;
 use metrics_exporter_prometheus::PrometheusHandle;
-use once_cell::sync::Lazy;
-use reth_auto_seal_consensus::{AutoSealBuilder, AutoSealConsensus, MiningMode};
+use once_cell::sync::{Lazy, OnceCell};
+use reth_auto_seal_consensus::{AutoSealBuilder, AutoSealConsensus, EvmApi, MiningMode};
 use reth_beacon_consensus::{
-    hooks::{EngineHooks, PruneHook},
+    hooks::{EngineHooks, HaltHook, PruneHook},
     BeaconConsensus, BeaconConsensusEngine, BeaconConsensusEngineError,
     MIN_BLOCKS_FOR_PIPELINE_RUN,
 };
 use reth_blockchain_tree::{
-    config::BlockchainTreeConfig, externals::TreeExternals, BlockchainTree, ShareableBlockchainTree,
+    config::BlockchainTreeConfig,
+    externals::TreeExternals,
+    perf::BlockPerfRecorder,
+    reorg_log::{ReorgLog, DEFAULT_REORG_LOG_CAPACITY},
+    root_check::StateRootCrossCheck,
+    BlockchainTree, ChainJournal, ShareableBlockchainTree,
 };
 use reth_config::{
-    config::{PruneConfig, StageConfig},
+    config::{MetricsConfig, PruneConfig, StageConfig},
     Config,
 };
 use reth_db::{
@@ -79,8 +97,16 @@ use reth_provider::{
     ProviderFactory, StageCheckpointReader,
 };
 use reth_prune::PrunerBuilder;
-use reth_revm::EvmProcessorFactory;
-use reth_rpc_engine_api::EngineApi;
+use reth_revm::{
+    state_access::DEFAULT_STATE_ACCESS_RETENTION, EvmConfig, EvmProcessorFactory,
+    StateAccessRecorder,
+};
+use reth_rpc::{ApiKeys, TransactionStatusApi};
+use reth_rpc_api::{
+    AdminConfigApiServer, AdminDiskApiServer, AdminFirstSeenApiServer, AdminLogApiServer,
+    EvmApiServer, RethTransactionStatusApiServer,
+};
+use reth_rpc_engine_api::{EngineApi, EngineApiJournal, EngineDebugApiServer};
 use reth_stages::{
     prelude::*,
     stages::{
@@ -90,14 +116,21 @@ use reth_stages::{
     },
     MetricEvent,
 };
-use reth_tasks::{TaskExecutor, TaskManager};
+use reth_tasks::{group::TaskGroup, TaskExecutor, TaskManager};
 use reth_transaction_pool::{
     blobstore::DiskFileBlobStore, EthTransactionPool, TransactionPool,
     TransactionValidationTaskExecutor,
 };
+use reth_trie::{
+    hashed_cursor::{SharedStorageFilter, DEFAULT_STORAGE_FILTER_FALSE_POSITIVE_RATE},
+    node_provider::StateRootTrieNodeProvider,
+    slo::RootSloTracker,
+    trie_cursor::pinned::PinnedAccountNodes,
+};
 use revm_inspectors::stack::Hook;
 use secp256k1::SecretKey;
 use std::{
+    collections::BTreeMap,
     net::{SocketAddr, SocketAddrV4},
     path::PathBuf,
     pin::Pin,
@@ -112,8 +145,23 @@ use tracing::*;
 
 /// The default prometheus recorder handle. We use a global static to ensure that it is only
 /// installed once.
-pub static PROMETHEUS_RECORDER_HANDLE: Lazy<PrometheusHandle> =
-    Lazy::new(|| prometheus_exporter::install_recorder().unwrap());
+///
+/// If [PROMETHEUS_PROFILE_LABEL] is set before the recorder is installed, all metrics are labeled
+/// with the profile. If [PROMETHEUS_LABEL_LIMITS] is set before the recorder is installed, the
+/// number of distinct values of the labels is limited.
+pub static PROMETHEUS_RECORDER_HANDLE: Lazy<PrometheusHandle> = Lazy::new(|| {
+    prometheus_exporter::install_recorder_with_labels(
+        PROMETHEUS_PROFILE_LABEL.get().map(|profile| ("profile", profile.as_str())),
+        PROMETHEUS_LABEL_LIMITS.get().cloned().unwrap_or_default(),
+    )
+    .unwrap()
+});
+
+/// The name of the node profile the [PROMETHEUS_RECORDER_HANDLE] labels all metrics with.
+static PROMETHEUS_PROFILE_LABEL: OnceCell<String> = OnceCell::new();
+
+/// The label limits of the [PROMETHEUS_RECORDER_HANDLE], see [MetricsConfig::label_limits].
+static PROMETHEUS_LABEL_LIMITS: OnceCell<BTreeMap<String, usize>> = OnceCell::new();
 
 /// This includes all necessary configuration to launch the node.
 /// The individual configuration options can be overwritten before launching the node.
@@ -190,6 +238,9 @@ pub struct NodeConfig {
     /// The path to the configuration file to use.
     pub config: Option<PathBuf>,
 
+    /// Watch the configuration file and apply changes to runtime-tunable settings.
+    pub config_watch: bool,
+
     /// The chain this node is running.
     ///
     /// Possible values are either a built-in chain or the path to a chain specification file.
@@ -198,8 +249,17 @@ pub struct NodeConfig {
     /// Enable Prometheus metrics.
     ///
     /// The metrics will be served at the given interface and port.
+    /// A JSON snapshot of the state root computations and trie caches is served at `/debug/trie`.
     pub metrics: Option<SocketAddr>,
 
+    /// Serve CPU and heap profiling endpoints on the metrics server.
+    pub metrics_profiling: bool,
+
+    /// The name of the node profile the node was started with, if any.
+    ///
+    /// All metrics are labeled with the profile.
+    pub profile: Option<String>,
+
     /// Add a new instance of a node.
     ///
     /// Configures the ports of the node to avoid conflicts with the defaults.
@@ -242,6 +302,24 @@ pub struct NodeConfig {
     /// All pruning related arguments
     pub pruning: PruningArgs,
 
+    /// All execution outcome stream related arguments with --outcome-stream prefix
+    pub outcome_stream: OutcomeStreamArgs,
+
+    /// All task group related arguments with --task-group prefix
+    pub task_groups: TaskGroupArgs,
+
+    /// All disk watchdog related arguments with --disk prefix
+    pub disk_watchdog: DiskWatchdogArgs,
+
+    /// All state root latency related arguments with --root-slo prefix
+    pub root_slo: RootSloArgs,
+
+    /// All pinned trie related arguments with --trie prefix
+    pub pinned_trie: PinnedTrieArgs,
+
+    /// All readiness check related arguments with --health prefix
+    pub health: HealthArgs,
+
     /// Rollup related arguments
     #[cfg(feature = "optimism")]
     pub rollup: crate::args::RollupArgs,
@@ -253,8 +331,11 @@ impl NodeConfig {
         let mut test = Self {
             database: DatabaseBuilder::test(),
             config: None,
+            config_watch: false,
             chain: MAINNET.clone(),
             metrics: None,
+            metrics_profiling: false,
+            profile: None,
             instance: 1,
             trusted_setup_file: None,
             network: NetworkArgs::default(),
@@ -265,6 +346,13 @@ impl NodeConfig {
             db: DatabaseArgs::default(),
             dev: DevArgs::default(),
             pruning: PruningArgs::default(),
+            outcome_stream: OutcomeStreamArgs::default(),
+            task_groups: TaskGroupArgs::default(),
+            // ephemeral test databases don't need to be guarded against full disks
+            disk_watchdog: DiskWatchdogArgs { disable: true, ..Default::default() },
+            root_slo: RootSloArgs::default(),
+            pinned_trie: PinnedTrieArgs::default(),
+            health: HealthArgs::default(),
             #[cfg(feature = "optimism")]
             rollup: crate::args::RollupArgs::default(),
         };
@@ -286,6 +374,12 @@ impl NodeConfig {
         self
     }
 
+    /// Watch the config file and apply changes to runtime-tunable settings
+    pub fn with_config_watch(mut self, config_watch: bool) -> Self {
+        self.config_watch = config_watch;
+        self
+    }
+
     /// Set the [ChainSpec] for the node
     pub fn with_chain(mut self, chain: impl Into<Arc<ChainSpec>>) -> Self {
         self.chain = chain.into();
@@ -298,6 +392,18 @@ impl NodeConfig {
         self
     }
 
+    /// Enable the profiling endpoints on the metrics server
+    pub fn with_metrics_profiling(mut self, metrics_profiling: bool) -> Self {
+        self.metrics_profiling = metrics_profiling;
+        self
+    }
+
+    /// Set the name of the node profile
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
     /// Set the instance for the node
     pub fn with_instance(mut self, instance: u16) -> Self {
         self.instance = instance;
@@ -358,6 +464,42 @@ impl NodeConfig {
         self
     }
 
+    /// Set the execution outcome stream args for the node
+    pub fn with_outcome_stream(mut self, outcome_stream: OutcomeStreamArgs) -> Self {
+        self.outcome_stream = outcome_stream;
+        self
+    }
+
+    /// Set the task group args for the node
+    pub fn with_task_groups(mut self, task_groups: TaskGroupArgs) -> Self {
+        self.task_groups = task_groups;
+        self
+    }
+
+    /// Set the disk watchdog args for the node
+    pub fn with_disk_watchdog(mut self, disk_watchdog: DiskWatchdogArgs) -> Self {
+        self.disk_watchdog = disk_watchdog;
+        self
+    }
+
+    /// Set the state root latency args for the node
+    pub fn with_root_slo(mut self, root_slo: RootSloArgs) -> Self {
+        self.root_slo = root_slo;
+        self
+    }
+
+    /// Set the pinned trie args for the node
+    pub fn with_pinned_trie(mut self, pinned_trie: PinnedTrieArgs) -> Self {
+        self.pinned_trie = pinned_trie;
+        self
+    }
+
+    /// Set the health args for the node
+    pub fn with_health(mut self, health: HealthArgs) -> Self {
+        self.health = health;
+        self
+    }
+
     /// Set the rollup args for the node
     #[cfg(feature = "optimism")]
     pub fn with_rollup(mut self, rollup: crate::args::RollupArgs) -> Self {
@@ -500,7 +642,38 @@ impl NodeConfig {
         Ok((client, builder))
     }
 
+    /// Returns the recorder of the state accessed by executed blocks, if enabled.
+    pub fn state_access_recorder(&self) -> eyre::Result<Option<StateAccessRecorder>> {
+        let Some(dir) = &self.debug.state_access_dir else { return Ok(None) };
+        let retention = self.debug.state_access_retention.unwrap_or(DEFAULT_STATE_ACCESS_RETENTION);
+        info!(target: "reth::cli", path = %dir.display(), retention, "Recording state accesses of executed blocks");
+        let recorder = StateAccessRecorder::new(dir.clone(), retention)
+            .wrap_err_with(|| format!("Could not open state access directory {}", dir.display()))?;
+        Ok(Some(recorder))
+    }
+
+    /// Connects to the trie node server that is asked for the trie nodes missing locally, if
+    /// configured.
+    pub async fn remote_trie_nodes(
+        &self,
+    ) -> eyre::Result<Option<Arc<dyn StateRootTrieNodeProvider>>> {
+        let Some(path) = &self.pinned_trie.remote_nodes else { return Ok(None) };
+        #[cfg(unix)]
+        {
+            let nodes = crate::trie_server::RemoteTrieNodeProvider::connect(path)
+                .await
+                .wrap_err_with(|| format!("Could not connect to the trie node server at {path}"))?;
+            info!(target: "reth::cli", %path, state_root = %nodes.pinned_state().state_root, "Connected to remote trie node server");
+            Ok(Some(Arc::new(nodes)))
+        }
+        #[cfg(not(unix))]
+        {
+            eyre::bail!("remote trie nodes are only supported on unix, can't connect to {path}")
+        }
+    }
+
     /// Build the blockchain tree
+    #[allow(clippy::too_many_arguments)]
     pub fn build_blockchain_tree<DB>(
         &self,
         provider_factory: ProviderFactory<DB>,
@@ -508,16 +681,41 @@ impl NodeConfig {
         prune_config: Option<PruneConfig>,
         sync_metrics_tx: UnboundedSender<MetricEvent>,
         tree_config: BlockchainTreeConfig,
+        root_slo: Arc<RootSloTracker>,
+        pinned_account_nodes: Option<Arc<PinnedAccountNodes>>,
+        remote_trie_nodes: Option<Arc<dyn StateRootTrieNodeProvider>>,
+        executor_factory: EvmProcessorFactory,
     ) -> eyre::Result<BlockchainTree<DB, EvmProcessorFactory>>
     where
         DB: Database + Unpin + Clone + 'static,
     {
         // configure blockchain tree
-        let tree_externals = TreeExternals::new(
-            provider_factory.clone(),
-            consensus.clone(),
-            EvmProcessorFactory::new(self.chain.clone()),
-        );
+        let mut tree_externals =
+            TreeExternals::new(provider_factory.clone(), consensus.clone(), executor_factory)
+                .with_root_slo(root_slo);
+        if let Some(path) = &self.debug.block_perf_log {
+            info!(target: "reth::cli", path = %path.display(), "Writing block performance records");
+            tree_externals =
+                tree_externals.with_perf_recorder(BlockPerfRecorder::with_log_file(path)?);
+        }
+        if let Some(interval) = self.debug.state_root_cross_check {
+            info!(target: "reth::cli", interval, "Cross-checking state roots");
+            let mut root_cross_check = StateRootCrossCheck::new(interval);
+            if let Some(dir) = &self.debug.state_root_cross_check_dir {
+                root_cross_check = root_cross_check.with_repro_dir(dir.clone());
+            }
+            tree_externals = tree_externals.with_state_root_cross_check(root_cross_check);
+        }
+        if let Some(pinned) = pinned_account_nodes {
+            info!(target: "reth::cli", prefixes = pinned.prefixes().len(), "Pinning account trie subtrees");
+            tree_externals = tree_externals.with_pinned_account_nodes(pinned);
+        }
+        if let Some(nodes) = remote_trie_nodes {
+            tree_externals = tree_externals.with_remote_trie_nodes(nodes);
+        }
+        if let Some(storage_filter) = provider_factory.storage_filter() {
+            tree_externals = tree_externals.with_storage_filter(storage_filter.clone());
+        }
         let tree = BlockchainTree::new(
             tree_externals,
             tree_config,
@@ -554,6 +752,10 @@ impl NodeConfig {
         let transaction_pool =
             reth_transaction_pool::Pool::eth_pool(validator, blob_store, self.txpool.pool_config());
         info!(target: "reth::cli", "Transaction pool initialized");
+        if self.txpool.first_seen {
+            reth_transaction_pool::first_seen::install(self.txpool.first_seen_max);
+            info!(target: "reth::cli", max = self.txpool.first_seen_max, "Tracking first seen transactions");
+        }
         let transactions_path = data_dir.txpool_transactions_path();
 
         // spawn txpool maintenance task
@@ -616,6 +818,7 @@ impl NodeConfig {
         metrics_tx: reth_stages::MetricEventsSender,
         prune_config: Option<PruneConfig>,
         max_block: Option<BlockNumber>,
+        executor_factory: EvmProcessorFactory,
     ) -> eyre::Result<Pipeline<DB>>
     where
         DB: Database + Unpin + Clone + 'static,
@@ -641,6 +844,7 @@ impl NodeConfig {
                 self.debug.continuous,
                 metrics_tx,
                 prune_config,
+                executor_factory,
             )
             .await?;
 
@@ -659,7 +863,23 @@ impl NodeConfig {
         }
     }
 
-    fn install_prometheus_recorder(&self) -> eyre::Result<PrometheusHandle> {
+    fn install_prometheus_recorder(
+        &self,
+        metrics_config: &MetricsConfig,
+    ) -> eyre::Result<PrometheusHandle> {
+        if let Some(profile) = &self.profile {
+            if Lazy::get(&PROMETHEUS_RECORDER_HANDLE).is_some() ||
+                PROMETHEUS_PROFILE_LABEL.set(profile.clone()).is_err()
+            {
+                warn!(target: "reth::cli", %profile, "Metrics recorder already installed, metrics are not labeled with the profile");
+            }
+        }
+        if !metrics_config.label_limits.is_empty() &&
+            (Lazy::get(&PROMETHEUS_RECORDER_HANDLE).is_some() ||
+                PROMETHEUS_LABEL_LIMITS.set(metrics_config.label_limits.clone()).is_err())
+        {
+            warn!(target: "reth::cli", "Metrics recorder already installed, label limits are not applied");
+        }
         Ok(PROMETHEUS_RECORDER_HANDLE.clone())
     }
 
@@ -667,6 +887,9 @@ impl NodeConfig {
         &self,
         prometheus_handle: PrometheusHandle,
         db: Metrics,
+        trie_debug: TrieDebugSources,
+        health: HealthSources,
+        subsystems: SubsystemFilter,
     ) -> eyre::Result<()>
     where
         Metrics: DatabaseMetrics + 'static + Send + Sync,
@@ -678,6 +901,10 @@ impl NodeConfig {
                 prometheus_handle,
                 db,
                 metrics_process::Collector::default(),
+                self.metrics_profiling,
+                trie_debug,
+                health,
+                subsystems,
             )
             .await?;
         }
@@ -846,6 +1073,7 @@ impl NodeConfig {
         continuous: bool,
         metrics_tx: reth_stages::MetricEventsSender,
         prune_config: Option<PruneConfig>,
+        executor_factory: EvmProcessorFactory,
     ) -> eyre::Result<Pipeline<DB>>
     where
         DB: Database + Clone + 'static,
@@ -861,8 +1089,6 @@ impl NodeConfig {
 
         let (tip_tx, tip_rx) = watch::channel(B256::ZERO);
         use revm_inspectors::stack::InspectorStackConfig;
-        let factory = reth_revm::EvmProcessorFactory::new(self.chain.clone());
-
         let stack_config = InspectorStackConfig {
             use_printer_tracer: self.debug.print_inspector,
             hook: if let Some(hook_block) = self.debug.hook_block {
@@ -876,7 +1102,7 @@ impl NodeConfig {
             },
         };
 
-        let factory = factory.with_stack_config(stack_config);
+        let factory = executor_factory.with_stack_config(stack_config);
 
         let prune_modes = prune_config.map(|prune| prune.segments).unwrap_or_default();
 
@@ -966,8 +1192,11 @@ impl Default for NodeConfig {
         Self {
             database: DatabaseBuilder::default(),
             config: None,
+            config_watch: false,
             chain: MAINNET.clone(),
             metrics: None,
+            metrics_profiling: false,
+            profile: None,
             instance: 1,
             trusted_setup_file: None,
             network: NetworkArgs::default(),
@@ -978,6 +1207,12 @@ impl Default for NodeConfig {
             db: DatabaseArgs::default(),
             dev: DevArgs::default(),
             pruning: PruningArgs::default(),
+            outcome_stream: OutcomeStreamArgs::default(),
+            task_groups: TaskGroupArgs::default(),
+            disk_watchdog: DiskWatchdogArgs::default(),
+            root_slo: RootSloArgs::default(),
+            pinned_trie: PinnedTrieArgs::default(),
+            health: HealthArgs::default(),
             #[cfg(feature = "optimism")]
             rollup: crate::args::RollupArgs::default(),
         }
@@ -1009,12 +1244,40 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
         // Does not do anything on windows.
         raise_fd_limit()?;
 
+        for (group, worker_threads) in &self.config.task_groups.dedicated_runtimes {
+            executor.spawn_dedicated_runtime(*group, *worker_threads)?;
+            info!(target: "reth::cli", %group, worker_threads, "Spawned dedicated runtime");
+        }
+
         // get config
         let config = self.load_config()?;
 
-        let prometheus_handle = self.config.install_prometheus_recorder()?;
+        // the customizations of the EVM apply to all components that execute transactions
+        let mut evm_config = EvmConfig::default();
+        ext.configure_evm(&mut evm_config)?;
+        let mut executor_factory = EvmProcessorFactory::new(Arc::clone(&self.config.chain))
+            .with_evm_config(evm_config.clone());
+        if let Some(recorder) = self.config.state_access_recorder()? {
+            executor_factory = executor_factory.with_state_access_recorder(recorder);
+        }
+
+        let prometheus_handle = self.config.install_prometheus_recorder(&config.metrics)?;
+        let root_slo = {
+            let prometheus_handle = prometheus_handle.clone();
+            Arc::new(
+                self.config
+                    .root_slo
+                    .tracker(self.data_dir.root_slo_dump_path())
+                    .with_stats_source("metrics", move || prometheus_handle.render()),
+            )
+        };
         info!(target: "reth::cli", "Database opened");
 
+        let prune_config = self
+            .config
+            .pruning
+            .prune_config(Arc::clone(&self.config.chain), config.prune.clone())?;
+
         let mut provider_factory =
             ProviderFactory::new(Arc::clone(&self.db), Arc::clone(&self.config.chain));
 
@@ -1025,12 +1288,59 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
             self.config.chain.snapshot_block_interval,
         )?;
 
+        // transactions whose lookup entries are pruned are found with the filters of snapshots
+        let load_snapshot_filters = prune_config
+            .as_ref()
+            .is_some_and(|prune_config| prune_config.segments.transaction_lookup.is_some());
         provider_factory = provider_factory.with_snapshots(
             self.data_dir.snapshots_path(),
             snapshotter.highest_snapshot_receiver(),
+            load_snapshot_filters,
         )?;
+        if self.config.pinned_trie.storage_filter {
+            info!(target: "reth::cli", "Building storage filter in the background");
+            let storage_filter = SharedStorageFilter::new(
+                Arc::clone(&self.db),
+                DEFAULT_STORAGE_FILTER_FALSE_POSITIVE_RATE,
+            );
+            storage_filter.rebuild_in_background();
+            provider_factory = provider_factory.with_storage_filter(storage_filter);
+        }
+
+        let pinned_account_nodes = self.config.pinned_trie.pinned_account_nodes();
+        let remote_trie_nodes = self.config.remote_trie_nodes().await?;
+        let mut trie_debug = TrieDebugSources::default().with_root_slo(Arc::clone(&root_slo));
+        if let Some(pinned) = &pinned_account_nodes {
+            trie_debug = trie_debug.with_pinned_account_nodes(Arc::clone(pinned));
+        }
+        if !self.config.debug.no_crash_bundle {
+            let prometheus_handle = prometheus_handle.clone();
+            let trie_debug = trie_debug.clone();
+            CrashBundle::new()
+                .with_data_dir(self.data_dir.clone())
+                .with_config(config.clone())
+                .with_checkpoints(Box::new(provider_factory.clone()))
+                .with_stats_source("metrics.txt", move || prometheus_handle.render())
+                .with_stats_source("trie.json", move || trie_debug.render())
+                .install_panic_hook(self.data_dir.crash_bundles_path());
+        }
 
-        self.config.start_metrics_endpoint(prometheus_handle, Arc::clone(&self.db)).await?;
+        // the checks of the components that are launched later are registered once they exist
+        let health = HealthSources::new(self.config.health.thresholds());
+        health.set_database(Arc::clone(&self.db) as _, self.data_dir.db_path());
+        if let Some(snapshots) = provider_factory.snapshot_provider() {
+            health.set_snapshots(snapshots);
+        }
+        let metrics_subsystems = SubsystemFilter::new(&config.metrics.disabled_subsystems);
+        self.config
+            .start_metrics_endpoint(
+                prometheus_handle,
+                Arc::clone(&self.db),
+                trie_debug,
+                health.clone(),
+                metrics_subsystems.clone(),
+            )
+            .await?;
 
         debug!(target: "reth::cli", chain=%self.config.chain.chain, genesis=?self.config.chain.genesis_hash(), "Initializing genesis");
 
@@ -1045,21 +1355,41 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
         let sync_metrics_listener = reth_stages::MetricsListener::new(sync_metrics_rx);
         executor.spawn_critical("stages metrics listener task", sync_metrics_listener);
 
-        let prune_config = self
-            .config
-            .pruning
-            .prune_config(Arc::clone(&self.config.chain))?
-            .or(config.prune.clone());
+        let disk_watchdog = if self.config.disk_watchdog.disable {
+            None
+        } else {
+            let thresholds = self.config.disk_watchdog.thresholds()?;
+            Some(
+                DiskWatchdog::new(self.data_dir.db_path(), thresholds)
+                    .with_check_interval(self.config.disk_watchdog.check_interval),
+            )
+        };
 
         // configure blockchain tree
         let tree_config = BlockchainTreeConfig::default();
-        let tree = self.config.build_blockchain_tree(
-            provider_factory.clone(),
-            consensus.clone(),
-            prune_config.clone(),
-            sync_metrics_tx.clone(),
-            tree_config,
-        )?;
+        let mut reorg_log =
+            ReorgLog::open(self.data_dir.reorg_log_path(), DEFAULT_REORG_LOG_CAPACITY)?;
+        let mut chain_journal = ChainJournal::open(self.data_dir.chain_journal_path())?;
+        if let Some(watchdog) = &disk_watchdog {
+            health.set_disk_status(watchdog.subscribe());
+            reorg_log = reorg_log.with_persist_paused(watchdog.degraded_signal());
+            chain_journal = chain_journal.with_persist_paused(watchdog.degraded_signal());
+        }
+        let tree = self
+            .config
+            .build_blockchain_tree(
+                provider_factory.clone(),
+                consensus.clone(),
+                prune_config.clone(),
+                sync_metrics_tx.clone(),
+                tree_config,
+                Arc::clone(&root_slo),
+                pinned_account_nodes,
+                remote_trie_nodes,
+                executor_factory.clone(),
+            )?
+            .with_reorg_log(reorg_log)
+            .with_chain_journal(chain_journal);
         let canon_state_notification_sender = tree.canon_state_notification_sender();
         let blockchain_tree = ShareableBlockchainTree::new(tree);
         debug!(target: "reth::cli", "configured blockchain tree");
@@ -1073,6 +1403,11 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
         // setup the blockchain provider
         let blockchain_db =
             BlockchainProvider::new(provider_factory.clone(), blockchain_tree.clone())?;
+        health.set_chain(Box::new(blockchain_db.clone()));
+        executor.spawn_critical_blocking(
+            "safe and finalized persistence",
+            blockchain_db.clone().persist_safe_and_finalized(),
+        );
 
         // build transaction pool
         let transaction_pool =
@@ -1084,7 +1419,7 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
             .build_network(
                 &config,
                 provider_factory.clone(),
-                executor.clone(),
+                executor.group(TaskGroup::Network),
                 head,
                 &self.data_dir,
             )
@@ -1096,7 +1431,8 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
             network_builder.handle(),
             executor.clone(),
             blockchain_db.clone(),
-        );
+        )
+        .with_evm_config(evm_config.clone());
 
         // allow network modifications
         ext.configure_network(network_builder.network_mut(), &components)?;
@@ -1104,19 +1440,46 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
         // launch network
         let network = self.config.start_network(
             network_builder,
-            &executor,
+            &executor.group(TaskGroup::Network),
             transaction_pool.clone(),
             network_client,
             &self.data_dir,
         );
 
+        health.set_peers(Box::new(network.clone()));
+
         info!(target: "reth::cli", peer_id = %network.peer_id(), local_addr = %network.local_addr(), enode = %network.local_node_record(), "Connected to P2P network");
         debug!(target: "reth::cli", peer_id = ?network.peer_id(), "Full peer ID");
         let network_client = network.fetch_client().await?;
 
+        let api_keys = config.rpc.api_keys.clone().map(ApiKeys::new);
+        let mut config_watcher = ConfigWatcher::new(
+            self.config_path(),
+            config.clone(),
+            CliOverrides::from_args(
+                &self.config.network,
+                &self.config.rpc.rpc_state_cache,
+                &self.config.builder,
+            ),
+            network.peers_handle().clone(),
+            metrics_subsystems,
+        );
+        if let Some(api_keys) = &api_keys {
+            config_watcher = config_watcher.with_api_keys(api_keys.clone());
+        }
+        let admin_config_api = AdminConfigApi::new(config_watcher.subscribe());
+
         ext.on_components_initialized(&components)?;
 
         debug!(target: "reth::cli", "Spawning payload builder service");
+        let payload_components = RethNodeComponentsImpl::new(
+            blockchain_db.clone(),
+            transaction_pool.clone(),
+            network.clone(),
+            executor.group(TaskGroup::PayloadBuilder),
+            blockchain_db.clone(),
+        )
+        .with_evm_config(evm_config.clone());
 
         // TODO: stateful node builder should handle this in with_payload_builder
         // Optimism's payload builder is implemented on the OptimismPayloadBuilder type.
@@ -1125,21 +1488,51 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
             .set_compute_pending_block(self.config.builder.compute_pending_block);
 
         #[cfg(feature = "optimism")]
-        let payload_builder: PayloadBuilderHandle<OptimismEngineTypes> =
-            ext.spawn_payload_builder_service(&self.config.builder, &components, payload_builder)?;
+        let payload_builder: PayloadBuilderHandle<OptimismEngineTypes> = ext
+            .spawn_payload_builder_service(
+                &PayloadBuilderConfigWithDeadline::new(
+                    &self.config.builder,
+                    config_watcher.payload_deadline(),
+                ),
+                &payload_components,
+                payload_builder,
+            )?;
 
         // The default payload builder is implemented on the unit type.
         #[cfg(not(feature = "optimism"))]
-        let payload_builder = reth_ethereum_payload_builder::EthereumPayloadBuilder::default();
+        let top_of_block_auction = self.config.builder.top_of_block_auction().map(Arc::new);
+        #[cfg(not(feature = "optimism"))]
+        let payload_builder = {
+            let mut payload_builder =
+                reth_ethereum_payload_builder::EthereumPayloadBuilder::default()
+                    .with_deferred_state_root(self.config.builder.deferred_state_root)
+                    .with_ordering(self.config.builder.ordering())
+                    .with_root_slo(Arc::clone(&root_slo))
+                    .with_evm_config(evm_config.clone());
+            if let Some(auction) = &top_of_block_auction {
+                payload_builder = payload_builder.with_top_of_block_auction(Arc::clone(auction));
+            }
+            payload_builder
+        };
 
         #[cfg(not(feature = "optimism"))]
-        let payload_builder: PayloadBuilderHandle<EthEngineTypes> =
-            ext.spawn_payload_builder_service(&self.config.builder, &components, payload_builder)?;
+        let payload_builder: PayloadBuilderHandle<EthEngineTypes> = ext
+            .spawn_payload_builder_service(
+                &PayloadBuilderConfigWithDeadline::new(
+                    &self.config.builder,
+                    config_watcher.payload_deadline(),
+                ),
+                &payload_components,
+                payload_builder,
+            )?;
 
         let (consensus_engine_tx, mut consensus_engine_rx) = unbounded_channel();
         if let Some(store_path) = self.config.debug.engine_api_store.clone() {
             let (engine_intercept_tx, engine_intercept_rx) = unbounded_channel();
-            let engine_api_store = EngineApiStore::new(store_path);
+            let mut engine_api_store = EngineApiStore::new(store_path);
+            if let Some(watchdog) = &disk_watchdog {
+                engine_api_store = engine_api_store.with_pause_signal(watchdog.degraded_signal());
+            }
             executor.spawn_critical(
                 "engine api interceptor",
                 engine_api_store.intercept(consensus_engine_rx, engine_intercept_tx),
@@ -1149,20 +1542,41 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
         let max_block = self.config.max_block(&network_client, provider_factory.clone()).await?;
 
         // Configure the pipeline
-        let (mut pipeline, client) = if self.config.dev.dev {
+        let (mut pipeline, client, mut extra_methods) = if self.config.dev.dev {
             info!(target: "reth::cli", "Starting Reth in dev mode");
             let mining_mode =
                 self.config.mining_mode(transaction_pool.pending_transactions_listener());
 
-            let (_, client, mut task) = AutoSealBuilder::new(
+            let auto_seal = AutoSealBuilder::new(
                 Arc::clone(&self.config.chain),
                 blockchain_db.clone(),
                 transaction_pool.clone(),
                 consensus_engine_tx.clone(),
                 canon_state_notification_sender,
                 mining_mode,
-            )
-            .build();
+            );
+            // blocks are built by the payload builder, the same way as for a consensus layer
+            #[cfg(not(feature = "optimism"))]
+            let auto_seal = {
+                let chain = Arc::clone(&self.config.chain);
+                auto_seal.with_payload_builder(payload_builder.clone(), move |parent, timestamp| {
+                    reth_payload_builder::EthPayloadBuilderAttributes::new(
+                        parent,
+                        reth_rpc_types::engine::PayloadAttributes {
+                            timestamp,
+                            prev_randao: B256::ZERO,
+                            suggested_fee_recipient: Default::default(),
+                            withdrawals: chain
+                                .is_shanghai_active_at_timestamp(timestamp)
+                                .then(Vec::new),
+                            parent_beacon_block_root: chain
+                                .is_cancun_active_at_timestamp(timestamp)
+                                .then_some(B256::ZERO),
+                        },
+                    )
+                })
+            };
+            let (_, client, mut task) = auto_seal.build();
 
             let mut pipeline = self
                 .config
@@ -1175,15 +1589,17 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
                     sync_metrics_tx,
                     prune_config.clone(),
                     max_block,
+                    executor_factory.clone(),
                 )
                 .await?;
 
             let pipeline_events = pipeline.events();
             task.set_pipeline_events(pipeline_events);
+            let dev_methods = EvmApi::new(task.control()).into_rpc();
             debug!(target: "reth::cli", "Spawning auto mine task");
             executor.spawn(Box::pin(task));
 
-            (pipeline, EitherDownloader::Left(client))
+            (pipeline, EitherDownloader::Left(client), dev_methods.into())
         } else {
             let pipeline = self
                 .config
@@ -1192,15 +1608,32 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
                     network_client.clone(),
                     Arc::clone(&consensus),
                     provider_factory.clone(),
-                    &executor.clone(),
+                    &executor.group(TaskGroup::Engine),
                     sync_metrics_tx,
                     prune_config.clone(),
                     max_block,
+                    executor_factory.clone(),
                 )
                 .await?;
 
-            (pipeline, EitherDownloader::Right(network_client))
+            (pipeline, EitherDownloader::Right(network_client), Methods::new())
         };
+        extra_methods.merge(admin_config_api.into_rpc())?;
+        if let Some(filters) = reth_tracing::log_filters::log_filters() {
+            let admin_log_api = AdminLogApi::new(filters, Box::new(executor.clone()));
+            extra_methods.merge(admin_log_api.into_rpc())?;
+        }
+        if let Some(tracker) = reth_transaction_pool::first_seen::tracker() {
+            extra_methods.merge(AdminFirstSeenApi::new(tracker).into_rpc())?;
+        }
+        extra_methods.merge(
+            TransactionStatusApi::new(
+                blockchain_db.clone(),
+                transaction_pool.clone(),
+                payload_builder.clone(),
+            )
+            .into_rpc(),
+        )?;
 
         let pipeline_events = pipeline.events();
 
@@ -1211,10 +1644,27 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
             let mut pruner = PrunerBuilder::new(prune_config.clone())
                 .max_reorg_depth(tree_config.max_reorg_depth() as usize)
                 .prune_delete_limit(self.config.chain.prune_delete_limit)
-                .build(provider_factory, snapshotter.highest_snapshot_receiver());
+                .build(provider_factory, snapshotter.highest_snapshot_receiver())
+                .with_delete_limit_updates(
+                    config_watcher.prune_delete_limit(self.config.chain.prune_delete_limit),
+                );
+            if let Some(watchdog) = &disk_watchdog {
+                pruner = pruner.with_aggressive_signal(watchdog.degraded_signal());
+            }
+            if self.config.pruning.transaction_lookup_distance.is_some() {
+                pruner = pruner.with_snapshotted_transaction_lookup();
+                if snapshotter
+                    .highest_snapshot_receiver()
+                    .borrow()
+                    .and_then(|snapshots| snapshots.transactions)
+                    .is_none()
+                {
+                    warn!(target: "reth::cli", "No transaction snapshots found, transaction lookup entries are not pruned");
+                }
+            }
 
             let events = pruner.events();
-            hooks.add(PruneHook::new(pruner, Box::new(executor.clone())));
+            hooks.add(PruneHook::new(pruner, Box::new(executor.group(TaskGroup::Pruning))));
 
             info!(target: "reth::cli", ?prune_config, "Pruner initialized");
             Either::Left(events)
@@ -1222,12 +1672,20 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
             Either::Right(stream::empty())
         };
 
+        if let Some(watchdog) = disk_watchdog {
+            hooks.add(HaltHook::new(watchdog.critical_signal()));
+            extra_methods.merge(AdminDiskApi::new(watchdog.subscribe()).into_rpc())?;
+            let status = watchdog.subscribe().borrow().clone();
+            info!(target: "reth::cli", ?status, "Disk watchdog initialized");
+            executor.spawn(Box::pin(watchdog.run()));
+        }
+
         // Configure the consensus engine
         let (beacon_consensus_engine, beacon_engine_handle) = BeaconConsensusEngine::with_channel(
             client,
             pipeline,
             blockchain_db.clone(),
-            Box::new(executor.clone()),
+            Box::new(executor.group(TaskGroup::Engine)),
             Box::new(network.clone()),
             max_block,
             self.config.debug.continuous,
@@ -1264,15 +1722,25 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
             ),
         );
 
-        let engine_api = EngineApi::new(
+        let mut engine_api = EngineApi::new(
             blockchain_db.clone(),
             self.config.chain.clone(),
             beacon_engine_handle,
             payload_builder.into(),
-            Box::new(executor.clone()),
+            Box::new(executor.group(TaskGroup::Rpc)),
         );
+        if let Some(path) = &self.config.debug.engine_api_journal {
+            engine_api = engine_api.with_journal(EngineApiJournal::open(path)?);
+            info!(target: "reth::cli", path = %path.display(), "Recording engine API exchanges");
+        }
         info!(target: "reth::cli", "Engine API handler initialized");
 
+        let mut extra_auth_methods = Methods::new();
+        if self.config.debug.insert_block_api {
+            extra_auth_methods.merge(EngineDebugApiServer::into_rpc(engine_api.clone()))?;
+            info!(target: "reth::cli", "Serving debug_insertBlock on the auth server");
+        }
+
         // extract the jwt secret from the args if possible
         let default_jwt_path = self.data_dir.jwt_path();
         let jwt_secret = self.config.rpc.auth_jwt_secret(default_jwt_path)?;
@@ -1281,13 +1749,83 @@ impl<DB: Database + DatabaseMetrics + DatabaseMetadata + 'static> NodeBuilderWit
         self.config.adjust_instance_ports();
 
         // Start RPC servers
-        let rpc_server_handles =
-            self.config.rpc.start_servers(&components, engine_api, jwt_secret, &mut ext).await?;
+        let rpc_components = RethNodeComponentsImpl::new(
+            blockchain_db.clone(),
+            transaction_pool.clone(),
+            network.clone(),
+            executor.group(TaskGroup::Rpc),
+            blockchain_db.clone(),
+        )
+        .with_evm_config(evm_config);
+        let rpc_server_handles = self
+            .config
+            .rpc
+            .start_servers_with(
+                &rpc_components,
+                engine_api,
+                jwt_secret.clone(),
+                &mut ext,
+                extra_methods,
+                extra_auth_methods,
+                api_keys,
+            )
+            .await?;
+
+        if self.config.config_watch {
+            info!(target: "reth::cli", path = ?self.config_path(), "Watching configuration file for changes");
+            let config_watcher =
+                config_watcher.with_eth_cache(rpc_server_handles.eth_cache.clone());
+            executor.spawn(Box::pin(config_watcher.run()));
+        }
+
+        #[cfg(not(feature = "optimism"))]
+        if let Some(auction) = top_of_block_auction {
+            let addr = self.config.builder.top_of_block_socket_addr();
+            let server = reth_rpc_builder::ServerBuilder::new().build(addr).await?;
+            let handle = server.start(reth_rpc_api::BuilderApiServer::into_rpc(
+                reth_rpc::BuilderApi::new(auction),
+            ));
+            info!(target: "reth::cli", %addr, "Top of block bid server started");
+            executor.spawn(Box::pin(async move { handle.stopped().await }));
+        }
+
+        if let Some(path) = &self.config.rpc.trie_server_ipcpath {
+            let handle = TrieNodeServer::new(provider_factory.clone()).start_ipc(path).await?;
+            info!(target: "reth::cli", %path, "Trie node server started");
+            executor.spawn(Box::pin(async move { handle.stopped().await }));
+        }
+
+        if let Some(addr) = self.config.rpc.grpc_addr {
+            let addr = GrpcServer::new(
+                blockchain_db.clone(),
+                network.clone(),
+                rpc_server_handles.eth_cache.clone(),
+                jwt_secret,
+                Box::new(executor.clone()),
+            )
+            .start(addr)?;
+            info!(target: "reth::cli", %addr, "gRPC server started");
+        }
+
+        if let Some(url) = &self.config.outcome_stream.nats {
+            let mut sink = NatsSink::new(url, self.config.outcome_stream.subject.clone())?;
+            if let Some(ca_file) = &self.config.outcome_stream.ca_file {
+                sink = sink.with_ca_file(ca_file)?;
+            }
+            let address = sink.address().to_string();
+            let publisher = OutcomePublisher::new(
+                blockchain_db.clone(),
+                sink,
+                self.data_dir.outcome_stream_token_path(),
+            );
+            executor.spawn_blocking(Box::pin(publisher.run()));
+            info!(target: "reth::cli", %address, "Execution outcome stream started");
+        }
 
         // Run consensus engine to completion
         let (tx, rx) = oneshot::channel();
         info!(target: "reth::cli", "Starting consensus engine");
-        executor.spawn_critical_blocking("consensus engine", async move {
+        executor.group(TaskGroup::Engine).spawn_critical_blocking("consensus engine", async move {
             let res = beacon_consensus_engine.await;
             let _ = tx.send(res);
         });
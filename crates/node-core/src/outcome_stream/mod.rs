@@ -0,0 +1,384 @@
+//! Streaming of canonical execution outcomes to external message queues.
+//!
+//! Every block that becomes canonical is published as a protobuf encoded
+//! [BlockOutcome](proto::BlockOutcome) with its receipts, the post-block values of the changed
+//! state and trie statistics. Blocks that are reverted by a reorg are published again with the
+//! `reverted` flag set, before the blocks of the new chain.
+//!
+//! Delivery is at-least-once: a message is retried until the [OutcomeSink] acknowledged it, and
+//! the [ResumeToken] of the last published canonical block is only stored afterwards. On restart,
+//! publishing resumes after the stored token by replaying the persisted blocks, so consumers must
+//! deduplicate by block hash.
+
+use futures::{future::BoxFuture, StreamExt};
+use reth_interfaces::provider::ProviderResult;
+use reth_metrics::{
+    metrics::{Counter, Gauge, Histogram},
+    Metrics,
+};
+use reth_primitives::{BlockNumHash, BlockNumber, B256};
+use reth_provider::{
+    BlockHashReader, BlockNumReader, CanonStateNotification, CanonStateReplay,
+    CanonStateReplayStream, Chain, DEFAULT_REPLAY_CHUNK_SIZE,
+};
+use reth_trie::updates::{TrieKey, TrieOp, TrieUpdates};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tracing::{error, info, warn};
+
+mod nats;
+pub use nats::NatsSink;
+
+pub mod proto;
+use proto::{AccountDiff, BlockOutcome, TrieStats};
+
+/// The initial delay before a failed publish is retried.
+const MIN_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The maximum delay before a failed publish is retried.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A message queue that execution outcomes are published to.
+pub trait OutcomeSink: Send {
+    /// Publishes the encoded message.
+    ///
+    /// Resolves once the queue acknowledged the message, an error means that the message may not
+    /// have been delivered and is retried.
+    fn publish<'a>(&'a mut self, payload: &'a [u8]) -> BoxFuture<'a, io::Result<()>>;
+}
+
+/// The last canonical block whose outcome was published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeToken {
+    /// Block number.
+    pub number: BlockNumber,
+    /// Block hash.
+    pub hash: B256,
+}
+
+impl ResumeToken {
+    /// Loads the token from the given file, if it exists.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        match File::open(path) {
+            Ok(file) => Ok(Some(serde_json::from_reader(BufReader::new(file))?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Stores the token in the given file.
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // write to a temporary file first, so a crash never leaves a truncated token behind
+        let tmp = path.with_extension("tmp");
+        serde_json::to_writer(BufWriter::new(File::create(&tmp)?), self)?;
+        fs::rename(tmp, path)
+    }
+}
+
+impl From<BlockNumHash> for ResumeToken {
+    fn from(block: BlockNumHash) -> Self {
+        Self { number: block.number, hash: block.hash }
+    }
+}
+
+/// Publishes the execution outcomes of canonical blocks to an [OutcomeSink].
+#[derive(Debug)]
+pub struct OutcomePublisher<P, S> {
+    provider: P,
+    sink: S,
+    /// The file the [ResumeToken] is stored in.
+    token_path: PathBuf,
+    metrics: OutcomeStreamMetrics,
+}
+
+impl<P, S> OutcomePublisher<P, S>
+where
+    P: CanonStateReplay,
+    S: OutcomeSink,
+{
+    /// Creates a new publisher that stores its [ResumeToken] in the given file.
+    pub fn new(provider: P, sink: S, token_path: PathBuf) -> Self {
+        Self { provider, sink, token_path, metrics: Default::default() }
+    }
+
+    /// Publishes outcomes until the canonical state stream ends or the persisted blocks can no
+    /// longer be read.
+    ///
+    /// Persisted blocks are read while publishing, so this should be spawned as a blocking task.
+    pub async fn run(mut self) {
+        let token = ResumeToken::load(&self.token_path).unwrap_or_else(|err| {
+            warn!(target: "reth::outcome_stream", %err, "Discarding unreadable resume token");
+            None
+        });
+        let from_block = match token {
+            Some(token) => {
+                if self.provider.block_hash(token.number).ok().flatten() == Some(token.hash) {
+                    token.number + 1
+                } else {
+                    // the token block was reorged while the node was stopped
+                    warn!(target: "reth::outcome_stream", number = token.number, hash = %token.hash, "Resume token is not canonical, republishing its height");
+                    token.number
+                }
+            }
+            None => match self.provider.last_block_number() {
+                Ok(number) => number + 1,
+                Err(err) => {
+                    error!(target: "reth::outcome_stream", %err, "Failed to read canonical tip");
+                    return
+                }
+            },
+        };
+        info!(target: "reth::outcome_stream", from_block, "Publishing execution outcomes");
+
+        let stream =
+            CanonStateReplayStream::new(&self.provider, from_block, DEFAULT_REPLAY_CHUNK_SIZE);
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                error!(target: "reth::outcome_stream", %err, "Failed to subscribe to canonical state");
+                return
+            }
+        };
+
+        let mut next_block = from_block;
+        while let Some(notification) = stream.next().await {
+            let notification = match notification {
+                Ok(notification) => notification,
+                Err(err) => {
+                    error!(target: "reth::outcome_stream", %err, "Failed to replay canonical state");
+                    return
+                }
+            };
+
+            let (outcomes, tip) = match notification_outcomes(
+                &self.provider,
+                next_block,
+                &notification,
+            ) {
+                Ok(outcomes) => outcomes,
+                Err(err) => {
+                    error!(target: "reth::outcome_stream", %err, "Failed to replay missed blocks");
+                    return
+                }
+            };
+
+            for outcome in &outcomes {
+                publish(&mut self.sink, &self.metrics, outcome).await;
+            }
+
+            let Some(tip) = tip else { continue };
+            next_block = tip.number + 1;
+            if let Err(err) = ResumeToken::from(tip).store(&self.token_path) {
+                warn!(target: "reth::outcome_stream", %err, "Failed to store resume token");
+            }
+        }
+    }
+}
+
+/// Returns the execution outcomes of a canonical state notification and the new canonical tip.
+///
+/// Reverted blocks come first, in reverse order. Notifications are skipped if a subscriber lags
+/// behind, so committed blocks between `next_block` and the first block of the notification are
+/// read from the database.
+pub(crate) fn notification_outcomes<P: CanonStateReplay>(
+    provider: &P,
+    next_block: BlockNumber,
+    notification: &CanonStateNotification,
+) -> ProviderResult<(Vec<BlockOutcome>, Option<BlockNumHash>)> {
+    let mut outcomes = Vec::new();
+    let new = notification.committed().filter(|new| !new.is_empty());
+    if let Some(new) = &new {
+        let first = new.first().number;
+        if first > next_block {
+            let missed = provider.replay_canonical_state(next_block..=first - 1)?;
+            if let Some(missed) = missed.committed() {
+                outcomes.extend(block_outcomes(&missed, false));
+            }
+        }
+    }
+    let old = notification.reverted();
+    if let Some(old) = &old {
+        outcomes.extend(block_outcomes(old, true).into_iter().rev());
+    }
+    if let Some(new) = &new {
+        outcomes.extend(block_outcomes(new, false));
+    }
+
+    let tip = match (&new, &old) {
+        (Some(new), _) => Some(new.tip().num_hash()),
+        (None, Some(old)) => Some(old.fork_block()),
+        (None, None) => None,
+    };
+    Ok((outcomes, tip))
+}
+
+/// Publishes the outcome, retrying until it was acknowledged.
+async fn publish<S: OutcomeSink>(
+    sink: &mut S,
+    metrics: &OutcomeStreamMetrics,
+    outcome: &BlockOutcome,
+) {
+    let payload = outcome.encode();
+    let mut backoff = MIN_RETRY_BACKOFF;
+    loop {
+        let started_at = Instant::now();
+        match sink.publish(&payload).await {
+            Ok(()) => {
+                metrics.publish_duration.record(started_at.elapsed());
+                metrics.published_blocks.increment(1);
+                if !outcome.reverted {
+                    metrics.last_published_block.set(outcome.number as f64);
+                }
+                return
+            }
+            Err(err) => {
+                metrics.publish_errors.increment(1);
+                warn!(target: "reth::outcome_stream", number = outcome.number, %err, ?backoff, "Failed to publish execution outcome, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+            }
+        }
+    }
+}
+
+/// Splits the chain into the execution outcomes of its blocks, ordered by block number.
+///
+/// The trie statistics of the whole chain are attached to the outcome of its tip, if the chain has
+/// trie updates.
+pub fn block_outcomes(chain: &Chain, reverted: bool) -> Vec<BlockOutcome> {
+    let mut state = chain.state().clone();
+    let mut outcomes = Vec::with_capacity(chain.len());
+    for block in chain.blocks().values().rev() {
+        // the state after the block, the last reverts are the changes of the block
+        state.revert_to(block.number);
+
+        let receipts = block
+            .body
+            .iter()
+            .zip(state.receipts_by_block(block.number))
+            .filter_map(|(transaction, receipt)| Some((transaction.hash(), receipt.clone()?)))
+            .collect();
+        let mut accounts = state
+            .state()
+            .reverts
+            .last()
+            .into_iter()
+            .flatten()
+            .map(|(address, revert)| {
+                let mut storage = revert
+                    .storage
+                    .keys()
+                    .map(|key| (*key, state.storage(address, *key).unwrap_or_default()))
+                    .collect::<Vec<_>>();
+                storage.sort_unstable();
+                AccountDiff {
+                    address: *address,
+                    info: state.account(address).flatten(),
+                    storage_wiped: revert.wipe_storage,
+                    storage,
+                }
+            })
+            .collect::<Vec<_>>();
+        accounts.sort_unstable_by_key(|account| account.address);
+
+        outcomes.push(BlockOutcome {
+            number: block.number,
+            hash: block.hash(),
+            parent_hash: block.parent_hash,
+            reverted,
+            receipts,
+            accounts,
+            trie_stats: None,
+        });
+    }
+    outcomes.reverse();
+
+    if let (Some(tip), Some(updates)) = (outcomes.last_mut(), chain.trie_updates()) {
+        tip.trie_stats = Some(trie_stats(updates));
+    }
+    outcomes
+}
+
+fn trie_stats(updates: &TrieUpdates) -> TrieStats {
+    let mut stats = TrieStats::default();
+    for (key, op) in updates.iter() {
+        match (key, op) {
+            (TrieKey::StorageTrie(_), _) => {}
+            (_, TrieOp::Delete) => stats.removed_nodes += 1,
+            (TrieKey::AccountNode(_), TrieOp::Update(_)) => stats.updated_account_nodes += 1,
+            (TrieKey::StorageNode(..), TrieOp::Update(_)) => stats.updated_storage_nodes += 1,
+        }
+    }
+    stats
+}
+
+/// Metrics of the execution outcome stream.
+#[derive(Metrics)]
+#[metrics(scope = "outcome_stream")]
+struct OutcomeStreamMetrics {
+    /// The number of published block outcomes, including reverted blocks
+    published_blocks: Counter,
+    /// The number of failed publish attempts
+    publish_errors: Counter,
+    /// The time it took to publish and acknowledge a block outcome
+    publish_duration: Histogram,
+    /// The number of the last published canonical block
+    last_published_block: Gauge,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::U256;
+    use reth_provider::test_utils::blocks::BlockChainTestData;
+
+    #[test]
+    fn split_chain_into_block_outcomes() {
+        let data = BlockChainTestData::default();
+        let (blocks, states): (Vec<_>, Vec<_>) = data.blocks.into_iter().unzip();
+        let mut state = states[0].clone();
+        state.extend(states[1].clone());
+        let chain = Chain::new(blocks.clone(), state, None);
+
+        let outcomes = block_outcomes(&chain, false);
+        assert_eq!(outcomes.len(), 2);
+        for (outcome, block) in outcomes.iter().zip(&blocks) {
+            assert_eq!(outcome.number, block.number);
+            assert_eq!(outcome.hash, block.hash());
+            assert_eq!(outcome.receipts.len(), 1);
+            assert_eq!(outcome.receipts[0].0, block.body[0].hash());
+        }
+
+        // block one creates two accounts, block two updates one of them
+        let account = |outcome: &BlockOutcome, byte| {
+            outcome.accounts.iter().find(|account| account.address == [byte; 20].into()).cloned()
+        };
+        assert_eq!(outcomes[0].accounts.len(), 2);
+        let first = account(&outcomes[0], 0x60).unwrap();
+        assert_eq!(first.info.unwrap().nonce, 1);
+        assert_eq!(first.storage, vec![(U256::from(5), U256::from(10))]);
+        assert_eq!(outcomes[1].accounts.len(), 1);
+        let second = account(&outcomes[1], 0x60).unwrap();
+        assert_eq!(second.info.unwrap().nonce, 3);
+        assert_eq!(second.storage, vec![(U256::from(5), U256::from(15))]);
+    }
+
+    #[test]
+    fn store_resume_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("outcome-stream.json");
+        assert_eq!(ResumeToken::load(&path).unwrap(), None);
+        let token = ResumeToken { number: 10, hash: B256::repeat_byte(1) };
+        token.store(&path).unwrap();
+        assert_eq!(ResumeToken::load(&path).unwrap(), Some(token));
+    }
+}
@@ -0,0 +1,169 @@
+//! Protobuf encoding of published execution outcomes.
+//!
+//! The schema is versioned by the package name and only ever extended with new fields. It is
+//! shipped as `proto/reth/outcome/v1/outcome.proto` of this crate:
+#![doc = concat!("```proto\n", include_str!("../../proto/reth/outcome/v1/outcome.proto"), "```")]
+
+use crate::protobuf::{
+    encode_bool, encode_bytes, encode_length_delimited, encode_message, encode_uint,
+};
+use reth_primitives::{Account, Address, Log, Receipt, TxHash, B256, U256};
+
+/// The execution outcome of a single block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockOutcome {
+    /// Block number.
+    pub number: u64,
+    /// Block hash.
+    pub hash: B256,
+    /// Parent block hash.
+    pub parent_hash: B256,
+    /// Whether the block was reverted from the canonical chain.
+    pub reverted: bool,
+    /// Receipts with the hashes of their transactions.
+    pub receipts: Vec<(TxHash, Receipt)>,
+    /// Post-block values of the accounts changed by the block.
+    pub accounts: Vec<AccountDiff>,
+    /// Trie statistics of the published chain segment.
+    pub trie_stats: Option<TrieStats>,
+}
+
+/// The post-block value of a changed account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDiff {
+    /// Account address.
+    pub address: Address,
+    /// The account info, `None` if the account was destroyed.
+    pub info: Option<Account>,
+    /// Whether all storage of the account was cleared.
+    pub storage_wiped: bool,
+    /// Changed storage slots and their new values.
+    pub storage: Vec<(U256, U256)>,
+}
+
+/// Statistics of the trie updates of a chain segment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrieStats {
+    /// Number of updated account trie nodes.
+    pub updated_account_nodes: u64,
+    /// Number of updated storage trie nodes.
+    pub updated_storage_nodes: u64,
+    /// Number of removed trie nodes.
+    pub removed_nodes: u64,
+}
+
+impl BlockOutcome {
+    /// Encodes the outcome as a `reth.outcome.v1.BlockOutcome` message.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_uint(&mut buf, 1, self.number);
+        encode_bytes(&mut buf, 2, self.hash.as_slice());
+        encode_bytes(&mut buf, 3, self.parent_hash.as_slice());
+        encode_bool(&mut buf, 4, self.reverted);
+        for (tx_hash, receipt) in &self.receipts {
+            encode_message(&mut buf, 5, |buf| encode_receipt(buf, tx_hash, receipt));
+        }
+        for account in &self.accounts {
+            encode_message(&mut buf, 6, |buf| encode_account(buf, account));
+        }
+        if let Some(stats) = &self.trie_stats {
+            encode_message(&mut buf, 7, |buf| {
+                encode_uint(buf, 1, stats.updated_account_nodes);
+                encode_uint(buf, 2, stats.updated_storage_nodes);
+                encode_uint(buf, 3, stats.removed_nodes);
+            });
+        }
+        buf
+    }
+}
+
+/// Encodes the fields of a `reth.outcome.v1.Receipt` message.
+pub(crate) fn encode_receipt(buf: &mut Vec<u8>, tx_hash: &TxHash, receipt: &Receipt) {
+    encode_bytes(buf, 1, tx_hash.as_slice());
+    encode_uint(buf, 2, receipt.tx_type as u64);
+    encode_bool(buf, 3, receipt.success);
+    encode_uint(buf, 4, receipt.cumulative_gas_used);
+    for log in &receipt.logs {
+        encode_message(buf, 5, |buf| encode_log(buf, log));
+    }
+}
+
+fn encode_log(buf: &mut Vec<u8>, log: &Log) {
+    encode_bytes(buf, 1, log.address.as_slice());
+    for topic in &log.topics {
+        encode_length_delimited(buf, 2, topic.as_slice());
+    }
+    encode_bytes(buf, 3, &log.data);
+}
+
+fn encode_account(buf: &mut Vec<u8>, account: &AccountDiff) {
+    encode_bytes(buf, 1, account.address.as_slice());
+    match &account.info {
+        Some(info) => {
+            encode_uint(buf, 3, info.nonce);
+            encode_bytes(buf, 4, &info.balance.to_be_bytes::<32>());
+            if let Some(code_hash) = &info.bytecode_hash {
+                encode_bytes(buf, 5, code_hash.as_slice());
+            }
+        }
+        None => encode_bool(buf, 2, true),
+    }
+    encode_bool(buf, 6, account.storage_wiped);
+    for (key, value) in &account.storage {
+        encode_message(buf, 7, |buf| {
+            encode_bytes(buf, 1, &key.to_be_bytes::<32>());
+            encode_bytes(buf, 2, &value.to_be_bytes::<32>());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{hex_literal::hex, TxType};
+
+    #[test]
+    fn encode_block_outcome() {
+        let outcome = BlockOutcome {
+            number: 300,
+            hash: B256::repeat_byte(0x11),
+            parent_hash: B256::ZERO,
+            reverted: true,
+            receipts: vec![(
+                B256::repeat_byte(0x22),
+                Receipt {
+                    tx_type: TxType::EIP1559,
+                    success: true,
+                    cumulative_gas_used: 21_000,
+                    logs: vec![],
+                    #[cfg(feature = "optimism")]
+                    deposit_nonce: None,
+                    #[cfg(feature = "optimism")]
+                    deposit_receipt_version: None,
+                },
+            )],
+            accounts: vec![AccountDiff {
+                address: Address::repeat_byte(0x33),
+                info: None,
+                storage_wiped: true,
+                storage: vec![],
+            }],
+            trie_stats: Some(TrieStats::default()),
+        };
+
+        let mut expected = hex!("08ac02").to_vec();
+        expected.extend(hex!("1220"));
+        expected.extend([0x11; 32]);
+        expected.extend(hex!("1a20"));
+        expected.extend([0x00; 32]);
+        expected.extend(hex!("2001"));
+        expected.extend(hex!("2a2a0a20"));
+        expected.extend([0x22; 32]);
+        expected.extend(hex!("100218012088a401"));
+        expected.extend(hex!("321a0a14"));
+        expected.extend([0x33; 20]);
+        expected.extend(hex!("10013001"));
+        expected.extend(hex!("3a00"));
+        assert_eq!(outcome.encode(), expected);
+    }
+}
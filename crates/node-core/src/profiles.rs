@@ -0,0 +1,186 @@
+//! Named node profiles.
+//!
+//! A profile bundles the settings that have to differ between nodes running on the same host: the
+//! chain, the data dir, the config file and the instance that the ports are derived from. Profiles
+//! are stored in a TOML file, by default `profiles.toml` in the reth configuration directory:
+//!
+//! ```toml
+//! [profiles.builder-mainnet]
+//! chain = "mainnet"
+//! datadir = "/data/reth/builder-mainnet"
+//! instance = 2
+//! ```
+//!
+//! A profile is selected with `reth node --profile <NAME>`. Its metrics are labeled with the
+//! profile name and its logs are written to a directory of its own.
+
+use crate::dirs::{config_dir, data_dir};
+use eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The file name of the profiles file in the reth configuration directory.
+pub const PROFILES_FILE_NAME: &str = "profiles.toml";
+
+/// Returns the default path of the profiles file.
+pub fn default_profiles_path() -> Option<PathBuf> {
+    config_dir().map(|root| root.join(PROFILES_FILE_NAME))
+}
+
+/// The settings of a named node profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeProfile {
+    /// The chain of the node, either a built-in chain or the path to a chain specification file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// The data dir of the node.
+    ///
+    /// Defaults to a directory named after the profile in `profiles/` of the default data dir.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datadir: Option<PathBuf>,
+    /// The config file of the node.
+    ///
+    /// Defaults to `reth.toml` in the data dir.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<PathBuf>,
+    /// The instance the ports of the node are derived from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<u16>,
+}
+
+impl NodeProfile {
+    /// Returns the data dir of the profile with the given name.
+    pub fn datadir(&self, name: &str) -> Option<PathBuf> {
+        self.datadir.clone().or_else(|| data_dir().map(|root| root.join("profiles").join(name)))
+    }
+}
+
+/// All named node profiles, as stored in the profiles file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeProfiles {
+    /// The profiles by name.
+    pub profiles: BTreeMap<String, NodeProfile>,
+}
+
+impl NodeProfiles {
+    /// Loads the profiles from the given file, or returns no profiles if the file doesn't exist.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default())
+        }
+        confy::load_path(path)
+            .wrap_err_with(|| format!("Could not load profiles file {}", path.display()))
+    }
+
+    /// Stores the profiles to the given file.
+    pub fn store(&self, path: &Path) -> eyre::Result<()> {
+        confy::store_path(path, self)
+            .wrap_err_with(|| format!("Could not store profiles file {}", path.display()))
+    }
+
+    /// Returns the profile with the given name.
+    pub fn get(&self, name: &str) -> eyre::Result<&NodeProfile> {
+        self.profiles.get(name).ok_or_else(|| eyre::eyre!("Unknown profile: {name}"))
+    }
+
+    /// Adds a profile, replacing the profile with the same name if there is one.
+    ///
+    /// If the profile has no instance, the lowest instance not used by another profile is
+    /// assigned, so the ports of the profiles don't collide.
+    pub fn insert(&mut self, name: String, mut profile: NodeProfile) -> eyre::Result<&NodeProfile> {
+        eyre::ensure!(is_valid_name(&name), "Invalid profile name: {name}");
+
+        if profile.instance.is_none() {
+            let used = self
+                .profiles
+                .iter()
+                .filter(|(other, _)| **other != name)
+                .filter_map(|(_, profile)| profile.instance)
+                .collect::<Vec<_>>();
+            let instance = (1..=MAX_INSTANCE)
+                .find(|instance| !used.contains(instance))
+                .ok_or_else(|| eyre::eyre!("All {MAX_INSTANCE} instances are in use"))?;
+            profile.instance = Some(instance);
+        }
+
+        self.profiles.insert(name.clone(), profile);
+        Ok(&self.profiles[&name])
+    }
+
+    /// Removes the profile with the given name.
+    pub fn remove(&mut self, name: &str) -> Option<NodeProfile> {
+        self.profiles.remove(name)
+    }
+}
+
+/// The highest instance, see `--instance`.
+const MAX_INSTANCE: u16 = 200;
+
+/// Profile names are used as directory names and metric labels.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.') &&
+        name != "." &&
+        name != ".."
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_assigns_free_instance() {
+        let mut profiles = NodeProfiles::default();
+        let builder = profiles
+            .insert(
+                "builder-mainnet".to_string(),
+                NodeProfile { chain: Some("mainnet".to_string()), ..Default::default() },
+            )
+            .unwrap();
+        assert_eq!(builder.instance, Some(1));
+
+        profiles
+            .insert("archive".to_string(), NodeProfile { instance: Some(2), ..Default::default() })
+            .unwrap();
+        let holesky = profiles.insert("holesky".to_string(), NodeProfile::default()).unwrap();
+        assert_eq!(holesky.instance, Some(3));
+
+        // replacing a profile may reuse its own instance
+        let builder =
+            profiles.insert("builder-mainnet".to_string(), NodeProfile::default()).unwrap();
+        assert_eq!(builder.instance, Some(1));
+
+        assert!(profiles.insert("../etc".to_string(), NodeProfile::default()).is_err());
+    }
+
+    #[test]
+    fn store_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PROFILES_FILE_NAME);
+        assert_eq!(NodeProfiles::load(&path).unwrap(), NodeProfiles::default());
+
+        let mut profiles = NodeProfiles::default();
+        profiles
+            .insert(
+                "builder-mainnet".to_string(),
+                NodeProfile {
+                    chain: Some("mainnet".to_string()),
+                    datadir: Some("/data/builder".into()),
+                    config: None,
+                    instance: Some(4),
+                },
+            )
+            .unwrap();
+        profiles.store(&path).unwrap();
+
+        let loaded = NodeProfiles::load(&path).unwrap();
+        assert_eq!(loaded, profiles);
+        assert_eq!(loaded.get("builder-mainnet").unwrap().instance, Some(4));
+        assert!(loaded.get("unknown").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Minimal protobuf wire format encoding and decoding.
+//!
+//! Only what is needed for the hand-written schemas of this crate is supported. Singular scalar
+//! fields with default values are omitted, as in proto3.
+
+const WIRE_TYPE_VARINT: u64 = 0;
+const WIRE_TYPE_FIXED64: u64 = 1;
+const WIRE_TYPE_LENGTH_DELIMITED: u64 = 2;
+const WIRE_TYPE_FIXED32: u64 = 5;
+
+pub(crate) fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn encode_key(buf: &mut Vec<u8>, field: u64, wire_type: u64) {
+    encode_varint(buf, (field << 3) | wire_type);
+}
+
+/// Encodes a singular integer field.
+pub(crate) fn encode_uint(buf: &mut Vec<u8>, field: u64, value: u64) {
+    if value != 0 {
+        encode_key(buf, field, WIRE_TYPE_VARINT);
+        encode_varint(buf, value);
+    }
+}
+
+/// Encodes a singular bool field.
+pub(crate) fn encode_bool(buf: &mut Vec<u8>, field: u64, value: bool) {
+    encode_uint(buf, field, value as u64);
+}
+
+/// Encodes a singular bytes or string field.
+pub(crate) fn encode_bytes(buf: &mut Vec<u8>, field: u64, value: &[u8]) {
+    if !value.is_empty() {
+        encode_length_delimited(buf, field, value);
+    }
+}
+
+/// Encodes an embedded message field.
+pub(crate) fn encode_message(buf: &mut Vec<u8>, field: u64, f: impl FnOnce(&mut Vec<u8>)) {
+    let mut message = Vec::new();
+    f(&mut message);
+    encode_length_delimited(buf, field, &message);
+}
+
+/// Encodes an element of a repeated bytes or string field.
+pub(crate) fn encode_length_delimited(buf: &mut Vec<u8>, field: u64, value: &[u8]) {
+    encode_key(buf, field, WIRE_TYPE_LENGTH_DELIMITED);
+    encode_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value);
+}
+
+/// The value of a decoded field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldValue<'a> {
+    /// A varint encoded integer or bool.
+    Varint(u64),
+    /// Bytes, a string or an embedded message.
+    LengthDelimited(&'a [u8]),
+    /// A fixed size integer.
+    Fixed(u64),
+}
+
+/// Error returned for malformed messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("malformed protobuf message")]
+pub(crate) struct DecodeError;
+
+/// Iterator over the `(field number, value)` pairs of an encoded message.
+///
+/// Iteration stops after the first error.
+#[derive(Debug)]
+pub(crate) struct FieldReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> FieldReader<'a> {
+    pub(crate) fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn read_field(&mut self) -> Result<(u64, FieldValue<'a>), DecodeError> {
+        let key = decode_varint(&mut self.buf)?;
+        let value = match key & 0x7 {
+            WIRE_TYPE_VARINT => FieldValue::Varint(decode_varint(&mut self.buf)?),
+            WIRE_TYPE_LENGTH_DELIMITED => {
+                let len =
+                    usize::try_from(decode_varint(&mut self.buf)?).map_err(|_| DecodeError)?;
+                if len > self.buf.len() {
+                    return Err(DecodeError)
+                }
+                let (value, rest) = self.buf.split_at(len);
+                self.buf = rest;
+                FieldValue::LengthDelimited(value)
+            }
+            WIRE_TYPE_FIXED64 => FieldValue::Fixed(u64::from_le_bytes(self.take::<8>()?)),
+            WIRE_TYPE_FIXED32 => FieldValue::Fixed(u32::from_le_bytes(self.take::<4>()?) as u64),
+            _ => return Err(DecodeError),
+        };
+        Ok((key >> 3, value))
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        if self.buf.len() < N {
+            return Err(DecodeError)
+        }
+        let (value, rest) = self.buf.split_at(N);
+        self.buf = rest;
+        Ok(value.try_into().expect("length checked"))
+    }
+}
+
+impl<'a> Iterator for FieldReader<'a> {
+    type Item = Result<(u64, FieldValue<'a>), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None
+        }
+        let field = self.read_field();
+        if field.is_err() {
+            self.buf = &[];
+        }
+        Some(field)
+    }
+}
+
+fn decode_varint(buf: &mut &[u8]) -> Result<u64, DecodeError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first().ok_or(DecodeError)?;
+        *buf = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            return Ok(value)
+        }
+    }
+    Err(DecodeError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_fields() {
+        let mut buf = Vec::new();
+        encode_uint(&mut buf, 1, 300);
+        encode_uint(&mut buf, 2, 0);
+        encode_bytes(&mut buf, 3, b"reth");
+        encode_message(&mut buf, 4, |buf| encode_bool(buf, 1, true));
+
+        let fields = FieldReader::new(&buf).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                (1, FieldValue::Varint(300)),
+                (3, FieldValue::LengthDelimited(b"reth")),
+                (4, FieldValue::LengthDelimited(&[0x08, 0x01])),
+            ]
+        );
+
+        // truncated length delimited field
+        assert_eq!(FieldReader::new(&buf[..7]).last(), Some(Err(DecodeError)));
+    }
+}
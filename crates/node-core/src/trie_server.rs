@@ -0,0 +1,563 @@
+//! Trie node server for distributed proof workers.
+//!
+//! Serves trie nodes and hashed state at a pinned state root over a dedicated IPC endpoint, so
+//! that external worker processes can compute storage roots and proofs in parallel.
+//!
+//! A pinned state holds a read transaction open, which prevents the database from reusing the
+//! pages of the pinned state. Workers are expected to unpin states as soon as they are done, and
+//! at most [MAX_PINNED_STATES] states can be pinned at the same time. A state that is not accessed
+//! for [PINNED_STATE_TTL] is unpinned, so a worker that goes away can't hold a state forever.
+//!
+//! [RemoteTrieNodeProvider] is the client side, it can be used as the last tier of a
+//! [FallbackTrieNodeProvider](reth_trie::node_provider::FallbackTrieNodeProvider) by instances
+//! that only hold part of the trie locally.
+
+use jsonrpsee::{
+    core::RpcResult,
+    server::ServerHandle,
+    types::error::{ErrorObject, ErrorObjectOwned, INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+};
+use parking_lot::Mutex;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+};
+use reth_primitives::{
+    stage::StageId,
+    trie::{BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey},
+    Bytes, StorageEntry, B256,
+};
+use reth_provider::{
+    DatabaseProviderRO, HeaderProvider, ProviderError, ProviderFactory, StageCheckpointReader,
+};
+use reth_rpc_api::{
+    HashedAccountEntry, PinnedState, TrieNodeApiClient, TrieNodeApiServer, TrieNodeEntry,
+};
+use reth_rpc_builder::IpcServerBuilder;
+use reth_trie::node_provider::{
+    StateRootTrieNodeProvider, TrieNodeProvider, TrieNodeProviderError,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// The maximum number of states that can be pinned at the same time.
+pub const MAX_PINNED_STATES: usize = 16;
+
+/// How long a pinned state is kept without being accessed before it is unpinned.
+pub const PINNED_STATE_TTL: Duration = Duration::from_secs(60);
+
+/// The maximum number of entries returned by a single range request.
+pub const MAX_RANGE_LIMIT: usize = 10_000;
+
+/// `trie_` implementation that serves trie nodes and hashed state from pinned database states.
+pub struct TrieNodeServer<DB: Database> {
+    inner: Arc<TrieNodeServerInner<DB>>,
+    /// How long a pinned state is kept without being accessed.
+    ttl: Duration,
+}
+
+impl<DB: Database + 'static> TrieNodeServer<DB> {
+    /// Creates a new instance of the [TrieNodeServer].
+    pub fn new(provider_factory: ProviderFactory<DB>) -> Self {
+        Self {
+            inner: Arc::new(TrieNodeServerInner {
+                provider_factory,
+                next_id: AtomicU64::new(0),
+                pinned: Mutex::new(HashMap::new()),
+            }),
+            ttl: PINNED_STATE_TTL,
+        }
+    }
+
+    /// Sets how long a pinned state is kept without being accessed, [PINNED_STATE_TTL] by
+    /// default.
+    pub fn with_pinned_state_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Starts serving the `trie_` namespace on the IPC endpoint at the given path.
+    ///
+    /// Also spawns the task that unpins expired states, which exits once the server is stopped.
+    pub async fn start_ipc(self, path: &str) -> eyre::Result<ServerHandle> {
+        let inner = Arc::downgrade(&self.inner);
+        let mut interval = tokio::time::interval(self.ttl);
+        tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                let Some(inner) = inner.upgrade() else { break };
+                inner.remove_expired();
+            }
+        });
+
+        let server = IpcServerBuilder::default().build(path)?;
+        Ok(server.start(self.into_rpc()).await?)
+    }
+
+    /// Returns the provider of the pinned state with the given id and extends its lease.
+    fn pinned(&self, id: u64) -> RpcResult<Arc<DatabaseProviderRO<DB>>> {
+        self.inner.remove_expired();
+        let mut pinned = self.inner.pinned.lock();
+        let state = pinned.get_mut(&id).ok_or_else(|| {
+            ErrorObject::owned(
+                INVALID_PARAMS_CODE,
+                format!("unknown pinned state {id}"),
+                None::<()>,
+            )
+        })?;
+        state.expires_at = Instant::now() + self.ttl;
+        Ok(Arc::clone(&state.provider))
+    }
+}
+
+impl<DB: Database + 'static> TrieNodeApiServer for TrieNodeServer<DB> {
+    /// Handler for `trie_pinState`
+    fn pin_state(&self) -> RpcResult<PinnedState> {
+        self.inner.remove_expired();
+        let mut pinned = self.inner.pinned.lock();
+        if pinned.len() >= MAX_PINNED_STATES {
+            return Err(ErrorObject::owned(
+                INVALID_PARAMS_CODE,
+                format!("at most {MAX_PINNED_STATES} states can be pinned"),
+                None::<()>,
+            ))
+        }
+
+        let provider = self
+            .inner
+            .provider_factory
+            .provider()
+            .map_err(internal_rpc_err)?
+            .disable_long_read_transaction_safety();
+
+        // the hashed state and the trie are only consistent once the merkle stage caught up with
+        // the hashing stages, the tip of the chain may be ahead of both during the sync
+        let mut checkpoints =
+            [StageId::AccountHashing, StageId::StorageHashing, StageId::MerkleExecute]
+                .into_iter()
+                .map(|id| {
+                    Ok(provider
+                        .get_stage_checkpoint(id)?
+                        .map(|c| c.block_number)
+                        .unwrap_or_default())
+                })
+                .collect::<Result<Vec<_>, ProviderError>>()
+                .map_err(internal_rpc_err)?;
+        checkpoints.dedup();
+        let [block_number] = checkpoints[..] else {
+            return Err(ErrorObject::owned(
+                INTERNAL_ERROR_CODE,
+                "the trie is being updated, retry later",
+                None::<()>,
+            ))
+        };
+        let header = provider
+            .sealed_header(block_number)
+            .map_err(internal_rpc_err)?
+            .ok_or_else(|| internal_rpc_err(ProviderError::HeaderNotFound(block_number.into())))?;
+
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        pinned.insert(
+            id,
+            PinnedProvider { provider: Arc::new(provider), expires_at: Instant::now() + self.ttl },
+        );
+
+        Ok(PinnedState {
+            id,
+            block_number,
+            block_hash: header.hash(),
+            state_root: header.state_root,
+        })
+    }
+
+    /// Handler for `trie_unpinState`
+    fn unpin_state(&self, id: u64) -> RpcResult<bool> {
+        Ok(self.inner.pinned.lock().remove(&id).is_some())
+    }
+
+    /// Handler for `trie_accountNodes`
+    fn account_nodes(&self, id: u64, from: Bytes, limit: usize) -> RpcResult<Vec<TrieNodeEntry>> {
+        let provider = self.pinned(id)?;
+        let from = StoredNibbles::from(nibbles(from)?);
+        let mut cursor =
+            provider.tx_ref().cursor_read::<tables::AccountsTrie>().map_err(internal_rpc_err)?;
+        cursor
+            .walk(Some(from))
+            .map_err(internal_rpc_err)?
+            .take(range_limit(limit)?)
+            .map(|entry| {
+                let (path, node) = entry.map_err(internal_rpc_err)?;
+                Ok(TrieNodeEntry { path: path.0.to_vec().into(), node: node.0 })
+            })
+            .collect()
+    }
+
+    /// Handler for `trie_storageNodes`
+    fn storage_nodes(
+        &self,
+        id: u64,
+        hashed_address: B256,
+        from: Bytes,
+        limit: usize,
+    ) -> RpcResult<Vec<TrieNodeEntry>> {
+        let provider = self.pinned(id)?;
+        let from = StoredNibblesSubKey::from(nibbles(from)?);
+        let mut cursor = provider
+            .tx_ref()
+            .cursor_dup_read::<tables::StoragesTrie>()
+            .map_err(internal_rpc_err)?;
+        cursor
+            .walk_dup(Some(hashed_address), Some(from))
+            .map_err(internal_rpc_err)?
+            .take(range_limit(limit)?)
+            .map(|entry| {
+                let (_, entry) = entry.map_err(internal_rpc_err)?;
+                Ok(TrieNodeEntry { path: entry.nibbles.0.to_vec().into(), node: entry.node })
+            })
+            .collect()
+    }
+
+    /// Handler for `trie_hashedAccounts`
+    fn hashed_accounts(
+        &self,
+        id: u64,
+        from: B256,
+        limit: usize,
+    ) -> RpcResult<Vec<HashedAccountEntry>> {
+        let provider = self.pinned(id)?;
+        let mut cursor =
+            provider.tx_ref().cursor_read::<tables::HashedAccount>().map_err(internal_rpc_err)?;
+        cursor
+            .walk(Some(from))
+            .map_err(internal_rpc_err)?
+            .take(range_limit(limit)?)
+            .map(|entry| {
+                let (hashed_address, account) = entry.map_err(internal_rpc_err)?;
+                Ok(HashedAccountEntry { hashed_address, account })
+            })
+            .collect()
+    }
+
+    /// Handler for `trie_hashedStorages`
+    fn hashed_storages(
+        &self,
+        id: u64,
+        hashed_address: B256,
+        from: B256,
+        limit: usize,
+    ) -> RpcResult<Vec<StorageEntry>> {
+        let provider = self.pinned(id)?;
+        let mut cursor = provider
+            .tx_ref()
+            .cursor_dup_read::<tables::HashedStorage>()
+            .map_err(internal_rpc_err)?;
+        cursor
+            .walk_dup(Some(hashed_address), Some(from))
+            .map_err(internal_rpc_err)?
+            .take(range_limit(limit)?)
+            .map(|entry| Ok(entry.map_err(internal_rpc_err)?.1))
+            .collect()
+    }
+}
+
+impl<DB: Database> std::fmt::Debug for TrieNodeServer<DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrieNodeServer").finish_non_exhaustive()
+    }
+}
+
+struct TrieNodeServerInner<DB: Database> {
+    /// Factory for the read transactions of pinned states.
+    provider_factory: ProviderFactory<DB>,
+    /// The id of the next pinned state.
+    next_id: AtomicU64,
+    /// All pinned states by id.
+    pinned: Mutex<HashMap<u64, PinnedProvider<DB>>>,
+}
+
+impl<DB: Database> TrieNodeServerInner<DB> {
+    /// Unpins the states whose lease expired.
+    fn remove_expired(&self) {
+        let now = Instant::now();
+        self.pinned.lock().retain(|_, state| state.expires_at > now);
+    }
+}
+
+/// The read transaction of a pinned state.
+struct PinnedProvider<DB: Database> {
+    provider: Arc<DatabaseProviderRO<DB>>,
+    /// When the state is unpinned unless it is accessed again.
+    expires_at: Instant,
+}
+
+/// A [TrieNodeProvider] that fetches nodes from a remote [TrieNodeServer] at a pinned state.
+///
+/// The remote state is checked against the local state with
+/// [ensure_state_root](StateRootTrieNodeProvider::ensure_state_root) before the nodes are used.
+///
+/// The lookups block on the remote calls. They fail instead of blocking when called on a
+/// current-thread runtime.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct RemoteTrieNodeProvider {
+    client: jsonrpsee::async_client::Client,
+    state: parking_lot::RwLock<PinnedState>,
+    runtime: tokio::runtime::Handle,
+}
+
+#[cfg(unix)]
+impl RemoteTrieNodeProvider {
+    /// Connects to the trie node server at the given IPC path and pins its current state.
+    pub async fn connect(path: &str) -> eyre::Result<Self> {
+        let client = reth_ipc::client::IpcClientBuilder::default().build(path).await?;
+        let state = client.pin_state().await?;
+        Ok(Self {
+            client,
+            state: parking_lot::RwLock::new(state),
+            runtime: tokio::runtime::Handle::current(),
+        })
+    }
+
+    /// Returns the pinned remote state.
+    pub fn pinned_state(&self) -> PinnedState {
+        self.state.read().clone()
+    }
+
+    /// Unpins the remote state.
+    pub async fn disconnect(self) -> eyre::Result<()> {
+        self.client.unpin_state(self.state.into_inner().id).await?;
+        Ok(())
+    }
+
+    /// Runs the future to completion on the runtime the provider was connected on.
+    fn block_on<F: std::future::Future>(
+        &self,
+        future: F,
+    ) -> Result<F::Output, TrieNodeProviderError> {
+        match tokio::runtime::Handle::try_current().map(|handle| handle.runtime_flavor()) {
+            Ok(tokio::runtime::RuntimeFlavor::CurrentThread) => Err(TrieNodeProviderError::Remote(
+                "remote lookups can't block a current-thread runtime".to_string(),
+            )),
+            Ok(_) => Ok(tokio::task::block_in_place(|| self.runtime.block_on(future))),
+            Err(_) => Ok(self.runtime.block_on(future)),
+        }
+    }
+
+    /// Returns the first entry if it is under the given path.
+    fn first_under(
+        prefix: &Nibbles,
+        entries: Result<Vec<TrieNodeEntry>, jsonrpsee::core::Error>,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        let entries = entries.map_err(remote_err)?;
+        Ok(entries
+            .into_iter()
+            .next()
+            .map(|entry| (Nibbles::from_nibbles_unchecked(&entry.path[..]), entry.node))
+            .filter(|(path, _)| path.starts_with(prefix)))
+    }
+}
+
+#[cfg(unix)]
+impl TrieNodeProvider for RemoteTrieNodeProvider {
+    fn seek_account_node(
+        &self,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        let id = self.state.read().id;
+        let from = Bytes::copy_from_slice(prefix);
+        Self::first_under(prefix, self.block_on(self.client.account_nodes(id, from, 1))?)
+    }
+
+    fn seek_storage_node(
+        &self,
+        hashed_address: B256,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        let id = self.state.read().id;
+        let from = Bytes::copy_from_slice(prefix);
+        let request = self.client.storage_nodes(id, hashed_address, from, 1);
+        Self::first_under(prefix, self.block_on(request)?)
+    }
+}
+
+#[cfg(unix)]
+impl StateRootTrieNodeProvider for RemoteTrieNodeProvider {
+    fn ensure_state_root(&self, state_root: B256) -> Result<(), TrieNodeProviderError> {
+        if self.state.read().state_root == state_root {
+            return Ok(())
+        }
+
+        // the remote moved on since the state was pinned, or lagged behind, pin its current state
+        let state = self.block_on(self.client.pin_state())?.map_err(remote_err)?;
+        let got = state.state_root;
+        let previous = std::mem::replace(&mut *self.state.write(), state);
+        if let Err(err) = self.block_on(self.client.unpin_state(previous.id))? {
+            debug!(target: "reth::cli", id = previous.id, %err, "Failed to unpin remote trie state");
+        }
+
+        if got != state_root {
+            return Err(TrieNodeProviderError::StateRootMismatch { got, expected: state_root })
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn remote_err(err: jsonrpsee::core::Error) -> TrieNodeProviderError {
+    TrieNodeProviderError::Remote(err.to_string())
+}
+
+/// Validates a path of one nibble per byte.
+fn nibbles(path: Bytes) -> RpcResult<Vec<u8>> {
+    if path.iter().any(|nibble| *nibble > 0xf) {
+        return Err(ErrorObject::owned(
+            INVALID_PARAMS_CODE,
+            "path must contain one nibble per byte",
+            None::<()>,
+        ))
+    }
+    Ok(path.to_vec())
+}
+
+/// Validates the limit of a range request.
+fn range_limit(limit: usize) -> RpcResult<usize> {
+    if limit == 0 || limit > MAX_RANGE_LIMIT {
+        return Err(ErrorObject::owned(
+            INVALID_PARAMS_CODE,
+            format!("limit must be between 1 and {MAX_RANGE_LIMIT}"),
+            None::<()>,
+        ))
+    }
+    Ok(limit)
+}
+
+fn internal_rpc_err(err: impl ToString) -> ErrorObjectOwned {
+    ErrorObject::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::transaction::DbTxMut;
+    use reth_primitives::{stage::StageCheckpoint, trie::StoredBranchNode, Account, Header, U256};
+    use reth_provider::{test_utils::create_test_provider_factory, StageCheckpointWriter};
+
+    #[test]
+    fn validate_request_params() {
+        assert_eq!(nibbles(Bytes::from_static(&[0x1, 0xf])).unwrap(), vec![0x1, 0xf]);
+        assert!(nibbles(Bytes::from_static(&[0x10])).is_err());
+
+        assert!(range_limit(0).is_err());
+        assert!(range_limit(MAX_RANGE_LIMIT + 1).is_err());
+        assert_eq!(range_limit(MAX_RANGE_LIMIT).unwrap(), MAX_RANGE_LIMIT);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_pinned_state() {
+        let factory = create_test_provider_factory();
+        let header =
+            Header { number: 1, state_root: B256::repeat_byte(0x11), ..Default::default() }
+                .seal_slow();
+        let node = BranchNodeCompact::new(
+            0b11,
+            0,
+            0b11,
+            vec![B256::repeat_byte(1), B256::repeat_byte(2)],
+            None,
+        );
+        let account = Account { nonce: 1, balance: U256::from(2), bytecode_hash: None };
+        let hashed_address = B256::repeat_byte(0xaa);
+        {
+            let provider = factory.provider_rw().unwrap();
+            let tx = provider.tx_ref();
+            tx.put::<tables::CanonicalHeaders>(1, header.hash()).unwrap();
+            tx.put::<tables::Headers>(1, header.clone().unseal()).unwrap();
+            tx.put::<tables::AccountsTrie>(
+                StoredNibbles::from(vec![0x1]),
+                StoredBranchNode(node.clone()),
+            )
+            .unwrap();
+            tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+            for id in [StageId::AccountHashing, StageId::StorageHashing] {
+                provider.save_stage_checkpoint(id, StageCheckpoint::new(1)).unwrap();
+            }
+            provider.commit().unwrap();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trie.ipc").to_string_lossy().into_owned();
+        let ttl = Duration::from_millis(500);
+        let handle = TrieNodeServer::new(factory.clone())
+            .with_pinned_state_ttl(ttl)
+            .start_ipc(&path)
+            .await
+            .unwrap();
+        let client = reth_ipc::client::IpcClientBuilder::default().build(&path).await.unwrap();
+
+        // the merkle stage has not caught up with the hashing stages yet
+        assert!(client.pin_state().await.is_err());
+        let provider = factory.provider_rw().unwrap();
+        provider.save_stage_checkpoint(StageId::MerkleExecute, StageCheckpoint::new(1)).unwrap();
+        provider.commit().unwrap();
+
+        let remote = RemoteTrieNodeProvider::connect(&path).await.unwrap();
+        let state = remote.pinned_state();
+        assert_eq!(state.block_number, 1);
+        assert_eq!(state.block_hash, header.hash());
+        assert_eq!(state.state_root, header.state_root);
+
+        let (remote, found, missing) = tokio::task::spawn_blocking(move || {
+            let found = remote.account_node(&Nibbles::from_nibbles_unchecked([0x1])).unwrap();
+            let missing = remote.account_node(&Nibbles::from_nibbles_unchecked([0x2])).unwrap();
+            (remote, found, missing)
+        })
+        .await
+        .unwrap();
+        assert_eq!(found, Some(node.clone()));
+        assert_eq!(missing, None);
+
+        // lookups can also be made on the threads of the runtime
+        assert_eq!(
+            remote.seek_account_node(&Nibbles::default()).unwrap(),
+            Some((Nibbles::from_nibbles_unchecked([0x1]), node))
+        );
+
+        // the nodes of another state are refused, after trying to pin the current remote state
+        remote.ensure_state_root(header.state_root).unwrap();
+        assert!(matches!(
+            remote.ensure_state_root(B256::ZERO),
+            Err(TrieNodeProviderError::StateRootMismatch { got, expected })
+                if got == header.state_root && expected == B256::ZERO
+        ));
+        assert_ne!(remote.pinned_state().id, state.id);
+        assert!(client.hashed_accounts(state.id, B256::ZERO, 10).await.is_err());
+        let state = remote.pinned_state();
+
+        let accounts = client.hashed_accounts(state.id, B256::ZERO, 10).await.unwrap();
+        assert_eq!(accounts, vec![HashedAccountEntry { hashed_address, account }]);
+        remote.disconnect().await.unwrap();
+        assert!(client.hashed_accounts(state.id, B256::ZERO, 10).await.is_err());
+
+        // a state that is not accessed expires, while accessing a state extends its lease
+        let expiring = client.pin_state().await.unwrap();
+        let kept = client.pin_state().await.unwrap();
+        for _ in 0..6 {
+            tokio::time::sleep(ttl / 4).await;
+            client.account_nodes(kept.id, Bytes::new(), 1).await.unwrap();
+        }
+        assert!(client.account_nodes(expiring.id, Bytes::new(), 1).await.is_err());
+        assert!(client.account_nodes(kept.id, Bytes::new(), 1).await.is_ok());
+
+        handle.stop().unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+//! Ordering constraints on the transactions of built blocks.
+
+use reth_primitives::{TransactionSigned, TxHash, B256, U256};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+/// The maximum number of parent blocks the [ConstraintRegistry] keeps constraints for.
+const MAX_CONSTRAINED_PARENTS: usize = 64;
+
+/// Constraints on the transactions of a block, e.g. commitments made by the proposer.
+///
+/// Transactions are referenced by hash and taken from the transaction pool, so they have to be
+/// submitted to the pool separately.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockConstraints {
+    /// Transactions that must be included in the block, in any position.
+    pub must_include: Vec<TxHash>,
+    /// Transactions that must be the first transactions of the block, in this order.
+    ///
+    /// No other transaction may be placed before them.
+    pub top_of_block: Vec<TxHash>,
+    /// Groups of mutually exclusive bundles, at most one bundle of each group is included.
+    pub exclusive_bundles: Vec<ExclusiveBundles>,
+}
+
+/// A group of bundles of which at most one is included in the block.
+///
+/// Once a transaction of a bundle is included, the transactions of all other bundles of the group
+/// are excluded from the block. Bundles are not atomic, the builder still includes the
+/// transactions of the chosen bundle one by one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExclusiveBundles {
+    /// The transactions of each bundle.
+    pub bundles: Vec<Vec<TxHash>>,
+}
+
+/// Errors of invalid [BlockConstraints].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConstraintError {
+    /// A transaction is listed more than once at the top of the block.
+    #[error("transaction {0} is listed more than once at the top of the block")]
+    DuplicateTopOfBlock(TxHash),
+    /// A bundle has no transactions.
+    #[error("bundle without transactions")]
+    EmptyBundle,
+    /// A transaction is part of more than one bundle.
+    #[error("transaction {0} is part of more than one bundle")]
+    SharedBundleTransaction(TxHash),
+    /// Two required transactions are in mutually exclusive bundles.
+    #[error("required transactions {0} and {1} are in mutually exclusive bundles")]
+    ConflictingRequirements(TxHash, TxHash),
+}
+
+impl BlockConstraints {
+    /// Returns `true` if there are no constraints.
+    pub fn is_empty(&self) -> bool {
+        self.must_include.is_empty() &&
+            self.top_of_block.is_empty() &&
+            self.exclusive_bundles.is_empty()
+    }
+
+    /// Returns the transactions that must be included, the top of block transactions first.
+    pub fn required(&self) -> impl Iterator<Item = &TxHash> + '_ {
+        self.top_of_block
+            .iter()
+            .chain(self.must_include.iter().filter(|hash| !self.top_of_block.contains(hash)))
+    }
+
+    /// Checks that the constraints can be satisfied together.
+    pub fn validate(&self) -> Result<(), ConstraintError> {
+        let mut top_of_block = HashSet::with_capacity(self.top_of_block.len());
+        for hash in &self.top_of_block {
+            if !top_of_block.insert(*hash) {
+                return Err(ConstraintError::DuplicateTopOfBlock(*hash))
+            }
+        }
+
+        let mut bundled = HashSet::new();
+        for group in &self.exclusive_bundles {
+            // the required transaction that chose a bundle of the group, by bundle index
+            let mut chosen: Option<(usize, TxHash)> = None;
+            for (index, bundle) in group.bundles.iter().enumerate() {
+                if bundle.is_empty() {
+                    return Err(ConstraintError::EmptyBundle)
+                }
+                for hash in bundle {
+                    if !bundled.insert(*hash) {
+                        return Err(ConstraintError::SharedBundleTransaction(*hash))
+                    }
+                    if !top_of_block.contains(hash) && !self.must_include.contains(hash) {
+                        continue
+                    }
+                    match chosen {
+                        Some((chosen_index, other)) if chosen_index != index => {
+                            return Err(ConstraintError::ConflictingRequirements(other, *hash))
+                        }
+                        _ => chosen = Some((index, *hash)),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of constraints the given block transactions do not satisfy.
+    ///
+    /// Every missing required transaction counts once, and the top of block constraint counts
+    /// once more if the block does not start with the top of block transactions.
+    pub fn unsatisfied(&self, transactions: &[TransactionSigned]) -> usize {
+        let included = transactions.iter().map(|tx| tx.hash).collect::<HashSet<_>>();
+        let missing = self.required().filter(|hash| !included.contains(*hash)).count();
+        let top_of_block = transactions.len() >= self.top_of_block.len() &&
+            transactions.iter().zip(&self.top_of_block).all(|(tx, hash)| tx.hash == *hash);
+        missing + usize::from(!top_of_block)
+    }
+}
+
+/// Tracks which transactions may still be added to a block under [BlockConstraints].
+#[derive(Debug)]
+pub struct ConstraintTracker {
+    /// The groups and bundles of all bundled transactions.
+    bundles: HashMap<TxHash, (usize, usize)>,
+    /// The bundle chosen for each group.
+    chosen: HashMap<usize, usize>,
+    /// The transactions already in the block.
+    included: HashSet<TxHash>,
+}
+
+impl ConstraintTracker {
+    /// Creates a tracker for an empty block, with the bundles of the required transactions
+    /// already chosen.
+    pub fn new(constraints: &BlockConstraints) -> Self {
+        let bundles = constraints
+            .exclusive_bundles
+            .iter()
+            .enumerate()
+            .flat_map(|(group, bundles)| {
+                bundles.bundles.iter().enumerate().flat_map(move |(bundle, hashes)| {
+                    hashes.iter().map(move |hash| (*hash, (group, bundle)))
+                })
+            })
+            .collect();
+        let mut tracker = Self { bundles, chosen: HashMap::new(), included: HashSet::new() };
+        for hash in constraints.required() {
+            if let Some(&(group, bundle)) = tracker.bundles.get(hash) {
+                tracker.chosen.insert(group, bundle);
+            }
+        }
+        tracker
+    }
+
+    /// Returns `true` if the transaction is not in the block yet and not part of a bundle that
+    /// is excluded by a chosen bundle.
+    pub fn is_allowed(&self, hash: &TxHash) -> bool {
+        if self.included.contains(hash) {
+            return false
+        }
+        match self.bundles.get(hash) {
+            Some((group, bundle)) => self.chosen.get(group).map_or(true, |chosen| chosen == bundle),
+            None => true,
+        }
+    }
+
+    /// Records that the transaction was added to the block, which chooses its bundle.
+    pub fn include(&mut self, hash: TxHash) {
+        if let Some(&(group, bundle)) = self.bundles.get(&hash) {
+            self.chosen.entry(group).or_insert(bundle);
+        }
+        self.included.insert(hash);
+    }
+}
+
+/// Ranks a score by the number of unsatisfied constraints of the candidate.
+///
+/// Candidates that satisfy more constraints always score higher, the given score only decides
+/// between candidates with the same number of unsatisfied constraints. Scores are capped at 192
+/// bits.
+pub fn constrained_score(score: U256, unsatisfied: usize) -> U256 {
+    let rank = U256::from(u64::MAX.saturating_sub(unsatisfied as u64)) << 192;
+    rank | score.min(U256::MAX >> 64)
+}
+
+/// The constraints of the blocks to build, by parent block.
+///
+/// Constraints are submitted before or while the payload jobs on the parent run, each build of a
+/// job picks up the latest constraints. Constraints of the oldest parents are dropped once more
+/// than 64 parents are constrained.
+#[derive(Debug, Default)]
+pub struct ConstraintRegistry {
+    inner: Mutex<ConstraintRegistryInner>,
+}
+
+#[derive(Debug, Default)]
+struct ConstraintRegistryInner {
+    constraints: HashMap<B256, Arc<BlockConstraints>>,
+    /// The constrained parents in submission order.
+    parents: VecDeque<B256>,
+}
+
+impl ConstraintRegistry {
+    /// Validates the constraints and sets them for blocks built on the given parent, replacing
+    /// previously submitted constraints.
+    pub fn submit(
+        &self,
+        parent_hash: B256,
+        constraints: BlockConstraints,
+    ) -> Result<(), ConstraintError> {
+        constraints.validate()?;
+
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        if inner.constraints.insert(parent_hash, Arc::new(constraints)).is_none() {
+            inner.parents.push_back(parent_hash);
+        }
+        while inner.parents.len() > MAX_CONSTRAINED_PARENTS {
+            if let Some(oldest) = inner.parents.pop_front() {
+                inner.constraints.remove(&oldest);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the constraints for blocks built on the given parent.
+    pub fn get(&self, parent_hash: &B256) -> Option<Arc<BlockConstraints>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .constraints
+            .get(parent_hash)
+            .cloned()
+    }
+
+    /// Removes the constraints for blocks built on the given parent.
+    pub fn remove(&self, parent_hash: &B256) -> Option<Arc<BlockConstraints>> {
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        inner.parents.retain(|parent| parent != parent_hash);
+        inner.constraints.remove(parent_hash)
+    }
+}
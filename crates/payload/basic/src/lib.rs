@@ -7,7 +7,10 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
-use crate::metrics::PayloadBuilderMetrics;
+use crate::{
+    metrics::PayloadBuilderMetrics,
+    siblings::{SharedReads, SiblingJobs},
+};
 use alloy_rlp::Encodable;
 use futures_core::ready;
 use futures_util::FutureExt;
@@ -19,35 +22,67 @@ use reth_payload_builder::{
 };
 use reth_primitives::{
     bytes::BytesMut,
-    constants::{EMPTY_WITHDRAWALS, ETHEREUM_BLOCK_GAS_LIMIT, RETH_CLIENT_VERSION, SLOT_DURATION},
+    constants::{
+        BEACON_ROOTS_ADDRESS, EMPTY_WITHDRAWALS, ETHEREUM_BLOCK_GAS_LIMIT, RETH_CLIENT_VERSION,
+        SLOT_DURATION,
+    },
     proofs, BlockNumberOrTag, Bytes, ChainSpec, SealedBlock, Withdrawal, B256, U256,
 };
 use reth_provider::{
     BlockReaderIdExt, BlockSource, CanonStateNotification, ProviderError, StateProviderFactory,
 };
-use reth_revm::state_change::{
-    apply_beacon_root_contract_call, post_block_withdrawals_balance_increments,
+use reth_revm::{
+    database::StateProviderDatabase,
+    state_change::{
+        apply_beacon_root_contract_call, post_block_withdrawals_balance_increments,
+        transact_beacon_root_contract_call,
+    },
 };
 use reth_tasks::TaskSpawner;
 use reth_transaction_pool::TransactionPool;
 use revm::{
     primitives::{BlockEnv, CfgEnv, Env},
-    Database, DatabaseCommit, State,
+    Database, DatabaseCommit, DatabaseRef, State,
 };
 use std::{
     future::Future,
     pin::Pin,
     sync::{atomic::AtomicBool, Arc},
     task::{Context, Poll},
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
-    sync::{oneshot, Semaphore},
+    sync::{oneshot, watch, Semaphore},
     time::{Interval, Sleep},
 };
 use tracing::{debug, trace, warn};
 
+/// The length of the ring buffer of the beacon roots contract, see EIP-4788.
+const BEACON_ROOTS_HISTORY_BUFFER_LENGTH: u64 = 8191;
+
+mod constraints;
 mod metrics;
+mod ordering;
+mod policy;
+mod proposers;
+mod scorer;
+mod siblings;
+mod system_calls;
+mod top_of_block;
+
+pub use constraints::{
+    constrained_score, BlockConstraints, ConstraintError, ConstraintRegistry, ConstraintTracker,
+    ExclusiveBundles,
+};
+pub use ordering::{order_by_profit_per_gas, OrderingAlgorithm, SimulatedTransaction};
+pub use policy::{
+    next_gas_limit, CoinbasePayment, PayloadJobPolicy, PayloadPolicyProvider, PolicyError,
+    COINBASE_PAYMENT_GAS,
+};
+pub use proposers::{ProposerPreferences, ProposerRegistry};
+pub use scorer::{BlockCandidate, BlockScorer, FeeScorer};
+pub use system_calls::SystemCallCache;
+pub use top_of_block::{BidValidation, TopOfBlockCache, ValidatedSegment};
 
 /// The [`PayloadJobGenerator`] that creates [`BasicPayloadJob`]s.
 #[derive(Debug)]
@@ -70,6 +105,12 @@ pub struct BasicPayloadJobGenerator<Client, Pool, Tasks, Builder> {
     builder: Builder,
     /// Stored cached_reads for new payload jobs.
     pre_cached: Option<PrecachedState>,
+    /// Resolves the policies of new payload jobs.
+    policies: Option<Arc<dyn PayloadPolicyProvider>>,
+    /// The state shared by jobs on the same parent, if enabled.
+    siblings: Option<SiblingJobs>,
+    /// Updates of the configured deadline at runtime, the latest value applies to new jobs.
+    deadline_updates: Option<watch::Receiver<Duration>>,
 }
 
 // === impl BasicPayloadJobGenerator ===
@@ -84,6 +125,7 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
         chain_spec: Arc<ChainSpec>,
         builder: Builder,
     ) -> Self {
+        let siblings = config.share_sibling_state.then(SiblingJobs::default);
         Self {
             client,
             pool,
@@ -93,9 +135,31 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
             chain_spec,
             builder,
             pre_cached: None,
+            policies: None,
+            siblings,
+            deadline_updates: None,
         }
     }
 
+    /// Sets the provider of the extra data, fee recipient and coinbase payment policies of new
+    /// payload jobs.
+    pub fn with_policies(mut self, policies: Arc<dyn PayloadPolicyProvider>) -> Self {
+        self.policies = Some(policies);
+        self
+    }
+
+    /// Uses the latest deadline of the given receiver for new jobs instead of the configured one,
+    /// so the deadline can be changed at runtime.
+    pub fn with_deadline_updates(mut self, deadline: watch::Receiver<Duration>) -> Self {
+        self.deadline_updates = Some(deadline);
+        self
+    }
+
+    /// Returns the current deadline for when new jobs should resolve.
+    fn deadline(&self) -> Duration {
+        self.deadline_updates.as_ref().map_or(self.config.deadline, |updates| *updates.borrow())
+    }
+
     /// Returns the maximum duration a job should be allowed to run.
     ///
     /// This adheres to the following specification:
@@ -108,9 +172,10 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
         let duration_until_timestamp = duration_until(unix_timestamp);
 
         // safety in case clocks are bad
-        let duration_until_timestamp = duration_until_timestamp.min(self.config.deadline * 3);
+        let deadline = self.deadline();
+        let duration_until_timestamp = duration_until_timestamp.min(deadline * 3);
 
-        self.config.deadline + duration_until_timestamp
+        deadline + duration_until_timestamp
     }
 
     /// Returns the [Instant](tokio::time::Instant) at which the job should be terminated because it
@@ -171,17 +236,63 @@ where
             block.seal(attributes.parent())
         };
 
-        let config = PayloadConfig::new(
+        let mut config = PayloadConfig::new(
             Arc::new(parent_block),
             self.config.extradata.clone(),
             attributes,
             Arc::clone(&self.chain_spec),
         );
 
+        if let Some(policy) = self.policies.as_ref().and_then(|policies| {
+            policies.policy(
+                config.payload_id(),
+                config.parent_block.hash(),
+                config.attributes.suggested_fee_recipient(),
+            )
+        }) {
+            config = match config.clone().with_policy(policy) {
+                Ok(config) => config,
+                Err(error) => {
+                    warn!(target: "payload_builder", id=%config.payload_id(), %error, "ignoring invalid payload job policy");
+                    config
+                }
+            };
+        }
+
+        let shared_reads = self.siblings.as_ref().map(|siblings| {
+            let (reads, system_calls) = siblings.join(
+                config.parent_block.hash(),
+                config.attributes.timestamp(),
+                config.attributes.parent_beacon_block_root(),
+            );
+            config.system_calls = system_calls;
+            reads
+        });
+
         let until = self.job_deadline(config.attributes.timestamp());
         let deadline = Box::pin(tokio::time::sleep_until(until));
 
-        let cached_reads = self.maybe_pre_cached(config.parent_block.hash());
+        let mut cached_reads = self.maybe_pre_cached(config.parent_block.hash());
+
+        // load the state every build touches while the job waits for its first build
+        let preload = self.config.preload_state.then(|| {
+            let (tx, rx) = oneshot::channel();
+            let client = self.client.clone();
+            let config = config.clone();
+            let mut cached = cached_reads.take().unwrap_or_default();
+            let metrics = PayloadBuilderMetrics::default();
+            self.executor.spawn_blocking(Box::pin(async move {
+                let started_at = Instant::now();
+                match preload_payload_state(&client, &config, &mut cached) {
+                    Ok(()) => metrics.preload_duration.record(started_at.elapsed()),
+                    Err(error) => {
+                        debug!(target: "payload_builder", %error, "failed to preload payload state")
+                    }
+                }
+                let _ = tx.send(cached);
+            }));
+            rx
+        });
 
         Ok(BasicPayloadJob {
             config,
@@ -191,8 +302,12 @@ where
             deadline,
             interval: tokio::time::interval(self.config.interval),
             best_payload: None,
+            finalized_payload: None,
+            finalizing: None,
             pending_block: None,
+            preload,
             cached_reads,
+            shared_reads,
             payload_task_guard: self.payload_task_guard.clone(),
             metrics: Default::default(),
             builder: self.builder.clone(),
@@ -258,6 +373,10 @@ pub struct BasicPayloadJobGeneratorConfig {
     deadline: Duration,
     /// Maximum number of tasks to spawn for building a payload.
     max_payload_tasks: usize,
+    /// Whether to preload the state every build of a new job touches before its first build.
+    preload_state: bool,
+    /// Whether jobs on the same parent share the state they read.
+    share_sibling_state: bool,
 }
 
 // === impl BasicPayloadJobGeneratorConfig ===
@@ -301,6 +420,24 @@ impl BasicPayloadJobGeneratorConfig {
         self.max_gas_limit = max_gas_limit;
         self
     }
+
+    /// Sets whether new jobs preload the state that every build touches, i.e. the fee recipient,
+    /// the withdrawal addresses and the beacon roots contract, before their first build.
+    ///
+    /// Enabled by default.
+    pub fn preload_state(mut self, preload_state: bool) -> Self {
+        self.preload_state = preload_state;
+        self
+    }
+
+    /// Sets whether jobs that build on the same parent, e.g. for different proposers, share the
+    /// state their builds read and the state changes of the pre-block system calls.
+    ///
+    /// Disabled by default.
+    pub fn share_sibling_state(mut self, share_sibling_state: bool) -> Self {
+        self.share_sibling_state = share_sibling_state;
+        self
+    }
 }
 
 impl Default for BasicPayloadJobGeneratorConfig {
@@ -314,6 +451,8 @@ impl Default for BasicPayloadJobGeneratorConfig {
             // 12s slot time
             deadline: SLOT_DURATION,
             max_payload_tasks: 3,
+            preload_state: true,
+            share_sibling_state: false,
         }
     }
 }
@@ -338,8 +477,14 @@ where
     interval: Interval,
     /// The best payload so far.
     best_payload: Option<Builder::BuiltPayload>,
+    /// The latest best payload that was finalized with [PayloadBuilder::finalize_payload].
+    finalized_payload: Option<Builder::BuiltPayload>,
+    /// Receiver for the best payload that is currently being finalized.
+    finalizing: Option<oneshot::Receiver<Result<Builder::BuiltPayload, PayloadBuilderError>>>,
     /// Receiver for the block that is currently being built.
     pending_block: Option<PendingPayload<Builder::BuiltPayload>>,
+    /// Receiver for the cached reads that are preloaded before the first build.
+    preload: Option<oneshot::Receiver<CachedReads>>,
     /// Restricts how many generator tasks can be executed at once.
     payload_task_guard: PayloadTaskGuard,
     /// Caches all disk reads for the state the new payloads builds on
@@ -347,6 +492,8 @@ where
     /// This is used to avoid reading the same state over and over again when new attempts are
     /// triggered, because during the building process we'll repeatedly execute the transactions.
     cached_reads: Option<CachedReads>,
+    /// The state read by the jobs on the same parent, if shared.
+    shared_reads: Option<SharedReads>,
     /// metrics for this type
     metrics: PayloadBuilderMetrics,
     /// The type responsible for building payloads.
@@ -375,6 +522,17 @@ where
             return Poll::Ready(Ok(()))
         }
 
+        // wait for the preloaded state before the first build
+        if let Some(mut preload) = this.preload.take() {
+            match preload.poll_unpin(cx) {
+                Poll::Ready(cached_reads) => this.cached_reads = cached_reads.ok(),
+                Poll::Pending => {
+                    this.preload = Some(preload);
+                    return Poll::Pending
+                }
+            }
+        }
+
         // check if the interval is reached
         while this.interval.poll_tick(cx).is_ready() {
             // start a new job if there is no pending block and we haven't reached the deadline
@@ -389,11 +547,15 @@ where
                 let payload_config = this.config.clone();
                 let best_payload = this.best_payload.clone();
                 this.metrics.inc_initiated_payload_builds();
-                let cached_reads = this.cached_reads.take().unwrap_or_default();
+                let mut cached_reads = this.cached_reads.take().unwrap_or_default();
+                let shared_reads = this.shared_reads.clone();
                 let builder = this.builder.clone();
                 this.executor.spawn_blocking(Box::pin(async move {
                     // acquire the permit for executing the task
                     let _permit = guard.0.acquire().await;
+                    if let Some(shared_reads) = &shared_reads {
+                        shared_reads.pull_into(&mut cached_reads);
+                    }
                     let args = BuildArguments {
                         client,
                         pool,
@@ -403,6 +565,16 @@ where
                         best_payload,
                     };
                     let result = builder.try_build(args);
+                    if let (
+                        Some(shared_reads),
+                        Ok(
+                            BuildOutcome::Better { cached_reads, .. } |
+                            BuildOutcome::Aborted { cached_reads, .. },
+                        ),
+                    ) = (&shared_reads, &result)
+                    {
+                        shared_reads.publish(cached_reads);
+                    }
                     let _ = tx.send(result);
                 }));
 
@@ -419,8 +591,7 @@ where
                         BuildOutcome::Better { payload, cached_reads } => {
                             this.cached_reads = Some(cached_reads);
                             debug!(target: "payload_builder", value = %payload.fees(), "built better payload");
-                            let payload = payload;
-                            this.best_payload = Some(payload);
+                            this.on_better_payload(payload);
                         }
                         BuildOutcome::Aborted { fees, cached_reads } => {
                             this.cached_reads = Some(cached_reads);
@@ -442,10 +613,58 @@ where
             }
         }
 
+        // poll the finalizing best payload
+        if let Some(mut finalizing) = this.finalizing.take() {
+            match finalizing.poll_unpin(cx) {
+                Poll::Ready(Ok(Ok(payload))) => {
+                    // replaced payloads drop their receiver, so this is the current best payload
+                    this.best_payload = Some(payload.clone());
+                    this.finalized_payload = Some(payload);
+                }
+                Poll::Ready(Ok(Err(error))) => {
+                    debug!(target: "payload_builder", ?error, "failed to finalize best payload");
+                }
+                Poll::Ready(Err(_)) => {}
+                Poll::Pending => this.finalizing = Some(finalizing),
+            }
+        }
+
         Poll::Pending
     }
 }
 
+impl<Client, Pool, Tasks, Builder> BasicPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    /// Stores a better payload and finalizes it on a blocking task, unless it has a state root
+    /// already.
+    ///
+    /// This way [PayloadJob::best_payload] can hand out the finalized payload without computing
+    /// anything.
+    fn on_better_payload(&mut self, payload: Builder::BuiltPayload) {
+        self.best_payload = Some(payload.clone());
+        if !payload.block().state_root.is_zero() {
+            self.finalizing = None;
+            self.finalized_payload = Some(payload);
+            return
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let builder = self.builder.clone();
+        let client = self.client.clone();
+        self.executor.spawn_blocking(Box::pin(async move {
+            let _ = tx.send(builder.finalize_payload(&client, payload));
+        }));
+        self.finalizing = Some(rx);
+    }
+}
+
 impl<Client, Pool, Tasks, Builder> PayloadJob for BasicPayloadJob<Client, Pool, Tasks, Builder>
 where
     Client: StateProviderFactory + Clone + Unpin + 'static,
@@ -460,11 +679,12 @@ where
     type BuiltPayload = Builder::BuiltPayload;
 
     fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
-        if let Some(ref payload) = self.best_payload {
+        // the best payload is finalized in the background, until then the previous one is returned
+        if let Some(ref payload) = self.finalized_payload {
             return Ok(payload.clone())
         }
-        // No payload has been built yet, but we need to return something that the CL then can
-        // deliver, so we need to return an empty payload.
+        // No payload has been built and finalized yet, but we need to return something that the CL
+        // then can deliver, so we need to return an empty payload.
         //
         // Note: it is assumed that this is unlikely to happen, as the payload job is started right
         // away and the first full block should have been built by the time CL is requesting the
@@ -473,6 +693,10 @@ where
         Builder::build_empty_payload(&self.client, self.config.clone())
     }
 
+    fn built_payload(&self) -> Option<Self::BuiltPayload> {
+        self.best_payload.clone()
+    }
+
     fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
         Ok(self.config.attributes.clone())
     }
@@ -481,6 +705,15 @@ where
         let best_payload = self.best_payload.take();
         let maybe_better = self.pending_block.take();
         let mut empty_payload = None;
+        let finalize = {
+            let builder = self.builder.clone();
+            let client = self.client.clone();
+            FinalizePayload {
+                finalize: Some(Box::new(move |payload| builder.finalize_payload(&client, payload))),
+                executor: Box::new(self.executor.clone()),
+                pending: None,
+            }
+        };
 
         if best_payload.is_none() {
             debug!(target: "payload_builder", id=%self.config.payload_id(), "no best payload yet to resolve, building empty payload");
@@ -500,7 +733,12 @@ where
             if let Some(payload) = self.builder.on_missing_payload(args) {
                 debug!(target: "payload_builder", id=%self.config.payload_id(), "resolving fallback payload as best payload");
                 return (
-                    ResolveBestPayload { best_payload: Some(payload), maybe_better, empty_payload },
+                    ResolveBestPayload {
+                        best_payload: Some(payload),
+                        maybe_better,
+                        empty_payload,
+                        finalize,
+                    },
                     KeepPayloadJobAlive::Yes,
                 )
             }
@@ -519,7 +757,7 @@ where
             empty_payload = Some(rx);
         }
 
-        let fut = ResolveBestPayload { best_payload, maybe_better, empty_payload };
+        let fut = ResolveBestPayload { best_payload, maybe_better, empty_payload, finalize };
 
         (fut, KeepPayloadJobAlive::No)
     }
@@ -534,6 +772,8 @@ where
 ///
 /// If no payload has been built so far, it will either return an empty payload or the result of the
 /// in progress build job, whatever finishes first.
+///
+/// The resolved payload is finalized with [PayloadBuilder::finalize_payload] before it is returned.
 #[derive(Debug)]
 pub struct ResolveBestPayload<Payload> {
     /// Best payload so far.
@@ -542,31 +782,39 @@ pub struct ResolveBestPayload<Payload> {
     maybe_better: Option<PendingPayload<Payload>>,
     /// The empty payload building job in progress.
     empty_payload: Option<oneshot::Receiver<Result<Payload, PayloadBuilderError>>>,
+    /// Finalizes the resolved payload.
+    finalize: FinalizePayload<Payload>,
 }
 
 impl<Payload> Future for ResolveBestPayload<Payload>
 where
-    Payload: Unpin,
+    Payload: Send + Unpin + 'static,
 {
     type Output = Result<Payload, PayloadBuilderError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
+        if this.finalize.is_pending() {
+            return this.finalize.poll_unpin(cx)
+        }
+
         // check if there is a better payload before returning the best payload
         if let Some(fut) = Pin::new(&mut this.maybe_better).as_pin_mut() {
             if let Poll::Ready(res) = fut.poll(cx) {
                 this.maybe_better = None;
                 if let Ok(BuildOutcome::Better { payload, .. }) = res {
                     debug!(target: "payload_builder", "resolving better payload");
-                    return Poll::Ready(Ok(payload))
+                    this.finalize.start(payload);
+                    return this.finalize.poll_unpin(cx)
                 }
             }
         }
 
         if let Some(best) = this.best_payload.take() {
             debug!(target: "payload_builder", "resolving best payload");
-            return Poll::Ready(Ok(best))
+            this.finalize.start(best);
+            return this.finalize.poll_unpin(cx)
         }
 
         let mut empty_payload = this.empty_payload.take().expect("polled after completion");
@@ -588,6 +836,50 @@ where
     }
 }
 
+/// Finalizes a resolved payload on a blocking task.
+struct FinalizePayload<P> {
+    /// The finalizer, until the payload is resolved.
+    finalize: Option<Box<dyn FnOnce(P) -> Result<P, PayloadBuilderError> + Send + Sync>>,
+    /// How to spawn the finalizing task.
+    executor: Box<dyn TaskSpawner>,
+    /// The finalizing task in progress.
+    pending: Option<oneshot::Receiver<Result<P, PayloadBuilderError>>>,
+}
+
+impl<P: Send + 'static> FinalizePayload<P> {
+    /// Returns `true` if the payload is being finalized.
+    fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Spawns the task that finalizes the payload.
+    fn start(&mut self, payload: P) {
+        let (tx, rx) = oneshot::channel();
+        let finalize = self.finalize.take().expect("payload finalized twice");
+        self.executor.spawn_blocking(Box::pin(async move {
+            let _ = tx.send(finalize(payload));
+        }));
+        self.pending = Some(rx);
+    }
+}
+
+impl<P> Future for FinalizePayload<P> {
+    type Output = Result<P, PayloadBuilderError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pending = self.pending.as_mut().expect("polled before payload was resolved");
+        let res = ready!(pending.poll_unpin(cx));
+        self.pending = None;
+        Poll::Ready(res.map_err(Into::into).and_then(|res| res))
+    }
+}
+
+impl<P> std::fmt::Debug for FinalizePayload<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FinalizePayload").field("pending", &self.pending.is_some()).finish()
+    }
+}
+
 /// A future that resolves to the result of the block building job.
 #[derive(Debug)]
 struct PendingPayload<P> {
@@ -642,6 +934,12 @@ pub struct PayloadConfig<Attributes> {
     pub attributes: Attributes,
     /// The chain spec.
     pub chain_spec: Arc<ChainSpec>,
+    /// The state changes of the system calls, shared by all builds of the payload.
+    pub system_calls: Arc<SystemCallCache>,
+    /// The validated bids for the top of the block, shared by all builds of the payload.
+    pub top_of_block: Arc<TopOfBlockCache>,
+    /// The policy applied to the payload, if any.
+    pub policy: Option<Arc<PayloadJobPolicy>>,
 }
 
 impl<Attributes> PayloadConfig<Attributes> {
@@ -673,6 +971,9 @@ where
             extra_data,
             attributes,
             chain_spec,
+            system_calls: Arc::default(),
+            top_of_block: Arc::default(),
+            policy: None,
         }
     }
 
@@ -680,6 +981,25 @@ where
     pub fn payload_id(&self) -> PayloadId {
         self.attributes.payload_id()
     }
+
+    /// Validates the policy and applies its extra data, fee recipient and gas limit overrides.
+    ///
+    /// The coinbase payment is up to the payload builder.
+    pub fn with_policy(mut self, policy: PayloadJobPolicy) -> Result<Self, PolicyError> {
+        policy.validate(self.attributes.suggested_fee_recipient())?;
+        if let Some(extra_data) = &policy.extra_data {
+            self.extra_data = extra_data.clone();
+        }
+        if let Some(fee_recipient) = policy.fee_recipient {
+            self.initialized_block_env.coinbase = fee_recipient;
+        }
+        if let Some(target) = policy.gas_limit {
+            self.initialized_block_env.gas_limit =
+                U256::from(next_gas_limit(self.parent_block.gas_limit, target));
+        }
+        self.policy = Some(Arc::new(policy));
+        Ok(self)
+    }
 }
 
 /// The possible outcomes of a payload building attempt.
@@ -782,6 +1102,19 @@ pub trait PayloadBuilder<Pool, Client>: Send + Sync + Clone {
         None
     }
 
+    /// Invoked with the payload the job resolves to, before it is handed out.
+    ///
+    /// Builders that skip expensive work for candidates that may never be picked, like computing
+    /// the state root, complete the payload here.
+    fn finalize_payload(
+        &self,
+        client: &Client,
+        payload: Self::BuiltPayload,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        let _client = client;
+        Ok(payload)
+    }
+
     /// Builds an empty payload without any transaction.
     fn build_empty_payload(
         client: &Client,
@@ -789,6 +1122,44 @@ pub trait PayloadBuilder<Pool, Client>: Send + Sync + Clone {
     ) -> Result<Self::BuiltPayload, PayloadBuilderError>;
 }
 
+/// Loads the state that every build of the payload touches into the cache: the fee recipient, the
+/// withdrawal addresses and the slots of the beacon roots contract written by the pre-block call.
+fn preload_payload_state<Client, Attributes>(
+    client: &Client,
+    config: &PayloadConfig<Attributes>,
+    cached_reads: &mut CachedReads,
+) -> Result<(), ProviderError>
+where
+    Client: StateProviderFactory,
+    Attributes: PayloadBuilderAttributes,
+{
+    let PayloadConfig { parent_block, attributes, chain_spec, .. } = config;
+    let state = client.state_by_block_hash(parent_block.hash())?;
+    let db = cached_reads.as_db(StateProviderDatabase::new(&state));
+    let timestamp = attributes.timestamp();
+
+    db.basic_ref(config.initialized_block_env.coinbase)?;
+    if chain_spec.is_shanghai_active_at_timestamp(timestamp) {
+        for withdrawal in attributes.withdrawals() {
+            db.basic_ref(withdrawal.address)?;
+        }
+    }
+    if chain_spec.is_cancun_active_at_timestamp(timestamp) &&
+        attributes.parent_beacon_block_root().is_some()
+    {
+        if let Some(account) = db.basic_ref(BEACON_ROOTS_ADDRESS)? {
+            db.code_by_hash_ref(account.code_hash)?;
+            let timestamp_index = timestamp % BEACON_ROOTS_HISTORY_BUFFER_LENGTH;
+            db.storage_ref(BEACON_ROOTS_ADDRESS, U256::from(timestamp_index))?;
+            db.storage_ref(
+                BEACON_ROOTS_ADDRESS,
+                U256::from(timestamp_index + BEACON_ROOTS_HISTORY_BUFFER_LENGTH),
+            )?;
+        }
+    }
+    Ok(())
+}
+
 /// Represents the outcome of committing withdrawals to the runtime database and post state.
 /// Pre-shanghai these are `None` values.
 #[derive(Default, Debug)]
@@ -888,6 +1259,50 @@ where
     .map_err(|err| PayloadBuilderError::Internal(err.into()))
 }
 
+/// Apply the [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788) pre block contract call like
+/// [pre_block_beacon_root_contract_call], but only execute it for the first build of the payload
+/// and commit the state changes cached in the [SystemCallCache] of the config for later builds.
+pub fn cached_pre_block_beacon_root_contract_call<DB, Attributes>(
+    db: &mut DB,
+    config: &PayloadConfig<Attributes>,
+) -> Result<(), PayloadBuilderError>
+where
+    DB: Database<Error = ProviderError> + DatabaseCommit,
+    Attributes: PayloadBuilderAttributes,
+{
+    let state = match config.system_calls.beacon_root() {
+        Some(state) => state.clone(),
+        None => {
+            let env = Env {
+                cfg: config.initialized_cfg.clone(),
+                block: config.initialized_block_env.clone(),
+                ..Default::default()
+            };
+            let mut evm_pre_block = revm::EVM::with_env(env);
+            evm_pre_block.database(&mut *db);
+            let state = transact_beacon_root_contract_call(
+                &config.chain_spec,
+                config.attributes.timestamp(),
+                config.initialized_block_env.number.to::<u64>(),
+                config.attributes.parent_beacon_block_root(),
+                &mut evm_pre_block,
+            )
+            .map_err(|err| PayloadBuilderError::Internal(err.into()))?;
+            config.system_calls.set_beacon_root(state.clone());
+            state
+        }
+    };
+
+    if let Some(state) = state {
+        // changed accounts have to be loaded before their changes can be committed
+        for address in state.keys() {
+            db.basic(*address)?;
+        }
+        db.commit(state);
+    }
+    Ok(())
+}
+
 /// Checks if the new payload is better than the current best.
 ///
 /// This compares the total fees of the blocks, higher is better.
@@ -900,6 +1315,18 @@ pub fn is_better_payload(best_payload: Option<&EthBuiltPayload>, new_fees: U256)
     }
 }
 
+/// Checks if a new payload with the given [BlockScorer] score is better than the current best.
+///
+/// This compares the scores of the blocks, higher is better.
+#[inline(always)]
+pub fn is_better_scored_payload(best_payload: Option<&EthBuiltPayload>, new_score: U256) -> bool {
+    if let Some(best_payload) = best_payload {
+        new_score > best_payload.score()
+    } else {
+        true
+    }
+}
+
 /// Returns the duration until the given unix timestamp in seconds.
 ///
 /// Returns `Duration::ZERO` if the given timestamp is in the past.
@@ -1,6 +1,9 @@
 //! Metrics for the payload builder impl
 
-use reth_metrics::{metrics::Counter, Metrics};
+use reth_metrics::{
+    metrics::{Counter, Histogram},
+    Metrics,
+};
 
 /// Transaction pool metrics
 #[derive(Metrics)]
@@ -12,6 +15,8 @@ pub(crate) struct PayloadBuilderMetrics {
     pub(crate) initiated_payload_builds: Counter,
     /// Total number of failed payload build attempts
     pub(crate) failed_payload_builds: Counter,
+    /// Time it took to preload the state of a new payload job
+    pub(crate) preload_duration: Histogram,
 }
 
 impl PayloadBuilderMetrics {
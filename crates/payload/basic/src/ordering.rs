@@ -0,0 +1,85 @@
+//! Ordering of the transactions of built blocks.
+
+use reth_primitives::{TxHash, U256};
+use reth_revm::conflict_graph::{ConflictGraph, ReadWriteSet};
+use std::{cmp::Ordering, time::Duration};
+
+/// How the payload builder orders the transactions of the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderingAlgorithm {
+    /// The order of the best transactions of the pool, by effective tip.
+    #[default]
+    Pool,
+    /// The best transactions of the pool are simulated on the parent state for up to
+    /// `time_budget` per build, and ordered with [order_by_profit_per_gas]. Transactions that
+    /// were not simulated in time follow in the order of the pool.
+    ConflictAware {
+        /// The maximum time spent simulating transactions per build.
+        time_budget: Duration,
+    },
+}
+
+/// A transaction simulated on the parent state of a payload, before it is ordered.
+#[derive(Debug, Clone)]
+pub struct SimulatedTransaction<T> {
+    /// The simulated transaction.
+    pub transaction: T,
+    /// The hash of the transaction.
+    pub hash: TxHash,
+    /// The gas used by the transaction.
+    pub gas_used: u64,
+    /// The increase of the fee recipient's balance caused by the transaction, including direct
+    /// transfers.
+    pub profit: U256,
+    /// The state locations the transaction accessed, without the fee recipient.
+    pub read_write_set: ReadWriteSet,
+}
+
+/// Orders simulated transactions by merging groups of non-conflicting transactions, the most
+/// profitable groups per gas first.
+///
+/// The transactions are grouped with [ConflictGraph::independent_groups] of their read/write
+/// sets. Transactions of different groups don't access the same state, so the groups can be
+/// merged in any order without changing the results of their transactions, and the transactions
+/// of a group keep the order they were simulated in, e.g. by nonce. Groups with the same profit
+/// per gas keep their simulation order.
+///
+/// Builders skip transactions that don't fit into the block anymore, so once the block is full,
+/// the groups with the highest profit per gas are the ones that were included.
+pub fn order_by_profit_per_gas<T>(simulated: Vec<SimulatedTransaction<T>>) -> Vec<T> {
+    let graph = ConflictGraph::build(
+        simulated.iter().map(|simulated| (simulated.hash, simulated.read_write_set.clone())),
+    );
+
+    let mut groups = graph
+        .independent_groups()
+        .into_iter()
+        .map(|group| {
+            let gas_used = group.iter().map(|index| simulated[*index].gas_used).sum::<u64>();
+            let profit = group
+                .iter()
+                .fold(U256::ZERO, |profit, index| profit.saturating_add(simulated[*index].profit));
+            (group, gas_used, profit)
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by(|(_, gas_a, profit_a), (_, gas_b, profit_b)| {
+        compare_profit_per_gas((*profit_b, *gas_b), (*profit_a, *gas_a))
+    });
+
+    let mut transactions =
+        simulated.into_iter().map(|simulated| Some(simulated.transaction)).collect::<Vec<_>>();
+    groups
+        .into_iter()
+        .flat_map(|(group, _, _)| group)
+        .filter_map(|index| transactions[index].take())
+        .collect()
+}
+
+/// Compares the profit per gas of two groups of transactions.
+fn compare_profit_per_gas(
+    (profit_a, gas_a): (U256, u64),
+    (profit_b, gas_b): (U256, u64),
+) -> Ordering {
+    // profit_a / gas_a <=> profit_b / gas_b, without rounding
+    profit_a.saturating_mul(U256::from(gas_b)).cmp(&profit_b.saturating_mul(U256::from(gas_a)))
+}
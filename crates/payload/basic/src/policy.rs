@@ -0,0 +1,195 @@
+//! Per job policies for the extra data, fee recipient and coinbase payment of built payloads.
+
+use reth_payload_builder::PayloadId;
+use reth_primitives::{
+    constants::{MAXIMUM_EXTRA_DATA_SIZE, MINIMUM_GAS_LIMIT},
+    keccak256, sign_message, Address, Bytes, ChainId, Transaction, TransactionKind,
+    TransactionSigned, TransactionSignedEcRecovered, TxEip1559, B256, U256,
+};
+use std::fmt;
+
+/// The gas limit of the coinbase payment transaction, a plain value transfer.
+pub const COINBASE_PAYMENT_GAS: u64 = 21_000;
+
+/// Overrides of how the payloads of a job are built.
+///
+/// Policies are resolved by a [PayloadPolicyProvider] when the job is created and validated
+/// against the payload attributes, see
+/// [PayloadConfig::with_policy](crate::PayloadConfig::with_policy).
+#[derive(Debug, Clone, Default)]
+pub struct PayloadJobPolicy {
+    /// The extra data of the payloads, instead of the extra data of the generator config.
+    pub extra_data: Option<Bytes>,
+    /// The fee recipient of the payloads, instead of the suggested fee recipient of the
+    /// attributes.
+    pub fee_recipient: Option<Address>,
+    /// The payment from the fee recipient to the suggested fee recipient at the end of every
+    /// payload.
+    pub coinbase_payment: Option<CoinbasePayment>,
+    /// The gas limit the payloads move towards, instead of keeping the gas limit of the parent.
+    ///
+    /// The gas limit of a payload can only differ from its parent's by less than 1/1024, see
+    /// [next_gas_limit].
+    pub gas_limit: Option<u64>,
+}
+
+impl PayloadJobPolicy {
+    /// Checks that payloads built with the policy can satisfy the chain rules.
+    ///
+    /// A coinbase payment requires the fee recipient to be overridden with the address of the
+    /// payment key, since the payment is sent from the fee recipient.
+    pub fn validate(&self, suggested_fee_recipient: Address) -> Result<(), PolicyError> {
+        if let Some(extra_data) = &self.extra_data {
+            if extra_data.len() > MAXIMUM_EXTRA_DATA_SIZE {
+                return Err(PolicyError::ExtraDataTooLong(extra_data.len()))
+            }
+        }
+
+        if let Some(gas_limit) = self.gas_limit {
+            if gas_limit < MINIMUM_GAS_LIMIT {
+                return Err(PolicyError::GasLimitTooLow(gas_limit))
+            }
+        }
+
+        if let Some(payment) = &self.coinbase_payment {
+            if payment.share_percent > 100 {
+                return Err(PolicyError::PaymentShareTooHigh(payment.share_percent))
+            }
+            let signer = payment.signer().ok_or(PolicyError::InvalidPaymentKey)?;
+            let fee_recipient = self.fee_recipient.unwrap_or(suggested_fee_recipient);
+            if signer != fee_recipient {
+                return Err(PolicyError::PaymentSignerMismatch { signer, fee_recipient })
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the gas limit of a child of a block with the given gas limit, moved as far towards the
+/// target as the consensus rules allow.
+pub fn next_gas_limit(parent_gas_limit: u64, target: u64) -> u64 {
+    // the difference to the parent must be strictly less than 1/1024 of the parent's gas limit
+    let max_delta = (parent_gas_limit / 1024).saturating_sub(1);
+    let gas_limit = if target > parent_gas_limit {
+        parent_gas_limit + (target - parent_gas_limit).min(max_delta)
+    } else {
+        parent_gas_limit - (parent_gas_limit - target).min(max_delta)
+    };
+    gas_limit.max(MINIMUM_GAS_LIMIT)
+}
+
+/// A payment of a share of the fee recipient's profit to the suggested fee recipient.
+///
+/// The payment is a transfer at the end of the block, signed with the key of the fee recipient.
+/// Its gas cost is deducted from the paid share.
+#[derive(Clone)]
+pub struct CoinbasePayment {
+    /// The secret key of the fee recipient.
+    secret_key: B256,
+    /// The share of the increase of the fee recipient's balance that is paid, in percent.
+    pub share_percent: u8,
+}
+
+impl CoinbasePayment {
+    /// Creates a payment of the given share of the profit, signed with the given secret key.
+    pub fn new(secret_key: B256, share_percent: u8) -> Self {
+        Self { secret_key, share_percent }
+    }
+
+    /// Returns the address of the payment key, or `None` if the key is invalid.
+    pub fn signer(&self) -> Option<Address> {
+        let message = keccak256(b"coinbase payment");
+        sign_message(self.secret_key, message).ok()?.recover_signer(message)
+    }
+
+    /// Returns the amount paid for the given increase of the fee recipient's balance, after the
+    /// gas cost of the payment at the given base fee.
+    pub fn value(&self, profit: U256, base_fee: u64) -> U256 {
+        let share = profit * U256::from(self.share_percent) / U256::from(100);
+        share.saturating_sub(U256::from(COINBASE_PAYMENT_GAS) * U256::from(base_fee))
+    }
+
+    /// Builds and signs the payment transaction.
+    pub fn transaction(
+        &self,
+        chain_id: ChainId,
+        nonce: u64,
+        recipient: Address,
+        value: U256,
+        base_fee: u64,
+    ) -> Result<TransactionSignedEcRecovered, PolicyError> {
+        let signer = self.signer().ok_or(PolicyError::InvalidPaymentKey)?;
+        let transaction = Transaction::Eip1559(TxEip1559 {
+            chain_id,
+            nonce,
+            gas_limit: COINBASE_PAYMENT_GAS,
+            max_fee_per_gas: base_fee as u128,
+            max_priority_fee_per_gas: 0,
+            to: TransactionKind::Call(recipient),
+            value: value.into(),
+            access_list: Default::default(),
+            input: Bytes::default(),
+        });
+        let signature = sign_message(self.secret_key, transaction.signature_hash())
+            .map_err(|_| PolicyError::InvalidPaymentKey)?;
+        Ok(TransactionSigned::from_transaction_and_signature(transaction, signature)
+            .with_signer(signer))
+    }
+}
+
+impl fmt::Debug for CoinbasePayment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoinbasePayment")
+            .field("signer", &self.signer())
+            .field("share_percent", &self.share_percent)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors of invalid [PayloadJobPolicy]s.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyError {
+    /// The extra data exceeds the maximum size of the header field.
+    #[error("extra data of {0} bytes exceeds the maximum of {MAXIMUM_EXTRA_DATA_SIZE} bytes")]
+    ExtraDataTooLong(usize),
+    /// The paid share is more than the whole profit.
+    #[error("coinbase payment share of {0}% exceeds 100%")]
+    PaymentShareTooHigh(u8),
+    /// The payment key is not a valid secret key.
+    #[error("invalid coinbase payment key")]
+    InvalidPaymentKey,
+    /// The payment key does not belong to the fee recipient of the payloads.
+    #[error("coinbase payment signer {signer} is not the fee recipient {fee_recipient}")]
+    PaymentSignerMismatch {
+        /// The address of the payment key.
+        signer: Address,
+        /// The fee recipient of the payloads.
+        fee_recipient: Address,
+    },
+    /// The gas limit is below the minimum gas limit of a block.
+    #[error("gas limit {0} is below the minimum of {MINIMUM_GAS_LIMIT}")]
+    GasLimitTooLow(u64),
+    /// The payment transaction did not succeed.
+    #[error("coinbase payment transaction failed")]
+    PaymentFailed,
+}
+
+/// Resolves the [PayloadJobPolicy] of new payload jobs.
+pub trait PayloadPolicyProvider: fmt::Debug + Send + Sync {
+    /// Returns the policy of the job building the payload with the given id on the given parent,
+    /// or `None` to build it with the defaults.
+    fn policy(
+        &self,
+        payload_id: PayloadId,
+        parent: B256,
+        suggested_fee_recipient: Address,
+    ) -> Option<PayloadJobPolicy>;
+}
+
+/// Applies the same policy to all jobs.
+impl PayloadPolicyProvider for PayloadJobPolicy {
+    fn policy(&self, _: PayloadId, _: B256, _: Address) -> Option<PayloadJobPolicy> {
+        Some(self.clone())
+    }
+}
@@ -0,0 +1,87 @@
+//! Registered proposers with their own fee recipient, gas limit and extra data preferences.
+
+use crate::{PayloadJobPolicy, PayloadPolicyProvider};
+use reth_payload_builder::PayloadId;
+use reth_primitives::{Address, Bytes, B256};
+use std::{collections::HashMap, sync::RwLock};
+
+/// How the payloads of a registered proposer are built.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProposerPreferences {
+    /// The fee recipient of the payloads, instead of the suggested fee recipient.
+    pub fee_recipient: Option<Address>,
+    /// The gas limit the payloads move towards.
+    pub gas_limit: Option<u64>,
+    /// The extra data of the payloads.
+    pub extra_data: Option<Bytes>,
+}
+
+/// The proposers the node builds payloads for, keyed by the suggested fee recipient of their
+/// payload attributes.
+///
+/// Every proposer gets its own payload job, even if several proposers build on the same parent.
+/// The registry is a [PayloadPolicyProvider] that applies the preferences of the proposer to its
+/// jobs, and proposers can be registered and unregistered while the node is running.
+#[derive(Debug, Default)]
+pub struct ProposerRegistry {
+    proposers: RwLock<HashMap<Address, ProposerPreferences>>,
+}
+
+impl ProposerRegistry {
+    /// Registers the preferences of the proposer with the given suggested fee recipient,
+    /// replacing and returning its previous preferences.
+    pub fn register(
+        &self,
+        suggested_fee_recipient: Address,
+        preferences: ProposerPreferences,
+    ) -> Option<ProposerPreferences> {
+        self.proposers
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(suggested_fee_recipient, preferences)
+    }
+
+    /// Removes the proposer with the given suggested fee recipient.
+    pub fn unregister(&self, suggested_fee_recipient: &Address) -> Option<ProposerPreferences> {
+        self.proposers
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(suggested_fee_recipient)
+    }
+
+    /// Returns the preferences of the proposer with the given suggested fee recipient.
+    pub fn get(&self, suggested_fee_recipient: &Address) -> Option<ProposerPreferences> {
+        self.proposers
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(suggested_fee_recipient)
+            .cloned()
+    }
+
+    /// Returns the number of registered proposers.
+    pub fn len(&self) -> usize {
+        self.proposers.read().unwrap_or_else(|err| err.into_inner()).len()
+    }
+
+    /// Returns `true` if no proposers are registered.
+    pub fn is_empty(&self) -> bool {
+        self.proposers.read().unwrap_or_else(|err| err.into_inner()).is_empty()
+    }
+}
+
+impl PayloadPolicyProvider for ProposerRegistry {
+    fn policy(
+        &self,
+        _: PayloadId,
+        _: B256,
+        suggested_fee_recipient: Address,
+    ) -> Option<PayloadJobPolicy> {
+        let preferences = self.get(&suggested_fee_recipient)?;
+        Some(PayloadJobPolicy {
+            extra_data: preferences.extra_data,
+            fee_recipient: preferences.fee_recipient,
+            coinbase_payment: None,
+            gas_limit: preferences.gas_limit,
+        })
+    }
+}
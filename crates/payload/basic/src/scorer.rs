@@ -0,0 +1,52 @@
+//! Scoring of candidate blocks.
+
+use reth_primitives::{Receipt, TransactionSigned, U256};
+use std::fmt::Debug;
+
+/// A block built during a payload job, before it is sealed.
+///
+/// Payload builders build a new candidate on every attempt and keep it only if it scores higher
+/// than the best payload built so far.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockCandidate<'a> {
+    /// The transactions of the candidate, in block order.
+    pub transactions: &'a [TransactionSigned],
+    /// The receipts of the transactions, in block order.
+    pub receipts: &'a [Receipt],
+    /// The gas used by all transactions.
+    pub gas_used: u64,
+    /// The priority fees paid by all transactions.
+    pub fees: U256,
+    /// The increase of the fee recipient's balance caused by the transactions.
+    ///
+    /// In addition to the priority fees, this includes direct transfers to the fee recipient and
+    /// is zero if its balance decreased.
+    pub coinbase_diff: U256,
+    /// The number of [BlockConstraints](crate::BlockConstraints) of the payload the candidate
+    /// does not satisfy.
+    ///
+    /// Builders rank the scores of constrained payloads with
+    /// [constrained_score](crate::constrained_score), so scorers only need to consider this to
+    /// tell apart candidates that violate the same number of constraints.
+    pub unsatisfied_constraints: usize,
+}
+
+/// Scores candidate blocks, the candidate with the highest score is the payload that is proposed.
+///
+/// Implementations are free to weigh the inputs of the [BlockCandidate] however they like, but
+/// scores of candidates of the same payload job must be comparable.
+pub trait BlockScorer: Debug + Send + Sync {
+    /// Returns the score of the candidate, higher is better.
+    fn score(&self, candidate: &BlockCandidate<'_>) -> U256;
+}
+
+/// The default [BlockScorer] that maximizes the priority fees of the block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FeeScorer;
+
+impl BlockScorer for FeeScorer {
+    fn score(&self, candidate: &BlockCandidate<'_>) -> U256 {
+        candidate.fees
+    }
+}
@@ -0,0 +1,73 @@
+//! Sharing of simulation state between the payload jobs of different proposers on the same parent.
+
+use crate::SystemCallCache;
+use reth_payload_builder::database::CachedReads;
+use reth_primitives::B256;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The state shared by all jobs that build on the same parent block.
+///
+/// Jobs for different proposers on the same parent differ in their fee recipient, gas limit and
+/// extra data, but execute their transactions against the same parent state. Every build
+/// publishes the state it read, and every build starts with the state its siblings read, so
+/// transactions that are ordered the same way in several jobs only load their state once.
+///
+/// The state changes of the pre-block system calls do not depend on the fee recipient either and
+/// are shared by the jobs with the same timestamp and parent beacon block root.
+///
+/// Only the siblings of the most recent parent are tracked.
+#[derive(Debug, Default)]
+pub(crate) struct SiblingJobs {
+    inner: Mutex<Option<SiblingState>>,
+}
+
+#[derive(Debug)]
+struct SiblingState {
+    parent: B256,
+    reads: SharedReads,
+    /// The system call caches by timestamp and parent beacon block root.
+    system_calls: HashMap<(u64, Option<B256>), Arc<SystemCallCache>>,
+}
+
+impl SiblingJobs {
+    /// Returns the state shared with the other jobs on the given parent, dropping the state of
+    /// the jobs on a previous parent.
+    pub(crate) fn join(
+        &self,
+        parent: B256,
+        timestamp: u64,
+        parent_beacon_block_root: Option<B256>,
+    ) -> (SharedReads, Arc<SystemCallCache>) {
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        let state = match inner.take() {
+            Some(state) if state.parent == parent => inner.insert(state),
+            _ => inner.insert(SiblingState {
+                parent,
+                reads: SharedReads::default(),
+                system_calls: HashMap::new(),
+            }),
+        };
+        let system_calls =
+            state.system_calls.entry((timestamp, parent_beacon_block_root)).or_default().clone();
+        (state.reads.clone(), system_calls)
+    }
+}
+
+/// The state read by the builds of all jobs on the same parent.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedReads(Arc<Mutex<CachedReads>>);
+
+impl SharedReads {
+    /// Adds the state read by the siblings to the given reads.
+    pub(crate) fn pull_into(&self, reads: &mut CachedReads) {
+        reads.extend_from(&self.0.lock().unwrap_or_else(|err| err.into_inner()));
+    }
+
+    /// Makes the given reads available to the siblings.
+    pub(crate) fn publish(&self, reads: &CachedReads) {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).extend_from(reads);
+    }
+}
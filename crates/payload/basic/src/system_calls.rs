@@ -0,0 +1,28 @@
+//! Caching of the state changes of pre-block system calls.
+
+use revm::primitives::State as EvmState;
+use std::sync::OnceLock;
+
+/// The state changes of the pre-block system calls of a payload.
+///
+/// System calls only depend on the parent state and the payload attributes, so their state changes
+/// are the same for every build of a payload job. The cache is part of the
+/// [PayloadConfig](crate::PayloadConfig) of a job and is dropped with it, so a new parent or new
+/// attributes always start with an empty cache.
+#[derive(Debug, Default)]
+pub struct SystemCallCache {
+    /// The state changes of the EIP-4788 beacon root contract call, `None` if there is no call.
+    beacon_root: OnceLock<Option<EvmState>>,
+}
+
+impl SystemCallCache {
+    /// Returns the cached state changes of the beacon root contract call, if it was executed.
+    pub fn beacon_root(&self) -> Option<&Option<EvmState>> {
+        self.beacon_root.get()
+    }
+
+    /// Caches the state changes of the beacon root contract call, unless they are already cached.
+    pub fn set_beacon_root(&self, state: Option<EvmState>) {
+        let _ = self.beacon_root.set(state);
+    }
+}
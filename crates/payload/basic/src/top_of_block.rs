@@ -0,0 +1,45 @@
+//! Caching of the validation of bids for the top of a payload.
+
+use reth_payload_builder::{BidRejection, WinningBid};
+use reth_primitives::B256;
+use revm::primitives::{ExecutionResult, State as EvmState};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// A bid for the top of the block that is valid on the state the block starts with.
+#[derive(Debug, Clone)]
+pub struct ValidatedSegment {
+    /// The accounting of the bid.
+    pub bid: WinningBid,
+    /// The results and state changes of the transactions of the segment, in execution order.
+    pub executed: Vec<(ExecutionResult, EvmState)>,
+}
+
+/// The outcome of validating a bid for the top of a payload.
+pub type BidValidation = Result<Arc<ValidatedSegment>, BidRejection>;
+
+/// The outcomes of validating the bids for the top of a payload, by bid hash.
+///
+/// Bids are validated on the parent state after the pre-block system calls, with the block
+/// environment of the payload, so the outcome is the same for every build of a payload job and
+/// every bid is only validated once. The state changes of a valid segment are committed to the
+/// block instead of executing the segment again. The cache is part of the
+/// [PayloadConfig](crate::PayloadConfig) of a job and is dropped with it.
+#[derive(Debug, Default)]
+pub struct TopOfBlockCache {
+    validations: Mutex<HashMap<B256, BidValidation>>,
+}
+
+impl TopOfBlockCache {
+    /// Returns the outcome of the validation of the bid, if it was validated.
+    pub fn get(&self, bid_hash: &B256) -> Option<BidValidation> {
+        self.validations.lock().unwrap_or_else(|err| err.into_inner()).get(bid_hash).cloned()
+    }
+
+    /// Caches the outcome of the validation of the bid.
+    pub fn insert(&self, bid_hash: B256, validation: BidValidation) {
+        self.validations.lock().unwrap_or_else(|err| err.into_inner()).insert(bid_hash, validation);
+    }
+}
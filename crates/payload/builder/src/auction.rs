@@ -0,0 +1,377 @@
+//! Auction of the top of built blocks.
+
+use reth_primitives::{
+    eip191_hash_message, keccak256, Address, Signature, TransactionSignedEcRecovered, TxHash, B256,
+    U256,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The maximum number of blocks the [TopOfBlockAuction] keeps bids for.
+const MAX_AUCTIONED_BLOCKS: usize = 64;
+
+/// The default time before the timestamp of a block after which no more bids are accepted.
+pub const DEFAULT_TOP_OF_BLOCK_CUTOFF: Duration = Duration::from_secs(2);
+
+/// The default maximum number of bids kept per block.
+pub const DEFAULT_MAX_TOP_OF_BLOCK_BIDS: usize = 32;
+
+/// A bid for the top of a block: transactions that are executed in this order before any other
+/// transaction of the block, in exchange for a payment to the fee recipient.
+///
+/// Bids are signed by their bidder, the [TopOfBlockAuction] only accepts bids of allowed bidders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopOfBlockBid {
+    /// The parent of the block.
+    pub parent_hash: B256,
+    /// The timestamp of the block.
+    pub timestamp: u64,
+    /// The transactions of the segment, in execution order.
+    pub transactions: Vec<TransactionSignedEcRecovered>,
+    /// The minimum increase of the fee recipient's balance the segment pays, including the
+    /// priority fees of its transactions.
+    pub value: U256,
+    /// The signature of the bidder over the [TopOfBlockBid::signature_hash].
+    pub signature: Signature,
+}
+
+impl TopOfBlockBid {
+    /// Returns the hash identifying the bid, over the block, the value and the transactions.
+    pub fn hash(&self) -> B256 {
+        let mut buf = Vec::with_capacity(32 + 8 + 32 + 32 * self.transactions.len());
+        buf.extend_from_slice(self.parent_hash.as_slice());
+        buf.extend_from_slice(&self.timestamp.to_be_bytes());
+        buf.extend_from_slice(&self.value.to_be_bytes::<32>());
+        for tx in &self.transactions {
+            buf.extend_from_slice(tx.hash.as_slice());
+        }
+        keccak256(buf)
+    }
+
+    /// Returns the hash the bidder signs, the [EIP-191](https://eips.ethereum.org/EIPS/eip-191)
+    /// hash of the message with the [TopOfBlockBid::hash], like `personal_sign`.
+    pub fn signature_hash(&self) -> B256 {
+        eip191_hash_message(self.hash())
+    }
+
+    /// Recovers the bidder from the signature of the bid.
+    pub fn recover_bidder(&self) -> Option<Address> {
+        self.signature.recover_signer(self.signature_hash())
+    }
+}
+
+/// A bid accepted by the [TopOfBlockAuction].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptedBid {
+    /// The hash of the bid.
+    pub hash: B256,
+    /// The bidder that signed the bid.
+    pub bidder: Address,
+    /// The bid.
+    pub bid: TopOfBlockBid,
+}
+
+/// Errors of bids rejected by the [TopOfBlockAuction].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuctionError {
+    /// The signature of the bid is invalid.
+    #[error("invalid bid signature")]
+    InvalidSignature,
+    /// The bid was signed by a bidder that is not allowed to bid.
+    #[error("bidder {0} is not allowed to bid")]
+    UnknownBidder(Address),
+    /// The bid was submitted after the cutoff of its block.
+    #[error("bids for the block closed at timestamp {0}")]
+    DeadlinePassed(u64),
+    /// The bid has no transactions.
+    #[error("bid without transactions")]
+    EmptySegment,
+    /// The bid contains a blob transaction, whose sidecar is not known.
+    #[error("blob transaction {0} can not be part of a bid")]
+    BlobTransaction(TxHash),
+    /// The same bid was already submitted.
+    #[error("bid {0} was already submitted")]
+    DuplicateBid(B256),
+    /// The block has the maximum number of bids, all of them with at least the same value.
+    #[error("bid value does not exceed the lowest of the {0} bids of the block")]
+    BidTooLow(usize),
+}
+
+/// Why the payload builder rejected a bid it validated.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BidRejection {
+    /// The transactions of the segment need more gas than the block has.
+    #[error("segment exceeds the gas limit of the block")]
+    GasLimitExceeded,
+    /// A transaction of the segment is invalid on the state the block starts with, e.g. because
+    /// of its nonce or the balance of its sender.
+    #[error("transaction {0} of the segment is invalid")]
+    InvalidTransaction(TxHash),
+    /// A transaction of the segment reverted.
+    #[error("transaction {0} of the segment reverted")]
+    Reverted(TxHash),
+    /// The segment paid the fee recipient less than its bid.
+    #[error("segment paid {paid} to the fee recipient, less than its bid")]
+    InsufficientPayment {
+        /// The increase of the fee recipient's balance caused by the segment.
+        paid: U256,
+    },
+}
+
+/// A bid the payload builder validated and rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedBid {
+    /// The hash of the bid.
+    pub bid_hash: B256,
+    /// The bidder that signed the bid.
+    pub bidder: Address,
+    /// The value of the bid.
+    pub value: U256,
+    /// Why the bid was rejected.
+    pub reason: BidRejection,
+}
+
+/// The bid the payload builder placed at the top of the block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinningBid {
+    /// The hash of the bid.
+    pub bid_hash: B256,
+    /// The bidder that signed the bid.
+    pub bidder: Address,
+    /// The value of the bid.
+    pub value: U256,
+    /// The increase of the fee recipient's balance caused by the segment, at least the value.
+    pub payment: U256,
+    /// The gas used by the transactions of the segment.
+    pub gas_used: u64,
+    /// The number of transactions of the segment.
+    pub transactions: usize,
+}
+
+/// The outcome of the auction of the top of a built block.
+///
+/// Bids are validated from the highest to the lowest value until one is valid, bids with a lower
+/// value than the winner are not validated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopOfBlockAccounting {
+    /// The number of bids for the block.
+    pub bids: usize,
+    /// The bid at the top of the block, if any was valid.
+    pub winner: Option<WinningBid>,
+    /// The bids with a higher value than the winner, which were rejected.
+    pub rejected: Vec<RejectedBid>,
+}
+
+/// The bids for the top of the blocks to build, by parent block and timestamp.
+///
+/// Bids are accepted from the allowed bidders until the cutoff before the timestamp of their
+/// block, each build of a payload job picks up the bids submitted so far. At most `max_bids` bids
+/// are kept per block, the lowest bid is dropped for a higher one. Bids of the oldest blocks are
+/// dropped once more than 64 blocks have bids.
+#[derive(Debug)]
+pub struct TopOfBlockAuction {
+    /// The time before the timestamp of a block after which no more bids are accepted.
+    cutoff: Duration,
+    /// The maximum number of bids kept per block.
+    max_bids: usize,
+    /// The addresses of the keys bids have to be signed with.
+    bidders: HashSet<Address>,
+    inner: Mutex<TopOfBlockAuctionInner>,
+}
+
+#[derive(Debug, Default)]
+struct TopOfBlockAuctionInner {
+    /// The bids of each block, from the highest to the lowest value.
+    bids: HashMap<(B256, u64), Vec<Arc<AcceptedBid>>>,
+    /// The blocks with bids in submission order.
+    blocks: VecDeque<(B256, u64)>,
+}
+
+impl TopOfBlockAuction {
+    /// Creates an auction that accepts bids signed by one of the `bidders` until `cutoff` before
+    /// the timestamp of their block, and keeps at most `max_bids` bids per block, at least one.
+    pub fn new(
+        cutoff: Duration,
+        max_bids: usize,
+        bidders: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        Self {
+            cutoff,
+            max_bids: max_bids.max(1),
+            bidders: bidders.into_iter().collect(),
+            inner: Default::default(),
+        }
+    }
+
+    /// Returns the time before the timestamp of a block after which no more bids are accepted.
+    pub fn cutoff(&self) -> Duration {
+        self.cutoff
+    }
+
+    /// Adds a bid of an allowed bidder for the top of its block, returning the hash of the bid.
+    pub fn submit(&self, bid: TopOfBlockBid) -> Result<B256, AuctionError> {
+        self.submit_at(bid, SystemTime::now())
+    }
+
+    fn submit_at(&self, bid: TopOfBlockBid, now: SystemTime) -> Result<B256, AuctionError> {
+        let bidder = bid.recover_bidder().ok_or(AuctionError::InvalidSignature)?;
+        if !self.bidders.contains(&bidder) {
+            return Err(AuctionError::UnknownBidder(bidder))
+        }
+        let deadline = UNIX_EPOCH + Duration::from_secs(bid.timestamp).saturating_sub(self.cutoff);
+        if now >= deadline {
+            return Err(AuctionError::DeadlinePassed(bid.timestamp))
+        }
+        if bid.transactions.is_empty() {
+            return Err(AuctionError::EmptySegment)
+        }
+        if let Some(tx) = bid.transactions.iter().find(|tx| tx.is_eip4844()) {
+            return Err(AuctionError::BlobTransaction(tx.hash))
+        }
+
+        let bid_hash = bid.hash();
+        let block = (bid.parent_hash, bid.timestamp);
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        let bids = inner.bids.entry(block).or_default();
+        if bids.iter().any(|other| other.hash == bid_hash) {
+            return Err(AuctionError::DuplicateBid(bid_hash))
+        }
+        if bids.len() >= self.max_bids {
+            if bids.last().map_or(true, |lowest| lowest.bid.value >= bid.value) {
+                return Err(AuctionError::BidTooLow(bids.len()))
+            }
+            bids.pop();
+        }
+        // bids with the same value keep their submission order
+        let index = bids.partition_point(|other| other.bid.value >= bid.value);
+        bids.insert(index, Arc::new(AcceptedBid { hash: bid_hash, bidder, bid }));
+
+        if bids.len() == 1 {
+            inner.blocks.push_back(block);
+        }
+        while inner.blocks.len() > MAX_AUCTIONED_BLOCKS {
+            if let Some(oldest) = inner.blocks.pop_front() {
+                inner.bids.remove(&oldest);
+            }
+        }
+        Ok(bid_hash)
+    }
+
+    /// Returns the bids for the block with the given parent and timestamp, from the highest to
+    /// the lowest value.
+    pub fn bids(&self, parent_hash: B256, timestamp: u64) -> Vec<Arc<AcceptedBid>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .bids
+            .get(&(parent_hash, timestamp))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        sign_message, Transaction, TransactionKind, TransactionSigned, TxLegacy,
+    };
+
+    const TIMESTAMP: u64 = 1_700_000_012;
+    const BIDDER_KEY: B256 = B256::with_last_byte(3);
+
+    fn auction(max_bids: usize) -> TopOfBlockAuction {
+        let bidder = bid(0, 0).recover_bidder().unwrap();
+        TopOfBlockAuction::new(Duration::from_secs(2), max_bids, [bidder])
+    }
+
+    fn sign(mut bid: TopOfBlockBid, key: B256) -> TopOfBlockBid {
+        bid.signature = sign_message(key, bid.signature_hash()).unwrap();
+        bid
+    }
+
+    fn bid(nonce: u64, value: u64) -> TopOfBlockBid {
+        let secret = B256::with_last_byte(1);
+        let transaction = Transaction::Legacy(TxLegacy {
+            nonce,
+            gas_price: 1,
+            gas_limit: 21_000,
+            to: TransactionKind::Call(Default::default()),
+            ..Default::default()
+        });
+        let signature = sign_message(secret, transaction.signature_hash()).unwrap();
+        let tx = TransactionSigned::from_transaction_and_signature(transaction, signature);
+        let bid = TopOfBlockBid {
+            parent_hash: B256::with_last_byte(2),
+            timestamp: TIMESTAMP,
+            transactions: vec![tx.into_ecrecovered().unwrap()],
+            value: U256::from(value),
+            signature: Signature::default(),
+        };
+        sign(bid, BIDDER_KEY)
+    }
+
+    fn before_deadline() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(TIMESTAMP - 3)
+    }
+
+    #[test]
+    fn rejects_bids_of_unknown_bidders() {
+        let auction = auction(2);
+        let unknown = sign(bid(0, 1), B256::with_last_byte(4));
+        let unknown_bidder = unknown.recover_bidder().unwrap();
+        assert_eq!(
+            auction.submit_at(unknown, before_deadline()),
+            Err(AuctionError::UnknownBidder(unknown_bidder))
+        );
+
+        // the signature does not cover the changed value
+        let mut tampered = bid(0, 1);
+        tampered.value = U256::from(2);
+        assert!(matches!(
+            auction.submit_at(tampered, before_deadline()),
+            Err(AuctionError::UnknownBidder(_) | AuctionError::InvalidSignature)
+        ));
+        assert!(auction.submit_at(bid(0, 1), before_deadline()).is_ok());
+    }
+
+    #[test]
+    fn rejects_bids_after_cutoff() {
+        let auction = auction(2);
+        let late = UNIX_EPOCH + Duration::from_secs(TIMESTAMP - 2);
+        assert_eq!(
+            auction.submit_at(bid(0, 1), late),
+            Err(AuctionError::DeadlinePassed(TIMESTAMP))
+        );
+        assert!(auction.submit_at(bid(0, 1), before_deadline()).is_ok());
+
+        let mut empty = bid(0, 1);
+        empty.transactions.clear();
+        let empty = sign(empty, BIDDER_KEY);
+        assert_eq!(auction.submit_at(empty, before_deadline()), Err(AuctionError::EmptySegment));
+    }
+
+    #[test]
+    fn orders_and_evicts_bids_by_value() {
+        let auction = auction(2);
+        let low = auction.submit_at(bid(0, 1), before_deadline()).unwrap();
+        let high = auction.submit_at(bid(1, 3), before_deadline()).unwrap();
+        assert_eq!(
+            auction.submit_at(bid(1, 3), before_deadline()),
+            Err(AuctionError::DuplicateBid(high))
+        );
+        assert_eq!(
+            auction.submit_at(bid(2, 1), before_deadline()),
+            Err(AuctionError::BidTooLow(2))
+        );
+
+        let mid = auction.submit_at(bid(2, 2), before_deadline()).unwrap();
+        let bids = auction.bids(B256::with_last_byte(2), TIMESTAMP);
+        assert_eq!(bids.iter().map(|bid| bid.hash).collect::<Vec<_>>(), vec![high, mid]);
+        assert!(!bids.iter().any(|bid| bid.hash == low));
+        assert!(auction.bids(B256::with_last_byte(2), TIMESTAMP + 12).is_empty());
+    }
+}
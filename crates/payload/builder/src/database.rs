@@ -60,6 +60,31 @@ impl CachedReads {
     ) {
         self.accounts.insert(address, CachedAccount { info: Some(info), storage });
     }
+
+    /// Inserts all reads of the other cache that are not cached yet.
+    ///
+    /// Both caches must hold reads of the same state.
+    pub fn extend_from(&mut self, other: &CachedReads) {
+        for (address, other_account) in &other.accounts {
+            match self.accounts.entry(*address) {
+                Entry::Occupied(mut entry) => {
+                    let storage = &mut entry.get_mut().storage;
+                    for (slot, value) in &other_account.storage {
+                        storage.entry(*slot).or_insert(*value);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(other_account.clone());
+                }
+            }
+        }
+        for (hash, code) in &other.contracts {
+            self.contracts.entry(*hash).or_insert_with(|| code.clone());
+        }
+        for (number, hash) in &other.block_hashes {
+            self.block_hashes.entry(*number).or_insert(*hash);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -23,6 +23,9 @@ pub enum PayloadBuilderError {
     /// Unrecoverable error during evm execution.
     #[error("evm execution error: {0}")]
     EvmExecutionError(EVMError<ProviderError>),
+    /// Thrown if a payload whose state root was not computed yet is about to be handed out.
+    #[error("payload {0} has no state root")]
+    MissingStateRoot(B256),
     /// Thrown if the payload requests withdrawals before Shanghai activation.
     #[error("withdrawals set before Shanghai activation")]
     WithdrawalsBeforeShanghai,
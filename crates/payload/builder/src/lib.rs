@@ -100,6 +100,7 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+mod auction;
 pub mod database;
 pub mod error;
 mod metrics;
@@ -113,8 +114,15 @@ pub mod noop;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
 
+pub use auction::{
+    AcceptedBid, AuctionError, BidRejection, RejectedBid, TopOfBlockAccounting, TopOfBlockAuction,
+    TopOfBlockBid, WinningBid, DEFAULT_MAX_TOP_OF_BLOCK_BIDS, DEFAULT_TOP_OF_BLOCK_CUTOFF,
+};
 pub use optimism::OptimismPayloadBuilderAttributes;
 pub use payload::{EthBuiltPayload, EthPayloadBuilderAttributes};
 pub use reth_rpc_types::engine::PayloadId;
-pub use service::{PayloadBuilderHandle, PayloadBuilderService, PayloadStore};
+pub use service::{
+    PayloadBuilderHandle, PayloadBuilderService, PayloadJobsConfig, PayloadStore,
+    DEFAULT_MAX_PAYLOAD_JOBS,
+};
 pub use traits::{KeepPayloadJobAlive, PayloadJob, PayloadJobGenerator};
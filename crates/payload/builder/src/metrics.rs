@@ -15,6 +15,10 @@ pub(crate) struct PayloadBuilderServiceMetrics {
     pub(crate) initiated_jobs: Counter,
     /// Total number of failed jobs
     pub(crate) failed_jobs: Counter,
+    /// Total number of unresolved jobs evicted to enforce the maximum number of jobs
+    pub(crate) evicted_unresolved_jobs: Counter,
+    /// Number of resolved payloads kept for repeated requests
+    pub(crate) stored_payloads: Gauge,
     /// Coinbase revenue for best payloads
     pub(crate) best_revenue: Gauge,
     /// Current block returned as the best payload
@@ -34,6 +38,14 @@ impl PayloadBuilderServiceMetrics {
         self.failed_jobs.increment(1);
     }
 
+    pub(crate) fn inc_evicted_unresolved_jobs(&self) {
+        self.evicted_unresolved_jobs.increment(1);
+    }
+
+    pub(crate) fn set_stored_payloads(&self, value: usize) {
+        self.stored_payloads.set(value as f64)
+    }
+
     pub(crate) fn set_active_jobs(&self, value: usize) {
         self.active_jobs.set(value as f64)
     }
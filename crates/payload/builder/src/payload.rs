@@ -1,8 +1,10 @@
 //! Contains types required for building a payload.
 
+use crate::TopOfBlockAccounting;
 use alloy_rlp::Encodable;
 use reth_node_api::{BuiltPayload, PayloadBuilderAttributes};
 use reth_primitives::{Address, BlobTransactionSidecar, SealedBlock, Withdrawal, B256, U256};
+use reth_provider::BundleStateWithReceipts;
 use reth_rpc_types::engine::{
     ExecutionPayloadEnvelopeV2, ExecutionPayloadEnvelopeV3, ExecutionPayloadV1, PayloadAttributes,
     PayloadId,
@@ -11,7 +13,7 @@ use reth_rpc_types_compat::engine::payload::{
     block_to_payload_v3, convert_block_to_payload_field_v2,
     convert_standalone_withdraw_to_withdrawal, try_block_to_payload_v1,
 };
-use std::convert::Infallible;
+use std::{convert::Infallible, sync::Arc};
 
 /// Contains the built payload.
 ///
@@ -26,9 +28,16 @@ pub struct EthBuiltPayload {
     pub(crate) block: SealedBlock,
     /// The fees of the block
     pub(crate) fees: U256,
+    /// The score the payload builder picked the block by, defaults to the fees.
+    pub(crate) score: U256,
     /// The blobs, proofs, and commitments in the block. If the block is pre-cancun, this will be
     /// empty.
     pub(crate) sidecars: Vec<BlobTransactionSidecar>,
+    /// The state changes of the block, if its state root is a placeholder that still needs to be
+    /// computed.
+    pub(crate) deferred_state: Option<Arc<BundleStateWithReceipts>>,
+    /// The outcome of the auction of the top of the block, if bids were considered.
+    pub(crate) top_of_block: Option<TopOfBlockAccounting>,
 }
 
 // === impl BuiltPayload ===
@@ -36,7 +45,32 @@ pub struct EthBuiltPayload {
 impl EthBuiltPayload {
     /// Initializes the payload with the given initial block.
     pub fn new(id: PayloadId, block: SealedBlock, fees: U256) -> Self {
-        Self { id, block, fees, sidecars: Vec::new() }
+        Self {
+            id,
+            block,
+            fees,
+            score: fees,
+            sidecars: Vec::new(),
+            deferred_state: None,
+            top_of_block: None,
+        }
+    }
+
+    /// Sets the score the payload builder picked the block by.
+    pub fn with_score(mut self, score: U256) -> Self {
+        self.score = score;
+        self
+    }
+
+    /// Sets the outcome of the auction of the top of the block.
+    pub fn with_top_of_block(mut self, accounting: TopOfBlockAccounting) -> Self {
+        self.top_of_block = Some(accounting);
+        self
+    }
+
+    /// Returns the outcome of the auction of the top of the block, if bids were considered.
+    pub fn top_of_block(&self) -> Option<&TopOfBlockAccounting> {
+        self.top_of_block.as_ref()
     }
 
     /// Returns the identifier of the payload.
@@ -54,6 +88,39 @@ impl EthBuiltPayload {
         self.fees
     }
 
+    /// Score of the block, see [EthBuiltPayload::with_score]
+    pub fn score(&self) -> U256 {
+        self.score
+    }
+
+    /// Marks the state root of the block as a placeholder and keeps the state changes of the
+    /// block to compute it with [EthBuiltPayload::with_state_root] once the payload is picked.
+    ///
+    /// The payload builder service refuses to hand out such a payload until the root is computed.
+    pub fn with_deferred_state_root(mut self, state: BundleStateWithReceipts) -> Self {
+        self.deferred_state = Some(Arc::new(state));
+        self
+    }
+
+    /// Returns the state changes of the block if its state root still needs to be computed.
+    pub fn deferred_state(&self) -> Option<&BundleStateWithReceipts> {
+        self.deferred_state.as_deref()
+    }
+
+    /// Returns `true` if the state root of the block is final.
+    pub fn has_state_root(&self) -> bool {
+        self.deferred_state.is_none()
+    }
+
+    /// Sets the computed state root of the block and reseals it.
+    pub fn with_state_root(mut self, state_root: B256) -> Self {
+        let mut block = self.block.unseal();
+        block.header.state_root = state_root;
+        self.block = block.seal_slow();
+        self.deferred_state = None;
+        self
+    }
+
     /// Adds sidecars to the payload.
     pub fn extend_sidecars(&mut self, sidecars: Vec<BlobTransactionSidecar>) {
         self.sidecars.extend(sidecars)
@@ -9,15 +9,21 @@ use crate::{
 };
 use futures_util::{future::FutureExt, Stream, StreamExt};
 use reth_node_api::{BuiltPayload, EngineTypes, PayloadBuilderAttributes};
+use reth_primitives::constants::SLOT_DURATION;
 use reth_provider::CanonStateNotification;
 use reth_rpc_types::engine::PayloadId;
 use std::{
+    collections::VecDeque,
     fmt,
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{Interval, MissedTickBehavior},
 };
-use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, info, trace, warn};
 
@@ -123,6 +129,18 @@ where
         rx.await.ok()?
     }
 
+    /// Returns the payloads the active jobs have built so far, see [PayloadJob::built_payload].
+    ///
+    /// Jobs that have not built a payload yet are skipped. The payloads are not finalized, so
+    /// this is cheap for the service but the payloads are only meant to be inspected.
+    pub async fn built_payloads(&self) -> Vec<(PayloadId, Engine::BuiltPayload)> {
+        let (tx, rx) = oneshot::channel();
+        if self.to_service.send(PayloadServiceCommand::BuiltPayloads(tx)).is_err() {
+            return Vec::new()
+        }
+        rx.await.unwrap_or_default()
+    }
+
     /// Returns the payload attributes associated with the given identifier.
     ///
     /// Note: this returns the attributes of the payload and does not resolve the job.
@@ -161,6 +179,64 @@ where
     }
 }
 
+/// The default maximum number of concurrent payload jobs.
+pub const DEFAULT_MAX_PAYLOAD_JOBS: usize = 32;
+
+/// How often the [PayloadBuilderService] checks for expired jobs and payloads.
+const STALE_JOBS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Settings for the lifecycle of the jobs and payloads of the [PayloadBuilderService].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadJobsConfig {
+    /// The maximum number of concurrent payload jobs.
+    ///
+    /// If a new job would exceed the limit, the oldest job is evicted.
+    max_jobs: usize,
+    /// How long resolved payloads and jobs are kept after they were resolved.
+    ///
+    /// Resolved payloads are served again if the consensus layer requests them again within this
+    /// duration.
+    resolved_retention: Duration,
+}
+
+impl PayloadJobsConfig {
+    /// Sets the maximum number of concurrent payload jobs.
+    pub fn max_jobs(mut self, max_jobs: usize) -> Self {
+        self.max_jobs = max_jobs.max(1);
+        self
+    }
+
+    /// Sets how long resolved payloads and jobs are kept after they were resolved.
+    pub fn resolved_retention(mut self, resolved_retention: Duration) -> Self {
+        self.resolved_retention = resolved_retention;
+        self
+    }
+}
+
+impl Default for PayloadJobsConfig {
+    fn default() -> Self {
+        Self { max_jobs: DEFAULT_MAX_PAYLOAD_JOBS, resolved_retention: SLOT_DURATION }
+    }
+}
+
+/// A payload job that is tracked by the [PayloadBuilderService].
+#[derive(Debug)]
+struct ActivePayloadJob<Job> {
+    job: Job,
+    id: PayloadId,
+    created_at: Instant,
+    /// When the job was resolved, if it was kept alive afterwards.
+    resolved_at: Option<Instant>,
+}
+
+/// A payload that was resolved and is kept for repeated requests.
+#[derive(Debug)]
+struct ResolvedPayload<Payload> {
+    id: PayloadId,
+    payload: Payload,
+    resolved_at: Instant,
+}
+
 /// A service that manages payload building tasks.
 ///
 /// This type is an endless future that manages the building of payloads.
@@ -180,7 +256,17 @@ where
     /// The type that knows how to create new payloads.
     generator: Gen,
     /// All active payload jobs.
-    payload_jobs: Vec<(Gen::Job, PayloadId)>,
+    payload_jobs: Vec<ActivePayloadJob<Gen::Job>>,
+    /// Payloads that were resolved, oldest first.
+    resolved_payloads: VecDeque<ResolvedPayload<Engine::BuiltPayload>>,
+    /// Sender half for resolved payloads, used by the futures returned by resolve.
+    resolved_tx: mpsc::UnboundedSender<(PayloadId, Engine::BuiltPayload)>,
+    /// Receiver half for resolved payloads.
+    resolved_rx: mpsc::UnboundedReceiver<(PayloadId, Engine::BuiltPayload)>,
+    /// Lifecycle settings for jobs and payloads.
+    config: PayloadJobsConfig,
+    /// Interval for checking for expired jobs and payloads, created on first poll.
+    stale_jobs_interval: Option<Interval>,
     /// Copy of the sender half, so new [`PayloadBuilderHandle`] can be created on demand.
     service_tx: mpsc::UnboundedSender<PayloadServiceCommand<Engine>>,
     /// Receiver half of the command channel.
@@ -206,10 +292,26 @@ where
     /// This also takes a stream of chain events that will be forwarded to the generator to apply
     /// additional logic when new state is committed. See also [PayloadJobGenerator::on_new_state].
     pub fn new(generator: Gen, chain_events: St) -> (Self, PayloadBuilderHandle<Engine>) {
+        Self::with_config(generator, chain_events, PayloadJobsConfig::default())
+    }
+
+    /// Creates a new payload builder service with the given lifecycle settings for jobs and
+    /// payloads, see also [PayloadBuilderService::new].
+    pub fn with_config(
+        generator: Gen,
+        chain_events: St,
+        config: PayloadJobsConfig,
+    ) -> (Self, PayloadBuilderHandle<Engine>) {
         let (service_tx, command_rx) = mpsc::unbounded_channel();
+        let (resolved_tx, resolved_rx) = mpsc::unbounded_channel();
         let service = Self {
             generator,
             payload_jobs: Vec::new(),
+            resolved_payloads: VecDeque::new(),
+            resolved_tx,
+            resolved_rx,
+            config,
+            stale_jobs_interval: None,
             service_tx,
             command_rx: UnboundedReceiverStream::new(command_rx),
             metrics: Default::default(),
@@ -227,7 +329,79 @@ where
 
     /// Returns true if the given payload is currently being built.
     fn contains_payload(&self, id: PayloadId) -> bool {
-        self.payload_jobs.iter().any(|(_, job_id)| *job_id == id)
+        self.payload_jobs.iter().any(|job| job.id == id)
+    }
+
+    /// Returns the stored payload for the given identifier, if it was resolved.
+    fn resolved_payload(&self, id: PayloadId) -> Option<&Engine::BuiltPayload> {
+        self.resolved_payloads.iter().find(|resolved| resolved.id == id).map(|r| &r.payload)
+    }
+
+    /// Stores a resolved payload, replacing a previously stored payload with the same identifier.
+    fn on_resolved_payload(&mut self, id: PayloadId, payload: Engine::BuiltPayload) {
+        self.resolved_payloads.retain(|resolved| resolved.id != id);
+        if self.resolved_payloads.len() >= self.config.max_jobs {
+            self.resolved_payloads.pop_front();
+        }
+        self.resolved_payloads.push_back(ResolvedPayload {
+            id,
+            payload,
+            resolved_at: Instant::now(),
+        });
+        self.metrics.set_stored_payloads(self.resolved_payloads.len());
+    }
+
+    /// Drops resolved payloads and resolved jobs that are kept alive once their retention period
+    /// has passed.
+    fn evict_expired(&mut self, now: Instant) {
+        let retention = self.config.resolved_retention;
+        while self
+            .resolved_payloads
+            .front()
+            .is_some_and(|resolved| now.duration_since(resolved.resolved_at) >= retention)
+        {
+            self.resolved_payloads.pop_front();
+        }
+        self.metrics.set_stored_payloads(self.resolved_payloads.len());
+
+        let jobs = self.payload_jobs.len();
+        self.payload_jobs.retain(|job| {
+            let expired = job
+                .resolved_at
+                .is_some_and(|resolved_at| now.duration_since(resolved_at) >= retention);
+            if expired {
+                trace!(id=%job.id, "dropping resolved payload job after retention period");
+            }
+            !expired
+        });
+        if self.payload_jobs.len() != jobs {
+            self.metrics.set_active_jobs(self.payload_jobs.len());
+        }
+    }
+
+    /// Evicts jobs until a new job can be added without exceeding the maximum number of jobs.
+    ///
+    /// Resolved jobs are evicted first, then the oldest unresolved jobs.
+    fn make_room_for_job(&mut self) {
+        while self.payload_jobs.len() >= self.config.max_jobs {
+            let Some(idx) = self
+                .payload_jobs
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, job)| (job.resolved_at.is_none(), job.created_at))
+                .map(|(idx, _)| idx)
+            else {
+                return
+            };
+            let evicted = self.payload_jobs.remove(idx);
+            if evicted.resolved_at.is_none() {
+                warn!(id=%evicted.id, age=?evicted.created_at.elapsed(), "Evicting unresolved payload job, too many active jobs");
+                self.metrics.inc_evicted_unresolved_jobs();
+            } else {
+                trace!(id=%evicted.id, "evicting resolved payload job, too many active jobs");
+            }
+        }
+        self.metrics.set_active_jobs(self.payload_jobs.len());
     }
 
     /// Returns the best payload for the given identifier that has been built so far.
@@ -238,8 +412,9 @@ where
         let res = self
             .payload_jobs
             .iter()
-            .find(|(_, job_id)| *job_id == id)
-            .map(|(j, _)| j.best_payload().map(|p| p.into()));
+            .find(|job| job.id == id)
+            .map(|job| job.job.best_payload().and_then(ensure_state_root).map(|p| p.into()))
+            .or_else(|| self.resolved_payload(id).cloned().map(Ok));
         if let Some(Ok(ref best)) = res {
             self.metrics.set_best_revenue(best.block().number, f64::from(best.fees()));
         }
@@ -247,29 +422,54 @@ where
         res
     }
 
+    /// Returns the payloads the active jobs have built so far, without building or finalizing
+    /// any payload.
+    fn built_payloads(&self) -> Vec<(PayloadId, Engine::BuiltPayload)> {
+        self.payload_jobs
+            .iter()
+            .filter_map(|job| Some((job.id, job.job.built_payload()?.into())))
+            .collect()
+    }
+
     /// Returns the best payload for the given identifier that has been built so far and terminates
     /// the job if requested.
     fn resolve(&mut self, id: PayloadId) -> Option<PayloadFuture<Engine::BuiltPayload>> {
         trace!(%id, "resolving payload job");
 
-        let job = self.payload_jobs.iter().position(|(_, job_id)| *job_id == id)?;
-        let (fut, keep_alive) = self.payload_jobs[job].0.resolve();
+        let Some(job) = self.payload_jobs.iter().position(|job| job.id == id) else {
+            // the job may have been resolved before, in which case the stored payload is returned
+            let payload = self.resolved_payload(id)?.clone();
+            trace!(%id, "serving stored resolved payload");
+            return Some(Box::pin(futures_util::future::ready(Ok(payload))))
+        };
+        let (fut, keep_alive) = self.payload_jobs[job].job.resolve();
 
         if keep_alive == KeepPayloadJobAlive::No {
-            let (_, id) = self.payload_jobs.remove(job);
-            trace!(%id, "terminated resolved job");
+            let job = self.payload_jobs.remove(job);
+            self.metrics.set_active_jobs(self.payload_jobs.len());
+            trace!(id=%job.id, "terminated resolved job");
+        } else {
+            let job = &mut self.payload_jobs[job];
+            if job.resolved_at.is_none() {
+                job.resolved_at = Some(Instant::now());
+            }
         }
 
         // Since the fees will not be known until the payload future is resolved / awaited, we wrap
-        // the future in a new future that will update the metrics.
+        // the future in a new future that will update the metrics and store the payload.
         let resolved_metrics = self.metrics.clone();
+        let resolved_tx = self.resolved_tx.clone();
         let fut = async move {
-            let res = fut.await;
+            let res = fut.await.and_then(ensure_state_root);
             if let Ok(ref payload) = res {
                 resolved_metrics
                     .set_resolved_revenue(payload.block().number, f64::from(payload.fees()));
             }
-            res.map(|p| p.into())
+            let res = res.map(|p| p.into());
+            if let Ok(ref payload) = res {
+                let _ = resolved_tx.send((id, payload.clone()));
+            }
+            res
         };
 
         Some(Box::pin(fut))
@@ -291,8 +491,8 @@ where
         let attributes = self
             .payload_jobs
             .iter()
-            .find(|(_, job_id)| *job_id == id)
-            .map(|(j, _)| j.payload_attributes());
+            .find(|job| job.id == id)
+            .map(|job| job.job.payload_attributes());
 
         if attributes.is_none() {
             trace!(%id, "no matching payload job found to get attributes for");
@@ -321,14 +521,30 @@ where
                 this.generator.on_new_state(new_head);
             }
 
+            // store payloads that were resolved since the last poll
+            while let Poll::Ready(Some((id, payload))) = this.resolved_rx.poll_recv(cx) {
+                this.on_resolved_payload(id, payload);
+            }
+
+            // drop resolved payloads and jobs once their retention period has passed
+            let stale_jobs_interval = this.stale_jobs_interval.get_or_insert_with(|| {
+                let mut interval = tokio::time::interval(STALE_JOBS_CHECK_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                interval
+            });
+            if stale_jobs_interval.poll_tick(cx).is_ready() {
+                this.evict_expired(Instant::now());
+            }
+
             // we poll all jobs first, so we always have the latest payload that we can report if
             // requests
             // we don't care about the order of the jobs, so we can just swap_remove them
             for idx in (0..this.payload_jobs.len()).rev() {
-                let (mut job, id) = this.payload_jobs.swap_remove(idx);
+                let mut job = this.payload_jobs.swap_remove(idx);
+                let id = job.id;
 
                 // drain better payloads from the job
-                match job.poll_unpin(cx) {
+                match job.job.poll_unpin(cx) {
                     Poll::Ready(Ok(_)) => {
                         this.metrics.set_active_jobs(this.payload_jobs.len());
                         trace!(%id, "payload job finished");
@@ -340,7 +556,7 @@ where
                     }
                     Poll::Pending => {
                         // still pending, put it back
-                        this.payload_jobs.push((job, id));
+                        this.payload_jobs.push(job);
                     }
                 }
             }
@@ -365,7 +581,14 @@ where
                                     info!(%id, %parent, "New payload job created");
                                     this.metrics.inc_initiated_jobs();
                                     new_job = true;
-                                    this.payload_jobs.push((job, id));
+                                    this.make_room_for_job();
+                                    this.payload_jobs.push(ActivePayloadJob {
+                                        job,
+                                        id,
+                                        created_at: Instant::now(),
+                                        resolved_at: None,
+                                    });
+                                    this.metrics.set_active_jobs(this.payload_jobs.len());
                                 }
                                 Err(err) => {
                                     this.metrics.inc_failed_jobs();
@@ -381,6 +604,9 @@ where
                     PayloadServiceCommand::BestPayload(id, tx) => {
                         let _ = tx.send(this.best_payload(id));
                     }
+                    PayloadServiceCommand::BuiltPayloads(tx) => {
+                        let _ = tx.send(this.built_payloads());
+                    }
                     PayloadServiceCommand::PayloadAttributes(id, tx) => {
                         let _ = tx.send(this.payload_attributes(id));
                     }
@@ -397,6 +623,18 @@ where
     }
 }
 
+/// Rejects a payload that is handed out before its deferred state root was computed.
+///
+/// Builders that defer the state root build candidates with a zero placeholder root, which is
+/// never the root of an actual state.
+fn ensure_state_root<P: BuiltPayload>(payload: P) -> Result<P, PayloadBuilderError> {
+    let block = payload.block();
+    if block.state_root.is_zero() {
+        return Err(PayloadBuilderError::MissingStateRoot(block.hash()))
+    }
+    Ok(payload)
+}
+
 // TODO: make generic over built payload type
 type PayloadFuture<P> = Pin<Box<dyn Future<Output = Result<P, PayloadBuilderError>> + Send + Sync>>;
 
@@ -412,6 +650,8 @@ pub enum PayloadServiceCommand<Engine: EngineTypes> {
         PayloadId,
         oneshot::Sender<Option<Result<Engine::BuiltPayload, PayloadBuilderError>>>,
     ),
+    /// Get the payloads the active jobs have built so far
+    BuiltPayloads(oneshot::Sender<Vec<(PayloadId, Engine::BuiltPayload)>>),
     /// Get the payload attributes for the given payload
     PayloadAttributes(
         PayloadId,
@@ -433,6 +673,9 @@ where
             PayloadServiceCommand::BestPayload(f0, f1) => {
                 f.debug_tuple("BestPayload").field(&f0).field(&f1).finish()
             }
+            PayloadServiceCommand::BuiltPayloads(f0) => {
+                f.debug_tuple("BuiltPayloads").field(&f0).finish()
+            }
             PayloadServiceCommand::PayloadAttributes(f0, f1) => {
                 f.debug_tuple("PayloadAttributes").field(&f0).field(&f1).finish()
             }
@@ -32,6 +32,15 @@ pub trait PayloadJob: Future<Output = Result<(), PayloadBuilderError>> + Send +
     /// Note: This is never called by the CL.
     fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError>;
 
+    /// Returns the best payload that has been built so far as is, if any.
+    ///
+    /// Unlike [`PayloadJob::best_payload`] this neither builds an empty payload nor finalizes the
+    /// payload, so it's cheap but the payload may not be complete, e.g. its state root may not be
+    /// computed yet. It's only meant for inspecting the transactions of the payload.
+    fn built_payload(&self) -> Option<Self::BuiltPayload> {
+        None
+    }
+
     /// Returns the payload attributes for the payload being built.
     fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError>;
 
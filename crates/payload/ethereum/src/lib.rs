@@ -13,11 +13,20 @@ pub use builder::*;
 #[cfg(not(feature = "optimism"))]
 mod builder {
     use reth_basic_payload_builder::{
-        commit_withdrawals, is_better_payload, pre_block_beacon_root_contract_call, BuildArguments,
-        BuildOutcome, PayloadBuilder, PayloadConfig, WithdrawalsOutcome,
+        cached_pre_block_beacon_root_contract_call, commit_withdrawals, constrained_score,
+        is_better_scored_payload, order_by_profit_per_gas, pre_block_beacon_root_contract_call,
+        BlockCandidate, BlockConstraints, BlockScorer, BuildArguments, BuildOutcome, Cancelled,
+        ConstraintRegistry, ConstraintTracker, FeeScorer, OrderingAlgorithm, PayloadBuilder,
+        PayloadConfig, PolicyError, SimulatedTransaction, ValidatedSegment, WithdrawalsOutcome,
+        COINBASE_PAYMENT_GAS,
+    };
+    use reth_consensus_common::validation::{
+        validate_block_standalone, validate_header_regarding_parent, validate_header_standalone,
     };
     use reth_payload_builder::{
-        error::PayloadBuilderError, EthBuiltPayload, EthPayloadBuilderAttributes,
+        error::PayloadBuilderError, AcceptedBid, BidRejection, EthBuiltPayload,
+        EthPayloadBuilderAttributes, RejectedBid, TopOfBlockAccounting, TopOfBlockAuction,
+        WinningBid,
     };
     use reth_primitives::{
         constants::{
@@ -26,22 +35,114 @@ mod builder {
         eip4844::calculate_excess_blob_gas,
         proofs,
         revm::{compat::into_reth_log, env::tx_env_with_recovered},
-        Block, Header, IntoRecoveredTransaction, Receipt, Receipts, EMPTY_OMMER_ROOT_HASH, U256,
+        Block, ChainSpec, Header, IntoRecoveredTransaction, Receipt, Receipts, B256,
+        EMPTY_OMMER_ROOT_HASH, U256,
+    };
+    use reth_provider::{BundleStateWithReceipts, ProviderError, StateProviderFactory};
+    use reth_revm::{conflict_graph::ReadWriteSet, database::StateProviderDatabase, EvmConfig};
+    use reth_tracing::otlp;
+    use reth_transaction_pool::{
+        BestTransactions, PoolTransaction, TransactionPool, ValidPoolTransaction,
     };
-    use reth_provider::{BundleStateWithReceipts, StateProviderFactory};
-    use reth_revm::database::StateProviderDatabase;
-    use reth_transaction_pool::TransactionPool;
+    use reth_trie::slo::{RootComputation, RootSloTracker};
     use revm::{
         db::states::bundle_state::BundleRetention,
-        primitives::{EVMError, Env, InvalidTransaction, ResultAndState},
-        DatabaseCommit, State,
+        primitives::{BlockEnv, CfgEnv, EVMError, Env, InvalidTransaction, ResultAndState},
+        Database, DatabaseCommit, DatabaseRef, State,
     };
-    use tracing::{debug, trace, warn};
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+    use tracing::{debug, debug_span, trace, warn};
 
     /// Ethereum payload builder
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-    #[non_exhaustive]
-    pub struct EthereumPayloadBuilder;
+    ///
+    /// Picks among the blocks built for a payload with a [BlockScorer], by default the
+    /// [FeeScorer], and honors the [BlockConstraints] submitted to its [ConstraintRegistry]. The
+    /// transactions of the pool are ordered with an [OrderingAlgorithm], by default in the order
+    /// of the pool. If it has a [TopOfBlockAuction], the highest valid bid is placed at the top of
+    /// the block.
+    #[derive(Debug, Clone)]
+    pub struct EthereumPayloadBuilder {
+        /// Scores the candidate blocks.
+        scorer: Arc<dyn BlockScorer>,
+        /// Whether the state root is only computed for the payload the job resolves to.
+        defer_state_root: bool,
+        /// The tracker the latency of state root computations is recorded to.
+        root_slo: Option<Arc<RootSloTracker>>,
+        /// The constraints on the transactions of the built blocks.
+        constraints: Option<Arc<ConstraintRegistry>>,
+        /// The customizations of the EVM the transactions are executed with.
+        evm_config: EvmConfig,
+        /// How the transactions of the pool are ordered.
+        ordering: OrderingAlgorithm,
+        /// The bids for the top of the built blocks.
+        top_of_block_auction: Option<Arc<TopOfBlockAuction>>,
+    }
+
+    impl EthereumPayloadBuilder {
+        /// Creates a new payload builder that picks among candidate blocks with the given scorer.
+        pub fn with_scorer(scorer: impl BlockScorer + 'static) -> Self {
+            Self {
+                scorer: Arc::new(scorer),
+                defer_state_root: false,
+                root_slo: None,
+                constraints: None,
+                evm_config: EvmConfig::default(),
+                ordering: OrderingAlgorithm::default(),
+                top_of_block_auction: None,
+            }
+        }
+
+        /// Sets whether the state root is only computed for the payload the job resolves to.
+        ///
+        /// Candidates are built and scored with a placeholder state root, which saves computing
+        /// the root for every candidate that is replaced by a better one, at the cost of computing
+        /// it when the consensus layer requests the payload.
+        pub fn with_deferred_state_root(mut self, defer_state_root: bool) -> Self {
+            self.defer_state_root = defer_state_root;
+            self
+        }
+
+        /// Sets the tracker the latency of state root computations is recorded to.
+        pub fn with_root_slo(mut self, root_slo: Arc<RootSloTracker>) -> Self {
+            self.root_slo = Some(root_slo);
+            self
+        }
+
+        /// Sets the registry the constraints on the transactions of the built blocks are looked up
+        /// in, by the parent of the payload.
+        pub fn with_constraints(mut self, constraints: Arc<ConstraintRegistry>) -> Self {
+            self.constraints = Some(constraints);
+            self
+        }
+
+        /// Sets the customizations of the EVM the transactions are executed with.
+        pub fn with_evm_config(mut self, evm_config: EvmConfig) -> Self {
+            self.evm_config = evm_config;
+            self
+        }
+
+        /// Sets how the transactions of the pool are ordered.
+        pub fn with_ordering(mut self, ordering: OrderingAlgorithm) -> Self {
+            self.ordering = ordering;
+            self
+        }
+
+        /// Sets the auction the bids for the top of the built blocks are looked up in, by the
+        /// parent and timestamp of the payload.
+        pub fn with_top_of_block_auction(mut self, auction: Arc<TopOfBlockAuction>) -> Self {
+            self.top_of_block_auction = Some(auction);
+            self
+        }
+    }
+
+    impl Default for EthereumPayloadBuilder {
+        fn default() -> Self {
+            Self::with_scorer(FeeScorer::default())
+        }
+    }
 
     // Default implementation of [PayloadBuilder] for unit type
     impl<Pool, Client> PayloadBuilder<Pool, Client> for EthereumPayloadBuilder
@@ -56,7 +157,52 @@ mod builder {
             &self,
             args: BuildArguments<Pool, Client, EthPayloadBuilderAttributes, EthBuiltPayload>,
         ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError> {
-            default_ethereum_payload_builder(args)
+            scored_ethereum_payload_builder(
+                args,
+                self.scorer.as_ref(),
+                self.defer_state_root,
+                self.root_slo.as_deref(),
+                self.constraints.as_deref(),
+                &self.evm_config,
+                self.ordering,
+                self.top_of_block_auction.as_deref(),
+            )
+        }
+
+        fn finalize_payload(
+            &self,
+            client: &Client,
+            payload: EthBuiltPayload,
+        ) -> Result<EthBuiltPayload, PayloadBuilderError> {
+            let Some(state) = payload.deferred_state() else { return Ok(payload) };
+
+            let parent_hash = payload.block().parent_hash;
+            let root_timer = self
+                .root_slo
+                .as_ref()
+                .map(|root_slo| root_slo.start(RootComputation::Building, payload.block().number));
+            // the root is part of the trace of the included transactions that are traced
+            let _root_spans = payload
+                .block()
+                .body
+                .iter()
+                .filter_map(|tx| {
+                    let trace = otlp::transaction_trace(&tx.hash.0)?;
+                    let span = debug_span!(target: "payload_builder", "state_root", id=%payload.id(), deferred = true);
+                    trace.set_as_parent(&span);
+                    Some(span)
+                })
+                .collect::<Vec<_>>();
+            let state_root = client.state_by_block_hash(parent_hash)?.state_root(state).map_err(|err| {
+                warn!(target: "payload_builder", %parent_hash, ?err, "failed to calculate deferred state root");
+                err
+            })?;
+            if let Some(root_timer) = root_timer {
+                root_timer.finish();
+            }
+            debug!(target: "payload_builder", id=%payload.id(), %state_root, "calculated deferred state root");
+
+            Ok(payload.with_state_root(state_root))
         }
 
         fn build_empty_payload(
@@ -159,6 +305,58 @@ mod builder {
     pub fn default_ethereum_payload_builder<Pool, Client>(
         args: BuildArguments<Pool, Client, EthPayloadBuilderAttributes, EthBuiltPayload>,
     ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError>
+    where
+        Client: StateProviderFactory,
+        Pool: TransactionPool,
+    {
+        scored_ethereum_payload_builder(
+            args,
+            &FeeScorer::default(),
+            false,
+            None,
+            None,
+            &EvmConfig::default(),
+            OrderingAlgorithm::default(),
+            None,
+        )
+    }
+
+    /// Constructs an Ethereum transaction payload using the best transactions from the pool, and
+    /// keeps it only if it scores higher than the best payload according to the given
+    /// [BlockScorer].
+    ///
+    /// If `defer_state_root` is set, the payload has a placeholder state root that has to be
+    /// computed with [PayloadBuilder::finalize_payload] before it is handed out. Otherwise the
+    /// latency of the state root computation is recorded to `root_slo`, if set.
+    ///
+    /// If `constraints` has [BlockConstraints] for the parent of the payload, the top of block
+    /// transactions are executed first and the other required transactions right after them,
+    /// before the best transactions of the pool. Transactions of bundles excluded by an included
+    /// transaction are skipped, and the score is ranked by the number of unsatisfied constraints
+    /// with [constrained_score].
+    ///
+    /// If `auction` has bids for the payload, they are validated on the parent state from the
+    /// highest to the lowest value, and the transactions of the first valid bid are placed at the
+    /// top of the block, before the required transactions. A bid is valid if all of its
+    /// transactions succeed and they pay the fee recipient at least the value of the bid. Each bid
+    /// is validated once per job, the outcomes are cached in the
+    /// [TopOfBlockCache](reth_basic_payload_builder::TopOfBlockCache) of the config. The auction
+    /// is skipped if the constraints have top of block transactions. Its outcome is attached to
+    /// the payload as [TopOfBlockAccounting].
+    ///
+    /// The transactions are executed with the customizations of the EVM of `evm_config`, and the
+    /// transactions of the pool are ordered with `ordering`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn scored_ethereum_payload_builder<Pool, Client>(
+        args: BuildArguments<Pool, Client, EthPayloadBuilderAttributes, EthBuiltPayload>,
+        scorer: &dyn BlockScorer,
+        defer_state_root: bool,
+        root_slo: Option<&RootSloTracker>,
+        constraints: Option<&ConstraintRegistry>,
+        evm_config: &EvmConfig,
+        ordering: OrderingAlgorithm,
+        auction: Option<&TopOfBlockAuction>,
+    ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError>
     where
         Client: StateProviderFactory,
         Pool: TransactionPool,
@@ -171,15 +369,22 @@ mod builder {
             .with_database_ref(cached_reads.as_db(&state))
             .with_bundle_update()
             .build();
+
+        // apply eip-4788 pre block contract call, only executed by the first build of the job
+        cached_pre_block_beacon_root_contract_call(&mut db, &config)?;
+
         let extra_data = config.extra_data();
         let PayloadConfig {
             initialized_block_env,
-            initialized_cfg,
+            mut initialized_cfg,
             parent_block,
             attributes,
             chain_spec,
+            policy,
+            top_of_block: validated_bids,
             ..
         } = config;
+        evm_config.configure_cfg(&mut initialized_cfg);
 
         debug!(target: "payload_builder", id=%attributes.id, parent_hash = ?parent_block.hash, parent_number = parent_block.number, "building new payload");
         let mut cumulative_gas_used = 0;
@@ -187,27 +392,153 @@ mod builder {
         let block_gas_limit: u64 = initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
         let base_fee = initialized_block_env.basefee.to::<u64>();
 
+        let coinbase_payment = policy.as_ref().and_then(|policy| policy.coinbase_payment.as_ref());
+        // the gas of the coinbase payment is kept free for the end of the block
+        let tx_gas_limit = if coinbase_payment.is_some() {
+            block_gas_limit.saturating_sub(COINBASE_PAYMENT_GAS)
+        } else {
+            block_gas_limit
+        };
+
+        let constraints = constraints.and_then(|constraints| constraints.get(&parent_block.hash));
+        let mut constraint_tracker =
+            ConstraintTracker::new(constraints.as_deref().unwrap_or(&BlockConstraints::default()));
+        // required transactions that are not in the pool are reported as unsatisfied constraints
+        let mut required_txs = constraints
+            .iter()
+            .flat_map(|constraints| constraints.required())
+            .filter_map(|hash| pool.get(hash))
+            .collect::<Vec<_>>()
+            .into_iter();
+
         let mut executed_txs = Vec::new();
+        // the spans of the included transactions that are traced, open until the block is built
+        let mut traced_txs = Vec::new();
         let mut best_txs = pool.best_transactions_with_base_fee(base_fee);
+        // the simulated transactions of the pool in their new order, followed by the rest of the
+        // best transactions
+        let mut ordered_txs = match ordering {
+            OrderingAlgorithm::Pool => Vec::new(),
+            OrderingAlgorithm::ConflictAware { time_budget } => {
+                let simulated = simulate_best_transactions(
+                    StateProviderDatabase::new(&state_provider),
+                    best_txs.as_mut(),
+                    &initialized_cfg,
+                    &initialized_block_env,
+                    evm_config,
+                    tx_gas_limit,
+                    time_budget,
+                    &cancel,
+                )?;
+                trace!(target: "payload_builder", id=%attributes.id, simulated = simulated.len(), "ordering simulated transactions");
+                order_by_profit_per_gas(simulated)
+            }
+        }
+        .into_iter();
 
         let mut total_fees = U256::ZERO;
 
         let block_number = initialized_block_env.number.to::<u64>();
 
-        // apply eip-4788 pre block contract call
-        pre_block_beacon_root_contract_call(
-            &mut db,
-            &chain_spec,
-            block_number,
-            &initialized_cfg,
-            &initialized_block_env,
-            &attributes,
-        )?;
+        let coinbase = initialized_block_env.coinbase;
+        let coinbase_balance_before =
+            db.basic(coinbase)?.map(|acc| acc.balance).unwrap_or_default();
 
         let mut receipts = Vec::new();
-        while let Some(pool_tx) = best_txs.next() {
+
+        // the top of block transactions of the constraints take precedence over any bid
+        let bids = auction
+            .filter(|_| constraints.as_ref().map_or(true, |c| c.top_of_block.is_empty()))
+            .map(|auction| auction.bids(parent_block.hash, attributes.timestamp))
+            .unwrap_or_default();
+        let mut top_of_block = None;
+        if !bids.is_empty() {
+            let mut accounting = TopOfBlockAccounting { bids: bids.len(), ..Default::default() };
+            let mut winner = None;
+            for bid in &bids {
+                let bid_hash = bid.hash;
+                // every bid is only validated by the first build of the job that sees it
+                let validation = match validated_bids.get(&bid_hash) {
+                    Some(validation) => validation,
+                    None => {
+                        if cancel.is_cancelled() {
+                            return Ok(BuildOutcome::Cancelled)
+                        }
+                        let validation = validate_top_of_block_bid(
+                            StateProviderDatabase::new(&state_provider),
+                            &chain_spec,
+                            &initialized_cfg,
+                            &initialized_block_env,
+                            &attributes,
+                            evm_config,
+                            tx_gas_limit,
+                            bid,
+                        )?
+                        .map(Arc::new);
+                        validated_bids.insert(bid_hash, validation.clone());
+                        validation
+                    }
+                };
+                match validation {
+                    Ok(segment) => {
+                        accounting.winner = Some(segment.bid.clone());
+                        winner = Some((bid, segment));
+                        break
+                    }
+                    Err(reason) => {
+                        trace!(target: "payload_builder", id=%attributes.id, bid=%bid_hash, %reason, "rejected top of block bid");
+                        accounting.rejected.push(RejectedBid {
+                            bid_hash,
+                            bidder: bid.bidder,
+                            value: bid.bid.value,
+                            reason,
+                        })
+                    }
+                }
+            }
+
+            // the segment was validated on the state the block starts with, so its state changes
+            // are committed instead of executing it again
+            if let Some((bid, segment)) = winner {
+                for (tx, (result, state)) in bid.bid.transactions.iter().zip(&segment.executed) {
+                    // changed accounts have to be loaded before their changes can be committed
+                    for address in state.keys() {
+                        db.basic(*address)?;
+                    }
+                    db.commit(state.clone());
+
+                    let gas_used = result.gas_used();
+                    cumulative_gas_used += gas_used;
+                    receipts.push(Receipt {
+                        tx_type: tx.tx_type(),
+                        success: true,
+                        cumulative_gas_used,
+                        logs: result.logs().into_iter().map(into_reth_log).collect(),
+                    });
+                    let miner_fee = tx
+                        .effective_tip_per_gas(Some(base_fee))
+                        .expect("fee is always valid; execution succeeded");
+                    total_fees += U256::from(miner_fee) * U256::from(gas_used);
+                    constraint_tracker.include(tx.hash);
+                    executed_txs.push(tx.clone().into_signed());
+                }
+            }
+            debug!(target: "payload_builder", id=%attributes.id, bids = accounting.bids, rejected = accounting.rejected.len(), winner = ?accounting.winner, "auctioned top of block");
+            top_of_block = Some(accounting);
+        }
+
+        while let Some((pool_tx, required)) = required_txs
+            .next()
+            .map(|tx| (tx, true))
+            .or_else(|| ordered_txs.next().or_else(|| best_txs.next()).map(|tx| (tx, false)))
+        {
+            if !constraint_tracker.is_allowed(pool_tx.hash()) {
+                // already included as a required transaction or part of an excluded bundle
+                continue
+            }
+
             // ensure we still have capacity for this transaction
-            if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
+            if cumulative_gas_used + pool_tx.gas_limit() > tx_gas_limit {
                 // we can't fit this transaction into the block, so we need to mark it as invalid
                 // which also removes all dependent transaction from the iterator before we can
                 // continue
@@ -238,6 +569,14 @@ mod builder {
                 }
             }
 
+            // the execution and inclusion of a transaction that was submitted in a traced request
+            // are part of the trace of the request
+            let tx_span = otlp::transaction_trace(&tx.hash.0).map(|trace| {
+                let span = debug_span!(target: "payload_builder", "include_transaction", id=%attributes.id, block_number, tx_hash=%tx.hash);
+                trace.set_as_parent(&span);
+                span
+            });
+
             // Configure the environment for the block.
             let env = Env {
                 cfg: initialized_cfg.clone(),
@@ -248,7 +587,7 @@ mod builder {
             let mut evm = revm::EVM::with_env(env);
             evm.database(&mut db);
 
-            let ResultAndState { result, state } = match evm.transact() {
+            let ResultAndState { result, state } = match evm_config.transact(&mut evm) {
                 Ok(res) => res,
                 Err(err) => {
                     match err {
@@ -256,6 +595,10 @@ mod builder {
                             if matches!(err, InvalidTransaction::NonceTooLow { .. }) {
                                 // if the nonce is too low, we can skip this transaction
                                 trace!(target: "payload_builder", ?err, ?tx, "skipping nonce too low transaction");
+                            } else if required {
+                                // the required transaction may still be executable after its
+                                // ancestors, so the best transactions can yield it again
+                                trace!(target: "payload_builder", ?err, ?tx, "skipping required transaction");
                             } else {
                                 // if the transaction is invalid, we can skip it and all of its
                                 // descendants
@@ -293,12 +636,12 @@ mod builder {
             cumulative_gas_used += gas_used;
 
             // Push transaction changeset and calculate header bloom filter for receipt.
-            receipts.push(Some(Receipt {
+            receipts.push(Receipt {
                 tx_type: tx.tx_type(),
                 success: result.is_success(),
                 cumulative_gas_used,
                 logs: result.logs().into_iter().map(into_reth_log).collect(),
-            }));
+            });
 
             // update add to total fees
             let miner_fee = tx
@@ -307,11 +650,73 @@ mod builder {
             total_fees += U256::from(miner_fee) * U256::from(gas_used);
 
             // append transaction to the list of executed transactions
+            constraint_tracker.include(tx.hash);
             executed_txs.push(tx.into_signed());
+            traced_txs.extend(tx_span);
+        }
+
+        // pay the suggested fee recipient its share of the profit of the fee recipient
+        if let Some(payment) = coinbase_payment {
+            let coinbase_account = db.basic(coinbase)?.unwrap_or_default();
+            let value = payment
+                .value(coinbase_account.balance.saturating_sub(coinbase_balance_before), base_fee);
+            if value > U256::ZERO {
+                let tx = payment
+                    .transaction(
+                        chain_spec.chain().id(),
+                        coinbase_account.nonce,
+                        attributes.suggested_fee_recipient,
+                        value,
+                        base_fee,
+                    )
+                    .map_err(PayloadBuilderError::other)?;
+                let env = Env {
+                    cfg: initialized_cfg.clone(),
+                    block: initialized_block_env.clone(),
+                    tx: tx_env_with_recovered(&tx),
+                };
+                let mut evm = revm::EVM::with_env(env);
+                evm.database(&mut db);
+                let ResultAndState { result, state } = evm_config
+                    .transact(&mut evm)
+                    .map_err(PayloadBuilderError::EvmExecutionError)?;
+                if !result.is_success() {
+                    return Err(PayloadBuilderError::other(PolicyError::PaymentFailed))
+                }
+                db.commit(state);
+
+                cumulative_gas_used += result.gas_used();
+                receipts.push(Receipt {
+                    tx_type: tx.tx_type(),
+                    success: true,
+                    cumulative_gas_used,
+                    logs: result.logs().into_iter().map(into_reth_log).collect(),
+                });
+                trace!(target: "payload_builder", id=%attributes.id, %value, "added coinbase payment");
+                executed_txs.push(tx.into_signed());
+            }
+        }
+
+        let coinbase_balance_after = db.basic(coinbase)?.map(|acc| acc.balance).unwrap_or_default();
+        let unsatisfied_constraints =
+            constraints.as_ref().map_or(0, |constraints| constraints.unsatisfied(&executed_txs));
+        let mut score = scorer.score(&BlockCandidate {
+            transactions: &executed_txs,
+            receipts: &receipts,
+            gas_used: cumulative_gas_used,
+            fees: total_fees,
+            coinbase_diff: coinbase_balance_after.saturating_sub(coinbase_balance_before),
+            unsatisfied_constraints,
+        });
+        if constraints.is_some() {
+            if unsatisfied_constraints > 0 {
+                debug!(target: "payload_builder", id=%attributes.id, unsatisfied_constraints, "built block does not satisfy all constraints");
+            }
+            score = constrained_score(score, unsatisfied_constraints);
         }
 
         // check if we have a better block
-        if !is_better_payload(best_payload.as_ref(), total_fees) {
+        if !is_better_scored_payload(best_payload.as_ref(), score) {
             // can skip building the block
             return Ok(BuildOutcome::Aborted { fees: total_fees, cached_reads })
         }
@@ -325,14 +730,28 @@ mod builder {
 
         let bundle = BundleStateWithReceipts::new(
             db.take_bundle(),
-            Receipts::from_vec(vec![receipts]),
+            Receipts::from_vec(vec![receipts.into_iter().map(Some).collect()]),
             block_number,
         );
         let receipts_root = bundle.receipts_root_slow(block_number).expect("Number is in range");
         let logs_bloom = bundle.block_logs_bloom(block_number).expect("Number is in range");
 
-        // calculate the state root
-        let state_root = state_provider.state_root(&bundle)?;
+        // calculate the state root, unless it's deferred until the payload is picked
+        let state_root = if defer_state_root {
+            B256::ZERO
+        } else {
+            let _root_spans = traced_txs
+                .iter()
+                .map(|span| debug_span!(target: "payload_builder", parent: span, "state_root"))
+                .collect::<Vec<_>>();
+            let root_timer =
+                root_slo.map(|root_slo| root_slo.start(RootComputation::Building, block_number));
+            let state_root = state_provider.state_root(&bundle)?;
+            if let Some(root_timer) = root_timer {
+                root_timer.finish();
+            }
+            state_root
+        };
 
         // create the block header
         let transactions_root = proofs::calculate_transaction_root(&executed_txs);
@@ -391,11 +810,184 @@ mod builder {
         let sealed_block = block.seal_slow();
         debug!(target: "payload_builder", ?sealed_block, "sealed built block");
 
-        let mut payload = EthBuiltPayload::new(attributes.id, sealed_block, total_fees);
+        if policy.is_some() {
+            // the policy overrides header fields and appends the payment, so make sure the block
+            // is still valid
+            validate_header_standalone(&sealed_block.header, &chain_spec)
+                .and_then(|_| {
+                    validate_header_regarding_parent(
+                        &parent_block.header,
+                        &sealed_block.header,
+                        &chain_spec,
+                    )
+                })
+                .and_then(|_| validate_block_standalone(&sealed_block, &chain_spec))
+                .map_err(PayloadBuilderError::other)?;
+        }
+
+        let mut payload =
+            EthBuiltPayload::new(attributes.id, sealed_block, total_fees).with_score(score);
+        if defer_state_root {
+            payload = payload.with_deferred_state_root(bundle);
+        }
+        if let Some(accounting) = top_of_block {
+            payload = payload.with_top_of_block(accounting);
+        }
 
         // extend the payload with the blob sidecars from the executed txs
         payload.extend_sidecars(blob_sidecars);
 
         Ok(BuildOutcome::Better { payload, cached_reads })
     }
+
+    /// Validates a bid for the top of the block on the parent state, after the pre block contract
+    /// call, returning the accounting and the executed transactions of the segment if the bid is
+    /// valid and the reason otherwise.
+    #[allow(clippy::too_many_arguments)]
+    fn validate_top_of_block_bid<DB>(
+        db: DB,
+        chain_spec: &ChainSpec,
+        cfg: &CfgEnv,
+        block_env: &BlockEnv,
+        attributes: &EthPayloadBuilderAttributes,
+        evm_config: &EvmConfig,
+        gas_limit: u64,
+        accepted: &AcceptedBid,
+    ) -> Result<Result<ValidatedSegment, BidRejection>, PayloadBuilderError>
+    where
+        DB: DatabaseRef<Error = ProviderError>,
+    {
+        let bid = &accepted.bid;
+        // the gas limits are chosen by the bidder, so their sum may overflow
+        let segment_gas_limit =
+            bid.transactions.iter().try_fold(0u64, |total, tx| total.checked_add(tx.gas_limit()));
+        if segment_gas_limit.map_or(true, |segment_gas_limit| segment_gas_limit > gas_limit) {
+            return Ok(Err(BidRejection::GasLimitExceeded))
+        }
+
+        let mut db = State::builder().with_database_ref(db).build();
+        pre_block_beacon_root_contract_call(
+            &mut db,
+            chain_spec,
+            block_env.number.to::<u64>(),
+            cfg,
+            block_env,
+            attributes,
+        )?;
+
+        let coinbase = block_env.coinbase;
+        let coinbase_balance = db.basic(coinbase)?.map(|acc| acc.balance).unwrap_or_default();
+        let mut gas_used = 0;
+        let mut executed = Vec::with_capacity(bid.transactions.len());
+        for tx in &bid.transactions {
+            let env =
+                Env { cfg: cfg.clone(), block: block_env.clone(), tx: tx_env_with_recovered(tx) };
+            let mut evm = revm::EVM::with_env(env);
+            evm.database(&mut db);
+            let ResultAndState { result, state } = match evm_config.transact(&mut evm) {
+                Ok(res) => res,
+                Err(EVMError::Transaction(_)) => {
+                    return Ok(Err(BidRejection::InvalidTransaction(tx.hash)))
+                }
+                Err(err) => return Err(PayloadBuilderError::EvmExecutionError(err)),
+            };
+            if !result.is_success() {
+                return Ok(Err(BidRejection::Reverted(tx.hash)))
+            }
+            db.commit(state.clone());
+            gas_used += result.gas_used();
+            executed.push((result, state));
+        }
+
+        let paid = db
+            .basic(coinbase)?
+            .map(|acc| acc.balance)
+            .unwrap_or_default()
+            .saturating_sub(coinbase_balance);
+        if paid < bid.value {
+            return Ok(Err(BidRejection::InsufficientPayment { paid }))
+        }
+        let bid = WinningBid {
+            bid_hash: accepted.hash,
+            bidder: accepted.bidder,
+            value: bid.value,
+            payment: paid,
+            gas_used,
+            transactions: bid.transactions.len(),
+        };
+        Ok(Ok(ValidatedSegment { bid, executed }))
+    }
+
+    /// Simulates the best transactions of the pool one after another on the parent state, for the
+    /// [OrderingAlgorithm::ConflictAware] ordering.
+    ///
+    /// Simulation stops once the time budget elapsed, the job was cancelled, or the simulated
+    /// transactions use twice the given gas limit, since a block could not include more of them.
+    /// Like in the block, invalid transactions are skipped, with their descendants unless their
+    /// nonce is too low.
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_best_transactions<DB, T>(
+        db: DB,
+        best_txs: &mut dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>,
+        cfg: &CfgEnv,
+        block_env: &BlockEnv,
+        evm_config: &EvmConfig,
+        gas_limit: u64,
+        time_budget: Duration,
+        cancel: &Cancelled,
+    ) -> Result<Vec<SimulatedTransaction<Arc<ValidPoolTransaction<T>>>>, PayloadBuilderError>
+    where
+        DB: DatabaseRef<Error = ProviderError>,
+        T: PoolTransaction,
+    {
+        let started_at = Instant::now();
+        let coinbase = block_env.coinbase;
+        let mut db = State::builder().with_database_ref(db).build();
+
+        let mut simulated = Vec::new();
+        let mut simulated_gas = 0u64;
+        while started_at.elapsed() < time_budget &&
+            simulated_gas < gas_limit.saturating_mul(2) &&
+            !cancel.is_cancelled()
+        {
+            let Some(pool_tx) = best_txs.next() else { break };
+            let tx = pool_tx.to_recovered_transaction();
+            let coinbase_balance = db.basic(coinbase)?.map(|acc| acc.balance).unwrap_or_default();
+
+            let env =
+                Env { cfg: cfg.clone(), block: block_env.clone(), tx: tx_env_with_recovered(&tx) };
+            let mut evm = revm::EVM::with_env(env);
+            evm.database(&mut db);
+            let ResultAndState { result, state } = match evm_config.transact(&mut evm) {
+                Ok(res) => res,
+                Err(EVMError::Transaction(err)) => {
+                    if !matches!(err, InvalidTransaction::NonceTooLow { .. }) {
+                        best_txs.mark_invalid(&pool_tx);
+                    }
+                    continue
+                }
+                Err(err) => return Err(PayloadBuilderError::EvmExecutionError(err)),
+            };
+
+            let mut read_write_set = ReadWriteSet::from_state(&state);
+            read_write_set.exclude_account(coinbase);
+            db.commit(state);
+
+            let profit = db
+                .basic(coinbase)?
+                .map(|acc| acc.balance)
+                .unwrap_or_default()
+                .saturating_sub(coinbase_balance);
+            let gas_used = result.gas_used();
+            simulated_gas += gas_used;
+            simulated.push(SimulatedTransaction {
+                hash: tx.hash,
+                transaction: pool_tx,
+                gas_used,
+                profit,
+                read_write_set,
+            });
+        }
+        Ok(simulated)
+    }
 }
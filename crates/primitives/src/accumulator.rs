@@ -0,0 +1,268 @@
+//! Accumulator of the canonical headers and proofs of header inclusion.
+//!
+//! The canonical chain is split into epochs of [EPOCH_SIZE] blocks. A completed epoch is committed
+//! to by its [EpochAccumulator], the SSZ list of the [HeaderRecord]s of its blocks, and the roots
+//! of all completed epochs form the historical accumulator.
+//!
+//! Hashing follows the pre-merge accumulator of the Portal network, so the roots of pre-merge
+//! epochs match the epoch accumulators distributed by Portal clients.
+
+use crate::{BlockNumber, B256, U256};
+use once_cell::sync::Lazy;
+use reth_codecs::{main_codec, Compact};
+use sha2::{Digest, Sha256};
+use std::ops::RangeInclusive;
+
+/// The number of blocks in an epoch.
+pub const EPOCH_SIZE: u64 = 8192;
+
+/// The depth of the merkle tree of an [EpochAccumulator].
+const EPOCH_DEPTH: usize = 13;
+
+/// The maximum number of epochs of the historical accumulator.
+pub const MAX_HISTORICAL_EPOCHS: u64 = 131_072;
+
+/// The depth of the merkle tree of the historical accumulator.
+const HISTORICAL_EPOCHS_DEPTH: usize = 17;
+
+/// The roots of empty merkle trees, by depth.
+static ZERO_HASHES: Lazy<[B256; HISTORICAL_EPOCHS_DEPTH + 1]> = Lazy::new(|| {
+    let mut hashes = [B256::ZERO; HISTORICAL_EPOCHS_DEPTH + 1];
+    for depth in 1..hashes.len() {
+        hashes[depth] = hash_pair(hashes[depth - 1], hashes[depth - 1]);
+    }
+    hashes
+});
+
+/// Returns the epoch of the given block.
+pub const fn epoch_of(block_number: BlockNumber) -> u64 {
+    block_number / EPOCH_SIZE
+}
+
+/// Returns the blocks of the given epoch.
+pub const fn epoch_block_range(epoch: u64) -> RangeInclusive<BlockNumber> {
+    epoch * EPOCH_SIZE..=(epoch + 1) * EPOCH_SIZE - 1
+}
+
+/// The entry of a block in its [EpochAccumulator].
+#[main_codec]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HeaderRecord {
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The total difficulty of the chain up to and including the block.
+    pub total_difficulty: U256,
+}
+
+impl HeaderRecord {
+    /// Returns the SSZ hash tree root of the record.
+    pub fn tree_hash_root(&self) -> B256 {
+        hash_pair(self.block_hash, B256::from(self.total_difficulty.to_le_bytes::<32>()))
+    }
+}
+
+/// The records of all blocks of an epoch.
+#[main_codec]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EpochAccumulator {
+    /// The records of the blocks of the epoch, in order.
+    pub records: Vec<HeaderRecord>,
+}
+
+impl EpochAccumulator {
+    /// Returns the SSZ hash tree root of the epoch accumulator.
+    pub fn root(&self) -> B256 {
+        list_root(&self.leaves(), EPOCH_DEPTH)
+    }
+
+    /// Returns the proof of the record at the given index, ordered from the leaf to the root and
+    /// followed by the length of the list.
+    ///
+    /// Returns `None` if the index is out of bounds.
+    pub fn proof(&self, index: usize) -> Option<Vec<B256>> {
+        (index < self.records.len()).then(|| list_proof(&self.leaves(), EPOCH_DEPTH, index))
+    }
+
+    fn leaves(&self) -> Vec<B256> {
+        self.records.iter().map(HeaderRecord::tree_hash_root).collect()
+    }
+}
+
+/// Returns the root of the historical accumulator with the given epoch roots.
+pub fn historical_accumulator_root(epoch_roots: &[B256]) -> B256 {
+    list_root(epoch_roots, HISTORICAL_EPOCHS_DEPTH)
+}
+
+/// The proof of inclusion of a header in the historical accumulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderAccumulatorProof {
+    /// The number of the block.
+    pub block_number: BlockNumber,
+    /// The record of the block.
+    pub record: HeaderRecord,
+    /// The root of the epoch accumulator of the block.
+    pub epoch_root: B256,
+    /// The proof of the record in the epoch accumulator.
+    pub epoch_proof: Vec<B256>,
+    /// The root of the historical accumulator the proof was created for.
+    pub accumulator_root: B256,
+    /// The proof of the epoch root in the historical accumulator.
+    pub accumulator_proof: Vec<B256>,
+}
+
+impl HeaderAccumulatorProof {
+    /// Creates the proof of the given block from the accumulator of its epoch and the roots of all
+    /// completed epochs.
+    ///
+    /// Returns `None` if the block is not part of the accumulators.
+    pub fn new(
+        block_number: BlockNumber,
+        epoch_accumulator: &EpochAccumulator,
+        epoch_roots: &[B256],
+    ) -> Option<Self> {
+        let epoch = epoch_of(block_number) as usize;
+        let index = (block_number % EPOCH_SIZE) as usize;
+        if epoch >= epoch_roots.len() {
+            return None
+        }
+
+        Some(Self {
+            block_number,
+            record: *epoch_accumulator.records.get(index)?,
+            epoch_root: epoch_roots[epoch],
+            epoch_proof: epoch_accumulator.proof(index)?,
+            accumulator_root: historical_accumulator_root(epoch_roots),
+            accumulator_proof: list_proof(epoch_roots, HISTORICAL_EPOCHS_DEPTH, epoch),
+        })
+    }
+
+    /// Returns the epoch of the block.
+    pub const fn epoch(&self) -> u64 {
+        epoch_of(self.block_number)
+    }
+
+    /// Returns true if the proof proves the record against the given historical accumulator
+    /// root.
+    pub fn verify(&self, accumulator_root: B256) -> bool {
+        let index = (self.block_number % EPOCH_SIZE) as usize;
+        let epoch_root =
+            proof_root(self.record.tree_hash_root(), index, &self.epoch_proof, EPOCH_DEPTH);
+        epoch_root == Some(self.epoch_root) &&
+            proof_root(
+                self.epoch_root,
+                self.epoch() as usize,
+                &self.accumulator_proof,
+                HISTORICAL_EPOCHS_DEPTH,
+            ) == Some(accumulator_root)
+    }
+}
+
+/// Returns the SSZ hash tree root of a list with the given leaves and a limit of `2^depth` leaves.
+fn list_root(leaves: &[B256], depth: usize) -> B256 {
+    let root = merkle_layers(leaves, depth).pop().and_then(|layer| layer.first().copied());
+    hash_pair(root.unwrap_or(ZERO_HASHES[depth]), length_chunk(leaves.len()))
+}
+
+/// Returns the proof of the leaf at the given index of a list with a limit of `2^depth` leaves.
+fn list_proof(leaves: &[B256], depth: usize, mut index: usize) -> Vec<B256> {
+    let layers = merkle_layers(leaves, depth);
+    let mut proof = Vec::with_capacity(depth + 1);
+    for (level, layer) in layers.iter().take(depth).enumerate() {
+        proof.push(layer.get(index ^ 1).copied().unwrap_or(ZERO_HASHES[level]));
+        index >>= 1;
+    }
+    proof.push(length_chunk(leaves.len()));
+    proof
+}
+
+/// Returns the root of the list a proof of the leaf at the given index was created for.
+///
+/// Returns `None` if the proof is malformed.
+fn proof_root(leaf: B256, mut index: usize, proof: &[B256], depth: usize) -> Option<B256> {
+    if proof.len() != depth + 1 || index >> depth != 0 {
+        return None
+    }
+    let mut node = leaf;
+    for sibling in &proof[..depth] {
+        node = if index & 1 == 1 { hash_pair(*sibling, node) } else { hash_pair(node, *sibling) };
+        index >>= 1;
+    }
+    Some(hash_pair(node, proof[depth]))
+}
+
+/// Returns all layers of the merkle tree over the given leaves, from the leaves to the root.
+///
+/// Missing leaves are filled with the roots of empty subtrees.
+fn merkle_layers(leaves: &[B256], depth: usize) -> Vec<Vec<B256>> {
+    let mut layers = Vec::with_capacity(depth + 1);
+    layers.push(leaves.to_vec());
+    for level in 0..depth {
+        let next = layers[level]
+            .chunks(2)
+            .map(|pair| hash_pair(pair[0], pair.get(1).copied().unwrap_or(ZERO_HASHES[level])))
+            .collect();
+        layers.push(next);
+    }
+    layers
+}
+
+/// Returns the SSZ chunk of the length of a list.
+fn length_chunk(len: usize) -> B256 {
+    let mut chunk = B256::ZERO;
+    chunk[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    chunk
+}
+
+fn hash_pair(left: B256, right: B256) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from_slice(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn epoch_accumulator(epoch: u64) -> EpochAccumulator {
+        let records = epoch_block_range(epoch)
+            .map(|number| HeaderRecord {
+                block_hash: B256::with_last_byte(number as u8),
+                total_difficulty: U256::from(number),
+            })
+            .collect();
+        EpochAccumulator { records }
+    }
+
+    #[test]
+    fn empty_list_root() {
+        assert_eq!(historical_accumulator_root(&[]), hash_pair(ZERO_HASHES[17], B256::ZERO));
+    }
+
+    #[test]
+    fn header_proofs() {
+        let epochs = (0..3).map(epoch_accumulator).collect::<Vec<_>>();
+        let epoch_roots = epochs.iter().map(EpochAccumulator::root).collect::<Vec<_>>();
+        let root = historical_accumulator_root(&epoch_roots);
+
+        for block_number in [0, 1, EPOCH_SIZE - 1, EPOCH_SIZE, 2 * EPOCH_SIZE + 4711] {
+            let epoch = &epochs[epoch_of(block_number) as usize];
+            let proof = HeaderAccumulatorProof::new(block_number, epoch, &epoch_roots).unwrap();
+            assert_eq!(proof.epoch_proof.len(), EPOCH_DEPTH + 1);
+            assert_eq!(proof.accumulator_proof.len(), HISTORICAL_EPOCHS_DEPTH + 1);
+            assert_eq!(proof.accumulator_root, root);
+            assert!(proof.verify(root));
+
+            let mut tampered = proof.clone();
+            tampered.record.total_difficulty += U256::from(1);
+            assert!(!tampered.verify(root));
+
+            let mut moved = proof;
+            moved.block_number += 1;
+            assert!(!moved.verify(root));
+        }
+
+        // blocks of incomplete epochs are not part of the accumulator
+        assert!(HeaderAccumulatorProof::new(3 * EPOCH_SIZE, &epochs[0], &epoch_roots).is_none());
+    }
+}
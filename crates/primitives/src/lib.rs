@@ -15,6 +15,7 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 mod account;
+pub mod accumulator;
 pub mod basefee;
 mod block;
 mod chain;
@@ -3,7 +3,7 @@
 use crate::{
     constants::EMPTY_OMMER_ROOT_HASH,
     keccak256,
-    trie::{HashBuilder, Nibbles, TrieAccount},
+    trie::{nodes::LeafNode, HashBuilder, Nibbles, TrieAccount},
     Address, Header, Receipt, ReceiptWithBloom, ReceiptWithBloomRef, TransactionSigned, Withdrawal,
     B256,
 };
@@ -11,6 +11,18 @@ use alloy_primitives::U256;
 use alloy_rlp::Encodable;
 use bytes::{BufMut, BytesMut};
 use itertools::Itertools;
+use once_cell::sync::Lazy;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+/// The number of items from which the leaves of an ordered trie are hashed in parallel.
+static PARALLEL_ORDERED_TRIE_ROOT_THRESHOLD: Lazy<usize> =
+    Lazy::new(|| match rayon::current_num_threads() {
+        0..=1 => usize::MAX,
+        _ => 128,
+    });
+
+/// The minimum number of leaves hashed by a single task when hashing in parallel.
+const ORDERED_TRIE_LEAVES_PER_TASK: usize = 32;
 
 /// Adjust the index of an item for rlp encoding.
 pub const fn adjust_index_for_rlp(i: usize, len: usize) -> usize {
@@ -53,25 +65,112 @@ where
     hb.root()
 }
 
+/// Compute a trie root of the collection of items with a custom encoder, encoding and hashing the
+/// leaves in parallel for large collections.
+///
+/// The position of every leaf in the trie follows from the keys of its neighbors, so the leaves are
+/// encoded and hashed in chunks on the rayon pool. The leaf hashes are then added to the hash
+/// builder in order, which only has to hash the branch nodes above them.
+///
+/// Falls back to [ordered_trie_root_with_encoder] for small collections.
+pub fn ordered_trie_root_with_encoder_parallel<T, F>(items: &[T], encode: F) -> B256
+where
+    T: Sync,
+    F: Fn(&T, &mut dyn BufMut) + Sync,
+{
+    if items.len() < *PARALLEL_ORDERED_TRIE_ROOT_THRESHOLD {
+        return ordered_trie_root_with_encoder(items, encode)
+    }
+    parallel_ordered_trie_root(items, encode)
+}
+
+fn parallel_ordered_trie_root<T, F>(items: &[T], encode: F) -> B256
+where
+    T: Sync,
+    F: Fn(&T, &mut dyn BufMut) + Sync,
+{
+    let items_len = items.len();
+    // a single leaf is the root node itself
+    if items_len < 2 {
+        return ordered_trie_root_with_encoder(items, encode)
+    }
+
+    // the keys in trie order, with the index of their item
+    let keys = (0..items_len)
+        .map(|i| {
+            let index = adjust_index_for_rlp(i, items_len);
+            let mut index_buffer = Vec::with_capacity(9);
+            index.encode(&mut index_buffer);
+            (index, Nibbles::unpack(&index_buffer))
+        })
+        .collect::<Vec<_>>();
+
+    let leaves = keys
+        .par_iter()
+        .enumerate()
+        .with_min_len(ORDERED_TRIE_LEAVES_PER_TASK)
+        .map(|(i, (index, key))| {
+            // the leaf is a child of the branch where its key diverges from its neighbors
+            let preceding =
+                i.checked_sub(1).map_or(0, |prev| key.common_prefix_length(&keys[prev].1));
+            let succeeding = keys.get(i + 1).map_or(0, |(_, next)| key.common_prefix_length(next));
+            let depth = preceding.max(succeeding) + 1;
+
+            let mut value = Vec::new();
+            encode(&items[*index], &mut value);
+
+            let leaf_key = key.slice(depth..);
+            let mut node = Vec::new();
+            LeafNode::new(&leaf_key, &value).encode(&mut node);
+            // leaves shorter than a hash are embedded into their parent and left to the builder
+            if node.len() < B256::len_bytes() {
+                OrderedTrieLeaf::Value(value)
+            } else {
+                OrderedTrieLeaf::Hash(key.slice(..depth), keccak256(&node))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut hb = HashBuilder::default();
+    for ((_, key), leaf) in keys.into_iter().zip(leaves) {
+        match leaf {
+            OrderedTrieLeaf::Hash(path, hash) => hb.add_branch(path, hash, false),
+            OrderedTrieLeaf::Value(value) => hb.add_leaf(key, &value),
+        }
+    }
+
+    hb.root()
+}
+
+/// A leaf of an ordered trie, prepared for the hash builder.
+enum OrderedTrieLeaf {
+    /// The hash of the leaf node, stored at the given path.
+    Hash(Nibbles, B256),
+    /// The encoded value of a leaf node that is embedded into its parent.
+    Value(Vec<u8>),
+}
+
 /// Calculate a transaction root.
 ///
 /// `(rlp(index), encoded(tx))` pairs.
 pub fn calculate_transaction_root<T>(transactions: &[T]) -> B256
 where
-    T: AsRef<TransactionSigned>,
+    T: AsRef<TransactionSigned> + Sync,
 {
-    ordered_trie_root_with_encoder(transactions, |tx: &T, buf| tx.as_ref().encode_inner(buf, false))
+    ordered_trie_root_with_encoder_parallel(transactions, |tx: &T, buf| {
+        tx.as_ref().encode_inner(buf, false)
+    })
 }
 
 /// Calculates the root hash of the withdrawals.
 pub fn calculate_withdrawals_root(withdrawals: &[Withdrawal]) -> B256 {
-    ordered_trie_root(withdrawals)
+    ordered_trie_root_with_encoder_parallel(withdrawals, |withdrawal, buf| withdrawal.encode(buf))
 }
 
 /// Calculates the receipt root for a header.
 #[cfg(not(feature = "optimism"))]
 pub fn calculate_receipt_root(receipts: &[ReceiptWithBloom]) -> B256 {
-    ordered_trie_root_with_encoder(receipts, |r, buf| r.encode_inner(buf, false))
+    ordered_trie_root_with_encoder_parallel(receipts, |r, buf| r.encode_inner(buf, false))
 }
 
 /// Calculates the receipt root for a header.
@@ -98,12 +197,12 @@ pub fn calculate_receipt_root(
             })
             .collect::<Vec<_>>();
 
-        return ordered_trie_root_with_encoder(receipts.as_slice(), |r, buf| {
+        return ordered_trie_root_with_encoder_parallel(receipts.as_slice(), |r, buf| {
             r.encode_inner(buf, false)
         })
     }
 
-    ordered_trie_root_with_encoder(receipts, |r, buf| r.encode_inner(buf, false))
+    ordered_trie_root_with_encoder_parallel(receipts, |r, buf| r.encode_inner(buf, false))
 }
 
 /// Calculates the receipt root for a header for the reference type of [Receipt].
@@ -111,7 +210,7 @@ pub fn calculate_receipt_root(
 /// NOTE: Prefer [calculate_receipt_root] if you have log blooms memoized.
 #[cfg(not(feature = "optimism"))]
 pub fn calculate_receipt_root_ref(receipts: &[&Receipt]) -> B256 {
-    ordered_trie_root_with_encoder(receipts, |r, buf| {
+    ordered_trie_root_with_encoder_parallel(receipts, |r, buf| {
         ReceiptWithBloomRef::from(*r).encode_inner(buf, false)
     })
 }
@@ -142,12 +241,12 @@ pub fn calculate_receipt_root_ref(
             })
             .collect::<Vec<_>>();
 
-        return ordered_trie_root_with_encoder(&receipts, |r, buf| {
+        return ordered_trie_root_with_encoder_parallel(&receipts, |r, buf| {
             ReceiptWithBloomRef::from(r).encode_inner(buf, false)
         })
     }
 
-    ordered_trie_root_with_encoder(receipts, |r, buf| {
+    ordered_trie_root_with_encoder_parallel(receipts, |r, buf| {
         ReceiptWithBloomRef::from(*r).encode_inner(buf, false)
     })
 }
@@ -268,13 +367,41 @@ mod tests {
         constants::EMPTY_ROOT_HASH,
         hex_literal::hex,
         proofs::{calculate_receipt_root, calculate_transaction_root},
-        Address, Block, GenesisAccount, Log, Receipt, ReceiptWithBloom, TxType, B256, GOERLI,
-        HOLESKY, MAINNET, SEPOLIA, U256,
+        Address, Block, Bytes, GenesisAccount, Log, Receipt, ReceiptWithBloom, TxType, B256,
+        GOERLI, HOLESKY, MAINNET, SEPOLIA, U256,
     };
     use alloy_primitives::b256;
     use alloy_rlp::Decodable;
     use std::collections::HashMap;
 
+    #[test]
+    fn parallel_ordered_trie_root_matches_sequential() {
+        for len in [0usize, 1, 2, 16, 127, 128, 129, 255, 256, 257, 1000, 5000] {
+            // short values are embedded into their parents, long values are hashed
+            let short = (0..len as u64).collect::<Vec<_>>();
+            let long = (0..len).map(|i| Bytes::from(vec![i as u8; 40])).collect::<Vec<_>>();
+            let mixed = (0..len)
+                .map(|i| if i % 3 == 0 { Bytes::from(vec![1; 40]) } else { Bytes::from(vec![2]) })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                parallel_ordered_trie_root(&short, |item, buf| item.encode(buf)),
+                ordered_trie_root(&short),
+                "short values, len {len}"
+            );
+            assert_eq!(
+                parallel_ordered_trie_root(&long, |item, buf| item.encode(buf)),
+                ordered_trie_root(&long),
+                "long values, len {len}"
+            );
+            assert_eq!(
+                parallel_ordered_trie_root(&mixed, |item, buf| item.encode(buf)),
+                ordered_trie_root(&mixed),
+                "mixed values, len {len}"
+            );
+        }
+    }
+
     #[test]
     fn check_transaction_root() {
         let data = &hex!("f90262f901f9a092230ce5476ae868e98c7979cfc165a93f8b6ad1922acf2df62e340916efd49da01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa02307107a867056ca33b5087e77c4174f47625e48fb49f1c70ced34890ddd88f3a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba0c598f69a5674cae9337261b669970e24abc0b46e6d284372a239ec8ccbf20b0ab901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8618203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0");
@@ -294,7 +421,7 @@ mod tests {
     #[cfg(feature = "optimism")]
     #[test]
     fn check_optimism_receipt_root() {
-        use crate::{Bloom, Bytes, OP_GOERLI};
+        use crate::{Bloom, OP_GOERLI};
 
         let cases = [
             // Deposit nonces didn't exist in Bedrock; No need to strip. For the purposes of this
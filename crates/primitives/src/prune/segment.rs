@@ -23,15 +23,21 @@ pub enum PruneSegment {
     Headers,
     /// Prune segment responsible for the `Transactions` table.
     Transactions,
+    /// Prune segment responsible for the history expiry of the `Transactions`, `TxSenders`,
+    /// `TxHashNumber`, `Receipts` and `BlockOmmers` tables, see
+    /// [HistoryExpiry](https://eips.ethereum.org/EIPS/eip-4444).
+    HistoryExpiry,
 }
 
 impl PruneSegment {
     /// Returns minimum number of blocks to left in the database for this segment.
     pub fn min_blocks(&self) -> u64 {
         match self {
-            Self::SenderRecovery | Self::TransactionLookup | Self::Headers | Self::Transactions => {
-                0
-            }
+            Self::SenderRecovery |
+            Self::TransactionLookup |
+            Self::Headers |
+            Self::Transactions |
+            Self::HistoryExpiry => 0,
             Self::Receipts | Self::ContractLogs | Self::AccountHistory | Self::StorageHistory => {
                 MINIMUM_PRUNING_DISTANCE
             }
@@ -39,6 +39,11 @@ pub struct PruneModes {
         deserialize_with = "deserialize_opt_prune_mode_with_min_blocks::<MINIMUM_PRUNING_DISTANCE, _>"
     )]
     pub storage_history: Option<PruneMode>,
+    /// History expiry configuration. Drops the bodies and receipts of the blocks after verifying
+    /// their headers against the header accumulator, see
+    /// [EIP-4444](https://eips.ethereum.org/EIPS/eip-4444).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_expiry: Option<PruneMode>,
     /// Receipts pruning configuration by retaining only those receipts that contain logs emitted
     /// by the specified addresses, discarding others. This setting is overridden by `receipts`.
     ///
@@ -61,6 +66,7 @@ impl PruneModes {
             receipts: Some(PruneMode::Full),
             account_history: Some(PruneMode::Full),
             storage_history: Some(PruneMode::Full),
+            history_expiry: None,
             receipts_log_filter: Default::default(),
         }
     }
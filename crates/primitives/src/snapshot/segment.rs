@@ -50,7 +50,15 @@ impl SnapshotSegment {
 
         match self {
             SnapshotSegment::Headers => default_config,
-            SnapshotSegment::Transactions => default_config,
+            // the transaction hash filters of all snapshots are kept in memory to find
+            // transactions whose lookup entries were pruned
+            SnapshotSegment::Transactions => SegmentConfig {
+                filters: Filters::WithFilters(
+                    InclusionFilter::Bloom,
+                    super::PerfectHashingFunction::Fmph,
+                ),
+                ..default_config
+            },
             SnapshotSegment::Receipts => default_config,
         }
     }
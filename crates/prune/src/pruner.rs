@@ -6,11 +6,14 @@ use crate::{
     Metrics, PrunerError, PrunerEvent,
 };
 use reth_db::database::Database;
-use reth_primitives::{BlockNumber, PruneMode, PruneProgress, PruneSegment};
-use reth_provider::{ProviderFactory, PruneCheckpointReader};
+use reth_primitives::{
+    snapshot::HighestSnapshots, BlockNumber, PruneMode, PruneProgress, PruneSegment,
+};
+use reth_provider::{FinalizedBlockReader, ProviderFactory, PruneCheckpointReader};
 use reth_snapshot::HighestSnapshotsTracker;
 use reth_tokio_util::EventListeners;
 use std::{collections::BTreeMap, sync::Arc, time::Instant};
+use tokio::sync::watch;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, trace};
 
@@ -34,11 +37,19 @@ pub struct Pruner<DB> {
     previous_tip_block_number: Option<BlockNumber>,
     /// Maximum total entries to prune (delete from database) per block.
     delete_limit: usize,
+    /// Updates of `delete_limit` at runtime, the latest value is used by every run.
+    delete_limit_updates: Option<watch::Receiver<usize>>,
     /// Maximum number of blocks to be pruned per run, as an additional restriction to
     /// `previous_tip_block_number`.
     prune_max_blocks_per_run: usize,
     #[allow(dead_code)]
     highest_snapshots_tracker: HighestSnapshotsTracker,
+    /// While this is set, the pruner runs on every block with the delete limit of
+    /// `prune_max_blocks_per_run` blocks.
+    aggressive: Option<watch::Receiver<bool>>,
+    /// If set, transaction lookup entries are only pruned up to the highest transaction snapshot,
+    /// because the pruned transactions are looked up by hash in the snapshots.
+    snapshotted_transaction_lookup: bool,
     metrics: Metrics,
     listeners: EventListeners<PrunerEvent>,
 }
@@ -59,13 +70,46 @@ impl<DB: Database> Pruner<DB> {
             min_block_interval,
             previous_tip_block_number: None,
             delete_limit,
+            delete_limit_updates: None,
             prune_max_blocks_per_run,
             highest_snapshots_tracker,
+            aggressive: None,
+            snapshotted_transaction_lookup: false,
             metrics: Metrics::default(),
             listeners: Default::default(),
         }
     }
 
+    /// Prunes as much as possible while the given signal is set, e.g. while disk space is low.
+    pub fn with_aggressive_signal(mut self, aggressive: watch::Receiver<bool>) -> Self {
+        self.aggressive = Some(aggressive);
+        self
+    }
+
+    /// Uses the latest delete limit of the given receiver instead of the fixed one, so the limit
+    /// can be changed at runtime.
+    pub fn with_delete_limit_updates(mut self, delete_limit: watch::Receiver<usize>) -> Self {
+        self.delete_limit_updates = Some(delete_limit);
+        self
+    }
+
+    /// Returns the current maximum number of entries to delete per block.
+    fn delete_limit(&self) -> usize {
+        self.delete_limit_updates.as_ref().map_or(self.delete_limit, |updates| *updates.borrow())
+    }
+
+    /// Only prunes the transaction lookup entries of blocks that are covered by transaction
+    /// snapshots, so pruned transactions can still be found by hash.
+    pub fn with_snapshotted_transaction_lookup(mut self) -> Self {
+        self.snapshotted_transaction_lookup = true;
+        self
+    }
+
+    /// Returns `true` if the pruner should prune as much as possible.
+    fn is_aggressive(&self) -> bool {
+        self.aggressive.as_ref().is_some_and(|aggressive| *aggressive.borrow())
+    }
+
     /// Listen for events on the prune.
     pub fn events(&mut self) -> UnboundedReceiverStream<PrunerEvent> {
         self.listeners.new_listener()
@@ -91,6 +135,11 @@ impl<DB: Database> Pruner<DB> {
         // TODO(alexey): prune snapshotted segments of data (headers, transactions)
         let highest_snapshots = *self.highest_snapshots_tracker.borrow();
 
+        // Blocks above the finalized block can still be reorged out, so their history is kept
+        // until the consensus layer finalizes them. If the finalized block is not known yet, the
+        // prune modes alone decide.
+        let last_finalized_block = provider.last_finalized_block_number()?;
+
         // Multiply `self.delete_limit` (number of rows to delete per block) by number of blocks
         // since last pruner run. `self.previous_tip_block_number` is close to
         // `tip_block_number`, usually within `self.block_interval` blocks, so
@@ -98,32 +147,38 @@ impl<DB: Database> Pruner<DB> {
         // `self.prune_max_blocks_per_run`.
         //
         // Also see docs for `self.previous_tip_block_number`.
-        let blocks_since_last_run =
+        //
+        // When pruning aggressively, the maximum delete limit is used regardless.
+        let blocks_since_last_run = if self.is_aggressive() {
+            self.prune_max_blocks_per_run
+        } else {
             (self.previous_tip_block_number.map_or(1, |previous_tip_block_number| {
                 // Saturating subtraction is needed for the case when the chain was reverted,
                 // meaning current block number might be less than the previous tip
                 // block number.
                 tip_block_number.saturating_sub(previous_tip_block_number) as usize
             }))
-            .min(self.prune_max_blocks_per_run);
-        let mut delete_limit = self.delete_limit * blocks_since_last_run;
+            .min(self.prune_max_blocks_per_run)
+        };
+        let mut delete_limit = self.delete_limit() * blocks_since_last_run;
 
         for segment in &self.segments {
             if delete_limit == 0 {
                 break
             }
 
-            if let Some((to_block, prune_mode)) = segment
-                .mode()
-                .map(|mode| mode.prune_target_block(tip_block_number, segment.segment()))
-                .transpose()?
-                .flatten()
-            {
+            if let Some((to_block, prune_mode)) = self.segment_target(
+                segment.as_ref(),
+                tip_block_number,
+                last_finalized_block,
+                highest_snapshots,
+            )? {
                 trace!(
                     target: "pruner",
                     segment = ?segment.segment(),
                     %to_block,
                     ?prune_mode,
+                    ?last_finalized_block,
                     "Got target block to prune"
                 );
 
@@ -238,15 +293,84 @@ impl<DB: Database> Pruner<DB> {
         Ok(PruneProgress::from_done(done))
     }
 
+    /// Returns the block the segment is pruned up to at the given tip and its prune mode, or
+    /// `None` if the segment is not pruned.
+    fn segment_target(
+        &self,
+        segment: &dyn Segment<DB>,
+        tip_block_number: BlockNumber,
+        last_finalized_block: Option<BlockNumber>,
+        highest_snapshots: Option<HighestSnapshots>,
+    ) -> Result<Option<(BlockNumber, PruneMode)>, PrunerError> {
+        let Some((to_block, prune_mode)) = segment
+            .mode()
+            .map(|mode| mode.prune_target_block(tip_block_number, segment.segment()))
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(None)
+        };
+
+        let mut to_block =
+            last_finalized_block.map_or(to_block, |finalized| to_block.min(finalized));
+        if self.snapshotted_transaction_lookup &&
+            segment.segment() == PruneSegment::TransactionLookup
+        {
+            let Some(snapshotted) = highest_snapshots.and_then(|snapshots| snapshots.transactions)
+            else {
+                trace!(target: "pruner", segment = ?segment.segment(), "No transaction snapshots to prune up to");
+                return Ok(None)
+            };
+            to_block = to_block.min(snapshotted);
+        }
+        Ok(Some((to_block, prune_mode)))
+    }
+
+    /// Returns the segments whose prune checkpoint is below the block they are pruned up to at the
+    /// given tip, with the checkpoint block and the block they are pruned up to.
+    ///
+    /// A run stops once the delete limit is reached, even if it reports
+    /// [PruneProgress::Finished] for the segments it pruned, so the segments after them may
+    /// still be unfinished.
+    pub fn unfinished_segments(
+        &self,
+        tip_block_number: BlockNumber,
+    ) -> Result<Vec<(PruneSegment, Option<BlockNumber>, BlockNumber)>, PrunerError> {
+        let provider = self.provider_factory.provider()?;
+        let last_finalized_block = provider.last_finalized_block_number()?;
+        let highest_snapshots = *self.highest_snapshots_tracker.borrow();
+
+        let mut unfinished = Vec::new();
+        for segment in &self.segments {
+            let Some((to_block, _)) = self.segment_target(
+                segment.as_ref(),
+                tip_block_number,
+                last_finalized_block,
+                highest_snapshots,
+            )?
+            else {
+                continue
+            };
+            let pruned = provider
+                .get_prune_checkpoint(segment.segment())?
+                .and_then(|checkpoint| checkpoint.block_number);
+            if pruned.map_or(true, |pruned| pruned < to_block) {
+                unfinished.push((segment.segment(), pruned, to_block));
+            }
+        }
+        Ok(unfinished)
+    }
+
     /// Returns `true` if the pruning is needed at the provided tip block number.
     /// This determined by the check against minimum pruning interval and last pruned block number.
+    /// When pruning aggressively, the minimum pruning interval is one block.
     pub fn is_pruning_needed(&self, tip_block_number: BlockNumber) -> bool {
+        let min_block_interval = if self.is_aggressive() { 1 } else { self.min_block_interval };
         if self.previous_tip_block_number.map_or(true, |previous_tip_block_number| {
             // Saturating subtraction is needed for the case when the chain was reverted, meaning
             // current block number might be less than the previous tip block number.
             // If that's the case, no pruning is needed as outdated data is also reverted.
-            tip_block_number.saturating_sub(previous_tip_block_number) >=
-                self.min_block_interval as u64
+            tip_block_number.saturating_sub(previous_tip_block_number) >= min_block_interval as u64
         }) {
             debug!(
                 target: "pruner",
@@ -263,10 +387,19 @@ impl<DB: Database> Pruner<DB> {
 
 #[cfg(test)]
 mod tests {
-    use crate::Pruner;
-    use reth_db::test_utils::create_test_rw_db;
-    use reth_primitives::MAINNET;
-    use reth_provider::ProviderFactory;
+    use crate::{
+        segments::{SenderRecovery, TransactionLookup},
+        Pruner,
+    };
+    use reth_db::{tables, test_utils::create_test_rw_db};
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_primitives::{PruneMode, PruneProgress, PruneSegment, B256, MAINNET};
+    use reth_provider::{
+        FinalizedBlockWriter, ProviderFactory, PruneCheckpointReader, TransactionsProvider,
+    };
+    use reth_snapshot::Snapshotter;
+    use reth_stages::test_utils::TestStageDB;
+    use std::sync::Arc;
     use tokio::sync::watch;
 
     #[test]
@@ -288,5 +421,191 @@ mod tests {
         // Tip block number delta is < than min block interval
         let third_block_number = second_block_number;
         assert!(!pruner.is_pruning_needed(third_block_number));
+
+        // Aggressive pruning runs on every block
+        let (aggressive_tx, aggressive_rx) = watch::channel(true);
+        let pruner = pruner.with_aggressive_signal(aggressive_rx);
+        assert!(pruner.is_pruning_needed(third_block_number + 1));
+        aggressive_tx.send_replace(false);
+        assert!(!pruner.is_pruning_needed(third_block_number + 1));
+    }
+
+    #[test]
+    fn delete_limit_updates() {
+        let db = create_test_rw_db();
+        let provider_factory = ProviderFactory::new(db, MAINNET.clone());
+        let pruner = Pruner::new(provider_factory, vec![], 5, 10, 5, watch::channel(None).1);
+        assert_eq!(pruner.delete_limit(), 10);
+
+        let (delete_limit_tx, delete_limit_rx) = watch::channel(20);
+        let pruner = pruner.with_delete_limit_updates(delete_limit_rx);
+        assert_eq!(pruner.delete_limit(), 20);
+        delete_limit_tx.send_replace(30);
+        assert_eq!(pruner.delete_limit(), 30);
+    }
+
+    #[test]
+    fn unfinished_segments() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let mut tx_hash_numbers = Vec::new();
+        let mut transaction_senders = Vec::new();
+        for block in &blocks {
+            for transaction in &block.body {
+                let tx_number = tx_hash_numbers.len() as u64;
+                tx_hash_numbers.push((transaction.hash, tx_number));
+                transaction_senders
+                    .push((tx_number, transaction.recover_signer().expect("recover signer")));
+            }
+        }
+        db.insert_tx_hash_numbers(tx_hash_numbers.clone()).expect("insert tx hash numbers");
+        db.insert_transaction_senders(transaction_senders).expect("insert transaction senders");
+
+        let segments: Vec<Arc<dyn crate::segments::Segment<_>>> = vec![
+            Arc::new(TransactionLookup::new(PruneMode::Before(4))),
+            Arc::new(SenderRecovery::new(PruneMode::Before(4))),
+        ];
+        // the delete limit is used up by the first segment
+        let mut pruner = Pruner::new(
+            db.factory.clone(),
+            segments,
+            1,
+            tx_hash_numbers.len(),
+            1,
+            watch::channel(None).1,
+        )
+        .with_aggressive_signal(watch::channel(true).1);
+        assert_eq!(
+            pruner.unfinished_segments(4).unwrap(),
+            vec![
+                (PruneSegment::TransactionLookup, None, 3),
+                (PruneSegment::SenderRecovery, None, 3)
+            ]
+        );
+
+        assert_eq!(pruner.run(4).unwrap(), PruneProgress::Finished);
+        assert!(db.table::<tables::TxHashNumber>().unwrap().is_empty());
+        assert_eq!(db.table::<tables::TxSenders>().unwrap().len(), tx_hash_numbers.len());
+        assert_eq!(
+            pruner.unfinished_segments(4).unwrap(),
+            vec![(PruneSegment::SenderRecovery, None, 3)]
+        );
+
+        pruner.run(4).unwrap();
+        assert!(db.table::<tables::TxSenders>().unwrap().is_empty());
+        assert!(pruner.unfinished_segments(4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn finalized_block_caps_pruning() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let mut tx_hash_numbers = Vec::new();
+        for block in &blocks {
+            for transaction in &block.body {
+                tx_hash_numbers.push((transaction.hash, tx_hash_numbers.len() as u64));
+            }
+        }
+        db.insert_tx_hash_numbers(tx_hash_numbers.clone()).expect("insert tx hash numbers");
+
+        let segments: Vec<Arc<dyn crate::segments::Segment<_>>> =
+            vec![Arc::new(TransactionLookup::new(PruneMode::Before(4)))];
+        let mut pruner =
+            Pruner::new(db.factory.clone(), segments, 1, 100, 5, watch::channel(None).1)
+                .with_aggressive_signal(watch::channel(true).1);
+
+        // the prune mode targets block 3, but only blocks up to the finalized block 1 are pruned
+        db.factory.save_finalized_block_number(1).unwrap();
+        assert_eq!(pruner.run(4).unwrap(), PruneProgress::Finished);
+        let finalized_txs = blocks[..2].iter().map(|block| block.body.len()).sum::<usize>();
+        assert_eq!(
+            db.table::<tables::TxHashNumber>().unwrap().len(),
+            tx_hash_numbers.len() - finalized_txs
+        );
+        let checkpoint =
+            db.factory.get_prune_checkpoint(PruneSegment::TransactionLookup).unwrap().unwrap();
+        assert_eq!(checkpoint.block_number, Some(1));
+        assert!(pruner.unfinished_segments(4).unwrap().is_empty());
+
+        // the rest is pruned once it's finalized
+        db.factory.save_finalized_block_number(3).unwrap();
+        assert_eq!(
+            pruner.unfinished_segments(4).unwrap(),
+            vec![(PruneSegment::TransactionLookup, Some(1), 3)]
+        );
+        pruner.run(4).unwrap();
+        assert!(db.table::<tables::TxHashNumber>().unwrap().is_empty());
+        assert!(pruner.unfinished_segments(4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn snapshotted_transaction_lookup() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        let mut tx_hash_numbers = Vec::new();
+        for block in &blocks {
+            for transaction in &block.body {
+                tx_hash_numbers.push((transaction.hash, tx_hash_numbers.len() as u64));
+            }
+        }
+        db.insert_tx_hash_numbers(tx_hash_numbers.clone()).expect("insert tx hash numbers");
+
+        let snapshots_dir = tempfile::TempDir::new().unwrap();
+        let provider_factory = db
+            .factory
+            .clone()
+            .with_snapshots(snapshots_dir.path().to_path_buf(), watch::channel(None).1, true)
+            .unwrap();
+        let mut snapshotter =
+            Snapshotter::new(db.factory.clone(), snapshots_dir.path(), 2).unwrap();
+        let segments: Vec<Arc<dyn crate::segments::Segment<_>>> =
+            vec![Arc::new(TransactionLookup::new(PruneMode::Before(4)))];
+        let mut pruner = Pruner::new(
+            provider_factory,
+            segments,
+            1,
+            100,
+            5,
+            snapshotter.highest_snapshot_receiver(),
+        )
+        .with_snapshotted_transaction_lookup();
+
+        // nothing is pruned before the transactions are snapshotted
+        pruner.run(3).unwrap();
+        assert_eq!(db.table::<tables::TxHashNumber>().unwrap().len(), tx_hash_numbers.len());
+
+        // blocks 0 and 1 are snapshotted, so only their lookup entries are pruned
+        let targets = snapshotter.get_snapshot_targets(1).unwrap();
+        snapshotter.run(targets).unwrap();
+        pruner.run(4).unwrap();
+        let snapshotted_txs = blocks[..2].iter().map(|block| block.body.len()).sum::<usize>();
+        assert_eq!(
+            db.table::<tables::TxHashNumber>().unwrap().len(),
+            tx_hash_numbers.len() - snapshotted_txs
+        );
+
+        // every transaction is still found by hash, from the snapshot or the database
+        let provider_factory = db
+            .factory
+            .clone()
+            .with_snapshots(
+                snapshots_dir.path().to_path_buf(),
+                snapshotter.highest_snapshot_receiver(),
+                true,
+            )
+            .unwrap();
+        let provider = provider_factory.provider().unwrap();
+        for (hash, number) in tx_hash_numbers {
+            assert_eq!(provider.transaction_id(hash).unwrap(), Some(number));
+        }
     }
 }
@@ -0,0 +1,288 @@
+use crate::{
+    segments::{PruneInput, PruneOutput, PruneOutputCheckpoint, Segment},
+    PrunerError,
+};
+use rayon::prelude::*;
+use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
+use reth_primitives::{
+    accumulator::{epoch_block_range, epoch_of, EpochAccumulator, HeaderRecord},
+    PruneMode, PruneSegment,
+};
+use reth_provider::{BlockReader, DatabaseProviderRW, ProviderError, TransactionsProvider};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{instrument, trace};
+
+/// Expires the bodies and receipts of the blocks, see
+/// [EIP-4444](https://eips.ethereum.org/EIPS/eip-4444).
+///
+/// Only the blocks of epochs committed to the header accumulator are expired, and the stored
+/// headers of an epoch are verified against its accumulator root before the first of its blocks is
+/// expired. Headers and body indices are retained, so the headers of the expired blocks can still
+/// be served and their bodies can be verified when restored from Portal or era archives.
+#[derive(Debug)]
+pub struct HistoryExpiry {
+    mode: PruneMode,
+    /// All epochs below this one were verified against the header accumulator.
+    verified_epochs: AtomicU64,
+}
+
+impl HistoryExpiry {
+    pub fn new(mode: PruneMode) -> Self {
+        Self { mode, verified_epochs: AtomicU64::new(0) }
+    }
+}
+
+impl<DB: Database> Segment<DB> for HistoryExpiry {
+    fn segment(&self) -> PruneSegment {
+        PruneSegment::HistoryExpiry
+    }
+
+    fn mode(&self) -> Option<PruneMode> {
+        Some(self.mode)
+    }
+
+    #[instrument(level = "trace", target = "pruner", skip(self, provider), ret)]
+    fn prune(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        input: PruneInput,
+    ) -> Result<PruneOutput, PrunerError> {
+        let block_range = match input.get_next_block_range() {
+            Some(range) => range,
+            None => {
+                trace!(target: "pruner", "No history to expire");
+                return Ok(PruneOutput::done())
+            }
+        };
+
+        // Only the blocks of committed epochs can be verified against the header accumulator.
+        let Some((last_committed_epoch, _)) =
+            provider.tx_ref().cursor_read::<tables::EpochAccumulatorRoots>()?.last()?
+        else {
+            trace!(target: "pruner", "No epochs committed to the header accumulator");
+            return Ok(PruneOutput::not_done())
+        };
+        let from_block = *block_range.start();
+        let to_block = (*block_range.end()).min(*epoch_block_range(last_committed_epoch).end());
+        if from_block > to_block {
+            trace!(target: "pruner", %from_block, "Epoch of the block is not committed yet");
+            return Ok(PruneOutput::not_done())
+        }
+
+        // Every transaction has a row in the transactions, senders, receipts and transaction
+        // lookup tables, and every block one in the ommers table. Blocks are expired as a whole,
+        // so the first block is expired even if it exceeds the delete limit.
+        let mut budget = input.delete_limit;
+        let mut first_tx_num = None;
+        let mut last = None;
+        for block_number in from_block..=to_block {
+            let body = provider
+                .block_body_indices(block_number)?
+                .ok_or(PrunerError::InconsistentData("Block body indices are not found"))?;
+            let rows = body.tx_count as usize * 4 + 1;
+            if rows > budget && last.is_some() {
+                break
+            }
+            budget = budget.saturating_sub(rows);
+            first_tx_num.get_or_insert(body.first_tx_num());
+            last = Some((block_number, body.next_tx_num()));
+            if budget == 0 {
+                break
+            }
+        }
+        let (Some(first_tx_num), Some((last_block, next_tx_num))) = (first_tx_num, last) else {
+            return Ok(PruneOutput::not_done())
+        };
+
+        for epoch in epoch_of(from_block)..=epoch_of(last_block) {
+            if epoch < self.verified_epochs.load(Ordering::Relaxed) {
+                continue
+            }
+            self.verify_epoch(provider, epoch)?;
+            self.verified_epochs.store(epoch + 1, Ordering::Relaxed);
+        }
+
+        let tx_range = first_tx_num..next_tx_num;
+        let mut pruned = 0;
+        if !tx_range.is_empty() {
+            // Transactions that were already dropped by other segments have no lookup entry left
+            let hashes = provider
+                .transactions_by_tx_range(tx_range.clone())?
+                .into_par_iter()
+                .map(|transaction| transaction.hash())
+                .collect::<Vec<_>>();
+            pruned += provider
+                .prune_table_with_iterator::<tables::TxHashNumber>(hashes, usize::MAX, |_| {})?
+                .0;
+            pruned += provider
+                .prune_table_with_range::<tables::Transactions>(
+                    tx_range.clone(),
+                    usize::MAX,
+                    |_| false,
+                    |_| {},
+                )?
+                .0;
+            pruned += provider
+                .prune_table_with_range::<tables::TxSenders>(
+                    tx_range.clone(),
+                    usize::MAX,
+                    |_| false,
+                    |_| {},
+                )?
+                .0;
+            pruned += provider
+                .prune_table_with_range::<tables::Receipts>(
+                    tx_range,
+                    usize::MAX,
+                    |_| false,
+                    |_| {},
+                )?
+                .0;
+        }
+        pruned += provider
+            .prune_table_with_range::<tables::BlockOmmers>(
+                from_block..=last_block,
+                usize::MAX,
+                |_| false,
+                |_| {},
+            )?
+            .0;
+
+        let done = last_block == *block_range.end();
+        trace!(target: "pruner", %pruned, %done, %last_block, "Expired history");
+
+        Ok(PruneOutput {
+            done,
+            pruned,
+            checkpoint: Some(PruneOutputCheckpoint {
+                block_number: Some(last_block),
+                tx_number: next_tx_num.checked_sub(1),
+            }),
+        })
+    }
+}
+
+impl HistoryExpiry {
+    /// Verifies the stored headers of the given epoch against its root in the header
+    /// accumulator.
+    fn verify_epoch<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        epoch: u64,
+    ) -> Result<(), PrunerError> {
+        let root = provider
+            .tx_ref()
+            .get::<tables::EpochAccumulatorRoots>(epoch)?
+            .ok_or(PrunerError::InconsistentData("Epoch is not committed to the accumulator"))?;
+
+        let mut headers_cursor = provider.tx_ref().cursor_read::<tables::Headers>()?;
+        let mut td_cursor = provider.tx_ref().cursor_read::<tables::HeaderTD>()?;
+        let mut records = Vec::with_capacity(epoch_block_range(epoch).count());
+        for block_number in epoch_block_range(epoch) {
+            let (_, header) = headers_cursor
+                .seek_exact(block_number)?
+                .ok_or_else(|| ProviderError::HeaderNotFound(block_number.into()))?;
+            let (_, total_difficulty) = td_cursor
+                .seek_exact(block_number)?
+                .ok_or(ProviderError::TotalDifficultyNotFound(block_number))?;
+            records.push(HeaderRecord {
+                block_hash: header.hash_slow(),
+                total_difficulty: total_difficulty.0,
+            });
+        }
+
+        let epoch_accumulator = EpochAccumulator { records };
+        if epoch_accumulator.root() != root {
+            return Err(PrunerError::InconsistentData(
+                "Stored headers do not match the header accumulator",
+            ))
+        }
+        trace!(target: "pruner", %epoch, "Verified epoch against the header accumulator");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::segments::{HistoryExpiry, PruneInput, PruneOutput, PruneOutputCheckpoint, Segment};
+    use assert_matches::assert_matches;
+    use reth_db::tables;
+    use reth_interfaces::{
+        provider::ProviderError,
+        test_utils::{generators, generators::random_block_range},
+    };
+    use reth_primitives::{accumulator::EPOCH_SIZE, BlockNumber, PruneMode, PruneSegment, B256};
+    use reth_provider::{HeaderAccumulatorWriter, PruneCheckpointReader, TransactionsProvider};
+    use reth_stages::test_utils::TestStageDB;
+
+    #[test]
+    fn prune() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        // the second epoch is incomplete
+        let tip = EPOCH_SIZE + 9;
+        let blocks = random_block_range(&mut rng, 0..=tip, B256::ZERO, 0..2);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+        db.insert_headers_with_td(blocks.iter().map(|block| &block.header))
+            .expect("insert headers");
+        let provider = db.factory.provider_rw().unwrap();
+        provider.backfill_header_accumulator(tip).unwrap();
+        provider.commit().expect("commit");
+
+        let prune_mode = PruneMode::Before(tip);
+        let segment = HistoryExpiry::new(prune_mode);
+        let prune = |to_block: BlockNumber, delete_limit: usize| {
+            let provider = db.factory.provider_rw().unwrap();
+            let input = PruneInput {
+                previous_checkpoint: provider
+                    .get_prune_checkpoint(PruneSegment::HistoryExpiry)
+                    .unwrap(),
+                to_block,
+                delete_limit,
+            };
+            let output = segment.prune(&provider, input).unwrap();
+            if let Some(checkpoint) = output.checkpoint {
+                segment
+                    .save_checkpoint(&provider, checkpoint.as_prune_checkpoint(prune_mode))
+                    .unwrap();
+            }
+            provider.commit().expect("commit");
+            output
+        };
+
+        assert_matches!(
+            prune(tip - 1, 10),
+            PruneOutput {
+                done: false,
+                checkpoint: Some(PruneOutputCheckpoint { block_number: Some(block_number), .. }),
+                ..
+            } if block_number < EPOCH_SIZE - 1
+        );
+
+        // the blocks of the incomplete epoch are not expired
+        assert_matches!(
+            prune(tip - 1, usize::MAX),
+            PruneOutput {
+                done: false,
+                checkpoint: Some(PruneOutputCheckpoint { block_number: Some(block_number), .. }),
+                ..
+            } if block_number == EPOCH_SIZE - 1
+        );
+        assert_eq!(prune(tip - 1, usize::MAX), PruneOutput::not_done());
+
+        let remaining_transactions =
+            blocks.iter().skip(EPOCH_SIZE as usize).map(|block| block.body.len()).sum::<usize>();
+        assert_eq!(db.table::<tables::Transactions>().unwrap().len(), remaining_transactions);
+        assert_eq!(db.table::<tables::CanonicalHeaders>().unwrap().len(), blocks.len());
+
+        let provider = db.factory.provider().unwrap();
+        assert_matches!(
+            provider.transactions_by_block(5.into()),
+            Err(ProviderError::HistoryExpired { block_number: 5, first_available_block })
+                if first_available_block == EPOCH_SIZE
+        );
+        assert_matches!(provider.transactions_by_block(EPOCH_SIZE.into()), Ok(Some(_)));
+    }
+}
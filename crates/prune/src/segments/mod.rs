@@ -1,6 +1,7 @@
 mod account_history;
 mod headers;
 mod history;
+mod history_expiry;
 mod receipts;
 mod receipts_by_logs;
 mod sender_recovery;
@@ -11,6 +12,7 @@ mod transactions;
 
 pub use account_history::AccountHistory;
 pub use headers::Headers;
+pub use history_expiry::HistoryExpiry;
 pub use receipts::Receipts;
 pub use receipts_by_logs::ReceiptsByLogs;
 pub use sender_recovery::SenderRecovery;
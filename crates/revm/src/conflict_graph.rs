@@ -0,0 +1,316 @@
+use reth_primitives::{Address, B256, U256};
+use revm::primitives::{AccountStatus, State as EvmState};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A state location that a transaction can read or write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StateKey {
+    /// The balance, nonce and code of an account.
+    Account(Address),
+    /// A storage slot of an account.
+    Storage(Address, U256),
+}
+
+impl StateKey {
+    /// Returns the address of the account the location belongs to.
+    pub fn address(&self) -> Address {
+        match self {
+            StateKey::Account(address) | StateKey::Storage(address, _) => *address,
+        }
+    }
+}
+
+/// The state locations a transaction read and wrote during execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadWriteSet {
+    /// Locations that were loaded but not changed.
+    pub reads: BTreeSet<StateKey>,
+    /// Locations that were changed.
+    pub writes: BTreeSet<StateKey>,
+}
+
+impl ReadWriteSet {
+    /// Records the read/write set from the state returned by executing a transaction.
+    ///
+    /// The state contains every account and slot that was loaded during execution. Touched
+    /// accounts and slots whose value changed are writes, everything else is a read.
+    pub fn from_state(state: &EvmState) -> Self {
+        let mut set = Self::default();
+        for (address, account) in state {
+            if account.status.contains(AccountStatus::Touched) {
+                set.writes.insert(StateKey::Account(*address));
+            } else {
+                set.reads.insert(StateKey::Account(*address));
+            }
+
+            for (slot, value) in &account.storage {
+                let key = StateKey::Storage(*address, *slot);
+                if value.previous_or_original_value != value.present_value {
+                    set.writes.insert(key);
+                } else {
+                    set.reads.insert(key);
+                }
+            }
+        }
+        set
+    }
+
+    /// Removes all locations of the given account.
+    ///
+    /// Every transaction pays fees to the block's coinbase, so it is usually excluded to not make
+    /// every transaction depend on the previous one.
+    pub fn exclude_account(&mut self, address: Address) {
+        self.reads.retain(|key| key.address() != address);
+        self.writes.retain(|key| key.address() != address);
+    }
+
+    /// Returns all locations the transaction accessed.
+    pub fn accessed(&self) -> impl Iterator<Item = &StateKey> {
+        self.reads.iter().chain(self.writes.iter())
+    }
+}
+
+/// How a transaction depends on an earlier transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictKind {
+    /// The later transaction writes a location the earlier transaction read.
+    WriteAfterRead,
+    /// Both transactions write the same location.
+    WriteAfterWrite,
+    /// The later transaction reads a location the earlier transaction wrote.
+    ReadAfterWrite,
+}
+
+/// An edge of the [ConflictGraph]: the transaction at `to` depends on the one at `from`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    /// Index of the earlier transaction.
+    pub from: usize,
+    /// Index of the later transaction.
+    pub to: usize,
+    /// The strongest kind of conflict between the transactions.
+    pub kind: ConflictKind,
+    /// The locations the transactions conflict on.
+    pub keys: Vec<StateKey>,
+}
+
+/// The dependencies between simulated transactions, derived from their read/write sets.
+///
+/// Transactions are identified by their index in the simulated order. A transaction depends on
+/// every earlier transaction it conflicts with, so transactions without a path between them can
+/// be executed in parallel, or reordered relative to each other, without changing their results.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictGraph {
+    /// The hashes of the transactions, in simulated order.
+    pub transactions: Vec<B256>,
+    /// All dependencies, ordered by `(to, from)`.
+    pub dependencies: Vec<Dependency>,
+}
+
+impl ConflictGraph {
+    /// Builds the graph from the read/write sets of transactions, in the order they were
+    /// simulated.
+    pub fn build(transactions: impl IntoIterator<Item = (B256, ReadWriteSet)>) -> Self {
+        let mut hashes = Vec::new();
+        // readers and writers of each location, in order
+        let mut accesses = HashMap::<StateKey, (Vec<usize>, Vec<usize>)>::new();
+        let mut dependencies = Vec::new();
+
+        for (index, (hash, set)) in transactions.into_iter().enumerate() {
+            hashes.push(hash);
+
+            let mut conflicts = BTreeMap::<usize, (ConflictKind, BTreeSet<StateKey>)>::new();
+            let mut add = |from: usize, kind: ConflictKind, key: StateKey| {
+                let (strongest, keys) = conflicts.entry(from).or_insert((kind, BTreeSet::new()));
+                *strongest = (*strongest).max(kind);
+                keys.insert(key);
+            };
+
+            for key in &set.reads {
+                if let Some((_, writers)) = accesses.get(key) {
+                    for from in writers {
+                        add(*from, ConflictKind::ReadAfterWrite, *key);
+                    }
+                }
+            }
+            for key in &set.writes {
+                if let Some((readers, writers)) = accesses.get(key) {
+                    for from in writers {
+                        add(*from, ConflictKind::WriteAfterWrite, *key);
+                    }
+                    for from in readers {
+                        add(*from, ConflictKind::WriteAfterRead, *key);
+                    }
+                }
+            }
+
+            dependencies.extend(conflicts.into_iter().map(|(from, (kind, keys))| Dependency {
+                from,
+                to: index,
+                kind,
+                keys: keys.into_iter().collect(),
+            }));
+
+            for key in set.reads {
+                accesses.entry(key).or_default().0.push(index);
+            }
+            for key in set.writes {
+                accesses.entry(key).or_default().1.push(index);
+            }
+        }
+
+        Self { transactions: hashes, dependencies }
+    }
+
+    /// Builds the graph from the states returned by executing transactions, in the order they
+    /// were executed, ignoring the accounts in `excluded`, e.g. the coinbase.
+    pub fn from_simulations<'a>(
+        simulations: impl IntoIterator<Item = (B256, &'a EvmState)>,
+        excluded: &[Address],
+    ) -> Self {
+        Self::build(simulations.into_iter().map(|(hash, state)| {
+            let mut set = ReadWriteSet::from_state(state);
+            for address in excluded {
+                set.exclude_account(*address);
+            }
+            (hash, set)
+        }))
+    }
+
+    /// Returns the number of transactions.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Returns `true` if the graph contains no transactions.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns the dependencies of the transaction at the given index on earlier transactions.
+    pub fn dependencies_of(&self, index: usize) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.iter().filter(move |dependency| dependency.to == index)
+    }
+
+    /// Returns the dependencies of later transactions on the transaction at the given index.
+    pub fn dependents_of(&self, index: usize) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.iter().filter(move |dependency| dependency.from == index)
+    }
+
+    /// Groups the transactions into rounds for a parallel scheduler.
+    ///
+    /// All transactions of a round only depend on transactions of earlier rounds, so they can be
+    /// executed in parallel once the earlier rounds are done.
+    pub fn rounds(&self) -> Vec<Vec<usize>> {
+        let mut round_of = vec![0; self.len()];
+        // dependencies are ordered by `to`, so all rounds of earlier transactions are final
+        for dependency in &self.dependencies {
+            round_of[dependency.to] = round_of[dependency.to].max(round_of[dependency.from] + 1);
+        }
+
+        let mut rounds = Vec::<Vec<usize>>::new();
+        for (index, round) in round_of.into_iter().enumerate() {
+            if rounds.len() <= round {
+                rounds.resize(round + 1, Vec::new());
+            }
+            rounds[round].push(index);
+        }
+        rounds
+    }
+
+    /// Groups the transactions into sets that don't conflict with each other.
+    ///
+    /// The transactions of different groups can be ordered freely relative to each other, the
+    /// transactions of a group keep their relative order.
+    pub fn independent_groups(&self) -> Vec<Vec<usize>> {
+        let mut parent = (0..self.len()).collect::<Vec<_>>();
+        fn root(parent: &mut [usize], mut index: usize) -> usize {
+            while parent[index] != index {
+                parent[index] = parent[parent[index]];
+                index = parent[index];
+            }
+            index
+        }
+        for dependency in &self.dependencies {
+            let (from, to) = (root(&mut parent, dependency.from), root(&mut parent, dependency.to));
+            parent[from.max(to)] = from.min(to);
+        }
+
+        let mut groups = BTreeMap::<usize, Vec<usize>>::new();
+        for index in 0..self.len() {
+            let root = root(&mut parent, index);
+            groups.entry(root).or_default().push(index);
+        }
+        groups.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(reads: &[StateKey], writes: &[StateKey]) -> ReadWriteSet {
+        ReadWriteSet {
+            reads: reads.iter().copied().collect(),
+            writes: writes.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn build_conflict_graph() {
+        let alice = StateKey::Account(Address::with_last_byte(1));
+        let bob = StateKey::Account(Address::with_last_byte(2));
+        let pool = StateKey::Storage(Address::with_last_byte(3), U256::from(1));
+        let coinbase = Address::with_last_byte(4);
+
+        let mut sets = vec![
+            // alice swaps
+            set(&[], &[alice, pool]),
+            // bob transfers
+            set(&[], &[bob]),
+            // bob reads the pool
+            set(&[pool], &[bob]),
+            // alice writes what bob read
+            set(&[], &[alice, pool]),
+        ];
+        for set in &mut sets {
+            set.writes.insert(StateKey::Account(coinbase));
+            set.exclude_account(coinbase);
+        }
+        let graph = ConflictGraph::build(
+            sets.into_iter()
+                .enumerate()
+                .map(|(index, set)| (B256::with_last_byte(index as u8), set)),
+        );
+
+        assert_eq!(
+            graph.dependencies_of(2).map(|d| (d.from, d.kind)).collect::<Vec<_>>(),
+            vec![(0, ConflictKind::ReadAfterWrite), (1, ConflictKind::WriteAfterWrite)]
+        );
+        assert_eq!(
+            graph.dependencies_of(3).map(|d| (d.from, d.kind)).collect::<Vec<_>>(),
+            vec![(0, ConflictKind::WriteAfterWrite), (2, ConflictKind::WriteAfterRead)]
+        );
+        assert_eq!(graph.rounds(), vec![vec![0, 1], vec![2], vec![3]]);
+        assert_eq!(graph.independent_groups(), vec![vec![0, 1, 2, 3]]);
+
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(serde_json::from_str::<ConflictGraph>(&json).unwrap(), graph);
+    }
+
+    #[test]
+    fn independent_transactions() {
+        let graph = ConflictGraph::build([
+            (B256::with_last_byte(1), set(&[], &[StateKey::Account(Address::with_last_byte(1))])),
+            (B256::with_last_byte(2), set(&[], &[StateKey::Account(Address::with_last_byte(2))])),
+            (B256::with_last_byte(3), set(&[StateKey::Account(Address::with_last_byte(1))], &[])),
+        ]);
+        assert_eq!(graph.rounds(), vec![vec![0, 1], vec![2]]);
+        assert_eq!(graph.independent_groups(), vec![vec![0, 2], vec![1]]);
+    }
+}
@@ -0,0 +1,222 @@
+use revm::{
+    precompile::{PrecompileWithAddress, Precompiles, SpecId as PrecompilesSpecId},
+    primitives::{
+        BerlinSpec, ByzantiumSpec, CancunSpec, CfgEnv, EVMResult, Env, FrontierSpec, HomesteadSpec,
+        IstanbulSpec, LatestSpec, LondonSpec, MergeSpec, PetersburgSpec, ShanghaiSpec, SpecId,
+        SpuriousDragonSpec, TangerineSpec,
+    },
+    Database, EVMImpl, Inspector, Transact, EVM,
+};
+use std::{fmt, sync::Arc};
+
+#[cfg(feature = "optimism")]
+use revm::primitives::{BedrockSpec, CanyonSpec, RegolithSpec};
+
+/// A function that modifies the [CfgEnv] of every execution.
+pub type CfgOverride = Arc<dyn Fn(&mut CfgEnv) + Send + Sync>;
+
+/// Customizations of the EVM that are applied alike to block execution, RPC simulations and
+/// payload building.
+///
+/// Custom precompiles are added to the precompiles of the active spec once the spec they are
+/// activated at is active, so they can be gated behind a hardfork. Overrides of the [CfgEnv] are
+/// applied after it was filled from the chain spec, e.g. to raise the contract size limit.
+///
+/// The opcodes and the gas schedule are compiled into revm and can't be customized.
+#[derive(Clone, Default)]
+pub struct EvmConfig {
+    /// Custom precompiles and the specs they are activated at.
+    precompiles: Vec<(SpecId, PrecompileWithAddress)>,
+    /// Overrides of the [CfgEnv], applied in order.
+    cfg_overrides: Vec<CfgOverride>,
+}
+
+impl fmt::Debug for EvmConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvmConfig")
+            .field(
+                "precompiles",
+                &self
+                    .precompiles
+                    .iter()
+                    .map(|(activation, precompile)| (activation, precompile.0))
+                    .collect::<Vec<_>>(),
+            )
+            .field("cfg_overrides", &self.cfg_overrides.len())
+            .finish()
+    }
+}
+
+impl EvmConfig {
+    /// Adds a precompile that is active from the given spec on.
+    ///
+    /// A custom precompile at the address of a precompile of the spec replaces it.
+    pub fn with_precompile(
+        mut self,
+        activation: SpecId,
+        precompile: PrecompileWithAddress,
+    ) -> Self {
+        self.precompiles.push((activation, precompile));
+        self
+    }
+
+    /// Adds an override of the [CfgEnv] of every execution.
+    pub fn with_cfg_override(mut self, f: impl Fn(&mut CfgEnv) + Send + Sync + 'static) -> Self {
+        self.cfg_overrides.push(Arc::new(f));
+        self
+    }
+
+    /// Returns `true` if the EVM is not customized.
+    pub fn is_default(&self) -> bool {
+        self.precompiles.is_empty() && self.cfg_overrides.is_empty()
+    }
+
+    /// Applies the overrides to the [CfgEnv].
+    pub fn configure_cfg(&self, cfg: &mut CfgEnv) {
+        for cfg_override in &self.cfg_overrides {
+            cfg_override(cfg);
+        }
+    }
+
+    /// Returns the precompiles of the spec, including the custom precompiles active at it.
+    pub fn precompiles(&self, spec_id: SpecId) -> Precompiles {
+        let mut precompiles = Precompiles::new(PrecompilesSpecId::from_spec_id(spec_id)).clone();
+        precompiles.extend(
+            self.precompiles
+                .iter()
+                .filter(|(activation, _)| spec_id >= *activation)
+                .map(|(_, precompile)| precompile.clone()),
+        );
+        precompiles
+    }
+
+    /// Executes the environment of the [EVM] without committing state changes, like
+    /// [EVM::transact] but with the custom precompiles.
+    pub fn transact<DB: Database>(&self, evm: &mut EVM<DB>) -> EVMResult<DB::Error> {
+        if self.precompiles.is_empty() {
+            return evm.transact()
+        }
+        let precompiles = self.precompiles(evm.env.cfg.spec_id);
+        let db = evm.db.as_mut().expect("Database needs to be set");
+        transact_with_precompiles(&mut evm.env, db, None, precompiles)
+    }
+
+    /// Executes the environment of the [EVM] with the inspector without committing state
+    /// changes, like [EVM::inspect] but with the custom precompiles.
+    pub fn inspect<DB: Database, I: Inspector<DB>>(
+        &self,
+        evm: &mut EVM<DB>,
+        mut inspector: I,
+    ) -> EVMResult<DB::Error> {
+        if self.precompiles.is_empty() {
+            return evm.inspect(inspector)
+        }
+        let precompiles = self.precompiles(evm.env.cfg.spec_id);
+        let db = evm.db.as_mut().expect("Database needs to be set");
+        transact_with_precompiles(
+            &mut evm.env,
+            db,
+            Some(&mut inspector as &mut dyn Inspector<DB>),
+            precompiles,
+        )
+    }
+}
+
+/// Executes the environment with the given precompiles instead of the precompiles of its spec.
+fn transact_with_precompiles<'a, DB: Database>(
+    env: &'a mut Env,
+    db: &'a mut DB,
+    inspector: Option<&'a mut dyn Inspector<DB>>,
+    precompiles: Precompiles,
+) -> EVMResult<DB::Error> {
+    macro_rules! transact {
+        ($spec:ident) => {
+            EVMImpl::<'a, $spec, DB>::new_with_spec(db, env, inspector, precompiles).transact()
+        };
+    }
+
+    let spec_id = env.cfg.spec_id;
+    match spec_id {
+        SpecId::FRONTIER | SpecId::FRONTIER_THAWING => transact!(FrontierSpec),
+        SpecId::HOMESTEAD | SpecId::DAO_FORK => transact!(HomesteadSpec),
+        SpecId::TANGERINE => transact!(TangerineSpec),
+        SpecId::SPURIOUS_DRAGON => transact!(SpuriousDragonSpec),
+        SpecId::BYZANTIUM => transact!(ByzantiumSpec),
+        SpecId::PETERSBURG | SpecId::CONSTANTINOPLE => transact!(PetersburgSpec),
+        SpecId::ISTANBUL | SpecId::MUIR_GLACIER => transact!(IstanbulSpec),
+        SpecId::BERLIN => transact!(BerlinSpec),
+        SpecId::LONDON | SpecId::ARROW_GLACIER | SpecId::GRAY_GLACIER => transact!(LondonSpec),
+        SpecId::MERGE => transact!(MergeSpec),
+        SpecId::SHANGHAI => transact!(ShanghaiSpec),
+        SpecId::CANCUN => transact!(CancunSpec),
+        SpecId::LATEST => transact!(LatestSpec),
+        #[cfg(feature = "optimism")]
+        SpecId::BEDROCK => transact!(BedrockSpec),
+        #[cfg(feature = "optimism")]
+        SpecId::REGOLITH => transact!(RegolithSpec),
+        #[cfg(feature = "optimism")]
+        SpecId::CANYON => transact!(CanyonSpec),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::{CacheDB, EmptyDB},
+        precompile::{Precompile, PrecompileResult},
+        primitives::{Address, Bytes, ExecutionResult, Output, TransactTo, U256},
+    };
+
+    const PRECOMPILE: Address = Address::with_last_byte(0xff);
+
+    fn echo(input: &[u8], _gas_limit: u64) -> PrecompileResult {
+        Ok((100, input.to_vec()))
+    }
+
+    fn call(config: &EvmConfig, spec_id: SpecId) -> ExecutionResult {
+        let mut evm = EVM::new();
+        evm.database(CacheDB::new(EmptyDB::default()));
+        evm.env.cfg.spec_id = spec_id;
+        evm.env.tx.transact_to = TransactTo::Call(PRECOMPILE);
+        evm.env.tx.data = Bytes::from_static(b"input");
+        evm.env.tx.gas_price = U256::ZERO;
+        config.transact(&mut evm).unwrap().result
+    }
+
+    #[test]
+    fn custom_precompile_is_active_from_its_spec() {
+        let config = EvmConfig::default().with_precompile(
+            SpecId::SHANGHAI,
+            PrecompileWithAddress(PRECOMPILE, Precompile::Standard(echo)),
+        );
+        let is_precompile =
+            |spec_id| config.precompiles(spec_id).addresses().any(|address| *address == PRECOMPILE);
+        assert!(is_precompile(SpecId::CANCUN));
+        assert!(!is_precompile(SpecId::MERGE));
+
+        match call(&config, SpecId::CANCUN) {
+            ExecutionResult::Success { output: Output::Call(output), .. } => {
+                assert_eq!(output, Bytes::from_static(b"input"))
+            }
+            result => panic!("unexpected result {result:?}"),
+        }
+        // before the activation the address is an empty account
+        match call(&config, SpecId::MERGE) {
+            ExecutionResult::Success { output: Output::Call(output), .. } => {
+                assert!(output.is_empty())
+            }
+            result => panic!("unexpected result {result:?}"),
+        }
+    }
+
+    #[test]
+    fn applies_cfg_overrides() {
+        let config = EvmConfig::default()
+            .with_cfg_override(|cfg| cfg.limit_contract_code_size = Some(0x10000));
+        assert!(!config.is_default());
+        let mut cfg = CfgEnv::default();
+        config.configure_cfg(&mut cfg);
+        assert_eq!(cfg.limit_contract_code_size, Some(0x10000));
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     database::StateProviderDatabase,
+    evm_config::EvmConfig,
     processor::EVMProcessor,
     stack::{InspectorStack, InspectorStackConfig},
+    state_access::StateAccessRecorder,
 };
 use reth_primitives::ChainSpec;
 use reth_provider::{ExecutorFactory, PrunableBlockExecutor, StateProvider};
@@ -12,12 +14,19 @@ use std::sync::Arc;
 pub struct EvmProcessorFactory {
     chain_spec: Arc<ChainSpec>,
     stack: Option<InspectorStack>,
+    evm_config: EvmConfig,
+    state_access_recorder: Option<StateAccessRecorder>,
 }
 
 impl EvmProcessorFactory {
     /// Create new factory
     pub fn new(chain_spec: Arc<ChainSpec>) -> Self {
-        Self { chain_spec, stack: None }
+        Self {
+            chain_spec,
+            stack: None,
+            evm_config: EvmConfig::default(),
+            state_access_recorder: None,
+        }
     }
 
     /// Sets the inspector stack for all generated executors.
@@ -31,6 +40,18 @@ impl EvmProcessorFactory {
         self.stack = Some(InspectorStack::new(config));
         self
     }
+
+    /// Sets the customizations of the EVM for all generated executors.
+    pub fn with_evm_config(mut self, evm_config: EvmConfig) -> Self {
+        self.evm_config = evm_config;
+        self
+    }
+
+    /// Records the state accessed by every block executed by the generated executors.
+    pub fn with_state_access_recorder(mut self, recorder: StateAccessRecorder) -> Self {
+        self.state_access_recorder = Some(recorder);
+        self
+    }
 }
 
 impl ExecutorFactory for EvmProcessorFactory {
@@ -43,6 +64,10 @@ impl ExecutorFactory for EvmProcessorFactory {
         if let Some(ref stack) = self.stack {
             evm.set_stack(stack.clone());
         }
+        evm.set_evm_config(self.evm_config.clone());
+        if let Some(ref recorder) = self.state_access_recorder {
+            evm.set_state_access_recorder(recorder.clone());
+        }
         evm
     }
 
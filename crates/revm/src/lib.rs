@@ -7,9 +7,16 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+/// Conflict graphs of transactions, built from their read/write sets.
+pub mod conflict_graph;
+
 /// Contains glue code for integrating reth database into revm's [Database].
 pub mod database;
 
+/// Customizations of the EVM shared by all executions of a node.
+pub mod evm_config;
+pub use evm_config::EvmConfig;
+
 /// revm implementation of reth block and transaction executors.
 mod factory;
 
@@ -19,6 +26,13 @@ pub mod processor;
 /// State changes that are not related to transactions.
 pub mod state_change;
 
+/// Recording of the state accessed by executed blocks.
+pub mod state_access;
+pub use state_access::StateAccessRecorder;
+
+/// Stateless block execution from an execution witness.
+pub mod stateless;
+
 /// revm executor factory.
 pub use factory::EvmProcessorFactory;
 
@@ -7,6 +7,7 @@ use reth_primitives::{
     Receipt, U256,
 };
 use reth_provider::{BlockExecutor, BlockExecutorStats, BundleStateWithReceipts};
+use reth_trie::keccak::KeySender;
 use revm::DatabaseCommit;
 use std::time::Instant;
 use tracing::{debug, trace};
@@ -118,6 +119,10 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
             self.stats.execution_duration += time.elapsed();
             let time = Instant::now();
 
+            if let Some(key_sender) = &self.key_sender {
+                key_sender.send_state(&state);
+            }
+            self.record_state_accesses(receipts.len(), &state);
             self.db_mut().commit(state);
 
             self.stats.apply_state_duration += time.elapsed();
@@ -166,4 +171,8 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
     fn size_hint(&self) -> Option<usize> {
         self.evm.db.as_ref().map(|db| db.bundle_size_hint())
     }
+
+    fn set_key_sender(&mut self, sender: KeySender) {
+        self.key_sender = Some(sender);
+    }
 }
@@ -1,7 +1,9 @@
 use crate::{
     database::StateProviderDatabase,
     eth_dao_fork::{DAO_HARDFORK_BENEFICIARY, DAO_HARDKFORK_ACCOUNTS},
+    evm_config::EvmConfig,
     stack::{InspectorStack, InspectorStackConfig},
+    state_access::{BlockStateAccesses, StateAccess, StateAccessRecorder},
     state_change::{apply_beacon_root_contract_call, post_block_balance_increments},
 };
 use reth_interfaces::executor::{BlockExecutionError, BlockValidationError};
@@ -14,9 +16,10 @@ use reth_primitives::{
 use reth_provider::{
     BlockExecutor, BlockExecutorStats, ProviderError, PrunableBlockExecutor, StateProvider,
 };
+use reth_trie::keccak::KeySender;
 use revm::{
     db::{states::bundle_state::BundleRetention, StateDBBox},
-    primitives::ResultAndState,
+    primitives::{ResultAndState, State as EvmState},
     State, EVM,
 };
 use std::{sync::Arc, time::Instant};
@@ -55,6 +58,8 @@ pub struct EVMProcessor<'a> {
     pub(crate) evm: EVM<StateDBBox<'a, ProviderError>>,
     /// Hook and inspector stack that we want to invoke on that hook.
     stack: InspectorStack,
+    /// Customizations of the EVM.
+    evm_config: EvmConfig,
     /// The collection of receipts.
     /// Outer vector stores receipts for each block sequentially.
     /// The inner vector stores receipts ordered by transaction number.
@@ -74,6 +79,12 @@ pub struct EVMProcessor<'a> {
     pruning_address_filter: Option<(u64, Vec<Address>)>,
     /// Execution stats
     pub(crate) stats: BlockExecutorStats,
+    /// Receives the keys of the state changed by every executed transaction.
+    pub(crate) key_sender: Option<KeySender>,
+    /// Records the state accessed by every executed block.
+    state_access_recorder: Option<StateAccessRecorder>,
+    /// The state accessed by the transactions of the block being executed, if recorded.
+    state_accesses: Vec<StateAccess>,
 }
 
 impl<'a> EVMProcessor<'a> {
@@ -89,12 +100,16 @@ impl<'a> EVMProcessor<'a> {
             chain_spec,
             evm,
             stack: InspectorStack::new(InspectorStackConfig::default()),
+            evm_config: EvmConfig::default(),
             receipts: Receipts::new(),
             first_block: None,
             tip: None,
             prune_modes: PruneModes::none(),
             pruning_address_filter: None,
             stats: BlockExecutorStats::default(),
+            key_sender: None,
+            state_access_recorder: None,
+            state_accesses: Vec::new(),
         }
     }
 
@@ -122,12 +137,16 @@ impl<'a> EVMProcessor<'a> {
             chain_spec,
             evm,
             stack: InspectorStack::new(InspectorStackConfig::default()),
+            evm_config: EvmConfig::default(),
             receipts: Receipts::new(),
             first_block: None,
             tip: None,
             prune_modes: PruneModes::none(),
             pruning_address_filter: None,
             stats: BlockExecutorStats::default(),
+            key_sender: None,
+            state_access_recorder: None,
+            state_accesses: Vec::new(),
         }
     }
 
@@ -136,6 +155,16 @@ impl<'a> EVMProcessor<'a> {
         self.stack = stack;
     }
 
+    /// Configures the executor with the given customizations of the EVM.
+    pub fn set_evm_config(&mut self, evm_config: EvmConfig) {
+        self.evm_config = evm_config;
+    }
+
+    /// Configures the executor to record the state accessed by every executed block.
+    pub fn set_state_access_recorder(&mut self, recorder: StateAccessRecorder) {
+        self.state_access_recorder = Some(recorder);
+    }
+
     /// Configure the executor with the given block.
     pub fn set_first_block(&mut self, num: BlockNumber) {
         self.first_block = Some(num);
@@ -164,6 +193,7 @@ impl<'a> EVMProcessor<'a> {
             header,
             total_difficulty,
         );
+        self.evm_config.configure_cfg(&mut self.evm.env.cfg);
     }
 
     /// Applies the pre-block call to the EIP-4788 beacon block root contract.
@@ -246,7 +276,7 @@ impl<'a> EVMProcessor<'a> {
         let hash = transaction.hash();
         let out = if self.stack.should_inspect(&self.evm.env, hash) {
             // execution with inspector.
-            let output = self.evm.inspect(&mut self.stack);
+            let output = self.evm_config.inspect(&mut self.evm, &mut self.stack);
             tracing::trace!(
                 target: "evm",
                 ?hash, ?output, ?transaction, env = ?self.evm.env,
@@ -255,11 +285,19 @@ impl<'a> EVMProcessor<'a> {
             output
         } else {
             // main execution.
-            self.evm.transact()
+            self.evm_config.transact(&mut self.evm)
         };
         out.map_err(|e| BlockValidationError::EVM { hash, error: e.into() }.into())
     }
 
+    /// Records the state accessed by the transaction at the index, if state accesses are
+    /// recorded.
+    pub(crate) fn record_state_accesses(&mut self, tx_index: usize, state: &EvmState) {
+        if self.state_access_recorder.is_some() {
+            self.state_accesses.extend(StateAccess::from_transaction(tx_index, state));
+        }
+    }
+
     /// Execute the block, verify gas usage and apply post-block state changes.
     pub(crate) fn execute_inner(
         &mut self,
@@ -268,6 +306,7 @@ impl<'a> EVMProcessor<'a> {
     ) -> Result<Vec<Receipt>, BlockExecutionError> {
         self.init_env(&block.header, total_difficulty);
         self.apply_beacon_root_contract_call(block)?;
+        self.state_accesses.clear();
         let (receipts, cumulative_gas_used) = self.execute_transactions(block, total_difficulty)?;
 
         // Check if gas used matches the value set in header.
@@ -283,6 +322,14 @@ impl<'a> EVMProcessor<'a> {
         self.apply_post_execution_state_change(block, total_difficulty)?;
         self.stats.apply_post_execution_state_changes_duration += time.elapsed();
 
+        if let Some(recorder) = &self.state_access_recorder {
+            recorder.record(&BlockStateAccesses {
+                number: block.number,
+                hash: block.header.hash_slow(),
+                accesses: std::mem::take(&mut self.state_accesses),
+            });
+        }
+
         let time = Instant::now();
         let retention = if self.tip.map_or(true, |tip| {
             !self
@@ -446,6 +493,10 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
             self.stats.execution_duration += time.elapsed();
             let time = Instant::now();
 
+            if let Some(key_sender) = &self.key_sender {
+                key_sender.send_state(&state);
+            }
+            self.record_state_accesses(receipts.len(), &state);
             self.db_mut().commit(state);
 
             self.stats.apply_state_duration += time.elapsed();
@@ -484,6 +535,10 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
     fn size_hint(&self) -> Option<usize> {
         self.evm.db.as_ref().map(|db| db.bundle_size_hint())
     }
+
+    fn set_key_sender(&mut self, sender: KeySender) {
+        self.key_sender = Some(sender);
+    }
 }
 
 impl<'a> PrunableBlockExecutor for EVMProcessor<'a> {
@@ -0,0 +1,268 @@
+use crate::conflict_graph::{ReadWriteSet, StateKey};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use parking_lot::Mutex;
+use reth_primitives::{Address, BlockHash, BlockNumber, U256};
+use revm::primitives::State as EvmState;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::{debug, warn};
+
+/// The default number of block numbers a [StateAccessRecorder] keeps the files of.
+pub const DEFAULT_STATE_ACCESS_RETENTION: u64 = 10_000;
+
+/// The prefix of the files written by a [StateAccessRecorder].
+const FILE_PREFIX: &str = "state-accesses-";
+
+/// The extension of the files written by a [StateAccessRecorder].
+const FILE_EXTENSION: &str = ".json.gz";
+
+/// Whether a transaction only read a state location or changed it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessKind {
+    /// The location was loaded but not changed.
+    Read,
+    /// The location was changed.
+    Write,
+}
+
+/// A state location accessed by a transaction of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateAccess {
+    /// The index of the transaction in the block.
+    pub tx_index: usize,
+    /// The address of the accessed account.
+    pub address: Address,
+    /// The accessed storage slot, `None` for the balance, nonce and code of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<U256>,
+    /// Whether the location was read or written.
+    pub kind: AccessKind,
+}
+
+impl StateAccess {
+    /// Returns the accesses of the transaction at the index from the state returned by executing
+    /// it, ordered by address with the account before its slots.
+    pub fn from_transaction(tx_index: usize, state: &EvmState) -> Vec<Self> {
+        let ReadWriteSet { reads, writes } = ReadWriteSet::from_state(state);
+        let mut accesses = reads
+            .into_iter()
+            .map(|key| (key, AccessKind::Read))
+            .chain(writes.into_iter().map(|key| (key, AccessKind::Write)))
+            .map(|(key, kind)| {
+                let slot = match key {
+                    StateKey::Account(_) => None,
+                    StateKey::Storage(_, slot) => Some(slot),
+                };
+                Self { tx_index, address: key.address(), slot, kind }
+            })
+            .collect::<Vec<_>>();
+        accesses.sort_unstable_by_key(|access| (access.address, access.slot));
+        accesses
+    }
+}
+
+/// The state accessed by the transactions of an executed block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockStateAccesses {
+    /// The block number.
+    pub number: BlockNumber,
+    /// The block hash.
+    pub hash: BlockHash,
+    /// The accesses of the transactions, in transaction order.
+    pub accesses: Vec<StateAccess>,
+}
+
+impl BlockStateAccesses {
+    /// Reads the accesses of a block from a file written by a [StateAccessRecorder].
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let file = GzDecoder::new(BufReader::new(File::open(path)?));
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Returns the name of the file the accesses are written to.
+    fn file_name(&self) -> String {
+        format!("{FILE_PREFIX}{}-{}{FILE_EXTENSION}", self.number, self.hash)
+    }
+}
+
+/// Writes the state accesses of executed blocks into a directory, one gzip compressed JSON file
+/// per block, for research on parallel execution and caching with real workloads.
+///
+/// Only the files of the last `retention` block numbers are kept: once a block is recorded, the
+/// files of blocks that many numbers below it are removed, including files left in the directory
+/// by previous runs. Blocks at the same height on different forks are recorded separately.
+///
+/// The recorder is cheap to clone and shared by all executors of a node.
+#[derive(Debug, Clone)]
+pub struct StateAccessRecorder {
+    inner: Arc<RecorderInner>,
+}
+
+#[derive(Debug)]
+struct RecorderInner {
+    dir: PathBuf,
+    retention: u64,
+    /// The recorded files by block number.
+    files: Mutex<BTreeMap<BlockNumber, Vec<PathBuf>>>,
+}
+
+impl StateAccessRecorder {
+    /// Creates a recorder that writes into the directory and keeps the files of the last
+    /// `retention` block numbers.
+    pub fn new(dir: PathBuf, retention: u64) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let mut files = BTreeMap::<BlockNumber, Vec<PathBuf>>::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if let Some(number) =
+                path.file_name().and_then(|name| name.to_str()).and_then(parse_file_name)
+            {
+                files.entry(number).or_default().push(path);
+            }
+        }
+        let inner = RecorderInner { dir, retention: retention.max(1), files: Mutex::new(files) };
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// Returns the directory the files are written to.
+    pub fn dir(&self) -> &Path {
+        &self.inner.dir
+    }
+
+    /// Writes the accesses of the block and removes the files that are out of retention.
+    ///
+    /// Failures are logged, recording never fails the execution of a block.
+    pub fn record(&self, accesses: &BlockStateAccesses) {
+        match self.write(accesses) {
+            Ok(path) => {
+                debug!(target: "evm", number = accesses.number, path = %path.display(), "Recorded state accesses");
+                self.retain(accesses.number, path);
+            }
+            Err(err) => {
+                warn!(target: "evm", number = accesses.number, %err, "Failed to record state accesses")
+            }
+        }
+    }
+
+    /// Writes the accesses to a temporary file and moves it into place once it's complete.
+    fn write(&self, accesses: &BlockStateAccesses) -> io::Result<PathBuf> {
+        let path = self.inner.dir.join(accesses.file_name());
+        let tmp_path = path.with_extension("tmp");
+        let mut file =
+            GzEncoder::new(BufWriter::new(File::create(&tmp_path)?), Compression::default());
+        serde_json::to_writer(&mut file, accesses)?;
+        file.finish()?.flush()?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(path)
+    }
+
+    /// Adds the file of the block and removes the files of blocks out of retention.
+    fn retain(&self, number: BlockNumber, path: PathBuf) {
+        let mut files = self.inner.files.lock();
+        let paths = files.entry(number).or_default();
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+
+        let highest = files.keys().next_back().copied().unwrap_or(number);
+        while let Some(entry) = files.first_entry() {
+            if *entry.key() + self.inner.retention > highest {
+                break
+            }
+            for path in entry.remove() {
+                if let Err(err) = fs::remove_file(&path) {
+                    warn!(target: "evm", path = %path.display(), %err, "Failed to remove state accesses");
+                }
+            }
+        }
+    }
+}
+
+/// Returns the block number of a file written by a [StateAccessRecorder].
+fn parse_file_name(name: &str) -> Option<BlockNumber> {
+    let (number, _hash) =
+        name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_EXTENSION)?.split_once('-')?;
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::B256;
+    use revm::primitives::{Account, AccountInfo, StorageSlot};
+
+    #[test]
+    fn orders_transaction_accesses() {
+        let caller = Address::with_last_byte(1);
+        let contract = Address::with_last_byte(2);
+
+        let mut state = EvmState::default();
+        let mut account = Account::from(AccountInfo::default());
+        account.mark_touch();
+        state.insert(caller, account);
+        let mut account = Account::from(AccountInfo::default());
+        account.storage.insert(
+            U256::from(2),
+            StorageSlot { previous_or_original_value: U256::ZERO, present_value: U256::from(1) },
+        );
+        account.storage.insert(
+            U256::from(1),
+            StorageSlot { previous_or_original_value: U256::from(1), present_value: U256::from(1) },
+        );
+        state.insert(contract, account);
+
+        let access = |address, slot: Option<u64>, kind| StateAccess {
+            tx_index: 3,
+            address,
+            slot: slot.map(U256::from),
+            kind,
+        };
+        assert_eq!(
+            StateAccess::from_transaction(3, &state),
+            vec![
+                access(caller, None, AccessKind::Write),
+                access(contract, None, AccessKind::Read),
+                access(contract, Some(1), AccessKind::Read),
+                access(contract, Some(2), AccessKind::Write),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_files_within_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let block = |number: u64| BlockStateAccesses {
+            number,
+            hash: B256::with_last_byte(number as u8),
+            accesses: vec![StateAccess {
+                tx_index: 0,
+                address: Address::with_last_byte(number as u8),
+                slot: Some(U256::from(number)),
+                kind: AccessKind::Write,
+            }],
+        };
+        let path = |number: u64| dir.path().join(block(number).file_name());
+
+        let recorder = StateAccessRecorder::new(dir.path().to_path_buf(), 2).unwrap();
+        recorder.record(&block(1));
+        recorder.record(&block(2));
+        assert_eq!(BlockStateAccesses::read(&path(1)).unwrap(), block(1));
+
+        // files of previous runs are removed as well
+        let recorder = StateAccessRecorder::new(dir.path().to_path_buf(), 2).unwrap();
+        recorder.record(&block(3));
+        assert!(!path(1).exists());
+        assert!(path(2).exists());
+        assert_eq!(BlockStateAccesses::read(&path(3)).unwrap(), block(3));
+        assert_eq!(parse_file_name(&block(3).file_name()), Some(3));
+    }
+}
@@ -4,7 +4,7 @@ use reth_primitives::{
     constants::SYSTEM_ADDRESS, revm::env::fill_tx_env_with_beacon_root_contract_call, Address,
     ChainSpec, Header, Withdrawal, B256, U256,
 };
-use revm::{Database, DatabaseCommit, EVM};
+use revm::{primitives::State as EvmState, Database, DatabaseCommit, EVM};
 use std::collections::HashMap;
 
 /// Collect all balance changes at the end of the block.
@@ -67,8 +67,39 @@ pub fn apply_beacon_root_contract_call<DB: Database + DatabaseCommit>(
 where
     DB::Error: std::fmt::Display,
 {
-    if !chain_spec.is_cancun_active_at_timestamp(block_timestamp) {
+    let Some(state) = transact_beacon_root_contract_call(
+        chain_spec,
+        block_timestamp,
+        block_number,
+        parent_beacon_block_root,
+        evm,
+    )?
+    else {
         return Ok(())
+    };
+
+    let db = evm.db().expect("db to not be moved");
+    db.commit(state);
+
+    Ok(())
+}
+
+/// Executes the pre-block call to the EIP-4788 beacon block root contract like
+/// [apply_beacon_root_contract_call], but returns the state changes instead of committing them.
+///
+/// Returns `None` if cancun is not activated or the block is the genesis block.
+pub fn transact_beacon_root_contract_call<DB: Database>(
+    chain_spec: &ChainSpec,
+    block_timestamp: u64,
+    block_number: u64,
+    parent_beacon_block_root: Option<B256>,
+    evm: &mut EVM<DB>,
+) -> Result<Option<EvmState>, BlockExecutionError>
+where
+    DB::Error: std::fmt::Display,
+{
+    if !chain_spec.is_cancun_active_at_timestamp(block_timestamp) {
+        return Ok(None)
     }
 
     let parent_beacon_block_root =
@@ -83,7 +114,7 @@ where
             }
             .into())
         }
-        return Ok(())
+        return Ok(None)
     }
 
     // get previous env
@@ -107,13 +138,10 @@ where
     state.remove(&SYSTEM_ADDRESS);
     state.remove(&evm.env.block.coinbase);
 
-    let db = evm.db().expect("db to not be moved");
-    db.commit(state);
-
     // re-set the previous env
     evm.env = previous_env;
 
-    Ok(())
+    Ok(Some(state))
 }
 
 /// Returns a map of addresses to their balance increments if the Shanghai hardfork is active at the
@@ -0,0 +1,96 @@
+use crate::{database::StateProviderDatabase, processor::EVMProcessor};
+use reth_interfaces::executor::{BlockExecutionError, BlockValidationError};
+use reth_primitives::{BlockWithSenders, ChainSpec, GotExpected, U256};
+use reth_provider::{
+    BlockExecutor, BundleStateWithReceipts, ExecutionWitness, StateRootProvider,
+    WitnessStateProvider,
+};
+use std::sync::Arc;
+
+/// Executes and verifies the block using only the state of the execution witness.
+///
+/// The witness headers are verified to end at the parent of the block and the witness state is
+/// verified against the state root of the parent header. After execution, the receipts and the
+/// post state root are checked against the block header.
+///
+/// Returns the post state of the block.
+pub fn execute_stateless(
+    chain_spec: Arc<ChainSpec>,
+    block: &BlockWithSenders,
+    total_difficulty: U256,
+    witness: ExecutionWitness,
+) -> Result<BundleStateWithReceipts, BlockExecutionError> {
+    let provider = WitnessStateProvider::new(witness, block.parent_hash)
+        .map_err(BlockValidationError::InvalidWitness)?;
+    let mut executor = EVMProcessor::new_with_db(chain_spec, StateProviderDatabase::new(&provider));
+    executor.execute_and_verify_receipt(block, total_difficulty)?;
+    let state = executor.take_output_state();
+
+    let state_root = provider.state_root(&state).map_err(BlockValidationError::InvalidWitness)?;
+    if state_root != block.state_root {
+        return Err(BlockValidationError::StateRootDiff(
+            GotExpected { got: state_root, expected: block.state_root }.into(),
+        )
+        .into())
+    }
+
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        constants::EMPTY_ROOT_HASH, Account, Address, Block, ChainSpecBuilder, Header, Withdrawal,
+        MAINNET,
+    };
+    use reth_trie::test_utils::state_root;
+
+    #[test]
+    fn withdrawal_into_empty_state() {
+        let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).shanghai_activated().build());
+        let parent = Header::default();
+        let recipient = Address::with_last_byte(1);
+        let withdrawal = Withdrawal { index: 0, validator_index: 0, address: recipient, amount: 1 };
+        let account = Account { balance: U256::from(1_000_000_000u64), ..Default::default() };
+        let header = Header {
+            number: 1,
+            timestamp: 1,
+            parent_hash: parent.hash_slow(),
+            state_root: state_root(std::iter::once((recipient, (account, std::iter::empty())))),
+            ..Header::default()
+        };
+        let block = |header| BlockWithSenders {
+            block: Block {
+                header,
+                body: vec![],
+                ommers: vec![],
+                withdrawals: Some(vec![withdrawal.clone()]),
+            },
+            senders: vec![],
+        };
+        let witness = ExecutionWitness { headers: vec![parent], ..Default::default() };
+
+        let state = execute_stateless(
+            chain_spec.clone(),
+            &block(header.clone()),
+            U256::ZERO,
+            witness.clone(),
+        )
+        .unwrap();
+        assert_eq!(state.account(&recipient), Some(Some(account)));
+
+        // the post state root does not match
+        let invalid = Header { state_root: EMPTY_ROOT_HASH, ..header.clone() };
+        assert!(matches!(
+            execute_stateless(chain_spec.clone(), &block(invalid), U256::ZERO, witness),
+            Err(BlockExecutionError::Validation(BlockValidationError::StateRootDiff(_)))
+        ));
+
+        // the witness does not contain the parent header
+        assert!(matches!(
+            execute_stateless(chain_spec, &block(header), U256::ZERO, ExecutionWitness::default()),
+            Err(BlockExecutionError::Validation(BlockValidationError::InvalidWitness(_)))
+        ));
+    }
+}
@@ -1,6 +1,8 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use reth_primitives::NodeRecord;
-use reth_rpc_types::{NodeInfo, PeerInfo};
+use reth_primitives::{NodeRecord, TxHash};
+use reth_rpc_types::{
+    ApiKey, DiskStatus, NodeInfo, PeerInfo, TargetLogLevel, TransactionFirstSeen,
+};
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
@@ -47,3 +49,82 @@ pub trait AdminApi {
     #[method(name = "nodeInfo")]
     async fn node_info(&self) -> RpcResult<NodeInfo>;
 }
+
+/// Admin namespace rpc interface for inspecting the node configuration.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdminConfigApi {
+    /// Returns the effective node configuration, including changes that were applied at runtime.
+    #[method(name = "config")]
+    fn config(&self) -> RpcResult<serde_json::Value>;
+}
+
+/// Admin namespace rpc interface for inspecting the disk space of the node.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdminDiskApi {
+    /// Returns the free disk space of the data directory and whether the node is restricted
+    /// because of it.
+    #[method(name = "diskStatus")]
+    fn disk_status(&self) -> RpcResult<DiskStatus>;
+}
+
+/// Admin namespace rpc interface for changing the log levels of the node at runtime.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdminLogApi {
+    /// Sets the level of the logs of the given target, e.g. `trie-parallel`, in all log outputs.
+    ///
+    /// If a duration in seconds is given, the configured level of the target is restored once it
+    /// elapsed.
+    #[method(name = "setLogLevel")]
+    fn set_log_level(
+        &self,
+        target: String,
+        level: String,
+        duration_secs: Option<u64>,
+    ) -> RpcResult<TargetLogLevel>;
+
+    /// Restores the configured level of the logs of the given target.
+    ///
+    /// Returns true if the level of the target was changed.
+    #[method(name = "resetLogLevel")]
+    fn reset_log_level(&self, target: String) -> RpcResult<bool>;
+
+    /// Returns the log levels of targets that were changed at runtime.
+    #[method(name = "logLevels")]
+    fn log_levels(&self) -> RpcResult<Vec<TargetLogLevel>>;
+}
+
+/// Admin namespace rpc interface for managing the API keys of the RPC server.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdminApiKeysApi {
+    /// Returns the API keys of the RPC server.
+    #[method(name = "apiKeys")]
+    fn api_keys(&self) -> RpcResult<Vec<ApiKey>>;
+
+    /// Adds the given API key, replacing the key with the same value.
+    ///
+    /// Returns true if a key was replaced.
+    #[method(name = "addApiKey")]
+    fn add_api_key(&self, key: ApiKey) -> RpcResult<bool>;
+
+    /// Removes the given API key.
+    ///
+    /// Returns true if the key existed.
+    #[method(name = "removeApiKey")]
+    fn remove_api_key(&self, key: String) -> RpcResult<bool>;
+}
+
+/// Admin namespace rpc interface for querying when transactions were first seen by the node.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdminFirstSeenApi {
+    /// Returns when the transaction was first seen and, if it was included in a canonical block,
+    /// how long before the block it was seen.
+    ///
+    /// Returns `None` if the transaction isn't tracked, e.g. because it was seen too long ago.
+    #[method(name = "transactionFirstSeen")]
+    fn transaction_first_seen(&self, hash: TxHash) -> RpcResult<Option<TransactionFirstSeen>>;
+}
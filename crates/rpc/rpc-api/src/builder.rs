@@ -0,0 +1,23 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_rpc_types::{TopOfBlockBidRequest, TopOfBlockBidResponse};
+
+/// Builder namespace rpc interface for external parties that bid for the placement of their
+/// transactions in built blocks.
+///
+/// This is served on a dedicated endpoint, bids are authenticated by the signature of their
+/// bidder.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "builder"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "builder"))]
+pub trait BuilderApi {
+    /// Submits a bid for the top of the block with the given parent and timestamp.
+    ///
+    /// Bids have to be signed by one of the allowed bidders and are accepted until a cutoff before
+    /// the timestamp of the block. The payload builder places the transactions of the highest bid
+    /// that is valid at the start of the block first in the block, a bid is valid if all of its
+    /// transactions succeed and they pay the fee recipient at least the value of the bid.
+    #[method(name = "submitTopOfBlockBid")]
+    async fn submit_top_of_block_bid(
+        &self,
+        request: TopOfBlockBidRequest,
+    ) -> RpcResult<TopOfBlockBidResponse>;
+}
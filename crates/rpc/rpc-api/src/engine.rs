@@ -208,3 +208,19 @@ pub trait EngineEthApi {
     #[method(name = "getLogs")]
     async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>>;
 }
+
+/// Debug methods of the engine auth server, for driving the node without a consensus layer.
+///
+/// These are meant for test setups that need to import blocks deterministically and are only
+/// served on the authenticated server if enabled.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "debug"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "debug"))]
+#[async_trait]
+pub trait EngineDebugApi {
+    /// Imports an RLP encoded block as if it was received with `engine_newPayload`.
+    ///
+    /// Returns the payload status of the block, the block becomes canonical with a subsequent
+    /// `engine_forkchoiceUpdated`.
+    #[method(name = "insertBlock")]
+    async fn insert_block(&self, block: Bytes) -> RpcResult<PayloadStatus>;
+}
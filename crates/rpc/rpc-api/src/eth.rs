@@ -4,9 +4,9 @@ use reth_primitives::{
     Address, BlockId, BlockNumberOrTag, Bytes, B256, B64, U256, U64,
 };
 use reth_rpc_types::{
-    state::StateOverride, AccessListWithGasUsed, BlockOverrides, Bundle, CallRequest,
-    EIP1186AccountProofResponse, EthCallResponse, FeeHistory, Index, RichBlock, StateContext,
-    SyncStatus, Transaction, TransactionReceipt, TransactionRequest, Work,
+    state::StateOverride, AccessListBatchItem, AccessListWithGasUsed, BlockOverrides, Bundle,
+    CallRequest, EIP1186AccountProofResponse, EthCallResponse, FeeHistory, Index, RichBlock,
+    StateContext, SyncStatus, Transaction, TransactionReceipt, TransactionRequest, Work,
 };
 
 /// Eth rpc interface: <https://ethereum.github.io/execution-apis/api-documentation/>
@@ -183,6 +183,18 @@ pub trait EthApi {
         block_number: Option<BlockId>,
     ) -> RpcResult<AccessListWithGasUsed>;
 
+    /// Generates the access lists of many independent transactions, see `eth_createAccessList`.
+    ///
+    /// All transactions are simulated concurrently on the state of the same block, none of them
+    /// sees the state changes of the others. Returns an item per transaction, in request order,
+    /// with either its access list and gas or the error it failed with.
+    #[method(name = "createAccessListBatch")]
+    async fn create_access_list_batch(
+        &self,
+        requests: Vec<CallRequest>,
+        block_number: Option<BlockId>,
+    ) -> RpcResult<Vec<AccessListBatchItem>>;
+
     /// Generates and returns an estimate of how much gas is necessary to allow the transaction to
     /// complete.
     #[method(name = "estimateGas")]
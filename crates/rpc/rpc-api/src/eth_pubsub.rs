@@ -1,5 +1,5 @@
 use jsonrpsee::proc_macros::rpc;
-use reth_rpc_types::pubsub::{Params, SubscriptionKind};
+use reth_rpc_types::{pubsub::Params, EthSubscriptionKind};
 
 /// Ethereum pub-sub rpc interface.
 #[rpc(server, namespace = "eth")]
@@ -12,7 +12,7 @@ pub trait EthPubSubApi {
     )]
     async fn subscribe(
         &self,
-        kind: SubscriptionKind,
+        kind: EthSubscriptionKind,
         params: Option<Params>,
     ) -> jsonrpsee::core::SubscriptionResult;
 }
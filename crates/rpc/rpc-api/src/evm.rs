@@ -0,0 +1,27 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_primitives::U64;
+
+/// Dev mode mining controls, compatible with the `evm_` namespace of anvil and hardhat.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "evm"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "evm"))]
+pub trait EvmApi {
+    /// Mines `blocks` new blocks (default 1, at most 1024) and returns once they are canonical.
+    #[method(name = "mine")]
+    async fn evm_mine(&self, blocks: Option<U64>) -> RpcResult<String>;
+
+    /// Sets the timestamp of the next block.
+    ///
+    /// The timestamp must be greater than the timestamp of the current best block.
+    #[method(name = "setNextBlockTimestamp")]
+    async fn evm_set_next_block_timestamp(&self, timestamp: U64) -> RpcResult<()>;
+
+    /// Shifts the clock used for all future blocks by `seconds` and returns the total offset.
+    #[method(name = "increaseTime")]
+    async fn evm_increase_time(&self, seconds: U64) -> RpcResult<U64>;
+
+    /// Sets the interval in milliseconds at which blocks are mined.
+    ///
+    /// An interval of `0` disables interval mining.
+    #[method(name = "setIntervalMining")]
+    async fn evm_set_interval_mining(&self, interval_ms: u64) -> RpcResult<()>;
+}
@@ -14,18 +14,21 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 mod admin;
+mod builder;
 mod bundle;
 mod debug;
 mod engine;
 mod eth;
 mod eth_filter;
 mod eth_pubsub;
+mod evm;
 mod mev;
 mod net;
 mod otterscan;
 mod reth;
 mod rpc;
 mod trace;
+mod trie;
 mod txpool;
 mod validation;
 mod web3;
@@ -33,22 +36,33 @@ mod web3;
 /// re-export of all server traits
 pub use servers::*;
 
+pub use trie::{HashedAccountEntry, PinnedState, TrieNodeEntry};
+
 /// Aggregates all server traits.
 pub mod servers {
     pub use crate::{
-        admin::AdminApiServer,
+        admin::{
+            AdminApiKeysApiServer, AdminApiServer, AdminConfigApiServer, AdminDiskApiServer,
+            AdminFirstSeenApiServer, AdminLogApiServer,
+        },
+        builder::BuilderApiServer,
         bundle::{EthBundleApiServer, EthCallBundleApiServer},
         debug::DebugApiServer,
-        engine::{EngineApiServer, EngineEthApiServer},
+        engine::{EngineApiServer, EngineDebugApiServer, EngineEthApiServer},
         eth::EthApiServer,
         eth_filter::EthFilterApiServer,
         eth_pubsub::EthPubSubApiServer,
+        evm::EvmApiServer,
         mev::MevApiServer,
         net::NetApiServer,
         otterscan::OtterscanServer,
-        reth::RethApiServer,
+        reth::{
+            RethApiServer, RethReceiptsApiServer, RethStreamApiServer,
+            RethTransactionStatusApiServer,
+        },
         rpc::RpcApiServer,
         trace::TraceApiServer,
+        trie::TrieNodeApiServer,
         txpool::TxPoolApiServer,
         validation::BlockSubmissionValidationApiServer,
         web3::Web3ApiServer,
@@ -63,17 +77,24 @@ pub use clients::*;
 #[cfg(feature = "client")]
 pub mod clients {
     pub use crate::{
-        admin::AdminApiClient,
+        admin::{
+            AdminApiClient, AdminApiKeysApiClient, AdminConfigApiClient, AdminDiskApiClient,
+            AdminFirstSeenApiClient, AdminLogApiClient,
+        },
+        builder::BuilderApiClient,
         bundle::{EthBundleApiClient, EthCallBundleApiClient},
         debug::DebugApiClient,
-        engine::{EngineApiClient, EngineEthApiClient},
+        engine::{EngineApiClient, EngineDebugApiClient, EngineEthApiClient},
         eth::EthApiClient,
         eth_filter::EthFilterApiClient,
+        evm::EvmApiClient,
         mev::MevApiClient,
         net::NetApiClient,
         otterscan::OtterscanClient,
+        reth::RethReceiptsApiClient,
         rpc::RpcApiServer,
         trace::TraceApiClient,
+        trie::TrieNodeApiClient,
         txpool::TxPoolApiClient,
         validation::BlockSubmissionValidationApiClient,
         web3::Web3ApiClient,
@@ -1,5 +1,9 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use reth_primitives::{Address, BlockId, U256};
+use reth_primitives::{Address, BlockId, TxHash, B256, U256, U64};
+use reth_rpc_types::{
+    AccountHistoryPage, HeaderAccumulatorProof, InclusionProof, ProofTarget, PruneSegmentStatus,
+    ReorgEntry, TransactionReceipt, TransactionStatus,
+};
 use std::collections::HashMap;
 
 /// Reth API namespace for reth-specific methods
@@ -12,4 +16,133 @@ pub trait RethApi {
         &self,
         block_id: BlockId,
     ) -> RpcResult<HashMap<Address, U256>>;
+
+    /// Returns the numbers of the blocks in which the account changed, starting at `fromBlock`.
+    ///
+    /// Results are paginated, the `nextBlock` of the response is the `fromBlock` of the next
+    /// page. Only blocks that are persisted and whose history is not pruned are returned.
+    #[method(name = "getAccountHistory")]
+    async fn reth_get_account_history(
+        &self,
+        address: Address,
+        from_block: Option<U64>,
+        limit: Option<usize>,
+    ) -> RpcResult<AccountHistoryPage>;
+
+    /// Returns the retention boundaries of all prune segments, i.e. the first block whose data of
+    /// the segment is still available.
+    ///
+    /// Requests for data of a segment before its boundary fail with an error whose data contains
+    /// the boundary.
+    #[method(name = "pruneStatus")]
+    async fn reth_prune_status(&self) -> RpcResult<Vec<PruneSegmentStatus>>;
+
+    /// Returns the most recent reorgs of the canonical chain, newest first.
+    ///
+    /// Returns at most `limit` reorgs, if set.
+    #[method(name = "reorgHistory")]
+    async fn reth_reorg_history(&self, limit: Option<usize>) -> RpcResult<Vec<ReorgEntry>>;
+
+    /// Returns the merkle proof of the transaction in the transactions trie of its block.
+    ///
+    /// Returns `null` if the transaction is not included in a persisted canonical block.
+    #[method(name = "getTransactionInclusionProof")]
+    async fn reth_get_transaction_inclusion_proof(
+        &self,
+        hash: TxHash,
+    ) -> RpcResult<Option<InclusionProof>>;
+
+    /// Returns the merkle proof of the receipt of the transaction in the receipts trie of its
+    /// block.
+    ///
+    /// Returns `null` if the transaction is not included in a persisted canonical block, or if its
+    /// receipt is pruned.
+    #[method(name = "getReceiptInclusionProof")]
+    async fn reth_get_receipt_inclusion_proof(
+        &self,
+        hash: TxHash,
+    ) -> RpcResult<Option<InclusionProof>>;
+
+    /// Returns the root of the header accumulator over all completed epochs of the canonical
+    /// chain.
+    #[method(name = "getHeaderAccumulatorRoot")]
+    async fn reth_get_header_accumulator_root(&self) -> RpcResult<B256>;
+
+    /// Returns the proof of the block in the header accumulator.
+    ///
+    /// Returns `null` if the epoch of the block is not completed yet.
+    #[method(name = "getHeaderAccumulatorProof")]
+    async fn reth_get_header_accumulator_proof(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Option<HeaderAccumulatorProof>>;
+
+    /// Creates a subscription that yields fresh merkle proofs of the given accounts and storage
+    /// slots for every new canonical block.
+    ///
+    /// At most 256 accounts with 1024 storage slots in total can be watched.
+    #[subscription(
+        name = "subscribeProofs",
+        unsubscribe = "unsubscribeProofs",
+        item = reth_rpc_types::ProofsUpdate
+    )]
+    async fn reth_subscribe_proofs(
+        &self,
+        targets: Vec<ProofTarget>,
+    ) -> jsonrpsee::core::SubscriptionResult;
+}
+
+/// Reth API namespace for tracking transactions from the pool into the canonical chain.
+///
+/// This is served by the node itself, since it needs access to the payload builder and the
+/// blockchain tree.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "reth"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "reth"))]
+pub trait RethTransactionStatusApi {
+    /// Returns how far the transaction is on its way into the canonical chain.
+    ///
+    /// The pool, the active payload jobs, the unpersisted blocks and the canonical chain are
+    /// queried in one call, the most advanced status is returned.
+    #[method(name = "getTransactionStatus")]
+    async fn reth_get_transaction_status(&self, hash: TxHash) -> RpcResult<TransactionStatus>;
+}
+
+/// Reth API namespace for reading the receipts of many transactions at once.
+///
+/// This is served by the `eth` handlers, since it builds the same receipts as
+/// `eth_getTransactionReceipt`.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "reth"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "reth"))]
+pub trait RethReceiptsApi {
+    /// Returns the receipts of the transactions with the given hashes, in the same order.
+    ///
+    /// Equivalent to calling `eth_getTransactionReceipt` for every hash, but the receipts of
+    /// transactions in the same block are read at once. Contains `null` for every transaction
+    /// that does not exist or is pending.
+    #[method(name = "getTransactionReceipts")]
+    async fn reth_get_transaction_receipts(
+        &self,
+        hashes: Vec<TxHash>,
+    ) -> RpcResult<Vec<Option<TransactionReceipt>>>;
+}
+
+/// Reth API namespace for streaming the results of calls that can return very large results.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "reth"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "reth"))]
+pub trait RethStreamApi {
+    /// Creates a subscription that streams the result of a call to the given method in chunks, as
+    /// they become available. The last chunk is marked as `done`.
+    ///
+    /// Supported methods are `eth_getLogs`, `trace_filter`, `debug_traceBlockByNumber` and
+    /// `debug_traceBlockByHash`.
+    #[subscription(
+        name = "subscribeResultStream",
+        unsubscribe = "unsubscribeResultStream",
+        item = reth_rpc_types::ResultStreamChunk
+    )]
+    async fn reth_subscribe_result_stream(
+        &self,
+        method: String,
+        params: Option<serde_json::Value>,
+    ) -> jsonrpsee::core::SubscriptionResult;
 }
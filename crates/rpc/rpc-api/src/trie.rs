@@ -0,0 +1,94 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_primitives::{
+    trie::BranchNodeCompact, Account, BlockHash, BlockNumber, Bytes, StorageEntry, B256,
+};
+use serde::{Deserialize, Serialize};
+
+/// Trie node namespace rpc interface that serves trie nodes and hashed state at a pinned state
+/// root.
+///
+/// This is an internal protocol for external proof workers that compute storage roots and proofs
+/// on other machines, it is only served on a dedicated IPC endpoint.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "trie"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "trie"))]
+pub trait TrieNodeApi {
+    /// Pins the latest state of the database whose trie is up to date, all subsequent requests
+    /// with the returned id are served from this state until it is unpinned.
+    ///
+    /// The server unpins states that were not accessed for a while.
+    #[method(name = "pinState")]
+    fn pin_state(&self) -> RpcResult<PinnedState>;
+
+    /// Releases a pinned state.
+    ///
+    /// Returns true if the state was pinned.
+    #[method(name = "unpinState")]
+    fn unpin_state(&self, id: u64) -> RpcResult<bool>;
+
+    /// Returns up to `limit` account trie nodes, starting at the given path.
+    #[method(name = "accountNodes")]
+    fn account_nodes(&self, id: u64, from: Bytes, limit: usize) -> RpcResult<Vec<TrieNodeEntry>>;
+
+    /// Returns up to `limit` storage trie nodes of the given account, starting at the given path.
+    #[method(name = "storageNodes")]
+    fn storage_nodes(
+        &self,
+        id: u64,
+        hashed_address: B256,
+        from: Bytes,
+        limit: usize,
+    ) -> RpcResult<Vec<TrieNodeEntry>>;
+
+    /// Returns up to `limit` hashed accounts, starting at the given hashed address.
+    #[method(name = "hashedAccounts")]
+    fn hashed_accounts(
+        &self,
+        id: u64,
+        from: B256,
+        limit: usize,
+    ) -> RpcResult<Vec<HashedAccountEntry>>;
+
+    /// Returns up to `limit` hashed storage slots of the given account, starting at the given
+    /// hashed slot.
+    #[method(name = "hashedStorages")]
+    fn hashed_storages(
+        &self,
+        id: u64,
+        hashed_address: B256,
+        from: B256,
+        limit: usize,
+    ) -> RpcResult<Vec<StorageEntry>>;
+}
+
+/// A state pinned by `trie_pinState`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedState {
+    /// The id to reference the pinned state with.
+    pub id: u64,
+    /// The number of the last block of the pinned state.
+    pub block_number: BlockNumber,
+    /// The hash of the last block of the pinned state.
+    pub block_hash: BlockHash,
+    /// The state root of the pinned state.
+    pub state_root: B256,
+}
+
+/// A trie node and its path.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrieNodeEntry {
+    /// The path of the node, one nibble per byte.
+    pub path: Bytes,
+    /// The node.
+    pub node: BranchNodeCompact,
+}
+
+/// An account and its hashed address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashedAccountEntry {
+    /// The hashed address of the account.
+    pub hashed_address: B256,
+    /// The account.
+    pub account: Account,
+}
@@ -1,5 +1,5 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use reth_primitives::Address;
+use reth_primitives::{Address, U64};
 use reth_rpc_types::txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolStatus};
 
 /// Txpool rpc interface.
@@ -34,4 +34,19 @@ pub trait TxPoolApi {
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content) for more details
     #[method(name = "content")]
     async fn txpool_content(&self) -> RpcResult<TxpoolContent>;
+
+    /// Creates a subscription that yields every event of the pool, tagged with a sequence number.
+    ///
+    /// If `fromSeq` is set, the subscription starts at that event, so subscribers can resume
+    /// from the last event they have seen after reconnecting. The subscription is closed with an
+    /// error if the subscriber falls so far behind that events would be skipped.
+    #[subscription(
+        name = "subscribeEvents",
+        unsubscribe = "unsubscribeEvents",
+        item = reth_rpc_types::TxpoolEvent
+    )]
+    async fn txpool_subscribe_events(
+        &self,
+        from_seq: Option<U64>,
+    ) -> jsonrpsee::core::SubscriptionResult;
 }
@@ -22,6 +22,13 @@ pub const DEFAULT_MAX_LOGS_PER_RESPONSE: usize = 20_000;
 /// The default maximum number of concurrently executed tracing calls
 pub const DEFAULT_MAX_TRACING_REQUESTS: u32 = 25;
 
+/// The default maximum number of concurrently executed calls of a batch, batches are executed by
+/// the server as usual by default
+pub const DEFAULT_BATCH_PARALLELISM: usize = 1;
+
+/// The default maximum number of calls of a batch that is executed concurrently
+pub const DEFAULT_MAX_BATCH_CALLS: usize = 100;
+
 /// The default IPC endpoint
 #[cfg(windows)]
 pub const DEFAULT_IPC_ENDPOINT: &str = r"\\.\pipe\reth.ipc";
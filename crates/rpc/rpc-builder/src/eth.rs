@@ -5,9 +5,9 @@ use reth_rpc::{
     eth::{
         cache::{EthStateCache, EthStateCacheConfig},
         gas_oracle::GasPriceOracleConfig,
-        EthFilterConfig, FeeHistoryCacheConfig, RPC_DEFAULT_GAS_CAP,
+        EthFilterConfig, EthPubSubConfig, FeeHistoryCacheConfig, RPC_DEFAULT_GAS_CAP,
     },
-    BlockingTaskPool, EthApi, EthFilter, EthPubSub,
+    BlockingTaskPool, EthApi, EthFilter, EthPubSub, TracingLimits,
 };
 use serde::{Deserialize, Serialize};
 
@@ -48,6 +48,10 @@ pub struct EthConfig {
     pub stale_filter_ttl: std::time::Duration,
     /// Settings for the fee history cache
     pub fee_history_cache: FeeHistoryCacheConfig,
+    /// Settings for the buffering of subscription notifications
+    pub pubsub: EthPubSubConfig,
+    /// Limits of the `debug_trace*` calls
+    pub tracing_limits: TracingLimits,
 }
 
 impl EthConfig {
@@ -74,6 +78,8 @@ impl Default for EthConfig {
             rpc_gas_cap: RPC_DEFAULT_GAS_CAP.into(),
             stale_filter_ttl: DEFAULT_STALE_FILTER_TTL,
             fee_history_cache: FeeHistoryCacheConfig::default(),
+            pubsub: EthPubSubConfig::default(),
+            tracing_limits: TracingLimits::default(),
         }
     }
 }
@@ -114,4 +120,16 @@ impl EthConfig {
         self.rpc_gas_cap = rpc_gas_cap;
         self
     }
+
+    /// Configures the buffering of subscription notifications
+    pub fn pubsub_config(mut self, pubsub: EthPubSubConfig) -> Self {
+        self.pubsub = pubsub;
+        self
+    }
+
+    /// Configures the limits of the `debug_trace*` calls
+    pub fn tracing_limits(mut self, tracing_limits: TracingLimits) -> Self {
+        self.tracing_limits = tracing_limits;
+        self
+    }
 }
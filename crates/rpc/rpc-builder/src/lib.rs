@@ -20,7 +20,8 @@
 //! use reth_network_api::{NetworkInfo, Peers};
 //! use reth_provider::{
 //!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     ChangeSetReader, EvmEnvProvider, HeaderAccumulatorReader, PruneCheckpointReader,
+//!     ReorgHistoryProvider, StateProviderFactory,
 //! };
 //! use reth_rpc_builder::{
 //!     RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig,
@@ -37,6 +38,9 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + HeaderAccumulatorReader
+//!         + PruneCheckpointReader
+//!         + ReorgHistoryProvider
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -72,7 +76,8 @@
 //! use reth_node_api::EngineTypes;
 //! use reth_provider::{
 //!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     ChangeSetReader, EvmEnvProvider, HeaderAccumulatorReader, PruneCheckpointReader,
+//!     ReorgHistoryProvider, StateProviderFactory,
 //! };
 //! use reth_rpc::JwtSecret;
 //! use reth_rpc_api::EngineApiServer;
@@ -94,6 +99,9 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + HeaderAccumulatorReader
+//!         + PruneCheckpointReader
+//!         + ReorgHistoryProvider
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -153,7 +161,11 @@ use jsonrpsee::{
 use reth_node_api::EngineTypes;
 use serde::{Deserialize, Serialize, Serializer};
 use strum::{AsRefStr, EnumIter, EnumVariantNames, IntoStaticStr, ParseError, VariantNames};
-use tower::layer::util::{Identity, Stack};
+use tower::{
+    layer::util::{Identity, Stack},
+    util::Either,
+    ServiceBuilder,
+};
 use tower_http::cors::CorsLayer;
 use tracing::{instrument, trace};
 
@@ -164,18 +176,21 @@ pub use reth_ipc::server::{Builder as IpcServerBuilder, Endpoint};
 use reth_network_api::{noop::NoopNetwork, NetworkInfo, Peers};
 use reth_provider::{
     AccountReader, BlockReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-    ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+    ChangeSetReader, EvmEnvProvider, HeaderAccumulatorReader, PruneCheckpointReader,
+    ReorgHistoryProvider, StateProviderFactory,
 };
+use reth_revm::EvmConfig;
 use reth_rpc::{
     eth::{
         cache::{cache_new_blocks_task, EthStateCache},
-        fee_history_cache_new_blocks_task,
+        estimate_gas_cache_new_blocks_task, fee_history_cache_new_blocks_task,
         gas_oracle::GasPriceOracle,
         EthBundle, FeeHistoryCache,
     },
-    AdminApi, AuthLayer, BlockingTaskGuard, BlockingTaskPool, Claims, DebugApi, EngineEthApi,
-    EthApi, EthFilter, EthPubSub, EthSubscriptionIdProvider, JwtAuthValidator, JwtSecret, NetApi,
-    OtterscanApi, RPCApi, RethApi, TraceApi, TxPoolApi, Web3Api,
+    AdminApi, ApiKeyLayer, AuthLayer, BatchLayer, BlockingTaskGuard, BlockingTaskPool, Claims,
+    DebugApi, EngineEthApi, EthApi, EthFilter, EthPubSub, EthSubscriptionIdProvider,
+    JwtAuthValidator, JwtSecret, NetApi, OtterscanApi, RPCApi, RethApi, StreamingApi,
+    StreamingLayer, TraceApi, TraceContextLayer, TxPoolApi, Web3Api,
 };
 use reth_rpc_api::{servers::*, EngineApiServer};
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
@@ -223,6 +238,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + HeaderAccumulatorReader
+        + PruneCheckpointReader
+        + ReorgHistoryProvider
         + Clone
         + Unpin
         + 'static,
@@ -254,6 +272,8 @@ pub struct RpcModuleBuilder<Provider, Pool, Network, Tasks, Events> {
     executor: Tasks,
     /// Provides access to chain events, such as new blocks, required by pubsub.
     events: Events,
+    /// Customizations of the EVM used by all handlers that execute transactions.
+    evm_config: EvmConfig,
 }
 
 // === impl RpcBuilder ===
@@ -269,7 +289,7 @@ impl<Provider, Pool, Network, Tasks, Events>
         executor: Tasks,
         events: Events,
     ) -> Self {
-        Self { provider, pool, network, executor, events }
+        Self { provider, pool, network, executor, events, evm_config: Default::default() }
     }
 
     /// Configure the provider instance.
@@ -277,8 +297,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         P: BlockReader + StateProviderFactory + EvmEnvProvider + 'static,
     {
-        let Self { pool, network, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { pool, network, executor, events, evm_config, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, evm_config }
     }
 
     /// Configure the transaction pool instance.
@@ -286,8 +306,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         P: TransactionPool + 'static,
     {
-        let Self { provider, network, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, network, executor, events, evm_config, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, evm_config }
     }
 
     /// Configure a [NoopTransactionPool] instance.
@@ -298,13 +318,14 @@ impl<Provider, Pool, Network, Tasks, Events>
     pub fn with_noop_pool(
         self,
     ) -> RpcModuleBuilder<Provider, NoopTransactionPool, Network, Tasks, Events> {
-        let Self { provider, executor, events, network, .. } = self;
+        let Self { provider, executor, events, network, evm_config, .. } = self;
         RpcModuleBuilder {
             provider,
             executor,
             events,
             network,
             pool: NoopTransactionPool::default(),
+            evm_config,
         }
     }
 
@@ -313,8 +334,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         N: NetworkInfo + Peers + 'static,
     {
-        let Self { provider, pool, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, pool, executor, events, evm_config, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, evm_config }
     }
 
     /// Configure a [NoopNetwork] instance.
@@ -323,8 +344,15 @@ impl<Provider, Pool, Network, Tasks, Events>
     /// This is only intended for allow easier setup of namespaces that depend on the [EthApi] which
     /// requires a [NetworkInfo] implementation.
     pub fn with_noop_network(self) -> RpcModuleBuilder<Provider, Pool, NoopNetwork, Tasks, Events> {
-        let Self { provider, pool, executor, events, .. } = self;
-        RpcModuleBuilder { provider, pool, executor, events, network: NoopNetwork::default() }
+        let Self { provider, pool, executor, events, evm_config, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            pool,
+            executor,
+            events,
+            network: NoopNetwork::default(),
+            evm_config,
+        }
     }
 
     /// Configure the task executor to use for additional tasks.
@@ -335,8 +363,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         T: TaskSpawner + 'static,
     {
-        let Self { pool, network, provider, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { pool, network, provider, events, evm_config, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, evm_config }
     }
 
     /// Configure [TokioTaskExecutor] as the task executor to use for additional tasks.
@@ -346,8 +374,21 @@ impl<Provider, Pool, Network, Tasks, Events>
     pub fn with_tokio_executor(
         self,
     ) -> RpcModuleBuilder<Provider, Pool, Network, TokioTaskExecutor, Events> {
-        let Self { pool, network, provider, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, events, executor: TokioTaskExecutor::default() }
+        let Self { pool, network, provider, events, evm_config, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            events,
+            executor: TokioTaskExecutor::default(),
+            evm_config,
+        }
+    }
+
+    /// Configure the customizations of the EVM used by all handlers that execute transactions.
+    pub fn with_evm_config(mut self, evm_config: EvmConfig) -> Self {
+        self.evm_config = evm_config;
+        self
     }
 
     /// Configure the event subscriber instance
@@ -355,8 +396,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         E: CanonStateSubscriptions + 'static,
     {
-        let Self { provider, pool, executor, network, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, pool, executor, network, evm_config, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, evm_config }
     }
 }
 
@@ -369,6 +410,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + HeaderAccumulatorReader
+        + PruneCheckpointReader
+        + ReorgHistoryProvider
         + Clone
         + Unpin
         + 'static,
@@ -396,7 +440,7 @@ where
     {
         let mut modules = TransportRpcModules::default();
 
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, evm_config } = self;
 
         let TransportRpcModuleConfig { http, ws, ipc, config } = module_config.clone();
 
@@ -407,7 +451,8 @@ where
             executor,
             events,
             config.unwrap_or_default(),
-        );
+        )
+        .with_evm_config(evm_config);
 
         modules.config = module_config;
         modules.http = registry.maybe_module(http.as_ref());
@@ -446,8 +491,9 @@ where
         self,
         config: RpcModuleConfig,
     ) -> RethModuleRegistry<Provider, Pool, Network, Tasks, Events> {
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, evm_config } = self;
         RethModuleRegistry::new(provider, pool, network, executor, events, config)
+            .with_evm_config(evm_config)
     }
 
     /// Configures all [RpcModule]s specific to the given [TransportRpcModuleConfig] which can be
@@ -457,7 +503,7 @@ where
     pub fn build(self, module_config: TransportRpcModuleConfig) -> TransportRpcModules<()> {
         let mut modules = TransportRpcModules::default();
 
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, evm_config } = self;
 
         if !module_config.is_empty() {
             let TransportRpcModuleConfig { http, ws, ipc, config } = module_config.clone();
@@ -469,7 +515,8 @@ where
                 executor,
                 events,
                 config.unwrap_or_default(),
-            );
+            )
+            .with_evm_config(evm_config);
 
             modules.config = module_config;
             modules.http = registry.maybe_module(http.as_ref());
@@ -655,6 +702,9 @@ impl RpcModuleSelection {
             + EvmEnvProvider
             + ChainSpecProvider
             + ChangeSetReader
+            + HeaderAccumulatorReader
+            + PruneCheckpointReader
+            + ReorgHistoryProvider
             + Clone
             + Unpin
             + 'static,
@@ -857,6 +907,8 @@ pub struct RethModuleRegistry<Provider, Pool, Network, Tasks, Events> {
     events: Events,
     /// Additional settings for handlers.
     config: RpcModuleConfig,
+    /// Customizations of the EVM used by all handlers that execute transactions.
+    evm_config: EvmConfig,
     /// Holds a clone of all the eth namespace handlers
     eth: Option<EthHandlers<Provider, Pool, Network, Events>>,
     /// to put trace calls behind semaphore
@@ -888,10 +940,19 @@ impl<Provider, Pool, Network, Tasks, Events>
             modules: Default::default(),
             blocking_pool_guard: BlockingTaskGuard::new(config.eth.max_tracing_requests),
             config,
+            evm_config: Default::default(),
             events,
         }
     }
 
+    /// Configures the customizations of the EVM used by all handlers that execute transactions.
+    ///
+    /// This must be set before the `eth` handlers are created.
+    pub fn with_evm_config(mut self, evm_config: EvmConfig) -> Self {
+        self.evm_config = evm_config;
+        self
+    }
+
     /// Returns a reference to the pool
     pub fn pool(&self) -> &Pool {
         &self.pool
@@ -966,6 +1027,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + HeaderAccumulatorReader
+        + PruneCheckpointReader
+        + ReorgHistoryProvider
         + Clone
         + Unpin
         + 'static,
@@ -1061,8 +1125,9 @@ where
     ///
     /// If called outside of the tokio runtime.
     pub fn register_reth(&mut self) -> &mut Self {
-        let rethapi = self.reth_api();
-        self.modules.insert(RethRpcModule::Reth, rethapi.into_rpc().into());
+        let mut module = self.reth_api().into_rpc();
+        module.merge(RethReceiptsApiServer::into_rpc(self.eth_api())).expect("No conflicts");
+        self.modules.insert(RethRpcModule::Reth, module.into());
         self
     }
 
@@ -1120,6 +1185,7 @@ where
                             self.provider.clone(),
                             eth_api.clone(),
                             self.blocking_pool_guard.clone(),
+                            self.config.eth.tracing_limits,
                         )
                         .into_rpc()
                         .into(),
@@ -1155,9 +1221,17 @@ where
                         .into(),
                         RethRpcModule::Ots => OtterscanApi::new(eth_api.clone()).into_rpc().into(),
                         RethRpcModule::Reth => {
-                            RethApi::new(self.provider.clone(), Box::new(self.executor.clone()))
-                                .into_rpc()
-                                .into()
+                            let mut module = RethApi::new(
+                                self.provider.clone(),
+                                self.events.clone(),
+                                Box::new(self.executor.clone()),
+                            )
+                            .into_rpc();
+                            module
+                                .merge(RethReceiptsApiServer::into_rpc(eth_api.clone()))
+                                .expect("No conflicts");
+
+                            module.into()
                         }
                         RethRpcModule::EthCallBundle => {
                             EthBundle::new(eth_api.clone(), self.blocking_pool_guard.clone())
@@ -1234,7 +1308,19 @@ where
                 executor.clone(),
                 blocking_task_pool.clone(),
                 fee_history_cache,
+                self.evm_config.clone(),
+            );
+
+            let new_canonical_blocks = self.events.canonical_state_stream();
+            let estimate_gas_cache = api.estimate_gas_cache().clone();
+            self.executor.spawn_critical(
+                "cache canonical state changes for gas estimates task",
+                Box::pin(async move {
+                    estimate_gas_cache_new_blocks_task(estimate_gas_cache, new_canonical_blocks)
+                        .await;
+                }),
             );
+
             let filter = EthFilter::new(
                 self.provider.clone(),
                 self.pool.clone(),
@@ -1249,7 +1335,8 @@ where
                 self.events.clone(),
                 self.network.clone(),
                 executor,
-            );
+            )
+            .with_config(self.config.eth.pubsub);
 
             let eth = EthHandlers { api, cache, filter, pubsub, blocking_task_pool };
             self.eth = Some(eth);
@@ -1314,7 +1401,12 @@ where
     /// If called outside of the tokio runtime. See also [Self::eth_api]
     pub fn debug_api(&mut self) -> DebugApi<Provider, EthApi<Provider, Pool, Network>> {
         let eth_api = self.eth_api();
-        DebugApi::new(self.provider.clone(), eth_api, self.blocking_pool_guard.clone())
+        DebugApi::new(
+            self.provider.clone(),
+            eth_api,
+            self.blocking_pool_guard.clone(),
+            self.config.eth.tracing_limits,
+        )
     }
 
     /// Instantiates NetApi
@@ -1328,8 +1420,22 @@ where
     }
 
     /// Instantiates RethApi
-    pub fn reth_api(&mut self) -> RethApi<Provider> {
-        RethApi::new(self.provider.clone(), Box::new(self.executor.clone()))
+    pub fn reth_api(&mut self) -> RethApi<Provider, Events> {
+        RethApi::new(self.provider.clone(), self.events.clone(), Box::new(self.executor.clone()))
+    }
+
+    /// Instantiates StreamingApi
+    ///
+    /// # Panics
+    ///
+    /// If called outside of the tokio runtime. See also [Self::eth_api]
+    pub fn streaming_api(
+        &mut self,
+    ) -> StreamingApi<Provider, Pool, EthApi<Provider, Pool, Network>> {
+        let filter = self.eth_handlers().filter;
+        let trace = self.trace_api();
+        let debug = self.debug_api();
+        StreamingApi::new(filter, trace, debug, Box::new(self.executor.clone()))
     }
 }
 
@@ -1364,6 +1470,8 @@ pub struct RpcServerConfig {
     ipc_endpoint: Option<Endpoint>,
     /// JWT secret for authentication
     jwt_secret: Option<JwtSecret>,
+    /// Additional middleware of the http and ws servers
+    middleware: RpcMiddleware,
 }
 
 impl fmt::Debug for RpcServerConfig {
@@ -1377,6 +1485,7 @@ impl fmt::Debug for RpcServerConfig {
             .field("ipc_server_config", &self.ipc_server_config)
             .field("ipc_endpoint", &self.ipc_endpoint.as_ref().map(|endpoint| endpoint.path()))
             .field("jwt_secret", &self.jwt_secret)
+            .field("middleware", &self.middleware)
             .finish()
     }
 }
@@ -1494,6 +1603,30 @@ impl RpcServerConfig {
         self
     }
 
+    /// Configures the API keys of the http and ws servers.
+    ///
+    /// If set, requests without one of the keys are rejected, see [ApiKeyLayer].
+    pub fn with_api_key_layer(mut self, layer: Option<ApiKeyLayer>) -> Self {
+        self.middleware.api_keys = layer;
+        self
+    }
+
+    /// Configures the concurrent execution of batches on the http server.
+    ///
+    /// See [BatchLayer].
+    pub fn with_batch_layer(mut self, layer: Option<BatchLayer>) -> Self {
+        self.middleware.batch = layer;
+        self
+    }
+
+    /// Configures the streaming of large results on the http server.
+    ///
+    /// See [StreamingLayer].
+    pub fn with_streaming_layer(mut self, layer: Option<StreamingLayer>) -> Self {
+        self.middleware.streaming = layer;
+        self
+    }
+
     /// Returns true if any server is configured.
     ///
     /// If no server is configured, no server will be be launched on [RpcServerConfig::start].
@@ -1575,6 +1708,7 @@ impl RpcServerConfig {
                 http_socket_addr,
                 cors,
                 secret,
+                self.middleware.clone(),
                 ServerKind::WsHttp(http_socket_addr),
                 modules
                     .http
@@ -1604,6 +1738,7 @@ impl RpcServerConfig {
                 ws_socket_addr,
                 self.ws_cors_domains.take(),
                 self.jwt_secret.clone(),
+                self.middleware.clone(),
                 ServerKind::WS(ws_socket_addr),
                 modules.ws.as_ref().map(RpcServerMetrics::new).unwrap_or_default(),
             )
@@ -1619,6 +1754,7 @@ impl RpcServerConfig {
                 http_socket_addr,
                 self.http_cors_domains.take(),
                 self.jwt_secret.clone(),
+                self.middleware.clone(),
                 ServerKind::Http(http_socket_addr),
                 modules.http.as_ref().map(RpcServerMetrics::new).unwrap_or_default(),
             )
@@ -1912,17 +2048,57 @@ impl Default for WsHttpServers {
     }
 }
 
+/// The middleware of the http and ws servers, applied after cors and auth.
+///
+/// Each request is handled in a span that is part of the trace of the caller, see
+/// [TraceContextLayer].
+#[derive(Debug, Clone, Default)]
+struct RpcMiddleware {
+    /// API key authentication
+    api_keys: Option<ApiKeyLayer>,
+    /// Concurrent execution of batches
+    batch: Option<BatchLayer>,
+    /// Streaming of large results
+    streaming: Option<StreamingLayer>,
+}
+
+impl RpcMiddleware {
+    /// Adds the configured layers to the given middleware.
+    fn apply<L>(self, builder: ServiceBuilder<L>) -> ServiceBuilder<WithRpcMiddleware<L>> {
+        builder
+            .option_layer(self.api_keys)
+            .option_layer(self.batch)
+            .option_layer(self.streaming)
+            // the calls of a batch are dispatched as individual requests, each in its own span
+            .layer(TraceContextLayer)
+    }
+}
+
+/// The given middleware followed by the [RpcMiddleware].
+type WithRpcMiddleware<L> = Stack<
+    TraceContextLayer,
+    Stack<
+        Either<StreamingLayer, Identity>,
+        Stack<Either<BatchLayer, Identity>, Stack<Either<ApiKeyLayer, Identity>, L>>,
+    >,
+>;
+
 /// Http Servers Enum
 enum WsHttpServerKind {
     /// Http server
-    Plain(Server<Identity, RpcServerMetrics>),
+    Plain(Server<WithRpcMiddleware<Identity>, RpcServerMetrics>),
     /// Http server with cors
-    WithCors(Server<Stack<CorsLayer, Identity>, RpcServerMetrics>),
+    WithCors(Server<WithRpcMiddleware<Stack<CorsLayer, Identity>>, RpcServerMetrics>),
     /// Http server with auth
-    WithAuth(Server<Stack<AuthLayer<JwtAuthValidator>, Identity>, RpcServerMetrics>),
+    WithAuth(
+        Server<WithRpcMiddleware<Stack<AuthLayer<JwtAuthValidator>, Identity>>, RpcServerMetrics>,
+    ),
     /// Http server with cors and auth
     WithCorsAuth(
-        Server<Stack<AuthLayer<JwtAuthValidator>, Stack<CorsLayer, Identity>>, RpcServerMetrics>,
+        Server<
+            WithRpcMiddleware<Stack<AuthLayer<JwtAuthValidator>, Stack<CorsLayer, Identity>>>,
+            RpcServerMetrics,
+        >,
     ),
 }
 
@@ -1947,6 +2123,7 @@ impl WsHttpServerKind {
         socket_addr: SocketAddr,
         cors_domains: Option<String>,
         jwt_secret: Option<JwtSecret>,
+        middleware: RpcMiddleware,
         server_kind: ServerKind,
         metrics: RpcServerMetrics,
     ) -> Result<(Self, SocketAddr), RpcError> {
@@ -1955,9 +2132,11 @@ impl WsHttpServerKind {
 
             if let Some(secret) = jwt_secret {
                 // stack cors and auth layers
-                let middleware = tower::ServiceBuilder::new()
-                    .layer(cors)
-                    .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone())));
+                let middleware = middleware.apply(
+                    tower::ServiceBuilder::new()
+                        .layer(cors)
+                        .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone()))),
+                );
 
                 let server = builder
                     .set_middleware(middleware)
@@ -1969,7 +2148,7 @@ impl WsHttpServerKind {
                 let server = WsHttpServerKind::WithCorsAuth(server);
                 Ok((server, local_addr))
             } else {
-                let middleware = tower::ServiceBuilder::new().layer(cors);
+                let middleware = middleware.apply(tower::ServiceBuilder::new().layer(cors));
                 let server = builder
                     .set_middleware(middleware)
                     .set_logger(metrics)
@@ -1982,8 +2161,10 @@ impl WsHttpServerKind {
             }
         } else if let Some(secret) = jwt_secret {
             // jwt auth layered service
-            let middleware = tower::ServiceBuilder::new()
-                .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone())));
+            let middleware = middleware.apply(
+                tower::ServiceBuilder::new()
+                    .layer(AuthLayer::new(JwtAuthValidator::new(secret.clone()))),
+            );
             let server = builder
                 .set_middleware(middleware)
                 .set_logger(metrics)
@@ -1996,8 +2177,10 @@ impl WsHttpServerKind {
             let server = WsHttpServerKind::WithAuth(server);
             Ok((server, local_addr))
         } else {
-            // plain server without any middleware
+            // plain server without any middleware except for the optional ones
+            let middleware = middleware.apply(tower::ServiceBuilder::new());
             let server = builder
+                .set_middleware(middleware)
                 .set_logger(metrics)
                 .build(socket_addr)
                 .await
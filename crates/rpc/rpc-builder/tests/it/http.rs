@@ -16,8 +16,8 @@ use reth_primitives::{
 };
 use reth_rpc_api::{
     clients::{AdminApiClient, EthApiClient},
-    DebugApiClient, EthFilterApiClient, NetApiClient, OtterscanClient, TraceApiClient,
-    Web3ApiClient,
+    DebugApiClient, EthFilterApiClient, NetApiClient, OtterscanClient, RethReceiptsApiClient,
+    TraceApiClient, Web3ApiClient,
 };
 use reth_rpc_builder::RethRpcModule;
 use reth_rpc_types::{
@@ -163,6 +163,13 @@ where
     EthApiClient::create_access_list(client, call_request.clone(), Some(block_number.into()))
         .await
         .unwrap();
+    EthApiClient::create_access_list_batch(
+        client,
+        vec![call_request.clone()],
+        Some(block_number.into()),
+    )
+    .await
+    .unwrap();
     EthApiClient::estimate_gas(client, call_request.clone(), Some(block_number.into()), None)
         .await
         .unwrap();
@@ -376,6 +383,22 @@ async fn test_call_eth_functions_http_and_ws() {
     test_basic_eth_calls(&client).await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_call_reth_transaction_receipts_http() {
+    reth_tracing::init_test_tracing();
+
+    let handle = launch_http(vec![RethRpcModule::Reth]).await;
+    let client = handle.http_client().unwrap();
+    let hashes = vec![TxHash::random(), TxHash::random()];
+    assert_eq!(
+        RethReceiptsApiClient::reth_get_transaction_receipts(&client, hashes).await.unwrap(),
+        vec![None, None]
+    );
+    RethReceiptsApiClient::reth_get_transaction_receipts(&client, vec![TxHash::random(); 1025])
+        .await
+        .unwrap_err();
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_call_debug_functions_http() {
     reth_tracing::init_test_tracing();
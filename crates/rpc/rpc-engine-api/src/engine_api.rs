@@ -1,4 +1,5 @@
-use crate::{metrics::EngineApiMetrics, EngineApiError, EngineApiResult};
+use crate::{metrics::EngineApiMetrics, EngineApiError, EngineApiJournal, EngineApiResult};
+use alloy_rlp::Decodable;
 use async_trait::async_trait;
 use jsonrpsee_core::RpcResult;
 use reth_beacon_consensus::BeaconConsensusEngineHandle;
@@ -8,19 +9,26 @@ use reth_node_api::{
     PayloadAttributes, PayloadBuilderAttributes, PayloadOrAttributes,
 };
 use reth_payload_builder::PayloadStore;
-use reth_primitives::{BlockHash, BlockHashOrNumber, BlockNumber, ChainSpec, Hardfork, B256, U64};
+use reth_primitives::{
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, Bytes, ChainSpec, Hardfork, SealedBlock,
+    B256, U64,
+};
 use reth_provider::{BlockReader, EvmEnvProvider, HeaderProvider, StateProviderFactory};
-use reth_rpc_api::EngineApiServer;
+use reth_rpc_api::{EngineApiServer, EngineDebugApiServer};
 use reth_rpc_types::engine::{
     CancunPayloadFields, ExecutionPayload, ExecutionPayloadBodiesV1, ExecutionPayloadEnvelopeV2,
     ExecutionPayloadEnvelopeV3, ExecutionPayloadInputV2, ExecutionPayloadV1, ExecutionPayloadV3,
     ForkchoiceUpdated, PayloadId, PayloadStatus, TransitionConfiguration, CAPABILITIES,
 };
 use reth_rpc_types_compat::engine::payload::{
-    convert_payload_input_v2_to_payload, convert_to_payload_body_v1,
+    convert_payload_input_v2_to_payload, convert_to_payload_body_v1, try_block_to_payload,
 };
 use reth_tasks::TaskSpawner;
-use std::{sync::Arc, time::Instant};
+use serde::Serialize;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::sync::oneshot;
 use tracing::trace;
 
@@ -34,6 +42,8 @@ const MAX_PAYLOAD_BODIES_LIMIT: u64 = 1024;
 /// functions in the Execution layer that are crucial for the consensus process.
 pub struct EngineApi<Provider, EngineT: EngineTypes> {
     inner: Arc<EngineApiInner<Provider, EngineT>>,
+    /// The journal exchanges are recorded to, if any.
+    journal: Option<Arc<EngineApiJournal>>,
 }
 
 struct EngineApiInner<Provider, EngineT: EngineTypes> {
@@ -72,7 +82,33 @@ where
             task_spawner,
             metrics: EngineApiMetrics::default(),
         });
-        Self { inner }
+        Self { inner, journal: None }
+    }
+
+    /// Records all `engine_newPayload`, `engine_forkchoiceUpdated` and `engine_getPayload`
+    /// exchanges to the given journal.
+    pub fn with_journal(mut self, journal: EngineApiJournal) -> Self {
+        self.journal = Some(Arc::new(journal));
+        self
+    }
+
+    /// Serializes the request parameters, if exchanges are recorded.
+    fn journal_params(&self, params: impl Serialize) -> Option<serde_json::Value> {
+        self.journal.as_ref()?;
+        serde_json::to_value(params).ok()
+    }
+
+    /// Records the exchange to the journal, if any.
+    fn record_exchange<T: Serialize>(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+        elapsed: Duration,
+        res: &EngineApiResult<T>,
+    ) {
+        if let (Some(journal), Some(params)) = (&self.journal, params) {
+            journal.record(method, params, elapsed, res.as_ref().map_err(ToString::to_string));
+        }
     }
 
     /// Fetches the attributes for the payload with the given id.
@@ -149,6 +185,39 @@ where
         Ok(self.inner.beacon_consensus.new_payload(payload, Some(cancun_fields)).await?)
     }
 
+    /// Imports a fully formed block as if it was received with `engine_newPayload`.
+    ///
+    /// The block goes through the same validation and execution as a payload from the consensus
+    /// layer, the payload version is derived from the fields of the block header. The block only
+    /// becomes canonical with a subsequent `engine_forkchoiceUpdated`.
+    pub async fn insert_block(&self, block: SealedBlock) -> EngineApiResult<PayloadStatus> {
+        let parent_beacon_block_root = block.header.parent_beacon_block_root;
+        let cancun_fields =
+            parent_beacon_block_root.map(|parent_beacon_block_root| CancunPayloadFields {
+                versioned_hashes: block.blob_versioned_hashes_iter().copied().collect(),
+                parent_beacon_block_root,
+            });
+
+        let payload = try_block_to_payload(block);
+        let version = match payload {
+            ExecutionPayload::V1(_) => EngineApiMessageVersion::V1,
+            ExecutionPayload::V2(_) => EngineApiMessageVersion::V2,
+            ExecutionPayload::V3(_) => EngineApiMessageVersion::V3,
+        };
+        let payload_or_attrs =
+            PayloadOrAttributes::<'_, EngineT::PayloadAttributes>::from_execution_payload(
+                &payload,
+                parent_beacon_block_root,
+            );
+        EngineT::validate_version_specific_fields(
+            &self.inner.chain_spec,
+            version,
+            payload_or_attrs,
+        )?;
+
+        Ok(self.inner.beacon_consensus.new_payload(payload, cancun_fields).await?)
+    }
+
     /// Sends a message to the beacon consensus engine to update the fork choice _without_
     /// withdrawals.
     ///
@@ -478,8 +547,11 @@ where
     async fn new_payload_v1(&self, payload: ExecutionPayloadV1) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV1");
         let start = Instant::now();
+        let params = self.journal_params((&payload,));
         let res = EngineApi::new_payload_v1(self, payload).await;
-        self.inner.metrics.new_payload_v1.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.new_payload_v1.record(elapsed);
+        self.record_exchange("engine_newPayloadV1", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -488,8 +560,11 @@ where
     async fn new_payload_v2(&self, payload: ExecutionPayloadInputV2) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV2");
         let start = Instant::now();
+        let params = self.journal_params((&payload,));
         let res = EngineApi::new_payload_v2(self, payload).await;
-        self.inner.metrics.new_payload_v2.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.new_payload_v2.record(elapsed);
+        self.record_exchange("engine_newPayloadV2", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -503,10 +578,13 @@ where
     ) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV3");
         let start = Instant::now();
+        let params = self.journal_params((&payload, &versioned_hashes, parent_beacon_block_root));
         let res =
             EngineApi::new_payload_v3(self, payload, versioned_hashes, parent_beacon_block_root)
                 .await;
-        self.inner.metrics.new_payload_v3.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.new_payload_v3.record(elapsed);
+        self.record_exchange("engine_newPayloadV3", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -521,9 +599,12 @@ where
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV1");
         let start = Instant::now();
+        let params = self.journal_params((fork_choice_state, &payload_attributes));
         let res =
             EngineApi::fork_choice_updated_v1(self, fork_choice_state, payload_attributes).await;
-        self.inner.metrics.fork_choice_updated_v1.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.fork_choice_updated_v1.record(elapsed);
+        self.record_exchange("engine_forkchoiceUpdatedV1", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -536,9 +617,12 @@ where
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV2");
         let start = Instant::now();
+        let params = self.journal_params((fork_choice_state, &payload_attributes));
         let res =
             EngineApi::fork_choice_updated_v2(self, fork_choice_state, payload_attributes).await;
-        self.inner.metrics.fork_choice_updated_v2.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.fork_choice_updated_v2.record(elapsed);
+        self.record_exchange("engine_forkchoiceUpdatedV2", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -552,9 +636,12 @@ where
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV3");
         let start = Instant::now();
+        let params = self.journal_params((fork_choice_state, &payload_attributes));
         let res =
             EngineApi::fork_choice_updated_v3(self, fork_choice_state, payload_attributes).await;
-        self.inner.metrics.fork_choice_updated_v3.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.fork_choice_updated_v3.record(elapsed);
+        self.record_exchange("engine_forkchoiceUpdatedV3", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -572,8 +659,11 @@ where
     async fn get_payload_v1(&self, payload_id: PayloadId) -> RpcResult<ExecutionPayloadV1> {
         trace!(target: "rpc::engine", "Serving engine_getPayloadV1");
         let start = Instant::now();
+        let params = self.journal_params((payload_id,));
         let res = EngineApi::get_payload_v1(self, payload_id).await;
-        self.inner.metrics.get_payload_v1.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.get_payload_v1.record(elapsed);
+        self.record_exchange("engine_getPayloadV1", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -589,8 +679,11 @@ where
     async fn get_payload_v2(&self, payload_id: PayloadId) -> RpcResult<ExecutionPayloadEnvelopeV2> {
         trace!(target: "rpc::engine", "Serving engine_getPayloadV2");
         let start = Instant::now();
+        let params = self.journal_params((payload_id,));
         let res = EngineApi::get_payload_v2(self, payload_id).await;
-        self.inner.metrics.get_payload_v2.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.get_payload_v2.record(elapsed);
+        self.record_exchange("engine_getPayloadV2", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -606,8 +699,11 @@ where
     async fn get_payload_v3(&self, payload_id: PayloadId) -> RpcResult<ExecutionPayloadEnvelopeV3> {
         trace!(target: "rpc::engine", "Serving engine_getPayloadV3");
         let start = Instant::now();
+        let params = self.journal_params((payload_id,));
         let res = EngineApi::get_payload_v3(self, payload_id).await;
-        self.inner.metrics.get_payload_v3.record(start.elapsed());
+        let elapsed = start.elapsed();
+        self.inner.metrics.get_payload_v3.record(elapsed);
+        self.record_exchange("engine_getPayloadV3", params, elapsed, &res);
         Ok(res?)
     }
 
@@ -672,6 +768,30 @@ where
     }
 }
 
+#[async_trait]
+impl<Provider, EngineT> EngineDebugApiServer for EngineApi<Provider, EngineT>
+where
+    Provider: HeaderProvider + BlockReader + StateProviderFactory + EvmEnvProvider + 'static,
+    EngineT: EngineTypes + 'static,
+{
+    /// Handler for `debug_insertBlock`
+    async fn insert_block(&self, block: Bytes) -> RpcResult<PayloadStatus> {
+        trace!(target: "rpc::engine", "Serving debug_insertBlock");
+        let block =
+            Block::decode(&mut block.as_ref()).map_err(EngineApiError::InvalidBlockEncoding)?;
+        Ok(EngineApi::insert_block(self, block.seal_slow()).await?)
+    }
+}
+
+impl<Provider, EngineT> Clone for EngineApi<Provider, EngineT>
+where
+    EngineT: EngineTypes,
+{
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner), journal: self.journal.clone() }
+    }
+}
+
 impl<Provider, EngineT> std::fmt::Debug for EngineApi<Provider, EngineT>
 where
     EngineT: EngineTypes,
@@ -732,6 +852,28 @@ mod tests {
         assert_matches!(handle.from_api.recv().await, Some(BeaconEngineMessage::NewPayload { .. }));
     }
 
+    #[tokio::test]
+    async fn forwards_inserted_blocks_to_consensus_engine() {
+        let (mut handle, api) = setup_engine_api();
+
+        let err = EngineDebugApiServer::insert_block(&api, Bytes::from_static(&[0x01]))
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            jsonrpsee_core::Error::Call(err)
+                if err.code() == jsonrpsee_types::error::INVALID_PARAMS_CODE
+        );
+
+        tokio::spawn(async move {
+            api.insert_block(SealedBlock::default()).await.unwrap();
+        });
+        assert_matches!(
+            handle.from_api.recv().await,
+            Some(BeaconEngineMessage::NewPayload { cancun_fields: None, .. })
+        );
+    }
+
     // tests covering `engine_getPayloadBodiesByRange` and `engine_getPayloadBodiesByHash`
     mod get_payload_bodies {
         use super::*;
@@ -81,6 +81,9 @@ pub enum EngineApiError {
     /// The payload or attributes are known to be malformed before processing.
     #[error(transparent)]
     AttributesValidationError(#[from] AttributesValidationError),
+    /// The block of `debug_insertBlock` is not a valid RLP encoded block.
+    #[error("invalid block encoding: {0}")]
+    InvalidBlockEncoding(alloy_rlp::Error),
     /// If the optimism feature flag is enabled, the payload attributes must have a present
     /// gas limit for the forkchoice updated method.
     #[cfg(feature = "optimism")]
@@ -106,6 +109,7 @@ impl From<EngineApiError> for jsonrpsee_types::error::ErrorObject<'static> {
     fn from(error: EngineApiError) -> Self {
         match error {
             EngineApiError::InvalidBodiesRange { .. } |
+            EngineApiError::InvalidBlockEncoding(_) |
             EngineApiError::AttributesValidationError(
                 AttributesValidationError::WithdrawalsNotSupportedInV1,
             ) |
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+/// A recorded engine API exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    /// Milliseconds since the unix epoch at which the request was received.
+    pub timestamp: u64,
+    /// The time it took to respond to the request.
+    pub elapsed: Duration,
+    /// The method name, e.g. `engine_newPayloadV3`.
+    pub method: String,
+    /// The request parameters, as sent by the consensus layer.
+    pub params: serde_json::Value,
+    /// The response, if the request succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// The error message, if the request failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Append-only journal of engine API exchanges, one JSON encoded [JournalEntry] per line.
+///
+/// Every entry is flushed right away, so the journal of a crashed node is complete up to the
+/// last answered request.
+#[derive(Debug)]
+pub struct EngineApiJournal {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl EngineApiJournal {
+    /// Opens the journal at the given path, appending to it if it exists.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { path, writer: Mutex::new(BufWriter::new(file)) })
+    }
+
+    /// Returns the path of the journal.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an exchange that was answered after `elapsed` to the journal.
+    pub(crate) fn record<T: Serialize>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        elapsed: Duration,
+        response: Result<&T, String>,
+    ) {
+        let received_at = SystemTime::now().checked_sub(elapsed).unwrap_or(UNIX_EPOCH);
+        let (result, error) = match response {
+            Ok(result) => (serde_json::to_value(result).ok(), None),
+            Err(error) => (None, Some(error)),
+        };
+        let entry = JournalEntry {
+            timestamp: received_at
+                .duration_since(UNIX_EPOCH)
+                .map(|timestamp| timestamp.as_millis() as u64)
+                .unwrap_or_default(),
+            elapsed,
+            method: method.to_string(),
+            params,
+            result,
+            error,
+        };
+
+        if let Err(err) = self.append(&entry) {
+            warn!(target: "rpc::engine", path = %self.path.display(), %err, method, "Failed to record engine API exchange");
+        }
+    }
+
+    fn append(&self, entry: &JournalEntry) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+        serde_json::to_writer(&mut *writer, entry)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+
+    /// Reads all entries of the journal at the given path, in the order they were recorded.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<JournalEntry>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue
+            }
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_types::engine::{PayloadStatus, PayloadStatusEnum};
+
+    #[test]
+    fn record_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engine").join("journal.jsonl");
+
+        let journal = EngineApiJournal::open(&path).unwrap();
+        let status = PayloadStatus::from_status(PayloadStatusEnum::Syncing);
+        journal.record(
+            "engine_newPayloadV1",
+            serde_json::json!([{}]),
+            Duration::from_millis(3),
+            Ok(&status),
+        );
+        journal.record::<PayloadStatus>(
+            "engine_getPayloadV1",
+            serde_json::json!(["0x0000000000000001"]),
+            Duration::from_millis(1),
+            Err("Unknown payload".to_string()),
+        );
+        drop(journal);
+
+        // reopening appends
+        EngineApiJournal::open(&path).unwrap().record(
+            "engine_forkchoiceUpdatedV1",
+            serde_json::json!([{}, null]),
+            Duration::ZERO,
+            Ok(&status),
+        );
+
+        let entries = EngineApiJournal::read(&path).unwrap();
+        let methods = entries.iter().map(|entry| entry.method.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            vec!["engine_newPayloadV1", "engine_getPayloadV1", "engine_forkchoiceUpdatedV1"]
+        );
+        assert_eq!(entries[0].result, Some(serde_json::to_value(&status).unwrap()));
+        assert_eq!(entries[1].error.as_deref(), Some("Unknown payload"));
+        assert_eq!(entries[1].elapsed, Duration::from_millis(1));
+    }
+}
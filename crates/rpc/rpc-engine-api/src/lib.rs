@@ -20,16 +20,13 @@ mod error;
 /// Engine API metrics.
 mod metrics;
 
+/// Engine API journal.
+mod journal;
+
 pub use engine_api::{EngineApi, EngineApiSender};
 pub use error::*;
+pub use journal::{EngineApiJournal, JournalEntry};
 pub use message::EngineApiMessageVersion;
 
 // re-export server trait for convenience
-pub use reth_rpc_api::EngineApiServer;
-
-#[cfg(test)]
-#[allow(unused_imports)]
-mod tests {
-    // silence unused import warning
-    use alloy_rlp as _;
-}
+pub use reth_rpc_api::{EngineApiServer, EngineDebugApiServer};
@@ -0,0 +1,958 @@
+//! A provider of the chain and state of a remote node at a fork block, with local changes.
+//!
+//! The [ForkedStateProvider] implements the provider traits over the RPC of a remote node, like
+//! forking in anvil, so integration tests of the payload builder and the trie code can run against
+//! real state without a datadir:
+//!
+//! ```no_run
+//! use reth_primitives::{BlockNumberOrTag, MAINNET};
+//! use reth_provider::StateProviderFactory;
+//! use reth_rpc_api_testing_util::forked::ForkedStateProvider;
+//!
+//! # fn t() -> reth_interfaces::provider::ProviderResult<()> {
+//! let provider = ForkedStateProvider::new(
+//!     "http://localhost:8545",
+//!     MAINNET.clone(),
+//!     BlockNumberOrTag::Finalized,
+//! )?;
+//! let state = provider.latest()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Everything that is fetched from the remote node is cached for the lifetime of the provider.
+//! State changes are kept in an in-memory overlay on top of the state of the fork block, and the
+//! state root of the changes is computed from the proofs of the changed accounts and slots, so
+//! the remote node must serve `eth_getProof` and the raw `debug_` methods at the fork block.
+//!
+//! Transaction numbers are an artifact of the database, so methods by transaction number are not
+//! supported.
+
+use jsonrpsee::core::Error as RpcError;
+use parking_lot::RwLock;
+use reth_db::models::StoredBlockBodyIndices;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_primitives::{
+    constants::EMPTY_ROOT_HASH,
+    keccak256,
+    revm::{
+        config::revm_spec,
+        env::{fill_block_env, fill_cfg_and_block_env, fill_cfg_env},
+    },
+    trie::{AccountProof, StorageProof},
+    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumber, BlockNumberOrTag,
+    BlockWithSenders, Bytecode, Bytes, ChainInfo, ChainSpec, Head, Header, Receipt,
+    ReceiptWithBloom, SealedBlock, SealedBlockWithSenders, SealedHeader, TransactionMeta,
+    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, B256, KECCAK_EMPTY,
+    U256,
+};
+use reth_provider::{
+    providers::BundleStateProvider, AccountReader, BlockHashReader, BlockIdReader, BlockNumReader,
+    BlockReader, BlockReaderIdExt, BlockSource, BundleStateDataProvider, BundleStateWithReceipts,
+    ChainSpecProvider, EvmEnvProvider, HeaderProvider, ReceiptProvider, ReceiptProviderIdExt,
+    StateProviderBox, StateProviderFactory, TransactionVariant, TransactionsProvider,
+    WithdrawalsProvider,
+};
+use reth_rpc_api::{clients::DebugApiClient, EthApiClient};
+use reth_rpc_types::serde_helpers::JsonStorageKey;
+use reth_trie::witness::WitnessState;
+use revm::primitives::{BlockEnv, CfgEnv, SpecId};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Bound, Range, RangeBounds, RangeInclusive},
+    sync::Arc,
+};
+
+mod remote;
+use remote::{remote_error, RemoteClient};
+
+mod state;
+pub use state::ForkedState;
+pub(crate) use state::Overlay;
+
+/// A provider of the chain and state of a remote node at a fork block, with local changes.
+///
+/// Blocks after the fork block are not part of the forked chain. The state of the fork block is
+/// the latest state, and includes the local changes made with [ForkedStateProvider::set_account],
+/// [ForkedStateProvider::set_storage] and the like.
+///
+/// Clones share the cache and the local changes.
+#[derive(Debug, Clone)]
+pub struct ForkedStateProvider {
+    fork: Arc<Fork>,
+}
+
+impl ForkedStateProvider {
+    /// Creates a provider of the chain of the node at the given HTTP URL, forked at the given
+    /// block.
+    ///
+    /// A block tag is resolved to its block number once, so the forked chain stays the same if
+    /// the remote node advances. Forking at a finalized block avoids reorgs of the remote chain
+    /// below the fork block.
+    pub fn new(
+        url: &str,
+        chain_spec: Arc<ChainSpec>,
+        fork_block: BlockNumberOrTag,
+    ) -> ProviderResult<Self> {
+        let remote = RemoteClient::new(url)?;
+
+        let chain_id = remote.request(|client| async move { client.chain_id().await })?;
+        if let Some(chain_id) = chain_id.map(|id| id.to::<u64>()) {
+            if chain_id != chain_spec.chain.id() {
+                return Err(ProviderError::Remote(format!(
+                    "remote chain id {chain_id} does not match chain spec id {}",
+                    chain_spec.chain.id()
+                )))
+            }
+        }
+
+        let raw = remote
+            .request(move |client| async move { client.raw_header(fork_block.into()).await })?;
+        if raw.is_empty() {
+            return Err(ProviderError::Remote(format!("fork block {fork_block} not found")))
+        }
+        let header = decode_header(&raw)?;
+
+        let mut cache = RemoteCache::default();
+        cache.insert_header(header.clone());
+        Ok(Self {
+            fork: Arc::new(Fork {
+                remote,
+                chain_spec,
+                header,
+                cache: RwLock::new(cache),
+                overlay: Default::default(),
+            }),
+        })
+    }
+
+    /// Returns the header of the fork block.
+    pub fn fork_block(&self) -> &SealedHeader {
+        &self.fork.header
+    }
+
+    /// Sets the account in the state of the fork block, keeping its storage.
+    pub fn set_account(&self, address: Address, account: Account) {
+        self.update_overlay(|overlay| overlay.set_account(address, Some(account)));
+    }
+
+    /// Removes the account and its storage from the state of the fork block.
+    pub fn remove_account(&self, address: Address) {
+        self.update_overlay(|overlay| overlay.set_account(address, None));
+    }
+
+    /// Sets the code of the account in the state of the fork block, creating the account if it
+    /// doesn't exist.
+    pub fn set_code(&self, address: Address, code: Bytes) -> ProviderResult<()> {
+        let mut account = self.basic_account(address)?.unwrap_or_default();
+        let code_hash = keccak256(&code);
+        account.bytecode_hash = (code_hash != KECCAK_EMPTY).then_some(code_hash);
+        self.update_overlay(|overlay| {
+            overlay.set_bytecode(code_hash, Bytecode::new_raw(code));
+            overlay.set_account(address, Some(account));
+        });
+        Ok(())
+    }
+
+    /// Sets the value of the storage slot of the account in the state of the fork block.
+    pub fn set_storage(&self, address: Address, slot: B256, value: U256) {
+        self.update_overlay(|overlay| overlay.set_storage(address, slot, value));
+    }
+
+    /// Applies the state changes of the executed bundle to the state of the fork block, e.g. to
+    /// build a payload on top of a previous one.
+    pub fn apply_bundle(&self, bundle: &BundleStateWithReceipts) {
+        self.update_overlay(|overlay| overlay.apply_bundle(bundle));
+    }
+
+    /// Discards all local changes.
+    pub fn reset(&self) {
+        *self.fork.overlay.write() = Default::default();
+    }
+
+    /// Applies the update to the local changes.
+    ///
+    /// States that were created before are not affected.
+    fn update_overlay(&self, update: impl FnOnce(&mut Overlay)) {
+        let mut overlay = self.fork.overlay.write();
+        update(Arc::make_mut(&mut overlay));
+    }
+
+    /// Returns the state of the fork block with the local changes.
+    fn fork_state(&self) -> ForkedState {
+        let overlay = self.fork.overlay.read().clone();
+        ForkedState::new(self.fork.clone(), self.fork.header.number, Some(overlay))
+    }
+
+    /// Returns the state at the given block of the forked chain.
+    fn state_at(&self, number: BlockNumber) -> ProviderResult<ForkedState> {
+        match number.cmp(&self.fork.header.number) {
+            std::cmp::Ordering::Less => Ok(ForkedState::new(self.fork.clone(), number, None)),
+            std::cmp::Ordering::Equal => Ok(self.fork_state()),
+            std::cmp::Ordering::Greater => Err(ProviderError::HeaderNotFound(number.into())),
+        }
+    }
+}
+
+/// The remote node, the fork block and everything that was fetched or changed.
+#[derive(Debug)]
+pub(crate) struct Fork {
+    remote: RemoteClient,
+    chain_spec: Arc<ChainSpec>,
+    header: SealedHeader,
+    cache: RwLock<RemoteCache>,
+    overlay: RwLock<Arc<Overlay>>,
+}
+
+/// Everything that was fetched from the remote node.
+#[derive(Debug, Default)]
+struct RemoteCache {
+    headers: HashMap<BlockNumber, SealedHeader>,
+    block_numbers: HashMap<BlockHash, BlockNumber>,
+    blocks: HashMap<BlockNumber, Block>,
+    receipts: HashMap<BlockNumber, Vec<Receipt>>,
+    total_difficulties: HashMap<BlockNumber, U256>,
+    accounts: HashMap<(BlockNumber, Address), Option<Account>>,
+    storage: HashMap<(BlockNumber, Address, B256), U256>,
+    bytecodes: HashMap<B256, Bytecode>,
+    /// The trie nodes of all fetched proofs, keyed by their hash.
+    trie_nodes: HashMap<B256, Bytes>,
+    /// The accounts whose proof at the fork block was fetched.
+    proven_accounts: HashSet<Address>,
+    /// The storage slots whose proof at the fork block was fetched.
+    proven_slots: HashSet<(Address, B256)>,
+}
+
+impl RemoteCache {
+    fn insert_header(&mut self, header: SealedHeader) {
+        self.block_numbers.insert(header.hash(), header.number);
+        self.headers.insert(header.number, header);
+    }
+}
+
+impl Fork {
+    /// Sends the request created with the client to the remote node.
+    fn request<T, F>(
+        &self,
+        request: impl FnOnce(jsonrpsee::http_client::HttpClient) -> F,
+    ) -> ProviderResult<T>
+    where
+        F: std::future::Future<Output = Result<T, RpcError>> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.remote.request(request)
+    }
+
+    /// Returns the canonical header with the given number, if it's part of the forked chain.
+    pub(crate) fn header_by_number(
+        &self,
+        number: BlockNumber,
+    ) -> ProviderResult<Option<SealedHeader>> {
+        if number > self.header.number {
+            return Ok(None)
+        }
+        if let Some(header) = self.cache.read().headers.get(&number) {
+            return Ok(Some(header.clone()))
+        }
+
+        let raw =
+            self.request(move |client| async move { client.raw_header(number.into()).await })?;
+        if raw.is_empty() {
+            return Ok(None)
+        }
+        let header = decode_header(&raw)?;
+        self.cache.write().insert_header(header.clone());
+        Ok(Some(header))
+    }
+
+    /// Returns the header with the given hash, if it's a canonical header of the forked chain.
+    fn header_by_hash(&self, hash: BlockHash) -> ProviderResult<Option<SealedHeader>> {
+        let number = self.cache.read().block_numbers.get(&hash).copied();
+        let number = match number {
+            Some(number) => number,
+            None => {
+                let raw = self
+                    .request(move |client| async move { client.raw_header(hash.into()).await })?;
+                if raw.is_empty() {
+                    return Ok(None)
+                }
+                decode_header(&raw)?.number
+            }
+        };
+        // the hash may be of an ommer or of a block after the fork block
+        Ok(self.header_by_number(number)?.filter(|header| header.hash() == hash))
+    }
+
+    /// Returns the number of the block with the given hash or number, if it's part of the forked
+    /// chain.
+    fn block_number(&self, id: BlockHashOrNumber) -> ProviderResult<Option<BlockNumber>> {
+        match id {
+            BlockHashOrNumber::Hash(hash) => {
+                Ok(self.header_by_hash(hash)?.map(|header| header.number))
+            }
+            BlockHashOrNumber::Number(number) => {
+                Ok((number <= self.header.number).then_some(number))
+            }
+        }
+    }
+
+    /// Returns the block with the given hash or number, if it's part of the forked chain.
+    fn block(&self, id: BlockHashOrNumber) -> ProviderResult<Option<Block>> {
+        let Some(number) = self.block_number(id)? else { return Ok(None) };
+        if let Some(block) = self.cache.read().blocks.get(&number) {
+            return Ok(Some(block.clone()))
+        }
+
+        let raw =
+            self.request(move |client| async move { client.raw_block(number.into()).await })?;
+        if raw.is_empty() {
+            return Ok(None)
+        }
+        let block =
+            <Block as alloy_rlp::Decodable>::decode(&mut raw.as_ref()).map_err(remote_error)?;
+        let mut cache = self.cache.write();
+        cache.insert_header(block.header.clone().seal_slow());
+        cache.blocks.insert(number, block.clone());
+        Ok(Some(block))
+    }
+
+    /// Returns the receipts of the block with the given hash or number, if it's part of the forked
+    /// chain.
+    fn receipts(&self, id: BlockHashOrNumber) -> ProviderResult<Option<Vec<Receipt>>> {
+        let Some(number) = self.block_number(id)? else { return Ok(None) };
+        if let Some(receipts) = self.cache.read().receipts.get(&number) {
+            return Ok(Some(receipts.clone()))
+        }
+
+        let raw =
+            self.request(move |client| async move { client.raw_receipts(number.into()).await })?;
+        let receipts = raw
+            .iter()
+            .map(|raw| decode_receipt(raw).map(|receipt| receipt.receipt))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(remote_error)?;
+        self.cache.write().receipts.insert(number, receipts.clone());
+        Ok(Some(receipts))
+    }
+
+    /// Returns the total difficulty of the block with the given number, if it's part of the forked
+    /// chain.
+    fn total_difficulty(&self, number: BlockNumber) -> ProviderResult<Option<U256>> {
+        if number > self.header.number {
+            return Ok(None)
+        }
+        if let Some(td) = self.cache.read().total_difficulties.get(&number) {
+            return Ok(Some(*td))
+        }
+
+        let block = self.request(move |client| async move {
+            client.block_by_number(BlockNumberOrTag::Number(number), false).await
+        })?;
+        let Some(td) = block.and_then(|block| block.inner.total_difficulty) else {
+            return Ok(None)
+        };
+        self.cache.write().total_difficulties.insert(number, td);
+        Ok(Some(td))
+    }
+
+    /// Returns the account at the given block.
+    pub(crate) fn account(
+        &self,
+        block: BlockNumber,
+        address: Address,
+    ) -> ProviderResult<Option<Account>> {
+        if let Some(account) = self.cache.read().accounts.get(&(block, address)) {
+            return Ok(*account)
+        }
+        self.fetch_proof(block, address, Vec::new())?;
+        Ok(self.cache.read().accounts.get(&(block, address)).copied().flatten())
+    }
+
+    /// Returns the value of the storage slot of the account at the given block.
+    pub(crate) fn storage(
+        &self,
+        block: BlockNumber,
+        address: Address,
+        slot: B256,
+    ) -> ProviderResult<U256> {
+        if let Some(value) = self.cache.read().storage.get(&(block, address, slot)) {
+            return Ok(*value)
+        }
+        self.fetch_proof(block, address, vec![slot])?;
+        Ok(self.cache.read().storage.get(&(block, address, slot)).copied().unwrap_or_default())
+    }
+
+    /// Returns the bytecode with the given hash, if it was fetched with its account.
+    pub(crate) fn bytecode(&self, code_hash: &B256) -> Option<Bytecode> {
+        self.cache.read().bytecodes.get(code_hash).cloned()
+    }
+
+    /// Fetches the proof of the account and the storage slots at the given block, caching the
+    /// account, the values of the slots and the trie nodes of the proof.
+    ///
+    /// The bytecode of the account is fetched as well, since it can't be fetched by its hash.
+    fn fetch_proof(
+        &self,
+        block: BlockNumber,
+        address: Address,
+        slots: Vec<B256>,
+    ) -> ProviderResult<AccountProof> {
+        let keys = slots.into_iter().map(JsonStorageKey).collect();
+        let proof = self.request(move |client| async move {
+            client.get_proof(address, keys, Some(block.into())).await
+        })?;
+
+        let account = Account {
+            nonce: proof.nonce.to::<u64>(),
+            balance: proof.balance,
+            bytecode_hash: (proof.code_hash != KECCAK_EMPTY).then_some(proof.code_hash),
+        };
+        // accounts that don't exist have an empty proof response
+        let exists = !account.is_empty() || proof.storage_hash != EMPTY_ROOT_HASH;
+
+        if let Some(code_hash) = account.bytecode_hash {
+            if !self.cache.read().bytecodes.contains_key(&code_hash) {
+                let code = self.request(move |client| async move {
+                    client.get_code(address, Some(block.into())).await
+                })?;
+                self.cache.write().bytecodes.insert(code_hash, Bytecode::new_raw(code));
+            }
+        }
+
+        let mut account_proof = AccountProof::new(address);
+        account_proof.set_proof(proof.account_proof.clone());
+        let mut storage_proofs = Vec::with_capacity(proof.storage_proof.len());
+
+        let mut cache = self.cache.write();
+        let at_fork = block == self.header.number;
+        cache.accounts.insert((block, address), exists.then_some(account));
+        cache
+            .trie_nodes
+            .extend(proof.account_proof.into_iter().map(|node| (keccak256(&node), node)));
+        if at_fork {
+            cache.proven_accounts.insert(address);
+        }
+        for storage_proof in proof.storage_proof {
+            let slot = storage_proof.key.0;
+            cache.storage.insert((block, address, slot), storage_proof.value);
+            cache
+                .trie_nodes
+                .extend(storage_proof.proof.iter().map(|node| (keccak256(node), node.clone())));
+            if at_fork {
+                cache.proven_slots.insert((address, slot));
+            }
+
+            let mut primitive_proof = StorageProof::new(slot);
+            primitive_proof.set_value(storage_proof.value);
+            primitive_proof.set_proof(storage_proof.proof);
+            storage_proofs.push(primitive_proof);
+        }
+        if exists {
+            account_proof.set_account(account, proof.storage_hash, storage_proofs);
+        } else {
+            account_proof.storage_root = proof.storage_hash;
+            account_proof.storage_proofs = storage_proofs;
+        }
+        Ok(account_proof)
+    }
+
+    /// Returns the proof of the account and the storage slots at the given block.
+    pub(crate) fn proof(
+        &self,
+        block: BlockNumber,
+        address: Address,
+        slots: &[B256],
+    ) -> ProviderResult<AccountProof> {
+        self.fetch_proof(block, address, slots.to_vec())
+    }
+
+    /// Computes the state root of the fork block after applying the changes.
+    ///
+    /// The proofs of the changed accounts and slots that weren't fetched yet are fetched first.
+    pub(crate) fn state_root(&self, changes: &Overlay) -> ProviderResult<B256> {
+        for (address, slots) in changes.changed_slots() {
+            let (proven, missing) = {
+                let cache = self.cache.read();
+                let missing = slots
+                    .into_iter()
+                    .filter(|slot| !cache.proven_slots.contains(&(address, *slot)))
+                    .collect::<Vec<_>>();
+                (cache.proven_accounts.contains(&address), missing)
+            };
+            if !proven || !missing.is_empty() {
+                self.fetch_proof(self.header.number, address, missing)?;
+            }
+        }
+
+        let nodes = self.cache.read().trie_nodes.values().cloned().collect::<Vec<_>>();
+        WitnessState::new(self.header.state_root, nodes)
+            .state_root(&changes.hashed_state())
+            .map_err(|err| ProviderError::ExecutionWitness(err.to_string()))
+    }
+
+    /// Returns the range of block numbers of the forked chain within the given bounds.
+    fn block_range(&self, range: impl RangeBounds<BlockNumber>) -> Range<BlockNumber> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1),
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => BlockNumber::MAX,
+        };
+        start..end.min(self.header.number + 1)
+    }
+}
+
+/// Decodes and seals a header returned by `debug_getRawHeader`.
+fn decode_header(raw: &[u8]) -> ProviderResult<SealedHeader> {
+    Ok(<Header as alloy_rlp::Decodable>::decode(&mut &raw[..]).map_err(remote_error)?.seal_slow())
+}
+
+/// Decodes a receipt returned by `debug_getRawReceipts`, which is either in the network encoding
+/// or, for typed receipts, in the EIP-2718 encoding.
+fn decode_receipt(raw: &[u8]) -> alloy_rlp::Result<ReceiptWithBloom> {
+    use alloy_rlp::{Decodable, Encodable};
+
+    match raw.first() {
+        Some(ty) if *ty < alloy_rlp::EMPTY_STRING_CODE => {
+            let mut buf = Vec::with_capacity(raw.len() + 9);
+            alloy_rlp::Header { list: false, payload_length: raw.len() }.encode(&mut buf);
+            buf.extend_from_slice(raw);
+            ReceiptWithBloom::decode(&mut buf.as_slice())
+        }
+        _ => ReceiptWithBloom::decode(&mut &raw[..]),
+    }
+}
+
+impl HeaderProvider for ForkedStateProvider {
+    fn header(&self, block_hash: &BlockHash) -> ProviderResult<Option<Header>> {
+        Ok(self.fork.header_by_hash(*block_hash)?.map(SealedHeader::unseal))
+    }
+
+    fn header_by_number(&self, num: u64) -> ProviderResult<Option<Header>> {
+        Ok(self.fork.header_by_number(num)?.map(SealedHeader::unseal))
+    }
+
+    fn header_td(&self, hash: &BlockHash) -> ProviderResult<Option<U256>> {
+        match self.fork.header_by_hash(*hash)? {
+            Some(header) => self.fork.total_difficulty(header.number),
+            None => Ok(None),
+        }
+    }
+
+    fn header_td_by_number(&self, number: BlockNumber) -> ProviderResult<Option<U256>> {
+        self.fork.total_difficulty(number)
+    }
+
+    fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> ProviderResult<Vec<Header>> {
+        Ok(self
+            .sealed_headers_while(range, |_| true)?
+            .into_iter()
+            .map(SealedHeader::unseal)
+            .collect())
+    }
+
+    fn sealed_header(&self, number: BlockNumber) -> ProviderResult<Option<SealedHeader>> {
+        self.fork.header_by_number(number)
+    }
+
+    fn sealed_headers_while(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        mut predicate: impl FnMut(&SealedHeader) -> bool,
+    ) -> ProviderResult<Vec<SealedHeader>> {
+        let mut headers = Vec::new();
+        for number in self.fork.block_range(range) {
+            let Some(header) = self.fork.header_by_number(number)? else { break };
+            if !predicate(&header) {
+                break
+            }
+            headers.push(header);
+        }
+        Ok(headers)
+    }
+}
+
+impl BlockHashReader for ForkedStateProvider {
+    fn block_hash(&self, number: u64) -> ProviderResult<Option<B256>> {
+        Ok(self.fork.header_by_number(number)?.map(|header| header.hash()))
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        Ok(self
+            .sealed_headers_while(start..end, |_| true)?
+            .iter()
+            .map(SealedHeader::hash)
+            .collect())
+    }
+}
+
+impl BlockNumReader for ForkedStateProvider {
+    fn chain_info(&self) -> ProviderResult<ChainInfo> {
+        Ok(ChainInfo { best_hash: self.fork.header.hash(), best_number: self.fork.header.number })
+    }
+
+    fn best_block_number(&self) -> ProviderResult<BlockNumber> {
+        Ok(self.fork.header.number)
+    }
+
+    fn last_block_number(&self) -> ProviderResult<BlockNumber> {
+        Ok(self.fork.header.number)
+    }
+
+    fn block_number(&self, hash: B256) -> ProviderResult<Option<BlockNumber>> {
+        Ok(self.fork.header_by_hash(hash)?.map(|header| header.number))
+    }
+}
+
+impl BlockIdReader for ForkedStateProvider {
+    fn pending_block_num_hash(&self) -> ProviderResult<Option<reth_primitives::BlockNumHash>> {
+        Ok(None)
+    }
+
+    fn safe_block_num_hash(&self) -> ProviderResult<Option<reth_primitives::BlockNumHash>> {
+        Ok(None)
+    }
+
+    fn finalized_block_num_hash(&self) -> ProviderResult<Option<reth_primitives::BlockNumHash>> {
+        Ok(None)
+    }
+}
+
+impl BlockReader for ForkedStateProvider {
+    fn find_block_by_hash(
+        &self,
+        hash: B256,
+        _source: BlockSource,
+    ) -> ProviderResult<Option<Block>> {
+        self.block(hash.into())
+    }
+
+    fn block(&self, id: BlockHashOrNumber) -> ProviderResult<Option<Block>> {
+        self.fork.block(id)
+    }
+
+    fn pending_block(&self) -> ProviderResult<Option<SealedBlock>> {
+        Ok(None)
+    }
+
+    fn pending_block_with_senders(&self) -> ProviderResult<Option<SealedBlockWithSenders>> {
+        Ok(None)
+    }
+
+    fn pending_block_and_receipts(&self) -> ProviderResult<Option<(SealedBlock, Vec<Receipt>)>> {
+        Ok(None)
+    }
+
+    fn ommers(&self, id: BlockHashOrNumber) -> ProviderResult<Option<Vec<Header>>> {
+        Ok(self.block(id)?.map(|block| block.ommers))
+    }
+
+    fn block_body_indices(&self, _num: u64) -> ProviderResult<Option<StoredBlockBodyIndices>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn block_with_senders(
+        &self,
+        id: BlockHashOrNumber,
+        _transaction_kind: TransactionVariant,
+    ) -> ProviderResult<Option<BlockWithSenders>> {
+        let Some(block) = self.block(id)? else { return Ok(None) };
+        block.with_recovered_senders().map(Some).ok_or(ProviderError::SenderRecoveryError)
+    }
+
+    fn block_range(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<Vec<Block>> {
+        let mut blocks = Vec::new();
+        for number in self.fork.block_range(range) {
+            let Some(block) = self.block(number.into())? else { break };
+            blocks.push(block);
+        }
+        Ok(blocks)
+    }
+}
+
+impl BlockReaderIdExt for ForkedStateProvider {
+    fn block_by_id(&self, id: BlockId) -> ProviderResult<Option<Block>> {
+        match id {
+            BlockId::Number(num) => self.block_by_number_or_tag(num),
+            BlockId::Hash(hash) => self.block_by_hash(hash.block_hash),
+        }
+    }
+
+    fn sealed_header_by_id(&self, id: BlockId) -> ProviderResult<Option<SealedHeader>> {
+        match id {
+            BlockId::Number(num) => self.sealed_header_by_number_or_tag(num),
+            BlockId::Hash(hash) => self.fork.header_by_hash(hash.block_hash),
+        }
+    }
+
+    fn header_by_id(&self, id: BlockId) -> ProviderResult<Option<Header>> {
+        Ok(self.sealed_header_by_id(id)?.map(SealedHeader::unseal))
+    }
+
+    fn ommers_by_id(&self, id: BlockId) -> ProviderResult<Option<Vec<Header>>> {
+        match id {
+            BlockId::Number(num) => self.ommers_by_number_or_tag(num),
+            BlockId::Hash(hash) => self.ommers(BlockHashOrNumber::Hash(hash.block_hash)),
+        }
+    }
+}
+
+impl TransactionsProvider for ForkedStateProvider {
+    fn transaction_id(&self, _tx_hash: TxHash) -> ProviderResult<Option<TxNumber>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn transaction_by_id(&self, _id: TxNumber) -> ProviderResult<Option<TransactionSigned>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn transaction_by_id_no_hash(
+        &self,
+        _id: TxNumber,
+    ) -> ProviderResult<Option<TransactionSignedNoHash>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn transaction_by_hash(&self, hash: TxHash) -> ProviderResult<Option<TransactionSigned>> {
+        Ok(self.transaction_by_hash_with_meta(hash)?.map(|(tx, _)| tx))
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> ProviderResult<Option<(TransactionSigned, TransactionMeta)>> {
+        let tx = self
+            .fork
+            .request(move |client| async move { client.transaction_by_hash(hash).await })?;
+        let Some(block_hash) = tx.and_then(|tx| tx.block_hash) else { return Ok(None) };
+        let Some(block) = self.block(block_hash.into())? else { return Ok(None) };
+
+        let Some((index, tx)) = block.body.iter().enumerate().find(|(_, tx)| tx.hash() == hash)
+        else {
+            return Ok(None)
+        };
+        let meta = TransactionMeta {
+            tx_hash: hash,
+            index: index as u64,
+            block_hash,
+            block_number: block.header.number,
+            base_fee: block.header.base_fee_per_gas,
+            excess_blob_gas: block.header.excess_blob_gas,
+        };
+        Ok(Some((tx.clone(), meta)))
+    }
+
+    fn transaction_block(&self, _id: TxNumber) -> ProviderResult<Option<BlockNumber>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn transactions_by_block(
+        &self,
+        id: BlockHashOrNumber,
+    ) -> ProviderResult<Option<Vec<TransactionSigned>>> {
+        Ok(self.block(id)?.map(|block| block.body))
+    }
+
+    fn transactions_by_block_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<Vec<TransactionSigned>>> {
+        let mut transactions = Vec::new();
+        for number in self.fork.block_range(range) {
+            let Some(block) = self.block(number.into())? else { break };
+            transactions.push(block.body);
+        }
+        Ok(transactions)
+    }
+
+    fn transactions_by_tx_range(
+        &self,
+        _range: impl RangeBounds<TxNumber>,
+    ) -> ProviderResult<Vec<TransactionSignedNoHash>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn senders_by_tx_range(
+        &self,
+        _range: impl RangeBounds<TxNumber>,
+    ) -> ProviderResult<Vec<Address>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn transaction_sender(&self, _id: TxNumber) -> ProviderResult<Option<Address>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+}
+
+impl ReceiptProvider for ForkedStateProvider {
+    fn receipt(&self, _id: TxNumber) -> ProviderResult<Option<Receipt>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn receipt_by_hash(&self, hash: TxHash) -> ProviderResult<Option<Receipt>> {
+        let Some((_, meta)) = self.transaction_by_hash_with_meta(hash)? else { return Ok(None) };
+        Ok(self
+            .fork
+            .receipts(meta.block_number.into())?
+            .and_then(|receipts| receipts.into_iter().nth(meta.index as usize)))
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> ProviderResult<Option<Vec<Receipt>>> {
+        self.fork.receipts(block)
+    }
+
+    fn receipts_by_tx_range(
+        &self,
+        _range: impl RangeBounds<TxNumber>,
+    ) -> ProviderResult<Vec<Receipt>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+}
+
+impl ReceiptProviderIdExt for ForkedStateProvider {}
+
+impl WithdrawalsProvider for ForkedStateProvider {
+    fn withdrawals_by_block(
+        &self,
+        id: BlockHashOrNumber,
+        _timestamp: u64,
+    ) -> ProviderResult<Option<Vec<Withdrawal>>> {
+        Ok(self.block(id)?.and_then(|block| block.withdrawals))
+    }
+
+    fn latest_withdrawal(&self) -> ProviderResult<Option<Withdrawal>> {
+        Ok(self
+            .withdrawals_by_block(self.fork.header.number.into(), self.fork.header.timestamp)?
+            .and_then(|mut withdrawals| withdrawals.pop()))
+    }
+}
+
+impl ChainSpecProvider for ForkedStateProvider {
+    fn chain_spec(&self) -> Arc<ChainSpec> {
+        self.fork.chain_spec.clone()
+    }
+}
+
+impl EvmEnvProvider for ForkedStateProvider {
+    fn fill_env_at(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
+    ) -> ProviderResult<()> {
+        let header = self.header_by_hash_or_number(at)?.ok_or(ProviderError::HeaderNotFound(at))?;
+        self.fill_env_with_header(cfg, block_env, &header)
+    }
+
+    fn fill_env_with_header(
+        &self,
+        cfg: &mut CfgEnv,
+        block_env: &mut BlockEnv,
+        header: &Header,
+    ) -> ProviderResult<()> {
+        let total_difficulty = self
+            .header_td_by_number(header.number)?
+            .ok_or_else(|| ProviderError::TotalDifficultyNotFound(header.number))?;
+        fill_cfg_and_block_env(cfg, block_env, &self.fork.chain_spec, header, total_difficulty);
+        Ok(())
+    }
+
+    fn fill_block_env_at(
+        &self,
+        block_env: &mut BlockEnv,
+        at: BlockHashOrNumber,
+    ) -> ProviderResult<()> {
+        let header = self.header_by_hash_or_number(at)?.ok_or(ProviderError::HeaderNotFound(at))?;
+        self.fill_block_env_with_header(block_env, &header)
+    }
+
+    fn fill_block_env_with_header(
+        &self,
+        block_env: &mut BlockEnv,
+        header: &Header,
+    ) -> ProviderResult<()> {
+        let total_difficulty = self
+            .header_td_by_number(header.number)?
+            .ok_or_else(|| ProviderError::TotalDifficultyNotFound(header.number))?;
+        let spec_id = revm_spec(
+            &self.fork.chain_spec,
+            Head {
+                number: header.number,
+                timestamp: header.timestamp,
+                difficulty: header.difficulty,
+                total_difficulty,
+                // Not required
+                hash: Default::default(),
+            },
+        );
+        let after_merge = spec_id >= SpecId::MERGE;
+        fill_block_env(block_env, &self.fork.chain_spec, header, after_merge);
+        Ok(())
+    }
+
+    fn fill_cfg_env_at(&self, cfg: &mut CfgEnv, at: BlockHashOrNumber) -> ProviderResult<()> {
+        let header = self.header_by_hash_or_number(at)?.ok_or(ProviderError::HeaderNotFound(at))?;
+        self.fill_cfg_env_with_header(cfg, &header)
+    }
+
+    fn fill_cfg_env_with_header(&self, cfg: &mut CfgEnv, header: &Header) -> ProviderResult<()> {
+        let total_difficulty = self
+            .header_td_by_number(header.number)?
+            .ok_or_else(|| ProviderError::TotalDifficultyNotFound(header.number))?;
+        fill_cfg_env(cfg, &self.fork.chain_spec, header, total_difficulty);
+        Ok(())
+    }
+}
+
+impl AccountReader for ForkedStateProvider {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        self.fork_state().basic_account(address)
+    }
+}
+
+impl StateProviderFactory for ForkedStateProvider {
+    fn latest(&self) -> ProviderResult<StateProviderBox> {
+        Ok(Box::new(self.fork_state()))
+    }
+
+    fn history_by_block_number(&self, block: BlockNumber) -> ProviderResult<StateProviderBox> {
+        Ok(Box::new(self.state_at(block)?))
+    }
+
+    fn history_by_block_hash(&self, block: BlockHash) -> ProviderResult<StateProviderBox> {
+        let number = self
+            .fork
+            .header_by_hash(block)?
+            .ok_or(ProviderError::StateForHashNotFound(block))?
+            .number;
+        Ok(Box::new(self.state_at(number)?))
+    }
+
+    fn state_by_block_hash(&self, block: BlockHash) -> ProviderResult<StateProviderBox> {
+        self.history_by_block_hash(block)
+    }
+
+    fn pending(&self) -> ProviderResult<StateProviderBox> {
+        self.latest()
+    }
+
+    fn pending_state_by_hash(&self, _block_hash: B256) -> ProviderResult<Option<StateProviderBox>> {
+        Ok(None)
+    }
+
+    fn pending_with_provider(
+        &self,
+        bundle_state_data: Box<dyn BundleStateDataProvider>,
+    ) -> ProviderResult<StateProviderBox> {
+        let state_provider = self.history_by_block_hash(bundle_state_data.canonical_fork().hash)?;
+        Ok(Box::new(BundleStateProvider::new(state_provider, bundle_state_data)))
+    }
+}
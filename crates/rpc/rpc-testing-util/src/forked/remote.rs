@@ -0,0 +1,62 @@
+//! Blocking access to the remote node of a [ForkedStateProvider](super::ForkedStateProvider).
+
+use jsonrpsee::{
+    core::Error as RpcError,
+    http_client::{HttpClient, HttpClientBuilder},
+};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+/// A client of the remote node that can be called from the synchronous provider traits.
+///
+/// Requests run on a dedicated runtime, so the client can be called from within and outside of
+/// other runtimes.
+#[derive(Debug)]
+pub(crate) struct RemoteClient {
+    client: HttpClient,
+    /// Only taken when the client is dropped.
+    runtime: Option<Runtime>,
+}
+
+impl RemoteClient {
+    /// Creates a client of the node at the given HTTP URL.
+    pub(crate) fn new(url: &str) -> ProviderResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("forked-provider")
+            .enable_all()
+            .build()
+            .map_err(remote_error)?;
+        let client = {
+            let _guard = runtime.enter();
+            HttpClientBuilder::default().build(url).map_err(remote_error)?
+        };
+        Ok(Self { client, runtime: Some(runtime) })
+    }
+
+    /// Sends the request created with the client and blocks until it completes.
+    pub(crate) fn request<T, F>(&self, request: impl FnOnce(HttpClient) -> F) -> ProviderResult<T>
+    where
+        F: Future<Output = Result<T, RpcError>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let runtime = self.runtime.as_ref().expect("runtime is only taken on drop");
+        let task = runtime.spawn(request(self.client.clone()));
+        futures::executor::block_on(task).map_err(remote_error)?.map_err(remote_error)
+    }
+}
+
+impl Drop for RemoteClient {
+    fn drop(&mut self) {
+        // dropping a runtime blocks, which panics if the client is dropped within a runtime
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Converts an error of the remote node to a [ProviderError].
+pub(crate) fn remote_error(err: impl std::fmt::Display) -> ProviderError {
+    ProviderError::Remote(err.to_string())
+}
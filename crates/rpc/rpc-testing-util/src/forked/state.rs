@@ -0,0 +1,221 @@
+//! The state of a forked chain with local changes.
+
+use super::Fork;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_primitives::{
+    keccak256, revm::compat::into_reth_acc, trie::AccountProof, Account, Address, BlockNumber,
+    Bytecode, StorageKey, StorageValue, B256, U256,
+};
+use reth_provider::{
+    AccountReader, BlockHashReader, BundleStateWithReceipts, StateProvider, StateRootProvider,
+};
+use reth_trie::{updates::TrieUpdates, HashedPostState, HashedStorage};
+use std::{collections::HashMap, sync::Arc};
+
+/// Local changes to the state of the fork block.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Overlay {
+    accounts: HashMap<Address, OverlayAccount>,
+    bytecodes: HashMap<B256, Bytecode>,
+}
+
+/// The local changes to an account.
+#[derive(Debug, Clone, Default)]
+struct OverlayAccount {
+    /// The changed account, `Some(None)` if it was removed, `None` if only its storage changed.
+    info: Option<Option<Account>>,
+    /// Whether the storage of the remote account was cleared.
+    storage_wiped: bool,
+    /// The changed storage slots.
+    storage: HashMap<B256, U256>,
+}
+
+impl Overlay {
+    /// Returns the changed account, `Some(None)` if it was removed.
+    pub(crate) fn account(&self, address: &Address) -> Option<Option<Account>> {
+        self.accounts.get(address).and_then(|account| account.info)
+    }
+
+    /// Returns the value of the storage slot, if it was changed.
+    pub(crate) fn storage(&self, address: &Address, slot: &B256) -> Option<U256> {
+        let account = self.accounts.get(address)?;
+        account.storage.get(slot).copied().or(account.storage_wiped.then_some(U256::ZERO))
+    }
+
+    /// Returns the bytecode with the given hash, if it was added.
+    pub(crate) fn bytecode(&self, code_hash: &B256) -> Option<Bytecode> {
+        self.bytecodes.get(code_hash).cloned()
+    }
+
+    /// Sets the account, or removes it with its storage if `None`.
+    pub(crate) fn set_account(&mut self, address: Address, account: Option<Account>) {
+        let entry = self.accounts.entry(address).or_default();
+        if account.is_none() {
+            entry.storage_wiped = true;
+            entry.storage.clear();
+        }
+        entry.info = Some(account);
+    }
+
+    /// Sets the value of the storage slot.
+    pub(crate) fn set_storage(&mut self, address: Address, slot: B256, value: U256) {
+        self.accounts.entry(address).or_default().storage.insert(slot, value);
+    }
+
+    /// Adds the bytecode with the given hash.
+    pub(crate) fn set_bytecode(&mut self, code_hash: B256, bytecode: Bytecode) {
+        self.bytecodes.insert(code_hash, bytecode);
+    }
+
+    /// Applies the changes of the bundle.
+    pub(crate) fn apply_bundle(&mut self, bundle: &BundleStateWithReceipts) {
+        for (address, account) in bundle.bundle_accounts_iter() {
+            let entry = self.accounts.entry(address).or_default();
+            if account.status.was_destroyed() {
+                entry.storage_wiped = true;
+                entry.storage.clear();
+            }
+            entry.info = Some(account.info.clone().map(into_reth_acc));
+            for (slot, value) in &account.storage {
+                entry.storage.insert(B256::new(slot.to_be_bytes()), value.present_value);
+            }
+        }
+        self.bytecodes.extend(
+            bundle.state().contracts.iter().map(|(hash, code)| (*hash, Bytecode(code.clone()))),
+        );
+    }
+
+    /// Returns the changed accounts with their changed storage slots.
+    ///
+    /// The slots of accounts whose storage was cleared are not needed to compute their storage
+    /// root.
+    pub(crate) fn changed_slots(&self) -> impl Iterator<Item = (Address, Vec<B256>)> + '_ {
+        self.accounts.iter().map(|(address, account)| {
+            let slots = if account.storage_wiped {
+                Vec::new()
+            } else {
+                account.storage.keys().copied().collect()
+            };
+            (*address, slots)
+        })
+    }
+
+    /// Returns the changes with hashed keys.
+    pub(crate) fn hashed_state(&self) -> HashedPostState {
+        let mut state = HashedPostState::default();
+        for (address, account) in &self.accounts {
+            let hashed_address = keccak256(address);
+            if let Some(info) = account.info {
+                state.insert_account(hashed_address, info);
+            }
+            let mut storage = HashedStorage::new(account.storage_wiped);
+            for (slot, value) in &account.storage {
+                storage.insert_slot(keccak256(slot), *value);
+            }
+            state.insert_hashed_storage(hashed_address, storage);
+        }
+        state.sorted()
+    }
+}
+
+/// The state of a [ForkedStateProvider](super::ForkedStateProvider) at a block of the forked
+/// chain.
+///
+/// The state of the fork block includes the local changes at the time the state was created.
+/// Earlier states are served from the remote node as is.
+#[derive(Debug)]
+pub struct ForkedState {
+    fork: Arc<Fork>,
+    block: BlockNumber,
+    /// The local changes, only set for the state of the fork block.
+    overlay: Option<Arc<Overlay>>,
+}
+
+impl ForkedState {
+    pub(crate) fn new(fork: Arc<Fork>, block: BlockNumber, overlay: Option<Arc<Overlay>>) -> Self {
+        Self { fork, block, overlay }
+    }
+}
+
+impl AccountReader for ForkedState {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        if let Some(account) = self.overlay.as_ref().and_then(|overlay| overlay.account(&address)) {
+            return Ok(account)
+        }
+        self.fork.account(self.block, address)
+    }
+}
+
+impl BlockHashReader for ForkedState {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        if number > self.block {
+            return Ok(None)
+        }
+        Ok(self.fork.header_by_number(number)?.map(|header| header.hash()))
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        (start..end.min(self.block + 1))
+            .map(|number| {
+                self.block_hash(number)?.ok_or(ProviderError::HeaderNotFound(number.into()))
+            })
+            .collect()
+    }
+}
+
+impl StateRootProvider for ForkedState {
+    /// Computes the state root from the proofs of the changed accounts and slots, which are
+    /// fetched from the remote node.
+    ///
+    /// Fails with [ProviderError::ExecutionWitness] if a removed account or slot collapses a
+    /// branch whose remaining child is not part of any fetched proof.
+    fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        let Some(overlay) = &self.overlay else {
+            return Err(ProviderError::StateRootNotAvailableForHistoricalBlock)
+        };
+        let mut changes = Overlay::clone(overlay);
+        changes.apply_bundle(bundle_state);
+        self.fork.state_root(&changes)
+    }
+
+    fn state_root_with_updates(
+        &self,
+        _bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+}
+
+impl StateProvider for ForkedState {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        let overlay_value =
+            self.overlay.as_ref().and_then(|overlay| overlay.storage(&account, &storage_key));
+        let value = match overlay_value {
+            Some(value) => value,
+            None => self.fork.storage(self.block, account, storage_key)?,
+        };
+        Ok((!value.is_zero()).then_some(value))
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        if let Some(bytecode) =
+            self.overlay.as_ref().and_then(|overlay| overlay.bytecode(&code_hash))
+        {
+            return Ok(Some(bytecode))
+        }
+        Ok(self.fork.bytecode(&code_hash))
+    }
+
+    /// Returns the proof of the remote state, without the local changes.
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        self.fork.proof(self.block, address, keys)
+    }
+}
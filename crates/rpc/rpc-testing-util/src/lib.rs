@@ -8,6 +8,8 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 pub mod debug;
+pub mod forked;
+pub mod prestate;
 pub mod trace;
 
 pub mod utils;
@@ -0,0 +1,195 @@
+//! Hashed post states built from the diffs of the prestate tracer.
+//!
+//! State root mismatches reported by third parties can be reproduced with only RPC access to
+//! their node: the state changes of the block are traced with the prestate tracer in diff mode and
+//! converted to the [HashedPostState] of the block, which yields the prefix sets of the root
+//! computation with [HashedPostState::construct_prefix_sets].
+
+use crate::forked::Overlay;
+use jsonrpsee::core::Error as RpcError;
+use reth_primitives::{keccak256, Account, Address, BlockNumberOrTag, Bytes, U256};
+use reth_rpc_api::clients::DebugApiClient;
+use reth_rpc_types::trace::geth::{
+    AccountState, DiffMode, GethDebugBuiltInTracerType, GethDebugTracerType,
+    GethDebugTracingOptions, GethTrace, PreStateFrame, TraceResult,
+};
+use reth_trie::HashedPostState;
+
+/// The state changes of a block, merged from the prestate diffs of its transactions.
+#[derive(Debug, Clone, Default)]
+pub struct PrestateDiff {
+    changes: Overlay,
+}
+
+impl PrestateDiff {
+    /// Traces all transactions of the block with the prestate tracer in diff mode and merges
+    /// their diffs.
+    pub async fn fetch<C>(client: &C, block: BlockNumberOrTag) -> Result<Self, RpcError>
+    where
+        C: DebugApiClient + Sync,
+    {
+        let traces =
+            client.debug_trace_block_by_number(block, Some(Self::tracing_options())).await?;
+        Self::from_traces(traces)
+    }
+
+    /// Returns the options of the prestate tracer in diff mode.
+    pub fn tracing_options() -> GethDebugTracingOptions {
+        GethDebugTracingOptions {
+            tracer: Some(GethDebugTracerType::BuiltInTracer(
+                GethDebugBuiltInTracerType::PreStateTracer,
+            )),
+            tracer_config: serde_json::json!({ "diffMode": true }).into(),
+            ..Default::default()
+        }
+    }
+
+    /// Merges the traces of the transactions of a block, in the order of the transactions.
+    ///
+    /// Fails if a transaction could not be traced or was not traced in diff mode.
+    pub fn from_traces(traces: impl IntoIterator<Item = TraceResult>) -> Result<Self, RpcError> {
+        let mut this = Self::default();
+        for trace in traces {
+            match trace {
+                TraceResult::Success {
+                    result: GethTrace::PreStateTracer(PreStateFrame::Diff(diff)),
+                    ..
+                } => this.apply(&diff),
+                TraceResult::Success { tx_hash, .. } => {
+                    return Err(RpcError::Custom(format!(
+                    "transaction {tx_hash:?} was not traced with the prestate tracer in diff mode"
+                )))
+                }
+                TraceResult::Error { error, tx_hash } => {
+                    return Err(RpcError::Custom(format!(
+                        "failed to trace transaction {tx_hash:?}: {error}"
+                    )))
+                }
+            }
+        }
+        Ok(this)
+    }
+
+    /// Applies the diff of a transaction on top of the diffs of the previous transactions.
+    ///
+    /// The prestate of a diff contains the full state of every changed account, but only the
+    /// changed slots with a non-zero value. The poststate only contains the changed fields and the
+    /// slots with a non-zero value, and no accounts that were destroyed.
+    pub fn apply(&mut self, diff: &DiffMode) {
+        for (address, pre) in &diff.pre {
+            match diff.post.get(address) {
+                Some(post) => {
+                    // changed slots missing from the poststate were cleared
+                    for slot in pre.storage.keys().filter(|slot| !post.storage.contains_key(slot)) {
+                        self.changes.set_storage(*address, *slot, U256::ZERO);
+                    }
+                }
+                None => self.changes.set_account(*address, None),
+            }
+        }
+
+        for (address, post) in &diff.post {
+            let pre = diff.pre.get(address);
+            self.changes.set_account(*address, Some(account(pre, post)));
+            for (slot, value) in &post.storage {
+                self.changes.set_storage(*address, *slot, U256::from_be_bytes(value.0));
+            }
+        }
+    }
+
+    /// Returns the changes with hashed keys.
+    pub fn hashed_post_state(&self) -> HashedPostState {
+        self.changes.hashed_state()
+    }
+}
+
+/// Returns the account after a transaction from its prestate, if it existed before, and the
+/// changed fields of its poststate.
+fn account(pre: Option<&AccountState>, post: &AccountState) -> Account {
+    let code = post.code.as_ref().or_else(|| pre.and_then(|pre| pre.code.as_ref()));
+    Account {
+        nonce: post.nonce.or_else(|| pre.and_then(|pre| pre.nonce)).unwrap_or_default(),
+        balance: post.balance.or_else(|| pre.and_then(|pre| pre.balance)).unwrap_or_default(),
+        bytecode_hash: code.filter(|code| !code.is_empty()).map(keccak256),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::B256;
+    use reth_trie::HashedStorage;
+    use std::collections::BTreeMap;
+
+    fn state(
+        balance: Option<u64>,
+        nonce: Option<u64>,
+        storage: impl IntoIterator<Item = (u8, u8)>,
+    ) -> AccountState {
+        AccountState {
+            balance: balance.map(U256::from),
+            nonce,
+            storage: storage
+                .into_iter()
+                .map(|(slot, value)| (B256::with_last_byte(slot), B256::with_last_byte(value)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merges_transaction_diffs() {
+        let sender = Address::with_last_byte(1);
+        let contract = Address::with_last_byte(2);
+        let destroyed = Address::with_last_byte(3);
+        let code = Bytes::from_static(&[0x60, 0x00]);
+
+        let mut diff = PrestateDiff::default();
+        diff.apply(&DiffMode {
+            pre: BTreeMap::from([
+                (sender, state(Some(16), Some(1), [])),
+                (
+                    contract,
+                    AccountState { code: Some(code.clone()), ..state(Some(0), None, [(1, 5)]) },
+                ),
+                (destroyed, state(Some(1), None, [(0, 1)])),
+            ]),
+            post: BTreeMap::from([
+                (sender, state(Some(8), Some(2), [])),
+                (contract, state(None, None, [(2, 7)])),
+            ]),
+        });
+        // the sender is changed again by the second transaction
+        diff.apply(&DiffMode {
+            pre: BTreeMap::from([(sender, state(Some(8), Some(2), []))]),
+            post: BTreeMap::from([(sender, state(None, Some(3), []))]),
+        });
+
+        let mut expected = HashedPostState::default();
+        expected.insert_account(
+            keccak256(sender),
+            Some(Account { nonce: 3, balance: U256::from(8), bytecode_hash: None }),
+        );
+        expected.insert_hashed_storage(keccak256(sender), HashedStorage::new(false));
+        expected.insert_account(
+            keccak256(contract),
+            Some(Account { nonce: 0, balance: U256::ZERO, bytecode_hash: Some(keccak256(&code)) }),
+        );
+        let mut storage = HashedStorage::new(false);
+        storage.insert_slot(keccak256(B256::with_last_byte(1)), U256::ZERO);
+        storage.insert_slot(keccak256(B256::with_last_byte(2)), U256::from(7));
+        expected.insert_hashed_storage(keccak256(contract), storage);
+        expected.insert_account(keccak256(destroyed), None);
+        expected.insert_hashed_storage(keccak256(destroyed), HashedStorage::new(true));
+        assert_eq!(diff.hashed_post_state(), expected.sorted());
+    }
+
+    #[test]
+    fn rejects_other_traces() {
+        let trace = TraceResult::Success {
+            result: GethTrace::PreStateTracer(PreStateFrame::Default(Default::default())),
+            tx_hash: None,
+        };
+        assert!(PrestateDiff::from_traces([trace]).is_err());
+    }
+}
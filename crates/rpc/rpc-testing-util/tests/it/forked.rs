@@ -0,0 +1,37 @@
+use reth_primitives::{Address, BlockNumberOrTag, MAINNET, U256};
+use reth_provider::{
+    BundleStateWithReceipts, StateProvider, StateProviderFactory, StateRootProvider,
+};
+use reth_rpc_api_testing_util::{forked::ForkedStateProvider, utils::parse_env_url};
+
+/// This is intended to be run locally against a running mainnet node.
+///
+/// This is a noop of env var `RETH_RPC_TEST_NODE_URL` is not set.
+#[test]
+fn forked_state_root() {
+    let url = parse_env_url("RETH_RPC_TEST_NODE_URL");
+    if url.is_err() {
+        return
+    }
+    let url = url.unwrap();
+
+    let provider =
+        ForkedStateProvider::new(&url, MAINNET.clone(), BlockNumberOrTag::Finalized).unwrap();
+    let state_root = provider.fork_block().state_root;
+    let empty = BundleStateWithReceipts::default();
+    assert_eq!(provider.latest().unwrap().state_root(&empty).unwrap(), state_root);
+
+    let address = Address::with_last_byte(0x42);
+    let slot = U256::from(1).into();
+    provider.set_storage(address, slot, U256::from(7));
+    let state = provider.latest().unwrap();
+    assert_eq!(state.storage(address, slot).unwrap(), Some(U256::from(7)));
+    assert_ne!(state.state_root(&empty).unwrap(), state_root);
+
+    // the state of the parent block is not affected by the local changes
+    let parent = provider.history_by_block_number(provider.fork_block().number - 1).unwrap();
+    assert_eq!(parent.storage(address, slot).unwrap(), None);
+
+    provider.reset();
+    assert_eq!(provider.latest().unwrap().state_root(&empty).unwrap(), state_root);
+}
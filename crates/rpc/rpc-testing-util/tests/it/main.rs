@@ -1,3 +1,4 @@
+mod forked;
 mod trace;
 
 fn main() {}
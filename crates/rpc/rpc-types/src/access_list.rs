@@ -0,0 +1,20 @@
+use alloy_primitives::U256;
+use alloy_rpc_types::AccessList;
+use serde::{Deserialize, Serialize};
+
+/// Item of the `eth_createAccessListBatch` response.
+///
+/// The access list and gas of a single call of the batch, or the error it failed with.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListBatchItem {
+    /// The access list of the call, `None` if the call failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    /// The gas used by the call with the access list, `None` if the call failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<U256>,
+    /// The error the call failed with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -89,6 +89,108 @@ pub struct EthProtocolInfo {
     pub genesis: B256,
 }
 
+/// How much the node is restricted due to low free disk space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiskPressure {
+    /// Enough free disk space, the node runs without restrictions.
+    #[default]
+    Normal,
+    /// Free disk space is below the degraded threshold: optional writes are paused and pruning
+    /// runs as often as possible.
+    Degraded,
+    /// Free disk space is below the critical threshold: sync is halted and nothing is written to
+    /// the database.
+    Critical,
+}
+
+/// The free disk space of the data directory, as seen by the node.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskStatus {
+    /// The current disk pressure.
+    pub pressure: DiskPressure,
+    /// The monitored directory.
+    pub path: String,
+    /// Bytes available to the node, `None` if free space could not be determined.
+    pub available_bytes: Option<u64>,
+    /// Total size of the filesystem in bytes, `None` if it could not be determined.
+    pub total_bytes: Option<u64>,
+    /// Available bytes below which the node is degraded.
+    pub degraded_threshold: u64,
+    /// Available bytes below which sync is halted.
+    pub critical_threshold: u64,
+}
+
+/// The log level of a target that was changed at runtime with `admin_setLogLevel`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetLogLevel {
+    /// The target, e.g. `trie-parallel`.
+    pub target: String,
+    /// The level of the target: `off`, `error`, `warn`, `info`, `debug` or `trace`.
+    pub level: String,
+    /// Seconds until the configured level of the target is restored, `None` if the level is kept
+    /// until it's reset.
+    pub expires_in_secs: Option<u64>,
+}
+
+/// An API key of the public RPC server and the access it grants.
+///
+/// Used in the `[rpc]` section of `reth.toml` and by the `admin_` API key methods.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// The key, sent by consumers in the `X-Api-Key` header or the `api_key` query parameter.
+    pub key: String,
+    /// The namespaces the key may call, e.g. `eth`. All namespaces of the server if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<String>,
+    /// The maximum number of calls per second, unlimited if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<u32>,
+    /// The maximum gas of `eth_call` and similar calls, the gas cap of the server if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_call_gas: Option<u64>,
+}
+
+impl ApiKey {
+    /// Returns true if the key can call all namespaces without limits.
+    pub fn is_unrestricted(&self) -> bool {
+        self.namespaces.is_empty() &&
+            self.requests_per_second.is_none() &&
+            self.max_call_gas.is_none()
+    }
+
+    /// Returns true if the key may call the given namespace.
+    pub fn allows_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.is_empty() || self.namespaces.iter().any(|allowed| allowed == namespace)
+    }
+}
+
+/// When a transaction was first seen by the node, as returned by `admin_transactionFirstSeen`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionFirstSeen {
+    /// When the transaction was first seen, in milliseconds since the unix epoch.
+    pub seen_at: u64,
+    /// Where the transaction was first seen from: `announcement`, `broadcast` or `rpc`.
+    pub origin: String,
+    /// The peer the transaction was first seen from, `None` if it was submitted over RPC.
+    pub peer_id: Option<PeerId>,
+    /// The canonical block the transaction was included in, `None` if it wasn't included yet.
+    pub block_number: Option<u64>,
+    /// The hash of the block the transaction was included in.
+    pub block_hash: Option<B256>,
+    /// The timestamp of the block the transaction was included in.
+    pub block_timestamp: Option<u64>,
+    /// When the block the transaction was included in became canonical, in milliseconds since the
+    /// unix epoch.
+    pub included_at: Option<u64>,
+    /// How many milliseconds before the timestamp of its block the transaction was first seen,
+    /// negative if it was first seen after the timestamp of the block.
+    pub lead_time_ms: Option<i64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
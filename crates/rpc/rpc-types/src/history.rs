@@ -0,0 +1,81 @@
+use alloy_primitives::{BlockHash, TxHash, U64};
+use serde::{Deserialize, Serialize};
+
+/// Response type of `reth_getAccountHistory`.
+///
+/// A page of the blocks in which an account changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountHistoryPage {
+    /// The numbers of the blocks in which the account changed, in ascending order.
+    pub blocks: Vec<U64>,
+    /// The block to request the next page from, `None` if this is the last page.
+    pub next_block: Option<U64>,
+}
+
+/// Item of the `reth_reorgHistory` response.
+///
+/// A reorg of the canonical chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorgEntry {
+    /// Unix timestamp in seconds of when the reorg happened.
+    pub timestamp: U64,
+    /// The number of the canonical tip before the reorg.
+    pub old_tip_number: U64,
+    /// The hash of the canonical tip before the reorg.
+    pub old_tip_hash: BlockHash,
+    /// The number of the canonical tip after the reorg.
+    pub new_tip_number: U64,
+    /// The hash of the canonical tip after the reorg.
+    pub new_tip_hash: BlockHash,
+    /// The number of canonical blocks that were reverted.
+    pub depth: U64,
+    /// Hashes of the reverted transactions that are not included in the new canonical chain.
+    pub dropped_transactions: Vec<TxHash>,
+    /// The time in milliseconds it took to make the new chain canonical.
+    pub duration_ms: U64,
+}
+
+/// Data of the error returned for blocks whose history has expired, see
+/// [EIP-4444](https://eips.ethereum.org/EIPS/eip-4444).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryExpiredErrorData {
+    /// The number of the requested block.
+    pub block_number: U64,
+    /// The first block whose history is still available from this node.
+    pub first_available_block: U64,
+    /// The sources the expired history can be retrieved from instead, e.g. `portal` for the
+    /// history network of Portal and `era1` for era1 archives.
+    pub sources: Vec<String>,
+}
+
+/// Data of the error returned for data that is pruned by this node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrunedErrorData {
+    /// The prune segment of the requested data, e.g. `Receipts` or `AccountHistory`.
+    pub segment: String,
+    /// The number of the requested block.
+    pub block_number: U64,
+    /// The first block whose data of the segment is still available from this node.
+    pub earliest_available_block: U64,
+}
+
+/// Item of the `reth_pruneStatus` response.
+///
+/// The retention boundary of a prune segment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneSegmentStatus {
+    /// The prune segment, e.g. `Receipts` or `AccountHistory`.
+    pub segment: String,
+    /// The highest pruned block, `None` if none of the segment is pruned.
+    pub pruned_block: Option<U64>,
+    /// The highest pruned transaction, `None` if the segment isn't pruned by transaction.
+    pub pruned_transaction: Option<U64>,
+    /// The first block whose data of the segment is available, `None` if none of the segment is
+    /// pruned.
+    pub earliest_available_block: Option<U64>,
+}
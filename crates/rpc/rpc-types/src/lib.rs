@@ -9,15 +9,23 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+mod access_list;
 mod admin;
 pub mod beacon;
 mod eth;
+mod history;
 mod mev;
 mod net;
 mod otterscan;
 mod peer;
+mod pool_events;
+mod proof;
 pub mod relay;
+mod result_stream;
 mod rpc;
+mod subscription;
+mod top_of_block;
+mod tx_status;
 
 // re-export for convenience
 pub use alloy_rpc_types::serde_helpers;
@@ -37,9 +45,17 @@ pub use eth::{
     transaction::{self, TransactionKind, TransactionRequest, TypedTransactionRequest},
 };
 
+pub use access_list::*;
 pub use admin::*;
+pub use history::*;
 pub use mev::*;
 pub use net::*;
 pub use otterscan::*;
 pub use peer::*;
+pub use pool_events::*;
+pub use proof::*;
+pub use result_stream::*;
 pub use rpc::*;
+pub use subscription::*;
+pub use top_of_block::*;
+pub use tx_status::*;
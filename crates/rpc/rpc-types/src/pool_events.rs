@@ -0,0 +1,84 @@
+use alloy_primitives::{BlockHash, TxHash, U64};
+use serde::{Deserialize, Serialize};
+
+/// Item of the `txpool_subscribeEvents` subscription.
+///
+/// Every event of the pool is tagged with a sequence number that increases by one with every
+/// event, so subscribers can detect gaps and resume from the last event they have seen.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolEvent {
+    /// The sequence number of the event.
+    pub seq: U64,
+    /// The hash of the transaction the event happened to.
+    pub hash: TxHash,
+    /// What happened to the transaction.
+    #[serde(flatten)]
+    pub kind: TxpoolEventKind,
+}
+
+/// The kinds of [TxpoolEvent]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TxpoolEventKind {
+    /// The transaction was added to the pool.
+    Added {
+        /// Whether the transaction can be included in the next block.
+        pending: bool,
+    },
+    /// The transaction became includable in the next block.
+    Promoted,
+    /// The transaction can no longer be included in the next block, but stays in the pool.
+    Demoted,
+    /// The transaction was replaced by another transaction of the same sender and nonce.
+    #[serde(rename_all = "camelCase")]
+    Replaced {
+        /// The hash of the replacement.
+        replaced_by: TxHash,
+    },
+    /// The transaction was included in a canonical block.
+    #[serde(rename_all = "camelCase")]
+    Mined {
+        /// The hash of the block.
+        block_hash: BlockHash,
+    },
+    /// The transaction was removed from the pool or rejected by it.
+    Dropped {
+        /// Why the transaction was dropped.
+        reason: TxpoolDropReason,
+    },
+}
+
+/// Why a transaction was dropped from the pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TxpoolDropReason {
+    /// The transaction failed validation.
+    Invalid,
+    /// The transaction became invalid after a state change, e.g. its nonce was used.
+    Outdated,
+    /// The transaction was evicted because the pool is full.
+    PoolFull,
+    /// The transaction was removed on request.
+    Removed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_txpool_event() {
+        let event = TxpoolEvent {
+            seq: U64::from(10),
+            hash: TxHash::with_last_byte(1),
+            kind: TxpoolEventKind::Dropped { reason: TxpoolDropReason::PoolFull },
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            r#"{"seq":"0xa","hash":"0x0000000000000000000000000000000000000000000000000000000000000001","event":"dropped","reason":"poolFull"}"#
+        );
+        assert_eq!(serde_json::from_str::<TxpoolEvent>(&json).unwrap(), event);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{serde_helpers::JsonStorageKey, EIP1186AccountProofResponse};
+use alloy_primitives::{Address, BlockHash, Bytes, B256, U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// An account, and optionally some of its storage slots, to generate proofs for.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofTarget {
+    /// The address of the account.
+    pub address: Address,
+    /// The storage slots of the account.
+    #[serde(default)]
+    pub storage_keys: Vec<JsonStorageKey>,
+}
+
+/// Item of the `reth_subscribeProofs` subscription.
+///
+/// Contains the proofs of all subscribed targets at a new canonical block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofsUpdate {
+    /// The number of the block the proofs were generated at.
+    pub block_number: U64,
+    /// The hash of the block the proofs were generated at.
+    pub block_hash: BlockHash,
+    /// The state root of the block, which all proofs are rooted in.
+    pub state_root: B256,
+    /// The proofs of the subscribed targets, in the order they were subscribed in.
+    pub proofs: Vec<EIP1186AccountProofResponse>,
+}
+
+/// Response type of `reth_getTransactionInclusionProof` and `reth_getReceiptInclusionProof`.
+///
+/// Proves that a transaction, or its receipt, is included in a block: the proof is a merkle proof
+/// of the item in the transactions trie, or the receipts trie, of the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProof {
+    /// The hash of the block the transaction is included in.
+    pub block_hash: BlockHash,
+    /// The number of the block the transaction is included in.
+    pub block_number: U64,
+    /// The index of the transaction in the block.
+    pub transaction_index: U64,
+    /// The root of the trie: the transactions root or the receipts root of the block.
+    pub root: B256,
+    /// The key of the item in the trie, the RLP encoding of the transaction index.
+    pub key: Bytes,
+    /// The item: the EIP-2718 encoded transaction or receipt.
+    pub value: Bytes,
+    /// The trie nodes on the path from the root to the item.
+    pub proof: Vec<Bytes>,
+}
+
+/// Response of the `reth_getHeaderAccumulatorProof` RPC method.
+///
+/// Proves that a block is part of the canonical chain: the record of the block is proven in the
+/// accumulator of its epoch, and the root of the epoch accumulator is proven in the historical
+/// accumulator. Both proofs are SSZ merkle proofs, ordered from the leaf to the root and followed
+/// by the length of the list.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeaderAccumulatorProof {
+    /// The number of the block.
+    pub block_number: U64,
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The total difficulty of the chain up to and including the block.
+    pub total_difficulty: U256,
+    /// The epoch of the block.
+    pub epoch: U64,
+    /// The root of the accumulator of the epoch.
+    pub epoch_root: B256,
+    /// The proof of the record of the block in the epoch accumulator.
+    pub epoch_proof: Vec<B256>,
+    /// The root of the historical accumulator the proof was created for.
+    pub accumulator_root: B256,
+    /// The proof of the epoch root in the historical accumulator.
+    pub accumulator_proof: Vec<B256>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_proof_target() {
+        let target: ProofTarget = serde_json::from_str(
+            r#"{"address":"0x0000000000000000000000000000000000000001","storageKeys":["0x01"]}"#,
+        )
+        .unwrap();
+        assert_eq!(target.address, Address::with_last_byte(1));
+        assert_eq!(target.storage_keys.len(), 1);
+
+        let target: ProofTarget =
+            serde_json::from_str(r#"{"address":"0x0000000000000000000000000000000000000001"}"#)
+                .unwrap();
+        assert!(target.storage_keys.is_empty());
+    }
+}
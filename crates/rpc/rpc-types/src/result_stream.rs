@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Item of the `reth_subscribeResultStream` subscription.
+///
+/// The items of the array result of the streamed call, in the order of the result. The last chunk
+/// of a stream is marked as `done`, a stream that ends without it failed.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResultStreamChunk {
+    /// The next items of the result.
+    pub items: Vec<Value>,
+    /// Whether this is the last chunk of the result.
+    pub done: bool,
+}
@@ -0,0 +1,60 @@
+use alloy_rpc_types::pubsub::SubscriptionKind;
+use serde::{Deserialize, Serialize};
+
+/// Subscription kind accepted by `eth_subscribe`.
+///
+/// Extends the standard [`SubscriptionKind`]s with subscriptions to the finality markers reported
+/// by the consensus layer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EthSubscriptionKind {
+    /// One of the standard subscription kinds.
+    Standard(SubscriptionKind),
+    /// A subscription to the safe or finalized block.
+    Finality(FinalitySubscriptionKind),
+}
+
+impl From<SubscriptionKind> for EthSubscriptionKind {
+    fn from(kind: SubscriptionKind) -> Self {
+        Self::Standard(kind)
+    }
+}
+
+impl From<FinalitySubscriptionKind> for EthSubscriptionKind {
+    fn from(kind: FinalitySubscriptionKind) -> Self {
+        Self::Finality(kind)
+    }
+}
+
+/// Subscriptions to the finality markers of the chain.
+///
+/// Each subscription yields the header of the marked block whenever the consensus layer moves the
+/// marker.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FinalitySubscriptionKind {
+    /// Yields the header of every new finalized block.
+    FinalizedHeads,
+    /// Yields the header of every new safe block.
+    SafeHeads,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_eth_subscription_kind() {
+        let kind: EthSubscriptionKind = serde_json::from_str(r#""newHeads""#).unwrap();
+        assert_eq!(kind, SubscriptionKind::NewHeads.into());
+
+        let kind: EthSubscriptionKind = serde_json::from_str(r#""finalizedHeads""#).unwrap();
+        assert_eq!(kind, FinalitySubscriptionKind::FinalizedHeads.into());
+        assert_eq!(serde_json::to_string(&kind).unwrap(), r#""finalizedHeads""#);
+
+        let kind: EthSubscriptionKind = serde_json::from_str(r#""safeHeads""#).unwrap();
+        assert_eq!(kind, FinalitySubscriptionKind::SafeHeads.into());
+
+        assert!(serde_json::from_str::<EthSubscriptionKind>(r#""unknown""#).is_err());
+    }
+}
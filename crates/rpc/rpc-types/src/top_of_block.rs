@@ -0,0 +1,46 @@
+use alloy_primitives::{Bytes, B256, U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Request type of `builder_submitTopOfBlockBid`.
+///
+/// A bid for the top of the block with the given parent and timestamp.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopOfBlockBidRequest {
+    /// The hash of the parent of the block.
+    pub parent_hash: B256,
+    /// The timestamp of the block.
+    pub timestamp: U64,
+    /// The EIP-2718 encoded signed transactions of the segment, in execution order.
+    pub transactions: Vec<Bytes>,
+    /// The minimum increase of the fee recipient's balance the segment pays.
+    pub value: U256,
+    /// The 65 byte `r || s || v` signature of the bidder over the bid, as returned by
+    /// `personal_sign` for the message with the 32 byte hash of the bid.
+    ///
+    /// The hash of the bid is the keccak256 hash of the parent hash, the big endian 8 byte
+    /// timestamp, the big endian 32 byte value and the hashes of the transactions.
+    pub signature: Bytes,
+}
+
+/// Response type of `builder_submitTopOfBlockBid`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopOfBlockBidResponse {
+    /// The hash identifying the bid.
+    pub bid_hash: B256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_top_of_block_bid_request() {
+        let json = r#"{"parentHash":"0x0000000000000000000000000000000000000000000000000000000000000001","timestamp":"0x65","transactions":["0x02"],"value":"0x3e8","signature":"0x01"}"#;
+        let request: TopOfBlockBidRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.timestamp, U64::from(101));
+        assert_eq!(request.value, U256::from(1000));
+        assert_eq!(serde_json::to_string(&request).unwrap(), json);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::engine::PayloadId;
+use alloy_primitives::{BlockHash, U64};
+use serde::{Deserialize, Serialize};
+
+/// Response type of `reth_getTransactionStatus`.
+///
+/// How far a transaction is on its way into the canonical chain, variants are ordered from the
+/// least to the most advanced status.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum TransactionStatus {
+    /// The transaction is not known to the node.
+    Unknown,
+    /// The transaction is in the pool but can not be included in the next block yet, e.g.
+    /// because of a nonce gap or a too low fee cap.
+    Queued,
+    /// The transaction is in the pool and can be included in the next block.
+    Pending,
+    /// The transaction is included in a payload built by at least one active payload job.
+    #[serde(rename_all = "camelCase")]
+    Candidate {
+        /// The ids of the payloads that include the transaction.
+        payload_ids: Vec<PayloadId>,
+    },
+    /// The transaction is included in a valid block that is not persisted, e.g. on a side chain.
+    #[serde(rename_all = "camelCase")]
+    Unpersisted {
+        /// The hash of the block.
+        block_hash: BlockHash,
+        /// The number of the block.
+        block_number: U64,
+    },
+    /// The transaction is included in a canonical block.
+    #[serde(rename_all = "camelCase")]
+    Canonical {
+        /// The hash of the block.
+        block_hash: BlockHash,
+        /// The number of the block.
+        block_number: U64,
+        /// The number of canonical blocks on top of the block.
+        depth: U64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_transaction_status() {
+        let status = TransactionStatus::Canonical {
+            block_hash: BlockHash::with_last_byte(1),
+            block_number: U64::from(10),
+            depth: U64::from(2),
+        };
+        let json = serde_json::to_string(&status).unwrap();
+        assert_eq!(
+            json,
+            r#"{"status":"canonical","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","blockNumber":"0xa","depth":"0x2"}"#
+        );
+        assert_eq!(serde_json::from_str::<TransactionStatus>(&json).unwrap(), status);
+
+        let json = serde_json::to_string(&TransactionStatus::Unknown).unwrap();
+        assert_eq!(json, r#"{"status":"unknown"}"#);
+    }
+}
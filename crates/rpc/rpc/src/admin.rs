@@ -1,10 +1,12 @@
-use crate::result::ToRpcResult;
+use crate::{layers::ApiKeys, result::ToRpcResult};
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use reth_network_api::{NetworkInfo, PeerKind, Peers};
 use reth_primitives::NodeRecord;
-use reth_rpc_api::AdminApiServer;
-use reth_rpc_types::{NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo};
+use reth_rpc_api::{AdminApiKeysApiServer, AdminApiServer};
+use reth_rpc_types::{
+    ApiKey, NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
+};
 
 /// `admin` API implementation.
 ///
@@ -101,3 +103,35 @@ impl<N> std::fmt::Debug for AdminApi<N> {
         f.debug_struct("AdminApi").finish_non_exhaustive()
     }
 }
+
+/// `admin` API implementation for managing the API keys of the RPC server.
+///
+/// Changes apply to the running servers right away, but are not persisted to the config file.
+#[derive(Debug)]
+pub struct AdminApiKeysApi {
+    keys: ApiKeys,
+}
+
+impl AdminApiKeysApi {
+    /// Creates a new instance of `AdminApiKeysApi`.
+    pub fn new(keys: ApiKeys) -> Self {
+        Self { keys }
+    }
+}
+
+impl AdminApiKeysApiServer for AdminApiKeysApi {
+    /// Handler for `admin_apiKeys`
+    fn api_keys(&self) -> RpcResult<Vec<ApiKey>> {
+        Ok(self.keys.list())
+    }
+
+    /// Handler for `admin_addApiKey`
+    fn add_api_key(&self, key: ApiKey) -> RpcResult<bool> {
+        Ok(self.keys.insert(key))
+    }
+
+    /// Handler for `admin_removeApiKey`
+    fn remove_api_key(&self, key: String) -> RpcResult<bool> {
+        Ok(self.keys.remove(&key))
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{eth::utils::recover_raw_transaction, result::invalid_params_rpc_err};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use reth_payload_builder::{TopOfBlockAuction, TopOfBlockBid};
+use reth_primitives::{Signature, U256};
+use reth_rpc_api::BuilderApiServer;
+use reth_rpc_types::{TopOfBlockBidRequest, TopOfBlockBidResponse};
+use std::sync::Arc;
+use tracing::trace;
+
+/// `builder` API implementation.
+///
+/// Submits bids for the top of built blocks to the auction the payload builder looks them up in,
+/// which only accepts bids signed by one of its bidders.
+#[derive(Debug, Clone)]
+pub struct BuilderApi {
+    /// The auction of the top of the built blocks.
+    auction: Arc<TopOfBlockAuction>,
+}
+
+impl BuilderApi {
+    /// Creates a new instance of the [BuilderApi].
+    pub fn new(auction: Arc<TopOfBlockAuction>) -> Self {
+        Self { auction }
+    }
+}
+
+#[async_trait]
+impl BuilderApiServer for BuilderApi {
+    /// Handler for `builder_submitTopOfBlockBid`
+    async fn submit_top_of_block_bid(
+        &self,
+        request: TopOfBlockBidRequest,
+    ) -> RpcResult<TopOfBlockBidResponse> {
+        trace!(target: "rpc::builder", parent_hash = %request.parent_hash, timestamp = %request.timestamp, "Serving builder_submitTopOfBlockBid");
+        let transactions = request
+            .transactions
+            .into_iter()
+            .map(|tx| recover_raw_transaction(tx).map(|tx| tx.into_ecrecovered_transaction()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let signature = decode_signature(&request.signature)
+            .ok_or_else(|| invalid_params_rpc_err("invalid bid signature"))?;
+        let bid = TopOfBlockBid {
+            parent_hash: request.parent_hash,
+            timestamp: request.timestamp.to(),
+            transactions,
+            value: request.value,
+            signature,
+        };
+        let bid_hash =
+            self.auction.submit(bid).map_err(|err| invalid_params_rpc_err(err.to_string()))?;
+        Ok(TopOfBlockBidResponse { bid_hash })
+    }
+}
+
+/// Decodes a 65 byte `r || s || v` signature, with `v` either the parity or `27 + parity`.
+fn decode_signature(bytes: &[u8]) -> Option<Signature> {
+    let bytes: &[u8; 65] = bytes.try_into().ok()?;
+    let odd_y_parity = match bytes[64] {
+        0 | 27 => false,
+        1 | 28 => true,
+        _ => return None,
+    };
+    Some(Signature {
+        r: U256::from_be_slice(&bytes[..32]),
+        s: U256::from_be_slice(&bytes[32..64]),
+        odd_y_parity,
+    })
+}
@@ -8,10 +8,12 @@ use crate::{
         EthTransactions, TransactionSource,
     },
     result::{internal_rpc_err, ToRpcResult},
-    BlockingTaskGuard, EthApiSpec,
+    tracing_limits::TraceLimits,
+    BlockingTaskGuard, EthApiSpec, TracingLimits,
 };
 use alloy_rlp::{Decodable, Encodable};
 use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
 use jsonrpsee::core::RpcResult;
 use reth_primitives::{
     revm::env::tx_env_with_recovered,
@@ -38,7 +40,11 @@ use reth_rpc_types::{
 use revm::{db::CacheDB, primitives::Env};
 
 use std::sync::Arc;
-use tokio::sync::{AcquireError, OwnedSemaphorePermit};
+use tokio::sync::{mpsc, AcquireError, OwnedSemaphorePermit};
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Number of traces that are buffered for a streaming [DebugApi::debug_trace_block_stream] call.
+const TRACE_STREAM_BUFFER: usize = 16;
 
 /// `debug` API implementation.
 ///
@@ -51,8 +57,14 @@ pub struct DebugApi<Provider, Eth> {
 
 impl<Provider, Eth> DebugApi<Provider, Eth> {
     /// Create a new instance of the [DebugApi]
-    pub fn new(provider: Provider, eth: Eth, blocking_task_guard: BlockingTaskGuard) -> Self {
-        let inner = Arc::new(DebugApiInner { provider, eth_api: eth, blocking_task_guard });
+    pub fn new(
+        provider: Provider,
+        eth: Eth,
+        blocking_task_guard: BlockingTaskGuard,
+        tracing_limits: TracingLimits,
+    ) -> Self {
+        let inner =
+            Arc::new(DebugApiInner { provider, eth_api: eth, blocking_task_guard, tracing_limits });
         Self { inner }
     }
 }
@@ -78,49 +90,78 @@ where
         block_env: BlockEnv,
         opts: GethDebugTracingOptions,
     ) -> EthResult<Vec<TraceResult>> {
+        let results = Vec::with_capacity(transactions.len());
+        self.replay_block_with(
+            at,
+            transactions,
+            cfg,
+            block_env,
+            opts,
+            results,
+            |results, result| {
+                results.push(result);
+                true
+            },
+        )
+        .await
+    }
+
+    /// Replays all transactions of the block on top of the state at the given block, and passes
+    /// the trace of each transaction to `f` as soon as it's available.
+    ///
+    /// Replaying stops early if `f` returns false.
+    #[allow(clippy::too_many_arguments)]
+    async fn replay_block_with<T, F>(
+        &self,
+        at: BlockId,
+        transactions: Vec<TransactionSignedEcRecovered>,
+        cfg: CfgEnv,
+        block_env: BlockEnv,
+        opts: GethDebugTracingOptions,
+        mut acc: T,
+        mut f: F,
+    ) -> EthResult<T>
+    where
+        T: Send + 'static,
+        F: FnMut(&mut T, TraceResult) -> bool + Send + 'static,
+    {
+        let limits = self.inner.tracing_limits.resolve(&opts)?;
+
         // replay all transactions of the block
         let this = self.clone();
-        self.inner
-            .eth_api
-            .spawn_with_state_at_block(at, move |state| {
-                let block_hash = at.as_block_hash();
-                let mut results = Vec::with_capacity(transactions.len());
-                let mut db = CacheDB::new(StateProviderDatabase::new(state));
-                let mut transactions = transactions.into_iter().enumerate().peekable();
-                while let Some((index, tx)) = transactions.next() {
-                    let tx_hash = tx.hash;
-                    let tx = tx_env_with_recovered(&tx);
-                    let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
-                    let (result, state_changes) = this
-                        .trace_transaction(
-                            opts.clone(),
-                            env,
-                            &mut db,
-                            Some(TransactionContext {
-                                block_hash,
-                                tx_hash: Some(tx_hash),
-                                tx_index: Some(index),
-                            }),
-                        )
-                        .map_err(|err| {
-                            results.push(TraceResult::Error {
-                                error: err.to_string(),
-                                tx_hash: Some(tx_hash),
-                            });
-                            err
-                        })?;
-
-                    results.push(TraceResult::Success { result, tx_hash: Some(tx_hash) });
-                    if transactions.peek().is_some() {
-                        // need to apply the state changes of this transaction before executing the
-                        // next transaction
-                        db.commit(state_changes)
-                    }
+        let replay = self.inner.eth_api.spawn_with_state_at_block(at, move |state| {
+            let block_hash = at.as_block_hash();
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+            let mut transactions = transactions.into_iter().enumerate().peekable();
+            while let Some((index, tx)) = transactions.next() {
+                let tx_hash = tx.hash;
+                let tx = tx_env_with_recovered(&tx);
+                let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
+                let (result, state_changes) = this.trace_transaction(
+                    opts.clone(),
+                    env,
+                    &mut db,
+                    Some(TransactionContext {
+                        block_hash,
+                        tx_hash: Some(tx_hash),
+                        tx_index: Some(index),
+                    }),
+                    limits,
+                )?;
+
+                if !f(&mut acc, TraceResult::Success { result, tx_hash: Some(tx_hash) }) {
+                    break
+                }
+                if transactions.peek().is_some() {
+                    // need to apply the state changes of this transaction before executing the
+                    // next transaction
+                    db.commit(state_changes)
                 }
+            }
 
-                Ok(results)
-            })
-            .await
+            Ok(acc)
+        });
+        limits.run(replay).await
     }
 
     /// Replays the given block and returns the trace of each transaction.
@@ -171,6 +212,53 @@ where
         block_id: BlockId,
         opts: GethDebugTracingOptions,
     ) -> EthResult<Vec<TraceResult>> {
+        let (state_at, transactions, cfg, block_env) = self.block_to_replay(block_id).await?;
+        self.trace_block_with(state_at, transactions, cfg, block_env, opts).await
+    }
+
+    /// Replays a block and returns a stream of the trace of each transaction.
+    ///
+    /// Same as [Self::debug_trace_block], but the traces are yielded as soon as they're available,
+    /// while the block is still being replayed. Replaying stops if the stream is dropped.
+    pub async fn debug_trace_block_stream(
+        &self,
+        block_id: BlockId,
+        opts: GethDebugTracingOptions,
+    ) -> EthResult<BoxStream<'static, EthResult<TraceResult>>> {
+        let (state_at, transactions, cfg, block_env) = self.block_to_replay(block_id).await?;
+
+        // the permit is held until the block is replayed
+        let permit = self.acquire_trace_permit().await;
+        let (tx, rx) = mpsc::channel(TRACE_STREAM_BUFFER);
+        let this = self.clone();
+        tokio::task::spawn(async move {
+            let _permit = permit;
+            let errors = tx.clone();
+            let res = this
+                .replay_block_with(
+                    state_at,
+                    transactions,
+                    cfg,
+                    block_env,
+                    opts,
+                    tx,
+                    |tx, result| tx.blocking_send(Ok(result)).is_ok(),
+                )
+                .await;
+            if let Err(err) = res {
+                let _ = errors.send(Err(err)).await;
+            }
+        });
+
+        Ok(ReceiverStream::new(rx).boxed())
+    }
+
+    /// Returns the block to replay on top of the state of its parent, with its recovered
+    /// transactions and its evm env.
+    async fn block_to_replay(
+        &self,
+        block_id: BlockId,
+    ) -> EthResult<(BlockId, Vec<TransactionSignedEcRecovered>, CfgEnv, BlockEnv)> {
         let block_hash = self
             .inner
             .provider
@@ -187,14 +275,7 @@ where
         // its parent block's state
         let state_at = block.parent_hash;
 
-        self.trace_block_with(
-            state_at.into(),
-            block.into_transactions_ecrecovered().collect(),
-            cfg,
-            block_env,
-            opts,
-        )
-        .await
+        Ok((state_at.into(), block.into_transactions_ecrecovered().collect(), cfg, block_env))
     }
 
     /// Trace the transaction according to the provided options.
@@ -216,38 +297,39 @@ where
         let state_at: BlockId = block.parent_hash.into();
         let block_hash = block.hash;
         let block_txs = block.body;
+        let limits = self.inner.tracing_limits.resolve(&opts)?;
 
         let this = self.clone();
-        self.inner
-            .eth_api
-            .spawn_with_state_at_block(state_at, move |state| {
-                // configure env for the target transaction
-                let tx = transaction.into_recovered();
-
-                let mut db = CacheDB::new(StateProviderDatabase::new(state));
-                // replay all transactions prior to the targeted transaction
-                let index = replay_transactions_until(
-                    &mut db,
-                    cfg.clone(),
-                    block_env.clone(),
-                    block_txs,
-                    tx.hash,
-                )?;
-
-                let env = Env { cfg, block: block_env, tx: tx_env_with_recovered(&tx) };
-                this.trace_transaction(
-                    opts,
-                    env,
-                    &mut db,
-                    Some(TransactionContext {
-                        block_hash: Some(block_hash),
-                        tx_index: Some(index),
-                        tx_hash: Some(tx.hash),
-                    }),
-                )
-                .map(|(trace, _)| trace)
-            })
-            .await
+        let trace = self.inner.eth_api.spawn_with_state_at_block(state_at, move |state| {
+            // configure env for the target transaction
+            let tx = transaction.into_recovered();
+
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+            // replay all transactions prior to the targeted transaction
+            let index = replay_transactions_until(
+                this.inner.eth_api.evm_config(),
+                &mut db,
+                cfg.clone(),
+                block_env.clone(),
+                block_txs,
+                tx.hash,
+            )?;
+
+            let env = Env { cfg, block: block_env, tx: tx_env_with_recovered(&tx) };
+            this.trace_transaction(
+                opts,
+                env,
+                &mut db,
+                Some(TransactionContext {
+                    block_hash: Some(block_hash),
+                    tx_index: Some(index),
+                    tx_hash: Some(tx.hash),
+                }),
+                limits,
+            )
+            .map(|(trace, _)| trace)
+        });
+        limits.run(trace).await
     }
 
     /// The debug_traceCall method lets you run an `eth_call` within the context of the given block
@@ -262,20 +344,25 @@ where
         let GethDebugTracingCallOptions { tracing_options, state_overrides, block_overrides } =
             opts;
         let overrides = EvmOverrides::new(state_overrides, block_overrides.map(Box::new));
+        let limits = self.inner.tracing_limits.resolve(&tracing_options)?;
         let GethDebugTracingOptions { config, tracer, tracer_config, .. } = tracing_options;
+        let evm_config = self.inner.eth_api.evm_config().clone();
 
         if let Some(tracer) = tracer {
             return match tracer {
                 GethDebugTracerType::BuiltInTracer(tracer) => match tracer {
                     GethDebugBuiltInTracerType::FourByteTracer => {
                         let mut inspector = FourByteInspector::default();
-                        let inspector = self
-                            .inner
-                            .eth_api
-                            .spawn_with_call_at(call, at, overrides, move |db, env| {
-                                inspect(db, env, &mut inspector)?;
-                                Ok(inspector)
-                            })
+                        let inspector = limits
+                            .run(self.inner.eth_api.spawn_with_call_at(
+                                call,
+                                at,
+                                overrides,
+                                move |db, env| {
+                                    inspect(&evm_config, db, env, &mut inspector)?;
+                                    Ok(inspector)
+                                },
+                            ))
                             .await?;
                         return Ok(FourByteFrame::from(inspector).into())
                     }
@@ -289,17 +376,21 @@ where
                                 .set_record_logs(call_config.with_log.unwrap_or_default()),
                         );
 
-                        let frame = self
-                            .inner
-                            .eth_api
-                            .spawn_with_call_at(call, at, overrides, move |db, env| {
-                                let (res, _) = inspect(db, env, &mut inspector)?;
-                                let frame = inspector
-                                    .into_geth_builder()
-                                    .geth_call_traces(call_config, res.result.gas_used());
-                                Ok(frame.into())
-                            })
+                        let mut frame = limits
+                            .run(self.inner.eth_api.spawn_with_call_at(
+                                call,
+                                at,
+                                overrides,
+                                move |db, env| {
+                                    let (res, _) = inspect(&evm_config, db, env, &mut inspector)?;
+                                    let frame = inspector
+                                        .into_geth_builder()
+                                        .geth_call_traces(call_config, res.result.gas_used());
+                                    Ok(frame.into())
+                                },
+                            ))
                             .await?;
+                        limits.apply(&mut frame)?;
                         return Ok(frame)
                     }
                     GethDebugBuiltInTracerType::PreStateTracer => {
@@ -313,18 +404,25 @@ where
                                 .set_steps_and_state_diffs(prestate_config.is_default_mode()),
                         );
 
-                        let frame =
-                            self.inner
-                                .eth_api
-                                .spawn_with_call_at(call, at, overrides, move |db, env| {
-                                    let (res, _, db) =
-                                        inspect_and_return_db(db, env, &mut inspector)?;
+                        let frame = limits
+                            .run(self.inner.eth_api.spawn_with_call_at(
+                                call,
+                                at,
+                                overrides,
+                                move |db, env| {
+                                    let (res, _, db) = inspect_and_return_db(
+                                        &evm_config,
+                                        db,
+                                        env,
+                                        &mut inspector,
+                                    )?;
                                     let frame = inspector
                                         .into_geth_builder()
                                         .geth_prestate_traces(&res, prestate_config, &db)?;
                                     Ok(frame)
-                                })
-                                .await?;
+                                },
+                            ))
+                            .await?;
                         return Ok(frame.into())
                     }
                     GethDebugBuiltInTracerType::NoopTracer => Ok(NoopFrame::default().into()),
@@ -334,15 +432,22 @@ where
 
                     let (_, _, at) = self.inner.eth_api.evm_env_at(at).await?;
 
-                    let res = self
-                        .inner
-                        .eth_api
-                        .spawn_with_call_at(call, at, overrides, move |db, env| {
-                            let mut inspector = JsInspector::new(code, config)?;
-                            let (res, _, db) =
-                                inspect_and_return_db(db, env.clone(), &mut inspector)?;
-                            Ok(inspector.json_result(res, &env, &db)?)
-                        })
+                    let res = limits
+                        .run(self.inner.eth_api.spawn_with_call_at(
+                            call,
+                            at,
+                            overrides,
+                            move |db, env| {
+                                let mut inspector = JsInspector::new(code, config)?;
+                                let (res, _, db) = inspect_and_return_db(
+                                    &evm_config,
+                                    db,
+                                    env.clone(),
+                                    &mut inspector,
+                                )?;
+                                Ok(inspector.json_result(res, &env, &db)?)
+                            },
+                        ))
                         .await?;
 
                     Ok(GethTrace::JS(res))
@@ -355,19 +460,19 @@ where
 
         let mut inspector = TracingInspector::new(inspector_config);
 
-        let (res, inspector) = self
-            .inner
-            .eth_api
-            .spawn_with_call_at(call, at, overrides, move |db, env| {
-                let (res, _) = inspect(db, env, &mut inspector)?;
+        let (res, inspector) = limits
+            .run(self.inner.eth_api.spawn_with_call_at(call, at, overrides, move |db, env| {
+                let (res, _) = inspect(&evm_config, db, env, &mut inspector)?;
                 Ok((res, inspector))
-            })
+            }))
             .await?;
         let gas_used = res.result.gas_used();
         let return_value = res.result.into_output().unwrap_or_default();
-        let frame = inspector.into_geth_builder().geth_traces(gas_used, return_value, config);
+        let mut frame =
+            inspector.into_geth_builder().geth_traces(gas_used, return_value, config).into();
+        limits.apply(&mut frame)?;
 
-        Ok(frame.into())
+        Ok(frame)
     }
 
     /// The debug_traceCallMany method lets you run an `eth_callMany` within the context of the
@@ -395,6 +500,7 @@ where
         let block = block.ok_or_else(|| EthApiError::UnknownBlockNumber)?;
         let GethDebugTracingCallOptions { tracing_options, mut state_overrides, .. } = opts;
         let gas_limit = self.inner.eth_api.call_gas_limit();
+        let limits = self.inner.tracing_limits.resolve(&tracing_options)?;
 
         // we're essentially replaying the transactions in the block here, hence we need the state
         // that points to the beginning of the block, which is the state at the parent block
@@ -409,66 +515,69 @@ where
         }
 
         let this = self.clone();
-        self.inner
-            .eth_api
-            .spawn_with_state_at_block(at.into(), move |state| {
-                // the outer vec for the bundles
-                let mut all_bundles = Vec::with_capacity(bundles.len());
-                let mut db = CacheDB::new(StateProviderDatabase::new(state));
-
-                if replay_block_txs {
-                    // only need to replay the transactions in the block if not all transactions are
-                    // to be replayed
-                    let transactions = block.into_transactions_ecrecovered().take(num_txs);
-
-                    // Execute all transactions until index
-                    for tx in transactions {
-                        let tx = tx_env_with_recovered(&tx);
-                        let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
-                        let (res, _) = transact(&mut db, env)?;
-                        db.commit(res.state);
-                    }
+        let traces = self.inner.eth_api.spawn_with_state_at_block(at.into(), move |state| {
+            // the outer vec for the bundles
+            let mut all_bundles = Vec::with_capacity(bundles.len());
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+
+            if replay_block_txs {
+                // only need to replay the transactions in the block if not all transactions are
+                // to be replayed
+                let transactions = block.into_transactions_ecrecovered().take(num_txs);
+
+                // Execute all transactions until index
+                for tx in transactions {
+                    let tx = tx_env_with_recovered(&tx);
+                    let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
+                    let (res, _) = transact(this.inner.eth_api.evm_config(), &mut db, env)?;
+                    db.commit(res.state);
                 }
+            }
 
-                // Trace all bundles
-                let mut bundles = bundles.into_iter().peekable();
-                while let Some(bundle) = bundles.next() {
-                    let mut results = Vec::with_capacity(bundle.transactions.len());
-                    let Bundle { transactions, block_override } = bundle;
-
-                    let block_overrides = block_override.map(Box::new);
-
-                    let mut transactions = transactions.into_iter().peekable();
-                    while let Some(tx) = transactions.next() {
-                        // apply state overrides only once, before the first transaction
-                        let state_overrides = state_overrides.take();
-                        let overrides = EvmOverrides::new(state_overrides, block_overrides.clone());
-
-                        let env = prepare_call_env(
-                            cfg.clone(),
-                            block_env.clone(),
-                            tx,
-                            gas_limit,
-                            &mut db,
-                            overrides,
-                        )?;
+            // Trace all bundles
+            let mut bundles = bundles.into_iter().peekable();
+            while let Some(bundle) = bundles.next() {
+                let mut results = Vec::with_capacity(bundle.transactions.len());
+                let Bundle { transactions, block_override } = bundle;
+
+                let block_overrides = block_override.map(Box::new);
+
+                let mut transactions = transactions.into_iter().peekable();
+                while let Some(tx) = transactions.next() {
+                    // apply state overrides only once, before the first transaction
+                    let state_overrides = state_overrides.take();
+                    let overrides = EvmOverrides::new(state_overrides, block_overrides.clone());
+
+                    let env = prepare_call_env(
+                        cfg.clone(),
+                        block_env.clone(),
+                        tx,
+                        gas_limit,
+                        &mut db,
+                        overrides,
+                    )?;
 
-                        let (trace, state) =
-                            this.trace_transaction(tracing_options.clone(), env, &mut db, None)?;
+                    let (trace, state) = this.trace_transaction(
+                        tracing_options.clone(),
+                        env,
+                        &mut db,
+                        None,
+                        limits,
+                    )?;
 
-                        // If there is more transactions, commit the database
-                        // If there is no transactions, but more bundles, commit to the database too
-                        if transactions.peek().is_some() || bundles.peek().is_some() {
-                            db.commit(state);
-                        }
-                        results.push(trace);
+                    // If there is more transactions, commit the database
+                    // If there is no transactions, but more bundles, commit to the database too
+                    if transactions.peek().is_some() || bundles.peek().is_some() {
+                        db.commit(state);
                     }
-
-                    all_bundles.push(results);
+                    results.push(trace);
                 }
-                Ok(all_bundles)
-            })
-            .await
+
+                all_bundles.push(results);
+            }
+            Ok(all_bundles)
+        });
+        limits.run(traces).await
     }
 
     /// Executes the configured transaction with the environment on the given database.
@@ -477,6 +586,8 @@ where
     ///
     /// Note: this does not apply any state overrides if they're configured in the `opts`.
     ///
+    /// Fails without executing the transaction if the timeout of the call elapsed.
+    ///
     /// Caution: this is blocking and should be performed on a blocking task.
     fn trace_transaction(
         &self,
@@ -484,15 +595,18 @@ where
         env: Env,
         db: &mut SubState<StateProviderBox>,
         transaction_context: Option<TransactionContext>,
+        limits: TraceLimits,
     ) -> EthResult<(GethTrace, revm_primitives::State)> {
+        limits.ensure_time_left()?;
         let GethDebugTracingOptions { config, tracer, tracer_config, .. } = opts;
+        let evm_config = self.inner.eth_api.evm_config();
 
         if let Some(tracer) = tracer {
             return match tracer {
                 GethDebugTracerType::BuiltInTracer(tracer) => match tracer {
                     GethDebugBuiltInTracerType::FourByteTracer => {
                         let mut inspector = FourByteInspector::default();
-                        let (res, _) = inspect(db, env, &mut inspector)?;
+                        let (res, _) = inspect(evm_config, db, env, &mut inspector)?;
                         return Ok((FourByteFrame::from(inspector).into(), res.state))
                     }
                     GethDebugBuiltInTracerType::CallTracer => {
@@ -505,13 +619,15 @@ where
                                 .set_record_logs(call_config.with_log.unwrap_or_default()),
                         );
 
-                        let (res, _) = inspect(db, env, &mut inspector)?;
+                        let (res, _) = inspect(evm_config, db, env, &mut inspector)?;
 
-                        let frame = inspector
+                        let mut frame = inspector
                             .into_geth_builder()
-                            .geth_call_traces(call_config, res.result.gas_used());
+                            .geth_call_traces(call_config, res.result.gas_used())
+                            .into();
+                        limits.apply(&mut frame)?;
 
-                        return Ok((frame.into(), res.state))
+                        return Ok((frame, res.state))
                     }
                     GethDebugBuiltInTracerType::PreStateTracer => {
                         let prestate_config = tracer_config
@@ -524,7 +640,7 @@ where
                                 // which we need to record steps and statediff
                                 .set_steps_and_state_diffs(prestate_config.is_default_mode()),
                         );
-                        let (res, _) = inspect(&mut *db, env, &mut inspector)?;
+                        let (res, _) = inspect(evm_config, &mut *db, env, &mut inspector)?;
 
                         let frame = inspector.into_geth_builder().geth_prestate_traces(
                             &res,
@@ -545,7 +661,8 @@ where
                         config,
                         transaction_context.unwrap_or_default(),
                     )?;
-                    let (res, env, db) = inspect_and_return_db(db, env, &mut inspector)?;
+                    let (res, env, db) =
+                        inspect_and_return_db(evm_config, db, env, &mut inspector)?;
 
                     let state = res.state.clone();
                     let result = inspector.json_result(res, &env, db)?;
@@ -559,12 +676,14 @@ where
 
         let mut inspector = TracingInspector::new(inspector_config);
 
-        let (res, _) = inspect(db, env, &mut inspector)?;
+        let (res, _) = inspect(evm_config, db, env, &mut inspector)?;
         let gas_used = res.result.gas_used();
         let return_value = res.result.into_output().unwrap_or_default();
-        let frame = inspector.into_geth_builder().geth_traces(gas_used, return_value, config);
+        let mut frame =
+            inspector.into_geth_builder().geth_traces(gas_used, return_value, config).into();
+        limits.apply(&mut frame)?;
 
-        Ok((frame.into(), res.state))
+        Ok((frame, res.state))
     }
 }
 
@@ -958,4 +1077,6 @@ struct DebugApiInner<Provider, Eth> {
     eth_api: Eth,
     // restrict the number of concurrent calls to blocking calls
     blocking_task_guard: BlockingTaskGuard,
+    /// Limits of the tracing calls.
+    tracing_limits: TracingLimits,
 }
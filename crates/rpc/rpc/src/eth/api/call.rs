@@ -2,24 +2,30 @@
 
 use crate::{
     eth::{
+        api::estimate_gas_cache::EstimateGasKey,
         error::{ensure_success, EthApiError, EthResult, RevertError, RpcInvalidTransactionError},
         revm_utils::{
             apply_state_overrides, build_call_evm_env, caller_gas_allowance,
             cap_tx_gas_limit_with_caller_allowance, get_precompiles, inspect, prepare_call_env,
             transact, EvmOverrides,
         },
+        shared_reads::SharedReads,
         EthTransactions,
     },
     EthApi,
 };
+use rayon::prelude::*;
 use reth_network_api::NetworkInfo;
-use reth_primitives::{revm::env::tx_env_with_recovered, BlockId, BlockNumberOrTag, Bytes, U256};
+use reth_primitives::{
+    revm::env::tx_env_with_recovered, BlockId, BlockNumberOrTag, Bytes, KECCAK_EMPTY, U256,
+};
 use reth_provider::{
     BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, StateProvider, StateProviderFactory,
 };
-use reth_revm::{access_list::AccessListInspector, database::StateProviderDatabase};
+use reth_revm::{access_list::AccessListInspector, database::StateProviderDatabase, EvmConfig};
 use reth_rpc_types::{
-    state::StateOverride, AccessListWithGasUsed, Bundle, CallRequest, EthCallResponse, StateContext,
+    state::StateOverride, AccessListBatchItem, AccessListWithGasUsed, Bundle, CallRequest,
+    EthCallResponse, StateContext,
 };
 use reth_transaction_pool::TransactionPool;
 use revm::{
@@ -47,11 +53,35 @@ where
         at: BlockId,
         state_override: Option<StateOverride>,
     ) -> EthResult<U256> {
+        // estimates at the latest block are cached until the state they touched changes
+        let cache_key =
+            if state_override.is_none() && at == BlockId::Number(BlockNumberOrTag::Latest) {
+                EstimateGasKey::new(&request)
+            } else {
+                None
+            };
         let (cfg, block_env, at) = self.evm_env_at(at).await?;
+        let block_hash = match at {
+            BlockId::Hash(hash) => Some(hash.block_hash),
+            BlockId::Number(_) => None,
+        };
+
+        if let (Some(key), Some(block_hash)) = (&cache_key, block_hash) {
+            if let Some(gas) = self.estimate_gas_cache().get(key, block_hash) {
+                trace!(target: "rpc::eth::estimate", ?gas, %block_hash, "Using cached gas estimate");
+                return Ok(gas)
+            }
+        }
 
         self.on_blocking_task(|this| async move {
             let state = this.state_at(at)?;
-            this.estimate_gas_with(cfg, block_env, request, state, state_override)
+            let mut db = CacheDB::new(StateProviderDatabase::new(state));
+            let gas =
+                this.estimate_gas_with_db(cfg, block_env, request, &mut db, state_override)?;
+            if let (Some(key), Some(block_hash)) = (cache_key, block_hash) {
+                this.estimate_gas_cache().insert(key, block_hash, gas, &db);
+            }
+            Ok(gas)
         })
         .await
     }
@@ -112,6 +142,7 @@ where
             replay_block_txs = false;
         }
 
+        let evm_config = self.evm_config().clone();
         self.spawn_with_state_at_block(at.into(), move |state| {
             let mut results = Vec::with_capacity(transactions.len());
             let mut db = CacheDB::new(StateProviderDatabase::new(state));
@@ -123,7 +154,7 @@ where
                 for tx in transactions {
                     let tx = tx_env_with_recovered(&tx);
                     let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
-                    let (res, _) = transact(&mut db, env)?;
+                    let (res, _) = transact(&evm_config, &mut db, env)?;
                     db.commit(res.state);
                 }
             }
@@ -144,7 +175,7 @@ where
                     &mut db,
                     overrides,
                 )?;
-                let (res, _) = transact(&mut db, env)?;
+                let (res, _) = transact(&evm_config, &mut db, env)?;
 
                 match ensure_success(res.result) {
                     Ok(output) => {
@@ -171,7 +202,7 @@ where
     /// This will execute the [CallRequest] and find the best gas limit via binary search
     pub fn estimate_gas_with<S>(
         &self,
-        mut cfg: CfgEnv,
+        cfg: CfgEnv,
         block: BlockEnv,
         request: CallRequest,
         state: S,
@@ -179,6 +210,25 @@ where
     ) -> EthResult<U256>
     where
         S: StateProvider,
+    {
+        let mut db = CacheDB::new(StateProviderDatabase::new(state));
+        self.estimate_gas_with_db(cfg, block, request, &mut db, state_override)
+    }
+
+    /// Estimates the gas usage of the `request` on top of the given database.
+    ///
+    /// The database keeps all state loaded by the estimate.
+    fn estimate_gas_with_db<DB>(
+        &self,
+        mut cfg: CfgEnv,
+        block: BlockEnv,
+        request: CallRequest,
+        db: &mut CacheDB<DB>,
+        state_override: Option<StateOverride>,
+    ) -> EthResult<U256>
+    where
+        DB: DatabaseRef,
+        EthApiError: From<DB::Error>,
     {
         // Disabled because eth_estimateGas is sometimes used with eoa senders
         // See <htps://github.com/paradigmxyz/reth/issues/1959>
@@ -200,17 +250,17 @@ where
 
         // Configure the evm env
         let mut env = build_call_evm_env(cfg, block, request)?;
-        let mut db = CacheDB::new(StateProviderDatabase::new(state));
 
         if let Some(state_override) = state_override {
             // apply state overrides
-            apply_state_overrides(state_override, &mut db)?;
+            apply_state_overrides(state_override, db)?;
         }
         // if the request is a simple transfer we can optimize
         if env.tx.data.is_empty() {
             if let TransactTo::Call(to) = env.tx.transact_to {
-                if let Ok(code) = db.db.state().account_code(to) {
-                    let no_code_callee = code.map(|code| code.is_empty()).unwrap_or(true);
+                if let Ok(callee) = db.db.basic_ref(to) {
+                    let no_code_callee =
+                        callee.map_or(true, |callee| callee.code_hash == KECCAK_EMPTY);
                     if no_code_callee {
                         // simple transfer, check if caller has sufficient funds
                         let available_funds =
@@ -228,7 +278,7 @@ where
 
         // check funds of the sender
         if env.tx.gas_price > U256::ZERO {
-            let allowance = caller_gas_allowance(&mut db, &env.tx)?;
+            let allowance = caller_gas_allowance(&mut *db, &env.tx)?;
 
             if highest_gas_limit > allowance {
                 // cap the highest gas limit by max gas caller can afford with given gas price
@@ -243,7 +293,7 @@ where
         trace!(target: "rpc::eth::estimate", ?env, "Starting gas estimation");
 
         // transact with the highest __possible__ gas limit
-        let ethres = transact(&mut db, env.clone());
+        let ethres = transact(self.evm_config(), &mut *db, env.clone());
 
         // Exceptional case: init used too much gas, we need to increase the gas limit and try
         // again
@@ -252,7 +302,7 @@ where
             // if price or limit was included in the request then we can execute the request
             // again with the block's gas limit to check if revert is gas related or not
             if request_gas.is_some() || request_gas_price.is_some() {
-                return Err(map_out_of_gas_err(env_gas_limit, env, &mut db))
+                return Err(map_out_of_gas_err(self.evm_config(), env_gas_limit, env, &mut *db))
             }
         }
 
@@ -270,7 +320,7 @@ where
                 // if price or limit was included in the request then we can execute the request
                 // again with the block's gas limit to check if revert is gas related or not
                 return if request_gas.is_some() || request_gas_price.is_some() {
-                    Err(map_out_of_gas_err(env_gas_limit, env, &mut db))
+                    Err(map_out_of_gas_err(self.evm_config(), env_gas_limit, env, &mut *db))
                 } else {
                     // the transaction did revert
                     Err(RpcInvalidTransactionError::Revert(RevertError::new(output)).into())
@@ -299,7 +349,7 @@ where
         while (highest_gas_limit - lowest_gas_limit) > 1 {
             let mut env = env.clone();
             env.tx.gas_limit = mid_gas_limit;
-            let ethres = transact(&mut db, env);
+            let ethres = transact(self.evm_config(), &mut *db, env);
 
             // Exceptional case: init used too much gas, we need to increase the gas limit and try
             // again
@@ -363,13 +413,75 @@ where
 
     async fn create_access_list_with(
         &self,
-        mut request: CallRequest,
+        request: CallRequest,
         at: Option<BlockId>,
     ) -> EthResult<AccessListWithGasUsed> {
         let block_id = at.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
         let (cfg, block, at) = self.evm_env_at(block_id).await?;
         let state = self.state_at(at)?;
 
+        self.create_access_list_with_db(cfg, block, request, StateProviderDatabase::new(state))
+    }
+
+    /// Creates the access lists of independent `requests` at the [BlockId] or latest.
+    ///
+    /// The requests are simulated in parallel on the same state and share the state they read, a
+    /// failed request does not fail the others.
+    pub(crate) async fn create_access_list_batch_at(
+        &self,
+        requests: Vec<CallRequest>,
+        block_number: Option<BlockId>,
+    ) -> EthResult<Vec<AccessListBatchItem>> {
+        let block_id = block_number.unwrap_or(BlockId::Number(BlockNumberOrTag::Latest));
+        let (cfg, block, at) = self.evm_env_at(block_id).await?;
+        let state = self.state_at(at)?;
+
+        let this = self.clone();
+        self.inner
+            .blocking_task_pool
+            .spawn(move || {
+                let db = SharedReads::new(StateProviderDatabase::new(state));
+                requests
+                    .into_par_iter()
+                    .map(|request| {
+                        match this.create_access_list_with_db(
+                            cfg.clone(),
+                            block.clone(),
+                            request,
+                            db.clone(),
+                        ) {
+                            Ok(AccessListWithGasUsed { access_list, gas_used }) => {
+                                AccessListBatchItem {
+                                    access_list: Some(access_list),
+                                    gas_used: Some(gas_used),
+                                    error: None,
+                                }
+                            }
+                            Err(err) => AccessListBatchItem {
+                                access_list: None,
+                                gas_used: None,
+                                error: Some(err.to_string()),
+                            },
+                        }
+                    })
+                    .collect()
+            })
+            .await
+            .map_err(|_| EthApiError::InternalBlockingTaskError)
+    }
+
+    /// Creates the access list for the `request` on top of the given database.
+    fn create_access_list_with_db<DB>(
+        &self,
+        cfg: CfgEnv,
+        block: BlockEnv,
+        mut request: CallRequest,
+        db: DB,
+    ) -> EthResult<AccessListWithGasUsed>
+    where
+        DB: DatabaseRef,
+        EthApiError: From<DB::Error>,
+    {
         let mut env = build_call_evm_env(cfg, block, request.clone())?;
 
         // we want to disable this in eth_createAccessList, since this is common practice used by
@@ -381,7 +493,7 @@ where
         // <https://github.com/ethereum/go-ethereum/blob/8990c92aea01ca07801597b00c0d83d4e2d9b811/internal/ethapi/api.go#L1476-L1476>
         env.cfg.disable_base_fee = true;
 
-        let mut db = CacheDB::new(StateProviderDatabase::new(state));
+        let mut db = CacheDB::new(db);
 
         if request.gas.is_none() && env.tx.gas_price > U256::ZERO {
             // no gas limit was provided in the request, so we need to cap the request's gas limit
@@ -399,9 +511,9 @@ where
         // can consume the list since we're not using the request anymore
         let initial = request.access_list.take().unwrap_or_default();
 
-        let precompiles = get_precompiles(env.cfg.spec_id);
+        let precompiles = get_precompiles(self.evm_config(), env.cfg.spec_id);
         let mut inspector = AccessListInspector::new(initial, from, to, precompiles);
-        let (result, env) = inspect(&mut db, env, &mut inspector)?;
+        let (result, env) = inspect(self.evm_config(), &mut db, env, &mut inspector)?;
 
         match result.result {
             ExecutionResult::Halt { reason, .. } => Err(match reason {
@@ -418,7 +530,8 @@ where
 
         // calculate the gas used using the access list
         request.access_list = Some(access_list.clone());
-        let gas_used = self.estimate_gas_with(env.cfg, env.block, request, db.db.state(), None)?;
+        let gas_used =
+            self.estimate_gas_with_db(env.cfg, env.block, request, &mut CacheDB::new(db.db), None)?;
 
         Ok(AccessListWithGasUsed { access_list, gas_used })
     }
@@ -427,17 +540,19 @@ where
 /// Executes the requests again after an out of gas error to check if the error is gas related or
 /// not
 #[inline]
-fn map_out_of_gas_err<S>(
+fn map_out_of_gas_err<DB>(
+    evm_config: &EvmConfig,
     env_gas_limit: U256,
     mut env: Env,
-    mut db: &mut CacheDB<StateProviderDatabase<S>>,
+    mut db: &mut CacheDB<DB>,
 ) -> EthApiError
 where
-    S: StateProvider,
+    DB: DatabaseRef,
+    EthApiError: From<DB::Error>,
 {
     let req_gas_limit = env.tx.gas_limit;
     env.tx.gas_limit = env_gas_limit.try_into().unwrap_or(u64::MAX);
-    let (res, _) = match transact(&mut db, env) {
+    let (res, _) = match transact(evm_config, &mut db, env) {
         Ok(res) => res,
         Err(err) => return err,
     };
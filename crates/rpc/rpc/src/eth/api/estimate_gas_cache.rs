@@ -0,0 +1,255 @@
+//! Cache of `eth_estimateGas` results at the latest block.
+
+use futures::{Stream, StreamExt};
+use metrics::Counter;
+use reth_metrics::{metrics::Gauge, Metrics};
+use reth_primitives::{Address, Bytes, B256, U256, U64};
+use reth_provider::CanonStateNotification;
+use reth_rpc_types::CallRequest;
+use revm::db::CacheDB;
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tracing::trace;
+
+/// The maximum number of cached estimates.
+const MAX_CACHED_ESTIMATES: u32 = 4096;
+
+/// Caches the gas estimates of calls at the latest block, together with the state they touched.
+///
+/// Wallets estimate the same calls over and over again. An estimate stays valid for as long as the
+/// state it touched is unchanged, so instead of dropping all estimates with every new block, the
+/// cache follows the canonical state changes, see [estimate_gas_cache_new_blocks_task], and only
+/// drops the estimates whose accounts or storage slots changed.
+///
+/// Estimates only depend on the state they touched and the block environment, so calls that read
+/// e.g. the block number or timestamp can get an estimate of an earlier block.
+#[derive(Debug, Clone, Default)]
+pub struct EstimateGasCache {
+    inner: Arc<Mutex<EstimateGasCacheInner>>,
+    metrics: EstimateGasCacheMetrics,
+}
+
+impl EstimateGasCache {
+    /// Returns the cached estimate of the call, if it was estimated at the given block or the
+    /// state it touched is unchanged since.
+    pub(crate) fn get(&self, key: &EstimateGasKey, block_hash: B256) -> Option<U256> {
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        let gas = inner
+            .entries
+            .get(key)
+            .filter(|estimate| estimate.block_hash == block_hash)
+            .map(|estimate| estimate.gas);
+        if gas.is_some() {
+            self.metrics.hits.increment(1);
+        } else {
+            self.metrics.misses.increment(1);
+        }
+        gas
+    }
+
+    /// Caches the estimate of the call at the given block, with the state loaded by the estimate.
+    pub(crate) fn insert<DB>(
+        &self,
+        key: EstimateGasKey,
+        block_hash: B256,
+        gas: U256,
+        db: &CacheDB<DB>,
+    ) {
+        let estimate = CachedEstimate {
+            block_hash,
+            gas,
+            accounts: db.accounts.keys().copied().collect(),
+            storage: db
+                .accounts
+                .iter()
+                .flat_map(|(address, account)| {
+                    account.storage.keys().map(move |slot| (*address, *slot))
+                })
+                .collect(),
+        };
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        inner.entries.insert(key, estimate);
+        self.metrics.cached_count.set(inner.entries.len() as f64);
+    }
+
+    /// Moves the estimates of the parent of the committed chain to its tip, unless they touched
+    /// state the chain changed, and drops all other estimates.
+    fn on_new_state(&self, event: CanonStateNotification) {
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(committed) = event.committed() else { return };
+        if event.reverted().is_some() {
+            // the state of the new chain is unrelated to the state the estimates touched
+            self.metrics.invalidated.increment(inner.entries.len() as u64);
+            inner.entries.clear();
+            self.metrics.cached_count.set(0.0);
+            return
+        }
+
+        let mut changed_accounts = HashSet::new();
+        let mut changed_storage = HashSet::new();
+        for (address, account) in committed.state().bundle_accounts_iter() {
+            if account.info != account.original_info {
+                changed_accounts.insert(address);
+            }
+            changed_storage.extend(account.storage.keys().map(|slot| (address, *slot)));
+        }
+
+        let parent = committed.fork_block().hash;
+        let tip = committed.tip().hash;
+        let stale = inner
+            .entries
+            .iter()
+            .filter(|(_, estimate)| {
+                estimate.block_hash != parent ||
+                    estimate.accounts.iter().any(|address| changed_accounts.contains(address)) ||
+                    estimate.storage.iter().any(|slot| changed_storage.contains(slot))
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in &stale {
+            inner.entries.remove(key);
+        }
+        for (_, estimate) in inner.entries.iter_mut() {
+            estimate.block_hash = tip;
+        }
+
+        trace!(target: "rpc::eth::estimate", %tip, invalidated = stale.len(), kept = inner.entries.len(), "Updated cached gas estimates");
+        self.metrics.invalidated.increment(stale.len() as u64);
+        self.metrics.cached_count.set(inner.entries.len() as f64);
+    }
+}
+
+struct EstimateGasCacheInner {
+    entries: LruMap<EstimateGasKey, CachedEstimate, ByLength>,
+}
+
+impl Default for EstimateGasCacheInner {
+    fn default() -> Self {
+        Self { entries: LruMap::new(ByLength::new(MAX_CACHED_ESTIMATES)) }
+    }
+}
+
+impl fmt::Debug for EstimateGasCacheInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EstimateGasCacheInner").field("entries", &self.entries.len()).finish()
+    }
+}
+
+/// The fields of a call request that determine its gas estimate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct EstimateGasKey {
+    from: Option<Address>,
+    to: Option<Address>,
+    input: Bytes,
+    value: Option<U256>,
+    gas: Option<U256>,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    nonce: Option<U64>,
+    chain_id: Option<U64>,
+}
+
+impl EstimateGasKey {
+    /// Returns the key of the request, or `None` if the estimate of the request is not cached.
+    ///
+    /// Requests with an access list or blobs are rare and not worth caching, and plain transfers
+    /// are estimated without executing them.
+    pub(crate) fn new(request: &CallRequest) -> Option<Self> {
+        if request.access_list.is_some() ||
+            request.blob_versioned_hashes.is_some() ||
+            request.max_fee_per_blob_gas.is_some()
+        {
+            return None
+        }
+        let input = request.input.clone().try_into_unique_input().ok()??;
+        if input.is_empty() {
+            return None
+        }
+
+        Some(Self {
+            from: request.from,
+            to: request.to,
+            input,
+            value: request.value,
+            gas: request.gas,
+            gas_price: request.gas_price,
+            max_fee_per_gas: request.max_fee_per_gas,
+            max_priority_fee_per_gas: request.max_priority_fee_per_gas,
+            nonce: request.nonce,
+            chain_id: request.chain_id,
+        })
+    }
+}
+
+/// A gas estimate and the state it touched.
+#[derive(Debug)]
+struct CachedEstimate {
+    /// The block the estimate is valid at.
+    block_hash: B256,
+    gas: U256,
+    accounts: Vec<Address>,
+    storage: Vec<(Address, U256)>,
+}
+
+#[derive(Metrics, Clone)]
+#[metrics(scope = "rpc.eth_estimate_gas_cache")]
+struct EstimateGasCacheMetrics {
+    /// The number of cached estimates.
+    cached_count: Gauge,
+    /// The number of estimates served from the cache.
+    hits: Counter,
+    /// The number of cacheable estimates that were not cached.
+    misses: Counter,
+    /// The number of estimates dropped because the state they touched changed.
+    invalidated: Counter,
+}
+
+/// Keeps the [EstimateGasCache] in sync with the canonical state changes.
+pub async fn estimate_gas_cache_new_blocks_task<St>(cache: EstimateGasCache, mut events: St)
+where
+    St: Stream<Item = CanonStateNotification> + Unpin + 'static,
+{
+    while let Some(event) = events.next().await {
+        cache.on_new_state(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{db::EmptyDB, primitives::AccountInfo};
+
+    #[test]
+    fn serves_estimates_at_their_block() {
+        let cache = EstimateGasCache::default();
+        let key = EstimateGasKey {
+            from: Some(Address::random()),
+            to: Some(Address::random()),
+            input: Bytes::from_static(&[0xa9, 0x05, 0x9c, 0xbb]),
+            value: None,
+            gas: None,
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            nonce: None,
+            chain_id: None,
+        };
+        let block_hash = B256::random();
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(key.to.unwrap(), AccountInfo::default());
+        cache.insert(key.clone(), block_hash, U256::from(46_000), &db);
+
+        assert_eq!(cache.get(&key, block_hash), Some(U256::from(46_000)));
+        assert_eq!(cache.get(&key, B256::random()), None);
+
+        let inner = cache.inner.lock().unwrap();
+        let estimate = inner.entries.peek(&key).unwrap();
+        assert_eq!(estimate.accounts, vec![key.to.unwrap()]);
+    }
+}
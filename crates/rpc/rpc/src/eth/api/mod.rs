@@ -3,6 +3,7 @@
 
 use crate::eth::{
     api::{
+        estimate_gas_cache::EstimateGasCache,
         fee_history::FeeHistoryCache,
         pending_block::{PendingBlock, PendingBlockEnv, PendingBlockEnvOrigin},
     },
@@ -23,6 +24,7 @@ use reth_primitives::{
 use reth_provider::{
     BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, StateProviderBox, StateProviderFactory,
 };
+use reth_revm::EvmConfig;
 use reth_rpc_types::{SyncInfo, SyncStatus};
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use reth_transaction_pool::TransactionPool;
@@ -37,6 +39,7 @@ use tokio::sync::{oneshot, Mutex};
 
 mod block;
 mod call;
+pub(crate) mod estimate_gas_cache;
 pub(crate) mod fee_history;
 mod fees;
 #[cfg(feature = "optimism")]
@@ -113,6 +116,7 @@ where
             Box::<TokioTaskExecutor>::default(),
             blocking_task_pool,
             fee_history_cache,
+            EvmConfig::default(),
         )
     }
 
@@ -128,6 +132,7 @@ where
         task_spawner: Box<dyn TaskSpawner>,
         blocking_task_pool: BlockingTaskPool,
         fee_history_cache: FeeHistoryCache,
+        evm_config: EvmConfig,
     ) -> Self {
         // get the block number of the latest block
         let latest_block = provider
@@ -150,6 +155,8 @@ where
             pending_block: Default::default(),
             blocking_task_pool,
             fee_history_cache,
+            estimate_gas_cache: Default::default(),
+            evm_config,
             #[cfg(feature = "optimism")]
             http_client: reqwest::Client::builder().use_rustls_tls().build().unwrap(),
         };
@@ -211,6 +218,13 @@ where
     pub fn fee_history_cache(&self) -> &FeeHistoryCache {
         &self.inner.fee_history_cache
     }
+
+    /// Returns the cache of gas estimates at the latest block.
+    ///
+    /// See also [crate::eth::estimate_gas_cache_new_blocks_task].
+    pub fn estimate_gas_cache(&self) -> &EstimateGasCache {
+        &self.inner.estimate_gas_cache
+    }
 }
 
 // === State access helpers ===
@@ -294,6 +308,7 @@ where
         // Note: for the PENDING block we assume it is past the known merge block and thus this will
         // not fail when looking up the total difficulty value for the blockenv.
         self.provider().fill_env_with_header(&mut cfg, &mut block_env, origin.header())?;
+        self.inner.evm_config.configure_cfg(&mut cfg);
 
         Ok(PendingBlockEnv { cfg, block_env, origin })
     }
@@ -327,13 +342,14 @@ where
             }
 
             // we rebuild the block
-            let pending_block = match pending.build_block(this.provider(), this.pool()) {
-                Ok(block) => block,
-                Err(err) => {
-                    tracing::debug!(target: "rpc", "Failed to build pending block: {:?}", err);
-                    return Ok(None)
-                }
-            };
+            let pending_block =
+                match pending.build_block(this.provider(), this.pool(), &this.inner.evm_config) {
+                    Ok(block) => block,
+                    Err(err) => {
+                        tracing::debug!(target: "rpc", "Failed to build pending block: {:?}", err);
+                        return Ok(None)
+                    }
+                };
 
             let now = Instant::now();
             *lock = Some(PendingBlock {
@@ -468,6 +484,10 @@ struct EthApiInner<Provider, Pool, Network> {
     blocking_task_pool: BlockingTaskPool,
     /// Cache for block fees history
     fee_history_cache: FeeHistoryCache,
+    /// Cache for gas estimates at the latest block
+    estimate_gas_cache: EstimateGasCache,
+    /// Customizations of the EVM used for all executions
+    evm_config: EvmConfig,
     /// An http client for communicating with sequencers.
     #[cfg(feature = "optimism")]
     http_client: reqwest::Client,
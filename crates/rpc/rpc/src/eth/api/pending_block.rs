@@ -15,6 +15,7 @@ use reth_provider::{BundleStateWithReceipts, ChainSpecProvider, StateProviderFac
 use reth_revm::{
     database::StateProviderDatabase,
     state_change::{apply_beacon_root_contract_call, post_block_withdrawals_balance_increments},
+    EvmConfig,
 };
 use reth_transaction_pool::TransactionPool;
 use revm::{db::states::bundle_state::BundleRetention, Database, DatabaseCommit, State};
@@ -32,7 +33,8 @@ pub(crate) struct PendingBlockEnv {
 }
 
 impl PendingBlockEnv {
-    /// Builds a pending block using the given client and pool.
+    /// Builds a pending block using the given client and pool, executing the transactions with
+    /// the [EvmConfig].
     ///
     /// If the origin is the actual pending block, the block is built with withdrawals.
     ///
@@ -42,6 +44,7 @@ impl PendingBlockEnv {
         self,
         client: &Client,
         pool: &Pool,
+        evm_config: &EvmConfig,
     ) -> EthResult<SealedBlockWithSenders>
     where
         Client: StateProviderFactory + ChainSpecProvider,
@@ -133,7 +136,7 @@ impl PendingBlockEnv {
             let mut evm = revm::EVM::with_env(env);
             evm.database(&mut db);
 
-            let ResultAndState { result, state } = match evm.transact() {
+            let ResultAndState { result, state } = match evm_config.transact(&mut evm) {
                 Ok(res) => res,
                 Err(err) => {
                     match err {
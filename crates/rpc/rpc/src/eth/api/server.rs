@@ -1,5 +1,5 @@
 //! Implementation of the [`jsonrpsee`] generated [`reth_rpc_api::EthApiServer`] trait
-//! Handles RPC requests for the `eth_` namespace.
+//! Handles RPC requests for the `eth_` namespace, and `reth_getTransactionReceipts`.
 
 use super::EthApiSpec;
 use crate::{
@@ -20,11 +20,11 @@ use reth_provider::{
     BlockIdReader, BlockReader, BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider,
     HeaderProvider, StateProviderFactory,
 };
-use reth_rpc_api::EthApiServer;
+use reth_rpc_api::{EthApiServer, RethReceiptsApiServer};
 use reth_rpc_types::{
-    state::StateOverride, AccessListWithGasUsed, BlockOverrides, Bundle, CallRequest,
-    EIP1186AccountProofResponse, EthCallResponse, FeeHistory, Index, RichBlock, StateContext,
-    SyncStatus, TransactionReceipt, TransactionRequest, Work,
+    state::StateOverride, AccessListBatchItem, AccessListWithGasUsed, BlockOverrides, Bundle,
+    CallRequest, EIP1186AccountProofResponse, EthCallResponse, FeeHistory, Index, RichBlock,
+    StateContext, SyncStatus, TransactionReceipt, TransactionRequest, Work,
 };
 use reth_transaction_pool::TransactionPool;
 use serde_json::Value;
@@ -261,6 +261,16 @@ where
         Ok(access_list_with_gas_used)
     }
 
+    /// Handler for: `eth_createAccessListBatch`
+    async fn create_access_list_batch(
+        &self,
+        requests: Vec<CallRequest>,
+        block_number: Option<BlockId>,
+    ) -> Result<Vec<AccessListBatchItem>> {
+        trace!(target: "rpc::eth", requests = requests.len(), ?block_number, "Serving eth_createAccessListBatch");
+        Ok(self.create_access_list_batch_at(requests, block_number).await?)
+    }
+
     /// Handler for: `eth_estimateGas`
     async fn estimate_gas(
         &self,
@@ -390,6 +400,24 @@ where
     }
 }
 
+#[async_trait::async_trait]
+impl<Provider, Pool, Network> RethReceiptsApiServer for EthApi<Provider, Pool, Network>
+where
+    Self: EthTransactions,
+    Pool: TransactionPool + 'static,
+    Provider: BlockReaderIdExt + 'static,
+    Network: NetworkInfo + Send + Sync + 'static,
+{
+    /// Handler for: `reth_getTransactionReceipts`
+    async fn reth_get_transaction_receipts(
+        &self,
+        hashes: Vec<B256>,
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        trace!(target: "rpc::eth", count = hashes.len(), "Serving reth_getTransactionReceipts");
+        Ok(EthTransactions::transaction_receipts(self, hashes).await?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -410,7 +438,7 @@ mod tests {
         test_utils::{MockEthProvider, NoopProvider},
         BlockReader, BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, StateProviderFactory,
     };
-    use reth_rpc_api::EthApiServer;
+    use reth_rpc_api::{EthApiServer, RethReceiptsApiServer};
     use reth_rpc_types::FeeHistory;
     use reth_transaction_pool::test_utils::{testing_pool, TestPool};
 
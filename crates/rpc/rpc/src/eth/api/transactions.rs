@@ -29,18 +29,23 @@ use reth_provider::{
 use reth_revm::{
     database::StateProviderDatabase,
     tracing::{TracingInspector, TracingInspectorConfig},
+    EvmConfig,
 };
 use reth_rpc_types::{
     CallRequest, Index, Log, Transaction, TransactionInfo, TransactionReceipt, TransactionRequest,
     TypedTransactionRequest,
 };
 use reth_rpc_types_compat::transaction::from_recovered_with_block_context;
-use reth_transaction_pool::{TransactionOrigin, TransactionPool};
+use reth_transaction_pool::{
+    first_seen::{self, SeenOrigin},
+    TransactionOrigin, TransactionPool,
+};
 use revm::{
     db::CacheDB,
     primitives::{BlockEnv, CfgEnv},
     Inspector,
 };
+use tracing::{debug_span, field, Instrument};
 
 #[cfg(feature = "optimism")]
 use crate::eth::api::optimism::OptimismTxMeta;
@@ -58,6 +63,9 @@ use std::ops::Div;
 /// Helper alias type for the state's [CacheDB]
 pub(crate) type StateCacheDB = CacheDB<StateProviderDatabase<StateProviderBox>>;
 
+/// The maximum number of transactions a single `reth_getTransactionReceipts` request can query.
+const MAX_TRANSACTION_RECEIPTS_BATCH: usize = 1024;
+
 /// Commonly used transaction related functions for the [EthApi] type in the `eth_` namespace.
 ///
 /// Async functions that are spawned onto the
@@ -67,6 +75,9 @@ pub trait EthTransactions: Send + Sync {
     /// Returns default gas limit to use for `eth_call` and tracing RPC methods.
     fn call_gas_limit(&self) -> u64;
 
+    /// Returns the customizations of the EVM that are applied to all executions.
+    fn evm_config(&self) -> &EvmConfig;
+
     /// Returns the state at the given [BlockId]
     fn state_at(&self, at: BlockId) -> EthResult<StateProviderBox>;
 
@@ -148,6 +159,15 @@ pub trait EthTransactions: Send + Sync {
     /// Note: The tx receipt is not available for pending transactions.
     async fn transaction_receipt(&self, hash: B256) -> EthResult<Option<TransactionReceipt>>;
 
+    /// Returns the transaction receipts for the given hashes, in the same order.
+    ///
+    /// The receipts of transactions in the same block are read at once. Contains `None` for every
+    /// transaction that does not exist or is pending.
+    async fn transaction_receipts(
+        &self,
+        hashes: Vec<B256>,
+    ) -> EthResult<Vec<Option<TransactionReceipt>>>;
+
     /// Decodes and recovers the transaction and submits it to the pool.
     ///
     /// Returns the hash of the transaction.
@@ -318,6 +338,10 @@ where
         self.inner.gas_cap
     }
 
+    fn evm_config(&self) -> &EvmConfig {
+        &self.inner.evm_config
+    }
+
     fn state_at(&self, at: BlockId) -> EthResult<StateProviderBox> {
         self.state_at_block_id(at)
     }
@@ -352,7 +376,8 @@ where
                 .provider()
                 .block_hash_for_id(at)?
                 .ok_or_else(|| EthApiError::UnknownBlockNumber)?;
-            let (cfg, env) = self.cache().get_evm_env(block_hash).await?;
+            let (mut cfg, env) = self.cache().get_evm_env(block_hash).await?;
+            self.evm_config().configure_cfg(&mut cfg);
             Ok((cfg, env, block_hash.into()))
         }
     }
@@ -500,6 +525,43 @@ where
         self.build_transaction_receipt(tx, meta, receipt).await.map(Some)
     }
 
+    async fn transaction_receipts(
+        &self,
+        hashes: Vec<B256>,
+    ) -> EthResult<Vec<Option<TransactionReceipt>>> {
+        if hashes.len() > MAX_TRANSACTION_RECEIPTS_BATCH {
+            return Err(EthApiError::InvalidParams(format!(
+                "too many transactions, at most {MAX_TRANSACTION_RECEIPTS_BATCH} are allowed"
+            )))
+        }
+
+        let transactions = self
+            .on_blocking_task(|this| async move {
+                Ok(this.provider().transactions_with_block_receipts(&hashes)?)
+            })
+            .await?;
+
+        let mut receipts = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            let Some((tx, meta, all_receipts)) = transaction else {
+                receipts.push(None);
+                continue
+            };
+            // the receipt is missing if the receipts of the block are pruned
+            let Some(receipt) = all_receipts.get(meta.index as usize).cloned() else {
+                receipts.push(None);
+                continue
+            };
+            #[cfg(not(feature = "optimism"))]
+            let receipt =
+                build_transaction_receipt_with_block_receipts(tx, meta, receipt, &all_receipts)?;
+            #[cfg(feature = "optimism")]
+            let receipt = self.build_transaction_receipt(tx, meta, receipt).await?;
+            receipts.push(Some(receipt));
+        }
+        Ok(receipts)
+    }
+
     async fn send_raw_transaction(&self, tx: Bytes) -> EthResult<B256> {
         // On optimism, transactions are forwarded directly to the sequencer to be included in
         // blocks that it builds.
@@ -507,10 +569,20 @@ where
         self.forward_to_sequencer(&tx).await?;
 
         let recovered = recover_raw_transaction(tx)?;
+        first_seen::record(*recovered.hash(), SeenOrigin::Rpc);
         let pool_transaction = <Pool::Transaction>::from_recovered_pooled_transaction(recovered);
 
         // submit the transaction to the pool with a `Local` origin
-        let hash = self.pool().add_transaction(TransactionOrigin::Local, pool_transaction).await?;
+        let span = debug_span!(target: "rpc::eth", "send_raw_transaction", tx_hash = field::Empty);
+        let hash = self
+            .pool()
+            .add_transaction(TransactionOrigin::Local, pool_transaction)
+            .instrument(span.clone())
+            .await?;
+        span.record("tx_hash", field::display(hash));
+
+        // the inclusion of the transaction in payloads is traced as part of this request
+        span.in_scope(|| reth_tracing::otlp::record_transaction(hash.0));
 
         Ok(hash)
     }
@@ -594,12 +666,22 @@ where
 
         let recovered =
             signed_tx.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
+        first_seen::record(recovered.hash(), SeenOrigin::Rpc);
 
         let pool_transaction =
             <Pool::Transaction>::from_recovered_pooled_transaction(recovered.into());
 
         // submit the transaction to the pool with a `Local` origin
-        let hash = self.pool().add_transaction(TransactionOrigin::Local, pool_transaction).await?;
+        let span = debug_span!(target: "rpc::eth", "send_transaction", tx_hash = field::Empty);
+        let hash = self
+            .pool()
+            .add_transaction(TransactionOrigin::Local, pool_transaction)
+            .instrument(span.clone())
+            .await?;
+        span.record("tx_hash", field::display(hash));
+
+        // the inclusion of the transaction in payloads is traced as part of this request
+        span.in_scope(|| reth_tracing::otlp::record_transaction(hash.0));
 
         Ok(hash)
     }
@@ -643,8 +725,11 @@ where
         at: BlockId,
         overrides: EvmOverrides,
     ) -> EthResult<(ResultAndState, Env)> {
-        self.spawn_with_call_at(request, at, overrides, move |mut db, env| transact(&mut db, env))
-            .await
+        let evm_config = self.evm_config().clone();
+        self.spawn_with_call_at(request, at, overrides, move |mut db, env| {
+            transact(&evm_config, &mut db, env)
+        })
+        .await
     }
 
     async fn spawn_inspect_call_at<I>(
@@ -657,8 +742,11 @@ where
     where
         I: Inspector<StateCacheDB> + Send + 'static,
     {
-        self.spawn_with_call_at(request, at, overrides, move |db, env| inspect(db, env, inspector))
-            .await
+        let evm_config = self.evm_config().clone();
+        self.spawn_with_call_at(request, at, overrides, move |db, env| {
+            inspect(&evm_config, db, env, inspector)
+        })
+        .await
     }
 
     fn trace_at<F, R>(
@@ -675,7 +763,7 @@ where
             let db = CacheDB::new(StateProviderDatabase::new(state));
 
             let mut inspector = TracingInspector::new(config);
-            let (res, _) = inspect(db, env, &mut inspector)?;
+            let (res, _) = inspect(self.evm_config(), db, env, &mut inspector)?;
 
             f(inspector, res)
         })
@@ -692,10 +780,11 @@ where
         F: FnOnce(TracingInspector, ResultAndState, StateCacheDB) -> EthResult<R> + Send + 'static,
         R: Send + 'static,
     {
+        let evm_config = self.evm_config().clone();
         self.spawn_with_state_at_block(at, move |state| {
             let db = CacheDB::new(StateProviderDatabase::new(state));
             let mut inspector = TracingInspector::new(config);
-            let (res, _, db) = inspect_and_return_db(db, env, &mut inspector)?;
+            let (res, _, db) = inspect_and_return_db(&evm_config, db, env, &mut inspector)?;
 
             f(inspector, res, db)
         })
@@ -744,17 +833,25 @@ where
         // block the transaction is included in
         let parent_block = block.parent_hash;
         let block_txs = block.body;
+        let evm_config = self.evm_config().clone();
 
         self.spawn_with_state_at_block(parent_block.into(), move |state| {
             let mut db = CacheDB::new(StateProviderDatabase::new(state));
 
             // replay all transactions prior to the targeted transaction
-            replay_transactions_until(&mut db, cfg.clone(), block_env.clone(), block_txs, tx.hash)?;
+            replay_transactions_until(
+                &evm_config,
+                &mut db,
+                cfg.clone(),
+                block_env.clone(),
+                block_txs,
+                tx.hash,
+            )?;
 
             let env = Env { cfg, block: block_env, tx: tx_env_with_recovered(&tx) };
 
             let mut inspector = TracingInspector::new(config);
-            let (res, _, db) = inspect_and_return_db(db, env, &mut inspector)?;
+            let (res, _, db) = inspect_and_return_db(&evm_config, db, env, &mut inspector)?;
             f(tx_info, inspector, res, db)
         })
         .await
@@ -847,7 +944,7 @@ where
                 let env = Env { cfg: cfg.clone(), block: block_env.clone(), tx };
 
                 let mut inspector = TracingInspector::new(config);
-                let (res, _) = inspect(&mut db, env, &mut inspector)?;
+                let (res, _) = inspect(this.evm_config(), &mut db, env, &mut inspector)?;
                 let ResultAndState { result, state } = res;
                 results.push(f(tx_info, inspector, result, &state, &db)?);
 
@@ -74,6 +74,7 @@ where
         // use the block number of the request
         block_env.number = U256::from(block_number);
 
+        let evm_config = self.inner.eth_api.evm_config().clone();
         self.inner
             .eth_api
             .spawn_with_state_at_block(at, move |state| {
@@ -104,7 +105,7 @@ where
                         .effective_tip_per_gas(basefee)
                         .ok_or_else(|| RpcInvalidTransactionError::FeeCapTooLow)?;
                     tx.try_fill_tx_env(&mut evm.env.tx)?;
-                    let ResultAndState { result, state } = evm.transact()?;
+                    let ResultAndState { result, state } = evm_config.transact(&mut evm)?;
 
                     let gas_used = result.gas_used();
                     total_gas_used += gas_used;
@@ -45,6 +45,9 @@ type ReceiptsResponseSender = oneshot::Sender<ProviderResult<Option<Arc<Vec<Rece
 /// The type that can send the response to a requested env
 type EnvResponseSender = oneshot::Sender<ProviderResult<(CfgEnv, BlockEnv)>>;
 
+/// The type that can send the number of entries removed by clearing the caches.
+type ClearResponseSender = oneshot::Sender<usize>;
+
 type BlockLruCache<L> = MultiConsumerLruCache<
     B256,
     BlockWithSenders,
@@ -246,6 +249,23 @@ impl EthStateCache {
         let _ = self.to_service.send(CacheAction::GetEnv { block_hash, response_tx });
         rx.await.map_err(|_| ProviderError::CacheServiceUnavailable)?
     }
+
+    /// Removes all cached blocks, receipts and envs and returns the number of removed entries.
+    ///
+    /// Requests that are in flight are still answered.
+    pub async fn clear(&self) -> ProviderResult<usize> {
+        let (response_tx, rx) = oneshot::channel();
+        let _ = self.to_service.send(CacheAction::Clear { response_tx });
+        rx.await.map_err(|_| ProviderError::CacheServiceUnavailable)
+    }
+
+    /// Changes the maximum number of cached blocks, receipts and envs.
+    ///
+    /// Shrinking a cache evicts its least recently used entries.
+    pub fn set_max_lengths(&self, max_blocks: u32, max_receipts: u32, max_envs: u32) {
+        let _ =
+            self.to_service.send(CacheAction::SetMaxLengths { max_blocks, max_receipts, max_envs });
+    }
 }
 
 /// A task than manages caches for data required by the `eth` rpc implementation.
@@ -499,6 +519,17 @@ where
                                 this.evm_env_cache.insert(block_hash, data);
                             }
                         }
+                        CacheAction::Clear { response_tx } => {
+                            let cleared = this.full_block_cache.clear() +
+                                this.receipts_cache.clear() +
+                                this.evm_env_cache.clear();
+                            let _ = response_tx.send(cleared);
+                        }
+                        CacheAction::SetMaxLengths { max_blocks, max_receipts, max_envs } => {
+                            this.full_block_cache.set_max_len(max_blocks);
+                            this.receipts_cache.set_max_len(max_receipts);
+                            this.evm_env_cache.set_max_len(max_envs);
+                        }
                         CacheAction::CacheNewCanonicalChain { blocks, receipts } => {
                             for block in blocks {
                                 this.on_new_block(block.hash, Ok(Some(block.unseal())));
@@ -531,6 +562,8 @@ enum CacheAction {
     ReceiptsResult { block_hash: B256, res: ProviderResult<Option<Arc<Vec<Receipt>>>> },
     EnvResult { block_hash: B256, res: Box<ProviderResult<(CfgEnv, BlockEnv)>> },
     CacheNewCanonicalChain { blocks: Vec<SealedBlockWithSenders>, receipts: Vec<BlockReceipts> },
+    Clear { response_tx: ClearResponseSender },
+    SetMaxLengths { max_blocks: u32, max_receipts: u32, max_envs: u32 },
 }
 
 struct BlockReceipts {
@@ -91,6 +91,13 @@ where
         self.cache.insert(key, value)
     }
 
+    /// Removes all cached values and returns how many were removed, queued consumers are kept.
+    pub fn clear(&mut self) -> usize {
+        let len = self.cache.len();
+        self.cache.clear();
+        len
+    }
+
     /// Update metrics for the inner cache.
     #[inline]
     pub fn update_cached_metrics(&self) {
@@ -110,4 +117,42 @@ where
             metrics: CacheMetrics::new_with_labels(&[("cache", cache_id.to_string())]),
         }
     }
+
+    /// Changes the maximum length of the map, evicting the least recently used values if it
+    /// shrinks.
+    pub fn set_max_len(&mut self, max_len: u32) {
+        let mut cache = LruMap::new(ByLength::new(max_len));
+        // re-insert from oldest to newest to keep the order of use
+        while let Some((key, value)) = self.cache.pop_oldest() {
+            cache.insert(key, value);
+        }
+        self.cache = cache;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinking_keeps_most_recently_used() {
+        let mut cache = MultiConsumerLruCache::<u64, u64, ByLength, ()>::new(4, "test");
+        for key in 0..4 {
+            cache.insert(key, key);
+        }
+        // promote the oldest value
+        assert!(cache.get(&0).is_some());
+
+        cache.set_max_len(2);
+        assert!(cache.get(&0).is_some());
+        assert!(cache.get(&3).is_some());
+        assert!(cache.get(&1).is_none());
+        assert!(cache.get(&2).is_none());
+
+        cache.set_max_len(3);
+        cache.insert(4, 4);
+        assert!(cache.get(&0).is_some());
+        assert!(cache.get(&3).is_some());
+        assert!(cache.get(&4).is_some());
+    }
 }
@@ -7,9 +7,11 @@ use jsonrpsee::{
     types::{error::CALL_EXECUTION_FAILED_CODE, ErrorObject},
 };
 use reth_interfaces::RethError;
-use reth_primitives::{revm_primitives::InvalidHeader, Address, Bytes, U256};
+use reth_primitives::{revm_primitives::InvalidHeader, Address, Bytes, PruneSegment, U256, U64};
 use reth_revm::tracing::js::JsInspectorError;
-use reth_rpc_types::{error::EthRpcErrorCode, BlockError, CallInputError};
+use reth_rpc_types::{
+    error::EthRpcErrorCode, BlockError, CallInputError, HistoryExpiredErrorData, PrunedErrorData,
+};
 use reth_transaction_pool::error::{
     Eip4844PoolTransactionError, InvalidPoolTransactionError, PoolError, PoolErrorKind,
     PoolTransactionError,
@@ -17,6 +19,9 @@ use reth_transaction_pool::error::{
 use revm::primitives::{EVMError, ExecutionResult, Halt, OutOfGasError};
 use std::time::Duration;
 
+/// The sources expired history can be retrieved from, see [EthApiError::HistoryExpired].
+const HISTORY_EXPIRY_SOURCES: [&str; 2] = ["portal", "era1"];
+
 /// Result alias
 pub type EthResult<T> = Result<T, EthApiError>;
 
@@ -48,6 +53,24 @@ pub enum EthApiError {
     /// When an invalid block range is provided
     #[error("invalid block range")]
     InvalidBlockRange,
+    /// Thrown when the body or receipts of a block were dropped by history expiry.
+    #[error("history of block #{block_number} has expired, the first available block is #{first_available_block}; retrieve it from the Portal network or era1 archives")]
+    HistoryExpired {
+        /// The requested block.
+        block_number: u64,
+        /// The first block with an available body and receipts.
+        first_available_block: u64,
+    },
+    /// Thrown when the requested data of a block is pruned by this node.
+    #[error("{segment} of block #{block_number} is pruned, the earliest available block is #{earliest_available_block}")]
+    Pruned {
+        /// The pruned segment.
+        segment: PruneSegment,
+        /// The requested block.
+        block_number: u64,
+        /// The first block whose data of the segment is available.
+        earliest_available_block: u64,
+    },
     /// An internal error where prevrandao is not set in the evm's environment
     #[error("prevrandao not in the EVM's environment after merge")]
     PrevrandaoNotSet,
@@ -100,6 +123,9 @@ pub enum EthApiError {
     /// Error thrown when a (tracing) call exceeds the configured timeout
     #[error("execution aborted (timeout = {0:?})")]
     ExecutionTimedOut(Duration),
+    /// Error thrown when a trace exceeds a limit of the tracing call
+    #[error("{0}")]
+    TracingLimitExceeded(String),
     /// Internal Error thrown by the javascript tracer
     #[error("{0}")]
     InternalJsTracerError(String),
@@ -157,11 +183,39 @@ impl From<EthApiError> for ErrorObject<'static> {
             EthApiError::UnknownSafeOrFinalizedBlock => {
                 rpc_error_with_code(EthRpcErrorCode::UnknownBlock.code(), error.to_string())
             }
+            EthApiError::HistoryExpired { block_number, first_available_block } => {
+                let data = HistoryExpiredErrorData {
+                    block_number: U64::from(block_number),
+                    first_available_block: U64::from(first_available_block),
+                    sources: HISTORY_EXPIRY_SOURCES
+                        .iter()
+                        .map(|source| source.to_string())
+                        .collect(),
+                };
+                ErrorObject::owned(
+                    EthRpcErrorCode::ResourceNotFound.code(),
+                    error.to_string(),
+                    Some(data),
+                )
+            }
+            EthApiError::Pruned { segment, block_number, earliest_available_block } => {
+                let data = PrunedErrorData {
+                    segment: segment.to_string(),
+                    block_number: U64::from(block_number),
+                    earliest_available_block: U64::from(earliest_available_block),
+                };
+                ErrorObject::owned(
+                    EthRpcErrorCode::ResourceNotFound.code(),
+                    error.to_string(),
+                    Some(data),
+                )
+            }
             EthApiError::Unsupported(msg) => internal_rpc_err(msg),
             EthApiError::InternalJsTracerError(msg) => internal_rpc_err(msg),
             EthApiError::InvalidParams(msg) => invalid_params_rpc_err(msg),
             EthApiError::InvalidRewardPercentiles => internal_rpc_err(error.to_string()),
-            err @ EthApiError::ExecutionTimedOut(_) => {
+            err @ EthApiError::ExecutionTimedOut(_) |
+            err @ EthApiError::TracingLimitExceeded(_) => {
                 rpc_error_with_code(CALL_EXECUTION_FAILED_CODE, err.to_string())
             }
             err @ EthApiError::InternalBlockingTaskError => internal_rpc_err(err.to_string()),
@@ -217,6 +271,12 @@ impl From<reth_interfaces::provider::ProviderError> for EthApiError {
             ProviderError::FinalizedBlockNotFound | ProviderError::SafeBlockNotFound => {
                 EthApiError::UnknownSafeOrFinalizedBlock
             }
+            ProviderError::HistoryExpired { block_number, first_available_block } => {
+                EthApiError::HistoryExpired { block_number, first_available_block }
+            }
+            ProviderError::Pruned { segment, block_number, earliest_available_block } => {
+                EthApiError::Pruned { segment, block_number, earliest_available_block }
+            }
             err => EthApiError::Internal(err.into()),
         }
     }
@@ -714,4 +774,24 @@ mod tests {
         let err = EthApiError::ExecutionTimedOut(Duration::from_secs(10));
         assert_eq!(err.to_string(), "execution aborted (timeout = 10s)");
     }
+
+    #[test]
+    fn pruned_error_data() {
+        let err: ErrorObject<'static> = EthApiError::Pruned {
+            segment: PruneSegment::Receipts,
+            block_number: 10,
+            earliest_available_block: 100,
+        }
+        .into();
+        assert_eq!(err.code(), EthRpcErrorCode::ResourceNotFound.code());
+        let data: PrunedErrorData = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            PrunedErrorData {
+                segment: "Receipts".to_string(),
+                block_number: U64::from(10),
+                earliest_available_block: U64::from(100),
+            }
+        );
+    }
 }
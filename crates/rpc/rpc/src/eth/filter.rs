@@ -10,8 +10,9 @@ use crate::{
 use core::fmt;
 
 use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
 use jsonrpsee::{core::RpcResult, server::IdProvider};
-use reth_primitives::{IntoRecoveredTransaction, TxHash};
+use reth_primitives::{BlockNumberOrTag, IntoRecoveredTransaction, TxHash};
 use reth_provider::{BlockIdReader, BlockReader, EvmEnvProvider, ProviderError};
 use reth_rpc_api::EthFilterApiServer;
 use reth_rpc_types::{
@@ -21,7 +22,7 @@ use reth_rpc_types::{
 use reth_tasks::TaskSpawner;
 use reth_transaction_pool::{NewSubpoolTransactionStream, PoolTransaction, TransactionPool};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     iter::StepBy,
     ops::RangeInclusive,
     sync::Arc,
@@ -197,6 +198,45 @@ where
         }
     }
 
+    /// Returns a stream of the logs matching the given filter, block by block.
+    ///
+    /// Unlike `eth_getLogs`, the logs of a block are yielded as soon as they're found, so the
+    /// number of logs isn't limited by [EthFilterConfig::max_logs_per_response]. The number of
+    /// blocks of the range still is.
+    pub fn logs_stream(
+        &self,
+        filter: Filter,
+    ) -> Result<BoxStream<'static, Result<Vec<Log>, FilterError>>, FilterError> {
+        let inner = self.inner.clone();
+        let FilterBlockOption::Range { from_block, to_block } = filter.block_option else {
+            // a single block
+            return Ok(
+                futures::stream::once(async move { inner.logs_for_filter(filter).await }).boxed()
+            )
+        };
+
+        let (from_block, to_block) = inner.block_range(from_block, to_block)?;
+        if to_block - from_block > inner.max_blocks_per_filter {
+            return Err(FilterError::QueryExceedsMaxBlocks(inner.max_blocks_per_filter))
+        }
+        trace!(target: "rpc::eth::filter", from=from_block, to=to_block, ?filter, "streaming logs in range");
+
+        let state = LogsStreamState {
+            filter_params: FilteredParams::new(Some(filter.clone())),
+            filter,
+            ranges: BlockRangeInclusiveIter::new(from_block..=to_block, inner.max_headers_range),
+            blocks: VecDeque::new(),
+        };
+        let stream = futures::stream::try_unfold(state, move |mut state| {
+            let inner = inner.clone();
+            async move {
+                let logs = inner.next_matching_logs(&mut state).await?;
+                Ok::<_, FilterError>(logs.map(|logs| (logs, state)))
+            }
+        });
+        Ok(stream.boxed())
+    }
+
     /// Returns an array of all logs matching filter with given id.
     ///
     /// Returns an error if no matching log filter exists.
@@ -367,26 +407,30 @@ where
                 Ok(all_logs)
             }
             FilterBlockOption::Range { from_block, to_block } => {
-                // compute the range
-                let info = self.provider.chain_info()?;
-
-                // we start at the most recent block if unset in filter
-                let start_block = info.best_number;
-                let from = from_block
-                    .map(|num| self.provider.convert_block_number(num))
-                    .transpose()?
-                    .flatten();
-                let to = to_block
-                    .map(|num| self.provider.convert_block_number(num))
-                    .transpose()?
-                    .flatten();
                 let (from_block_number, to_block_number) =
-                    logs_utils::get_filter_block_range(from, to, start_block, info);
+                    self.block_range(from_block, to_block)?;
                 self.get_logs_in_block_range(&filter, from_block_number, to_block_number).await
             }
         }
     }
 
+    /// Returns the _inclusive_ range of block numbers of the filter's range.
+    fn block_range(
+        &self,
+        from_block: Option<BlockNumberOrTag>,
+        to_block: Option<BlockNumberOrTag>,
+    ) -> Result<(u64, u64), FilterError> {
+        // compute the range
+        let info = self.provider.chain_info()?;
+
+        // we start at the most recent block if unset in filter
+        let start_block = info.best_number;
+        let from =
+            from_block.map(|num| self.provider.convert_block_number(num)).transpose()?.flatten();
+        let to = to_block.map(|num| self.provider.convert_block_number(num)).transpose()?.flatten();
+        Ok(logs_utils::get_filter_block_range(from, to, start_block, info))
+    }
+
     /// Installs a new filter and returns the new identifier.
     async fn install_filter(&self, kind: FilterKind) -> RpcResult<FilterId> {
         let last_poll_block_number = self.provider.best_block_number().to_rpc_result()?;
@@ -423,50 +467,27 @@ where
         let mut all_logs = Vec::new();
         let filter_params = FilteredParams::new(Some(filter.clone()));
 
-        // derive bloom filters from filter input
-        let address_filter = FilteredParams::address_filter(&filter.address);
-        let topics_filter = FilteredParams::topics_filter(&filter.topics);
-
         // loop over the range of new blocks and check logs if the filter matches the log's bloom
         // filter
         for (from, to) in
             BlockRangeInclusiveIter::new(from_block..=to_block, self.max_headers_range)
         {
-            let headers = self.provider.headers_range(from..=to)?;
-
-            for (idx, header) in headers.iter().enumerate() {
-                // only if filter matches
-                if FilteredParams::matches_address(header.logs_bloom, &address_filter) &&
-                    FilteredParams::matches_topics(header.logs_bloom, &topics_filter)
-                {
-                    // these are consecutive headers, so we can use the parent hash of the next
-                    // block to get the current header's hash
-                    let block_hash = match headers.get(idx + 1) {
-                        Some(parent) => parent.parent_hash,
-                        None => self
-                            .provider
-                            .block_hash(header.number)?
-                            .ok_or(ProviderError::BlockNotFound(header.number.into()))?,
-                    };
-
-                    if let Some(receipts) = self.eth_cache.get_receipts(block_hash).await? {
-                        append_matching_block_logs(
-                            &mut all_logs,
-                            &self.provider,
-                            &filter_params,
-                            BlockNumHash::new(header.number, block_hash),
-                            &receipts,
-                            false,
-                        )?;
-
-                        // size check but only if range is multiple blocks, so we always return all
-                        // logs of a single block
-                        let is_multi_block_range = from_block != to_block;
-                        if is_multi_block_range && all_logs.len() > self.max_logs_per_response {
-                            return Err(FilterError::QueryExceedsMaxResults(
-                                self.max_logs_per_response,
-                            ))
-                        }
+            for block in self.matching_blocks(filter, from, to)? {
+                if let Some(receipts) = self.eth_cache.get_receipts(block.hash).await? {
+                    append_matching_block_logs(
+                        &mut all_logs,
+                        &self.provider,
+                        &filter_params,
+                        block,
+                        &receipts,
+                        false,
+                    )?;
+
+                    // size check but only if range is multiple blocks, so we always return all
+                    // logs of a single block
+                    let is_multi_block_range = from_block != to_block;
+                    if is_multi_block_range && all_logs.len() > self.max_logs_per_response {
+                        return Err(FilterError::QueryExceedsMaxResults(self.max_logs_per_response))
                     }
                 }
             }
@@ -474,6 +495,83 @@ where
 
         Ok(all_logs)
     }
+
+    /// Returns the blocks in the given _inclusive_ range whose logs bloom matches the filter.
+    fn matching_blocks(
+        &self,
+        filter: &Filter,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<BlockNumHash>, FilterError> {
+        // derive bloom filters from filter input
+        let address_filter = FilteredParams::address_filter(&filter.address);
+        let topics_filter = FilteredParams::topics_filter(&filter.topics);
+
+        let headers = self.provider.headers_range(from_block..=to_block)?;
+        let mut blocks = Vec::new();
+        for (idx, header) in headers.iter().enumerate() {
+            // only if filter matches
+            if FilteredParams::matches_address(header.logs_bloom, &address_filter) &&
+                FilteredParams::matches_topics(header.logs_bloom, &topics_filter)
+            {
+                // these are consecutive headers, so we can use the parent hash of the next
+                // block to get the current header's hash
+                let block_hash = match headers.get(idx + 1) {
+                    Some(parent) => parent.parent_hash,
+                    None => self
+                        .provider
+                        .block_hash(header.number)?
+                        .ok_or(ProviderError::BlockNotFound(header.number.into()))?,
+                };
+                blocks.push(BlockNumHash::new(header.number, block_hash));
+            }
+        }
+
+        Ok(blocks)
+    }
+
+    /// Returns the logs of the next block of the stream that has matching logs, if any.
+    async fn next_matching_logs(
+        &self,
+        state: &mut LogsStreamState,
+    ) -> Result<Option<Vec<Log>>, FilterError> {
+        loop {
+            let Some(block) = state.blocks.pop_front() else {
+                match state.ranges.next() {
+                    Some((from, to)) => {
+                        state.blocks.extend(self.matching_blocks(&state.filter, from, to)?);
+                        continue
+                    }
+                    None => return Ok(None),
+                }
+            };
+
+            let mut logs = Vec::new();
+            if let Some(receipts) = self.eth_cache.get_receipts(block.hash).await? {
+                append_matching_block_logs(
+                    &mut logs,
+                    &self.provider,
+                    &state.filter_params,
+                    block,
+                    &receipts,
+                    false,
+                )?;
+            }
+            if !logs.is_empty() {
+                return Ok(Some(logs))
+            }
+        }
+    }
+}
+
+/// The state of a stream of the logs in a range of blocks, see [EthFilter::logs_stream].
+struct LogsStreamState {
+    filter: Filter,
+    filter_params: FilteredParams,
+    /// The remaining ranges of headers to check
+    ranges: BlockRangeInclusiveIter,
+    /// The blocks of the current range of headers whose logs bloom matches the filter
+    blocks: VecDeque<BlockNumHash>,
 }
 
 /// Config for the filter
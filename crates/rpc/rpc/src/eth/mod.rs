@@ -10,10 +10,12 @@ mod id_provider;
 mod logs_utils;
 mod pubsub;
 pub mod revm_utils;
+mod shared_reads;
 mod signer;
 pub(crate) mod utils;
 
 pub use api::{
+    estimate_gas_cache::{estimate_gas_cache_new_blocks_task, EstimateGasCache},
     fee_history::{fee_history_cache_new_blocks_task, FeeHistoryCache, FeeHistoryCacheConfig},
     EthApi, EthApiSpec, EthTransactions, TransactionSource, RPC_DEFAULT_GAS_CAP,
 };
@@ -21,4 +23,4 @@ pub use api::{
 pub use bundle::EthBundle;
 pub use filter::{EthFilter, EthFilterConfig};
 pub use id_provider::EthSubscriptionIdProvider;
-pub use pubsub::EthPubSub;
+pub use pubsub::{EthPubSub, EthPubSubConfig, SlowConsumerPolicy};
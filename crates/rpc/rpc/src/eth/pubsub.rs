@@ -1,25 +1,34 @@
 //! `eth_` PubSub RPC handler implementation
 
-use crate::{eth::logs_utils, result::invalid_params_rpc_err};
-use futures::StreamExt;
+use crate::{
+    eth::logs_utils,
+    result::{internal_rpc_err, invalid_params_rpc_err},
+};
+use futures::{
+    future::{Fuse, FusedFuture},
+    FutureExt, StreamExt,
+};
 use jsonrpsee::{server::SubscriptionMessage, PendingSubscriptionSink, SubscriptionSink};
+use reth_metrics::{metrics::Counter, Metrics};
 use reth_network_api::NetworkInfo;
 use reth_primitives::{IntoRecoveredTransaction, TxHash};
-use reth_provider::{BlockReader, CanonStateSubscriptions, EvmEnvProvider};
+use reth_provider::{
+    BlockReader, CanonStateSubscriptions, EvmEnvProvider, ForkChoiceNotifications,
+};
 use reth_rpc_api::EthPubSubApiServer;
 use reth_rpc_types::{
     pubsub::{
         Params, PubSubSyncStatus, SubscriptionKind, SubscriptionResult as EthSubscriptionResult,
         SyncStatusMetadata,
     },
-    FilteredParams, Header, Log,
+    EthSubscriptionKind, FilteredParams, FinalitySubscriptionKind, Header, Log,
 };
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use reth_transaction_pool::{NewTransactionEvent, TransactionPool};
-use serde::Serialize;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fmt, str::FromStr, sync::Arc};
 use tokio_stream::{
-    wrappers::{BroadcastStream, ReceiverStream},
+    wrappers::{BroadcastStream, ReceiverStream, WatchStream},
     Stream,
 };
 
@@ -32,6 +41,8 @@ pub struct EthPubSub<Provider, Pool, Events, Network> {
     inner: Arc<EthPubSubInner<Provider, Pool, Events, Network>>,
     /// The type that's used to spawn subscription tasks.
     subscription_task_spawner: Box<dyn TaskSpawner>,
+    /// How notifications are buffered for slow subscribers.
+    config: EthPubSubConfig,
 }
 
 // === impl EthPubSub ===
@@ -59,7 +70,17 @@ impl<Provider, Pool, Events, Network> EthPubSub<Provider, Pool, Events, Network>
         subscription_task_spawner: Box<dyn TaskSpawner>,
     ) -> Self {
         let inner = EthPubSubInner { provider, pool, chain_events, network };
-        Self { inner: Arc::new(inner), subscription_task_spawner }
+        Self {
+            inner: Arc::new(inner),
+            subscription_task_spawner,
+            config: EthPubSubConfig::default(),
+        }
+    }
+
+    /// Configures how notifications are buffered for slow subscribers.
+    pub fn with_config(mut self, config: EthPubSubConfig) -> Self {
+        self.config = config;
+        self
     }
 }
 
@@ -76,13 +97,20 @@ where
     async fn subscribe(
         &self,
         pending: PendingSubscriptionSink,
-        kind: SubscriptionKind,
+        kind: EthSubscriptionKind,
         params: Option<Params>,
     ) -> jsonrpsee::core::SubscriptionResult {
+        if let EthSubscriptionKind::Finality(kind) = kind {
+            if self.inner.finality_headers(kind).is_none() {
+                pending.reject(internal_rpc_err("finality is not tracked by this node")).await;
+                return Ok(())
+            }
+        }
         let sink = pending.accept().await?;
         let pubsub = self.inner.clone();
+        let config = self.config;
         self.subscription_task_spawner.spawn(Box::pin(async move {
-            let _ = handle_accepted(pubsub, sink, kind, params).await;
+            let _ = handle_accepted(pubsub, config, sink, kind, params).await;
         }));
 
         Ok(())
@@ -92,8 +120,9 @@ where
 /// The actual handler for an accepted [`EthPubSub::subscribe`] call.
 async fn handle_accepted<Provider, Pool, Events, Network>(
     pubsub: Arc<EthPubSubInner<Provider, Pool, Events, Network>>,
+    config: EthPubSubConfig,
     accepted_sink: SubscriptionSink,
-    kind: SubscriptionKind,
+    kind: EthSubscriptionKind,
     params: Option<Params>,
 ) -> Result<(), jsonrpsee::core::Error>
 where
@@ -102,12 +131,37 @@ where
     Events: CanonStateSubscriptions + Clone + 'static,
     Network: NetworkInfo + Clone + 'static,
 {
+    let kind = match kind {
+        EthSubscriptionKind::Standard(kind) => kind,
+        EthSubscriptionKind::Finality(kind) => {
+            let Some(headers) = pubsub.finality_headers(kind) else {
+                return Err(internal_rpc_err("finality is not tracked by this node").into())
+            };
+            // yields the current marker first, if already known, and then every update
+            let stream = WatchStream::new(headers).filter_map(|header| async move {
+                header.map(|header| {
+                    EthSubscriptionResult::Header(Box::new(
+                        reth_rpc_types_compat::block::from_primitive_with_hash(header),
+                    ))
+                })
+            });
+            // the watch channel only ever holds the latest marker
+            let label = match kind {
+                FinalitySubscriptionKind::FinalizedHeads => "finalizedHeads",
+                FinalitySubscriptionKind::SafeHeads => "safeHeads",
+            };
+            let buffer = config.notification_buffer(label, false);
+            return pipe_from_stream(accepted_sink, Box::pin(stream), buffer).await
+        }
+    };
+
     match kind {
         SubscriptionKind::NewHeads => {
             let stream = pubsub
                 .new_headers_stream()
                 .map(|block| EthSubscriptionResult::Header(Box::new(block.into())));
-            pipe_from_stream(accepted_sink, stream).await
+            let buffer = config.notification_buffer("newHeads", true);
+            pipe_from_stream(accepted_sink, stream, buffer).await
         }
         SubscriptionKind::Logs => {
             // if no params are provided, used default filter params
@@ -120,7 +174,8 @@ where
             };
             let stream =
                 pubsub.log_stream(filter).map(|log| EthSubscriptionResult::Log(Box::new(log)));
-            pipe_from_stream(accepted_sink, stream).await
+            let buffer = config.notification_buffer("logs", false);
+            pipe_from_stream(accepted_sink, stream, buffer).await
         }
         SubscriptionKind::NewPendingTransactions => {
             if let Some(params) = params {
@@ -134,7 +189,8 @@ where
                                 ),
                             ))
                         });
-                        return pipe_from_stream(accepted_sink, stream).await
+                        let buffer = config.notification_buffer("newPendingTransactions", false);
+                        return pipe_from_stream(accepted_sink, stream, buffer).await
                     }
                     Params::Bool(false) | Params::None => {
                         // only hashes requested
@@ -151,7 +207,8 @@ where
             let stream = pubsub
                 .pending_transaction_hashes_stream()
                 .map(EthSubscriptionResult::TransactionHash);
-            pipe_from_stream(accepted_sink, stream).await
+            let buffer = config.notification_buffer("newPendingTransactions", false);
+            pipe_from_stream(accepted_sink, stream, buffer).await
         }
         SubscriptionKind::Syncing => {
             // get new block subscription
@@ -189,37 +246,193 @@ where
 }
 
 /// Pipes all stream items to the subscription sink.
+///
+/// The stream is drained while the subscriber is busy, so a slow subscriber doesn't hold back the
+/// stream. Items that can't be sent right away are kept in the given buffer.
 async fn pipe_from_stream<T, St>(
     sink: SubscriptionSink,
     mut stream: St,
+    mut buffer: NotificationBuffer,
 ) -> Result<(), jsonrpsee::core::Error>
 where
     St: Stream<Item = T> + Unpin,
     T: Serialize,
 {
+    let mut stream_ended = false;
+    let mut in_flight = std::pin::pin!(Fuse::terminated());
     loop {
+        if in_flight.is_terminated() {
+            match buffer.pop() {
+                Some(msg) => in_flight.set(sink.send(msg).fuse()),
+                None if stream_ended => break Ok(()),
+                None => {}
+            }
+        }
+
         tokio::select! {
             _ = sink.closed() => {
                 // connection dropped
                 break Ok(())
             },
-            maybe_item = stream.next() => {
-                let item = match maybe_item {
-                    Some(item) => item,
-                    None => {
-                        // stream ended
-                        break  Ok(())
-                    },
+            res = &mut in_flight, if !in_flight.is_terminated() => {
+                if res.is_err() {
+                    break Ok(())
+                }
+            }
+            maybe_item = stream.next(), if !stream_ended => {
+                let Some(item) = maybe_item else {
+                    // stream ended, send the remaining items
+                    stream_ended = true;
+                    continue
                 };
                 let msg = SubscriptionMessage::from_json(&item)?;
-                if sink.send(msg).await.is_err() {
-                    break Ok(());
+                if !buffer.push(msg) {
+                    break Ok(())
                 }
             }
         }
     }
 }
 
+/// Config for the buffering of subscription notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthPubSubConfig {
+    /// Maximum number of notifications that are buffered per subscription while the subscriber
+    /// is busy.
+    pub buffer_capacity: usize,
+    /// What happens if the buffer of a subscription is full.
+    pub slow_consumer_policy: SlowConsumerPolicy,
+}
+
+impl EthPubSubConfig {
+    /// Sets the maximum number of notifications that are buffered per subscription.
+    pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buffer_capacity = capacity;
+        self
+    }
+
+    /// Sets what happens if the buffer of a subscription is full.
+    pub fn slow_consumer_policy(mut self, policy: SlowConsumerPolicy) -> Self {
+        self.slow_consumer_policy = policy;
+        self
+    }
+
+    /// Returns a new buffer for a subscription of the given kind.
+    fn notification_buffer(&self, kind: &'static str, is_new_heads: bool) -> NotificationBuffer {
+        NotificationBuffer {
+            queue: VecDeque::new(),
+            capacity: self.buffer_capacity.max(1),
+            policy: self.slow_consumer_policy,
+            is_new_heads,
+            metrics: SubscriptionMetrics::new_with_labels(&[("kind", kind)]),
+        }
+    }
+}
+
+impl Default for EthPubSubConfig {
+    fn default() -> Self {
+        Self { buffer_capacity: 256, slow_consumer_policy: SlowConsumerPolicy::default() }
+    }
+}
+
+/// What happens to a subscription if its subscriber can't keep up with the notifications.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SlowConsumerPolicy {
+    /// Drop the oldest buffered notification.
+    #[default]
+    DropOldest,
+    /// End the subscription.
+    Disconnect,
+    /// Drop all buffered headers of `newHeads` subscriptions, so the subscriber continues with the
+    /// latest header, and drop the oldest buffered notification of other subscriptions.
+    CoalesceNewHeads,
+}
+
+impl SlowConsumerPolicy {
+    /// Returns the name of the policy.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            SlowConsumerPolicy::DropOldest => "drop-oldest",
+            SlowConsumerPolicy::Disconnect => "disconnect",
+            SlowConsumerPolicy::CoalesceNewHeads => "coalesce-new-heads",
+        }
+    }
+}
+
+impl fmt::Display for SlowConsumerPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SlowConsumerPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop-oldest" => Ok(SlowConsumerPolicy::DropOldest),
+            "disconnect" => Ok(SlowConsumerPolicy::Disconnect),
+            "coalesce-new-heads" => Ok(SlowConsumerPolicy::CoalesceNewHeads),
+            _ => Err(format!(
+                "invalid slow consumer policy {s}, expected one of drop-oldest, disconnect, \
+                 coalesce-new-heads"
+            )),
+        }
+    }
+}
+
+/// The notifications of a subscription that weren't sent yet.
+struct NotificationBuffer {
+    queue: VecDeque<SubscriptionMessage>,
+    capacity: usize,
+    policy: SlowConsumerPolicy,
+    /// Whether the notifications are the headers of a `newHeads` subscription.
+    is_new_heads: bool,
+    metrics: SubscriptionMetrics,
+}
+
+impl NotificationBuffer {
+    /// Buffers the notification, applying the slow consumer policy if the buffer is full.
+    ///
+    /// Returns false if the subscription should be ended.
+    fn push(&mut self, msg: SubscriptionMessage) -> bool {
+        if self.queue.len() >= self.capacity {
+            match self.policy {
+                SlowConsumerPolicy::Disconnect => {
+                    self.metrics.dropped_notifications.increment(self.queue.len() as u64 + 1);
+                    self.metrics.slow_consumer_disconnects.increment(1);
+                    return false
+                }
+                SlowConsumerPolicy::CoalesceNewHeads if self.is_new_heads => {
+                    self.metrics.dropped_notifications.increment(self.queue.len() as u64);
+                    self.queue.clear();
+                }
+                SlowConsumerPolicy::DropOldest | SlowConsumerPolicy::CoalesceNewHeads => {
+                    self.metrics.dropped_notifications.increment(1);
+                    self.queue.pop_front();
+                }
+            }
+        }
+        self.queue.push_back(msg);
+        true
+    }
+
+    fn pop(&mut self) -> Option<SubscriptionMessage> {
+        self.queue.pop_front()
+    }
+}
+
+/// Metrics of the subscriptions of a kind.
+#[derive(Metrics)]
+#[metrics(scope = "rpc.eth_subscriptions")]
+struct SubscriptionMetrics {
+    /// The number of notifications dropped because the subscriber was too slow.
+    dropped_notifications: Counter,
+    /// The number of subscriptions ended because the subscriber was too slow.
+    slow_consumer_disconnects: Counter,
+}
+
 impl<Provider, Pool, Events, Network> std::fmt::Debug
     for EthPubSub<Provider, Pool, Events, Network>
 {
@@ -288,6 +501,17 @@ where
     Network: NetworkInfo + 'static,
     Pool: 'static,
 {
+    /// Returns the receiver of the finality marker tracked for the given subscription kind, if the
+    /// node tracks it.
+    fn finality_headers(&self, kind: FinalitySubscriptionKind) -> Option<ForkChoiceNotifications> {
+        match kind {
+            FinalitySubscriptionKind::FinalizedHeads => {
+                self.chain_events.subscribe_finalized_block()
+            }
+            FinalitySubscriptionKind::SafeHeads => self.chain_events.subscribe_safe_block(),
+        }
+    }
+
     /// Returns a stream that yields all new RPC blocks.
     fn new_headers_stream(&self) -> impl Stream<Item = Header> {
         self.chain_events.canonical_state_stream().flat_map(|new_chain| {
@@ -319,3 +543,50 @@ where
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_all(buffer: &mut NotificationBuffer, items: impl IntoIterator<Item = u64>) -> bool {
+        items.into_iter().all(|item| buffer.push(SubscriptionMessage::from_json(&item).unwrap()))
+    }
+
+    fn buffered(buffer: &mut NotificationBuffer) -> usize {
+        std::iter::from_fn(|| buffer.pop()).count()
+    }
+
+    #[test]
+    fn slow_consumer_policies() {
+        let config = EthPubSubConfig::default().buffer_capacity(2);
+
+        let mut buffer = config.notification_buffer("logs", false);
+        assert!(push_all(&mut buffer, 0..5));
+        assert_eq!(buffered(&mut buffer), 2);
+
+        let config = config.slow_consumer_policy(SlowConsumerPolicy::Disconnect);
+        let mut buffer = config.notification_buffer("logs", false);
+        assert!(push_all(&mut buffer, 0..2));
+        assert!(!push_all(&mut buffer, 2..3));
+
+        let config = config.slow_consumer_policy(SlowConsumerPolicy::CoalesceNewHeads);
+        let mut buffer = config.notification_buffer("newHeads", true);
+        assert!(push_all(&mut buffer, 0..3));
+        assert_eq!(buffered(&mut buffer), 1);
+        let mut buffer = config.notification_buffer("logs", false);
+        assert!(push_all(&mut buffer, 0..3));
+        assert_eq!(buffered(&mut buffer), 2);
+    }
+
+    #[test]
+    fn parse_slow_consumer_policy() {
+        for policy in [
+            SlowConsumerPolicy::DropOldest,
+            SlowConsumerPolicy::Disconnect,
+            SlowConsumerPolicy::CoalesceNewHeads,
+        ] {
+            assert_eq!(policy.to_string().parse::<SlowConsumerPolicy>(), Ok(policy));
+        }
+        assert!("block".parse::<SlowConsumerPolicy>().is_err());
+    }
+}
@@ -5,13 +5,13 @@ use reth_primitives::{
     revm::env::{fill_tx_env, fill_tx_env_with_recovered},
     Address, TransactionSigned, TransactionSignedEcRecovered, TxHash, B256, U256,
 };
+use reth_revm::EvmConfig;
 use reth_rpc_types::{
     state::{AccountOverride, StateOverride},
     BlockOverrides, CallRequest,
 };
 use revm::{
     db::CacheDB,
-    precompile::{Precompiles, SpecId as PrecompilesSpecId},
     primitives::{BlockEnv, CfgEnv, Env, ResultAndState, SpecId, TransactTo, TxEnv},
     Database, Inspector,
 };
@@ -110,27 +110,39 @@ impl FillableTransaction for TransactionSigned {
     }
 }
 
-/// Returns the addresses of the precompiles corresponding to the SpecId.
+/// Returns the addresses of the precompiles corresponding to the SpecId, including the custom
+/// precompiles of the [EvmConfig].
 #[inline]
-pub(crate) fn get_precompiles(spec_id: SpecId) -> impl IntoIterator<Item = Address> {
-    let spec = PrecompilesSpecId::from_spec_id(spec_id);
-    Precompiles::new(spec).addresses().into_iter().copied().map(Address::from)
+pub(crate) fn get_precompiles(
+    evm_config: &EvmConfig,
+    spec_id: SpecId,
+) -> impl IntoIterator<Item = Address> {
+    evm_config.precompiles(spec_id).addresses().copied().map(Address::from).collect::<Vec<_>>()
 }
 
 /// Executes the [Env] against the given [Database] without committing state changes.
-pub(crate) fn transact<DB>(db: DB, env: Env) -> EthResult<(ResultAndState, Env)>
+pub(crate) fn transact<DB>(
+    evm_config: &EvmConfig,
+    db: DB,
+    env: Env,
+) -> EthResult<(ResultAndState, Env)>
 where
     DB: Database,
     <DB as Database>::Error: Into<EthApiError>,
 {
     let mut evm = revm::EVM::with_env(env);
     evm.database(db);
-    let res = evm.transact()?;
+    let res = evm_config.transact(&mut evm)?;
     Ok((res, evm.env))
 }
 
 /// Executes the [Env] against the given [Database] without committing state changes.
-pub(crate) fn inspect<DB, I>(db: DB, env: Env, inspector: I) -> EthResult<(ResultAndState, Env)>
+pub(crate) fn inspect<DB, I>(
+    evm_config: &EvmConfig,
+    db: DB,
+    env: Env,
+    inspector: I,
+) -> EthResult<(ResultAndState, Env)>
 where
     DB: Database,
     <DB as Database>::Error: Into<EthApiError>,
@@ -138,7 +150,7 @@ where
 {
     let mut evm = revm::EVM::with_env(env);
     evm.database(db);
-    let res = evm.inspect(inspector)?;
+    let res = evm_config.inspect(&mut evm, inspector)?;
     Ok((res, evm.env))
 }
 
@@ -147,6 +159,7 @@ where
 /// Even though [Database] is also implemented on `&mut`
 /// this is still useful if there are certain trait bounds on the Inspector's database generic type
 pub(crate) fn inspect_and_return_db<DB, I>(
+    evm_config: &EvmConfig,
     db: DB,
     env: Env,
     inspector: I,
@@ -158,7 +171,7 @@ where
 {
     let mut evm = revm::EVM::with_env(env);
     evm.database(db);
-    let res = evm.inspect(inspector)?;
+    let res = evm_config.inspect(&mut evm, inspector)?;
     let db = evm.take_db();
     Ok((res, evm.env, db))
 }
@@ -171,6 +184,7 @@ where
 /// Note: This assumes the target transaction is in the given iterator.
 /// Returns the index of the target transaction in the given iterator.
 pub(crate) fn replay_transactions_until<DB, I, Tx>(
+    evm_config: &EvmConfig,
     db: &mut CacheDB<DB>,
     cfg: CfgEnv,
     block_env: BlockEnv,
@@ -194,7 +208,7 @@ where
         }
 
         tx.try_fill_tx_env(&mut evm.env.tx)?;
-        let res = evm.transact()?;
+        let res = evm_config.transact(&mut evm)?;
         evm.db.as_mut().expect("is set").commit(res.state);
         index += 1;
     }
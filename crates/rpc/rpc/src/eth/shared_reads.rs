@@ -0,0 +1,94 @@
+//! A read cache that is shared by calls simulated concurrently on the same state.
+
+use reth_primitives::{Address, B256, U256};
+use revm::{
+    primitives::{AccountInfo, Bytecode},
+    DatabaseRef,
+};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, RwLock},
+};
+
+/// Caches the reads of a [DatabaseRef] for all its clones.
+///
+/// Calls that are simulated concurrently on the same state mostly read the same accounts and
+/// storage, e.g. of popular tokens and routers, so every value is only read from the underlying
+/// database once.
+#[derive(Debug)]
+pub(crate) struct SharedReads<DB> {
+    inner: Arc<SharedReadsInner<DB>>,
+}
+
+impl<DB> SharedReads<DB> {
+    /// Creates a new shared cache of the reads of the database.
+    pub(crate) fn new(db: DB) -> Self {
+        Self {
+            inner: Arc::new(SharedReadsInner {
+                db,
+                accounts: Default::default(),
+                storage: Default::default(),
+                contracts: Default::default(),
+                block_hashes: Default::default(),
+            }),
+        }
+    }
+}
+
+impl<DB> Clone for SharedReads<DB> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+#[derive(Debug)]
+struct SharedReadsInner<DB> {
+    db: DB,
+    accounts: RwLock<HashMap<Address, Option<AccountInfo>>>,
+    storage: RwLock<HashMap<(Address, U256), U256>>,
+    contracts: RwLock<HashMap<B256, Bytecode>>,
+    block_hashes: RwLock<HashMap<U256, B256>>,
+}
+
+/// Returns the cached value of the key, or reads and caches it.
+fn read_through<K, V, E>(
+    cache: &RwLock<HashMap<K, V>>,
+    key: K,
+    read: impl FnOnce() -> Result<V, E>,
+) -> Result<V, E>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    if let Some(value) = cache.read().unwrap_or_else(|err| err.into_inner()).get(&key) {
+        return Ok(value.clone())
+    }
+    // concurrent misses of the same key both read it, which is cheaper than holding the write
+    // lock during the read
+    let value = read()?;
+    cache.write().unwrap_or_else(|err| err.into_inner()).insert(key, value.clone());
+    Ok(value)
+}
+
+impl<DB: DatabaseRef> DatabaseRef for SharedReads<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        read_through(&self.inner.accounts, address, || self.inner.db.basic_ref(address))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        read_through(&self.inner.contracts, code_hash, || self.inner.db.code_by_hash_ref(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        read_through(&self.inner.storage, (address, index), || {
+            self.inner.db.storage_ref(address, index)
+        })
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        read_through(&self.inner.block_hashes, number, || self.inner.db.block_hash_ref(number))
+    }
+}
@@ -0,0 +1,512 @@
+use http::{header, HeaderValue, Request, Response, StatusCode};
+use http_body::Limited;
+use hyper::Body;
+use parking_lot::{Mutex, RwLock};
+use reth_rpc_types::ApiKey;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
+use tower::{Layer, Service};
+
+/// The header that carries the API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The query parameter that carries the API key, for consumers that can't set headers.
+pub const API_KEY_QUERY_PARAM: &str = "api_key";
+
+/// Error code of calls to a namespace the API key has no access to.
+const NAMESPACE_NOT_ALLOWED_CODE: i32 = -32004;
+
+/// Error code of calls that exceed the rate limit of the API key.
+const RATE_LIMITED_CODE: i32 = -32005;
+
+/// Error code of calls with invalid params.
+const INVALID_PARAMS_CODE: i32 = -32602;
+
+/// Methods whose first param is a call request with an optional `gas` field.
+const CALL_METHODS: [&str; 5] =
+    ["eth_call", "eth_estimateGas", "eth_createAccessList", "debug_traceCall", "trace_call"];
+
+/// The registered API keys.
+///
+/// The registry is shared by the [`ApiKeyLayer`] of the servers and the `admin_` API key methods,
+/// so keys can be added and removed while the servers are running.
+#[derive(Clone, Debug, Default)]
+pub struct ApiKeys {
+    inner: Arc<RwLock<HashMap<String, Arc<ApiKeyEntry>>>>,
+}
+
+impl ApiKeys {
+    /// Creates a new registry with the given keys.
+    pub fn new(keys: impl IntoIterator<Item = ApiKey>) -> Self {
+        let keys = Self::default();
+        for key in keys {
+            keys.insert(key);
+        }
+        keys
+    }
+
+    /// Inserts the key, replacing the key with the same value.
+    ///
+    /// Returns true if the key was replaced.
+    pub fn insert(&self, key: ApiKey) -> bool {
+        self.inner.write().insert(key.key.clone(), Arc::new(ApiKeyEntry::new(key))).is_some()
+    }
+
+    /// Removes the key.
+    ///
+    /// Returns true if the key was registered.
+    pub fn remove(&self, key: &str) -> bool {
+        self.inner.write().remove(key).is_some()
+    }
+
+    /// Returns all registered keys.
+    pub fn list(&self) -> Vec<ApiKey> {
+        let mut keys =
+            self.inner.read().values().map(|entry| entry.key.clone()).collect::<Vec<_>>();
+        keys.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        keys
+    }
+
+    fn get(&self, key: &str) -> Option<Arc<ApiKeyEntry>> {
+        self.inner.read().get(key).cloned()
+    }
+}
+
+/// A registered key and the state of its rate limit.
+#[derive(Debug)]
+struct ApiKeyEntry {
+    key: ApiKey,
+    rate_limiter: Option<Mutex<RateLimiter>>,
+}
+
+impl ApiKeyEntry {
+    fn new(key: ApiKey) -> Self {
+        let rate_limiter = key.requests_per_second.map(|rps| Mutex::new(RateLimiter::new(rps)));
+        Self { key, rate_limiter }
+    }
+
+    /// Takes one token per call from the rate limit of the key.
+    fn try_acquire(&self, calls: usize) -> bool {
+        self.rate_limiter.as_ref().map_or(true, |limiter| limiter.lock().try_acquire(calls))
+    }
+}
+
+/// A token bucket that holds up to one second of calls.
+#[derive(Debug)]
+struct RateLimiter {
+    per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn new(per_second: u32) -> Self {
+        let per_second = per_second as f64;
+        Self { per_second, tokens: per_second, last_refill: Instant::now() }
+    }
+
+    fn try_acquire(&mut self, calls: usize) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.per_second);
+        self.last_refill = now;
+
+        let calls = calls as f64;
+        if self.tokens < calls {
+            return false
+        }
+        self.tokens -= calls;
+        true
+    }
+}
+
+/// An Http middleware layer that authenticates requests by API key and restricts them to the
+/// namespaces, rate limit and call gas of their key.
+///
+/// The key is read from the `X-Api-Key` header or the `api_key` query parameter. Requests without
+/// a registered key are rejected with `401 Unauthorized`.
+///
+/// The body of every request is buffered to check its calls, so a batch is either forwarded or
+/// rejected as a whole, and every call of a batch counts against the rate limit. Calls without a
+/// `gas` field are capped at the maximum call gas of the key.
+///
+/// Messages of a WebSocket connection can't be inspected by an Http middleware, so WebSocket
+/// upgrades are only accepted for keys without restrictions.
+#[derive(Clone, Debug)]
+pub struct ApiKeyLayer {
+    keys: ApiKeys,
+    max_request_size: u32,
+}
+
+impl ApiKeyLayer {
+    /// Creates a new layer that checks requests against the given keys.
+    pub fn new(keys: ApiKeys) -> Self {
+        Self { keys, max_request_size: 10 * 1024 * 1024 }
+    }
+
+    /// Configures the maximum size of a request body in bytes, which should match the server.
+    ///
+    /// Default is 10MB.
+    pub fn max_request_size(mut self, max_request_size: u32) -> Self {
+        self.max_request_size = max_request_size;
+        self
+    }
+}
+
+impl<S> Layer<S> for ApiKeyLayer {
+    type Service = ApiKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyService { keys: self.keys.clone(), max_request_size: self.max_request_size, inner }
+    }
+}
+
+/// The implementation of the [`ApiKeyLayer`] middleware.
+#[derive(Clone, Debug)]
+pub struct ApiKeyService<S> {
+    keys: ApiKeys,
+    max_request_size: u32,
+    /// Recipient of permitted Http requests
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for ApiKeyService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // the inner service was polled ready, so it's the one that has to handle the request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let Some(entry) = api_key(&req).and_then(|key| self.keys.get(&key)) else {
+            let response = plain_response(StatusCode::UNAUTHORIZED, "Missing or invalid API key");
+            return Box::pin(async move { Ok::<_, S::Error>(response) })
+        };
+
+        if is_upgrade_request(&req) {
+            if !entry.key.is_unrestricted() {
+                let response = plain_response(
+                    StatusCode::FORBIDDEN,
+                    "WebSocket connections are not permitted for restricted API keys",
+                );
+                return Box::pin(async move { Ok::<_, S::Error>(response) })
+            }
+            return Box::pin(inner.call(req))
+        }
+
+        let max_request_size = self.max_request_size as usize;
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = match hyper::body::to_bytes(Limited::new(body, max_request_size)).await {
+                Ok(body) => body,
+                Err(_) => {
+                    return Ok(plain_response(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "Request body is too large",
+                    ))
+                }
+            };
+
+            let body = match check_request(&entry, &body) {
+                Ok(Some(rewritten)) => Body::from(rewritten),
+                Ok(None) => Body::from(body),
+                Err(response) => return Ok(response),
+            };
+            inner.call(Request::from_parts(parts, body)).await
+        })
+    }
+}
+
+/// Checks the calls of the request body against the key.
+///
+/// Returns the rewritten body if the gas of calls was capped, and the error response if any call
+/// is not permitted. Bodies that aren't valid JSON-RPC are left to the server to reject.
+fn check_request(entry: &ApiKeyEntry, body: &[u8]) -> Result<Option<Vec<u8>>, Response<Body>> {
+    let Ok(mut request) = serde_json::from_slice::<Value>(body) else { return Ok(None) };
+    let is_batch = request.is_array();
+    let calls = match &mut request {
+        Value::Array(calls) => calls.iter_mut().collect::<Vec<_>>(),
+        call @ Value::Object(_) => vec![call],
+        _ => return Ok(None),
+    };
+    let ids =
+        calls.iter().map(|call| call.get("id").cloned().unwrap_or(Value::Null)).collect::<Vec<_>>();
+
+    let mut rewritten = false;
+    for call in calls.iter_mut() {
+        let Some(method) = call.get("method").and_then(Value::as_str) else { continue };
+        let namespace = method.split_once('_').map_or(method, |(namespace, _)| namespace);
+        if !entry.key.allows_namespace(namespace) {
+            let message = format!("namespace {namespace} is not permitted for this API key");
+            return Err(error_response(
+                StatusCode::OK,
+                is_batch,
+                ids,
+                NAMESPACE_NOT_ALLOWED_CODE,
+                &message,
+            ))
+        }
+
+        if let Some(max_call_gas) = entry.key.max_call_gas {
+            if CALL_METHODS.contains(&method) {
+                match cap_call_gas(call, max_call_gas) {
+                    Ok(capped) => rewritten |= capped,
+                    Err(message) => {
+                        return Err(error_response(
+                            StatusCode::OK,
+                            is_batch,
+                            ids,
+                            INVALID_PARAMS_CODE,
+                            &message,
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    if !entry.try_acquire(calls.len()) {
+        return Err(error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            is_batch,
+            ids,
+            RATE_LIMITED_CODE,
+            "rate limit of the API key exceeded",
+        ))
+    }
+
+    Ok(rewritten.then(|| serde_json::to_vec(&request).expect("serializable")))
+}
+
+/// Sets the gas of the call request to the maximum if it has none.
+///
+/// Returns true if the call was modified, and an error if the gas of the call exceeds the maximum.
+fn cap_call_gas(call: &mut Value, max_call_gas: u64) -> Result<bool, String> {
+    let Some(request) = call.get_mut("params").and_then(|params| params.get_mut(0)) else {
+        return Ok(false)
+    };
+    let Some(request) = request.as_object_mut() else { return Ok(false) };
+
+    match request.get("gas") {
+        None | Some(Value::Null) => {
+            request.insert("gas".to_string(), Value::String(format!("{max_call_gas:#x}")));
+            Ok(true)
+        }
+        Some(gas) => {
+            let gas = gas
+                .as_str()
+                .and_then(|gas| gas.strip_prefix("0x"))
+                .and_then(|gas| u64::from_str_radix(gas, 16).ok());
+            match gas {
+                Some(gas) if gas <= max_call_gas => Ok(false),
+                Some(gas) => Err(format!(
+                    "call gas {gas} exceeds the maximum of {max_call_gas} for this API key"
+                )),
+                // let the server reject malformed values
+                None => Ok(false),
+            }
+        }
+    }
+}
+
+/// Returns the API key of the request, from the header or the query parameter.
+fn api_key(req: &Request<Body>) -> Option<String> {
+    if let Some(key) = req.headers().get(API_KEY_HEADER) {
+        return key.to_str().ok().map(str::to_string)
+    }
+    req.uri().query()?.split('&').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        (name == API_KEY_QUERY_PARAM).then(|| value.to_string())
+    })
+}
+
+/// Returns true if the request is a WebSocket upgrade.
+fn is_upgrade_request(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(header::UPGRADE)
+        .and_then(|upgrade| upgrade.to_str().ok())
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+}
+
+fn plain_response(status: StatusCode, body: &'static str) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+}
+
+/// Returns a JSON-RPC error for every call of the request.
+fn error_response(
+    status: StatusCode,
+    is_batch: bool,
+    ids: Vec<Value>,
+    code: i32,
+    message: &str,
+) -> Response<Body> {
+    let mut errors = ids.into_iter().map(
+        |id| json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+    );
+    let body = if is_batch {
+        Value::Array(errors.collect())
+    } else {
+        errors.next().unwrap_or(Value::Null)
+    };
+
+    let mut response = Response::new(Body::from(serde_json::to_vec(&body).expect("serializable")));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiKeyLayer, ApiKeys};
+    use http::{Method, Request, StatusCode};
+    use hyper::{body, Body};
+    use jsonrpsee::{
+        server::{ServerBuilder, ServerHandle},
+        types::Params,
+        RpcModule,
+    };
+    use reth_rpc_types::ApiKey;
+    use serde_json::{json, Value};
+    use std::net::SocketAddr;
+
+    const ADDR: &str = "127.0.0.1:8552";
+
+    #[tokio::test]
+    async fn test_api_key_layer() {
+        // We group all tests into one to avoid concurrently spawning servers on the same port.
+        let keys = ApiKeys::new([
+            ApiKey {
+                key: "internal".to_string(),
+                namespaces: vec![],
+                requests_per_second: None,
+                max_call_gas: None,
+            },
+            ApiKey {
+                key: "public".to_string(),
+                namespaces: vec!["eth".to_string()],
+                requests_per_second: Some(1),
+                max_call_gas: Some(1_000_000),
+            },
+        ]);
+        let server = spawn_server(keys.clone()).await;
+
+        missing_key().await;
+        namespaces().await;
+        call_gas().await;
+        rate_limit().await;
+
+        // removed keys are rejected right away
+        assert!(keys.remove("internal"));
+        let (status, _) = send_request(Some("internal"), rpc_call("debug_echo", json!([]))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        server.stop().unwrap();
+        server.stopped().await;
+    }
+
+    async fn missing_key() {
+        let (status, _) = send_request(None, rpc_call("eth_echo", json!([]))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send_request(Some("unknown"), rpc_call("eth_echo", json!([]))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    async fn namespaces() {
+        let (status, body) =
+            send_request(Some("internal"), rpc_call("debug_echo", json!([1]))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["result"], json!([1]));
+
+        let (status, body) = send_request(Some("public"), rpc_call("debug_echo", json!([]))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["error"]["code"], -32004);
+    }
+
+    async fn call_gas() {
+        // the gas of calls without gas is capped
+        let (_, body) =
+            send_request(Some("public"), rpc_call("eth_call", json!([{ "to": "0x01" }]))).await;
+        assert_eq!(body["result"], json!([{ "to": "0x01", "gas": "0xf4240" }]));
+
+        let (_, body) =
+            send_request(Some("public"), rpc_call("eth_call", json!([{ "gas": "0xf4241" }]))).await;
+        assert_eq!(body["error"]["code"], -32602);
+
+        // the gas of calls of unrestricted keys is left as is
+        let (_, body) =
+            send_request(Some("internal"), rpc_call("eth_call", json!([{ "to": "0x01" }]))).await;
+        assert_eq!(body["result"], json!([{ "to": "0x01" }]));
+    }
+
+    async fn rate_limit() {
+        // the bucket of the key holds a single call
+        let batch = json!([rpc_call("eth_echo", json!([])), rpc_call("eth_echo", json!([]))]);
+        let (status, body) = send_request(Some("public"), batch).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body[0]["error"]["code"], -32005);
+        assert_eq!(body[1]["error"]["code"], -32005);
+    }
+
+    fn rpc_call(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 })
+    }
+
+    async fn send_request(key: Option<&str>, body: Value) -> (StatusCode, Value) {
+        let client = hyper::Client::new();
+        let mut req = Request::builder()
+            .method(Method::POST)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .uri(format!("http://{ADDR}"));
+        if let Some(key) = key {
+            req = req.header("X-Api-Key", key);
+        }
+        let req = req.body(Body::from(body.to_string())).unwrap();
+
+        let res = client.request(req).await.unwrap();
+        let status = res.status();
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    /// Spawn a new RPC server equipped with an API key middleware.
+    async fn spawn_server(keys: ApiKeys) -> ServerHandle {
+        let middleware = tower::ServiceBuilder::default().layer(ApiKeyLayer::new(keys));
+        let server = ServerBuilder::default()
+            .set_middleware(middleware)
+            .build(ADDR.parse::<SocketAddr>().unwrap())
+            .await
+            .unwrap();
+
+        // Create a mock rpc module that echoes the params of the calls
+        let mut module = RpcModule::new(());
+        for method in ["eth_echo", "eth_call", "debug_echo"] {
+            module
+                .register_method(method, |params: Params<'_>, _| params.parse::<Value>())
+                .unwrap();
+        }
+
+        server.start(module)
+    }
+}
@@ -0,0 +1,551 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::{future::poll_fn, StreamExt};
+use http::{header, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri, Version};
+use http_body::Limited;
+use hyper::Body;
+use jsonrpsee::types::error::{TOO_BIG_BATCH_REQUEST_CODE, TOO_BIG_BATCH_RESPONSE_CODE};
+use serde::Deserialize;
+use serde_json::{json, value::RawValue, Value};
+use std::{
+    borrow::Cow,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// Error code of batches that exceed the cost limit.
+const LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+/// Error code of calls that failed in the server.
+const INTERNAL_ERROR_CODE: i32 = -32603;
+
+/// Returns the cost of a call to the given method, used for the cost limit of batches.
+///
+/// Calls that execute transactions or scan ranges of blocks cost more than plain lookups.
+pub fn method_cost(method: &str) -> u64 {
+    match method {
+        "eth_call" |
+        "eth_estimateGas" |
+        "eth_createAccessList" |
+        "eth_callBundle" |
+        "eth_callMany" |
+        "eth_getLogs" => 10,
+        _ if method.starts_with("debug_trace") || method.starts_with("trace_") => 50,
+        _ => 1,
+    }
+}
+
+/// Returns true if calls to the given method don't change the state of the node, and can be
+/// executed concurrently with other such calls.
+fn is_read_only(method: &str) -> bool {
+    let namespace = method.split_once('_').map_or(method, |(namespace, _)| namespace);
+    !(method.starts_with("eth_send") ||
+        method.starts_with("eth_newFilter") ||
+        method.starts_with("eth_newBlockFilter") ||
+        method.starts_with("eth_newPendingTransactionFilter") ||
+        method == "eth_uninstallFilter" ||
+        matches!(namespace, "admin" | "evm" | "miner" | "personal"))
+}
+
+/// An Http middleware layer that executes the calls of JSON-RPC batches concurrently.
+///
+/// The calls of a batch are dispatched to the server as individual requests, on up to
+/// `parallelism` tasks at a time, and their responses are combined into the batch response. Calls
+/// that change the state of the node, like `eth_sendRawTransaction`, are executed on their own
+/// once all previous calls completed, so later calls of the batch observe their effects.
+///
+/// Since the server only sees individual calls, the layer enforces the limits of batches itself:
+/// batches with more than `max_calls` calls, or whose calls cost more than the cost limit in total
+/// (see [method_cost]), are rejected, and the batch fails once the combined responses exceed the
+/// maximum response size. Only the method and id of the calls are parsed, the calls are forwarded
+/// to the server as they were sent.
+///
+/// Messages of a WebSocket connection can't be inspected by an Http middleware, so batches sent
+/// over WebSocket are executed by the server as usual.
+#[derive(Clone, Debug)]
+pub struct BatchLayer {
+    parallelism: usize,
+    max_calls: usize,
+    max_cost: Option<u64>,
+    max_request_size: u32,
+    max_response_size: u32,
+}
+
+impl BatchLayer {
+    /// Creates a new layer that executes up to `parallelism` calls of a batch at a time.
+    pub fn new(parallelism: usize) -> Self {
+        Self {
+            parallelism: parallelism.max(1),
+            max_calls: 100,
+            max_cost: None,
+            max_request_size: 10 * 1024 * 1024,
+            max_response_size: 10 * 1024 * 1024,
+        }
+    }
+
+    /// Configures the maximum number of calls of a batch.
+    ///
+    /// Default is 100.
+    pub fn max_calls(mut self, max_calls: usize) -> Self {
+        self.max_calls = max_calls.max(1);
+        self
+    }
+
+    /// Configures the maximum total cost of the calls of a batch.
+    ///
+    /// Default is no limit.
+    pub fn max_cost(mut self, max_cost: Option<u64>) -> Self {
+        self.max_cost = max_cost;
+        self
+    }
+
+    /// Configures the maximum size of a request body in bytes, which should match the server.
+    ///
+    /// Default is 10MB.
+    pub fn max_request_size(mut self, max_request_size: u32) -> Self {
+        self.max_request_size = max_request_size;
+        self
+    }
+
+    /// Configures the maximum size of the combined responses of a batch in bytes, which should
+    /// match the server.
+    ///
+    /// Default is 10MB.
+    pub fn max_response_size(mut self, max_response_size: u32) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+}
+
+impl<S> Layer<S> for BatchLayer {
+    type Service = BatchService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BatchService { layer: self.clone(), inner }
+    }
+}
+
+/// The implementation of the [`BatchLayer`] middleware.
+#[derive(Clone, Debug)]
+pub struct BatchService<S> {
+    layer: BatchLayer,
+    /// Recipient of the requests and the calls of batches
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for BatchService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // the inner service was polled ready, so it's the one that has to handle the request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if req.method() != Method::POST || req.headers().contains_key(header::UPGRADE) {
+            return Box::pin(inner.call(req))
+        }
+
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body =
+                match hyper::body::to_bytes(Limited::new(body, layer.max_request_size as usize))
+                    .await
+                {
+                    Ok(body) => body,
+                    Err(_) => {
+                        let mut response = Response::new(Body::from("Request body is too large"));
+                        *response.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                        return Ok(response)
+                    }
+                };
+
+            // single calls and invalid requests are left to the server
+            let calls = match parse_batch(&body) {
+                Some(calls) if calls.len() > 1 => calls,
+                _ => return inner.call(Request::from_parts(parts, Body::from(body))).await,
+            };
+
+            if calls.len() > layer.max_calls {
+                let message = format!(
+                    "batch of {} calls exceeds the limit of {}",
+                    calls.len(),
+                    layer.max_calls
+                );
+                return Ok(json_response(error_object(
+                    &Value::Null,
+                    TOO_BIG_BATCH_REQUEST_CODE,
+                    &message,
+                )))
+            }
+
+            if let Some(max_cost) = layer.max_cost {
+                let cost = calls.iter().filter_map(|call| call.method.as_deref()).map(method_cost);
+                let cost = cost.sum::<u64>();
+                if cost > max_cost {
+                    let message = format!("batch cost {cost} exceeds the limit of {max_cost}");
+                    let errors = calls
+                        .iter()
+                        .map(|call| error_object(&call.id, LIMIT_EXCEEDED_CODE, &message))
+                        .collect();
+                    return Ok(json_response(join_responses(errors)))
+                }
+            }
+
+            let head = Arc::new(RequestHead {
+                method: parts.method,
+                uri: parts.uri,
+                version: parts.version,
+                headers: parts.headers,
+            });
+            let mut batch = BatchResponse::new(layer.max_response_size as usize);
+            let mut concurrent = Vec::new();
+            for call in calls {
+                if call.method.as_deref().map_or(true, is_read_only) {
+                    concurrent.push(call);
+                    continue
+                }
+                // wait for all previous calls, then execute the call on its own
+                let calls = std::mem::take(&mut concurrent);
+                execute(inner.clone(), head.clone(), calls, layer.parallelism, &mut batch).await;
+                execute(inner.clone(), head.clone(), vec![call], 1, &mut batch).await;
+            }
+            execute(inner, head, concurrent, layer.parallelism, &mut batch).await;
+
+            let Some(responses) = batch.responses else {
+                let message = format!(
+                    "batch response exceeds the limit of {} bytes",
+                    layer.max_response_size
+                );
+                return Ok(json_response(error_object(
+                    &Value::Null,
+                    TOO_BIG_BATCH_RESPONSE_CODE,
+                    &message,
+                )))
+            };
+            if responses.is_empty() {
+                // the batch only consisted of notifications
+                return Ok(Response::new(Body::empty()))
+            }
+            Ok(json_response(join_responses(responses)))
+        })
+    }
+}
+
+/// A call of a batch.
+#[derive(Debug)]
+struct Call {
+    /// The method of the call, if the call has one.
+    method: Option<String>,
+    /// The id of the call, used for the error responses of the layer.
+    id: Value,
+    /// The call as it was sent.
+    body: Bytes,
+}
+
+/// The fields of a call the layer needs, the params are skipped.
+#[derive(Deserialize)]
+struct CallFields<'a> {
+    #[serde(borrow, default)]
+    method: Option<Cow<'a, str>>,
+    #[serde(default)]
+    id: Value,
+}
+
+/// Splits the body into its calls if it's a batch, without parsing the params of the calls.
+fn parse_batch(body: &[u8]) -> Option<Vec<Call>> {
+    if body.iter().find(|byte| !byte.is_ascii_whitespace()) != Some(&b'[') {
+        return None
+    }
+    let calls = serde_json::from_slice::<Vec<&RawValue>>(body).ok()?;
+    let calls = calls
+        .into_iter()
+        .map(|call| {
+            let fields = serde_json::from_str::<CallFields<'_>>(call.get()).ok();
+            let (method, id) =
+                fields.map_or((None, Value::Null), |f| (f.method.map(Cow::into_owned), f.id));
+            Call { method, id, body: Bytes::copy_from_slice(call.get().as_bytes()) }
+        })
+        .collect();
+    Some(calls)
+}
+
+/// The responses of the executed calls of a batch.
+#[derive(Debug)]
+struct BatchResponse {
+    /// The responses in the order of the calls, `None` once they exceeded the size limit.
+    responses: Option<Vec<Bytes>>,
+    /// The remaining size of the combined responses.
+    remaining: usize,
+}
+
+impl BatchResponse {
+    fn new(max_size: usize) -> Self {
+        Self { responses: Some(Vec::new()), remaining: max_size }
+    }
+
+    /// Adds a response, dropping all responses if the size limit is exceeded.
+    fn push(&mut self, response: Bytes) {
+        let Some(responses) = &mut self.responses else { return };
+        // the separator between the responses
+        let size = response.len() + 1;
+        if size > self.remaining {
+            self.responses = None;
+            return
+        }
+        self.remaining -= size;
+        responses.push(response);
+    }
+
+    fn is_exceeded(&self) -> bool {
+        self.responses.is_none()
+    }
+}
+
+/// The parts of the batch request that are shared by the requests of its calls.
+#[derive(Debug)]
+struct RequestHead {
+    method: Method,
+    uri: Uri,
+    version: Version,
+    headers: HeaderMap,
+}
+
+/// Executes the calls concurrently, and adds their responses in the order of the calls.
+///
+/// Notifications have no response. Nothing is executed once the responses exceeded the size
+/// limit, and the execution stops as soon as they exceed it.
+async fn execute<S>(
+    inner: S,
+    head: Arc<RequestHead>,
+    calls: Vec<Call>,
+    parallelism: usize,
+    batch: &mut BatchResponse,
+) where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send + 'static,
+{
+    if batch.is_exceeded() {
+        return
+    }
+    let calls = calls
+        .into_iter()
+        .map(|call| execute_call(inner.clone(), head.clone(), call))
+        .collect::<Vec<_>>();
+    let mut responses = futures::stream::iter(calls).buffered(parallelism);
+    while let Some(response) = responses.next().await {
+        if let Some(response) = response {
+            batch.push(response);
+        }
+        if batch.is_exceeded() {
+            return
+        }
+    }
+}
+
+/// Dispatches the call as an individual request on a new task.
+async fn execute_call<S>(mut inner: S, head: Arc<RequestHead>, call: Call) -> Option<Bytes>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Future: Send,
+    S::Error: Send + 'static,
+{
+    let mut req = Request::new(Body::empty());
+    *req.method_mut() = head.method.clone();
+    *req.uri_mut() = head.uri.clone();
+    *req.version_mut() = head.version;
+    *req.headers_mut() = head.headers.clone();
+    req.headers_mut().insert(header::CONTENT_LENGTH, HeaderValue::from(call.body.len()));
+    *req.body_mut() = Body::from(call.body);
+
+    let response = tokio::task::spawn(async move {
+        poll_fn(|cx| inner.poll_ready(cx)).await?;
+        inner.call(req).await
+    })
+    .await;
+
+    let body = match response {
+        Ok(Ok(response)) => hyper::body::to_bytes(response.into_body()).await.ok(),
+        _ => None,
+    };
+    match body {
+        // notifications have no response
+        Some(body) if body.is_empty() => None,
+        // the server responds with JSON, unless the call was rejected before it was parsed
+        Some(body) if serde_json::from_slice::<&RawValue>(&body).is_ok() => Some(body),
+        Some(body) => {
+            Some(error_object(&call.id, INTERNAL_ERROR_CODE, &String::from_utf8_lossy(&body)))
+        }
+        None => Some(error_object(&call.id, INTERNAL_ERROR_CODE, "call failed")),
+    }
+}
+
+fn error_object(id: &Value, code: i32, message: &str) -> Bytes {
+    let error =
+        json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } });
+    serde_json::to_vec(&error).expect("serializable").into()
+}
+
+/// Combines the responses into the JSON array of a batch response.
+fn join_responses(responses: Vec<Bytes>) -> Bytes {
+    let size = responses.iter().map(|response| response.len() + 1).sum::<usize>() + 1;
+    let mut body = BytesMut::with_capacity(size);
+    body.put_u8(b'[');
+    for (i, response) in responses.iter().enumerate() {
+        if i > 0 {
+            body.put_u8(b',');
+        }
+        body.put_slice(response);
+    }
+    body.put_u8(b']');
+    body.freeze()
+}
+
+fn json_response(body: Bytes) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_read_only, method_cost, BatchLayer};
+    use http::{Method, Request};
+    use hyper::{body, Body};
+    use jsonrpsee::{
+        server::{ServerBuilder, ServerHandle},
+        types::Params,
+        RpcModule,
+    };
+    use serde_json::{json, Value};
+    use std::{
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    const ADDR: &str = "127.0.0.1:8553";
+
+    #[test]
+    fn read_only_methods() {
+        assert!(is_read_only("eth_getBalance"));
+        assert!(is_read_only("debug_traceTransaction"));
+        assert!(!is_read_only("eth_sendRawTransaction"));
+        assert!(!is_read_only("eth_newFilter"));
+        assert!(!is_read_only("admin_addPeer"));
+        assert_eq!(method_cost("eth_getBalance"), 1);
+        assert_eq!(method_cost("eth_getLogs"), 10);
+        assert_eq!(method_cost("trace_filter"), 50);
+    }
+
+    #[tokio::test]
+    async fn test_batch_layer() {
+        let server = spawn_server().await;
+
+        // the sleeping calls are executed concurrently, and the counter observes the increments
+        // of all previous calls
+        let mut batch = (0..8).map(|id| call(id, "test_sleep")).collect::<Vec<_>>();
+        batch.push(call(8, "admin_increment"));
+        batch.push(call(9, "test_counter"));
+        batch.push(json!({ "jsonrpc": "2.0", "method": "test_sleep", "params": [] }));
+        let started = std::time::Instant::now();
+        let responses = send_request(Value::Array(batch)).await;
+        assert!(started.elapsed() < Duration::from_millis(8 * 200));
+
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 10);
+        for (id, response) in responses.iter().enumerate() {
+            assert_eq!(response["id"], id);
+        }
+        assert_eq!(responses[9]["result"], 1);
+
+        // batches above the cost limit are rejected
+        let batch = (0..3).map(|id| call(id, "trace_filter")).collect::<Vec<_>>();
+        let responses = send_request(Value::Array(batch)).await;
+        assert_eq!(responses[0]["error"]["code"], -32005);
+        assert_eq!(responses[2]["error"]["code"], -32005);
+
+        // batches with too many calls are rejected as a whole
+        let batch = (0..21).map(|id| call(id, "test_counter")).collect::<Vec<_>>();
+        let response = send_request(Value::Array(batch)).await;
+        assert_eq!(response["error"]["code"], -32010);
+
+        // the combined responses are bounded
+        let batch = (0..2).map(|id| call(id, "test_large")).collect::<Vec<_>>();
+        assert_eq!(send_request(Value::Array(batch)).await.as_array().unwrap().len(), 2);
+        let batch = (0..3).map(|id| call(id, "test_large")).collect::<Vec<_>>();
+        let response = send_request(Value::Array(batch)).await;
+        assert_eq!(response["error"]["code"], -32011);
+
+        server.stop().unwrap();
+        server.stopped().await;
+    }
+
+    fn call(id: u64, method: &str) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": [], "id": id })
+    }
+
+    async fn send_request(body: Value) -> Value {
+        let client = hyper::Client::new();
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .uri(format!("http://{ADDR}"))
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let res = client.request(req).await.unwrap();
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Spawn a new RPC server equipped with a batch middleware.
+    async fn spawn_server() -> ServerHandle {
+        let middleware = tower::ServiceBuilder::default()
+            .layer(BatchLayer::new(8).max_calls(20).max_cost(Some(100)).max_response_size(2500));
+        let server = ServerBuilder::default()
+            .set_middleware(middleware)
+            .build(ADDR.parse::<SocketAddr>().unwrap())
+            .await
+            .unwrap();
+
+        let mut module = RpcModule::new(Arc::new(AtomicU64::new(0)));
+        module
+            .register_async_method("test_sleep", |_: Params<'static>, _| async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                Ok::<_, jsonrpsee::types::ErrorObjectOwned>(true)
+            })
+            .unwrap();
+        module
+            .register_method("admin_increment", |_, counter| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                true
+            })
+            .unwrap();
+        module
+            .register_method("test_counter", |_, counter| counter.load(Ordering::SeqCst))
+            .unwrap();
+        module.register_method("trace_filter", |_, _| true).unwrap();
+        module.register_method("test_large", |_, _| "a".repeat(1000)).unwrap();
+
+        server.start(module)
+    }
+}
@@ -1,11 +1,19 @@
 use http::{HeaderMap, Response};
 
+mod api_keys;
 mod auth_layer;
+mod batch;
 mod jwt_secret;
 mod jwt_validator;
+mod streaming;
+mod trace_context;
+pub use api_keys::{ApiKeyLayer, ApiKeyService, ApiKeys, API_KEY_HEADER, API_KEY_QUERY_PARAM};
 pub use auth_layer::AuthLayer;
+pub use batch::{method_cost, BatchLayer, BatchService};
 pub use jwt_secret::{Claims, JwtError, JwtSecret};
 pub use jwt_validator::JwtAuthValidator;
+pub use streaming::{ResultStream, StreamingLayer, StreamingMethods, StreamingService};
+pub use trace_context::{TraceContextLayer, TraceContextService};
 
 /// General purpose trait to validate Http Authorization headers. It's supposed to be integrated as
 /// a validator trait into an [`AuthLayer`].
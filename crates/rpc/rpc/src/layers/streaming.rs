@@ -0,0 +1,295 @@
+use futures::{future::BoxFuture, stream::BoxStream, StreamExt};
+use http::{header, HeaderValue, Method, Request, Response, StatusCode};
+use http_body::Limited;
+use hyper::{body::Bytes, Body};
+use jsonrpsee::types::{ErrorObjectOwned, Params};
+use serde_json::{json, Value};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// A stream of the items of the array result of a call, in chunks.
+pub type ResultStream = BoxStream<'static, Result<Vec<Value>, ErrorObjectOwned>>;
+
+/// Methods whose array result can be streamed, instead of being materialized in full before the
+/// response is sent.
+pub trait StreamingMethods: Send + Sync + 'static {
+    /// Returns the stream of the result of a call to the given method, or `None` if the method
+    /// isn't streamed.
+    ///
+    /// The returned future resolves once the call is validated, an error of the future is sent as
+    /// the error response of the call.
+    fn stream(
+        &self,
+        method: &str,
+        params: Params<'_>,
+    ) -> Option<BoxFuture<'static, Result<ResultStream, ErrorObjectOwned>>>;
+}
+
+/// An Http middleware layer that streams the results of calls to [StreamingMethods].
+///
+/// The response is sent with chunked transfer encoding, and each chunk of the result is written
+/// to the response body as soon as it's available, so the full result is never held in memory. If
+/// the stream fails after the response has been started, the response body is aborted.
+///
+/// Batches are executed by the server as usual.
+#[derive(Clone)]
+pub struct StreamingLayer {
+    methods: Arc<dyn StreamingMethods>,
+    max_request_size: u32,
+}
+
+impl StreamingLayer {
+    /// Creates a new layer that streams the results of calls to the given methods.
+    pub fn new(methods: Arc<dyn StreamingMethods>) -> Self {
+        Self { methods, max_request_size: 10 * 1024 * 1024 }
+    }
+
+    /// Configures the maximum size of a request body in bytes, which should match the server.
+    ///
+    /// Default is 10MB.
+    pub fn max_request_size(mut self, max_request_size: u32) -> Self {
+        self.max_request_size = max_request_size;
+        self
+    }
+}
+
+impl std::fmt::Debug for StreamingLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingLayer")
+            .field("max_request_size", &self.max_request_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for StreamingLayer {
+    type Service = StreamingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        StreamingService { layer: self.clone(), inner }
+    }
+}
+
+/// The implementation of the [`StreamingLayer`] middleware.
+#[derive(Clone, Debug)]
+pub struct StreamingService<S> {
+    layer: StreamingLayer,
+    /// Recipient of the requests of calls that aren't streamed
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for StreamingService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // the inner service was polled ready, so it's the one that has to handle the request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if req.method() != Method::POST || req.headers().contains_key(header::UPGRADE) {
+            return Box::pin(inner.call(req))
+        }
+
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body =
+                match hyper::body::to_bytes(Limited::new(body, layer.max_request_size as usize))
+                    .await
+                {
+                    Ok(body) => body,
+                    Err(_) => {
+                        let mut response = Response::new(Body::from("Request body is too large"));
+                        *response.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                        return Ok(response)
+                    }
+                };
+
+            // batches, notifications and invalid requests are left to the server
+            let streamed = serde_json::from_slice::<jsonrpsee::types::Request<'_>>(&body)
+                .ok()
+                .and_then(|call| {
+                    let params = Params::new(call.params.as_ref().map(|params| params.get()));
+                    let stream = layer.methods.stream(&call.method, params)?;
+                    Some((serde_json::to_value(&call.id).unwrap_or(Value::Null), stream))
+                });
+            let Some((id, stream)) = streamed else {
+                return inner.call(Request::from_parts(parts, Body::from(body))).await
+            };
+
+            let stream = match stream.await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    let response = json!({ "jsonrpc": "2.0", "id": id, "error": err });
+                    return Ok(json_response(Body::from(
+                        serde_json::to_vec(&response).expect("serializable"),
+                    )))
+                }
+            };
+
+            let (sender, body) = Body::channel();
+            tokio::task::spawn(stream_result(sender, id, stream));
+            Ok(json_response(body))
+        })
+    }
+}
+
+/// Writes the response of the call with the items of the result stream to the body.
+async fn stream_result(mut sender: hyper::body::Sender, id: Value, mut stream: ResultStream) {
+    let prefix = format!(r#"{{"jsonrpc":"2.0","id":{id},"result":["#);
+    if sender.send_data(Bytes::from(prefix)).await.is_err() {
+        return
+    }
+
+    let mut first = true;
+    while let Some(items) = stream.next().await {
+        let items = match items {
+            Ok(items) => items,
+            Err(err) => {
+                debug!(target: "rpc::streaming", ?err, "Failed to stream result");
+                sender.abort();
+                return
+            }
+        };
+        let mut chunk = Vec::new();
+        for item in items {
+            if !std::mem::take(&mut first) {
+                chunk.push(b',');
+            }
+            serde_json::to_writer(&mut chunk, &item).expect("serializable");
+        }
+        if !chunk.is_empty() && sender.send_data(Bytes::from(chunk)).await.is_err() {
+            // the connection was closed
+            return
+        }
+    }
+
+    let _ = sender.send_data(Bytes::from_static(b"]}")).await;
+}
+
+fn json_response(body: Body) -> Response<Body> {
+    let mut response = Response::new(body);
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResultStream, StreamingLayer, StreamingMethods};
+    use futures::{future::BoxFuture, FutureExt, StreamExt};
+    use http::{Method, Request};
+    use hyper::{body, Body};
+    use jsonrpsee::{
+        server::{ServerBuilder, ServerHandle},
+        types::{ErrorObjectOwned, Params},
+        RpcModule,
+    };
+    use serde_json::{json, Value};
+    use std::{net::SocketAddr, sync::Arc};
+
+    const ADDR: &str = "127.0.0.1:8554";
+
+    /// Streams the numbers up to the given count in chunks of two.
+    struct Numbers;
+
+    impl StreamingMethods for Numbers {
+        fn stream(
+            &self,
+            method: &str,
+            params: Params<'_>,
+        ) -> Option<BoxFuture<'static, Result<ResultStream, ErrorObjectOwned>>> {
+            if method != "test_numbers" {
+                return None
+            }
+            let count = params.one::<u64>();
+            Some(
+                async move {
+                    let count = count?;
+                    if count > 100 {
+                        return Err(ErrorObjectOwned::owned(-32602, "too many", None::<()>))
+                    }
+                    let numbers = (0..count).map(Value::from).collect::<Vec<_>>();
+                    let chunks = numbers
+                        .chunks(2)
+                        .map(|chunk| Ok::<_, ErrorObjectOwned>(chunk.to_vec()))
+                        .collect::<Vec<_>>();
+                    Ok(futures::stream::iter(chunks).boxed())
+                }
+                .boxed(),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_layer() {
+        let server = spawn_server().await;
+
+        let response = send_request(call("test_numbers", json!([5]))).await;
+        assert_eq!(response, json!({ "jsonrpc": "2.0", "id": 1, "result": [0, 1, 2, 3, 4] }));
+
+        let response = send_request(call("test_numbers", json!([0]))).await;
+        assert_eq!(response["result"], json!([]));
+
+        // errors before the stream started are sent as error response
+        let response = send_request(call("test_numbers", json!([101]))).await;
+        assert_eq!(response["error"]["code"], -32602);
+
+        // other methods are served as usual
+        let response = send_request(call("test_hello", json!([]))).await;
+        assert_eq!(response["result"], "hello");
+
+        server.stop().unwrap();
+        server.stopped().await;
+    }
+
+    fn call(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 })
+    }
+
+    async fn send_request(body: Value) -> Value {
+        let client = hyper::Client::new();
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .uri(format!("http://{ADDR}"))
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let res = client.request(req).await.unwrap();
+        let body = body::to_bytes(res.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Spawn a new RPC server equipped with a streaming middleware.
+    async fn spawn_server() -> ServerHandle {
+        let middleware =
+            tower::ServiceBuilder::default().layer(StreamingLayer::new(Arc::new(Numbers)));
+        let server = ServerBuilder::default()
+            .set_middleware(middleware)
+            .build(ADDR.parse::<SocketAddr>().unwrap())
+            .await
+            .unwrap();
+
+        let mut module = RpcModule::new(());
+        module.register_method("test_hello", |_, _| "hello").unwrap();
+
+        server.start(module)
+    }
+}
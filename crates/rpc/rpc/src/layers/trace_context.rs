@@ -0,0 +1,96 @@
+use http::{HeaderMap, Request};
+use reth_tracing::otlp::{TraceContext, TRACEPARENT_HEADER, TRACESTATE_HEADER};
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::{debug_span, instrument::Instrumented, Instrument};
+
+/// An Http middleware layer that handles each request in a span, which is part of the trace of
+/// the caller if the request has a W3C `traceparent` header.
+///
+/// The spans of the calls of the request, e.g. of the validation of a submitted transaction, are
+/// children of the request span, so they are exported as part of the same trace, see
+/// [reth_tracing::otlp].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceContextLayer;
+
+impl<S> Layer<S> for TraceContextLayer {
+    type Service = TraceContextService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceContextService { inner }
+    }
+}
+
+/// The implementation of the [`TraceContextLayer`] middleware.
+#[derive(Debug, Clone)]
+pub struct TraceContextService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<Request<B>> for TraceContextService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Instrumented<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let span = debug_span!(target: "rpc", "rpc_request", path = %req.uri().path());
+        if let Some(context) = trace_context(req.headers()) {
+            context.set_as_parent(&span);
+        }
+        span.in_scope(|| self.inner.call(req)).instrument(span)
+    }
+}
+
+/// Returns the trace context of the caller from the headers of the request, if any.
+fn trace_context(headers: &HeaderMap) -> Option<TraceContext> {
+    let traceparent = headers.get(TRACEPARENT_HEADER)?.to_str().ok()?;
+    let tracestate = headers.get(TRACESTATE_HEADER).and_then(|value| value.to_str().ok());
+    TraceContext::from_headers(traceparent, tracestate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::BoxFuture;
+    use reth_tracing::tracing_subscriber::{registry, util::SubscriberInitExt};
+    use std::convert::Infallible;
+
+    /// Responds with the name of the span the request is handled in.
+    struct CurrentSpan;
+
+    impl Service<Request<()>> for CurrentSpan {
+        type Response = Option<&'static str>;
+        type Error = Infallible;
+        type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: Request<()>) -> Self::Future {
+            Box::pin(async {
+                Ok(tracing::Span::current().metadata().map(|metadata| metadata.name()))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn handles_requests_in_span() {
+        let _guard = registry().set_default();
+        let mut service = TraceContextLayer.layer(CurrentSpan);
+
+        let mut req = Request::new(());
+        req.headers_mut().insert(
+            TRACEPARENT_HEADER,
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".parse().unwrap(),
+        );
+        assert_eq!(service.call(req).await.unwrap(), Some("rpc_request"));
+    }
+}
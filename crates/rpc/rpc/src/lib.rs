@@ -25,6 +25,7 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 mod admin;
+mod builder;
 mod debug;
 mod engine;
 pub mod eth;
@@ -33,20 +34,31 @@ mod net;
 mod otterscan;
 mod reth;
 mod rpc;
+mod streaming;
 mod trace;
+mod tracing_limits;
+mod tx_status;
 mod txpool;
 mod web3;
-pub use admin::AdminApi;
+pub use admin::{AdminApi, AdminApiKeysApi};
 pub use blocking_pool::{BlockingTaskGuard, BlockingTaskPool};
+pub use builder::BuilderApi;
 pub use debug::DebugApi;
 pub use engine::{EngineApi, EngineEthApi};
 pub use eth::{EthApi, EthApiSpec, EthFilter, EthPubSub, EthSubscriptionIdProvider};
-pub use layers::{AuthLayer, AuthValidator, Claims, JwtAuthValidator, JwtError, JwtSecret};
+pub use layers::{
+    ApiKeyLayer, ApiKeyService, ApiKeys, AuthLayer, AuthValidator, BatchLayer, BatchService,
+    Claims, JwtAuthValidator, JwtError, JwtSecret, ResultStream, StreamingLayer, StreamingMethods,
+    StreamingService, TraceContextLayer, TraceContextService, API_KEY_HEADER, API_KEY_QUERY_PARAM,
+};
 pub use net::NetApi;
 pub use otterscan::OtterscanApi;
 pub use reth::RethApi;
 pub use rpc::RPCApi;
+pub use streaming::StreamingApi;
 pub use trace::TraceApi;
+pub use tracing_limits::TracingLimits;
+pub use tx_status::TransactionStatusApi;
 pub use txpool::TxPoolApi;
 pub use web3::Web3Api;
 pub mod blocking_pool;
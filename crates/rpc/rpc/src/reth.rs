@@ -1,39 +1,111 @@
 use crate::eth::error::{EthApiError, EthResult};
 use async_trait::async_trait;
-use jsonrpsee::core::RpcResult;
-use reth_interfaces::RethResult;
-use reth_primitives::{Address, BlockId, U256};
-use reth_provider::{BlockReaderIdExt, ChangeSetReader, StateProviderFactory};
+use futures::StreamExt;
+use jsonrpsee::{
+    core::RpcResult, server::SubscriptionMessage, PendingSubscriptionSink, SubscriptionSink,
+};
+use reth_interfaces::{RethError, RethResult};
+use reth_primitives::{
+    Address, BlockHash, BlockId, PruneSegment, ReceiptWithBloomRef, SealedBlockWithSenders, TxHash,
+    B256, U256, U64,
+};
+use reth_provider::{
+    BlockReaderIdExt, CanonStateSubscriptions, ChangeSetReader, HeaderAccumulatorReader,
+    PruneCheckpointReader, ReorgHistoryProvider, StateProviderFactory,
+};
 use reth_rpc_api::RethApiServer;
+use reth_rpc_types::{
+    AccountHistoryPage, HeaderAccumulatorProof, InclusionProof, ProofTarget, ProofsUpdate,
+    PruneSegmentStatus, ReorgEntry,
+};
+use reth_rpc_types_compat::proof::from_primitive_account_proof;
 use reth_tasks::TaskSpawner;
-use std::{collections::HashMap, future::Future, sync::Arc};
+use reth_trie::OrderedTrieProofs;
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
 use tokio::sync::oneshot;
+use tracing::debug;
+
+/// The maximum number of accounts a single `reth_subscribeProofs` subscription can watch.
+pub const MAX_PROOF_SUBSCRIPTION_TARGETS: usize = 256;
+
+/// The maximum number of storage slots of all accounts a single `reth_subscribeProofs`
+/// subscription can watch.
+pub const MAX_PROOF_SUBSCRIPTION_STORAGE_KEYS: usize = 1024;
+
+/// The maximum number of blocks a single `reth_getAccountHistory` page can contain.
+pub const MAX_ACCOUNT_HISTORY_PAGE_SIZE: usize = 1024;
+
+/// The number of tries whose inclusion proofs are cached, two per block at most.
+pub const INCLUSION_PROOF_CACHE_SIZE: u32 = 256;
+
+/// The segments reported by `reth_pruneStatus`.
+const PRUNE_SEGMENTS: [PruneSegment; 9] = [
+    PruneSegment::SenderRecovery,
+    PruneSegment::TransactionLookup,
+    PruneSegment::Receipts,
+    PruneSegment::ContractLogs,
+    PruneSegment::AccountHistory,
+    PruneSegment::StorageHistory,
+    PruneSegment::Headers,
+    PruneSegment::Transactions,
+    PruneSegment::HistoryExpiry,
+];
+
+/// The per-block tries inclusion proofs are served for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum InclusionTrie {
+    /// The transactions trie.
+    Transactions,
+    /// The receipts trie.
+    Receipts,
+}
 
 /// `reth` API implementation.
 ///
 /// This type provides the functionality for handling `reth` prototype RPC requests.
-pub struct RethApi<Provider> {
-    inner: Arc<RethApiInner<Provider>>,
+pub struct RethApi<Provider, Events> {
+    inner: Arc<RethApiInner<Provider, Events>>,
 }
 
 // === impl RethApi ===
 
-impl<Provider> RethApi<Provider> {
+impl<Provider, Events> RethApi<Provider, Events> {
     /// The provider that can interact with the chain.
     pub fn provider(&self) -> &Provider {
         &self.inner.provider
     }
 
     /// Create a new instance of the [RethApi]
-    pub fn new(provider: Provider, task_spawner: Box<dyn TaskSpawner>) -> Self {
-        let inner = Arc::new(RethApiInner { provider, task_spawner });
+    pub fn new(
+        provider: Provider,
+        chain_events: Events,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let inner = Arc::new(RethApiInner {
+            provider,
+            chain_events,
+            task_spawner,
+            inclusion_proofs: Mutex::new(LruMap::new(ByLength::new(INCLUSION_PROOF_CACHE_SIZE))),
+        });
         Self { inner }
     }
 }
 
-impl<Provider> RethApi<Provider>
+impl<Provider, Events> RethApi<Provider, Events>
 where
-    Provider: BlockReaderIdExt + ChangeSetReader + StateProviderFactory + 'static,
+    Provider: BlockReaderIdExt
+        + ChangeSetReader
+        + HeaderAccumulatorReader
+        + PruneCheckpointReader
+        + ReorgHistoryProvider
+        + StateProviderFactory
+        + 'static,
+    Events: CanonStateSubscriptions + 'static,
 {
     /// Executes the future on a new blocking task.
     async fn on_blocking_task<C, F, R>(&self, c: C) -> EthResult<R>
@@ -81,12 +153,290 @@ where
         )?;
         Ok(hash_map)
     }
+
+    /// Returns a page of the blocks in which the account changed, starting at the given block.
+    ///
+    /// The page size is capped at [MAX_ACCOUNT_HISTORY_PAGE_SIZE].
+    pub async fn account_history(
+        &self,
+        address: Address,
+        from_block: u64,
+        limit: Option<usize>,
+    ) -> EthResult<AccountHistoryPage> {
+        let limit =
+            limit.unwrap_or(MAX_ACCOUNT_HISTORY_PAGE_SIZE).min(MAX_ACCOUNT_HISTORY_PAGE_SIZE);
+        self.on_blocking_task(|this| async move {
+            // fetch one more block to determine the start of the next page
+            let mut blocks =
+                this.provider().account_change_blocks(address, from_block, limit + 1)?;
+            let next_block = (blocks.len() > limit).then(|| U64::from(blocks[limit]));
+            blocks.truncate(limit);
+            Ok(AccountHistoryPage {
+                blocks: blocks.into_iter().map(U64::from).collect(),
+                next_block,
+            })
+        })
+        .await
+    }
+
+    /// Returns the retention boundaries of all prune segments.
+    pub async fn prune_status(&self) -> EthResult<Vec<PruneSegmentStatus>> {
+        self.on_blocking_task(|this| async move {
+            PRUNE_SEGMENTS
+                .into_iter()
+                .map(|segment| {
+                    let checkpoint = this.provider().get_prune_checkpoint(segment)?;
+                    let pruned_block = checkpoint.and_then(|checkpoint| checkpoint.block_number);
+                    Ok(PruneSegmentStatus {
+                        segment: segment.to_string(),
+                        pruned_block: pruned_block.map(U64::from),
+                        pruned_transaction: checkpoint
+                            .and_then(|checkpoint| checkpoint.tx_number)
+                            .map(U64::from),
+                        earliest_available_block: pruned_block
+                            .map(|block_number| U64::from(block_number + 1)),
+                    })
+                })
+                .collect()
+        })
+        .await
+    }
+
+    /// Returns the most recent reorgs of the canonical chain, newest first.
+    pub async fn reorg_history(&self, limit: Option<usize>) -> EthResult<Vec<ReorgEntry>> {
+        // the history is guarded by the blockchain tree lock
+        self.on_blocking_task(|this| async move {
+            let history = this.inner.provider.reorg_history();
+            Ok(history
+                .into_iter()
+                .rev()
+                .take(limit.unwrap_or(usize::MAX))
+                .map(|event| ReorgEntry {
+                    timestamp: U64::from(event.timestamp),
+                    old_tip_number: U64::from(event.old_tip.number),
+                    old_tip_hash: event.old_tip.hash,
+                    new_tip_number: U64::from(event.new_tip.number),
+                    new_tip_hash: event.new_tip.hash,
+                    depth: U64::from(event.depth),
+                    dropped_transactions: event.dropped_transactions,
+                    duration_ms: U64::from(event.duration.as_millis() as u64),
+                })
+                .collect())
+        })
+        .await
+    }
+
+    /// Returns the root of the header accumulator over all completed epochs.
+    pub async fn header_accumulator_root(&self) -> EthResult<B256> {
+        self.on_blocking_task(|this| async move { Ok(this.provider().header_accumulator_root()?) })
+            .await
+    }
+
+    /// Returns the proof of the block in the header accumulator.
+    pub async fn header_accumulator_proof(
+        &self,
+        block_id: BlockId,
+    ) -> EthResult<Option<HeaderAccumulatorProof>> {
+        self.on_blocking_task(|this| async move { this.try_header_accumulator_proof(block_id) })
+            .await
+    }
+
+    fn try_header_accumulator_proof(
+        &self,
+        block_id: BlockId,
+    ) -> EthResult<Option<HeaderAccumulatorProof>> {
+        let Some(block_number) = self.provider().block_number_for_id(block_id)? else {
+            return Err(EthApiError::UnknownBlockNumber)
+        };
+        let Some(proof) = self.provider().header_accumulator_proof(block_number)? else {
+            return Ok(None)
+        };
+
+        Ok(Some(HeaderAccumulatorProof {
+            block_number: U64::from(proof.block_number),
+            block_hash: proof.record.block_hash,
+            total_difficulty: proof.record.total_difficulty,
+            epoch: U64::from(proof.epoch()),
+            epoch_root: proof.epoch_root,
+            epoch_proof: proof.epoch_proof,
+            accumulator_root: proof.accumulator_root,
+            accumulator_proof: proof.accumulator_proof,
+        }))
+    }
+
+    /// Returns the merkle proof of the transaction in the transactions trie of its block.
+    pub async fn transaction_inclusion_proof(
+        &self,
+        hash: TxHash,
+    ) -> EthResult<Option<InclusionProof>> {
+        self.on_blocking_task(|this| async move {
+            this.try_inclusion_proof(hash, InclusionTrie::Transactions)
+        })
+        .await
+    }
+
+    /// Returns the merkle proof of the receipt of the transaction in the receipts trie of its
+    /// block.
+    pub async fn receipt_inclusion_proof(&self, hash: TxHash) -> EthResult<Option<InclusionProof>> {
+        self.on_blocking_task(|this| async move {
+            this.try_inclusion_proof(hash, InclusionTrie::Receipts)
+        })
+        .await
+    }
+
+    fn try_inclusion_proof(
+        &self,
+        hash: TxHash,
+        trie: InclusionTrie,
+    ) -> EthResult<Option<InclusionProof>> {
+        let Some((transaction, meta)) = self.provider().transaction_by_hash_with_meta(hash)? else {
+            return Ok(None)
+        };
+        let Some(proofs) = self.inclusion_proofs(meta.block_hash, trie)? else { return Ok(None) };
+        let index = meta.index as usize;
+        let Some(proof) = proofs.proof(index) else { return Ok(None) };
+
+        let mut value = Vec::new();
+        match trie {
+            InclusionTrie::Transactions => transaction.encode_enveloped(&mut value),
+            InclusionTrie::Receipts => {
+                let Some(receipt) = self.provider().receipt_by_hash(hash)? else { return Ok(None) };
+                ReceiptWithBloomRef::from(&receipt).encode_inner(&mut value, false);
+            }
+        }
+
+        Ok(Some(InclusionProof {
+            block_hash: meta.block_hash,
+            block_number: U64::from(meta.block_number),
+            transaction_index: U64::from(meta.index),
+            root: proofs.root(),
+            key: alloy_rlp::encode(index).into(),
+            value: value.into(),
+            proof,
+        }))
+    }
+
+    /// Returns the inclusion proofs of all items of the given trie of the block.
+    ///
+    /// The proofs of recently requested blocks are cached, since requests for the transactions of
+    /// a block usually arrive together.
+    fn inclusion_proofs(
+        &self,
+        block_hash: BlockHash,
+        trie: InclusionTrie,
+    ) -> EthResult<Option<Arc<OrderedTrieProofs>>> {
+        if let Some(proofs) = self.inner.inclusion_proofs.lock().unwrap().get(&(block_hash, trie)) {
+            return Ok(Some(Arc::clone(proofs)))
+        }
+
+        let Some(header) = self.provider().header(&block_hash)? else { return Ok(None) };
+        let (proofs, expected_root) = match trie {
+            InclusionTrie::Transactions => {
+                let Some(transactions) =
+                    self.provider().transactions_by_block(block_hash.into())?
+                else {
+                    return Ok(None)
+                };
+                let proofs =
+                    OrderedTrieProofs::new(&transactions, |tx, buf| tx.encode_enveloped(buf));
+                (proofs, header.transactions_root)
+            }
+            InclusionTrie::Receipts => {
+                let Some(receipts) = self.provider().receipts_by_block(block_hash.into())? else {
+                    return Ok(None)
+                };
+                let proofs = OrderedTrieProofs::new(&receipts, |receipt, buf| {
+                    ReceiptWithBloomRef::from(receipt).encode_inner(buf, false)
+                });
+                (proofs, header.receipts_root)
+            }
+        };
+
+        // the proofs would not verify against the header, e.g. for the receipts of optimism
+        // Regolith blocks, which are committed to with a different encoding
+        if proofs.root() != expected_root {
+            return Err(EthApiError::Internal(RethError::Custom(format!(
+                "computed {trie:?} root {} does not match the root of block {block_hash}",
+                proofs.root()
+            ))))
+        }
+
+        let proofs = Arc::new(proofs);
+        self.inner.inclusion_proofs.lock().unwrap().insert((block_hash, trie), Arc::clone(&proofs));
+        Ok(Some(proofs))
+    }
+
+    /// Sends the proofs of the given targets to the sink for every new canonical block, until the
+    /// subscription is closed.
+    async fn pipe_proofs(
+        self,
+        sink: SubscriptionSink,
+        targets: Vec<ProofTarget>,
+    ) -> Result<(), jsonrpsee::core::Error> {
+        let targets = Arc::new(targets);
+        let mut canon_state = self.inner.chain_events.canonical_state_stream();
+        loop {
+            tokio::select! {
+                _ = sink.closed() => break Ok(()),
+                maybe_notification = canon_state.next() => {
+                    let Some(notification) = maybe_notification else { break Ok(()) };
+                    let tip = notification.tip().clone();
+                    let targets = Arc::clone(&targets);
+                    let update = self
+                        .on_blocking_task(|this| async move { this.try_proofs_update(&tip, &targets) })
+                        .await;
+                    let update = match update {
+                        Ok(update) => update,
+                        Err(err) => {
+                            // the canonical chain may have already advanced past the block, in
+                            // which case the proofs are generated for the next block
+                            debug!(target: "rpc::reth", %err, "Failed to generate proofs for subscription");
+                            continue
+                        }
+                    };
+                    let msg = SubscriptionMessage::from_json(&update)?;
+                    if sink.send(msg).await.is_err() {
+                        break Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates the proofs of all targets at the given canonical block.
+    fn try_proofs_update(
+        &self,
+        block: &SealedBlockWithSenders,
+        targets: &[ProofTarget],
+    ) -> EthResult<ProofsUpdate> {
+        let state = self.provider().state_by_block_hash(block.hash())?;
+        let proofs = targets
+            .iter()
+            .map(|target| {
+                let keys = target.storage_keys.iter().map(|key| key.0).collect::<Vec<_>>();
+                Ok(from_primitive_account_proof(state.proof(target.address, &keys)?))
+            })
+            .collect::<EthResult<Vec<_>>>()?;
+        Ok(ProofsUpdate {
+            block_number: U64::from(block.number),
+            block_hash: block.hash(),
+            state_root: block.state_root,
+            proofs,
+        })
+    }
 }
 
 #[async_trait]
-impl<Provider> RethApiServer for RethApi<Provider>
+impl<Provider, Events> RethApiServer for RethApi<Provider, Events>
 where
-    Provider: BlockReaderIdExt + ChangeSetReader + StateProviderFactory + 'static,
+    Provider: BlockReaderIdExt
+        + ChangeSetReader
+        + HeaderAccumulatorReader
+        + PruneCheckpointReader
+        + ReorgHistoryProvider
+        + StateProviderFactory
+        + 'static,
+    Events: CanonStateSubscriptions + 'static,
 {
     /// Handler for `reth_getBalanceChangesInBlock`
     async fn reth_get_balance_changes_in_block(
@@ -95,23 +445,109 @@ where
     ) -> RpcResult<HashMap<Address, U256>> {
         Ok(RethApi::balance_changes_in_block(self, block_id).await?)
     }
+
+    /// Handler for `reth_getAccountHistory`
+    async fn reth_get_account_history(
+        &self,
+        address: Address,
+        from_block: Option<U64>,
+        limit: Option<usize>,
+    ) -> RpcResult<AccountHistoryPage> {
+        let from_block = from_block.unwrap_or_default().to();
+        Ok(RethApi::account_history(self, address, from_block, limit).await?)
+    }
+
+    /// Handler for `reth_pruneStatus`
+    async fn reth_prune_status(&self) -> RpcResult<Vec<PruneSegmentStatus>> {
+        Ok(RethApi::prune_status(self).await?)
+    }
+
+    /// Handler for `reth_reorgHistory`
+    async fn reth_reorg_history(&self, limit: Option<usize>) -> RpcResult<Vec<ReorgEntry>> {
+        Ok(RethApi::reorg_history(self, limit).await?)
+    }
+
+    /// Handler for `reth_getTransactionInclusionProof`
+    async fn reth_get_transaction_inclusion_proof(
+        &self,
+        hash: TxHash,
+    ) -> RpcResult<Option<InclusionProof>> {
+        Ok(RethApi::transaction_inclusion_proof(self, hash).await?)
+    }
+
+    /// Handler for `reth_getReceiptInclusionProof`
+    async fn reth_get_receipt_inclusion_proof(
+        &self,
+        hash: TxHash,
+    ) -> RpcResult<Option<InclusionProof>> {
+        Ok(RethApi::receipt_inclusion_proof(self, hash).await?)
+    }
+
+    /// Handler for `reth_getHeaderAccumulatorRoot`
+    async fn reth_get_header_accumulator_root(&self) -> RpcResult<B256> {
+        Ok(RethApi::header_accumulator_root(self).await?)
+    }
+
+    /// Handler for `reth_getHeaderAccumulatorProof`
+    async fn reth_get_header_accumulator_proof(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Option<HeaderAccumulatorProof>> {
+        Ok(RethApi::header_accumulator_proof(self, block_id).await?)
+    }
+
+    /// Handler for `reth_subscribeProofs`
+    async fn reth_subscribe_proofs(
+        &self,
+        pending: PendingSubscriptionSink,
+        targets: Vec<ProofTarget>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        if targets.is_empty() {
+            return Err("no proof targets provided".into())
+        }
+        if targets.len() > MAX_PROOF_SUBSCRIPTION_TARGETS {
+            return Err(format!(
+                "too many proof targets, at most {MAX_PROOF_SUBSCRIPTION_TARGETS} are allowed"
+            )
+            .into())
+        }
+        let storage_keys = targets.iter().map(|target| target.storage_keys.len()).sum::<usize>();
+        if storage_keys > MAX_PROOF_SUBSCRIPTION_STORAGE_KEYS {
+            return Err(format!(
+                "too many storage keys, at most {MAX_PROOF_SUBSCRIPTION_STORAGE_KEYS} are allowed"
+            )
+            .into())
+        }
+
+        let sink = pending.accept().await?;
+        let this = self.clone();
+        self.inner.task_spawner.spawn(Box::pin(async move {
+            let _ = this.pipe_proofs(sink, targets).await;
+        }));
+
+        Ok(())
+    }
 }
 
-impl<Provider> std::fmt::Debug for RethApi<Provider> {
+impl<Provider, Events> std::fmt::Debug for RethApi<Provider, Events> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RethApi").finish_non_exhaustive()
     }
 }
 
-impl<Provider> Clone for RethApi<Provider> {
+impl<Provider, Events> Clone for RethApi<Provider, Events> {
     fn clone(&self) -> Self {
         Self { inner: Arc::clone(&self.inner) }
     }
 }
 
-struct RethApiInner<Provider> {
+struct RethApiInner<Provider, Events> {
     /// The provider that can interact with the chain.
     provider: Provider,
+    /// A type that allows to create new event subscriptions.
+    chain_events: Events,
     /// The type that can spawn tasks which would otherwise block.
     task_spawner: Box<dyn TaskSpawner>,
+    /// The inclusion proofs of recently requested blocks.
+    inclusion_proofs: Mutex<LruMap<(BlockHash, InclusionTrie), Arc<OrderedTrieProofs>>>,
 }
@@ -0,0 +1,236 @@
+use crate::{
+    eth::EthTransactions,
+    layers::{ResultStream, StreamingMethods},
+    result::invalid_params_rpc_err,
+    DebugApi, EthFilter, TraceApi,
+};
+use async_trait::async_trait;
+use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
+use jsonrpsee::{
+    server::SubscriptionMessage,
+    types::{ErrorObjectOwned, Params},
+    PendingSubscriptionSink, SubscriptionSink,
+};
+use reth_primitives::{BlockId, BlockNumberOrTag, B256};
+use reth_provider::{BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, StateProviderFactory};
+use reth_rpc_api::RethStreamApiServer;
+use reth_rpc_types::{
+    trace::{filter::TraceFilter, geth::GethDebugTracingOptions},
+    Filter, ResultStreamChunk,
+};
+use reth_tasks::TaskSpawner;
+use reth_transaction_pool::TransactionPool;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Streams the results of the calls that can return very large results, like `eth_getLogs`,
+/// `trace_filter` and `debug_traceBlockByNumber`.
+///
+/// The results are streamed over Http by the [StreamingLayer](crate::StreamingLayer) middleware,
+/// and over WebSocket and IPC by the `reth_subscribeResultStream` subscription.
+pub struct StreamingApi<Provider, Pool, Eth> {
+    inner: Arc<StreamingApiInner<Provider, Pool, Eth>>,
+}
+
+impl<Provider, Pool, Eth> StreamingApi<Provider, Pool, Eth> {
+    /// Creates a new instance that streams the results of the given handlers.
+    pub fn new(
+        filter: EthFilter<Provider, Pool>,
+        trace: TraceApi<Provider, Eth>,
+        debug: DebugApi<Provider, Eth>,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let inner = Arc::new(StreamingApiInner { filter, trace, debug, task_spawner });
+        Self { inner }
+    }
+}
+
+impl<Provider, Pool, Eth> StreamingApi<Provider, Pool, Eth>
+where
+    Provider:
+        BlockReaderIdExt + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
+    Pool: TransactionPool + 'static,
+    Eth: EthTransactions + 'static,
+{
+    /// Returns the stream of the result of the call.
+    async fn result_stream(self, call: StreamedCall) -> Result<ResultStream, ErrorObjectOwned> {
+        let stream = match call {
+            StreamedCall::Logs(filter) => {
+                into_result_stream(self.inner.filter.logs_stream(filter)?)
+            }
+            StreamedCall::TraceFilter(filter) => {
+                into_result_stream(self.inner.trace.trace_filter_stream(filter)?)
+            }
+            StreamedCall::DebugTraceBlock(block_id, opts) => {
+                let traces = self.inner.debug.debug_trace_block_stream(block_id, opts).await?;
+                into_result_stream(traces.map(|trace| trace.map(|trace| vec![trace])))
+            }
+        };
+        Ok(stream)
+    }
+}
+
+impl<Provider, Pool, Eth> StreamingMethods for StreamingApi<Provider, Pool, Eth>
+where
+    Provider:
+        BlockReaderIdExt + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
+    Pool: TransactionPool + 'static,
+    Eth: EthTransactions + 'static,
+{
+    fn stream(
+        &self,
+        method: &str,
+        params: Params<'_>,
+    ) -> Option<BoxFuture<'static, Result<ResultStream, ErrorObjectOwned>>> {
+        let call = StreamedCall::parse(method, params)?;
+        let this = self.clone();
+        Some(async move { this.result_stream(call?).await }.boxed())
+    }
+}
+
+#[async_trait]
+impl<Provider, Pool, Eth> RethStreamApiServer for StreamingApi<Provider, Pool, Eth>
+where
+    Provider:
+        BlockReaderIdExt + StateProviderFactory + EvmEnvProvider + ChainSpecProvider + 'static,
+    Pool: TransactionPool + 'static,
+    Eth: EthTransactions + 'static,
+{
+    /// Handler for `reth_subscribeResultStream`
+    async fn reth_subscribe_result_stream(
+        &self,
+        pending: PendingSubscriptionSink,
+        method: String,
+        params: Option<Value>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let params = params.map(|params| params.to_string());
+        let call =
+            StreamedCall::parse(&method, Params::new(params.as_deref())).unwrap_or_else(|| {
+                Err(invalid_params_rpc_err(format!("results of {method} can't be streamed")))
+            });
+        let stream = match call {
+            Ok(call) => self.clone().result_stream(call).await,
+            Err(err) => Err(err),
+        };
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                pending.reject(err).await;
+                return Ok(())
+            }
+        };
+
+        let sink = pending.accept().await?;
+        self.inner.task_spawner.spawn(Box::pin(async move {
+            let _ = pipe_result_stream(sink, stream).await;
+        }));
+
+        Ok(())
+    }
+}
+
+impl<Provider, Pool, Eth> std::fmt::Debug for StreamingApi<Provider, Pool, Eth> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingApi").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, Pool, Eth> Clone for StreamingApi<Provider, Pool, Eth> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+struct StreamingApiInner<Provider, Pool, Eth> {
+    /// Handler of `eth_getLogs`
+    filter: EthFilter<Provider, Pool>,
+    /// Handler of `trace_filter`
+    trace: TraceApi<Provider, Eth>,
+    /// Handler of `debug_traceBlockByNumber` and `debug_traceBlockByHash`
+    debug: DebugApi<Provider, Eth>,
+    /// The type that can spawn the tasks of subscriptions.
+    task_spawner: Box<dyn TaskSpawner>,
+}
+
+/// A call whose result can be streamed.
+#[derive(Debug)]
+enum StreamedCall {
+    /// `eth_getLogs`
+    Logs(Filter),
+    /// `trace_filter`
+    TraceFilter(TraceFilter),
+    /// `debug_traceBlockByNumber` and `debug_traceBlockByHash`
+    DebugTraceBlock(BlockId, GethDebugTracingOptions),
+}
+
+impl StreamedCall {
+    /// Parses the params of a call to the given method, returns `None` if the method's result
+    /// can't be streamed.
+    fn parse(method: &str, params: Params<'_>) -> Option<Result<Self, ErrorObjectOwned>> {
+        let call = match method {
+            "eth_getLogs" => params.one().map(Self::Logs),
+            "trace_filter" => params.one().map(Self::TraceFilter),
+            "debug_traceBlockByNumber" => Self::debug_trace_block::<BlockNumberOrTag>(params),
+            "debug_traceBlockByHash" => Self::debug_trace_block::<B256>(params),
+            _ => return None,
+        };
+        Some(call)
+    }
+
+    fn debug_trace_block<B>(params: Params<'_>) -> Result<Self, ErrorObjectOwned>
+    where
+        B: DeserializeOwned + Into<BlockId>,
+    {
+        let mut params = params.sequence();
+        let block = params.next::<B>()?;
+        let opts = params.optional_next::<GethDebugTracingOptions>()?;
+        Ok(Self::DebugTraceBlock(block.into(), opts.unwrap_or_default()))
+    }
+}
+
+/// Converts a stream of chunks of the result into a [ResultStream].
+fn into_result_stream<T, E>(
+    stream: impl Stream<Item = Result<Vec<T>, E>> + Send + 'static,
+) -> ResultStream
+where
+    T: Serialize,
+    E: Into<ErrorObjectOwned>,
+{
+    stream
+        .map(|items| {
+            let items: Result<Vec<T>, ErrorObjectOwned> = items.map_err(Into::into);
+            items.map(|items| {
+                items.iter().map(|item| serde_json::to_value(item).expect("serializable")).collect()
+            })
+        })
+        .boxed()
+}
+
+/// Sends the chunks of the result to the subscriber, followed by a final chunk marked as done.
+async fn pipe_result_stream(
+    sink: SubscriptionSink,
+    mut stream: ResultStream,
+) -> Result<(), jsonrpsee::core::Error> {
+    loop {
+        tokio::select! {
+            _ = sink.closed() => break Ok(()),
+            items = stream.next() => {
+                let chunk = match items {
+                    Some(Ok(items)) => ResultStreamChunk { items, done: false },
+                    Some(Err(err)) => {
+                        debug!(target: "rpc::streaming", ?err, "Failed to stream result");
+                        break Ok(())
+                    }
+                    None => ResultStreamChunk { items: Vec::new(), done: true },
+                };
+                let done = chunk.done;
+                let msg = SubscriptionMessage::from_json(&chunk)?;
+                if sink.send(msg).await.is_err() || done {
+                    break Ok(())
+                }
+            }
+        }
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     BlockingTaskGuard,
 };
 use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
 use jsonrpsee::core::RpcResult as Result;
 use reth_consensus_common::calc::{base_block_reward, block_reward};
 use reth_primitives::{
@@ -72,10 +73,11 @@ where
         let overrides =
             EvmOverrides::new(trace_request.state_overrides, trace_request.block_overrides);
         let mut inspector = TracingInspector::new(config);
+        let evm_config = self.inner.eth_api.evm_config().clone();
         self.inner
             .eth_api
             .spawn_with_call_at(trace_request.call, at, overrides, move |db, env| {
-                let (res, _, db) = inspect_and_return_db(db, env, &mut inspector)?;
+                let (res, _, db) = inspect_and_return_db(&evm_config, db, env, &mut inspector)?;
                 let trace_res = inspector.into_parity_builder().into_trace_results_with_state(
                     &res,
                     &trace_request.trace_types,
@@ -130,6 +132,7 @@ where
         let (cfg, block_env, at) = self.inner.eth_api.evm_env_at(at).await?;
 
         let gas_limit = self.inner.eth_api.call_gas_limit();
+        let evm_config = self.inner.eth_api.evm_config().clone();
         // execute all transactions on top of each other and record the traces
         self.inner
             .eth_api
@@ -150,7 +153,7 @@ where
                     )?;
                     let config = TracingInspectorConfig::from_parity_config(&trace_types);
                     let mut inspector = TracingInspector::new(config);
-                    let (res, _) = inspect(&mut db, env, &mut inspector)?;
+                    let (res, _) = inspect(&evm_config, &mut db, env, &mut inspector)?;
 
                     let trace_res = inspector.into_parity_builder().into_trace_results_with_state(
                         &res,
@@ -239,6 +242,41 @@ where
         &self,
         filter: TraceFilter,
     ) -> EthResult<Vec<LocalizedTransactionTrace>> {
+        let target_blocks = self.trace_filter_targets(filter)?;
+
+        // trace all relevant blocks
+        let block_traces = target_blocks
+            .into_iter()
+            .map(|(num, indices, highest_idx)| self.trace_filter_block(num, indices, highest_idx));
+        let block_traces = futures::future::try_join_all(block_traces).await?;
+
+        Ok(block_traces.into_iter().flatten().collect())
+    }
+
+    /// Returns a stream of the transaction traces that match the given filter, block by block.
+    ///
+    /// Same as [Self::trace_filter], but the blocks are traced one after another and their traces
+    /// are yielded as soon as they're available.
+    pub fn trace_filter_stream(
+        &self,
+        filter: TraceFilter,
+    ) -> EthResult<BoxStream<'static, EthResult<Vec<LocalizedTransactionTrace>>>> {
+        let target_blocks = self.trace_filter_targets(filter)?;
+        let this = self.clone();
+        let stream =
+            futures::stream::iter(target_blocks).then(move |(num, indices, highest_idx)| {
+                let this = this.clone();
+                async move { this.trace_filter_block(num, indices, highest_idx).await }
+            });
+        Ok(stream.boxed())
+    }
+
+    /// Returns the blocks in the range of the filter that contain matching transactions, with the
+    /// indices of the matching transactions and the highest of them.
+    fn trace_filter_targets(
+        &self,
+        filter: TraceFilter,
+    ) -> EthResult<Vec<(u64, HashSet<u64>, u64)>> {
         let matcher = filter.matcher();
         let TraceFilter { from_block, to_block, after: _after, count: _count, .. } = filter;
         let start = from_block.unwrap_or(0);
@@ -278,10 +316,21 @@ where
             }
         }
 
-        // trace all relevant blocks
-        let mut block_traces = Vec::with_capacity(target_blocks.len());
-        for (num, indices, highest_idx) in target_blocks {
-            let traces = self.inner.eth_api.trace_block_until(
+        Ok(target_blocks)
+    }
+
+    /// Traces the block up to the highest matching transaction, and returns the traces of the
+    /// matching transactions.
+    async fn trace_filter_block(
+        &self,
+        num: u64,
+        indices: HashSet<u64>,
+        highest_idx: u64,
+    ) -> EthResult<Vec<LocalizedTransactionTrace>> {
+        let traces = self
+            .inner
+            .eth_api
+            .trace_block_until(
                 num.into(),
                 Some(highest_idx),
                 TracingInspectorConfig::default_parity(),
@@ -298,18 +347,10 @@ where
                         .into_localized_transaction_traces(tx_info);
                     Ok(Some(traces))
                 },
-            );
-            block_traces.push(traces);
-        }
-
-        let block_traces = futures::future::try_join_all(block_traces).await?;
-        let all_traces = block_traces
-            .into_iter()
-            .flatten()
-            .flat_map(|traces| traces.into_iter().flatten().flat_map(|traces| traces.into_iter()))
-            .collect();
+            )
+            .await?;
 
-        Ok(all_traces)
+        Ok(traces.into_iter().flatten().flatten().flatten().collect())
     }
 
     /// Returns all traces for the given transaction hash
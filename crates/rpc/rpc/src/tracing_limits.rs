@@ -0,0 +1,330 @@
+//! Limits of `debug_trace*` calls that requests can override within operator-configured bounds.
+
+use crate::eth::error::{EthApiError, EthResult};
+use reth_rpc_types::trace::geth::{CallFrame, GethDebugTracingOptions, GethTrace};
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// The maximum call depth of the EVM.
+const MAX_CALL_DEPTH: u64 = 1024;
+
+/// Limits of the `debug_trace*` calls, configured by the operator.
+///
+/// A request can override the limits within the configured bounds: the geth `timeout` option sets
+/// the timeout, and the `maxDepth` and `memoryLimit` fields of the `tracerConfig` set the maximum
+/// logged call depth and the maximum memory captured per step. Requests that exceed a bound are
+/// rejected instead of being clamped, and traces that exceed the memory limit fail instead of
+/// being truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TracingLimits {
+    /// The timeout of calls that don't set one.
+    pub default_timeout: Duration,
+    /// The maximum timeout a call can set.
+    pub max_timeout: Duration,
+    /// The maximum number of bytes of memory captured per step by calls that don't set a limit.
+    pub default_memory_limit: usize,
+    /// The maximum memory limit a call can set.
+    pub max_memory_limit: usize,
+    /// The maximum call depth that is logged by the struct logger and the call tracer, and the
+    /// maximum depth a call can set.
+    pub max_depth: u64,
+}
+
+impl TracingLimits {
+    /// Sets the timeout of calls that don't set one.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum timeout a call can set.
+    pub fn max_timeout(mut self, timeout: Duration) -> Self {
+        self.max_timeout = timeout;
+        self
+    }
+
+    /// Sets the memory limit of calls that don't set one.
+    pub fn default_memory_limit(mut self, limit: usize) -> Self {
+        self.default_memory_limit = limit;
+        self
+    }
+
+    /// Sets the maximum memory limit a call can set.
+    pub fn max_memory_limit(mut self, limit: usize) -> Self {
+        self.max_memory_limit = limit;
+        self
+    }
+
+    /// Sets the maximum logged call depth.
+    pub fn max_depth(mut self, depth: u64) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Returns the limits of a call with the given options, starting its timeout.
+    pub(crate) fn resolve(&self, opts: &GethDebugTracingOptions) -> EthResult<TraceLimits> {
+        let timeout = match opts.timeout.as_deref() {
+            Some(timeout) => {
+                let timeout = parse_timeout(timeout).ok_or_else(|| {
+                    EthApiError::InvalidParams(format!("invalid timeout {timeout:?}"))
+                })?;
+                if timeout > self.max_timeout {
+                    return Err(EthApiError::InvalidParams(format!(
+                        "timeout {timeout:?} exceeds the maximum of {:?}",
+                        self.max_timeout
+                    )))
+                }
+                timeout
+            }
+            None => self.default_timeout,
+        };
+
+        // the overrides are read from the tracer config, which only fails for invalid values
+        let overrides = match opts.tracer_config.clone().into_json() {
+            value @ serde_json::Value::Object(_) => serde_json::from_value::<LimitOverrides>(value)
+                .map_err(|err| EthApiError::InvalidParams(err.to_string()))?,
+            _ => LimitOverrides::default(),
+        };
+        let memory_limit = overrides.memory_limit.unwrap_or(self.default_memory_limit);
+        if memory_limit > self.max_memory_limit {
+            return Err(EthApiError::InvalidParams(format!(
+                "memoryLimit {memory_limit} exceeds the maximum of {} bytes",
+                self.max_memory_limit
+            )))
+        }
+        let max_depth = overrides.max_depth.unwrap_or(self.max_depth);
+        if max_depth > self.max_depth {
+            return Err(EthApiError::InvalidParams(format!(
+                "maxDepth {max_depth} exceeds the maximum of {}",
+                self.max_depth
+            )))
+        }
+
+        Ok(TraceLimits { started_at: Instant::now(), timeout, memory_limit, max_depth })
+    }
+}
+
+impl Default for TracingLimits {
+    fn default() -> Self {
+        Self {
+            default_timeout: Duration::from_secs(60),
+            max_timeout: Duration::from_secs(5 * 60),
+            default_memory_limit: 1024 * 1024,
+            max_memory_limit: 16 * 1024 * 1024,
+            max_depth: MAX_CALL_DEPTH,
+        }
+    }
+}
+
+/// The limits the request sets in the `tracerConfig`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LimitOverrides {
+    max_depth: Option<u64>,
+    memory_limit: Option<usize>,
+}
+
+/// The limits of a single `debug_trace*` call.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TraceLimits {
+    /// When the call started.
+    started_at: Instant,
+    /// The timeout of the call.
+    timeout: Duration,
+    /// The maximum number of bytes of memory captured per step.
+    memory_limit: usize,
+    /// The maximum logged call depth.
+    max_depth: u64,
+}
+
+impl TraceLimits {
+    /// Awaits the future, failing once the timeout of the call elapsed.
+    ///
+    /// Transactions that are being traced when the timeout elapses are executed to completion on
+    /// the blocking pool, but the traces of further transactions are not started, see
+    /// [Self::ensure_time_left].
+    pub(crate) async fn run<T>(&self, fut: impl Future<Output = EthResult<T>>) -> EthResult<T> {
+        let remaining = self.timeout.saturating_sub(self.started_at.elapsed());
+        tokio::time::timeout(remaining, fut)
+            .await
+            .map_err(|_| EthApiError::ExecutionTimedOut(self.timeout))?
+    }
+
+    /// Fails if the timeout of the call elapsed, to be checked before tracing each transaction.
+    pub(crate) fn ensure_time_left(&self) -> EthResult<()> {
+        if self.started_at.elapsed() >= self.timeout {
+            return Err(EthApiError::ExecutionTimedOut(self.timeout))
+        }
+        Ok(())
+    }
+
+    /// Drops the steps and calls deeper than the maximum depth from the trace, and fails if a
+    /// step captured more memory than the limit.
+    pub(crate) fn apply(&self, trace: &mut GethTrace) -> EthResult<()> {
+        match trace {
+            GethTrace::Default(frame) => {
+                frame.struct_logs.retain(|log| log.depth <= self.max_depth);
+                let memory_size = frame
+                    .struct_logs
+                    .iter()
+                    .filter_map(|log| log.memory.as_ref())
+                    .map(|memory| memory.len() * 32)
+                    .max()
+                    .unwrap_or_default();
+                if memory_size > self.memory_limit {
+                    return Err(EthApiError::TracingLimitExceeded(format!(
+                        "captured memory of {memory_size} bytes exceeds the memory limit of {} bytes",
+                        self.memory_limit
+                    )))
+                }
+            }
+            GethTrace::CallTracer(frame) => truncate_calls(frame, 1, self.max_depth),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Drops the calls of the frame at the given depth that are deeper than the maximum depth.
+fn truncate_calls(frame: &mut CallFrame, depth: u64, max_depth: u64) {
+    if depth >= max_depth {
+        frame.calls.clear();
+        return
+    }
+    for call in &mut frame.calls {
+        truncate_calls(call, depth + 1, max_depth);
+    }
+}
+
+/// Parses a duration in the format of Go's `time.ParseDuration`, e.g. `300ms` or `1m30s`, which
+/// is the format of the timeouts geth accepts.
+fn parse_timeout(timeout: &str) -> Option<Duration> {
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+    let mut rest = timeout.trim();
+    if rest.is_empty() {
+        return None
+    }
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let (number, tail) = rest.split_at(rest.find(|c| !is_number(c)).unwrap_or(rest.len()));
+        let (unit, tail) = tail.split_at(tail.find(is_number).unwrap_or(tail.len()));
+        let unit_secs = match unit {
+            "ns" => 1e-9,
+            "us" | "µs" => 1e-6,
+            "ms" => 1e-3,
+            "s" => 1.,
+            "m" => 60.,
+            "h" => 3600.,
+            _ => return None,
+        };
+        let number = number.parse::<f64>().ok()?;
+        total = total.checked_add(Duration::try_from_secs_f64(number * unit_secs).ok()?)?;
+        rest = tail;
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_types::trace::geth::{DefaultFrame, GethDebugTracerConfig};
+    use serde_json::json;
+
+    fn opts(timeout: Option<&str>, tracer_config: serde_json::Value) -> GethDebugTracingOptions {
+        GethDebugTracingOptions {
+            timeout: timeout.map(String::from),
+            tracer_config: GethDebugTracerConfig(tracer_config),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_go_durations() {
+        assert_eq!(parse_timeout("300ms"), Some(Duration::from_millis(300)));
+        assert_eq!(parse_timeout("1m30s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_timeout("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_timeout("10"), None);
+        assert_eq!(parse_timeout("1d"), None);
+        assert_eq!(parse_timeout(""), None);
+    }
+
+    #[test]
+    fn resolves_overrides_within_bounds() {
+        let limits = TracingLimits::default().max_depth(16);
+
+        let resolved = limits.resolve(&opts(None, serde_json::Value::Null)).unwrap();
+        assert_eq!(resolved.timeout, limits.default_timeout);
+        assert_eq!(resolved.memory_limit, limits.default_memory_limit);
+        assert_eq!(resolved.max_depth, 16);
+
+        let resolved = limits
+            .resolve(&opts(Some("2m"), json!({ "maxDepth": 4, "memoryLimit": 4096 })))
+            .unwrap();
+        assert_eq!(resolved.timeout, Duration::from_secs(120));
+        assert_eq!((resolved.memory_limit, resolved.max_depth), (4096, 4));
+
+        assert!(limits.resolve(&opts(Some("1h"), serde_json::Value::Null)).is_err());
+        assert!(limits.resolve(&opts(None, json!({ "maxDepth": 17 }))).is_err());
+        assert!(limits.resolve(&opts(None, json!({ "memoryLimit": usize::MAX }))).is_err());
+        assert!(limits.resolve(&opts(None, json!({ "maxDepth": "deep" }))).is_err());
+    }
+
+    #[test]
+    fn applies_limits_to_traces() {
+        let limits = TracingLimits::default()
+            .resolve(&opts(None, json!({ "maxDepth": 2, "memoryLimit": 64 })))
+            .unwrap();
+
+        let step = |depth: u64, words: usize| {
+            json!({
+                "pc": 0,
+                "op": "PUSH1",
+                "gas": 0,
+                "gasCost": 3,
+                "depth": depth,
+                "memory": vec!["00".repeat(32); words],
+            })
+        };
+        let frame = |steps: Vec<serde_json::Value>| {
+            GethTrace::Default(
+                serde_json::from_value::<DefaultFrame>(json!({
+                    "failed": false,
+                    "gas": 0,
+                    "returnValue": "0x",
+                    "structLogs": steps,
+                }))
+                .unwrap(),
+            )
+        };
+
+        // memory captured deeper than the logged depth doesn't count
+        let mut trace = frame(vec![step(1, 2), step(2, 1), step(3, 4)]);
+        limits.apply(&mut trace).unwrap();
+        let GethTrace::Default(default) = &trace else { unreachable!() };
+        assert_eq!(default.struct_logs.iter().map(|log| log.depth).collect::<Vec<_>>(), [1, 2]);
+
+        let mut trace = frame(vec![step(1, 3)]);
+        assert!(matches!(limits.apply(&mut trace), Err(EthApiError::TracingLimitExceeded(_))));
+
+        let call = |calls: Vec<serde_json::Value>| {
+            json!({
+                "type": "CALL",
+                "from": "0x0000000000000000000000000000000000000000",
+                "gas": "0x0",
+                "gasUsed": "0x0",
+                "input": "0x",
+                "calls": calls,
+            })
+        };
+        let mut trace = GethTrace::CallTracer(
+            serde_json::from_value(call(vec![call(vec![call(vec![])]), call(vec![])])).unwrap(),
+        );
+        limits.apply(&mut trace).unwrap();
+        let GethTrace::CallTracer(frame) = &trace else { unreachable!() };
+        assert_eq!(frame.calls.len(), 2);
+        assert!(frame.calls.iter().all(|call| call.calls.is_empty()));
+    }
+}
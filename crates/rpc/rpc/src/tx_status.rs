@@ -0,0 +1,118 @@
+use crate::eth::error::EthResult;
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use reth_interfaces::blockchain_tree::BlockchainTreeViewer;
+use reth_node_api::{BuiltPayload, EngineTypes};
+use reth_payload_builder::PayloadBuilderHandle;
+use reth_primitives::{TxHash, U64};
+use reth_provider::TransactionsProvider;
+use reth_rpc_api::RethTransactionStatusApiServer;
+use reth_rpc_types::TransactionStatus;
+use reth_transaction_pool::TransactionPool;
+use std::fmt;
+use tracing::trace;
+
+/// `reth_getTransactionStatus` implementation.
+///
+/// Follows a transaction from the pool through the payload jobs and the blockchain tree into the
+/// canonical chain.
+pub struct TransactionStatusApi<Provider, Pool, Engine: EngineTypes> {
+    /// The provider of the canonical chain and the blockchain tree.
+    provider: Provider,
+    /// The transaction pool.
+    pool: Pool,
+    /// The handle to the payload builder service.
+    payload_builder: PayloadBuilderHandle<Engine>,
+}
+
+impl<Provider, Pool, Engine> TransactionStatusApi<Provider, Pool, Engine>
+where
+    Engine: EngineTypes,
+{
+    /// Creates a new instance of the [TransactionStatusApi].
+    pub fn new(
+        provider: Provider,
+        pool: Pool,
+        payload_builder: PayloadBuilderHandle<Engine>,
+    ) -> Self {
+        Self { provider, pool, payload_builder }
+    }
+}
+
+impl<Provider, Pool, Engine> TransactionStatusApi<Provider, Pool, Engine>
+where
+    Provider: TransactionsProvider + BlockchainTreeViewer + 'static,
+    Pool: TransactionPool + 'static,
+    Engine: EngineTypes + 'static,
+{
+    /// Returns the most advanced status of the transaction.
+    pub async fn transaction_status(&self, hash: TxHash) -> EthResult<TransactionStatus> {
+        if let Some((_, meta)) = self.provider.transaction_by_hash_with_meta(hash)? {
+            let best_number = self.provider.best_block_number()?;
+            return Ok(TransactionStatus::Canonical {
+                block_hash: meta.block_hash,
+                block_number: U64::from(meta.block_number),
+                depth: U64::from(best_number.saturating_sub(meta.block_number)),
+            })
+        }
+
+        // the blocks of the tree are validated but not persisted, newest first
+        for (block_number, block_hashes) in self.provider.blocks().into_iter().rev() {
+            for block_hash in block_hashes {
+                let Some(block) = BlockchainTreeViewer::block_by_hash(&self.provider, block_hash)
+                else {
+                    continue
+                };
+                if block.body.iter().any(|tx| tx.hash == hash) {
+                    return Ok(TransactionStatus::Unpersisted {
+                        block_hash,
+                        block_number: U64::from(block_number),
+                    })
+                }
+            }
+        }
+
+        let payload_ids = self
+            .payload_builder
+            .built_payloads()
+            .await
+            .into_iter()
+            .filter(|(_, payload)| payload.block().body.iter().any(|tx| tx.hash == hash))
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        if !payload_ids.is_empty() {
+            return Ok(TransactionStatus::Candidate { payload_ids })
+        }
+
+        if self.pool.contains(&hash) {
+            let pending = self.pool.pending_transactions().iter().any(|tx| *tx.hash() == hash);
+            return Ok(if pending { TransactionStatus::Pending } else { TransactionStatus::Queued })
+        }
+
+        Ok(TransactionStatus::Unknown)
+    }
+}
+
+#[async_trait]
+impl<Provider, Pool, Engine> RethTransactionStatusApiServer
+    for TransactionStatusApi<Provider, Pool, Engine>
+where
+    Provider: TransactionsProvider + BlockchainTreeViewer + 'static,
+    Pool: TransactionPool + 'static,
+    Engine: EngineTypes + 'static,
+{
+    /// Handler for `reth_getTransactionStatus`
+    async fn reth_get_transaction_status(&self, hash: TxHash) -> RpcResult<TransactionStatus> {
+        trace!(target: "rpc::reth", %hash, "Serving reth_getTransactionStatus");
+        Ok(TransactionStatusApi::transaction_status(self, hash).await?)
+    }
+}
+
+impl<Provider, Pool, Engine> fmt::Debug for TransactionStatusApi<Provider, Pool, Engine>
+where
+    Engine: EngineTypes,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionStatusApi").finish_non_exhaustive()
+    }
+}
@@ -1,12 +1,18 @@
 use async_trait::async_trait;
-use jsonrpsee::core::RpcResult as Result;
+use jsonrpsee::{
+    core::RpcResult as Result, server::SubscriptionMessage, PendingSubscriptionSink,
+    SubscriptionSink,
+};
 use reth_primitives::{Address, U256, U64};
 use reth_rpc_api::TxPoolApiServer;
 use reth_rpc_types::{
     txpool::{TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolInspectSummary, TxpoolStatus},
-    Transaction,
+    Transaction, TxpoolDropReason, TxpoolEvent, TxpoolEventKind,
+};
+use reth_transaction_pool::{
+    AllPoolTransactions, DropReason, PoolEventKind, PoolEventSubscription, PoolTransaction,
+    SequencedPoolEvent, TransactionPool,
 };
-use reth_transaction_pool::{AllPoolTransactions, PoolTransaction, TransactionPool};
 use std::collections::BTreeMap;
 use tracing::trace;
 
@@ -57,6 +63,53 @@ where
     }
 }
 
+/// The maximum number of pool events sent to a `txpool_subscribeEvents` subscriber at once.
+const POOL_EVENTS_BATCH_SIZE: usize = 1024;
+
+/// Sends the events of the subscription to the sink until the subscription is closed.
+///
+/// Fails if the subscriber fell behind the event log of the pool.
+async fn pipe_pool_events(
+    sink: SubscriptionSink,
+    mut subscription: PoolEventSubscription,
+) -> std::result::Result<(), String> {
+    loop {
+        tokio::select! {
+            _ = sink.closed() => break Ok(()),
+            events = subscription.recv_batch(POOL_EVENTS_BATCH_SIZE) => {
+                for event in events.map_err(|err| err.to_string())? {
+                    let msg = SubscriptionMessage::from_json(&txpool_event(event))
+                        .map_err(|err| err.to_string())?;
+                    if sink.send(msg).await.is_err() {
+                        return Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Converts a pool event into its rpc representation.
+fn txpool_event(event: SequencedPoolEvent) -> TxpoolEvent {
+    let kind = match event.kind {
+        PoolEventKind::Added { pending } => TxpoolEventKind::Added { pending },
+        PoolEventKind::Promoted => TxpoolEventKind::Promoted,
+        PoolEventKind::Demoted => TxpoolEventKind::Demoted,
+        PoolEventKind::Replaced { replaced_by } => TxpoolEventKind::Replaced { replaced_by },
+        PoolEventKind::Mined { block_hash } => TxpoolEventKind::Mined { block_hash },
+        PoolEventKind::Dropped { reason } => {
+            let reason = match reason {
+                DropReason::Invalid => TxpoolDropReason::Invalid,
+                DropReason::Outdated => TxpoolDropReason::Outdated,
+                DropReason::PoolFull => TxpoolDropReason::PoolFull,
+                DropReason::Removed => TxpoolDropReason::Removed,
+            };
+            TxpoolEventKind::Dropped { reason }
+        }
+    };
+    TxpoolEvent { seq: U64::from(event.seq), hash: event.hash, kind }
+}
+
 #[async_trait]
 impl<Pool> TxPoolApiServer for TxPoolApi<Pool>
 where
@@ -138,6 +191,22 @@ where
         trace!(target: "rpc::eth", "Serving txpool_inspect");
         Ok(self.content())
     }
+
+    /// Handler for `txpool_subscribeEvents`
+    async fn txpool_subscribe_events(
+        &self,
+        pending: PendingSubscriptionSink,
+        from_seq: Option<U64>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        trace!(target: "rpc::eth", ?from_seq, "Serving txpool_subscribeEvents");
+        let subscription =
+            self.pool.transaction_event_log().subscribe(from_seq.map(|seq| seq.to::<u64>()))?;
+
+        let sink = pending.accept().await?;
+        // an error closes the subscription with an error notification
+        pipe_pool_events(sink, subscription).await?;
+        Ok(())
+    }
 }
 
 impl<Pool> std::fmt::Debug for TxPoolApi<Pool> {
@@ -89,6 +89,7 @@ pub(crate) fn prepare_jar<DB: Database, const COLUMNS: usize>(
     if let Filters::WithFilters(inclusion_filter, phf) = segment_config.filters {
         nippy_jar = match inclusion_filter {
             InclusionFilter::Cuckoo => nippy_jar.with_cuckoo_filter(total_rows),
+            InclusionFilter::Bloom => nippy_jar.with_bloom_filter(total_rows),
         };
         nippy_jar = match phf {
             PerfectHashingFunction::Fmph => nippy_jar.with_fmph(),
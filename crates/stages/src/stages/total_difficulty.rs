@@ -11,7 +11,7 @@ use reth_primitives::{
     stage::{EntitiesCheckpoint, StageCheckpoint, StageId},
     U256,
 };
-use reth_provider::DatabaseProviderRW;
+use reth_provider::{DatabaseProviderRW, HeaderAccumulatorWriter};
 use std::sync::Arc;
 use tracing::*;
 
@@ -20,6 +20,10 @@ use tracing::*;
 /// This stage walks over inserted headers and computes total difficulty
 /// at each block. The entries are inserted into [`HeaderTD`][reth_db::tables::HeaderTD]
 /// table.
+///
+/// The epochs of the header accumulator that are completed by the new headers are committed as
+/// well, since their records include the total difficulty. Epochs that were completed before the
+/// accumulator existed are committed with `reth db backfill-header-accumulator`.
 #[derive(Debug, Clone)]
 pub struct TotalDifficultyStage {
     /// Consensus client implementation
@@ -90,6 +94,8 @@ impl<DB: Database> Stage<DB> for TotalDifficultyStage {
             cursor_td.append(block_number, td.into())?;
         }
 
+        provider.append_header_accumulator(start_block..=end_block)?;
+
         Ok(ExecOutput {
             checkpoint: StageCheckpoint::new(end_block)
                 .with_entities_stage_checkpoint(stage_checkpoint(provider)?),
@@ -106,6 +112,7 @@ impl<DB: Database> Stage<DB> for TotalDifficultyStage {
         let (_, unwind_to, _) = input.unwind_block_range_with_threshold(self.commit_threshold);
 
         provider.unwind_table_by_num::<tables::HeaderTD>(unwind_to)?;
+        provider.unwind_header_accumulator(unwind_to)?;
 
         Ok(UnwindOutput {
             checkpoint: StageCheckpoint::new(unwind_to)
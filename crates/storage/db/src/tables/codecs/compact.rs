@@ -3,7 +3,7 @@ use crate::{
     tables::models::*,
 };
 use reth_codecs::{main_codec, Compact};
-use reth_primitives::{stage::StageCheckpoint, trie::*, *};
+use reth_primitives::{accumulator::EpochAccumulator, stage::StageCheckpoint, trie::*, *};
 
 /// Implements compression for Compact type.
 macro_rules! impl_compression_for_compact {
@@ -47,7 +47,8 @@ impl_compression_for_compact!(
     TransactionSignedNoHash,
     CompactU256,
     StageCheckpoint,
-    PruneCheckpoint
+    PruneCheckpoint,
+    EpochAccumulator
 );
 
 macro_rules! impl_compression_fixed_compact {
@@ -30,11 +30,12 @@ use crate::{
             accounts::{AccountBeforeTx, BlockNumberAddress},
             blocks::{HeaderHash, StoredBlockOmmers},
             storage_sharded_key::StorageShardedKey,
-            ShardedKey, StoredBlockBodyIndices, StoredBlockWithdrawals,
+            ChainStateKey, ShardedKey, StoredBlockBodyIndices, StoredBlockWithdrawals,
         },
     },
 };
 use reth_primitives::{
+    accumulator::EpochAccumulator,
     stage::StageCheckpoint,
     trie::{StorageTrieEntry, StoredBranchNode, StoredNibbles, StoredNibblesSubKey},
     Account, Address, BlockHash, BlockNumber, Bytecode, Header, IntegerList, PruneCheckpoint,
@@ -51,7 +52,7 @@ pub enum TableType {
 }
 
 /// Number of tables that should be present inside database.
-pub const NUM_TABLES: usize = 26;
+pub const NUM_TABLES: usize = 30;
 
 /// The general purpose of this is to use with a combination of Tables enum,
 /// by implementing a `TableViewer` trait you can operate on db tables in an abstract way.
@@ -215,12 +216,22 @@ tables!([
             TxSenders,
             SyncStage,
             SyncStageProgress,
-            PruneCheckpoints
+            PruneCheckpoints,
+            ChainState,
+            EpochAccumulators,
+            EpochAccumulatorRoots
         ]
     ),
     (
         TableType::DupSort,
-        [PlainStorageState, AccountChangeSet, StorageChangeSet, HashedStorage, StoragesTrie]
+        [
+            PlainStorageState,
+            AccountChangeSet,
+            StorageChangeSet,
+            HashedStorage,
+            StoragesTrie,
+            HotHashedStorages
+        ]
     )
 ]);
 
@@ -337,11 +348,18 @@ table!(
 
 table!(
     /// Stores the current state of an [`Account`].
+    ///
+    /// Together with [`PlainStorageState`] this is the flat state at the canonical head, which
+    /// serves point reads of the latest state without going through the hashed state or history
+    /// indices. Both tables are updated when blocks are committed and reverted from the changesets
+    /// when blocks are unwound.
     ( PlainAccountState ) Address | Account
 );
 
 dupsort!(
     /// Stores the current value of a storage key.
+    ///
+    /// See [`PlainAccountState`].
     ( PlainStorageState ) Address | [B256] StorageEntry
 );
 
@@ -429,6 +447,15 @@ dupsort!(
     ( StoragesTrie ) B256 | [StoredNibblesSubKey] StorageTrieEntry
 );
 
+dupsort!(
+    /// Stores the hashed storage of hot contracts, moved out of [`HashedStorage`] so it doesn't
+    /// share pages with the storage of all other accounts.
+    ///
+    /// Only populated by the experimental storage layout migration of `reth_trie`, the storage of
+    /// an account is either in this table or in [`HashedStorage`].
+    ( HotHashedStorages ) B256 | [B256] StorageEntry
+);
+
 table!(
     /// Stores the transaction sender for each canonical transaction.
     /// It is needed to speed up execution stage and allows fetching signer without doing
@@ -451,6 +478,22 @@ table!(
     ( PruneCheckpoints ) PruneSegment | PruneCheckpoint
 );
 
+table!(
+    /// Stores the safe and finalized block numbers of the chain, as reported by the consensus
+    /// layer.
+    ( ChainState ) ChainStateKey | BlockNumber
+);
+
+table!(
+    /// Stores the header records of every completed epoch of the canonical chain.
+    ( EpochAccumulators ) u64 | EpochAccumulator
+);
+
+table!(
+    /// Stores the root of the accumulator of every completed epoch of the canonical chain.
+    ( EpochAccumulatorRoots ) u64 | B256
+);
+
 /// Alias Types
 
 /// List with transaction numbers.
@@ -485,11 +528,15 @@ mod tests {
         (TableType::Table, SyncStage::NAME),
         (TableType::Table, SyncStageProgress::NAME),
         (TableType::Table, PruneCheckpoints::NAME),
+        (TableType::Table, ChainState::NAME),
+        (TableType::Table, EpochAccumulators::NAME),
+        (TableType::Table, EpochAccumulatorRoots::NAME),
         (TableType::DupSort, PlainStorageState::NAME),
         (TableType::DupSort, AccountChangeSet::NAME),
         (TableType::DupSort, StorageChangeSet::NAME),
         (TableType::DupSort, HashedStorage::NAME),
         (TableType::DupSort, StoragesTrie::NAME),
+        (TableType::DupSort, HotHashedStorages::NAME),
     ];
 
     #[test]
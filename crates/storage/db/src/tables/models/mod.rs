@@ -9,6 +9,7 @@ use reth_primitives::{
     trie::{StoredNibbles, StoredNibblesSubKey},
     Address, PruneSegment, B256,
 };
+use serde::{Deserialize, Serialize};
 
 pub mod accounts;
 pub mod blocks;
@@ -139,6 +140,36 @@ impl Decode for StoredNibblesSubKey {
     }
 }
 
+/// The keys of the [ChainState](crate::tables::ChainState) table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ChainStateKey {
+    /// The last block the consensus layer considered finalized.
+    LastFinalizedBlock,
+    /// The last block the consensus layer considered safe.
+    LastSafeBlock,
+}
+
+impl Encode for ChainStateKey {
+    type Encoded = [u8; 1];
+
+    fn encode(self) -> Self::Encoded {
+        match self {
+            Self::LastFinalizedBlock => [0],
+            Self::LastSafeBlock => [1],
+        }
+    }
+}
+
+impl Decode for ChainStateKey {
+    fn decode<B: AsRef<[u8]>>(value: B) -> Result<Self, DatabaseError> {
+        match value.as_ref() {
+            [0] => Ok(Self::LastFinalizedBlock),
+            [1] => Ok(Self::LastSafeBlock),
+            _ => Err(DatabaseError::Decode),
+        }
+    }
+}
+
 impl Encode for PruneSegment {
     type Encoded = [u8; 1];
 
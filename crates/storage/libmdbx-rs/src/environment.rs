@@ -142,6 +142,29 @@ impl Environment {
         mdbx_result(unsafe { ffi::mdbx_env_sync_ex(self.env_ptr(), force, false) })
     }
 
+    /// Copies the environment to a new file at the given path, while it may be in use by other
+    /// transactions and processes.
+    ///
+    /// The copy is consistent at a single read transaction. If `compact` is set, free pages are
+    /// omitted and all pages are renumbered sequentially.
+    ///
+    /// The path may not contain the null character, and the file must not exist yet.
+    pub fn copy(&self, dest: &Path, compact: bool) -> Result<()> {
+        let dest = CString::new(path_to_bytes(dest)).map_err(|_| Error::Invalid)?;
+        mdbx_result(unsafe {
+            ffi::mdbx_env_copy(self.env_ptr(), dest.as_ptr(), copy_flags(compact))
+        })?;
+        Ok(())
+    }
+
+    /// Same as [Environment::copy], but writes the copy to the given file descriptor, which may
+    /// also be a pipe.
+    #[cfg(unix)]
+    pub fn copy_to_fd(&self, fd: std::os::fd::RawFd, compact: bool) -> Result<()> {
+        mdbx_result(unsafe { ffi::mdbx_env_copy2fd(self.env_ptr(), fd, copy_flags(compact)) })?;
+        Ok(())
+    }
+
     /// Retrieves statistics about this environment.
     pub fn stat(&self) -> Result<Stat> {
         unsafe {
@@ -230,6 +253,28 @@ struct EnvironmentInner {
     txn_manager: TxnManager,
 }
 
+#[cfg(unix)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_ref().as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(windows)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
+    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
+    // really help.
+    path.as_ref().to_string_lossy().to_string().into_bytes()
+}
+
+fn copy_flags(compact: bool) -> ffi::MDBX_copy_flags_t {
+    if compact {
+        ffi::MDBX_CP_COMPACT
+    } else {
+        ffi::MDBX_CP_DEFAULTS
+    }
+}
+
 impl Drop for EnvironmentInner {
     fn drop(&mut self) {
         // Close open mdbx environment on drop
@@ -667,20 +712,6 @@ impl EnvironmentBuilder {
                     ))?;
                 }
 
-                #[cfg(unix)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    use std::os::unix::ffi::OsStrExt;
-                    path.as_ref().as_os_str().as_bytes().to_vec()
-                }
-
-                #[cfg(windows)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
-                    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
-                    // really help.
-                    path.as_ref().to_string_lossy().to_string().into_bytes()
-                }
-
                 let path = match CString::new(path_to_bytes(path)) {
                     Ok(path) => path,
                     Err(_) => return Err(Error::Invalid),
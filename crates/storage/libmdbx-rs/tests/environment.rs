@@ -81,6 +81,27 @@ fn test_sync() {
     }
 }
 
+#[test]
+fn test_copy() {
+    let dir = tempdir().unwrap();
+    let env = Environment::builder().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(db.dbi(), b"key", b"value", WriteFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    for compact in [false, true] {
+        let copy_dir = tempdir().unwrap();
+        env.copy(&copy_dir.path().join("mdbx.dat"), compact).unwrap();
+
+        let copy = Environment::builder().open(copy_dir.path()).unwrap();
+        let txn = copy.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(db.dbi(), b"key").unwrap(), Some(*b"value"));
+    }
+}
+
 #[test]
 fn test_stat() {
     let dir = tempdir().unwrap();
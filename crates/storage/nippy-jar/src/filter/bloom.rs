@@ -0,0 +1,116 @@
+use super::InclusionFilter;
+use crate::NippyJarError;
+use serde::{Deserialize, Serialize};
+
+/// Number of bits per element, which with [NUM_HASHES] hash functions gives a false positive rate
+/// of about 1% at max capacity.
+const BITS_PER_ELEMENT: usize = 10;
+
+/// Number of hash functions.
+const NUM_HASHES: u64 = 7;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// [BloomFilter](https://en.wikipedia.org/wiki/Bloom_filter). It builds and provides an approximated set-membership filter to answer queries such as "Does this element belong to this set?". Has a theoretical 1% false positive rate at max capacity.
+///
+/// Uses less memory than [Cuckoo](super::Cuckoo) for the same number of elements, which makes it
+/// suitable for filters that are kept in memory for every file, like the transaction hashes of
+/// snapshots.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Bloom {
+    /// Remaining number of elements that can be added before the false positive rate exceeds
+    /// the target.
+    remaining: usize,
+
+    /// Bit set of the filter.
+    bits: Vec<u64>,
+}
+
+impl Bloom {
+    pub fn new(max_capacity: usize) -> Self {
+        let num_bits = max_capacity.max(1) * BITS_PER_ELEMENT;
+        Bloom { remaining: max_capacity, bits: vec![0; (num_bits + 63) / 64] }
+    }
+
+    /// Returns the indices of the bits of the element, derived with double hashing from two
+    /// [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function) hashes.
+    ///
+    /// The hashes are stable across platforms and versions, since the filter is persisted.
+    fn bit_indices(&self, element: &[u8]) -> impl Iterator<Item = usize> {
+        let h1 = fnv1a(FNV_OFFSET_BASIS, element);
+        let h2 = fnv1a(h1, element) | 1;
+        let num_bits = self.bits.len() as u64 * 64;
+        (0..NUM_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+impl InclusionFilter for Bloom {
+    fn add(&mut self, element: &[u8]) -> Result<(), NippyJarError> {
+        if self.remaining == 0 {
+            return Err(NippyJarError::FilterMaxCapacity)
+        }
+
+        self.remaining -= 1;
+
+        for index in self.bit_indices(element).collect::<Vec<_>>() {
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+        Ok(())
+    }
+
+    fn contains(&self, element: &[u8]) -> Result<bool, NippyJarError> {
+        Ok(self.bit_indices(element).all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0))
+    }
+
+    fn size(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+}
+
+impl std::fmt::Debug for Bloom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Bloom")
+            .field("remaining", &self.remaining)
+            .field("filter_size", &self.size())
+            .finish_non_exhaustive()
+    }
+}
+
+fn fnv1a(basis: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(basis, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, RngCore, SeedableRng};
+
+    #[test]
+    fn false_positive_rate() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut random_hash = || {
+            let mut hash = [0u8; 32];
+            rng.fill_bytes(&mut hash);
+            hash
+        };
+
+        let capacity = 10_000;
+        let mut bloom = Bloom::new(capacity);
+        let elements = (0..capacity).map(|_| random_hash()).collect::<Vec<_>>();
+        for element in &elements {
+            bloom.add(element).unwrap();
+        }
+        assert!(matches!(bloom.add(&random_hash()), Err(NippyJarError::FilterMaxCapacity)));
+
+        // no false negatives
+        for element in &elements {
+            assert!(bloom.contains(element).unwrap());
+        }
+
+        let false_positives =
+            (0..capacity).filter(|_| bloom.contains(&random_hash()).unwrap()).count();
+        assert!(false_positives < capacity / 50, "{false_positives} false positives");
+    }
+}
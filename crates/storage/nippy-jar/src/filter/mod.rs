@@ -1,6 +1,9 @@
 use crate::NippyJarError;
 use serde::{Deserialize, Serialize};
 
+mod bloom;
+pub use bloom::Bloom;
+
 mod cuckoo;
 pub use cuckoo::Cuckoo;
 
@@ -20,29 +23,28 @@ pub trait InclusionFilter {
 #[cfg_attr(test, derive(PartialEq))]
 pub enum InclusionFilters {
     Cuckoo(Cuckoo),
-    // Avoids irrefutable let errors. Remove this after adding another one.
-    Unused,
+    Bloom(Bloom),
 }
 
 impl InclusionFilter for InclusionFilters {
     fn add(&mut self, element: &[u8]) -> Result<(), NippyJarError> {
         match self {
             InclusionFilters::Cuckoo(c) => c.add(element),
-            InclusionFilters::Unused => todo!(),
+            InclusionFilters::Bloom(b) => b.add(element),
         }
     }
 
     fn contains(&self, element: &[u8]) -> Result<bool, NippyJarError> {
         match self {
             InclusionFilters::Cuckoo(c) => c.contains(element),
-            InclusionFilters::Unused => todo!(),
+            InclusionFilters::Bloom(b) => b.contains(element),
         }
     }
 
     fn size(&self) -> usize {
         match self {
             InclusionFilters::Cuckoo(c) => c.size(),
-            InclusionFilters::Unused => 0,
+            InclusionFilters::Bloom(b) => b.size(),
         }
     }
 }
@@ -21,7 +21,7 @@ use sucds::{int_vectors::PrefixSummedEliasFano, Serializable};
 use tracing::*;
 
 pub mod filter;
-use filter::{Cuckoo, InclusionFilter, InclusionFilters};
+use filter::{Bloom, Cuckoo, InclusionFilter, InclusionFilters};
 
 pub mod compression;
 use compression::{Compression, Compressors};
@@ -190,6 +190,12 @@ impl<H: NippyJarHeader> NippyJar<H> {
         self
     }
 
+    /// Adds [`filter::Bloom`] filter.
+    pub fn with_bloom_filter(mut self, max_capacity: usize) -> Self {
+        self.filter = Some(InclusionFilters::Bloom(Bloom::new(max_capacity)));
+        self
+    }
+
     /// Adds [`phf::Fmph`] perfect hashing function.
     pub fn with_fmph(mut self) -> Self {
         self.phf = Some(Functions::Fmph(Fmph::new()));
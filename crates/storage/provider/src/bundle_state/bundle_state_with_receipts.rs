@@ -11,7 +11,7 @@ use reth_primitives::{
     Account, Address, BlockNumber, Bloom, Bytecode, Log, Receipt, Receipts, StorageEntry, B256,
     U256,
 };
-use reth_trie::HashedPostState;
+use reth_trie::{keccak::PrehashedKeys, HashedPostState};
 use revm::{
     db::{states::BundleState, BundleAccount},
     primitives::AccountInfo,
@@ -137,6 +137,12 @@ impl BundleStateWithReceipts {
         HashedPostState::from_bundle_state(&self.bundle.state)
     }
 
+    /// Returns [HashedPostState] for this bundle state, reusing the given prehashed keys.
+    /// See [HashedPostState::from_bundle_state_prehashed] for more info.
+    pub fn hash_state_prehashed(&self, prehashed: &PrehashedKeys) -> HashedPostState {
+        HashedPostState::from_bundle_state_prehashed(&self.bundle.state, prehashed)
+    }
+
     /// Transform block number to the index of block.
     fn block_number_to_index(&self, block_number: BlockNumber) -> Option<usize> {
         if self.first_block > block_number {
@@ -73,6 +73,11 @@ impl Chain {
         &self.state
     }
 
+    /// Get the trie updates of this chain, if any.
+    pub fn trie_updates(&self) -> Option<&TrieUpdates> {
+        self.trie_updates.as_ref()
+    }
+
     /// Prepends the given state to the current state.
     pub fn prepend_state(&mut self, state: BundleState) {
         self.state.prepend_state(state);
@@ -18,8 +18,9 @@ pub use traits::*;
 /// Provider trait implementations.
 pub mod providers;
 pub use providers::{
-    DatabaseProvider, DatabaseProviderRO, DatabaseProviderRW, HistoricalStateProvider,
-    HistoricalStateProviderRef, LatestStateProvider, LatestStateProviderRef, ProviderFactory,
+    DatabaseProvider, DatabaseProviderRO, DatabaseProviderRW, ExecutionWitness,
+    HistoricalStateProvider, HistoricalStateProviderRef, LatestStateProvider,
+    LatestStateProviderRef, ProviderFactory, WitnessStateProvider,
 };
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -5,13 +5,17 @@ use crate::{
     },
     traits::{BlockSource, ReceiptProvider},
     BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider, EvmEnvProvider,
-    HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, HeaderSyncMode, ProviderError,
-    PruneCheckpointReader, StageCheckpointReader, StateProviderBox, TransactionVariant,
+    FinalizedBlockReader, FinalizedBlockWriter, HeaderAccumulatorReader, HeaderProvider,
+    HeaderSyncGap, HeaderSyncGapProvider, HeaderSyncMode, ProviderError, PruneCheckpointReader,
+    StageCheckpointReader, StateProviderBox, TransactionVariant, TransactionWithBlockReceipts,
     TransactionsProvider, WithdrawalsProvider,
 };
-use reth_db::{database::Database, init_db, models::StoredBlockBodyIndices, DatabaseEnv};
+use reth_db::{
+    database::Database, init_db, models::StoredBlockBodyIndices, transaction::DbTx, DatabaseEnv,
+};
 use reth_interfaces::{provider::ProviderResult, RethError, RethResult};
 use reth_primitives::{
+    accumulator::HeaderAccumulatorProof,
     snapshot::HighestSnapshots,
     stage::{StageCheckpoint, StageId},
     Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, ChainInfo,
@@ -19,6 +23,7 @@ use reth_primitives::{
     SealedHeader, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
     Withdrawal, B256, U256,
 };
+use reth_trie::hashed_cursor::SharedStorageFilter;
 use revm::primitives::{BlockEnv, CfgEnv};
 use std::{
     ops::{RangeBounds, RangeInclusive},
@@ -45,6 +50,8 @@ pub struct ProviderFactory<DB> {
     chain_spec: Arc<ChainSpec>,
     /// Snapshot Provider
     snapshot_provider: Option<Arc<SnapshotProvider>>,
+    /// Filter over the hashed addresses with storage, consulted by the latest state provider
+    storage_filter: Option<SharedStorageFilter>,
 }
 
 impl<DB: Clone> Clone for ProviderFactory<DB> {
@@ -53,6 +60,7 @@ impl<DB: Clone> Clone for ProviderFactory<DB> {
             db: self.db.clone(),
             chain_spec: Arc::clone(&self.chain_spec),
             snapshot_provider: self.snapshot_provider.clone(),
+            storage_filter: self.storage_filter.clone(),
         }
     }
 }
@@ -60,7 +68,7 @@ impl<DB: Clone> Clone for ProviderFactory<DB> {
 impl<DB> ProviderFactory<DB> {
     /// Create new database provider factory.
     pub fn new(db: DB, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { db, chain_spec, snapshot_provider: None }
+        Self { db, chain_spec, snapshot_provider: None, storage_filter: None }
     }
 
     /// Create new database provider by passing a path. [`ProviderFactory`] will own the database
@@ -74,26 +82,50 @@ impl<DB> ProviderFactory<DB> {
             db: init_db(path, args).map_err(|e| RethError::Custom(e.to_string()))?,
             chain_spec,
             snapshot_provider: None,
+            storage_filter: None,
         })
     }
 
     /// Database provider that comes with a shared snapshot provider.
+    ///
+    /// If `load_filters` is set, the filters of the snapshots are loaded into memory, which is
+    /// required to find transactions whose lookup entries were pruned.
     pub fn with_snapshots(
         mut self,
         snapshots_path: PathBuf,
         highest_snapshot_tracker: watch::Receiver<Option<HighestSnapshots>>,
+        load_filters: bool,
     ) -> ProviderResult<Self> {
-        self.snapshot_provider = Some(Arc::new(
-            SnapshotProvider::new(snapshots_path)?
-                .with_highest_tracker(Some(highest_snapshot_tracker)),
-        ));
+        let mut snapshot_provider = SnapshotProvider::new(snapshots_path)?
+            .with_highest_tracker(Some(highest_snapshot_tracker));
+        if load_filters {
+            snapshot_provider = snapshot_provider.with_filters();
+        }
+        self.snapshot_provider = Some(Arc::new(snapshot_provider));
         Ok(self)
     }
 
+    /// Sets the filter over the hashed addresses with storage, which the latest state provider
+    /// consults before reading storages for proofs.
+    pub fn with_storage_filter(mut self, storage_filter: SharedStorageFilter) -> Self {
+        self.storage_filter = Some(storage_filter);
+        self
+    }
+
+    /// Returns the filter over the hashed addresses with storage, if set.
+    pub fn storage_filter(&self) -> Option<&SharedStorageFilter> {
+        self.storage_filter.as_ref()
+    }
+
     /// Returns reference to the underlying database.
     pub fn db_ref(&self) -> &DB {
         &self.db
     }
+
+    /// Returns the shared snapshot provider, if snapshots are configured.
+    pub fn snapshot_provider(&self) -> Option<Arc<SnapshotProvider>> {
+        self.snapshot_provider.clone()
+    }
 }
 
 impl<DB: Database> ProviderFactory<DB> {
@@ -126,11 +158,22 @@ impl<DB: Database> ProviderFactory<DB> {
         Ok(DatabaseProviderRW(provider))
     }
 
+    /// Sets the storage filter of the factory on the latest state provider.
+    fn latest_with_filter<TX: DbTx>(
+        &self,
+        provider: LatestStateProvider<TX>,
+    ) -> LatestStateProvider<TX> {
+        match &self.storage_filter {
+            Some(filter) => provider.with_storage_filter(filter.clone()),
+            None => provider,
+        }
+    }
+
     /// Storage provider for latest block
     #[track_caller]
     pub fn latest(&self) -> ProviderResult<StateProviderBox> {
         trace!(target: "providers::db", "Returning latest state provider");
-        Ok(Box::new(LatestStateProvider::new(self.db.tx()?)))
+        Ok(Box::new(self.latest_with_filter(LatestStateProvider::new(self.db.tx()?))))
     }
 
     /// Storage provider for state at that given block
@@ -143,7 +186,9 @@ impl<DB: Database> ProviderFactory<DB> {
         if block_number == provider.best_block_number().unwrap_or_default() &&
             block_number == provider.last_block_number().unwrap_or_default()
         {
-            return Ok(Box::new(LatestStateProvider::new(provider.into_tx())))
+            return Ok(Box::new(
+                self.latest_with_filter(LatestStateProvider::new(provider.into_tx())),
+            ))
         }
 
         // +1 as the changeset that we want is the one that was applied after this block.
@@ -325,6 +370,13 @@ impl<DB: Database> BlockReader for ProviderFactory<DB> {
     fn block_range(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<Vec<Block>> {
         self.provider()?.block_range(range)
     }
+
+    fn transactions_with_block_receipts(
+        &self,
+        hashes: &[TxHash],
+    ) -> ProviderResult<Vec<Option<TransactionWithBlockReceipts>>> {
+        self.provider()?.transactions_with_block_receipts(hashes)
+    }
 }
 
 impl<DB: Database> TransactionsProvider for ProviderFactory<DB> {
@@ -498,27 +550,73 @@ impl<DB: Database> PruneCheckpointReader for ProviderFactory<DB> {
     }
 }
 
+impl<DB: Database> FinalizedBlockReader for ProviderFactory<DB> {
+    fn last_finalized_block_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.provider()?.last_finalized_block_number()
+    }
+
+    fn last_safe_block_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.provider()?.last_safe_block_number()
+    }
+}
+
+impl<DB: Database> HeaderAccumulatorReader for ProviderFactory<DB> {
+    fn header_accumulator_root(&self) -> ProviderResult<B256> {
+        self.provider()?.header_accumulator_root()
+    }
+
+    fn header_accumulator_proof(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Option<HeaderAccumulatorProof>> {
+        self.provider()?.header_accumulator_proof(block_number)
+    }
+}
+
+impl<DB: Database> FinalizedBlockWriter for ProviderFactory<DB> {
+    fn save_finalized_block_number(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        let provider = self.provider_rw()?;
+        provider.save_finalized_block_number(block_number)?;
+        provider.commit()?;
+        Ok(())
+    }
+
+    fn save_safe_block_number(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        let provider = self.provider_rw()?;
+        provider.save_safe_block_number(block_number)?;
+        provider.commit()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ProviderFactory;
     use crate::{
-        test_utils::create_test_provider_factory, BlockHashReader, BlockNumReader, BlockWriter,
-        HeaderSyncGapProvider, HeaderSyncMode, TransactionsProvider,
+        test_utils::{blocks::BlockChainTestData, create_test_provider_factory},
+        BlockExecutionWriter, BlockHashReader, BlockNumReader, BlockReader, BlockWriter,
+        ChainSpecProvider, ChangeSetReader, FinalizedBlockReader, FinalizedBlockWriter,
+        HeaderSyncGapProvider, HeaderSyncMode, HistoryWriter, OriginalValuesKnown, ReceiptProvider,
+        TransactionsProvider,
     };
     use alloy_rlp::Decodable;
     use assert_matches::assert_matches;
     use rand::Rng;
-    use reth_db::{tables, test_utils::ERROR_TEMPDIR, transaction::DbTxMut, DatabaseEnv};
+    use reth_db::{
+        models::ShardedKey, tables, test_utils::ERROR_TEMPDIR, transaction::DbTxMut,
+        BlockNumberList, DatabaseEnv,
+    };
     use reth_interfaces::{
         provider::ProviderError,
         test_utils::{
             generators,
-            generators::{random_block, random_header},
+            generators::{random_block, random_header, random_receipt},
         },
         RethError,
     };
     use reth_primitives::{
-        hex_literal::hex, ChainSpecBuilder, PruneMode, PruneModes, SealedBlock, TxNumber, B256,
+        hex_literal::hex, Address, ChainSpecBuilder, PruneMode, PruneModes, SealedBlock, TxNumber,
+        B256,
     };
     use std::{ops::RangeInclusive, sync::Arc};
     use tokio::sync::watch;
@@ -566,6 +664,18 @@ mod tests {
         provider.block_hash(0).unwrap();
     }
 
+    #[test]
+    fn finalized_and_safe_block_numbers() {
+        let factory = create_test_provider_factory();
+        assert_eq!(factory.last_finalized_block_number().unwrap(), None);
+        assert_eq!(factory.last_safe_block_number().unwrap(), None);
+
+        factory.save_finalized_block_number(10).unwrap();
+        factory.save_safe_block_number(12).unwrap();
+        assert_eq!(factory.last_finalized_block_number().unwrap(), Some(10));
+        assert_eq!(factory.last_safe_block_number().unwrap(), Some(12));
+    }
+
     #[test]
     fn insert_block_with_prune_modes() {
         let factory = create_test_provider_factory();
@@ -712,4 +822,76 @@ mod tests {
             Err(RethError::Provider(ProviderError::InconsistentHeaderGap))
         );
     }
+
+    #[test]
+    fn account_change_blocks() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+
+        let address = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        for (key, blocks) in [
+            (ShardedKey::new(address, 5), vec![1, 3, 5]),
+            (ShardedKey::new(address, u64::MAX), vec![7, 9]),
+            (ShardedKey::new(other, u64::MAX), vec![2, 4]),
+        ] {
+            provider
+                .tx_ref()
+                .put::<tables::AccountHistory>(key, BlockNumberList::new_pre_sorted(blocks))
+                .expect("failed to write history");
+        }
+
+        assert_eq!(provider.account_change_blocks(address, 0, 10).unwrap(), vec![1, 3, 5, 7, 9]);
+        assert_eq!(provider.account_change_blocks(address, 2, 3).unwrap(), vec![3, 5, 7]);
+        assert_eq!(provider.account_change_blocks(address, 6, 10).unwrap(), vec![7, 9]);
+        assert_eq!(provider.account_change_blocks(address, 10, 10).unwrap(), Vec::<u64>::new());
+        assert_eq!(provider.account_change_blocks(address, 0, 0).unwrap(), Vec::<u64>::new());
+        assert_eq!(
+            provider.account_change_blocks(Address::with_last_byte(3), 0, 10).unwrap(),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn transactions_with_block_receipts() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+
+        let mut rng = generators::rng();
+        let first = random_block(&mut rng, 0, None, Some(2), None);
+        let second = random_block(&mut rng, 1, Some(first.hash()), Some(2), None);
+        let mut receipts = Vec::new();
+        for block in [&first, &second] {
+            provider.insert_block(block.clone().try_seal_with_senders().unwrap(), None).unwrap();
+            for transaction in &block.body {
+                let receipt = random_receipt(&mut rng, transaction, Some(1));
+                provider
+                    .tx_ref()
+                    .put::<tables::Receipts>(receipts.len() as u64, receipt.clone())
+                    .expect("failed to write receipt");
+                receipts.push(receipt);
+            }
+        }
+
+        let hashes = [second.body[1].hash, B256::random(), first.body[0].hash, second.body[0].hash];
+        let transactions = provider.transactions_with_block_receipts(&hashes).unwrap();
+        assert_eq!(transactions.len(), hashes.len());
+        assert!(transactions[1].is_none());
+        for (hash, transaction) in hashes.iter().zip(transactions) {
+            let Some((transaction, meta, block_receipts)) = transaction else { continue };
+            let (expected_transaction, expected_meta) =
+                provider.transaction_by_hash_with_meta(*hash).unwrap().unwrap();
+            assert_eq!(transaction, expected_transaction);
+            assert_eq!(meta, expected_meta);
+            assert_eq!(
+                *block_receipts,
+                provider.receipts_by_block(meta.block_hash.into()).unwrap().unwrap()
+            );
+            assert_eq!(
+                block_receipts.get(meta.index as usize),
+                provider.receipt_by_hash(*hash).unwrap().as_ref()
+            );
+        }
+        assert_eq!(receipts[3], provider.receipt_by_hash(second.body[1].hash).unwrap().unwrap());
+    }
 }
@@ -1,15 +1,21 @@
 use crate::{
     bundle_state::{BundleStateInit, BundleStateWithReceipts, HashedStateChanges, RevertsInit},
-    providers::{database::metrics, SnapshotProvider},
+    providers::{
+        database::metrics,
+        state::historical::{HistoricalStateProviderRef, LowestAvailableBlocks},
+        SnapshotProvider,
+    },
     to_range,
     traits::{
         AccountExtReader, BlockSource, ChangeSetReader, ReceiptProvider, StageCheckpointWriter,
     },
     AccountReader, BlockExecutionWriter, BlockHashReader, BlockNumReader, BlockReader, BlockWriter,
-    Chain, EvmEnvProvider, HashingWriter, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider,
-    HeaderSyncMode, HistoryWriter, OriginalValuesKnown, ProviderError, PruneCheckpointReader,
-    PruneCheckpointWriter, StageCheckpointReader, StorageReader, TransactionVariant,
-    TransactionsProvider, TransactionsProviderExt, WithdrawalsProvider,
+    Chain, EvmEnvProvider, FinalizedBlockReader, FinalizedBlockWriter, HashingWriter,
+    HeaderAccumulatorReader, HeaderAccumulatorWriter, HeaderProvider, HeaderSyncGap,
+    HeaderSyncGapProvider, HeaderSyncMode, HistoryWriter, OriginalValuesKnown, ProviderError,
+    PruneCheckpointReader, PruneCheckpointWriter, StageCheckpointReader, StateProvider,
+    StorageReader, TransactionVariant, TransactionWithBlockReceipts, TransactionsProvider,
+    TransactionsProviderExt, WithdrawalsProvider,
 };
 use ahash::{AHashMap, AHashSet};
 use itertools::{izip, Itertools};
@@ -19,7 +25,8 @@ use reth_db::{
     database::Database,
     models::{
         sharded_key, storage_sharded_key::StorageShardedKey, AccountBeforeTx, BlockNumberAddress,
-        ShardedKey, StoredBlockBodyIndices, StoredBlockOmmers, StoredBlockWithdrawals,
+        ChainStateKey, ShardedKey, StoredBlockBodyIndices, StoredBlockOmmers,
+        StoredBlockWithdrawals,
     },
     table::{Table, TableRow},
     tables,
@@ -32,6 +39,10 @@ use reth_interfaces::{
     RethError, RethResult,
 };
 use reth_primitives::{
+    accumulator::{
+        epoch_block_range, epoch_of, historical_accumulator_root, EpochAccumulator,
+        HeaderAccumulatorProof, HeaderRecord,
+    },
     keccak256,
     revm::{
         config::revm_spec,
@@ -41,9 +52,10 @@ use reth_primitives::{
     trie::Nibbles,
     Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders,
     ChainInfo, ChainSpec, GotExpected, Hardfork, Head, Header, PruneCheckpoint, PruneModes,
-    PruneSegment, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, SnapshotSegment,
-    StorageEntry, TransactionMeta, TransactionSigned, TransactionSignedEcRecovered,
-    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, B256, U256,
+    PruneSegment, Receipt, Receipts, SealedBlock, SealedBlockWithSenders, SealedHeader,
+    SnapshotSegment, StorageEntry, TransactionMeta, TransactionSigned,
+    TransactionSignedEcRecovered, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, B256,
+    U256,
 };
 use reth_trie::{prefix_set::PrefixSetMut, updates::TrieUpdates, HashedPostState, StateRoot};
 use revm::primitives::{BlockEnv, CfgEnv, SpecId};
@@ -363,6 +375,114 @@ impl<TX: DbTx> DatabaseProvider<TX> {
             |_| true,
         )
     }
+
+    /// Returns the canonical blocks in the given range and their execution outcome.
+    ///
+    /// Unlike [BlockExecutionWriter::get_block_and_execution_range], the range does not need to
+    /// end at the tip and only a read-only transaction is required. The state after the range is
+    /// read from the history of the next block, which fails if that history was pruned.
+    pub fn canonical_chain(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<Chain> {
+        let mut blocks = Vec::new();
+        let mut receipts = Vec::new();
+        for number in range.clone() {
+            let hash = self
+                .block_hash(number)?
+                .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))?;
+            let block = self
+                .block_with_senders(number.into(), TransactionVariant::WithHash)?
+                .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+            let block_receipts = self.receipts_by_block(number.into())?.unwrap_or_default();
+            receipts.push(block_receipts.into_iter().map(Some).collect());
+            blocks.push(block.seal(hash));
+        }
+        let Some(first_block_number) = blocks.first().map(|block| block.number) else {
+            return Ok(Chain::default())
+        };
+
+        // The state after the range is the state before the changes of the next block.
+        let lowest_history_block = |segment: PruneSegment| -> ProviderResult<_> {
+            Ok(self.get_prune_checkpoint(segment)?.and_then(|checkpoint| checkpoint.block_number))
+        };
+        let state_after_range = HistoricalStateProviderRef::new_with_lowest_available_blocks(
+            &self.tx,
+            range.end() + 1,
+            LowestAvailableBlocks {
+                account_history_block_number: lowest_history_block(PruneSegment::AccountHistory)?
+                    .map(|number| number + 1),
+                storage_history_block_number: lowest_history_block(PruneSegment::StorageHistory)?
+                    .map(|number| number + 1),
+            },
+        );
+
+        let mut state: BundleStateInit = HashMap::new();
+        // every block gets a revert entry, so the reverts line up with the blocks even if a block
+        // did not change any state
+        let mut reverts: RevertsInit =
+            range.clone().map(|number| (number, HashMap::new())).collect();
+
+        // iterate the changesets in reverse, so the oldest value of every account and storage
+        // slot in the range is kept
+        let account_changeset = self
+            .tx
+            .cursor_read::<tables::AccountChangeSet>()?
+            .walk_range(range.clone())?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (block_number, account_before) in account_changeset.into_iter().rev() {
+            let AccountBeforeTx { info: old_info, address } = account_before;
+            match state.entry(address) {
+                hash_map::Entry::Vacant(entry) => {
+                    let new_info = state_after_range.basic_account(address)?;
+                    entry.insert((old_info, new_info, HashMap::new()));
+                }
+                hash_map::Entry::Occupied(mut entry) => {
+                    entry.get_mut().0 = old_info;
+                }
+            }
+            reverts.entry(block_number).or_default().entry(address).or_default().0 = Some(old_info);
+        }
+
+        let storage_changeset = self
+            .tx
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk_range(BlockNumberAddress::range(range))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (block_and_address, old_storage) in storage_changeset.into_iter().rev() {
+            let BlockNumberAddress((block_number, address)) = block_and_address;
+            let account_state = match state.entry(address) {
+                hash_map::Entry::Vacant(entry) => {
+                    let info = state_after_range.basic_account(address)?;
+                    entry.insert((info, info, HashMap::new()))
+                }
+                hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            };
+            match account_state.2.entry(old_storage.key) {
+                hash_map::Entry::Vacant(entry) => {
+                    let new_value =
+                        state_after_range.storage(address, old_storage.key)?.unwrap_or_default();
+                    entry.insert((old_storage.value, new_value));
+                }
+                hash_map::Entry::Occupied(mut entry) => {
+                    entry.get_mut().0 = old_storage.value;
+                }
+            }
+            reverts
+                .entry(block_number)
+                .or_default()
+                .entry(address)
+                .or_default()
+                .1
+                .push(old_storage);
+        }
+
+        let state = BundleStateWithReceipts::new_init(
+            state,
+            reverts,
+            Vec::new(),
+            Receipts::from_vec(receipts),
+            first_block_number,
+        );
+        Ok(Chain::new(blocks, state, None))
+    }
 }
 
 impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
@@ -544,7 +664,7 @@ impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
             state,
             reverts,
             Vec::new(),
-            reth_primitives::Receipts::from_vec(receipts),
+            Receipts::from_vec(receipts),
             start_block_number,
         ))
     }
@@ -1036,6 +1156,38 @@ impl<TX: DbTx> ChangeSetReader for DatabaseProvider<TX> {
             })
             .collect()
     }
+
+    fn account_change_blocks(
+        &self,
+        address: Address,
+        from_block: BlockNumber,
+        limit: usize,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        let mut blocks = Vec::new();
+        if limit == 0 {
+            return Ok(blocks)
+        }
+
+        // Shards are keyed by their highest block number, so the walk starts at the first shard
+        // that can contain `from_block`.
+        let mut cursor = self.tx.cursor_read::<tables::AccountHistory>()?;
+        for entry in cursor.walk(Some(ShardedKey::new(address, from_block)))? {
+            let (key, list) = entry?;
+            if key.key != address {
+                break
+            }
+            for block in list.iter(0).map(|block| block as BlockNumber) {
+                if block < from_block {
+                    continue
+                }
+                blocks.push(block);
+                if blocks.len() == limit {
+                    return Ok(blocks)
+                }
+            }
+        }
+        Ok(blocks)
+    }
 }
 
 impl<TX: DbTx> HeaderSyncGapProvider for DatabaseProvider<TX> {
@@ -1294,6 +1446,7 @@ impl<TX: DbTx> BlockReader for DatabaseProvider<TX> {
             if self.chain_spec.final_paris_total_difficulty(number).is_some() {
                 return Ok(Some(Vec::new()))
             }
+            Self::ensure_history_available(number, self.history_expiry_boundary()?)?;
 
             let ommers = self.tx.get::<tables::BlockOmmers>(number)?.map(|o| o.ommers);
             return Ok(ommers)
@@ -1321,6 +1474,7 @@ impl<TX: DbTx> BlockReader for DatabaseProvider<TX> {
     ) -> ProviderResult<Option<BlockWithSenders>> {
         let Some(block_number) = self.convert_hash_or_number(id)? else { return Ok(None) };
         let Some(header) = self.header_by_number(block_number)? else { return Ok(None) };
+        Self::ensure_history_available(block_number, self.history_expiry_boundary()?)?;
 
         let ommers = self.ommers(block_number.into())?.unwrap_or_default();
         let withdrawals = self.withdrawals_by_block(block_number.into(), header.timestamp)?;
@@ -1372,6 +1526,9 @@ impl<TX: DbTx> BlockReader for DatabaseProvider<TX> {
             return Ok(Vec::new())
         }
 
+        let history_expiry_boundary = self.history_expiry_boundary()?;
+        Self::ensure_history_available(*range.start(), history_expiry_boundary)?;
+
         let len = range.end().saturating_sub(*range.start()) as usize;
         let mut blocks = Vec::with_capacity(len);
 
@@ -1424,6 +1581,46 @@ impl<TX: DbTx> BlockReader for DatabaseProvider<TX> {
         }
         Ok(blocks)
     }
+
+    fn transactions_with_block_receipts(
+        &self,
+        hashes: &[TxHash],
+    ) -> ProviderResult<Vec<Option<TransactionWithBlockReceipts>>> {
+        // Group the transactions by block, so that the header and the receipts of each block are
+        // read at once.
+        let mut blocks: BTreeMap<BlockNumber, Vec<(usize, TxNumber)>> = BTreeMap::new();
+        for (index, hash) in hashes.iter().enumerate() {
+            let Some(id) = self.transaction_id(*hash)? else { continue };
+            let Some(number) = self.transaction_block(id)? else { continue };
+            blocks.entry(number).or_default().push((index, id));
+        }
+
+        let mut transactions = vec![None; hashes.len()];
+        for (block_number, block_transactions) in blocks {
+            let Some(header) = self.sealed_header(block_number)? else { continue };
+            let Some(body) = self.block_body_indices(block_number)? else { continue };
+            let (header, block_hash) = header.split();
+            let receipts = Arc::new(self.receipts_by_tx_range(body.tx_num_range())?);
+            for (index, id) in block_transactions {
+                let Some(tx) = self.transaction_by_id_no_hash(id)? else { continue };
+                let transaction = TransactionSigned {
+                    hash: hashes[index],
+                    signature: tx.signature,
+                    transaction: tx.transaction,
+                };
+                let meta = TransactionMeta {
+                    tx_hash: hashes[index],
+                    index: id - body.first_tx_num(),
+                    block_hash,
+                    block_number,
+                    base_fee: header.base_fee_per_gas,
+                    excess_blob_gas: header.excess_blob_gas,
+                };
+                transactions[index] = Some((transaction, meta, Arc::clone(&receipts)));
+            }
+        }
+        Ok(transactions)
+    }
 }
 
 impl<TX: DbTx> TransactionsProviderExt for DatabaseProvider<TX> {
@@ -1497,7 +1694,21 @@ impl<TX: DbTx> TransactionsProviderExt for DatabaseProvider<TX> {
 
 impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
     fn transaction_id(&self, tx_hash: TxHash) -> ProviderResult<Option<TxNumber>> {
-        Ok(self.tx.get::<tables::TxHashNumber>(tx_hash)?)
+        if let Some(id) = self.tx.get::<tables::TxHashNumber>(tx_hash)? {
+            return Ok(Some(id))
+        }
+
+        // Lookup entries of pruned transactions are replaced by the transaction hash filters of
+        // the transaction snapshots, so only the snapshots whose filter matches are read.
+        match &self.snapshot_provider {
+            Some(snapshot_provider)
+                if snapshot_provider.loads_filters() &&
+                    self.get_prune_checkpoint(PruneSegment::TransactionLookup)?.is_some() =>
+            {
+                snapshot_provider.transaction_id(tx_hash)
+            }
+            _ => Ok(None),
+        }
     }
 
     fn transaction_by_id(&self, id: TxNumber) -> ProviderResult<Option<TransactionSigned>> {
@@ -1590,6 +1801,7 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
 
         if let Some(block_number) = self.convert_hash_or_number(id)? {
             if let Some(body) = self.block_body_indices(block_number)? {
+                Self::ensure_history_available(block_number, self.history_expiry_boundary()?)?;
                 let tx_range = body.tx_num_range();
                 return if tx_range.is_empty() {
                     Ok(Some(Vec::new()))
@@ -1613,8 +1825,10 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
         let mut tx_cursor = self.tx.cursor_read::<tables::Transactions>()?;
         let mut results = Vec::new();
         let mut body_cursor = self.tx.cursor_read::<tables::BlockBodyIndices>()?;
+        let history_expiry_boundary = self.history_expiry_boundary()?;
         for entry in body_cursor.walk_range(range)? {
-            let (_, body) = entry?;
+            let (block_number, body) = entry?;
+            Self::ensure_history_available(block_number, history_expiry_boundary)?;
             let tx_num_range = body.tx_num_range();
             if tx_num_range.is_empty() {
                 results.push(Vec::new());
@@ -1654,12 +1868,21 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
 
 impl<TX: DbTx> ReceiptProvider for DatabaseProvider<TX> {
     fn receipt(&self, id: TxNumber) -> ProviderResult<Option<Receipt>> {
-        self.get_with_snapshot(
+        let receipt = self.get_with_snapshot(
             SnapshotSegment::Receipts,
             id,
             |snapshot| snapshot.receipt(id),
             || Ok(self.tx.get::<tables::Receipts>(id)?),
-        )
+        )?;
+
+        // tell a pruned receipt apart from an unknown transaction
+        if receipt.is_none() {
+            if let Some(block_number) = self.transaction_block(id)? {
+                Self::ensure_history_available(block_number, self.history_expiry_boundary()?)?;
+                self.ensure_receipts_available(block_number)?;
+            }
+        }
+        Ok(receipt)
     }
 
     fn receipt_by_hash(&self, hash: TxHash) -> ProviderResult<Option<Receipt>> {
@@ -1673,6 +1896,8 @@ impl<TX: DbTx> ReceiptProvider for DatabaseProvider<TX> {
     fn receipts_by_block(&self, block: BlockHashOrNumber) -> ProviderResult<Option<Vec<Receipt>>> {
         if let Some(number) = self.convert_hash_or_number(block)? {
             if let Some(body) = self.block_body_indices(number)? {
+                Self::ensure_history_available(number, self.history_expiry_boundary()?)?;
+                self.ensure_receipts_available(number)?;
                 let tx_range = body.tx_num_range();
                 return if tx_range.is_empty() {
                     Ok(Some(Vec::new()))
@@ -2320,8 +2545,9 @@ impl<TX: DbTxMut + DbTx> BlockExecutionWriter for DatabaseProvider<TX> {
             // rm block bodies
             self.get_or_take::<tables::BlockBodyIndices, TAKE>(range)?;
 
-            // Update pipeline progress
             if let Some(fork_number) = unwind_to {
+                self.unwind_header_accumulator(fork_number)?;
+                // Update pipeline progress
                 self.update_pipeline_stages(fork_number, true)?;
             }
         }
@@ -2488,6 +2714,8 @@ impl<TX: DbTxMut + DbTx> BlockWriter for DatabaseProvider<TX> {
             durations_recorder.record_relative(metrics::Action::InsertBlock);
         }
 
+        self.append_header_accumulator(first_number..=last_block_number)?;
+
         // Write state and changesets to the database.
         // Must be written after blocks because of the receipt lookup.
         state.write_to_db(self.tx_ref(), OriginalValuesKnown::No)?;
@@ -2532,6 +2760,179 @@ impl<TX: DbTxMut> PruneCheckpointWriter for DatabaseProvider<TX> {
     }
 }
 
+impl<TX: DbTx> FinalizedBlockReader for DatabaseProvider<TX> {
+    fn last_finalized_block_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        Ok(self.tx.get::<tables::ChainState>(ChainStateKey::LastFinalizedBlock)?)
+    }
+
+    fn last_safe_block_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        Ok(self.tx.get::<tables::ChainState>(ChainStateKey::LastSafeBlock)?)
+    }
+}
+
+impl<TX: DbTxMut> FinalizedBlockWriter for DatabaseProvider<TX> {
+    fn save_finalized_block_number(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        Ok(self.tx.put::<tables::ChainState>(ChainStateKey::LastFinalizedBlock, block_number)?)
+    }
+
+    fn save_safe_block_number(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        Ok(self.tx.put::<tables::ChainState>(ChainStateKey::LastSafeBlock, block_number)?)
+    }
+}
+
+impl<TX: DbTx> DatabaseProvider<TX> {
+    /// Returns the first block with an available body and receipts, or `None` if no history has
+    /// expired.
+    ///
+    /// See [PruneSegment::HistoryExpiry].
+    pub fn history_expiry_boundary(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.earliest_available_block(PruneSegment::HistoryExpiry)
+    }
+
+    /// Returns [ProviderError::HistoryExpired] if the body and receipts of the given block have
+    /// expired.
+    fn ensure_history_available(
+        block_number: BlockNumber,
+        boundary: Option<BlockNumber>,
+    ) -> ProviderResult<()> {
+        match boundary {
+            Some(first_available_block) if block_number < first_available_block => {
+                Err(ProviderError::HistoryExpired { block_number, first_available_block })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns [ProviderError::Pruned] if the receipts of the given block are pruned.
+    ///
+    /// Receipts that are only partially pruned by [PruneSegment::ContractLogs] are still
+    /// returned, since the receipts of the configured addresses are kept.
+    fn ensure_receipts_available(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        match self.earliest_available_block(PruneSegment::Receipts)? {
+            Some(earliest_available_block) if block_number < earliest_available_block => {
+                Err(ProviderError::Pruned {
+                    segment: PruneSegment::Receipts,
+                    block_number,
+                    earliest_available_block,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the roots of all completed epochs, in order.
+    fn epoch_accumulator_roots(&self) -> ProviderResult<Vec<B256>> {
+        self.tx
+            .cursor_read::<tables::EpochAccumulatorRoots>()?
+            .walk(None)?
+            .map(|entry| Ok(entry?.1))
+            .collect()
+    }
+}
+
+impl<TX: DbTx> HeaderAccumulatorReader for DatabaseProvider<TX> {
+    fn header_accumulator_root(&self) -> ProviderResult<B256> {
+        Ok(historical_accumulator_root(&self.epoch_accumulator_roots()?))
+    }
+
+    fn header_accumulator_proof(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Option<HeaderAccumulatorProof>> {
+        let Some(epoch_accumulator) =
+            self.tx.get::<tables::EpochAccumulators>(epoch_of(block_number))?
+        else {
+            return Ok(None)
+        };
+        Ok(HeaderAccumulatorProof::new(
+            block_number,
+            &epoch_accumulator,
+            &self.epoch_accumulator_roots()?,
+        ))
+    }
+}
+
+impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
+    /// Returns the first epoch that is not committed to the header accumulator.
+    fn next_accumulator_epoch(&self) -> ProviderResult<u64> {
+        Ok(self
+            .tx
+            .cursor_read::<tables::EpochAccumulatorRoots>()?
+            .last()?
+            .map_or(0, |(epoch, _)| epoch + 1))
+    }
+
+    /// Commits the given epochs to the header accumulator, the first one has to be the next
+    /// uncommitted epoch.
+    fn commit_accumulator_epochs(&self, epochs: Range<u64>) -> ProviderResult<()> {
+        let mut roots_cursor = self.tx.cursor_write::<tables::EpochAccumulatorRoots>()?;
+        let mut hashes_cursor = self.tx.cursor_read::<tables::CanonicalHeaders>()?;
+        let mut td_cursor = self.tx.cursor_read::<tables::HeaderTD>()?;
+        for epoch in epochs {
+            let mut records = Vec::with_capacity(epoch_block_range(epoch).count());
+            for block_number in epoch_block_range(epoch) {
+                let (_, block_hash) = hashes_cursor
+                    .seek_exact(block_number)?
+                    .ok_or_else(|| ProviderError::HeaderNotFound(block_number.into()))?;
+                let (_, total_difficulty) = td_cursor
+                    .seek_exact(block_number)?
+                    .ok_or(ProviderError::TotalDifficultyNotFound(block_number))?;
+                records.push(HeaderRecord { block_hash, total_difficulty: total_difficulty.0 });
+            }
+
+            let epoch_accumulator = EpochAccumulator { records };
+            roots_cursor.append(epoch, epoch_accumulator.root())?;
+            self.tx.put::<tables::EpochAccumulators>(epoch, epoch_accumulator)?;
+            debug!(target: "providers::db", epoch, "Committed epoch to the header accumulator");
+        }
+
+        Ok(())
+    }
+}
+
+impl<TX: DbTxMut + DbTx> HeaderAccumulatorWriter for DatabaseProvider<TX> {
+    fn append_header_accumulator(&self, blocks: RangeInclusive<BlockNumber>) -> ProviderResult<()> {
+        let completed = epoch_of(*blocks.start())..epoch_of(blocks.end() + 1);
+        if completed.is_empty() {
+            return Ok(())
+        }
+
+        let next_epoch = self.next_accumulator_epoch()?;
+        if next_epoch < completed.start {
+            debug!(target: "providers::db", next_epoch, "Header accumulator is not backfilled, skipping completed epochs");
+            return Ok(())
+        }
+        self.commit_accumulator_epochs(next_epoch.max(completed.start)..completed.end)
+    }
+
+    fn backfill_header_accumulator(&self, tip: BlockNumber) -> ProviderResult<u64> {
+        let next_epoch = self.next_accumulator_epoch()?;
+        let completed_epochs = epoch_of(tip + 1);
+        if next_epoch >= completed_epochs {
+            return Ok(0)
+        }
+        self.commit_accumulator_epochs(next_epoch..completed_epochs)?;
+        Ok(completed_epochs - next_epoch)
+    }
+
+    fn unwind_header_accumulator(&self, unwind_to: BlockNumber) -> ProviderResult<()> {
+        // the epochs that contain blocks above the unwind target are no longer completed
+        let first_removed = epoch_of(unwind_to + 1);
+        let mut roots_cursor = self.tx.cursor_write::<tables::EpochAccumulatorRoots>()?;
+        let mut accumulators_cursor = self.tx.cursor_write::<tables::EpochAccumulators>()?;
+        while let Some((epoch, _)) = roots_cursor.last()? {
+            if epoch < first_removed {
+                break
+            }
+            roots_cursor.delete_current()?;
+            if accumulators_cursor.seek_exact(epoch)?.is_some() {
+                accumulators_cursor.delete_current()?;
+            }
+        }
+        Ok(())
+    }
+}
+
 fn range_size_hint(range: &impl RangeBounds<TxNumber>) -> Option<usize> {
     let start = match range.start_bound().cloned() {
         Bound::Included(start) => start,
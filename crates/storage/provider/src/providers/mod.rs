@@ -1,10 +1,13 @@
 use crate::{
     AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
     BlockchainTreePendingStateProvider, BundleStateDataProvider, CanonChainTracker,
-    CanonStateNotifications, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    DatabaseProviderFactory, EvmEnvProvider, HeaderProvider, ProviderError, PruneCheckpointReader,
-    ReceiptProvider, ReceiptProviderIdExt, StageCheckpointReader, StateProviderBox,
-    StateProviderFactory, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
+    CanonStateNotification, CanonStateNotifications, CanonStateReplay, CanonStateSubscriptions,
+    ChainSpecProvider, ChangeSetReader, DatabaseProviderFactory, EvmEnvProvider,
+    FinalizedBlockReader, FinalizedBlockWriter, ForkChoiceNotifications, HeaderAccumulatorReader,
+    HeaderProvider, ProviderError, PruneCheckpointReader, ReceiptProvider, ReceiptProviderIdExt,
+    ReorgEvent, ReorgHistoryProvider, StageCheckpointReader, StateProviderBox,
+    StateProviderFactory, TransactionVariant, TransactionWithBlockReceipts, TransactionsProvider,
+    WithdrawalsProvider,
 };
 use reth_db::{database::Database, models::StoredBlockBodyIndices};
 use reth_interfaces::{
@@ -14,6 +17,7 @@ use reth_interfaces::{
     RethError, RethResult,
 };
 use reth_primitives::{
+    accumulator::HeaderAccumulatorProof,
     stage::{StageCheckpoint, StageId},
     Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber,
     BlockNumberOrTag, BlockWithSenders, ChainInfo, ChainSpec, Header, PruneCheckpoint,
@@ -27,11 +31,12 @@ use std::{
     sync::Arc,
     time::Instant,
 };
-use tracing::trace;
+use tracing::{debug, error, trace};
 
 pub use state::{
     historical::{HistoricalStateProvider, HistoricalStateProviderRef},
     latest::{LatestStateProvider, LatestStateProviderRef},
+    witness::{ExecutionWitness, WitnessStateProvider},
 };
 
 mod bundle_state_provider;
@@ -77,17 +82,87 @@ where
 {
     /// Create a new provider using only the database and the tree, fetching the latest header from
     /// the database to initialize the provider.
+    ///
+    /// The safe and finalized blocks are restored from the database, if they were recorded.
     pub fn new(database: ProviderFactory<DB>, tree: Tree) -> ProviderResult<Self> {
         let provider = database.provider()?;
         let best: ChainInfo = provider.chain_info()?;
-        match provider.header_by_number(best.best_number)? {
-            Some(header) => {
-                drop(provider);
-                Ok(Self::with_latest(database, tree, header.seal(best.best_hash)))
+        let Some(header) = provider.header_by_number(best.best_number)? else {
+            return Err(ProviderError::HeaderNotFound(best.best_number.into()))
+        };
+        let safe = provider
+            .last_safe_block_number()?
+            .map(|number| provider.sealed_header(number))
+            .transpose()?
+            .flatten();
+        let finalized = provider
+            .last_finalized_block_number()?
+            .map(|number| provider.sealed_header(number))
+            .transpose()?
+            .flatten();
+        drop(provider);
+
+        let this = Self::with_latest(database, tree, header.seal(best.best_hash));
+        if let Some(header) = safe {
+            this.chain_info.set_safe(header);
+        }
+        if let Some(header) = finalized {
+            this.chain_info.set_finalized(header);
+        }
+        Ok(this)
+    }
+
+    /// Persists the safe and finalized blocks whenever the consensus layer reports new ones.
+    ///
+    /// This is meant to run as a background task, so the forkchoice updates of the engine don't
+    /// wait for the write transaction. A failed write is logged and retried with the next update,
+    /// until then the persisted finalized block lags behind, which only makes the pruner more
+    /// conservative.
+    pub async fn persist_safe_and_finalized(self) {
+        let mut safe = self.chain_info.subscribe_safe_block();
+        let mut finalized = self.chain_info.subscribe_finalized_block();
+        let mut persisted = (None, None);
+        loop {
+            let latest = (
+                safe.borrow_and_update().as_ref().map(|header| header.number),
+                finalized.borrow_and_update().as_ref().map(|header| header.number),
+            );
+            if latest != persisted {
+                match self.save_safe_and_finalized(latest) {
+                    Ok(()) => {
+                        debug!(target: "providers::blockchain", safe = ?latest.0, finalized = ?latest.1, "Persisted safe and finalized blocks");
+                        persisted = latest;
+                    }
+                    Err(err) => {
+                        error!(target: "providers::blockchain", %err, safe = ?latest.0, finalized = ?latest.1, "Failed to persist safe and finalized blocks");
+                    }
+                }
+            }
+
+            // the senders are owned by the chain info tracker of this provider, so the receivers
+            // can't be closed
+            tokio::select! {
+                _ = safe.changed() => {}
+                _ = finalized.changed() => {}
             }
-            None => Err(ProviderError::HeaderNotFound(best.best_number.into())),
         }
     }
+
+    /// Writes the given safe and finalized block numbers in a single transaction.
+    fn save_safe_and_finalized(
+        &self,
+        (safe, finalized): (Option<BlockNumber>, Option<BlockNumber>),
+    ) -> ProviderResult<()> {
+        let provider = self.database.provider_rw()?;
+        if let Some(number) = safe {
+            provider.save_safe_block_number(number)?;
+        }
+        if let Some(number) = finalized {
+            provider.save_finalized_block_number(number)?;
+        }
+        provider.commit()?;
+        Ok(())
+    }
 }
 
 impl<DB, Tree> BlockchainProvider<DB, Tree>
@@ -297,6 +372,13 @@ where
     fn block_range(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<Vec<Block>> {
         self.database.provider()?.block_range(range)
     }
+
+    fn transactions_with_block_receipts(
+        &self,
+        hashes: &[TxHash],
+    ) -> ProviderResult<Vec<Option<TransactionWithBlockReceipts>>> {
+        self.database.provider()?.transactions_with_block_receipts(hashes)
+    }
 }
 
 impl<DB, Tree> TransactionsProvider for BlockchainProvider<DB, Tree>
@@ -499,6 +581,51 @@ where
     }
 }
 
+impl<DB, Tree> FinalizedBlockReader for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: Send + Sync,
+{
+    fn last_finalized_block_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.database.last_finalized_block_number()
+    }
+
+    fn last_safe_block_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.database.last_safe_block_number()
+    }
+}
+
+impl<DB, Tree> HeaderAccumulatorReader for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: Send + Sync,
+{
+    fn header_accumulator_root(&self) -> ProviderResult<B256> {
+        self.database.header_accumulator_root()
+    }
+
+    fn header_accumulator_proof(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Option<HeaderAccumulatorProof>> {
+        self.database.header_accumulator_proof(block_number)
+    }
+}
+
+impl<DB, Tree> FinalizedBlockWriter for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: Send + Sync,
+{
+    fn save_finalized_block_number(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        self.database.save_finalized_block_number(block_number)
+    }
+
+    fn save_safe_block_number(&self, block_number: BlockNumber) -> ProviderResult<()> {
+        self.database.save_safe_block_number(block_number)
+    }
+}
+
 impl<DB, Tree> PruneCheckpointReader for BlockchainProvider<DB, Tree>
 where
     DB: Database,
@@ -708,7 +835,7 @@ where
 
 impl<DB, Tree> CanonChainTracker for BlockchainProvider<DB, Tree>
 where
-    DB: Send + Sync,
+    DB: Database,
     Tree: Send + Sync,
     Self: BlockReader,
 {
@@ -835,6 +962,16 @@ where
     }
 }
 
+impl<DB, Tree> ReorgHistoryProvider for BlockchainProvider<DB, Tree>
+where
+    DB: Send + Sync,
+    Tree: ReorgHistoryProvider,
+{
+    fn reorg_history(&self) -> Vec<ReorgEvent> {
+        self.tree.reorg_history()
+    }
+}
+
 impl<DB, Tree> CanonStateSubscriptions for BlockchainProvider<DB, Tree>
 where
     DB: Send + Sync,
@@ -843,6 +980,28 @@ where
     fn subscribe_to_canonical_state(&self) -> CanonStateNotifications {
         self.tree.subscribe_to_canonical_state()
     }
+
+    fn subscribe_safe_block(&self) -> Option<ForkChoiceNotifications> {
+        Some(self.chain_info.subscribe_safe_block())
+    }
+
+    fn subscribe_finalized_block(&self) -> Option<ForkChoiceNotifications> {
+        Some(self.chain_info.subscribe_finalized_block())
+    }
+}
+
+impl<DB, Tree> CanonStateReplay for BlockchainProvider<DB, Tree>
+where
+    DB: Database,
+    Tree: CanonStateSubscriptions + BlockchainTreeViewer + Send + Sync,
+{
+    fn replay_canonical_state(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<CanonStateNotification> {
+        let chain = self.database.provider()?.canonical_chain(range)?;
+        Ok(CanonStateNotification::Commit { new: Arc::new(chain) })
+    }
 }
 
 impl<DB, Tree> ChangeSetReader for BlockchainProvider<DB, Tree>
@@ -856,6 +1015,15 @@ where
     ) -> ProviderResult<Vec<AccountBeforeTx>> {
         self.database.provider()?.account_block_changeset(block_number)
     }
+
+    fn account_change_blocks(
+        &self,
+        address: Address,
+        from_block: BlockNumber,
+        limit: usize,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.database.provider()?.account_change_blocks(address, from_block, limit)
+    }
 }
 
 impl<DB, Tree> AccountReader for BlockchainProvider<DB, Tree>
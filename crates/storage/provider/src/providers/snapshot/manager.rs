@@ -24,6 +24,7 @@ use std::{
     ops::{Range, RangeBounds, RangeInclusive},
     path::{Path, PathBuf},
 };
+use strum::IntoEnumIterator;
 use tokio::sync::watch;
 
 /// Alias type for a map that can be queried for transaction/block ranges from a block/transaction
@@ -76,6 +77,11 @@ impl SnapshotProvider {
         self
     }
 
+    /// Returns `true` if filters are loaded into memory, so `by_hash` queries are supported.
+    pub fn loads_filters(&self) -> bool {
+        self.load_filters
+    }
+
     /// Adds a highest snapshot tracker to the provider
     pub fn with_highest_tracker(
         mut self,
@@ -262,6 +268,54 @@ impl SnapshotProvider {
         Ok(())
     }
 
+    /// Checks the integrity of the snapshots on disk.
+    ///
+    /// The snapshots of every segment must cover a contiguous block range from genesis, every
+    /// snapshot must load, and the highest snapshot of every segment must not be behind the one
+    /// reported by the highest snapshot tracker.
+    ///
+    /// Returns a description of every problem found, which is empty if the snapshots are intact.
+    pub fn check_integrity(&self) -> ProviderResult<Vec<String>> {
+        let mut problems = Vec::new();
+        let snapshots = iter_snapshots(&self.path)?;
+
+        for (segment, ranges) in &snapshots {
+            let mut next_block = 0;
+            for (block_range, tx_range) in ranges {
+                if *block_range.start() != next_block {
+                    problems.push(format!(
+                        "{segment} snapshot of blocks {block_range:?} doesn't start at block {next_block}"
+                    ));
+                }
+                next_block = block_range.end() + 1;
+
+                if let Err(err) = self.get_or_create_jar_provider(*segment, block_range, tx_range) {
+                    problems.push(format!(
+                        "{segment} snapshot of blocks {block_range:?} doesn't load: {err}"
+                    ));
+                }
+            }
+        }
+
+        let highest = self.highest_tracker.as_ref().and_then(|tracker| *tracker.borrow());
+        if let Some(highest) = highest {
+            for segment in SnapshotSegment::iter() {
+                let Some(expected) = highest.highest(segment) else { continue };
+                let on_disk = snapshots
+                    .get(&segment)
+                    .and_then(|ranges| ranges.last())
+                    .map(|(block_range, _)| *block_range.end());
+                if on_disk.map_or(true, |on_disk| on_disk < expected) {
+                    problems.push(format!(
+                        "highest {segment} snapshot is at block {on_disk:?}, expected block {expected}"
+                    ));
+                }
+            }
+        }
+
+        Ok(problems)
+    }
+
     /// Gets the highest snapshot block if it exists for a snapshot segment.
     pub fn get_highest_snapshot_block(&self, segment: SnapshotSegment) -> Option<BlockNumber> {
         self.snapshots_block_index
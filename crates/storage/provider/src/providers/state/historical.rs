@@ -12,7 +12,8 @@ use reth_db::{
 };
 use reth_interfaces::provider::ProviderResult;
 use reth_primitives::{
-    trie::AccountProof, Account, Address, BlockNumber, Bytecode, StorageKey, StorageValue, B256,
+    trie::AccountProof, Account, Address, BlockNumber, Bytecode, PruneSegment, StorageKey,
+    StorageValue, B256,
 };
 use reth_trie::updates::TrieUpdates;
 
@@ -63,9 +64,10 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
 
     /// Lookup an account in the AccountHistory table
     pub fn account_history_lookup(&self, address: Address) -> ProviderResult<HistoryInfo> {
-        if !self.lowest_available_blocks.is_account_history_available(self.block_number) {
-            return Err(ProviderError::StateAtBlockPruned(self.block_number))
-        }
+        self.ensure_available(
+            PruneSegment::AccountHistory,
+            self.lowest_available_blocks.account_history_block_number,
+        )?;
 
         // history key to search IntegerList of block number changesets.
         let history_key = ShardedKey::new(address, self.block_number);
@@ -82,9 +84,10 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         address: Address,
         storage_key: StorageKey,
     ) -> ProviderResult<HistoryInfo> {
-        if !self.lowest_available_blocks.is_storage_history_available(self.block_number) {
-            return Err(ProviderError::StateAtBlockPruned(self.block_number))
-        }
+        self.ensure_available(
+            PruneSegment::StorageHistory,
+            self.lowest_available_blocks.storage_history_block_number,
+        )?;
 
         // history key to search IntegerList of block number changesets.
         let history_key = StorageShardedKey::new(address, storage_key, self.block_number);
@@ -95,6 +98,25 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         )
     }
 
+    /// Returns [ProviderError::Pruned] if the history of the segment is pruned at the block of
+    /// the provider.
+    fn ensure_available(
+        &self,
+        segment: PruneSegment,
+        lowest_available_block_number: Option<BlockNumber>,
+    ) -> ProviderResult<()> {
+        match lowest_available_block_number {
+            Some(earliest_available_block) if earliest_available_block > self.block_number => {
+                Err(ProviderError::Pruned {
+                    segment,
+                    block_number: self.block_number,
+                    earliest_available_block,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn history_info<T, K>(
         &self,
         key: K,
@@ -346,7 +368,9 @@ mod tests {
         BlockNumberList,
     };
     use reth_interfaces::provider::ProviderError;
-    use reth_primitives::{address, b256, Account, Address, StorageEntry, B256, U256};
+    use reth_primitives::{
+        address, b256, Account, Address, PruneSegment, StorageEntry, B256, U256,
+    };
 
     const ADDRESS: Address = address!("0000000000000000000000000000000000000001");
     const HIGHER_ADDRESS: Address = address!("0000000000000000000000000000000000000005");
@@ -574,11 +598,19 @@ mod tests {
         );
         assert_eq!(
             provider.account_history_lookup(ADDRESS),
-            Err(ProviderError::StateAtBlockPruned(provider.block_number))
+            Err(ProviderError::Pruned {
+                segment: PruneSegment::AccountHistory,
+                block_number: provider.block_number,
+                earliest_available_block: 3
+            })
         );
         assert_eq!(
             provider.storage_history_lookup(ADDRESS, STORAGE),
-            Err(ProviderError::StateAtBlockPruned(provider.block_number))
+            Err(ProviderError::Pruned {
+                segment: PruneSegment::StorageHistory,
+                block_number: provider.block_number,
+                earliest_available_block: 3
+            })
         );
 
         // provider block_number == lowest available block number,
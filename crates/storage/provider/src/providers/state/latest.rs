@@ -11,19 +11,32 @@ use reth_interfaces::provider::{ProviderError, ProviderResult};
 use reth_primitives::{
     trie::AccountProof, Account, Address, BlockNumber, Bytecode, StorageKey, StorageValue, B256,
 };
-use reth_trie::{proof::Proof, updates::TrieUpdates};
+use reth_trie::{
+    hashed_cursor::{FilteredHashedCursorFactory, SharedStorageFilter},
+    proof::Proof,
+    updates::TrieUpdates,
+};
 
 /// State provider over latest state that takes tx reference.
 #[derive(Debug)]
 pub struct LatestStateProviderRef<'b, TX: DbTx> {
     /// database transaction
     db: &'b TX,
+    /// Filter over the hashed addresses with storage
+    storage_filter: Option<&'b SharedStorageFilter>,
 }
 
 impl<'b, TX: DbTx> LatestStateProviderRef<'b, TX> {
     /// Create new state provider
     pub fn new(db: &'b TX) -> Self {
-        Self { db }
+        Self { db, storage_filter: None }
+    }
+
+    /// Sets the filter over the hashed addresses with storage, which is consulted for proofs if
+    /// it is at the block of the state.
+    pub fn with_storage_filter(mut self, storage_filter: Option<&'b SharedStorageFilter>) -> Self {
+        self.storage_filter = storage_filter;
+        self
     }
 }
 
@@ -99,9 +112,17 @@ impl<'b, TX: DbTx> StateProvider for LatestStateProviderRef<'b, TX> {
     }
 
     fn proof(&self, address: Address, slots: &[B256]) -> ProviderResult<AccountProof> {
-        Ok(Proof::new(self.db)
-            .account_proof(address, slots)
-            .map_err(Into::<reth_db::DatabaseError>::into)?)
+        let storage_filter = match self.storage_filter {
+            Some(filter) => filter.get_for_tx(self.db)?,
+            None => None,
+        };
+        let proof = match storage_filter {
+            Some(filter) => Proof::new(self.db)
+                .with_hashed_cursor_factory(FilteredHashedCursorFactory::new(self.db, filter))
+                .account_proof(address, slots),
+            None => Proof::new(self.db).account_proof(address, slots),
+        };
+        Ok(proof.map_err(Into::<reth_db::DatabaseError>::into)?)
     }
 }
 
@@ -110,18 +131,27 @@ impl<'b, TX: DbTx> StateProvider for LatestStateProviderRef<'b, TX> {
 pub struct LatestStateProvider<TX: DbTx> {
     /// database transaction
     db: TX,
+    /// Filter over the hashed addresses with storage
+    storage_filter: Option<SharedStorageFilter>,
 }
 
 impl<TX: DbTx> LatestStateProvider<TX> {
     /// Create new state provider
     pub fn new(db: TX) -> Self {
-        Self { db }
+        Self { db, storage_filter: None }
+    }
+
+    /// Sets the filter over the hashed addresses with storage, which is consulted for proofs if
+    /// it is at the block of the state.
+    pub fn with_storage_filter(mut self, storage_filter: SharedStorageFilter) -> Self {
+        self.storage_filter = Some(storage_filter);
+        self
     }
 
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref(&self) -> LatestStateProviderRef<'_, TX> {
-        LatestStateProviderRef::new(&self.db)
+        LatestStateProviderRef::new(&self.db).with_storage_filter(self.storage_filter.as_ref())
     }
 }
 
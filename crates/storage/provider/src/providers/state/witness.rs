@@ -0,0 +1,163 @@
+use crate::{
+    AccountReader, BlockHashReader, BundleStateWithReceipts, ProviderError, StateProvider,
+    StateRootProvider,
+};
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{
+    keccak256, trie::AccountProof, Account, Address, BlockNumber, Bytecode, Bytes, Header,
+    SealedHeader, StorageKey, StorageValue, B256, KECCAK_EMPTY,
+};
+use reth_trie::{
+    updates::TrieUpdates,
+    witness::{WitnessError, WitnessState},
+};
+use std::collections::HashMap;
+
+/// Everything that is needed to execute a block without access to the database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionWitness {
+    /// RLP encoded nodes of the account and storage tries that are accessed by the block.
+    pub state: Vec<Bytes>,
+    /// Bytecodes of the contracts that are called by the block.
+    pub codes: Vec<Bytes>,
+    /// Consecutive ancestor headers of the block, ending with the parent. Must include all
+    /// headers that are accessed with `BLOCKHASH`.
+    pub headers: Vec<Header>,
+}
+
+/// State provider that serves the parent state of a block from an [ExecutionWitness].
+///
+/// The headers of the witness are verified to form a chain that ends at the parent of the block,
+/// and the state is verified against the state root of the parent. Any access to state that is not
+/// part of the witness returns [ProviderError::ExecutionWitness].
+#[derive(Debug)]
+pub struct WitnessStateProvider {
+    /// Account and storage tries.
+    state: WitnessState,
+    /// Bytecodes by their hash.
+    bytecodes: HashMap<B256, Bytecode>,
+    /// Block hashes of the witness headers.
+    block_hashes: HashMap<BlockNumber, B256>,
+}
+
+impl WitnessStateProvider {
+    /// Create new state provider for the parent state of a block with the given parent hash.
+    ///
+    /// The headers of the witness must be ordered by block number and end with the parent header.
+    pub fn new(witness: ExecutionWitness, parent_hash: B256) -> ProviderResult<Self> {
+        let mut block_hashes = HashMap::with_capacity(witness.headers.len());
+        let mut parent: Option<SealedHeader> = None;
+        for header in witness.headers {
+            let header = header.seal_slow();
+            if let Some(previous) = &parent {
+                if header.parent_hash != previous.hash() || header.number != previous.number + 1 {
+                    return Err(ProviderError::ExecutionWitness(format!(
+                        "header #{} does not extend header #{}",
+                        header.number, previous.number
+                    )))
+                }
+            }
+            block_hashes.insert(header.number, header.hash());
+            parent = Some(header);
+        }
+        let parent = parent.filter(|parent| parent.hash() == parent_hash).ok_or_else(|| {
+            ProviderError::ExecutionWitness(format!(
+                "parent header {parent_hash} is not in witness"
+            ))
+        })?;
+
+        Ok(Self {
+            state: WitnessState::new(parent.state_root, witness.state),
+            bytecodes: witness
+                .codes
+                .into_iter()
+                .map(|code| (keccak256(&code), Bytecode::new_raw(code)))
+                .collect(),
+            block_hashes,
+        })
+    }
+}
+
+impl AccountReader for WitnessStateProvider {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        Ok(self.state.account(keccak256(address)).map_err(witness_error)?.map(|account| Account {
+            nonce: account.nonce(),
+            balance: account.balance(),
+            bytecode_hash: (account.code_hash() != KECCAK_EMPTY).then_some(account.code_hash()),
+        }))
+    }
+}
+
+impl BlockHashReader for WitnessStateProvider {
+    fn block_hash(&self, number: u64) -> ProviderResult<Option<B256>> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(Some(*hash)),
+            None => {
+                Err(ProviderError::ExecutionWitness(format!("header #{number} is not in witness")))
+            }
+        }
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        (start..end).map(|number| self.block_hash(number).map(Option::unwrap_or_default)).collect()
+    }
+}
+
+impl StateRootProvider for WitnessStateProvider {
+    fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        self.state.state_root(&bundle_state.hash_state_slow()).map_err(witness_error)
+    }
+
+    fn state_root_with_updates(
+        &self,
+        _bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+}
+
+impl StateProvider for WitnessStateProvider {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        let value = self
+            .state
+            .storage(keccak256(account), keccak256(storage_key))
+            .map_err(witness_error)?;
+        Ok((!value.is_zero()).then_some(value))
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        match self.bytecodes.get(&code_hash) {
+            Some(bytecode) => Ok(Some(bytecode.clone())),
+            None => Err(ProviderError::ExecutionWitness(format!(
+                "bytecode {code_hash} is not in witness"
+            ))),
+        }
+    }
+
+    fn proof(&self, _address: Address, _keys: &[B256]) -> ProviderResult<AccountProof> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+}
+
+fn witness_error(err: WitnessError) -> ProviderError {
+    ProviderError::ExecutionWitness(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_state_provider<T: StateProvider>() {}
+    #[allow(dead_code)]
+    fn assert_witness_state_provider() {
+        assert_state_provider::<WitnessStateProvider>();
+    }
+}
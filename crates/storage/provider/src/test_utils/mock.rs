@@ -2,18 +2,22 @@ use crate::{
     bundle_state::BundleStateWithReceipts,
     traits::{BlockSource, ReceiptProvider},
     AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    BundleStateDataProvider, ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderProvider,
-    ReceiptProviderIdExt, StateProvider, StateProviderBox, StateProviderFactory, StateRootProvider,
+    BundleStateDataProvider, ChainSpecProvider, ChangeSetReader, EvmEnvProvider,
+    HeaderAccumulatorReader, HeaderProvider, ReceiptProviderIdExt, ReorgEvent,
+    ReorgHistoryProvider, StateProvider, StateProviderBox, StateProviderFactory, StateRootProvider,
     TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use parking_lot::Mutex;
 use reth_db::models::{AccountBeforeTx, StoredBlockBodyIndices};
 use reth_interfaces::provider::{ProviderError, ProviderResult};
 use reth_primitives::{
-    keccak256, trie::AccountProof, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId,
-    BlockNumber, BlockWithSenders, Bytecode, Bytes, ChainInfo, ChainSpec, Header, Receipt,
-    SealedBlock, SealedBlockWithSenders, SealedHeader, StorageKey, StorageValue, TransactionMeta,
-    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, B256, U256,
+    accumulator::{historical_accumulator_root, HeaderAccumulatorProof},
+    keccak256,
+    trie::AccountProof,
+    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumber, BlockWithSenders,
+    Bytecode, Bytes, ChainInfo, ChainSpec, Header, Receipt, SealedBlock, SealedBlockWithSenders,
+    SealedHeader, StorageKey, StorageValue, TransactionMeta, TransactionSigned,
+    TransactionSignedNoHash, TxHash, TxNumber, B256, U256,
 };
 use reth_trie::updates::TrieUpdates;
 use revm::primitives::{BlockEnv, CfgEnv};
@@ -676,6 +680,25 @@ impl WithdrawalsProvider for MockEthProvider {
     }
 }
 
+impl HeaderAccumulatorReader for MockEthProvider {
+    fn header_accumulator_root(&self) -> ProviderResult<B256> {
+        Ok(historical_accumulator_root(&[]))
+    }
+
+    fn header_accumulator_proof(
+        &self,
+        _block_number: BlockNumber,
+    ) -> ProviderResult<Option<HeaderAccumulatorProof>> {
+        Ok(None)
+    }
+}
+
+impl ReorgHistoryProvider for MockEthProvider {
+    fn reorg_history(&self) -> Vec<ReorgEvent> {
+        Vec::new()
+    }
+}
+
 impl ChangeSetReader for MockEthProvider {
     fn account_block_changeset(
         &self,
@@ -683,4 +706,12 @@ impl ChangeSetReader for MockEthProvider {
     ) -> ProviderResult<Vec<AccountBeforeTx>> {
         Ok(Vec::default())
     }
+    fn account_change_blocks(
+        &self,
+        _address: Address,
+        _from_block: BlockNumber,
+        _limit: usize,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        Ok(Vec::default())
+    }
 }
@@ -7,6 +7,8 @@ use reth_primitives::{ChainSpec, MAINNET};
 use std::sync::Arc;
 
 pub mod blocks;
+pub mod reorg;
+
 mod events;
 mod executor;
 mod mock;
@@ -2,14 +2,15 @@ use crate::{
     bundle_state::BundleStateWithReceipts,
     traits::{BlockSource, ReceiptProvider},
     AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
-    ReceiptProviderIdExt, StageCheckpointReader, StateProvider, StateProviderBox,
-    StateProviderFactory, StateRootProvider, TransactionVariant, TransactionsProvider,
-    WithdrawalsProvider,
+    ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderAccumulatorReader, HeaderProvider,
+    PruneCheckpointReader, ReceiptProviderIdExt, ReorgEvent, ReorgHistoryProvider,
+    StageCheckpointReader, StateProvider, StateProviderBox, StateProviderFactory,
+    StateRootProvider, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::models::{AccountBeforeTx, StoredBlockBodyIndices};
 use reth_interfaces::provider::ProviderResult;
 use reth_primitives::{
+    accumulator::{historical_accumulator_root, HeaderAccumulatorProof},
     stage::{StageCheckpoint, StageId},
     trie::AccountProof,
     Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumber, Bytecode,
@@ -273,6 +274,25 @@ impl AccountReader for NoopProvider {
     }
 }
 
+impl HeaderAccumulatorReader for NoopProvider {
+    fn header_accumulator_root(&self) -> ProviderResult<B256> {
+        Ok(historical_accumulator_root(&[]))
+    }
+
+    fn header_accumulator_proof(
+        &self,
+        _block_number: BlockNumber,
+    ) -> ProviderResult<Option<HeaderAccumulatorProof>> {
+        Ok(None)
+    }
+}
+
+impl ReorgHistoryProvider for NoopProvider {
+    fn reorg_history(&self) -> Vec<ReorgEvent> {
+        Vec::new()
+    }
+}
+
 impl ChangeSetReader for NoopProvider {
     fn account_block_changeset(
         &self,
@@ -280,6 +300,14 @@ impl ChangeSetReader for NoopProvider {
     ) -> ProviderResult<Vec<AccountBeforeTx>> {
         Ok(Vec::default())
     }
+    fn account_change_blocks(
+        &self,
+        _address: Address,
+        _from_block: BlockNumber,
+        _limit: usize,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        Ok(Vec::default())
+    }
 }
 
 impl StateRootProvider for NoopProvider {
@@ -0,0 +1,404 @@
+//! Competing chain branches for testing the handling of reorgs.
+//!
+//! A [ChainBranch] is a chain of empty blocks on top of a parent block, whose execution results
+//! change the state deterministically. Branches built on the same parent with different seeds
+//! compete with each other: they change the same shared accounts to different values, and each of
+//! them also changes accounts that are only touched on that branch, so a reorg between them has
+//! to both change and revert state.
+//!
+//! After a branch became canonical, [assert_canonical_branch] checks that the canonical chain, the
+//! plain and hashed state and the trie tables of the database match the branch, and
+//! [assert_cursor_cache_consistent] checks that a [CursorCache] serves the canonical state.
+
+use crate::{
+    bundle_state::{BundleStateInit, RevertsInit},
+    AccountReader, BlockHashReader, BlockNumReader, BundleStateWithReceipts, ProviderFactory,
+    StateProvider,
+};
+use reth_db::{database::Database, tables};
+use reth_primitives::{
+    constants::{EMPTY_OMMER_ROOT_HASH, EMPTY_ROOT_HASH, MIN_PROTOCOL_BASE_FEE},
+    keccak256, Account, Address, BlockNumber, Header, Receipts, SealedBlock,
+    SealedBlockWithSenders, SealedHeader, StorageEntry, B256, U256,
+};
+use reth_trie::{cached_cursors::CursorCache, test_utils::state_root, StateRoot};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The plain state of all accounts of a test chain, without empty storage slots.
+pub type TestState = BTreeMap<Address, (Account, BTreeMap<B256, U256>)>;
+
+/// Returns the address of the shared account with the given index, which is changed by all
+/// branches.
+pub fn shared_account(index: usize) -> Address {
+    Address::from_word(keccak256(format!("shared account {index}")))
+}
+
+/// Builder of a [ChainBranch].
+#[derive(Debug, Clone)]
+pub struct ChainBranchBuilder {
+    parent: SealedHeader,
+    parent_state: TestState,
+    seed: u64,
+    depth: u64,
+    shared_accounts: usize,
+    branch_accounts: usize,
+    slots_per_account: usize,
+}
+
+impl ChainBranchBuilder {
+    /// Creates a builder of a branch on top of the given parent block, whose state must be the
+    /// full state of the chain at the parent.
+    ///
+    /// By default the branch has a single block that changes two shared accounts and one account
+    /// of the branch, with two storage slots each.
+    pub fn new(parent: SealedHeader, parent_state: TestState) -> Self {
+        Self {
+            parent,
+            parent_state,
+            seed: 0,
+            depth: 1,
+            shared_accounts: 2,
+            branch_accounts: 1,
+            slots_per_account: 2,
+        }
+    }
+
+    /// Sets the seed of the branch. Branches on the same parent must have different seeds.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the number of blocks of the branch.
+    pub fn with_depth(mut self, depth: u64) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Sets the number of [shared accounts](shared_account) changed in every block.
+    pub fn with_shared_accounts(mut self, shared_accounts: usize) -> Self {
+        self.shared_accounts = shared_accounts;
+        self
+    }
+
+    /// Sets the number of accounts only changed by this branch in every block.
+    pub fn with_branch_accounts(mut self, branch_accounts: usize) -> Self {
+        self.branch_accounts = branch_accounts;
+        self
+    }
+
+    /// Sets the number of storage slots of every changed account that are changed in every block.
+    ///
+    /// Some of the slots are cleared instead of changed.
+    pub fn with_slots_per_account(mut self, slots_per_account: usize) -> Self {
+        self.slots_per_account = slots_per_account;
+        self
+    }
+
+    /// Builds the blocks of the branch and their execution results.
+    pub fn build(self) -> ChainBranch {
+        let mut state = self.parent_state.clone();
+        let mut parent = self.parent.clone();
+        let mut blocks = Vec::with_capacity(self.depth as usize);
+        for _ in 0..self.depth {
+            let block = self.build_block(&parent, &mut state);
+            parent = block.0.header.clone();
+            blocks.push(block);
+        }
+        ChainBranch { parent: self.parent, parent_state: self.parent_state, blocks, state }
+    }
+
+    /// Returns the accounts changed in every block.
+    fn changed_accounts(&self) -> impl Iterator<Item = Address> + '_ {
+        let branch_accounts = (0..self.branch_accounts).map(|index| {
+            Address::from_word(keccak256(format!("branch {} account {index}", self.seed)))
+        });
+        (0..self.shared_accounts).map(shared_account).chain(branch_accounts)
+    }
+
+    /// Returns the value the block with the given number writes to the given key of the account.
+    fn value(&self, number: BlockNumber, address: Address, key: u64) -> U256 {
+        let mut preimage = Vec::with_capacity(44);
+        preimage.extend_from_slice(&self.seed.to_be_bytes());
+        preimage.extend_from_slice(&number.to_be_bytes());
+        preimage.extend_from_slice(address.as_slice());
+        preimage.extend_from_slice(&key.to_be_bytes());
+        U256::from_be_bytes(keccak256(preimage).0)
+    }
+
+    /// Builds the block on top of the parent, applying its changes to the state.
+    fn build_block(
+        &self,
+        parent: &SealedHeader,
+        state: &mut TestState,
+    ) -> (SealedBlockWithSenders, BundleStateWithReceipts) {
+        let number = parent.number + 1;
+        let mut state_init = BundleStateInit::default();
+        let mut account_reverts = HashMap::default();
+        for address in self.changed_accounts() {
+            let (previous, mut storage) = match state.get(&address) {
+                Some((account, storage)) => (Some(*account), storage.clone()),
+                None => (None, BTreeMap::default()),
+            };
+            let account = Account {
+                nonce: previous.map_or(0, |account| account.nonce) + 1,
+                balance: self.value(number, address, u64::MAX),
+                bytecode_hash: None,
+            };
+
+            let mut slots = HashMap::with_capacity(self.slots_per_account);
+            let mut slot_reverts = Vec::with_capacity(self.slots_per_account);
+            for index in 0..self.slots_per_account as u64 {
+                let slot = B256::from(U256::from(index));
+                let previous_value = storage.get(&slot).copied().unwrap_or_default();
+                // clear every third slot
+                let value = if (number + index) % 3 == 0 {
+                    storage.remove(&slot);
+                    U256::ZERO
+                } else {
+                    let value = self.value(number, address, index);
+                    storage.insert(slot, value);
+                    value
+                };
+                slots.insert(slot, (previous_value, value));
+                slot_reverts.push(StorageEntry::new(slot, previous_value));
+            }
+
+            state_init.insert(address, (previous, Some(account), slots));
+            account_reverts.insert(address, (Some(previous), slot_reverts));
+            state.insert(address, (account, storage));
+        }
+
+        let header = Header {
+            parent_hash: parent.hash(),
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            state_root: state_root(
+                state
+                    .iter()
+                    .map(|(address, (account, storage))| (*address, (*account, storage.clone()))),
+            ),
+            transactions_root: EMPTY_ROOT_HASH,
+            receipts_root: EMPTY_ROOT_HASH,
+            number,
+            gas_limit: parent.gas_limit,
+            timestamp: parent.timestamp + 12,
+            // distinguishes the blocks of branches with the same changes
+            mix_hash: keccak256(self.seed.to_be_bytes()),
+            base_fee_per_gas: Some(MIN_PROTOCOL_BASE_FEE),
+            ..Default::default()
+        };
+        let block = SealedBlock { header: header.seal_slow(), ..Default::default() };
+
+        let bundle = BundleStateWithReceipts::new_init(
+            state_init,
+            RevertsInit::from([(number, account_reverts)]),
+            Vec::new(),
+            Receipts::from_vec(vec![Vec::new()]),
+            number,
+        );
+        (SealedBlockWithSenders { block, senders: Vec::new() }, bundle)
+    }
+}
+
+/// A chain of blocks on top of a parent block, with their execution results.
+#[derive(Debug, Clone)]
+pub struct ChainBranch {
+    /// The parent of the first block.
+    pub parent: SealedHeader,
+    /// The state of the chain at the parent.
+    pub parent_state: TestState,
+    /// The blocks with their execution results.
+    pub blocks: Vec<(SealedBlockWithSenders, BundleStateWithReceipts)>,
+    /// The state of the chain at the tip.
+    pub state: TestState,
+}
+
+impl ChainBranch {
+    /// Returns the header of the last block, or of the parent if the branch is empty.
+    pub fn tip(&self) -> &SealedHeader {
+        self.blocks.last().map_or(&self.parent, |(block, _)| &block.header)
+    }
+
+    /// Returns a builder of a branch on top of the tip of this branch.
+    pub fn extend(&self) -> ChainBranchBuilder {
+        ChainBranchBuilder::new(self.tip().clone(), self.state.clone())
+    }
+
+    /// Returns the accounts and storage slots of the parent state and the ones changed by the
+    /// blocks of the branch.
+    fn touched_keys(&self) -> BTreeMap<Address, BTreeSet<B256>> {
+        let mut touched = BTreeMap::<Address, BTreeSet<B256>>::new();
+        for (address, (_, storage)) in &self.parent_state {
+            touched.entry(*address).or_default().extend(storage.keys().copied());
+        }
+        for (_, bundle) in &self.blocks {
+            for (address, account) in bundle.bundle_accounts_iter() {
+                touched
+                    .entry(address)
+                    .or_default()
+                    .extend(account.storage.keys().map(|slot| B256::from(*slot)));
+            }
+        }
+        touched
+    }
+}
+
+/// Returns the execution results of the blocks of the branches in the order the
+/// [TestExecutorFactory](super::TestExecutorFactory) returns them, if the branches are imported
+/// one after another.
+pub fn executor_results<'a>(
+    branches: impl IntoIterator<Item = &'a ChainBranch>,
+) -> Vec<BundleStateWithReceipts> {
+    // the factory pops the results
+    let mut results = branches
+        .into_iter()
+        .flat_map(|branch| branch.blocks.iter().map(|(_, bundle)| bundle.clone()))
+        .collect::<Vec<_>>();
+    results.reverse();
+    results
+}
+
+/// Asserts that the branch is the canonical chain of the database and that the state of the
+/// database is the state at its tip.
+///
+/// The accounts and slots touched by the reorged branches must not have any other values than the
+/// ones of the canonical branch.
+pub fn assert_canonical_branch<DB: Database>(
+    factory: &ProviderFactory<DB>,
+    branch: &ChainBranch,
+    reorged: &[&ChainBranch],
+) {
+    let provider = factory.provider().unwrap();
+    let tip = branch.tip();
+    assert_eq!(provider.last_block_number().unwrap(), tip.number, "canonical tip number");
+    for header in
+        std::iter::once(&branch.parent).chain(branch.blocks.iter().map(|(b, _)| &b.header))
+    {
+        assert_eq!(
+            provider.block_hash(header.number).unwrap(),
+            Some(header.hash()),
+            "canonical hash of block {}",
+            header.number
+        );
+    }
+
+    // the plain state
+    let state = factory.latest().unwrap();
+    let mut touched = branch.touched_keys();
+    for reorged in reorged {
+        for (address, slots) in reorged.touched_keys() {
+            touched.entry(address).or_default().extend(slots);
+        }
+    }
+    for (address, slots) in touched {
+        let expected = branch.state.get(&address);
+        assert_eq!(
+            state.basic_account(address).unwrap(),
+            expected.map(|(account, _)| *account),
+            "account {address}"
+        );
+        for slot in slots {
+            assert_eq!(
+                state.storage(address, slot).unwrap(),
+                expected.and_then(|(_, storage)| storage.get(&slot).copied()),
+                "slot {slot} of account {address}"
+            );
+        }
+    }
+
+    // the hashed state
+    let mut expected_accounts = Vec::with_capacity(branch.state.len());
+    let mut expected_storage = Vec::new();
+    for (address, (account, storage)) in &branch.state {
+        let hashed_address = keccak256(address);
+        expected_accounts.push((hashed_address, *account));
+        expected_storage.extend(
+            storage
+                .iter()
+                .map(|(slot, value)| (hashed_address, StorageEntry::new(keccak256(slot), *value))),
+        );
+    }
+    expected_accounts.sort_unstable_by_key(|(hashed_address, _)| *hashed_address);
+    expected_storage.sort_unstable_by_key(|(hashed_address, entry)| (*hashed_address, entry.key));
+    assert_eq!(provider.table::<tables::HashedAccount>().unwrap(), expected_accounts);
+    assert_eq!(provider.table::<tables::HashedStorage>().unwrap(), expected_storage);
+
+    // the trie tables
+    assert_eq!(
+        StateRoot::from_tx(provider.tx_ref()).root().unwrap(),
+        tip.state_root,
+        "state root of the trie tables"
+    );
+}
+
+/// Computes the state root of the database through the cursor cache.
+pub fn cursor_cache_root<DB: Database>(factory: &ProviderFactory<DB>, cache: &CursorCache) -> B256 {
+    let provider = factory.provider().unwrap();
+    let tx = provider.tx_ref();
+    StateRoot::new(cache.trie_cursor_factory(tx), cache.hashed_cursor_factory(tx)).root().unwrap()
+}
+
+/// Asserts that the state root computed through the cursor cache is the state root at the tip of
+/// the canonical branch, i.e. that the cache doesn't serve state of reorged blocks.
+pub fn assert_cursor_cache_consistent<DB: Database>(
+    factory: &ProviderFactory<DB>,
+    cache: &CursorCache,
+    branch: &ChainBranch,
+) {
+    assert_eq!(
+        cursor_cache_root(factory, cache),
+        branch.tip().state_root,
+        "state root through the cursor cache"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn competing_branches_diverge() {
+        let genesis = Header::default().seal_slow();
+        let first = ChainBranchBuilder::new(genesis.clone(), TestState::default())
+            .with_seed(1)
+            .with_depth(3)
+            .build();
+        let second = ChainBranchBuilder::new(genesis, TestState::default())
+            .with_seed(2)
+            .with_depth(2)
+            .with_branch_accounts(2)
+            .build();
+
+        assert_eq!(first.tip().number, 3);
+        assert_eq!(first.blocks[1].0.parent_hash, first.blocks[0].0.hash);
+        assert_ne!(first.blocks[0].0.hash, second.blocks[0].0.hash);
+        assert_ne!(first.blocks[0].0.state_root, second.blocks[0].0.state_root);
+
+        // both change the shared accounts, to different values
+        let shared = shared_account(0);
+        assert_eq!(first.state[&shared].0.nonce, 3);
+        assert_eq!(second.state[&shared].0.nonce, 2);
+        assert_ne!(first.state[&shared].0.balance, second.state[&shared].0.balance);
+        // 2 shared accounts and the accounts of the branch
+        assert_eq!(first.state.len(), 3);
+        assert_eq!(second.state.len(), 4);
+        let common = first
+            .state
+            .keys()
+            .filter(|address| second.state.contains_key(*address))
+            .copied()
+            .collect::<BTreeSet<_>>();
+        assert_eq!(common, BTreeSet::from([shared_account(0), shared_account(1)]));
+
+        // the execution results are popped in import order
+        let results = executor_results([&first, &second]);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results.last().unwrap().first_block(), 1);
+        assert_eq!(results[0].first_block(), 2);
+
+        // the extension continues from the tip state
+        let extension = first.extend().with_seed(1).build();
+        assert_eq!(extension.parent, *first.tip());
+        assert_eq!(extension.state[&shared].0.nonce, 4);
+    }
+}
@@ -52,4 +52,16 @@ pub trait ChangeSetReader: Send + Sync {
         &self,
         block_number: BlockNumber,
     ) -> ProviderResult<Vec<AccountBeforeTx>>;
+
+    /// Returns up to `limit` numbers of blocks, starting at `from_block`, in which the account
+    /// changed, in ascending order.
+    ///
+    /// The blocks are read from the account history index, so blocks whose history was pruned
+    /// are not included.
+    fn account_change_blocks(
+        &self,
+        address: Address,
+        from_block: BlockNumber,
+        limit: usize,
+    ) -> ProviderResult<Vec<BlockNumber>>;
 }
@@ -7,10 +7,15 @@ use reth_db::models::StoredBlockBodyIndices;
 use reth_interfaces::provider::ProviderResult;
 use reth_primitives::{
     Block, BlockHashOrNumber, BlockId, BlockNumber, BlockNumberOrTag, BlockWithSenders, ChainSpec,
-    Header, PruneModes, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, B256,
+    Header, PruneModes, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader,
+    TransactionMeta, TransactionSigned, TxHash, B256,
 };
 use reth_trie::{updates::TrieUpdates, HashedPostState};
-use std::ops::RangeInclusive;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    ops::RangeInclusive,
+    sync::Arc,
+};
 
 /// Enum to control transaction hash inclusion.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -133,8 +138,43 @@ pub trait BlockReader:
     ///
     /// Note: returns only available blocks
     fn block_range(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<Vec<Block>>;
+
+    /// Returns the transactions with the given hashes, together with their metadata and all
+    /// receipts of their blocks, in the order of the given hashes.
+    ///
+    /// The receipt of a transaction is at its [TransactionMeta::index] in the receipts of its
+    /// block. Contains `None` for every transaction that is not found.
+    fn transactions_with_block_receipts(
+        &self,
+        hashes: &[TxHash],
+    ) -> ProviderResult<Vec<Option<TransactionWithBlockReceipts>>> {
+        let mut blocks: HashMap<B256, Arc<Vec<Receipt>>> = HashMap::new();
+        let mut transactions = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let Some((transaction, meta)) = self.transaction_by_hash_with_meta(*hash)? else {
+                transactions.push(None);
+                continue
+            };
+            let receipts = match blocks.entry(meta.block_hash) {
+                Entry::Occupied(entry) => Arc::clone(entry.get()),
+                Entry::Vacant(entry) => {
+                    let Some(receipts) = self.receipts_by_block(meta.block_hash.into())? else {
+                        transactions.push(None);
+                        continue
+                    };
+                    Arc::clone(entry.insert(Arc::new(receipts)))
+                }
+            };
+            transactions.push(Some((transaction, meta, receipts)));
+        }
+        Ok(transactions)
+    }
 }
 
+/// A transaction with its metadata and all receipts of its block, as returned by
+/// [BlockReader::transactions_with_block_receipts].
+pub type TransactionWithBlockReceipts = (TransactionSigned, TransactionMeta, Arc<Vec<Receipt>>);
+
 /// Trait extension for `BlockReader`, for types that implement `BlockId` conversion.
 ///
 /// The `BlockReader` trait should be implemented on types that can retrieve a block from either
@@ -1,14 +1,16 @@
 //! Canonical chain state notification trait and types.
 
-use crate::{chain::BlockReceipts, Chain};
+use crate::{chain::BlockReceipts, BlockNumReader, Chain};
 use auto_impl::auto_impl;
-use reth_primitives::SealedBlockWithSenders;
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{BlockNumber, SealedBlockWithSenders, SealedHeader};
 use std::{
+    ops::RangeInclusive,
     pin::Pin,
     sync::Arc,
     task::{ready, Context, Poll},
 };
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 use tokio_stream::{wrappers::BroadcastStream, Stream};
 use tracing::debug;
 
@@ -18,6 +20,10 @@ pub type CanonStateNotifications = broadcast::Receiver<CanonStateNotification>;
 /// Type alias for a sender that sends [CanonStateNotification]
 pub type CanonStateNotificationSender = broadcast::Sender<CanonStateNotification>;
 
+/// Type alias for a receiver that tracks the safe or finalized block reported by the consensus
+/// layer.
+pub type ForkChoiceNotifications = watch::Receiver<Option<SealedHeader>>;
+
 /// A type that allows to register chain related event subscriptions.
 #[auto_impl(&, Arc)]
 pub trait CanonStateSubscriptions: Send + Sync {
@@ -32,6 +38,107 @@ pub trait CanonStateSubscriptions: Send + Sync {
             st: BroadcastStream::new(self.subscribe_to_canonical_state()),
         }
     }
+
+    /// Get notified when the consensus layer reports a new safe block.
+    ///
+    /// Returns `None` if the safe block is not tracked.
+    fn subscribe_safe_block(&self) -> Option<ForkChoiceNotifications> {
+        None
+    }
+
+    /// Get notified when the consensus layer reports a new finalized block.
+    ///
+    /// Returns `None` if the finalized block is not tracked.
+    fn subscribe_finalized_block(&self) -> Option<ForkChoiceNotifications> {
+        None
+    }
+}
+
+/// A type that can replay the canonical state notifications of blocks that were already persisted.
+#[auto_impl(&, Arc)]
+pub trait CanonStateReplay: CanonStateSubscriptions + BlockNumReader {
+    /// Returns a [CanonStateNotification::Commit] of the persisted canonical blocks in the range,
+    /// including their execution outcome.
+    fn replay_canonical_state(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<CanonStateNotification>;
+}
+
+/// The default number of blocks per replayed [CanonStateNotification].
+pub const DEFAULT_REPLAY_CHUNK_SIZE: u64 = 64;
+
+/// A Stream of [CanonStateNotification] that replays the persisted canonical chain starting at
+/// a given block before yielding live notifications.
+///
+/// The live subscription is created before the replay range is determined, so no block is
+/// missed. Live commits that end at or below the last replayed block are skipped, a live
+/// notification that only partially overlaps with the replay is yielded as is.
+///
+/// Replayed notifications are read from the database while the stream is polled, so the stream
+/// should be polled from a task that is allowed to block. The stream ends after the first error.
+#[derive(Debug)]
+#[pin_project::pin_project]
+pub struct CanonStateReplayStream<P> {
+    provider: P,
+    /// The next block to replay.
+    next_block: BlockNumber,
+    /// The last block to replay, the canonical tip at the time of subscription.
+    replay_tip: BlockNumber,
+    /// The maximum number of blocks per replayed notification.
+    chunk_size: u64,
+    /// Set after an error was returned.
+    failed: bool,
+    #[pin]
+    live: CanonStateNotificationStream,
+}
+
+impl<P: CanonStateReplay> CanonStateReplayStream<P> {
+    /// Creates a new stream that replays the canonical chain from the given block in chunks of
+    /// up to `chunk_size` blocks.
+    pub fn new(provider: P, from_block: BlockNumber, chunk_size: u64) -> ProviderResult<Self> {
+        let live = provider.canonical_state_stream();
+        let replay_tip = provider.last_block_number()?;
+        Ok(Self {
+            provider,
+            next_block: from_block,
+            replay_tip,
+            chunk_size: chunk_size.max(1),
+            failed: false,
+            live,
+        })
+    }
+}
+
+impl<P: CanonStateReplay> Stream for CanonStateReplayStream<P> {
+    type Item = ProviderResult<CanonStateNotification>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if *this.failed {
+            return Poll::Ready(None)
+        }
+
+        if *this.next_block <= *this.replay_tip {
+            let end = (*this.next_block + *this.chunk_size - 1).min(*this.replay_tip);
+            let replayed = this.provider.replay_canonical_state(*this.next_block..=end);
+            *this.next_block = end + 1;
+            *this.failed = replayed.is_err();
+            return Poll::Ready(Some(replayed))
+        }
+
+        loop {
+            let Some(notification) = ready!(this.live.as_mut().poll_next(cx)) else {
+                return Poll::Ready(None)
+            };
+            if let CanonStateNotification::Commit { new } = &notification {
+                if new.tip().number <= *this.replay_tip {
+                    continue
+                }
+            }
+            return Poll::Ready(Some(Ok(notification)))
+        }
+    }
 }
 
 /// A Stream of [CanonStateNotification].
@@ -3,6 +3,7 @@
 use crate::{bundle_state::BundleStateWithReceipts, StateProvider};
 use reth_interfaces::executor::BlockExecutionError;
 use reth_primitives::{BlockNumber, BlockWithSenders, ChainSpec, PruneModes, Receipt, U256};
+use reth_trie::keccak::KeySender;
 use std::time::Duration;
 use tracing::debug;
 
@@ -64,6 +65,12 @@ pub trait BlockExecutor {
 
     /// Returns the size hint of current in-memory changes.
     fn size_hint(&self) -> Option<usize>;
+
+    /// Sends the keys of the state changed by every executed transaction to the given sender, so
+    /// they can be hashed while the block is executed.
+    ///
+    /// Executors that don't support it ignore the sender.
+    fn set_key_sender(&mut self, _sender: KeySender) {}
 }
 
 /// A [BlockExecutor] capable of in-memory pruning of the data that will be written to the database.
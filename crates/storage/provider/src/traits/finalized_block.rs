@@ -0,0 +1,24 @@
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::BlockNumber;
+
+/// The trait for fetching the safe and finalized blocks reported by the consensus layer.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait FinalizedBlockReader: Send + Sync {
+    /// Returns the number of the last block the consensus layer considered finalized, if any was
+    /// recorded.
+    fn last_finalized_block_number(&self) -> ProviderResult<Option<BlockNumber>>;
+
+    /// Returns the number of the last block the consensus layer considered safe, if any was
+    /// recorded.
+    fn last_safe_block_number(&self) -> ProviderResult<Option<BlockNumber>>;
+}
+
+/// The trait for recording the safe and finalized blocks reported by the consensus layer.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait FinalizedBlockWriter: Send + Sync {
+    /// Saves the number of the last finalized block.
+    fn save_finalized_block_number(&self, block_number: BlockNumber) -> ProviderResult<()>;
+
+    /// Saves the number of the last safe block.
+    fn save_safe_block_number(&self, block_number: BlockNumber) -> ProviderResult<()>;
+}
@@ -0,0 +1,43 @@
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{accumulator::HeaderAccumulatorProof, BlockNumber, B256};
+use std::ops::RangeInclusive;
+
+/// The trait for fetching the header accumulator and proofs of header inclusion.
+///
+/// The accumulator commits to all headers of completed epochs, see
+/// [accumulator](reth_primitives::accumulator).
+#[auto_impl::auto_impl(&, Arc)]
+pub trait HeaderAccumulatorReader: Send + Sync {
+    /// Returns the root of the historical accumulator over all completed epochs.
+    fn header_accumulator_root(&self) -> ProviderResult<B256>;
+
+    /// Returns the proof of inclusion of the given block in the historical accumulator.
+    ///
+    /// Returns `None` if the epoch of the block is not completed yet.
+    fn header_accumulator_proof(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Option<HeaderAccumulatorProof>>;
+}
+
+/// The trait for maintaining the header accumulator.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait HeaderAccumulatorWriter: Send + Sync {
+    /// Commits the epochs that are completed by the given range of newly appended canonical
+    /// blocks.
+    ///
+    /// This only reads the records of the completed epochs, and nothing is committed if an
+    /// earlier epoch is missing from the accumulator. Missing epochs are committed with
+    /// [HeaderAccumulatorWriter::backfill_header_accumulator].
+    fn append_header_accumulator(&self, blocks: RangeInclusive<BlockNumber>) -> ProviderResult<()>;
+
+    /// Commits all epochs that are completed by the canonical chain up to the given block and
+    /// were not committed yet, and returns the number of committed epochs.
+    ///
+    /// This reads the records of every missing epoch and is meant to be run offline.
+    fn backfill_header_accumulator(&self, tip: BlockNumber) -> ProviderResult<u64>;
+
+    /// Removes the epochs that are no longer completed once the canonical chain is unwound to the
+    /// given block.
+    fn unwind_header_accumulator(&self, unwind_to: BlockNumber) -> ProviderResult<()>;
+}
@@ -9,7 +9,7 @@ pub use storage::StorageReader;
 mod block;
 pub use block::{
     BlockExecutionWriter, BlockReader, BlockReaderIdExt, BlockSource, BlockWriter,
-    TransactionVariant,
+    TransactionVariant, TransactionWithBlockReceipts,
 };
 
 mod block_hash;
@@ -51,9 +51,13 @@ pub use executor::{BlockExecutor, BlockExecutorStats, ExecutorFactory, PrunableB
 mod chain;
 pub use chain::{
     CanonStateNotification, CanonStateNotificationSender, CanonStateNotificationStream,
-    CanonStateNotifications, CanonStateSubscriptions,
+    CanonStateNotifications, CanonStateReplay, CanonStateReplayStream, CanonStateSubscriptions,
+    ForkChoiceNotifications, DEFAULT_REPLAY_CHUNK_SIZE,
 };
 
+mod reorg;
+pub use reorg::{ReorgEvent, ReorgHistoryProvider};
+
 mod spec;
 pub use spec::ChainSpecProvider;
 
@@ -69,5 +73,11 @@ pub use history::HistoryWriter;
 mod prune_checkpoint;
 pub use prune_checkpoint::{PruneCheckpointReader, PruneCheckpointWriter};
 
+mod finalized_block;
+pub use finalized_block::{FinalizedBlockReader, FinalizedBlockWriter};
+
+mod header_accumulator;
+pub use header_accumulator::{HeaderAccumulatorReader, HeaderAccumulatorWriter};
+
 mod database_provider;
 pub use database_provider::DatabaseProviderFactory;
@@ -1,5 +1,5 @@
 use reth_interfaces::provider::ProviderResult;
-use reth_primitives::{PruneCheckpoint, PruneSegment};
+use reth_primitives::{BlockNumber, PruneCheckpoint, PruneSegment};
 
 /// The trait for fetching prune checkpoint related data.
 #[auto_impl::auto_impl(&, Arc)]
@@ -9,6 +9,18 @@ pub trait PruneCheckpointReader: Send + Sync {
         &self,
         segment: PruneSegment,
     ) -> ProviderResult<Option<PruneCheckpoint>>;
+
+    /// Returns the first block whose data of the given prune segment is available, or `None` if
+    /// none of it is pruned.
+    fn earliest_available_block(
+        &self,
+        segment: PruneSegment,
+    ) -> ProviderResult<Option<BlockNumber>> {
+        Ok(self
+            .get_prune_checkpoint(segment)?
+            .and_then(|checkpoint| checkpoint.block_number)
+            .map(|block_number| block_number + 1))
+    }
 }
 
 /// The trait for updating prune checkpoint related data.
@@ -0,0 +1,28 @@
+use reth_primitives::{BlockNumHash, TxHash};
+use std::time::Duration;
+
+/// The trait for fetching the recorded reorgs of the canonical chain.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait ReorgHistoryProvider: Send + Sync {
+    /// Returns the most recent reorgs of the canonical chain, oldest first.
+    ///
+    /// Returns an empty list if reorgs are not recorded.
+    fn reorg_history(&self) -> Vec<ReorgEvent>;
+}
+
+/// A reorg of the canonical chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReorgEvent {
+    /// Unix timestamp in seconds of when the reorg happened.
+    pub timestamp: u64,
+    /// The canonical tip before the reorg.
+    pub old_tip: BlockNumHash,
+    /// The canonical tip after the reorg.
+    pub new_tip: BlockNumHash,
+    /// The number of canonical blocks that were reverted.
+    pub depth: u64,
+    /// Hashes of the reverted transactions that are not included in the new canonical chain.
+    pub dropped_transactions: Vec<TxHash>,
+    /// The time it took to make the new chain canonical, including reverting the old chain.
+    pub duration: Duration,
+}
@@ -0,0 +1,251 @@
+//! Named task groups with per-group runtime metrics.
+//!
+//! Tasks spawned via an executor returned by [TaskExecutor::group](crate::TaskExecutor::group) are
+//! instrumented: the time spent in each poll and the time between being woken and being polled
+//! again are recorded per group, as well as the number of tasks that are currently waiting to be
+//! polled. Together they help to tell apart a subsystem that is slow from a subsystem that is
+//! starved by others sharing the same runtime.
+//!
+//! A group can also be moved to a dedicated runtime, see
+//! [TaskExecutor::spawn_dedicated_runtime](crate::TaskExecutor::spawn_dedicated_runtime).
+
+use futures_util::Future;
+use reth_metrics::{
+    metrics::{Counter, Gauge, Histogram},
+    Metrics,
+};
+use std::{
+    fmt,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+    time::Instant,
+};
+use tokio::runtime::{Handle, Runtime};
+
+/// A subsystem whose tasks are grouped for metrics and scheduling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskGroup {
+    /// P2P networking: sessions, discovery and the network manager.
+    Network,
+    /// The consensus engine, including the pipeline and the blockchain tree.
+    Engine,
+    /// Payload building jobs.
+    PayloadBuilder,
+    /// The pruner.
+    Pruning,
+    /// RPC servers and request handlers.
+    Rpc,
+}
+
+impl TaskGroup {
+    /// All task groups.
+    pub const ALL: [TaskGroup; 5] =
+        [Self::Network, Self::Engine, Self::PayloadBuilder, Self::Pruning, Self::Rpc];
+
+    /// Returns the name of the group, as used in metric labels and thread names.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Network => "network",
+            Self::Engine => "engine",
+            Self::PayloadBuilder => "payload-builder",
+            Self::Pruning => "pruning",
+            Self::Rpc => "rpc",
+        }
+    }
+}
+
+impl fmt::Display for TaskGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TaskGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|group| group.as_str() == s)
+            .ok_or_else(|| format!("unknown task group: {s}"))
+    }
+}
+
+/// Metrics of the tasks of a [TaskGroup].
+#[derive(Metrics, Clone)]
+#[metrics(scope = "executor.group")]
+pub(crate) struct TaskGroupMetrics {
+    /// Number of spawned tasks
+    pub(crate) spawned_tasks: Counter,
+    /// Number of finished tasks
+    pub(crate) finished_tasks: Counter,
+    /// Number of tasks that were woken and wait to be polled
+    pub(crate) scheduled_tasks: Gauge,
+    /// Time spent in a single poll of a task
+    pub(crate) poll_duration: Histogram,
+    /// Time between a task being woken and being polled
+    pub(crate) schedule_delay: Histogram,
+    /// Number of worker threads of the runtime the group runs on
+    pub(crate) workers: Gauge,
+}
+
+impl TaskGroupMetrics {
+    pub(crate) fn for_group(group: TaskGroup) -> Self {
+        Self::new_with_labels(&[("group", group.as_str())])
+    }
+}
+
+/// A runtime that only runs the tasks of one [TaskGroup].
+///
+/// The runtime is shut down without waiting for its tasks when dropped.
+#[derive(Debug)]
+pub(crate) struct DedicatedRuntime {
+    runtime: Option<Runtime>,
+    worker_threads: usize,
+}
+
+impl DedicatedRuntime {
+    pub(crate) fn new(group: TaskGroup, worker_threads: usize) -> std::io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(worker_threads)
+            .thread_name(format!("reth-{group}"))
+            .enable_all()
+            .build()?;
+        Ok(Self { runtime: Some(runtime), worker_threads })
+    }
+
+    pub(crate) fn handle(&self) -> &Handle {
+        self.runtime.as_ref().expect("runtime exists until dropped").handle()
+    }
+
+    pub(crate) fn worker_threads(&self) -> usize {
+        self.worker_threads
+    }
+}
+
+impl Drop for DedicatedRuntime {
+    fn drop(&mut self) {
+        // dropping a runtime blocks, which panics if done from within a runtime
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// A future that records the poll and schedule metrics of its [TaskGroup].
+pub(crate) struct InstrumentedTask<F> {
+    fut: Pin<Box<F>>,
+    schedule: Arc<ScheduleState>,
+    /// The waker passed to the inner future, rebuilt if the task is polled with another waker.
+    waker: Option<(Waker, Waker)>,
+}
+
+impl<F> InstrumentedTask<F> {
+    pub(crate) fn new(fut: F, metrics: TaskGroupMetrics) -> Self {
+        metrics.spawned_tasks.increment(1);
+        let schedule = Arc::new(ScheduleState { scheduled_at: Mutex::new(None), metrics });
+        // a spawned task is scheduled right away
+        schedule.schedule();
+        Self { fut: Box::pin(fut), schedule, waker: None }
+    }
+}
+
+impl<F: Future> Future for InstrumentedTask<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.schedule.unschedule();
+
+        if !this.waker.as_ref().is_some_and(|(outer, _)| outer.will_wake(cx.waker())) {
+            let waker = Waker::from(Arc::new(TaskWaker {
+                inner: cx.waker().clone(),
+                schedule: this.schedule.clone(),
+            }));
+            this.waker = Some((cx.waker().clone(), waker));
+        }
+        let waker = &this.waker.as_ref().expect("waker is set").1;
+
+        let started_at = Instant::now();
+        let poll = this.fut.as_mut().poll(&mut Context::from_waker(waker));
+        this.schedule.metrics.poll_duration.record(started_at.elapsed());
+        poll
+    }
+}
+
+impl<F> Drop for InstrumentedTask<F> {
+    fn drop(&mut self) {
+        self.schedule.unschedule();
+        self.schedule.metrics.finished_tasks.increment(1);
+    }
+}
+
+/// Tracks whether a task is waiting to be polled.
+struct ScheduleState {
+    /// When the task was woken, `None` if it was polled since.
+    scheduled_at: Mutex<Option<Instant>>,
+    metrics: TaskGroupMetrics,
+}
+
+impl ScheduleState {
+    fn schedule(&self) {
+        let mut scheduled_at = self.scheduled_at.lock().unwrap_or_else(|err| err.into_inner());
+        if scheduled_at.is_none() {
+            *scheduled_at = Some(Instant::now());
+            self.metrics.scheduled_tasks.increment(1.0);
+        }
+    }
+
+    fn unschedule(&self) {
+        let scheduled_at = self.scheduled_at.lock().unwrap_or_else(|err| err.into_inner()).take();
+        if let Some(scheduled_at) = scheduled_at {
+            self.metrics.scheduled_tasks.decrement(1.0);
+            self.metrics.schedule_delay.record(scheduled_at.elapsed());
+        }
+    }
+}
+
+/// Marks the task as scheduled before waking it.
+struct TaskWaker {
+    inner: Waker,
+    schedule: Arc<ScheduleState>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.schedule.schedule();
+        self.inner.wake_by_ref();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_task_group() {
+        for group in TaskGroup::ALL {
+            assert_eq!(group.as_str().parse::<TaskGroup>(), Ok(group));
+        }
+        assert!("consensus".parse::<TaskGroup>().is_err());
+    }
+
+    #[test]
+    fn instrumented_task_output() {
+        let runtime = DedicatedRuntime::new(TaskGroup::Engine, 1).unwrap();
+        let task = InstrumentedTask::new(
+            async {
+                tokio::task::yield_now().await;
+                1
+            },
+            TaskGroupMetrics::for_group(TaskGroup::Engine),
+        );
+        assert_eq!(runtime.handle().block_on(task), 1);
+    }
+}
@@ -8,21 +8,23 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 use crate::{
+    group::{DedicatedRuntime, InstrumentedTask, TaskGroup, TaskGroupMetrics},
     metrics::{IncCounterOnDrop, TaskExecutorMetrics},
     shutdown::{signal, GracefulShutdown, GracefulShutdownGuard, Shutdown, Signal},
 };
 use dyn_clone::DynClone;
 use futures_util::{
-    future::{select, BoxFuture},
+    future::{select, BoxFuture, Either},
     pin_mut, Future, FutureExt, TryFutureExt,
 };
 use std::{
     any::Any,
+    collections::HashMap,
     fmt::{Display, Formatter},
     pin::Pin,
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     task::{ready, Context, Poll},
 };
@@ -34,6 +36,7 @@ use tokio::{
 use tracing::{debug, error};
 use tracing_futures::Instrument;
 
+pub mod group;
 pub mod metrics;
 pub mod shutdown;
 
@@ -155,6 +158,8 @@ pub struct TaskManager {
     on_shutdown: Shutdown,
     /// How many [GracefulShutdown] tasks are currently active
     graceful_tasks: Arc<AtomicUsize>,
+    /// Runtimes dedicated to a [TaskGroup].
+    dedicated_runtimes: Arc<Mutex<HashMap<TaskGroup, DedicatedRuntime>>>,
 }
 
 // === impl TaskManager ===
@@ -181,6 +186,7 @@ impl TaskManager {
             signal: Some(signal),
             on_shutdown,
             graceful_tasks: Arc::new(AtomicUsize::new(0)),
+            dedicated_runtimes: Default::default(),
         }
     }
 
@@ -193,6 +199,8 @@ impl TaskManager {
             panicked_tasks_tx: self.panicked_tasks_tx.clone(),
             metrics: Default::default(),
             graceful_tasks: Arc::clone(&self.graceful_tasks),
+            dedicated_runtimes: Arc::clone(&self.dedicated_runtimes),
+            group: None,
         }
     }
 
@@ -283,6 +291,10 @@ pub struct TaskExecutor {
     metrics: TaskExecutorMetrics,
     /// How many [GracefulShutdown] tasks are currently active
     graceful_tasks: Arc<AtomicUsize>,
+    /// Runtimes dedicated to a [TaskGroup], shared with the [TaskManager].
+    dedicated_runtimes: Arc<Mutex<HashMap<TaskGroup, DedicatedRuntime>>>,
+    /// The group spawned tasks are instrumented for.
+    group: Option<(TaskGroup, TaskGroupMetrics)>,
 }
 
 // === impl TaskExecutor ===
@@ -298,11 +310,62 @@ impl TaskExecutor {
         &self.on_shutdown
     }
 
+    /// Returns the [TaskGroup] of the tasks spawned by this executor, if any.
+    pub fn task_group(&self) -> Option<TaskGroup> {
+        self.group.as_ref().map(|(group, _)| *group)
+    }
+
+    /// Returns an executor that spawns the tasks of the given [TaskGroup].
+    ///
+    /// Tasks spawned by the returned executor record the metrics of the group and run on the
+    /// dedicated runtime of the group, if one was spawned before.
+    pub fn group(&self, group: TaskGroup) -> TaskExecutor {
+        let metrics = TaskGroupMetrics::for_group(group);
+        let runtimes = self.dedicated_runtimes.lock().unwrap_or_else(|err| err.into_inner());
+        let handle = match runtimes.get(&group) {
+            Some(runtime) => {
+                metrics.workers.set(runtime.worker_threads() as f64);
+                runtime.handle().clone()
+            }
+            None => self.handle.clone(),
+        };
+        drop(runtimes);
+
+        TaskExecutor { handle, group: Some((group, metrics)), ..self.clone() }
+    }
+
+    /// Spawns a runtime with the given number of worker threads that runs all tasks of the
+    /// [TaskGroup].
+    ///
+    /// This only applies to executors returned by [TaskExecutor::group] afterwards, so it should
+    /// be called before the subsystem is launched. The runtime is shut down once the [TaskManager]
+    /// and all executors are dropped.
+    pub fn spawn_dedicated_runtime(
+        &self,
+        group: TaskGroup,
+        worker_threads: usize,
+    ) -> std::io::Result<()> {
+        let mut runtimes = self.dedicated_runtimes.lock().unwrap_or_else(|err| err.into_inner());
+        if runtimes.contains_key(&group) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("task group {group} already has a dedicated runtime"),
+            ))
+        }
+        runtimes.insert(group, DedicatedRuntime::new(group, worker_threads)?);
+        debug!(%group, worker_threads, "Spawned dedicated runtime");
+        Ok(())
+    }
+
     /// Spawns a future on the tokio runtime depending on the [TaskKind]
     fn spawn_on_rt<F>(&self, fut: F, task_kind: TaskKind) -> JoinHandle<()>
     where
         F: Future<Output = ()> + Send + 'static,
     {
+        let fut = match &self.group {
+            Some((_, metrics)) => Either::Left(InstrumentedTask::new(fut, metrics.clone())),
+            None => Either::Right(fut),
+        };
         match task_kind {
             TaskKind::Default => self.handle.spawn(fut),
             TaskKind::Blocking => {
@@ -370,7 +433,7 @@ impl TaskExecutor {
 
         let task = fut.in_current_span();
 
-        self.handle.spawn(task)
+        self.spawn_on_rt(task, TaskKind::Default)
     }
 
     /// Spawns a critical task depending on the given [TaskKind]
@@ -456,7 +519,7 @@ impl TaskExecutor {
             .map(|_| ())
             .in_current_span();
 
-        self.handle.spawn(task)
+        self.spawn_on_rt(task, TaskKind::Default)
     }
 
     /// This spawns a critical task onto the runtime.
@@ -505,7 +568,7 @@ impl TaskExecutor {
             .map(|_| ())
             .in_current_span();
 
-        self.handle.spawn(task)
+        self.spawn_on_rt(task, TaskKind::Default)
     }
 
     /// This spawns a regular task onto the runtime.
@@ -540,7 +603,7 @@ impl TaskExecutor {
         );
         let fut = f(on_shutdown);
 
-        self.handle.spawn(fut)
+        self.spawn_on_rt(fut, TaskKind::Default)
     }
 }
 
@@ -750,6 +813,24 @@ mod tests {
         assert_eq!(counter.load(Ordering::Relaxed), num);
     }
 
+    #[test]
+    fn test_dedicated_runtime() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = TaskManager::new(runtime.handle().clone());
+        let executor = manager.executor();
+
+        executor.spawn_dedicated_runtime(TaskGroup::Engine, 1).unwrap();
+        assert!(executor.spawn_dedicated_runtime(TaskGroup::Engine, 1).is_err());
+
+        let engine = executor.group(TaskGroup::Engine);
+        assert_eq!(engine.task_group(), Some(TaskGroup::Engine));
+        let (tx, rx) = std::sync::mpsc::channel();
+        engine.spawn(async move {
+            tx.send(std::thread::current().name().map(str::to_string)).unwrap();
+        });
+        assert_eq!(rx.recv().unwrap().as_deref(), Some("reth-engine"));
+    }
+
     #[test]
     fn test_manager_graceful_shutdown_timeout() {
         let runtime = tokio::runtime::Runtime::new().unwrap();
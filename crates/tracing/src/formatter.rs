@@ -2,7 +2,7 @@ use crate::layers::BoxedLayer;
 use clap::ValueEnum;
 use std::{fmt, fmt::Display};
 use tracing_appender::non_blocking::NonBlocking;
-use tracing_subscriber::{EnvFilter, Layer, Registry};
+use tracing_subscriber::{layer::Filter, Layer, Registry};
 
 /// Represents the logging format.
 ///
@@ -13,6 +13,11 @@ pub enum LogFormat {
     /// Represents JSON formatting for logs.
     /// This format outputs log records as JSON objects,
     /// making it suitable for structured logging.
+    ///
+    /// Every record has the same fields, regardless of the color and target settings:
+    /// `timestamp`, `level`, `target`, `fields` with the `message` and the fields of the event,
+    /// and, if the event is in a span, `span` with the name and fields of the current span and
+    /// `spans` with all entered spans, from root to leaf.
     Json,
 
     /// Represents logfmt (key=value) formatting for logs.
@@ -31,7 +36,7 @@ impl LogFormat {
     /// along with additional configurations for filtering and output.
     ///
     /// # Arguments
-    /// * `filter` - An `EnvFilter` used to determine which log records to output.
+    /// * `filter` - A filter, e.g. an `EnvFilter`, used to determine which log records to output.
     /// * `color` - An optional string that enables or disables ANSI color codes in the logs.
     /// * `file_writer` - An optional `NonBlocking` writer for directing logs to a file.
     ///
@@ -39,7 +44,7 @@ impl LogFormat {
     /// A `BoxedLayer<Registry>` that can be added to a tracing subscriber.
     pub fn apply(
         &self,
-        filter: EnvFilter,
+        filter: impl Filter<Registry> + Send + Sync + 'static,
         color: Option<String>,
         file_writer: Option<NonBlocking>,
     ) -> BoxedLayer<Registry> {
@@ -52,8 +57,14 @@ impl LogFormat {
 
         match self {
             LogFormat::Json => {
-                let layer =
-                    tracing_subscriber::fmt::layer().json().with_ansi(ansi).with_target(target);
+                // the fields must not depend on the environment, so records can be parsed
+                // reliably
+                let layer = tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_ansi(false)
+                    .with_target(true)
+                    .with_current_span(true)
+                    .with_span_list(true);
 
                 if let Some(writer) = file_writer {
                     layer.with_writer(writer).with_filter(filter).boxed()
@@ -2,9 +2,9 @@ use std::path::{Path, PathBuf};
 
 use rolling_file::{RollingConditionBasic, RollingFileAppender};
 use tracing_appender::non_blocking::WorkerGuard;
-use tracing_subscriber::{filter::Directive, EnvFilter, Layer, Registry};
+use tracing_subscriber::{filter::Directive, reload, EnvFilter, Layer, Registry};
 
-use crate::formatter::LogFormat;
+use crate::{formatter::LogFormat, log_filters::ReloadableFilter};
 
 /// A worker guard returned by the file layer.
 ///
@@ -28,17 +28,31 @@ const DEFAULT_ENV_FILTER_DIRECTIVES: [&str; 3] =
 /// Each layer can be configured separately and then combined into a tracing subscriber.
 pub(crate) struct Layers {
     inner: Vec<BoxedLayer<Registry>>,
+    filters: Vec<ReloadableFilter>,
 }
 
 impl Layers {
     /// Creates a new `Layers` instance.
     pub(crate) fn new() -> Self {
-        Self { inner: vec![] }
+        Self { inner: vec![], filters: vec![] }
     }
 
-    /// Consumes the `Layers` instance, returning the inner vector of layers.
-    pub(crate) fn into_inner(self) -> Vec<BoxedLayer<Registry>> {
-        self.inner
+    /// Consumes the `Layers` instance, returning the inner vector of layers and the filters of
+    /// the log layers, which can be changed at runtime.
+    pub(crate) fn into_inner(self) -> (Vec<BoxedLayer<Registry>>, Vec<ReloadableFilter>) {
+        (self.inner, self.filters)
+    }
+
+    /// Creates the filter of a log layer from the given directives, whose levels can be changed
+    /// at runtime, see [crate::log_filters].
+    fn reloadable_filter(
+        &mut self,
+        default_directive: Option<Directive>,
+        directives: &str,
+    ) -> eyre::Result<reload::Layer<EnvFilter, Registry>> {
+        let (filter, reloadable) = ReloadableFilter::new(default_directive, directives)?;
+        self.filters.push(reloadable);
+        Ok(filter)
     }
 
     /// Adds a journald layer to the layers collection.
@@ -49,7 +63,7 @@ impl Layers {
     /// # Returns
     /// An `eyre::Result<()>` indicating the success or failure of the operation.
     pub(crate) fn journald(&mut self, filter: &str) -> eyre::Result<()> {
-        let journald_filter = build_env_filter(None, filter)?;
+        let journald_filter = self.reloadable_filter(None, filter)?;
         let layer = tracing_journald::layer()?.with_filter(journald_filter).boxed();
         self.inner.push(layer);
         Ok(())
@@ -75,12 +89,28 @@ impl Layers {
         filter: &str,
         color: Option<String>,
     ) -> eyre::Result<()> {
-        let filter = build_env_filter(Some(directive), filter)?;
+        let filter = self.reloadable_filter(Some(directive), filter)?;
         let layer = format.apply(filter, color, None);
         self.inner.push(layer.boxed());
         Ok(())
     }
 
+    /// Adds a layer that exports spans to an OpenTelemetry collector.
+    ///
+    /// # Arguments
+    /// * `endpoint` - The URL of the OTLP/gRPC endpoint of the collector.
+    /// * `filter` - A string containing additional filter directives for this layer.
+    ///
+    /// # Returns
+    /// An `eyre::Result<()>` indicating the success or failure of the operation.
+    #[cfg(feature = "otlp")]
+    pub(crate) fn otlp(&mut self, endpoint: &str, filter: &str) -> eyre::Result<()> {
+        let otlp_filter = build_env_filter(None, filter)?;
+        let layer = crate::otlp::layer(endpoint)?.with_filter(otlp_filter).boxed();
+        self.inner.push(layer);
+        Ok(())
+    }
+
     /// Adds a file logging layer to the layers collection.
     ///
     /// # Arguments
@@ -97,7 +127,7 @@ impl Layers {
         file_info: FileInfo,
     ) -> eyre::Result<FileWorkerGuard> {
         let (writer, guard) = file_info.create_log_writer();
-        let file_filter = build_env_filter(None, filter)?;
+        let file_filter = self.reloadable_filter(None, filter)?;
         let layer = format.apply(file_filter, None, Some(writer));
         self.inner.push(layer);
         Ok(guard)
@@ -161,7 +191,7 @@ impl FileInfo {
 ///
 /// # Returns
 /// An `eyre::Result<EnvFilter>` that can be used to configure a tracing subscriber.
-fn build_env_filter(
+pub(crate) fn build_env_filter(
     default_directive: Option<Directive>,
     directives: &str,
 ) -> eyre::Result<EnvFilter> {
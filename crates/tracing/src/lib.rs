@@ -56,6 +56,8 @@ pub use test_tracer::TestTracer;
 
 mod formatter;
 mod layers;
+pub mod log_filters;
+pub mod otlp;
 mod test_tracer;
 
 use crate::layers::Layers;
@@ -72,6 +74,7 @@ pub struct RethTracer {
     stdout: LayerInfo,
     journald: Option<String>,
     file: Option<(LayerInfo, FileInfo)>,
+    otlp: Option<(String, String)>,
 }
 
 impl RethTracer {
@@ -80,7 +83,7 @@ impl RethTracer {
     ///  Initializes with default stdout layer configuration.
     ///  Journald and file layers are not set by default.
     pub fn new() -> Self {
-        Self { stdout: LayerInfo::default(), journald: None, file: None }
+        Self { stdout: LayerInfo::default(), journald: None, file: None, otlp: None }
     }
 
     ///  Sets a custom configuration for the stdout layer.
@@ -110,6 +113,18 @@ impl RethTracer {
         self.file = Some((config, file_info));
         self
     }
+
+    ///  Sets the OpenTelemetry collector that spans are exported to, see [otlp].
+    ///
+    ///  Requires the `otlp` feature.
+    ///
+    ///  # Arguments
+    ///  * `endpoint` - The URL of the OTLP/gRPC endpoint of the collector.
+    ///  * `filter` - The `filter` to use for the exported spans.
+    pub fn with_otlp(mut self, endpoint: String, filter: String) -> Self {
+        self.otlp = Some((endpoint, filter));
+        self
+    }
 }
 
 impl Default for RethTracer {
@@ -184,7 +199,8 @@ impl Tracer for RethTracer {
     ///  Initializes the logging system based on the configured layers.
     ///
     ///  This method sets up the global tracing subscriber with the specified
-    ///  stdout, journald, and file layers.
+    ///  stdout, journald, file and OpenTelemetry layers. The levels of the log layers can be
+    ///  changed at runtime with [log_filters::log_filters].
     ///
     ///  The default layer is stdout.
     ///
@@ -211,9 +227,21 @@ impl Tracer for RethTracer {
             None
         };
 
+        if let Some((endpoint, filter)) = self.otlp {
+            #[cfg(feature = "otlp")]
+            layers.otlp(&endpoint, &filter)?;
+            #[cfg(not(feature = "otlp"))]
+            eyre::bail!(
+                "cannot export spans to {endpoint} ({filter}), the otlp feature is disabled"
+            );
+        }
+
         // The error is returned if the global default subscriber is already set,
         // so it's safe to ignore it
-        let _ = tracing_subscriber::registry().with(layers.into_inner()).try_init();
+        let (layers, filters) = layers.into_inner();
+        if tracing_subscriber::registry().with(layers).try_init().is_ok() {
+            log_filters::install(filters);
+        }
         Ok(file_guard)
     }
 }
@@ -0,0 +1,164 @@
+//! Runtime changes of the log levels of targets.
+//!
+//! The filters of the stdout, file and journald layers of [RethTracer](crate::RethTracer) can be
+//! reloaded, so the level of a target can be changed without a restart, e.g. to capture trace logs
+//! of a subsystem during a transient issue. A level can be set for a limited time, after which the
+//! configured filters apply again.
+
+use crate::layers::build_env_filter;
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{filter::Directive, reload, EnvFilter, Registry};
+
+/// The filters of the installed tracer, if initialized by [RethTracer](crate::RethTracer).
+static LOG_FILTERS: OnceLock<LogFilters> = OnceLock::new();
+
+/// Returns the filters of the installed tracer, or `None` if it wasn't initialized by
+/// [RethTracer](crate::RethTracer).
+pub fn log_filters() -> Option<&'static LogFilters> {
+    LOG_FILTERS.get()
+}
+
+/// Installs the filters of the layers of the global subscriber.
+pub(crate) fn install(filters: Vec<ReloadableFilter>) {
+    let _ = LOG_FILTERS.set(LogFilters { filters, levels: Default::default() });
+}
+
+/// A filter of a layer that is rebuilt from its configured directives when levels change.
+pub(crate) struct ReloadableFilter {
+    default_directive: Option<Directive>,
+    directives: String,
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl ReloadableFilter {
+    /// Creates the filter from the configured directives, returning it with the handle to reload
+    /// it.
+    pub(crate) fn new(
+        default_directive: Option<Directive>,
+        directives: &str,
+    ) -> eyre::Result<(reload::Layer<EnvFilter, Registry>, Self)> {
+        let filter = build_env_filter(default_directive.clone(), directives)?;
+        let (filter, handle) = reload::Layer::new(filter);
+        Ok((filter, Self { default_directive, directives: directives.to_string(), handle }))
+    }
+
+    /// Rebuilds the filter with the given levels of targets on top of the configured directives.
+    fn reload(&self, levels: &BTreeMap<String, TargetLevel>) -> eyre::Result<()> {
+        let filter = levels.values().try_fold(
+            build_env_filter(self.default_directive.clone(), &self.directives)?,
+            |filter, level| Ok::<_, eyre::Report>(filter.add_directive(level.directive()?)),
+        )?;
+        self.handle.reload(filter)?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ReloadableFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadableFilter")
+            .field("default_directive", &self.default_directive)
+            .field("directives", &self.directives)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The level of a target that was set at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetLevel {
+    /// The target, e.g. `trie-parallel` or `reth::cli`.
+    pub target: String,
+    /// The level of the target.
+    pub level: LevelFilter,
+    /// When the level is reset, `None` if it's kept until it's removed.
+    pub expires_at: Option<Instant>,
+}
+
+impl TargetLevel {
+    fn directive(&self) -> eyre::Result<Directive> {
+        Ok(format!("{}={}", self.target, self.level).parse()?)
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// The reloadable filters of all layers, and the levels of targets set at runtime.
+#[derive(Debug)]
+pub struct LogFilters {
+    filters: Vec<ReloadableFilter>,
+    levels: Mutex<BTreeMap<String, TargetLevel>>,
+}
+
+impl LogFilters {
+    /// Sets the level of the given target in all layers, replacing the level that was previously
+    /// set for it.
+    ///
+    /// If a duration is given, the level is reset once [remove_expired](Self::remove_expired) is
+    /// called after it elapsed.
+    pub fn set_level(
+        &self,
+        target: &str,
+        level: LevelFilter,
+        duration: Option<Duration>,
+    ) -> eyre::Result<TargetLevel> {
+        let level = TargetLevel {
+            target: target.to_string(),
+            level,
+            expires_at: duration.map(|duration| Instant::now() + duration),
+        };
+        // reject invalid targets before any filter is changed
+        level.directive()?;
+
+        let mut levels = self.levels.lock().map_err(|_| eyre::eyre!("log filters poisoned"))?;
+        levels.insert(level.target.clone(), level.clone());
+        self.reload(&levels)?;
+        Ok(level)
+    }
+
+    /// Resets the level of the given target to the configured filters.
+    ///
+    /// Returns `true` if a level was set for the target.
+    pub fn remove_level(&self, target: &str) -> eyre::Result<bool> {
+        let mut levels = self.levels.lock().map_err(|_| eyre::eyre!("log filters poisoned"))?;
+        if levels.remove(target).is_none() {
+            return Ok(false)
+        }
+        self.reload(&levels)?;
+        Ok(true)
+    }
+
+    /// Resets the levels whose duration elapsed.
+    pub fn remove_expired(&self) -> eyre::Result<()> {
+        let mut levels = self.levels.lock().map_err(|_| eyre::eyre!("log filters poisoned"))?;
+        let now = Instant::now();
+        let len = levels.len();
+        levels.retain(|_, level| !level.is_expired(now));
+        if levels.len() != len {
+            self.reload(&levels)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the levels of targets that are currently set.
+    pub fn levels(&self) -> Vec<TargetLevel> {
+        let now = Instant::now();
+        self.levels
+            .lock()
+            .map(|levels| levels.values().filter(|level| !level.is_expired(now)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn reload(&self, levels: &BTreeMap<String, TargetLevel>) -> eyre::Result<()> {
+        for filter in &self.filters {
+            filter.reload(levels)?;
+        }
+        Ok(())
+    }
+}
+
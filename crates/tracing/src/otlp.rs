@@ -0,0 +1,219 @@
+//! Export of spans to an OpenTelemetry collector, and propagation of trace contexts.
+//!
+//! If the tracer is initialized with [RethTracer::with_otlp](crate::RethTracer::with_otlp), spans
+//! are exported in batches over OTLP/gRPC. Spans of different subsystems are joined into a single
+//! trace by setting the [TraceContext] of a span as the parent of another, e.g. of an RPC request
+//! that is forwarded by an upstream service, or of a submitted transaction that is later included
+//! in a payload.
+//!
+//! Without the `otlp` feature, or if the export isn't enabled, all functions are no-ops.
+
+use tracing::Span;
+
+#[cfg(feature = "otlp")]
+use opentelemetry::trace::TraceContextExt;
+#[cfg(feature = "otlp")]
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "otlp")]
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// The name of the header of the W3C trace context of a request.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// The name of the header of the vendor specific W3C trace state of a request.
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+/// The maximum number of submitted transactions whose trace contexts are kept until they are
+/// included.
+pub const MAX_TRACED_TRANSACTIONS: u32 = 10_000;
+
+/// The name of the service the spans are exported as.
+#[cfg(feature = "otlp")]
+const SERVICE_NAME: &str = "reth";
+
+/// The trace contexts of the submitted transactions, by transaction hash.
+///
+/// Only initialized once the export is enabled.
+#[cfg(feature = "otlp")]
+static TRANSACTIONS: OnceLock<
+    Mutex<schnellru::LruMap<[u8; 32], opentelemetry::Context, schnellru::ByLength>>,
+> = OnceLock::new();
+
+/// The runtime the batches of spans are exported on.
+///
+/// Tracing is initialized before the runtime of the node is started, so the exporter has its own.
+#[cfg(feature = "otlp")]
+static EXPORT_RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+/// The trace context of a span, that other spans can be parented to.
+#[derive(Debug, Clone, Default)]
+pub struct TraceContext {
+    #[cfg(feature = "otlp")]
+    context: opentelemetry::Context,
+}
+
+impl TraceContext {
+    /// Returns the trace context of the current span.
+    pub fn current() -> Self {
+        #[cfg(feature = "otlp")]
+        {
+            Self { context: Span::current().context() }
+        }
+        #[cfg(not(feature = "otlp"))]
+        {
+            Self::default()
+        }
+    }
+
+    /// Parses the trace context from the values of the [TRACEPARENT_HEADER] and
+    /// [TRACESTATE_HEADER] headers of a request.
+    ///
+    /// Returns `None` if the trace parent is invalid, or the export isn't enabled.
+    pub fn from_headers(traceparent: &str, tracestate: Option<&str>) -> Option<Self> {
+        #[cfg(feature = "otlp")]
+        {
+            use opentelemetry::propagation::TextMapPropagator;
+
+            if !is_enabled() {
+                return None
+            }
+
+            let headers = TraceHeaders { traceparent, tracestate };
+            let context =
+                opentelemetry_sdk::propagation::TraceContextPropagator::new().extract(&headers);
+            context.span().span_context().is_valid().then_some(Self { context })
+        }
+        #[cfg(not(feature = "otlp"))]
+        {
+            let _ = (traceparent, tracestate);
+            None
+        }
+    }
+
+    /// Sets this trace context as the parent of the given span.
+    ///
+    /// This must be called before the span is entered for the first time.
+    pub fn set_as_parent(&self, span: &Span) {
+        #[cfg(feature = "otlp")]
+        span.set_parent(self.context.clone());
+        #[cfg(not(feature = "otlp"))]
+        let _ = span;
+    }
+}
+
+/// Returns `true` if spans are exported.
+pub fn is_enabled() -> bool {
+    #[cfg(feature = "otlp")]
+    {
+        TRANSACTIONS.get().is_some()
+    }
+    #[cfg(not(feature = "otlp"))]
+    {
+        false
+    }
+}
+
+/// Records the trace context of the current span as the trace of the submitted transaction with
+/// the given hash, see [transaction_trace].
+///
+/// Only the most recent [MAX_TRACED_TRANSACTIONS] transactions are kept.
+pub fn record_transaction(hash: [u8; 32]) {
+    #[cfg(feature = "otlp")]
+    if let Some(transactions) = TRANSACTIONS.get() {
+        let context = Span::current().context();
+        if context.span().span_context().is_valid() {
+            if let Ok(mut transactions) = transactions.lock() {
+                transactions.insert(hash, context);
+            }
+        }
+    }
+    #[cfg(not(feature = "otlp"))]
+    let _ = hash;
+}
+
+/// Returns the trace context recorded for the submitted transaction with the given hash, if any.
+pub fn transaction_trace(hash: &[u8; 32]) -> Option<TraceContext> {
+    #[cfg(feature = "otlp")]
+    {
+        let mut transactions = TRANSACTIONS.get()?.lock().ok()?;
+        transactions.get(hash).map(|context| TraceContext { context: context.clone() })
+    }
+    #[cfg(not(feature = "otlp"))]
+    {
+        let _ = hash;
+        None
+    }
+}
+
+/// Flushes the spans that were not exported yet and stops the export.
+pub fn shutdown() {
+    #[cfg(feature = "otlp")]
+    {
+        if is_enabled() {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// Creates the layer that exports spans to the OTLP/gRPC collector at the given endpoint, and
+/// enables the propagation of trace contexts.
+#[cfg(feature = "otlp")]
+pub(crate) fn layer<S>(
+    endpoint: &str,
+) -> eyre::Result<impl tracing_subscriber::Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry_otlp::WithExportConfig;
+
+    if EXPORT_RUNTIME.get().is_some() {
+        eyre::bail!("span export is already initialized")
+    }
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("otlp-export")
+        .enable_all()
+        .build()?;
+    let tracer = {
+        // the exporter and the batch processor are spawned on the current runtime
+        let _guard = runtime.enter();
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+            .with_trace_config(opentelemetry_sdk::trace::config().with_resource(
+                opentelemetry_sdk::Resource::new([opentelemetry::KeyValue::new(
+                    "service.name",
+                    SERVICE_NAME,
+                )]),
+            ))
+            .install_batch(opentelemetry_sdk::runtime::Tokio)?
+    };
+    let _ = EXPORT_RUNTIME.set(runtime);
+    let _ = TRANSACTIONS
+        .set(Mutex::new(schnellru::LruMap::new(schnellru::ByLength::new(MAX_TRACED_TRANSACTIONS))));
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// The trace context headers of a request.
+#[cfg(feature = "otlp")]
+struct TraceHeaders<'a> {
+    traceparent: &'a str,
+    tracestate: Option<&'a str>,
+}
+
+#[cfg(feature = "otlp")]
+impl opentelemetry::propagation::Extractor for TraceHeaders<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        match key {
+            TRACEPARENT_HEADER => Some(self.traceparent),
+            TRACESTATE_HEADER => self.tracestate,
+            _ => None,
+        }
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        vec![TRACEPARENT_HEADER, TRACESTATE_HEADER]
+    }
+}
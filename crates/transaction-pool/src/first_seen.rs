@@ -0,0 +1,276 @@
+//! Tracking of when transactions were first seen, to measure how early the node sees the
+//! transactions that end up in blocks.
+//!
+//! The tracker is opt-in and only records transactions once it was [installed](install). A
+//! transaction is recorded when it's first announced or broadcast by a peer, or submitted over
+//! RPC, and its first sighting is correlated with the canonical block it's included in by the
+//! [pool maintenance task](crate::maintain::maintain_transaction_pool).
+//!
+//! The metrics of the tracker are labeled by the [origin](SeenOrigin::as_str) the transactions
+//! were first seen from, so the visibility of the order flow of different origins can be
+//! compared.
+
+use crate::metrics::{FirstSeenMetrics, FirstSeenOriginMetrics};
+use reth_primitives::{BlockHash, BlockNumber, PeerId, SealedBlock, TxHash};
+use schnellru::{ByLength, LruMap};
+use std::{
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The default number of transactions whose first sighting is kept.
+pub const DEFAULT_MAX_TRACKED_TRANSACTIONS: u32 = 100_000;
+
+/// The installed tracker.
+static TRACKER: OnceLock<FirstSeenTracker> = OnceLock::new();
+
+/// Installs the tracker that keeps the first sighting of up to the given number of transactions,
+/// returning the installed tracker.
+///
+/// If the tracker was installed before, the installed tracker is returned.
+pub fn install(max_transactions: u32) -> &'static FirstSeenTracker {
+    TRACKER.get_or_init(|| FirstSeenTracker::new(max_transactions))
+}
+
+/// Returns the installed tracker, if any.
+pub fn tracker() -> Option<&'static FirstSeenTracker> {
+    TRACKER.get()
+}
+
+/// Records that the transaction with the given hash was seen now, if the tracker is installed.
+pub fn record(hash: TxHash, origin: SeenOrigin) {
+    if let Some(tracker) = tracker() {
+        tracker.record(hash, origin);
+    }
+}
+
+/// Where a transaction was seen from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeenOrigin {
+    /// The hash of the transaction was announced by the peer.
+    Announcement(PeerId),
+    /// The transaction was broadcast in full by the peer.
+    Broadcast(PeerId),
+    /// The transaction was submitted over RPC.
+    Rpc,
+}
+
+impl SeenOrigin {
+    /// Returns the label of the origin in metrics.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Announcement(_) => "announcement",
+            Self::Broadcast(_) => "broadcast",
+            Self::Rpc => "rpc",
+        }
+    }
+
+    /// Returns the peer the transaction was seen from, if it was seen from a peer.
+    pub const fn peer_id(&self) -> Option<PeerId> {
+        match self {
+            Self::Announcement(peer_id) | Self::Broadcast(peer_id) => Some(*peer_id),
+            Self::Rpc => None,
+        }
+    }
+}
+
+/// The first sighting of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirstSeen {
+    /// When the transaction was first seen, in milliseconds since the unix epoch.
+    pub seen_at: u64,
+    /// Where the transaction was first seen from.
+    pub origin: SeenOrigin,
+    /// The canonical block the transaction was included in, if it was included yet.
+    pub inclusion: Option<Inclusion>,
+}
+
+impl FirstSeen {
+    /// Returns how many milliseconds before the timestamp of its block the transaction was first
+    /// seen, negative if it was first seen after the timestamp of the block.
+    pub fn lead_time_ms(&self) -> Option<i64> {
+        self.inclusion
+            .map(|inclusion| (inclusion.block_timestamp * 1000) as i64 - self.seen_at as i64)
+    }
+}
+
+/// The inclusion of a transaction in a canonical block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inclusion {
+    /// The number of the block.
+    pub block_number: BlockNumber,
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The timestamp of the block, in seconds since the unix epoch.
+    pub block_timestamp: u64,
+    /// When the block became canonical, in milliseconds since the unix epoch.
+    pub observed_at: u64,
+}
+
+/// Keeps the first sighting of the most recently seen transactions, and records the lead time of
+/// the included ones.
+#[derive(Debug)]
+pub struct FirstSeenTracker {
+    transactions: Mutex<LruMap<TxHash, FirstSeen, ByLength>>,
+    metrics: FirstSeenMetrics,
+    announcement_metrics: FirstSeenOriginMetrics,
+    broadcast_metrics: FirstSeenOriginMetrics,
+    rpc_metrics: FirstSeenOriginMetrics,
+}
+
+impl FirstSeenTracker {
+    /// Creates a tracker that keeps the first sighting of up to the given number of transactions.
+    pub fn new(max_transactions: u32) -> Self {
+        let origin_metrics = |origin: SeenOrigin| {
+            FirstSeenOriginMetrics::new_with_labels(&[("origin", origin.as_str())])
+        };
+        Self {
+            transactions: Mutex::new(LruMap::new(ByLength::new(max_transactions))),
+            metrics: FirstSeenMetrics::default(),
+            announcement_metrics: origin_metrics(SeenOrigin::Announcement(PeerId::ZERO)),
+            broadcast_metrics: origin_metrics(SeenOrigin::Broadcast(PeerId::ZERO)),
+            rpc_metrics: origin_metrics(SeenOrigin::Rpc),
+        }
+    }
+
+    /// Records that the transaction was seen now, unless it was seen before.
+    pub fn record(&self, hash: TxHash, origin: SeenOrigin) {
+        self.record_at(hash, origin, unix_millis())
+    }
+
+    fn record_at(&self, hash: TxHash, origin: SeenOrigin, seen_at: u64) {
+        let Ok(mut transactions) = self.transactions.lock() else { return };
+        if transactions.peek(&hash).is_some() {
+            return
+        }
+        transactions.insert(hash, FirstSeen { seen_at, origin, inclusion: None });
+        self.metrics.tracked_transactions.set(transactions.len() as f64);
+        drop(transactions);
+
+        self.origin_metrics(origin).seen.increment(1);
+    }
+
+    /// Correlates the transactions of the given blocks, which became canonical, with their first
+    /// sighting.
+    ///
+    /// If a block is reorged out, the inclusion of its transactions is replaced by the inclusion
+    /// in the new canonical block, but only their first inclusion is recorded in the metrics.
+    pub fn on_canonical_blocks<'a>(&self, blocks: impl IntoIterator<Item = &'a SealedBlock>) {
+        self.on_canonical_blocks_at(blocks, unix_millis())
+    }
+
+    fn on_canonical_blocks_at<'a>(
+        &self,
+        blocks: impl IntoIterator<Item = &'a SealedBlock>,
+        observed_at: u64,
+    ) {
+        let Ok(mut transactions) = self.transactions.lock() else { return };
+        for block in blocks {
+            let inclusion = Inclusion {
+                block_number: block.number,
+                block_hash: block.hash,
+                block_timestamp: block.timestamp,
+                observed_at,
+            };
+            for tx in &block.body {
+                let Some(first_seen) = transactions.get(&tx.hash) else {
+                    self.metrics.included_unseen.increment(1);
+                    continue
+                };
+                let first_inclusion = first_seen.inclusion.is_none();
+                first_seen.inclusion = Some(inclusion);
+                if !first_inclusion {
+                    continue
+                }
+
+                let metrics = self.origin_metrics(first_seen.origin);
+                metrics.included.increment(1);
+                let lead_time_ms = first_seen.lead_time_ms().unwrap_or_default();
+                if lead_time_ms < 0 {
+                    metrics.seen_after_block.increment(1);
+                }
+                metrics.lead_time_seconds.record(lead_time_ms.max(0) as f64 / 1000.0);
+            }
+        }
+    }
+
+    /// Returns the first sighting of the transaction, if it's tracked.
+    pub fn get(&self, hash: &TxHash) -> Option<FirstSeen> {
+        self.transactions.lock().ok()?.peek(hash).copied()
+    }
+
+    fn origin_metrics(&self, origin: SeenOrigin) -> &FirstSeenOriginMetrics {
+        match origin {
+            SeenOrigin::Announcement(_) => &self.announcement_metrics,
+            SeenOrigin::Broadcast(_) => &self.broadcast_metrics,
+            SeenOrigin::Rpc => &self.rpc_metrics,
+        }
+    }
+}
+
+/// Returns the current time in milliseconds since the unix epoch.
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, TransactionSigned, B256};
+
+    fn block(number: u64, timestamp: u64, txs: &[TxHash]) -> SealedBlock {
+        let body = txs
+            .iter()
+            .map(|hash| TransactionSigned { hash: *hash, ..Default::default() })
+            .collect();
+        SealedBlock {
+            header: Header { number, timestamp, ..Default::default() }.seal_slow(),
+            body,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn correlates_first_sighting_with_inclusion() {
+        let tracker = FirstSeenTracker::new(10);
+        let peer = PeerId::random();
+        let (first, second, unseen) =
+            (B256::with_last_byte(1), B256::with_last_byte(2), B256::ZERO);
+
+        tracker.record_at(first, SeenOrigin::Announcement(peer), 10_000);
+        // later sightings are ignored
+        tracker.record_at(first, SeenOrigin::Rpc, 11_000);
+        tracker.record_at(second, SeenOrigin::Rpc, 13_500);
+
+        let block = block(1, 12, &[first, second, unseen]);
+        tracker.on_canonical_blocks_at([&block], 12_100);
+
+        let seen = tracker.get(&first).unwrap();
+        assert_eq!(seen.origin, SeenOrigin::Announcement(peer));
+        assert_eq!(seen.origin.peer_id(), Some(peer));
+        assert_eq!(
+            seen.inclusion,
+            Some(Inclusion {
+                block_number: 1,
+                block_hash: block.hash,
+                block_timestamp: 12,
+                observed_at: 12_100
+            })
+        );
+        assert_eq!(seen.lead_time_ms(), Some(2_000));
+
+        // seen after the timestamp of the block
+        assert_eq!(tracker.get(&second).unwrap().lead_time_ms(), Some(-1_500));
+        assert!(tracker.get(&unseen).is_none());
+    }
+
+    #[test]
+    fn keeps_most_recent_transactions() {
+        let tracker = FirstSeenTracker::new(2);
+        for i in 1..=3 {
+            tracker.record_at(B256::with_last_byte(i), SeenOrigin::Rpc, i as u64);
+        }
+        assert!(tracker.get(&B256::with_last_byte(1)).is_none());
+        assert_eq!(tracker.get(&B256::with_last_byte(3)).unwrap().seen_at, 3);
+    }
+}
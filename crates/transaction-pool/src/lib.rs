@@ -166,8 +166,10 @@ pub use crate::{
     error::PoolResult,
     ordering::{CoinbaseTipOrdering, Priority, TransactionOrdering},
     pool::{
-        blob_tx_priority, fee_delta, state::SubPool, AllTransactionsEvents, FullTransactionEvent,
-        TransactionEvent, TransactionEvents,
+        blob_tx_priority, fee_delta, state::SubPool, AllTransactionsEvents, DropReason,
+        FullTransactionEvent, PoolEventKind, PoolEventLog, PoolEventLogError,
+        PoolEventSubscription, SequencedPoolEvent, TransactionEvent, TransactionEvents,
+        POOL_EVENT_LOG_CAPACITY,
     },
     traits::*,
     validate::{
@@ -177,6 +179,7 @@ pub use crate::{
 };
 
 pub mod error;
+pub mod first_seen;
 pub mod maintain;
 pub mod metrics;
 pub mod noop;
@@ -373,6 +376,10 @@ where
         self.pool.add_all_transactions_event_listener()
     }
 
+    fn transaction_event_log(&self) -> PoolEventLog {
+        self.pool.event_log()
+    }
+
     fn pending_transactions_listener_for(&self, kind: TransactionListenerKind) -> Receiver<TxHash> {
         self.pool.add_pending_listener(kind)
     }
@@ -3,6 +3,7 @@
 use crate::{
     blobstore::{BlobStoreCanonTracker, BlobStoreUpdates},
     error::PoolError,
+    first_seen,
     metrics::MaintainPoolMetrics,
     traits::{CanonicalStateUpdate, ChangedAccount, TransactionPool, TransactionPoolExt},
     BlockInfo,
@@ -239,6 +240,11 @@ pub async fn maintain_transaction_pool<Client, P, St, Tasks>(
 
         // handle the new block or reorg
         let Some(event) = event else { continue };
+
+        // correlate the transactions of the new canonical blocks with their first sighting
+        if let (Some(first_seen), Some(chain)) = (first_seen::tracker(), event.committed()) {
+            first_seen.on_canonical_blocks(chain.blocks_iter().map(|block| &block.block));
+        }
         match event {
             CanonStateNotification::Reorg { old, new } => {
                 let (old_blocks, old_state) = old.inner();
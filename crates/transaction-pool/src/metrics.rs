@@ -1,7 +1,7 @@
 //! Transaction pool metrics.
 
 use reth_metrics::{
-    metrics::{Counter, Gauge},
+    metrics::{Counter, Gauge, Histogram},
     Metrics,
 };
 
@@ -52,6 +52,22 @@ pub struct BlobStoreMetrics {
     pub(crate) blobstore_entries: Gauge,
 }
 
+/// Blob sidecar verification metrics
+#[derive(Metrics)]
+#[metrics(scope = "transaction_pool.blob_verifier")]
+pub struct BlobVerifierMetrics {
+    /// Number of sidecars whose blobs were all verified before
+    pub(crate) cache_hits: Counter,
+    /// Number of sidecars with at least one blob that was not verified before
+    pub(crate) cache_misses: Counter,
+    /// Number of multi-proof verifications
+    pub(crate) batch_verifications: Counter,
+    /// Number of multi-proof verifications that failed and were repeated per transaction
+    pub(crate) batch_fallbacks: Counter,
+    /// Number of blobs passed to multi-proof verifications
+    pub(crate) verified_blobs: Counter,
+}
+
 /// Transaction pool maintenance metrics
 #[derive(Metrics)]
 #[metrics(scope = "transaction_pool")]
@@ -88,3 +104,30 @@ impl MaintainPoolMetrics {
         self.drift_count.increment(1);
     }
 }
+
+/// Metrics of the [first sighting](crate::first_seen) of transactions
+#[derive(Metrics)]
+#[metrics(scope = "transaction_pool.first_seen")]
+pub struct FirstSeenMetrics {
+    /// Number of transactions whose first sighting is tracked
+    pub(crate) tracked_transactions: Gauge,
+    /// Number of transactions in canonical blocks that were not seen before
+    pub(crate) included_unseen: Counter,
+}
+
+/// Metrics of the [first sighting](crate::first_seen) of transactions, by the origin they were
+/// first seen from
+#[derive(Metrics)]
+#[metrics(scope = "transaction_pool.first_seen")]
+pub struct FirstSeenOriginMetrics {
+    /// Number of transactions that were first seen from the origin
+    pub(crate) seen: Counter,
+    /// Number of transactions in canonical blocks that were first seen from the origin
+    pub(crate) included: Counter,
+    /// Number of transactions in canonical blocks that were first seen after the timestamp of
+    /// their block
+    pub(crate) seen_after_block: Counter,
+    /// Seconds from the first sighting of transactions until the timestamp of the canonical block
+    /// they were included in, zero if they were seen after it
+    pub(crate) lead_time_seconds: Histogram,
+}
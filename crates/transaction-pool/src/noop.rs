@@ -12,9 +12,9 @@ use crate::{
     },
     validate::ValidTransaction,
     AllPoolTransactions, AllTransactionsEvents, BestTransactions, BlockInfo, EthPooledTransaction,
-    NewTransactionEvent, PoolResult, PoolSize, PoolTransaction, PooledTransactionsElement,
-    PropagatedTransactions, TransactionEvents, TransactionOrigin, TransactionPool,
-    TransactionValidationOutcome, TransactionValidator, ValidPoolTransaction,
+    NewTransactionEvent, PoolEventLog, PoolResult, PoolSize, PoolTransaction,
+    PooledTransactionsElement, PropagatedTransactions, TransactionEvents, TransactionOrigin,
+    TransactionPool, TransactionValidationOutcome, TransactionValidator, ValidPoolTransaction,
 };
 use reth_primitives::{Address, BlobTransactionSidecar, TxHash};
 use std::{collections::HashSet, marker::PhantomData, sync::Arc};
@@ -85,6 +85,10 @@ impl TransactionPool for NoopTransactionPool {
         AllTransactionsEvents { events: mpsc::channel(1).1 }
     }
 
+    fn transaction_event_log(&self) -> PoolEventLog {
+        PoolEventLog::default()
+    }
+
     fn pending_transactions_listener_for(
         &self,
         _kind: TransactionListenerKind,
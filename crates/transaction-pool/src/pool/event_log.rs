@@ -0,0 +1,260 @@
+//! A sequenced log of pool events that subscribers can resume from.
+
+use parking_lot::Mutex;
+use reth_primitives::{TxHash, B256};
+use std::{collections::VecDeque, sync::Arc};
+use tokio::sync::Notify;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The number of events the [PoolEventLog] of the pool keeps for resuming subscribers.
+pub const POOL_EVENT_LOG_CAPACITY: usize = 65_536;
+
+/// A pool event, tagged with its position in the [PoolEventLog].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequencedPoolEvent {
+    /// The sequence number of the event, increasing by one with every event.
+    pub seq: u64,
+    /// The hash of the transaction the event happened to.
+    pub hash: TxHash,
+    /// What happened to the transaction.
+    pub kind: PoolEventKind,
+}
+
+/// The kinds of events recorded in the [PoolEventLog].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PoolEventKind {
+    /// The transaction was added to the pool, either to the pending or to a parked sub-pool.
+    Added {
+        /// Whether the transaction was added to the pending sub-pool.
+        pending: bool,
+    },
+    /// The transaction was moved from a parked sub-pool to the pending sub-pool.
+    Promoted,
+    /// The transaction was moved from the pending sub-pool to a parked sub-pool.
+    Demoted,
+    /// The transaction was replaced by another transaction of the same sender and nonce.
+    Replaced {
+        /// The hash of the replacement.
+        replaced_by: TxHash,
+    },
+    /// The transaction was included in a canonical block and removed from the pool.
+    Mined {
+        /// The hash of the block.
+        block_hash: B256,
+    },
+    /// The transaction was removed from the pool or rejected by it.
+    Dropped {
+        /// Why the transaction was dropped.
+        reason: DropReason,
+    },
+}
+
+/// Why a transaction was dropped from the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DropReason {
+    /// The transaction failed validation and was never added.
+    Invalid,
+    /// The transaction became invalid after a state change, e.g. its nonce was used.
+    Outdated,
+    /// The transaction was evicted to enforce the size limits of the pool.
+    PoolFull,
+    /// The transaction was removed on request.
+    Removed,
+}
+
+/// Errors of [PoolEventLog] subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PoolEventLogError {
+    /// The requested events are no longer kept by the log.
+    #[error("events before {oldest} are no longer available")]
+    Pruned {
+        /// The sequence number of the oldest event still kept.
+        oldest: u64,
+    },
+    /// The requested sequence number was not assigned yet, e.g. because the node restarted.
+    #[error("sequence number {requested} is ahead of the next event {next}")]
+    Ahead {
+        /// The requested sequence number.
+        requested: u64,
+        /// The sequence number of the next event.
+        next: u64,
+    },
+}
+
+/// A bounded log of all pool events, in the order they happened.
+///
+/// Unlike the [AllTransactionsEvents](crate::AllTransactionsEvents) listener, which drops events
+/// for slow listeners, every event is assigned a sequence number, and subscribers that reconnect
+/// can resume from the last event they have seen for as long as it is kept by the log.
+///
+/// Sequence numbers start at zero when the pool is created, so they do not survive restarts.
+#[derive(Debug, Clone)]
+pub struct PoolEventLog {
+    inner: Arc<PoolEventLogInner>,
+}
+
+#[derive(Debug)]
+struct PoolEventLogInner {
+    events: Mutex<SequencedEvents>,
+    /// Notifies subscribers about new events.
+    new_events: Notify,
+}
+
+#[derive(Debug)]
+struct SequencedEvents {
+    /// The sequence number of the next event.
+    next_seq: u64,
+    events: VecDeque<SequencedPoolEvent>,
+    capacity: usize,
+}
+
+impl PoolEventLog {
+    /// Creates a log that keeps the given number of events.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(PoolEventLogInner {
+                events: Mutex::new(SequencedEvents {
+                    next_seq: 0,
+                    events: VecDeque::new(),
+                    capacity: capacity.max(1),
+                }),
+                new_events: Notify::new(),
+            }),
+        }
+    }
+
+    /// Returns the sequence number of the next event.
+    pub fn next_seq(&self) -> u64 {
+        self.inner.events.lock().next_seq
+    }
+
+    /// Appends an event to the log, dropping the oldest event if the log is full.
+    pub(crate) fn push(&self, hash: TxHash, kind: PoolEventKind) {
+        {
+            let mut log = self.inner.events.lock();
+            let event = SequencedPoolEvent { seq: log.next_seq, hash, kind };
+            log.next_seq += 1;
+            if log.events.len() == log.capacity {
+                log.events.pop_front();
+            }
+            log.events.push_back(event);
+        }
+        self.inner.new_events.notify_waiters();
+    }
+
+    /// Subscribes to the events starting at the given sequence number, or to new events only.
+    pub fn subscribe(&self, from: Option<u64>) -> Result<PoolEventSubscription, PoolEventLogError> {
+        let log = self.inner.events.lock();
+        let next_seq = from.unwrap_or(log.next_seq);
+        if next_seq > log.next_seq {
+            return Err(PoolEventLogError::Ahead { requested: next_seq, next: log.next_seq })
+        }
+        let oldest = log.oldest_seq();
+        if next_seq < oldest {
+            return Err(PoolEventLogError::Pruned { oldest })
+        }
+        Ok(PoolEventSubscription { log: self.clone(), next_seq })
+    }
+}
+
+impl Default for PoolEventLog {
+    fn default() -> Self {
+        Self::new(POOL_EVENT_LOG_CAPACITY)
+    }
+}
+
+impl SequencedEvents {
+    /// Returns the sequence number of the oldest kept event.
+    fn oldest_seq(&self) -> u64 {
+        self.events.front().map_or(self.next_seq, |event| event.seq)
+    }
+}
+
+/// A subscription to a [PoolEventLog], see [PoolEventLog::subscribe].
+#[derive(Debug)]
+pub struct PoolEventSubscription {
+    log: PoolEventLog,
+    /// The sequence number of the next event to return.
+    next_seq: u64,
+}
+
+impl PoolEventSubscription {
+    /// Returns the sequence number of the next event the subscription returns.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Waits for the next events and returns up to `max` of them, in order.
+    ///
+    /// Fails if the subscriber fell so far behind that the next event is no longer kept by the
+    /// log, in which case no events are skipped silently.
+    pub async fn recv_batch(
+        &mut self,
+        max: usize,
+    ) -> Result<Vec<SequencedPoolEvent>, PoolEventLogError> {
+        loop {
+            // registered before the log is checked, so no event is missed in between
+            let new_events = self.log.inner.new_events.notified();
+            {
+                let log = self.log.inner.events.lock();
+                let oldest = log.oldest_seq();
+                if self.next_seq < oldest {
+                    return Err(PoolEventLogError::Pruned { oldest })
+                }
+                if self.next_seq < log.next_seq {
+                    let batch = log
+                        .events
+                        .iter()
+                        .skip((self.next_seq - oldest) as usize)
+                        .take(max.max(1))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    self.next_seq += batch.len() as u64;
+                    return Ok(batch)
+                }
+            }
+            new_events.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn resumes_from_sequence_number() {
+        let log = PoolEventLog::new(2);
+        let mut subscription = log.subscribe(None).unwrap();
+
+        log.push(TxHash::with_last_byte(1), PoolEventKind::Added { pending: true });
+        log.push(TxHash::with_last_byte(2), PoolEventKind::Promoted);
+        let events = subscription.recv_batch(10).await.unwrap();
+        assert_eq!(events.iter().map(|event| event.seq).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(subscription.next_seq(), 2);
+
+        // resume after the first event
+        let mut resumed = log.subscribe(Some(1)).unwrap();
+        assert_eq!(resumed.recv_batch(10).await.unwrap()[0].kind, PoolEventKind::Promoted);
+
+        log.push(TxHash::with_last_byte(3), PoolEventKind::Demoted);
+        assert_eq!(log.subscribe(Some(0)).unwrap_err(), PoolEventLogError::Pruned { oldest: 1 });
+        assert_eq!(
+            log.subscribe(Some(4)).unwrap_err(),
+            PoolEventLogError::Ahead { requested: 4, next: 3 }
+        );
+
+        // a subscriber that fell behind is not silently advanced
+        log.push(TxHash::with_last_byte(4), PoolEventKind::Demoted);
+        log.push(TxHash::with_last_byte(5), PoolEventKind::Demoted);
+        assert_eq!(
+            subscription.recv_batch(10).await.unwrap_err(),
+            PoolEventLogError::Pruned { oldest: 3 }
+        );
+    }
+}
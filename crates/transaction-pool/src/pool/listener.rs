@@ -1,7 +1,10 @@
 //! Listeners for the transaction-pool
 
 use crate::{
-    pool::events::{FullTransactionEvent, TransactionEvent},
+    pool::{
+        event_log::{DropReason, PoolEventKind, PoolEventLog},
+        events::{FullTransactionEvent, TransactionEvent},
+    },
     traits::PropagateKind,
     PoolTransaction, ValidPoolTransaction,
 };
@@ -71,6 +74,8 @@ pub(crate) struct PoolEventBroadcast<T: PoolTransaction> {
     all_events_broadcaster: AllPoolEventsBroadcaster<T>,
     /// All listeners for events for a certain transaction hash.
     broadcasters_by_hash: HashMap<TxHash, PoolEventBroadcaster>,
+    /// The sequenced log of all events.
+    log: PoolEventLog,
 }
 
 impl<T: PoolTransaction> Default for PoolEventBroadcast<T> {
@@ -78,6 +83,7 @@ impl<T: PoolTransaction> Default for PoolEventBroadcast<T> {
         Self {
             all_events_broadcaster: AllPoolEventsBroadcaster::default(),
             broadcasters_by_hash: HashMap::default(),
+            log: PoolEventLog::default(),
         }
     }
 }
@@ -103,6 +109,11 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
         self.all_events_broadcaster.broadcast(pool_event);
     }
 
+    /// Returns the sequenced log of all events.
+    pub(crate) fn log(&self) -> &PoolEventLog {
+        &self.log
+    }
+
     /// Create a new subscription for the given transaction hash.
     pub(crate) fn subscribe(&mut self, tx_hash: TxHash) -> TransactionEvents {
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
@@ -127,6 +138,7 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
 
     /// Notify listeners about a transaction that was added to the pending queue.
     pub(crate) fn pending(&mut self, tx: &TxHash, replaced: Option<Arc<ValidPoolTransaction<T>>>) {
+        self.log.push(*tx, PoolEventKind::Added { pending: true });
         self.broadcast_event(tx, TransactionEvent::Pending, FullTransactionEvent::Pending(*tx));
 
         if let Some(replaced) = replaced {
@@ -137,6 +149,7 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
 
     /// Notify listeners about a transaction that was replaced.
     pub(crate) fn replaced(&mut self, tx: Arc<ValidPoolTransaction<T>>, replaced_by: TxHash) {
+        self.log.push(*tx.hash(), PoolEventKind::Replaced { replaced_by });
         let transaction = Arc::clone(&tx);
         self.broadcast_event(
             tx.hash(),
@@ -147,9 +160,23 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
 
     /// Notify listeners about a transaction that was added to the queued pool.
     pub(crate) fn queued(&mut self, tx: &TxHash) {
+        self.log.push(*tx, PoolEventKind::Added { pending: false });
         self.broadcast_event(tx, TransactionEvent::Queued, FullTransactionEvent::Queued(*tx));
     }
 
+    /// Notify listeners about a parked transaction that was moved to the pending queue.
+    pub(crate) fn promoted(&mut self, tx: &TxHash) {
+        self.log.push(*tx, PoolEventKind::Promoted);
+        self.broadcast_event(tx, TransactionEvent::Pending, FullTransactionEvent::Pending(*tx));
+    }
+
+    /// Records a pending transaction that was moved to a parked pool.
+    ///
+    /// Note: this is only recorded to the [PoolEventLog], listeners are not notified.
+    pub(crate) fn demoted(&mut self, tx: &TxHash) {
+        self.log.push(*tx, PoolEventKind::Demoted);
+    }
+
     /// Notify listeners about a transaction that was propagated.
     pub(crate) fn propagated(&mut self, tx: &TxHash, peers: Vec<PropagateKind>) {
         let peers = Arc::new(peers);
@@ -161,12 +188,14 @@ impl<T: PoolTransaction> PoolEventBroadcast<T> {
     }
 
     /// Notify listeners about a transaction that was discarded.
-    pub(crate) fn discarded(&mut self, tx: &TxHash) {
+    pub(crate) fn discarded(&mut self, tx: &TxHash, reason: DropReason) {
+        self.log.push(*tx, PoolEventKind::Dropped { reason });
         self.broadcast_event(tx, TransactionEvent::Discarded, FullTransactionEvent::Discarded(*tx));
     }
 
     /// Notify listeners that the transaction was mined
     pub(crate) fn mined(&mut self, tx: &TxHash, block_hash: B256) {
+        self.log.push(*tx, PoolEventKind::Mined { block_hash });
         self.broadcast_event(
             tx,
             TransactionEvent::Mined(block_hash),
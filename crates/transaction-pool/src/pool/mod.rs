@@ -94,7 +94,12 @@ use std::{
 };
 use tokio::sync::mpsc;
 use tracing::{debug, trace, warn};
+mod event_log;
 mod events;
+pub use event_log::{
+    DropReason, PoolEventKind, PoolEventLog, PoolEventLogError, PoolEventSubscription,
+    SequencedPoolEvent, POOL_EVENT_LOG_CAPACITY,
+};
 pub use events::{FullTransactionEvent, TransactionEvent};
 
 mod listener;
@@ -276,6 +281,11 @@ where
         self.event_listener.write().subscribe_all()
     }
 
+    /// Returns the sequenced log of all transaction events.
+    pub(crate) fn event_log(&self) -> PoolEventLog {
+        self.event_listener.read().log().clone()
+    }
+
     /// Returns a read lock to the pool's data.
     pub(crate) fn get_pool_data(&self) -> RwLockReadGuard<'_, TxPool<T>> {
         self.pool.read()
@@ -369,12 +379,13 @@ where
     /// This will either promote or discard transactions based on the new account state.
     pub(crate) fn update_accounts(&self, accounts: Vec<ChangedAccount>) {
         let changed_senders = self.changed_senders(accounts.into_iter());
-        let UpdateOutcome { promoted, discarded } =
+        let UpdateOutcome { promoted, demoted, discarded } =
             self.pool.write().update_accounts(changed_senders);
         let mut listener = self.event_listener.write();
 
-        promoted.iter().for_each(|tx| listener.pending(tx.hash(), None));
-        discarded.iter().for_each(|tx| listener.discarded(tx.hash()));
+        promoted.iter().for_each(|tx| listener.promoted(tx.hash()));
+        demoted.iter().for_each(|tx| listener.demoted(tx.hash()));
+        discarded.iter().for_each(|tx| listener.discarded(tx.hash(), DropReason::Outdated));
 
         // This deletes outdated blob txs from the blob store, based on the account's nonce. This is
         // called during txpool maintenance when the pool drifted.
@@ -456,12 +467,12 @@ where
             }
             TransactionValidationOutcome::Invalid(tx, err) => {
                 let mut listener = self.event_listener.write();
-                listener.discarded(tx.hash());
+                listener.discarded(tx.hash(), DropReason::Invalid);
                 Err(PoolError::new(*tx.hash(), err))
             }
             TransactionValidationOutcome::Error(tx_hash, err) => {
                 let mut listener = self.event_listener.write();
-                listener.discarded(&tx_hash);
+                listener.discarded(&tx_hash, DropReason::Invalid);
                 Err(PoolError::other(tx_hash, err))
             }
         }
@@ -498,7 +509,7 @@ where
         }
 
         let mut listener = self.event_listener.write();
-        discarded.iter().for_each(|tx| listener.discarded(tx));
+        discarded.iter().for_each(|tx| listener.discarded(tx, DropReason::PoolFull));
 
         // It may happen that a newly added transaction is immediately discarded, so we need to
         // adjust the result here
@@ -584,14 +595,16 @@ where
             })
         }
 
-        let OnNewCanonicalStateOutcome { mined, promoted, discarded, block_hash } = outcome;
+        let OnNewCanonicalStateOutcome { mined, promoted, demoted, discarded, block_hash } =
+            outcome;
 
         // broadcast specific transaction events
         let mut listener = self.event_listener.write();
 
         mined.iter().for_each(|tx| listener.mined(tx, block_hash));
-        promoted.iter().for_each(|tx| listener.pending(tx.hash(), None));
-        discarded.iter().for_each(|tx| listener.discarded(tx.hash()));
+        promoted.iter().for_each(|tx| listener.promoted(tx.hash()));
+        demoted.iter().for_each(|tx| listener.demoted(tx.hash()));
+        discarded.iter().for_each(|tx| listener.discarded(tx.hash(), DropReason::Outdated));
     }
 
     /// Fire events for the newly added transaction if there are any.
@@ -600,17 +613,20 @@ where
 
         match tx {
             AddedTransaction::Pending(tx) => {
-                let AddedPendingTransaction { transaction, promoted, discarded, replaced } = tx;
+                let AddedPendingTransaction { transaction, promoted, demoted, discarded, replaced } =
+                    tx;
 
                 listener.pending(transaction.hash(), replaced.clone());
-                promoted.iter().for_each(|tx| listener.pending(tx.hash(), None));
-                discarded.iter().for_each(|tx| listener.discarded(tx.hash()));
+                promoted.iter().for_each(|tx| listener.promoted(tx.hash()));
+                demoted.iter().for_each(|tx| listener.demoted(tx.hash()));
+                discarded.iter().for_each(|tx| listener.discarded(tx.hash(), DropReason::Outdated));
             }
-            AddedTransaction::Parked { transaction, replaced, .. } => {
+            AddedTransaction::Parked { transaction, replaced, demoted, .. } => {
                 listener.queued(transaction.hash());
                 if let Some(replaced) = replaced {
                     listener.replaced(replaced.clone(), *transaction.hash());
                 }
+                demoted.iter().for_each(|tx| listener.demoted(tx.hash()));
             }
         }
     }
@@ -671,7 +687,7 @@ where
 
         let mut listener = self.event_listener.write();
 
-        removed.iter().for_each(|tx| listener.discarded(tx.hash()));
+        removed.iter().for_each(|tx| listener.discarded(tx.hash(), DropReason::Removed));
 
         removed
     }
@@ -893,6 +909,8 @@ pub struct AddedPendingTransaction<T: PoolTransaction> {
     replaced: Option<Arc<ValidPoolTransaction<T>>>,
     /// transactions promoted to the pending queue
     promoted: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// transactions moved from the pending queue to a parked pool
+    demoted: Vec<Arc<ValidPoolTransaction<T>>>,
     /// transactions that failed and became discarded
     discarded: Vec<Arc<ValidPoolTransaction<T>>>,
 }
@@ -981,6 +999,8 @@ pub enum AddedTransaction<T: PoolTransaction> {
         replaced: Option<Arc<ValidPoolTransaction<T>>>,
         /// The subpool it was moved to.
         subpool: SubPool,
+        /// Transactions moved from the pending pool to a parked pool.
+        demoted: Vec<Arc<ValidPoolTransaction<T>>>,
     },
 }
 
@@ -1062,6 +1082,8 @@ pub(crate) struct OnNewCanonicalStateOutcome<T: PoolTransaction> {
     pub(crate) mined: Vec<TxHash>,
     /// Transactions promoted to the pending pool.
     pub(crate) promoted: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// Transactions moved from the pending pool to a parked pool.
+    pub(crate) demoted: Vec<Arc<ValidPoolTransaction<T>>>,
     /// transaction that were discarded during the update
     pub(crate) discarded: Vec<Arc<ValidPoolTransaction<T>>>,
 }
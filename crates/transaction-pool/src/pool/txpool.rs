@@ -440,11 +440,17 @@ impl<T: TransactionOrdering> TxPool<T> {
             }
         }
 
-        let UpdateOutcome { promoted, discarded } = self.update_accounts(changed_senders);
+        let UpdateOutcome { promoted, demoted, discarded } = self.update_accounts(changed_senders);
 
         self.metrics.performed_state_updates.increment(1);
 
-        OnNewCanonicalStateOutcome { block_hash, mined: mined_transactions, promoted, discarded }
+        OnNewCanonicalStateOutcome {
+            block_hash,
+            mined: mined_transactions,
+            promoted,
+            demoted,
+            discarded,
+        }
     }
 
     /// Update sub-pools size metrics.
@@ -506,7 +512,7 @@ impl<T: TransactionOrdering> TxPool<T> {
                 self.add_new_transaction(transaction.clone(), replaced_tx.clone(), move_to);
                 // Update inserted transactions metric
                 self.metrics.inserted_transactions.increment(1);
-                let UpdateOutcome { promoted, discarded } = self.process_updates(updates);
+                let UpdateOutcome { promoted, demoted, discarded } = self.process_updates(updates);
 
                 let replaced = replaced_tx.map(|(tx, _)| tx);
 
@@ -515,11 +521,12 @@ impl<T: TransactionOrdering> TxPool<T> {
                     AddedTransaction::Pending(AddedPendingTransaction {
                         transaction,
                         promoted,
+                        demoted,
                         discarded,
                         replaced,
                     })
                 } else {
-                    AddedTransaction::Parked { transaction, subpool: move_to, replaced }
+                    AddedTransaction::Parked { transaction, subpool: move_to, replaced, demoted }
                 };
 
                 Ok(res)
@@ -596,9 +603,11 @@ impl<T: TransactionOrdering> TxPool<T> {
                 Destination::Pool(move_to) => {
                     debug_assert!(!move_to.eq(&current), "destination must be different");
                     let moved = self.move_transaction(current, move_to, &id);
-                    if matches!(move_to, SubPool::Pending) {
-                        if let Some(tx) = moved {
+                    if let Some(tx) = moved {
+                        if move_to.is_pending() {
                             outcome.promoted.push(tx);
+                        } else if current.is_pending() {
+                            outcome.demoted.push(tx);
                         }
                     }
                 }
@@ -1744,13 +1753,15 @@ impl<T: PoolTransaction> PoolInternalTransaction<T> {
 pub(crate) struct UpdateOutcome<T: PoolTransaction> {
     /// transactions promoted to the pending pool
     pub(crate) promoted: Vec<Arc<ValidPoolTransaction<T>>>,
+    /// transactions moved from the pending pool to a parked pool
+    pub(crate) demoted: Vec<Arc<ValidPoolTransaction<T>>>,
     /// transaction that failed and were discarded
     pub(crate) discarded: Vec<Arc<ValidPoolTransaction<T>>>,
 }
 
 impl<T: PoolTransaction> Default for UpdateOutcome<T> {
     fn default() -> Self {
-        Self { promoted: vec![], discarded: vec![] }
+        Self { promoted: vec![], demoted: vec![], discarded: vec![] }
     }
 }
 
@@ -1,6 +1,6 @@
 use crate::{
     error::PoolResult,
-    pool::{state::SubPool, TransactionEvents},
+    pool::{state::SubPool, PoolEventLog, TransactionEvents},
     validate::ValidPoolTransaction,
     AllTransactionsEvents,
 };
@@ -109,6 +109,12 @@ pub trait TransactionPool: Send + Sync + Clone {
     /// Returns a new transaction change event stream for _all_ transactions in the pool.
     fn all_transactions_event_listener(&self) -> AllTransactionsEvents<Self::Transaction>;
 
+    /// Returns the sequenced log of all transaction events of the pool.
+    ///
+    /// In contrast to [TransactionPool::all_transactions_event_listener], subscribers of the log
+    /// never miss events silently and can resume from a sequence number.
+    fn transaction_event_log(&self) -> PoolEventLog;
+
     /// Returns a new Stream that yields transactions hashes for new __pending__ transactions
     /// inserted into the pool that are allowed to be propagated.
     ///
@@ -812,6 +818,14 @@ pub trait EthPoolTransaction: PoolTransaction {
     /// Returns the transaction as EIP-4844 transaction if it is one.
     fn as_eip4844(&self) -> Option<&TxEip4844>;
 
+    /// Returns the blob sidecar of the transaction, if it is still attached.
+    ///
+    /// This allows verifying the sidecars of multiple transactions in one batch before they are
+    /// extracted with [EthPoolTransaction::take_blob].
+    fn blob_sidecar(&self) -> Option<&BlobTransactionSidecar> {
+        None
+    }
+
     /// Validates the blob sidecar of the transaction with the given settings.
     fn validate_blob(
         &self,
@@ -1044,6 +1058,13 @@ impl EthPoolTransaction for EthPooledTransaction {
         self.transaction.as_eip4844()
     }
 
+    fn blob_sidecar(&self) -> Option<&BlobTransactionSidecar> {
+        match &self.blob_sidecar {
+            EthBlobTransactionSidecar::Present(sidecar) => Some(sidecar),
+            _ => None,
+        }
+    }
+
     fn validate_blob(
         &self,
         sidecar: &BlobTransactionSidecar,
@@ -0,0 +1,311 @@
+//! Batched verification of blob sidecars.
+
+use crate::metrics::BlobVerifierMetrics;
+use parking_lot::Mutex;
+use reth_primitives::{
+    eip4844::kzg_to_versioned_hash,
+    keccak256,
+    kzg::{self, Blob, Bytes48, KzgCommitment, KzgProof, KzgSettings},
+    BlobTransactionSidecar, BlobTransactionValidationError, TxEip4844, B256,
+};
+use schnellru::{ByLength, LruMap};
+use std::{fmt, ops::Deref, sync::Arc};
+
+/// The default number of verified blobs to remember.
+pub const DEFAULT_VERIFIED_BLOBS_CACHE_SIZE: u32 = 4096;
+
+/// Verifies the KZG proofs of blob sidecars.
+///
+/// All blobs passed to [BlobVerifier::verify_batch] are verified in a single multi-proof
+/// operation. The blobs that passed verification are cached by their versioned hash, so a blob is
+/// only verified once, e.g. if it is announced again by another peer or with a replacement
+/// transaction.
+///
+/// A cache hit requires both the blob and its proof to be identical to the verified ones, because
+/// the sidecar is stored and propagated as is: a verified blob that is resent with a different
+/// proof is verified again.
+///
+/// The verifier is cheap to clone, clones share the cache.
+#[derive(Clone)]
+pub struct BlobVerifier {
+    inner: Arc<BlobVerifierInner>,
+}
+
+impl BlobVerifier {
+    /// Creates a new verifier with the given KZG settings and the
+    /// [default](DEFAULT_VERIFIED_BLOBS_CACHE_SIZE) cache size.
+    pub fn new(kzg_settings: Arc<KzgSettings>) -> Self {
+        Self::with_cache_size(kzg_settings, DEFAULT_VERIFIED_BLOBS_CACHE_SIZE)
+    }
+
+    /// Creates a new verifier that remembers up to `cache_size` verified blobs.
+    pub fn with_cache_size(kzg_settings: Arc<KzgSettings>, cache_size: u32) -> Self {
+        let inner = BlobVerifierInner {
+            kzg_settings,
+            verified: Mutex::new(LruMap::new(ByLength::new(cache_size))),
+            metrics: BlobVerifierMetrics::default(),
+        };
+        Self { inner: Arc::new(inner) }
+    }
+
+    /// Returns the KZG settings used for verification.
+    pub fn kzg_settings(&self) -> &Arc<KzgSettings> {
+        &self.inner.kzg_settings
+    }
+
+    /// Verifies the sidecar of a single blob transaction.
+    pub fn verify(
+        &self,
+        transaction: &TxEip4844,
+        sidecar: &BlobTransactionSidecar,
+    ) -> Result<(), BlobTransactionValidationError> {
+        self.verify_batch(&[(transaction, sidecar)]).pop().expect("one result per sidecar")
+    }
+
+    /// Verifies the sidecars of all given blob transactions.
+    ///
+    /// Returns one result per transaction, in the same order.
+    pub fn verify_batch(
+        &self,
+        sidecars: &[(&TxEip4844, &BlobTransactionSidecar)],
+    ) -> Vec<Result<(), BlobTransactionValidationError>> {
+        let mut results = Vec::with_capacity(sidecars.len());
+        // the transactions with blobs that still need to be verified, with their blob hashes
+        let mut unverified = Vec::new();
+
+        for (idx, (transaction, sidecar)) in sidecars.iter().enumerate() {
+            if let Err(err) = ensure_matching_versioned_hashes(transaction, sidecar) {
+                results.push(Err(err));
+                continue
+            }
+            results.push(Ok(()));
+
+            let entry_hashes = sidecar
+                .blobs
+                .iter()
+                .zip(&sidecar.proofs)
+                .map(|(blob, proof)| entry_hash(blob, proof))
+                .collect::<Vec<_>>();
+            let cached = {
+                let mut verified = self.inner.verified.lock();
+                transaction.blob_versioned_hashes.iter().zip(&entry_hashes).all(
+                    |(versioned_hash, entry_hash)| {
+                        verified
+                            .get(versioned_hash)
+                            .is_some_and(|verified| *verified == *entry_hash)
+                    },
+                )
+            };
+            if cached {
+                self.inner.metrics.cache_hits.increment(1);
+            } else {
+                self.inner.metrics.cache_misses.increment(1);
+                unverified.push((idx, entry_hashes));
+            }
+        }
+
+        if unverified.is_empty() {
+            return results
+        }
+
+        let mut blobs = Vec::new();
+        let mut commitments = Vec::new();
+        let mut proofs = Vec::new();
+        for (idx, _) in &unverified {
+            let sidecar = sidecars[*idx].1;
+            blobs.extend_from_slice(&sidecar.blobs);
+            commitments.extend_from_slice(&sidecar.commitments);
+            proofs.extend_from_slice(&sidecar.proofs);
+        }
+
+        self.inner.metrics.batch_verifications.increment(1);
+        self.inner.metrics.verified_blobs.increment(blobs.len() as u64);
+        let batch_valid = self.inner.verify_proofs(&blobs, &commitments, &proofs).is_ok();
+
+        for (idx, entry_hashes) in unverified {
+            let (transaction, sidecar) = sidecars[idx];
+            // if the batch failed, verify the transactions one by one to find the invalid ones
+            if !batch_valid {
+                if let Err(err) =
+                    self.inner.verify_proofs(&sidecar.blobs, &sidecar.commitments, &sidecar.proofs)
+                {
+                    results[idx] = Err(err);
+                    continue
+                }
+            }
+            let mut verified = self.inner.verified.lock();
+            for (versioned_hash, entry_hash) in
+                transaction.blob_versioned_hashes.iter().zip(entry_hashes)
+            {
+                verified.insert(*versioned_hash, entry_hash);
+            }
+        }
+
+        if !batch_valid {
+            self.inner.metrics.batch_fallbacks.increment(1);
+        }
+
+        results
+    }
+}
+
+impl fmt::Debug for BlobVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlobVerifier")
+            .field("cached", &self.inner.verified.lock().len())
+            .finish_non_exhaustive()
+    }
+}
+
+struct BlobVerifierInner {
+    /// Stores the setup and parameters needed for validating KZG proofs.
+    kzg_settings: Arc<KzgSettings>,
+    /// The [entry hashes](entry_hash) of the verified blobs, by versioned hash.
+    verified: Mutex<LruMap<B256, B256, ByLength>>,
+    /// Verification metrics.
+    metrics: BlobVerifierMetrics,
+}
+
+impl BlobVerifierInner {
+    /// Verifies the proofs of the given blobs in one multi-proof operation.
+    fn verify_proofs(
+        &self,
+        blobs: &[Blob],
+        commitments: &[Bytes48],
+        proofs: &[Bytes48],
+    ) -> Result<(), BlobTransactionValidationError> {
+        let valid =
+            KzgProof::verify_blob_kzg_proof_batch(blobs, commitments, proofs, &self.kzg_settings)
+                .map_err(BlobTransactionValidationError::KZGError)?;
+        if valid {
+            Ok(())
+        } else {
+            Err(BlobTransactionValidationError::InvalidProof)
+        }
+    }
+}
+
+/// Returns the hash that identifies a verified blob together with its proof in the cache.
+fn entry_hash(blob: &Blob, proof: &Bytes48) -> B256 {
+    let mut buf = [0u8; 32 + 48];
+    buf[..32].copy_from_slice(keccak256(blob.as_slice()).as_slice());
+    buf[32..].copy_from_slice(proof.as_slice());
+    keccak256(buf)
+}
+
+/// Ensures that the sidecar is complete and that its commitments match the versioned hashes of the
+/// transaction.
+fn ensure_matching_versioned_hashes(
+    transaction: &TxEip4844,
+    sidecar: &BlobTransactionSidecar,
+) -> Result<(), BlobTransactionValidationError> {
+    let num_hashes = transaction.blob_versioned_hashes.len();
+    if num_hashes != sidecar.commitments.len() ||
+        num_hashes != sidecar.blobs.len() ||
+        num_hashes != sidecar.proofs.len()
+    {
+        return Err(kzg::Error::MismatchLength(format!(
+            "There are {} versioned commitment hashes, {} blobs, {} commitments and {} proofs",
+            num_hashes,
+            sidecar.blobs.len(),
+            sidecar.commitments.len(),
+            sidecar.proofs.len()
+        ))
+        .into())
+    }
+
+    for (versioned_hash, commitment) in
+        transaction.blob_versioned_hashes.iter().zip(sidecar.commitments.iter())
+    {
+        let commitment = KzgCommitment::from(*commitment.deref());
+        if *versioned_hash != kzg_to_versioned_hash(commitment) {
+            return Err(BlobTransactionValidationError::InvalidProof)
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        constants::eip4844::MAINNET_KZG_TRUSTED_SETUP,
+        kzg::{BYTES_PER_BLOB, BYTES_PER_FIELD_ELEMENT},
+    };
+
+    fn blob_transaction(seed: u8) -> (TxEip4844, BlobTransactionSidecar) {
+        let mut bytes = vec![seed; BYTES_PER_BLOB];
+        // every field element has to be smaller than the BLS modulus
+        for chunk in bytes.chunks_mut(BYTES_PER_FIELD_ELEMENT) {
+            chunk[0] = 0;
+        }
+        let blob = Blob::from_bytes(&bytes).unwrap();
+        let commitment =
+            KzgCommitment::blob_to_kzg_commitment(&blob, &MAINNET_KZG_TRUSTED_SETUP).unwrap();
+        let commitment_bytes = commitment.to_bytes();
+        let proof =
+            KzgProof::compute_blob_kzg_proof(&blob, &commitment_bytes, &MAINNET_KZG_TRUSTED_SETUP)
+                .unwrap();
+
+        let transaction = TxEip4844 {
+            blob_versioned_hashes: vec![kzg_to_versioned_hash(commitment)],
+            ..Default::default()
+        };
+        let sidecar = BlobTransactionSidecar {
+            blobs: vec![blob],
+            commitments: vec![commitment_bytes],
+            proofs: vec![proof.to_bytes()],
+        };
+        (transaction, sidecar)
+    }
+
+    #[test]
+    fn verify_batch_and_cache() {
+        let verifier = BlobVerifier::new(MAINNET_KZG_TRUSTED_SETUP.clone());
+        let (tx1, sidecar1) = blob_transaction(1);
+        let (tx2, sidecar2) = blob_transaction(2);
+
+        let results = verifier.verify_batch(&[(&tx1, &sidecar1), (&tx2, &sidecar2)]);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(verifier.inner.verified.lock().len(), 2);
+
+        // an identical sidecar is a cache hit
+        assert!(verifier.verify(&tx1, &sidecar1).is_ok());
+
+        // a cached blob that is resent with a wrong proof is verified again and rejected
+        let mut resent = sidecar1.clone();
+        resent.proofs = sidecar2.proofs.clone();
+        assert!(matches!(
+            verifier.verify(&tx1, &resent),
+            Err(BlobTransactionValidationError::InvalidProof)
+        ));
+        let mut garbage = sidecar1.clone();
+        garbage.proofs = vec![Bytes48::from_bytes(&[0xab; 48]).unwrap()];
+        assert!(verifier.verify(&tx1, &garbage).is_err());
+
+        // the rejected proofs didn't evict the verified entry
+        assert!(verifier.verify(&tx1, &sidecar1).is_ok());
+
+        // a different blob for a cached versioned hash is verified again
+        let mut tampered = sidecar1;
+        tampered.blobs = sidecar2.blobs;
+        assert!(verifier.verify(&tx1, &tampered).is_err());
+    }
+
+    #[test]
+    fn verify_batch_finds_invalid_sidecar() {
+        let verifier = BlobVerifier::new(MAINNET_KZG_TRUSTED_SETUP.clone());
+        let (tx1, mut sidecar1) = blob_transaction(1);
+        let (tx2, sidecar2) = blob_transaction(2);
+        sidecar1.proofs = sidecar2.proofs.clone();
+
+        let results = verifier.verify_batch(&[(&tx1, &sidecar1), (&tx2, &sidecar2)]);
+        assert!(matches!(results[0], Err(BlobTransactionValidationError::InvalidProof)));
+        assert!(results[1].is_ok());
+        assert_eq!(verifier.inner.verified.lock().len(), 1);
+
+        // versioned hashes that don't match the commitments are rejected without verification
+        assert!(verifier.verify(&tx2, &sidecar1).is_err());
+    }
+}
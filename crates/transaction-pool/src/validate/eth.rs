@@ -4,7 +4,7 @@ use crate::{
     blobstore::BlobStore,
     error::{Eip4844PoolTransactionError, InvalidPoolTransactionError},
     traits::TransactionOrigin,
-    validate::{ValidTransaction, ValidationTask, MAX_INIT_CODE_SIZE, TX_MAX_SIZE},
+    validate::{BlobVerifier, ValidTransaction, ValidationTask, MAX_INIT_CODE_SIZE, TX_MAX_SIZE},
     EthBlobTransactionSidecar, EthPoolTransaction, LocalTransactionConfig, PoolTransaction,
     TransactionValidationOutcome, TransactionValidationTaskExecutor, TransactionValidator,
 };
@@ -64,6 +64,7 @@ where
         &self,
         transactions: Vec<(TransactionOrigin, Tx)>,
     ) -> Vec<TransactionValidationOutcome<Tx>> {
+        self.inner.verify_blob_sidecars(&transactions);
         transactions.into_iter().map(|(origin, tx)| self.validate_one(origin, tx)).collect()
     }
 }
@@ -122,8 +123,8 @@ where
     minimum_priority_fee: Option<u128>,
     /// Toggle to determine if a local transaction should be propagated
     propagate_local_transactions: bool,
-    /// Stores the setup and parameters needed for validating KZG proofs.
-    kzg_settings: Arc<KzgSettings>,
+    /// Verifies the KZG proofs of blob sidecars.
+    blob_verifier: BlobVerifier,
     /// How to handle [TransactionOrigin::Local](TransactionOrigin) transactions.
     local_transactions_config: LocalTransactionConfig,
     /// Marker for the transaction type
@@ -327,7 +328,7 @@ where
                 EthBlobTransactionSidecar::Present(blob) => {
                     if let Some(eip4844) = transaction.as_eip4844() {
                         // validate the blob
-                        if let Err(err) = eip4844.validate_blob(&blob, &self.kzg_settings) {
+                        if let Err(err) = self.blob_verifier.verify(eip4844, &blob) {
                             return TransactionValidationOutcome::Invalid(
                                 transaction,
                                 InvalidPoolTransactionError::Eip4844(
@@ -447,6 +448,25 @@ where
         }
     }
 
+    /// Verifies the sidecars of all blob transactions in one batch.
+    ///
+    /// The verifier caches the blobs that passed, so the validation of the individual transactions
+    /// does not verify them again.
+    fn verify_blob_sidecars(&self, transactions: &[(TransactionOrigin, Tx)]) {
+        if !self.eip4844 || !self.fork_tracker.is_cancun_activated() {
+            return
+        }
+        let sidecars = transactions
+            .iter()
+            .filter(|(_, tx)| (1..=MAX_BLOBS_PER_BLOCK).contains(&tx.blob_count()))
+            .filter_map(|(_, tx)| Some((tx.as_eip4844()?, tx.blob_sidecar()?)))
+            .collect::<Vec<_>>();
+        // a single sidecar is verified along with its transaction
+        if sidecars.len() > 1 {
+            self.blob_verifier.verify_batch(&sidecars);
+        }
+    }
+
     fn on_new_head_block(&self, new_tip_block: &SealedBlock) {
         // update all forks
         if self.chain_spec.is_cancun_active_at_timestamp(new_tip_block.timestamp) {
@@ -486,6 +506,10 @@ pub struct EthTransactionValidatorBuilder {
 
     /// Stores the setup and parameters needed for validating KZG proofs.
     kzg_settings: Arc<KzgSettings>,
+    /// Verifies the KZG proofs of blob sidecars, shared with other components.
+    ///
+    /// If unset, a verifier is created from the configured [KzgSettings].
+    blob_verifier: Option<BlobVerifier>,
     /// How to handle [TransactionOrigin::Local](TransactionOrigin) transactions.
     local_transactions_config: LocalTransactionConfig,
 }
@@ -504,6 +528,7 @@ impl EthTransactionValidatorBuilder {
             // default to true, can potentially take this as a param in the future
             propagate_local_transactions: true,
             kzg_settings: Arc::clone(&MAINNET_KZG_TRUSTED_SETUP),
+            blob_verifier: None,
             local_transactions_config: Default::default(),
 
             // by default all transaction types are allowed
@@ -578,6 +603,15 @@ impl EthTransactionValidatorBuilder {
         self
     }
 
+    /// Sets the [BlobVerifier] to use for validating KZG proofs.
+    ///
+    /// This allows sharing the cache of verified blobs with other components. Takes precedence
+    /// over the configured [KzgSettings].
+    pub fn blob_verifier(mut self, blob_verifier: BlobVerifier) -> Self {
+        self.blob_verifier = Some(blob_verifier);
+        self
+    }
+
     /// Sets toggle to propagate transactions received locally by this client (e.g
     /// transactions from eth_sendTransaction to this nodes' RPC server)
     ///
@@ -638,6 +672,7 @@ impl EthTransactionValidatorBuilder {
             minimum_priority_fee,
             propagate_local_transactions,
             kzg_settings,
+            blob_verifier,
             local_transactions_config,
             ..
         } = self;
@@ -656,7 +691,7 @@ impl EthTransactionValidatorBuilder {
             minimum_priority_fee,
             propagate_local_transactions,
             blob_store: Box::new(blob_store),
-            kzg_settings,
+            blob_verifier: blob_verifier.unwrap_or_else(|| BlobVerifier::new(kzg_settings)),
             local_transactions_config,
             _marker: Default::default(),
         };
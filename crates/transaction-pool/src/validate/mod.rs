@@ -11,10 +11,14 @@ use reth_primitives::{
 };
 use std::{fmt, time::Instant};
 
+mod blob;
 mod constants;
 mod eth;
 mod task;
 
+/// Batched verification of blob sidecars.
+pub use blob::{BlobVerifier, DEFAULT_VERIFIED_BLOBS_CACHE_SIZE};
+
 /// A `TransactionValidator` implementation that validates ethereum transaction.
 pub use eth::*;
 
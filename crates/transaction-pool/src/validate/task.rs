@@ -16,6 +16,7 @@ use tokio::{
     sync::{mpsc, oneshot},
 };
 use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug_span, Instrument};
 
 /// A service that performs validation jobs.
 #[derive(Clone)]
@@ -167,11 +168,16 @@ where
                 let to_validation_task = self.to_validation_task.clone();
                 let to_validation_task = to_validation_task.lock().await;
                 let validator = self.validator.clone();
+                // the job runs on the validation task, but is traced as part of the caller's span
+                let span = debug_span!(target: "txpool", "validate_transaction", tx_hash = %hash);
                 to_validation_task
-                    .send(Box::pin(async move {
-                        let res = validator.validate_transaction(origin, transaction).await;
-                        let _ = tx.send(res);
-                    }))
+                    .send(Box::pin(
+                        async move {
+                            let res = validator.validate_transaction(origin, transaction).await;
+                            let _ = tx.send(res);
+                        }
+                        .instrument(span),
+                    ))
                     .await
             };
             if res.is_err() {
@@ -2,8 +2,8 @@ use assert_matches::assert_matches;
 use reth_transaction_pool::{
     noop::MockTransactionValidator,
     test_utils::{testing_pool, testing_pool_with_validator, MockTransactionFactory},
-    FullTransactionEvent, TransactionEvent, TransactionListenerKind, TransactionOrigin,
-    TransactionPool,
+    DropReason, FullTransactionEvent, PoolEventKind, TransactionEvent, TransactionListenerKind,
+    TransactionOrigin, TransactionPool,
 };
 use std::{future::poll_fn, task::Poll};
 use tokio_stream::StreamExt;
@@ -87,3 +87,29 @@ async fn txpool_listener_new_propagate_only() {
     })
     .await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn txpool_event_log_resume() {
+    let txpool = testing_pool();
+    let mut mock_tx_factory = MockTransactionFactory::default();
+    let transaction = mock_tx_factory.create_eip1559();
+    let hash = *transaction.hash();
+
+    let mut events = txpool.transaction_event_log().subscribe(None).unwrap();
+    txpool
+        .add_transaction(TransactionOrigin::External, transaction.transaction.clone())
+        .await
+        .unwrap();
+    txpool.remove_transactions(vec![hash]);
+
+    let batch = events.recv_batch(1).await.unwrap();
+    assert_eq!(batch[0].seq, 0);
+    assert_eq!(batch[0].kind, PoolEventKind::Added { pending: true });
+
+    // a reconnecting subscriber resumes after the last event it has seen
+    let mut resumed = txpool.transaction_event_log().subscribe(Some(1)).unwrap();
+    let batch = resumed.recv_batch(10).await.unwrap();
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].hash, hash);
+    assert_eq!(batch[0].kind, PoolEventKind::Dropped { reason: DropReason::Removed });
+}
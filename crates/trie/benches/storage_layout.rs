@@ -0,0 +1,143 @@
+//! Compares the storage root computation of a hot contract depending on where its slots are
+//! stored, with a cold page cache.
+//!
+//! `HashedStorage` is a dupsort table keyed by hashed address, so the slots of one account are
+//! already contiguous, but they share the pages of the table with the storage of all other
+//! accounts. The alternate layout moves the storage of hot contracts into `HotHashedStorages`
+//! with [migrate_hot_storages].
+//!
+//! The pages of the database file are evicted from the page cache before every computation, so
+//! every page the computation reads is faulted in from disk. The average number of major page
+//! faults per computation is printed next to the timings. Evicting pages is only supported on
+//! Linux, elsewhere the page cache stays hot.
+#![allow(missing_docs, unreachable_pub)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use reth_db::{
+    database::Database,
+    init_db,
+    mdbx::DatabaseArguments,
+    open_db_read_only, tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{keccak256, StorageEntry, B256, U256};
+use reth_trie::{
+    hashed_cursor::{migrate_hot_storages, HotStorageHashedCursorFactory},
+    StorageRoot,
+};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// The number of cold accounts stored next to the hot contract.
+const COLD_ACCOUNTS: u64 = 50_000;
+
+/// The number of slots of each cold account.
+const COLD_SLOTS: u64 = 16;
+
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    /// All storage is in `HashedStorage`.
+    Shared,
+    /// The storage of the hot contract was moved to `HotHashedStorages`.
+    Dedicated,
+}
+
+pub fn storage_layout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Storage Layout");
+    group.sample_size(20);
+
+    let hot_contract = keccak256(B256::ZERO);
+    for hot_slots in [10_000, 100_000] {
+        for layout in [Layout::Shared, Layout::Dedicated] {
+            let dir = tempfile::tempdir().unwrap();
+            create_db(dir.path(), hot_contract, hot_slots, layout);
+
+            let mut faults = 0;
+            let mut computations = 0;
+            group.bench_function(format!("hot slots: {hot_slots} | {layout:?}"), |b| {
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        evict_page_cache(dir.path());
+                        let db =
+                            open_db_read_only(dir.path(), DatabaseArguments::default()).unwrap();
+                        let tx = db.tx().unwrap();
+
+                        let faults_before = major_page_faults();
+                        let started_at = Instant::now();
+                        let root = StorageRoot::from_tx_hashed(&tx, hot_contract);
+                        let root = match layout {
+                            Layout::Shared => root.root(),
+                            Layout::Dedicated => root
+                                .with_hashed_cursor_factory(HotStorageHashedCursorFactory::new(&tx))
+                                .root(),
+                        };
+                        elapsed += started_at.elapsed();
+                        faults += major_page_faults() - faults_before;
+                        computations += 1;
+                        black_box(root.unwrap());
+                    }
+                    elapsed
+                })
+            });
+            println!(
+                "hot slots: {hot_slots} | {layout:?}: {:.1} major page faults per root",
+                faults as f64 / computations.max(1) as f64
+            );
+        }
+    }
+}
+
+/// Creates a database with the storage of the hot contract and of the cold accounts, whose hashed
+/// addresses are interleaved with the hot contract, in the given layout.
+fn create_db(path: &Path, hot_contract: B256, hot_slots: u64, layout: Layout) {
+    let db = init_db(path, DatabaseArguments::default()).unwrap();
+    let tx = db.tx_mut().unwrap();
+    for slot in 0..hot_slots {
+        let entry =
+            StorageEntry { key: keccak256(slot.to_be_bytes()), value: U256::from(slot + 1) };
+        tx.put::<tables::HashedStorage>(hot_contract, entry).unwrap();
+    }
+    for account in 1..=COLD_ACCOUNTS {
+        let hashed_address = keccak256(account.to_be_bytes());
+        for slot in 0..COLD_SLOTS {
+            let entry =
+                StorageEntry { key: keccak256(slot.to_be_bytes()), value: U256::from(slot + 1) };
+            tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+        }
+    }
+    if let Layout::Dedicated = layout {
+        migrate_hot_storages(&tx, [hot_contract]).unwrap();
+    }
+    tx.commit().unwrap();
+}
+
+/// Evicts the pages of the database file from the page cache. The database must not be open, the
+/// pages that are mapped by a process are not evicted.
+#[cfg(target_os = "linux")]
+fn evict_page_cache(path: &Path) {
+    use std::os::fd::AsRawFd;
+
+    let file = std::fs::File::open(path.join("mdbx.dat")).unwrap();
+    // SAFETY: the file descriptor is open for the duration of the call
+    let res = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    assert_eq!(res, 0, "failed to evict the page cache");
+}
+
+#[cfg(not(target_os = "linux"))]
+fn evict_page_cache(_path: &Path) {}
+
+/// Returns the number of major page faults of the process so far.
+fn major_page_faults() -> i64 {
+    // SAFETY: `getrusage` only writes to the given struct, which is valid for any bit pattern
+    unsafe {
+        let mut usage = std::mem::zeroed::<libc::rusage>();
+        libc::getrusage(libc::RUSAGE_SELF, &mut usage);
+        usage.ru_majflt as i64
+    }
+}
+
+criterion_group!(layout, storage_layout);
+criterion_main!(layout);
@@ -0,0 +1,36 @@
+//! Benchmarks the state root computation of synthetic mainnet-like transitions at different state
+//! sizes.
+#![allow(missing_docs, unreachable_pub)]
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use reth_db::{database::Database, test_utils::create_test_rw_db, transaction::DbTx};
+use reth_trie::test_utils::workload::{WorkloadConfig, WorkloadGenerator};
+
+/// The seed of all generated workloads.
+const SEED: u64 = 1;
+
+pub fn workload(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Workload");
+    group.sample_size(20);
+
+    for accounts in [10_000, 100_000] {
+        let mut generator =
+            WorkloadGenerator::new(WorkloadConfig { accounts, ..Default::default() }, SEED);
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        generator.write_state(&tx).unwrap();
+        tx.commit().unwrap();
+
+        let post_state = generator.next_transition();
+        group.bench_function(format!("accounts: {accounts}"), |b| {
+            b.iter_batched(
+                || db.tx().unwrap(),
+                |tx| post_state.state_root(&tx).unwrap(),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+}
+
+criterion_group!(benches, workload);
+criterion_main!(benches);
@@ -0,0 +1,327 @@
+//! Eviction of cached cursor results.
+
+use schnellru::{LruMap, Unlimited};
+use std::hash::Hash;
+
+/// The policy for evicting the entries of a cache of cursor results.
+///
+/// Capacities are in cached entries: every cached seek result, entry of a `next` run and storage
+/// emptiness flag counts as one entry, like in [CursorCache::size](super::CursorCache::size).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EvictionPolicy {
+    /// Entries are never evicted.
+    #[default]
+    Unbounded,
+    /// The least recently used entries are evicted once the cache holds more than `capacity`
+    /// entries.
+    Lru {
+        /// The maximum number of cached entries.
+        capacity: usize,
+    },
+    /// Segmented LRU: entries start out on probation and are protected once they are used again,
+    /// so the entries of a single scan evict each other before they evict entries that are used
+    /// repeatedly.
+    ///
+    /// The least recently protected entries are moved back to probation once the protected ones
+    /// exceed `protected` entries, and the least recently used entries on probation are evicted
+    /// once the cache holds more than `capacity` entries.
+    SegmentedLru {
+        /// The maximum number of cached entries.
+        capacity: usize,
+        /// The maximum number of protected entries.
+        protected: usize,
+    },
+}
+
+/// The eviction policies of the caches of a [CursorCache](super::CursorCache).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CursorCacheConfig {
+    /// The policy of the hashed account cursor results.
+    pub accounts: EvictionPolicy,
+    /// The policy of the hashed storage cursor results, which are evicted per account.
+    pub storages: EvictionPolicy,
+    /// The policy of the account trie cursor results and, separately, of the storage trie cursor
+    /// results, which are evicted per account.
+    pub tries: EvictionPolicy,
+}
+
+/// A value whose size counts against the capacity of a [BoundedMap].
+pub(super) trait Weighted {
+    /// Returns the number of cached entries of the value.
+    fn weight(&self) -> usize;
+}
+
+/// The segment of a [BoundedMap] an entry is in.
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Probation,
+    Protected,
+}
+
+/// A map whose entries are evicted according to an [EvictionPolicy].
+///
+/// The weight of an entry may change when it's updated.
+pub(super) struct BoundedMap<K: Hash + PartialEq, V> {
+    policy: EvictionPolicy,
+    /// All entries, or the entries on probation with [EvictionPolicy::SegmentedLru].
+    probation: LruMap<K, V, Unlimited>,
+    /// The protected entries with [EvictionPolicy::SegmentedLru].
+    protected: LruMap<K, V, Unlimited>,
+    probation_weight: usize,
+    protected_weight: usize,
+}
+
+impl<K: Hash + PartialEq, V> std::fmt::Debug for BoundedMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoundedMap")
+            .field("policy", &self.policy)
+            .field("len", &self.len())
+            .field("weight", &self.weight())
+            .finish()
+    }
+}
+
+impl<K: Hash + PartialEq + Clone, V: Weighted> BoundedMap<K, V> {
+    /// Creates an empty map with the given policy.
+    pub(super) fn new(policy: EvictionPolicy) -> Self {
+        Self {
+            policy,
+            probation: LruMap::new(Unlimited),
+            protected: LruMap::new(Unlimited),
+            probation_weight: 0,
+            protected_weight: 0,
+        }
+    }
+
+    /// Returns the number of entries.
+    pub(super) fn len(&self) -> usize {
+        self.probation.len() + self.protected.len()
+    }
+
+    /// Returns the total weight of the entries.
+    pub(super) fn weight(&self) -> usize {
+        self.probation_weight + self.protected_weight
+    }
+
+    /// Returns whether the map has an entry of the key, without marking it as used.
+    pub(super) fn contains_key(&self, key: &K) -> bool {
+        self.probation.peek(key).is_some() || self.protected.peek(key).is_some()
+    }
+
+    /// Returns the entries without marking them as used, from the least to the most recently
+    /// used, with the entries on probation before the protected ones.
+    pub(super) fn entries(&self) -> Vec<(&K, &V)> {
+        // the maps iterate from the most recently used entry
+        let mut entries = self.protected.iter().chain(self.probation.iter()).collect::<Vec<_>>();
+        entries.reverse();
+        entries
+    }
+
+    /// Returns the entry of the key without marking it as used.
+    #[cfg(test)]
+    pub(super) fn peek(&self, key: &K) -> Option<&V> {
+        self.protected.peek(key).or_else(|| self.probation.peek(key))
+    }
+
+    /// Calls `f` with the entry of the key, if there is one, and marks it as used.
+    pub(super) fn get_mut<R>(&mut self, key: &K, f: impl FnOnce(&mut V) -> R) -> Option<R> {
+        let segment = self.touch(key)?;
+        let result = self.apply(segment, key, f);
+        self.evict();
+        Some(result)
+    }
+
+    /// Calls `f` with the entry of the key, inserting the default entry if there is none, and
+    /// marks it as used.
+    pub(super) fn update<R>(&mut self, key: K, f: impl FnOnce(&mut V) -> R) -> R
+    where
+        V: Default,
+    {
+        let segment = match self.touch(&key) {
+            Some(segment) => segment,
+            None => {
+                let value = V::default();
+                self.probation_weight += value.weight();
+                self.probation.insert(key.clone(), value);
+                Segment::Probation
+            }
+        };
+        let result = self.apply(segment, &key, f);
+        self.evict();
+        result
+    }
+
+    /// Removes the entry of the key.
+    pub(super) fn remove(&mut self, key: &K) -> Option<V> {
+        if let Some(value) = self.probation.remove(key) {
+            self.probation_weight -= value.weight();
+            return Some(value)
+        }
+        let value = self.protected.remove(key)?;
+        self.protected_weight -= value.weight();
+        Some(value)
+    }
+
+    /// Calls `f` with every entry without marking it as used, and removes the entries `f` returns
+    /// `false` for.
+    pub(super) fn retain(&mut self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        for (map, weight) in [
+            (&mut self.probation, &mut self.probation_weight),
+            (&mut self.protected, &mut self.protected_weight),
+        ] {
+            let keys = map.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+            for key in keys {
+                let value = map.peek_mut(&key).expect("entry is in the segment");
+                let before = value.weight();
+                let keep = f(&key, value);
+                *weight = *weight + value.weight() - before;
+                if !keep {
+                    let value = map.remove(&key).expect("entry is in the segment");
+                    *weight -= value.weight();
+                }
+            }
+        }
+        self.evict();
+    }
+
+    /// Marks the entry of the key as used, returning its segment.
+    fn touch(&mut self, key: &K) -> Option<Segment> {
+        if !matches!(self.policy, EvictionPolicy::SegmentedLru { .. }) {
+            return self.probation.get(key).map(|_| Segment::Probation)
+        }
+        if self.protected.get(key).is_some() {
+            return Some(Segment::Protected)
+        }
+
+        // an entry on probation that is used again is protected
+        let value = self.probation.remove(key)?;
+        let weight = value.weight();
+        self.probation_weight -= weight;
+        self.protected_weight += weight;
+        self.protected.insert(key.clone(), value);
+        Some(Segment::Protected)
+    }
+
+    /// Calls `f` with the entry of the key in the segment and updates the weight of the segment.
+    fn apply<R>(&mut self, segment: Segment, key: &K, f: impl FnOnce(&mut V) -> R) -> R {
+        let (map, weight) = match segment {
+            Segment::Probation => (&mut self.probation, &mut self.probation_weight),
+            Segment::Protected => (&mut self.protected, &mut self.protected_weight),
+        };
+        let value = map.peek_mut(key).expect("entry is in the segment");
+        let before = value.weight();
+        let result = f(value);
+        *weight = *weight + value.weight() - before;
+        result
+    }
+
+    /// Evicts the least recently used entries until the map is within the capacity of the
+    /// policy.
+    fn evict(&mut self) {
+        match self.policy {
+            EvictionPolicy::Unbounded => {}
+            EvictionPolicy::Lru { capacity } => {
+                while self.probation_weight > capacity {
+                    let Some((_, value)) = self.probation.pop_oldest() else { break };
+                    self.probation_weight -= value.weight();
+                }
+            }
+            EvictionPolicy::SegmentedLru { capacity, protected } => {
+                while self.protected_weight > protected {
+                    let Some((key, value)) = self.protected.pop_oldest() else { break };
+                    let weight = value.weight();
+                    self.protected_weight -= weight;
+                    self.probation_weight += weight;
+                    self.probation.insert(key, value);
+                }
+                while self.weight() > capacity {
+                    if let Some((_, value)) = self.probation.pop_oldest() {
+                        self.probation_weight -= value.weight();
+                    } else if let Some((_, value)) = self.protected.pop_oldest() {
+                        self.protected_weight -= value.weight();
+                    } else {
+                        break
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K: Hash + PartialEq + Clone, V: Weighted> Weighted for BoundedMap<K, V> {
+    fn weight(&self) -> usize {
+        self.probation_weight + self.protected_weight
+    }
+}
+
+impl<K: Hash + PartialEq + Clone, V: Weighted> Default for BoundedMap<K, V> {
+    fn default() -> Self {
+        Self::new(EvictionPolicy::Unbounded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Weighted for Vec<u8> {
+        fn weight(&self) -> usize {
+            self.len()
+        }
+    }
+
+    fn insert(map: &mut BoundedMap<u8, Vec<u8>>, key: u8, weight: usize) {
+        map.update(key, |value| *value = vec![key; weight]);
+    }
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut map = BoundedMap::new(EvictionPolicy::Lru { capacity: 4 });
+        insert(&mut map, 1, 1);
+        insert(&mut map, 2, 1);
+        insert(&mut map, 3, 2);
+        // 1 becomes the most recently used
+        assert_eq!(map.get_mut(&1, |value| value.len()), Some(1));
+
+        // growing 3 evicts 2
+        map.update(3, |value| value.push(3));
+        assert!(map.peek(&2).is_none());
+        assert_eq!(map.weight(), 4);
+
+        insert(&mut map, 4, 2);
+        assert!(map.peek(&1).is_none());
+        assert!(map.peek(&3).is_none());
+        assert_eq!(map.weight(), 2);
+
+        assert_eq!(map.remove(&4).map(|value| value.len()), Some(2));
+        assert_eq!((map.len(), map.weight()), (0, 0));
+    }
+
+    #[test]
+    fn segmented_lru_protects_reused_entries() {
+        let mut map = BoundedMap::new(EvictionPolicy::SegmentedLru { capacity: 4, protected: 2 });
+        insert(&mut map, 1, 1);
+        insert(&mut map, 2, 1);
+        map.get_mut(&1, |_| ());
+        map.get_mut(&2, |_| ());
+
+        // a scan of entries used once doesn't evict the protected ones
+        for key in 10..20 {
+            insert(&mut map, key, 1);
+        }
+        assert!(map.peek(&1).is_some());
+        assert!(map.peek(&2).is_some());
+        assert!(map.peek(&18).is_some());
+        assert!(map.peek(&17).is_none());
+        assert_eq!(map.weight(), 4);
+
+        // protecting another entry moves the least recently protected one back to probation
+        map.get_mut(&19, |_| ());
+        insert(&mut map, 20, 1);
+        insert(&mut map, 21, 1);
+        assert!(map.peek(&1).is_none());
+        assert!(map.peek(&2).is_some());
+        assert!(map.peek(&19).is_some());
+        assert_eq!(map.weight(), 4);
+    }
+}
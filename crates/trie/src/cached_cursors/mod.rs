@@ -0,0 +1,1437 @@
+//! Cursor caches shared by state root computations over the same state.
+//!
+//! Computing the state roots of several candidate blocks on the same parent, e.g. while building
+//! payloads, repeats most of the cursor operations. A [CursorCache] records the results of hashed
+//! and trie cursor operations, so the cached cursor factories of later computations serve them
+//! from memory. The cache is only valid for the database state it was populated from. When a block
+//! is written to that state, the cache is updated with [CursorCache::apply_post_state] instead of
+//! being dropped, which keeps the results the block did not touch.
+//!
+//! A hashed cursor seek that was not cached itself is still served from the cache if its key lies
+//! within a cached `next` run, or after the last entry of a run that reached the end, because the
+//! run determines the entry at or after the key. The cursor continues with the rest of the run.
+//!
+//! ## Errors
+//!
+//! A failed operation of an underlying cursor never records anything. The results of consecutive
+//! `next` calls are cached as runs following the entry a seek returned, and a run is only marked
+//! as terminated when the underlying cursor returned `None`, so a transient error in the middle of
+//! a run is never cached as its end.
+//!
+//! The run a failed call was extending is poisoned: it is evicted, because the transaction it was
+//! read with may no longer be usable, and the cursor loses its position. Further `next` calls on
+//! that cursor are passed through without being cached until the next seek, and a retry of the
+//! computation reads the run from the database again.
+//!
+//! ## Eviction
+//!
+//! The caches of hashed accounts, hashed storages and trie nodes are bounded separately by the
+//! [EvictionPolicy] of a [CursorCacheConfig]. Storages and storage tries are evicted per account.
+//! An evicted run is read from the underlying cursor again by the next cursor that reaches it, and
+//! `next` results are only recorded on a run that is still cached, so a run never has gaps.
+//!
+//! ## Updates
+//!
+//! Applying the [HashedPostState](crate::HashedPostState) of a block updates cached hashed cursor
+//! results in place where the changes determine them, e.g. a seek whose entry was updated or
+//! preceded by a created one, and merges the changes into cached `next` runs. Results the changes
+//! leave undetermined, like a seek whose entry was removed, are evicted. Cached trie nodes are not
+//! recomputed: every seek whose result may be a node on the path of a changed key is evicted. The
+//! cache must not be used by any cursor while it is updated.
+//!
+//! Pending changes that are not written to the database yet, e.g. of a block being built, are
+//! layered over the cached cursors with [CursorCache::overlay_hashed_cursor_factory] instead, which
+//! leaves the cache valid for the database state.
+//!
+//! ## Concurrency
+//!
+//! Each cache is split into shards by the hash of the keys it is accessed with: the hashed address
+//! for storages and storage tries, and the path for the account trie. Hashed accounts are split by
+//! the leading byte of the key of a seek or the start of a run instead, so each shard holds a range
+//! of consecutive keys, and a run ends with its first entry in a later shard, where the following
+//! run starts. The shards are locked independently, so the cursors of parallel storage root
+//! computations rarely wait for each other. A bounded cache splits its capacity between the
+//! shards, and its size is tracked without locking them. A hashed account seek that misses its
+//! shard only looks for a run containing its key in the preceding shard.
+//!
+//! ## Persistence
+//!
+//! A cache can be saved with [CursorCache::save_to], e.g. on shutdown, and restored with
+//! [CursorCache::load_from] at boot, so the first computations after a restart don't read the
+//! whole working set from the database again. The snapshot is a compact binary encoding with a
+//! version header and the state root of the state the cache was populated from. It is only loaded
+//! if that root matches the current state root, because the cached results of any other state may
+//! be stale.
+//!
+//! Processes running against the same datadir can share a warm cache through shared memory, which
+//! is experimental. A [SharedCursorCacheWriter], e.g. in a building process, publishes snapshots
+//! to a fixed-size memory mapped region, and a [SharedCursorCacheReader], e.g. in a simulation
+//! process, loads the latest one for the current state root. Readers don't take locks: they copy
+//! the snapshot and retry if the writer published a new one meanwhile.
+//!
+//! ## Statistics
+//!
+//! Every cursor counts the operations it served from the cache, passed to the underlying cursor
+//! and recorded, and adds them to the [CursorCacheStats] of its cache when it is dropped.
+
+use crate::{
+    hashed_cursor::{
+        HashedAccountCursor, HashedCursorFactory, HashedStorageCursor, OverlayHashedCursorFactory,
+    },
+    trie_cursor::{TrieCursor, TrieCursorFactory, TrieCursorKey},
+    updates::TrieKey,
+    HashedPostState,
+};
+use eviction::{BoundedMap, Weighted};
+use reth_db::DatabaseError;
+use reth_primitives::{
+    trie::{BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey},
+    Account, StorageEntry, B256, U256,
+};
+use sharded::Sharded;
+use stats::SharedCacheStats;
+use std::{collections::BTreeSet, sync::Arc};
+
+mod eviction;
+pub use eviction::{CursorCacheConfig, EvictionPolicy};
+
+mod post_state;
+
+mod sharded;
+
+mod snapshot;
+
+mod shared;
+pub use shared::{SharedCursorCacheReader, SharedCursorCacheWriter};
+
+mod stats;
+pub use stats::{CacheStats, CursorCacheStats};
+
+/// Cached results of hashed and trie cursor operations.
+#[derive(Debug, Default, Clone)]
+pub struct CursorCache {
+    hashed_cursors: Arc<HashedCursorCache>,
+    trie_cursors: Arc<TrieCursorsCaches>,
+}
+
+impl CursorCache {
+    /// Creates an empty cache with the given eviction policies.
+    pub fn new(config: CursorCacheConfig) -> Self {
+        Self {
+            hashed_cursors: Arc::new(HashedCursorCache::new(config.accounts, config.storages)),
+            trie_cursors: Arc::new(TrieCursorsCaches::new(config.tries)),
+        }
+    }
+
+    /// Returns a hashed cursor factory wrapping the given one, which reads through the cache.
+    pub fn hashed_cursor_factory<H>(&self, inner: H) -> CachedHashedCursorFactory<H> {
+        CachedHashedCursorFactory::new(inner, Arc::clone(&self.hashed_cursors))
+    }
+
+    /// Returns a hashed cursor factory layering the post state over the cursors of the given
+    /// factory, which read through the cache.
+    ///
+    /// The cache is populated with the database state only, so it stays valid for the state it
+    /// was populated from, and the pending changes of a block don't need to be copied into it.
+    pub fn overlay_hashed_cursor_factory<'b, H>(
+        &self,
+        inner: H,
+        post_state: &'b HashedPostState,
+    ) -> OverlayHashedCursorFactory<'b, CachedHashedCursorFactory<H>> {
+        OverlayHashedCursorFactory::new(self.hashed_cursor_factory(inner), post_state)
+    }
+
+    /// Returns a trie cursor factory wrapping the given one, which reads through the cache.
+    pub fn trie_cursor_factory<T>(&self, inner: T) -> CachedTrieCursorFactory<T> {
+        CachedTrieCursorFactory::new(inner, Arc::clone(&self.trie_cursors))
+    }
+
+    /// Returns the cache of hashed cursors.
+    pub fn hashed_cursors(&self) -> &Arc<HashedCursorCache> {
+        &self.hashed_cursors
+    }
+
+    /// Returns the caches of trie cursors.
+    pub fn trie_cursors(&self) -> &Arc<TrieCursorsCaches> {
+        &self.trie_cursors
+    }
+
+    /// Returns the number of cached entries.
+    pub fn size(&self) -> usize {
+        self.hashed_cursors.size() + self.trie_cursors.size()
+    }
+
+    /// Returns the operations of the dropped cursors on each cache.
+    pub fn stats(&self) -> CursorCacheStats {
+        CursorCacheStats {
+            accounts: self.hashed_cursors.account_stats.get(),
+            storages: self.hashed_cursors.storage_stats.get(),
+            account_trie: self.trie_cursors.account_trie_stats.get(),
+            storage_tries: self.trie_cursors.storage_tries_stats.get(),
+        }
+    }
+}
+
+/// Results of `next` calls following an entry.
+#[derive(Debug)]
+struct CachedRun<V> {
+    /// The entries returned by consecutive `next` calls.
+    entries: Vec<(B256, V)>,
+    /// Whether the last `next` call returned `None`.
+    terminated: bool,
+}
+
+impl<V> Default for CachedRun<V> {
+    fn default() -> Self {
+        Self { entries: Vec::new(), terminated: false }
+    }
+}
+
+impl<V> CachedRun<V> {
+    /// Records the result of a `next` call at `index` of the run. Results that would leave a gap
+    /// in the run are not recorded.
+    ///
+    /// Returns whether the result was recorded.
+    fn record(&mut self, index: usize, entry: Option<(B256, V)>) -> bool {
+        if self.terminated || self.entries.len() != index {
+            return false
+        }
+        match entry {
+            Some(entry) => self.entries.push(entry),
+            None => self.terminated = true,
+        }
+        true
+    }
+}
+
+/// The cached seek at a key and the cached run following it.
+#[derive(Debug)]
+struct CachedKey<V> {
+    /// The entry at or after the key, if the key was sought.
+    seek: Option<Option<(B256, V)>>,
+    /// The run following the key, if a seek returned the key.
+    run: Option<CachedRun<V>>,
+}
+
+impl<V> Default for CachedKey<V> {
+    fn default() -> Self {
+        Self { seek: None, run: None }
+    }
+}
+
+impl<V> Weighted for CachedKey<V> {
+    fn weight(&self) -> usize {
+        self.seek.is_some() as usize + self.run.as_ref().map_or(0, |run| run.entries.len() + 1)
+    }
+}
+
+/// Cached seeks and `next` runs of a cursor over sorted `B256` keys.
+#[derive(Debug)]
+struct RunCache<V> {
+    keys: BoundedMap<B256, CachedKey<V>>,
+    /// The starts of the cached runs, in order. Starts of evicted runs are removed lazily.
+    run_starts: BTreeSet<B256>,
+}
+
+impl<V> Default for RunCache<V> {
+    fn default() -> Self {
+        Self::new(EvictionPolicy::Unbounded)
+    }
+}
+
+impl<V> RunCache<V> {
+    fn new(policy: EvictionPolicy) -> Self {
+        Self { keys: BoundedMap::new(policy), run_starts: BTreeSet::new() }
+    }
+
+    /// Returns the cached entry at or after `key`, or `None` if it is not cached.
+    fn seek(&mut self, key: &B256) -> Option<Option<(B256, V)>>
+    where
+        V: Copy,
+    {
+        self.keys.get_mut(key, |cached| cached.seek).flatten()
+    }
+
+    /// Returns the entry at or after `key` from the cached run with the last start before `key`
+    /// and the position of a cursor at the entry, or `None` if the run doesn't determine it.
+    ///
+    /// The entry is determined if `key` is at or before the last entry of the run, or if the run
+    /// is terminated.
+    fn seek_in_run(&mut self, key: &B256) -> Option<(Option<(B256, V)>, Position)>
+    where
+        V: Copy,
+    {
+        let start = *self.run_starts.range(..*key).next_back()?;
+        let Some(found) = self
+            .keys
+            .get_mut(&start, |cached| {
+                cached.run.as_ref().map(|run| {
+                    let index = run.entries.partition_point(|(entry_key, _)| entry_key < key);
+                    match run.entries.get(index) {
+                        Some(entry) => {
+                            Some((Some(*entry), Position::after_next(start, index, Some(entry.0))))
+                        }
+                        None => run.terminated.then_some((None, Position::Exhausted)),
+                    }
+                })
+            })
+            .flatten()
+        else {
+            // the run was evicted
+            self.run_starts.remove(&start);
+            return None
+        };
+        found
+    }
+
+    /// Records the entry at or after `key`.
+    fn record_seek(&mut self, key: B256, entry: Option<(B256, V)>) {
+        self.keys.update(key, |cached| cached.seek = Some(entry));
+    }
+
+    /// Returns the cached entry at `index` of the run following `start`, `Some(None)` if the run
+    /// is known to end before it, or `None` if it is not cached.
+    fn next_entry(&mut self, start: &B256, index: usize) -> Option<Option<(B256, V)>>
+    where
+        V: Copy,
+    {
+        self.keys
+            .get_mut(start, |cached| {
+                let run = cached.run.as_ref()?;
+                match run.entries.get(index) {
+                    Some(entry) => Some(Some(*entry)),
+                    None => run.terminated.then_some(None),
+                }
+            })
+            .flatten()
+    }
+
+    /// Records the result of a `next` call at `index` of the run following `start`. Results
+    /// following an evicted run are not recorded.
+    ///
+    /// Returns whether the result was recorded.
+    fn record_next(&mut self, start: B256, index: usize, entry: Option<(B256, V)>) -> bool {
+        if index == 0 {
+            let recorded = self.keys.update(start, |cached| {
+                cached.run.get_or_insert_with(CachedRun::default).record(index, entry)
+            });
+            self.insert_run_start(start);
+            recorded
+        } else {
+            self.keys
+                .get_mut(&start, |cached| {
+                    cached.run.as_mut().map_or(false, |run| run.record(index, entry))
+                })
+                .unwrap_or_default()
+        }
+    }
+
+    /// Adds the start of a run, removing the starts of evicted runs once they make up half of the
+    /// starts.
+    fn insert_run_start(&mut self, start: B256) {
+        if self.run_starts.insert(start) && self.run_starts.len() > 2 * self.keys.len() {
+            let keys = &self.keys;
+            self.run_starts.retain(|start| keys.contains_key(start));
+        }
+    }
+
+    /// Evicts the run following `start`.
+    fn remove_run(&mut self, start: &B256) {
+        let unused = self.keys.get_mut(start, |cached| {
+            cached.run = None;
+            cached.seek.is_none()
+        });
+        if unused == Some(true) {
+            self.keys.remove(start);
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.keys.weight()
+    }
+}
+
+impl<V> Weighted for RunCache<V> {
+    fn weight(&self) -> usize {
+        self.size()
+    }
+}
+
+/// The position of a cached hashed cursor.
+#[derive(Debug, Clone, Copy)]
+enum Position {
+    /// The cursor was not positioned by a seek or lost its position to an error.
+    Unknown,
+    /// The cursor is past the last entry.
+    Exhausted,
+    /// The cursor is at the entry `last`, after `index` entries of the run following the entry
+    /// `start` returned by the last seek.
+    InRun { start: B256, index: usize, last: B256 },
+}
+
+impl Position {
+    fn after_seek(entry: Option<B256>) -> Self {
+        entry.map_or(Self::Exhausted, |key| Self::InRun { start: key, index: 0, last: key })
+    }
+
+    fn after_next(start: B256, index: usize, entry: Option<B256>) -> Self {
+        entry.map_or(Self::Exhausted, |last| Self::InRun { start, index: index + 1, last })
+    }
+}
+
+/// Cached results of hashed account and storage cursor operations.
+#[derive(Debug, Default)]
+pub struct HashedCursorCache {
+    accounts: Sharded<RunCache<Account>>,
+    /// Storages by hashed address.
+    storages: Sharded<BoundedMap<B256, StorageCursorCache>>,
+    account_stats: SharedCacheStats,
+    storage_stats: SharedCacheStats,
+}
+
+/// Cached results of hashed storage cursor operations on the storage of an account.
+#[derive(Debug, Default)]
+struct StorageCursorCache {
+    /// Whether the storage is empty.
+    is_empty: Option<bool>,
+    slots: RunCache<U256>,
+}
+
+impl Weighted for StorageCursorCache {
+    fn weight(&self) -> usize {
+        self.is_empty.is_some() as usize + self.slots.size()
+    }
+}
+
+impl HashedCursorCache {
+    /// Creates an empty cache with the given eviction policies of the accounts and of the
+    /// storages, which are evicted per account.
+    pub fn new(accounts: EvictionPolicy, storages: EvictionPolicy) -> Self {
+        Self {
+            accounts: Sharded::new(accounts, RunCache::new),
+            storages: Sharded::new(storages, BoundedMap::new),
+            account_stats: SharedCacheStats::default(),
+            storage_stats: SharedCacheStats::default(),
+        }
+    }
+
+    /// Returns the number of cached entries.
+    pub fn size(&self) -> usize {
+        self.accounts.weight() + self.storages.weight()
+    }
+}
+
+/// A [HashedCursorFactory] reading through a [HashedCursorCache].
+#[derive(Debug, Clone)]
+pub struct CachedHashedCursorFactory<H> {
+    inner: H,
+    cache: Arc<HashedCursorCache>,
+}
+
+impl<H> CachedHashedCursorFactory<H> {
+    /// Create a new factory wrapping the given one. The cache must have been populated from the
+    /// same state the wrapped factory reads.
+    pub fn new(inner: H, cache: Arc<HashedCursorCache>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+impl<H: HashedCursorFactory> HashedCursorFactory for CachedHashedCursorFactory<H> {
+    type AccountCursor = CachedHashedAccountCursor<H::AccountCursor>;
+    type StorageCursor = CachedHashedStorageCursor<H::StorageCursor>;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor, DatabaseError> {
+        Ok(CachedHashedAccountCursor {
+            inner: self.inner.hashed_account_cursor()?,
+            cache: Arc::clone(&self.cache),
+            position: Position::Unknown,
+            inner_positioned: false,
+            stats: CacheStats::default(),
+        })
+    }
+
+    fn hashed_storage_cursor(&self) -> Result<Self::StorageCursor, DatabaseError> {
+        Ok(CachedHashedStorageCursor {
+            inner: self.inner.hashed_storage_cursor()?,
+            cache: Arc::clone(&self.cache),
+            hashed_address: None,
+            position: Position::Unknown,
+            inner_positioned: false,
+            stats: CacheStats::default(),
+        })
+    }
+}
+
+/// A hashed account cursor reading through a [HashedCursorCache].
+#[derive(Debug)]
+pub struct CachedHashedAccountCursor<C> {
+    inner: C,
+    cache: Arc<HashedCursorCache>,
+    position: Position,
+    /// Whether the underlying cursor is at the position of this cursor.
+    inner_positioned: bool,
+    stats: CacheStats,
+}
+
+impl<C> CachedHashedAccountCursor<C> {
+    /// Returns the cached entry at or after `key` and the position of a cursor at it, from a
+    /// cached seek at `key` or from a cached run containing `key`.
+    ///
+    /// Only the runs of the shard of `key` and of the preceding shard are considered. Since runs
+    /// end with the first entry of a later shard, the run containing `key` is in one of them
+    /// unless the shards in between hold no accounts.
+    fn cached_seek(&self, key: B256) -> Option<(Option<(B256, Account)>, Position)> {
+        let accounts = &self.cache.accounts;
+        let found = accounts.with_ordered(&key, |accounts| match accounts.seek(&key) {
+            Some(entry) => Some((entry, Position::after_seek(entry.map(|(key, _)| key)))),
+            None => accounts.seek_in_run(&key),
+        });
+        let (entry, position) = found.or_else(|| {
+            accounts.with_preceding(&key, |accounts| accounts.seek_in_run(&key)).flatten()
+        })?;
+        Some((entry, self.split_run(position)))
+    }
+
+    /// Splits runs at shard boundaries: a position at an entry in a later shard than the start of
+    /// its run continues with the run following the entry, which is cached in the entry's shard.
+    fn split_run(&self, position: Position) -> Position {
+        match position {
+            Position::InRun { start, last, .. }
+                if !self.cache.accounts.same_ordered_shard(&start, &last) =>
+            {
+                Position::after_seek(Some(last))
+            }
+            position => position,
+        }
+    }
+
+    /// Poisons the run following `start` after a failed call.
+    fn poison(&mut self, start: Option<B256>) {
+        if let Some(start) = start {
+            self.cache.accounts.with_ordered(&start, |accounts| accounts.remove_run(&start));
+        }
+        self.position = Position::Unknown;
+        self.inner_positioned = false;
+    }
+}
+
+impl<C> Drop for CachedHashedAccountCursor<C> {
+    fn drop(&mut self) {
+        self.cache.account_stats.add(self.stats);
+    }
+}
+
+impl<C: HashedAccountCursor> HashedAccountCursor for CachedHashedAccountCursor<C> {
+    fn seek(&mut self, key: B256) -> Result<Option<(B256, Account)>, DatabaseError> {
+        let (entry, position) = match self.cached_seek(key) {
+            Some(cached) => {
+                self.stats.hit();
+                self.inner_positioned = false;
+                cached
+            }
+            None => {
+                self.stats.miss();
+                let entry = self.inner.seek(key).map_err(|err| {
+                    self.poison(None);
+                    err
+                })?;
+                self.inner_positioned = true;
+                self.cache.accounts.with_ordered(&key, |accounts| accounts.record_seek(key, entry));
+                self.stats.insert();
+                (entry, Position::after_seek(entry.map(|(key, _)| key)))
+            }
+        };
+        self.position = position;
+        Ok(entry)
+    }
+
+    fn next(&mut self) -> Result<Option<(B256, Account)>, DatabaseError> {
+        let (start, index, last) = match self.position {
+            Position::Unknown => return self.inner.next(),
+            Position::Exhausted => return Ok(None),
+            Position::InRun { start, index, last } => (start, index, last),
+        };
+
+        let cached =
+            self.cache.accounts.with_ordered(&start, |accounts| accounts.next_entry(&start, index));
+        let entry = match cached {
+            Some(entry) => {
+                self.stats.hit();
+                self.inner_positioned = false;
+                entry
+            }
+            None => {
+                self.stats.miss();
+                if !self.inner_positioned {
+                    self.inner.seek(last).map_err(|err| {
+                        self.poison(Some(start));
+                        err
+                    })?;
+                }
+                let entry = self.inner.next().map_err(|err| {
+                    self.poison(Some(start));
+                    err
+                })?;
+                self.inner_positioned = true;
+                if self
+                    .cache
+                    .accounts
+                    .with_ordered(&start, |accounts| accounts.record_next(start, index, entry))
+                {
+                    self.stats.insert();
+                }
+                entry
+            }
+        };
+        self.position =
+            self.split_run(Position::after_next(start, index, entry.map(|(key, _)| key)));
+        Ok(entry)
+    }
+}
+
+/// A hashed storage cursor reading through a [HashedCursorCache].
+#[derive(Debug)]
+pub struct CachedHashedStorageCursor<C> {
+    inner: C,
+    cache: Arc<HashedCursorCache>,
+    /// The hashed address of the last seek.
+    hashed_address: Option<B256>,
+    position: Position,
+    /// Whether the underlying cursor is at the position of this cursor.
+    inner_positioned: bool,
+    stats: CacheStats,
+}
+
+impl<C> CachedHashedStorageCursor<C> {
+    /// Poisons the run following `start` in the storage of `hashed_address` after a failed call.
+    fn poison(&mut self, hashed_address: B256, start: Option<B256>) {
+        if let Some(start) = start {
+            self.cache.storages.with(&hashed_address, |storages| {
+                storages.get_mut(&hashed_address, |storage| storage.slots.remove_run(&start))
+            });
+        }
+        self.position = Position::Unknown;
+        self.inner_positioned = false;
+    }
+}
+
+impl<C> Drop for CachedHashedStorageCursor<C> {
+    fn drop(&mut self) {
+        self.cache.storage_stats.add(self.stats);
+    }
+}
+
+impl<C: HashedStorageCursor> HashedStorageCursor for CachedHashedStorageCursor<C> {
+    fn is_storage_empty(&mut self, key: B256) -> Result<bool, DatabaseError> {
+        if let Some(is_empty) = self
+            .cache
+            .storages
+            .with(&key, |storages| storages.get_mut(&key, |storage| storage.is_empty))
+            .flatten()
+        {
+            self.stats.hit();
+            return Ok(is_empty)
+        }
+
+        self.stats.miss();
+        // the underlying cursor may be moved
+        self.inner_positioned = false;
+        let is_empty = self.inner.is_storage_empty(key)?;
+        self.cache.storages.with(&key, |storages| {
+            storages.update(key, |storage| storage.is_empty = Some(is_empty))
+        });
+        self.stats.insert();
+        Ok(is_empty)
+    }
+
+    fn seek(&mut self, key: B256, subkey: B256) -> Result<Option<StorageEntry>, DatabaseError> {
+        self.hashed_address = Some(key);
+        let cached = self
+            .cache
+            .storages
+            .with(&key, |storages| {
+                storages.get_mut(&key, |storage| match storage.slots.seek(&subkey) {
+                    Some(entry) => Some((entry, Position::after_seek(entry.map(|(slot, _)| slot)))),
+                    None => storage.slots.seek_in_run(&subkey),
+                })
+            })
+            .flatten();
+        let (entry, position) = match cached {
+            Some(cached) => {
+                self.stats.hit();
+                self.inner_positioned = false;
+                cached
+            }
+            None => {
+                self.stats.miss();
+                let entry = self
+                    .inner
+                    .seek(key, subkey)
+                    .map_err(|err| {
+                        self.poison(key, None);
+                        err
+                    })?
+                    .map(|entry| (entry.key, entry.value));
+                self.inner_positioned = true;
+                self.cache.storages.with(&key, |storages| {
+                    storages.update(key, |storage| storage.slots.record_seek(subkey, entry))
+                });
+                self.stats.insert();
+                (entry, Position::after_seek(entry.map(|(slot, _)| slot)))
+            }
+        };
+        self.position = position;
+        Ok(entry.map(|(key, value)| StorageEntry { key, value }))
+    }
+
+    fn next(&mut self) -> Result<Option<StorageEntry>, DatabaseError> {
+        let (hashed_address, start, index, last) = match (self.hashed_address, self.position) {
+            (_, Position::Exhausted) => return Ok(None),
+            (Some(hashed_address), Position::InRun { start, index, last }) => {
+                (hashed_address, start, index, last)
+            }
+            _ => return self.inner.next(),
+        };
+
+        let cached = self
+            .cache
+            .storages
+            .with(&hashed_address, |storages| {
+                storages.get_mut(&hashed_address, |storage| storage.slots.next_entry(&start, index))
+            })
+            .flatten();
+        let entry = match cached {
+            Some(entry) => {
+                self.stats.hit();
+                self.inner_positioned = false;
+                entry
+            }
+            None => {
+                self.stats.miss();
+                if !self.inner_positioned {
+                    self.inner.seek(hashed_address, last).map_err(|err| {
+                        self.poison(hashed_address, Some(start));
+                        err
+                    })?;
+                }
+                let entry = self
+                    .inner
+                    .next()
+                    .map_err(|err| {
+                        self.poison(hashed_address, Some(start));
+                        err
+                    })?
+                    .map(|entry| (entry.key, entry.value));
+                self.inner_positioned = true;
+                let recorded = self.cache.storages.with(&hashed_address, |storages| {
+                    storages.get_mut(&hashed_address, |storage| {
+                        storage.slots.record_next(start, index, entry)
+                    })
+                });
+                if recorded == Some(true) {
+                    self.stats.insert();
+                }
+                entry
+            }
+        };
+        self.position = Position::after_next(start, index, entry.map(|(slot, _)| slot));
+        Ok(entry.map(|(key, value)| StorageEntry { key, value }))
+    }
+}
+
+/// Cached results of account and storage trie cursor operations.
+#[derive(Debug, Default)]
+pub struct TrieCursorsCaches {
+    account_trie: Sharded<TrieCursorCache>,
+    /// Storage tries by hashed address.
+    storage_tries: Sharded<BoundedMap<B256, TrieCursorCache>>,
+    account_trie_stats: SharedCacheStats,
+    storage_tries_stats: SharedCacheStats,
+}
+
+impl TrieCursorsCaches {
+    /// Creates empty caches with the given eviction policy of the account trie and, separately,
+    /// of the storage tries, which are evicted per account.
+    pub fn new(policy: EvictionPolicy) -> Self {
+        Self {
+            account_trie: Sharded::new(policy, TrieCursorCache::new),
+            storage_tries: Sharded::new(policy, BoundedMap::new),
+            account_trie_stats: SharedCacheStats::default(),
+            storage_tries_stats: SharedCacheStats::default(),
+        }
+    }
+
+    /// Returns the number of cached entries.
+    pub fn size(&self) -> usize {
+        self.account_trie.weight() + self.storage_tries.weight()
+    }
+}
+
+type TrieEntry = Option<(Vec<u8>, BranchNodeCompact)>;
+
+/// The cached seeks at a path.
+#[derive(Debug, Default)]
+struct CachedPath {
+    /// The node at or after the path.
+    seek: Option<TrieEntry>,
+    /// The node at the path.
+    exact_seek: Option<TrieEntry>,
+}
+
+impl Weighted for CachedPath {
+    fn weight(&self) -> usize {
+        self.seek.is_some() as usize + self.exact_seek.is_some() as usize
+    }
+}
+
+/// Cached seeks of a trie cursor.
+#[derive(Debug, Default)]
+struct TrieCursorCache {
+    paths: BoundedMap<Nibbles, CachedPath>,
+}
+
+impl TrieCursorCache {
+    fn new(policy: EvictionPolicy) -> Self {
+        Self { paths: BoundedMap::new(policy) }
+    }
+
+    /// Returns the cached node of a seek at the path, or `None` if it is not cached.
+    fn seek(&mut self, path: &Nibbles, exact: bool) -> Option<TrieEntry> {
+        self.paths
+            .get_mut(
+                path,
+                |cached| if exact { cached.exact_seek.clone() } else { cached.seek.clone() },
+            )
+            .flatten()
+    }
+
+    /// Records the node of a seek at the path.
+    fn record_seek(&mut self, path: Nibbles, exact: bool, entry: TrieEntry) {
+        self.paths.update(path, |cached| {
+            let seek = if exact { &mut cached.exact_seek } else { &mut cached.seek };
+            *seek = Some(entry);
+        });
+    }
+
+    fn size(&self) -> usize {
+        self.paths.weight()
+    }
+}
+
+impl Weighted for TrieCursorCache {
+    fn weight(&self) -> usize {
+        self.size()
+    }
+}
+
+/// A [TrieCursorFactory] reading through [TrieCursorsCaches].
+#[derive(Debug, Clone)]
+pub struct CachedTrieCursorFactory<T> {
+    inner: T,
+    cache: Arc<TrieCursorsCaches>,
+}
+
+impl<T> CachedTrieCursorFactory<T> {
+    /// Create a new factory wrapping the given one. The cache must have been populated from the
+    /// same state the wrapped factory reads.
+    pub fn new(inner: T, cache: Arc<TrieCursorsCaches>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+impl<T: TrieCursorFactory> TrieCursorFactory for CachedTrieCursorFactory<T> {
+    fn account_trie_cursor(
+        &self,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibbles> + '_>, DatabaseError> {
+        Ok(Box::new(CachedTrieCursor {
+            inner: self.inner.account_trie_cursor()?,
+            cache: &self.cache,
+            hashed_address: None,
+            current: None,
+            stats: CacheStats::default(),
+        }))
+    }
+
+    fn storage_tries_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError> {
+        Ok(Box::new(CachedTrieCursor {
+            inner: self.inner.storage_tries_cursor(hashed_address)?,
+            cache: &self.cache,
+            hashed_address: Some(hashed_address),
+            current: None,
+            stats: CacheStats::default(),
+        }))
+    }
+}
+
+/// An account or storage trie cursor reading through [TrieCursorsCaches].
+struct CachedTrieCursor<'a, K> {
+    inner: Box<dyn TrieCursor<Key = K> + 'a>,
+    cache: &'a TrieCursorsCaches,
+    /// The hashed address of the storage trie, `None` for the account trie.
+    hashed_address: Option<B256>,
+    /// The path of the last returned node.
+    current: Option<Nibbles>,
+    stats: CacheStats,
+}
+
+impl<'a, K: TrieCursorKey> CachedTrieCursor<'a, K> {
+    /// Calls `f` with the cache of the trie, locking the shard of the path in the account trie.
+    fn with_cache<R>(&self, path: &Nibbles, f: impl FnOnce(&mut TrieCursorCache) -> R) -> R {
+        match self.hashed_address {
+            Some(hashed_address) => self
+                .cache
+                .storage_tries
+                .with(&hashed_address, |storage_tries| storage_tries.update(hashed_address, f)),
+            None => self.cache.account_trie.with(path, f),
+        }
+    }
+
+    fn lookup(
+        &mut self,
+        key: K,
+        exact: bool,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let path = key.nibbles().clone();
+        let cached = self.with_cache(&path, |cache| cache.seek(&path, exact));
+        let entry = match cached {
+            Some(entry) => {
+                self.stats.hit();
+                entry
+            }
+            None => {
+                self.stats.miss();
+                self.current = None;
+                let entry =
+                    if exact { self.inner.seek_exact(key)? } else { self.inner.seek(key)? };
+                self.with_cache(&path, |cache| {
+                    cache.record_seek(path.clone(), exact, entry.clone())
+                });
+                self.stats.insert();
+                entry
+            }
+        };
+        self.current = entry.as_ref().map(|(path, _)| Nibbles::from_nibbles_unchecked(path));
+        Ok(entry)
+    }
+}
+
+impl<'a, K> Drop for CachedTrieCursor<'a, K> {
+    fn drop(&mut self) {
+        let stats = match self.hashed_address {
+            Some(_) => &self.cache.storage_tries_stats,
+            None => &self.cache.account_trie_stats,
+        };
+        stats.add(self.stats);
+    }
+}
+
+impl<'a, K: TrieCursorKey> TrieCursor for CachedTrieCursor<'a, K> {
+    type Key = K;
+
+    fn seek_exact(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        self.lookup(key, true)
+    }
+
+    fn seek(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        self.lookup(key, false)
+    }
+
+    fn current(&mut self) -> Result<Option<TrieKey>, DatabaseError> {
+        Ok(self.current.clone().map(|path| match self.hashed_address {
+            Some(hashed_address) => TrieKey::StorageNode(hashed_address, StoredNibblesSubKey(path)),
+            None => TrieKey::AccountNode(StoredNibbles(path)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{
+            state_root, state_root_prehashed, CursorCall, TestCursorFactory, INJECTED_FAILURE_CODE,
+        },
+        updates::TrieOp,
+        HashedPostState, HashedStorage, StateRoot,
+    };
+    use reth_primitives::{keccak256, Address};
+    use std::collections::BTreeMap;
+
+    type HashedState = BTreeMap<B256, (Account, BTreeMap<B256, U256>)>;
+
+    fn storage(i: u8) -> Vec<(B256, U256)> {
+        (1..=i).map(|slot| (B256::with_last_byte(slot), U256::from(slot))).collect()
+    }
+
+    fn account(i: u8) -> Account {
+        Account { nonce: i as u64, ..Default::default() }
+    }
+
+    fn factory() -> TestCursorFactory {
+        let mut builder = TestCursorFactory::builder();
+        for i in 1..=10u8 {
+            let hashed_address = keccak256(Address::with_last_byte(i));
+            builder = builder.hashed_account(hashed_address, account(i)).hashed_storage(
+                hashed_address,
+                storage(i).into_iter().map(|(slot, value)| (keccak256(slot), value)),
+            );
+        }
+        builder.build()
+    }
+
+    fn cached_root(
+        factory: &TestCursorFactory,
+        cache: &CursorCache,
+    ) -> Result<B256, crate::StateRootError> {
+        StateRoot::new(
+            cache.trie_cursor_factory(factory.clone()),
+            cache.hashed_cursor_factory(factory),
+        )
+        .root()
+    }
+
+    fn expected_root() -> B256 {
+        state_root((1..=10u8).map(|i| (Address::with_last_byte(i), (account(i), storage(i)))))
+    }
+
+    /// Asserts that the cursor operations since the calls of the factory were reset were all
+    /// served from a cache.
+    fn assert_served_from_cache(factory: &TestCursorFactory) {
+        for call in [
+            CursorCall::HashedAccountSeek,
+            CursorCall::HashedAccountNext,
+            CursorCall::IsStorageEmpty,
+            CursorCall::HashedStorageSeek,
+            CursorCall::HashedStorageNext,
+            CursorCall::AccountTrieSeek,
+            CursorCall::StorageTrieSeek,
+        ] {
+            factory.assert_calls(call, 0);
+        }
+    }
+
+    #[test]
+    fn serves_repeated_computations_from_cache() {
+        let factory = factory();
+        let cache = CursorCache::default();
+
+        assert_eq!(cached_root(&factory, &cache).unwrap(), expected_root());
+        assert!(cache.size() > 0);
+
+        factory.reset_calls();
+        assert_eq!(cached_root(&factory, &cache).unwrap(), expected_root());
+        assert_served_from_cache(&factory);
+    }
+
+    #[test]
+    fn counts_operations_of_dropped_cursors() {
+        let factory = factory();
+        let cache = CursorCache::default();
+
+        cached_root(&factory, &cache).unwrap();
+        let first = cache.stats();
+        assert_eq!(first.accounts.hits, 0);
+        assert_eq!(
+            first.accounts.misses,
+            (factory.calls(CursorCall::HashedAccountSeek) +
+                factory.calls(CursorCall::HashedAccountNext)) as u64
+        );
+        assert_eq!(first.accounts.inserts, first.accounts.misses);
+        assert!(first.storages.misses > 0);
+        assert!(first.account_trie.misses > 0);
+        assert!(first.storage_tries.misses > 0);
+
+        // a repeated computation is served from the cache
+        cached_root(&factory, &cache).unwrap();
+        let second = cache.stats();
+        assert_eq!(second.total().misses, first.total().misses);
+        assert_eq!(second.total().inserts, first.total().inserts);
+        let first_operations = first.total().hits + first.total().misses;
+        assert_eq!(second.total().hits - first.total().hits, first_operations);
+        assert_eq!(CacheStats { hits: 1, misses: 3, inserts: 0 }.hit_rate(), Some(0.25));
+    }
+
+    #[test]
+    fn serves_seeks_within_cached_runs() {
+        let factory = factory();
+        let cache = CursorCache::default();
+        let hashed = cache.hashed_cursor_factory(&factory);
+        let before = |key: B256| B256::from(U256::from_be_bytes(key.0) - U256::from(1));
+
+        // cache the runs over all accounts and over the slots of an account
+        let hashed_address = keccak256(Address::with_last_byte(10));
+        let mut cursor = hashed.hashed_account_cursor().unwrap();
+        let mut accounts = vec![cursor.seek(B256::ZERO).unwrap().unwrap()];
+        while let Some(entry) = cursor.next().unwrap() {
+            accounts.push(entry);
+        }
+        let mut storage_cursor = hashed.hashed_storage_cursor().unwrap();
+        let mut slots = vec![storage_cursor.seek(hashed_address, B256::ZERO).unwrap().unwrap()];
+        while let Some(entry) = storage_cursor.next().unwrap() {
+            slots.push(entry);
+        }
+        drop((cursor, storage_cursor));
+
+        factory.reset_calls();
+        let mut cursor = hashed.hashed_account_cursor().unwrap();
+        // a key before an entry of the run returns the entry and continues the run after it
+        assert_eq!(cursor.seek(before(accounts[4].0)).unwrap(), Some(accounts[4]));
+        assert_eq!(cursor.next().unwrap(), Some(accounts[5]));
+        assert_eq!(cursor.seek(accounts[9].0).unwrap(), Some(accounts[9]));
+        assert_eq!(cursor.next().unwrap(), None);
+        // the run reached the end, so there is no entry after its last one
+        assert_eq!(cursor.seek(B256::repeat_byte(0xff)).unwrap(), None);
+        assert_eq!(cursor.next().unwrap(), None);
+
+        let mut storage_cursor = hashed.hashed_storage_cursor().unwrap();
+        assert_eq!(
+            storage_cursor.seek(hashed_address, before(slots[7].key)).unwrap(),
+            Some(slots[7])
+        );
+        assert_eq!(storage_cursor.next().unwrap(), Some(slots[8]));
+        drop((cursor, storage_cursor));
+
+        for call in [
+            CursorCall::HashedAccountSeek,
+            CursorCall::HashedAccountNext,
+            CursorCall::HashedStorageSeek,
+            CursorCall::HashedStorageNext,
+        ] {
+            factory.assert_calls(call, 0);
+        }
+        assert_eq!(cache.stats().total().hits, 7);
+    }
+
+    #[test]
+    fn failed_next_does_not_terminate_run() {
+        let factory = factory();
+        let cache = CursorCache::default();
+        let hashed_address = keccak256(Address::with_last_byte(10));
+
+        factory.fail_on(CursorCall::HashedStorageNext, 4);
+        let hashed = cache.hashed_cursor_factory(&factory);
+        let mut cursor = hashed.hashed_storage_cursor().unwrap();
+        let first = cursor.seek(hashed_address, B256::ZERO).unwrap().unwrap();
+        for _ in 0..3 {
+            assert!(cursor.next().unwrap().is_some());
+        }
+        let err = cursor.next().unwrap_err();
+        assert_eq!(err, DatabaseError::Read(INJECTED_FAILURE_CODE));
+
+        // the run is poisoned and nothing after the failure was recorded
+        cache.hashed_cursors().storages.with(&hashed_address, |storages| {
+            let slots = &storages.peek(&hashed_address).unwrap().slots.keys;
+            assert!(slots.peek(&first.key).is_none());
+            assert!(slots.peek(&B256::ZERO).unwrap().seek.is_some());
+        });
+
+        // a retry reads the full run from the underlying cursor and caches its end
+        factory.reset_calls();
+        let mut cursor = hashed.hashed_storage_cursor().unwrap();
+        cursor.seek(hashed_address, B256::ZERO).unwrap();
+        let mut slots = 1;
+        while cursor.next().unwrap().is_some() {
+            slots += 1;
+        }
+        assert_eq!(slots, 10);
+        // the seek is cached, the underlying cursor is only repositioned
+        factory.assert_calls(CursorCall::HashedStorageSeek, 1);
+        factory.assert_calls(CursorCall::HashedStorageNext, 10);
+        cache.hashed_cursors().storages.with(&hashed_address, |storages| {
+            let slots = &storages.peek(&hashed_address).unwrap().slots.keys;
+            assert!(slots.peek(&first.key).unwrap().run.as_ref().unwrap().terminated);
+        });
+    }
+
+    #[test]
+    fn retry_after_failure_computes_root() {
+        let factory = factory();
+        let cache = CursorCache::default();
+
+        // the first computation fails after the storage roots of 5 accounts with 15 `next` calls
+        // over their slots, the second one in the storage of the sixth account
+        factory.fail_on(CursorCall::HashedAccountNext, 6);
+        factory.fail_on(CursorCall::HashedStorageNext, 20);
+        assert!(cached_root(&factory, &cache).is_err());
+        assert!(cached_root(&factory, &cache).is_err());
+        assert_eq!(cached_root(&factory, &cache).unwrap(), expected_root());
+
+        factory.reset_calls();
+        assert_eq!(cached_root(&factory, &cache).unwrap(), expected_root());
+        factory.assert_calls(CursorCall::HashedAccountNext, 0);
+        factory.assert_calls(CursorCall::HashedStorageNext, 0);
+    }
+
+    #[test]
+    fn bounded_cache_computes_root() {
+        let factory = factory();
+        for policy in [
+            EvictionPolicy::Lru { capacity: 8 },
+            EvictionPolicy::SegmentedLru { capacity: 8, protected: 4 },
+        ] {
+            let cache = CursorCache::new(CursorCacheConfig {
+                accounts: policy,
+                storages: policy,
+                tries: policy,
+            });
+            for _ in 0..3 {
+                factory.reset_calls();
+                assert_eq!(cached_root(&factory, &cache).unwrap(), expected_root());
+                // the account cache, the storage cache and both trie caches are bounded
+                assert!(cache.size() <= 4 * 8);
+            }
+            // the run over all accounts exceeds the capacity and is read again
+            factory.assert_calls(CursorCall::HashedAccountNext, 10);
+        }
+    }
+
+    #[test]
+    fn concurrent_computations_share_cache() {
+        let factory = factory();
+        let cache = CursorCache::default();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert_eq!(cached_root(&factory, &cache).unwrap(), expected_root()));
+            }
+        });
+        assert!(cache.size() > 0);
+        assert_eq!(cached_root(&factory, &cache).unwrap(), expected_root());
+    }
+
+    #[test]
+    fn restores_saved_cache() {
+        let state = (1..=10u8)
+            .map(|i| {
+                let storage = storage(i).into_iter().map(|(slot, value)| (keccak256(slot), value));
+                (keccak256(Address::with_last_byte(i)), (account(i), storage.collect()))
+            })
+            .collect::<HashedState>();
+        let factory = factory_with_trie(&state);
+        let cache = CursorCache::default();
+        let root = cached_root(&factory, &cache).unwrap();
+        assert_eq!(root, expected_root());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cursor-cache");
+        cache.save_to(&path, root).unwrap();
+        let config = CursorCacheConfig::default();
+        // missing, truncated and stale snapshots are not loaded
+        assert!(CursorCache::load_from(&dir.path().join("missing"), config, root)
+            .unwrap()
+            .is_none());
+        let encoded = std::fs::read(&path).unwrap();
+        assert!(CursorCache::decode(config, root, &encoded[..encoded.len() - 1]).is_none());
+        assert!(CursorCache::load_from(&path, config, B256::ZERO).unwrap().is_none());
+
+        let loaded = CursorCache::load_from(&path, config, root).unwrap().unwrap();
+        assert_eq!(loaded.size(), cache.size());
+        factory.reset_calls();
+        assert_eq!(cached_root(&factory, &loaded).unwrap(), root);
+        assert_served_from_cache(&factory);
+    }
+
+    #[test]
+    fn shares_cache_through_shared_memory() {
+        let state = (1..=10u8)
+            .map(|i| {
+                let storage = storage(i).into_iter().map(|(slot, value)| (keccak256(slot), value));
+                (keccak256(Address::with_last_byte(i)), (account(i), storage.collect()))
+            })
+            .collect::<HashedState>();
+        let factory = factory_with_trie(&state);
+        let cache = CursorCache::default();
+        let root = cached_root(&factory, &cache).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cursor-cache.shm");
+        let config = CursorCacheConfig::default();
+        let encoded_len = cache.encode(root).len();
+        assert!(SharedCursorCacheWriter::create(&path, encoded_len / 2)
+            .unwrap()
+            .publish(&cache, root)
+            .is_err());
+
+        let writer = SharedCursorCacheWriter::create(&path, encoded_len).unwrap();
+        let reader = SharedCursorCacheReader::open(&path).unwrap();
+        // nothing published yet
+        assert!(reader.load(config, root).is_none());
+
+        writer.publish(&cache, root).unwrap();
+        assert!(reader.load(config, B256::ZERO).is_none());
+        let loaded = reader.load(config, root).unwrap();
+        assert_eq!(loaded.size(), cache.size());
+        factory.reset_calls();
+        assert_eq!(cached_root(&factory, &loaded).unwrap(), root);
+        assert_served_from_cache(&factory);
+
+        // readers never load a torn snapshot while the writer keeps publishing
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    writer.publish(&cache, root).unwrap();
+                }
+            });
+            for _ in 0..100 {
+                if let Some(loaded) = reader.load(config, root) {
+                    assert_eq!(loaded.size(), cache.size());
+                }
+            }
+        });
+    }
+
+    /// Returns a factory of the hashed state and of the trie nodes of its root computation.
+    fn factory_with_trie(state: &HashedState) -> TestCursorFactory {
+        let builder = || {
+            state.iter().fold(TestCursorFactory::builder(), |builder, (hashed_address, entry)| {
+                let (account, storage) = entry;
+                builder
+                    .hashed_account(*hashed_address, *account)
+                    .hashed_storage(*hashed_address, storage.clone())
+            })
+        };
+        let hashed = builder().build();
+        let (_, updates) = StateRoot::new(hashed.clone(), &hashed).root_with_updates().unwrap();
+        updates
+            .into_iter()
+            .fold(builder(), |builder, update| match update {
+                (TrieKey::AccountNode(path), TrieOp::Update(node)) => {
+                    builder.account_node(path.0, node)
+                }
+                (TrieKey::StorageNode(hashed_address, path), TrieOp::Update(node)) => {
+                    builder.storage_node(hashed_address, path.0, node)
+                }
+                _ => builder,
+            })
+            .build()
+    }
+
+    /// Returns the hashed state of 10 accounts, the sorted post state of a block changing it and
+    /// the hashed state after the block.
+    fn block_fixture() -> (HashedState, HashedPostState, HashedState) {
+        let hashed_address = |i: u8| keccak256(Address::with_last_byte(i));
+        let hashed_slot = |i: u8| keccak256(B256::with_last_byte(i));
+        let mut state = (1..=10u8)
+            .map(|i| {
+                let storage = storage(i).into_iter().map(|(slot, value)| (keccak256(slot), value));
+                (hashed_address(i), (account(i), storage.collect()))
+            })
+            .collect::<HashedState>();
+        let old = state.clone();
+
+        let mut post_state = HashedPostState::default();
+        // a changed account with updated, cleared and created slots
+        post_state.insert_account(hashed_address(5), Some(account(50)));
+        let mut storage = HashedStorage::new(false);
+        storage.insert_slot(hashed_slot(1), U256::from(100));
+        storage.insert_slot(hashed_slot(2), U256::ZERO);
+        storage.insert_slot(hashed_slot(20), U256::from(20));
+        post_state.insert_hashed_storage(hashed_address(5), storage);
+        // a destroyed account
+        post_state.insert_account(hashed_address(7), None);
+        post_state.insert_hashed_storage(hashed_address(7), HashedStorage::new(true));
+        // a created account
+        post_state.insert_account(hashed_address(11), Some(account(11)));
+        let mut storage = HashedStorage::new(false);
+        storage.insert_slot(hashed_slot(1), U256::from(1));
+        post_state.insert_hashed_storage(hashed_address(11), storage);
+        let post_state = post_state.sorted();
+
+        for (hashed_address, account) in post_state.accounts() {
+            match account {
+                Some(account) => state.entry(hashed_address).or_default().0 = account,
+                None => {
+                    state.remove(&hashed_address);
+                }
+            }
+        }
+        for (hashed_address, changes) in post_state.storages() {
+            let Some((_, storage)) = state.get_mut(hashed_address) else { continue };
+            for (slot, value) in changes.storage_slots() {
+                if value.is_zero() {
+                    storage.remove(&slot);
+                } else {
+                    storage.insert(slot, value);
+                }
+            }
+        }
+        (old, post_state, state)
+    }
+
+    fn hashed_state_root(state: &HashedState) -> B256 {
+        state_root_prehashed(state.iter().map(|(hashed_address, (account, storage))| {
+            (*hashed_address, (*account, storage.clone()))
+        }))
+    }
+
+    #[test]
+    fn applies_post_state() {
+        let (old_state, post_state, state) = block_fixture();
+        let old = factory_with_trie(&old_state);
+        let new = factory_with_trie(&state);
+        let expected = hashed_state_root(&state);
+
+        // the root of the block recomputes the changed paths
+        let block_root = |factory: &TestCursorFactory, cache: &CursorCache| {
+            let (account_prefixes, storage_prefixes) = post_state.construct_prefix_sets();
+            StateRoot::new(
+                cache.trie_cursor_factory(factory.clone()),
+                cache.hashed_cursor_factory(factory),
+            )
+            .with_changed_account_prefixes(account_prefixes)
+            .with_changed_storage_prefixes(storage_prefixes)
+            .with_destroyed_accounts(post_state.destroyed_accounts())
+            .root()
+            .unwrap()
+        };
+        let cache = CursorCache::default();
+        block_root(&old, &cache);
+        cache.apply_post_state(&post_state);
+
+        let fresh = CursorCache::default();
+        assert_eq!(block_root(&new, &fresh), expected);
+        let misses = cache.stats().total().misses;
+        assert_eq!(block_root(&new, &cache), expected);
+        // the results the block did not touch are still cached
+        assert!(cache.stats().total().misses - misses < fresh.stats().total().misses);
+        assert_eq!(cached_root(&new, &cache).unwrap(), expected);
+    }
+
+    #[test]
+    fn overlays_post_state() {
+        let (old_state, post_state, state) = block_fixture();
+        let old = factory_with_trie(&old_state);
+        let block_root = |cache: &CursorCache| {
+            let (account_prefixes, storage_prefixes) = post_state.construct_prefix_sets();
+            StateRoot::new(
+                cache.trie_cursor_factory(old.clone()),
+                cache.overlay_hashed_cursor_factory(&old, &post_state),
+            )
+            .with_changed_account_prefixes(account_prefixes)
+            .with_changed_storage_prefixes(storage_prefixes)
+            .with_destroyed_accounts(post_state.destroyed_accounts())
+            .root()
+            .unwrap()
+        };
+
+        let cache = CursorCache::default();
+        let old_root = cached_root(&old, &cache).unwrap();
+        assert_eq!(block_root(&cache), hashed_state_root(&state));
+
+        // the pending changes were not copied into the cache of the old state
+        old.reset_calls();
+        assert_eq!(cached_root(&old, &cache).unwrap(), old_root);
+        assert_served_from_cache(&old);
+        // a repeated computation on top of the pending changes is served from the cache
+        assert_eq!(block_root(&cache), hashed_state_root(&state));
+        assert_served_from_cache(&old);
+    }
+}
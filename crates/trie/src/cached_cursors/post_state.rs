@@ -0,0 +1,287 @@
+//! Updating cursor caches with the changes of a block.
+
+use super::{
+    CachedRun, CursorCache, HashedCursorCache, RunCache, TrieCursorCache, TrieCursorsCaches,
+};
+use crate::HashedPostState;
+use reth_primitives::{trie::Nibbles, B256};
+
+/// Changes of hashed entries sorted by key, `None` for removed entries.
+type Changes<V> = [(B256, Option<V>)];
+
+impl CursorCache {
+    /// Updates the cache to the state after the post state was written to the database state it
+    /// was populated from.
+    ///
+    /// Cached results of hashed cursors are updated in place where the post state determines them
+    /// and evicted otherwise. Cached trie nodes on the paths of changed keys are evicted, as well
+    /// as cached seeks that may now return a new node. The caches of the storage and storage trie
+    /// of destroyed accounts and wiped storages are evicted.
+    ///
+    /// No cursor of the cache may be in use while it is updated.
+    pub fn apply_post_state(&self, post_state: &HashedPostState) {
+        self.hashed_cursors.apply_post_state(post_state);
+        self.trie_cursors.apply_post_state(post_state);
+    }
+}
+
+impl HashedCursorCache {
+    fn apply_post_state(&self, post_state: &HashedPostState) {
+        // accounts of the post state take precedence over destroyed ones with the same address
+        let mut accounts = post_state.accounts().collect::<Vec<_>>();
+        accounts
+            .sort_unstable_by_key(|(hashed_address, account)| (*hashed_address, account.is_none()));
+        accounts.dedup_by_key(|(hashed_address, _)| *hashed_address);
+        self.accounts.for_each(|cache| cache.apply_changes(&accounts));
+
+        self.storages.for_each(|storages| {
+            storages.retain(|hashed_address, storage| {
+                if post_state.destroyed_accounts.contains(hashed_address) {
+                    return false
+                }
+                let Some(changes) = post_state.storages.get(hashed_address) else { return true };
+                if changes.wiped() {
+                    return false
+                }
+
+                let mut slots = changes
+                    .storage_slots()
+                    .map(|(slot, value)| (slot, (!value.is_zero()).then_some(value)))
+                    .collect::<Vec<_>>();
+                slots.sort_unstable_by_key(|(slot, _)| *slot);
+                let created = slots.iter().any(|(_, value)| value.is_some());
+                let cleared = slots.iter().any(|(_, value)| value.is_none());
+                storage.is_empty = match storage.is_empty {
+                    _ if created => Some(false),
+                    // the storage may have been emptied
+                    Some(false) if cleared => None,
+                    is_empty => is_empty,
+                };
+                storage.slots.apply_changes(&slots);
+                true
+            })
+        });
+    }
+}
+
+impl<V: Copy> RunCache<V> {
+    /// Applies the changes to the cached seeks and runs.
+    fn apply_changes(&mut self, changes: &Changes<V>) {
+        if changes.is_empty() {
+            return
+        }
+        self.keys.retain(|key, cached| {
+            if let Some(entry) = cached.seek {
+                cached.seek = seek_after_changes(*key, entry, changes);
+            }
+            let removed = changes
+                .binary_search_by_key(key, |(changed, _)| *changed)
+                .map_or(false, |index| changes[index].1.is_none());
+            if removed {
+                // no seek returns a removed entry, so the run following it is never used
+                cached.run = None;
+            } else if let Some(run) = &mut cached.run {
+                run.apply_changes(*key, changes);
+            }
+            cached.seek.is_some() || cached.run.is_some()
+        });
+    }
+}
+
+/// Returns the entry at or after `key` after the changes, given the entry before them, or `None`
+/// if it is not determined by the changes.
+fn seek_after_changes<V: Copy>(
+    key: B256,
+    entry: Option<(B256, V)>,
+    changes: &Changes<V>,
+) -> Option<Option<(B256, V)>> {
+    let first = changes.partition_point(|(changed, _)| *changed < key);
+    for (changed, value) in &changes[first..] {
+        match entry {
+            Some((entry_key, _)) if *changed > entry_key => break,
+            Some((entry_key, _)) if *changed == entry_key => {
+                // the entry was updated, or removed and the next one is not known
+                return value.map(|value| Some((entry_key, value)))
+            }
+            _ => {}
+        }
+        // an entry before the previous one was created, removals before it are no-ops
+        if let Some(value) = value {
+            return Some(Some((*changed, *value)))
+        }
+    }
+    Some(entry)
+}
+
+impl<V: Copy> CachedRun<V> {
+    /// Applies the changes to the entries of the run following `start`.
+    fn apply_changes(&mut self, start: B256, changes: &Changes<V>) {
+        let changes = &changes[changes.partition_point(|(changed, _)| *changed <= start)..];
+        // changes after the last entry of a run that did not reach the end are outside of it
+        let end = match self.entries.last() {
+            _ if self.terminated => changes.len(),
+            Some((last, _)) => changes.partition_point(|(changed, _)| changed <= last),
+            None => 0,
+        };
+        if end == 0 {
+            return
+        }
+
+        let mut changes = changes[..end].iter().peekable();
+        let mut entries = Vec::with_capacity(self.entries.len() + end);
+        for (key, value) in self.entries.drain(..) {
+            while let Some((changed, created)) = changes.next_if(|(changed, _)| *changed < key) {
+                entries.extend(created.map(|created| (*changed, created)));
+            }
+            match changes.next_if(|(changed, _)| *changed == key) {
+                Some((_, updated)) => entries.extend(updated.map(|updated| (key, updated))),
+                None => entries.push((key, value)),
+            }
+        }
+        entries.extend(changes.filter_map(|(changed, created)| created.map(|c| (*changed, c))));
+        self.entries = entries;
+    }
+}
+
+impl TrieCursorsCaches {
+    fn apply_post_state(&self, post_state: &HashedPostState) {
+        // the leaves of accounts with changed storage change with their storage root
+        let mut changed_accounts = post_state
+            .accounts()
+            .map(|(hashed_address, _)| hashed_address)
+            .chain(post_state.storages.keys().copied())
+            .map(Nibbles::unpack)
+            .collect::<Vec<_>>();
+        changed_accounts.sort_unstable();
+        changed_accounts.dedup();
+        self.account_trie.for_each(|cache| cache.apply_changes(&changed_accounts));
+
+        self.storage_tries.for_each(|storage_tries| {
+            storage_tries.retain(|hashed_address, cache| {
+                if post_state.destroyed_accounts.contains(hashed_address) {
+                    return false
+                }
+                let Some(changes) = post_state.storages.get(hashed_address) else { return true };
+                if changes.wiped() {
+                    return false
+                }
+
+                let mut changed_slots = changes
+                    .storage_slots()
+                    .map(|(slot, _)| Nibbles::unpack(slot))
+                    .collect::<Vec<_>>();
+                changed_slots.sort_unstable();
+                cache.apply_changes(&changed_slots);
+                true
+            })
+        });
+    }
+}
+
+impl TrieCursorCache {
+    /// Evicts the cached seeks whose result may differ after the keys changed.
+    ///
+    /// Only the nodes on the paths of changed keys are updated, created or removed, so a seek at
+    /// a path is affected if the path is a prefix of a changed key, and a seek at or after a path
+    /// is affected if the path of an affected node lies between the path and the returned node.
+    fn apply_changes(&mut self, changed: &[Nibbles]) {
+        if changed.is_empty() {
+            return
+        }
+        self.paths.retain(|path, cached| {
+            // the first changed key at or after the path
+            let next = changed.get(changed.partition_point(|key| key < path));
+            if next.map_or(false, |key| key.starts_with(path)) {
+                cached.exact_seek = None;
+            }
+            if let Some(entry) = &cached.seek {
+                let node = entry.as_ref().map(|(node, _)| node.as_slice());
+                if next.map_or(false, |key| seek_affected(path, node, key)) {
+                    cached.seek = None;
+                }
+            }
+            cached.exact_seek.is_some() || cached.seek.is_some()
+        });
+    }
+}
+
+/// Returns whether a seek at `path` that returned the node at `node` is affected by a change of
+/// `key`, the first changed key at or after `path`.
+///
+/// The first affected node at or after `path` is `path` itself if it is a prefix of `key`, or the
+/// prefix of `key` one nibble past their common prefix otherwise. Later changed keys only affect
+/// later nodes.
+fn seek_affected(path: &Nibbles, node: Option<&[u8]>, key: &Nibbles) -> bool {
+    let Some(node) = node else { return true };
+    let common = path.iter().zip(key.iter()).take_while(|(a, b)| a == b).count();
+    if common == path.len() {
+        return true
+    }
+    &key[..=common] <= node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> B256 {
+        B256::with_last_byte(byte)
+    }
+
+    fn run(entries: &[u8], terminated: bool) -> CachedRun<u8> {
+        CachedRun { entries: entries.iter().map(|byte| (key(*byte), *byte)).collect(), terminated }
+    }
+
+    #[test]
+    fn updates_seeks() {
+        let changes = [(key(3), None), (key(5), Some(50)), (key(8), None)];
+        // an entry is created before the previous one, the removal before it is a no-op
+        assert_eq!(
+            seek_after_changes(key(2), Some((key(6), 6)), &changes),
+            Some(Some((key(5), 50)))
+        );
+        // the entry is updated
+        assert_eq!(
+            seek_after_changes(key(4), Some((key(5), 5)), &changes),
+            Some(Some((key(5), 50)))
+        );
+        // the entry is removed
+        assert_eq!(seek_after_changes(key(6), Some((key(8), 8)), &changes), None);
+        // there was no entry after the key and still is none
+        assert_eq!(seek_after_changes(key(6), None, &changes), Some(None));
+        // no change between the key and the entry
+        assert_eq!(
+            seek_after_changes(key(9), Some((key(10), 10)), &changes),
+            Some(Some((key(10), 10)))
+        );
+    }
+
+    #[test]
+    fn updates_runs() {
+        let changes = [(key(1), Some(10)), (key(3), None), (key(4), Some(40)), (key(9), Some(90))];
+
+        let mut terminated = run(&[2, 3, 5], true);
+        terminated.apply_changes(key(1), &changes);
+        assert_eq!(terminated.entries, vec![(key(2), 2), (key(4), 40), (key(5), 5), (key(9), 90)]);
+
+        // changes after the last entry of an unterminated run are not part of it
+        let mut unterminated = run(&[2, 3, 5], false);
+        unterminated.apply_changes(key(1), &changes);
+        assert_eq!(unterminated.entries, vec![(key(2), 2), (key(4), 40), (key(5), 5)]);
+        assert!(!unterminated.terminated);
+    }
+
+    #[test]
+    fn detects_affected_trie_seeks() {
+        let path = |nibbles: &[u8]| Nibbles::from_nibbles_unchecked(nibbles);
+        let key = path(&[0x1, 0x2, 0x3]);
+        // the path is a prefix of the key
+        assert!(seek_affected(&path(&[0x1]), Some(&[0x5][..]), &key));
+        // the branch at 0x12 lies between the path and the node
+        assert!(seek_affected(&path(&[0x1, 0x1, 0xf]), Some(&[0x1, 0x3][..]), &key));
+        // the returned node is before the affected branch
+        assert!(!seek_affected(&path(&[0x1, 0x1, 0xf]), Some(&[0x1, 0x1, 0xf, 0x1][..]), &key));
+        // there was no node after the path
+        assert!(seek_affected(&path(&[0x1, 0x1]), None, &key));
+    }
+}
@@ -0,0 +1,194 @@
+//! Caches split into independently locked shards.
+
+use super::{eviction::Weighted, EvictionPolicy};
+use parking_lot::Mutex;
+use reth_primitives::B256;
+use std::{
+    hash::{BuildHasher, Hash},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The number of shards of a cache.
+const SHARDS: usize = 16;
+
+/// The minimum capacity of a shard of a bounded cache. Caches with a smaller capacity have fewer
+/// shards, down to a single one.
+const MIN_SHARD_CAPACITY: usize = 1024;
+
+/// A cache split into shards by the hash of the keys it is accessed with, or by the order of
+/// `B256` keys.
+///
+/// The shards are locked independently, so cursors accessing different keys rarely wait for each
+/// other. Each shard of a bounded cache is bounded by an equal share of the capacity, so the
+/// least recently used entries are evicted per shard. The total weight of the shards is tracked
+/// outside of the locks and can be read without locking.
+pub(super) struct Sharded<T> {
+    shards: Box<[Mutex<T>]>,
+    hasher: ahash::RandomState,
+    /// The total weight of the shards, updated whenever a shard is unlocked.
+    weight: AtomicUsize,
+}
+
+impl<T> std::fmt::Debug for Sharded<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sharded")
+            .field("shards", &self.shards.len())
+            .field("weight", &self.weight())
+            .finish()
+    }
+}
+
+impl<T: Weighted> Sharded<T> {
+    /// Creates a cache whose shards share the capacity of the policy, creating each shard with
+    /// its share of the policy.
+    pub(super) fn new(policy: EvictionPolicy, shard: impl Fn(EvictionPolicy) -> T) -> Self {
+        let shards = shard_count(policy);
+        let shard_policy = shard_policy(policy, shards);
+        Self {
+            shards: (0..shards).map(|_| Mutex::new(shard(shard_policy))).collect(),
+            hasher: ahash::RandomState::new(),
+            weight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the total weight of the shards without locking them.
+    pub(super) fn weight(&self) -> usize {
+        self.weight.load(Ordering::Relaxed)
+    }
+
+    /// Calls `f` with the shard of the key.
+    pub(super) fn with<K: Hash + ?Sized, R>(&self, key: &K, f: impl FnOnce(&mut T) -> R) -> R {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        self.with_shard(&self.shards[index], f)
+    }
+
+    /// Calls `f` with the shard of the key by its leading byte, so that each shard holds a range of
+    /// consecutive keys.
+    ///
+    /// A cache must either be accessed by hash or by order.
+    pub(super) fn with_ordered<R>(&self, key: &B256, f: impl FnOnce(&mut T) -> R) -> R {
+        self.with_shard(&self.shards[self.ordered_index(key)], f)
+    }
+
+    /// Calls `f` with the shard holding the keys right before the keys of the shard of the key by
+    /// order, returns `None` if the key is in the first shard.
+    pub(super) fn with_preceding<R>(&self, key: &B256, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let index = self.ordered_index(key).checked_sub(1)?;
+        Some(self.with_shard(&self.shards[index], f))
+    }
+
+    /// Returns whether the keys are in the same shard by order.
+    pub(super) fn same_ordered_shard(&self, a: &B256, b: &B256) -> bool {
+        self.ordered_index(a) == self.ordered_index(b)
+    }
+
+    /// Calls `f` with every shard in turn.
+    pub(super) fn for_each(&self, mut f: impl FnMut(&mut T)) {
+        for shard in self.shards.iter() {
+            self.with_shard(shard, &mut f);
+        }
+    }
+
+    fn ordered_index(&self, key: &B256) -> usize {
+        key[0] as usize * self.shards.len() / 256
+    }
+
+    fn with_shard<R>(&self, shard: &Mutex<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut shard = shard.lock();
+        let before = shard.weight();
+        let result = f(&mut shard);
+        let after = shard.weight();
+        // updated while the shard is locked, so the updates of a shard are never reordered
+        if after > before {
+            self.weight.fetch_add(after - before, Ordering::Relaxed);
+        } else if before > after {
+            self.weight.fetch_sub(before - after, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+impl<T: Weighted + Default> Default for Sharded<T> {
+    fn default() -> Self {
+        Self::new(EvictionPolicy::Unbounded, |_| T::default())
+    }
+}
+
+/// Returns the number of shards of a cache with the policy.
+fn shard_count(policy: EvictionPolicy) -> usize {
+    match policy {
+        EvictionPolicy::Unbounded => SHARDS,
+        EvictionPolicy::Lru { capacity } | EvictionPolicy::SegmentedLru { capacity, .. } => {
+            (capacity / MIN_SHARD_CAPACITY).clamp(1, SHARDS)
+        }
+    }
+}
+
+/// Returns the policy of each of the shards of a cache with the policy.
+fn shard_policy(policy: EvictionPolicy, shards: usize) -> EvictionPolicy {
+    match policy {
+        EvictionPolicy::Unbounded => EvictionPolicy::Unbounded,
+        EvictionPolicy::Lru { capacity } => EvictionPolicy::Lru { capacity: capacity / shards },
+        EvictionPolicy::SegmentedLru { capacity, protected } => EvictionPolicy::SegmentedLru {
+            capacity: capacity / shards,
+            protected: protected / shards,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cached_cursors::eviction::BoundedMap;
+
+    #[test]
+    fn splits_capacity_between_shards() {
+        assert_eq!(shard_count(EvictionPolicy::Lru { capacity: 8 }), 1);
+        let policy = EvictionPolicy::SegmentedLru { capacity: 64 * 1024, protected: 32 * 1024 };
+        assert_eq!(shard_count(policy), SHARDS);
+        assert_eq!(
+            shard_policy(policy, SHARDS),
+            EvictionPolicy::SegmentedLru { capacity: 4 * 1024, protected: 2 * 1024 }
+        );
+    }
+
+    #[test]
+    fn orders_keys_between_shards() {
+        let sharded = Sharded::<BoundedMap<B256, Vec<u8>>>::default();
+        for byte in [0x00, 0x0f, 0x10, 0x7f, 0x80, 0xff] {
+            let mut key = B256::ZERO;
+            key[0] = byte;
+            sharded.with_ordered(&key, |map| map.update(key, |value| value.push(byte)));
+        }
+        let mut shards = Vec::new();
+        sharded.for_each(|map| {
+            let mut bytes = map.entries().into_iter().map(|(key, _)| key[0]).collect::<Vec<_>>();
+            bytes.sort_unstable();
+            shards.push(bytes);
+        });
+        assert_eq!(shards[0], vec![0x00, 0x0f]);
+        assert_eq!(shards[1], vec![0x10]);
+        assert_eq!(shards[7], vec![0x7f]);
+        assert_eq!(shards[8], vec![0x80]);
+        assert_eq!(shards[15], vec![0xff]);
+
+        assert!(sharded.same_ordered_shard(&B256::ZERO, &B256::repeat_byte(0x0f)));
+        assert!(!sharded.same_ordered_shard(&B256::repeat_byte(0x0f), &B256::repeat_byte(0x10)));
+        assert_eq!(sharded.with_preceding(&B256::ZERO, |map| map.len()), None);
+        assert_eq!(sharded.with_preceding(&B256::repeat_byte(0x10), |map| map.len()), Some(2));
+    }
+
+    #[test]
+    fn tracks_weight_of_shards() {
+        let sharded = Sharded::<BoundedMap<u8, Vec<u8>>>::default();
+        for key in 0..100u8 {
+            sharded.with(&key, |map| map.update(key, |value| *value = vec![key; 2]));
+        }
+        assert_eq!(sharded.weight(), 200);
+
+        sharded.for_each(|map| map.retain(|key, _| key % 2 == 0));
+        assert_eq!(sharded.weight(), 100);
+        sharded.with(&4, |map| map.remove(&4));
+        assert_eq!(sharded.weight(), 98);
+    }
+}
@@ -0,0 +1,174 @@
+//! Sharing cursor caches between processes through shared memory.
+
+use super::{CursorCache, CursorCacheConfig};
+use memmap2::MmapRaw;
+use reth_primitives::B256;
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    path::Path,
+    sync::atomic::{fence, AtomicU64, Ordering},
+    thread,
+};
+
+/// Identifies a file as a shared cursor cache region.
+const MAGIC: u64 = u64::from_le_bytes(*b"rethccv1");
+
+/// The number of words in front of the snapshot: the magic, the sequence number and the length of
+/// the snapshot.
+const HEADER_WORDS: usize = 3;
+
+/// How often a reader retries to copy the snapshot while it is being published.
+const MAX_READ_ATTEMPTS: usize = 16;
+
+/// A fixed-size region of a memory mapped file holding one encoded cursor cache snapshot, see
+/// [CursorCache::encode].
+///
+/// The snapshot is guarded by a sequence lock: the writer makes the sequence number odd while it
+/// publishes a snapshot, and readers retry if the sequence number was odd or changed while they
+/// copied the snapshot. All accesses are atomic, so readers never block the writer and never see
+/// a torn snapshot.
+#[derive(Debug)]
+struct Region {
+    map: MmapRaw,
+}
+
+impl Region {
+    fn map(file: &File) -> io::Result<Self> {
+        let map = MmapRaw::map_raw(file)?;
+        if map.len() < HEADER_WORDS * 8 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "shared cursor cache too small"))
+        }
+        Ok(Self { map })
+    }
+
+    fn words(&self) -> &[AtomicU64] {
+        // SAFETY: the mapping is page aligned and lives as long as `self`, and any bit pattern is
+        // a valid `AtomicU64`
+        unsafe {
+            std::slice::from_raw_parts(self.map.as_ptr() as *const AtomicU64, self.map.len() / 8)
+        }
+    }
+
+    fn magic(&self) -> &AtomicU64 {
+        &self.words()[0]
+    }
+
+    fn sequence(&self) -> &AtomicU64 {
+        &self.words()[1]
+    }
+
+    fn len(&self) -> &AtomicU64 {
+        &self.words()[2]
+    }
+
+    fn data(&self) -> &[AtomicU64] {
+        &self.words()[HEADER_WORDS..]
+    }
+
+    /// Returns the number of bytes a snapshot can have.
+    fn capacity(&self) -> usize {
+        self.data().len() * 8
+    }
+}
+
+/// Publishes cursor cache snapshots to a shared memory region, e.g. from a process building
+/// payloads, so that [SharedCursorCacheReader]s in other processes can load them.
+///
+/// There must be only one writer per region.
+#[derive(Debug)]
+pub struct SharedCursorCacheWriter {
+    region: Region,
+}
+
+impl SharedCursorCacheWriter {
+    /// Creates the region at the given path, e.g. in the datadir, with room for snapshots of up
+    /// to `capacity` bytes.
+    ///
+    /// A previous region at the path is replaced, not overwritten: readers that opened it keep
+    /// reading its last snapshot until they open the path again.
+    pub fn create(path: &Path, capacity: usize) -> io::Result<Self> {
+        let tmp = path.with_extension("tmp");
+        let file =
+            OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&tmp)?;
+        file.set_len(((HEADER_WORDS + capacity.div_ceil(8)) * 8) as u64)?;
+        let region = Region::map(&file)?;
+        region.magic().store(MAGIC, Ordering::Release);
+        fs::rename(tmp, path)?;
+        Ok(Self { region })
+    }
+
+    /// Publishes the cache together with the state root of the database state it was populated
+    /// from, replacing the previous snapshot.
+    ///
+    /// Returns an error if the encoded cache doesn't fit in the region.
+    pub fn publish(&self, cache: &CursorCache, state_root: B256) -> io::Result<()> {
+        let buf = cache.encode(state_root);
+        if buf.len() > self.region.capacity() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "cursor cache snapshot of {} bytes exceeds the shared region of {} bytes",
+                    buf.len(),
+                    self.region.capacity()
+                ),
+            ))
+        }
+
+        let sequence = self.region.sequence().load(Ordering::Relaxed);
+        self.region.sequence().store(sequence + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        for (word, chunk) in self.region.data().iter().zip(buf.chunks(8)) {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            word.store(u64::from_le_bytes(bytes), Ordering::Relaxed);
+        }
+        self.region.len().store(buf.len() as u64, Ordering::Relaxed);
+        self.region.sequence().store(sequence + 2, Ordering::Release);
+        Ok(())
+    }
+}
+
+/// Loads the cursor cache snapshots published by a [SharedCursorCacheWriter] in another process.
+#[derive(Debug)]
+pub struct SharedCursorCacheReader {
+    region: Region,
+}
+
+impl SharedCursorCacheReader {
+    /// Opens the region at the given path.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let region = Region::map(&file)?;
+        if region.magic().load(Ordering::Acquire) != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a shared cursor cache"))
+        }
+        Ok(Self { region })
+    }
+
+    /// Loads the published snapshot into a cache with the given eviction policies, if it was
+    /// populated from the state with the given state root.
+    ///
+    /// Returns `None` if nothing was published, the snapshot was published for a different state
+    /// root, or a consistent copy could not be taken because the writer kept publishing.
+    pub fn load(&self, config: CursorCacheConfig, state_root: B256) -> Option<CursorCache> {
+        for _ in 0..MAX_READ_ATTEMPTS {
+            let sequence = self.region.sequence().load(Ordering::Acquire);
+            if sequence % 2 == 0 {
+                let len = (self.region.len().load(Ordering::Relaxed) as usize)
+                    .min(self.region.capacity());
+                let mut buf = Vec::with_capacity(len.div_ceil(8) * 8);
+                for word in &self.region.data()[..len.div_ceil(8)] {
+                    buf.extend_from_slice(&word.load(Ordering::Relaxed).to_le_bytes());
+                }
+                buf.truncate(len);
+                fence(Ordering::Acquire);
+                if self.region.sequence().load(Ordering::Relaxed) == sequence {
+                    return CursorCache::decode(config, state_root, &buf)
+                }
+            }
+            thread::yield_now();
+        }
+        None
+    }
+}
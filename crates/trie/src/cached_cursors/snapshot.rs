@@ -0,0 +1,335 @@
+//! Persisting cursor caches across restarts.
+
+use super::{
+    CachedKey, CachedPath, CachedRun, CursorCache, CursorCacheConfig, RunCache, TrieCursorCache,
+    TrieEntry,
+};
+use crate::journal::{encode_nibbles, Reader};
+use reth_db::table::{Compress, Decompress};
+use reth_primitives::{
+    trie::{Nibbles, StoredBranchNode},
+    Account, B256, U256,
+};
+use std::{fs, io, path::Path};
+
+/// The version of the persisted snapshot format.
+const SNAPSHOT_VERSION: u8 = 1;
+
+impl CursorCache {
+    /// Encodes the cached results together with the state root of the database state the cache
+    /// was populated from.
+    ///
+    /// The entries of each cache are encoded from the least to the most recently used. Statistics
+    /// are not encoded.
+    pub fn encode(&self, state_root: B256) -> Vec<u8> {
+        let mut buf = vec![SNAPSHOT_VERSION];
+        buf.extend_from_slice(state_root.as_slice());
+
+        let hashed_cursors = &self.hashed_cursors;
+        encode_counted(&mut buf, |buf| {
+            let mut count = 0;
+            hashed_cursors.accounts.for_each(|accounts| count += accounts.encode(buf));
+            count
+        });
+        encode_counted(&mut buf, |buf| {
+            let mut count = 0;
+            hashed_cursors.storages.for_each(|storages| {
+                for (hashed_address, storage) in storages.entries() {
+                    buf.extend_from_slice(hashed_address.as_slice());
+                    buf.push(match storage.is_empty {
+                        None => 0,
+                        Some(false) => 1,
+                        Some(true) => 2,
+                    });
+                    encode_counted(buf, |buf| storage.slots.encode(buf));
+                    count += 1;
+                }
+            });
+            count
+        });
+
+        let trie_cursors = &self.trie_cursors;
+        encode_counted(&mut buf, |buf| {
+            let mut count = 0;
+            trie_cursors.account_trie.for_each(|account_trie| count += account_trie.encode(buf));
+            count
+        });
+        encode_counted(&mut buf, |buf| {
+            let mut count = 0;
+            trie_cursors.storage_tries.for_each(|storage_tries| {
+                for (hashed_address, storage_trie) in storage_tries.entries() {
+                    buf.extend_from_slice(hashed_address.as_slice());
+                    encode_counted(buf, |buf| storage_trie.encode(buf));
+                    count += 1;
+                }
+            });
+            count
+        });
+        buf
+    }
+
+    /// Decodes a cache that was encoded with [CursorCache::encode] into a cache with the given
+    /// eviction policies.
+    ///
+    /// Returns `None` if the encoding is invalid or the cache was populated from a state with a
+    /// different state root, whose results may be stale. If the cache holds more entries than the
+    /// policies allow, the least recently used ones are evicted.
+    pub fn decode(config: CursorCacheConfig, state_root: B256, buf: &[u8]) -> Option<Self> {
+        let mut reader = Reader(buf);
+        if reader.u8()? != SNAPSHOT_VERSION || decode_b256(&mut reader)? != state_root {
+            return None
+        }
+
+        let cache = Self::new(config);
+        let hashed_cursors = &cache.hashed_cursors;
+        for _ in 0..reader.u32()? {
+            let (key, cached) = decode_cached_key::<Account>(&mut reader)?;
+            hashed_cursors.accounts.with_ordered(&key, |accounts| accounts.restore(key, cached));
+        }
+        for _ in 0..reader.u32()? {
+            let hashed_address = decode_b256(&mut reader)?;
+            let is_empty = match reader.u8()? {
+                0 => None,
+                1 => Some(false),
+                2 => Some(true),
+                _ => return None,
+            };
+            let slots = (0..reader.u32()?)
+                .map(|_| decode_cached_key::<U256>(&mut reader))
+                .collect::<Option<Vec<_>>>()?;
+            hashed_cursors.storages.with(&hashed_address, |storages| {
+                storages.update(hashed_address, |storage| {
+                    storage.is_empty = is_empty;
+                    for (slot, cached) in slots {
+                        storage.slots.restore(slot, cached);
+                    }
+                })
+            });
+        }
+
+        let trie_cursors = &cache.trie_cursors;
+        for _ in 0..reader.u32()? {
+            let (path, cached) = decode_cached_path(&mut reader)?;
+            trie_cursors
+                .account_trie
+                .with(&path, |account_trie| account_trie.restore(path.clone(), cached));
+        }
+        for _ in 0..reader.u32()? {
+            let hashed_address = decode_b256(&mut reader)?;
+            let paths = (0..reader.u32()?)
+                .map(|_| decode_cached_path(&mut reader))
+                .collect::<Option<Vec<_>>>()?;
+            trie_cursors.storage_tries.with(&hashed_address, |storage_tries| {
+                storage_tries.update(hashed_address, |storage_trie| {
+                    for (path, cached) in paths {
+                        storage_trie.restore(path, cached);
+                    }
+                })
+            });
+        }
+        reader.0.is_empty().then_some(cache)
+    }
+
+    /// Persists the cache to the given path, together with the state root of the database state
+    /// the cache was populated from, e.g. on shutdown.
+    pub fn save_to(&self, path: &Path, state_root: B256) -> io::Result<()> {
+        // write to a temporary file first, so a crash never leaves a truncated snapshot behind
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.encode(state_root))?;
+        fs::rename(tmp, path)
+    }
+
+    /// Loads a cache persisted with [CursorCache::save_to] into a cache with the given eviction
+    /// policies, if it was populated from the state with the given state root.
+    ///
+    /// Returns `None` if the file does not exist, is invalid or was saved for a different state
+    /// root.
+    pub fn load_from(
+        path: &Path,
+        config: CursorCacheConfig,
+        state_root: B256,
+    ) -> io::Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(buf) => Ok(Self::decode(config, state_root, &buf)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<V: SnapshotValue> RunCache<V> {
+    /// Appends the cached seeks and runs to the buffer, returning their number.
+    fn encode(&self, buf: &mut Vec<u8>) -> u32 {
+        let entries = self.keys.entries();
+        for (key, cached) in &entries {
+            buf.extend_from_slice(key.as_slice());
+            encode_cached(&cached.seek, buf, |entry, buf| encode_cached(entry, buf, encode_entry));
+            match &cached.run {
+                None => buf.push(0),
+                Some(run) => {
+                    buf.push(1 + run.terminated as u8);
+                    encode_counted(buf, |buf| {
+                        run.entries.iter().for_each(|entry| encode_entry(entry, buf));
+                        run.entries.len() as u32
+                    });
+                }
+            }
+        }
+        entries.len() as u32
+    }
+}
+
+impl<V> RunCache<V> {
+    /// Restores the cached seek and run of the key.
+    fn restore(&mut self, key: B256, cached: CachedKey<V>) {
+        let has_run = cached.run.is_some();
+        self.keys.update(key, |entry| *entry = cached);
+        if has_run {
+            self.insert_run_start(key);
+        }
+    }
+}
+
+impl TrieCursorCache {
+    /// Appends the cached seeks to the buffer, returning their number.
+    fn encode(&self, buf: &mut Vec<u8>) -> u32 {
+        let entries = self.paths.entries();
+        for (path, cached) in &entries {
+            encode_nibbles(path, buf);
+            encode_cached(&cached.seek, buf, encode_trie_entry);
+            encode_cached(&cached.exact_seek, buf, encode_trie_entry);
+        }
+        entries.len() as u32
+    }
+
+    /// Restores the cached seeks at the path.
+    fn restore(&mut self, path: Nibbles, cached: CachedPath) {
+        self.paths.update(path, |entry| *entry = cached);
+    }
+}
+
+/// A value of the entries of a hashed cursor.
+trait SnapshotValue: Copy {
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    fn decode(reader: &mut Reader<'_>) -> Option<Self>;
+}
+
+impl SnapshotValue for U256 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_be_bytes::<32>());
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Option<Self> {
+        Some(U256::from_be_slice(reader.bytes(32)?))
+    }
+}
+
+impl SnapshotValue for Account {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.nonce.to_le_bytes());
+        SnapshotValue::encode(&self.balance, buf);
+        match self.bytecode_hash {
+            None => buf.push(0),
+            Some(bytecode_hash) => {
+                buf.push(1);
+                buf.extend_from_slice(bytecode_hash.as_slice());
+            }
+        }
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Option<Self> {
+        let nonce = reader.u64()?;
+        let balance = <U256 as SnapshotValue>::decode(reader)?;
+        let bytecode_hash = match reader.u8()? {
+            0 => None,
+            1 => Some(decode_b256(reader)?),
+            _ => return None,
+        };
+        Some(Account { nonce, balance, bytecode_hash })
+    }
+}
+
+/// Reserves the count of the items `f` appends to the buffer and fills it in once they are
+/// appended.
+fn encode_counted(buf: &mut Vec<u8>, f: impl FnOnce(&mut Vec<u8>) -> u32) {
+    let offset = buf.len();
+    buf.extend_from_slice(&[0; 4]);
+    let count = f(buf);
+    buf[offset..offset + 4].copy_from_slice(&count.to_le_bytes());
+}
+
+/// Appends a cached result, which is `None` if the result is not cached.
+fn encode_cached<T>(cached: &Option<T>, buf: &mut Vec<u8>, f: impl FnOnce(&T, &mut Vec<u8>)) {
+    match cached {
+        None => buf.push(0),
+        Some(result) => {
+            buf.push(1);
+            f(result, buf);
+        }
+    }
+}
+
+fn decode_cached<T>(
+    reader: &mut Reader<'_>,
+    f: impl FnOnce(&mut Reader<'_>) -> Option<T>,
+) -> Option<Option<T>> {
+    match reader.u8()? {
+        0 => Some(None),
+        1 => f(reader).map(Some),
+        _ => None,
+    }
+}
+
+fn encode_entry<V: SnapshotValue>((key, value): &(B256, V), buf: &mut Vec<u8>) {
+    buf.extend_from_slice(key.as_slice());
+    value.encode(buf);
+}
+
+fn decode_entry<V: SnapshotValue>(reader: &mut Reader<'_>) -> Option<(B256, V)> {
+    Some((decode_b256(reader)?, V::decode(reader)?))
+}
+
+fn decode_cached_key<V: SnapshotValue>(reader: &mut Reader<'_>) -> Option<(B256, CachedKey<V>)> {
+    let key = decode_b256(reader)?;
+    let seek = decode_cached(reader, |reader| decode_cached(reader, decode_entry))?;
+    let run = match reader.u8()? {
+        0 => None,
+        tag @ (1 | 2) => {
+            let entries =
+                (0..reader.u32()?).map(|_| decode_entry(reader)).collect::<Option<Vec<_>>>()?;
+            Some(CachedRun { entries, terminated: tag == 2 })
+        }
+        _ => return None,
+    };
+    Some((key, CachedKey { seek, run }))
+}
+
+fn encode_trie_entry(entry: &TrieEntry, buf: &mut Vec<u8>) {
+    encode_cached(entry, buf, |(path, node), buf| {
+        encode_nibbles(path, buf);
+        let node = StoredBranchNode(node.clone()).compress();
+        let node = node.as_ref();
+        buf.extend_from_slice(&(node.len() as u32).to_le_bytes());
+        buf.extend_from_slice(node);
+    });
+}
+
+fn decode_trie_entry(reader: &mut Reader<'_>) -> Option<TrieEntry> {
+    decode_cached(reader, |reader| {
+        let path = reader.nibbles()?;
+        let len = reader.u32()? as usize;
+        Some((path, StoredBranchNode::decompress(reader.bytes(len)?).ok()?.0))
+    })
+}
+
+fn decode_cached_path(reader: &mut Reader<'_>) -> Option<(Nibbles, CachedPath)> {
+    let path = Nibbles::from_nibbles_unchecked(reader.nibbles()?);
+    let seek = decode_cached(reader, decode_trie_entry)?;
+    let exact_seek = decode_cached(reader, decode_trie_entry)?;
+    Some((path, CachedPath { seek, exact_seek }))
+}
+
+fn decode_b256(reader: &mut Reader<'_>) -> Option<B256> {
+    Some(B256::from_slice(reader.bytes(32)?))
+}
@@ -0,0 +1,95 @@
+//! Hit, miss and insert statistics of cursor caches.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The operations on a cache of cursor results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// The number of operations served from the cache.
+    pub hits: u64,
+    /// The number of operations passed to the underlying cursor.
+    pub misses: u64,
+    /// The number of results recorded in the cache.
+    pub inserts: u64,
+}
+
+impl CacheStats {
+    /// Returns the share of operations served from the cache, `None` if there were none.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+
+    pub(super) fn hit(&mut self) {
+        self.hits += 1;
+    }
+
+    pub(super) fn miss(&mut self) {
+        self.misses += 1;
+    }
+
+    pub(super) fn insert(&mut self) {
+        self.inserts += 1;
+    }
+}
+
+impl std::ops::Add for CacheStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            hits: self.hits + rhs.hits,
+            misses: self.misses + rhs.misses,
+            inserts: self.inserts + rhs.inserts,
+        }
+    }
+}
+
+/// The operations on each cache of a [CursorCache](super::CursorCache).
+///
+/// Cursors add their operations when they are dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CursorCacheStats {
+    /// Hashed account seeks and `next` calls.
+    pub accounts: CacheStats,
+    /// Hashed storage emptiness checks, seeks and `next` calls.
+    pub storages: CacheStats,
+    /// Account trie seeks.
+    pub account_trie: CacheStats,
+    /// Storage trie seeks.
+    pub storage_tries: CacheStats,
+}
+
+impl CursorCacheStats {
+    /// Returns the operations on all caches.
+    pub fn total(&self) -> CacheStats {
+        self.accounts + self.storages + self.account_trie + self.storage_tries
+    }
+}
+
+/// The operations on a cache shared by cursors.
+#[derive(Debug, Default)]
+pub(super) struct SharedCacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+}
+
+impl SharedCacheStats {
+    /// Adds the operations of a cursor.
+    pub(super) fn add(&self, stats: CacheStats) {
+        self.hits.fetch_add(stats.hits, Ordering::Relaxed);
+        self.misses.fetch_add(stats.misses, Ordering::Relaxed);
+        self.inserts.fetch_add(stats.inserts, Ordering::Relaxed);
+    }
+
+    /// Returns the operations added so far.
+    pub(super) fn get(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+        }
+    }
+}
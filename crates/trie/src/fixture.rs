@@ -0,0 +1,613 @@
+//! Fixtures for replaying state root computations offline.
+//!
+//! A [RootFixture] captures everything a state root computation of a block reads: the prefix sets
+//! and destroyed accounts derived from the block's [HashedPostState], and every key/value pair the
+//! hashed and trie cursors returned. The cursors are recorded as seen through the post state, so
+//! the fixture can be replayed without the database with [RootFixture::replay], e.g. in unit tests
+//! of root computation strategies:
+//!
+//! ```no_run
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! use reth_trie::fixture::RootFixture;
+//!
+//! let fixture = RootFixture::load("fixtures/block-18000000.json".as_ref())?;
+//! let (root, _updates) = fixture.replay()?;
+//! assert_eq!(root, fixture.state_root);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    hashed_cursor::{
+        HashedAccountCursor, HashedCursorFactory, HashedPostStateCursorFactory, HashedStorageCursor,
+    },
+    prefix_set::PrefixSetMut,
+    trie_cursor::{TrieCursor, TrieCursorFactory, TrieCursorKey},
+    updates::{TrieKey, TrieUpdates},
+    HashedPostState, HashedStorage, StateRoot, StateRootError,
+};
+use parking_lot::Mutex;
+use reth_db::{transaction::DbTx, DatabaseError};
+use reth_primitives::{
+    trie::{BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey},
+    Account, BlockNumHash, BlockNumber, StorageEntry, B256, U256,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    marker::PhantomData,
+    ops::Bound,
+    path::Path,
+    sync::Arc,
+};
+
+/// The inputs and database reads of the state root computation of a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootFixture {
+    /// The number of the block.
+    pub block_number: BlockNumber,
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The state root computed when the fixture was captured.
+    pub state_root: B256,
+    /// The changed account prefixes.
+    pub account_prefix_set: Vec<Nibbles>,
+    /// The changed storage prefixes by hashed address.
+    pub storage_prefix_sets: BTreeMap<B256, Vec<Nibbles>>,
+    /// The hashed addresses of destroyed accounts.
+    pub destroyed_accounts: BTreeSet<B256>,
+    /// The hashed post state of the block.
+    pub post_state: FixturePostState,
+    /// The entries returned by the cursors during the computation.
+    pub trace: CursorTrace,
+}
+
+impl RootFixture {
+    /// Computes the state root of the hashed post state on top of the database state and records
+    /// all cursor reads.
+    ///
+    /// The database must be at the parent of the block.
+    pub fn capture<TX: DbTx>(
+        tx: &TX,
+        block: BlockNumHash,
+        hashed_state: &HashedPostState,
+    ) -> Result<Self, StateRootError> {
+        let (account_prefix_set, storage_prefix_sets) = hashed_state.construct_prefix_sets();
+        let destroyed_accounts = hashed_state.destroyed_accounts();
+
+        let trace = Arc::new(Mutex::new(CursorTrace::default()));
+        let state_root = StateRoot::from_tx(tx)
+            .with_trie_cursor_factory(RecordingCursorFactory::new(tx, Arc::clone(&trace)))
+            .with_hashed_cursor_factory(RecordingCursorFactory::new(
+                HashedPostStateCursorFactory::new(tx, hashed_state),
+                Arc::clone(&trace),
+            ))
+            .with_changed_account_prefixes(account_prefix_set.clone())
+            .with_changed_storage_prefixes(storage_prefix_sets.clone())
+            .with_destroyed_accounts(destroyed_accounts.clone())
+            .root()?;
+        let trace = std::mem::take(&mut *trace.lock());
+
+        Ok(Self {
+            block_number: block.number,
+            block_hash: block.hash,
+            state_root,
+            account_prefix_set: account_prefix_set.keys().to_vec(),
+            storage_prefix_sets: storage_prefix_sets
+                .into_iter()
+                .map(|(hashed_address, prefix_set)| (hashed_address, prefix_set.keys().to_vec()))
+                .collect(),
+            destroyed_accounts: destroyed_accounts.into_iter().collect(),
+            post_state: FixturePostState::from(hashed_state),
+            trace,
+        })
+    }
+
+    /// Returns a state root calculator with the inputs of the fixture that reads from the
+    /// recorded cursor entries instead of a database.
+    pub fn state_root_calculator(&self) -> StateRoot<&CursorTrace, &CursorTrace> {
+        StateRoot::new(&self.trace, &self.trace)
+            .with_changed_account_prefixes(
+                PrefixSetMut::from(self.account_prefix_set.iter().cloned()).freeze(),
+            )
+            .with_changed_storage_prefixes(
+                self.storage_prefix_sets
+                    .iter()
+                    .map(|(hashed_address, prefixes)| {
+                        (*hashed_address, PrefixSetMut::from(prefixes.iter().cloned()).freeze())
+                    })
+                    .collect(),
+            )
+            .with_destroyed_accounts(self.destroyed_accounts.iter().copied().collect())
+    }
+
+    /// Replays the state root computation from the recorded cursor entries.
+    pub fn replay(&self) -> Result<(B256, TrieUpdates), StateRootError> {
+        self.state_root_calculator().root_with_updates()
+    }
+
+    /// Loads a fixture from a JSON file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    /// Writes the fixture to a JSON file.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        Ok(serde_json::to_writer(BufWriter::new(File::create(path)?), self)?)
+    }
+}
+
+/// The hashed post state of a [RootFixture].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixturePostState {
+    /// Changed accounts, `None` if the account was destroyed.
+    pub accounts: BTreeMap<B256, Option<Account>>,
+    /// Changed storages.
+    pub storages: BTreeMap<B256, FixtureStorage>,
+}
+
+impl From<&HashedPostState> for FixturePostState {
+    fn from(hashed_state: &HashedPostState) -> Self {
+        Self {
+            accounts: hashed_state.accounts().collect(),
+            storages: hashed_state
+                .storages()
+                .map(|(hashed_address, storage)| {
+                    let storage = FixtureStorage {
+                        wiped: storage.wiped(),
+                        slots: storage.storage_slots().collect(),
+                    };
+                    (*hashed_address, storage)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&FixturePostState> for HashedPostState {
+    fn from(post_state: &FixturePostState) -> Self {
+        let mut hashed_state = HashedPostState::default();
+        for (hashed_address, account) in &post_state.accounts {
+            hashed_state.insert_account(*hashed_address, *account);
+        }
+        for (hashed_address, storage) in &post_state.storages {
+            let mut hashed_storage = HashedStorage::new(storage.wiped);
+            for (slot, value) in &storage.slots {
+                hashed_storage.insert_slot(*slot, *value);
+            }
+            hashed_state.insert_hashed_storage(*hashed_address, hashed_storage);
+        }
+        hashed_state.sorted()
+    }
+}
+
+/// The changed storage of an account in a [FixturePostState].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureStorage {
+    /// Whether all storage of the account was removed before the slots were written.
+    pub wiped: bool,
+    /// The changed slots, zero if the slot was cleared.
+    pub slots: BTreeMap<B256, U256>,
+}
+
+/// The entries the cursors of a state root computation returned.
+///
+/// Replaying the same computation over the trace yields the same results as the original cursors:
+/// every seek or step returned an entry that is in the trace, and the trace can't contain an entry
+/// between two entries the original cursors returned consecutively.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorTrace {
+    /// Nodes of the account trie.
+    pub account_trie: TrieNodes,
+    /// Nodes of the storage tries by hashed address.
+    pub storage_tries: BTreeMap<B256, TrieNodes>,
+    /// Hashed accounts.
+    pub hashed_accounts: BTreeMap<B256, Account>,
+    /// Hashed storage slots by hashed address.
+    pub hashed_storages: BTreeMap<B256, BTreeMap<B256, U256>>,
+    /// Hashed addresses whose storage was checked and is not empty.
+    pub non_empty_storages: BTreeSet<B256>,
+}
+
+/// Trie nodes by path, serialized as a sequence of pairs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrieNodes(pub BTreeMap<Nibbles, BranchNodeCompact>);
+
+impl Serialize for TrieNodes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for TrieNodes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self(
+            Vec::<(Nibbles, BranchNodeCompact)>::deserialize(deserializer)?.into_iter().collect(),
+        ))
+    }
+}
+
+/// Wraps trie and hashed cursor factories and records every entry their cursors return.
+#[derive(Debug, Clone)]
+struct RecordingCursorFactory<F> {
+    inner: F,
+    trace: Arc<Mutex<CursorTrace>>,
+}
+
+impl<F> RecordingCursorFactory<F> {
+    fn new(inner: F, trace: Arc<Mutex<CursorTrace>>) -> Self {
+        Self { inner, trace }
+    }
+}
+
+impl<F: TrieCursorFactory> TrieCursorFactory for RecordingCursorFactory<F> {
+    fn account_trie_cursor(
+        &self,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibbles> + '_>, DatabaseError> {
+        Ok(Box::new(RecordingTrieCursor {
+            inner: self.inner.account_trie_cursor()?,
+            trace: Arc::clone(&self.trace),
+            hashed_address: None,
+        }))
+    }
+
+    fn storage_tries_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError> {
+        Ok(Box::new(RecordingTrieCursor {
+            inner: self.inner.storage_tries_cursor(hashed_address)?,
+            trace: Arc::clone(&self.trace),
+            hashed_address: Some(hashed_address),
+        }))
+    }
+}
+
+impl<F: HashedCursorFactory> HashedCursorFactory for RecordingCursorFactory<F> {
+    type AccountCursor = RecordingHashedCursor<F::AccountCursor>;
+    type StorageCursor = RecordingHashedCursor<F::StorageCursor>;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor, DatabaseError> {
+        Ok(RecordingHashedCursor {
+            inner: self.inner.hashed_account_cursor()?,
+            trace: Arc::clone(&self.trace),
+            hashed_address: None,
+        })
+    }
+
+    fn hashed_storage_cursor(&self) -> Result<Self::StorageCursor, DatabaseError> {
+        Ok(RecordingHashedCursor {
+            inner: self.inner.hashed_storage_cursor()?,
+            trace: Arc::clone(&self.trace),
+            hashed_address: None,
+        })
+    }
+}
+
+/// A trie cursor that records the nodes it returns.
+struct RecordingTrieCursor<'a, K> {
+    inner: Box<dyn TrieCursor<Key = K> + 'a>,
+    trace: Arc<Mutex<CursorTrace>>,
+    /// The hashed address of the storage trie, `None` for the account trie.
+    hashed_address: Option<B256>,
+}
+
+impl<'a, K> RecordingTrieCursor<'a, K> {
+    fn record(
+        &self,
+        entry: Option<(Vec<u8>, BranchNodeCompact)>,
+    ) -> Option<(Vec<u8>, BranchNodeCompact)> {
+        if let Some((path, node)) = &entry {
+            let mut trace = self.trace.lock();
+            let nodes = match self.hashed_address {
+                Some(hashed_address) => {
+                    &mut trace.storage_tries.entry(hashed_address).or_default().0
+                }
+                None => &mut trace.account_trie.0,
+            };
+            nodes.insert(Nibbles::from_nibbles_unchecked(path.clone()), node.clone());
+        }
+        entry
+    }
+}
+
+impl<'a, K: From<Vec<u8>>> TrieCursor for RecordingTrieCursor<'a, K> {
+    type Key = K;
+
+    fn seek_exact(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let entry = self.inner.seek_exact(key)?;
+        Ok(self.record(entry))
+    }
+
+    fn seek(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let entry = self.inner.seek(key)?;
+        Ok(self.record(entry))
+    }
+
+    fn current(&mut self) -> Result<Option<TrieKey>, DatabaseError> {
+        self.inner.current()
+    }
+}
+
+/// A hashed cursor that records the entries it returns.
+#[derive(Debug)]
+struct RecordingHashedCursor<C> {
+    inner: C,
+    trace: Arc<Mutex<CursorTrace>>,
+    /// The hashed address of the last storage seek.
+    hashed_address: Option<B256>,
+}
+
+impl<C: HashedAccountCursor> HashedAccountCursor for RecordingHashedCursor<C> {
+    fn seek(&mut self, key: B256) -> Result<Option<(B256, Account)>, DatabaseError> {
+        let entry = self.inner.seek(key)?;
+        if let Some((hashed_address, account)) = entry {
+            self.trace.lock().hashed_accounts.insert(hashed_address, account);
+        }
+        Ok(entry)
+    }
+
+    fn next(&mut self) -> Result<Option<(B256, Account)>, DatabaseError> {
+        let entry = self.inner.next()?;
+        if let Some((hashed_address, account)) = entry {
+            self.trace.lock().hashed_accounts.insert(hashed_address, account);
+        }
+        Ok(entry)
+    }
+}
+
+impl<C: HashedStorageCursor> RecordingHashedCursor<C> {
+    fn record_slot(&self, entry: Option<StorageEntry>) -> Option<StorageEntry> {
+        if let Some((hashed_address, entry)) = self.hashed_address.zip(entry) {
+            self.trace
+                .lock()
+                .hashed_storages
+                .entry(hashed_address)
+                .or_default()
+                .insert(entry.key, entry.value);
+        }
+        entry
+    }
+}
+
+impl<C: HashedStorageCursor> HashedStorageCursor for RecordingHashedCursor<C> {
+    fn is_storage_empty(&mut self, key: B256) -> Result<bool, DatabaseError> {
+        let is_empty = self.inner.is_storage_empty(key)?;
+        if !is_empty {
+            self.trace.lock().non_empty_storages.insert(key);
+        }
+        Ok(is_empty)
+    }
+
+    fn seek(&mut self, key: B256, subkey: B256) -> Result<Option<StorageEntry>, DatabaseError> {
+        self.hashed_address = Some(key);
+        let entry = self.inner.seek(key, subkey)?;
+        Ok(self.record_slot(entry))
+    }
+
+    fn next(&mut self) -> Result<Option<StorageEntry>, DatabaseError> {
+        let entry = self.inner.next()?;
+        Ok(self.record_slot(entry))
+    }
+}
+
+/// Replays trie cursors from the recorded nodes.
+impl TrieCursorFactory for CursorTrace {
+    fn account_trie_cursor(
+        &self,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibbles> + '_>, DatabaseError> {
+        Ok(Box::new(TraceTrieCursor::<StoredNibbles>::new(&self.account_trie.0, None)))
+    }
+
+    fn storage_tries_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError> {
+        static EMPTY: BTreeMap<Nibbles, BranchNodeCompact> = BTreeMap::new();
+        let nodes = self.storage_tries.get(&hashed_address).map_or(&EMPTY, |nodes| &nodes.0);
+        Ok(Box::new(TraceTrieCursor::<StoredNibblesSubKey>::new(nodes, Some(hashed_address))))
+    }
+}
+
+impl<'a> TrieCursorFactory for &'a CursorTrace {
+    fn account_trie_cursor(
+        &self,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibbles> + '_>, DatabaseError> {
+        (**self).account_trie_cursor()
+    }
+
+    fn storage_tries_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError> {
+        (**self).storage_tries_cursor(hashed_address)
+    }
+}
+
+/// Replays hashed cursors from the recorded entries.
+impl<'a> HashedCursorFactory for &'a CursorTrace {
+    type AccountCursor = TraceHashedCursor<'a>;
+    type StorageCursor = TraceHashedCursor<'a>;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor, DatabaseError> {
+        Ok(TraceHashedCursor { trace: self, hashed_address: None, last: None })
+    }
+
+    fn hashed_storage_cursor(&self) -> Result<Self::StorageCursor, DatabaseError> {
+        Ok(TraceHashedCursor { trace: self, hashed_address: None, last: None })
+    }
+}
+
+/// A trie cursor over recorded nodes.
+#[derive(Debug)]
+struct TraceTrieCursor<'a, K> {
+    nodes: &'a BTreeMap<Nibbles, BranchNodeCompact>,
+    /// The hashed address of the storage trie, `None` for the account trie.
+    hashed_address: Option<B256>,
+    /// The path of the last returned node.
+    last: Option<Nibbles>,
+    _key: PhantomData<K>,
+}
+
+impl<'a, K> TraceTrieCursor<'a, K> {
+    fn new(nodes: &'a BTreeMap<Nibbles, BranchNodeCompact>, hashed_address: Option<B256>) -> Self {
+        Self { nodes, hashed_address, last: None, _key: PhantomData }
+    }
+
+    fn position(
+        &mut self,
+        entry: Option<(&Nibbles, &BranchNodeCompact)>,
+    ) -> Option<(Vec<u8>, BranchNodeCompact)> {
+        self.last = entry.map(|(path, _)| path.clone());
+        entry.map(|(path, node)| (path.to_vec(), node.clone()))
+    }
+}
+
+impl<'a, K: TrieCursorKey> TrieCursor for TraceTrieCursor<'a, K> {
+    type Key = K;
+
+    fn seek_exact(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let entry = self.nodes.get_key_value(key.nibbles());
+        Ok(self.position(entry))
+    }
+
+    fn seek(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let entry = self.nodes.range(key.nibbles().clone()..).next();
+        Ok(self.position(entry))
+    }
+
+    fn current(&mut self) -> Result<Option<TrieKey>, DatabaseError> {
+        Ok(self.last.clone().map(|path| match self.hashed_address {
+            Some(hashed_address) => TrieKey::StorageNode(hashed_address, StoredNibblesSubKey(path)),
+            None => TrieKey::AccountNode(StoredNibbles(path)),
+        }))
+    }
+}
+
+/// A hashed account or storage cursor over recorded entries.
+#[derive(Debug)]
+pub struct TraceHashedCursor<'a> {
+    trace: &'a CursorTrace,
+    /// The hashed address of the last storage seek.
+    hashed_address: Option<B256>,
+    /// The key of the last returned entry.
+    last: Option<B256>,
+}
+
+impl<'a> HashedAccountCursor for TraceHashedCursor<'a> {
+    fn seek(&mut self, key: B256) -> Result<Option<(B256, Account)>, DatabaseError> {
+        let entry = self.trace.hashed_accounts.range(key..).next();
+        self.last = entry.map(|(hashed_address, _)| *hashed_address);
+        Ok(entry.map(|(hashed_address, account)| (*hashed_address, *account)))
+    }
+
+    fn next(&mut self) -> Result<Option<(B256, Account)>, DatabaseError> {
+        let Some(last) = self.last else { return Ok(None) };
+        let entry =
+            self.trace.hashed_accounts.range((Bound::Excluded(last), Bound::Unbounded)).next();
+        self.last = entry.map(|(hashed_address, _)| *hashed_address);
+        Ok(entry.map(|(hashed_address, account)| (*hashed_address, *account)))
+    }
+}
+
+impl<'a> TraceHashedCursor<'a> {
+    fn slot(&mut self, bound: Bound<B256>) -> Option<StorageEntry> {
+        let entry = self
+            .hashed_address
+            .and_then(|hashed_address| self.trace.hashed_storages.get(&hashed_address))
+            .and_then(|slots| slots.range((bound, Bound::Unbounded)).next());
+        self.last = entry.map(|(slot, _)| *slot);
+        entry.map(|(slot, value)| StorageEntry { key: *slot, value: *value })
+    }
+}
+
+impl<'a> HashedStorageCursor for TraceHashedCursor<'a> {
+    fn is_storage_empty(&mut self, key: B256) -> Result<bool, DatabaseError> {
+        Ok(!self.trace.non_empty_storages.contains(&key))
+    }
+
+    fn seek(&mut self, key: B256, subkey: B256) -> Result<Option<StorageEntry>, DatabaseError> {
+        self.hashed_address = Some(key);
+        Ok(self.slot(Bound::Included(subkey)))
+    }
+
+    fn next(&mut self) -> Result<Option<StorageEntry>, DatabaseError> {
+        let Some(last) = self.last else { return Ok(None) };
+        Ok(self.slot(Bound::Excluded(last)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{tables, transaction::DbTxMut};
+    use reth_primitives::keccak256;
+    use reth_provider::test_utils::create_test_provider_factory;
+
+    #[test]
+    fn capture_and_replay() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let tx = provider.tx_ref();
+
+        // the parent state, with intermediate nodes
+        for i in 0..100u8 {
+            let hashed_address = keccak256([i]);
+            let account = Account { nonce: i as u64, ..Default::default() };
+            tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+            for slot in 0..3u8 {
+                let entry = StorageEntry { key: keccak256([slot]), value: U256::from(slot + 1) };
+                tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+            }
+        }
+        let (_, updates) = StateRoot::from_tx(tx).root_with_updates().unwrap();
+        updates.flush(tx).unwrap();
+
+        let mut hashed_state = HashedPostState::default();
+        hashed_state
+            .insert_account(keccak256([1u8]), Some(Account { nonce: 10, ..Default::default() }));
+        hashed_state.insert_account(keccak256([2u8]), None);
+        hashed_state.insert_hashed_storage(keccak256([2u8]), HashedStorage::new(true));
+        let mut storage = HashedStorage::new(false);
+        storage.insert_slot(keccak256([0u8]), U256::from(42));
+        storage.insert_slot(keccak256([1u8]), U256::ZERO);
+        hashed_state.insert_hashed_storage(keccak256([3u8]), storage);
+        let hashed_state = hashed_state.sorted();
+
+        let fixture =
+            RootFixture::capture(tx, BlockNumHash::new(1, B256::ZERO), &hashed_state).unwrap();
+        let (expected_root, expected_updates) = hashed_state.state_root_with_updates(tx).unwrap();
+        assert_eq!(fixture.state_root, expected_root);
+        assert!(!fixture.trace.account_trie.0.is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.json");
+        fixture.write(&path).unwrap();
+        let loaded = RootFixture::load(&path).unwrap();
+        assert_eq!(loaded, fixture);
+        assert_eq!(
+            FixturePostState::from(&HashedPostState::from(&loaded.post_state)),
+            fixture.post_state
+        );
+
+        // the replay doesn't touch the database
+        drop(provider);
+        let (root, updates) = loaded.replay().unwrap();
+        assert_eq!(root, expected_root);
+        assert_eq!(updates, expected_updates);
+    }
+}
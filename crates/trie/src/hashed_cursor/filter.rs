@@ -0,0 +1,493 @@
+use super::{HashedCursorFactory, HashedStorageCursor};
+use crate::HashedPostState;
+use parking_lot::Mutex;
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+    DatabaseError,
+};
+use reth_primitives::{stage::StageId, BlockNumHash, StorageEntry, B256};
+use std::{fmt, fs, io, path::Path, sync::Arc, thread};
+use tracing::{debug, warn};
+
+/// The version of the persisted filter format.
+const FILTER_VERSION: u8 = 1;
+
+/// The default false positive rate of a [SharedStorageFilter].
+pub const DEFAULT_STORAGE_FILTER_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// A compact probabilistic set of hashed keys.
+///
+/// The filter never reports a false negative: if [ExistenceFilter::may_contain] returns `false`,
+/// the key was never inserted. Keys are expected to be keccak hashes, so their bits are used
+/// directly as the hash functions of the filter.
+///
+/// Removed keys can not be deleted from the filter, they only increase the false positive rate
+/// until the filter is rebuilt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistenceFilter {
+    /// The bits of the filter.
+    bits: Vec<u64>,
+    /// The number of bits set per key.
+    num_hashes: u32,
+}
+
+impl ExistenceFilter {
+    /// Creates an empty filter sized for the expected number of keys at the given false positive
+    /// rate.
+    ///
+    /// # Panics
+    ///
+    /// If the false positive rate is not between 0 and 1, exclusive.
+    pub fn with_capacity(expected_keys: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false positive rate must be between 0 and 1, got {false_positive_rate}"
+        );
+        let expected_keys = expected_keys.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-expected_keys * false_positive_rate.ln() / (ln2 * ln2)).ceil().max(64.0);
+        let num_hashes = ((num_bits / expected_keys) * ln2).round().clamp(1.0, 16.0) as u32;
+        Self { bits: vec![0; (num_bits as usize + 63) / 64], num_hashes }
+    }
+
+    /// Builds a filter over all hashed addresses that have at least one storage entry.
+    pub fn storage_filter_from_tx<TX: DbTx>(
+        tx: &TX,
+        false_positive_rate: f64,
+    ) -> Result<Self, DatabaseError> {
+        // Every account with storage has a hashed account entry.
+        let mut filter =
+            Self::with_capacity(tx.entries::<tables::HashedAccount>()?, false_positive_rate);
+        let mut cursor = tx.cursor_dup_read::<tables::HashedStorage>()?;
+        let mut entry = cursor.first()?;
+        while let Some((hashed_address, _)) = entry {
+            filter.insert(&hashed_address);
+            entry = cursor.next_no_dup()?;
+        }
+        Ok(filter)
+    }
+
+    /// Inserts all hashed addresses with non-zero storage slots in the post state, so the filter
+    /// stays valid after the post state was written to the database.
+    pub fn extend_storages(&mut self, post_state: &HashedPostState) {
+        for hashed_address in storages_of(post_state) {
+            self.insert(&hashed_address);
+        }
+    }
+
+    /// Inserts the key into the filter.
+    pub fn insert(&mut self, key: &B256) {
+        for idx in self.bit_indices(key) {
+            self.bits[idx / 64] |= 1 << (idx % 64);
+        }
+    }
+
+    /// Returns `false` if the key was definitely never inserted.
+    pub fn may_contain(&self, key: &B256) -> bool {
+        self.bit_indices(key).all(|idx| self.bits[idx / 64] & (1 << (idx % 64)) != 0)
+    }
+
+    /// Returns the size of the filter in bytes.
+    pub fn size(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    /// Returns the indices of the bits of the key, using double hashing.
+    fn bit_indices(&self, key: &B256) -> impl Iterator<Item = usize> {
+        let h1 = u64::from_le_bytes(key[..8].try_into().expect("8 bytes"));
+        let h2 = u64::from_le_bytes(key[8..16].try_into().expect("8 bytes")) | 1;
+        let num_bits = self.bits.len() as u64 * 64;
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+
+    /// Encodes the filter.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(5 + self.size());
+        buf.push(FILTER_VERSION);
+        buf.extend_from_slice(&self.num_hashes.to_le_bytes());
+        for word in &self.bits {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Decodes a filter that was encoded with [ExistenceFilter::encode].
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let (&version, buf) = buf.split_first()?;
+        if version != FILTER_VERSION || buf.len() < 4 || (buf.len() - 4) % 8 != 0 {
+            return None
+        }
+        let num_hashes = u32::from_le_bytes(buf[..4].try_into().ok()?);
+        let bits = buf[4..]
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().expect("8 bytes")))
+            .collect::<Vec<_>>();
+        if num_hashes == 0 || bits.is_empty() {
+            return None
+        }
+        Some(Self { bits, num_hashes })
+    }
+
+    /// Loads a persisted filter, returns `None` if the file does not exist or is invalid.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(buf) => Ok(Self::decode(&buf)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Persists the filter to the given path.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        // write to a temporary file first, so a crash never leaves a truncated filter behind
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.encode())?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// Returns the hashed addresses with non-zero storage slots in the post state.
+pub fn storages_of(post_state: &HashedPostState) -> Vec<B256> {
+    post_state
+        .storages
+        .iter()
+        .filter(|(_, storage)| !storage.non_zero_valued_storage.is_empty())
+        .map(|(hashed_address, _)| *hashed_address)
+        .collect()
+}
+
+/// Returns the block the hashed state tables of the database are at, `None` while the pipeline
+/// is running, which writes headers before the state.
+pub fn state_tip<TX: DbTx>(tx: &TX) -> Result<Option<BlockNumHash>, DatabaseError> {
+    let finished = tx
+        .get::<tables::SyncStage>(StageId::Finish.to_string())?
+        .map(|checkpoint| checkpoint.block_number)
+        .unwrap_or_default();
+    let Some((number, hash)) = tx.cursor_read::<tables::CanonicalHeaders>()?.last()? else {
+        return Ok(None)
+    };
+    Ok((number == finished).then(|| BlockNumHash::new(number, hash)))
+}
+
+/// An [ExistenceFilter] over the hashed addresses with storage in the database, shared by the
+/// components reading the hashed state.
+///
+/// The filter is at the block the hashed state tables are at, see [state_tip]. It is extended with
+/// the storages of the blocks written on top of that block with [SharedStorageFilter::extend], and
+/// rebuilt from the database in the background when it is requested at another block, e.g. after
+/// the pipeline ran or blocks were unwound. Until the rebuild finished, no filter is returned.
+#[derive(Clone)]
+pub struct SharedStorageFilter {
+    inner: Arc<Mutex<SharedStorageFilterInner>>,
+    build: Arc<BuildStorageFilter>,
+}
+
+/// Builds the filter from the database, along with the block it is at.
+type BuildStorageFilter =
+    dyn Fn() -> Result<Option<(BlockNumHash, ExistenceFilter)>, DatabaseError> + Send + Sync;
+
+impl fmt::Debug for SharedStorageFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedStorageFilter").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default)]
+struct SharedStorageFilterInner {
+    /// The filter and the block it is at.
+    filter: Option<(BlockNumHash, Arc<ExistenceFilter>)>,
+    /// The blocks written while the filter is rebuilt, with the hashed addresses of their
+    /// storages, `None` if the filter is not rebuilt.
+    rebuilding: Option<Vec<(BlockNumHash, BlockNumHash, Vec<B256>)>>,
+}
+
+impl SharedStorageFilter {
+    /// Creates an empty shared filter, which is built from the database at the given false
+    /// positive rate once it is first requested.
+    pub fn new<DB: Database + 'static>(db: DB, false_positive_rate: f64) -> Self {
+        let build = move || -> Result<_, DatabaseError> {
+            let tx = db.tx()?;
+            let Some(tip) = state_tip(&tx)? else { return Ok(None) };
+            let filter = ExistenceFilter::storage_filter_from_tx(&tx, false_positive_rate)?;
+            Ok(Some((tip, filter)))
+        };
+        Self { inner: Default::default(), build: Arc::new(build) }
+    }
+
+    /// Returns the block the filter is at, `None` if there is no filter.
+    pub fn tip(&self) -> Option<BlockNumHash> {
+        self.inner.lock().filter.as_ref().map(|(tip, _)| *tip)
+    }
+
+    /// Returns the filter if it is at the given block.
+    pub fn get(&self, tip: BlockNumHash) -> Option<Arc<ExistenceFilter>> {
+        let inner = self.inner.lock();
+        inner.filter.as_ref().filter(|(at, _)| *at == tip).map(|(_, filter)| Arc::clone(filter))
+    }
+
+    /// Returns the filter if it is at the block the hashed state tables of the transaction are at.
+    pub fn get_for_tx<TX: DbTx>(
+        &self,
+        tx: &TX,
+    ) -> Result<Option<Arc<ExistenceFilter>>, DatabaseError> {
+        if self.tip().is_none() {
+            return Ok(None)
+        }
+        Ok(state_tip(tx)?.and_then(|tip| self.get(tip)))
+    }
+
+    /// Returns the filter if it is at the given block, otherwise starts rebuilding it from the
+    /// database on a background thread, unless it is rebuilt already.
+    pub fn get_or_rebuild(&self, tip: BlockNumHash) -> Option<Arc<ExistenceFilter>> {
+        let mut inner = self.inner.lock();
+        if let Some((at, filter)) = &inner.filter {
+            if *at == tip {
+                return Some(Arc::clone(filter))
+            }
+        }
+        self.spawn_rebuild(&mut inner);
+        None
+    }
+
+    /// Starts rebuilding the filter from the database on a background thread, unless it is
+    /// rebuilt already.
+    pub fn rebuild_in_background(&self) {
+        self.spawn_rebuild(&mut self.inner.lock());
+    }
+
+    fn spawn_rebuild(&self, inner: &mut SharedStorageFilterInner) {
+        if inner.rebuilding.is_some() {
+            return
+        }
+        // blocks written from now on may be missing from the rebuilt filter
+        inner.rebuilding = Some(Vec::new());
+        let this = self.clone();
+        let spawned =
+            thread::Builder::new().name("storage-filter".to_string()).spawn(move || this.rebuild());
+        if let Err(err) = spawned {
+            warn!(target: "trie::filter", %err, "Failed to spawn storage filter rebuild");
+            inner.rebuilding = None;
+        }
+    }
+
+    /// Rebuilds the filter from the database and catches up with the blocks written meanwhile.
+    fn rebuild(&self) {
+        let built = (self.build)();
+
+        let mut inner = self.inner.lock();
+        let written = inner.rebuilding.take().unwrap_or_default();
+        match built {
+            Ok(Some((mut tip, mut filter))) => {
+                for (parent, block, storages) in written {
+                    // the storages of blocks the filter was built at already are inserted again,
+                    // which is harmless
+                    for hashed_address in &storages {
+                        filter.insert(hashed_address);
+                    }
+                    if parent == tip {
+                        tip = block;
+                    }
+                }
+                debug!(target: "trie::filter", ?tip, size = filter.size(), "Rebuilt storage filter");
+                inner.filter = Some((tip, Arc::new(filter)));
+            }
+            Ok(None) => {
+                debug!(target: "trie::filter", "Hashed state is not at a block, storage filter is not rebuilt")
+            }
+            Err(err) => warn!(target: "trie::filter", %err, "Failed to rebuild storage filter"),
+        }
+    }
+
+    /// Extends the filter with the hashed addresses with storage of the blocks written on top of
+    /// `parent`, which moved the hashed state tables to `tip`.
+    ///
+    /// Must be called after the blocks were committed. If the filter is not at `parent`, it is
+    /// dropped and rebuilt when it is requested next.
+    pub fn extend(&self, parent: BlockNumHash, tip: BlockNumHash, storages: Vec<B256>) {
+        let mut inner = self.inner.lock();
+        if let Some(written) = &mut inner.rebuilding {
+            written.push((parent, tip, storages));
+            return
+        }
+        if inner.filter.as_ref().map(|(at, _)| *at) != Some(parent) {
+            inner.filter = None;
+            return
+        }
+        if let Some((at, filter)) = &mut inner.filter {
+            let filter = Arc::make_mut(filter);
+            for hashed_address in &storages {
+                filter.insert(hashed_address);
+            }
+            *at = tip;
+        }
+    }
+}
+
+/// A hashed cursor factory that consults an [ExistenceFilter] over hashed addresses with storage
+/// before checking whether a storage is empty, skipping reads that are guaranteed to miss.
+///
+/// The filter must contain every hashed address that has storage in the underlying state.
+#[derive(Debug, Clone)]
+pub struct FilteredHashedCursorFactory<H> {
+    inner: H,
+    storage_filter: Arc<ExistenceFilter>,
+}
+
+impl<H> FilteredHashedCursorFactory<H> {
+    /// Creates a new factory that wraps the given one.
+    pub fn new(inner: H, storage_filter: Arc<ExistenceFilter>) -> Self {
+        Self { inner, storage_filter }
+    }
+}
+
+impl<H: HashedCursorFactory> HashedCursorFactory for FilteredHashedCursorFactory<H> {
+    type AccountCursor = H::AccountCursor;
+    type StorageCursor = FilteredHashedStorageCursor<H::StorageCursor>;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor, DatabaseError> {
+        self.inner.hashed_account_cursor()
+    }
+
+    fn hashed_storage_cursor(&self) -> Result<Self::StorageCursor, DatabaseError> {
+        Ok(FilteredHashedStorageCursor {
+            inner: self.inner.hashed_storage_cursor()?,
+            storage_filter: Arc::clone(&self.storage_filter),
+        })
+    }
+}
+
+/// A hashed storage cursor that answers [HashedStorageCursor::is_storage_empty] from an
+/// [ExistenceFilter] when possible.
+#[derive(Debug)]
+pub struct FilteredHashedStorageCursor<C> {
+    inner: C,
+    storage_filter: Arc<ExistenceFilter>,
+}
+
+impl<C: HashedStorageCursor> HashedStorageCursor for FilteredHashedStorageCursor<C> {
+    fn is_storage_empty(&mut self, key: B256) -> Result<bool, DatabaseError> {
+        if !self.storage_filter.may_contain(&key) {
+            return Ok(true)
+        }
+        self.inner.is_storage_empty(key)
+    }
+
+    fn seek(&mut self, key: B256, subkey: B256) -> Result<Option<StorageEntry>, DatabaseError> {
+        self.inner.seek(key, subkey)
+    }
+
+    fn next(&mut self) -> Result<Option<StorageEntry>, DatabaseError> {
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StorageRoot;
+    use reth_db::{test_utils::create_test_rw_db, transaction::DbTxMut};
+    use reth_primitives::{keccak256, stage::StageCheckpoint, U256};
+    use std::time::Duration;
+
+    #[test]
+    fn no_false_negatives() {
+        let keys = (0..10_000u64).map(|i| keccak256(i.to_be_bytes())).collect::<Vec<_>>();
+        let mut filter = ExistenceFilter::with_capacity(keys.len(), 0.01);
+        for key in &keys {
+            filter.insert(key);
+        }
+        assert!(keys.iter().all(|key| filter.may_contain(key)));
+
+        let false_positives =
+            (10_000..20_000u64).filter(|i| filter.may_contain(&keccak256(i.to_be_bytes()))).count();
+        assert!(false_positives < 300, "false positive rate too high: {false_positives}");
+
+        assert_eq!(ExistenceFilter::decode(&filter.encode()), Some(filter));
+        assert_eq!(ExistenceFilter::decode(&[FILTER_VERSION, 1]), None);
+    }
+
+    #[test]
+    fn filtered_storage_root() {
+        let db = create_test_rw_db();
+        let with_storage = keccak256([1]);
+        let without_storage = keccak256([2]);
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::HashedStorage>(
+            with_storage,
+            StorageEntry { key: B256::with_last_byte(1), value: U256::from(1) },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        let filter = Arc::new(ExistenceFilter::storage_filter_from_tx(&tx, 0.01).unwrap());
+        assert!(filter.may_contain(&with_storage));
+
+        let factory = FilteredHashedCursorFactory::new(&tx, filter);
+        let mut cursor = factory.hashed_storage_cursor().unwrap();
+        assert!(!cursor.is_storage_empty(with_storage).unwrap());
+        assert!(cursor.is_storage_empty(without_storage).unwrap());
+
+        for hashed_address in [with_storage, without_storage] {
+            let expected = StorageRoot::from_tx_hashed(&tx, hashed_address).root().unwrap();
+            let filtered = StorageRoot::from_tx_hashed(&tx, hashed_address)
+                .with_hashed_cursor_factory(factory.clone())
+                .root()
+                .unwrap();
+            assert_eq!(filtered, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "false positive rate must be between 0 and 1")]
+    fn invalid_false_positive_rate() {
+        ExistenceFilter::with_capacity(10, 0.0);
+    }
+
+    #[test]
+    fn shared_filter() {
+        let db = create_test_rw_db();
+        let with_storage = keccak256([1]);
+        let tip = BlockNumHash::new(1, B256::with_last_byte(1));
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::HashedStorage>(
+            with_storage,
+            StorageEntry { key: B256::with_last_byte(1), value: U256::from(1) },
+        )
+        .unwrap();
+        tx.put::<tables::CanonicalHeaders>(tip.number, tip.hash).unwrap();
+        tx.put::<tables::SyncStage>(StageId::Finish.to_string(), StageCheckpoint::new(tip.number))
+            .unwrap();
+        tx.commit().unwrap();
+
+        let shared = SharedStorageFilter::new(Arc::clone(&db), 0.01);
+        assert!(shared.get_or_rebuild(tip).is_none());
+        let mut filter = None;
+        for _ in 0..500 {
+            filter = shared.get(tip);
+            if filter.is_some() {
+                break
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(filter.expect("filter is rebuilt").may_contain(&with_storage));
+        assert!(shared.get_for_tx(&db.tx().unwrap()).unwrap().is_some());
+
+        // blocks written on top of the filter extend it
+        let next = BlockNumHash::new(2, B256::with_last_byte(2));
+        let new_storage = keccak256([2]);
+        shared.extend(tip, next, vec![new_storage]);
+        assert!(shared.get(tip).is_none());
+        assert!(shared.get(next).unwrap().may_contain(&new_storage));
+
+        // blocks written on top of another block drop it
+        shared.extend(tip, next, Vec::new());
+        assert_eq!(shared.tip(), None);
+    }
+}
@@ -0,0 +1,217 @@
+use super::{HashedCursorFactory, HashedStorageCursor};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseError,
+};
+use reth_primitives::{StorageEntry, B256};
+
+/// Moves the hashed storage of the given accounts from [tables::HashedStorage] into
+/// [tables::HotHashedStorages].
+///
+/// Returns the number of moved storage entries. Accounts without storage, or whose storage was
+/// already moved, are skipped.
+///
+/// The node only writes [tables::HashedStorage], so the storage of the moved accounts is stale
+/// once a block changes it. The migration is meant for experiments on a copy of the database, and
+/// must be reverted with [revert_hot_storages] before the node writes to it.
+pub fn migrate_hot_storages<TX: DbTx + DbTxMut>(
+    tx: &TX,
+    hashed_addresses: impl IntoIterator<Item = B256>,
+) -> Result<usize, DatabaseError> {
+    let mut storage = tx.cursor_dup_read::<tables::HashedStorage>()?;
+    let mut moved = 0;
+    for hashed_address in hashed_addresses {
+        let mut entry = storage.seek_exact(hashed_address)?.map(|(_, entry)| entry);
+        if entry.is_none() {
+            continue
+        }
+        while let Some(value) = entry {
+            tx.put::<tables::HotHashedStorages>(hashed_address, value)?;
+            moved += 1;
+            entry = storage.next_dup_val()?;
+        }
+        tx.delete::<tables::HashedStorage>(hashed_address, None)?;
+    }
+    Ok(moved)
+}
+
+/// Moves all hashed storage from [tables::HotHashedStorages] back into [tables::HashedStorage],
+/// reverting [migrate_hot_storages].
+///
+/// Returns the number of moved storage entries.
+pub fn revert_hot_storages<TX: DbTx + DbTxMut>(tx: &TX) -> Result<usize, DatabaseError> {
+    let mut hot = tx.cursor_dup_read::<tables::HotHashedStorages>()?;
+    let mut moved = 0;
+    let mut entry = hot.first()?;
+    while let Some((hashed_address, value)) = entry {
+        tx.put::<tables::HashedStorage>(hashed_address, value)?;
+        moved += 1;
+        entry = hot.next()?;
+    }
+    tx.clear::<tables::HotHashedStorages>()?;
+    Ok(moved)
+}
+
+/// A hashed cursor factory reading the storage of the accounts moved by [migrate_hot_storages]
+/// from [tables::HotHashedStorages], and the storage of all other accounts from
+/// [tables::HashedStorage].
+#[derive(Debug)]
+pub struct HotStorageHashedCursorFactory<'a, TX> {
+    tx: &'a TX,
+}
+
+impl<'a, TX> HotStorageHashedCursorFactory<'a, TX> {
+    /// Creates a new factory reading from the given transaction.
+    pub fn new(tx: &'a TX) -> Self {
+        Self { tx }
+    }
+}
+
+impl<'a, TX> Clone for HotStorageHashedCursorFactory<'a, TX> {
+    fn clone(&self) -> Self {
+        Self { tx: self.tx }
+    }
+}
+
+impl<'a, TX: DbTx> HashedCursorFactory for HotStorageHashedCursorFactory<'a, TX> {
+    type AccountCursor = <TX as DbTx>::Cursor<tables::HashedAccount>;
+    type StorageCursor = HotStorageCursor<
+        <TX as DbTx>::DupCursor<tables::HashedStorage>,
+        <TX as DbTx>::DupCursor<tables::HotHashedStorages>,
+    >;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor, DatabaseError> {
+        self.tx.cursor_read::<tables::HashedAccount>()
+    }
+
+    fn hashed_storage_cursor(&self) -> Result<Self::StorageCursor, DatabaseError> {
+        Ok(HotStorageCursor {
+            storage: self.tx.cursor_dup_read::<tables::HashedStorage>()?,
+            hot: self.tx.cursor_dup_read::<tables::HotHashedStorages>()?,
+            current: None,
+        })
+    }
+}
+
+/// A hashed storage cursor reading from the table that holds the storage of an account.
+#[derive(Debug)]
+pub struct HotStorageCursor<C, H> {
+    storage: C,
+    hot: H,
+    /// The last account the cursor was used with, and whether its storage is hot.
+    current: Option<(B256, bool)>,
+}
+
+impl<C, H> HotStorageCursor<C, H>
+where
+    H: DbDupCursorRO<tables::HotHashedStorages>,
+{
+    /// Returns whether the storage of the account was moved to [tables::HotHashedStorages].
+    fn is_hot(&mut self, hashed_address: B256) -> Result<bool, DatabaseError> {
+        if let Some((current, is_hot)) = self.current {
+            if current == hashed_address {
+                return Ok(is_hot)
+            }
+        }
+        let is_hot = self.hot.seek_exact(hashed_address)?.is_some();
+        self.current = Some((hashed_address, is_hot));
+        Ok(is_hot)
+    }
+}
+
+impl<C, H> HashedStorageCursor for HotStorageCursor<C, H>
+where
+    C: DbDupCursorRO<tables::HashedStorage>,
+    H: DbDupCursorRO<tables::HotHashedStorages>,
+{
+    fn is_storage_empty(&mut self, key: B256) -> Result<bool, DatabaseError> {
+        if self.is_hot(key)? {
+            return Ok(false)
+        }
+        Ok(self.storage.seek_exact(key)?.is_none())
+    }
+
+    fn seek(&mut self, key: B256, subkey: B256) -> Result<Option<StorageEntry>, DatabaseError> {
+        if self.is_hot(key)? {
+            self.hot.seek_by_key_subkey(key, subkey)
+        } else {
+            self.storage.seek_by_key_subkey(key, subkey)
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<StorageEntry>, DatabaseError> {
+        match self.current {
+            Some((_, true)) => self.hot.next_dup_val(),
+            _ => self.storage.next_dup_val(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StorageRoot;
+    use reth_db::{database::Database, test_utils::create_test_rw_db};
+    use reth_primitives::{keccak256, U256};
+
+    #[test]
+    fn migrate_and_revert() {
+        let db = create_test_rw_db();
+        let hot = keccak256([1]);
+        let cold = keccak256([2]);
+        let empty = keccak256([3]);
+
+        let tx = db.tx_mut().unwrap();
+        for (hashed_address, slots) in [(hot, 100u64), (cold, 10)] {
+            for slot in 0..slots {
+                let entry = StorageEntry {
+                    key: keccak256(slot.to_be_bytes()),
+                    value: U256::from(slot + 1),
+                };
+                tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+            }
+        }
+        tx.commit().unwrap();
+
+        let roots = {
+            let tx = db.tx().unwrap();
+            [hot, cold, empty]
+                .map(|hashed_address| StorageRoot::from_tx_hashed(&tx, hashed_address).root())
+                .map(Result::unwrap)
+        };
+
+        let tx = db.tx_mut().unwrap();
+        assert_eq!(migrate_hot_storages(&tx, [hot, empty]).unwrap(), 100);
+        // already moved
+        assert_eq!(migrate_hot_storages(&tx, [hot]).unwrap(), 0);
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.entries::<tables::HotHashedStorages>().unwrap(), 100);
+        assert_eq!(tx.entries::<tables::HashedStorage>().unwrap(), 10);
+        let factory = HotStorageHashedCursorFactory::new(&tx);
+        let mut cursor = factory.hashed_storage_cursor().unwrap();
+        assert!(!cursor.is_storage_empty(hot).unwrap());
+        assert!(!cursor.is_storage_empty(cold).unwrap());
+        assert!(cursor.is_storage_empty(empty).unwrap());
+        for (hashed_address, root) in [hot, cold, empty].into_iter().zip(roots) {
+            let migrated = StorageRoot::from_tx_hashed(&tx, hashed_address)
+                .with_hashed_cursor_factory(factory.clone())
+                .root()
+                .unwrap();
+            assert_eq!(migrated, root);
+        }
+        drop(tx);
+
+        let tx = db.tx_mut().unwrap();
+        assert_eq!(revert_hot_storages(&tx).unwrap(), 100);
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.entries::<tables::HotHashedStorages>().unwrap(), 0);
+        assert_eq!(tx.entries::<tables::HashedStorage>().unwrap(), 110);
+        assert_eq!(StorageRoot::from_tx_hashed(&tx, hot).root().unwrap(), roots[0]);
+    }
+}
@@ -7,6 +7,18 @@ mod default;
 mod post_state;
 pub use post_state::*;
 
+/// Implementation of hashed state cursor traits layering the post state over other cursors.
+mod overlay;
+pub use overlay::*;
+
+/// Existence filter to skip guaranteed-miss storage lookups.
+mod filter;
+pub use filter::*;
+
+/// Alternate storage layout keeping the storage of hot contracts in a table of its own.
+mod hot;
+pub use hot::*;
+
 /// The factory trait for creating cursors over the hashed state.
 pub trait HashedCursorFactory {
     /// The hashed account cursor type.
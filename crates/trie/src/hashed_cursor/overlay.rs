@@ -0,0 +1,413 @@
+use super::{HashedAccountCursor, HashedCursorFactory, HashedStorageCursor};
+use crate::state::HashedPostState;
+use reth_primitives::{Account, StorageEntry, B256, U256};
+
+/// A hashed cursor factory layering an in-memory [HashedPostState] over the cursors of another
+/// factory, e.g. a [CachedHashedCursorFactory](crate::cached_cursors::CachedHashedCursorFactory).
+///
+/// Unlike [HashedPostStateCursorFactory](super::HashedPostStateCursorFactory), the underlying
+/// cursors are only used through the [HashedAccountCursor] and [HashedStorageCursor] traits, so
+/// the pending changes of a block can be layered over a cache of the database state without
+/// copying them into the cache.
+#[derive(Debug)]
+pub struct OverlayHashedCursorFactory<'b, F> {
+    inner: F,
+    post_state: &'b HashedPostState,
+}
+
+impl<'b, F: Clone> Clone for OverlayHashedCursorFactory<'b, F> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), post_state: self.post_state }
+    }
+}
+
+impl<'b, F> OverlayHashedCursorFactory<'b, F> {
+    /// Create a new factory layering the post state over the cursors of the given factory.
+    ///
+    /// The post state must be sorted.
+    pub fn new(inner: F, post_state: &'b HashedPostState) -> Self {
+        Self { inner, post_state }
+    }
+}
+
+impl<'b, F: HashedCursorFactory> HashedCursorFactory for OverlayHashedCursorFactory<'b, F> {
+    type AccountCursor = OverlayHashedAccountCursor<'b, F::AccountCursor>;
+    type StorageCursor = OverlayHashedStorageCursor<'b, F::StorageCursor>;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor, reth_db::DatabaseError> {
+        Ok(OverlayHashedAccountCursor::new(self.inner.hashed_account_cursor()?, self.post_state))
+    }
+
+    fn hashed_storage_cursor(&self) -> Result<Self::StorageCursor, reth_db::DatabaseError> {
+        Ok(OverlayHashedStorageCursor::new(self.inner.hashed_storage_cursor()?, self.post_state))
+    }
+}
+
+/// The cursor over the hashed accounts of the post state and of an underlying cursor. It always
+/// gives precedence to the post state.
+#[derive(Debug, Clone)]
+pub struct OverlayHashedAccountCursor<'b, C> {
+    /// The underlying cursor.
+    cursor: C,
+    /// The reference to the in-memory [HashedPostState].
+    post_state: &'b HashedPostState,
+    /// The entry the underlying cursor is at, `None` if it is not known, e.g. after a seek was
+    /// served from the post state.
+    cursor_entry: Option<Option<(B256, Account)>>,
+    /// The last hashed account key that was returned by the cursor.
+    last_account: Option<B256>,
+}
+
+impl<'b, C> OverlayHashedAccountCursor<'b, C> {
+    /// Create new instance of [OverlayHashedAccountCursor].
+    pub fn new(cursor: C, post_state: &'b HashedPostState) -> Self {
+        Self { cursor, post_state, cursor_entry: None, last_account: None }
+    }
+
+    /// Returns the first post state account after `key`, or at `key` if `inclusive` is set.
+    fn post_state_account(&self, key: &B256, inclusive: bool) -> Option<(B256, Account)> {
+        let accounts = &self.post_state.accounts;
+        let index =
+            accounts.partition_point(
+                |(address, _)| {
+                    if inclusive {
+                        address < key
+                    } else {
+                        address <= key
+                    }
+                },
+            );
+        accounts.get(index).copied()
+    }
+}
+
+impl<'b, C: HashedAccountCursor> OverlayHashedAccountCursor<'b, C> {
+    /// Moves the underlying cursor past destroyed accounts and accounts at or before `last`.
+    fn skip_cursor_entries(
+        &mut self,
+        mut entry: Option<(B256, Account)>,
+        last: Option<B256>,
+    ) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
+        while entry.as_ref().map_or(false, |(address, _)| {
+            last.map_or(false, |last| *address <= last) ||
+                self.post_state.destroyed_accounts.contains(address)
+        }) {
+            entry = self.cursor.next()?;
+        }
+        Ok(entry)
+    }
+}
+
+impl<'b, C: HashedAccountCursor> HashedAccountCursor for OverlayHashedAccountCursor<'b, C> {
+    /// Seek the next entry for a given hashed account key.
+    ///
+    /// If the post state contains the exact match for the key, it's returned without using the
+    /// underlying cursor. Otherwise, the lowest of the next post state and underlying entries is
+    /// returned.
+    fn seek(&mut self, key: B256) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
+        debug_assert!(self.post_state.sorted, "`HashedPostState` must be pre-sorted");
+
+        let post_state_entry = self.post_state_account(&key, true);
+        if let Some((address, account)) = post_state_entry.filter(|(address, _)| address == &key) {
+            self.cursor_entry = None;
+            self.last_account = Some(address);
+            return Ok(Some((address, account)))
+        }
+
+        let entry = self.cursor.seek(key)?;
+        let cursor_entry = self.skip_cursor_entries(entry, None)?;
+        self.cursor_entry = Some(cursor_entry);
+
+        let result = lowest_entry(post_state_entry, cursor_entry);
+        self.last_account = result.map(|(address, _)| address);
+        Ok(result)
+    }
+
+    /// Retrieve the next entry after the last returned one.
+    ///
+    /// The underlying cursor is only repositioned if the last seek was served from the post
+    /// state.
+    fn next(&mut self) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
+        debug_assert!(self.post_state.sorted, "`HashedPostState` must be pre-sorted");
+
+        let Some(last_account) = self.last_account else { return Ok(None) };
+
+        let entry = match self.cursor_entry {
+            Some(entry) => entry,
+            None => self.cursor.seek(last_account)?,
+        };
+        let cursor_entry = self.skip_cursor_entries(entry, Some(last_account))?;
+        self.cursor_entry = Some(cursor_entry);
+
+        let post_state_entry = self.post_state_account(&last_account, false);
+        let result = lowest_entry(post_state_entry, cursor_entry);
+        self.last_account = result.map(|(address, _)| address);
+        Ok(result)
+    }
+}
+
+/// The cursor over the hashed storages of the post state and of an underlying cursor. It always
+/// gives precedence to the post state.
+#[derive(Debug, Clone)]
+pub struct OverlayHashedStorageCursor<'b, C> {
+    /// The underlying cursor.
+    cursor: C,
+    /// The reference to the in-memory [HashedPostState].
+    post_state: &'b HashedPostState,
+    /// The current hashed account key.
+    account: Option<B256>,
+    /// The entry of the current account the underlying cursor is at, `None` if it is not known.
+    cursor_entry: Option<Option<(B256, U256)>>,
+    /// The last slot that has been returned by the cursor.
+    last_slot: Option<B256>,
+}
+
+impl<'b, C> OverlayHashedStorageCursor<'b, C> {
+    /// Create new instance of [OverlayHashedStorageCursor].
+    pub fn new(cursor: C, post_state: &'b HashedPostState) -> Self {
+        Self { cursor, post_state, account: None, cursor_entry: None, last_slot: None }
+    }
+
+    /// Returns the first non-zero post state slot of the account after `slot`, or at `slot` if
+    /// `inclusive` is set.
+    fn post_state_slot(
+        &self,
+        account: &B256,
+        slot: &B256,
+        inclusive: bool,
+    ) -> Option<(B256, U256)> {
+        let storage = self.post_state.storages.get(account)?;
+        debug_assert!(storage.sorted, "`HashedStorage` must be pre-sorted");
+        let slots = &storage.non_zero_valued_storage;
+        let index =
+            slots.partition_point(|(key, _)| if inclusive { key < slot } else { key <= slot });
+        slots.get(index).copied()
+    }
+
+    /// Returns `true` if the storage of the account was wiped, so the underlying entries are
+    /// ignored.
+    fn is_storage_wiped(&self, account: &B256) -> bool {
+        self.post_state.storages.get(account).map_or(false, |storage| storage.wiped)
+    }
+}
+
+impl<'b, C: HashedStorageCursor> OverlayHashedStorageCursor<'b, C> {
+    /// Moves the underlying cursor past slots that were zeroed in the post state and slots at or
+    /// before `last`.
+    fn skip_cursor_entries(
+        &mut self,
+        account: &B256,
+        mut entry: Option<StorageEntry>,
+        last: Option<B256>,
+    ) -> Result<Option<(B256, U256)>, reth_db::DatabaseError> {
+        let zero_valued_slots =
+            self.post_state.storages.get(account).map(|storage| &storage.zero_valued_slots);
+        while entry.as_ref().map_or(false, |entry| {
+            last.map_or(false, |last| entry.key <= last) ||
+                zero_valued_slots.map_or(false, |slots| slots.contains(&entry.key))
+        }) {
+            entry = self.cursor.next()?;
+        }
+        Ok(entry.map(|entry| (entry.key, entry.value)))
+    }
+}
+
+impl<'b, C: HashedStorageCursor> HashedStorageCursor for OverlayHashedStorageCursor<'b, C> {
+    /// Returns `true` if the account has no storage entries after the post state is applied.
+    ///
+    /// This function should be called before attempting to call [HashedStorageCursor::seek] or
+    /// [HashedStorageCursor::next].
+    fn is_storage_empty(&mut self, key: B256) -> Result<bool, reth_db::DatabaseError> {
+        let Some(storage) = self.post_state.storages.get(&key) else {
+            self.cursor_entry = None;
+            return self.cursor.is_storage_empty(key)
+        };
+        if !storage.non_zero_valued_storage.is_empty() {
+            return Ok(false)
+        }
+        if storage.wiped {
+            return Ok(true)
+        }
+
+        // the post state may have zeroed all remaining slots
+        self.cursor_entry = None;
+        let entry = self.cursor.seek(key, B256::ZERO)?;
+        Ok(self.skip_cursor_entries(&key, entry, None)?.is_none())
+    }
+
+    /// Seek the next account storage entry for a given hashed key pair.
+    fn seek(
+        &mut self,
+        account: B256,
+        subkey: B256,
+    ) -> Result<Option<StorageEntry>, reth_db::DatabaseError> {
+        self.account = Some(account);
+
+        let post_state_entry = self.post_state_slot(&account, &subkey, true);
+        if let Some((slot, value)) = post_state_entry.filter(|(slot, _)| slot == &subkey) {
+            self.cursor_entry = None;
+            self.last_slot = Some(slot);
+            return Ok(Some(StorageEntry { key: slot, value }))
+        }
+
+        let cursor_entry = if self.is_storage_wiped(&account) {
+            None
+        } else {
+            let entry = self.cursor.seek(account, subkey)?;
+            self.skip_cursor_entries(&account, entry, None)?
+        };
+        self.cursor_entry = Some(cursor_entry);
+
+        let result = lowest_entry(post_state_entry, cursor_entry);
+        self.last_slot = result.map(|(slot, _)| slot);
+        Ok(result.map(|(key, value)| StorageEntry { key, value }))
+    }
+
+    /// Return the next account storage entry for the current account key.
+    ///
+    /// # Panics
+    ///
+    /// If the account key is not set. [HashedStorageCursor::seek] must be called first in order to
+    /// position the cursor.
+    fn next(&mut self) -> Result<Option<StorageEntry>, reth_db::DatabaseError> {
+        let account = self.account.expect("`seek` must be called first");
+        let Some(last_slot) = self.last_slot else { return Ok(None) };
+
+        let cursor_entry = if self.is_storage_wiped(&account) {
+            None
+        } else {
+            let entry = match self.cursor_entry {
+                Some(entry) => entry.map(|(key, value)| StorageEntry { key, value }),
+                None => self.cursor.seek(account, last_slot)?,
+            };
+            self.skip_cursor_entries(&account, entry, Some(last_slot))?
+        };
+        self.cursor_entry = Some(cursor_entry);
+
+        let post_state_entry = self.post_state_slot(&account, &last_slot, false);
+        let result = lowest_entry(post_state_entry, cursor_entry);
+        self.last_slot = result.map(|(slot, _)| slot);
+        Ok(result.map(|(key, value)| StorageEntry { key, value }))
+    }
+}
+
+/// Returns the entry with the lowest key, giving precedence to the post state entry if the keys
+/// are equal.
+fn lowest_entry<V>(
+    post_state_entry: Option<(B256, V)>,
+    cursor_entry: Option<(B256, V)>,
+) -> Option<(B256, V)> {
+    match (post_state_entry, cursor_entry) {
+        (Some(post_state_entry), Some(cursor_entry)) => {
+            if post_state_entry.0 <= cursor_entry.0 {
+                Some(post_state_entry)
+            } else {
+                Some(cursor_entry)
+            }
+        }
+        (post_state_entry, cursor_entry) => post_state_entry.or(cursor_entry),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hashed_cursor::HashedPostStateCursorFactory, HashedStorage};
+    use reth_db::{
+        database::Database, tables, test_utils::create_test_rw_db, transaction::DbTxMut,
+    };
+    use std::collections::BTreeMap;
+
+    fn key(byte: u8) -> B256 {
+        B256::with_last_byte(byte)
+    }
+
+    fn account(nonce: u64) -> Account {
+        Account { nonce, ..Default::default() }
+    }
+
+    fn accounts(factory: &impl HashedCursorFactory) -> Vec<(B256, Account)> {
+        let mut cursor = factory.hashed_account_cursor().unwrap();
+        let mut accounts = Vec::from_iter(cursor.seek(B256::ZERO).unwrap());
+        while let Some(entry) = cursor.next().unwrap() {
+            accounts.push(entry);
+        }
+        accounts
+    }
+
+    fn storage(factory: &impl HashedCursorFactory, address: B256) -> Vec<(B256, U256)> {
+        let mut cursor = factory.hashed_storage_cursor().unwrap();
+        let mut storage = Vec::from_iter(cursor.seek(address, B256::ZERO).unwrap());
+        while let Some(entry) = cursor.next().unwrap() {
+            storage.push(entry);
+        }
+        storage.into_iter().map(|entry| (entry.key, entry.value)).collect()
+    }
+
+    #[test]
+    fn layers_post_state_over_cursors() {
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for i in 1..=10u8 {
+                tx.put::<tables::HashedAccount>(key(i), account(i as u64)).unwrap();
+                for address in [key(1), key(2), key(3)] {
+                    let entry = StorageEntry { key: key(i), value: U256::from(i) };
+                    tx.put::<tables::HashedStorage>(address, entry).unwrap();
+                }
+            }
+        })
+        .unwrap();
+
+        let mut post_state = HashedPostState::default();
+        post_state.insert_account(key(3), Some(account(30)));
+        post_state.insert_account(key(5), None);
+        post_state.insert_account(key(12), Some(account(12)));
+        // updated, zeroed and created slots
+        let mut storage_1 = HashedStorage::new(false);
+        storage_1.insert_slot(key(2), U256::ZERO);
+        storage_1.insert_slot(key(4), U256::from(40));
+        storage_1.insert_slot(key(11), U256::from(11));
+        post_state.insert_hashed_storage(key(1), storage_1);
+        // a wiped storage with a new slot
+        let mut storage_2 = HashedStorage::new(true);
+        storage_2.insert_slot(key(5), U256::from(50));
+        post_state.insert_hashed_storage(key(2), storage_2);
+        // all slots are zeroed
+        let mut storage_3 = HashedStorage::new(false);
+        (1..=10).for_each(|i| storage_3.insert_slot(key(i), U256::ZERO));
+        post_state.insert_hashed_storage(key(3), storage_3);
+        let post_state = post_state.sorted();
+
+        let tx = db.tx().unwrap();
+        let overlay = OverlayHashedCursorFactory::new(&tx, &post_state);
+        let reference = HashedPostStateCursorFactory::new(&tx, &post_state);
+
+        let mut expected =
+            (1..=10u8).filter(|i| *i != 5).map(|i| (key(i), account(i as u64))).collect::<Vec<_>>();
+        expected[2].1 = account(30);
+        expected.push((key(12), account(12)));
+        assert_eq!(accounts(&overlay), expected);
+        assert_eq!(accounts(&reference), expected);
+
+        let mut expected = (1..=10u8).map(|i| (key(i), U256::from(i))).collect::<BTreeMap<_, _>>();
+        expected.remove(&key(2));
+        expected.insert(key(4), U256::from(40));
+        expected.insert(key(11), U256::from(11));
+        assert_eq!(storage(&overlay, key(1)), Vec::from_iter(expected.clone()));
+        assert_eq!(storage(&reference, key(1)), Vec::from_iter(expected));
+        assert_eq!(storage(&overlay, key(2)), vec![(key(5), U256::from(50))]);
+        assert!(storage(&overlay, key(3)).is_empty());
+
+        let mut cursor = overlay.hashed_storage_cursor().unwrap();
+        assert!(!cursor.is_storage_empty(key(1)).unwrap());
+        assert!(!cursor.is_storage_empty(key(2)).unwrap());
+        assert!(cursor.is_storage_empty(key(3)).unwrap());
+        assert!(cursor.is_storage_empty(key(4)).unwrap());
+
+        // a seek served from the post state continues with the entries of the underlying cursor
+        let mut cursor = overlay.hashed_account_cursor().unwrap();
+        assert_eq!(cursor.seek(key(3)).unwrap(), Some((key(3), account(30))));
+        assert_eq!(cursor.next().unwrap(), Some((key(4), account(4))));
+        assert_eq!(cursor.next().unwrap(), Some((key(6), account(6))));
+    }
+}
@@ -0,0 +1,120 @@
+use alloy_rlp::{BufMut, Encodable};
+use reth_primitives::{
+    proofs::adjust_index_for_rlp,
+    trie::{HashBuilder, Nibbles},
+    Bytes, B256,
+};
+use std::collections::BTreeMap;
+
+/// Merkle proofs of inclusion for all items of an ordered trie, like the transactions or the
+/// receipts trie of a block.
+///
+/// The items are keyed by the RLP encoding of their index. All trie nodes are retained while the
+/// root is computed, so the proof of every item can be assembled without rebuilding the trie.
+#[derive(Clone, Debug)]
+pub struct OrderedTrieProofs {
+    /// The root of the trie.
+    root: B256,
+    /// The number of items in the trie.
+    len: usize,
+    /// All nodes of the trie, by path.
+    nodes: BTreeMap<Nibbles, Bytes>,
+}
+
+impl OrderedTrieProofs {
+    /// Builds the trie of the given items, encoded with the given encoder, and retains all nodes.
+    pub fn new<T, F>(items: &[T], mut encode: F) -> Self
+    where
+        F: FnMut(&T, &mut dyn BufMut),
+    {
+        let len = items.len();
+        let targets = (0..len).map(Self::key).collect();
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(targets);
+
+        let mut value = Vec::new();
+        for i in 0..len {
+            let index = adjust_index_for_rlp(i, len);
+            value.clear();
+            encode(&items[index], &mut value);
+            hash_builder.add_leaf(Self::key(index), &value);
+        }
+
+        let root = hash_builder.root();
+        Self { root, len, nodes: hash_builder.take_proofs() }
+    }
+
+    /// Returns the trie key of the item at the given index.
+    pub fn key(index: usize) -> Nibbles {
+        let mut buf = Vec::with_capacity(9);
+        index.encode(&mut buf);
+        Nibbles::unpack(buf)
+    }
+
+    /// Returns the root of the trie.
+    pub fn root(&self) -> B256 {
+        self.root
+    }
+
+    /// Returns the number of items in the trie.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the trie has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the proof of the item at the given index, ordered from the root to the leaf.
+    ///
+    /// Returns `None` if the index is out of bounds.
+    pub fn proof(&self, index: usize) -> Option<Vec<Bytes>> {
+        if index >= self.len {
+            return None
+        }
+        let key = Self::key(index);
+        // the nodes are ordered by path, so the prefixes of the key are ordered from the root
+        Some(
+            self.nodes
+                .iter()
+                .filter(|(path, _)| key.starts_with(path))
+                .map(|(_, node)| node.clone())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{keccak256, proofs::ordered_trie_root};
+
+    #[test]
+    fn proofs_of_all_items() {
+        for len in [1usize, 2, 17, 130, 300] {
+            let items = (0..len as u64).map(|i| i * 1_000_003).collect::<Vec<_>>();
+            let proofs = OrderedTrieProofs::new(&items, |item, buf| item.encode(buf));
+            assert_eq!(proofs.root(), ordered_trie_root(&items));
+
+            for index in 0..len {
+                let proof = proofs.proof(index).unwrap();
+                // the first node is the root node
+                assert_eq!(keccak256(&proof[0]), proofs.root());
+
+                // the leaf contains the encoded item
+                let mut value = Vec::new();
+                items[index].encode(&mut value);
+                let leaf = proof.last().unwrap();
+                assert!(leaf.windows(value.len()).any(|window| window == value.as_slice()));
+            }
+            assert!(proofs.proof(len).is_none());
+        }
+    }
+
+    #[test]
+    fn key_is_rlp_index() {
+        assert_eq!(OrderedTrieProofs::key(0), Nibbles::unpack([0x80]));
+        assert_eq!(OrderedTrieProofs::key(1), Nibbles::unpack([0x01]));
+        assert_eq!(OrderedTrieProofs::key(128), Nibbles::unpack([0x81, 0x80]));
+    }
+}
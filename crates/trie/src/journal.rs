@@ -0,0 +1,370 @@
+use crate::updates::{TrieKey, TrieOp, TrieUpdates};
+use reth_db::{
+    table::{Compress, Decompress},
+    transaction::DbTx,
+    DatabaseError,
+};
+use reth_primitives::{
+    trie::{StoredBranchNode, StoredNibbles, StoredNibblesSubKey},
+    BlockNumHash, BlockNumber, B256,
+};
+use std::{collections::VecDeque, fs, io, path::Path};
+
+/// The version of the persisted journal format.
+const JOURNAL_VERSION: u8 = 1;
+
+/// The trie updates of a single block together with the updates that revert them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieJournalEntry {
+    /// The block the updates were produced by.
+    pub block: BlockNumHash,
+    /// The updates that were applied to the trie tables.
+    pub updates: TrieUpdates,
+    /// The updates that restore the trie tables to the state before the block.
+    pub reverts: TrieUpdates,
+}
+
+impl TrieJournalEntry {
+    /// Creates the journal entry for the block, reading the reverts from the current trie tables.
+    ///
+    /// Must be called before the updates are flushed to the database.
+    pub fn new<TX: DbTx>(
+        tx: &TX,
+        block: BlockNumHash,
+        updates: TrieUpdates,
+    ) -> Result<Self, DatabaseError> {
+        let reverts = updates.reverts(tx)?;
+        Ok(Self { block, updates, reverts })
+    }
+}
+
+/// A ring journal of the trie updates of the most recent blocks.
+///
+/// Blocks can be undone and redone in memory, which returns the [TrieUpdates] to apply to the
+/// trie tables, so shallow reorgs can be handled without recomputing the state root of the
+/// reverted blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieJournal {
+    /// The maximum number of blocks kept in the journal.
+    capacity: usize,
+    /// The applied blocks in ascending order.
+    applied: VecDeque<TrieJournalEntry>,
+    /// The undone blocks, the most recently undone block is last.
+    undone: Vec<TrieJournalEntry>,
+}
+
+impl TrieJournal {
+    /// Creates an empty journal that keeps at most `capacity` blocks.
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, applied: VecDeque::with_capacity(capacity), undone: Vec::new() }
+    }
+
+    /// Returns the number of applied blocks in the journal.
+    pub fn len(&self) -> usize {
+        self.applied.len()
+    }
+
+    /// Returns `true` if the journal has no applied blocks.
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty()
+    }
+
+    /// Returns the most recently applied block.
+    pub fn tip(&self) -> Option<BlockNumHash> {
+        self.applied.back().map(|entry| entry.block)
+    }
+
+    /// Records the entry of a newly applied block.
+    ///
+    /// All undone blocks are discarded, since they can no longer be redone. If the entry does not
+    /// extend the current tip, the journal is cleared first.
+    pub fn push(&mut self, entry: TrieJournalEntry) {
+        self.undone.clear();
+        if self.tip().is_some_and(|tip| tip.number + 1 != entry.block.number) {
+            self.applied.clear();
+        }
+        if self.applied.len() == self.capacity {
+            self.applied.pop_front();
+        }
+        if self.capacity > 0 {
+            self.applied.push_back(entry);
+        }
+    }
+
+    /// Undoes the most recently applied block and returns the updates that revert it.
+    pub fn undo(&mut self) -> Option<(BlockNumHash, TrieUpdates)> {
+        let entry = self.applied.pop_back()?;
+        let result = (entry.block, entry.reverts.clone());
+        self.undone.push(entry);
+        Some(result)
+    }
+
+    /// Redoes the most recently undone block and returns its updates.
+    pub fn redo(&mut self) -> Option<(BlockNumHash, TrieUpdates)> {
+        let entry = self.undone.pop()?;
+        let result = (entry.block, entry.updates.clone());
+        self.applied.push_back(entry);
+        Some(result)
+    }
+
+    /// Undoes all blocks above the given block and returns the aggregated updates that revert
+    /// them.
+    ///
+    /// Returns `None` and leaves the journal unchanged if the journal does not reach back to the
+    /// given block.
+    pub fn undo_to(&mut self, block_number: BlockNumber) -> Option<TrieUpdates> {
+        let first = self.applied.front()?.block.number;
+        if block_number + 1 < first || block_number > self.tip()?.number {
+            return None
+        }
+
+        // Reverts of older blocks take precedence, since they restore the earlier state.
+        let mut reverts = TrieUpdates::default();
+        while self.tip().is_some_and(|tip| tip.number > block_number) {
+            let (_, block_reverts) = self.undo().expect("tip exists");
+            reverts.extend(block_reverts.into_iter());
+        }
+        Some(reverts)
+    }
+
+    /// Encodes the applied blocks of the journal.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![JOURNAL_VERSION];
+        buf.extend_from_slice(&(self.applied.len() as u32).to_le_bytes());
+        for entry in &self.applied {
+            buf.extend_from_slice(&entry.block.number.to_le_bytes());
+            buf.extend_from_slice(entry.block.hash.as_slice());
+            encode_trie_updates(&entry.updates, &mut buf);
+            encode_trie_updates(&entry.reverts, &mut buf);
+        }
+        buf
+    }
+
+    /// Decodes a journal that was encoded with [TrieJournal::encode].
+    ///
+    /// If the journal contains more blocks than the capacity, only the most recent ones are kept.
+    pub fn decode(capacity: usize, buf: &[u8]) -> Option<Self> {
+        let mut reader = Reader(buf);
+        if reader.u8()? != JOURNAL_VERSION {
+            return None
+        }
+
+        let mut journal = Self::new(capacity);
+        for _ in 0..reader.u32()? {
+            let number = reader.u64()?;
+            let hash = B256::from_slice(reader.bytes(32)?);
+            let updates = decode_updates(&mut reader)?;
+            let reverts = decode_updates(&mut reader)?;
+            journal.push(TrieJournalEntry {
+                block: BlockNumHash { number, hash },
+                updates,
+                reverts,
+            });
+        }
+        reader.0.is_empty().then_some(journal)
+    }
+
+    /// Loads a persisted journal, returns `None` if the file does not exist or is invalid.
+    pub fn load(capacity: usize, path: &Path) -> io::Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(buf) => Ok(Self::decode(capacity, &buf)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Persists the journal to the given path.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        // write to a temporary file first, so a crash never leaves a truncated journal behind
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.encode())?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// Appends the encoding of the trie updates to the buffer, so the updates can be embedded in other
+/// persisted formats.
+pub fn encode_trie_updates(updates: &TrieUpdates, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(updates.len() as u32).to_le_bytes());
+    for (key, op) in updates.iter() {
+        match key {
+            TrieKey::AccountNode(nibbles) => {
+                buf.push(0);
+                encode_nibbles(&nibbles.0, buf);
+            }
+            TrieKey::StorageNode(hashed_address, nibbles) => {
+                buf.push(1);
+                buf.extend_from_slice(hashed_address.as_slice());
+                encode_nibbles(&nibbles.0, buf);
+            }
+            TrieKey::StorageTrie(hashed_address) => {
+                buf.push(2);
+                buf.extend_from_slice(hashed_address.as_slice());
+            }
+        }
+        match op {
+            TrieOp::Delete => buf.push(0),
+            TrieOp::Update(node) => {
+                buf.push(1);
+                let node = StoredBranchNode(node.clone()).compress();
+                let node = node.as_ref();
+                buf.extend_from_slice(&(node.len() as u32).to_le_bytes());
+                buf.extend_from_slice(node);
+            }
+        }
+    }
+}
+
+/// Decodes trie updates that were encoded with [encode_trie_updates] from the front of the
+/// buffer, advancing the buffer past them.
+pub fn decode_trie_updates(buf: &mut &[u8]) -> Option<TrieUpdates> {
+    let mut reader = Reader(buf);
+    let updates = decode_updates(&mut reader)?;
+    *buf = reader.0;
+    Some(updates)
+}
+
+/// Appends the length-prefixed nibbles to the buffer.
+pub(crate) fn encode_nibbles(nibbles: &[u8], buf: &mut Vec<u8>) {
+    buf.push(nibbles.len() as u8);
+    buf.extend_from_slice(nibbles);
+}
+
+fn decode_updates(reader: &mut Reader<'_>) -> Option<TrieUpdates> {
+    let mut updates = TrieUpdates::default();
+    for _ in 0..reader.u32()? {
+        let key = match reader.u8()? {
+            0 => TrieKey::AccountNode(StoredNibbles::from(reader.nibbles()?)),
+            1 => {
+                let hashed_address = B256::from_slice(reader.bytes(32)?);
+                TrieKey::StorageNode(hashed_address, StoredNibblesSubKey::from(reader.nibbles()?))
+            }
+            2 => TrieKey::StorageTrie(B256::from_slice(reader.bytes(32)?)),
+            _ => return None,
+        };
+        let op = match reader.u8()? {
+            0 => TrieOp::Delete,
+            1 => {
+                let len = reader.u32()? as usize;
+                TrieOp::Update(StoredBranchNode::decompress(reader.bytes(len)?).ok()?.0)
+            }
+            _ => return None,
+        };
+        updates.extend(std::iter::once((key, op)));
+    }
+    Some(updates)
+}
+
+/// A cursor over an encoded journal, or other persisted formats with the same encoding.
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    pub(crate) fn nibbles(&mut self) -> Option<Vec<u8>> {
+        let len = self.u8()? as usize;
+        let nibbles = self.bytes(len)?;
+        nibbles.iter().all(|nibble| *nibble <= 0xf).then(|| nibbles.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StateRoot;
+    use reth_db::{
+        cursor::DbCursorRO, database::Database, tables, test_utils::create_test_rw_db,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{
+        keccak256,
+        trie::{StorageTrieEntry, StoredNibbles},
+        Account, StorageEntry, U256,
+    };
+
+    fn insert_block(tx: &(impl DbTx + DbTxMut), number: u64) -> (B256, TrieJournalEntry) {
+        for i in 0..16u64 {
+            let hashed_address = keccak256((number * 16 + i).to_be_bytes());
+            let account = Account { nonce: number, balance: U256::from(i), bytecode_hash: None };
+            tx.put::<tables::HashedAccount>(hashed_address, account).unwrap();
+            tx.put::<tables::HashedStorage>(
+                keccak256([number as u8]),
+                StorageEntry { key: hashed_address, value: U256::from(number + 1) },
+            )
+            .unwrap();
+        }
+        let (root, updates) = StateRoot::from_tx(tx).root_with_updates().unwrap();
+        let block = BlockNumHash { number, hash: B256::with_last_byte(number as u8) };
+        let entry = TrieJournalEntry::new(tx, block, updates.clone()).unwrap();
+        updates.flush(tx).unwrap();
+        (root, entry)
+    }
+
+    type TrieTables = (Vec<(StoredNibbles, StoredBranchNode)>, Vec<(B256, StorageTrieEntry)>);
+
+    fn trie_tables(tx: &impl DbTx) -> TrieTables {
+        let accounts = tx.cursor_read::<tables::AccountsTrie>().unwrap().walk(None).unwrap();
+        let storages = tx.cursor_read::<tables::StoragesTrie>().unwrap().walk(None).unwrap();
+        (accounts.collect::<Result<_, _>>().unwrap(), storages.collect::<Result<_, _>>().unwrap())
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        let mut journal = TrieJournal::new(2);
+
+        let (_, entry) = insert_block(&tx, 0);
+        journal.push(entry);
+        let tables_0 = trie_tables(&tx);
+
+        let (_, entry) = insert_block(&tx, 1);
+        journal.push(entry);
+        let (root_2, entry) = insert_block(&tx, 2);
+        journal.push(entry);
+        let tables_2 = trie_tables(&tx);
+        assert_eq!(journal.len(), 2);
+        assert_eq!(journal.tip().map(|tip| tip.number), Some(2));
+
+        let decoded = TrieJournal::decode(2, &journal.encode()).unwrap();
+        assert_eq!(decoded, journal);
+
+        // block 0 was evicted
+        assert_eq!(journal.clone().undo_to(0).map(|reverts| reverts.is_empty()), Some(false));
+        assert!(journal.clone().undo_to(3).is_none());
+
+        let (block, reverts) = journal.undo().unwrap();
+        assert_eq!(block.number, 2);
+        reverts.flush(&tx).unwrap();
+        journal.undo().unwrap().1.flush(&tx).unwrap();
+        assert_eq!(trie_tables(&tx), tables_0);
+        assert!(journal.undo().is_none());
+
+        let (block, updates) = journal.redo().unwrap();
+        assert_eq!(block.number, 1);
+        updates.flush(&tx).unwrap();
+        journal.redo().unwrap().1.flush(&tx).unwrap();
+
+        assert_eq!(trie_tables(&tx), tables_2);
+        assert_eq!(StateRoot::from_tx(&tx).root().unwrap(), root_2);
+    }
+}
@@ -0,0 +1,381 @@
+use ahash::RandomState;
+use rayon::prelude::*;
+use reth_primitives::{keccak256, Address, B256};
+use revm::primitives::State as EvmState;
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    io,
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// The minimum number of inputs for which [keccak256_batch] hashes in parallel.
+pub const PARALLEL_BATCH_THRESHOLD: usize = 1024;
+
+/// Hashes all inputs and returns the hashes in the same order.
+///
+/// Large batches are split across the rayon thread pool, which makes this preferable over hashing
+/// in a loop whenever many independent inputs are available at once.
+pub fn keccak256_batch<T: AsRef<[u8]> + Sync>(inputs: &[T]) -> Vec<B256> {
+    if inputs.len() < PARALLEL_BATCH_THRESHOLD {
+        inputs.iter().map(keccak256).collect()
+    } else {
+        inputs.par_iter().map(keccak256).collect()
+    }
+}
+
+/// A backend that hashes batches of independent inputs, e.g. on a GPU.
+///
+/// [CpuKeccakBackend] is the default. A backend that can't hash a batch, e.g. because it is too
+/// small to be worth the transfer or the device is unavailable, returns `None` and the batch is
+/// hashed with [keccak256_batch] instead.
+pub trait KeccakBackend: fmt::Debug + Send + Sync {
+    /// Hashes all inputs and returns the hashes in the same order, or `None` if the batch has to
+    /// be hashed on the CPU.
+    fn try_keccak256_batch(&self, inputs: &[&[u8]]) -> Option<Vec<B256>>;
+}
+
+/// Hashes batches on the CPU with [keccak256_batch].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuKeccakBackend;
+
+impl KeccakBackend for CpuKeccakBackend {
+    fn try_keccak256_batch(&self, inputs: &[&[u8]]) -> Option<Vec<B256>> {
+        Some(keccak256_batch(inputs))
+    }
+}
+
+/// Hashes all inputs with the given backend, falling back to the CPU if the backend can't hash
+/// the batch or its output is invalid.
+///
+/// The output of the backend is invalid if it has the wrong number of hashes, or if the hash of
+/// the first input, which is recomputed on the CPU as a spot check, doesn't match.
+pub fn keccak256_batch_with(backend: &dyn KeccakBackend, inputs: &[&[u8]]) -> Vec<B256> {
+    let Some(hashes) = backend.try_keccak256_batch(inputs) else { return keccak256_batch(inputs) };
+    if hashes.len() != inputs.len() {
+        warn!(
+            target: "trie::keccak",
+            ?backend,
+            expected = inputs.len(),
+            got = hashes.len(),
+            "Keccak backend returned the wrong number of hashes, hashing on the CPU"
+        );
+        return keccak256_batch(inputs)
+    }
+    if let (Some(input), Some(hash)) = (inputs.first(), hashes.first()) {
+        if keccak256(input) != *hash {
+            warn!(target: "trie::keccak", ?backend, "Keccak backend returned a wrong hash, hashing on the CPU");
+            return keccak256_batch(inputs)
+        }
+    }
+    hashes
+}
+
+/// A small direct-mapped cache of recently hashed fixed-size inputs.
+///
+/// Hot addresses and storage slots are hashed again for every block, the cache avoids repeating
+/// that work. Every input maps to exactly one entry, so a colliding input evicts the previous one.
+#[derive(Debug, Clone)]
+pub struct KeccakCache<const N: usize> {
+    /// The cached inputs and their hashes.
+    entries: Vec<Option<([u8; N], B256)>>,
+    /// The hasher used to map inputs to entries.
+    hasher: RandomState,
+    /// The number of cache hits.
+    hits: u64,
+    /// The number of cache misses.
+    misses: u64,
+}
+
+impl<const N: usize> KeccakCache<N> {
+    /// Creates a new cache with at least the given number of entries, rounded up to a power of
+    /// two.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: vec![None; capacity.max(1).next_power_of_two()],
+            hasher: RandomState::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the hash of the input, computing it if it is not cached.
+    pub fn keccak256(&mut self, input: [u8; N]) -> B256 {
+        let idx = self.hasher.hash_one(input) as usize & (self.entries.len() - 1);
+        match &self.entries[idx] {
+            Some((cached, hash)) if *cached == input => {
+                self.hits += 1;
+                *hash
+            }
+            _ => {
+                self.misses += 1;
+                let hash = keccak256(input);
+                self.entries[idx] = Some((input, hash));
+                hash
+            }
+        }
+    }
+
+    /// Returns the number of cache hits and misses.
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+}
+
+/// Hashes of addresses and storage slots computed ahead of time by a [KeyHasher].
+#[derive(Debug, Clone)]
+pub struct PrehashedKeys {
+    addresses: HashMap<Address, B256>,
+    slots: HashMap<B256, B256>,
+    /// The time spent hashing.
+    hashing: Duration,
+    /// The backend the keys that were not hashed ahead of time are hashed with.
+    backend: Arc<dyn KeccakBackend>,
+}
+
+impl Default for PrehashedKeys {
+    fn default() -> Self {
+        Self {
+            addresses: HashMap::default(),
+            slots: HashMap::default(),
+            hashing: Duration::default(),
+            backend: Arc::new(CpuKeccakBackend),
+        }
+    }
+}
+
+impl PrehashedKeys {
+    /// Sets the backend the keys that were not hashed ahead of time are hashed with.
+    pub fn with_backend(mut self, backend: Arc<dyn KeccakBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Returns the number of hashed addresses and storage slots.
+    pub fn len(&self) -> usize {
+        self.addresses.len() + self.slots.len()
+    }
+
+    /// Returns `true` if nothing was hashed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the time the [KeyHasher] spent hashing.
+    pub fn hashing_duration(&self) -> Duration {
+        self.hashing
+    }
+
+    /// Returns the hashes of the addresses in the same order, hashing the ones that were not
+    /// hashed ahead of time with the [KeccakBackend].
+    pub fn hash_addresses(&self, addresses: &[Address]) -> Vec<B256> {
+        hash_missing(self.backend.as_ref(), &self.addresses, addresses)
+    }
+
+    /// Returns the hashes of the storage slots in the same order, hashing the ones that were not
+    /// hashed ahead of time with the [KeccakBackend].
+    pub fn hash_slots(&self, slots: &[B256]) -> Vec<B256> {
+        hash_missing(self.backend.as_ref(), &self.slots, slots)
+    }
+}
+
+fn hash_missing<K>(
+    backend: &dyn KeccakBackend,
+    prehashed: &HashMap<K, B256>,
+    keys: &[K],
+) -> Vec<B256>
+where
+    K: AsRef<[u8]> + Copy + Eq + Hash + Sync,
+{
+    let missing = keys
+        .iter()
+        .filter(|key| !prehashed.contains_key(key))
+        .map(AsRef::<[u8]>::as_ref)
+        .collect::<Vec<_>>();
+    // missing keys are hashed in the order they appear in
+    let mut hashed = keccak256_batch_with(backend, &missing).into_iter();
+    keys.iter()
+        .map(|key| match prehashed.get(key) {
+            Some(hash) => *hash,
+            None => hashed.next().expect("all missing keys are hashed"),
+        })
+        .collect()
+}
+
+enum KeyHasherMessage {
+    Keys { addresses: Vec<Address>, slots: Vec<B256> },
+    Finish,
+}
+
+/// Sends keys to a [KeyHasher].
+#[derive(Debug, Clone)]
+pub struct KeySender(mpsc::Sender<KeyHasherMessage>);
+
+impl KeySender {
+    /// Sends the addresses of the touched accounts and the changed storage slots of the given
+    /// state changes, e.g. of an executed transaction.
+    pub fn send_state(&self, state: &EvmState) {
+        let mut addresses = Vec::with_capacity(state.len());
+        let mut slots = Vec::new();
+        for (address, account) in state.iter().filter(|(_, account)| account.is_touched()) {
+            addresses.push(*address);
+            slots.extend(
+                account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .map(|(key, _)| B256::new(key.to_be_bytes())),
+            );
+        }
+        self.send(addresses, slots)
+    }
+
+    /// Sends addresses and storage slots to hash.
+    pub fn send(&self, addresses: Vec<Address>, slots: Vec<B256>) {
+        // keys sent after the hasher finished are not needed anymore
+        let _ = self.0.send(KeyHasherMessage::Keys { addresses, slots });
+    }
+}
+
+/// Hashes addresses and storage slots on a background thread while they are produced, e.g. by
+/// executing a block, so building the hashed post state afterwards does not have to hash them.
+#[derive(Debug)]
+pub struct KeyHasher {
+    sender: KeySender,
+    handle: JoinHandle<PrehashedKeys>,
+}
+
+impl KeyHasher {
+    /// Spawns the hashing thread.
+    pub fn spawn() -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::Builder::new().name("key-hasher".to_string()).spawn(move || {
+            let mut keys = PrehashedKeys::default();
+            for message in rx {
+                let KeyHasherMessage::Keys { addresses, slots } = message else { break };
+                let started_at = Instant::now();
+                for address in addresses {
+                    keys.addresses.entry(address).or_insert_with(|| keccak256(address));
+                }
+                for slot in slots {
+                    keys.slots.entry(slot).or_insert_with(|| keccak256(slot));
+                }
+                keys.hashing += started_at.elapsed();
+            }
+            keys
+        })?;
+        Ok(Self { sender: KeySender(tx), handle })
+    }
+
+    /// Returns a sender for keys to hash.
+    pub fn sender(&self) -> KeySender {
+        self.sender.clone()
+    }
+
+    /// Waits until all keys sent so far are hashed and returns their hashes.
+    pub fn finish(self) -> PrehashedKeys {
+        let _ = self.sender.0.send(KeyHasherMessage::Finish);
+        self.handle.join().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_matches_sequential() {
+        for len in [0, 1, PARALLEL_BATCH_THRESHOLD + 1] {
+            let inputs = (0..len as u64).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+            let expected = inputs.iter().map(keccak256).collect::<Vec<_>>();
+            assert_eq!(keccak256_batch(&inputs), expected);
+        }
+    }
+
+    #[test]
+    fn backend_falls_back_to_cpu() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// A backend that counts the batches it was asked to hash and returns the given output.
+        #[derive(Debug)]
+        struct TestBackend {
+            calls: AtomicUsize,
+            output: fn(&[&[u8]]) -> Option<Vec<B256>>,
+        }
+
+        impl KeccakBackend for TestBackend {
+            fn try_keccak256_batch(&self, inputs: &[&[u8]]) -> Option<Vec<B256>> {
+                self.calls.fetch_add(1, Ordering::Relaxed);
+                (self.output)(inputs)
+            }
+        }
+
+        let inputs = (0..4u64).map(|i| i.to_be_bytes()).collect::<Vec<_>>();
+        let inputs = inputs.iter().map(|input| input.as_slice()).collect::<Vec<_>>();
+        let expected = inputs.iter().map(keccak256).collect::<Vec<_>>();
+        assert_eq!(keccak256_batch_with(&CpuKeccakBackend, &inputs), expected);
+
+        let outputs: [fn(&[&[u8]]) -> Option<Vec<B256>>; 4] = [
+            // can't hash the batch
+            |_| None,
+            // too few hashes
+            |inputs| Some(keccak256_batch(&inputs[1..])),
+            // wrong hashes
+            |inputs| Some(vec![B256::ZERO; inputs.len()]),
+            // correct hashes, but the first
+            |inputs| {
+                let mut hashes = keccak256_batch(inputs);
+                hashes[0] = B256::ZERO;
+                Some(hashes)
+            },
+        ];
+        for output in outputs {
+            let backend = TestBackend { calls: AtomicUsize::new(0), output };
+            assert_eq!(keccak256_batch_with(&backend, &inputs), expected);
+            assert_eq!(backend.calls.load(Ordering::Relaxed), 1);
+        }
+
+        // the keys that were not hashed ahead of time are hashed with the backend
+        let backend = Arc::new(TestBackend { calls: AtomicUsize::new(0), output: |_| None });
+        let keys = PrehashedKeys::default().with_backend(backend.clone());
+        let address = Address::with_last_byte(1);
+        assert_eq!(keys.hash_addresses(&[address, address]), vec![keccak256(address); 2]);
+        assert_eq!(backend.calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn cache_hits() {
+        let mut cache = KeccakCache::<8>::new(16);
+        let input = 1u64.to_be_bytes();
+        assert_eq!(cache.keccak256(input), keccak256(input));
+        assert_eq!(cache.keccak256(input), keccak256(input));
+        assert_eq!(cache.stats(), (1, 1));
+
+        for i in 0..64u64 {
+            assert_eq!(cache.keccak256(i.to_be_bytes()), keccak256(i.to_be_bytes()));
+        }
+    }
+
+    #[test]
+    fn key_hasher_prehashes_sent_keys() {
+        let hasher = KeyHasher::spawn().unwrap();
+        let sender = hasher.sender();
+        let (a, b) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let slot = B256::with_last_byte(3);
+        sender.send(vec![a], vec![slot]);
+        sender.send(vec![a], Vec::new());
+        let keys = hasher.finish();
+        assert_eq!(keys.len(), 2);
+
+        // keys that were not sent are hashed on demand
+        assert_eq!(keys.hash_addresses(&[b, a]), vec![keccak256(b), keccak256(a)]);
+        assert_eq!(keys.hash_slots(&[slot]), vec![keccak256(slot)]);
+
+        // the sender outlives the hasher
+        sender.send(vec![b], Vec::new());
+    }
+}
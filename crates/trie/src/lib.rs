@@ -26,6 +26,12 @@ pub mod hashed_cursor;
 /// The trie walker for iterating over the trie nodes.
 pub mod walker;
 
+/// Accelerated nibble operations.
+pub mod nibbles;
+
+/// Batched and cached keccak hashing.
+pub mod keccak;
+
 mod errors;
 pub use errors::*;
 
@@ -39,6 +45,32 @@ pub use state::{HashedPostState, HashedStorage};
 /// Merkle proof generation.
 pub mod proof;
 
+/// Inclusion proofs for ordered tries.
+mod inclusion;
+pub use inclusion::OrderedTrieProofs;
+
+/// Proof node retention with bounded memory.
+pub mod spill;
+
+/// Trie node providers with fallback tiers.
+pub mod node_provider;
+
+/// Sparse tries revealed from the trie nodes of an execution witness.
+pub mod witness;
+
+/// Cursor caches shared by state root computations over the same state.
+pub mod cached_cursors;
+
+/// Storage roots computed ahead of state root computations.
+pub mod precomputed;
+
+/// Fixtures for replaying state root computations offline.
+pub mod fixture;
+
+/// Views of the state trie at recent historical blocks.
+mod view;
+pub use view::{HistoricalTrieView, TrieViews};
+
 /// The implementation of the Merkle Patricia Trie.
 mod trie;
 pub use trie::{StateRoot, StorageRoot};
@@ -46,6 +78,12 @@ pub use trie::{StateRoot, StorageRoot};
 /// Buffer for trie updates.
 pub mod updates;
 
+/// Journal of trie updates of recent blocks.
+pub mod journal;
+
+/// Latency objectives of state root computations.
+pub mod slo;
+
 /// Utilities for state root checkpoint progress.
 mod progress;
 pub use progress::{IntermediateStateRootState, StateRootProgress};
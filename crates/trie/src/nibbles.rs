@@ -0,0 +1,228 @@
+//! Accelerated implementations of hot [Nibbles] operations.
+//!
+//! On `x86_64`, unpacking and packing use SSE2, which is part of the baseline instruction set,
+//! and the common prefix length uses AVX2 if it is detected at runtime, falling back to SSE2. All
+//! other targets use the scalar implementations, which are also used for the remainder of inputs
+//! that do not fill a vector register.
+
+use reth_primitives::trie::Nibbles;
+
+/// Unpacks the bytes into [Nibbles], one nibble per byte.
+///
+/// Equivalent to [Nibbles::unpack].
+#[inline]
+pub fn unpack(bytes: impl AsRef<[u8]>) -> Nibbles {
+    let bytes = bytes.as_ref();
+    // Hashed keys are 32 bytes long, unpack them without a heap allocation.
+    if bytes.len() <= 32 {
+        let mut nibbles = [0; 64];
+        unpack_into(bytes, &mut nibbles[..bytes.len() * 2]);
+        Nibbles::from_nibbles_unchecked(&nibbles[..bytes.len() * 2])
+    } else {
+        let mut nibbles = vec![0; bytes.len() * 2];
+        unpack_into(bytes, &mut nibbles);
+        Nibbles::from_nibbles_unchecked(nibbles)
+    }
+}
+
+/// Unpacks the bytes into the output, which must be twice as long as the input.
+#[inline]
+pub fn unpack_into(bytes: &[u8], out: &mut [u8]) {
+    assert_eq!(out.len(), bytes.len() * 2, "output must be twice as long as the input");
+    imp::unpack(bytes, out)
+}
+
+/// Packs the nibbles into bytes, padding an odd number of nibbles with a zero nibble.
+///
+/// Equivalent to [Nibbles::pack].
+#[inline]
+pub fn pack(nibbles: &[u8]) -> Vec<u8> {
+    let mut out = vec![0; (nibbles.len() + 1) / 2];
+    imp::pack(nibbles, &mut out);
+    out
+}
+
+/// Returns the length of the common prefix of the two nibble sequences.
+///
+/// Equivalent to [Nibbles::common_prefix_length].
+#[inline]
+pub fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+    imp::common_prefix_length(a, b)
+}
+
+#[cfg(target_arch = "x86_64")]
+use x86 as imp;
+
+#[cfg(not(target_arch = "x86_64"))]
+use scalar as imp;
+
+/// The scalar implementations.
+mod scalar {
+    pub(super) fn unpack(bytes: &[u8], out: &mut [u8]) {
+        for (byte, nibbles) in bytes.iter().zip(out.chunks_exact_mut(2)) {
+            nibbles[0] = byte >> 4;
+            nibbles[1] = byte & 0x0f;
+        }
+    }
+
+    pub(super) fn pack(nibbles: &[u8], out: &mut [u8]) {
+        for (pair, byte) in nibbles.chunks(2).zip(out.iter_mut()) {
+            *byte = (pair[0] << 4) | pair.get(1).copied().unwrap_or(0);
+        }
+    }
+
+    pub(super) fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+        a.iter().zip(b).take_while(|(a, b)| a == b).count()
+    }
+}
+
+/// The `x86_64` implementations.
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::scalar;
+    use std::arch::x86_64::*;
+
+    pub(super) fn unpack(bytes: &[u8], out: &mut [u8]) {
+        // SAFETY: SSE2 is part of the x86_64 baseline and the output length was checked.
+        unsafe { unpack_sse2(bytes, out) }
+    }
+
+    pub(super) fn pack(nibbles: &[u8], out: &mut [u8]) {
+        // SAFETY: SSE2 is part of the x86_64 baseline and the output length is correct.
+        unsafe { pack_sse2(nibbles, out) }
+    }
+
+    pub(super) fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was detected at runtime.
+            unsafe { common_prefix_length_avx2(a, b) }
+        } else {
+            // SAFETY: SSE2 is part of the x86_64 baseline.
+            unsafe { common_prefix_length_sse2(a, b) }
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The output must be twice as long as the input.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn unpack_sse2(bytes: &[u8], out: &mut [u8]) {
+        let mask = _mm_set1_epi8(0x0f);
+        let chunks = bytes.len() / 16;
+        for i in 0..chunks {
+            let v = _mm_loadu_si128(bytes.as_ptr().add(i * 16) as *const __m128i);
+            let hi = _mm_and_si128(_mm_srli_epi16(v, 4), mask);
+            let lo = _mm_and_si128(v, mask);
+            let out = out.as_mut_ptr().add(i * 32);
+            _mm_storeu_si128(out as *mut __m128i, _mm_unpacklo_epi8(hi, lo));
+            _mm_storeu_si128(out.add(16) as *mut __m128i, _mm_unpackhi_epi8(hi, lo));
+        }
+        scalar::unpack(&bytes[chunks * 16..], &mut out[chunks * 32..]);
+    }
+
+    /// # Safety
+    ///
+    /// The output must be half as long as the input, rounded up.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn pack_sse2(nibbles: &[u8], out: &mut [u8]) {
+        let mask = _mm_set1_epi16(0x00ff);
+        let chunks = nibbles.len() / 32;
+        for i in 0..chunks {
+            let input = nibbles.as_ptr().add(i * 32);
+            let a = pack_lanes(_mm_loadu_si128(input as *const __m128i), mask);
+            let b = pack_lanes(_mm_loadu_si128(input.add(16) as *const __m128i), mask);
+            _mm_storeu_si128(out.as_mut_ptr().add(i * 16) as *mut __m128i, _mm_packus_epi16(a, b));
+        }
+        scalar::pack(&nibbles[chunks * 32..], &mut out[chunks * 16..]);
+    }
+
+    /// Each 16-bit lane holds a high nibble in its low byte and a low nibble in its high byte,
+    /// combines them into the low byte of the lane.
+    #[target_feature(enable = "sse2")]
+    unsafe fn pack_lanes(v: __m128i, mask: __m128i) -> __m128i {
+        _mm_and_si128(_mm_or_si128(_mm_slli_epi16(v, 4), _mm_srli_epi16(v, 8)), mask)
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support SSE2.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn common_prefix_length_sse2(a: &[u8], b: &[u8]) -> usize {
+        let len = a.len().min(b.len());
+        let mut offset = 0;
+        while offset + 16 <= len {
+            let va = _mm_loadu_si128(a.as_ptr().add(offset) as *const __m128i);
+            let vb = _mm_loadu_si128(b.as_ptr().add(offset) as *const __m128i);
+            let equal = _mm_movemask_epi8(_mm_cmpeq_epi8(va, vb)) as u32;
+            if equal != 0xffff {
+                return offset + equal.trailing_ones() as usize
+            }
+            offset += 16;
+        }
+        offset + scalar::common_prefix_length(&a[offset..len], &b[offset..len])
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn common_prefix_length_avx2(a: &[u8], b: &[u8]) -> usize {
+        let len = a.len().min(b.len());
+        let mut offset = 0;
+        while offset + 32 <= len {
+            let va = _mm256_loadu_si256(a.as_ptr().add(offset) as *const __m256i);
+            let vb = _mm256_loadu_si256(b.as_ptr().add(offset) as *const __m256i);
+            let equal = _mm256_movemask_epi8(_mm256_cmpeq_epi8(va, vb)) as u32;
+            if equal != u32::MAX {
+                return offset + equal.trailing_ones() as usize
+            }
+            offset += 32;
+        }
+        offset + scalar::common_prefix_length(&a[offset..len], &b[offset..len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    fn nibbles() -> impl Strategy<Value = Vec<u8>> {
+        vec(0..16u8, 0..150)
+    }
+
+    proptest! {
+        #[test]
+        fn unpack_matches_scalar(bytes in vec(any::<u8>(), 0..80)) {
+            let mut expected = vec![0; bytes.len() * 2];
+            scalar::unpack(&bytes, &mut expected);
+            let nibbles = unpack(&bytes);
+            prop_assert_eq!(&nibbles[..], &expected[..]);
+            prop_assert_eq!(nibbles, Nibbles::unpack(&bytes));
+        }
+
+        #[test]
+        fn pack_matches_scalar(nibbles in nibbles()) {
+            let mut expected = vec![0; (nibbles.len() + 1) / 2];
+            scalar::pack(&nibbles, &mut expected);
+            let packed = pack(&nibbles);
+            prop_assert_eq!(&packed, &expected);
+            prop_assert_eq!(&packed[..], &Nibbles::from_nibbles_unchecked(&nibbles).pack()[..]);
+        }
+
+        #[test]
+        fn common_prefix_length_matches_scalar(a in nibbles(), b in nibbles(), shared in nibbles()) {
+            let a = [shared.clone(), a].concat();
+            let b = [shared, b].concat();
+            let expected = scalar::common_prefix_length(&a, &b);
+            prop_assert_eq!(common_prefix_length(&a, &b), expected);
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: SSE2 is available on all x86_64 targets.
+            prop_assert_eq!(unsafe { x86::common_prefix_length_sse2(&a, &b) }, expected);
+            prop_assert_eq!(
+                expected,
+                Nibbles::from_nibbles_unchecked(&a).common_prefix_length(&b)
+            );
+        }
+    }
+}
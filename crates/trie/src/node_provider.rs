@@ -0,0 +1,629 @@
+//! Providers of trie nodes by path, for tries that are only partially available locally.
+//!
+//! A [FallbackTrieNodeProvider] asks a chain of providers in order, for example an in-memory
+//! cache, the local database and a remote node, and records hits, misses and errors per tier.
+//! [TrieNodeProviderCursorFactory] reads the trie through a provider, so state roots can be
+//! computed with nodes that are missing locally.
+
+use crate::{
+    trie_cursor::{TrieCursor, TrieCursorFactory},
+    updates::TrieKey,
+};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    tables,
+    transaction::DbTx,
+    DatabaseError,
+};
+use reth_metrics::{metrics::Counter, Metrics};
+use reth_primitives::{
+    trie::{BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey},
+    B256,
+};
+use std::{collections::HashMap, fmt, sync::RwLock};
+use tracing::debug;
+
+/// Error returned by a [TrieNodeProvider].
+#[derive(thiserror::Error, Debug)]
+pub enum TrieNodeProviderError {
+    /// Internal database error.
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+    /// Error of a remote provider.
+    #[error("remote trie node provider error: {0}")]
+    Remote(String),
+    /// The provider serves the trie of another state.
+    #[error("trie node provider is at state root {got}, expected {expected}")]
+    StateRootMismatch {
+        /// The state root the provider serves.
+        got: B256,
+        /// The requested state root.
+        expected: B256,
+    },
+}
+
+impl From<TrieNodeProviderError> for DatabaseError {
+    fn from(err: TrieNodeProviderError) -> Self {
+        match err {
+            TrieNodeProviderError::Database(err) => err,
+            err => DatabaseError::Other(err.to_string()),
+        }
+    }
+}
+
+/// Provides branch nodes of the account and storage tries by path.
+///
+/// `Ok(None)` means the provider does not have the node, either because the node does not exist
+/// or because the provider only holds part of the trie. Providers that hold part of the trie are
+/// expected to hold complete subtrees.
+#[auto_impl::auto_impl(&, Box, Arc)]
+pub trait TrieNodeProvider: Send + Sync {
+    /// Returns the first account trie node, in path order, whose path starts with `prefix`.
+    fn seek_account_node(
+        &self,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError>;
+
+    /// Returns the first storage trie node of the account, in path order, whose path starts with
+    /// `prefix`.
+    fn seek_storage_node(
+        &self,
+        hashed_address: B256,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError>;
+
+    /// Returns the account trie node at the given path.
+    fn account_node(
+        &self,
+        path: &Nibbles,
+    ) -> Result<Option<BranchNodeCompact>, TrieNodeProviderError> {
+        Ok(self.seek_account_node(path)?.filter(|(found, _)| found == path).map(|(_, node)| node))
+    }
+
+    /// Returns the storage trie node of the account at the given path.
+    fn storage_node(
+        &self,
+        hashed_address: B256,
+        path: &Nibbles,
+    ) -> Result<Option<BranchNodeCompact>, TrieNodeProviderError> {
+        Ok(self
+            .seek_storage_node(hashed_address, path)?
+            .filter(|(found, _)| found == path)
+            .map(|(_, node)| node))
+    }
+}
+
+/// A [TrieNodeProvider] that serves the trie of a single state at a time, e.g. a remote node
+/// that pinned a state.
+pub trait StateRootTrieNodeProvider: TrieNodeProvider + fmt::Debug {
+    /// Makes the provider serve the trie with the given state root.
+    ///
+    /// Returns [TrieNodeProviderError::StateRootMismatch] if the provider can't serve that state,
+    /// in which case its nodes must not be mixed with the nodes of the given state.
+    fn ensure_state_root(&self, state_root: B256) -> Result<(), TrieNodeProviderError>;
+}
+
+/// A [TrieNodeProvider] that reads nodes from the trie tables.
+#[derive(Debug)]
+pub struct DatabaseTrieNodeProvider<'a, TX> {
+    tx: &'a TX,
+}
+
+impl<'a, TX> DatabaseTrieNodeProvider<'a, TX> {
+    /// Creates a new provider reading from the given transaction.
+    pub fn new(tx: &'a TX) -> Self {
+        Self { tx }
+    }
+}
+
+impl<'a, TX: DbTx> TrieNodeProvider for DatabaseTrieNodeProvider<'a, TX> {
+    fn seek_account_node(
+        &self,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        Ok(self
+            .tx
+            .cursor_read::<tables::AccountsTrie>()?
+            .seek(StoredNibbles(prefix.clone()))?
+            .map(|(path, node)| (path.0, node.0))
+            .filter(|(path, _)| path.starts_with(prefix)))
+    }
+
+    fn seek_storage_node(
+        &self,
+        hashed_address: B256,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        Ok(self
+            .tx
+            .cursor_dup_read::<tables::StoragesTrie>()?
+            .seek_by_key_subkey(hashed_address, StoredNibblesSubKey(prefix.clone()))?
+            .map(|entry| (entry.nibbles.0, entry.node))
+            .filter(|(path, _)| path.starts_with(prefix)))
+    }
+}
+
+/// The nodes found by the tiers of a [FallbackTrieNodeProvider], by the path they were looked up
+/// with.
+///
+/// Keying by the sought path keeps the cache correct although it only holds part of the trie: a
+/// cached node is only returned for the lookup that found it.
+#[derive(Debug, Default)]
+pub struct TrieNodeCache {
+    account_nodes: RwLock<HashMap<Nibbles, (Nibbles, BranchNodeCompact)>>,
+    storage_nodes: RwLock<HashMap<(B256, Nibbles), (Nibbles, BranchNodeCompact)>>,
+}
+
+impl TrieNodeCache {
+    /// Removes all nodes.
+    pub fn clear(&self) {
+        self.account_nodes.write().expect("not poisoned").clear();
+        self.storage_nodes.write().expect("not poisoned").clear();
+    }
+
+    fn insert_account_node(&self, prefix: Nibbles, entry: (Nibbles, BranchNodeCompact)) {
+        self.account_nodes.write().expect("not poisoned").insert(prefix, entry);
+    }
+
+    fn insert_storage_node(
+        &self,
+        hashed_address: B256,
+        prefix: Nibbles,
+        entry: (Nibbles, BranchNodeCompact),
+    ) {
+        self.storage_nodes.write().expect("not poisoned").insert((hashed_address, prefix), entry);
+    }
+}
+
+impl TrieNodeProvider for TrieNodeCache {
+    fn seek_account_node(
+        &self,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        Ok(self.account_nodes.read().expect("not poisoned").get(prefix).cloned())
+    }
+
+    fn seek_storage_node(
+        &self,
+        hashed_address: B256,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        Ok(self
+            .storage_nodes
+            .read()
+            .expect("not poisoned")
+            .get(&(hashed_address, prefix.clone()))
+            .cloned())
+    }
+}
+
+/// A [TrieNodeProvider] that asks a chain of providers in order and returns the first node found.
+///
+/// Nodes found by a later tier are inserted into the cache, which is always asked first. A tier
+/// that fails is skipped, the error is only returned if no later tier has the node either.
+pub struct FallbackTrieNodeProvider<'a> {
+    cache: TrieNodeCache,
+    cache_metrics: TrieNodeProviderTierMetrics,
+    tiers: Vec<(&'static str, Box<dyn TrieNodeProvider + 'a>, TrieNodeProviderTierMetrics)>,
+}
+
+impl<'a> FallbackTrieNodeProvider<'a> {
+    /// Creates a new provider that only has the cache tier.
+    pub fn new() -> Self {
+        Self {
+            cache: TrieNodeCache::default(),
+            cache_metrics: TrieNodeProviderTierMetrics::new_with_labels(&[("tier", "cache")]),
+            tiers: Vec::new(),
+        }
+    }
+
+    /// Appends a tier to the chain, the tier name is used as the label of its metrics.
+    pub fn with_tier(mut self, name: &'static str, provider: impl TrieNodeProvider + 'a) -> Self {
+        let metrics = TrieNodeProviderTierMetrics::new_with_labels(&[("tier", name)]);
+        self.tiers.push((name, Box::new(provider), metrics));
+        self
+    }
+
+    /// Returns the cache tier.
+    pub fn cache(&self) -> &TrieNodeCache {
+        &self.cache
+    }
+
+    /// Asks all tiers in order until one returns a node.
+    fn lookup<F>(
+        &self,
+        get: F,
+        insert: impl FnOnce(&TrieNodeCache, (Nibbles, BranchNodeCompact)),
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError>
+    where
+        F: Fn(
+            &dyn TrieNodeProvider,
+        ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError>,
+    {
+        if let Ok(Some(entry)) = self.cache_metrics.record(get(&self.cache)) {
+            return Ok(Some(entry))
+        }
+
+        let mut error = None;
+        for (name, provider, metrics) in &self.tiers {
+            match metrics.record(get(provider.as_ref())) {
+                Ok(Some(entry)) => {
+                    insert(&self.cache, entry.clone());
+                    return Ok(Some(entry))
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    debug!(target: "trie::node_provider", tier = name, %err, "Trie node lookup failed, asking next tier");
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        error.map_or(Ok(None), Err)
+    }
+}
+
+impl<'a> Default for FallbackTrieNodeProvider<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> fmt::Debug for FallbackTrieNodeProvider<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackTrieNodeProvider")
+            .field("tiers", &self.tiers.iter().map(|(name, ..)| name).collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> TrieNodeProvider for FallbackTrieNodeProvider<'a> {
+    fn seek_account_node(
+        &self,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        self.lookup(
+            |provider| provider.seek_account_node(prefix),
+            |cache, entry| cache.insert_account_node(prefix.clone(), entry),
+        )
+    }
+
+    fn seek_storage_node(
+        &self,
+        hashed_address: B256,
+        prefix: &Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+        self.lookup(
+            |provider| provider.seek_storage_node(hashed_address, prefix),
+            |cache, entry| cache.insert_storage_node(hashed_address, prefix.clone(), entry),
+        )
+    }
+}
+
+/// A [TrieCursorFactory] that reads the account and storage tries through a [TrieNodeProvider].
+///
+/// The cursors answer a seek with the first node under the sought path rather than the first node
+/// after it. The [TrieWalker](crate::walker::TrieWalker) only seeks paths under which a node is
+/// stored, where both are the same.
+#[derive(Debug)]
+pub struct TrieNodeProviderCursorFactory<P> {
+    provider: P,
+}
+
+impl<P> TrieNodeProviderCursorFactory<P> {
+    /// Creates a new factory reading from the given provider.
+    pub fn new(provider: P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P: TrieNodeProvider> TrieCursorFactory for TrieNodeProviderCursorFactory<P> {
+    fn account_trie_cursor(
+        &self,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibbles> + '_>, DatabaseError> {
+        Ok(Box::new(TrieNodeProviderAccountCursor { provider: &self.provider, current: None }))
+    }
+
+    fn storage_tries_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError> {
+        Ok(Box::new(TrieNodeProviderStorageCursor {
+            provider: &self.provider,
+            hashed_address,
+            current: None,
+        }))
+    }
+}
+
+/// An account trie cursor of a [TrieNodeProviderCursorFactory].
+#[derive(Debug)]
+pub struct TrieNodeProviderAccountCursor<'a, P> {
+    provider: &'a P,
+    /// The path of the last returned node.
+    current: Option<Nibbles>,
+}
+
+impl<'a, P: TrieNodeProvider> TrieCursor for TrieNodeProviderAccountCursor<'a, P> {
+    type Key = StoredNibbles;
+
+    fn seek_exact(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let node = self.provider.account_node(&key.0)?;
+        self.current = node.is_some().then(|| key.0.clone());
+        Ok(node.map(|node| (key.0.to_vec(), node)))
+    }
+
+    fn seek(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let entry = self.provider.seek_account_node(&key.0)?;
+        self.current = entry.as_ref().map(|(path, _)| path.clone());
+        Ok(entry.map(|(path, node)| (path.to_vec(), node)))
+    }
+
+    fn current(&mut self) -> Result<Option<TrieKey>, DatabaseError> {
+        Ok(self.current.clone().map(|path| TrieKey::AccountNode(StoredNibbles(path))))
+    }
+}
+
+/// A storage trie cursor of a [TrieNodeProviderCursorFactory].
+#[derive(Debug)]
+pub struct TrieNodeProviderStorageCursor<'a, P> {
+    provider: &'a P,
+    hashed_address: B256,
+    /// The path of the last returned node.
+    current: Option<Nibbles>,
+}
+
+impl<'a, P: TrieNodeProvider> TrieCursor for TrieNodeProviderStorageCursor<'a, P> {
+    type Key = StoredNibblesSubKey;
+
+    fn seek_exact(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let node = self.provider.storage_node(self.hashed_address, &key.0)?;
+        self.current = node.is_some().then(|| key.0.clone());
+        Ok(node.map(|node| (key.0.to_vec(), node)))
+    }
+
+    fn seek(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let entry = self.provider.seek_storage_node(self.hashed_address, &key.0)?;
+        self.current = entry.as_ref().map(|(path, _)| path.clone());
+        Ok(entry.map(|(path, node)| (path.to_vec(), node)))
+    }
+
+    fn current(&mut self) -> Result<Option<TrieKey>, DatabaseError> {
+        Ok(self
+            .current
+            .clone()
+            .map(|path| TrieKey::StorageNode(self.hashed_address, StoredNibblesSubKey(path))))
+    }
+}
+
+/// Metrics of a single tier of the [FallbackTrieNodeProvider].
+#[derive(Metrics)]
+#[metrics(scope = "trie.node_provider")]
+struct TrieNodeProviderTierMetrics {
+    /// The number of lookups the tier returned a node for.
+    hits: Counter,
+    /// The number of lookups the tier did not have a node for.
+    misses: Counter,
+    /// The number of lookups that failed.
+    errors: Counter,
+}
+
+impl TrieNodeProviderTierMetrics {
+    /// Records the result of a lookup.
+    fn record(
+        &self,
+        result: Result<Option<BranchNodeCompact>, TrieNodeProviderError>,
+    ) -> Result<Option<BranchNodeCompact>, TrieNodeProviderError> {
+        match &result {
+            Ok(Some(_)) => self.hits.increment(1),
+            Ok(None) => self.misses.increment(1),
+            Err(_) => self.errors.increment(1),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashedPostState;
+    use reth_db::{
+        cursor::DbCursorRW, database::Database, test_utils::create_test_rw_db, transaction::DbTxMut,
+    };
+    use reth_primitives::{
+        keccak256,
+        trie::{StorageTrieEntry, StoredBranchNode, TrieMask},
+        Account, U256,
+    };
+    use reth_provider::test_utils::create_test_provider_factory;
+
+    fn node(mask: u16) -> BranchNodeCompact {
+        BranchNodeCompact::new(
+            TrieMask::new(mask),
+            TrieMask::new(0),
+            TrieMask::new(0),
+            vec![],
+            None,
+        )
+    }
+
+    /// A tier that fails every lookup.
+    struct FailingTier;
+
+    impl TrieNodeProvider for FailingTier {
+        fn seek_account_node(
+            &self,
+            _prefix: &Nibbles,
+        ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+            Err(TrieNodeProviderError::Remote("unreachable".to_string()))
+        }
+
+        fn seek_storage_node(
+            &self,
+            _hashed_address: B256,
+            _prefix: &Nibbles,
+        ) -> Result<Option<(Nibbles, BranchNodeCompact)>, TrieNodeProviderError> {
+            Err(TrieNodeProviderError::Remote("unreachable".to_string()))
+        }
+    }
+
+    #[test]
+    fn fallback_chain() {
+        let db = create_test_rw_db();
+        let local = Nibbles::from_nibbles_unchecked([0x1]);
+        let remote = Nibbles::from_nibbles_unchecked([0x2, 0x3]);
+        let hashed_address = B256::with_last_byte(1);
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::AccountsTrie>(StoredNibbles(local.clone()), StoredBranchNode(node(0b11)))
+            .unwrap();
+        tx.put::<tables::StoragesTrie>(
+            hashed_address,
+            StorageTrieEntry { nibbles: StoredNibblesSubKey(local.clone()), node: node(0b101) },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        // A second partial trie that stands in for a remote peer.
+        let remote_db = create_test_rw_db();
+        let tx = remote_db.tx_mut().unwrap();
+        tx.put::<tables::AccountsTrie>(
+            StoredNibbles(remote.clone()),
+            StoredBranchNode(node(0b110)),
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        let remote_tx = remote_db.tx().unwrap();
+        let provider = FallbackTrieNodeProvider::new()
+            .with_tier("database", DatabaseTrieNodeProvider::new(&tx))
+            .with_tier("remote", DatabaseTrieNodeProvider::new(&remote_tx));
+
+        assert_eq!(provider.account_node(&local).unwrap(), Some(node(0b11)));
+        assert_eq!(provider.account_node(&remote).unwrap(), Some(node(0b110)));
+        assert_eq!(provider.storage_node(hashed_address, &local).unwrap(), Some(node(0b101)));
+        assert_eq!(provider.storage_node(hashed_address, &remote).unwrap(), None);
+        assert_eq!(provider.account_node(&Nibbles::default()).unwrap(), None);
+
+        // seeks only return nodes under the sought path
+        let under_remote = Nibbles::from_nibbles_unchecked([0x2]);
+        assert_eq!(
+            provider.seek_account_node(&under_remote).unwrap(),
+            Some((remote.clone(), node(0b110)))
+        );
+        assert_eq!(
+            provider.seek_account_node(&Nibbles::from_nibbles_unchecked([0x3])).unwrap(),
+            None
+        );
+
+        // found nodes are cached by the path they were looked up with
+        assert_eq!(provider.cache().account_node(&remote).unwrap(), Some(node(0b110)));
+        assert_eq!(
+            provider.cache().seek_account_node(&under_remote).unwrap(),
+            Some((remote, node(0b110)))
+        );
+        assert_eq!(
+            provider.cache().storage_node(hashed_address, &local).unwrap(),
+            Some(node(0b101))
+        );
+    }
+
+    #[test]
+    fn failing_tier_falls_through() {
+        let db = create_test_rw_db();
+        let path = Nibbles::from_nibbles_unchecked([0x1]);
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::AccountsTrie>(StoredNibbles(path.clone()), StoredBranchNode(node(0b11)))
+            .unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        let provider = FallbackTrieNodeProvider::new()
+            .with_tier("remote", FailingTier)
+            .with_tier("database", DatabaseTrieNodeProvider::new(&tx));
+        assert_eq!(provider.account_node(&path).unwrap(), Some(node(0b11)));
+
+        // the error is returned if no tier has the node
+        let missing = Nibbles::from_nibbles_unchecked([0x2]);
+        assert!(matches!(provider.account_node(&missing), Err(TrieNodeProviderError::Remote(_))));
+    }
+
+    #[test]
+    fn root_with_partial_local_trie() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let tx = provider.tx_ref();
+
+        let mut hashed_accounts = tx.cursor_write::<tables::HashedAccount>().unwrap();
+        let hashed_addresses = (0..1000u64)
+            .map(|i| {
+                let hashed_address = keccak256(B256::from(U256::from(i)));
+                let account = Account { nonce: i, balance: U256::from(i), bytecode_hash: None };
+                hashed_accounts.upsert(hashed_address, account).unwrap();
+                hashed_address
+            })
+            .collect::<Vec<_>>();
+        let (_, updates) = crate::StateRoot::from_tx(tx).root_with_updates().unwrap();
+        updates.flush(tx).unwrap();
+
+        let mut post_state = HashedPostState::default();
+        for (idx, hashed_address) in hashed_addresses.iter().enumerate().step_by(7) {
+            let account = (idx % 2 == 0).then(|| Account {
+                nonce: 1,
+                balance: U256::from(idx * 3),
+                bytecode_hash: None,
+            });
+            post_state.insert_account(*hashed_address, account);
+        }
+        post_state.sort();
+        let (expected_root, expected_updates) = post_state.state_root_with_updates(tx).unwrap();
+
+        // move the subtrees under the upper half of the first nibbles to the stand-in of a remote
+        // peer
+        let remote_db = create_test_rw_db();
+        let remote_tx = remote_db.tx_mut().unwrap();
+        let mut account_trie = tx.cursor_write::<tables::AccountsTrie>().unwrap();
+        let mut walker = account_trie.walk(Some(StoredNibbles::from(vec![0x8]))).unwrap();
+        while let Some(entry) = walker.next() {
+            let (path, node) = entry.unwrap();
+            remote_tx.put::<tables::AccountsTrie>(path, node).unwrap();
+            walker.delete_current().unwrap();
+        }
+        remote_tx.commit().unwrap();
+        assert!(tx.get::<tables::AccountsTrie>(StoredNibbles::from(vec![0x8])).unwrap().is_none());
+
+        let remote_tx = remote_db.tx().unwrap();
+        let nodes = FallbackTrieNodeProvider::new()
+            .with_tier("database", DatabaseTrieNodeProvider::new(tx))
+            .with_tier("remote", DatabaseTrieNodeProvider::new(&remote_tx));
+        let (root, updates) = post_state
+            .state_root_calculator(tx)
+            .with_trie_cursor_factory(TrieNodeProviderCursorFactory::new(&nodes))
+            .root_with_updates()
+            .unwrap();
+        assert_eq!(root, expected_root);
+        assert_eq!(updates, expected_updates);
+
+        // without the remote tier the root is computed from an incomplete trie
+        let local = FallbackTrieNodeProvider::new()
+            .with_tier("database", DatabaseTrieNodeProvider::new(tx));
+        let root = post_state
+            .state_root_calculator(tx)
+            .with_trie_cursor_factory(TrieNodeProviderCursorFactory::new(&local))
+            .root()
+            .unwrap();
+        assert_ne!(root, expected_root);
+    }
+}
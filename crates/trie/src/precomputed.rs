@@ -0,0 +1,256 @@
+//! Storage roots computed ahead of a state root computation.
+//!
+//! The storage roots of the changed accounts can be computed before the account trie walk reaches
+//! them, e.g. while the block is still executing. [StateRoot](crate::StateRoot) uses a
+//! precomputed root instead of computing it inline. An account leaf whose root was not
+//! precomputed is a missed leaf, and is counted by the reason it was missed, so the heuristics
+//! that decide what to prefetch can be tuned.
+
+use crate::{
+    hashed_cursor::HashedCursorFactory, prefix_set::PrefixSet, trie_cursor::TrieCursorFactory,
+    updates::TrieUpdates, StorageRoot, StorageRootError,
+};
+use ahash::AHashMap;
+use parking_lot::Mutex;
+use reth_primitives::{trie::Nibbles, B256};
+use serde::Serialize;
+
+/// Why the storage root of an account leaf was not precomputed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissCause {
+    /// The account was never prefetched.
+    NotPrefetched,
+    /// The account was prefetched, but its root was not computed before the walk reached it.
+    CacheMiss,
+    /// The root was computed, but the storage of the account changed after the prefetch.
+    ChangedAfterPrefetch,
+}
+
+/// The precomputed storage roots used by state root computations, broken down by [MissCause] for
+/// the missed leaves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PrecomputedStorageRootStats {
+    /// The number of account leaves whose precomputed root was used.
+    pub hits: u64,
+    /// The number of missed leaves of accounts that were never prefetched.
+    pub not_prefetched: u64,
+    /// The number of missed leaves of prefetched accounts whose root was not computed in time.
+    pub cache_misses: u64,
+    /// The number of missed leaves of accounts whose storage changed after the prefetch.
+    pub changed_after_prefetch: u64,
+}
+
+impl PrecomputedStorageRootStats {
+    /// Returns the number of account leaves whose storage root was computed inline.
+    pub fn missed_leaves(&self) -> u64 {
+        self.not_prefetched + self.cache_misses + self.changed_after_prefetch
+    }
+
+    fn record_miss(&mut self, cause: MissCause) {
+        match cause {
+            MissCause::NotPrefetched => self.not_prefetched += 1,
+            MissCause::CacheMiss => self.cache_misses += 1,
+            MissCause::ChangedAfterPrefetch => self.changed_after_prefetch += 1,
+        }
+    }
+}
+
+/// A storage root computed ahead of the state root.
+#[derive(Debug)]
+pub(crate) struct PrecomputedStorageRoot {
+    /// The storage root.
+    pub(crate) root: B256,
+    /// The number of storage slots walked to compute the root.
+    pub(crate) slots_walked: usize,
+    /// The storage trie updates.
+    pub(crate) updates: TrieUpdates,
+}
+
+#[derive(Debug)]
+enum Prefetch {
+    /// The account was prefetched, its root is not computed yet.
+    Pending,
+    /// The root was computed with the given changed storage prefixes.
+    Computed { keys: Vec<Nibbles>, subtrees: Vec<Nibbles>, root: PrecomputedStorageRoot },
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    prefetched: AHashMap<B256, Prefetch>,
+    stats: PrecomputedStorageRootStats,
+    /// The missed leaves in the order they were missed, if recording is enabled.
+    missed: Option<Vec<(B256, MissCause)>>,
+}
+
+/// Storage roots computed ahead of a state root computation.
+///
+/// Shared with the [StateRoot](crate::StateRoot) computations that use the roots, each root is
+/// used at most once.
+#[derive(Debug, Default)]
+pub struct PrecomputedStorageRoots {
+    inner: Mutex<Inner>,
+}
+
+impl PrecomputedStorageRoots {
+    /// Records the hashed address and [MissCause] of every missed leaf, see
+    /// [PrecomputedStorageRoots::missed_leaves].
+    pub fn with_missed_leaves_recording(mut self) -> Self {
+        self.inner.get_mut().missed = Some(Vec::new());
+        self
+    }
+
+    /// Marks the accounts as prefetched, their roots are expected to be inserted before the walk
+    /// reaches them.
+    pub fn prefetch(&self, hashed_addresses: impl IntoIterator<Item = B256>) {
+        let mut inner = self.inner.lock();
+        for hashed_address in hashed_addresses {
+            inner.prefetched.entry(hashed_address).or_insert(Prefetch::Pending);
+        }
+    }
+
+    /// Inserts the storage root of the account, computed with the given changed storage
+    /// prefixes.
+    pub fn insert(
+        &self,
+        hashed_address: B256,
+        changed_prefixes: &PrefixSet,
+        (root, slots_walked, updates): (B256, usize, TrieUpdates),
+    ) {
+        let computed = Prefetch::Computed {
+            keys: changed_prefixes.keys().to_vec(),
+            subtrees: changed_prefixes.subtrees().to_vec(),
+            root: PrecomputedStorageRoot { root, slots_walked, updates },
+        };
+        self.inner.lock().prefetched.insert(hashed_address, computed);
+    }
+
+    /// Prefetches the account and computes its storage root with the given changed storage
+    /// prefixes.
+    pub fn precompute<T, H>(
+        &self,
+        trie_cursor_factory: T,
+        hashed_cursor_factory: H,
+        hashed_address: B256,
+        changed_prefixes: PrefixSet,
+    ) -> Result<(), StorageRootError>
+    where
+        T: TrieCursorFactory,
+        H: HashedCursorFactory,
+    {
+        self.prefetch([hashed_address]);
+        let root =
+            StorageRoot::new_hashed(trie_cursor_factory, hashed_cursor_factory, hashed_address)
+                .with_changed_prefixes(changed_prefixes.clone())
+                .root_with_updates()?;
+        self.insert(hashed_address, &changed_prefixes, root);
+        Ok(())
+    }
+
+    /// Returns the statistics of all lookups so far.
+    pub fn stats(&self) -> PrecomputedStorageRootStats {
+        self.inner.lock().stats
+    }
+
+    /// Returns the hashed address and [MissCause] of every missed leaf so far, `None` unless
+    /// recording was enabled with [PrecomputedStorageRoots::with_missed_leaves_recording].
+    pub fn missed_leaves(&self) -> Option<Vec<(B256, MissCause)>> {
+        self.inner.lock().missed.clone()
+    }
+
+    /// Takes the precomputed root of the account if it was computed with the given changed
+    /// storage prefixes, records the missed leaf otherwise.
+    pub(crate) fn take(
+        &self,
+        hashed_address: B256,
+        changed_prefixes: &PrefixSet,
+    ) -> Option<PrecomputedStorageRoot> {
+        let mut inner = self.inner.lock();
+        let cause = match inner.prefetched.remove(&hashed_address) {
+            None => MissCause::NotPrefetched,
+            Some(Prefetch::Pending) => MissCause::CacheMiss,
+            Some(Prefetch::Computed { keys, subtrees, root }) => {
+                if keys == changed_prefixes.keys() && subtrees == changed_prefixes.subtrees() {
+                    inner.stats.hits += 1;
+                    return Some(root)
+                }
+                MissCause::ChangedAfterPrefetch
+            }
+        };
+        inner.stats.record_miss(cause);
+        if let Some(missed) = &mut inner.missed {
+            missed.push((hashed_address, cause));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prefix_set::PrefixSetMut, StateRoot};
+    use reth_db::{
+        database::Database,
+        tables,
+        test_utils::create_test_rw_db,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{keccak256, Account, StorageEntry, U256};
+    use std::sync::Arc;
+
+    #[test]
+    fn missed_leaves_by_cause() {
+        let db = create_test_rw_db();
+        // in the order the walk reaches them
+        let mut hashed_addresses = [0u64, 1, 2, 3].map(|i| keccak256(i.to_be_bytes()));
+        hashed_addresses.sort();
+        let [hit, pending, changed, not_prefetched] = hashed_addresses;
+
+        let tx = db.tx_mut().unwrap();
+        for hashed_address in hashed_addresses {
+            tx.put::<tables::HashedAccount>(hashed_address, Account::default()).unwrap();
+            let entry = StorageEntry { key: keccak256(hashed_address), value: U256::from(1) };
+            tx.put::<tables::HashedStorage>(hashed_address, entry).unwrap();
+        }
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        let expected = StateRoot::from_tx(&tx).root().unwrap();
+
+        let mut changed_slot = PrefixSetMut::default();
+        changed_slot.insert(Nibbles::unpack(keccak256(changed)));
+        let changed_storage_prefixes = AHashMap::from_iter([(changed, changed_slot.freeze())]);
+
+        let roots = PrecomputedStorageRoots::default().with_missed_leaves_recording();
+        roots.precompute(&tx, &tx, hit, PrefixSet::default()).unwrap();
+        roots.prefetch([pending]);
+        // precomputed before the slot of the account changed
+        roots.precompute(&tx, &tx, changed, PrefixSet::default()).unwrap();
+        let roots = Arc::new(roots);
+
+        let root = StateRoot::from_tx(&tx)
+            .with_changed_storage_prefixes(changed_storage_prefixes)
+            .with_precomputed_storage_roots(Arc::clone(&roots))
+            .root()
+            .unwrap();
+        assert_eq!(root, expected);
+        assert_eq!(
+            roots.stats(),
+            PrecomputedStorageRootStats {
+                hits: 1,
+                not_prefetched: 1,
+                cache_misses: 1,
+                changed_after_prefetch: 1,
+            }
+        );
+        assert_eq!(roots.stats().missed_leaves(), 3);
+        assert_eq!(
+            roots.missed_leaves().unwrap(),
+            vec![
+                (pending, MissCause::CacheMiss),
+                (changed, MissCause::ChangedAfterPrefetch),
+                (not_prefetched, MissCause::NotPrefetched),
+            ]
+        );
+    }
+}
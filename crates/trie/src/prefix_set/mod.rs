@@ -19,6 +19,9 @@ pub use loader::{LoadedPrefixSets, PrefixSetLoader};
 /// This means that a `PrefixSet` will always be sorted and deduplicated when constructed from a
 /// `PrefixSetMut`.
 ///
+/// Large sets can be [compacted](PrefixSetMut::compact), which replaces the keys under dense
+/// prefixes with the prefixes themselves. A compacted prefix marks its whole subtree as changed.
+///
 /// # Examples
 ///
 /// ```
@@ -36,6 +39,8 @@ pub struct PrefixSetMut {
     keys: Vec<Nibbles>,
     sorted: bool,
     index: usize,
+    /// Sorted prefixes whose whole subtrees are changed, none of which is a prefix of another.
+    subtrees: Vec<Nibbles>,
 }
 
 impl<I> From<I> for PrefixSetMut
@@ -57,6 +62,10 @@ impl PrefixSetMut {
             self.sorted = true;
         }
 
+        if !self.subtrees.is_empty() && subtrees_contain(&self.subtrees, prefix) {
+            return true
+        }
+
         while self.index > 0 && self.keys[self.index] > *prefix {
             self.index -= 1;
         }
@@ -82,14 +91,54 @@ impl PrefixSetMut {
         self.keys.push(nibbles);
     }
 
-    /// Returns the number of elements in the set.
+    /// Returns the number of elements in the set, including compacted prefixes.
     pub fn len(&self) -> usize {
-        self.keys.len()
+        self.keys.len() + self.subtrees.len()
     }
 
     /// Returns `true` if the set is empty.
     pub fn is_empty(&self) -> bool {
-        self.keys.is_empty()
+        self.keys.is_empty() && self.subtrees.is_empty()
+    }
+
+    /// Replaces the keys under dense prefixes with the prefixes, trading walking more of the trie
+    /// for faster membership checks.
+    ///
+    /// A prefix is dense if at least `min_dense_children` of its 16 children are dense, where a
+    /// child is dense if it is a key in the set or a dense prefix itself. The threshold is clamped
+    /// to `2..=16`. Compacting an already compacted set only compacts the keys.
+    pub fn compact(&mut self, min_dense_children: usize) -> PrefixSetCompaction {
+        let min_dense_children = min_dense_children.clamp(2, 16);
+        if !self.sorted {
+            self.keys.sort();
+            self.keys.dedup();
+            self.sorted = true;
+        }
+
+        let keys = self.keys.len();
+        let mut compacted = Compacted::default();
+        if compact_under(&self.keys, 0, min_dense_children, &mut compacted) {
+            compacted.subtrees.push(Nibbles::from_nibbles_unchecked([]));
+        }
+        let stats = PrefixSetCompaction {
+            keys,
+            remaining_keys: compacted.keys.len(),
+            subtrees: compacted.subtrees.len(),
+        };
+
+        self.keys = compacted.keys;
+        self.index = 0;
+        if self.subtrees.is_empty() {
+            self.subtrees = compacted.subtrees;
+        } else {
+            self.subtrees.extend(compacted.subtrees);
+            self.subtrees.sort();
+            // subtrees covered by a shorter one are redundant
+            self.subtrees.dedup_by(|subtree, covering| subtree.starts_with(covering));
+            let subtrees = &self.subtrees;
+            self.keys.retain(|key| !subtrees_contain(subtrees, key));
+        }
+        stats
     }
 
     /// Returns a `PrefixSet` with the same elements as this set.
@@ -101,8 +150,100 @@ impl PrefixSetMut {
             self.keys.dedup();
         }
 
-        PrefixSet { keys: Rc::new(self.keys), index: self.index }
+        PrefixSet { keys: Rc::new(self.keys), index: self.index, subtrees: Rc::new(self.subtrees) }
+    }
+}
+
+/// Statistics of a [PrefixSetMut::compact] call.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrefixSetCompaction {
+    /// The number of keys before compaction.
+    pub keys: usize,
+    /// The number of keys that were not under a dense prefix.
+    pub remaining_keys: usize,
+    /// The number of dense prefixes the other keys were replaced with.
+    pub subtrees: usize,
+}
+
+impl PrefixSetCompaction {
+    /// Adds the statistics of another compaction.
+    pub fn extend(&mut self, other: Self) {
+        self.keys += other.keys;
+        self.remaining_keys += other.remaining_keys;
+        self.subtrees += other.subtrees;
+    }
+
+    /// Returns the number of keys before compaction per entry after it, `1.0` if nothing was
+    /// compacted.
+    pub fn ratio(&self) -> f64 {
+        let entries = self.remaining_keys + self.subtrees;
+        if entries == 0 {
+            return 1.0
+        }
+        self.keys as f64 / entries as f64
+    }
+}
+
+/// The output of compacting a prefix set.
+#[derive(Default)]
+struct Compacted {
+    keys: Vec<Nibbles>,
+    subtrees: Vec<Nibbles>,
+}
+
+/// Compacts the given sorted keys, all of which share the prefix of length `depth`, into `out`.
+///
+/// Returns `true` if the prefix is dense, in which case nothing is written and the caller replaces
+/// the keys with the prefix.
+fn compact_under(
+    keys: &[Nibbles],
+    depth: usize,
+    min_dense_children: usize,
+    out: &mut Compacted,
+) -> bool {
+    // every dense child has at least one key
+    if keys.len() < min_dense_children {
+        out.keys.extend_from_slice(keys);
+        return false
+    }
+
+    let (keys_len, subtrees_len) = (out.keys.len(), out.subtrees.len());
+    let mut dense_children = 0;
+    let mut rest = keys;
+    while let Some(first) = rest.first() {
+        // the prefix itself sorts before the keys under it
+        if first.len() == depth {
+            out.keys.push(first.clone());
+            rest = &rest[1..];
+            continue
+        }
+
+        let nibble = first[depth];
+        let (child, next) = rest.split_at(rest.partition_point(|key| key[depth] <= nibble));
+        rest = next;
+
+        if child.len() == 1 && child[0].len() == depth + 1 {
+            out.keys.push(child[0].clone());
+            dense_children += 1;
+        } else if compact_under(child, depth + 1, min_dense_children, out) {
+            out.subtrees.push(Nibbles::from_nibbles_unchecked(&child[0][..=depth]));
+            dense_children += 1;
+        }
+    }
+
+    if dense_children >= min_dense_children {
+        out.keys.truncate(keys_len);
+        out.subtrees.truncate(subtrees_len);
+        return true
     }
+    false
+}
+
+/// Returns `true` if any of the sorted subtrees is under the given prefix or covers it.
+fn subtrees_contain(subtrees: &[Nibbles], prefix: &[u8]) -> bool {
+    let idx = subtrees.partition_point(|subtree| subtree.as_slice() < prefix);
+    subtrees.get(idx).is_some_and(|subtree| subtree.starts_with(prefix)) ||
+        subtrees[..idx].last().is_some_and(|subtree| prefix.starts_with(subtree))
 }
 
 /// A sorted prefix set that has an immutable _sorted_ list of unique keys.
@@ -112,6 +253,7 @@ impl PrefixSetMut {
 pub struct PrefixSet {
     keys: Rc<Vec<Nibbles>>,
     index: usize,
+    subtrees: Rc<Vec<Nibbles>>,
 }
 
 impl PrefixSet {
@@ -119,6 +261,10 @@ impl PrefixSet {
     /// if the given prefix is a prefix of any key in the set.
     #[inline]
     pub fn contains(&mut self, prefix: &Nibbles) -> bool {
+        if !self.subtrees.is_empty() && subtrees_contain(&self.subtrees, prefix) {
+            return true
+        }
+
         while self.index > 0 && &self.keys[self.index] > prefix {
             self.index -= 1;
         }
@@ -138,14 +284,24 @@ impl PrefixSet {
         false
     }
 
-    /// Returns the number of elements in the set.
+    /// Returns the number of elements in the set, including compacted prefixes.
     pub fn len(&self) -> usize {
-        self.keys.len()
+        self.keys.len() + self.subtrees.len()
     }
 
     /// Returns `true` if the set is empty.
     pub fn is_empty(&self) -> bool {
-        self.keys.is_empty()
+        self.keys.is_empty() && self.subtrees.is_empty()
+    }
+
+    /// Returns the sorted keys of the set, excluding compacted prefixes.
+    pub fn keys(&self) -> &[Nibbles] {
+        &self.keys
+    }
+
+    /// Returns the sorted prefixes whose whole subtrees are changed.
+    pub fn subtrees(&self) -> &[Nibbles] {
+        &self.subtrees
     }
 }
 
@@ -166,4 +322,37 @@ mod tests {
         assert!(!prefix_set.contains(b"78"));
         assert_eq!(prefix_set.len(), 3); // Length should be 3 (excluding duplicate)
     }
+
+    #[test]
+    fn compact_dense_prefixes() {
+        let mut prefix_set = PrefixSetMut::default();
+        // all children of 0x1 and 0x32, but only 3 of 16 under 0x4
+        for nibble in 0..16 {
+            prefix_set.insert(Nibbles::from_nibbles_unchecked([0x1, nibble]));
+            prefix_set.insert(Nibbles::from_nibbles_unchecked([0x3, 0x2, nibble]));
+        }
+        for nibble in 0..3 {
+            prefix_set.insert(Nibbles::from_nibbles_unchecked([0x4, nibble, 0x5]));
+        }
+
+        let stats = prefix_set.compact(12);
+        assert_eq!(stats, PrefixSetCompaction { keys: 35, remaining_keys: 3, subtrees: 2 });
+        assert_eq!(stats.ratio(), 7.0);
+
+        let mut prefix_set = prefix_set.freeze();
+        assert_eq!(
+            prefix_set.subtrees(),
+            [Nibbles::from_nibbles_unchecked([0x1]), Nibbles::from_nibbles_unchecked([0x3, 0x2])]
+        );
+        // paths above and inside compacted prefixes are contained
+        assert!(prefix_set.contains(&Nibbles::from_nibbles_unchecked([])));
+        assert!(prefix_set.contains(&Nibbles::from_nibbles_unchecked([0x3])));
+        assert!(prefix_set.contains(&Nibbles::from_nibbles_unchecked([0x1, 0x7, 0xf])));
+        assert!(prefix_set.contains(&Nibbles::from_nibbles_unchecked([0x3, 0x2, 0x8, 0x1])));
+        assert!(!prefix_set.contains(&Nibbles::from_nibbles_unchecked([0x3, 0x3])));
+        // sparse keys are kept
+        assert!(prefix_set.contains(&Nibbles::from_nibbles_unchecked([0x4, 0x1])));
+        assert!(!prefix_set.contains(&Nibbles::from_nibbles_unchecked([0x4, 0x1, 0x6])));
+        assert!(!prefix_set.contains(&Nibbles::from_nibbles_unchecked([0x4, 0x3])));
+    }
 }
@@ -2,18 +2,21 @@ use crate::{
     hashed_cursor::{HashedCursorFactory, HashedStorageCursor},
     node_iter::{AccountNode, AccountNodeIter, StorageNode, StorageNodeIter},
     prefix_set::PrefixSetMut,
+    spill::{ProofSpillError, SpillingProofRetainer},
     trie_cursor::{DatabaseAccountTrieCursor, DatabaseStorageTrieCursor},
     walker::TrieWalker,
     StateRootError, StorageRootError,
 };
+use ahash::AHashMap;
 use alloy_rlp::{BufMut, Encodable};
 use reth_db::{tables, transaction::DbTx};
 use reth_primitives::{
     constants::EMPTY_ROOT_HASH,
     keccak256,
     trie::{AccountProof, HashBuilder, Nibbles, StorageProof, TrieAccount},
-    Address, B256,
+    Address, Bytes, B256, U256,
 };
+use std::collections::BTreeMap;
 
 /// A struct for generating merkle proofs.
 ///
@@ -26,12 +29,49 @@ pub struct Proof<'a, TX, H> {
     tx: &'a TX,
     /// The factory for hashed cursors.
     hashed_cursor_factory: H,
+    /// A set of account prefixes that have changed relative to the stored trie.
+    changed_account_prefixes: PrefixSetMut,
+    /// A map containing storage changes with the hashed address as key and a set of storage key
+    /// prefixes as the value.
+    changed_storage_prefixes: AHashMap<B256, PrefixSetMut>,
 }
 
 impl<'a, TX> Proof<'a, TX, &'a TX> {
     /// Create a new [Proof] instance.
     pub fn new(tx: &'a TX) -> Self {
-        Self { tx, hashed_cursor_factory: tx }
+        Self {
+            tx,
+            hashed_cursor_factory: tx,
+            changed_account_prefixes: PrefixSetMut::default(),
+            changed_storage_prefixes: AHashMap::default(),
+        }
+    }
+}
+
+impl<'a, TX, H> Proof<'a, TX, H> {
+    /// Set the hashed cursor factory.
+    pub fn with_hashed_cursor_factory<HF>(self, hashed_cursor_factory: HF) -> Proof<'a, TX, HF> {
+        Proof {
+            tx: self.tx,
+            hashed_cursor_factory,
+            changed_account_prefixes: self.changed_account_prefixes,
+            changed_storage_prefixes: self.changed_storage_prefixes,
+        }
+    }
+
+    /// Set the changed account prefixes.
+    ///
+    /// Stored trie nodes under these prefixes are not trusted and recomputed from the hashed
+    /// state, which is required when the hashed cursor factory overlays the database state.
+    pub fn with_changed_account_prefixes(mut self, prefixes: PrefixSetMut) -> Self {
+        self.changed_account_prefixes = prefixes;
+        self
+    }
+
+    /// Set the changed storage prefixes.
+    pub fn with_changed_storage_prefixes(mut self, prefixes: AHashMap<B256, PrefixSetMut>) -> Self {
+        self.changed_storage_prefixes = prefixes;
+        self
     }
 }
 
@@ -55,7 +95,7 @@ where
             DatabaseAccountTrieCursor::new(self.tx.cursor_read::<tables::AccountsTrie>()?);
 
         // Create the walker.
-        let mut prefix_set = PrefixSetMut::default();
+        let mut prefix_set = self.changed_account_prefixes.clone();
         prefix_set.insert(target_nibbles.clone());
         let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
 
@@ -119,14 +159,112 @@ where
         }
 
         let target_nibbles = proofs.iter().map(|p| p.nibbles.clone()).collect::<Vec<_>>();
-        let prefix_set = PrefixSetMut::from(target_nibbles.clone()).freeze();
+        let (root, all_proof_nodes) = self.storage_root_with_proof_nodes(
+            hashed_address,
+            target_nibbles,
+            |nibbles, value| {
+                if let Some(proof) = proofs.iter_mut().find(|proof| &proof.nibbles == nibbles) {
+                    proof.set_value(value);
+                }
+            },
+        )?;
+
+        for proof in proofs.iter_mut() {
+            // Iterate over all proof nodes and find the matching ones.
+            // The filtered results are guaranteed to be in order.
+            let matching_proof_nodes = all_proof_nodes
+                .iter()
+                .filter(|(path, _)| proof.nibbles.starts_with(path))
+                .map(|(_, node)| node.clone());
+            proof.set_proof(matching_proof_nodes.collect());
+        }
+
+        Ok((root, proofs))
+    }
+
+    /// Compute the storage root and proofs for a large number of slots with bounded memory.
+    ///
+    /// The slots are sorted by their hashed key and proven in passes of at most `targets_per_pass`
+    /// slots. A pass only recomputes the subtries on the paths to its targets and takes the rest of
+    /// the trie from the stored branch nodes. The nodes retained in a pass are moved to the
+    /// `retainer`, which spills them to disk, and the proofs of the pass are assembled from it and
+    /// passed to `on_proof` in order of the hashed slot.
+    pub fn storage_multiproof_spilled(
+        &self,
+        hashed_address: B256,
+        slots: &[B256],
+        targets_per_pass: usize,
+        retainer: &mut SpillingProofRetainer,
+        mut on_proof: impl FnMut(StorageProof),
+    ) -> Result<B256, ProofSpillError> {
+        let mut hashed_storage_cursor = self.hashed_cursor_factory.hashed_storage_cursor()?;
+
+        let mut targets = slots.iter().copied().map(StorageProof::new).collect::<Vec<_>>();
+        targets.sort_unstable_by(|a, b| a.nibbles.cmp(&b.nibbles));
+        targets.dedup_by(|a, b| a.nibbles == b.nibbles);
+
+        // short circuit on empty storage
+        if hashed_storage_cursor.is_storage_empty(hashed_address)? {
+            targets.into_iter().for_each(on_proof);
+            return Ok(EMPTY_ROOT_HASH)
+        }
+
+        if targets.is_empty() {
+            return Ok(self.storage_root(hashed_address)?)
+        }
+
+        let mut root = EMPTY_ROOT_HASH;
+        let mut targets = targets.into_iter();
+        loop {
+            let mut batch = targets.by_ref().take(targets_per_pass.max(1)).collect::<Vec<_>>();
+            if batch.is_empty() {
+                break
+            }
+
+            let target_nibbles = batch.iter().map(|p| p.nibbles.clone()).collect::<Vec<_>>();
+            let (pass_root, proof_nodes) = self.storage_root_with_proof_nodes(
+                hashed_address,
+                target_nibbles,
+                |nibbles, value| {
+                    if let Ok(idx) = batch.binary_search_by(|proof| proof.nibbles.cmp(nibbles)) {
+                        batch[idx].set_value(value);
+                    }
+                },
+            )?;
+            root = pass_root;
+
+            retainer.retain(proof_nodes)?;
+            for mut proof in batch {
+                proof.set_proof(retainer.proof(&proof.nibbles)?);
+                on_proof(proof);
+            }
+        }
+
+        Ok(root)
+    }
+
+    /// Compute the storage root of a non-empty storage, retaining the nodes on the paths to the
+    /// targets. `on_leaf` is called with every leaf of the recomputed subtries.
+    fn storage_root_with_proof_nodes(
+        &self,
+        hashed_address: B256,
+        targets: Vec<Nibbles>,
+        mut on_leaf: impl FnMut(&Nibbles, U256),
+    ) -> Result<(B256, BTreeMap<Nibbles, Bytes>), StorageRootError> {
+        let hashed_storage_cursor = self.hashed_cursor_factory.hashed_storage_cursor()?;
+
+        let mut prefix_set =
+            self.changed_storage_prefixes.get(&hashed_address).cloned().unwrap_or_default();
+        for nibbles in &targets {
+            prefix_set.insert(nibbles.clone());
+        }
         let trie_cursor = DatabaseStorageTrieCursor::new(
             self.tx.cursor_dup_read::<tables::StoragesTrie>()?,
             hashed_address,
         );
-        let walker = TrieWalker::new(trie_cursor, prefix_set);
+        let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
 
-        let mut hash_builder = HashBuilder::default().with_proof_retainer(target_nibbles);
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(targets);
         let mut storage_node_iter =
             StorageNodeIter::new(walker, hashed_storage_cursor, hashed_address);
         while let Some(node) = storage_node_iter.try_next()? {
@@ -136,28 +274,14 @@ where
                 }
                 StorageNode::Leaf(hashed_slot, value) => {
                     let nibbles = Nibbles::unpack(hashed_slot);
-                    if let Some(proof) = proofs.iter_mut().find(|proof| proof.nibbles == nibbles) {
-                        proof.set_value(value);
-                    }
+                    on_leaf(&nibbles, value);
                     hash_builder.add_leaf(nibbles, alloy_rlp::encode_fixed_size(&value).as_ref());
                 }
             }
         }
 
         let root = hash_builder.root();
-
-        let all_proof_nodes = hash_builder.take_proofs();
-        for proof in proofs.iter_mut() {
-            // Iterate over all proof nodes and find the matching ones.
-            // The filtered results are guaranteed to be in order.
-            let matching_proof_nodes = all_proof_nodes
-                .iter()
-                .filter(|(path, _)| proof.nibbles.starts_with(path))
-                .map(|(_, node)| node.clone());
-            proof.set_proof(matching_proof_nodes.collect());
-        }
-
-        Ok((root, proofs))
+        Ok((root, hash_builder.take_proofs()))
     }
 }
 
@@ -169,7 +293,7 @@ mod tests {
     use once_cell::sync::Lazy;
     use reth_db::database::Database;
     use reth_interfaces::RethResult;
-    use reth_primitives::{Account, Bytes, ChainSpec, StorageEntry, HOLESKY, MAINNET, U256};
+    use reth_primitives::{Account, ChainSpec, StorageEntry, HOLESKY, MAINNET};
     use reth_provider::{test_utils::create_test_provider_factory, HashingWriter, ProviderFactory};
     use std::{str::FromStr, sync::Arc};
 
@@ -442,4 +566,34 @@ mod tests {
         let account_proof = Proof::new(provider.tx_ref()).account_proof(target, &slots).unwrap();
         similar_asserts::assert_eq!(account_proof, expected);
     }
+
+    #[test]
+    fn holesky_deposit_contract_spilled_multiproof() {
+        let factory = create_test_provider_factory();
+        insert_genesis(&factory, HOLESKY.clone()).unwrap();
+
+        let target = Address::from_str("0x4242424242424242424242424242424242424242").unwrap();
+        let hashed_address = keccak256(target);
+        // existent slots 0x22..=0x41 and a few non-existent ones
+        let slots = (0x20..0x48u64).map(|slot| B256::from(U256::from(slot))).collect::<Vec<_>>();
+
+        let provider = factory.provider().unwrap();
+        let proof = Proof::new(provider.tx_ref());
+        let (expected_root, mut expected) =
+            proof.storage_root_with_proofs(hashed_address, &slots).unwrap();
+        expected.sort_unstable_by(|a, b| a.nibbles.cmp(&b.nibbles));
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut retainer = SpillingProofRetainer::new_in(dir.path(), 0).unwrap();
+        let mut proofs = Vec::new();
+        let root = proof
+            .storage_multiproof_spilled(hashed_address, &slots, 3, &mut retainer, |proof| {
+                proofs.push(proof)
+            })
+            .unwrap();
+
+        assert_eq!(root, expected_root);
+        assert!(retainer.spilled_bytes() > 0);
+        similar_asserts::assert_eq!(proofs, expected);
+    }
 }
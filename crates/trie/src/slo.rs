@@ -0,0 +1,661 @@
+//! Latency objectives of state root computations.
+//!
+//! The [RootSloTracker] keeps the durations of recent state root computations in a sliding window,
+//! separately for validating blocks and building payloads, and exports their p50, p95 and p99 as
+//! metrics. If a percentile exceeds its configured threshold, the breach is logged and, the first
+//! time, a dump of the tracked durations and all registered stats is written for postmortems.
+//!
+//! The tracker also keeps the most recent computations and the computations in progress, see
+//! [RootSloTracker::snapshot].
+
+use parking_lot::Mutex;
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_primitives::BlockNumber;
+use serde::{Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{error, info, warn};
+
+/// The default length of the sliding window.
+pub const DEFAULT_ROOT_SLO_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// The default number of durations in the window below which thresholds are not evaluated.
+pub const DEFAULT_ROOT_SLO_MIN_SAMPLES: usize = 20;
+
+/// The maximum number of durations kept per window, older durations are dropped first.
+const MAX_SAMPLES: usize = 4096;
+
+/// The number of most recent computations kept for [RootSloTracker::snapshot].
+const RECENT_ROOTS: usize = 64;
+
+/// The purpose a state root was computed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RootComputation {
+    /// Validating a block received from the consensus layer.
+    Validation,
+    /// Building a payload.
+    Building,
+}
+
+impl RootComputation {
+    /// Returns the name of the computation, as used in metric labels.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            RootComputation::Validation => "validation",
+            RootComputation::Building => "building",
+        }
+    }
+}
+
+impl fmt::Display for RootComputation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The latency percentiles of the state root computations in a window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RootLatencyPercentiles {
+    /// The number of computations in the window.
+    pub samples: usize,
+    /// The median duration.
+    #[serde(rename = "p50_us", serialize_with = "serialize_micros")]
+    pub p50: Duration,
+    /// The 95th percentile duration.
+    #[serde(rename = "p95_us", serialize_with = "serialize_micros")]
+    pub p95: Duration,
+    /// The 99th percentile duration.
+    #[serde(rename = "p99_us", serialize_with = "serialize_micros")]
+    pub p99: Duration,
+}
+
+/// The alert thresholds of the latency percentiles, unset percentiles are not alerted on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RootSloThresholds {
+    /// The threshold of the median duration.
+    pub p50: Option<Duration>,
+    /// The threshold of the 95th percentile duration.
+    pub p95: Option<Duration>,
+    /// The threshold of the 99th percentile duration.
+    pub p99: Option<Duration>,
+}
+
+impl RootSloThresholds {
+    /// Returns `true` if no threshold is set.
+    pub fn is_empty(&self) -> bool {
+        self.p50.is_none() && self.p95.is_none() && self.p99.is_none()
+    }
+
+    /// Returns the first percentile that exceeds its threshold, with the observed duration and
+    /// the threshold.
+    fn breach(&self, percentiles: &RootLatencyPercentiles) -> Option<Breach> {
+        [
+            ("p99", percentiles.p99, self.p99),
+            ("p95", percentiles.p95, self.p95),
+            ("p50", percentiles.p50, self.p50),
+        ]
+        .into_iter()
+        .find_map(|(percentile, observed, threshold)| {
+            let threshold = threshold?;
+            (observed > threshold).then_some(Breach { percentile, observed, threshold })
+        })
+    }
+}
+
+/// A percentile that exceeds its threshold.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct Breach {
+    percentile: &'static str,
+    #[serde(rename = "observed_us", serialize_with = "serialize_micros")]
+    observed: Duration,
+    #[serde(rename = "threshold_us", serialize_with = "serialize_micros")]
+    threshold: Duration,
+}
+
+/// Tracks the latency of state root computations against alert thresholds.
+///
+/// The tracker is shared between the blockchain tree, which records the roots of validated
+/// blocks, and the payload builder, which records the roots of built payloads.
+#[derive(Debug)]
+pub struct RootSloTracker {
+    /// The length of the sliding window.
+    window: Duration,
+    /// The number of durations in a window below which thresholds are not evaluated.
+    min_samples: usize,
+    validation: TrackedComputation,
+    building: TrackedComputation,
+    /// The directory to write the dump to on the first breach.
+    dump_dir: Option<PathBuf>,
+    /// Whether the dump was already written.
+    dumped: AtomicBool,
+    /// Stats that are included in the dump, by name.
+    stats: Vec<(&'static str, StatsSource)>,
+    /// The most recent and the running computations.
+    activity: Mutex<RootActivity>,
+}
+
+impl RootSloTracker {
+    /// Creates a new tracker with the given window length and no thresholds.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            min_samples: DEFAULT_ROOT_SLO_MIN_SAMPLES,
+            validation: TrackedComputation::new(RootComputation::Validation),
+            building: TrackedComputation::new(RootComputation::Building),
+            dump_dir: None,
+            dumped: AtomicBool::new(false),
+            stats: Vec::new(),
+            activity: Mutex::default(),
+        }
+    }
+
+    /// Sets the alert thresholds of the given computation.
+    pub fn with_thresholds(mut self, kind: RootComputation, thresholds: RootSloThresholds) -> Self {
+        self.computation_mut(kind).thresholds = thresholds;
+        self
+    }
+
+    /// Sets the number of durations in a window below which thresholds are not evaluated.
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Sets the directory to write a dump to when a threshold is breached for the first time.
+    pub fn with_dump_dir(mut self, dir: PathBuf) -> Self {
+        self.dump_dir = Some(dir);
+        self
+    }
+
+    /// Adds stats that are rendered into the dump, e.g. the current values of all metrics.
+    pub fn with_stats_source(
+        mut self,
+        name: &'static str,
+        source: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.stats.push((name, StatsSource(Box::new(source))));
+        self
+    }
+
+    /// Records the duration of a state root computation.
+    pub fn record(&self, kind: RootComputation, duration: Duration) {
+        self.activity.lock().push_recent(kind, None, duration);
+        self.record_at(kind, duration, Instant::now())
+    }
+
+    /// Starts timing the computation of the state root of the given block. The computation is
+    /// reported as in progress until the returned timer is finished or dropped, and only recorded
+    /// if it is finished.
+    pub fn start(&self, kind: RootComputation, block_number: BlockNumber) -> RootTimer<'_> {
+        let started_at = Instant::now();
+        let id = self.activity.lock().start(kind, block_number, started_at);
+        RootTimer { tracker: self, id, kind, block_number, started_at }
+    }
+
+    /// Returns the latency percentiles, the most recent computations and the computations in
+    /// progress.
+    pub fn snapshot(&self) -> RootSloSnapshot {
+        let now = Instant::now();
+        let activity = self.activity.lock();
+        RootSloSnapshot {
+            validation: self.percentiles(RootComputation::Validation),
+            building: self.percentiles(RootComputation::Building),
+            recent: activity.recent.iter().rev().copied().collect(),
+            in_flight: activity
+                .in_flight
+                .values()
+                .map(|(kind, block_number, started_at)| InFlightRoot {
+                    kind: *kind,
+                    block_number: *block_number,
+                    elapsed: now.saturating_duration_since(*started_at),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the latency percentiles of the computations in the current window.
+    pub fn percentiles(&self, kind: RootComputation) -> RootLatencyPercentiles {
+        let computation = self.computation(kind);
+        let mut samples = computation.samples.lock();
+        samples.evict(Instant::now(), self.window);
+        samples.percentiles()
+    }
+
+    fn record_at(&self, kind: RootComputation, duration: Duration, now: Instant) {
+        let computation = self.computation(kind);
+        let (percentiles, breach) = {
+            let mut samples = computation.samples.lock();
+            samples.push(now, duration);
+            samples.evict(now, self.window);
+            let percentiles = samples.percentiles();
+
+            let breach = if percentiles.samples < self.min_samples {
+                None
+            } else {
+                computation.thresholds.breach(&percentiles)
+            };
+            let was_breached = std::mem::replace(&mut samples.breached, breach.is_some());
+            if was_breached && breach.is_none() {
+                info!(target: "trie::slo", %kind, ?percentiles, "State root latency recovered");
+            }
+            (percentiles, breach.filter(|_| !was_breached))
+        };
+        computation.metrics.update(&percentiles);
+
+        let Some(breach) = breach else { return };
+        computation.metrics.breaches.increment(1);
+        warn!(
+            target: "trie::slo",
+            %kind,
+            percentile = breach.percentile,
+            observed = ?breach.observed,
+            threshold = ?breach.threshold,
+            "State root latency exceeds threshold"
+        );
+
+        if let Some(dir) = &self.dump_dir {
+            if !self.dumped.swap(true, Ordering::Relaxed) {
+                match self.write_dump(dir, kind, breach) {
+                    Ok(path) => {
+                        warn!(target: "trie::slo", path = %path.display(), "Wrote state root latency dump")
+                    }
+                    Err(err) => {
+                        error!(target: "trie::slo", %err, "Failed to write state root latency dump")
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_dump(&self, dir: &Path, kind: RootComputation, breach: Breach) -> io::Result<PathBuf> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let dump = SloDump {
+            timestamp,
+            kind: kind.as_str(),
+            breach,
+            window_secs: self.window.as_secs(),
+            computations: [&self.validation, &self.building]
+                .into_iter()
+                .map(|computation| {
+                    let samples = computation.samples.lock();
+                    let dump = ComputationDump {
+                        percentiles: samples.percentiles(),
+                        durations_us: samples
+                            .samples
+                            .iter()
+                            .map(|(_, duration)| duration.as_micros() as u64)
+                            .collect(),
+                    };
+                    (computation.kind.as_str(), dump)
+                })
+                .collect(),
+            stats: self.stats.iter().map(|(name, source)| (*name, (source.0)())).collect(),
+        };
+
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("root-slo-{timestamp}.json"));
+        serde_json::to_writer_pretty(BufWriter::new(File::create(&path)?), &dump)?;
+        Ok(path)
+    }
+
+    fn computation(&self, kind: RootComputation) -> &TrackedComputation {
+        match kind {
+            RootComputation::Validation => &self.validation,
+            RootComputation::Building => &self.building,
+        }
+    }
+
+    fn computation_mut(&mut self, kind: RootComputation) -> &mut TrackedComputation {
+        match kind {
+            RootComputation::Validation => &mut self.validation,
+            RootComputation::Building => &mut self.building,
+        }
+    }
+}
+
+/// Times a state root computation started with [RootSloTracker::start].
+#[derive(Debug)]
+pub struct RootTimer<'a> {
+    tracker: &'a RootSloTracker,
+    id: u64,
+    kind: RootComputation,
+    block_number: BlockNumber,
+    started_at: Instant,
+}
+
+impl<'a> RootTimer<'a> {
+    /// Records the duration of the computation and returns it.
+    pub fn finish(self) -> Duration {
+        let duration = self.started_at.elapsed();
+        self.tracker.activity.lock().push_recent(self.kind, Some(self.block_number), duration);
+        self.tracker.record_at(self.kind, duration, Instant::now());
+        duration
+    }
+}
+
+impl<'a> Drop for RootTimer<'a> {
+    fn drop(&mut self) {
+        self.tracker.activity.lock().in_flight.remove(&self.id);
+    }
+}
+
+/// A state root computation that finished recently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RecentRoot {
+    /// The purpose of the computation.
+    pub kind: RootComputation,
+    /// The block the root was computed for, if known.
+    pub block_number: Option<BlockNumber>,
+    /// The duration of the computation.
+    #[serde(rename = "duration_us", serialize_with = "serialize_micros")]
+    pub duration: Duration,
+    /// Milliseconds since the unix epoch at which the computation finished.
+    pub finished_at_ms: u64,
+}
+
+/// A state root computation in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InFlightRoot {
+    /// The purpose of the computation.
+    pub kind: RootComputation,
+    /// The block the root is computed for.
+    pub block_number: BlockNumber,
+    /// The time since the computation started.
+    #[serde(rename = "elapsed_us", serialize_with = "serialize_micros")]
+    pub elapsed: Duration,
+}
+
+/// A snapshot of the state root computations tracked by a [RootSloTracker].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RootSloSnapshot {
+    /// The latency percentiles of validating blocks in the current window.
+    pub validation: RootLatencyPercentiles,
+    /// The latency percentiles of building payloads in the current window.
+    pub building: RootLatencyPercentiles,
+    /// The most recent computations, newest first.
+    pub recent: Vec<RecentRoot>,
+    /// The computations in progress, in the order they started.
+    pub in_flight: Vec<InFlightRoot>,
+}
+
+/// The most recent and the running computations.
+#[derive(Debug, Default)]
+struct RootActivity {
+    /// The most recent computations, oldest first.
+    recent: VecDeque<RecentRoot>,
+    /// The running computations by id.
+    in_flight: BTreeMap<u64, (RootComputation, BlockNumber, Instant)>,
+    next_id: u64,
+}
+
+impl RootActivity {
+    fn start(&mut self, kind: RootComputation, block_number: BlockNumber, now: Instant) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.in_flight.insert(id, (kind, block_number, now));
+        id
+    }
+
+    fn push_recent(
+        &mut self,
+        kind: RootComputation,
+        block_number: Option<BlockNumber>,
+        duration: Duration,
+    ) {
+        if self.recent.len() == RECENT_ROOTS {
+            self.recent.pop_front();
+        }
+        let finished_at_ms =
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        self.recent.push_back(RecentRoot { kind, block_number, duration, finished_at_ms });
+    }
+}
+
+impl Default for RootSloTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_ROOT_SLO_WINDOW)
+    }
+}
+
+/// The window, thresholds and metrics of one kind of computation.
+#[derive(Debug)]
+struct TrackedComputation {
+    kind: RootComputation,
+    thresholds: RootSloThresholds,
+    samples: Mutex<SlidingWindow>,
+    metrics: RootSloMetrics,
+}
+
+impl TrackedComputation {
+    fn new(kind: RootComputation) -> Self {
+        Self {
+            kind,
+            thresholds: RootSloThresholds::default(),
+            samples: Mutex::new(SlidingWindow::default()),
+            metrics: RootSloMetrics::new_with_labels(&[("kind", kind.as_str())]),
+        }
+    }
+}
+
+/// The durations of the computations in the window, oldest first.
+#[derive(Debug, Default)]
+struct SlidingWindow {
+    samples: VecDeque<(Instant, Duration)>,
+    /// Whether a threshold was exceeded when the window was last evaluated.
+    breached: bool,
+}
+
+impl SlidingWindow {
+    fn push(&mut self, now: Instant, duration: Duration) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, duration));
+    }
+
+    fn evict(&mut self, now: Instant, window: Duration) {
+        while self
+            .samples
+            .front()
+            .map_or(false, |(recorded_at, _)| now.saturating_duration_since(*recorded_at) > window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    fn percentiles(&self) -> RootLatencyPercentiles {
+        let mut durations = self.samples.iter().map(|(_, duration)| *duration).collect::<Vec<_>>();
+        durations.sort_unstable();
+        // nearest-rank percentile
+        let percentile = |p: usize| {
+            let rank = (durations.len() * p).div_ceil(100);
+            durations.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+        };
+        RootLatencyPercentiles {
+            samples: durations.len(),
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// Renders stats for the dump.
+struct StatsSource(Box<dyn Fn() -> String + Send + Sync>);
+
+impl fmt::Debug for StatsSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatsSource").finish_non_exhaustive()
+    }
+}
+
+/// The dump written on the first breach.
+#[derive(Debug, Serialize)]
+struct SloDump {
+    /// Seconds since the unix epoch.
+    timestamp: u64,
+    /// The computation whose threshold was exceeded.
+    kind: &'static str,
+    breach: Breach,
+    window_secs: u64,
+    computations: BTreeMap<&'static str, ComputationDump>,
+    stats: BTreeMap<&'static str, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ComputationDump {
+    percentiles: RootLatencyPercentiles,
+    /// The durations in the window, oldest first.
+    durations_us: Vec<u64>,
+}
+
+/// State root latency metrics of one kind of computation.
+#[derive(Metrics)]
+#[metrics(scope = "trie.root_slo")]
+struct RootSloMetrics {
+    /// The number of computations in the window
+    samples: Gauge,
+    /// The median duration of the computations in the window, in seconds
+    p50: Gauge,
+    /// The 95th percentile duration of the computations in the window, in seconds
+    p95: Gauge,
+    /// The 99th percentile duration of the computations in the window, in seconds
+    p99: Gauge,
+    /// The number of times a percentile started exceeding its threshold
+    breaches: Counter,
+}
+
+impl RootSloMetrics {
+    fn update(&self, percentiles: &RootLatencyPercentiles) {
+        self.samples.set(percentiles.samples as f64);
+        self.p50.set(percentiles.p50.as_secs_f64());
+        self.p95.set(percentiles.p95.as_secs_f64());
+        self.p99.set(percentiles.p99.as_secs_f64());
+    }
+}
+
+fn serialize_micros<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_micros() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliding_window_percentiles() {
+        let tracker = RootSloTracker::new(Duration::from_secs(60));
+        let start = Instant::now();
+        for millis in 1..=100 {
+            tracker.record_at(RootComputation::Validation, Duration::from_millis(millis), start);
+        }
+        let percentiles = tracker.percentiles(RootComputation::Validation);
+        assert_eq!(percentiles.samples, 100);
+        assert_eq!(percentiles.p50, Duration::from_millis(50));
+        assert_eq!(percentiles.p95, Duration::from_millis(95));
+        assert_eq!(percentiles.p99, Duration::from_millis(99));
+        assert_eq!(tracker.percentiles(RootComputation::Building).samples, 0);
+
+        // all earlier durations fall out of the window
+        tracker.record_at(
+            RootComputation::Validation,
+            Duration::from_millis(7),
+            start + Duration::from_secs(61),
+        );
+        let percentiles = tracker.validation.samples.lock().percentiles();
+        assert_eq!(percentiles.samples, 1);
+        assert_eq!(percentiles.p99, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn breach_writes_dump_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = RootSloTracker::new(Duration::from_secs(60))
+            .with_thresholds(
+                RootComputation::Building,
+                RootSloThresholds { p99: Some(Duration::from_millis(500)), ..Default::default() },
+            )
+            .with_min_samples(2)
+            .with_dump_dir(dir.path().to_path_buf())
+            .with_stats_source("metrics", || "reth_metric 1".to_string());
+
+        let now = Instant::now();
+        tracker.record_at(RootComputation::Building, Duration::from_secs(1), now);
+        // below the minimum number of samples
+        assert!(!tracker.building.samples.lock().breached);
+
+        tracker.record_at(RootComputation::Building, Duration::from_millis(100), now);
+        assert!(tracker.building.samples.lock().breached);
+        // validation has no thresholds
+        tracker.record_at(RootComputation::Validation, Duration::from_secs(10), now);
+        tracker.record_at(RootComputation::Validation, Duration::from_secs(10), now);
+        assert!(!tracker.validation.samples.lock().breached);
+
+        let dumps = fs::read_dir(dir.path()).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(dumps.len(), 1);
+        let dump: serde_json::Value =
+            serde_json::from_reader(File::open(dumps[0].path()).unwrap()).unwrap();
+        assert_eq!(dump["kind"], "building");
+        assert_eq!(dump["breach"]["percentile"], "p99");
+        assert_eq!(dump["breach"]["threshold_us"], 500_000);
+        assert_eq!(
+            dump["computations"]["building"]["durations_us"],
+            serde_json::json!([1_000_000, 100_000])
+        );
+        assert_eq!(dump["stats"]["metrics"], "reth_metric 1");
+    }
+
+    #[test]
+    fn snapshot_tracks_recent_and_in_flight() {
+        let tracker = RootSloTracker::new(Duration::from_secs(60));
+        tracker.record(RootComputation::Building, Duration::from_millis(3));
+
+        let validation = tracker.start(RootComputation::Validation, 10);
+        let building = tracker.start(RootComputation::Building, 11);
+        let snapshot = tracker.snapshot();
+        assert_eq!(
+            snapshot
+                .in_flight
+                .iter()
+                .map(|root| (root.kind, root.block_number))
+                .collect::<Vec<_>>(),
+            vec![(RootComputation::Validation, 10), (RootComputation::Building, 11)]
+        );
+        assert_eq!(snapshot.recent.len(), 1);
+        assert_eq!(snapshot.recent[0].block_number, None);
+
+        let duration = validation.finish();
+        // unfinished computations are not recorded
+        drop(building);
+        let snapshot = tracker.snapshot();
+        assert!(snapshot.in_flight.is_empty());
+        assert_eq!(snapshot.recent.len(), 2);
+        assert_eq!(snapshot.recent[0].kind, RootComputation::Validation);
+        assert_eq!(snapshot.recent[0].block_number, Some(10));
+        assert_eq!(snapshot.recent[0].duration, duration);
+        assert_eq!(snapshot.validation.samples, 1);
+        assert_eq!(snapshot.building.samples, 1);
+
+        for block_number in 0..RECENT_ROOTS as u64 {
+            tracker.start(RootComputation::Validation, block_number).finish();
+        }
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.recent.len(), RECENT_ROOTS);
+        assert_eq!(snapshot.recent[0].block_number, Some(RECENT_ROOTS as u64 - 1));
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["recent"][0]["kind"], "validation");
+        assert!(json["recent"][0]["duration_us"].is_u64());
+    }
+}
@@ -0,0 +1,195 @@
+//! Retention of proof nodes with bounded memory.
+//!
+//! The proof retainer of the [HashBuilder](reth_primitives::trie::HashBuilder) keeps every retained
+//! node in memory until the proof is assembled, which does not scale to proofs of all slots of
+//! large contracts. [SpillingProofRetainer] buffers retained nodes up to a byte limit and spills
+//! them to an indexed temporary file, from which the proofs of individual targets are assembled.
+//!
+//! See [Proof::storage_multiproof_spilled](crate::proof::Proof::storage_multiproof_spilled).
+
+use crate::StorageRootError;
+use reth_db::DatabaseError;
+use reth_primitives::{trie::Nibbles, Bytes};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+use thiserror::Error;
+
+/// The default number of bytes of retained nodes buffered in memory before they are spilled.
+pub const DEFAULT_SPILL_BUFFER_BYTES: usize = 64 * 1024 * 1024;
+
+/// The default number of targets proven in a single pass over the trie.
+pub const DEFAULT_TARGETS_PER_PASS: usize = 4096;
+
+/// Error of a proof computation that spills retained nodes to disk.
+#[derive(Error, Debug)]
+pub enum ProofSpillError {
+    /// Storage root error.
+    #[error(transparent)]
+    StorageRoot(#[from] StorageRootError),
+    /// Error reading or writing the spill file.
+    #[error("failed to access proof spill file: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl From<DatabaseError> for ProofSpillError {
+    fn from(err: DatabaseError) -> Self {
+        Self::StorageRoot(err.into())
+    }
+}
+
+/// Proof node retainer that spills retained nodes to a temporary file.
+///
+/// Nodes are keyed by their path in the trie and retained at most once, so the nodes shared by the
+/// proofs of several targets are stored only once. The file is removed when the retainer is
+/// dropped.
+#[derive(Debug)]
+pub struct SpillingProofRetainer {
+    /// Nodes that were not spilled yet.
+    buffer: BTreeMap<Nibbles, Bytes>,
+    /// The total length of the buffered nodes.
+    buffered_bytes: usize,
+    /// The length of the buffered nodes above which they are spilled.
+    max_buffered_bytes: usize,
+    /// The spill file.
+    file: File,
+    /// The length of the spill file.
+    file_len: u64,
+    /// The offset and length of the spilled nodes in the file.
+    index: BTreeMap<Nibbles, (u64, u32)>,
+}
+
+impl SpillingProofRetainer {
+    /// Create a retainer that spills to a file in the temporary directory of the system once more
+    /// than `max_buffered_bytes` of nodes are buffered.
+    pub fn new(max_buffered_bytes: usize) -> io::Result<Self> {
+        Ok(Self::with_file(tempfile::tempfile()?, max_buffered_bytes))
+    }
+
+    /// Create a retainer that spills to a file in the given directory.
+    pub fn new_in(dir: impl AsRef<Path>, max_buffered_bytes: usize) -> io::Result<Self> {
+        Ok(Self::with_file(tempfile::tempfile_in(dir)?, max_buffered_bytes))
+    }
+
+    fn with_file(file: File, max_buffered_bytes: usize) -> Self {
+        Self {
+            buffer: BTreeMap::default(),
+            buffered_bytes: 0,
+            max_buffered_bytes,
+            file,
+            file_len: 0,
+            index: BTreeMap::default(),
+        }
+    }
+
+    /// Returns the number of retained nodes.
+    pub fn len(&self) -> usize {
+        self.buffer.len() + self.index.len()
+    }
+
+    /// Returns `true` if no nodes are retained.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes of nodes that were spilled to disk.
+    pub fn spilled_bytes(&self) -> u64 {
+        self.file_len
+    }
+
+    /// Retain the given nodes, spilling the buffer if it exceeds the limit. Nodes at paths that
+    /// are already retained are skipped.
+    pub fn retain(&mut self, nodes: impl IntoIterator<Item = (Nibbles, Bytes)>) -> io::Result<()> {
+        for (path, node) in nodes {
+            if self.index.contains_key(&path) || self.buffer.contains_key(&path) {
+                continue
+            }
+            self.buffered_bytes += node.len();
+            self.buffer.insert(path, node);
+        }
+
+        if self.buffered_bytes > self.max_buffered_bytes {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Write all buffered nodes to the end of the file.
+    fn spill(&mut self) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(self.buffered_bytes);
+        for (path, node) in std::mem::take(&mut self.buffer) {
+            self.index.insert(path, (self.file_len + buf.len() as u64, node.len() as u32));
+            buf.extend_from_slice(&node);
+        }
+
+        self.file.seek(SeekFrom::Start(self.file_len))?;
+        self.file.write_all(&buf)?;
+        self.file_len += buf.len() as u64;
+        self.buffered_bytes = 0;
+        Ok(())
+    }
+
+    /// Returns the retained node at the given path.
+    pub fn node(&mut self, path: &Nibbles) -> io::Result<Option<Bytes>> {
+        if let Some(node) = self.buffer.get(path) {
+            return Ok(Some(node.clone()))
+        }
+
+        let Some(&(offset, len)) = self.index.get(path) else { return Ok(None) };
+        let mut node = vec![0; len as usize];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut node)?;
+        Ok(Some(node.into()))
+    }
+
+    /// Returns the retained nodes on the path to the given target, starting from the root node.
+    pub fn proof(&mut self, target: &Nibbles) -> io::Result<Vec<Bytes>> {
+        let mut proof = Vec::new();
+        for len in 0..=target.len() {
+            let path = Nibbles::from_nibbles_unchecked(&target[..len]);
+            if let Some(node) = self.node(&path)? {
+                proof.push(node);
+            }
+        }
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spill_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut retainer = SpillingProofRetainer::new_in(dir.path(), 4).unwrap();
+
+        let root = Nibbles::from_nibbles_unchecked([]);
+        let branch = Nibbles::from_nibbles_unchecked([0x1]);
+        let leaf = Nibbles::from_nibbles_unchecked([0x1, 0x2]);
+        let other = Nibbles::from_nibbles_unchecked([0x3]);
+
+        retainer
+            .retain([
+                (root.clone(), Bytes::from_static(b"root")),
+                (other, Bytes::from_static(b"x")),
+            ])
+            .unwrap();
+        assert_eq!(retainer.spilled_bytes(), 5);
+
+        // nodes at retained paths are not duplicated
+        retainer
+            .retain([(root, Bytes::from_static(b"root")), (branch, Bytes::from_static(b"br"))])
+            .unwrap();
+        assert_eq!(retainer.len(), 3);
+        assert_eq!(retainer.spilled_bytes(), 5);
+
+        assert_eq!(
+            retainer.proof(&leaf).unwrap(),
+            Vec::from([Bytes::from_static(b"root"), Bytes::from_static(b"br")])
+        );
+    }
+}
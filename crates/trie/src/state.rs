@@ -1,6 +1,8 @@
 use crate::{
     hashed_cursor::HashedPostStateCursorFactory,
-    prefix_set::{PrefixSet, PrefixSetMut},
+    keccak::PrehashedKeys,
+    prefix_set::{PrefixSet, PrefixSetCompaction, PrefixSetMut},
+    trie_cursor::noop::NoopTrieCursorFactory,
     updates::TrieUpdates,
     StateRoot, StateRootError,
 };
@@ -53,17 +55,37 @@ impl HashedPostState {
     pub fn from_bundle_state<'a>(
         state: impl IntoIterator<Item = (&'a Address, &'a BundleAccount)>,
     ) -> Self {
-        let mut this = Self::default();
+        Self::from_bundle_state_prehashed(state, &PrehashedKeys::default())
+    }
 
-        for (address, account) in state {
-            let hashed_address = keccak256(address);
+    /// Initialize [HashedPostState] from bundle state like [HashedPostState::from_bundle_state],
+    /// but only hash the addresses and storage entries that are not among the given prehashed
+    /// keys.
+    pub fn from_bundle_state_prehashed<'a>(
+        state: impl IntoIterator<Item = (&'a Address, &'a BundleAccount)>,
+        prehashed: &PrehashedKeys,
+    ) -> Self {
+        let accounts = state.into_iter().collect::<Vec<_>>();
+
+        // Hash all addresses and slots in batches.
+        let hashed_addresses = prehashed
+            .hash_addresses(&accounts.iter().map(|(address, _)| **address).collect::<Vec<_>>());
+        let slots = accounts
+            .iter()
+            .flat_map(|(_, account)| account.storage.keys())
+            .map(|key| B256::new(key.to_be_bytes()))
+            .collect::<Vec<_>>();
+        let mut hashed_slots = prehashed.hash_slots(&slots).into_iter();
+
+        let mut this = Self::default();
+        for ((_, account), hashed_address) in accounts.into_iter().zip(hashed_addresses) {
             this.insert_account(hashed_address, account.info.clone().map(into_reth_acc));
 
             // insert storage.
             let mut hashed_storage = HashedStorage::new(account.status.was_destroyed());
 
-            for (key, value) in account.storage.iter() {
-                let hashed_key = keccak256(B256::new(key.to_be_bytes()));
+            // values are iterated in the same order as the keys were
+            for (value, hashed_key) in account.storage.values().zip(&mut hashed_slots) {
                 hashed_storage.insert_slot(hashed_key, value.present_value);
             }
             this.insert_hashed_storage(hashed_address, hashed_storage)
@@ -78,7 +100,7 @@ impl HashedPostState {
     /// NOTE: In order to have the resulting [HashedPostState] be a correct
     /// overlay of the plain state, the end of the range must be the current tip.
     pub fn from_revert_range<TX: DbTx>(
-        tx: TX,
+        tx: &TX,
         range: RangeInclusive<BlockNumber>,
     ) -> Result<Self, DatabaseError> {
         // A single map for aggregating state changes where each map value is a tuple
@@ -187,6 +209,37 @@ impl HashedPostState {
     /// The prefix sets contain the hashed account and storage keys that have been changed in the
     /// post state.
     pub fn construct_prefix_sets(&self) -> (PrefixSet, AHashMap<B256, PrefixSet>) {
+        let (account_prefix_set, storage_prefix_set) = self.construct_prefix_sets_mut();
+        (
+            account_prefix_set.freeze(),
+            storage_prefix_set.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
+        )
+    }
+
+    /// Construct [PrefixSet] from hashed post state like [HashedPostState::construct_prefix_sets]
+    /// and compact them, see [PrefixSetMut::compact].
+    ///
+    /// Returns the prefix sets along with the aggregated statistics of the compaction.
+    pub fn construct_compacted_prefix_sets(
+        &self,
+        min_dense_children: usize,
+    ) -> (PrefixSet, AHashMap<B256, PrefixSet>, PrefixSetCompaction) {
+        let (mut account_prefix_set, storage_prefix_set) = self.construct_prefix_sets_mut();
+        let mut compaction = account_prefix_set.compact(min_dense_children);
+        let storage_prefix_set = storage_prefix_set
+            .into_iter()
+            .map(|(hashed_address, mut prefix_set)| {
+                compaction.extend(prefix_set.compact(min_dense_children));
+                (hashed_address, prefix_set.freeze())
+            })
+            .collect();
+        (account_prefix_set.freeze(), storage_prefix_set, compaction)
+    }
+
+    /// Construct [PrefixSetMut] from hashed post state.
+    /// Same as [HashedPostState::construct_prefix_sets], but the returned sets can be extended
+    /// and sent across threads.
+    pub fn construct_prefix_sets_mut(&self) -> (PrefixSetMut, AHashMap<B256, PrefixSetMut>) {
         // Initialize prefix sets.
         let mut account_prefix_set = PrefixSetMut::default();
         let mut storage_prefix_set: AHashMap<B256, PrefixSetMut> = AHashMap::default();
@@ -212,14 +265,11 @@ impl HashedPostState {
             }
         }
 
-        (
-            account_prefix_set.freeze(),
-            storage_prefix_set.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
-        )
+        (account_prefix_set, storage_prefix_set)
     }
 
     /// Returns [StateRoot] calculator based on database and in-memory state.
-    fn state_root_calculator<'a, TX: DbTx>(
+    pub fn state_root_calculator<'a, TX: DbTx>(
         &self,
         tx: &'a TX,
     ) -> StateRoot<&'a TX, HashedPostStateCursorFactory<'a, '_, TX>> {
@@ -232,6 +282,27 @@ impl HashedPostState {
             .with_destroyed_accounts(self.destroyed_accounts())
     }
 
+    /// Returns [StateRoot] calculator like [Self::state_root_calculator], but with compacted prefix
+    /// sets, along with the statistics of the compaction.
+    ///
+    /// This trades walking more of the trie for faster prefix set lookups, which pays off for
+    /// large post states.
+    pub fn compacted_state_root_calculator<'a, TX: DbTx>(
+        &self,
+        tx: &'a TX,
+        min_dense_children: usize,
+    ) -> (StateRoot<&'a TX, HashedPostStateCursorFactory<'a, '_, TX>>, PrefixSetCompaction) {
+        let (account_prefix_set, storage_prefix_set, compaction) =
+            self.construct_compacted_prefix_sets(min_dense_children);
+        let hashed_cursor_factory = HashedPostStateCursorFactory::new(tx, self);
+        let calculator = StateRoot::from_tx(tx)
+            .with_hashed_cursor_factory(hashed_cursor_factory)
+            .with_changed_account_prefixes(account_prefix_set)
+            .with_changed_storage_prefixes(storage_prefix_set)
+            .with_destroyed_accounts(self.destroyed_accounts());
+        (calculator, compaction)
+    }
+
     /// Calculate the state root for this [HashedPostState].
     /// Internally, this method retrieves prefixsets and uses them
     /// to calculate incremental state root.
@@ -274,6 +345,17 @@ impl HashedPostState {
     ) -> Result<(B256, TrieUpdates), StateRootError> {
         self.state_root_calculator(tx).root_with_updates()
     }
+
+    /// Calculates the state root for this [HashedPostState] from scratch, ignoring the
+    /// intermediate trie nodes stored in the database.
+    ///
+    /// This hashes the entire state and is only meant as a reference for [Self::state_root].
+    pub fn state_root_from_scratch<TX: DbTx>(&self, tx: &TX) -> Result<B256, StateRootError> {
+        StateRoot::from_tx(tx)
+            .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, self))
+            .with_trie_cursor_factory(NoopTrieCursorFactory)
+            .root()
+    }
 }
 
 /// The post state account storage with hashed slots.
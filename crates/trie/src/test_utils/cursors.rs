@@ -0,0 +1,384 @@
+use crate::{
+    fixture::{CursorTrace, TraceHashedCursor},
+    hashed_cursor::{HashedAccountCursor, HashedCursorFactory, HashedStorageCursor},
+    trie_cursor::{TrieCursor, TrieCursorFactory},
+    updates::TrieKey,
+};
+use parking_lot::Mutex;
+use reth_db::DatabaseError;
+use reth_primitives::{
+    trie::{BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey},
+    Account, StorageEntry, B256, U256,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// The error code of injected [DatabaseError::Read] failures.
+pub const INJECTED_FAILURE_CODE: i32 = -30_000;
+
+/// A cursor operation counted by the [TestCursorFactory].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorCall {
+    /// [TrieCursorFactory::account_trie_cursor]
+    AccountTrieCursor,
+    /// [TrieCursorFactory::storage_tries_cursor]
+    StorageTrieCursor,
+    /// [TrieCursor::seek] on an account trie cursor.
+    AccountTrieSeek,
+    /// [TrieCursor::seek_exact] on an account trie cursor.
+    AccountTrieSeekExact,
+    /// [TrieCursor::seek] on a storage trie cursor.
+    StorageTrieSeek,
+    /// [TrieCursor::seek_exact] on a storage trie cursor.
+    StorageTrieSeekExact,
+    /// [HashedCursorFactory::hashed_account_cursor]
+    HashedAccountCursor,
+    /// [HashedCursorFactory::hashed_storage_cursor]
+    HashedStorageCursor,
+    /// [HashedAccountCursor::seek]
+    HashedAccountSeek,
+    /// [HashedAccountCursor::next]
+    HashedAccountNext,
+    /// [HashedStorageCursor::is_storage_empty]
+    IsStorageEmpty,
+    /// [HashedStorageCursor::seek]
+    HashedStorageSeek,
+    /// [HashedStorageCursor::next]
+    HashedStorageNext,
+}
+
+/// Call counts and injected failures, shared by a [TestCursorFactory] and all of its cursors.
+#[derive(Debug, Default)]
+struct CursorCalls {
+    /// The number of calls per operation, including failed ones.
+    counts: HashMap<CursorCall, usize>,
+    /// The call numbers, starting at 1, at which operations fail.
+    failures: HashMap<CursorCall, Vec<usize>>,
+}
+
+/// In-memory trie and hashed cursor factory for tests.
+///
+/// The cursors iterate over a dataset assembled with the [TestCursorFactoryBuilder], count every
+/// call per [CursorCall], and return a [DatabaseError] on the calls configured with
+/// [TestCursorFactory::fail_on]. Clones share the dataset, the counts and the failures.
+#[derive(Debug, Clone, Default)]
+pub struct TestCursorFactory {
+    data: Arc<CursorTrace>,
+    calls: Arc<Mutex<CursorCalls>>,
+}
+
+impl TestCursorFactory {
+    /// Returns a builder for the dataset of the factory.
+    pub fn builder() -> TestCursorFactoryBuilder {
+        TestCursorFactoryBuilder::default()
+    }
+
+    /// Creates a factory over the entries of a recorded [CursorTrace].
+    pub fn from_trace(trace: CursorTrace) -> Self {
+        Self { data: Arc::new(trace), calls: Arc::default() }
+    }
+
+    /// Returns the dataset of the factory.
+    pub fn data(&self) -> &CursorTrace {
+        &self.data
+    }
+
+    /// Makes the `nth` call of the operation, counting from 1 and including past calls, return
+    /// a [DatabaseError::Read] with [INJECTED_FAILURE_CODE].
+    pub fn fail_on(&self, call: CursorCall, nth: usize) {
+        self.calls.lock().failures.entry(call).or_default().push(nth);
+    }
+
+    /// Returns the number of calls of the operation so far.
+    pub fn calls(&self, call: CursorCall) -> usize {
+        self.calls.lock().counts.get(&call).copied().unwrap_or_default()
+    }
+
+    /// Asserts the number of calls of the operation so far.
+    #[track_caller]
+    pub fn assert_calls(&self, call: CursorCall, expected: usize) {
+        let calls = self.calls(call);
+        assert_eq!(calls, expected, "unexpected number of {call:?} calls");
+    }
+
+    /// Resets the call counts, keeping the injected failures that were not hit yet.
+    pub fn reset_calls(&self) {
+        let mut calls = self.calls.lock();
+        let counts = std::mem::take(&mut calls.counts);
+        for (call, failures) in calls.failures.iter_mut() {
+            let count = counts.get(call).copied().unwrap_or_default();
+            failures.retain_mut(|nth| {
+                if *nth <= count {
+                    return false
+                }
+                *nth -= count;
+                true
+            });
+        }
+    }
+
+    fn record(&self, call: CursorCall) -> Result<(), DatabaseError> {
+        record(&self.calls, call)
+    }
+}
+
+/// Counts the call and returns the injected failure, if any.
+fn record(calls: &Mutex<CursorCalls>, call: CursorCall) -> Result<(), DatabaseError> {
+    let mut calls = calls.lock();
+    let count = calls.counts.entry(call).or_default();
+    *count += 1;
+    let count = *count;
+    if calls.failures.get(&call).is_some_and(|failures| failures.contains(&count)) {
+        return Err(DatabaseError::Read(INJECTED_FAILURE_CODE))
+    }
+    Ok(())
+}
+
+/// Builder of the dataset of a [TestCursorFactory].
+#[derive(Debug, Default)]
+pub struct TestCursorFactoryBuilder {
+    data: CursorTrace,
+}
+
+impl TestCursorFactoryBuilder {
+    /// Adds a node of the account trie.
+    pub fn account_node(mut self, path: Nibbles, node: BranchNodeCompact) -> Self {
+        self.data.account_trie.0.insert(path, node);
+        self
+    }
+
+    /// Adds a node of the storage trie of the hashed address.
+    pub fn storage_node(
+        mut self,
+        hashed_address: B256,
+        path: Nibbles,
+        node: BranchNodeCompact,
+    ) -> Self {
+        self.data.storage_tries.entry(hashed_address).or_default().0.insert(path, node);
+        self
+    }
+
+    /// Adds a hashed account.
+    pub fn hashed_account(mut self, hashed_address: B256, account: Account) -> Self {
+        self.data.hashed_accounts.insert(hashed_address, account);
+        self
+    }
+
+    /// Adds hashed accounts.
+    pub fn hashed_accounts(mut self, accounts: impl IntoIterator<Item = (B256, Account)>) -> Self {
+        self.data.hashed_accounts.extend(accounts);
+        self
+    }
+
+    /// Adds a hashed storage slot of the hashed address.
+    pub fn hashed_slot(mut self, hashed_address: B256, slot: B256, value: U256) -> Self {
+        self.data.hashed_storages.entry(hashed_address).or_default().insert(slot, value);
+        self.data.non_empty_storages.insert(hashed_address);
+        self
+    }
+
+    /// Adds hashed storage slots of the hashed address.
+    pub fn hashed_storage(
+        mut self,
+        hashed_address: B256,
+        slots: impl IntoIterator<Item = (B256, U256)>,
+    ) -> Self {
+        let storage = self.data.hashed_storages.entry(hashed_address).or_default();
+        storage.extend(slots);
+        if !storage.is_empty() {
+            self.data.non_empty_storages.insert(hashed_address);
+        }
+        self
+    }
+
+    /// Builds the factory.
+    pub fn build(self) -> TestCursorFactory {
+        TestCursorFactory::from_trace(self.data)
+    }
+}
+
+impl TrieCursorFactory for TestCursorFactory {
+    fn account_trie_cursor(
+        &self,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibbles> + '_>, DatabaseError> {
+        self.record(CursorCall::AccountTrieCursor)?;
+        Ok(Box::new(TestTrieCursor {
+            inner: self.data.account_trie_cursor()?,
+            calls: &self.calls,
+            seek: CursorCall::AccountTrieSeek,
+            seek_exact: CursorCall::AccountTrieSeekExact,
+        }))
+    }
+
+    fn storage_tries_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError> {
+        self.record(CursorCall::StorageTrieCursor)?;
+        Ok(Box::new(TestTrieCursor {
+            inner: self.data.storage_tries_cursor(hashed_address)?,
+            calls: &self.calls,
+            seek: CursorCall::StorageTrieSeek,
+            seek_exact: CursorCall::StorageTrieSeekExact,
+        }))
+    }
+}
+
+impl<'a> HashedCursorFactory for &'a TestCursorFactory {
+    type AccountCursor = TestAccountCursor<'a>;
+    type StorageCursor = TestStorageCursor<'a>;
+
+    fn hashed_account_cursor(&self) -> Result<Self::AccountCursor, DatabaseError> {
+        self.record(CursorCall::HashedAccountCursor)?;
+        let data: &'a CursorTrace = &self.data;
+        Ok(TestAccountCursor { inner: data.hashed_account_cursor()?, calls: &self.calls })
+    }
+
+    fn hashed_storage_cursor(&self) -> Result<Self::StorageCursor, DatabaseError> {
+        self.record(CursorCall::HashedStorageCursor)?;
+        let data: &'a CursorTrace = &self.data;
+        Ok(TestStorageCursor { inner: data.hashed_storage_cursor()?, calls: &self.calls })
+    }
+}
+
+/// Account or storage trie cursor of a [TestCursorFactory].
+struct TestTrieCursor<'a, K> {
+    inner: Box<dyn TrieCursor<Key = K> + 'a>,
+    calls: &'a Mutex<CursorCalls>,
+    seek: CursorCall,
+    seek_exact: CursorCall,
+}
+
+impl<'a, K: From<Vec<u8>>> TrieCursor for TestTrieCursor<'a, K> {
+    type Key = K;
+
+    fn seek_exact(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        record(self.calls, self.seek_exact)?;
+        self.inner.seek_exact(key)
+    }
+
+    fn seek(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        record(self.calls, self.seek)?;
+        self.inner.seek(key)
+    }
+
+    fn current(&mut self) -> Result<Option<TrieKey>, DatabaseError> {
+        self.inner.current()
+    }
+}
+
+/// Hashed account cursor of a [TestCursorFactory].
+#[derive(Debug)]
+pub struct TestAccountCursor<'a> {
+    inner: TraceHashedCursor<'a>,
+    calls: &'a Mutex<CursorCalls>,
+}
+
+impl<'a> HashedAccountCursor for TestAccountCursor<'a> {
+    fn seek(&mut self, key: B256) -> Result<Option<(B256, Account)>, DatabaseError> {
+        record(self.calls, CursorCall::HashedAccountSeek)?;
+        HashedAccountCursor::seek(&mut self.inner, key)
+    }
+
+    fn next(&mut self) -> Result<Option<(B256, Account)>, DatabaseError> {
+        record(self.calls, CursorCall::HashedAccountNext)?;
+        HashedAccountCursor::next(&mut self.inner)
+    }
+}
+
+/// Hashed storage cursor of a [TestCursorFactory].
+#[derive(Debug)]
+pub struct TestStorageCursor<'a> {
+    inner: TraceHashedCursor<'a>,
+    calls: &'a Mutex<CursorCalls>,
+}
+
+impl<'a> HashedStorageCursor for TestStorageCursor<'a> {
+    fn is_storage_empty(&mut self, key: B256) -> Result<bool, DatabaseError> {
+        record(self.calls, CursorCall::IsStorageEmpty)?;
+        self.inner.is_storage_empty(key)
+    }
+
+    fn seek(&mut self, key: B256, subkey: B256) -> Result<Option<StorageEntry>, DatabaseError> {
+        record(self.calls, CursorCall::HashedStorageSeek)?;
+        HashedStorageCursor::seek(&mut self.inner, key, subkey)
+    }
+
+    fn next(&mut self) -> Result<Option<StorageEntry>, DatabaseError> {
+        record(self.calls, CursorCall::HashedStorageNext)?;
+        HashedStorageCursor::next(&mut self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{state_root, storage_root},
+        StateRoot, StorageRoot,
+    };
+    use reth_primitives::{keccak256, Address};
+
+    fn storage(i: u8) -> Vec<(B256, U256)> {
+        (1..=i).map(|slot| (B256::with_last_byte(slot), U256::from(slot))).collect()
+    }
+
+    fn factory() -> TestCursorFactory {
+        let mut builder = TestCursorFactory::builder();
+        for i in 1..=10u8 {
+            let hashed_address = keccak256(Address::with_last_byte(i));
+            builder = builder
+                .hashed_account(hashed_address, Account { nonce: i as u64, ..Default::default() })
+                .hashed_storage(
+                    hashed_address,
+                    storage(i).into_iter().map(|(slot, value)| (keccak256(slot), value)),
+                );
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn computes_roots_and_counts_calls() {
+        let factory = factory();
+
+        let address = Address::with_last_byte(3);
+        let root = StorageRoot::new(factory.clone(), &factory, address).root().unwrap();
+        assert_eq!(root, storage_root(storage(3).into_iter()));
+        factory.assert_calls(CursorCall::HashedStorageSeek, 1);
+        factory.assert_calls(CursorCall::HashedStorageNext, 3);
+        factory.assert_calls(CursorCall::HashedAccountSeek, 0);
+
+        factory.reset_calls();
+        let root = StateRoot::new(factory.clone(), &factory).root().unwrap();
+        let expected = state_root((1..=10u8).map(|i| {
+            (
+                Address::with_last_byte(i),
+                (Account { nonce: i as u64, ..Default::default() }, storage(i)),
+            )
+        }));
+        assert_eq!(root, expected);
+        factory.assert_calls(CursorCall::AccountTrieCursor, 1);
+        factory.assert_calls(CursorCall::HashedAccountNext, 10);
+    }
+
+    #[test]
+    fn injects_failures() {
+        let factory = factory();
+        factory.fail_on(CursorCall::HashedStorageNext, 5);
+
+        let address = Address::with_last_byte(10);
+        let err = StorageRoot::new(factory.clone(), &factory, address).root().unwrap_err();
+        assert!(err.to_string().contains(&INJECTED_FAILURE_CODE.to_string()));
+        factory.assert_calls(CursorCall::HashedStorageNext, 5);
+
+        // the failure is consumed, so a retry succeeds
+        factory.reset_calls();
+        let root = StorageRoot::new(factory.clone(), &factory, address).root().unwrap();
+        assert_eq!(root, storage_root(storage(10).into_iter()));
+    }
+}
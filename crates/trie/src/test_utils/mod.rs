@@ -6,6 +6,11 @@ use reth_primitives::{
 /// Re-export of [triehash].
 pub use triehash;
 
+mod cursors;
+pub use cursors::*;
+
+pub mod workload;
+
 /// Compute the state root of a given set of accounts using [triehash::sec_trie_root].
 pub fn state_root<I, S>(accounts: I) -> B256
 where
@@ -0,0 +1,333 @@
+//! Deterministic synthetic state transitions for trie benchmarks.
+//!
+//! A [WorkloadGenerator] creates a state of the configured size and a sequence of transitions on
+//! top of it, both fully determined by a seed, so state root computations can be benchmarked at
+//! different state sizes without a synced datadir:
+//!
+//! ```no_run
+//! # fn bench<TX: reth_db::transaction::DbTxMut + reth_db::transaction::DbTx>(tx: &TX) {
+//! use reth_trie::test_utils::workload::{WorkloadConfig, WorkloadGenerator};
+//!
+//! let config = WorkloadConfig { accounts: 1_000_000, ..Default::default() };
+//! let mut generator = WorkloadGenerator::new(config, 1);
+//! generator.write_state(tx).unwrap();
+//! let post_state = generator.next_transition();
+//! let root = post_state.state_root(tx).unwrap();
+//! assert_eq!(root, generator.state_root());
+//! # }
+//! ```
+
+use crate::{
+    test_utils::state_root_prehashed, HashedPostState, HashedStorage, StateRoot, StateRootError,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reth_db::{
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives::{Account, StorageEntry, B256, U256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A bounded power law distribution of integers: small values are common and large ones rare.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerLaw {
+    /// The smallest value.
+    pub min: usize,
+    /// The largest value.
+    pub max: usize,
+    /// The exponent of the tail; the larger it is, the rarer large values are.
+    pub exponent: f64,
+}
+
+impl PowerLaw {
+    /// Samples a value by inverse transform sampling of a Pareto distribution, capped at
+    /// [PowerLaw::max].
+    pub fn sample(&self, rng: &mut impl Rng) -> usize {
+        let uniform: f64 = rng.gen();
+        let value = self.min as f64 * (1.0 - uniform).powf(-1.0 / self.exponent);
+        (value as usize).clamp(self.min, self.max)
+    }
+}
+
+/// The shape of a synthetic workload.
+///
+/// The defaults approximate mainnet blocks of late 2023: about 1 000 changed accounts per block,
+/// few of which are created or destroyed, and storage writes concentrated on a small number of
+/// popular contracts, most of which have few slots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadConfig {
+    /// The number of accounts of the initial state.
+    pub accounts: usize,
+    /// The share of accounts that are contracts with storage.
+    pub contract_ratio: f64,
+    /// The number of slots of a contract.
+    pub storage_slots: PowerLaw,
+    /// The number of changed accounts per transition.
+    pub changed_accounts: usize,
+    /// The share of changes that create an account.
+    pub created_ratio: f64,
+    /// The share of changes that destroy an account.
+    pub destroyed_ratio: f64,
+    /// The share of changes that write the storage of a contract.
+    pub storage_change_ratio: f64,
+    /// The popularity rank of a contract whose storage is written, the lowest ranks being the
+    /// oldest contracts.
+    pub contract_popularity: PowerLaw,
+    /// The number of slots written by a storage change.
+    pub changed_slots: PowerLaw,
+    /// The share of slot writes that create a slot.
+    pub created_slot_ratio: f64,
+    /// The share of slot writes that clear a slot.
+    pub cleared_slot_ratio: f64,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            accounts: 100_000,
+            contract_ratio: 0.2,
+            storage_slots: PowerLaw { min: 1, max: 100_000, exponent: 1.2 },
+            changed_accounts: 1_000,
+            created_ratio: 0.05,
+            destroyed_ratio: 0.001,
+            storage_change_ratio: 0.4,
+            contract_popularity: PowerLaw { min: 1, max: usize::MAX, exponent: 0.8 },
+            changed_slots: PowerLaw { min: 1, max: 200, exponent: 1.5 },
+            created_slot_ratio: 0.2,
+            cleared_slot_ratio: 0.1,
+        }
+    }
+}
+
+/// An account of the generated state with its storage.
+type StateAccount = (Account, BTreeMap<B256, U256>);
+
+/// The changes of a transition to an account.
+#[derive(Debug, Default)]
+struct AccountChange {
+    /// Whether the account was destroyed during the transition.
+    wiped: bool,
+    /// The written slots.
+    slots: BTreeSet<B256>,
+}
+
+/// Generates a state and transitions on top of it, see the [module docs](self).
+#[derive(Debug)]
+pub struct WorkloadGenerator {
+    config: WorkloadConfig,
+    rng: StdRng,
+    /// The current state by hashed address.
+    state: BTreeMap<B256, StateAccount>,
+    /// The hashed addresses of all accounts, to pick accounts uniformly.
+    accounts: Vec<B256>,
+    /// The hashed addresses of the contracts, from the oldest to the newest.
+    contracts: Vec<B256>,
+}
+
+impl WorkloadGenerator {
+    /// Creates the initial state of the configured size from the seed.
+    pub fn new(config: WorkloadConfig, seed: u64) -> Self {
+        let mut this = Self {
+            rng: StdRng::seed_from_u64(seed),
+            state: BTreeMap::new(),
+            accounts: Vec::with_capacity(config.accounts),
+            contracts: Vec::new(),
+            config,
+        };
+        for _ in 0..this.config.accounts {
+            let is_contract = this.rng.gen_bool(this.config.contract_ratio);
+            let slots =
+                if is_contract { this.config.storage_slots.sample(&mut this.rng) } else { 0 };
+            this.create_account(is_contract, slots);
+        }
+        this
+    }
+
+    /// Returns the current state by hashed address.
+    pub fn state(&self) -> &BTreeMap<B256, (Account, BTreeMap<B256, U256>)> {
+        &self.state
+    }
+
+    /// Computes the root of the current state in memory.
+    pub fn state_root(&self) -> B256 {
+        state_root_prehashed(self.state.iter().map(|(hashed_address, (account, storage))| {
+            (*hashed_address, (*account, storage.iter().map(|(k, v)| (*k, *v))))
+        }))
+    }
+
+    /// Writes the current state to the hashed tables of an empty database, computes its trie and
+    /// returns the state root.
+    pub fn write_state<TX: DbTx + DbTxMut>(&self, tx: &TX) -> Result<B256, StateRootError> {
+        for (hashed_address, (account, storage)) in &self.state {
+            tx.put::<tables::HashedAccount>(*hashed_address, *account)?;
+            for (slot, value) in storage {
+                tx.put::<tables::HashedStorage>(
+                    *hashed_address,
+                    StorageEntry { key: *slot, value: *value },
+                )?;
+            }
+        }
+        let (root, updates) = StateRoot::from_tx(tx).root_with_updates()?;
+        updates.flush(tx)?;
+        Ok(root)
+    }
+
+    /// Generates the next transition, applies it to the current state and returns its changes.
+    pub fn next_transition(&mut self) -> HashedPostState {
+        let mut changes = BTreeMap::<B256, AccountChange>::new();
+        for _ in 0..self.config.changed_accounts {
+            let roll: f64 = self.rng.gen();
+            let mut threshold = self.config.created_ratio;
+            if roll < threshold || self.accounts.is_empty() {
+                let is_contract = self.rng.gen_bool(self.config.contract_ratio);
+                let slots =
+                    if is_contract { self.config.changed_slots.sample(&mut self.rng) } else { 0 };
+                let hashed_address = self.create_account(is_contract, slots);
+                let change = changes.entry(hashed_address).or_default();
+                change.slots.extend(self.state[&hashed_address].1.keys().copied());
+                continue
+            }
+
+            threshold += self.config.destroyed_ratio;
+            if roll < threshold {
+                let hashed_address = self.destroy_account();
+                let change = changes.entry(hashed_address).or_default();
+                change.wiped = true;
+                change.slots.clear();
+                continue
+            }
+
+            threshold += self.config.storage_change_ratio;
+            if roll < threshold && !self.contracts.is_empty() {
+                let rank = self.config.contract_popularity.sample(&mut self.rng);
+                let hashed_address = self.contracts[(rank - 1).min(self.contracts.len() - 1)];
+                let slots = self.write_slots(hashed_address);
+                changes.entry(hashed_address).or_default().slots.extend(slots);
+                continue
+            }
+
+            let hashed_address = self.accounts[self.rng.gen_range(0..self.accounts.len())];
+            let account = &mut self.state.get_mut(&hashed_address).expect("account exists").0;
+            account.nonce += 1;
+            account.balance = U256::from(self.rng.gen::<u64>());
+            changes.entry(hashed_address).or_default();
+        }
+
+        let mut post_state = HashedPostState::default();
+        for (hashed_address, change) in changes {
+            let state_account = self.state.get(&hashed_address);
+            post_state.insert_account(hashed_address, state_account.map(|(account, _)| *account));
+            let mut storage = HashedStorage::new(change.wiped);
+            for slot in change.slots {
+                let value = state_account
+                    .and_then(|(_, storage)| storage.get(&slot).copied())
+                    .unwrap_or_default();
+                storage.insert_slot(slot, value);
+            }
+            post_state.insert_hashed_storage(hashed_address, storage);
+        }
+        post_state.sorted()
+    }
+
+    /// Creates an account with the given number of slots and returns its hashed address.
+    fn create_account(&mut self, is_contract: bool, slots: usize) -> B256 {
+        let hashed_address = B256::from(self.rng.gen::<[u8; 32]>());
+        let account = Account {
+            nonce: self.rng.gen_range(0..1_000),
+            balance: U256::from(self.rng.gen::<u64>()),
+            bytecode_hash: is_contract.then(|| B256::from(self.rng.gen::<[u8; 32]>())),
+        };
+        let storage = (0..slots).map(|_| self.random_slot()).collect();
+        self.state.insert(hashed_address, (account, storage));
+        self.accounts.push(hashed_address);
+        if is_contract {
+            self.contracts.push(hashed_address);
+        }
+        hashed_address
+    }
+
+    /// Destroys a random account and returns its hashed address.
+    fn destroy_account(&mut self) -> B256 {
+        let hashed_address = self.accounts.swap_remove(self.rng.gen_range(0..self.accounts.len()));
+        let (account, _) = self.state.remove(&hashed_address).expect("account exists");
+        if account.bytecode_hash.is_some() {
+            self.contracts.retain(|contract| *contract != hashed_address);
+        }
+        hashed_address
+    }
+
+    /// Creates, changes and clears slots of the contract and returns the written slots.
+    fn write_slots(&mut self, hashed_address: B256) -> Vec<B256> {
+        let count = self.config.changed_slots.sample(&mut self.rng);
+        let mut written = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (new_slot, value) = self.random_slot();
+            let storage = &mut self.state.get_mut(&hashed_address).expect("contract exists").1;
+            let roll: f64 = self.rng.gen();
+            if storage.is_empty() || roll < self.config.created_slot_ratio {
+                storage.insert(new_slot, value);
+                written.push(new_slot);
+                continue
+            }
+
+            // slots are uniformly distributed, so the slot after a random key is a random slot
+            let slot = storage
+                .range(new_slot..)
+                .next()
+                .or_else(|| storage.iter().next())
+                .map(|(slot, _)| *slot)
+                .expect("storage is not empty");
+            if roll < self.config.created_slot_ratio + self.config.cleared_slot_ratio {
+                storage.remove(&slot);
+            } else {
+                storage.insert(slot, value);
+            }
+            written.push(slot);
+        }
+        written
+    }
+
+    /// Returns a random slot with a non-zero value.
+    fn random_slot(&mut self) -> (B256, U256) {
+        (B256::from(self.rng.gen::<[u8; 32]>()), U256::from(self.rng.gen_range(1..=u64::MAX)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{database::Database, test_utils::create_test_rw_db};
+
+    fn config() -> WorkloadConfig {
+        WorkloadConfig {
+            accounts: 500,
+            storage_slots: PowerLaw { min: 1, max: 100, exponent: 1.2 },
+            changed_accounts: 100,
+            destroyed_ratio: 0.05,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn generates_same_workload_from_seed() {
+        let mut first = WorkloadGenerator::new(config(), 7);
+        let mut second = WorkloadGenerator::new(config(), 7);
+        assert_eq!(first.state(), second.state());
+        for _ in 0..3 {
+            assert_eq!(first.next_transition(), second.next_transition());
+        }
+        assert_eq!(first.state_root(), second.state_root());
+    }
+
+    #[test]
+    fn transitions_compute_state_root() {
+        let mut generator = WorkloadGenerator::new(config(), 1);
+        for _ in 0..3 {
+            let db = create_test_rw_db();
+            let tx = db.tx_mut().unwrap();
+            assert_eq!(generator.write_state(&tx).unwrap(), generator.state_root());
+            let post_state = generator.next_transition();
+            assert_eq!(post_state.state_root(&tx).unwrap(), generator.state_root());
+        }
+    }
+}
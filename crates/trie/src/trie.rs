@@ -1,6 +1,8 @@
 use crate::{
     hashed_cursor::{HashedCursorFactory, HashedStorageCursor},
+    nibbles,
     node_iter::{AccountNode, AccountNodeIter, StorageNode, StorageNodeIter},
+    precomputed::PrecomputedStorageRoots,
     prefix_set::{PrefixSet, PrefixSetLoader, PrefixSetMut},
     progress::{IntermediateStateRootState, StateRootProgress},
     trie_cursor::TrieCursorFactory,
@@ -14,10 +16,10 @@ use reth_db::transaction::DbTx;
 use reth_primitives::{
     constants::EMPTY_ROOT_HASH,
     keccak256,
-    trie::{HashBuilder, Nibbles, TrieAccount},
+    trie::{HashBuilder, TrieAccount},
     Address, BlockNumber, B256,
 };
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, sync::Arc};
 use tracing::{debug, trace};
 
 /// StateRoot is used to compute the root node of a state trie.
@@ -38,9 +40,25 @@ pub struct StateRoot<T, H> {
     previous_state: Option<IntermediateStateRootState>,
     /// The number of updates after which the intermediate progress should be returned.
     threshold: u64,
+    /// The storage roots computed ahead of the walk.
+    precomputed_storage_roots: Option<Arc<PrecomputedStorageRoots>>,
 }
 
 impl<T, H> StateRoot<T, H> {
+    /// Creates a new [StateRoot] instance from the given cursor factories.
+    pub fn new(trie_cursor_factory: T, hashed_cursor_factory: H) -> Self {
+        Self {
+            trie_cursor_factory,
+            hashed_cursor_factory,
+            changed_account_prefixes: PrefixSetMut::default().freeze(),
+            changed_storage_prefixes: AHashMap::default(),
+            destroyed_accounts: AHashSet::default(),
+            previous_state: None,
+            threshold: 100_000,
+            precomputed_storage_roots: None,
+        }
+    }
+
     /// Set the changed account prefixes.
     pub fn with_changed_account_prefixes(mut self, prefixes: PrefixSet) -> Self {
         self.changed_account_prefixes = prefixes;
@@ -77,6 +95,13 @@ impl<T, H> StateRoot<T, H> {
         self
     }
 
+    /// Use the storage roots computed ahead of the walk, the storage roots of all other accounts
+    /// are computed inline.
+    pub fn with_precomputed_storage_roots(mut self, roots: Arc<PrecomputedStorageRoots>) -> Self {
+        self.precomputed_storage_roots = Some(roots);
+        self
+    }
+
     /// Set the hashed cursor factory.
     pub fn with_hashed_cursor_factory<HF>(self, hashed_cursor_factory: HF) -> StateRoot<T, HF> {
         StateRoot {
@@ -87,6 +112,7 @@ impl<T, H> StateRoot<T, H> {
             destroyed_accounts: self.destroyed_accounts,
             threshold: self.threshold,
             previous_state: self.previous_state,
+            precomputed_storage_roots: self.precomputed_storage_roots,
         }
     }
 
@@ -100,6 +126,7 @@ impl<T, H> StateRoot<T, H> {
             destroyed_accounts: self.destroyed_accounts,
             threshold: self.threshold,
             previous_state: self.previous_state,
+            precomputed_storage_roots: self.precomputed_storage_roots,
         }
     }
 }
@@ -107,15 +134,7 @@ impl<T, H> StateRoot<T, H> {
 impl<'a, TX: DbTx> StateRoot<&'a TX, &'a TX> {
     /// Create a new [StateRoot] instance.
     pub fn from_tx(tx: &'a TX) -> Self {
-        Self {
-            trie_cursor_factory: tx,
-            hashed_cursor_factory: tx,
-            changed_account_prefixes: PrefixSetMut::default().freeze(),
-            changed_storage_prefixes: AHashMap::default(),
-            destroyed_accounts: AHashSet::default(),
-            previous_state: None,
-            threshold: 100_000,
-        }
+        Self::new(tx, tx)
     }
 
     /// Given a block number range, identifies all the accounts and storage keys that
@@ -275,19 +294,29 @@ where
                     // progress.
                     // TODO: We can consider introducing the TrieProgress::Progress/Complete
                     // abstraction inside StorageRoot, but let's give it a try as-is for now.
+                    let changed_prefixes = self
+                        .changed_storage_prefixes
+                        .get(&hashed_address)
+                        .cloned()
+                        .unwrap_or_default();
+                    let precomputed = self
+                        .precomputed_storage_roots
+                        .as_ref()
+                        .and_then(|roots| roots.take(hashed_address, &changed_prefixes));
                     let storage_root_calculator = StorageRoot::new_hashed(
                         self.trie_cursor_factory.clone(),
                         self.hashed_cursor_factory.clone(),
                         hashed_address,
                     )
-                    .with_changed_prefixes(
-                        self.changed_storage_prefixes
-                            .get(&hashed_address)
-                            .cloned()
-                            .unwrap_or_default(),
-                    );
+                    .with_changed_prefixes(changed_prefixes);
 
-                    let storage_root = if retain_updates {
+                    let storage_root = if let Some(precomputed) = precomputed {
+                        if retain_updates {
+                            hashed_entries_walked += precomputed.slots_walked;
+                            trie_updates.extend(precomputed.updates.into_iter());
+                        }
+                        precomputed.root
+                    } else if retain_updates {
                         let (root, storage_slots_walked, updates) =
                             storage_root_calculator.root_with_updates()?;
                         hashed_entries_walked += storage_slots_walked;
@@ -302,7 +331,7 @@ where
                     account_rlp.clear();
                     account.encode(&mut account_rlp as &mut dyn BufMut);
 
-                    hash_builder.add_leaf(Nibbles::unpack(hashed_address), &account_rlp);
+                    hash_builder.add_leaf(nibbles::unpack(hashed_address), &account_rlp);
 
                     // Decide if we need to return intermediate progress.
                     let total_updates_len = trie_updates.len() +
@@ -474,7 +503,7 @@ where
                 StorageNode::Leaf(hashed_slot, value) => {
                     storage_slots_walked += 1;
                     hash_builder.add_leaf(
-                        Nibbles::unpack(hashed_slot),
+                        nibbles::unpack(hashed_slot),
                         alloy_rlp::encode_fixed_size(&value).as_ref(),
                     );
                 }
@@ -498,8 +527,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::{
-        state_root, state_root_prehashed, storage_root, storage_root_prehashed,
+    use crate::{
+        test_utils::{state_root, state_root_prehashed, storage_root, storage_root_prehashed},
+        HashedPostState, HashedStorage,
     };
     use proptest::{prelude::ProptestConfig, proptest};
     use reth_db::{
@@ -513,7 +543,7 @@ mod tests {
         hex_literal::hex,
         keccak256,
         proofs::triehash::KeccakHasher,
-        trie::{BranchNodeCompact, TrieMask},
+        trie::{BranchNodeCompact, Nibbles, TrieMask},
         Account, Address, StorageEntry, B256, U256,
     };
     use reth_provider::{test_utils::create_test_provider_factory, DatabaseProviderRW};
@@ -767,6 +797,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn compacted_prefix_sets_root() {
+        let factory = create_test_provider_factory();
+        let tx = factory.provider_rw().unwrap();
+
+        // a contract with densely populated storage keys
+        let hashed_address = B256::with_last_byte(0x42);
+        let slot = |high: u8, low: u8| {
+            let mut key = B256::ZERO;
+            key[30] = high;
+            key[31] = low;
+            key
+        };
+        let account = Account { nonce: 1, balance: U256::from(1), bytecode_hash: None };
+        tx.tx_ref().put::<tables::HashedAccount>(hashed_address, account).unwrap();
+        for i in 0..100u8 {
+            tx.tx_ref().put::<tables::HashedAccount>(keccak256([i]), account).unwrap();
+        }
+        let mut hashed_storage_cursor =
+            tx.tx_ref().cursor_dup_write::<tables::HashedStorage>().unwrap();
+        for high in 0..3 {
+            for low in 0..=255 {
+                let entry =
+                    StorageEntry { key: slot(high, low), value: U256::from(low) + U256::from(1) };
+                hashed_storage_cursor.upsert(hashed_address, entry).unwrap();
+            }
+        }
+        let (_, updates) = StateRoot::from_tx(tx.tx_ref()).root_with_updates().unwrap();
+        updates.flush(tx.tx_ref()).unwrap();
+
+        // change 15 of 16 slot groups under the first prefix and some accounts
+        let mut post_state = HashedPostState::default();
+        let mut storage = HashedStorage::new(false);
+        for low in 0..240 {
+            storage.insert_slot(slot(0, low), U256::from(low) * U256::from(2));
+        }
+        post_state.insert_hashed_storage(hashed_address, storage);
+        for i in (0..100u8).step_by(3) {
+            let account = Account { nonce: 2, balance: U256::from(i), bytecode_hash: None };
+            post_state.insert_account(keccak256([i]), Some(account));
+        }
+        post_state.sort();
+
+        let expected = post_state.state_root(tx.tx_ref()).unwrap();
+        let (calculator, compaction) = post_state.compacted_state_root_calculator(tx.tx_ref(), 12);
+        assert_eq!(compaction.subtrees, 1);
+        assert!(compaction.ratio() > 1.0);
+        assert_eq!(calculator.root().unwrap(), expected);
+    }
+
     fn test_state_root_with_state(state: State) {
         let factory = create_test_provider_factory();
         let tx = factory.provider_rw().unwrap();
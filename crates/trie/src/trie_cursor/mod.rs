@@ -1,7 +1,7 @@
 use crate::updates::TrieKey;
 use reth_db::DatabaseError;
 use reth_primitives::{
-    trie::{BranchNodeCompact, StoredNibbles, StoredNibblesSubKey},
+    trie::{BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey},
     B256,
 };
 
@@ -11,6 +11,9 @@ mod subnode;
 /// Noop trie cursor implementations.
 pub mod noop;
 
+/// Account trie cursors backed by pinned in-memory subtrees.
+pub mod pinned;
+
 pub use self::{
     database_cursors::{DatabaseAccountTrieCursor, DatabaseStorageTrieCursor},
     subnode::CursorSubNode,
@@ -30,6 +33,24 @@ pub trait TrieCursorFactory {
     ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError>;
 }
 
+/// The keys of the account and storage tries.
+pub(crate) trait TrieCursorKey: From<Vec<u8>> {
+    /// Returns the path of the key.
+    fn nibbles(&self) -> &Nibbles;
+}
+
+impl TrieCursorKey for StoredNibbles {
+    fn nibbles(&self) -> &Nibbles {
+        &self.0
+    }
+}
+
+impl TrieCursorKey for StoredNibblesSubKey {
+    fn nibbles(&self) -> &Nibbles {
+        &self.0
+    }
+}
+
 /// A cursor for navigating a trie that works with both Tables and DupSort tables.
 #[auto_impl::auto_impl(&mut, Box)]
 pub trait TrieCursor {
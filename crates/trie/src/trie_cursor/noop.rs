@@ -4,7 +4,7 @@ use reth_db::DatabaseError;
 use reth_primitives::trie::{BranchNodeCompact, StoredNibbles, StoredNibblesSubKey};
 
 /// Noop trie cursor factory.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 #[non_exhaustive]
 pub struct NoopTrieCursorFactory;
 
@@ -0,0 +1,381 @@
+use super::{TrieCursor, TrieCursorFactory};
+use crate::updates::{TrieKey, TrieOp, TrieUpdates};
+use parking_lot::RwLock;
+use reth_db::{cursor::DbCursorRO, tables, transaction::DbTx, DatabaseError};
+use reth_metrics::{
+    metrics::{Counter, Gauge},
+    Metrics,
+};
+use reth_primitives::{
+    trie::{BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey},
+    BlockNumHash, B256,
+};
+use std::{collections::BTreeMap, sync::Arc};
+use tracing::debug;
+
+/// Account trie nodes under a set of pinned key prefixes, kept in memory and shared by all state
+/// root computations.
+///
+/// The nodes mirror the account trie table at [tip](Self::tip). They are refreshed incrementally
+/// with the [TrieUpdates] written to the database through [Self::apply_updates] and reloaded if
+/// the database moved to another block by other means.
+#[derive(Debug)]
+pub struct PinnedAccountNodes {
+    /// Sorted prefixes, none of which is a prefix of another.
+    prefixes: Vec<Nibbles>,
+    /// The pinned nodes and the block they are at.
+    inner: RwLock<PinnedNodes>,
+    metrics: PinnedAccountNodesMetrics,
+}
+
+#[derive(Debug, Default)]
+struct PinnedNodes {
+    tip: Option<BlockNumHash>,
+    nodes: BTreeMap<Nibbles, BranchNodeCompact>,
+}
+
+impl PinnedAccountNodes {
+    /// Create an empty set of pinned nodes under the given prefixes.
+    ///
+    /// The nodes have to be [loaded](Self::load) before they are used.
+    pub fn new(prefixes: impl IntoIterator<Item = Nibbles>) -> Self {
+        let mut prefixes = prefixes.into_iter().collect::<Vec<_>>();
+        prefixes.sort_unstable();
+        // prefixes covered by a shorter one are redundant
+        prefixes.dedup_by(|prefix, covering| prefix.starts_with(covering));
+        Self { prefixes, inner: RwLock::default(), metrics: PinnedAccountNodesMetrics::default() }
+    }
+
+    /// Returns the pinned prefixes.
+    pub fn prefixes(&self) -> &[Nibbles] {
+        &self.prefixes
+    }
+
+    /// Returns the block the pinned nodes are at, or `None` if they were never loaded.
+    pub fn tip(&self) -> Option<BlockNumHash> {
+        self.inner.read().tip
+    }
+
+    /// Returns the number of pinned nodes.
+    pub fn len(&self) -> usize {
+        self.inner.read().nodes.len()
+    }
+
+    /// Returns `true` if there are no pinned nodes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the pinned prefix covering the given path.
+    fn covering_prefix(&self, path: &[u8]) -> Option<&Nibbles> {
+        // the only candidate is the greatest prefix less than or equal to the path
+        let idx = self.prefixes.partition_point(|prefix| prefix.as_slice() <= path);
+        self.prefixes[..idx].last().filter(|prefix| path.starts_with(prefix))
+    }
+
+    /// Returns `true` if the node at the given path is pinned.
+    pub fn is_pinned(&self, path: &[u8]) -> bool {
+        self.covering_prefix(path).is_some()
+    }
+
+    /// Load all nodes under the pinned prefixes from the account trie table, which is at the given
+    /// block.
+    pub fn load<TX: DbTx>(&self, tx: &TX, tip: BlockNumHash) -> Result<(), DatabaseError> {
+        let mut cursor = tx.cursor_read::<tables::AccountsTrie>()?;
+        let mut nodes = BTreeMap::default();
+        for prefix in &self.prefixes {
+            let mut entry = cursor.seek(StoredNibbles(prefix.clone()))?;
+            while let Some((StoredNibbles(path), node)) = entry {
+                if !path.starts_with(prefix) {
+                    break
+                }
+                nodes.insert(path, node.0);
+                entry = cursor.next()?;
+            }
+        }
+
+        debug!(target: "trie::pinned", ?tip, nodes = nodes.len(), "Loaded pinned account trie nodes");
+        self.metrics.nodes.set(nodes.len() as f64);
+        self.metrics.reloads.increment(1);
+        *self.inner.write() = PinnedNodes { tip: Some(tip), nodes };
+        Ok(())
+    }
+
+    /// Reload the nodes if they are not at the given block.
+    pub fn ensure_tip<TX: DbTx>(&self, tx: &TX, tip: BlockNumHash) -> Result<(), DatabaseError> {
+        if self.tip() != Some(tip) {
+            self.load(tx, tip)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the updates of pinned nodes, so they can be applied after the full updates are
+    /// written to the database.
+    pub fn pinned_updates(&self, updates: &TrieUpdates) -> TrieUpdates {
+        let mut pinned = TrieUpdates::default();
+        pinned.extend(
+            updates
+                .iter()
+                .filter(|(key, _)| {
+                    matches!(key, TrieKey::AccountNode(StoredNibbles(path)) if self.is_pinned(path))
+                })
+                .map(|(key, op)| (key.clone(), op.clone())),
+        );
+        pinned
+    }
+
+    /// Apply the updates written to the database, which moved the account trie table from the
+    /// current tip to the given block.
+    ///
+    /// If the nodes are not at the parent of the written blocks, they are dropped and have to be
+    /// reloaded.
+    pub fn apply_updates(&self, parent: BlockNumHash, tip: BlockNumHash, updates: &TrieUpdates) {
+        let mut inner = self.inner.write();
+        if inner.tip != Some(parent) {
+            *inner = PinnedNodes::default();
+            return
+        }
+
+        for (key, op) in updates.iter() {
+            let TrieKey::AccountNode(StoredNibbles(path)) = key else { continue };
+            // the root node is never stored
+            if path.is_empty() || !self.is_pinned(path) {
+                continue
+            }
+            match op {
+                TrieOp::Update(node) => inner.nodes.insert(path.clone(), node.clone()),
+                TrieOp::Delete => inner.nodes.remove(path),
+            };
+        }
+        inner.tip = Some(tip);
+        self.metrics.nodes.set(inner.nodes.len() as f64);
+    }
+
+    /// Returns the first pinned node at or after the given path under the prefix.
+    fn seek(&self, prefix: &Nibbles, path: &[u8]) -> Option<(Vec<u8>, BranchNodeCompact)> {
+        let inner = self.inner.read();
+        inner
+            .nodes
+            .range(Nibbles::from_nibbles_unchecked(path)..)
+            .next()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, node)| (key.to_vec(), node.clone()))
+    }
+}
+
+/// Returns the smallest path that is greater than all paths starting with the given prefix.
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+    while let Some(last) = successor.pop() {
+        if last < 0xf {
+            successor.push(last + 1);
+            return Some(successor)
+        }
+    }
+    None
+}
+
+/// A [TrieCursorFactory] that answers account trie lookups under pinned prefixes from memory.
+#[derive(Debug, Clone)]
+pub struct PinnedTrieCursorFactory<F> {
+    inner: F,
+    pinned: Arc<PinnedAccountNodes>,
+}
+
+impl<F> PinnedTrieCursorFactory<F> {
+    /// Create a new factory wrapping the given one. The pinned nodes must be at the state of the
+    /// trie the wrapped factory reads.
+    pub fn new(inner: F, pinned: Arc<PinnedAccountNodes>) -> Self {
+        Self { inner, pinned }
+    }
+}
+
+impl<F: TrieCursorFactory> TrieCursorFactory for PinnedTrieCursorFactory<F> {
+    fn account_trie_cursor(
+        &self,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibbles> + '_>, DatabaseError> {
+        Ok(Box::new(PinnedAccountTrieCursor {
+            inner: self.inner.account_trie_cursor()?,
+            pinned: &self.pinned,
+            current: None,
+        }))
+    }
+
+    fn storage_tries_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Box<dyn TrieCursor<Key = StoredNibblesSubKey> + '_>, DatabaseError> {
+        self.inner.storage_tries_cursor(hashed_address)
+    }
+}
+
+/// An account trie cursor that answers lookups under pinned prefixes from memory.
+#[derive(Debug)]
+pub struct PinnedAccountTrieCursor<'a, C> {
+    inner: C,
+    pinned: &'a PinnedAccountNodes,
+    /// The key of the last entry returned from memory.
+    current: Option<Nibbles>,
+}
+
+impl<'a, C> TrieCursor for PinnedAccountTrieCursor<'a, C>
+where
+    C: TrieCursor<Key = StoredNibbles>,
+{
+    type Key = StoredNibbles;
+
+    fn seek_exact(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let pinned = self.pinned;
+        if let Some(prefix) = pinned.covering_prefix(&key.0) {
+            pinned.metrics.hits.increment(1);
+            let entry = pinned.seek(prefix, &key.0).filter(|(path, _)| key == *path.as_slice());
+            self.current = entry.as_ref().map(|(path, _)| Nibbles::from_nibbles_unchecked(path));
+            return Ok(entry)
+        }
+
+        self.current = None;
+        self.inner.seek_exact(key)
+    }
+
+    fn seek(
+        &mut self,
+        key: Self::Key,
+    ) -> Result<Option<(Vec<u8>, BranchNodeCompact)>, DatabaseError> {
+        let pinned = self.pinned;
+        let mut key = key;
+        if let Some(prefix) = pinned.covering_prefix(&key.0) {
+            pinned.metrics.hits.increment(1);
+            if let Some(entry) = pinned.seek(prefix, &key.0) {
+                self.current = Some(Nibbles::from_nibbles_unchecked(&entry.0));
+                return Ok(Some(entry))
+            }
+
+            // continue after the pinned subtree
+            match prefix_successor(prefix) {
+                Some(successor) => key = successor.into(),
+                None => {
+                    self.current = None;
+                    return Ok(None)
+                }
+            }
+        }
+
+        self.current = None;
+        self.inner.seek(key)
+    }
+
+    fn current(&mut self) -> Result<Option<TrieKey>, DatabaseError> {
+        match &self.current {
+            Some(path) => Ok(Some(TrieKey::AccountNode(StoredNibbles(path.clone())))),
+            None => self.inner.current(),
+        }
+    }
+}
+
+/// Metrics of the pinned account trie nodes.
+#[derive(Metrics)]
+#[metrics(scope = "trie.pinned")]
+struct PinnedAccountNodesMetrics {
+    /// The number of pinned nodes.
+    nodes: Gauge,
+    /// The number of lookups answered from the pinned nodes.
+    hits: Counter,
+    /// The number of times the nodes were loaded from the database.
+    reloads: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::state_root_prehashed, HashedPostState, StateRoot};
+    use reth_db::{cursor::DbCursorRW, transaction::DbTxMut};
+    use reth_primitives::{keccak256, Account, U256};
+    use reth_provider::test_utils::create_test_provider_factory;
+
+    #[test]
+    fn covering_prefix() {
+        let pinned = PinnedAccountNodes::new([
+            Nibbles::from_nibbles_unchecked([0x1, 0x2]),
+            Nibbles::from_nibbles_unchecked([0x1]),
+            Nibbles::from_nibbles_unchecked([0xa, 0xb, 0xc]),
+        ]);
+        assert_eq!(pinned.prefixes().len(), 2);
+        assert!(pinned.is_pinned(&[0x1]));
+        assert!(pinned.is_pinned(&[0x1, 0x3, 0x4]));
+        assert!(pinned.is_pinned(&[0xa, 0xb, 0xc, 0x0]));
+        assert!(!pinned.is_pinned(&[0xa, 0xb]));
+        assert!(!pinned.is_pinned(&[0x2]));
+        assert!(!pinned.is_pinned(&[]));
+
+        assert_eq!(prefix_successor(&[0x1, 0xf]), Some(vec![0x2]));
+        assert_eq!(prefix_successor(&[0xf, 0xf]), None);
+    }
+
+    #[test]
+    fn pinned_root_matches_database() {
+        let factory = create_test_provider_factory();
+        let tx = factory.provider_rw().unwrap();
+
+        let accounts = (0..1000u64)
+            .map(|i| {
+                let account = Account { nonce: i, balance: U256::from(i), bytecode_hash: None };
+                (keccak256(B256::from(U256::from(i))), account)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut hashed_account_cursor =
+            tx.tx_ref().cursor_write::<tables::HashedAccount>().unwrap();
+        for (hashed_address, account) in &accounts {
+            hashed_account_cursor.upsert(*hashed_address, *account).unwrap();
+        }
+        let (root, updates) = StateRoot::from_tx(tx.tx_ref()).root_with_updates().unwrap();
+        assert_eq!(
+            root,
+            state_root_prehashed(accounts.iter().map(|(k, v)| (*k, (*v, Default::default()))))
+        );
+        updates.flush(tx.tx_ref()).unwrap();
+
+        let pinned = Arc::new(PinnedAccountNodes::new([
+            Nibbles::from_nibbles_unchecked([0x3]),
+            Nibbles::from_nibbles_unchecked([0xa, 0x7]),
+            Nibbles::from_nibbles_unchecked([0xf]),
+        ]));
+        let parent = BlockNumHash::new(1, B256::with_last_byte(1));
+        pinned.load(tx.tx_ref(), parent).unwrap();
+        assert!(!pinned.is_empty());
+
+        let mut post_state = HashedPostState::default();
+        for (idx, hashed_address) in accounts.keys().enumerate().step_by(7) {
+            let account = (idx % 2 == 0).then(|| Account {
+                nonce: 1,
+                balance: U256::from(idx * 3),
+                bytecode_hash: None,
+            });
+            post_state.insert_account(*hashed_address, account);
+        }
+        post_state.sort();
+
+        let (expected_root, expected_updates) =
+            post_state.state_root_with_updates(tx.tx_ref()).unwrap();
+        let (root, updates) = post_state
+            .state_root_calculator(tx.tx_ref())
+            .with_trie_cursor_factory(PinnedTrieCursorFactory::new(tx.tx_ref(), pinned.clone()))
+            .root_with_updates()
+            .unwrap();
+        assert_eq!(root, expected_root);
+        assert_eq!(updates, expected_updates);
+
+        // refresh the pinned nodes from the updates and compare with a fresh load
+        let pinned_updates = pinned.pinned_updates(&updates);
+        updates.flush(tx.tx_ref()).unwrap();
+        let tip = BlockNumHash::new(2, B256::with_last_byte(2));
+        pinned.apply_updates(parent, tip, &pinned_updates);
+        assert_eq!(pinned.tip(), Some(tip));
+
+        let reloaded = PinnedAccountNodes::new(pinned.prefixes().to_vec());
+        reloaded.load(tx.tx_ref(), tip).unwrap();
+        assert_eq!(pinned.inner.read().nodes, reloaded.inner.read().nodes);
+    }
+}
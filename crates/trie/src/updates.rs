@@ -104,6 +104,50 @@ impl TrieUpdates {
         self.extend(keys.map(|key| (key, TrieOp::Delete)));
     }
 
+    /// Returns the updates that restore the current state of all nodes touched by these updates.
+    ///
+    /// Must be called before the updates are flushed to the database.
+    pub fn reverts(&self, tx: &impl DbTx) -> Result<TrieUpdates, reth_db::DatabaseError> {
+        let mut account_trie_cursor = tx.cursor_read::<tables::AccountsTrie>()?;
+        let mut storage_trie_cursor = tx.cursor_dup_read::<tables::StoragesTrie>()?;
+
+        let mut reverts = TrieUpdates::default();
+        for key in self.trie_operations.keys() {
+            match key {
+                TrieKey::AccountNode(nibbles) => {
+                    let op = match account_trie_cursor.seek_exact(nibbles.clone())? {
+                        Some((_, node)) => TrieOp::Update(node.0),
+                        None => TrieOp::Delete,
+                    };
+                    reverts.trie_operations.insert(key.clone(), op);
+                }
+                TrieKey::StorageNode(hashed_address, nibbles) => {
+                    let op = match storage_trie_cursor
+                        .seek_by_key_subkey(*hashed_address, nibbles.clone())?
+                        .filter(|e| e.nibbles == *nibbles)
+                    {
+                        Some(entry) => TrieOp::Update(entry.node),
+                        None => TrieOp::Delete,
+                    };
+                    reverts.trie_operations.insert(key.clone(), op);
+                }
+                TrieKey::StorageTrie(hashed_address) => {
+                    // Restore all nodes of the wiped storage trie. The wipe itself is not
+                    // reverted, nodes that did not exist before are deleted individually.
+                    for entry in storage_trie_cursor.walk_dup(Some(*hashed_address), None)? {
+                        let (_, entry) = entry?;
+                        reverts.trie_operations.insert(
+                            TrieKey::StorageNode(*hashed_address, entry.nibbles),
+                            TrieOp::Update(entry.node),
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(reverts)
+    }
+
     /// Flush updates all aggregated updates to the database.
     pub fn flush(self, tx: &(impl DbTx + DbTxMut)) -> Result<(), reth_db::DatabaseError> {
         if self.trie_operations.is_empty() {
@@ -0,0 +1,226 @@
+use crate::{
+    hashed_cursor::HashedPostStateCursorFactory, prefix_set::PrefixSetMut, proof::Proof,
+    HashedPostState, StateRootError,
+};
+use ahash::AHashMap;
+use reth_db::{transaction::DbTx, DatabaseError};
+use reth_primitives::{trie::AccountProof, Address, BlockNumber, B256};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// A view of the state trie at a historical block.
+///
+/// The view overlays the current hashed state with the state reverts of all blocks above its
+/// block, and recomputes every trie path touched by those reverts instead of trusting the stored
+/// trie nodes. This allows computing roots and proofs at recent heights without unwinding the
+/// trie tables.
+///
+/// The view is immutable and can be shared between threads, but every call must be made with a
+/// transaction that observes the same tip the view was created for.
+#[derive(Debug)]
+pub struct HistoricalTrieView {
+    /// The block number of the view.
+    block_number: BlockNumber,
+    /// The tip the reverts were collected up to.
+    tip: BlockNumber,
+    /// The reverted hashed state.
+    state: HashedPostState,
+    /// The account prefixes changed by the reverts.
+    account_prefixes: PrefixSetMut,
+    /// The storage prefixes changed by the reverts.
+    storage_prefixes: AHashMap<B256, PrefixSetMut>,
+}
+
+impl HistoricalTrieView {
+    /// Creates the view at the given block, by collecting the reverts of all blocks up to the tip.
+    ///
+    /// Changesets of the range must not be pruned.
+    pub fn from_tx<TX: DbTx>(
+        tx: &TX,
+        block_number: BlockNumber,
+        tip: BlockNumber,
+    ) -> Result<Self, DatabaseError> {
+        let state = if block_number < tip {
+            HashedPostState::from_revert_range(tx, block_number + 1..=tip)?
+        } else {
+            HashedPostState::default()
+        };
+        let (account_prefixes, storage_prefixes) = state.construct_prefix_sets_mut();
+        Ok(Self { block_number, tip, state, account_prefixes, storage_prefixes })
+    }
+
+    /// Returns the block number of the view.
+    pub fn block_number(&self) -> BlockNumber {
+        self.block_number
+    }
+
+    /// Returns the tip the view was created for.
+    pub fn tip(&self) -> BlockNumber {
+        self.tip
+    }
+
+    /// Calculates the state root at the block of the view.
+    pub fn state_root<TX: DbTx>(&self, tx: &TX) -> Result<B256, StateRootError> {
+        self.state.state_root(tx)
+    }
+
+    /// Generates the account proof at the block of the view.
+    pub fn account_proof<TX: DbTx>(
+        &self,
+        tx: &TX,
+        address: Address,
+        slots: &[B256],
+    ) -> Result<AccountProof, StateRootError> {
+        Proof::new(tx)
+            .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, &self.state))
+            .with_changed_account_prefixes(self.account_prefixes.clone())
+            .with_changed_storage_prefixes(self.storage_prefixes.clone())
+            .account_proof(address, slots)
+    }
+}
+
+/// A bounded set of [HistoricalTrieView]s for the most recent blocks.
+///
+/// Views are created lazily on first access and are dropped once the tip changes, since the
+/// reverts they are built from are relative to the tip.
+#[derive(Debug)]
+pub struct TrieViews {
+    /// The number of blocks below the tip that views can be created for.
+    max_depth: u64,
+    /// The current tip.
+    tip: BlockNumber,
+    /// The created views by block number.
+    views: BTreeMap<BlockNumber, Arc<HistoricalTrieView>>,
+}
+
+impl TrieViews {
+    /// Creates a new set of views for at most `max_depth` blocks below the tip.
+    pub fn new(max_depth: u64, tip: BlockNumber) -> Self {
+        Self { max_depth, tip, views: BTreeMap::new() }
+    }
+
+    /// Returns the current tip.
+    pub fn tip(&self) -> BlockNumber {
+        self.tip
+    }
+
+    /// Sets a new tip and drops all views if it changed.
+    pub fn on_new_tip(&mut self, tip: BlockNumber) {
+        if tip != self.tip {
+            self.tip = tip;
+            self.views.clear();
+        }
+    }
+
+    /// Returns the view at the given block, or `None` if the block is above the tip or deeper
+    /// than the configured depth.
+    ///
+    /// The transaction must observe the current tip.
+    pub fn view<TX: DbTx>(
+        &mut self,
+        tx: &TX,
+        block_number: BlockNumber,
+    ) -> Result<Option<Arc<HistoricalTrieView>>, DatabaseError> {
+        if block_number > self.tip || self.tip - block_number > self.max_depth {
+            return Ok(None)
+        }
+
+        if let Some(view) = self.views.get(&block_number) {
+            return Ok(Some(Arc::clone(view)))
+        }
+
+        let view = Arc::new(HistoricalTrieView::from_tx(tx, block_number, self.tip)?);
+        self.views.insert(block_number, Arc::clone(&view));
+        Ok(Some(view))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{trie_cursor::noop::NoopTrieCursorFactory, StateRoot};
+    use reth_db::{
+        models::{AccountBeforeTx, BlockNumberAddress},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{Account, StorageEntry, U256};
+    use reth_provider::{test_utils::create_test_provider_factory, HashingWriter};
+
+    #[test]
+    fn historical_roots_and_proofs() {
+        let factory = create_test_provider_factory();
+        let with_storage = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let created = Address::with_last_byte(3);
+        let slot = B256::with_last_byte(1);
+        let account = Account { nonce: 1, balance: U256::from(10), bytecode_hash: None };
+
+        // Block 0
+        let provider = factory.provider_rw().unwrap();
+        provider
+            .insert_account_for_hashing([(with_storage, Some(account)), (other, Some(account))])
+            .unwrap();
+        provider
+            .insert_storage_for_hashing([(
+                with_storage,
+                [StorageEntry { key: slot, value: U256::from(1) }],
+            )])
+            .unwrap();
+        let (root_0, updates) = StateRoot::from_tx(provider.tx_ref()).root_with_updates().unwrap();
+        updates.flush(provider.tx_ref()).unwrap();
+        let proof_0 = Proof::new(provider.tx_ref()).account_proof(with_storage, &[slot]).unwrap();
+        provider.commit().unwrap();
+
+        // Block 1
+        let provider = factory.provider_rw().unwrap();
+        let tx = provider.tx_ref();
+        tx.put::<tables::AccountChangeSet>(
+            1,
+            AccountBeforeTx { address: with_storage, info: Some(account) },
+        )
+        .unwrap();
+        tx.put::<tables::AccountChangeSet>(1, AccountBeforeTx { address: created, info: None })
+            .unwrap();
+        tx.put::<tables::StorageChangeSet>(
+            BlockNumberAddress((1, with_storage)),
+            StorageEntry { key: slot, value: U256::from(1) },
+        )
+        .unwrap();
+        let updated = Account { balance: U256::from(20), ..account };
+        provider
+            .insert_account_for_hashing([(with_storage, Some(updated)), (created, Some(account))])
+            .unwrap();
+        provider
+            .insert_storage_for_hashing([(
+                with_storage,
+                [StorageEntry { key: slot, value: U256::from(2) }],
+            )])
+            .unwrap();
+        let (root_1, updates) = StateRoot::from_tx(tx).root_with_updates().unwrap();
+        updates.flush(tx).unwrap();
+        provider.commit().unwrap();
+        assert_ne!(root_0, root_1);
+
+        let provider = factory.provider().unwrap();
+        let tx = provider.tx_ref();
+        let mut views = TrieViews::new(1, 1);
+        assert!(views.view(tx, 2).unwrap().is_none());
+
+        let view_0 = views.view(tx, 0).unwrap().unwrap();
+        assert_eq!(view_0.state_root(tx).unwrap(), root_0);
+        let full_root_0 = StateRoot::from_tx(tx)
+            .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, &view_0.state))
+            .with_trie_cursor_factory(NoopTrieCursorFactory)
+            .root()
+            .unwrap();
+        assert_eq!(full_root_0, root_0);
+        assert_eq!(view_0.account_proof(tx, with_storage, &[slot]).unwrap(), proof_0);
+        assert!(Arc::ptr_eq(&view_0, &views.view(tx, 0).unwrap().unwrap()));
+
+        let view_1 = views.view(tx, 1).unwrap().unwrap();
+        assert_eq!(view_1.state_root(tx).unwrap(), root_1);
+
+        views.on_new_tip(2);
+        assert!(views.view(tx, 0).unwrap().is_none());
+    }
+}
@@ -0,0 +1,690 @@
+//! Sparse state tries revealed from the trie nodes of an execution witness.
+//!
+//! A witness contains the RLP encoded nodes of the account and storage tries that are touched by a
+//! block. Nodes that are not part of the witness are kept as hashes, so the root of a
+//! [WitnessTrie] can be computed and updated without the full trie. Accessing a key that leads
+//! into a node missing from the witness returns [WitnessError::MissingNode].
+
+use crate::{nibbles, HashedPostState};
+use alloy_rlp::{Decodable, Encodable, Header, EMPTY_STRING_CODE};
+use reth_primitives::{
+    constants::EMPTY_ROOT_HASH, keccak256, trie::TrieAccount, Bytes, B256, U256,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// Error returned when accessing a [WitnessTrie] or [WitnessState].
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
+pub enum WitnessError {
+    /// The witness does not contain a node that is required to access the trie.
+    #[error("trie node {0} is missing from the witness")]
+    MissingNode(B256),
+    /// A node of the witness is not a valid trie node.
+    #[error("invalid trie node in witness")]
+    InvalidNode,
+    /// A node or value of the witness could not be decoded.
+    #[error(transparent)]
+    Rlp(#[from] alloy_rlp::Error),
+}
+
+/// The nodes of an execution witness, keyed by their hash.
+pub type WitnessNodes = Arc<HashMap<B256, Bytes>>;
+
+/// A node of a [WitnessTrie].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Node {
+    /// The empty trie.
+    #[default]
+    Empty,
+    /// A leaf with the remaining key nibbles.
+    Leaf { key: Vec<u8>, value: Vec<u8> },
+    /// An extension with the shared key nibbles.
+    Extension { key: Vec<u8>, child: Box<Node> },
+    /// A branch without a value, keys of the state tries all have the same length.
+    Branch { children: Box<[Node; 16]> },
+    /// A node that has not been resolved from the witness yet.
+    Hash(B256),
+}
+
+/// Scratch space for RLP encoding the nodes of a [WitnessTrie].
+///
+/// Nodes are encoded into a single buffer: child nodes are encoded after the partial encoding of
+/// their parent and truncated once they are hashed, so computing a root doesn't allocate per
+/// node. Reusing the arena across payloads, see [WitnessState::state_root_with_arena], also keeps
+/// its capacity between roots.
+#[derive(Clone, Debug, Default)]
+pub struct RlpArena {
+    buf: Vec<u8>,
+}
+
+impl RlpArena {
+    /// Creates an arena with the given capacity in bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { buf: Vec::with_capacity(capacity) }
+    }
+
+    /// Returns the capacity of the arena in bytes.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+}
+
+/// A Merkle Patricia Trie over fixed-length keys that is resolved lazily from witness nodes.
+///
+/// If a mutation returns an error, the trie is left in an unspecified state and must be
+/// discarded.
+#[derive(Clone, Debug)]
+pub struct WitnessTrie {
+    root: Node,
+    nodes: WitnessNodes,
+}
+
+impl WitnessTrie {
+    /// Creates a trie with the given root, resolving nodes from the witness.
+    pub fn new(root: B256, nodes: WitnessNodes) -> Self {
+        let root = if root == EMPTY_ROOT_HASH { Node::Empty } else { Node::Hash(root) };
+        Self { root, nodes }
+    }
+
+    /// Returns the value of the key.
+    pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, WitnessError> {
+        self.get_at(&self.root, &nibbles::unpack(key))
+    }
+
+    /// Inserts or updates the value of the key.
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) -> Result<(), WitnessError> {
+        let root = std::mem::take(&mut self.root);
+        self.root = self.insert_at(root, &nibbles::unpack(key), value)?;
+        Ok(())
+    }
+
+    /// Removes the key.
+    ///
+    /// Removing a key can collapse a branch into its remaining child, which requires that child
+    /// to be part of the witness.
+    pub fn remove(&mut self, key: &[u8]) -> Result<(), WitnessError> {
+        let root = std::mem::take(&mut self.root);
+        self.root = self.remove_at(root, &nibbles::unpack(key))?;
+        Ok(())
+    }
+
+    /// Computes the root hash of the trie.
+    pub fn root(&self) -> B256 {
+        self.root_with_arena(&mut RlpArena::default())
+    }
+
+    /// Computes the root hash of the trie, encoding the nodes in the given arena.
+    pub fn root_with_arena(&self, arena: &mut RlpArena) -> B256 {
+        match &self.root {
+            Node::Empty => EMPTY_ROOT_HASH,
+            Node::Hash(hash) => *hash,
+            node => {
+                arena.buf.clear();
+                encode_node(node, &mut arena.buf);
+                keccak256(&arena.buf)
+            }
+        }
+    }
+
+    fn resolve(&self, hash: B256) -> Result<Node, WitnessError> {
+        decode_node(self.nodes.get(&hash).ok_or(WitnessError::MissingNode(hash))?)
+    }
+
+    fn get_at(&self, node: &Node, path: &[u8]) -> Result<Option<Vec<u8>>, WitnessError> {
+        match node {
+            Node::Empty => Ok(None),
+            Node::Leaf { key, value } => Ok((key[..] == *path).then(|| value.clone())),
+            Node::Extension { key, child } => match path.strip_prefix(key.as_slice()) {
+                Some(rest) => self.get_at(child, rest),
+                None => Ok(None),
+            },
+            Node::Branch { children } => match path.split_first() {
+                Some((nibble, rest)) => self.get_at(&children[*nibble as usize], rest),
+                None => Ok(None),
+            },
+            Node::Hash(hash) => self.get_at(&self.resolve(*hash)?, path),
+        }
+    }
+
+    fn insert_at(&self, node: Node, path: &[u8], value: Vec<u8>) -> Result<Node, WitnessError> {
+        match node {
+            Node::Empty => Ok(Node::Leaf { key: path.to_vec(), value }),
+            Node::Hash(hash) => self.insert_at(self.resolve(hash)?, path, value),
+            Node::Leaf { key, value: existing } => {
+                if key == path {
+                    return Ok(Node::Leaf { key, value })
+                }
+                let common = nibbles::common_prefix_length(&key, path);
+                let mut children = empty_children();
+                children[key[common] as usize] =
+                    Node::Leaf { key: key[common + 1..].to_vec(), value: existing };
+                children[path[common] as usize] =
+                    Node::Leaf { key: path[common + 1..].to_vec(), value };
+                Ok(extension(path[..common].to_vec(), Node::Branch { children }))
+            }
+            Node::Extension { key, child } => {
+                let common = nibbles::common_prefix_length(&key, path);
+                if common == key.len() {
+                    let child = self.insert_at(*child, &path[common..], value)?;
+                    return Ok(Node::Extension { key, child: Box::new(child) })
+                }
+                let mut children = empty_children();
+                children[key[common] as usize] = extension(key[common + 1..].to_vec(), *child);
+                children[path[common] as usize] =
+                    Node::Leaf { key: path[common + 1..].to_vec(), value };
+                Ok(extension(path[..common].to_vec(), Node::Branch { children }))
+            }
+            Node::Branch { mut children } => {
+                let idx = path[0] as usize;
+                children[idx] =
+                    self.insert_at(std::mem::take(&mut children[idx]), &path[1..], value)?;
+                Ok(Node::Branch { children })
+            }
+        }
+    }
+
+    fn remove_at(&self, node: Node, path: &[u8]) -> Result<Node, WitnessError> {
+        match node {
+            Node::Empty => Ok(Node::Empty),
+            Node::Hash(hash) => self.remove_at(self.resolve(hash)?, path),
+            Node::Leaf { key, value } => {
+                Ok(if key == path { Node::Empty } else { Node::Leaf { key, value } })
+            }
+            Node::Extension { key, child } => match path.strip_prefix(key.as_slice()) {
+                Some(rest) => {
+                    let child = self.remove_at(*child, rest)?;
+                    Ok(prepend(key, child))
+                }
+                None => Ok(Node::Extension { key, child }),
+            },
+            Node::Branch { mut children } => {
+                let idx = path[0] as usize;
+                children[idx] = self.remove_at(std::mem::take(&mut children[idx]), &path[1..])?;
+                self.collapse(children)
+            }
+        }
+    }
+
+    /// Replaces a branch that has only one child left by that child.
+    fn collapse(&self, mut children: Box<[Node; 16]>) -> Result<Node, WitnessError> {
+        let mut remaining = children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| **child != Node::Empty)
+            .map(|(idx, _)| idx);
+        match (remaining.next(), remaining.next()) {
+            (None, _) => Ok(Node::Empty),
+            (Some(idx), None) => {
+                let child = match std::mem::take(&mut children[idx]) {
+                    Node::Hash(hash) => self.resolve(hash)?,
+                    child => child,
+                };
+                Ok(prepend(vec![idx as u8], child))
+            }
+            _ => Ok(Node::Branch { children }),
+        }
+    }
+}
+
+/// The account trie and the storage tries revealed from an execution witness.
+#[derive(Clone, Debug)]
+pub struct WitnessState {
+    accounts: WitnessTrie,
+    nodes: WitnessNodes,
+}
+
+impl WitnessState {
+    /// Creates the state with the given state root from the RLP encoded nodes of the witness.
+    pub fn new(state_root: B256, nodes: impl IntoIterator<Item = Bytes>) -> Self {
+        let nodes: WitnessNodes =
+            Arc::new(nodes.into_iter().map(|node| (keccak256(&node), node)).collect());
+        Self { accounts: WitnessTrie::new(state_root, nodes.clone()), nodes }
+    }
+
+    /// Returns the state root the witness was revealed against.
+    pub fn root(&self) -> B256 {
+        self.accounts.root()
+    }
+
+    /// Returns the account with the given hashed address.
+    pub fn account(&self, hashed_address: B256) -> Result<Option<TrieAccount>, WitnessError> {
+        Ok(self
+            .accounts
+            .get(hashed_address.as_slice())?
+            .map(|rlp| TrieAccount::decode(&mut rlp.as_slice()))
+            .transpose()?)
+    }
+
+    /// Returns the value of the hashed slot in the storage of the account with the given hashed
+    /// address.
+    pub fn storage(&self, hashed_address: B256, hashed_slot: B256) -> Result<U256, WitnessError> {
+        let storage_root = self.storage_root(hashed_address)?;
+        Ok(self
+            .storage_trie(storage_root)
+            .get(hashed_slot.as_slice())?
+            .map(|rlp| U256::decode(&mut rlp.as_slice()))
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// Computes the state root after applying the post state.
+    ///
+    /// The witness must contain all nodes on the paths of changed keys, as well as the siblings of
+    /// removed keys whose branch collapses.
+    pub fn state_root(&self, post_state: &HashedPostState) -> Result<B256, WitnessError> {
+        self.state_root_with_arena(post_state, &mut RlpArena::default())
+    }
+
+    /// Computes the state root after applying the post state, encoding the trie nodes in the
+    /// given arena.
+    ///
+    /// See [WitnessState::state_root].
+    pub fn state_root_with_arena(
+        &self,
+        post_state: &HashedPostState,
+        arena: &mut RlpArena,
+    ) -> Result<B256, WitnessError> {
+        let mut accounts = self.accounts.clone();
+        let mut changed = HashSet::new();
+        for (hashed_address, account) in post_state.accounts() {
+            changed.insert(hashed_address);
+            let Some(account) = account else {
+                accounts.remove(hashed_address.as_slice())?;
+                continue
+            };
+            let storage_root = self.updated_storage_root(hashed_address, post_state, arena)?;
+            let rlp = alloy_rlp::encode(TrieAccount::from((account, storage_root)));
+            accounts.insert(hashed_address.as_slice(), rlp)?;
+        }
+
+        // Storage changes of accounts that did not change themselves.
+        for hashed_address in post_state.storages.keys() {
+            if changed.contains(hashed_address) {
+                continue
+            }
+            let Some(account) = self.account(*hashed_address)? else { continue };
+            let storage_root = self.updated_storage_root(*hashed_address, post_state, arena)?;
+            let account = TrieAccount::from((
+                reth_primitives::Account {
+                    nonce: account.nonce(),
+                    balance: account.balance(),
+                    bytecode_hash: Some(account.code_hash()),
+                },
+                storage_root,
+            ));
+            accounts.insert(hashed_address.as_slice(), alloy_rlp::encode(account))?;
+        }
+
+        Ok(accounts.root_with_arena(arena))
+    }
+
+    fn storage_root(&self, hashed_address: B256) -> Result<B256, WitnessError> {
+        Ok(self.account(hashed_address)?.map_or(EMPTY_ROOT_HASH, |account| account.storage_root()))
+    }
+
+    fn storage_trie(&self, storage_root: B256) -> WitnessTrie {
+        WitnessTrie::new(storage_root, self.nodes.clone())
+    }
+
+    fn updated_storage_root(
+        &self,
+        hashed_address: B256,
+        post_state: &HashedPostState,
+        arena: &mut RlpArena,
+    ) -> Result<B256, WitnessError> {
+        let Some(storage) = post_state.storages.get(&hashed_address) else {
+            return self.storage_root(hashed_address)
+        };
+        let mut trie = if storage.wiped() {
+            self.storage_trie(EMPTY_ROOT_HASH)
+        } else {
+            self.storage_trie(self.storage_root(hashed_address)?)
+        };
+        for (hashed_slot, value) in storage.storage_slots() {
+            if value.is_zero() {
+                trie.remove(hashed_slot.as_slice())?;
+            } else {
+                trie.insert(hashed_slot.as_slice(), alloy_rlp::encode(value))?;
+            }
+        }
+        Ok(trie.root_with_arena(arena))
+    }
+}
+
+fn empty_children() -> Box<[Node; 16]> {
+    Box::new(std::array::from_fn(|_| Node::Empty))
+}
+
+/// Returns an extension with the given key, or the child itself if the key is empty.
+fn extension(key: Vec<u8>, child: Node) -> Node {
+    if key.is_empty() {
+        child
+    } else {
+        Node::Extension { key, child: Box::new(child) }
+    }
+}
+
+/// Prepends the key nibbles to the resolved node.
+fn prepend(prefix: Vec<u8>, node: Node) -> Node {
+    match node {
+        Node::Empty => Node::Empty,
+        Node::Leaf { key, value } => Node::Leaf { key: [prefix, key].concat(), value },
+        Node::Extension { key, child } => Node::Extension { key: [prefix, key].concat(), child },
+        node => extension(prefix, node),
+    }
+}
+
+/// Appends the hex-prefix encoding of the key nibbles as an RLP string.
+fn encode_path(key: &[u8], is_leaf: bool, buf: &mut Vec<u8>) {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let start = buf.len();
+    let rest = match key.split_first() {
+        Some((first, rest)) if key.len() % 2 == 1 => {
+            buf.push(flag | 0x10 | first);
+            rest
+        }
+        _ => {
+            buf.push(flag);
+            key
+        }
+    };
+    buf.extend(rest.chunks_exact(2).map(|pair| (pair[0] << 4) | pair[1]));
+    // a single byte below the string offset is its own encoding
+    if buf.len() - start > 1 || buf[start] >= EMPTY_STRING_CODE {
+        prepend_header(buf, start, false);
+    }
+}
+
+/// Appends the encoding of the node.
+fn encode_node(node: &Node, buf: &mut Vec<u8>) {
+    let start = buf.len();
+    match node {
+        Node::Empty => return buf.push(EMPTY_STRING_CODE),
+        Node::Hash(hash) => return hash.encode(buf),
+        Node::Leaf { key, value } => {
+            encode_path(key, true, buf);
+            value.as_slice().encode(buf);
+        }
+        Node::Extension { key, child } => {
+            encode_path(key, false, buf);
+            encode_reference(child, buf);
+        }
+        Node::Branch { children } => {
+            for child in children.iter() {
+                encode_reference(child, buf);
+            }
+            buf.push(EMPTY_STRING_CODE);
+        }
+    }
+    prepend_header(buf, start, true);
+}
+
+/// Appends the reference to a child node, which is the node itself if its encoding is shorter
+/// than 32 bytes and its hash otherwise.
+fn encode_reference(node: &Node, buf: &mut Vec<u8>) {
+    if let Node::Hash(hash) = node {
+        return hash.encode(buf)
+    }
+    let start = buf.len();
+    encode_node(node, buf);
+    if buf.len() - start >= 32 {
+        let hash = keccak256(&buf[start..]);
+        buf.truncate(start);
+        hash.encode(buf);
+    }
+}
+
+/// Inserts the RLP header of the payload that starts at `start` in front of it.
+fn prepend_header(buf: &mut Vec<u8>, start: usize, list: bool) {
+    let end = buf.len();
+    Header { list, payload_length: end - start }.encode(buf);
+    let header_length = buf.len() - end;
+    buf[start..].rotate_right(header_length);
+}
+
+fn decode_node(rlp: &[u8]) -> Result<Node, WitnessError> {
+    let mut buf = rlp;
+    let header = Header::decode(&mut buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString.into())
+    }
+    let mut payload = buf.get(..header.payload_length).ok_or(alloy_rlp::Error::InputTooShort)?;
+
+    let mut items = Vec::with_capacity(17);
+    while !payload.is_empty() {
+        let item = payload;
+        let header = Header::decode(&mut payload)?;
+        payload = payload.get(header.payload_length..).ok_or(alloy_rlp::Error::InputTooShort)?;
+        items.push(&item[..item.len() - payload.len()]);
+    }
+
+    match items.as_slice() {
+        [path, value] => {
+            let path = decode_string(path)?;
+            let (first, rest) = path.split_first().ok_or(WitnessError::InvalidNode)?;
+            let mut key = Vec::with_capacity(rest.len() * 2 + 1);
+            if first & 0x10 != 0 {
+                key.push(first & 0x0f);
+            }
+            key.extend_from_slice(&nibbles::unpack(rest));
+            match first >> 4 {
+                0 | 1 if !key.is_empty() => {
+                    Ok(Node::Extension { key, child: Box::new(decode_reference(value)?) })
+                }
+                2 | 3 => Ok(Node::Leaf { key, value: decode_string(value)?.to_vec() }),
+                _ => Err(WitnessError::InvalidNode),
+            }
+        }
+        [children @ .., value] if children.len() == 16 => {
+            if !decode_string(value)?.is_empty() {
+                return Err(WitnessError::InvalidNode)
+            }
+            let mut decoded = empty_children();
+            for (node, child) in decoded.iter_mut().zip(children) {
+                *node = decode_reference(child)?;
+            }
+            Ok(Node::Branch { children: decoded })
+        }
+        _ => Err(WitnessError::InvalidNode),
+    }
+}
+
+fn decode_reference(rlp: &[u8]) -> Result<Node, WitnessError> {
+    let mut buf = rlp;
+    let header = Header::decode(&mut buf)?;
+    if header.list {
+        return decode_node(rlp)
+    }
+    match buf.get(..header.payload_length).ok_or(alloy_rlp::Error::InputTooShort)? {
+        [] => Ok(Node::Empty),
+        hash if hash.len() == 32 => Ok(Node::Hash(B256::from_slice(hash))),
+        _ => Err(WitnessError::InvalidNode),
+    }
+}
+
+fn decode_string(rlp: &[u8]) -> Result<&[u8], WitnessError> {
+    let mut buf = rlp;
+    let header = Header::decode(&mut buf)?;
+    if header.list {
+        return Err(alloy_rlp::Error::UnexpectedList.into())
+    }
+    Ok(buf.get(..header.payload_length).ok_or(alloy_rlp::Error::InputTooShort)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::state_root_prehashed, HashedStorage};
+    use proptest::{collection::btree_map, prelude::*};
+    use reth_primitives::{Account, KECCAK_EMPTY};
+    use std::collections::BTreeMap;
+
+    /// Collects the encodings of all nodes that are referenced by hash.
+    fn collect_nodes(node: &Node, nodes: &mut Vec<Bytes>) {
+        match node {
+            Node::Extension { child, .. } => collect_nodes(child, nodes),
+            Node::Branch { children } => {
+                children.iter().for_each(|child| collect_nodes(child, nodes))
+            }
+            _ => {}
+        }
+        let mut rlp = Vec::new();
+        encode_node(node, &mut rlp);
+        if rlp.len() >= 32 {
+            nodes.push(rlp.into());
+        }
+    }
+
+    fn full_trie(entries: &BTreeMap<B256, Vec<u8>>) -> WitnessTrie {
+        let mut trie = WitnessTrie::new(EMPTY_ROOT_HASH, Default::default());
+        for (key, value) in entries {
+            trie.insert(key.as_slice(), value.clone()).unwrap();
+        }
+        trie
+    }
+
+    fn witness(trie: &WitnessTrie) -> Vec<Bytes> {
+        let mut nodes = Vec::new();
+        collect_nodes(&trie.root, &mut nodes);
+        nodes
+    }
+
+    fn expected_root(entries: &BTreeMap<B256, Vec<u8>>) -> B256 {
+        triehash::trie_root::<reth_primitives::proofs::triehash::KeccakHasher, _, _, _>(
+            entries.iter().map(|(key, value)| (key, value)),
+        )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn witness_trie_roots(
+            entries in btree_map(any::<[u8; 32]>(), 1..64u8, 1..64),
+            inserted in btree_map(any::<[u8; 32]>(), 64..128u8, 0..16),
+            removed in 0..64usize,
+        ) {
+            let mut entries = entries
+                .into_iter()
+                .map(|(key, len)| (B256::from(key), vec![len; len as usize]))
+                .collect::<BTreeMap<_, _>>();
+            let full = full_trie(&entries);
+            prop_assert_eq!(full.root(), expected_root(&entries));
+
+            // a trie revealed from the witness returns the same values and roots
+            let nodes = witness(&full);
+            let mut trie = WitnessTrie::new(
+                full.root(),
+                Arc::new(nodes.into_iter().map(|node| (keccak256(&node), node)).collect()),
+            );
+            for (key, value) in &entries {
+                prop_assert_eq!(trie.get(key.as_slice()).unwrap(), Some(value.clone()));
+            }
+
+            let removed = entries.keys().step_by(3).take(removed).copied().collect::<Vec<_>>();
+            for key in removed {
+                trie.remove(key.as_slice()).unwrap();
+                entries.remove(&key);
+            }
+            for (key, len) in inserted {
+                let value = vec![len; len as usize];
+                trie.insert(&key, value.clone()).unwrap();
+                entries.insert(B256::from(key), value);
+            }
+            prop_assert_eq!(trie.root(), expected_root(&entries));
+        }
+    }
+
+    #[test]
+    fn reused_arena() {
+        let entries = (0..64u8).map(|i| (keccak256([i]), vec![i; 40])).collect::<BTreeMap<_, _>>();
+        let trie = full_trie(&entries);
+        let mut arena = RlpArena::default();
+        assert_eq!(trie.root_with_arena(&mut arena), expected_root(&entries));
+        let capacity = arena.capacity();
+        assert!(capacity > 0);
+
+        // the capacity is kept, so the next root is computed in the same buffer
+        let mut smaller = trie.clone();
+        smaller.remove(keccak256([0]).as_slice()).unwrap();
+        let mut expected = entries;
+        expected.remove(&keccak256([0]));
+        assert_eq!(smaller.root_with_arena(&mut arena), expected_root(&expected));
+        assert_eq!(arena.capacity(), capacity);
+    }
+
+    #[test]
+    fn missing_nodes() {
+        let entries = (0..16u8).map(|i| (keccak256([i]), vec![i; 40])).collect::<BTreeMap<_, _>>();
+        let full = full_trie(&entries);
+        let trie = WitnessTrie::new(full.root(), Default::default());
+        assert_eq!(
+            trie.get(keccak256([0]).as_slice()),
+            Err(WitnessError::MissingNode(full.root()))
+        );
+    }
+
+    #[test]
+    fn witness_state_root() {
+        let account = |nonce| Account { nonce, balance: U256::from(nonce), bytecode_hash: None };
+        let storage = |value: u64| {
+            (0..8u64).map(move |slot| {
+                (keccak256(B256::with_last_byte(slot as u8)), U256::from(value + slot))
+            })
+        };
+
+        // build the pre state with storage tries revealed in the witness
+        let mut accounts = full_trie(&BTreeMap::new());
+        let mut nodes = Vec::new();
+        let mut expected = BTreeMap::new();
+        for i in 1..=16u64 {
+            let hashed_address = keccak256(i.to_be_bytes());
+            let slots = storage(i).map(|(slot, value)| (slot, alloy_rlp::encode(value)));
+            let storage_trie = full_trie(&slots.collect());
+            nodes.extend(witness(&storage_trie));
+            let rlp = alloy_rlp::encode(TrieAccount::from((account(i), storage_trie.root())));
+            accounts.insert(hashed_address.as_slice(), rlp).unwrap();
+            expected.insert(hashed_address, (account(i), storage(i).collect::<BTreeMap<_, _>>()));
+        }
+        nodes.extend(witness(&accounts));
+        let state = WitnessState::new(accounts.root(), nodes);
+        assert_eq!(state.root(), state_root_prehashed(expected.clone().into_iter()));
+
+        let hashed_address = keccak256(3u64.to_be_bytes());
+        let first_slot = keccak256(B256::with_last_byte(0));
+        assert_eq!(state.account(hashed_address).unwrap().map(|a| a.nonce()), Some(3));
+        assert_eq!(
+            state.account(hashed_address).unwrap().map(|a| a.code_hash()),
+            Some(KECCAK_EMPTY)
+        );
+        assert_eq!(state.storage(hashed_address, first_slot).unwrap(), U256::from(3));
+
+        // update an account, change its storage, wipe another storage and destroy an account
+        let mut post_state = HashedPostState::default();
+        post_state.insert_account(hashed_address, Some(account(30)));
+        let mut hashed_storage = HashedStorage::new(false);
+        hashed_storage.insert_slot(first_slot, U256::ZERO);
+        hashed_storage.insert_slot(keccak256(B256::with_last_byte(100)), U256::from(100));
+        post_state.insert_hashed_storage(hashed_address, hashed_storage);
+        let wiped_address = keccak256(4u64.to_be_bytes());
+        post_state.insert_account(wiped_address, Some(account(4)));
+        post_state.insert_hashed_storage(wiped_address, HashedStorage::new(true));
+        let destroyed_address = keccak256(5u64.to_be_bytes());
+        post_state.insert_account(destroyed_address, None);
+        let post_state = post_state.sorted();
+
+        let (_, storage) = expected.get_mut(&hashed_address).unwrap();
+        storage.remove(&first_slot);
+        storage.insert(keccak256(B256::with_last_byte(100)), U256::from(100));
+        expected.get_mut(&hashed_address).unwrap().0 = account(30);
+        expected.get_mut(&wiped_address).unwrap().1.clear();
+        expected.remove(&destroyed_address);
+
+        assert_eq!(
+            state.state_root(&post_state).unwrap(),
+            state_root_prehashed(expected.into_iter())
+        );
+    }
+}